@@ -0,0 +1,171 @@
+//! The computer's mallet at the top of the table. It only sees the puck as it was a reaction
+//! time ago, which is what the difficulty levels turn.
+
+use crate::physics::{Puck, Table};
+use bevy::math::Vec2;
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+
+    /// How far behind the puck the computer's view is, in seconds.
+    pub fn reaction(self) -> f32 {
+        match self {
+            Self::Easy => 0.3,
+            Self::Medium => 0.15,
+            Self::Hard => 0.05,
+        }
+    }
+
+    /// How fast the computer's mallet moves, in pixels per second.
+    pub fn speed(self) -> f32 {
+        match self {
+            Self::Easy => 450.0,
+            Self::Medium => 650.0,
+            Self::Hard => 850.0,
+        }
+    }
+}
+
+pub struct Opponent {
+    pub difficulty: Difficulty,
+    /// The puck as it was seen, with the time, oldest first.
+    seen: VecDeque<(f32, Puck)>,
+}
+
+impl Opponent {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            difficulty,
+            seen: VecDeque::new(),
+        }
+    }
+
+    /// Forgets what it saw, e.g. once the puck is put back for a serve.
+    pub fn clear(&mut self) {
+        self.seen.clear();
+    }
+
+    /// Sees the puck at time `now`, dropping what is older than the reaction time needs.
+    pub fn observe(&mut self, now: f32, puck: Puck) {
+        self.seen.push_back((now, puck));
+        let horizon = now - self.difficulty.reaction();
+        while self.seen.len() > 1 && self.seen[1].0 <= horizon {
+            self.seen.pop_front();
+        }
+    }
+
+    /// The puck as the computer believes it to be: where it was a reaction time ago.
+    pub fn perceived(&self) -> Option<Puck> {
+        self.seen.front().map(|(_, puck)| *puck)
+    }
+
+    /// Where the computer wants its mallet: in front of its goal while the puck is in the
+    /// player's half or coming at it fast, and behind the puck to shoot it down the table
+    /// otherwise. `reach` is the distance between the centers of a touching mallet and puck.
+    pub fn target(&self, mallet: Vec2, table: &Table, reach: f32) -> Vec2 {
+        let half = table.size / 2.0;
+        let home = Vec2::new(0.0, half.y - reach * 1.2);
+        let puck = match self.perceived() {
+            Some(puck) => puck,
+            None => return home,
+        };
+        let attacking = puck.position.y > 0.0 && puck.velocity.y < 300.0;
+        if !attacking {
+            let guard = table.goal_width / 2.0;
+            return Vec2::new(puck.position.x.clamp(-guard, guard), home.y);
+        }
+        let aim = (Vec2::new(0.0, -half.y) - puck.position).normalize_or_zero();
+        let behind = mallet.y > puck.position.y + reach * 0.5;
+        if behind {
+            // Lined up: drive through the puck.
+            puck.position + aim * reach
+        } else {
+            // Get round to the goal side of the puck first, without pushing it home.
+            let side = if mallet.x < puck.position.x {
+                -1.0
+            } else {
+                1.0
+            };
+            puck.position + Vec2::new(side * reach * 1.2, reach * 1.5)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table {
+            goal_width: 120.0,
+            size: Vec2::new(400.0, 600.0),
+        }
+    }
+
+    fn puck(x: f32, y: f32, vy: f32) -> Puck {
+        Puck {
+            position: Vec2::new(x, y),
+            velocity: Vec2::new(0.0, vy),
+        }
+    }
+
+    #[test]
+    fn sees_the_puck_a_reaction_time_late() {
+        let mut opponent = Opponent::new(Difficulty::Easy);
+        assert_eq!(opponent.perceived(), None);
+        for frame in 0..=100 {
+            opponent.observe(frame as f32 / 100.0, puck(frame as f32, 0.0, 0.0));
+        }
+        // At one second it sees the puck of about 0.7 seconds.
+        let seen = opponent.perceived().unwrap().position.x;
+        assert!((69.0..=71.0).contains(&seen), "saw {}", seen);
+        let mut opponent = Opponent::new(Difficulty::Hard);
+        for frame in 0..=100 {
+            opponent.observe(frame as f32 / 100.0, puck(frame as f32, 0.0, 0.0));
+        }
+        let seen = opponent.perceived().unwrap().position.x;
+        assert!((94.0..=96.0).contains(&seen), "saw {}", seen);
+    }
+
+    #[test]
+    fn defends_the_goal_and_attacks_in_its_half() {
+        let mut opponent = Opponent::new(Difficulty::Hard);
+        let mallet = Vec2::new(0.0, 250.0);
+        // Nothing seen yet: in front of the goal.
+        assert_eq!(
+            opponent.target(mallet, &table(), 40.0),
+            Vec2::new(0.0, 252.0)
+        );
+        opponent.observe(0.0, puck(150.0, -100.0, 400.0));
+        assert_eq!(
+            opponent.target(mallet, &table(), 40.0),
+            Vec2::new(60.0, 252.0)
+        );
+        // Slow in its own half, with the mallet behind it: a shot towards the player's goal.
+        opponent.clear();
+        opponent.observe(0.0, puck(0.0, 100.0, 0.0));
+        assert_eq!(
+            opponent.target(mallet, &table(), 40.0),
+            Vec2::new(0.0, 60.0)
+        );
+        // With the mallet in front of it, round the puck first.
+        let target = opponent.target(Vec2::new(-50.0, 50.0), &table(), 40.0);
+        assert!(target.y > 100.0 && target.x < 0.0);
+    }
+}
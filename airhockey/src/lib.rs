@@ -0,0 +1,667 @@
+use ai::{Difficulty, Opponent};
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use physics::{Goal, Puck, Table};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod ai;
+pub mod physics;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.13);
+const COMPUTER_COLOR: Color = Color::rgb(0.9, 0.3, 0.3);
+const GAME: &str = "airhockey";
+const GOAL_COLOR: Color = Color::rgb(0.05, 0.05, 0.07);
+const GOAL_WIDTH: f32 = 140.0;
+const LINE_COLOR: Color = Color::rgb(0.55, 0.7, 0.85);
+const LOSS_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
+const MALLET_RADIUS: f32 = 26.0;
+const PLAYER_COLOR: Color = Color::rgb(0.3, 0.6, 0.95);
+/// How fast the player's mallet follows the mouse, in pixels per second.
+const PLAYER_SPEED: f32 = 2400.0;
+const PUCK_COLOR: Color = Color::rgb(0.15, 0.15, 0.18);
+const PUCK_RADIUS: f32 = 16.0;
+const RECORDS_FILE: &str = "records";
+/// Seconds the puck rests after a goal before it is back in play.
+const SERVE_PAUSE: f32 = 1.2;
+/// Physics steps per frame, so a hard shot cannot pass through a mallet.
+const SUBSTEPS: usize = 8;
+const TABLE_COLOR: Color = Color::rgb(0.88, 0.92, 0.96);
+const TABLE_HEIGHT: f32 = 680.0;
+const TABLE_WIDTH: f32 = 400.0;
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const WINNING_SCORE: u32 = 7;
+const WIN_COLOR: Color = Color::rgb(0.15, 0.6, 0.25);
+
+pub const WINDOW_HEIGHT: f32 = 760.0;
+pub const WINDOW_WIDTH: f32 = 480.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Clone, Copy, Default)]
+struct Mallet {
+    position: Vec2,
+    /// Over the last frame, handed on to the puck when it is hit.
+    velocity: Vec2,
+}
+
+/// A mallet's sprite, the computer's or the player's.
+#[derive(Component)]
+struct MalletSprite {
+    computer: bool,
+}
+
+struct Match {
+    computer: Mallet,
+    computer_score: u32,
+    /// Seconds played, the clock the computer's reaction runs on.
+    elapsed: f32,
+    /// Who scored last, for the banner during the serve pause.
+    last_goal: Option<Goal>,
+    opponent: Opponent,
+    player: Mallet,
+    player_score: u32,
+    puck: Puck,
+    /// Seconds left before the puck is back in play.
+    serve: f32,
+}
+
+impl Match {
+    fn new(difficulty: Difficulty) -> Self {
+        Self {
+            computer: Mallet {
+                position: Vec2::new(0.0, TABLE_HEIGHT / 2.0 - MALLET_RADIUS * 2.0),
+                velocity: Vec2::ZERO,
+            },
+            computer_score: 0,
+            elapsed: 0.0,
+            last_goal: None,
+            opponent: Opponent::new(difficulty),
+            player: Mallet {
+                position: Vec2::new(0.0, -TABLE_HEIGHT / 2.0 + MALLET_RADIUS * 2.0),
+                velocity: Vec2::ZERO,
+            },
+            player_score: 0,
+            puck: Puck::default(),
+            serve: SERVE_PAUSE,
+        }
+    }
+
+    /// Puts the puck back in the half of the side that conceded, at rest.
+    fn serve(&mut self, goal: Goal) {
+        let y = match goal {
+            Goal::Bottom => -TABLE_HEIGHT / 4.0,
+            Goal::Top => TABLE_HEIGHT / 4.0,
+        };
+        self.puck = Puck {
+            position: Vec2::new(0.0, y),
+            velocity: Vec2::ZERO,
+        };
+        self.last_goal = Some(goal);
+        self.serve = SERVE_PAUSE;
+        self.opponent.clear();
+    }
+
+    fn winner(&self) -> Option<Goal> {
+        if self.player_score >= WINNING_SCORE {
+            Some(Goal::Top)
+        } else if self.computer_score >= WINNING_SCORE {
+            Some(Goal::Bottom)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Component)]
+struct PuckSprite;
+
+/// Matches played and won against each difficulty, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records {
+    played: HashMap<String, u32>,
+    won: HashMap<String, u32>,
+}
+
+impl Records {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, self) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+struct Sounds {
+    conceded: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    lost: Handle<AudioSource>,
+    rail: Handle<AudioSource>,
+    scored: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Mallets,
+    Puck,
+}
+
+pub struct AirHockeyPlugin;
+
+impl Plugin for AirHockeyPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Match::new(Difficulty::Medium))
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_update.label(Step::Mallets))
+                    .with_system(computer_update.label(Step::Mallets))
+                    .with_system(puck_update.label(Step::Puck).after(Step::Mallets)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over_enter))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system(sprites_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Air Hockey".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Moves the mallet towards a point, no faster than `speed`, and keeps it on its side of the
+/// table.
+fn approach(mallet: &mut Mallet, target: Vec2, speed: f32, delta: f32, top: bool) {
+    let half = Vec2::new(TABLE_WIDTH, TABLE_HEIGHT) / 2.0 - MALLET_RADIUS;
+    let (low, high) = if top {
+        (MALLET_RADIUS, half.y)
+    } else {
+        (-half.y, -MALLET_RADIUS)
+    };
+    let target = Vec2::new(target.x.clamp(-half.x, half.x), target.y.clamp(low, high));
+    let step = (target - mallet.position).clamp_length_max(speed * delta);
+    mallet.position += step;
+    mallet.velocity = if delta > 0.0 {
+        step / delta
+    } else {
+        Vec2::ZERO
+    };
+}
+
+/// The computer watches the puck, late by its reaction time, and moves its mallet.
+fn computer_update(time: Res<Time>, mut game: ResMut<Match>) {
+    let delta = time.delta_seconds();
+    let game = &mut *game;
+    game.elapsed += delta;
+    if game.serve <= 0.0 {
+        game.opponent.observe(game.elapsed, game.puck);
+    }
+    let target = game.opponent.target(
+        game.computer.position,
+        &table(),
+        MALLET_RADIUS + PUCK_RADIUS,
+    );
+    let speed = game.opponent.difficulty.speed();
+    approach(&mut game.computer, target, speed, delta, true);
+}
+
+/// A white disc with a soft edge, tinted per sprite for the puck and the mallets.
+fn disc_image() -> Image {
+    const SIZE: u32 = 64;
+    let center = SIZE as f32 / 2.0;
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = Vec2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center).length();
+            let alpha = (center - distance).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn disc_sprite(
+    disc: &Handle<Image>,
+    color: Color,
+    diameter: f32,
+    translation: Vec3,
+) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(diameter)),
+            ..Default::default()
+        },
+        texture: disc.clone(),
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+/// Plays the fanfare and keeps the records once someone has seven goals.
+fn game_over_enter(
+    game: Res<Match>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let won = game.winner() == Some(Goal::Top);
+    audio.play(if won {
+        sounds.won.clone()
+    } else {
+        sounds.lost.clone()
+    });
+    let name = game.opponent.difficulty.name().to_string();
+    *records.played.entry(name.clone()).or_default() += 1;
+    if won {
+        *records.won.entry(name).or_default() += 1;
+    }
+    records.save();
+}
+
+/// Enter plays again at the same difficulty, Esc goes back to the menu.
+fn game_over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Match>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Match::new(game.opponent.difficulty);
+        state.set(GameState::Playing).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Left and right or 1 to 3 pick the difficulty, Enter starts a match.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Match>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let current = game.opponent.difficulty;
+    let index = Difficulty::ALL
+        .iter()
+        .position(|difficulty| *difficulty == current)
+        .unwrap_or(0);
+    let picked = if menu.just_pressed(Navigate::Left) {
+        index.checked_sub(1)
+    } else if menu.just_pressed(Navigate::Right) {
+        Some(index + 1)
+    } else {
+        [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3]
+            .iter()
+            .position(|key| keyboard_input.just_pressed(*key))
+    };
+    if let Some(difficulty) = picked.and_then(|index| Difficulty::ALL.get(index)) {
+        game.opponent.difficulty = *difficulty;
+    }
+    if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Match::new(game.opponent.difficulty);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// The player's mallet follows the mouse; Esc gives up the match.
+fn player_update(
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    mut game: ResMut<Match>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    let target = game_hud::cursor_world_position(&windows).unwrap_or(game.player.position);
+    approach(
+        &mut game.player,
+        target,
+        PLAYER_SPEED,
+        time.delta_seconds(),
+        false,
+    );
+}
+
+/// Slides the puck in small steps, with the mallets moving along, and counts the goals.
+fn puck_update(
+    time: Res<Time>,
+    mut game: ResMut<Match>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let delta = time.delta_seconds();
+    if game.serve > 0.0 {
+        game.serve -= delta;
+        return;
+    }
+    let game = &mut *game;
+    let step = delta / SUBSTEPS as f32;
+    let reach = MALLET_RADIUS + PUCK_RADIUS;
+    let (mut hit, mut rail) = (false, false);
+    for substep in 1..=SUBSTEPS {
+        // Where the mallets were this far into the frame.
+        let behind = (SUBSTEPS - substep) as f32 * step;
+        for mallet in [game.player, game.computer] {
+            let position = mallet.position - mallet.velocity * behind;
+            hit |= game.puck.hit(position, mallet.velocity, reach);
+        }
+        let before = game.puck.velocity;
+        if let Some(goal) = game.puck.step(&table(), PUCK_RADIUS, step) {
+            match goal {
+                Goal::Top => game.player_score += 1,
+                Goal::Bottom => game.computer_score += 1,
+            }
+            audio.play(match goal {
+                Goal::Top => sounds.scored.clone(),
+                Goal::Bottom => sounds.conceded.clone(),
+            });
+            game.serve(goal);
+            if game.winner().is_some() {
+                state.set(GameState::GameOver).unwrap();
+            }
+            return;
+        }
+        let after = game.puck.velocity;
+        rail |= before.x * after.x < 0.0 || before.y * after.y < 0.0;
+    }
+    if hit {
+        audio.play(sounds.hit.clone());
+    } else if rail {
+        audio.play(sounds.rail.clone());
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut images: ResMut<Assets<Image>>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            BACKGROUND_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    // The table with its goals, center line and center circle.
+    let disc = images.add(disc_image());
+    commands.spawn_bundle(sprite(
+        TABLE_COLOR,
+        Vec2::new(TABLE_WIDTH, TABLE_HEIGHT),
+        Vec3::ZERO,
+    ));
+    for y in [-TABLE_HEIGHT / 2.0, TABLE_HEIGHT / 2.0] {
+        commands.spawn_bundle(sprite(
+            GOAL_COLOR,
+            Vec2::new(GOAL_WIDTH, 8.0),
+            Vec3::new(0.0, y, 1.0),
+        ));
+    }
+    commands.spawn_bundle(sprite(
+        LINE_COLOR,
+        Vec2::new(TABLE_WIDTH, 3.0),
+        Vec3::new(0.0, 0.0, 1.0),
+    ));
+    for (diameter, color, z) in [(120.0, LINE_COLOR, 2.0), (114.0, TABLE_COLOR, 3.0)] {
+        commands.spawn_bundle(disc_sprite(&disc, color, diameter, Vec3::new(0.0, 0.0, z)));
+    }
+
+    commands
+        .spawn_bundle(disc_sprite(
+            &disc,
+            PUCK_COLOR,
+            PUCK_RADIUS * 2.0,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(PuckSprite);
+    for (color, top) in [(PLAYER_COLOR, false), (COMPUTER_COLOR, true)] {
+        let mut mallet = commands.spawn_bundle(disc_sprite(
+            &disc,
+            color,
+            MALLET_RADIUS * 2.0,
+            Vec3::new(0.0, 0.0, 20.0),
+        ));
+        mallet.with_children(|parent| {
+            parent.spawn_bundle(disc_sprite(
+                &disc,
+                color * 0.7,
+                MALLET_RADIUS,
+                Vec3::new(0.0, 0.0, 1.0),
+            ));
+        });
+        mallet.insert(MalletSprite { computer: top });
+    }
+
+    commands.insert_resource(Sounds {
+        conceded: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.1).volume(0.15),
+            Tone::new(Waveform::Square, 220.0, 0.25).fade().volume(0.15),
+        ])),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Noise, 900.0, 0.04)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        lost: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        rail: audio_sources.add(
+            Tone::new(Waveform::Sine, 220.0, 0.05)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        scored: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.25).fade(),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4).fade(),
+        ])),
+    });
+}
+
+fn sprite(color: Color, size: Vec2, translation: Vec3) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+/// Puts the puck and the mallets where the match has them; the puck blinks while it waits to be
+/// served.
+fn sprites_update(
+    game: Res<Match>,
+    mut pucks: Query<(&mut Transform, &mut Visibility), With<PuckSprite>>,
+    mut mallets: Query<(&mut Transform, &MalletSprite), Without<PuckSprite>>,
+) {
+    for (mut transform, mut visibility) in pucks.iter_mut() {
+        transform.translation = game.puck.position.extend(transform.translation.z);
+        visibility.is_visible = game.serve <= 0.0 || (game.serve * 4.0).fract() < 0.5;
+    }
+    for (mut transform, mallet) in mallets.iter_mut() {
+        let position = if mallet.computer {
+            game.computer.position
+        } else {
+            game.player.position
+        };
+        transform.translation = position.extend(transform.translation.z);
+    }
+}
+
+fn table() -> Table {
+    Table {
+        goal_width: GOAL_WIDTH,
+        size: Vec2::new(TABLE_WIDTH, TABLE_HEIGHT),
+    }
+}
+
+fn texts_update(
+    game: Res<Match>,
+    records: Res<Records>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let difficulty = game.opponent.difficulty;
+    let hud = match state.current() {
+        GameState::Menu => String::new(),
+        _ => format!(
+            "You {} - {} Computer   {}   First to {}",
+            game.player_score,
+            game.computer_score,
+            difficulty.name(),
+            WINNING_SCORE
+        ),
+    };
+    let (help, banner, color) = match state.current() {
+        GameState::Menu => {
+            let levels = Difficulty::ALL
+                .iter()
+                .map(|level| {
+                    let name = level.name();
+                    let won = records.won.get(name).copied().unwrap_or(0);
+                    let played = records.played.get(name).copied().unwrap_or(0);
+                    let mark = if *level == difficulty { ">" } else { " " };
+                    format!("{} {:<6}  won {} of {}", mark, name, won, played)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Left/Right or 1-3: difficulty   Enter: start".to_string(),
+                format!("AIR HOCKEY\n\n{}", levels),
+                BACKGROUND_COLOR,
+            )
+        }
+        GameState::Playing => {
+            let (banner, color) = match (game.serve > 0.0, game.last_goal) {
+                (true, Some(Goal::Top)) => ("GOAL!", WIN_COLOR),
+                (true, Some(Goal::Bottom)) => ("Computer scores", LOSS_COLOR),
+                _ => ("", TEXT_COLOR),
+            };
+            (
+                "Mouse: move the mallet   Esc: give up".to_string(),
+                banner.to_string(),
+                color,
+            )
+        }
+        GameState::GameOver => {
+            let (result, color) = if game.winner() == Some(Goal::Top) {
+                ("YOU WIN", WIN_COLOR)
+            } else {
+                ("YOU LOSE", LOSS_COLOR)
+            };
+            (
+                "Enter: rematch   Esc: menu".to_string(),
+                format!(
+                    "{}\n\n{} - {}",
+                    result, game.player_score, game.computer_score
+                ),
+                color,
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+        text.sections[0].style.color = color;
+    }
+}
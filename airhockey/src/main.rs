@@ -0,0 +1,14 @@
+use airhockey::AirHockeyPlugin;
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(airhockey::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AirHockeyPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
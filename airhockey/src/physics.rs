@@ -0,0 +1,155 @@
+//! The puck sliding on the table: friction, bounces off the rails, goals through the gaps in the
+//! end rails and hits from the mallets. The table is centered on the origin with the player's
+//! goal at the bottom.
+
+use bevy::math::Vec2;
+
+/// Share of its speed the puck keeps on a bounce off a rail.
+const BOUNCE: f32 = 0.9;
+/// Share of its speed the puck loses every second.
+const FRICTION: f32 = 0.25;
+/// The puck never goes faster, however hard it is hit, in pixels per second.
+pub const MAX_SPEED: f32 = 1600.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Goal {
+    /// Into the goal at the bottom, the player's.
+    Bottom,
+    /// Into the goal at the top, the computer's.
+    Top,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Puck {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+pub struct Table {
+    /// Width of the gap in each end rail.
+    pub goal_width: f32,
+    pub size: Vec2,
+}
+
+impl Puck {
+    /// Bounces off a mallet overlapping it: pushed out along the line between the two and sent
+    /// off with the mallet's speed added.
+    pub fn hit(&mut self, mallet: Vec2, mallet_velocity: Vec2, reach: f32) -> bool {
+        let offset = self.position - mallet;
+        let distance = offset.length();
+        if distance >= reach {
+            return false;
+        }
+        let normal = if distance > 0.0 {
+            offset / distance
+        } else {
+            Vec2::Y
+        };
+        self.position = mallet + normal * reach;
+        let relative = self.velocity - mallet_velocity;
+        let along = relative.dot(normal);
+        if along < 0.0 {
+            self.velocity -= normal * along * (1.0 + BOUNCE);
+        }
+        // A mallet standing still still pushes a little.
+        self.velocity += normal * 60.0;
+        self.velocity = self.velocity.clamp_length_max(MAX_SPEED);
+        true
+    }
+
+    /// Slides the puck on for `delta` seconds; returns the goal it went into, if any.
+    pub fn step(&mut self, table: &Table, radius: f32, delta: f32) -> Option<Goal> {
+        self.velocity *= (1.0 - FRICTION * delta).max(0.0);
+        self.position += self.velocity * delta;
+        let half = table.size / 2.0;
+        if self.position.x.abs() > half.x - radius {
+            self.position.x = self.position.x.signum() * (half.x - radius);
+            self.velocity.x = -self.velocity.x * BOUNCE;
+        }
+        let in_gap = self.position.x.abs() < table.goal_width / 2.0;
+        if self.position.y.abs() > half.y - radius {
+            if in_gap {
+                if self.position.y.abs() > half.y + radius {
+                    return Some(if self.position.y > 0.0 {
+                        Goal::Top
+                    } else {
+                        Goal::Bottom
+                    });
+                }
+            } else {
+                self.position.y = self.position.y.signum() * (half.y - radius);
+                self.velocity.y = -self.velocity.y * BOUNCE;
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> Table {
+        Table {
+            goal_width: 100.0,
+            size: Vec2::new(400.0, 600.0),
+        }
+    }
+
+    fn run(puck: &mut Puck, seconds: f32) -> Option<Goal> {
+        let steps = (seconds * 120.0) as usize;
+        (0..steps).find_map(|_| puck.step(&table(), 10.0, 1.0 / 120.0))
+    }
+
+    #[test]
+    fn bounces_off_rails_and_slows_down() {
+        let mut puck = Puck {
+            position: Vec2::ZERO,
+            velocity: Vec2::new(600.0, 0.0),
+        };
+        assert_eq!(run(&mut puck, 0.5), None);
+        assert!(puck.velocity.x < 0.0);
+        assert!(puck.velocity.length() < 600.0 * BOUNCE);
+        assert!(puck.position.x <= 190.0);
+        // Off the end rail beside the goal.
+        let mut puck = Puck {
+            position: Vec2::new(120.0, 0.0),
+            velocity: Vec2::new(0.0, 800.0),
+        };
+        assert_eq!(run(&mut puck, 0.5), None);
+        assert!(puck.velocity.y < 0.0);
+    }
+
+    #[test]
+    fn scores_through_the_gaps() {
+        let mut puck = Puck {
+            position: Vec2::ZERO,
+            velocity: Vec2::new(0.0, 800.0),
+        };
+        assert_eq!(run(&mut puck, 2.0), Some(Goal::Top));
+        let mut puck = Puck {
+            position: Vec2::new(10.0, 0.0),
+            velocity: Vec2::new(0.0, -800.0),
+        };
+        assert_eq!(run(&mut puck, 2.0), Some(Goal::Bottom));
+    }
+
+    #[test]
+    fn mallets_send_the_puck_off() {
+        let mut puck = Puck {
+            position: Vec2::new(0.0, 20.0),
+            velocity: Vec2::ZERO,
+        };
+        assert!(!puck.hit(Vec2::new(0.0, -30.0), Vec2::ZERO, 40.0));
+        assert!(puck.hit(Vec2::new(0.0, -10.0), Vec2::new(0.0, 500.0), 40.0));
+        assert_eq!(puck.position, Vec2::new(0.0, 30.0));
+        assert!(puck.velocity.y > 900.0);
+        // Moving away from the mallet already, it only gets the little push.
+        let mut puck = Puck {
+            position: Vec2::new(30.0, 0.0),
+            velocity: Vec2::new(300.0, 0.0),
+        };
+        puck.hit(Vec2::ZERO, Vec2::ZERO, 40.0);
+        assert_eq!(puck.velocity, Vec2::new(360.0, 0.0));
+    }
+}
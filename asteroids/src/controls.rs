@@ -0,0 +1,77 @@
+//! What the ship's keys, buttons and stick do, rebindable through `game_input` on the controls
+//! screen.
+
+use bevy::prelude::*;
+use game_input::{Action, Binding, Sign};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum AsteroidsAction {
+    Fire,
+    /// Jumps to a random spot, which can go wrong.
+    Hyperspace,
+    /// Starts a new game after game over.
+    Restart,
+    RotateLeft,
+    RotateRight,
+    Thrust,
+}
+
+impl Action for AsteroidsAction {
+    const ALL: &'static [Self] = &[
+        Self::RotateLeft,
+        Self::RotateRight,
+        Self::Thrust,
+        Self::Fire,
+        Self::Hyperspace,
+        Self::Restart,
+    ];
+
+    fn default_bindings(self) -> Vec<Binding> {
+        match self {
+            Self::Fire => vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::South),
+            ],
+            Self::Hyperspace => vec![
+                Binding::Key(KeyCode::LShift),
+                Binding::Key(KeyCode::RShift),
+                Binding::Key(KeyCode::H),
+                Binding::Gamepad(GamepadButtonType::West),
+            ],
+            Self::Restart => vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            Self::RotateLeft => vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Negative),
+            ],
+            Self::RotateRight => vec![
+                Binding::Key(KeyCode::Right),
+                Binding::Key(KeyCode::D),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Positive),
+            ],
+            Self::Thrust => vec![
+                Binding::Key(KeyCode::Up),
+                Binding::Key(KeyCode::W),
+                Binding::Gamepad(GamepadButtonType::DPadUp),
+                Binding::Axis(GamepadAxisType::LeftStickY, Sign::Positive),
+            ],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Fire => "Fire",
+            Self::Hyperspace => "Hyperspace",
+            Self::Restart => "New game",
+            Self::RotateLeft => "Rotate left",
+            Self::RotateRight => "Rotate right",
+            Self::Thrust => "Thrust",
+        }
+    }
+}
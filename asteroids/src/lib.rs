@@ -0,0 +1,805 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_input::{ActionState, ControlsScreen, InputPlugin};
+use rand::{random, thread_rng, Rng};
+use std::f32::consts::PI;
+
+pub mod controls;
+
+pub use controls::AsteroidsAction;
+
+pub const ARENA_HEIGHT: f32 = 600.0;
+pub const ARENA_WIDTH: f32 = 800.0;
+const ASTEROID_COLOR: Color = Color::rgb(0.55, 0.5, 0.45);
+const ASTEROID_SPEED: f32 = 60.0;
+const BACKGROUND_COLOR: Color = Color::rgb(0.01, 0.01, 0.04);
+const BULLET_COLOR: Color = Color::rgb(1.0, 1.0, 0.8);
+const BULLET_COOLDOWN: f32 = 0.22;
+const BULLET_LIFETIME: f32 = 1.0;
+const BULLET_SPEED: f32 = 480.0;
+const HUD_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const HYPERSPACE_FAILURE_CHANCE: f32 = 0.1;
+const INVULNERABILITY_TIME: f32 = 2.5;
+const SAFE_SPAWN_DISTANCE: f32 = 160.0;
+const SHIP_COLOR: Color = Color::rgb(0.4, 0.9, 1.0);
+const SHIP_DRAG: f32 = 0.55;
+const SHIP_MAX_SPEED: f32 = 360.0;
+const SHIP_ROTATION_SPEED: f32 = 4.5;
+const SHIP_SIZE: f32 = 20.0;
+const SHIP_THRUST: f32 = 320.0;
+const STARTING_LIVES: u32 = 3;
+const UFO_BULLET_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+const UFO_COLOR: Color = Color::rgb(0.8, 0.3, 1.0);
+const UFO_FIRE_INTERVAL: f32 = 1.3;
+const UFO_RADIUS: f32 = 14.0;
+const UFO_SCORE: u32 = 200;
+const UFO_SPAWN_INTERVAL: f32 = 18.0;
+const UFO_SPEED: f32 = 110.0;
+
+#[derive(Component)]
+struct Asteroid {
+    size: AsteroidSize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum AsteroidSize {
+    Large,
+    Medium,
+    Small,
+}
+
+impl AsteroidSize {
+    fn radius(self) -> f32 {
+        match self {
+            Self::Large => 40.0,
+            Self::Medium => 22.0,
+            Self::Small => 11.0,
+        }
+    }
+
+    fn score(self) -> u32 {
+        match self {
+            Self::Large => 20,
+            Self::Medium => 50,
+            Self::Small => 100,
+        }
+    }
+
+    fn smaller(self) -> Option<Self> {
+        match self {
+            Self::Large => Some(Self::Medium),
+            Self::Medium => Some(Self::Small),
+            Self::Small => None,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Bullet {
+    lifetime: Timer,
+    from_ufo: bool,
+}
+
+struct FireCooldown(Timer);
+
+#[derive(Default)]
+struct Game {
+    lives: u32,
+    score: u32,
+    wave: u32,
+}
+
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct Ship {
+    angle: f32,
+    invulnerable: Timer,
+}
+
+struct ShipHitEvent;
+
+#[derive(Component)]
+struct ShipPart;
+
+struct Sounds {
+    explosion: Handle<AudioSource>,
+    fire: Handle<AudioSource>,
+    hyperspace: Handle<AudioSource>,
+    ufo_fire: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+struct Spin(f32);
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Input,
+    Movement,
+    Collision,
+}
+
+#[derive(Component)]
+struct Ufo {
+    fire_timer: Timer,
+}
+
+struct UfoTimer(Timer);
+
+#[derive(Component)]
+struct Velocity(Vec2);
+
+/// Marks entities that leave one edge of the arena and come back in on the opposite one.
+#[derive(Component)]
+struct Wraps;
+
+pub struct AsteroidsPlugin;
+
+impl Plugin for AsteroidsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::default())
+            .insert_resource(FireCooldown(Timer::from_seconds(BULLET_COOLDOWN, false)))
+            .insert_resource(UfoTimer(Timer::from_seconds(UFO_SPAWN_INTERVAL, true)))
+            .add_plugin(HudPlugin)
+            .add_plugin(InputPlugin::<AsteroidsAction>::new("asteroids"))
+            .add_state(GameState::Playing)
+            .add_event::<ShipHitEvent>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(ship_input.label(Step::Input))
+                    .with_system(ufo_spawner.label(Step::Input))
+                    .with_system(ufo_behaviour.label(Step::Input))
+                    .with_system(movement.label(Step::Movement).after(Step::Input))
+                    .with_system(bullet_expiry.after(Step::Movement))
+                    .with_system(
+                        bullet_collisions
+                            .label(Step::Collision)
+                            .after(Step::Movement),
+                    )
+                    .with_system(ship_collisions.label(Step::Collision).after(Step::Movement))
+                    .with_system(ship_hit.after(Step::Collision))
+                    .with_system(ship_blink.after(Step::Collision))
+                    .with_system(next_wave.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(restart_input))
+            .add_system(spin)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Asteroids".to_string(),
+        width: ARENA_WIDTH,
+        height: ARENA_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn asteroid_spawn(commands: &mut Commands, size: AsteroidSize, position: Vec2, wave: u32) {
+    let angle = random::<f32>() * 2.0 * PI;
+    let speed = ASTEROID_SPEED * (0.6 + random::<f32>()) * (1.0 + wave as f32 * 0.08);
+    let speed = match size {
+        AsteroidSize::Large => speed,
+        AsteroidSize::Medium => speed * 1.4,
+        AsteroidSize::Small => speed * 1.9,
+    };
+    // Two squares rotated against each other read as a rough octagon.
+    let side = size.radius() * 1.7;
+    let facet = || SpriteBundle {
+        sprite: Sprite {
+            color: ASTEROID_COLOR,
+            custom_size: Some(Vec2::splat(side)),
+            ..Default::default()
+        },
+        transform: Transform::from_rotation(Quat::from_rotation_z(PI / 4.0)),
+        ..Default::default()
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: ASTEROID_COLOR,
+                custom_size: Some(Vec2::splat(side)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 0.0),
+            ..Default::default()
+        })
+        .insert(Asteroid { size })
+        .insert(Velocity(Vec2::new(angle.cos(), angle.sin()) * speed))
+        .insert(Spin((random::<f32>() - 0.5) * 2.0))
+        .insert(Wraps)
+        .with_children(|parent| {
+            parent.spawn_bundle(facet());
+        });
+}
+
+fn bullet_collisions(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    bullets: Query<(Entity, &Bullet, &Transform)>,
+    asteroids: Query<(Entity, &Asteroid, &Transform)>,
+    ufos: Query<(Entity, &Transform), With<Ufo>>,
+) {
+    let mut destroyed: Vec<Entity> = Vec::new();
+    for (bullet_entity, bullet, bullet_transform) in bullets.iter() {
+        if bullet.from_ufo {
+            continue;
+        }
+        let bullet_position = bullet_transform.translation.truncate();
+        for (asteroid_entity, asteroid, asteroid_transform) in asteroids.iter() {
+            if destroyed.contains(&asteroid_entity) {
+                continue;
+            }
+            let asteroid_position = asteroid_transform.translation.truncate();
+            if bullet_position.distance(asteroid_position) < asteroid.size.radius() {
+                destroyed.push(asteroid_entity);
+                destroyed.push(bullet_entity);
+                game.score += asteroid.size.score();
+                if let Some(smaller) = asteroid.size.smaller() {
+                    for _ in 0..2 {
+                        asteroid_spawn(&mut commands, smaller, asteroid_position, game.wave);
+                    }
+                }
+                audio.play(sounds.explosion.clone());
+                break;
+            }
+        }
+        if destroyed.contains(&bullet_entity) {
+            continue;
+        }
+        for (ufo_entity, ufo_transform) in ufos.iter() {
+            if !destroyed.contains(&ufo_entity)
+                && bullet_position.distance(ufo_transform.translation.truncate()) < UFO_RADIUS
+            {
+                destroyed.push(ufo_entity);
+                destroyed.push(bullet_entity);
+                game.score += UFO_SCORE;
+                audio.play(sounds.explosion.clone());
+                break;
+            }
+        }
+    }
+    for entity in destroyed {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn bullet_expiry(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &mut Bullet)>,
+) {
+    for (entity, mut bullet) in bullets.iter_mut() {
+        if bullet.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn bullet_spawn(commands: &mut Commands, position: Vec2, velocity: Vec2, from_ufo: bool) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: if from_ufo {
+                    UFO_BULLET_COLOR
+                } else {
+                    BULLET_COLOR
+                },
+                custom_size: Some(Vec2::splat(4.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(position.x, position.y, 1.0),
+            ..Default::default()
+        })
+        .insert(Bullet {
+            lifetime: Timer::from_seconds(BULLET_LIFETIME, false),
+            from_ufo,
+        })
+        .insert(Velocity(velocity))
+        .insert(Wraps);
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "GAME OVER\nScore {}\nPress Enter, or C for the controls",
+                game.score
+            ),
+            40.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(GameOverText);
+}
+
+fn heading(angle: f32) -> Vec2 {
+    Vec2::new(-angle.sin(), angle.cos())
+}
+
+fn hud_update(game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if game.is_changed() {
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = format!(
+                "SCORE {:>6}   LIVES {}   WAVE {}",
+                game.score, game.lives, game.wave
+            );
+        }
+    }
+}
+
+fn movement(time: Res<Time>, mut query: Query<(&Velocity, &mut Transform, Option<&Wraps>)>) {
+    let half = Vec2::new(ARENA_WIDTH, ARENA_HEIGHT) / 2.0;
+    for (velocity, mut transform, wraps) in query.iter_mut() {
+        transform.translation += (velocity.0 * time.delta_seconds()).extend(0.0);
+        if wraps.is_some() {
+            let translation = &mut transform.translation;
+            if translation.x < -half.x {
+                translation.x += ARENA_WIDTH;
+            } else if translation.x > half.x {
+                translation.x -= ARENA_WIDTH;
+            }
+            if translation.y < -half.y {
+                translation.y += ARENA_HEIGHT;
+            } else if translation.y > half.y {
+                translation.y -= ARENA_HEIGHT;
+            }
+        }
+    }
+}
+
+fn new_game(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut ufo_timer: ResMut<UfoTimer>,
+    leftovers: Query<Entity, Or<(With<Velocity>, With<GameOverText>)>>,
+) {
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *game = Game {
+        lives: STARTING_LIVES,
+        score: 0,
+        wave: 0,
+    };
+    ufo_timer.0.reset();
+    ship_spawn(&mut commands);
+}
+
+fn next_wave(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    asteroids: Query<Entity, With<Asteroid>>,
+    ships: Query<&Transform, With<Ship>>,
+) {
+    if !asteroids.is_empty() {
+        return;
+    }
+    game.wave += 1;
+    let ship_position = ships
+        .iter()
+        .next()
+        .map(|transform| transform.translation.truncate())
+        .unwrap_or_default();
+    for _ in 0..(3 + game.wave).min(11) {
+        asteroid_spawn(
+            &mut commands,
+            AsteroidSize::Large,
+            random_position_away_from(ship_position),
+            game.wave,
+        );
+    }
+}
+
+fn random_position() -> Vec2 {
+    Vec2::new(
+        (random::<f32>() - 0.5) * ARENA_WIDTH,
+        (random::<f32>() - 0.5) * ARENA_HEIGHT,
+    )
+}
+
+fn random_position_away_from(point: Vec2) -> Vec2 {
+    loop {
+        let position = random_position();
+        if position.distance(point) > SAFE_SPAWN_DISTANCE {
+            return position;
+        }
+    }
+}
+
+/// The restart action starts a new game and C opens the controls screen, which has the keys to
+/// itself while it is open.
+fn restart_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<ActionState<AsteroidsAction>>,
+    mut controls: ResMut<ControlsScreen>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if controls.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::C) {
+        controls.open();
+    } else if actions.just_pressed(AsteroidsAction::Restart) {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.insert_resource(Sounds {
+        explosion: audio_sources.add(Tone::new(Waveform::Noise, 0.0, 0.45).fade().into()),
+        fire: audio_sources.add(
+            Tone::new(Waveform::Square, 880.0, 0.08)
+                .slide(440.0)
+                .volume(0.2)
+                .into(),
+        ),
+        hyperspace: audio_sources.add(Tone::new(Waveform::Sine, 200.0, 0.3).slide(1200.0).into()),
+        ufo_fire: audio_sources.add(
+            Tone::new(Waveform::Saw, 600.0, 0.1)
+                .slide(300.0)
+                .volume(0.2)
+                .into(),
+        ),
+    });
+}
+
+fn rotate(vector: Vec2, angle: f32) -> Vec2 {
+    let (sin, cos) = angle.sin_cos();
+    Vec2::new(
+        vector.x * cos - vector.y * sin,
+        vector.x * sin + vector.y * cos,
+    )
+}
+
+fn ship_blink(
+    time: Res<Time>,
+    ships: Query<&Ship>,
+    mut parts: Query<&mut Visibility, With<ShipPart>>,
+) {
+    if let Some(ship) = ships.iter().next() {
+        let visible =
+            ship.invulnerable.finished() || (time.seconds_since_startup() * 8.0) as u64 % 2 == 0;
+        for mut visibility in parts.iter_mut() {
+            visibility.is_visible = visible;
+        }
+    }
+}
+
+fn ship_collisions(
+    mut hit_writer: EventWriter<ShipHitEvent>,
+    ships: Query<(&Ship, &Transform)>,
+    asteroids: Query<(&Asteroid, &Transform)>,
+    bullets: Query<(&Bullet, &Transform)>,
+    ufos: Query<&Transform, With<Ufo>>,
+) {
+    if let Some((ship, ship_transform)) = ships.iter().next() {
+        if !ship.invulnerable.finished() {
+            return;
+        }
+        let hull = ship_hull(ship_transform);
+        let hit = asteroids.iter().any(|(asteroid, transform)| {
+            triangle_circle_overlap(
+                &hull,
+                transform.translation.truncate(),
+                asteroid.size.radius(),
+            )
+        }) || ufos.iter().any(|transform| {
+            triangle_circle_overlap(&hull, transform.translation.truncate(), UFO_RADIUS)
+        }) || bullets.iter().any(|(bullet, transform)| {
+            bullet.from_ufo && triangle_circle_overlap(&hull, transform.translation.truncate(), 2.0)
+        });
+        if hit {
+            hit_writer.send(ShipHitEvent);
+        }
+    }
+}
+
+/// The ship's triangle in world space, used for polygon/circle collision.
+fn ship_hull(transform: &Transform) -> [Vec2; 3] {
+    [
+        Vec3::new(0.0, SHIP_SIZE * 0.7, 0.0),
+        Vec3::new(-SHIP_SIZE * 0.5, -SHIP_SIZE * 0.5, 0.0),
+        Vec3::new(SHIP_SIZE * 0.5, -SHIP_SIZE * 0.5, 0.0),
+    ]
+    .map(|corner| (transform.translation + transform.rotation * corner).truncate())
+}
+
+fn ship_hit(
+    mut commands: Commands,
+    mut hit_reader: EventReader<ShipHitEvent>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut ships: Query<(Entity, &mut Ship, &mut Velocity, &mut Transform)>,
+) {
+    if hit_reader.iter().next().is_none() {
+        return;
+    }
+    audio.play(sounds.explosion.clone());
+    game.lives = game.lives.saturating_sub(1);
+    if game.lives == 0 {
+        for (entity, _, _, _) in ships.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        state.set(GameState::GameOver).unwrap();
+        return;
+    }
+    for (_, mut ship, mut velocity, mut transform) in ships.iter_mut() {
+        ship.angle = 0.0;
+        ship.invulnerable.reset();
+        velocity.0 = Vec2::ZERO;
+        *transform = Transform::from_xyz(0.0, 0.0, 2.0);
+    }
+}
+
+fn ship_input(
+    mut commands: Commands,
+    actions: Res<ActionState<AsteroidsAction>>,
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut cooldown: ResMut<FireCooldown>,
+    mut hit_writer: EventWriter<ShipHitEvent>,
+    mut ships: Query<(&mut Ship, &mut Velocity, &mut Transform)>,
+) {
+    cooldown.0.tick(time.delta());
+    let delta = time.delta_seconds();
+    if let Some((mut ship, mut velocity, mut transform)) = ships.iter_mut().next() {
+        ship.invulnerable.tick(time.delta());
+        if actions.pressed(AsteroidsAction::RotateLeft) {
+            ship.angle += SHIP_ROTATION_SPEED * delta;
+        }
+        if actions.pressed(AsteroidsAction::RotateRight) {
+            ship.angle -= SHIP_ROTATION_SPEED * delta;
+        }
+        transform.rotation = Quat::from_rotation_z(ship.angle);
+        if actions.pressed(AsteroidsAction::Thrust) {
+            velocity.0 += heading(ship.angle) * SHIP_THRUST * delta;
+        }
+        // Inertia: the ship keeps drifting and only slowly loses speed.
+        velocity.0 *= SHIP_DRAG.powf(delta);
+        if velocity.0.length() > SHIP_MAX_SPEED {
+            velocity.0 = velocity.0.normalize() * SHIP_MAX_SPEED;
+        }
+        if actions.pressed(AsteroidsAction::Fire) && cooldown.0.finished() {
+            cooldown.0.reset();
+            let nose = transform.translation.truncate() + heading(ship.angle) * SHIP_SIZE * 0.7;
+            bullet_spawn(
+                &mut commands,
+                nose,
+                velocity.0 + heading(ship.angle) * BULLET_SPEED,
+                false,
+            );
+            audio.play(sounds.fire.clone());
+        }
+        if actions.just_pressed(AsteroidsAction::Hyperspace) {
+            let destination = random_position();
+            transform.translation = destination.extend(transform.translation.z);
+            velocity.0 = Vec2::ZERO;
+            audio.play(sounds.hyperspace.clone());
+            if random::<f32>() < HYPERSPACE_FAILURE_CHANCE {
+                hit_writer.send(ShipHitEvent);
+            }
+        }
+    }
+}
+
+fn ship_spawn(commands: &mut Commands) {
+    let part = |width: f32, height: f32, y: f32| SpriteBundle {
+        sprite: Sprite {
+            color: SHIP_COLOR,
+            custom_size: Some(Vec2::new(width, height)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0.0, y, 0.0),
+        ..Default::default()
+    };
+    commands
+        .spawn_bundle((
+            Transform::from_xyz(0.0, 0.0, 2.0),
+            GlobalTransform::identity(),
+        ))
+        .insert(Ship {
+            angle: 0.0,
+            invulnerable: Timer::from_seconds(INVULNERABILITY_TIME, false),
+        })
+        .insert(Velocity(Vec2::ZERO))
+        .insert(Wraps)
+        .with_children(|parent| {
+            for (width, height, y) in [
+                (SHIP_SIZE, SHIP_SIZE * 0.3, -SHIP_SIZE * 0.35),
+                (SHIP_SIZE * 0.55, SHIP_SIZE * 0.6, SHIP_SIZE * 0.05),
+                (SHIP_SIZE * 0.2, SHIP_SIZE * 0.35, SHIP_SIZE * 0.5),
+            ] {
+                parent.spawn_bundle(part(width, height, y)).insert(ShipPart);
+            }
+        });
+}
+
+fn spin(time: Res<Time>, mut query: Query<(&Spin, &mut Transform)>) {
+    for (spin, mut transform) in query.iter_mut() {
+        transform.rotate(Quat::from_rotation_z(spin.0 * time.delta_seconds()));
+    }
+}
+
+fn triangle_circle_overlap(triangle: &[Vec2; 3], center: Vec2, radius: f32) -> bool {
+    let sign = |a: Vec2, b: Vec2, p: Vec2| (b - a).perp_dot(p - a);
+    let (d1, d2, d3) = (
+        sign(triangle[0], triangle[1], center),
+        sign(triangle[1], triangle[2], center),
+        sign(triangle[2], triangle[0], center),
+    );
+    let inside = (d1 >= 0.0 && d2 >= 0.0 && d3 >= 0.0) || (d1 <= 0.0 && d2 <= 0.0 && d3 <= 0.0);
+    inside
+        || triangle
+            .iter()
+            .zip(triangle.iter().cycle().skip(1))
+            .any(|(a, b)| {
+                let edge = *b - *a;
+                let t = ((center - *a).dot(edge) / edge.length_squared())
+                    .max(0.0)
+                    .min(1.0);
+                (*a + edge * t).distance(center) < radius
+            })
+}
+
+fn ufo_behaviour(
+    mut commands: Commands,
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut ufos: Query<(Entity, &mut Ufo, &mut Velocity, &Transform)>,
+    ships: Query<&Transform, With<Ship>>,
+) {
+    let target = ships
+        .iter()
+        .next()
+        .map(|transform| transform.translation.truncate());
+    for (entity, mut ufo, mut velocity, transform) in ufos.iter_mut() {
+        let position = transform.translation.truncate();
+        if position.x.abs() > ARENA_WIDTH / 2.0 + UFO_RADIUS * 2.0 {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        if ufo.fire_timer.tick(time.delta()).just_finished() {
+            velocity.0.y = (random::<f32>() - 0.5) * UFO_SPEED;
+            if let Some(target) = target {
+                let inaccuracy = thread_rng().gen_range(-0.3, 0.3);
+                let aim = rotate((target - position).normalize_or_zero(), inaccuracy);
+                bullet_spawn(&mut commands, position, aim * BULLET_SPEED * 0.6, true);
+                audio.play(sounds.ufo_fire.clone());
+            }
+        }
+    }
+}
+
+fn ufo_spawner(mut commands: Commands, time: Res<Time>, mut timer: ResMut<UfoTimer>) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let from_left = random::<bool>();
+    let x = if from_left { -1.0 } else { 1.0 } * (ARENA_WIDTH / 2.0 + UFO_RADIUS);
+    let y = (random::<f32>() - 0.5) * ARENA_HEIGHT * 0.8;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: UFO_COLOR,
+                custom_size: Some(Vec2::new(UFO_RADIUS * 2.0, UFO_RADIUS * 0.7)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, 1.0),
+            ..Default::default()
+        })
+        .insert(Ufo {
+            fire_timer: Timer::from_seconds(UFO_FIRE_INTERVAL, true),
+        })
+        .insert(Velocity(Vec2::new(
+            if from_left { UFO_SPEED } else { -UFO_SPEED },
+            0.0,
+        )))
+        .with_children(|parent| {
+            parent.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: UFO_COLOR,
+                    custom_size: Some(Vec2::new(UFO_RADIUS, UFO_RADIUS * 0.6)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, UFO_RADIUS * 0.5, 0.0),
+                ..Default::default()
+            });
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn asteroids_split_into_smaller_ones_worth_more() {
+        let mut size = AsteroidSize::Large;
+        let mut sizes = 1;
+        while let Some(smaller) = size.smaller() {
+            assert!(smaller.radius() < size.radius());
+            assert!(smaller.score() > size.score());
+            size = smaller;
+            sizes += 1;
+        }
+        assert_eq!(sizes, 3);
+    }
+
+    #[test]
+    fn ship_points_up_and_turns_anticlockwise() {
+        assert!(heading(0.0).distance(Vec2::Y) < 1e-6);
+        assert!(heading(PI / 2.0).distance(-Vec2::X) < 1e-6);
+        assert!(rotate(Vec2::Y, PI / 2.0).distance(heading(PI / 2.0)) < 1e-6);
+        let hull = ship_hull(&Transform::from_rotation(Quat::from_rotation_z(PI / 2.0)));
+        assert!(hull[0].distance(heading(PI / 2.0) * SHIP_SIZE * 0.7) < 1e-4);
+    }
+
+    #[test]
+    fn circles_touching_the_ship_hit_it() {
+        let hull = ship_hull(&Transform::from_xyz(100.0, 50.0, 0.0));
+        assert!(triangle_circle_overlap(&hull, Vec2::new(100.0, 50.0), 1.0));
+        assert!(triangle_circle_overlap(
+            &hull,
+            Vec2::new(100.0, 50.0 + SHIP_SIZE * 0.7 + 5.0),
+            6.0
+        ));
+        assert!(!triangle_circle_overlap(
+            &hull,
+            Vec2::new(100.0, 50.0 + SHIP_SIZE * 0.7 + 5.0),
+            4.0
+        ));
+        assert!(!triangle_circle_overlap(&hull, Vec2::ZERO, 40.0));
+    }
+
+    #[test]
+    fn spawns_keep_away_from_the_ship() {
+        for _ in 0..100 {
+            let position = random_position_away_from(Vec2::ZERO);
+            assert!(position.length() > SAFE_SPAWN_DISTANCE);
+            assert!(position.x.abs() <= ARENA_WIDTH / 2.0);
+            assert!(position.y.abs() <= ARENA_HEIGHT / 2.0);
+        }
+    }
+}
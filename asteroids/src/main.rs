@@ -0,0 +1,14 @@
+use asteroids::AsteroidsPlugin;
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(asteroids::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(AsteroidsPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,103 @@
+//! Picks the turn leading to the best board by a hand-weighted evaluation: race, blots left
+//! in reach of the opponent, points made and opposing checkers sent back.
+
+use crate::rules::{Board, Cube, Move, Side};
+
+/// Penalty for a blot the opponent hits with a single die.
+const DIRECT_SHOT: i32 = 12;
+/// The evaluation at which the computer doubles.
+const DOUBLE_AT: i32 = 45;
+/// Bonus for each opposing checker on the bar.
+const HIT: i32 = 8;
+/// Extra bonus for a point made in the own home board.
+const HOME_POINT: i32 = 4;
+/// Penalty for a blot the opponent needs both dice to hit.
+const INDIRECT_SHOT: i32 = 4;
+/// Bonus for each point held with two or more checkers.
+const MADE_POINT: i32 = 5;
+/// Below this evaluation a double is refused.
+const TAKE_LIMIT: i32 = -70;
+
+/// The turn giving the best board for `side` afterwards.
+pub fn choose(board: &Board, side: Side, dice: &[u8]) -> Vec<Move> {
+    board
+        .turns(side, dice)
+        .into_iter()
+        .max_by_key(|(_, after)| evaluate(after, side))
+        .map(|(steps, _)| steps)
+        .unwrap_or_default()
+}
+
+/// Score of the board for `side`, positive when ahead.
+pub fn evaluate(board: &Board, side: Side) -> i32 {
+    let other = side.opposite();
+    let race = board.pips(other) as i32 - board.pips(side) as i32;
+    if board.is_race() {
+        return race * 2;
+    }
+    let mut score = race + board.bar[other.index()] as i32 * HIT;
+    for point in 0..24 {
+        match board.count(side, point) {
+            0 => {}
+            1 => score -= shot_penalty(board, side, point),
+            _ => {
+                score += MADE_POINT;
+                if side.pip(point) <= 6 {
+                    score += HOME_POINT;
+                }
+            }
+        }
+    }
+    score
+}
+
+/// Doubles when clearly ahead and the cube is free or owned.
+pub fn should_double(board: &Board, side: Side, cube: &Cube) -> bool {
+    cube.can_double(side) && evaluate(board, side) >= DOUBLE_AT
+}
+
+pub fn should_take(board: &Board, side: Side) -> bool {
+    evaluate(board, side) > TAKE_LIMIT
+}
+
+/// How much the blot of `side` on `point` is at risk from the nearest opposing checker behind it.
+fn shot_penalty(board: &Board, side: Side, point: usize) -> i32 {
+    let other = side.opposite();
+    let target = other.pip(point);
+    let mut nearest = if board.bar[other.index()] > 0 {
+        25 - target
+    } else {
+        u8::MAX
+    };
+    for pip in target + 1..=24 {
+        if board.count(other, other.point(pip)) > 0 {
+            nearest = nearest.min(pip - target);
+        }
+    }
+    match nearest {
+        1..=6 => DIRECT_SHOT,
+        7..=12 => INDIRECT_SHOT,
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_is_even() {
+        let board = Board::start();
+        assert_eq!(evaluate(&board, Side::White), evaluate(&board, Side::Black));
+    }
+
+    #[test]
+    fn makes_a_point_rather_than_leaving_blots() {
+        // 3-1 from the start: the five point is made instead of splitting into two blots.
+        let steps = choose(&Board::start(), Side::White, &[3, 1]);
+        let board = steps.iter().fold(Board::start(), |board, step| {
+            board.apply(Side::White, *step)
+        });
+        assert_eq!(board.count(Side::White, 4), 2);
+    }
+}
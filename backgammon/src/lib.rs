@@ -0,0 +1,950 @@
+use bevy::math::const_vec2;
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::{thread_rng, Rng};
+use rules::{Board, Cube, Move, Side, Spot};
+
+pub mod ai;
+pub mod rules;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.08, 0.06);
+const BAR_WIDTH: f32 = 48.0;
+const BLACK_COLOR: Color = Color::rgb(0.16, 0.12, 0.12);
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, -20.0]);
+const BOARD_HEIGHT: f32 = 520.0;
+const BOARD_WIDTH: f32 = 12.0 * POINT_WIDTH + BAR_WIDTH;
+const CHECKER_FONT_SIZE: f32 = 66.0;
+const CHECKER_SPACING: f32 = 42.0;
+/// Doubling stops here, like on a real cube.
+const CUBE_LIMIT: u32 = 64;
+/// Pause before each action of the computer, and after a roll that cannot be played.
+const DELAY: f32 = 0.7;
+const DIE_SIZE: f32 = 40.0;
+const FELT_COLOR: Color = Color::rgb(0.16, 0.32, 0.22);
+const FRAME_COLOR: Color = Color::rgb(0.36, 0.22, 0.12);
+const OUTLINE_COLOR: Color = Color::rgb(0.5, 0.45, 0.4);
+const POINT_COLORS: [Color; 2] = [Color::rgb(0.86, 0.76, 0.56), Color::rgb(0.62, 0.2, 0.15)];
+const POINT_LENGTH: f32 = 210.0;
+/// Each point is drawn as a stack of bars narrowing towards its tip.
+const POINT_STEPS: usize = 8;
+const POINT_WIDTH: f32 = 52.0;
+/// Checkers drawn on a point before the rest is shown as a number.
+const STACK_LIMIT: usize = 5;
+const SELECTED_COLOR: Color = Color::rgba(0.95, 0.85, 0.2, 0.6);
+const TARGET_COLOR: Color = Color::rgba(0.35, 0.9, 0.45, 0.6);
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.9, 0.86);
+const TRAY_WIDTH: f32 = 52.0;
+const WHITE_COLOR: Color = Color::rgb(0.95, 0.93, 0.88);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+#[derive(Component)]
+struct CenterText;
+
+/// Runs out when the computer acts next, or a blocked roll passes the turn.
+struct Delay(Timer);
+
+struct Game {
+    board: Board,
+    cube: Cube,
+    message: String,
+    mode: Mode,
+    phase: Phase,
+    /// The moves the computer still has to show for its roll.
+    planned: Vec<Move>,
+    /// Dice not used yet this turn.
+    remaining: Vec<u8>,
+    roll: [u8; 2],
+    /// Points won so far this session.
+    score: [u32; 2],
+    selected: Option<Spot>,
+    /// The side to move.
+    side: Side,
+    /// The board and dice before each move of the current turn, to take moves back.
+    undo: Vec<(Board, Vec<u8>)>,
+    winner: Option<Side>,
+}
+
+impl Game {
+    fn new(mode: Mode) -> Self {
+        let mut game = Self {
+            board: Board::start(),
+            cube: Cube::new(),
+            message: String::new(),
+            mode,
+            phase: Phase::Rolling,
+            planned: Vec::new(),
+            remaining: Vec::new(),
+            roll: [1, 1],
+            score: [0; 2],
+            selected: None,
+            side: Side::White,
+            undo: Vec::new(),
+            winner: None,
+        };
+        game.open();
+        game
+    }
+
+    /// The side that has to act now: the one answering a double, otherwise the side to move.
+    fn acting(&self) -> Side {
+        match self.phase {
+            Phase::Doubled => self.side.opposite(),
+            _ => self.side,
+        }
+    }
+
+    fn can_double(&self) -> bool {
+        self.phase == Phase::Rolling
+            && self.cube.can_double(self.side)
+            && self.cube.value < CUBE_LIMIT
+    }
+
+    fn double(&mut self) {
+        self.phase = Phase::Doubled;
+        self.message = format!(
+            "{} doubles to {}: {} takes (Y) or passes (N)",
+            self.name(self.side),
+            self.cube.value * 2,
+            self.name(self.side.opposite())
+        );
+    }
+
+    fn end_turn(&mut self) {
+        self.side = self.side.opposite();
+        self.phase = Phase::Rolling;
+        self.planned.clear();
+        self.remaining.clear();
+        self.selected = None;
+        self.undo.clear();
+        self.message = format!("{} to roll", self.name(self.side));
+    }
+
+    fn finish(&mut self, winner: Side, points: u32) {
+        self.winner = Some(winner);
+        self.score[winner.index()] += points;
+        let plural = if points == 1 { "" } else { "s" };
+        self.message = format!("{} won {} point{}", self.name(winner), points, plural);
+    }
+
+    fn is_human(&self, side: Side) -> bool {
+        self.mode == Mode::TwoPlayers || side == Side::White
+    }
+
+    fn legal_moves(&self) -> Vec<Move> {
+        self.board.legal_moves(self.side, &self.remaining)
+    }
+
+    fn name(&self, side: Side) -> &'static str {
+        match (self.mode, side) {
+            (Mode::Computer, Side::White) => "You",
+            (Mode::Computer, Side::Black) => "The computer",
+            (Mode::TwoPlayers, side) => side.name(),
+        }
+    }
+
+    /// A new game in the same session, keeping the score.
+    fn next_game(&mut self) {
+        let score = self.score;
+        *self = Self::new(self.mode);
+        self.score = score;
+    }
+
+    /// Each side rolls one die, the higher one starts and plays both.
+    fn open(&mut self) {
+        let mut rng = thread_rng();
+        let roll = loop {
+            let roll = [rng.gen_range(1, 7), rng.gen_range(1, 7)];
+            if roll[0] != roll[1] {
+                break roll;
+            }
+        };
+        self.side = if roll[0] > roll[1] {
+            Side::White
+        } else {
+            Side::Black
+        };
+        self.start_moving(roll);
+        self.message = format!(
+            "{} {} the opening roll {}-{}",
+            self.name(self.side),
+            if self.side == Side::White && self.mode == Mode::Computer {
+                "win"
+            } else {
+                "wins"
+            },
+            roll[0],
+            roll[1]
+        );
+    }
+
+    /// Refuses the double, losing the game at the current stake.
+    fn pass(&mut self) {
+        self.finish(self.side, self.cube.value);
+    }
+
+    /// Plays one move; returns whether it hit an opposing checker.
+    fn play(&mut self, step: Move) -> bool {
+        let other = self.side.opposite();
+        let bar = self.board.bar[other.index()];
+        self.undo.push((self.board.clone(), self.remaining.clone()));
+        self.board = self.board.apply(self.side, step);
+        if let Some(index) = self.remaining.iter().position(|die| *die == step.die) {
+            self.remaining.remove(index);
+        }
+        self.selected = None;
+        if let Some(winner) = self.board.winner() {
+            self.finish(winner, self.board.result(winner) * self.cube.value);
+        } else if self.legal_moves().is_empty() && self.is_human(self.side) {
+            self.message = "Space: end the turn   U: take back".to_string();
+        }
+        self.board.bar[other.index()] > bar
+    }
+
+    fn roll(&mut self) {
+        let mut rng = thread_rng();
+        self.start_moving([rng.gen_range(1, 7), rng.gen_range(1, 7)]);
+        self.message = format!(
+            "{} rolled {}-{}",
+            self.name(self.side),
+            self.roll[0],
+            self.roll[1]
+        );
+    }
+
+    fn start_moving(&mut self, roll: [u8; 2]) {
+        self.roll = roll;
+        self.remaining = rules::dice(roll);
+        self.undo.clear();
+        self.phase = if self.legal_moves().is_empty() {
+            Phase::Blocked
+        } else {
+            Phase::Moving
+        };
+    }
+
+    fn take(&mut self) {
+        let taker = self.side.opposite();
+        self.cube.take(taker);
+        self.phase = Phase::Rolling;
+        self.message = format!(
+            "{} {} the double, the game is worth {}",
+            self.name(taker),
+            if taker == Side::White && self.mode == Mode::Computer {
+                "take"
+            } else {
+                "takes"
+            },
+            self.cube.value
+        );
+    }
+
+    fn take_back(&mut self) {
+        if let Some((board, remaining)) = self.undo.pop() {
+            self.board = board;
+            self.remaining = remaining;
+            self.selected = None;
+            self.message.clear();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// You play white against the computer.
+    Computer,
+    /// Two players taking turns at one screen.
+    TwoPlayers,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    /// The side to move may double before rolling.
+    Rolling,
+    /// The side to move offered a double, the other side takes or passes.
+    Doubled,
+    Moving,
+    /// The roll cannot be played; the turn passes after a moment.
+    Blocked,
+}
+
+struct Sounds {
+    checker: Handle<AudioSource>,
+    double: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    roll: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct BackgammonPlugin;
+
+impl Plugin for BackgammonPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(Mode::Computer))
+            .insert_resource(Delay(Timer::from_seconds(DELAY, false)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(turn_update),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(board_render)
+            .add_system(escape_input)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Backgammon".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws the board every frame: points, checkers, the dice, the cube and what a click may do.
+fn board_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+) {
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    sprite(
+        &mut commands,
+        FRAME_COLOR,
+        Vec2::new(BOARD_WIDTH + 2.0 * TRAY_WIDTH + 48.0, BOARD_HEIGHT + 24.0),
+        BOARD_CENTER,
+        0.0,
+    );
+    for half in [-1.0, 1.0] {
+        let width = 6.0 * POINT_WIDTH;
+        sprite(
+            &mut commands,
+            FELT_COLOR,
+            Vec2::new(width, BOARD_HEIGHT),
+            BOARD_CENTER + Vec2::new(half * (width + BAR_WIDTH) / 2.0, 0.0),
+            1.0,
+        );
+    }
+    for tray in [-1.0, 1.0] {
+        sprite(
+            &mut commands,
+            FELT_COLOR,
+            Vec2::new(TRAY_WIDTH, BOARD_HEIGHT),
+            BOARD_CENTER + Vec2::new(tray * tray_x(), 0.0),
+            1.0,
+        );
+    }
+
+    let moves = if game.phase == Phase::Moving && game.is_human(game.side) {
+        game.legal_moves()
+    } else {
+        Vec::new()
+    };
+    for point in 0..24 {
+        let (base, direction) = point_base(point);
+        for step in 0..POINT_STEPS {
+            let height = POINT_LENGTH / POINT_STEPS as f32;
+            let width = POINT_WIDTH * (1.0 - step as f32 / POINT_STEPS as f32) - 4.0;
+            let center = base + Vec2::new(0.0, direction * (step as f32 + 0.5) * height);
+            sprite(
+                &mut commands,
+                POINT_COLORS[point % 2],
+                Vec2::new(width, height + 0.5),
+                center,
+                2.0,
+            );
+        }
+    }
+
+    // The selected checker and where it may go, or else every checker that may move.
+    let highlights: Vec<(Spot, Color)> = match game.selected {
+        Some(from) => std::iter::once((from, SELECTED_COLOR))
+            .chain(
+                moves
+                    .iter()
+                    .filter(|step| step.from == from)
+                    .map(|step| (step.to, TARGET_COLOR)),
+            )
+            .collect(),
+        None => moves
+            .iter()
+            .map(|step| (step.from, SELECTED_COLOR))
+            .collect(),
+    };
+    for (spot, color) in highlights {
+        let (size, center) = match spot {
+            Spot::Point(point) => {
+                let (base, direction) = point_base(point);
+                (
+                    Vec2::new(POINT_WIDTH - 2.0, POINT_LENGTH + 20.0),
+                    base + Vec2::new(0.0, direction * (POINT_LENGTH + 20.0) / 2.0),
+                )
+            }
+            Spot::Bar => (
+                Vec2::new(BAR_WIDTH, BOARD_HEIGHT / 2.0),
+                bar_position(game.side, 0) + Vec2::new(0.0, bar_direction(game.side) * 100.0),
+            ),
+            Spot::Off => (
+                Vec2::new(TRAY_WIDTH, BOARD_HEIGHT / 2.0),
+                BOARD_CENTER + Vec2::new(tray_x(), off_direction(game.side) * -BOARD_HEIGHT / 4.0),
+            ),
+        };
+        sprite(&mut commands, color, size, center, 3.0);
+    }
+
+    for side in [Side::White, Side::Black] {
+        let (color, outline) = match side {
+            Side::White => (WHITE_COLOR, OUTLINE_COLOR),
+            Side::Black => (BLACK_COLOR, OUTLINE_COLOR),
+        };
+        let mut checkers = Vec::new();
+        for point in 0..24 {
+            let count = game.board.count(side, point) as usize;
+            let (base, direction) = point_base(point);
+            for index in 0..count.min(STACK_LIMIT) {
+                let offset = (index as f32 + 0.5) * CHECKER_SPACING;
+                checkers.push(base + Vec2::new(0.0, direction * offset));
+            }
+            if count > STACK_LIMIT {
+                let offset = (STACK_LIMIT as f32 - 0.5) * CHECKER_SPACING;
+                let label = count.to_string();
+                let center = base + Vec2::new(0.0, direction * offset);
+                let label_color = match side {
+                    Side::White => BLACK_COLOR,
+                    Side::Black => WHITE_COLOR,
+                };
+                commands
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        label,
+                        18.0,
+                        label_color,
+                        center.extend(5.0),
+                    ))
+                    .insert(Actor);
+            }
+        }
+        for index in 0..game.board.bar[side.index()] as usize {
+            checkers.push(bar_position(side, index));
+        }
+        for center in checkers {
+            for (glyph, color, z) in [('\u{25CF}', color, 4.0), ('\u{25CB}', outline, 4.1)] {
+                commands
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        glyph,
+                        CHECKER_FONT_SIZE,
+                        color,
+                        center.extend(z),
+                    ))
+                    .insert(Actor);
+            }
+        }
+        // Borne off checkers lie flat in the tray by the own home board.
+        let direction = off_direction(side);
+        let bottom = BOARD_CENTER.y - direction * (BOARD_HEIGHT / 2.0 - 8.0);
+        for index in 0..game.board.off[side.index()] {
+            let y = bottom + direction * index as f32 * 11.0;
+            sprite(
+                &mut commands,
+                color,
+                Vec2::new(TRAY_WIDTH - 12.0, 9.0),
+                Vec2::new(BOARD_CENTER.x + tray_x(), y),
+                4.0,
+            );
+        }
+    }
+
+    // The cube sits in the middle until taken, then on the side of its owner.
+    let cube_y = match game.cube.owner {
+        None => 0.0,
+        Some(owner) => -off_direction(owner) * (BOARD_HEIGHT / 2.0 - 40.0),
+    };
+    let cube_center = BOARD_CENTER + Vec2::new(-tray_x(), cube_y);
+    sprite(
+        &mut commands,
+        WHITE_COLOR,
+        Vec2::splat(DIE_SIZE),
+        cube_center,
+        6.0,
+    );
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            game.cube.value.to_string(),
+            22.0,
+            BLACK_COLOR,
+            cube_center.extend(7.0),
+        ))
+        .insert(Actor);
+
+    if game.phase == Phase::Rolling || game.phase == Phase::Doubled {
+        return;
+    }
+    let shown = rules::dice(game.roll);
+    let mut left = game.remaining.clone();
+    // The dice lie on the right half of the board, used ones faded.
+    let right_half = BOARD_CENTER.x + (3.0 * POINT_WIDTH + BAR_WIDTH / 2.0);
+    for (index, die) in shown.iter().enumerate() {
+        let unused = match left.iter().position(|other| other == die) {
+            Some(position) => {
+                left.remove(position);
+                true
+            }
+            None => false,
+        };
+        let alpha = if unused { 1.0 } else { 0.35 };
+        let x = right_half + (index as f32 - (shown.len() - 1) as f32 / 2.0) * (DIE_SIZE + 10.0);
+        let center = Vec2::new(x, BOARD_CENTER.y);
+        let mut face = WHITE_COLOR;
+        face.set_a(alpha);
+        sprite(&mut commands, face, Vec2::splat(DIE_SIZE), center, 6.0);
+        let mut pip = BLACK_COLOR;
+        pip.set_a(alpha);
+        for (column, row) in die_pips(*die) {
+            let offset = Vec2::new(*column as f32, *row as f32) * DIE_SIZE * 0.27;
+            sprite(&mut commands, pip, Vec2::splat(7.0), center + offset, 7.0);
+        }
+    }
+}
+
+/// Where the `index`th checker of `side` on the bar goes: white in the upper half, next to
+/// where it enters, black in the lower half.
+fn bar_position(side: Side, index: usize) -> Vec2 {
+    let direction = bar_direction(side);
+    BOARD_CENTER + Vec2::new(0.0, direction * (40.0 + index as f32 * CHECKER_SPACING))
+}
+
+fn bar_direction(side: Side) -> f32 {
+    match side {
+        Side::White => 1.0,
+        Side::Black => -1.0,
+    }
+}
+
+/// Offsets of the pips on a die face, in steps from its center.
+fn die_pips(die: u8) -> &'static [(i8, i8)] {
+    match die {
+        1 => &[(0, 0)],
+        2 => &[(-1, 1), (1, -1)],
+        3 => &[(-1, 1), (0, 0), (1, -1)],
+        4 => &[(-1, 1), (1, 1), (-1, -1), (1, -1)],
+        5 => &[(-1, 1), (1, 1), (0, 0), (-1, -1), (1, -1)],
+        _ => &[(-1, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (1, -1)],
+    }
+}
+
+/// Escape leaves a game for the menu at any time.
+fn escape_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() != GameState::Menu {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Up/Down picks the opponent, Enter starts a new session.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.vertical() != 0 {
+        game.mode = match game.mode {
+            Mode::Computer => Mode::TwoPlayers,
+            Mode::TwoPlayers => Mode::Computer,
+        };
+    }
+    if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(game.mode);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Which way the own tray fills: white bears off at the bottom, black at the top.
+fn off_direction(side: Side) -> f32 {
+    match side {
+        Side::White => 1.0,
+        Side::Black => -1.0,
+    }
+}
+
+fn over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        game.next_game();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Rolling, doubling and moving for whoever sits at the screen.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.is_human(game.acting()) {
+        return;
+    }
+    match game.phase {
+        Phase::Rolling => {
+            if keyboard_input.just_pressed(KeyCode::D) && game.can_double() {
+                game.double();
+                audio.play(sounds.double.clone());
+            } else if keyboard_input.just_pressed(KeyCode::Space)
+                || mouse_input.just_pressed(MouseButton::Left)
+            {
+                game.roll();
+                audio.play(sounds.roll.clone());
+            }
+        }
+        Phase::Doubled => {
+            if keyboard_input.just_pressed(KeyCode::Y) {
+                game.take();
+            } else if keyboard_input.just_pressed(KeyCode::N) {
+                game.pass();
+                audio.play(sounds.won.clone());
+                state.set(GameState::Over).unwrap();
+            }
+        }
+        Phase::Moving => {
+            let moves = game.legal_moves();
+            if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) {
+                game.take_back();
+            } else if moves.is_empty() {
+                if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+                    game.end_turn();
+                }
+            } else if mouse_input.just_pressed(MouseButton::Right) {
+                game.selected = None;
+            } else if mouse_input.just_pressed(MouseButton::Left) {
+                let clicked = match game_hud::cursor_world_position(&windows).and_then(spot_at) {
+                    Some(spot) => spot,
+                    None => return,
+                };
+                // The smallest die that gets the selected checker there.
+                let step = moves
+                    .iter()
+                    .filter(|step| Some(step.from) == game.selected && step.to == clicked)
+                    .min_by_key(|step| step.die)
+                    .copied();
+                if let Some(step) = step {
+                    let hit = game.play(step);
+                    audio.play(if hit {
+                        sounds.hit.clone()
+                    } else {
+                        sounds.checker.clone()
+                    });
+                    if game.winner.is_some() {
+                        audio.play(sounds.won.clone());
+                        state.set(GameState::Over).unwrap();
+                    }
+                } else if moves.iter().any(|step| step.from == clicked) {
+                    game.selected = Some(clicked);
+                } else {
+                    game.selected = None;
+                }
+            }
+        }
+        Phase::Blocked => {}
+    }
+}
+
+/// The middle of the base of a point, and whether it points up (1) or down (-1).
+fn point_base(point: usize) -> (Vec2, f32) {
+    // White's home board is at the bottom right, black's at the top right.
+    let (column, top) = if point < 12 {
+        (11 - point, false)
+    } else {
+        (point - 12, true)
+    };
+    let mut x = -BOARD_WIDTH / 2.0 + (column as f32 + 0.5) * POINT_WIDTH;
+    if column >= 6 {
+        x += BAR_WIDTH;
+    }
+    let (y, direction) = if top {
+        (BOARD_HEIGHT / 2.0, -1.0)
+    } else {
+        (-BOARD_HEIGHT / 2.0, 1.0)
+    };
+    (BOARD_CENTER + Vec2::new(x, y), direction)
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, WINDOW_HEIGHT / 2.0 - 48.0, 10.0),
+        ))
+        .insert(MessageText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 20.0),
+        ))
+        .insert(CenterText);
+
+    commands.insert_resource(Sounds {
+        checker: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.05)
+                .fade()
+                .volume(0.35)
+                .into(),
+        ),
+        double: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 660.0, 0.2).volume(0.2),
+        ])),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Square, 440.0, 0.18)
+                .slide(220.0)
+                .fade()
+                .volume(0.25)
+                .into(),
+        ),
+        roll: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.04).volume(0.3),
+            Tone::new(Waveform::Noise, 0.0, 0.04).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.06).fade().volume(0.3),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.12),
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.4),
+        ])),
+    });
+}
+
+fn sprite(commands: &mut Commands, color: Color, size: Vec2, center: Vec2, z: f32) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(z)),
+            ..Default::default()
+        })
+        .insert(Actor);
+}
+
+/// The point, bar or tray under `position`.
+fn spot_at(position: Vec2) -> Option<Spot> {
+    let offset = position - BOARD_CENTER;
+    if offset.y.abs() > BOARD_HEIGHT / 2.0 {
+        return None;
+    }
+    if offset.x.abs() <= BAR_WIDTH / 2.0 {
+        return Some(Spot::Bar);
+    }
+    if (offset.x - tray_x()).abs() <= TRAY_WIDTH / 2.0 {
+        return Some(Spot::Off);
+    }
+    if offset.x.abs() > BOARD_WIDTH / 2.0 {
+        return None;
+    }
+    let mut x = offset.x + BOARD_WIDTH / 2.0;
+    if x > 6.0 * POINT_WIDTH {
+        x -= BAR_WIDTH;
+    }
+    let column = ((x / POINT_WIDTH) as usize).min(11);
+    Some(Spot::Point(if offset.y > 0.0 {
+        12 + column
+    } else {
+        11 - column
+    }))
+}
+
+fn texts_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+        QueryState<&mut Text, With<CenterText>>,
+    )>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    let current = *state.current();
+    let hud = match current {
+        GameState::Menu => String::new(),
+        _ => format!(
+            "{} {}   {} {}      PIPS {} {}  {} {}",
+            game.name(Side::White).to_uppercase(),
+            game.score[0],
+            game.name(Side::Black).to_uppercase(),
+            game.score[1],
+            Side::White.name().to_uppercase(),
+            game.board.pips(Side::White),
+            Side::Black.name().to_uppercase(),
+            game.board.pips(Side::Black)
+        ),
+    };
+    let message = match (current, game.phase) {
+        (GameState::Menu, _) => String::new(),
+        (GameState::Over, _) => format!("{}   Enter: next game   Esc: menu", game.message),
+        (_, Phase::Rolling) if game.is_human(game.side) => {
+            let double = if game.can_double() {
+                "   D: double"
+            } else {
+                ""
+            };
+            format!("{}: Space or click to roll{}", game.name(game.side), double)
+        }
+        (_, Phase::Blocked) => format!("{} and cannot move", game.message),
+        _ => game.message.clone(),
+    };
+    let center = match current {
+        GameState::Menu => {
+            let marker = |mode| if game.mode == mode { ">" } else { " " };
+            format!(
+                "BACKGAMMON\n\n{} Play white against the computer\n{} Two players at one screen\n\n\
+                 Up/Down: opponent   Enter: start",
+                marker(Mode::Computer),
+                marker(Mode::TwoPlayers)
+            )
+        }
+        _ => String::new(),
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = center.clone();
+    }
+}
+
+/// Horizontal distance from the board center to the middle of the trays.
+fn tray_x() -> f32 {
+    BOARD_WIDTH / 2.0 + 12.0 + TRAY_WIDTH / 2.0
+}
+
+/// Plays the computer's side one action per delay, and passes turns that cannot be played.
+fn turn_update(
+    time: Res<Time>,
+    mut delay: ResMut<Delay>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.phase != Phase::Blocked && game.is_human(game.acting()) {
+        delay.0.reset();
+        return;
+    }
+    if !delay.0.tick(time.delta()).finished() {
+        return;
+    }
+    delay.0.reset();
+    let side = game.acting();
+    match game.phase {
+        Phase::Blocked => game.end_turn(),
+        Phase::Rolling => {
+            if game.can_double() && ai::should_double(&game.board, side, &game.cube) {
+                game.double();
+                audio.play(sounds.double.clone());
+            } else {
+                game.roll();
+                audio.play(sounds.roll.clone());
+            }
+        }
+        Phase::Doubled => {
+            if ai::should_take(&game.board, side) {
+                game.take();
+            } else {
+                game.pass();
+                audio.play(sounds.won.clone());
+                state.set(GameState::Over).unwrap();
+            }
+        }
+        Phase::Moving => {
+            if game.planned.is_empty() {
+                game.planned = ai::choose(&game.board, side, &game.remaining);
+            }
+            if game.planned.is_empty() {
+                game.end_turn();
+                return;
+            }
+            let step = game.planned.remove(0);
+            let hit = game.play(step);
+            audio.play(if hit {
+                sounds.hit.clone()
+            } else {
+                sounds.checker.clone()
+            });
+            if game.winner.is_some() {
+                audio.play(sounds.won.clone());
+                state.set(GameState::Over).unwrap();
+            }
+        }
+    }
+}
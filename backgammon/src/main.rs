@@ -0,0 +1,14 @@
+use backgammon::BackgammonPlugin;
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(backgammon::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(BackgammonPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,452 @@
+//! Points are numbered 0 to 23: white moves its checkers down towards point 0 and bears off
+//! from points 0-5, black moves up and bears off from points 18-23.
+
+/// Checkers each side starts with.
+pub const CHECKERS: u8 = 15;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    White,
+    Black,
+}
+
+impl Side {
+    pub fn index(self) -> usize {
+        match self {
+            Self::White => 0,
+            Self::Black => 1,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::White => "White",
+            Self::Black => "Black",
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    /// The point `pip` steps away from bearing off, from this side's view (1 to 24).
+    pub fn point(self, pip: u8) -> usize {
+        match self {
+            Self::White => pip as usize - 1,
+            Self::Black => 24 - pip as usize,
+        }
+    }
+
+    /// How far a checker on `point` still has to go to bear off.
+    pub fn pip(self, point: usize) -> u8 {
+        match self {
+            Self::White => point as u8 + 1,
+            Self::Black => 24 - point as u8,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Spot {
+    Bar,
+    Point(usize),
+    Off,
+}
+
+/// One checker moved by one die.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub die: u8,
+    pub from: Spot,
+    pub to: Spot,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Board {
+    pub bar: [u8; 2],
+    pub off: [u8; 2],
+    /// Checkers on each point, white counted positive and black negative.
+    pub points: [i8; 24],
+}
+
+impl Board {
+    pub fn start() -> Self {
+        let mut board = Self {
+            bar: [0; 2],
+            off: [0; 2],
+            points: [0; 24],
+        };
+        for side in [Side::White, Side::Black] {
+            for (pip, count) in [(24, 2), (13, 5), (8, 3), (6, 5)] {
+                board.put(side, side.point(pip), count);
+            }
+        }
+        board
+    }
+
+    /// Plays a move, sending a single opposing checker on the target point to the bar.
+    pub fn apply(&self, side: Side, step: Move) -> Self {
+        let mut board = self.clone();
+        match step.from {
+            Spot::Bar => board.bar[side.index()] -= 1,
+            Spot::Point(point) => board.put(side, point, -1),
+            Spot::Off => unreachable!("checkers never come back once borne off"),
+        }
+        match step.to {
+            Spot::Point(point) => {
+                let other = side.opposite();
+                if board.count(other, point) == 1 {
+                    board.put(other, point, -1);
+                    board.bar[other.index()] += 1;
+                }
+                board.put(side, point, 1);
+            }
+            Spot::Off => board.off[side.index()] += 1,
+            Spot::Bar => unreachable!("checkers only reach the bar by being hit"),
+        }
+        board
+    }
+
+    /// Checkers of `side` on `point`.
+    pub fn count(&self, side: Side, point: usize) -> u8 {
+        let count = self.points[point];
+        match side {
+            Side::White if count > 0 => count as u8,
+            Side::Black if count < 0 => -count as u8,
+            _ => 0,
+        }
+    }
+
+    /// Whether both sides have passed each other, leaving a pure race.
+    pub fn is_race(&self) -> bool {
+        if self.bar != [0; 2] {
+            return false;
+        }
+        let white_back = (0..24)
+            .rev()
+            .find(|point| self.count(Side::White, *point) > 0);
+        let black_back = (0..24).find(|point| self.count(Side::Black, *point) > 0);
+        match (white_back, black_back) {
+            (Some(white), Some(black)) => white < black,
+            _ => true,
+        }
+    }
+
+    /// The moves that may start the rest of the turn. As many dice as possible have to be
+    /// used, and if only one of two can be, the larger one.
+    pub fn legal_moves(&self, side: Side, dice: &[u8]) -> Vec<Move> {
+        let most = self.most_usable(side, dice);
+        if most == 0 {
+            return Vec::new();
+        }
+        let mut moves = Vec::new();
+        for (index, die) in distinct(dice) {
+            let rest = without(dice, index);
+            for step in self.single_moves(side, die) {
+                if 1 + self.apply(side, step).most_usable(side, &rest) == most {
+                    moves.push(step);
+                }
+            }
+        }
+        if most == 1 && dice.len() == 2 && dice[0] != dice[1] {
+            let high = dice[0].max(dice[1]);
+            if moves.iter().any(|step| step.die == high) {
+                moves.retain(|step| step.die == high);
+            }
+        }
+        moves
+    }
+
+    /// Total pips `side` needs to bear off every checker.
+    pub fn pips(&self, side: Side) -> u32 {
+        let on_points: u32 = (0..24)
+            .map(|point| self.count(side, point) as u32 * side.pip(point) as u32)
+            .sum();
+        on_points + self.bar[side.index()] as u32 * 25
+    }
+
+    /// Points `side` wins with all checkers off: a gammon if the loser has borne off none,
+    /// a backgammon if they also still have one on the bar or in the winner's home board.
+    pub fn result(&self, side: Side) -> u32 {
+        let loser = side.opposite();
+        if self.off[loser.index()] > 0 {
+            return 1;
+        }
+        let stuck = self.bar[loser.index()] > 0
+            || (1..=6).any(|pip| self.count(loser, side.point(pip)) > 0);
+        if stuck {
+            3
+        } else {
+            2
+        }
+    }
+
+    /// Every way to play the whole roll, each leading to a different board.
+    pub fn turns(&self, side: Side, dice: &[u8]) -> Vec<(Vec<Move>, Board)> {
+        let moves = self.legal_moves(side, dice);
+        if moves.is_empty() {
+            return vec![(Vec::new(), self.clone())];
+        }
+        let mut turns: Vec<(Vec<Move>, Board)> = Vec::new();
+        for step in moves {
+            let index = dice.iter().position(|die| *die == step.die).unwrap();
+            let rest = without(dice, index);
+            for (mut steps, board) in self.apply(side, step).turns(side, &rest) {
+                if turns.iter().all(|(_, other)| *other != board) {
+                    steps.insert(0, step);
+                    turns.push((steps, board));
+                }
+            }
+        }
+        turns
+    }
+
+    pub fn winner(&self) -> Option<Side> {
+        [Side::White, Side::Black]
+            .into_iter()
+            .find(|side| self.off[side.index()] == CHECKERS)
+    }
+
+    /// The most dice that can still be used, whatever order they are played in.
+    fn most_usable(&self, side: Side, dice: &[u8]) -> usize {
+        let mut most = 0;
+        for (index, die) in distinct(dice) {
+            let rest = without(dice, index);
+            for step in self.single_moves(side, die) {
+                most = most.max(1 + self.apply(side, step).most_usable(side, &rest));
+                if most == dice.len() {
+                    return most;
+                }
+            }
+        }
+        most
+    }
+
+    /// Whether `side` may land on `point`: it is not held by two or more opposing checkers.
+    fn is_open(&self, side: Side, point: usize) -> bool {
+        self.count(side.opposite(), point) < 2
+    }
+
+    fn put(&mut self, side: Side, point: usize, count: i8) {
+        match side {
+            Side::White => self.points[point] += count,
+            Side::Black => self.points[point] -= count,
+        }
+    }
+
+    /// Moves of a single checker by `die`, ignoring the rest of the roll.
+    fn single_moves(&self, side: Side, die: u8) -> Vec<Move> {
+        if self.bar[side.index()] > 0 {
+            let point = side.point(25 - die);
+            return if self.is_open(side, point) {
+                vec![Move {
+                    die,
+                    from: Spot::Bar,
+                    to: Spot::Point(point),
+                }]
+            } else {
+                Vec::new()
+            };
+        }
+        let farthest = (1..=24)
+            .rev()
+            .find(|pip| self.count(side, side.point(*pip)) > 0)
+            .unwrap_or(0);
+        let bearing_off = farthest <= 6;
+        let mut moves = Vec::new();
+        for pip in 1..=24 {
+            let from = side.point(pip);
+            if self.count(side, from) == 0 {
+                continue;
+            }
+            let to = if pip > die {
+                let to = side.point(pip - die);
+                if !self.is_open(side, to) {
+                    continue;
+                }
+                Spot::Point(to)
+            } else if bearing_off && (pip == die || pip == farthest) {
+                // A die larger than needed bears off only from the farthest point.
+                Spot::Off
+            } else {
+                continue;
+            };
+            moves.push(Move {
+                die,
+                from: Spot::Point(from),
+                to,
+            });
+        }
+        moves
+    }
+}
+
+/// Who may double next and how much the game is worth.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cube {
+    /// The side that took the last double; nobody owns a cube still in the middle.
+    pub owner: Option<Side>,
+    pub value: u32,
+}
+
+impl Cube {
+    pub fn new() -> Self {
+        Self {
+            owner: None,
+            value: 1,
+        }
+    }
+
+    pub fn can_double(&self, side: Side) -> bool {
+        self.owner != Some(side.opposite())
+    }
+
+    /// The double was taken: the game is worth twice as much and `taker` owns the cube.
+    pub fn take(&mut self, taker: Side) {
+        self.value *= 2;
+        self.owner = Some(taker);
+    }
+}
+
+impl Default for Cube {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The dice to play for a roll: doubles are played four times.
+pub fn dice(roll: [u8; 2]) -> Vec<u8> {
+    if roll[0] == roll[1] {
+        vec![roll[0]; 4]
+    } else {
+        roll.to_vec()
+    }
+}
+
+/// Each different die once, with the index of its first occurrence.
+fn distinct(dice: &[u8]) -> Vec<(usize, u8)> {
+    let mut found: Vec<(usize, u8)> = Vec::new();
+    for (index, die) in dice.iter().enumerate() {
+        if found.iter().all(|(_, other)| other != die) {
+            found.push((index, *die));
+        }
+    }
+    found
+}
+
+fn without(dice: &[u8], index: usize) -> Vec<u8> {
+    let mut rest = dice.to_vec();
+    rest.remove(index);
+    rest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn empty() -> Board {
+        Board {
+            bar: [0; 2],
+            off: [0; 2],
+            points: [0; 24],
+        }
+    }
+
+    #[test]
+    fn start_position_is_mirrored() {
+        let board = Board::start();
+        assert_eq!(board.pips(Side::White), 167);
+        assert_eq!(board.pips(Side::Black), 167);
+        assert_eq!(board.points[23], 2);
+        assert_eq!(board.points[0], -2);
+        // The classic opening 3-1 makes white's five point.
+        let mut made = Board::start();
+        made.points[7] -= 1;
+        made.points[5] -= 1;
+        made.points[4] = 2;
+        assert!(board
+            .turns(Side::White, &[3, 1])
+            .iter()
+            .any(|(steps, board)| steps.len() == 2 && *board == made));
+    }
+
+    #[test]
+    fn bar_checkers_enter_first_and_hits_go_to_the_bar() {
+        let mut board = empty();
+        board.bar[Side::White.index()] = 1;
+        board.points[10] = 2;
+        board.points[21] = -1;
+        board.points[20] = -2;
+        let moves = board.legal_moves(Side::White, &[3, 4]);
+        // Entering with the 4 is blocked, so the 3 enters and hits.
+        assert_eq!(
+            moves,
+            vec![Move {
+                die: 3,
+                from: Spot::Bar,
+                to: Spot::Point(21),
+            }]
+        );
+        let board = board.apply(Side::White, moves[0]);
+        assert_eq!(board.bar, [0, 1]);
+        assert_eq!(board.count(Side::White, 21), 1);
+    }
+
+    #[test]
+    fn bearing_off_needs_every_checker_home() {
+        let mut board = empty();
+        board.points[2] = 2;
+        board.points[8] = 1;
+        assert!(board
+            .single_moves(Side::White, 3)
+            .iter()
+            .all(|step| step.to != Spot::Off));
+        board.points[8] = 0;
+        board.points[4] = 1;
+        let offs: Vec<Spot> = board
+            .single_moves(Side::White, 6)
+            .iter()
+            .filter(|step| step.to == Spot::Off)
+            .map(|step| step.from)
+            .collect();
+        // A 6 bears off from the farthest point only.
+        assert_eq!(offs, vec![Spot::Point(4)]);
+        board.points[4] = 0;
+        board.points[1] = -1;
+        board.off[0] = CHECKERS - 2;
+        let board = board.apply(Side::White, board.legal_moves(Side::White, &[3, 5])[0]);
+        let board = board.apply(Side::White, board.legal_moves(Side::White, &[5])[0]);
+        assert_eq!(board.winner(), Some(Side::White));
+        assert_eq!(
+            board.result(Side::White),
+            3,
+            "black never left white's home"
+        );
+    }
+
+    #[test]
+    fn the_larger_die_must_be_played_when_only_one_can() {
+        let mut board = empty();
+        board.points[10] = 1;
+        // Either die alone is fine, but not both: 10 -> 4 and 10 -> 8 each end blocked.
+        board.points[2] = -2;
+        board.points[0] = -2;
+        board.points[1] = -2;
+        let moves = board.legal_moves(Side::White, &[2, 6]);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].die, 6);
+    }
+
+    #[test]
+    fn cube_goes_to_the_taker() {
+        let mut cube = Cube::new();
+        assert!(cube.can_double(Side::White) && cube.can_double(Side::Black));
+        cube.take(Side::Black);
+        assert_eq!(cube.value, 2);
+        assert!(!cube.can_double(Side::White));
+        assert!(cube.can_double(Side::Black));
+    }
+}
@@ -0,0 +1,91 @@
+//! Hunt and target: fire on a checkerboard pattern until something is hit, then finish it off.
+
+use crate::board::{in_bounds, Board, Cell, Mark, SIZE};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const NEIGHBOURS: [Cell; 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+
+/// The next cell to fire at, only looking at the marks a player can see.
+pub fn choose(board: &Board, rng: &mut impl Rng) -> Cell {
+    let open = |cell: Cell| in_bounds(cell) && board.mark(cell).is_none();
+    let hits: Vec<Cell> = cells()
+        .filter(|cell| board.mark(*cell) == Some(Mark::Hit))
+        .collect();
+
+    // Target: extend a line of hits first, otherwise try around any hit.
+    let mut line = Vec::new();
+    let mut around = Vec::new();
+    for &(x, y) in hits.iter() {
+        for (dx, dy) in NEIGHBOURS {
+            let next = (x + dx, y + dy);
+            if !open(next) {
+                continue;
+            }
+            around.push(next);
+            if board.mark((x - dx, y - dy)) == Some(Mark::Hit) {
+                line.push(next);
+            }
+        }
+    }
+    if let Some(cell) = line.choose(rng).or_else(|| around.choose(rng)) {
+        return *cell;
+    }
+
+    // Hunt: every ship covers a cell of either color, so one color is enough to find them all.
+    let pattern: Vec<Cell> = cells()
+        .filter(|(x, y)| (x + y) % 2 == 0 && open((*x, *y)))
+        .collect();
+    let rest: Vec<Cell> = cells().filter(|cell| open(*cell)).collect();
+    *pattern
+        .choose(rng)
+        .or_else(|| rest.choose(rng))
+        .expect("the game ends before every cell is fired at")
+}
+
+fn cells() -> impl Iterator<Item = Cell> {
+    (0..SIZE).flat_map(|y| (0..SIZE).map(move |x| (x, y)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn a_hit_is_followed_up_along_the_ship() {
+        let mut rng = StdRng::seed_from_u64(2);
+        for _ in 0..20 {
+            let mut board = Board::random(&mut rng);
+            // Alone, so no other ship is hit on the way.
+            board.ships.truncate(1);
+            let ship = board.ships[0];
+            board.fire(ship.cells()[2]);
+            let mut shots = 0;
+            while !board.is_sunk(0) {
+                board.fire(choose(&board, &mut rng));
+                shots += 1;
+            }
+            // Four more cells, plus a few misses while finding the direction and the ends.
+            assert!(shots <= 4 + 4, "{} shots", shots);
+        }
+    }
+
+    #[test]
+    fn sinks_a_fleet_well_before_firing_everywhere() {
+        let mut rng = StdRng::seed_from_u64(9);
+        let mut total = 0;
+        for _ in 0..20 {
+            let mut board = Board::random(&mut rng);
+            let mut shots = 0;
+            while !board.all_sunk() {
+                let shot = board.fire(choose(&board, &mut rng));
+                assert!(shot.is_some(), "never fires at the same cell twice");
+                shots += 1;
+            }
+            total += shots;
+        }
+        assert!(total / 20 < 70, "{} shots on average", total / 20);
+    }
+}
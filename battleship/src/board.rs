@@ -0,0 +1,248 @@
+//! One player's waters: the fleet placed in them and the shots fired at it.
+
+use rand::Rng;
+
+pub const SIZE: i32 = 10;
+
+/// Column and row, counted from the top left.
+pub type Cell = (i32, i32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShipKind {
+    Carrier,
+    Battleship,
+    Cruiser,
+    Submarine,
+    Destroyer,
+}
+
+impl ShipKind {
+    /// A fleet has one ship of each kind.
+    pub const ALL: [Self; 5] = [
+        Self::Carrier,
+        Self::Battleship,
+        Self::Cruiser,
+        Self::Submarine,
+        Self::Destroyer,
+    ];
+
+    pub fn length(self) -> i32 {
+        match self {
+            Self::Carrier => 5,
+            Self::Battleship => 4,
+            Self::Cruiser | Self::Submarine => 3,
+            Self::Destroyer => 2,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Carrier => "Carrier",
+            Self::Battleship => "Battleship",
+            Self::Cruiser => "Cruiser",
+            Self::Submarine => "Submarine",
+            Self::Destroyer => "Destroyer",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ship {
+    pub horizontal: bool,
+    pub kind: ShipKind,
+    /// The top or left end.
+    pub origin: Cell,
+}
+
+impl Ship {
+    pub fn cells(&self) -> Vec<Cell> {
+        let (x, y) = self.origin;
+        (0..self.kind.length())
+            .map(|index| {
+                if self.horizontal {
+                    (x + index, y)
+                } else {
+                    (x, y + index)
+                }
+            })
+            .collect()
+    }
+
+    pub fn contains(&self, cell: Cell) -> bool {
+        self.cells().contains(&cell)
+    }
+}
+
+/// What is known about a cell that was fired at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Miss,
+    Hit,
+    /// Part of a ship that went down.
+    Sunk,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shot {
+    Miss,
+    Hit,
+    Sunk(ShipKind),
+}
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    marks: [[Option<Mark>; SIZE as usize]; SIZE as usize],
+    pub ships: Vec<Ship>,
+}
+
+impl Board {
+    /// The whole fleet at random spots.
+    pub fn random(rng: &mut impl Rng) -> Self {
+        let mut board = Self {
+            marks: Default::default(),
+            ships: Vec::new(),
+        };
+        for kind in ShipKind::ALL {
+            loop {
+                let ship = Ship {
+                    horizontal: rng.gen(),
+                    kind,
+                    origin: (rng.gen_range(0, SIZE), rng.gen_range(0, SIZE)),
+                };
+                if board.fits(&ship, None) {
+                    board.ships.push(ship);
+                    break;
+                }
+            }
+        }
+        board
+    }
+
+    pub fn all_sunk(&self) -> bool {
+        (0..self.ships.len()).all(|index| self.is_sunk(index))
+    }
+
+    /// Fires at `cell`; `None` if it is off the board or was fired at before.
+    pub fn fire(&mut self, cell: Cell) -> Option<Shot> {
+        if !in_bounds(cell) || self.mark(cell).is_some() {
+            return None;
+        }
+        let index = match self.ship_at(cell) {
+            Some(index) => index,
+            None => {
+                self.set_mark(cell, Mark::Miss);
+                return Some(Shot::Miss);
+            }
+        };
+        self.set_mark(cell, Mark::Hit);
+        if !self.is_sunk(index) {
+            return Some(Shot::Hit);
+        }
+        let ship = self.ships[index];
+        for cell in ship.cells() {
+            self.set_mark(cell, Mark::Sunk);
+        }
+        Some(Shot::Sunk(ship.kind))
+    }
+
+    /// Whether `ship` lies on the board without overlapping another ship; `ignore` is the ship being moved.
+    pub fn fits(&self, ship: &Ship, ignore: Option<usize>) -> bool {
+        ship.cells().into_iter().all(|cell| {
+            in_bounds(cell)
+                && self
+                    .ships
+                    .iter()
+                    .enumerate()
+                    .all(|(index, other)| Some(index) == ignore || !other.contains(cell))
+        })
+    }
+
+    pub fn is_sunk(&self, index: usize) -> bool {
+        self.ships[index]
+            .cells()
+            .into_iter()
+            .all(|cell| self.mark(cell).is_some())
+    }
+
+    pub fn mark(&self, cell: Cell) -> Option<Mark> {
+        if in_bounds(cell) {
+            self.marks[cell.1 as usize][cell.0 as usize]
+        } else {
+            None
+        }
+    }
+
+    pub fn ship_at(&self, cell: Cell) -> Option<usize> {
+        self.ships.iter().position(|ship| ship.contains(cell))
+    }
+
+    fn set_mark(&mut self, cell: Cell, mark: Mark) {
+        self.marks[cell.1 as usize][cell.0 as usize] = Some(mark);
+    }
+}
+
+pub fn in_bounds((x, y): Cell) -> bool {
+    (0..SIZE).contains(&x) && (0..SIZE).contains(&y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn random_fleets_fit_without_overlapping() {
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..50 {
+            let board = Board::random(&mut rng);
+            assert_eq!(board.ships.len(), ShipKind::ALL.len());
+            for (index, ship) in board.ships.iter().enumerate() {
+                assert!(board.fits(ship, Some(index)));
+            }
+        }
+    }
+
+    #[test]
+    fn ships_sink_once_every_cell_is_hit() {
+        let mut board = Board {
+            marks: Default::default(),
+            ships: vec![Ship {
+                horizontal: false,
+                kind: ShipKind::Destroyer,
+                origin: (3, 8),
+            }],
+        };
+        assert_eq!(board.fire((0, 0)), Some(Shot::Miss));
+        assert_eq!(board.fire((0, 0)), None);
+        assert_eq!(board.fire((3, 10)), None);
+        assert_eq!(board.fire((3, 9)), Some(Shot::Hit));
+        assert_eq!(board.mark((3, 9)), Some(Mark::Hit));
+        assert!(!board.all_sunk());
+        assert_eq!(board.fire((3, 8)), Some(Shot::Sunk(ShipKind::Destroyer)));
+        assert_eq!(board.mark((3, 9)), Some(Mark::Sunk));
+        assert!(board.all_sunk());
+    }
+
+    #[test]
+    fn ships_must_stay_in_the_waters_and_apart() {
+        let board = Board {
+            marks: Default::default(),
+            ships: vec![Ship {
+                horizontal: true,
+                kind: ShipKind::Carrier,
+                origin: (2, 2),
+            }],
+        };
+        let ship = |origin, horizontal| Ship {
+            horizontal,
+            kind: ShipKind::Cruiser,
+            origin,
+        };
+        assert!(board.fits(&ship((7, 0), true), None));
+        assert!(!board.fits(&ship((8, 0), true), None));
+        assert!(!board.fits(&ship((4, 0), false), None));
+        assert!(board.fits(&ship((4, 3), false), None), "touching is fine");
+        assert!(board.fits(&board.ships[0], Some(0)));
+    }
+}
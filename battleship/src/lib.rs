@@ -0,0 +1,679 @@
+use bevy::math::const_vec2;
+use bevy::prelude::*;
+use board::{Board, Cell, Mark, Ship, Shot, SIZE};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+
+pub mod ai;
+pub mod board;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.06, 0.1, 0.16);
+const CELL_SIZE: f32 = 34.0;
+/// Pause after a shot before the other side fires.
+const DELAY: f32 = 1.0;
+const GRID_CENTERS: [Vec2; 2] = [const_vec2!([-215.0, -30.0]), const_vec2!([215.0, -30.0])];
+const HIT_COLOR: Color = Color::rgb(0.9, 0.25, 0.15);
+const INVALID_COLOR: Color = Color::rgba(0.9, 0.2, 0.2, 0.6);
+const MISS_COLOR: Color = Color::rgb(0.85, 0.9, 0.95);
+const SHIP_COLOR: Color = Color::rgb(0.55, 0.58, 0.6);
+const SUNK_COLOR: Color = Color::rgb(0.45, 0.1, 0.08);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.93, 0.96);
+const VALID_COLOR: Color = Color::rgba(0.3, 0.85, 0.4, 0.6);
+const WATER_COLOR: Color = Color::rgb(0.12, 0.3, 0.5);
+
+pub const WINDOW_HEIGHT: f32 = 600.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+/// A column letter or row number next to one of the grids.
+#[derive(Component)]
+struct Axis(usize);
+
+#[derive(Component)]
+struct CenterText;
+
+/// Runs out when the other side may fire.
+struct Delay(Timer);
+
+/// The ship being dragged during placement.
+struct Drag {
+    /// Which cell of the ship was grabbed, counted from its origin.
+    grabbed: i32,
+    horizontal: bool,
+    ship: usize,
+}
+
+struct Game {
+    /// `boards[player]` holds that player's fleet and the shots fired at it.
+    boards: [Board; 2],
+    message: String,
+    mode: Mode,
+    /// The state to go on to after handing the screen over.
+    next: GameState,
+    /// The player placing ships or firing next.
+    turn: usize,
+    /// The shot was fired, the other side fires once the delay ran out.
+    waiting: bool,
+    winner: Option<usize>,
+}
+
+impl Game {
+    fn new(mode: Mode) -> Self {
+        let mut rng = thread_rng();
+        Self {
+            boards: [Board::random(&mut rng), Board::random(&mut rng)],
+            message: String::new(),
+            mode,
+            next: GameState::Placing,
+            turn: 0,
+            waiting: false,
+            winner: None,
+        }
+    }
+
+    fn name(&self, player: usize) -> String {
+        match (self.mode, player) {
+            (Mode::Computer, 0) => "You".to_string(),
+            (Mode::Computer, _) => "The computer".to_string(),
+            (Mode::HotSeat, _) => format!("Player {}", player + 1),
+        }
+    }
+
+    /// The player whose fleet is on the left, looking at the other player's waters on the right.
+    fn viewer(&self) -> usize {
+        match self.mode {
+            Mode::Computer => 0,
+            // Right after a shot the turn has passed, but the shooter still sees the result.
+            Mode::HotSeat if self.waiting => 1 - self.turn,
+            Mode::HotSeat => self.turn,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Placing,
+    /// In hot-seat games, the screen is blanked while the players swap seats.
+    Handover,
+    Firing,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+/// The title above one of the grids.
+#[derive(Component)]
+struct Label(usize);
+
+#[derive(Component)]
+struct MessageText;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Computer,
+    /// Two players taking turns at one screen.
+    HotSeat,
+}
+
+struct Sounds {
+    hit: Handle<AudioSource>,
+    miss: Handle<AudioSource>,
+    sunk: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct BattleshipPlugin;
+
+impl Plugin for BattleshipPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(Mode::Computer))
+            .insert_resource(Delay(Timer::from_seconds(DELAY, false)))
+            .insert_resource::<Option<Drag>>(None)
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_update(GameState::Placing).with_system(placing_input))
+            .add_system_set(SystemSet::on_update(GameState::Handover).with_system(handover_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Firing)
+                    .with_system(firing_input)
+                    .with_system(turn_update),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(board_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Battleship".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws both grids every frame: the own fleet on the left, the enemy waters on the right.
+fn board_render(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    drag: Res<Option<Drag>>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    let drag: &Option<Drag> = &drag;
+    let (own, target) = match state.current() {
+        GameState::Menu | GameState::Handover => return,
+        GameState::Placing => (&game.boards[game.turn], None),
+        _ => {
+            let viewer = game.viewer();
+            (&game.boards[viewer], Some(&game.boards[1 - viewer]))
+        }
+    };
+    let over = *state.current() == GameState::Over;
+    for (grid, board) in [(0, Some(own)), (1, target)] {
+        let board = match board {
+            Some(board) => board,
+            None => continue,
+        };
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let center = cell_center(grid, (x, y));
+                commands
+                    .spawn_bundle(square(WATER_COLOR, CELL_SIZE - 2.0, center, 0.0))
+                    .insert(Actor);
+            }
+        }
+        // The own fleet is always shown, the enemy's only once the game is over.
+        let dragged = drag.as_ref().map(|drag| drag.ship);
+        for (index, ship) in board.ships.iter().enumerate() {
+            if (grid == 1 && !over) || (grid == 0 && Some(index) == dragged) {
+                continue;
+            }
+            for cell in ship.cells() {
+                commands
+                    .spawn_bundle(square(
+                        SHIP_COLOR,
+                        CELL_SIZE - 6.0,
+                        cell_center(grid, cell),
+                        1.0,
+                    ))
+                    .insert(Actor);
+            }
+        }
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let center = cell_center(grid, (x, y));
+                let (color, size) = match board.mark((x, y)) {
+                    Some(Mark::Miss) => (MISS_COLOR, 8.0),
+                    Some(Mark::Hit) => (HIT_COLOR, 16.0),
+                    Some(Mark::Sunk) => (SUNK_COLOR, CELL_SIZE - 6.0),
+                    None => continue,
+                };
+                commands
+                    .spawn_bundle(square(color, size, center, 2.0))
+                    .insert(Actor);
+            }
+        }
+    }
+
+    if let (Some(drag), Some(cursor)) = (drag.as_ref(), game_hud::cursor_world_position(&windows)) {
+        let ship = dragged_ship(own, drag, cursor);
+        let color = if own.fits(&ship, Some(drag.ship)) {
+            VALID_COLOR
+        } else {
+            INVALID_COLOR
+        };
+        for cell in ship.cells() {
+            commands
+                .spawn_bundle(square(color, CELL_SIZE - 6.0, cell_center(0, cell), 3.0))
+                .insert(Actor);
+        }
+    }
+}
+
+fn cell_at(grid: usize, position: Vec2) -> Option<Cell> {
+    let offset = (position - GRID_CENTERS[grid]) / CELL_SIZE + Vec2::splat(SIZE as f32 / 2.0);
+    let cell = (offset.x.floor() as i32, SIZE - 1 - offset.y.floor() as i32);
+    if board::in_bounds(cell) {
+        Some(cell)
+    } else {
+        None
+    }
+}
+
+fn cell_center(grid: usize, (x, y): Cell) -> Vec2 {
+    let half = (SIZE - 1) as f32 / 2.0;
+    GRID_CENTERS[grid] + Vec2::new(x as f32 - half, half - y as f32) * CELL_SIZE
+}
+
+/// Column letter and row number, like `C7`.
+fn cell_name((x, y): Cell) -> String {
+    format!("{}{}", (b'A' + x as u8) as char, y + 1)
+}
+
+/// Where the dragged ship would land, keeping the grabbed cell under the cursor.
+fn dragged_ship(board: &Board, drag: &Drag, cursor: Vec2) -> Ship {
+    let ship = board.ships[drag.ship];
+    let half = (SIZE - 1) as f32 / 2.0;
+    let offset = (cursor - GRID_CENTERS[0]) / CELL_SIZE;
+    let (x, y) = (
+        (offset.x + half).round() as i32,
+        (half - offset.y).round() as i32,
+    );
+    Ship {
+        horizontal: drag.horizontal,
+        origin: if drag.horizontal {
+            (x - drag.grabbed, y)
+        } else {
+            (x, y - drag.grabbed)
+        },
+        ..ship
+    }
+}
+
+/// Clicking a cell of the enemy waters fires at it.
+fn firing_input(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut delay: ResMut<Delay>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.waiting || (game.mode == Mode::Computer && game.turn == 1) {
+        return;
+    }
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let cell = match game_hud::cursor_world_position(&windows).and_then(|cursor| cell_at(1, cursor))
+    {
+        Some(cell) => cell,
+        None => return,
+    };
+    let shooter = game.turn;
+    if fire(&mut game, shooter, cell, &audio, &sounds) {
+        state.set(GameState::Over).unwrap();
+        return;
+    }
+    game.turn = 1 - shooter;
+    game.waiting = true;
+    delay.0.reset();
+}
+
+/// Fires at the other player's waters; returns whether that sank the last ship.
+fn fire(game: &mut Game, shooter: usize, cell: Cell, audio: &Audio, sounds: &Sounds) -> bool {
+    let shot = match game.boards[1 - shooter].fire(cell) {
+        Some(shot) => shot,
+        None => return false,
+    };
+    let name = game.name(shooter);
+    let (result, sound) = match shot {
+        Shot::Miss => ("miss".to_string(), &sounds.miss),
+        Shot::Hit => ("hit!".to_string(), &sounds.hit),
+        Shot::Sunk(kind) => (format!("hit and sunk the {}!", kind.name()), &sounds.sunk),
+    };
+    audio.play(sound.clone());
+    game.message = format!("{} fired at {}: {}", name, cell_name(cell), result);
+    if game.boards[1 - shooter].all_sunk() {
+        game.winner = Some(shooter);
+        game.waiting = false;
+        audio.play(sounds.won.clone());
+        return true;
+    }
+    false
+}
+
+fn handover_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    game: Res<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        keyboard_input.clear_just_pressed(KeyCode::Space);
+        state.set(game.next).unwrap();
+    }
+}
+
+/// Up/Down picks the opponent, Enter starts placing ships.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.vertical() != 0 {
+        game.mode = match game.mode {
+            Mode::Computer => Mode::HotSeat,
+            Mode::HotSeat => Mode::Computer,
+        };
+    }
+    if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(game.mode);
+        let next = match game.mode {
+            Mode::Computer => GameState::Placing,
+            Mode::HotSeat => GameState::Handover,
+        };
+        state.set(next).unwrap();
+    }
+}
+
+fn over_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Ships are dragged with the mouse and rotated with R or the right button; Enter confirms the fleet.
+fn placing_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut drag: ResMut<Option<Drag>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let cursor = game_hud::cursor_world_position(&windows);
+    let turn = game.turn;
+    let board = &mut game.boards[turn];
+    let rotate =
+        keyboard_input.just_pressed(KeyCode::R) || mouse_input.just_pressed(MouseButton::Right);
+
+    if let Some(current) = drag.as_mut() {
+        if rotate {
+            current.horizontal = !current.horizontal;
+        }
+        if mouse_input.just_released(MouseButton::Left) {
+            if let Some(cursor) = cursor {
+                let ship = dragged_ship(board, current, cursor);
+                if board.fits(&ship, Some(current.ship)) {
+                    board.ships[current.ship] = ship;
+                }
+            }
+            *drag = None;
+        }
+        return;
+    }
+
+    let hovered = cursor
+        .and_then(|cursor| cell_at(0, cursor))
+        .and_then(|cell| board.ship_at(cell).map(|index| (cell, index)));
+    if let Some((cell, index)) = hovered {
+        let ship = board.ships[index];
+        if mouse_input.just_pressed(MouseButton::Left) {
+            *drag = Some(Drag {
+                grabbed: (cell.0 - ship.origin.0) + (cell.1 - ship.origin.1),
+                horizontal: ship.horizontal,
+                ship: index,
+            });
+        } else if rotate {
+            let rotated = Ship {
+                horizontal: !ship.horizontal,
+                ..ship
+            };
+            if board.fits(&rotated, Some(index)) {
+                board.ships[index] = rotated;
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        *board = Board::random(&mut thread_rng());
+    }
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let next = match (game.mode, turn) {
+            (Mode::HotSeat, 0) => {
+                game.turn = 1;
+                game.next = GameState::Placing;
+                GameState::Handover
+            }
+            (Mode::HotSeat, _) => {
+                game.turn = 0;
+                game.next = GameState::Firing;
+                GameState::Handover
+            }
+            (Mode::Computer, _) => GameState::Firing,
+        };
+        state.set(next).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 225.0, 10.0),
+        ))
+        .insert(MessageText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(CenterText);
+    for (grid, center) in GRID_CENTERS.iter().enumerate() {
+        let top = cell_center(grid, (0, 0)) + Vec2::new(0.0, CELL_SIZE);
+        let left = cell_center(grid, (0, 0)) - Vec2::new(CELL_SIZE, 0.0);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                "",
+                20.0,
+                TEXT_COLOR,
+                Vec3::new(center.x, top.y + 26.0, 10.0),
+            ))
+            .insert(Label(grid));
+        for index in 0..SIZE {
+            let step = index as f32 * CELL_SIZE;
+            let axes = [
+                (
+                    cell_name((index, 0))[..1].to_string(),
+                    top + Vec2::new(step, 0.0),
+                ),
+                ((index + 1).to_string(), left - Vec2::new(0.0, step)),
+            ];
+            for (value, position) in axes {
+                commands
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        value,
+                        14.0,
+                        TEXT_COLOR,
+                        position.extend(10.0),
+                    ))
+                    .insert(Axis(grid));
+            }
+        }
+    }
+
+    commands.insert_resource(Sounds {
+        hit: audio_sources.add(
+            Tone::new(Waveform::Noise, 120.0, 0.35)
+                .fade()
+                .volume(0.5)
+                .into(),
+        ),
+        miss: audio_sources.add(
+            Tone::new(Waveform::Sine, 500.0, 0.2)
+                .slide(200.0)
+                .fade()
+                .volume(0.25)
+                .into(),
+        ),
+        sunk: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 100.0, 0.3).volume(0.5),
+            Tone::new(Waveform::Square, 110.0, 0.5)
+                .slide(55.0)
+                .fade()
+                .volume(0.3),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.12),
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.4),
+        ])),
+    });
+}
+
+fn square(color: Color, size: f32, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(size)),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn texts_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+        QueryState<&mut Text, With<CenterText>>,
+        QueryState<(&Label, &mut Text)>,
+    )>,
+    mut axes: Query<(&Axis, &mut Visibility)>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    let current = *state.current();
+    let hud = match (game.mode, current) {
+        (_, GameState::Menu) => String::new(),
+        (Mode::Computer, _) => "AGAINST THE COMPUTER".to_string(),
+        (Mode::HotSeat, _) => "TWO PLAYERS".to_string(),
+    };
+    let message = match current {
+        GameState::Placing => format!("{}: place your fleet", game.name(game.turn)),
+        GameState::Firing if game.message.is_empty() => {
+            "Click the enemy waters to fire".to_string()
+        }
+        GameState::Over => {
+            let winner = game.name(game.winner.unwrap_or(0));
+            format!("{}\n{} won!   Enter: menu", game.message, winner)
+        }
+        _ => game.message.clone(),
+    };
+    let center = match current {
+        GameState::Menu => {
+            let marker = |mode| if game.mode == mode { ">" } else { " " };
+            format!(
+                "BATTLESHIP\n\n{} Play against the computer\n{} Two players at one screen\n\n\
+                 Up/Down: opponent   Enter: start",
+                marker(Mode::Computer),
+                marker(Mode::HotSeat)
+            )
+        }
+        GameState::Handover => format!(
+            "Pass the screen to {}\n\nEnter: continue",
+            game.name(game.turn)
+        ),
+        _ => String::new(),
+    };
+    let labels = match current {
+        GameState::Placing => [
+            "Your fleet".to_string(),
+            "Drag: move   R / right click: rotate\nSpace: shuffle   Enter: done".to_string(),
+        ],
+        GameState::Firing | GameState::Over => [
+            format!("{}'s fleet", game.name(game.viewer())),
+            format!("{}'s waters", game.name(1 - game.viewer())),
+        ],
+        _ => [String::new(), String::new()],
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = center.clone();
+    }
+    for (label, mut text) in texts.q3().iter_mut() {
+        text.sections[0].value = labels[label.0].clone();
+    }
+    for (axis, mut visibility) in axes.iter_mut() {
+        visibility.is_visible = match current {
+            GameState::Menu | GameState::Handover => false,
+            GameState::Placing => axis.0 == 0,
+            _ => true,
+        };
+    }
+}
+
+/// Once the delay after a shot ran out, the computer fires back or the screen is handed over.
+fn turn_update(
+    time: Res<Time>,
+    mut delay: ResMut<Delay>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.waiting || !delay.0.tick(time.delta()).finished() {
+        return;
+    }
+    game.waiting = false;
+    match game.mode {
+        Mode::Computer => {
+            let cell = ai::choose(&game.boards[0], &mut thread_rng());
+            if fire(&mut game, 1, cell, &audio, &sounds) {
+                state.set(GameState::Over).unwrap();
+            } else {
+                game.turn = 0;
+            }
+        }
+        Mode::HotSeat => {
+            game.next = GameState::Firing;
+            state.set(GameState::Handover).unwrap();
+        }
+    }
+}
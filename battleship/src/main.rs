@@ -0,0 +1,14 @@
+use battleship::BattleshipPlugin;
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(battleship::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(BattleshipPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
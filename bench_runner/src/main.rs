@@ -0,0 +1,310 @@
+use game_test::TestApp;
+use report::{GameReport, Report};
+use scenario::{Scenario, Step};
+use stats::Frames;
+use std::{
+    env, fs,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+    process, thread,
+    time::{Duration, Instant},
+};
+
+mod alloc;
+mod report;
+mod scenario;
+mod stats;
+
+#[global_allocator]
+static ALLOCATOR: alloc::Counting = alloc::Counting;
+
+/// Growth of a measure past a baseline, in percent, counted as a regression by default.
+const DEFAULT_THRESHOLD: f64 = 20.0;
+const USAGE: &str = "Usage: bench-runner [game...] [--frames N] [--out DIR] [--baseline REPORT.json] [--threshold PERCENT]";
+
+/// The games the runner can play, by name; each is compiled in only with its feature.
+macro_rules! games {
+    ($($name:literal => $game:ident::$plugin:ident,)*) => {
+        /// The names of the games compiled in.
+        fn names() -> Vec<&'static str> {
+            [$(($name, cfg!(feature = $name))),*]
+                .into_iter()
+                .filter(|(_, compiled)| *compiled)
+                .map(|(name, _)| name)
+                .collect()
+        }
+
+        /// The game with the name, built headless; `None` if it is not compiled in.
+        fn build(name: &str) -> Option<TestApp> {
+            match name {
+                $(
+                    #[cfg(feature = $name)]
+                    $name => Some(TestApp::new($game::window_descriptor(), $game::$plugin)),
+                )*
+                _ => None,
+            }
+        }
+    };
+}
+
+games! {
+    "airhockey" => airhockey::AirHockeyPlugin,
+    "asteroids" => asteroids::AsteroidsPlugin,
+    "backgammon" => backgammon::BackgammonPlugin,
+    "battleship" => battleship::BattleshipPlugin,
+    "blackjack" => blackjack::BlackjackPlugin,
+    "bomber" => bomber::BomberPlugin,
+    "centipede" => centipede::CentipedePlugin,
+    "checkers" => checkers::CheckersPlugin,
+    "chess" => chess::ChessPlugin,
+    "digger" => digger::DiggerPlugin,
+    "fifteen" => fifteen::FifteenPlugin,
+    "frogger" => frogger::FroggerPlugin,
+    "galaga" => galaga::GalagaPlugin,
+    "hangman" => hangman::HangmanPlugin,
+    "invaders" => invaders::InvadersPlugin,
+    "lander" => lander::LanderPlugin,
+    "lemmings" => lemmings::LemmingsPlugin,
+    "lightcycles" => lightcycles::LightCyclesPlugin,
+    "lightsout" => lightsout::LightsOutPlugin,
+    "mahjong" => mahjong::MahjongPlugin,
+    "mastermind" => mastermind::MastermindPlugin,
+    "match3" => match3::Match3Plugin,
+    "memory" => memory::MemoryPlugin,
+    "missilecommand" => missilecommand::MissileCommandPlugin,
+    "pacman" => pacman::PacmanPlugin,
+    "picross" => picross::PicrossPlugin,
+    "pinball" => pinball::PinballPlugin,
+    "pipes" => pipes::PipesPlugin,
+    "platformer" => platformer::PlatformerPlugin,
+    "qix" => qix::QixPlugin,
+    "racer" => racer::RacerPlugin,
+    "reversi" => reversi::ReversiPlugin,
+    "rogue" => rogue::RoguePlugin,
+    "rps" => rps::RpsPlugin,
+    "simon" => simon::SimonPlugin,
+    "snake" => snake::SnakePlugin,
+    "snakesladders" => snakesladders::SnakesLaddersPlugin,
+    "solitaire" => solitaire::SolitairePlugin,
+    "sudoku" => sudoku::SudokuPlugin,
+    "textadventure" => textadventure::TextAdventurePlugin,
+    "towerdefense" => towerdefense::TowerDefensePlugin,
+    "typing" => typing::TypingPlugin,
+    "videopoker" => videopoker::VideoPokerPlugin,
+    "whack" => whack::WhackPlugin,
+    "wordle" => wordle::WordlePlugin,
+    "yahtzee" => yahtzee::YahtzeePlugin,
+}
+
+/// What to run and where to write, from the command line.
+struct Options {
+    baseline: Option<PathBuf>,
+    /// Frames every scenario is scaled to, for quick runs.
+    frames: Option<u32>,
+    games: Vec<String>,
+    out: PathBuf,
+    threshold: f64,
+}
+
+impl Options {
+    fn parse() -> Result<Self, String> {
+        let mut options = Options {
+            baseline: None,
+            frames: None,
+            games: Vec::new(),
+            out: PathBuf::from("target/bench"),
+            threshold: DEFAULT_THRESHOLD,
+        };
+        let mut args = env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--baseline" => options.baseline = Some(PathBuf::from(value()?)),
+                "--frames" => {
+                    let frames = value()?;
+                    options.frames = Some(
+                        frames
+                            .parse()
+                            .map_err(|_| format!("{} is no frame count", frames))?,
+                    );
+                }
+                "--out" => options.out = PathBuf::from(value()?),
+                "--threshold" => {
+                    let threshold = value()?;
+                    options.threshold = threshold
+                        .parse()
+                        .map_err(|_| format!("{} is no percentage", threshold))?;
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option {}", arg)),
+                _ => options.games.push(arg),
+            }
+        }
+        let names = names();
+        for game in &options.games {
+            if !names.contains(&game.as_str()) {
+                return Err(format!(
+                    "No game {}; the games are: {}",
+                    game,
+                    names.join(", ")
+                ));
+            }
+        }
+        if options.games.is_empty() {
+            options.games = names.into_iter().map(String::from).collect();
+        }
+        Ok(options)
+    }
+}
+
+/// Plays `scenario` in `name`, measuring every frame the scenario asks for. Frames are paced
+/// to the scenario's rate, and the wait between them is not measured.
+fn bench(name: &str, scenario: &Scenario) -> GameReport {
+    let before = alloc::Count::now();
+    let started = Instant::now();
+    let mut app = build(name).expect("the game is compiled in");
+    app.step();
+    let setup_micros = started.elapsed().as_micros() as u64;
+    let setup = alloc::Count::now().since(before);
+
+    let interval = Duration::from_secs_f32(1.0 / scenario.rate);
+    let mut frames = Frames::default();
+    let mut allocated = alloc::Count::default();
+    let mut last = Instant::now();
+    let mut frame = |app: &mut TestApp| {
+        let next = last + interval;
+        let now = Instant::now();
+        if next > now {
+            thread::sleep(next - now);
+        }
+        let before = alloc::Count::now();
+        let started = Instant::now();
+        app.step();
+        last = Instant::now();
+        frames.record(last - started);
+        let count = alloc::Count::now().since(before);
+        allocated.allocations += count.allocations;
+        allocated.bytes += count.bytes;
+    };
+    for step in &scenario.steps {
+        match step {
+            Step::Frames(count) => {
+                for _ in 0..*count {
+                    frame(&mut app);
+                }
+            }
+            Step::Press(key) => app.press(*key),
+            Step::Release(key) => app.release(*key),
+            Step::Tap(key) => {
+                app.press(*key);
+                frame(&mut app);
+                app.release(*key);
+            }
+        }
+    }
+
+    let frame_micros = frames.summary();
+    let measured = frame_micros.frames.max(1);
+    GameReport {
+        allocations_per_frame: allocated.allocations / measured,
+        bytes_per_frame: allocated.bytes / measured,
+        frame_micros,
+        setup_allocations: setup.allocations,
+        setup_micros,
+    }
+}
+
+fn read_baseline(path: &PathBuf) -> Result<Report, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|error| format!("could not read {}: {}", path.display(), error))?;
+    serde_json::from_str(&contents)
+        .map_err(|error| format!("could not read {}: {}", path.display(), error))
+}
+
+/// `bench-runner` plays every game compiled in through its scenario and writes `report.json`
+/// and `report.html`; with `--baseline` it exits with 1 if a game regressed.
+fn main() {
+    let options = Options::parse().unwrap_or_else(|error| {
+        eprintln!("{}\n{}", error, USAGE);
+        process::exit(2);
+    });
+    let scenarios = scenario::parse(include_str!("../scenarios.ron"))
+        .expect("scenarios.ron holds a scenario per game name");
+    let baseline = options.baseline.as_ref().map(|path| {
+        read_baseline(path).unwrap_or_else(|error| {
+            eprintln!("{}", error);
+            process::exit(2);
+        })
+    });
+
+    let mut report = Report::default();
+    for game in &options.games {
+        let mut scenario = scenarios.get(game).cloned().unwrap_or_default();
+        if let Some(frames) = options.frames {
+            scenario = scenario.scaled(frames);
+        }
+        eprint!("{}: {} frames... ", game, scenario.frames());
+        match panic::catch_unwind(AssertUnwindSafe(|| bench(game, &scenario))) {
+            Ok(result) => {
+                eprintln!(
+                    "p95 {:.2}ms, {} allocations per frame",
+                    result.frame_micros.p95 as f64 / 1_000.0,
+                    result.allocations_per_frame
+                );
+                report.games.insert(game.clone(), result);
+            }
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| {
+                        panic
+                            .downcast_ref::<&str>()
+                            .map(|message| message.to_string())
+                    })
+                    .unwrap_or_else(|| "panicked".to_string());
+                eprintln!("failed");
+                report.failed.insert(game.clone(), message);
+            }
+        }
+    }
+
+    let regressions = baseline
+        .map(|baseline| report::regressions(&baseline, &report, options.threshold))
+        .unwrap_or_default();
+    let written = fs::create_dir_all(&options.out)
+        .and_then(|_| {
+            fs::write(
+                options.out.join("report.json"),
+                serde_json::to_string_pretty(&report).expect("reports serialize"),
+            )
+        })
+        .and_then(|_| {
+            fs::write(
+                options.out.join("report.html"),
+                report::html(&report, &regressions),
+            )
+        });
+    if let Err(error) = written {
+        eprintln!(
+            "could not write the report to {}: {}",
+            options.out.display(),
+            error
+        );
+        process::exit(2);
+    }
+    eprintln!("Report written to {}", options.out.display());
+
+    for regression in &regressions {
+        eprintln!(
+            "{} regressed: {} {} -> {} (+{:.0}%)",
+            regression.game,
+            regression.measure,
+            regression.baseline,
+            regression.current,
+            regression.percent()
+        );
+    }
+    if !regressions.is_empty() {
+        process::exit(1);
+    }
+}
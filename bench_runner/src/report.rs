@@ -0,0 +1,222 @@
+//! The results of a run as JSON to keep and compare, and as an HTML page to read.
+
+use crate::stats::{Summary, BUCKETS};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How one game ran.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct GameReport {
+    pub allocations_per_frame: u64,
+    pub bytes_per_frame: u64,
+    pub frame_micros: Summary,
+    /// Allocations while the game's plugin was built and its startup systems ran.
+    pub setup_allocations: u64,
+    pub setup_micros: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Report {
+    /// Why a game could not be run, by name.
+    pub failed: BTreeMap<String, String>,
+    pub games: BTreeMap<String, GameReport>,
+}
+
+/// A game that got slower, or allocates more, than in the baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub game: String,
+    pub measure: &'static str,
+    pub baseline: u64,
+    pub current: u64,
+}
+
+impl Regression {
+    /// How much worse it got, in percent.
+    pub fn percent(&self) -> f64 {
+        (self.current as f64 / self.baseline.max(1) as f64 - 1.0) * 100.0
+    }
+}
+
+/// Measures that grew by more than `threshold` percent from `baseline` to `current`, for games
+/// in both. Times below a millisecond are too noisy to count.
+pub fn regressions(baseline: &Report, current: &Report, threshold: f64) -> Vec<Regression> {
+    let mut regressions = Vec::new();
+    for (game, now) in &current.games {
+        let before = match baseline.games.get(game) {
+            Some(before) => before,
+            None => continue,
+        };
+        let measures = [
+            ("p95", before.frame_micros.p95, now.frame_micros.p95, 1_000),
+            (
+                "mean",
+                before.frame_micros.mean,
+                now.frame_micros.mean,
+                1_000,
+            ),
+            (
+                "allocations per frame",
+                before.allocations_per_frame,
+                now.allocations_per_frame,
+                0,
+            ),
+        ];
+        for (measure, baseline, current, floor) in measures {
+            let regression = Regression {
+                game: game.clone(),
+                measure,
+                baseline,
+                current,
+            };
+            if current > floor && regression.percent() > threshold {
+                regressions.push(regression);
+            }
+        }
+    }
+    regressions
+}
+
+fn bucket_label(index: usize) -> String {
+    let micros = |micros: u64| {
+        if micros < 1_000 {
+            format!("{}µs", micros)
+        } else {
+            format!("{:.1}ms", micros as f64 / 1_000.0)
+        }
+    };
+    match BUCKETS.get(index) {
+        Some(bound) => format!("≤{}", micros(*bound)),
+        None => format!(">{}", micros(BUCKETS[BUCKETS.len() - 1])),
+    }
+}
+
+/// A page with a row per game: its frame times, a histogram of them and its allocations, with
+/// the games that regressed against a baseline marked.
+pub fn html(report: &Report, regressions: &[Regression]) -> String {
+    let mut rows = String::new();
+    for (game, result) in &report.games {
+        let frames = &result.frame_micros;
+        let most = frames.histogram.iter().copied().max().unwrap_or(0).max(1);
+        let bars: String = frames
+            .histogram
+            .iter()
+            .enumerate()
+            .map(|(index, count)| {
+                format!(
+                    "<span class=\"bar\" style=\"height: {}px\" title=\"{}: {} frames\"></span>",
+                    count * 40 / most,
+                    bucket_label(index),
+                    count
+                )
+            })
+            .collect();
+        let regressed: Vec<String> = regressions
+            .iter()
+            .filter(|regression| regression.game == *game)
+            .map(|regression| format!("{} +{:.0}%", regression.measure, regression.percent()))
+            .collect();
+        rows.push_str(&format!(
+            "<tr{}><td>{}</td><td>{}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td>{:.2}</td><td class=\"histogram\">{}</td><td>{}</td><td>{}</td><td>{:.1}</td><td>{}</td></tr>\n",
+            if regressed.is_empty() { "" } else { " class=\"regressed\"" },
+            game,
+            frames.frames,
+            frames.mean as f64 / 1_000.0,
+            frames.p50 as f64 / 1_000.0,
+            frames.p95 as f64 / 1_000.0,
+            frames.p99 as f64 / 1_000.0,
+            frames.max as f64 / 1_000.0,
+            bars,
+            result.allocations_per_frame,
+            result.bytes_per_frame,
+            result.setup_micros as f64 / 1_000.0,
+            regressed.join(", "),
+        ));
+    }
+    for (game, error) in &report.failed {
+        rows.push_str(&format!(
+            "<tr class=\"failed\"><td>{}</td><td colspan=\"11\">{}</td></tr>\n",
+            game,
+            escape(error)
+        ));
+    }
+    format!(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Rust Games benchmark</title>
+<style>
+body {{ font-family: sans-serif; }}
+td, th {{ padding: 2px 8px; text-align: right; }}
+td:first-child {{ text-align: left; }}
+.histogram {{ display: flex; align-items: flex-end; height: 40px; gap: 1px; }}
+.bar {{ display: inline-block; width: 6px; background: #48c; }}
+.regressed {{ background: #fdd; }}
+.failed {{ color: #a00; }}
+</style>
+</head>
+<body>
+<h1>Rust Games benchmark</h1>
+<table>
+<tr><th>Game</th><th>Frames</th><th>Mean ms</th><th>p50 ms</th><th>p95 ms</th><th>p99 ms</th><th>Max ms</th><th>Frame times</th><th>Allocations/frame</th><th>Bytes/frame</th><th>Setup ms</th><th>Regressed</th></tr>
+{}</table>
+</body>
+</html>
+",
+        rows
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(p95: u64, allocations_per_frame: u64) -> Report {
+        let game = GameReport {
+            allocations_per_frame,
+            frame_micros: Summary {
+                frames: 10,
+                histogram: vec![10],
+                mean: 2_000,
+                p95,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        Report {
+            games: BTreeMap::from([("snake".to_string(), game)]),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn finds_regressions_past_the_threshold() {
+        let baseline = report(4_000, 100);
+        assert!(regressions(&baseline, &report(4_400, 100), 20.0).is_empty());
+        let slower = regressions(&baseline, &report(6_000, 130), 20.0);
+        assert_eq!(
+            slower
+                .iter()
+                .map(|regression| (regression.measure, regression.percent().round()))
+                .collect::<Vec<_>>(),
+            [("p95", 50.0), ("allocations per frame", 30.0)]
+        );
+        // Sub-millisecond frames jitter too much to compare.
+        assert!(regressions(&report(300, 100), &report(900, 100), 20.0).is_empty());
+    }
+
+    #[test]
+    fn reads_back_its_json() {
+        let report = report(4_000, 100);
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+        assert!(html(&report, &[]).contains("<td>snake</td>"));
+    }
+}
@@ -0,0 +1,95 @@
+//! What the runner plays in each game, read from `scenarios.ron`.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// One thing a scenario does, in order.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Step {
+    /// Runs frames, each measured.
+    Frames(u32),
+    /// Holds a key down from the next frame on.
+    Press(KeyCode),
+    Release(KeyCode),
+    /// Presses a key for a single measured frame.
+    Tap(KeyCode),
+}
+
+/// Steps played in a game, at `rate` frames per second of real time so fixed timesteps run as
+/// they would on screen.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Scenario {
+    pub rate: f32,
+    pub steps: Vec<Step>,
+}
+
+impl Default for Scenario {
+    /// Starts the game from its menu and lets it run for five seconds.
+    fn default() -> Self {
+        Self {
+            rate: 60.0,
+            steps: vec![Step::Tap(KeyCode::Return), Step::Frames(300)],
+        }
+    }
+}
+
+impl Scenario {
+    /// Frames the scenario measures.
+    pub fn frames(&self) -> u32 {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                Step::Frames(frames) => *frames,
+                Step::Tap(_) => 1,
+                Step::Press(_) | Step::Release(_) => 0,
+            })
+            .sum()
+    }
+
+    /// The same steps with every run of frames scaled to `frames` in all, for quick runs.
+    pub fn scaled(&self, frames: u32) -> Self {
+        let runs: u32 = self
+            .steps
+            .iter()
+            .map(|step| match step {
+                Step::Frames(frames) => *frames,
+                _ => 0,
+            })
+            .sum();
+        let mut scenario = self.clone();
+        if runs == 0 {
+            return scenario;
+        }
+        for step in &mut scenario.steps {
+            if let Step::Frames(run) = step {
+                *run = (u64::from(*run) * u64::from(frames) / u64::from(runs)).max(1) as u32;
+            }
+        }
+        scenario
+    }
+}
+
+/// The scenario of every game named in the file; others play `Scenario::default()`.
+pub fn parse(text: &str) -> Result<BTreeMap<String, Scenario>, ron::Error> {
+    ron::from_str(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_scenarios() {
+        let scenarios = parse(
+            "{\"snake\": (steps: [Press(Right), Frames(90), Release(Right), Tap(Up), Frames(30)])}",
+        )
+        .unwrap();
+        let snake = &scenarios["snake"];
+        assert_eq!(snake.rate, 60.0);
+        assert_eq!(snake.frames(), 121);
+        assert_eq!(snake.scaled(12).frames(), 13);
+        assert_eq!(Scenario::default().frames(), 301);
+    }
+}
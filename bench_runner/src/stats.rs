@@ -0,0 +1,85 @@
+//! Frame times of a run, as a histogram and the percentiles reports compare.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Upper bounds of the histogram's buckets, in microseconds; the last bucket has none.
+pub const BUCKETS: [u64; 9] = [250, 500, 1_000, 2_000, 4_000, 8_000, 16_667, 33_333, 66_667];
+
+/// The frame times of a run, in microseconds.
+#[derive(Debug, Default)]
+pub struct Frames(Vec<u64>);
+
+impl Frames {
+    pub fn record(&mut self, duration: Duration) {
+        self.0.push(duration.as_micros() as u64);
+    }
+
+    pub fn summary(&self) -> Summary {
+        let mut sorted = self.0.clone();
+        sorted.sort_unstable();
+        let percentile = |percent: usize| {
+            if sorted.is_empty() {
+                0
+            } else {
+                sorted[((sorted.len() - 1) * percent + 50) / 100]
+            }
+        };
+        let mut histogram = vec![0; BUCKETS.len() + 1];
+        for time in &sorted {
+            let bucket = BUCKETS
+                .iter()
+                .position(|bound| time <= bound)
+                .unwrap_or(BUCKETS.len());
+            histogram[bucket] += 1;
+        }
+        Summary {
+            frames: sorted.len() as u64,
+            histogram,
+            max: sorted.last().copied().unwrap_or(0),
+            mean: if sorted.is_empty() {
+                0
+            } else {
+                sorted.iter().sum::<u64>() / sorted.len() as u64
+            },
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
+
+/// Frame times in microseconds.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Summary {
+    pub frames: u64,
+    /// Frames per bucket of `BUCKETS`, and those slower than the last bound.
+    pub histogram: Vec<u64>,
+    pub max: u64,
+    pub mean: u64,
+    pub p50: u64,
+    pub p95: u64,
+    pub p99: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn summarizes_frame_times() {
+        let mut frames = Frames::default();
+        for micros in 1..=100 {
+            frames.record(Duration::from_micros(micros * 100));
+        }
+        let summary = frames.summary();
+        assert_eq!(summary.frames, 100);
+        assert_eq!(
+            (summary.p50, summary.p95, summary.max),
+            (5_100, 9_500, 10_000)
+        );
+        assert_eq!(summary.mean, 5_050);
+        assert_eq!(summary.histogram, [2, 3, 5, 10, 20, 40, 20, 0, 0, 0]);
+        assert_eq!(Frames::default().summary().p99, 0);
+    }
+}
@@ -0,0 +1,744 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_cards::Card;
+use game_hud::{HudFont, HudPlugin};
+use rand::thread_rng;
+use rules::{Hand, Outcome, Shoe, MAX_HANDS};
+use serde::{Deserialize, Serialize};
+use strategy::Action;
+
+pub mod rules;
+pub mod strategy;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.04, 0.32, 0.16);
+const BANKROLL_FILE: &str = "bankroll";
+const BET_STEP: u32 = 10;
+const BUTTON_COLOR: Color = Color::rgb(0.12, 0.18, 0.14);
+const BUTTON_HINT_COLOR: Color = Color::rgb(0.75, 0.6, 0.1);
+const BUTTON_SIZE: Vec2 = const_vec2!([130.0, 40.0]);
+const BUTTON_Y: f32 = -270.0;
+const CARD_SIZE: Vec2 = const_vec2!([80.0, 112.0]);
+/// Seconds between the dealer's cards.
+const DEALER_DELAY: f32 = 0.6;
+const DEALER_FAN: f32 = 32.0;
+const DEALER_Y: f32 = 160.0;
+const DISABLED_COLOR: Color = Color::rgb(0.45, 0.5, 0.47);
+const HAND_FAN: f32 = 24.0;
+const HAND_SPACING: f32 = 210.0;
+const HAND_Y: f32 = -100.0;
+const MAX_BET: u32 = 500;
+/// Chips to start with, and to start over with once broke.
+const STARTING_CHIPS: u32 = 1000;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.9);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+#[derive(Serialize, Deserialize)]
+struct Bankroll {
+    best: u32,
+    chips: u32,
+}
+
+impl Default for Bankroll {
+    fn default() -> Self {
+        Self {
+            best: STARTING_CHIPS,
+            chips: STARTING_CHIPS,
+        }
+    }
+}
+
+impl Bankroll {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("blackjack", BANKROLL_FILE, self) {
+            eprintln!("could not save bankroll: {}", error);
+        }
+    }
+}
+
+/// A clickable button for a play, dimmed when the play is not possible.
+#[derive(Component)]
+struct ButtonSprite(Action);
+
+#[derive(Component)]
+struct ButtonText;
+
+/// Cards and labels on the table, rebuilt whenever the table changes.
+#[derive(Component)]
+struct CardSprite;
+
+struct DealerTimer(Timer);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Betting,
+    Playing,
+    /// The dealer turns the hole card and draws.
+    Dealer,
+    Result,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+struct Sounds {
+    blackjack: Handle<AudioSource>,
+    card: Handle<AudioSource>,
+    chips: Handle<AudioSource>,
+    lose: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+}
+
+struct Table {
+    /// The hand being played.
+    active: usize,
+    /// The bet placed on the next round.
+    bet: u32,
+    dealer: Vec<Card>,
+    hands: Vec<Hand>,
+    /// Show the basic strategy play for the active hand.
+    hints: bool,
+    /// The dealer's second card is still face down.
+    hole_hidden: bool,
+    message: String,
+    results: Vec<Outcome>,
+    shoe: Shoe,
+}
+
+impl Table {
+    fn active_hand(&self) -> Option<&Hand> {
+        self.hands.get(self.active)
+    }
+
+    /// Whether `action` may be played on the active hand with the chips left.
+    fn allows(&self, action: Action, chips: u32) -> bool {
+        let hand = match self.active_hand() {
+            Some(hand) if !hand.done => hand,
+            _ => return false,
+        };
+        match action {
+            Action::Hit | Action::Stand => true,
+            Action::Double => hand.can_double() && chips >= hand.bet,
+            Action::Split => hand.can_split() && chips >= hand.bet && self.hands.len() < MAX_HANDS,
+        }
+    }
+
+    fn draw(&mut self) -> Card {
+        self.shoe.draw(&mut thread_rng())
+    }
+
+    /// Moves on to the next hand still to be played; false once all are done.
+    fn next_hand(&mut self) -> bool {
+        while self.active < self.hands.len() {
+            let hand = &mut self.hands[self.active];
+            if rules::total(&hand.cards).0 >= 21 {
+                hand.done = true;
+            }
+            if !hand.done {
+                return true;
+            }
+            self.active += 1;
+        }
+        false
+    }
+}
+
+pub struct BlackjackPlugin;
+
+impl Plugin for BlackjackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(game_persistence::load_or_default::<Bankroll>(
+                "blackjack",
+                BANKROLL_FILE,
+            ))
+            .insert_resource(Table {
+                active: 0,
+                bet: 50,
+                dealer: Vec::new(),
+                hands: Vec::new(),
+                hints: false,
+                hole_hidden: true,
+                message: String::new(),
+                results: Vec::new(),
+                shoe: Shoe::new(&mut thread_rng()),
+            })
+            .insert_resource(DealerTimer(Timer::from_seconds(DEALER_DELAY, true)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Betting)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Betting).with_system(betting_enter))
+            .add_system_set(SystemSet::on_update(GameState::Betting).with_system(betting_input))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(play_input))
+            .add_system_set(SystemSet::on_enter(GameState::Dealer).with_system(dealer_enter))
+            .add_system_set(SystemSet::on_update(GameState::Dealer).with_system(dealer_update))
+            .add_system_set(SystemSet::on_update(GameState::Result).with_system(result_input))
+            .add_system(buttons_update)
+            .add_system(hints_input)
+            .add_system(table_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Blackjack".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Clears the table and, if the chips ran out, starts over with a fresh stack.
+fn betting_enter(mut table: ResMut<Table>, mut bankroll: ResMut<Bankroll>) {
+    table.hands.clear();
+    table.dealer.clear();
+    table.results.clear();
+    table.active = 0;
+    if bankroll.chips < BET_STEP {
+        bankroll.chips = STARTING_CHIPS;
+        bankroll.save();
+        table.message = format!("Out of chips: starting over with {}", STARTING_CHIPS);
+    } else {
+        table.message = "Place your bet".to_string();
+    }
+    table.bet = table.bet.min(bankroll.chips).max(BET_STEP);
+}
+
+/// Up/Down changes the bet, Enter deals. Both blackjacks are settled right away.
+fn betting_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut table: ResMut<Table>,
+    mut bankroll: ResMut<Bankroll>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let most = bankroll.chips.min(MAX_BET);
+    if keyboard_input.any_just_pressed([KeyCode::Up, KeyCode::Right]) {
+        table.bet = (table.bet + BET_STEP).min(most);
+        audio.play(sounds.chips.clone());
+    } else if keyboard_input.any_just_pressed([KeyCode::Down, KeyCode::Left]) {
+        table.bet = table.bet.saturating_sub(BET_STEP).max(BET_STEP);
+        audio.play(sounds.chips.clone());
+    }
+    if !keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        return;
+    }
+    if table.shoe.needs_shuffle() {
+        table.shoe = Shoe::new(&mut thread_rng());
+    }
+    bankroll.chips -= table.bet;
+    table.hands = vec![Hand::new(table.bet)];
+    table.hole_hidden = true;
+    for _ in 0..2 {
+        let card = table.draw();
+        table.hands[0].cards.push(card);
+        let card = table.draw();
+        table.dealer.push(card);
+    }
+    table.message.clear();
+    audio.play(sounds.card.clone());
+    // With the dealer peeking for blackjack, either blackjack ends the round at once.
+    if table.hands[0].is_blackjack() || rules::is_blackjack(&table.dealer) {
+        table.hands[0].done = true;
+        settle(&mut table, &mut bankroll, &audio, &sounds);
+        state.set(GameState::Result).unwrap();
+    } else if !table.next_hand() {
+        state.set(GameState::Dealer).unwrap();
+    } else {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn button_center(action: Action) -> Vec2 {
+    let index = match action {
+        Action::Hit => 0,
+        Action::Stand => 1,
+        Action::Double => 2,
+        Action::Split => 3,
+    };
+    Vec2::new((index as f32 - 1.5) * (BUTTON_SIZE.x + 16.0), BUTTON_Y)
+}
+
+/// Shows the buttons while a hand is played, dims what is not allowed and marks the hint.
+fn buttons_update(
+    table: Res<Table>,
+    bankroll: Res<Bankroll>,
+    state: Res<State<GameState>>,
+    mut buttons: Query<(&ButtonSprite, &mut Sprite, &mut Visibility, &Children)>,
+    mut texts: Query<(&mut Text, &mut Visibility), (With<ButtonText>, Without<ButtonSprite>)>,
+) {
+    if !table.is_changed() && !state.is_changed() && !bankroll.is_changed() {
+        return;
+    }
+    let playing = *state.current() == GameState::Playing;
+    let hint = hint(&table, &bankroll);
+    for (ButtonSprite(action), mut sprite, mut visibility, children) in buttons.iter_mut() {
+        visibility.is_visible = playing;
+        sprite.color = if table.hints && hint == Some(*action) {
+            BUTTON_HINT_COLOR
+        } else {
+            BUTTON_COLOR
+        };
+        let color = if table.allows(*action, bankroll.chips) {
+            TEXT_COLOR
+        } else {
+            DISABLED_COLOR
+        };
+        for child in children.iter() {
+            if let Ok((mut text, mut visibility)) = texts.get_mut(*child) {
+                visibility.is_visible = playing;
+                text.sections[0].style.color = color;
+            }
+        }
+    }
+}
+
+fn dealer_enter(mut table: ResMut<Table>, mut timer: ResMut<DealerTimer>) {
+    table.hole_hidden = false;
+    timer.0.reset();
+}
+
+/// The dealer draws one card per tick until 17 or more, unless every hand is already bust.
+fn dealer_update(
+    time: Res<Time>,
+    mut timer: ResMut<DealerTimer>,
+    mut table: ResMut<Table>,
+    mut bankroll: ResMut<Bankroll>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let all_bust = table.hands.iter().all(Hand::is_bust);
+    if !all_bust && rules::dealer_hits(&table.dealer) {
+        let card = table.draw();
+        table.dealer.push(card);
+        audio.play(sounds.card.clone());
+        return;
+    }
+    settle(&mut table, &mut bankroll, &audio, &sounds);
+    state.set(GameState::Result).unwrap();
+}
+
+/// Position of a card of the dealer, or of player hand `hand`.
+fn card_translation(hand: Option<(usize, usize)>, index: usize, count: usize) -> Vec3 {
+    let z = 1.0 + index as f32 * 0.1;
+    match hand {
+        None => {
+            let x = (index as f32 - (count - 1) as f32 / 2.0) * DEALER_FAN;
+            Vec3::new(x, DEALER_Y, z)
+        }
+        Some((hand, hands)) => Vec3::new(
+            hand_x(hand, hands) + (index as f32 - (count - 1) as f32 / 2.0) * HAND_FAN,
+            HAND_Y - index as f32 * 6.0,
+            z,
+        ),
+    }
+}
+
+fn hand_x(hand: usize, hands: usize) -> f32 {
+    (hand as f32 - (hands - 1) as f32 / 2.0) * HAND_SPACING
+}
+
+fn hint(table: &Table, bankroll: &Bankroll) -> Option<Action> {
+    let hand = table.active_hand()?;
+    let up = *table.dealer.first()?;
+    Some(strategy::advice(
+        hand,
+        up,
+        table.allows(Action::Double, bankroll.chips),
+        table.allows(Action::Split, bankroll.chips),
+    ))
+}
+
+/// Tab turns the basic strategy hints on and off.
+fn hints_input(keyboard_input: Res<Input<KeyCode>>, mut table: ResMut<Table>) {
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        table.hints = !table.hints;
+    }
+}
+
+fn outcome_text(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::Blackjack => "BLACKJACK",
+        Outcome::Win => "WIN",
+        Outcome::Push => "PUSH",
+        Outcome::Lose => "LOSE",
+    }
+}
+
+/// H / S / D / P or the buttons play the active hand.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut table: ResMut<Table>,
+    mut bankroll: ResMut<Bankroll>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let keys = [
+        (KeyCode::H, Action::Hit),
+        (KeyCode::S, Action::Stand),
+        (KeyCode::D, Action::Double),
+        (KeyCode::P, Action::Split),
+    ];
+    let mut action = keys
+        .iter()
+        .find(|(key, _)| keyboard_input.just_pressed(*key))
+        .map(|(_, action)| *action);
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+            action = action.or_else(|| {
+                keys.iter().map(|(_, action)| *action).find(|action| {
+                    let offset = (cursor - button_center(*action)).abs();
+                    offset.x <= BUTTON_SIZE.x / 2.0 && offset.y <= BUTTON_SIZE.y / 2.0
+                })
+            });
+        }
+    }
+    let action = match action {
+        Some(action) if table.allows(action, bankroll.chips) => action,
+        _ => return,
+    };
+    let active = table.active;
+    match action {
+        Action::Hit => {
+            let card = table.draw();
+            table.hands[active].cards.push(card);
+            audio.play(sounds.card.clone());
+        }
+        Action::Stand => table.hands[active].done = true,
+        Action::Double => {
+            let bet = table.hands[active].bet;
+            bankroll.chips -= bet;
+            let card = table.draw();
+            let hand = &mut table.hands[active];
+            hand.bet *= 2;
+            hand.doubled = true;
+            hand.cards.push(card);
+            hand.done = true;
+            audio.play(sounds.chips.clone());
+        }
+        Action::Split => {
+            let bet = table.hands[active].bet;
+            bankroll.chips -= bet;
+            let moved = table.hands[active].cards.pop().unwrap();
+            let mut second = Hand::new(bet);
+            second.cards.push(moved);
+            second.split = true;
+            table.hands[active].split = true;
+            table.hands.insert(active + 1, second);
+            // Split aces get one card each and no more.
+            let aces = moved.rank == 1;
+            for index in [active, active + 1] {
+                let card = table.draw();
+                let hand = &mut table.hands[index];
+                hand.cards.push(card);
+                hand.done = aces;
+            }
+            audio.play(sounds.chips.clone());
+        }
+    }
+    if !table.next_hand() {
+        state.set(GameState::Dealer).unwrap();
+    }
+}
+
+fn result_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        // Otherwise the same key press would deal the next round right away.
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        keyboard_input.clear_just_pressed(KeyCode::Space);
+        state.set(GameState::Betting).unwrap();
+    }
+}
+
+/// Pays out every hand and saves the bankroll.
+fn settle(table: &mut Table, bankroll: &mut Bankroll, audio: &Audio, sounds: &Sounds) {
+    table.hole_hidden = false;
+    let dealer = table.dealer.clone();
+    table.results = table
+        .hands
+        .iter()
+        .map(|hand| rules::outcome(hand, &dealer))
+        .collect();
+    let staked: u32 = table.hands.iter().map(|hand| hand.bet).sum();
+    let paid: u32 = table
+        .hands
+        .iter()
+        .zip(&table.results)
+        .map(|(hand, outcome)| outcome.payout(hand.bet))
+        .sum();
+    bankroll.chips += paid;
+    bankroll.best = bankroll.best.max(bankroll.chips);
+    bankroll.save();
+    table.message = if paid > staked {
+        format!("You win {}", paid - staked)
+    } else if paid < staked {
+        format!("You lose {}", staked - paid)
+    } else {
+        "Push".to_string()
+    };
+    let sound = if table.results.contains(&Outcome::Blackjack) {
+        &sounds.blackjack
+    } else if paid > staked {
+        &sounds.win
+    } else {
+        &sounds.lose
+    };
+    audio.play(sound.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 30.0, 20.0),
+        ))
+        .insert(MessageText);
+    for action in [Action::Hit, Action::Stand, Action::Double, Action::Split] {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: BUTTON_COLOR,
+                    custom_size: Some(BUTTON_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(button_center(action).extend(5.0)),
+                ..Default::default()
+            })
+            .insert(ButtonSprite(action))
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        action.name(),
+                        20.0,
+                        TEXT_COLOR,
+                        Vec3::new(0.0, 0.0, 0.1),
+                    ))
+                    .insert(ButtonText);
+            });
+    }
+
+    commands.insert_resource(Sounds {
+        blackjack: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35),
+        ])),
+        card: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.06)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        chips: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 1200.0, 0.03).volume(0.12),
+            Tone::new(Waveform::Square, 1500.0, 0.03).volume(0.12),
+        ])),
+        lose: audio_sources.add(
+            Tone::new(Waveform::Sine, 330.0, 0.35)
+                .slide(220.0)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        win: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 880.0, 0.25),
+        ])),
+    });
+}
+
+/// Rebuilds the cards and the labels under them whenever the table changes.
+fn table_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    table: Res<Table>,
+    state: Res<State<GameState>>,
+    sprites: Query<Entity, With<CardSprite>>,
+) {
+    if !table.is_changed() && !state.is_changed() {
+        return;
+    }
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let label = |commands: &mut Commands, value: String, position: Vec2, color: Color| {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                value,
+                18.0,
+                color,
+                position.extend(10.0),
+            ))
+            .insert(CardSprite);
+    };
+
+    for (index, card) in table.dealer.iter().enumerate() {
+        let shown = if index == 1 && table.hole_hidden {
+            None
+        } else {
+            Some(*card)
+        };
+        game_cards::spawn_card(
+            &mut commands,
+            &font,
+            shown,
+            CARD_SIZE,
+            card_translation(None, index, table.dealer.len()),
+        )
+        .insert(CardSprite);
+    }
+    if !table.dealer.is_empty() {
+        let value = if table.hole_hidden {
+            format!("Dealer shows {}", rules::total(&table.dealer[..1]).0)
+        } else {
+            format!("Dealer {}", total_text(&table.dealer))
+        };
+        let position = Vec2::new(0.0, DEALER_Y + CARD_SIZE.y / 2.0 + 18.0);
+        label(&mut commands, value, position, TEXT_COLOR);
+    }
+
+    let playing = *state.current() == GameState::Playing;
+    for (hand_index, hand) in table.hands.iter().enumerate() {
+        for (index, card) in hand.cards.iter().enumerate() {
+            game_cards::spawn_card(
+                &mut commands,
+                &font,
+                Some(*card),
+                CARD_SIZE,
+                card_translation(
+                    Some((hand_index, table.hands.len())),
+                    index,
+                    hand.cards.len(),
+                ),
+            )
+            .insert(CardSprite);
+        }
+        let x = hand_x(hand_index, table.hands.len());
+        let mut value = format!("{}   bet {}", total_text(&hand.cards), hand.bet);
+        if let Some(outcome) = table.results.get(hand_index) {
+            value = format!("{}\n{}", value, outcome_text(*outcome));
+        }
+        let active = playing && hand_index == table.active;
+        let color = if active {
+            BUTTON_HINT_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        let position = Vec2::new(x, HAND_Y - CARD_SIZE.y / 2.0 - 50.0);
+        label(&mut commands, value, position, color);
+        if active {
+            let position = Vec2::new(x, HAND_Y + CARD_SIZE.y / 2.0 + 16.0);
+            label(&mut commands, "\u{25BC}".to_string(), position, color);
+        }
+    }
+}
+
+fn texts_update(
+    table: Res<Table>,
+    bankroll: Res<Bankroll>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MessageText>>,
+    )>,
+) {
+    if !table.is_changed() && !bankroll.is_changed() && !state.is_changed() {
+        return;
+    }
+    let hud = format!(
+        "CHIPS {}   BET {}   BEST {}        SHOE {} cards",
+        bankroll.chips,
+        table.bet,
+        bankroll.best,
+        table.shoe.cards.len()
+    );
+    let hints = if table.hints { "on" } else { "off" };
+    let help = match state.current() {
+        GameState::Betting => "Up/Down: change bet   Enter: deal".to_string(),
+        GameState::Playing => format!(
+            "H: hit   S: stand   D: double   P: split   Tab: hints ({})",
+            hints
+        ),
+        GameState::Dealer => String::new(),
+        GameState::Result => "Enter: next round".to_string(),
+    };
+    let message = match (state.current(), hint(&table, &bankroll)) {
+        (GameState::Playing, Some(action)) if table.hints => {
+            format!("Basic strategy: {}", action.name())
+        }
+        _ => table.message.clone(),
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+/// The total, with soft ones shown both ways like "7 / 17".
+fn total_text(cards: &[Card]) -> String {
+    match rules::total(cards) {
+        (21, _) if rules::is_blackjack(cards) => "Blackjack".to_string(),
+        (total, true) if total < 21 => format!("{} / {}", total - 10, total),
+        (total, _) => total.to_string(),
+    }
+}
@@ -0,0 +1,183 @@
+//! Hands, the shoe and how a round is settled. The dealer stands on every 17, blackjack pays
+//! 3:2 and split hands may be doubled.
+
+use game_cards::Card;
+use rand::Rng;
+
+/// Decks shuffled together into the shoe.
+pub const DECKS: usize = 6;
+/// Hands a player may split into.
+pub const MAX_HANDS: usize = 4;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Hand {
+    pub bet: u32,
+    pub cards: Vec<Card>,
+    pub doubled: bool,
+    /// Made by splitting, so 21 with two cards is not a blackjack.
+    pub split: bool,
+    /// No more cards: stood, doubled, bust or a split ace.
+    pub done: bool,
+}
+
+impl Hand {
+    pub fn new(bet: u32) -> Self {
+        Self {
+            bet,
+            ..Default::default()
+        }
+    }
+
+    pub fn can_double(&self) -> bool {
+        !self.done && self.cards.len() == 2
+    }
+
+    pub fn can_split(&self) -> bool {
+        !self.done && self.cards.len() == 2 && points(self.cards[0]) == points(self.cards[1])
+    }
+
+    pub fn is_blackjack(&self) -> bool {
+        !self.split && is_blackjack(&self.cards)
+    }
+
+    pub fn is_bust(&self) -> bool {
+        total(&self.cards).0 > 21
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Blackjack,
+    Win,
+    Push,
+    Lose,
+}
+
+impl Outcome {
+    /// Chips handed back for a hand with `bet` on it, the bet included.
+    pub fn payout(self, bet: u32) -> u32 {
+        match self {
+            Self::Blackjack => bet + bet * 3 / 2,
+            Self::Win => bet * 2,
+            Self::Push => bet,
+            Self::Lose => 0,
+        }
+    }
+}
+
+/// The cards still to be dealt, shuffled anew once they run low.
+pub struct Shoe {
+    pub cards: Vec<Card>,
+}
+
+impl Shoe {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        Self {
+            cards: game_cards::shuffled_decks(DECKS, rng),
+        }
+    }
+
+    pub fn draw(&mut self, rng: &mut impl Rng) -> Card {
+        if self.cards.is_empty() {
+            *self = Self::new(rng);
+        }
+        self.cards.pop().unwrap()
+    }
+
+    /// Whether the cut card came out: a quarter of the shoe is never dealt.
+    pub fn needs_shuffle(&self) -> bool {
+        self.cards.len() < DECKS * 52 / 4
+    }
+}
+
+/// The dealer draws to 16 and stands on every 17, soft ones too.
+pub fn dealer_hits(cards: &[Card]) -> bool {
+    total(cards).0 < 17
+}
+
+pub fn is_blackjack(cards: &[Card]) -> bool {
+    cards.len() == 2 && total(cards).0 == 21
+}
+
+/// How a finished hand did against the dealer's cards.
+pub fn outcome(hand: &Hand, dealer: &[Card]) -> Outcome {
+    let dealer_total = total(dealer).0;
+    let own = total(&hand.cards).0;
+    if hand.is_blackjack() {
+        if is_blackjack(dealer) {
+            Outcome::Push
+        } else {
+            Outcome::Blackjack
+        }
+    } else if own > 21 || is_blackjack(dealer) {
+        Outcome::Lose
+    } else if dealer_total > 21 || own > dealer_total {
+        Outcome::Win
+    } else if own == dealer_total {
+        Outcome::Push
+    } else {
+        Outcome::Lose
+    }
+}
+
+/// A card's value with aces counted as 1.
+pub fn points(card: Card) -> u32 {
+    card.rank.min(10) as u32
+}
+
+/// The best total of the cards, and whether an ace still counts as 11 in it (a soft total).
+pub fn total(cards: &[Card]) -> (u32, bool) {
+    let hard: u32 = cards.iter().map(|card| points(*card)).sum();
+    if cards.iter().any(|card| card.rank == 1) && hard + 10 <= 21 {
+        (hard + 10, true)
+    } else {
+        (hard, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_cards::Suit;
+
+    /// Cards by rank, all spades.
+    fn cards(ranks: &[u8]) -> Vec<Card> {
+        ranks
+            .iter()
+            .map(|rank| Card::new(*rank, Suit::Spades))
+            .collect()
+    }
+
+    #[test]
+    fn aces_count_eleven_while_they_can() {
+        assert_eq!(total(&cards(&[1, 6])), (17, true));
+        assert_eq!(total(&cards(&[1, 6, 10])), (17, false));
+        assert_eq!(total(&cards(&[1, 1, 9])), (21, true));
+        assert_eq!(total(&cards(&[13, 12, 2])), (22, false));
+        assert!(is_blackjack(&cards(&[1, 11])));
+    }
+
+    #[test]
+    fn dealer_stands_on_soft_seventeen() {
+        assert!(dealer_hits(&cards(&[10, 6])));
+        assert!(!dealer_hits(&cards(&[1, 6])));
+        assert!(dealer_hits(&cards(&[1, 5])));
+    }
+
+    #[test]
+    fn outcomes_and_payouts() {
+        let mut hand = Hand::new(20);
+        hand.cards = cards(&[1, 13]);
+        assert_eq!(outcome(&hand, &cards(&[10, 9])), Outcome::Blackjack);
+        assert_eq!(Outcome::Blackjack.payout(20), 50);
+        assert_eq!(outcome(&hand, &cards(&[1, 10])), Outcome::Push);
+        // 21 on split hands is no blackjack.
+        hand.split = true;
+        assert_eq!(outcome(&hand, &cards(&[10, 6, 5])), Outcome::Push);
+        hand.cards = cards(&[10, 8]);
+        assert_eq!(outcome(&hand, &cards(&[10, 6, 9])), Outcome::Win);
+        assert_eq!(outcome(&hand, &cards(&[1, 10])), Outcome::Lose);
+        hand.cards = cards(&[10, 8, 5]);
+        assert_eq!(outcome(&hand, &cards(&[10, 6, 9])), Outcome::Lose);
+    }
+}
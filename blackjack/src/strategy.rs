@@ -0,0 +1,116 @@
+//! Basic strategy for a multi-deck shoe where the dealer stands on soft 17 and split hands
+//! may be doubled.
+
+use crate::rules::{self, Hand};
+use game_cards::Card;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Hit,
+    Stand,
+    Double,
+    Split,
+}
+
+impl Action {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Hit => "Hit",
+            Self::Stand => "Stand",
+            Self::Double => "Double",
+            Self::Split => "Split",
+        }
+    }
+}
+
+/// The basic strategy play for the hand against the dealer's up card, given what the
+/// bankroll allows.
+pub fn advice(hand: &Hand, up: Card, can_double: bool, can_split: bool) -> Action {
+    // The dealer's ace counts as 11 in the tables.
+    let dealer = match rules::points(up) {
+        1 => 11,
+        points => points,
+    };
+    let double_or = |fallback| {
+        if can_double && hand.can_double() {
+            Action::Double
+        } else {
+            fallback
+        }
+    };
+    if can_split && hand.can_split() {
+        let split = match rules::points(hand.cards[0]) {
+            1 | 8 => true,
+            9 => !matches!(dealer, 7 | 10 | 11),
+            7 | 3 | 2 => dealer <= 7,
+            6 => dealer <= 6,
+            4 => dealer == 5 || dealer == 6,
+            _ => false,
+        };
+        if split {
+            return Action::Split;
+        }
+    }
+    let (total, soft) = rules::total(&hand.cards);
+    if soft {
+        return match total {
+            13 | 14 if (5..=6).contains(&dealer) => double_or(Action::Hit),
+            15 | 16 if (4..=6).contains(&dealer) => double_or(Action::Hit),
+            17 if (3..=6).contains(&dealer) => double_or(Action::Hit),
+            18 if (3..=6).contains(&dealer) => double_or(Action::Stand),
+            18 if dealer >= 9 => Action::Hit,
+            18.. => Action::Stand,
+            _ => Action::Hit,
+        };
+    }
+    match total {
+        9 if (3..=6).contains(&dealer) => double_or(Action::Hit),
+        10 if dealer <= 9 => double_or(Action::Hit),
+        11 if dealer <= 10 => double_or(Action::Hit),
+        12 if (4..=6).contains(&dealer) => Action::Stand,
+        13..=16 if dealer <= 6 => Action::Stand,
+        17.. => Action::Stand,
+        _ => Action::Hit,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_cards::Suit;
+
+    fn hand(ranks: &[u8]) -> Hand {
+        let mut hand = Hand::new(10);
+        hand.cards = ranks
+            .iter()
+            .map(|rank| Card::new(*rank, Suit::Hearts))
+            .collect();
+        hand
+    }
+
+    fn up(rank: u8) -> Card {
+        Card::new(rank, Suit::Clubs)
+    }
+
+    #[test]
+    fn hard_totals() {
+        assert_eq!(advice(&hand(&[10, 6]), up(6), true, true), Action::Stand);
+        assert_eq!(advice(&hand(&[10, 6]), up(7), true, true), Action::Hit);
+        assert_eq!(advice(&hand(&[10, 2]), up(3), true, true), Action::Hit);
+        assert_eq!(advice(&hand(&[6, 5]), up(10), true, true), Action::Double);
+        assert_eq!(advice(&hand(&[6, 5]), up(1), true, true), Action::Hit);
+        // Without the chips to double, hit instead.
+        assert_eq!(advice(&hand(&[6, 4]), up(5), false, true), Action::Hit);
+    }
+
+    #[test]
+    fn soft_totals_and_pairs() {
+        assert_eq!(advice(&hand(&[1, 7]), up(5), true, true), Action::Double);
+        assert_eq!(advice(&hand(&[1, 7]), up(5), false, true), Action::Stand);
+        assert_eq!(advice(&hand(&[1, 7]), up(10), true, true), Action::Hit);
+        assert_eq!(advice(&hand(&[8, 8]), up(1), true, true), Action::Split);
+        assert_eq!(advice(&hand(&[13, 12]), up(6), true, true), Action::Stand);
+        assert_eq!(advice(&hand(&[5, 5]), up(6), true, true), Action::Double);
+        assert_eq!(advice(&hand(&[9, 9]), up(7), true, true), Action::Stand);
+    }
+}
@@ -0,0 +1,244 @@
+//! The arena: a grid of floor, hard blocks that never break and soft blocks that bombs blow
+//! away, some with a power-up underneath. Rows count down from the top.
+
+use rand::Rng;
+
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+pub struct Arena {
+    pub columns: i32,
+    /// Power-ups, hidden while a soft block still covers them.
+    items: Vec<Option<PowerUp>>,
+    pub rows: i32,
+    tiles: Vec<Tile>,
+}
+
+impl Arena {
+    /// Hard blocks around the edge and on every other cell inside, soft blocks on a share of the
+    /// rest. The cells around each of `clear` stay open, so nobody starts boxed in.
+    pub fn generate(
+        columns: i32,
+        rows: i32,
+        soft_chance: f64,
+        item_chance: f64,
+        clear: &[(i32, i32)],
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut arena = Self {
+            columns,
+            items: vec![None; (columns * rows) as usize],
+            rows,
+            tiles: vec![Tile::Floor; (columns * rows) as usize],
+        };
+        for y in 0..rows {
+            for x in 0..columns {
+                let edge = x == 0 || y == 0 || x == columns - 1 || y == rows - 1;
+                let kept_clear = clear
+                    .iter()
+                    .any(|(cx, cy)| (x - cx).abs() + (y - cy).abs() <= 1);
+                let tile = if edge || (x % 2 == 0 && y % 2 == 0) {
+                    Tile::Hard
+                } else if !kept_clear && rng.gen_bool(soft_chance) {
+                    Tile::Soft
+                } else {
+                    continue;
+                };
+                arena.set((x, y), tile);
+                if tile == Tile::Soft && rng.gen_bool(item_chance) {
+                    let index = arena.index((x, y));
+                    arena.items[index] = Some(PowerUp::ALL[rng.gen_range(0, PowerUp::ALL.len())]);
+                }
+            }
+        }
+        arena
+    }
+
+    /// The cells a bomb's blast covers: its own and up to `range` each way, stopping before a
+    /// hard block and at the first soft one.
+    pub fn blast(&self, origin: (i32, i32), range: i32) -> Vec<(i32, i32)> {
+        let mut cells = vec![origin];
+        for (dx, dy) in DIRECTIONS {
+            for step in 1..=range {
+                let cell = (origin.0 + dx * step, origin.1 + dy * step);
+                match self.get(cell) {
+                    Tile::Hard => break,
+                    Tile::Soft => {
+                        cells.push(cell);
+                        break;
+                    }
+                    Tile::Floor => cells.push(cell),
+                }
+            }
+        }
+        cells
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        (0..self.columns).contains(&cell.0) && (0..self.rows).contains(&cell.1)
+    }
+
+    /// Sets off `bombs[index]` and every bomb its blast reaches, on and on. Soft blocks in the
+    /// way break, and power-ups lying in the open burn. Returns the cells on fire and how many
+    /// blocks broke.
+    pub fn explode(&mut self, bombs: &mut Vec<Bomb>, index: usize) -> (Vec<(i32, i32)>, usize) {
+        let mut cells: Vec<(i32, i32)> = Vec::new();
+        let mut pending = vec![bombs.remove(index)];
+        while let Some(bomb) = pending.pop() {
+            for cell in self.blast(bomb.cell, bomb.range) {
+                if !cells.contains(&cell) {
+                    cells.push(cell);
+                }
+                while let Some(chained) = bombs.iter().position(|other| other.cell == cell) {
+                    pending.push(bombs.remove(chained));
+                }
+            }
+        }
+        let mut broken = 0;
+        for cell in &cells {
+            let index = self.index(*cell);
+            match self.tiles[index] {
+                Tile::Soft => {
+                    self.tiles[index] = Tile::Floor;
+                    broken += 1;
+                }
+                Tile::Floor => self.items[index] = None,
+                Tile::Hard => {}
+            }
+        }
+        (cells, broken)
+    }
+
+    /// Cells off the arena count as hard blocks.
+    pub fn get(&self, cell: (i32, i32)) -> Tile {
+        if self.contains(cell) {
+            self.tiles[self.index(cell)]
+        } else {
+            Tile::Hard
+        }
+    }
+
+    /// The power-up lying open on a cell, if any.
+    pub fn item(&self, cell: (i32, i32)) -> Option<PowerUp> {
+        if self.get(cell) == Tile::Floor {
+            self.items[self.index(cell)]
+        } else {
+            None
+        }
+    }
+
+    /// Picks up the power-up lying open on a cell.
+    pub fn take_item(&mut self, cell: (i32, i32)) -> Option<PowerUp> {
+        let item = self.item(cell);
+        if item.is_some() {
+            let index = self.index(cell);
+            self.items[index] = None;
+        }
+        item
+    }
+
+    fn index(&self, cell: (i32, i32)) -> usize {
+        (cell.1 * self.columns + cell.0) as usize
+    }
+
+    fn set(&mut self, cell: (i32, i32), tile: Tile) {
+        let index = self.index(cell);
+        self.tiles[index] = tile;
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bomb {
+    pub cell: (i32, i32),
+    /// Seconds until it goes off.
+    pub fuse: f32,
+    pub owner: usize,
+    pub range: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerUp {
+    /// One more bomb out at a time.
+    Bomb,
+    /// A longer blast.
+    Range,
+    Speed,
+}
+
+impl PowerUp {
+    pub const ALL: [PowerUp; 3] = [Self::Bomb, Self::Range, Self::Speed];
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Floor,
+    Hard,
+    Soft,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// A 9x7 arena with only the fixed hard blocks.
+    fn open() -> Arena {
+        Arena::generate(9, 7, 0.0, 0.0, &[], &mut StdRng::seed_from_u64(1))
+    }
+
+    fn bomb(cell: (i32, i32), range: i32) -> Bomb {
+        Bomb {
+            cell,
+            fuse: 1.0,
+            owner: 0,
+            range,
+        }
+    }
+
+    #[test]
+    fn generates_blocks_around_clear_corners() {
+        let arena = Arena::generate(9, 7, 1.0, 1.0, &[(1, 1)], &mut StdRng::seed_from_u64(2));
+        assert_eq!(arena.get((0, 3)), Tile::Hard);
+        assert_eq!(arena.get((2, 2)), Tile::Hard);
+        assert_eq!(arena.get((1, 1)), Tile::Floor);
+        assert_eq!(arena.get((2, 1)), Tile::Floor);
+        assert_eq!(arena.get((1, 2)), Tile::Floor);
+        assert_eq!(arena.get((3, 1)), Tile::Soft);
+        assert_eq!(arena.get((9, 1)), Tile::Hard);
+        // Power-ups stay hidden until their block is gone.
+        assert_eq!(arena.item((3, 1)), None);
+    }
+
+    #[test]
+    fn blasts_stop_at_blocks() {
+        let mut arena = open();
+        arena.set((3, 1), Tile::Soft);
+        let mut cells = arena.blast((1, 1), 3);
+        cells.sort_unstable();
+        assert_eq!(cells, [(1, 1), (1, 2), (1, 3), (1, 4), (2, 1), (3, 1)]);
+        assert_eq!(arena.blast((1, 3), 5).len(), 10);
+    }
+
+    #[test]
+    fn explosions_chain_and_break_blocks() {
+        let mut arena = open();
+        arena.set((5, 1), Tile::Soft);
+        let (block, open) = (arena.index((5, 1)), arena.index((1, 5)));
+        arena.items[block] = Some(PowerUp::Range);
+        arena.items[open] = Some(PowerUp::Speed);
+        let mut bombs = vec![
+            bomb((7, 5), 1),
+            bomb((1, 1), 2),
+            bomb((3, 1), 2),
+            bomb((1, 3), 2),
+        ];
+        let (cells, broken) = arena.explode(&mut bombs, 1);
+        assert_eq!(bombs, [bomb((7, 5), 1)]);
+        assert_eq!(broken, 1);
+        assert!(cells.contains(&(4, 1)) && cells.contains(&(5, 1)) && cells.contains(&(1, 5)));
+        assert_eq!(arena.get((5, 1)), Tile::Floor);
+        // The block's power-up shows up; the one lying in the open burnt.
+        assert_eq!(arena.item((1, 5)), None);
+        assert_eq!(arena.take_item((5, 1)), Some(PowerUp::Range));
+        assert_eq!(arena.item((5, 1)), None);
+    }
+}
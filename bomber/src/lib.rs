@@ -0,0 +1,968 @@
+use arena::{Arena, Bomb, PowerUp, Tile};
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+pub mod arena;
+
+const ARROWS: [KeyCode; 5] = [
+    KeyCode::Up,
+    KeyCode::Left,
+    KeyCode::Down,
+    KeyCode::Right,
+    KeyCode::RShift,
+];
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.08);
+/// Side of a cell in pixels.
+const CELL: f32 = 44.0;
+const COLUMNS: i32 = 15;
+const ENEMY_COLOR: Color = Color::rgb(1.0, 0.45, 0.7);
+const FLAME_COLOR: Color = Color::rgb(1.0, 0.6, 0.15);
+/// Seconds the flames of a blast stay.
+const FLAME_TIME: f32 = 0.5;
+const FLOOR_COLOR: Color = Color::rgb(0.2, 0.45, 0.25);
+/// Seconds from dropping a bomb until it goes off.
+const FUSE: f32 = 2.5;
+const GAME: &str = "bomber";
+const HARD_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+const IJKL: [KeyCode; 5] = [KeyCode::I, KeyCode::J, KeyCode::K, KeyCode::L, KeyCode::O];
+/// Chance that a soft block hides a power-up.
+const ITEM_CHANCE: f64 = 0.3;
+const LEADERBOARD_SIZE: usize = 5;
+const LIVES: u32 = 3;
+const NUMPAD: [KeyCode; 5] = [
+    KeyCode::Numpad8,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad0,
+];
+/// Cells per second.
+const PLAYER_SPEED: f32 = 3.5;
+const ROWS: i32 = 13;
+/// Start corner and color of each player slot.
+const SLOTS: [((i32, i32), Color); 4] = [
+    ((1, 1), Color::rgb(0.95, 0.95, 1.0)),
+    ((COLUMNS - 2, ROWS - 2), Color::rgb(0.2, 0.2, 0.25)),
+    ((COLUMNS - 2, 1), Color::rgb(1.0, 0.3, 0.25)),
+    ((1, ROWS - 2), Color::rgb(0.3, 0.6, 1.0)),
+];
+const SOFT_CHANCE: f64 = 0.6;
+const SOFT_COLOR: Color = Color::rgb(0.7, 0.5, 0.3);
+/// Cells per second each speed power-up adds, up to `SPEED_UPS` of them.
+const SPEED_STEP: f32 = 0.7;
+const SPEED_UPS: u32 = 4;
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const WASD: [KeyCode; 5] = [
+    KeyCode::W,
+    KeyCode::A,
+    KeyCode::S,
+    KeyCode::D,
+    KeyCode::Space,
+];
+/// Rounds a player has to win to take a battle.
+const WINS_NEEDED: u32 = 3;
+
+pub const WINDOW_HEIGHT: f32 = 660.0;
+pub const WINDOW_WIDTH: f32 = CELL * COLUMNS as f32;
+
+/// Everything that moves or burns, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+/// Blocks and power-ups, rebuilt whenever the arena changes.
+#[derive(Component)]
+struct ArenaSprite;
+
+struct Game {
+    arena: Arena,
+    bombs: Vec<Bomb>,
+    enemies: Vec<Mover>,
+    /// Burning cells with the seconds they have left.
+    flames: Vec<((i32, i32), f32)>,
+    lives: u32,
+    mode: Mode,
+    players: Vec<Player>,
+    /// The arena has changed since it was last drawn.
+    redraw: bool,
+    /// The battle round, or the stage on your own.
+    round: u32,
+    /// Winner of the last battle round, `None` when nobody was left standing.
+    round_winner: Option<usize>,
+    score: u32,
+    wins: Vec<u32>,
+}
+
+impl Game {
+    fn new(mode: Mode) -> Self {
+        let mut game = Self {
+            arena: Arena::generate(COLUMNS, ROWS, 0.0, 0.0, &[], &mut thread_rng()),
+            bombs: Vec::new(),
+            enemies: Vec::new(),
+            flames: Vec::new(),
+            lives: LIVES,
+            mode,
+            players: Vec::new(),
+            redraw: true,
+            round: 1,
+            round_winner: None,
+            score: 0,
+            wins: vec![0; mode.controls().len()],
+        };
+        game.start_round();
+        game
+    }
+
+    /// The player who has won enough battle rounds.
+    fn champion(&self) -> Option<usize> {
+        self.wins.iter().position(|wins| *wins >= WINS_NEEDED)
+    }
+
+    fn enemy_speed(&self) -> f32 {
+        (1.8 + 0.15 * self.round as f32).min(3.2)
+    }
+
+    /// A fresh arena for `round`. Players come back to their corners; in a battle, or after
+    /// losing a life, without their power-ups.
+    fn start_round(&mut self) {
+        let mut rng = thread_rng();
+        let controls = self.mode.controls();
+        let starts: Vec<(i32, i32)> = SLOTS[..controls.len()]
+            .iter()
+            .map(|(start, _)| *start)
+            .collect();
+        self.arena = Arena::generate(COLUMNS, ROWS, SOFT_CHANCE, ITEM_CHANCE, &starts, &mut rng);
+        self.bombs.clear();
+        self.flames.clear();
+        self.redraw = true;
+        self.round_winner = None;
+        let keep =
+            self.mode == Mode::Solo && matches!(self.players.first(), Some(player) if player.alive);
+        if !keep {
+            self.players = controls
+                .into_iter()
+                .enumerate()
+                .map(|(index, keys)| Player::new(index, keys))
+                .collect();
+        }
+        for (player, start) in self.players.iter_mut().zip(&starts) {
+            player.alive = true;
+            player.mover = Mover::at(*start);
+        }
+
+        self.enemies.clear();
+        if self.mode == Mode::Solo {
+            let count = (3 + self.round as usize).min(9);
+            let start = starts[0];
+            let mut cells: Vec<(i32, i32)> = (1..ROWS - 1)
+                .flat_map(|y| (1..COLUMNS - 1).map(move |x| (x, y)))
+                .filter(|(x, y)| {
+                    self.arena.get((*x, *y)) == Tile::Floor
+                        && (x - start.0).abs() + (y - start.1).abs() >= 6
+                })
+                .collect();
+            cells.shuffle(&mut rng);
+            self.enemies = cells.into_iter().take(count).map(Mover::at).collect();
+        }
+    }
+
+    /// Nothing stands in the way: no block and no bomb.
+    fn walkable(&self, cell: (i32, i32)) -> bool {
+        self.arena.get(cell) == Tile::Floor && self.bombs.iter().all(|bomb| bomb.cell != cell)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    RoundOver,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The menu, the end of a round and the end of the game.
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    Solo,
+    TwoPlayers,
+    ThreePlayers,
+    FourPlayers,
+}
+
+impl Mode {
+    const ALL: [Mode; 4] = [
+        Self::Solo,
+        Self::TwoPlayers,
+        Self::ThreePlayers,
+        Self::FourPlayers,
+    ];
+
+    /// Up, left, down, right and bomb keys of each player; a lone player may use two sets.
+    fn controls(self) -> Vec<&'static [[KeyCode; 5]]> {
+        match self {
+            Self::Solo => vec![&[WASD, ARROWS]],
+            Self::TwoPlayers => vec![&[WASD], &[ARROWS]],
+            Self::ThreePlayers => vec![&[WASD], &[ARROWS], &[IJKL]],
+            Self::FourPlayers => vec![&[WASD], &[ARROWS], &[IJKL], &[NUMPAD]],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Solo => "Solo stages",
+            Self::TwoPlayers => "Battle, 2 players",
+            Self::ThreePlayers => "Battle, 3 players",
+            Self::FourPlayers => "Battle, 4 players",
+        }
+    }
+}
+
+/// Something moving from cell to cell, drawn part way between the two.
+struct Mover {
+    cell: (i32, i32),
+    facing: (i32, i32),
+    from: (i32, i32),
+    /// How far along from `from` to `cell`, from 0 to 1.
+    progress: f32,
+}
+
+impl Mover {
+    fn at(cell: (i32, i32)) -> Self {
+        Self {
+            cell,
+            facing: (0, 1),
+            from: cell,
+            progress: 1.0,
+        }
+    }
+
+    /// Heads for the next cell.
+    fn go(&mut self, cell: (i32, i32)) {
+        self.facing = (cell.0 - self.cell.0, cell.1 - self.cell.1);
+        self.from = self.cell;
+        self.cell = cell;
+        self.progress = 0.0;
+    }
+
+    /// The cell it is mostly on.
+    fn nearest(&self) -> (i32, i32) {
+        if self.progress < 0.5 {
+            self.from
+        } else {
+            self.cell
+        }
+    }
+
+    fn position(&self) -> Vec2 {
+        let from = cell_center(self.from);
+        from + (cell_center(self.cell) - from) * self.progress
+    }
+}
+
+struct Player {
+    alive: bool,
+    /// Bombs out at a time.
+    bombs: usize,
+    index: usize,
+    keys: &'static [[KeyCode; 5]],
+    mover: Mover,
+    range: i32,
+    speed_ups: u32,
+}
+
+impl Player {
+    fn new(index: usize, keys: &'static [[KeyCode; 5]]) -> Self {
+        Self {
+            alive: true,
+            bombs: 1,
+            index,
+            keys,
+            mover: Mover::at(SLOTS[index].0),
+            range: 1,
+            speed_ups: 0,
+        }
+    }
+
+    fn power_up(&mut self, item: PowerUp) {
+        match item {
+            PowerUp::Bomb => self.bombs += 1,
+            PowerUp::Range => self.range += 1,
+            PowerUp::Speed => self.speed_ups = (self.speed_ups + 1).min(SPEED_UPS),
+        }
+    }
+}
+
+struct Sounds {
+    blast: Handle<AudioSource>,
+    bomb: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    pickup: Handle<AudioSource>,
+    round: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Movement,
+    Collision,
+}
+
+pub struct BomberPlugin;
+
+impl Plugin for BomberPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(Mode::Solo))
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(round_start))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(players_update.label(Step::Movement))
+                    .with_system(enemies_update.label(Step::Movement))
+                    .with_system(bombs_update.label(Step::Movement))
+                    .with_system(collisions.label(Step::Collision).after(Step::Movement))
+                    .with_system(round_end.after(Step::Collision)),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::RoundOver).with_system(round_over_input),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(actors_render)
+            .add_system(arena_render)
+            .add_system(texts_update);
+        game_console::add_command(
+            app,
+            "give_powerup",
+            "<bomb|range|speed> [player]",
+            "Gives a player, the first by default, a power-up",
+            give_powerup,
+        );
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Bomber".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Flames, bombs, enemies and players.
+fn actors_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    time: Res<Time>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() != GameState::Playing {
+        // Shades the arena behind the banner.
+        commands
+            .spawn_bundle(sprite(
+                Color::rgba(0.0, 0.0, 0.0, 0.7),
+                Vec2::new(COLUMNS as f32, ROWS as f32) * CELL,
+                Vec3::new(0.0, 0.0, 850.0),
+            ))
+            .insert(Actor);
+    }
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    for (cell, _) in &game.flames {
+        commands
+            .spawn_bundle(sprite(
+                FLAME_COLOR,
+                Vec2::splat(CELL * 0.9),
+                cell_center(*cell).extend(5.0),
+            ))
+            .insert(Actor);
+    }
+    let mut glyph = |value: &str, size: f32, color: Color, position: Vec3| {
+        commands
+            .spawn_bundle(game_hud::world_text(&font, value, size, color, position))
+            .insert(Actor);
+    };
+    for bomb in &game.bombs {
+        // Bombs swell faster as the fuse burns down.
+        let pulse = (time.seconds_since_startup() as f32 * (16.0 - bomb.fuse * 4.0)).sin();
+        glyph(
+            "\u{25CF}",
+            CELL * (0.95 + 0.08 * pulse),
+            Color::rgb(0.05, 0.05, 0.05),
+            cell_center(bomb.cell).extend(3.0),
+        );
+    }
+    for enemy in &game.enemies {
+        glyph(
+            "\u{25C6}",
+            CELL * 0.9,
+            ENEMY_COLOR,
+            enemy.position().extend(4.0),
+        );
+    }
+    for player in game.players.iter().filter(|player| player.alive) {
+        let position = player.mover.position();
+        glyph(
+            "\u{25A0}",
+            CELL * 0.9,
+            SLOTS[player.index].1,
+            position.extend(6.0),
+        );
+        let label = format!("{}", player.index + 1);
+        glyph(
+            &label,
+            CELL * 0.4,
+            Color::rgb(0.5, 0.5, 0.5),
+            position.extend(7.0),
+        );
+    }
+}
+
+/// The floor, the blocks and the power-ups lying open.
+fn arena_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut game: ResMut<Game>,
+    sprites: Query<Entity, With<ArenaSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let arena = &game.arena;
+    let size = Vec2::new(arena.columns as f32, arena.rows as f32) * CELL;
+    commands
+        .spawn_bundle(sprite(FLOOR_COLOR, size, Vec3::ZERO))
+        .insert(ArenaSprite);
+    for y in 0..arena.rows {
+        for x in 0..arena.columns {
+            let center = cell_center((x, y));
+            let color = match arena.get((x, y)) {
+                Tile::Hard => HARD_COLOR,
+                Tile::Soft => SOFT_COLOR,
+                Tile::Floor => {
+                    if let Some(item) = arena.item((x, y)) {
+                        let (value, color) = power_up_glyph(item);
+                        commands
+                            .spawn_bundle(sprite(
+                                Color::rgb(0.15, 0.15, 0.2),
+                                Vec2::splat(CELL * 0.8),
+                                center.extend(1.0),
+                            ))
+                            .insert(ArenaSprite);
+                        commands
+                            .spawn_bundle(game_hud::world_text(
+                                &font,
+                                value,
+                                CELL * 0.6,
+                                color,
+                                center.extend(2.0),
+                            ))
+                            .insert(ArenaSprite);
+                    }
+                    continue;
+                }
+            };
+            commands
+                .spawn_bundle(sprite(color, Vec2::splat(CELL - 2.0), center.extend(1.0)))
+                .insert(ArenaSprite);
+        }
+    }
+}
+
+/// Counts down the fuses and sets off the bombs that are due or caught in flames.
+fn bombs_update(time: Res<Time>, mut game: ResMut<Game>, audio: Res<Audio>, sounds: Res<Sounds>) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    for (_, seconds) in &mut game.flames {
+        *seconds -= delta;
+    }
+    game.flames.retain(|(_, seconds)| *seconds > 0.0);
+    for bomb in &mut game.bombs {
+        bomb.fuse -= delta;
+        if game.flames.iter().any(|(cell, _)| *cell == bomb.cell) {
+            bomb.fuse = 0.0;
+        }
+    }
+    while let Some(index) = game.bombs.iter().position(|bomb| bomb.fuse <= 0.0) {
+        let (cells, broken) = game.arena.explode(&mut game.bombs, index);
+        for cell in cells {
+            game.flames.retain(|(burning, _)| *burning != cell);
+            game.flames.push((cell, FLAME_TIME));
+        }
+        if game.mode == Mode::Solo {
+            game.score += 10 * broken as u32;
+        }
+        game.redraw = true;
+        audio.play(sounds.blast.clone());
+    }
+}
+
+fn cell_center(cell: (i32, i32)) -> Vec2 {
+    let top_left = Vec2::new(-(COLUMNS as f32), ROWS as f32) * CELL / 2.0;
+    top_left + Vec2::new(cell.0 as f32 + 0.5, -(cell.1 as f32) - 0.5) * CELL
+}
+
+/// Flames catch players and enemies; enemies catch players.
+fn collisions(mut game: ResMut<Game>, audio: Res<Audio>, sounds: Res<Sounds>) {
+    let game = &mut *game;
+    let burning = |cell: (i32, i32)| game.flames.iter().any(|(flame, _)| *flame == cell);
+    let before = game.enemies.len();
+    game.enemies.retain(|enemy| !burning(enemy.nearest()));
+    game.score += (before - game.enemies.len()) as u32 * 100 * game.round;
+    for player in game.players.iter_mut().filter(|player| player.alive) {
+        let position = player.mover.position();
+        let caught = game
+            .enemies
+            .iter()
+            .any(|enemy| enemy.position().distance(position) < CELL * 0.6);
+        if caught || burning(player.mover.nearest()) {
+            player.alive = false;
+            audio.play(sounds.death.clone());
+        }
+    }
+}
+
+/// Enemies wander the open floor, mostly straight on.
+fn enemies_update(time: Res<Time>, mut game: ResMut<Game>) {
+    let game = &mut *game;
+    let mut rng = thread_rng();
+    let step = game.enemy_speed() * time.delta_seconds();
+    let mut enemies = std::mem::take(&mut game.enemies);
+    for enemy in &mut enemies {
+        enemy.progress += step;
+        if enemy.progress < 1.0 {
+            continue;
+        }
+        enemy.progress = 1.0;
+        let (x, y) = enemy.cell;
+        let ahead = (x + enemy.facing.0, y + enemy.facing.1);
+        let open: Vec<(i32, i32)> = [(0, -1), (1, 0), (0, 1), (-1, 0)]
+            .iter()
+            .map(|(dx, dy)| (x + dx, y + dy))
+            .filter(|cell| game.walkable(*cell))
+            .collect();
+        let next = if open.contains(&ahead) && rng.gen_bool(0.85) {
+            Some(ahead)
+        } else {
+            open.choose(&mut rng).copied()
+        };
+        if let Some(next) = next {
+            enemy.go(next);
+        }
+    }
+    game.enemies = enemies;
+}
+
+fn game_over(game: Res<Game>, mut scores: ResMut<Scores>, platform: Res<Platform>) {
+    if game.mode == Mode::Solo && scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+fn give_powerup(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let item = match args.first() {
+        Some(&"bomb") => PowerUp::Bomb,
+        Some(&"range") => PowerUp::Range,
+        Some(&"speed") => PowerUp::Speed,
+        Some(other) => {
+            return Err(format!(
+                "no power-up {}; they are bomb, range and speed",
+                other
+            ))
+        }
+        None => return Err("missing <bomb|range|speed>".to_string()),
+    };
+    let number: usize = match args.get(1) {
+        Some(_) => game_console::arg(args, 1, "player")?,
+        None => 1,
+    };
+    let mut game = world.get_resource_mut::<Game>().unwrap();
+    let player = game
+        .players
+        .get_mut(number.wrapping_sub(1))
+        .ok_or(format!("there is no player {}", number))?;
+    player.power_up(item);
+    Ok(format!("Player {} got {}", number, args[0]))
+}
+
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let index = Mode::ALL
+        .iter()
+        .position(|mode| *mode == game.mode)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) || keyboard_input.just_pressed(KeyCode::W) {
+        game.mode = Mode::ALL[(index + Mode::ALL.len() - 1) % Mode::ALL.len()];
+    } else if menu.just_pressed(Navigate::Down) || keyboard_input.just_pressed(KeyCode::S) {
+        game.mode = Mode::ALL[(index + 1) % Mode::ALL.len()];
+    } else if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(game.mode);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn over_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Walking from cell to cell, dropping bombs and picking up power-ups.
+fn players_update(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    let mut players = std::mem::take(&mut game.players);
+    for player in players.iter_mut().filter(|player| player.alive) {
+        let keys = player.keys;
+        let dropping = keys
+            .iter()
+            .any(|[_, _, _, _, bomb]| keyboard_input.just_pressed(*bomb));
+        let cell = player.mover.nearest();
+        let out = game
+            .bombs
+            .iter()
+            .filter(|bomb| bomb.owner == player.index)
+            .count();
+        if dropping && out < player.bombs && game.bombs.iter().all(|bomb| bomb.cell != cell) {
+            game.bombs.push(Bomb {
+                cell,
+                fuse: FUSE,
+                owner: player.index,
+                range: player.range,
+            });
+            audio.play(sounds.bomb.clone());
+        }
+
+        if let Some(item) = game.arena.take_item(cell) {
+            player.power_up(item);
+            game.redraw = true;
+            audio.play(sounds.pickup.clone());
+        }
+
+        let speed = PLAYER_SPEED + SPEED_STEP * player.speed_ups as f32;
+        player.mover.progress = (player.mover.progress + speed * delta).min(1.0);
+        if player.mover.progress < 1.0 {
+            continue;
+        }
+        let direction = keys.iter().find_map(|[up, left, down, right, _]| {
+            [
+                (up, (0, -1)),
+                (left, (-1, 0)),
+                (down, (0, 1)),
+                (right, (1, 0)),
+            ]
+            .iter()
+            .find(|(key, _)| keyboard_input.pressed(**key))
+            .map(|(_, direction)| *direction)
+        });
+        if let Some((dx, dy)) = direction {
+            let (x, y) = player.mover.cell;
+            let next = (x + dx, y + dy);
+            if game.walkable(next) {
+                player.mover.go(next);
+            } else {
+                player.mover.facing = (dx, dy);
+            }
+        }
+    }
+    game.players = players;
+}
+
+/// The icon and color a power-up is drawn with.
+fn power_up_glyph(item: PowerUp) -> (&'static str, Color) {
+    match item {
+        PowerUp::Bomb => ("\u{25CF}", Color::rgb(0.9, 0.9, 0.9)),
+        PowerUp::Range => ("\u{2605}", FLAME_COLOR),
+        PowerUp::Speed => ("\u{25B2}", Color::rgb(0.4, 0.8, 1.0)),
+    }
+}
+
+/// Ends the stage once the enemies are gone or the player is caught, and the battle round once
+/// at most one player is left.
+fn round_end(
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.mode == Mode::Solo {
+        if !game.players[0].alive {
+            game.lives -= 1;
+            let next = if game.lives == 0 {
+                GameState::Over
+            } else {
+                GameState::RoundOver
+            };
+            state.set(next).unwrap();
+        } else if game.enemies.is_empty() {
+            game.score += 500 * game.round;
+            audio.play(sounds.round.clone());
+            state.set(GameState::RoundOver).unwrap();
+        }
+        return;
+    }
+    let standing: Vec<usize> = game
+        .players
+        .iter()
+        .filter(|player| player.alive)
+        .map(|player| player.index)
+        .collect();
+    if standing.len() > 1 {
+        return;
+    }
+    game.round_winner = standing.first().copied();
+    if let Some(winner) = game.round_winner {
+        game.wins[winner] += 1;
+        audio.play(sounds.round.clone());
+    }
+    let next = if game.champion().is_some() {
+        GameState::Over
+    } else {
+        GameState::RoundOver
+    };
+    state.set(next).unwrap();
+}
+
+fn round_over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        // A lost life plays the same stage again.
+        if game.mode != Mode::Solo || game.players[0].alive {
+            game.round += 1;
+        }
+        game.start_round();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn round_start(audio: Res<Audio>, sounds: Res<Sounds>) {
+    audio.play(sounds.bomb.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        blast: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.5)
+                .fade()
+                .volume(0.5)
+                .into(),
+        ),
+        bomb: audio_sources.add(
+            Tone::new(Waveform::Triangle, 220.0, 0.08)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        death: audio_sources.add(
+            Tone::new(Waveform::Square, 500.0, 0.5)
+                .slide(80.0)
+                .volume(0.2)
+                .into(),
+        ),
+        pickup: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 660.0, 0.06).volume(0.3),
+            Tone::new(Waveform::Sine, 990.0, 0.1).fade().volume(0.3),
+        ])),
+        round: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 392.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 523.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 659.0, 0.3).volume(0.25),
+        ])),
+    });
+}
+
+fn sprite(color: Color, size: Vec2, translation: Vec3) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+fn texts_update(
+    game: Res<Game>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let solo = game.mode == Mode::Solo;
+    let hud = if *state.current() == GameState::Menu {
+        String::new()
+    } else if solo {
+        let player = &game.players[0];
+        format!(
+            "Score {}   High {}   Lives {}   Stage {}   \u{25CF}{} \u{2605}{} \u{25B2}{}",
+            game.score,
+            scores
+                .best("")
+                .map_or(0, |entry| entry.score)
+                .max(game.score),
+            game.lives,
+            game.round,
+            player.bombs,
+            player.range,
+            player.speed_ups
+        )
+    } else {
+        let wins = game
+            .wins
+            .iter()
+            .enumerate()
+            .map(|(index, wins)| format!("P{} {}", index + 1, wins))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!("Round {}   First to {}   {}", game.round, WINS_NEEDED, wins)
+    };
+    let keys = if solo {
+        "Arrows or WASD: move   Space or Right Shift: bomb   \u{25CF} bombs  \u{2605} range  \u{25B2} speed"
+    } else {
+        "P1 WASD, Space   P2 arrows, RShift   P3 IJKL, O   P4 numpad 8456, 0"
+    };
+    let (help, banner) = match state.current() {
+        GameState::Menu => {
+            let modes = Mode::ALL
+                .iter()
+                .map(|mode| {
+                    let marker = if *mode == game.mode { ">" } else { " " };
+                    format!("{} {:<18}", marker, mode.name())
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Up/Down: choose   Enter: start".to_string(),
+                format!("BOMBER\n\n{}", modes),
+            )
+        }
+        GameState::Playing => (keys.to_string(), String::new()),
+        GameState::RoundOver => {
+            let headline = if solo && game.players[0].alive {
+                format!("STAGE {} CLEAR", game.round)
+            } else if solo {
+                format!("CAUGHT\n\n{} lives left", game.lives)
+            } else if let Some(winner) = game.round_winner {
+                format!("P{} WINS ROUND {}", winner + 1, game.round)
+            } else {
+                format!("ROUND {} IS A DRAW", game.round)
+            };
+            ("Enter: go on".to_string(), headline)
+        }
+        GameState::Over => {
+            let banner = match game.champion() {
+                Some(champion) if !solo => format!("P{} WINS THE BATTLE", champion + 1),
+                _ => {
+                    let scores = scores
+                        .table("")
+                        .iter()
+                        .take(LEADERBOARD_SIZE)
+                        .enumerate()
+                        .map(|(place, entry)| format!("{}. {:>7}", place + 1, entry.score))
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    format!("GAME OVER\n\nScore {}\n\n{}", game.score, scores)
+                }
+            };
+            ("Enter: menu".to_string(), banner)
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
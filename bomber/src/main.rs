@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use bomber::BomberPlugin;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(bomber::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(BomberPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,721 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use rand::{thread_rng, Rng};
+use swarm::{Centipede, Mushrooms, MUSHROOM_HEALTH};
+
+pub mod swarm;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.02, 0.02, 0.04);
+const BULLET_COLOR: Color = Color::rgb(1.0, 1.0, 0.6);
+const BULLET_SPEED: f32 = 900.0;
+/// Side of a cell in pixels.
+const CELL: f32 = 18.0;
+/// Cells across and up the field.
+const COLUMNS: i32 = 30;
+/// Points that earn another life.
+const EXTRA_LIFE: u32 = 12_000;
+const FIELD_CENTER: Vec2 = const_vec2!([0.0, -12.0]);
+const FLEA_COLOR: Color = Color::rgb(0.95, 0.55, 0.9);
+/// Fewer mushrooms than this in the player's rows bring in a flea.
+const FLEA_MUSHROOMS: usize = 5;
+const FLEA_SPEED: f32 = 240.0;
+const GAME: &str = "centipede";
+const HEAD_COLOR: Color = Color::rgb(0.95, 0.3, 0.25);
+const LEADERBOARD_SIZE: usize = 5;
+const LIVES: u32 = 3;
+/// Mushroom colors, one wave after another.
+const MUSHROOM_COLORS: [Color; 4] = [
+    Color::rgb(0.9, 0.45, 0.15),
+    Color::rgb(0.3, 0.75, 0.9),
+    Color::rgb(0.85, 0.3, 0.6),
+    Color::rgb(0.6, 0.85, 0.3),
+];
+/// Mushrooms scattered over a new field.
+const MUSHROOMS: usize = 45;
+const PLAYER_COLOR: Color = Color::rgb(0.95, 0.95, 1.0);
+const PLAYER_SPEED: f32 = 260.0;
+const ROWS: i32 = 32;
+const SEGMENT_COLOR: Color = Color::rgb(0.3, 0.85, 0.35);
+/// Segments of a new centipede.
+const SEGMENTS: usize = 12;
+const SPIDER_COLOR: Color = Color::rgb(0.75, 0.4, 1.0);
+/// Seconds per cell for the centipede on the first wave.
+const STEP_TIME: f32 = 0.07;
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+/// Rows at the bottom the player moves in and the centipede comes back up through.
+const ZONE_ROWS: i32 = 6;
+
+pub const WINDOW_HEIGHT: f32 = 660.0;
+pub const WINDOW_WIDTH: f32 = 600.0;
+
+/// Everything drawn on the field, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+/// Falls straight down, leaving mushrooms behind, and takes two shots.
+struct Flea {
+    hits: u8,
+    position: Vec2,
+}
+
+struct Game {
+    bullet: Option<Vec2>,
+    centipede: Centipede,
+    flea: Option<Flea>,
+    /// Where the mouse was last frame; the player only follows it when it moves.
+    last_cursor: Option<Vec2>,
+    lives: u32,
+    mushrooms: Mushrooms,
+    player: Vec2,
+    score: u32,
+    spider: Option<Spider>,
+    /// Seconds until the next spider.
+    spider_wait: f32,
+    /// Seconds into the centipede's current step.
+    step: f32,
+    wave: u32,
+}
+
+impl Game {
+    fn new() -> Self {
+        Self {
+            bullet: None,
+            centipede: Centipede::new(SEGMENTS, ROWS - 1),
+            flea: None,
+            last_cursor: None,
+            lives: LIVES,
+            mushrooms: Mushrooms::scatter(
+                COLUMNS,
+                ROWS,
+                MUSHROOMS,
+                (ZONE_ROWS, ROWS - 1),
+                &mut thread_rng(),
+            ),
+            player: player_start(),
+            score: 0,
+            spider: None,
+            spider_wait: 5.0,
+            step: 0.0,
+            wave: 1,
+        }
+    }
+
+    /// Adds points, with another life for every `EXTRA_LIFE` of them.
+    fn add_score(&mut self, points: u32) {
+        if (self.score + points) / EXTRA_LIFE > self.score / EXTRA_LIFE {
+            self.lives += 1;
+        }
+        self.score += points;
+    }
+
+    /// Where each segment is drawn, part way along its current step.
+    fn segment_positions(&self) -> Vec<(usize, usize, Vec2)> {
+        let progress = (self.step / self.step_time()).min(1.0);
+        let mut positions = Vec::new();
+        for (train, segments) in self.centipede.trains.iter().enumerate() {
+            for (index, segment) in segments.iter().enumerate() {
+                let from = cell_center(segment.from);
+                positions.push((
+                    train,
+                    index,
+                    from + (cell_center(segment.cell) - from) * progress,
+                ));
+            }
+        }
+        positions
+    }
+
+    fn step_time(&self) -> f32 {
+        (STEP_TIME * 0.92_f32.powi(self.wave as i32 - 1)).max(0.035)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The result at the end.
+#[derive(Component)]
+struct MenuText;
+
+struct PlayerHitEvent;
+
+struct Sounds {
+    fire: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    player_death: Handle<AudioSource>,
+    segment: Handle<AudioSource>,
+    wave: Handle<AudioSource>,
+}
+
+/// Zigzags through the bottom rows, eating the mushrooms it crosses.
+struct Spider {
+    position: Vec2,
+    velocity: Vec2,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Movement,
+    Collision,
+}
+
+pub struct CentipedePlugin;
+
+impl Plugin for CentipedePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_event::<PlayerHitEvent>()
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_input.label(Step::Movement))
+                    .with_system(bullet_movement.label(Step::Movement))
+                    .with_system(centipede_movement.label(Step::Movement))
+                    .with_system(critters_movement.label(Step::Movement))
+                    .with_system(collisions.label(Step::Collision).after(Step::Movement))
+                    .with_system(player_hit.after(Step::Collision))
+                    .with_system(next_wave.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(restart_input))
+            .add_system(field_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Centipede".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn bullet_movement(time: Res<Time>, mut game: ResMut<Game>) {
+    let top = FIELD_CENTER.y + ROWS as f32 * CELL / 2.0;
+    if let Some(bullet) = &mut game.bullet {
+        bullet.y += BULLET_SPEED * time.delta_seconds();
+        if bullet.y > top {
+            game.bullet = None;
+        }
+    }
+}
+
+fn cell_at(position: Vec2) -> (i32, i32) {
+    let offset = (position - field_bottom_left()) / CELL;
+    (offset.x.floor() as i32, offset.y.floor() as i32)
+}
+
+fn cell_center(cell: (i32, i32)) -> Vec2 {
+    field_bottom_left() + (Vec2::new(cell.0 as f32, cell.1 as f32) + 0.5) * CELL
+}
+
+fn centipede_movement(time: Res<Time>, mut game: ResMut<Game>) {
+    let game = &mut *game;
+    game.step += time.delta_seconds();
+    let step_time = game.step_time();
+    while game.step >= step_time {
+        game.step -= step_time;
+        game.centipede.step(&game.mushrooms, ZONE_ROWS);
+    }
+}
+
+/// The bullet against mushrooms, segments, the spider and the flea; then everything that can
+/// run into the player.
+fn collisions(
+    mut game: ResMut<Game>,
+    mut hit_writer: EventWriter<PlayerHitEvent>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    if let Some(bullet) = game.bullet {
+        let cell = cell_at(bullet);
+        let segment = game
+            .segment_positions()
+            .into_iter()
+            .find(|(_, _, position)| position.distance(bullet) < CELL * 0.6);
+        if let Some((train, index, _)) = segment {
+            let (cell, head) = game.centipede.shoot(train, index);
+            game.mushrooms.grow(cell);
+            game.add_score(if head { 100 } else { 10 });
+            game.bullet = None;
+            audio.play(sounds.segment.clone());
+        } else if game.mushrooms.at(cell) {
+            if game.mushrooms.hit(cell) {
+                game.add_score(1);
+            }
+            game.bullet = None;
+            audio.play(sounds.hit.clone());
+        } else if matches!(&game.spider, Some(spider) if spider.position.distance(bullet) < CELL * 0.8)
+        {
+            let spider = game.spider.take().unwrap();
+            // Closer to the player is worth more.
+            let distance = spider.position.distance(game.player) / CELL;
+            let points = if distance < 3.0 {
+                900
+            } else if distance < 6.0 {
+                600
+            } else {
+                300
+            };
+            game.add_score(points);
+            game.bullet = None;
+            audio.play(sounds.segment.clone());
+        } else if let Some(flea) = &mut game.flea {
+            if flea.position.distance(bullet) < CELL * 0.7 {
+                flea.hits += 1;
+                game.bullet = None;
+                if flea.hits == 2 {
+                    game.flea = None;
+                    game.add_score(200);
+                }
+                audio.play(sounds.hit.clone());
+            }
+        }
+    }
+
+    let player = game.player;
+    let mut caught = game
+        .segment_positions()
+        .iter()
+        .any(|(_, _, position)| position.distance(player) < CELL * 0.8);
+    if let Some(spider) = &game.spider {
+        caught |= spider.position.distance(player) < CELL;
+    }
+    if let Some(flea) = &game.flea {
+        caught |= flea.position.distance(player) < CELL * 0.8;
+    }
+    if caught {
+        hit_writer.send(PlayerHitEvent);
+    }
+}
+
+/// Brings in, moves and sends off the spider and the flea.
+fn critters_movement(time: Res<Time>, mut game: ResMut<Game>) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    let mut rng = thread_rng();
+    let left = field_bottom_left().x;
+    let right = left + COLUMNS as f32 * CELL;
+    let bottom = field_bottom_left().y + CELL / 2.0;
+    let ceiling = bottom + (ZONE_ROWS + 1) as f32 * CELL;
+
+    game.spider_wait -= delta;
+    if game.spider.is_none() && game.spider_wait <= 0.0 {
+        let from_left = rng.gen_bool(0.5);
+        game.spider = Some(Spider {
+            position: Vec2::new(
+                if from_left { left - CELL } else { right + CELL },
+                rng.gen_range(bottom, ceiling),
+            ),
+            velocity: Vec2::new(
+                rng.gen_range(60.0, 110.0) * if from_left { 1.0 } else { -1.0 },
+                150.0,
+            ),
+        });
+    }
+    if let Some(spider) = &mut game.spider {
+        spider.position += spider.velocity * delta;
+        if spider.position.y < bottom || spider.position.y > ceiling {
+            spider.velocity.y = -spider.velocity.y;
+            spider.position.y = spider.position.y.clamp(bottom, ceiling);
+        }
+        game.mushrooms.remove(cell_at(spider.position));
+        if spider.position.x < left - 2.0 * CELL || spider.position.x > right + 2.0 * CELL {
+            game.spider = None;
+            game.spider_wait = rng.gen_range(3.0, 8.0);
+        }
+    }
+
+    let zone_mushrooms = game
+        .mushrooms
+        .cells()
+        .filter(|((_, row), _)| *row < ZONE_ROWS)
+        .count();
+    let flea_due = rng.gen_bool((delta as f64 * 0.5).min(1.0));
+    if game.flea.is_none() && game.wave > 1 && zone_mushrooms < FLEA_MUSHROOMS && flea_due {
+        let column = rng.gen_range(0, COLUMNS);
+        game.flea = Some(Flea {
+            hits: 0,
+            position: cell_center((column, ROWS)),
+        });
+    }
+    if let Some(flea) = &mut game.flea {
+        let before = cell_at(flea.position);
+        let speed = FLEA_SPEED * (1 + flea.hits) as f32;
+        flea.position.y -= speed * delta;
+        let cell = cell_at(flea.position);
+        if cell != before && cell.1 >= ZONE_ROWS && rng.gen_bool(0.3) {
+            game.mushrooms.grow(cell);
+        }
+        if flea.position.y < field_bottom_left().y {
+            game.flea = None;
+        }
+    }
+}
+
+fn field_bottom_left() -> Vec2 {
+    FIELD_CENTER - Vec2::new(COLUMNS as f32, ROWS as f32) * CELL / 2.0
+}
+
+/// The mushrooms, the centipede, the critters, the bullet and the player.
+fn field_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut sprite = |color: Color, size: Vec2, position: Vec2, z: f32| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(z)),
+                ..Default::default()
+            })
+            .insert(Actor);
+    };
+
+    let cap_color = MUSHROOM_COLORS[(game.wave as usize - 1) % MUSHROOM_COLORS.len()];
+    for (cell, health) in game.mushrooms.cells() {
+        let center = cell_center(cell);
+        // Shots eat the mushroom away from the bottom up.
+        let left = health as f32 / MUSHROOM_HEALTH as f32;
+        let stem = Vec2::new(CELL * 0.3, CELL * 0.45 * left);
+        sprite(
+            Color::rgb(0.9, 0.85, 0.75),
+            stem,
+            center + Vec2::new(0.0, -CELL / 2.0 + stem.y / 2.0 + CELL * 0.05),
+            1.0,
+        );
+        let cap = Vec2::new(CELL * 0.85, CELL * 0.45);
+        sprite(
+            cap_color,
+            Vec2::new(cap.x, cap.y * left.max(0.5)),
+            center + Vec2::new(0.0, CELL * 0.15),
+            2.0,
+        );
+    }
+
+    for (train, index, position) in game.segment_positions() {
+        let segment = game.centipede.trains[train][index];
+        let color = if index == 0 {
+            HEAD_COLOR
+        } else {
+            SEGMENT_COLOR
+        };
+        sprite(color, Vec2::splat(CELL * 0.85), position, 3.0);
+        if index == 0 {
+            // Eyes looking the way it goes.
+            let ahead = segment.across as f32 * CELL * 0.2;
+            for y in [-0.15, 0.15] {
+                sprite(
+                    Color::BLACK,
+                    Vec2::splat(3.0),
+                    position + Vec2::new(ahead, y * CELL),
+                    4.0,
+                );
+            }
+        }
+    }
+
+    if let Some(bullet) = game.bullet {
+        sprite(BULLET_COLOR, Vec2::new(2.0, 10.0), bullet, 5.0);
+    }
+    if *state.current() == GameState::Over {
+        return;
+    }
+    let glyphs = [
+        (
+            game.spider.as_ref().map(|spider| spider.position),
+            "\u{2715}",
+            SPIDER_COLOR,
+            30.0,
+        ),
+        (
+            game.flea.as_ref().map(|flea| flea.position),
+            "\u{25C6}",
+            FLEA_COLOR,
+            22.0,
+        ),
+        (Some(game.player), "\u{25B2}", PLAYER_COLOR, 22.0),
+    ];
+    for (position, glyph, color, size) in glyphs {
+        if let Some(position) = position {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    glyph,
+                    size,
+                    color,
+                    position.extend(6.0),
+                ))
+                .insert(Actor);
+        }
+    }
+}
+
+fn game_over(
+    game: Res<Game>,
+    mut scores: ResMut<Scores>,
+    platform: Res<Platform>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.player_death.clone());
+    if scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// Once the centipede is all shot, a new one comes in a little faster.
+fn next_wave(mut game: ResMut<Game>, audio: Res<Audio>, sounds: Res<Sounds>) {
+    if !game.centipede.is_empty() {
+        return;
+    }
+    game.wave += 1;
+    game.centipede = Centipede::new(SEGMENTS, ROWS - 1);
+    game.step = 0.0;
+    audio.play(sounds.wave.clone());
+}
+
+fn player_hit(
+    mut events: EventReader<PlayerHitEvent>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    game.lives -= 1;
+    if game.lives == 0 {
+        state.set(GameState::Over).unwrap();
+        return;
+    }
+    audio.play(sounds.player_death.clone());
+    // Damaged mushrooms grow back, worth a few points each, and the centipede starts over
+    // from the top with the segments it has left.
+    let restored = game.mushrooms.restore() as u32;
+    game.add_score(restored * 5);
+    let segments = game.centipede.len();
+    game.centipede = Centipede::new(segments, ROWS - 1);
+    game.step = 0.0;
+    game.bullet = None;
+    game.spider = None;
+    game.flea = None;
+    game.player = player_start();
+}
+
+/// Arrow keys or WASD move and Space fires; the player also follows the mouse, which fires
+/// with its left button.
+fn player_input(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let mut direction = Vec2::ZERO;
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        direction.x -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        direction.x += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Up, KeyCode::W]) {
+        direction.y += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Down, KeyCode::S]) {
+        direction.y -= 1.0;
+    }
+    let mut target =
+        game.player + direction.normalize_or_zero() * PLAYER_SPEED * time.delta_seconds();
+    let cursor = game_hud::cursor_world_position(&windows);
+    if let (Some(cursor), Some(last)) = (cursor, game.last_cursor) {
+        if cursor != last {
+            target = cursor;
+        }
+    }
+    game.last_cursor = cursor;
+    let low = field_bottom_left() + CELL / 2.0;
+    let high = Vec2::new(
+        low.x + (COLUMNS - 1) as f32 * CELL,
+        low.y + (ZONE_ROWS - 1) as f32 * CELL,
+    );
+    game.player = target.clamp(low, high);
+
+    let fire = keyboard_input.pressed(KeyCode::Space) || mouse_input.pressed(MouseButton::Left);
+    if fire && game.bullet.is_none() {
+        game.bullet = Some(game.player + Vec2::new(0.0, CELL / 2.0));
+        audio.play(sounds.fire.clone());
+    }
+}
+
+fn player_start() -> Vec2 {
+    cell_center((COLUMNS / 2, 0))
+}
+
+fn restart_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(6.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 40.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        fire: audio_sources.add(
+            Tone::new(Waveform::Square, 1200.0, 0.03)
+                .fade()
+                .volume(0.08)
+                .into(),
+        ),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.03)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        player_death: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 400.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 300.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 200.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.4).fade().volume(0.3),
+        ])),
+        segment: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 660.0, 0.04).volume(0.15),
+            Tone::new(Waveform::Noise, 0.0, 0.06).fade().volume(0.2),
+        ])),
+        wave: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.2).fade(),
+        ])),
+    });
+}
+
+fn texts_update(
+    game: Res<Game>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let hud = format!(
+        "Score {}   High {}   Lives {}   Wave {}",
+        game.score,
+        scores
+            .best("")
+            .map_or(0, |entry| entry.score)
+            .max(game.score),
+        game.lives,
+        game.wave
+    );
+    let (help, banner) = match state.current() {
+        GameState::Playing => (
+            "Arrows/WASD or mouse: move   Space or left click: fire".to_string(),
+            String::new(),
+        ),
+        GameState::Over => {
+            let scores = scores
+                .table("")
+                .iter()
+                .take(LEADERBOARD_SIZE)
+                .enumerate()
+                .map(|(place, entry)| format!("{}. {:>7}", place + 1, entry.score))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Enter: new game".to_string(),
+                format!("GAME OVER\n\nScore {}\n\n{}", game.score, scores),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use centipede::CentipedePlugin;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(centipede::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(CentipedePlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,281 @@
+//! The mushroom field and the centipede winding through it, on a grid with row 0 at the bottom.
+//! Every segment follows the same rules, so a train keeps together without any of them knowing
+//! where the others are.
+
+use rand::Rng;
+
+/// Hits a mushroom takes.
+pub const MUSHROOM_HEALTH: u8 = 4;
+
+pub struct Mushrooms {
+    /// Hits left per cell, 0 for none.
+    health: Vec<u8>,
+    pub columns: i32,
+    pub rows: i32,
+}
+
+impl Mushrooms {
+    /// A field with `count` mushrooms somewhere in `rows`, never on the same cell twice.
+    pub fn scatter(
+        columns: i32,
+        rows: i32,
+        count: usize,
+        between: (i32, i32),
+        rng: &mut impl Rng,
+    ) -> Self {
+        let mut mushrooms = Self {
+            health: vec![0; (columns * rows) as usize],
+            columns,
+            rows,
+        };
+        let mut placed = 0;
+        while placed < count {
+            let cell = (
+                rng.gen_range(0, columns),
+                rng.gen_range(between.0, between.1),
+            );
+            if !mushrooms.at(cell) {
+                mushrooms.grow(cell);
+                placed += 1;
+            }
+        }
+        mushrooms
+    }
+
+    pub fn at(&self, cell: (i32, i32)) -> bool {
+        self.health(cell) > 0
+    }
+
+    /// Every mushroom with the hits it has left.
+    pub fn cells(&self) -> impl Iterator<Item = ((i32, i32), u8)> + '_ {
+        let columns = self.columns;
+        self.health
+            .iter()
+            .enumerate()
+            .filter(|(_, health)| **health > 0)
+            .map(move |(index, health)| ((index as i32 % columns, index as i32 / columns), *health))
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        (0..self.columns).contains(&cell.0) && (0..self.rows).contains(&cell.1)
+    }
+
+    /// A fresh mushroom, if the cell is on the field.
+    pub fn grow(&mut self, cell: (i32, i32)) {
+        if let Some(index) = self.index(cell) {
+            self.health[index] = MUSHROOM_HEALTH;
+        }
+    }
+
+    pub fn health(&self, cell: (i32, i32)) -> u8 {
+        self.index(cell).map_or(0, |index| self.health[index])
+    }
+
+    /// Takes a hit off a mushroom; true if that was its last.
+    pub fn hit(&mut self, cell: (i32, i32)) -> bool {
+        match self.index(cell) {
+            Some(index) if self.health[index] > 0 => {
+                self.health[index] -= 1;
+                self.health[index] == 0
+            }
+            _ => false,
+        }
+    }
+
+    pub fn remove(&mut self, cell: (i32, i32)) {
+        if let Some(index) = self.index(cell) {
+            self.health[index] = 0;
+        }
+    }
+
+    /// Brings damaged mushrooms back to full health; returns how many there were.
+    pub fn restore(&mut self) -> usize {
+        let mut restored = 0;
+        for health in &mut self.health {
+            if (1..MUSHROOM_HEALTH).contains(health) {
+                *health = MUSHROOM_HEALTH;
+                restored += 1;
+            }
+        }
+        restored
+    }
+
+    fn index(&self, cell: (i32, i32)) -> Option<usize> {
+        if self.contains(cell) {
+            Some((cell.1 * self.columns + cell.0) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Segment {
+    pub cell: (i32, i32),
+    /// The cell before the last step, to draw the segment on its way.
+    pub from: (i32, i32),
+    /// -1 or 1 across.
+    pub across: i32,
+    /// -1 on the way down, 1 on the way back up through the bottom rows.
+    pub down: i32,
+}
+
+impl Segment {
+    /// One step across, or a row down and turning round when a mushroom or the side is in the
+    /// way. At the bottom it heads back up, but no higher than `floor_rows`.
+    pub fn step(&mut self, mushrooms: &Mushrooms, floor_rows: i32) {
+        self.from = self.cell;
+        let next = (self.cell.0 + self.across, self.cell.1);
+        if mushrooms.contains(next) && !mushrooms.at(next) {
+            self.cell = next;
+            return;
+        }
+        if self.cell.1 == 0 {
+            self.down = 1;
+        } else if self.down == 1 && self.cell.1 >= floor_rows - 1 {
+            self.down = -1;
+        }
+        self.cell.1 += self.down;
+        self.across = -self.across;
+    }
+}
+
+/// The centipede as trains of segments, each led by a head.
+#[derive(Default)]
+pub struct Centipede {
+    pub trains: Vec<Vec<Segment>>,
+}
+
+impl Centipede {
+    /// A single train of `length` segments entering the top row from the left.
+    pub fn new(length: usize, top: i32) -> Self {
+        let train = (0..length as i32)
+            .map(|index| Segment {
+                cell: (-index, top),
+                from: (-index - 1, top),
+                across: 1,
+                down: -1,
+            })
+            .collect();
+        Self {
+            trains: vec![train],
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.trains.is_empty()
+    }
+
+    /// Segments left in all trains.
+    pub fn len(&self) -> usize {
+        self.trains.iter().map(Vec::len).sum()
+    }
+
+    /// Shoots segment `index` of train `train`: the segments behind it go on as a train of their
+    /// own. Returns the cell it was on and whether it was a head.
+    pub fn shoot(&mut self, train: usize, index: usize) -> ((i32, i32), bool) {
+        let behind = self.trains[train].split_off(index + 1);
+        let shot = self.trains[train].pop().unwrap();
+        if !behind.is_empty() {
+            self.trains.push(behind);
+        }
+        self.trains.retain(|train| !train.is_empty());
+        (shot.cell, index == 0)
+    }
+
+    pub fn step(&mut self, mushrooms: &Mushrooms, floor_rows: i32) {
+        for segment in self.trains.iter_mut().flatten() {
+            // Segments still coming in from off the field only move across.
+            if segment.cell.0 < 0 {
+                segment.from = segment.cell;
+                segment.cell.0 += 1;
+            } else {
+                segment.step(mushrooms, floor_rows);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn empty(columns: i32, rows: i32) -> Mushrooms {
+        Mushrooms::scatter(columns, rows, 0, (0, rows), &mut StdRng::seed_from_u64(1))
+    }
+
+    #[test]
+    fn mushrooms_take_hits_and_regrow() {
+        let mut mushrooms = Mushrooms::scatter(10, 10, 12, (2, 8), &mut StdRng::seed_from_u64(4));
+        assert_eq!(mushrooms.cells().count(), 12);
+        assert!(mushrooms.cells().all(|((_, row), _)| (2..8).contains(&row)));
+        let (cell, _) = mushrooms.cells().next().unwrap();
+        assert!(!mushrooms.hit(cell));
+        assert!(!mushrooms.hit(cell));
+        assert_eq!(mushrooms.health(cell), 2);
+        assert_eq!(mushrooms.restore(), 1);
+        assert_eq!(mushrooms.health(cell), MUSHROOM_HEALTH);
+        for _ in 0..MUSHROOM_HEALTH - 1 {
+            mushrooms.hit(cell);
+        }
+        assert!(mushrooms.hit(cell));
+        assert!(!mushrooms.at(cell));
+        assert!(!mushrooms.hit((-1, 0)));
+    }
+
+    #[test]
+    fn segments_turn_at_mushrooms_and_sides() {
+        let mut mushrooms = empty(6, 6);
+        mushrooms.grow((3, 5));
+        let mut segment = Segment {
+            cell: (1, 5),
+            from: (0, 5),
+            across: 1,
+            down: -1,
+        };
+        segment.step(&mushrooms, 2);
+        assert_eq!(segment.cell, (2, 5));
+        segment.step(&mushrooms, 2);
+        assert_eq!((segment.cell, segment.across), ((2, 4), -1));
+        for _ in 0..3 {
+            segment.step(&mushrooms, 2);
+        }
+        assert_eq!((segment.cell, segment.across), ((0, 3), 1));
+    }
+
+    #[test]
+    fn segments_bounce_in_the_bottom_rows() {
+        let mushrooms = empty(2, 6);
+        let mut segment = Segment {
+            cell: (1, 1),
+            from: (0, 1),
+            across: 1,
+            down: -1,
+        };
+        let rows: Vec<i32> = (0..5)
+            .map(|_| {
+                segment.step(&mushrooms, 3);
+                segment.step(&mushrooms, 3);
+                segment.cell.1
+            })
+            .collect();
+        assert_eq!(rows, [0, 1, 2, 1, 0]);
+    }
+
+    #[test]
+    fn shooting_splits_a_train() {
+        let mut centipede = Centipede::new(5, 9);
+        assert_eq!(centipede.shoot(0, 2), ((-2, 9), false));
+        assert_eq!(centipede.trains.len(), 2);
+        assert_eq!(centipede.trains[0].len(), 2);
+        assert_eq!(centipede.trains[1][0].cell, (-3, 9));
+        assert!(centipede.shoot(1, 0).1);
+        assert_eq!(centipede.len(), 3);
+        centipede.shoot(1, 0);
+        assert_eq!(centipede.trains.len(), 1);
+        let mushrooms = empty(10, 10);
+        centipede.step(&mushrooms, 3);
+        assert_eq!(centipede.trains[0][1].cell, (0, 9));
+    }
+}
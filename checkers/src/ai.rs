@@ -0,0 +1,153 @@
+//! Minimax with alpha-beta pruning over material, advancement and a guarded back row.
+
+use crate::draughts::{self, Move, Position, Side, DRAW_PLIES};
+use rand::{seq::SliceRandom, thread_rng};
+
+const KING: i32 = 160;
+const MAN: i32 = 100;
+/// Forced jumps are followed past the depth limit, but never deeper than this.
+const MAX_PLY: i32 = 40;
+const WIN: i32 = 100_000;
+
+/// Search depth in plies per difficulty level.
+pub const LEVELS: [u32; 4] = [1, 3, 6, 9];
+
+/// Score of the position for the side to move.
+pub fn evaluate(position: &Position) -> i32 {
+    let mut score = 0;
+    for (square, piece) in position.squares.iter().enumerate() {
+        let piece = match piece {
+            Some(piece) => piece,
+            None => continue,
+        };
+        let square = square as u8;
+        // Rows advanced from the own back row.
+        let advance = match piece.side {
+            Side::Red => draughts::row(square),
+            Side::White => 7 - draughts::row(square),
+        } as i32;
+        let points = if piece.king {
+            let column = draughts::column(square) as i32;
+            let row = draughts::row(square) as i32;
+            // Kings are strongest in the middle of the board.
+            KING - ((2 * column - 7).abs() + (2 * row - 7).abs())
+        } else if advance == 0 {
+            // Men left on the back row keep the opponent from crowning.
+            MAN + 10
+        } else {
+            MAN + advance * 4
+        };
+        score += if piece.side == position.side {
+            points
+        } else {
+            -points
+        };
+    }
+    score
+}
+
+/// The best move found at `depth`, picking randomly between equally good moves.
+pub fn best_move(position: &Position, depth: u32) -> Option<Move> {
+    let mut moves = position.legal_moves();
+    moves.shuffle(&mut thread_rng());
+    let mut best = None;
+    let mut alpha = -WIN - 1;
+    for mv in moves {
+        let score = -search(
+            &position.play(&mv),
+            depth.saturating_sub(1),
+            -WIN - 1,
+            -alpha,
+            1,
+        );
+        if score > alpha {
+            alpha = score;
+            best = Some(mv);
+        }
+    }
+    best
+}
+
+fn search(position: &Position, depth: u32, mut alpha: i32, beta: i32, ply: i32) -> i32 {
+    let mut moves = position.legal_moves();
+    if moves.is_empty() {
+        // Quicker wins score higher, so the AI does not dawdle with a won game.
+        return -WIN + ply;
+    }
+    if position.quiet_plies >= DRAW_PLIES {
+        return 0;
+    }
+    // Jumps are mandatory, so stopping in the middle of an exchange would misjudge the position.
+    if (depth == 0 && moves[0].captured.is_empty()) || ply >= MAX_PLY {
+        return evaluate(position);
+    }
+    // Longer jumps first, as they are the likeliest to cut the search short.
+    moves.sort_by_key(|mv| std::cmp::Reverse(mv.captured.len()));
+    for mv in moves {
+        let score = -search(
+            &position.play(&mv),
+            depth.saturating_sub(1),
+            -beta,
+            -alpha,
+            ply + 1,
+        );
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draughts::Piece;
+
+    fn position(pieces: &[(u8, Side)], side: Side) -> Position {
+        let mut squares = [None; 64];
+        for (square, piece_side) in pieces {
+            squares[*square as usize] = Some(Piece {
+                king: false,
+                side: *piece_side,
+            });
+        }
+        Position {
+            quiet_plies: 0,
+            side,
+            squares,
+        }
+    }
+
+    #[test]
+    fn start_is_balanced() {
+        assert_eq!(evaluate(&Position::start()), 0);
+    }
+
+    #[test]
+    fn avoids_giving_away_a_piece() {
+        // Red on c3 can go to b4 or d4; on d4 White's e5 man jumps it for free.
+        let position = position(&[(18, Side::Red), (36, Side::White)], Side::Red);
+        for _ in 0..10 {
+            let mv = best_move(&position, 3).unwrap();
+            assert_eq!(mv.to(), 25);
+        }
+    }
+
+    #[test]
+    fn prefers_the_double_jump() {
+        // a1 can take b2, or e1 can take f2 and f4 in one turn.
+        let position = position(
+            &[
+                (0, Side::Red),
+                (4, Side::Red),
+                (9, Side::White),
+                (13, Side::White),
+                (29, Side::White),
+            ],
+            Side::Red,
+        );
+        let mv = best_move(&position, 1).unwrap();
+        assert_eq!(mv.path, vec![4, 22, 36]);
+    }
+}
@@ -0,0 +1,351 @@
+//! English draughts on an 8x8 board: men move and capture diagonally forward, kings in all four directions.
+
+/// Plies without a capture or a man moving after which the game is drawn (40 moves per player).
+pub const DRAW_PLIES: u32 = 80;
+
+const KING_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (-1, 1), (1, -1), (-1, -1)];
+const RED_DIRECTIONS: [(i8, i8); 2] = [(1, 1), (-1, 1)];
+const WHITE_DIRECTIONS: [(i8, i8); 2] = [(1, -1), (-1, -1)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// Starts at the bottom and moves first.
+    Red,
+    White,
+}
+
+impl Side {
+    /// The row where men of this side are crowned.
+    pub fn crown_row(self) -> i8 {
+        match self {
+            Self::Red => 7,
+            Self::White => 0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::White => "White",
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Red => Self::White,
+            Self::White => Self::Red,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub king: bool,
+    pub side: Side,
+}
+
+impl Piece {
+    fn directions(self) -> &'static [(i8, i8)] {
+        match (self.king, self.side) {
+            (true, _) => &KING_DIRECTIONS,
+            (false, Side::Red) => &RED_DIRECTIONS,
+            (false, Side::White) => &WHITE_DIRECTIONS,
+        }
+    }
+}
+
+/// A whole turn: a single step, or a jump with every square landed on along the way.
+/// Squares are numbered `row * 8 + column` from the bottom left corner.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Move {
+    pub captured: Vec<u8>,
+    pub path: Vec<u8>,
+}
+
+impl Move {
+    pub fn from(&self) -> u8 {
+        self.path[0]
+    }
+
+    pub fn to(&self) -> u8 {
+        self.path[self.path.len() - 1]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Draw,
+    Win(Side),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    /// Plies since the last capture or man move, for the draw rule.
+    pub quiet_plies: u32,
+    pub side: Side,
+    pub squares: [Option<Piece>; 64],
+}
+
+pub fn column(square: u8) -> i8 {
+    (square % 8) as i8
+}
+
+pub fn row(square: u8) -> i8 {
+    (square / 8) as i8
+}
+
+/// Pieces only ever stand on the dark squares, which include the bottom left corner.
+pub fn is_dark(square: u8) -> bool {
+    (column(square) + row(square)) % 2 == 0
+}
+
+fn offset(square: u8, (column_step, row_step): (i8, i8)) -> Option<u8> {
+    let (column, row) = (column(square) + column_step, row(square) + row_step);
+    if (0..8).contains(&column) && (0..8).contains(&row) {
+        Some((row * 8 + column) as u8)
+    } else {
+        None
+    }
+}
+
+impl Position {
+    pub fn start() -> Self {
+        let mut squares = [None; 64];
+        for square in (0..64).filter(|square| is_dark(*square)) {
+            let side = match row(square) {
+                0..=2 => Side::Red,
+                5..=7 => Side::White,
+                _ => continue,
+            };
+            squares[square as usize] = Some(Piece { king: false, side });
+        }
+        Self {
+            quiet_plies: 0,
+            side: Side::Red,
+            squares,
+        }
+    }
+
+    /// Men and kings of `side`.
+    pub fn count(&self, side: Side) -> (usize, usize) {
+        let pieces = self
+            .squares
+            .iter()
+            .flatten()
+            .filter(|piece| piece.side == side);
+        pieces.fold((0, 0), |(men, kings), piece| {
+            if piece.king {
+                (men, kings + 1)
+            } else {
+                (men + 1, kings)
+            }
+        })
+    }
+
+    /// Every move of the side to move; captures are mandatory, so only jumps are returned if there are any.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        let mut jumps = Vec::new();
+        let mut steps = Vec::new();
+        for from in 0..64u8 {
+            let piece = match self.squares[from as usize] {
+                Some(piece) if piece.side == self.side => piece,
+                _ => continue,
+            };
+            self.jumps(piece, &mut vec![from], &mut Vec::new(), &mut jumps);
+            if !jumps.is_empty() {
+                continue;
+            }
+            for direction in piece.directions() {
+                if let Some(to) = offset(from, *direction) {
+                    if self.squares[to as usize].is_none() {
+                        steps.push(Move {
+                            captured: Vec::new(),
+                            path: vec![from, to],
+                        });
+                    }
+                }
+            }
+        }
+        if jumps.is_empty() {
+            steps
+        } else {
+            jumps
+        }
+    }
+
+    /// Follows every jump sequence from the end of `path`; a sequence only ends when no jump is left.
+    fn jumps(
+        &self,
+        piece: Piece,
+        path: &mut Vec<u8>,
+        captured: &mut Vec<u8>,
+        moves: &mut Vec<Move>,
+    ) {
+        let here = path[path.len() - 1];
+        // A man reaching the far row is crowned, which ends its move.
+        let crowned = !piece.king && path.len() > 1 && row(here) == piece.side.crown_row();
+        let mut extended = false;
+        if !crowned {
+            for direction in piece.directions() {
+                let (over, landing) = match (
+                    offset(here, *direction),
+                    offset(here, (direction.0 * 2, direction.1 * 2)),
+                ) {
+                    (Some(over), Some(landing)) => (over, landing),
+                    _ => continue,
+                };
+                let enemy =
+                    self.squares[over as usize].map_or(false, |other| other.side != piece.side);
+                // The jumping piece has left its starting square, so it may land there again.
+                let free = self.squares[landing as usize].is_none() || landing == path[0];
+                if enemy && free && !captured.contains(&over) {
+                    path.push(landing);
+                    captured.push(over);
+                    self.jumps(piece, path, captured, moves);
+                    path.pop();
+                    captured.pop();
+                    extended = true;
+                }
+            }
+        }
+        if !extended && path.len() > 1 {
+            moves.push(Move {
+                captured: captured.clone(),
+                path: path.clone(),
+            });
+        }
+    }
+
+    /// The side to move loses when it has no legal move left, blocked or out of pieces.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if self.legal_moves().is_empty() {
+            Some(Outcome::Win(self.side.opposite()))
+        } else if self.quiet_plies >= DRAW_PLIES {
+            Some(Outcome::Draw)
+        } else {
+            None
+        }
+    }
+
+    /// The position after a move returned by `legal_moves`.
+    pub fn play(&self, mv: &Move) -> Position {
+        let mut next = self.clone();
+        let mut piece = match next.squares[mv.from() as usize].take() {
+            Some(piece) => piece,
+            None => return next,
+        };
+        for square in &mv.captured {
+            next.squares[*square as usize] = None;
+        }
+        let quiet = piece.king && mv.captured.is_empty();
+        if row(mv.to()) == piece.side.crown_row() {
+            piece.king = true;
+        }
+        next.squares[mv.to() as usize] = Some(piece);
+        next.quiet_plies = if quiet { self.quiet_plies + 1 } else { 0 };
+        next.side = self.side.opposite();
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(column: i8, row: i8) -> u8 {
+        (row * 8 + column) as u8
+    }
+
+    fn empty(side: Side) -> Position {
+        Position {
+            quiet_plies: 0,
+            side,
+            squares: [None; 64],
+        }
+    }
+
+    fn place(position: &mut Position, column: i8, row: i8, side: Side, king: bool) {
+        position.squares[square(column, row) as usize] = Some(Piece { king, side });
+    }
+
+    #[test]
+    fn start_position() {
+        let start = Position::start();
+        assert_eq!(start.count(Side::Red), (12, 0));
+        assert_eq!(start.count(Side::White), (12, 0));
+        assert_eq!(start.legal_moves().len(), 7);
+        assert_eq!(start.outcome(), None);
+    }
+
+    #[test]
+    fn captures_are_mandatory() {
+        let mut position = empty(Side::Red);
+        place(&mut position, 2, 2, Side::Red, false);
+        place(&mut position, 3, 3, Side::White, false);
+        place(&mut position, 6, 2, Side::Red, false);
+        let moves = position.legal_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].path, vec![square(2, 2), square(4, 4)]);
+        assert_eq!(moves[0].captured, vec![square(3, 3)]);
+    }
+
+    #[test]
+    fn multi_jump_takes_every_piece() {
+        let mut position = empty(Side::Red);
+        place(&mut position, 0, 0, Side::Red, false);
+        place(&mut position, 1, 1, Side::White, false);
+        place(&mut position, 3, 3, Side::White, false);
+        let moves = position.legal_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(
+            moves[0].path,
+            vec![square(0, 0), square(2, 2), square(4, 4)]
+        );
+        let next = position.play(&moves[0]);
+        assert_eq!(next.count(Side::White), (0, 0));
+        assert_eq!(next.outcome(), Some(Outcome::Win(Side::Red)));
+    }
+
+    #[test]
+    fn men_do_not_capture_backwards_but_kings_do() {
+        let mut position = empty(Side::Red);
+        place(&mut position, 4, 4, Side::Red, false);
+        place(&mut position, 3, 3, Side::White, false);
+        assert!(position
+            .legal_moves()
+            .iter()
+            .all(|mv| mv.captured.is_empty()));
+        place(&mut position, 4, 4, Side::Red, true);
+        let moves = position.legal_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].to(), square(2, 2));
+    }
+
+    #[test]
+    fn crowning_ends_the_move() {
+        let mut position = empty(Side::Red);
+        place(&mut position, 1, 5, Side::Red, false);
+        place(&mut position, 2, 6, Side::White, false);
+        // As a king it could go on jumping this piece, but the crowning ends the turn.
+        place(&mut position, 4, 6, Side::White, false);
+        let moves = position.legal_moves();
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].path, vec![square(1, 5), square(3, 7)]);
+        let next = position.play(&moves[0]);
+        assert_eq!(next.count(Side::Red), (0, 1));
+    }
+
+    #[test]
+    fn blocked_side_loses_and_quiet_kings_draw() {
+        let mut position = empty(Side::White);
+        place(&mut position, 0, 0, Side::White, false);
+        place(&mut position, 7, 7, Side::Red, false);
+        assert_eq!(position.outcome(), Some(Outcome::Win(Side::Red)));
+        let mut kings = empty(Side::Red);
+        place(&mut kings, 0, 0, Side::Red, true);
+        place(&mut kings, 7, 7, Side::White, true);
+        kings.quiet_plies = DRAW_PLIES - 1;
+        let mv = kings.legal_moves()[0].clone();
+        assert_eq!(kings.play(&mv).outcome(), Some(Outcome::Draw));
+    }
+}
@@ -0,0 +1,743 @@
+use bevy::{math::const_vec2, prelude::*};
+use draughts::{Move, Outcome, Position, Side};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+mod ai;
+pub mod draughts;
+
+/// The AI's move is shown no sooner than this, so a quick reply can still be followed.
+const AI_DELAY: f32 = 0.5;
+const BACKGROUND_COLOR: Color = Color::rgb(0.16, 0.15, 0.13);
+const BOARD_SIZE: f32 = 8.0 * SQUARE_SIZE;
+const DARK_SQUARE_COLOR: Color = Color::rgb(0.3, 0.45, 0.3);
+/// Search depth of the suggestion shown with hints on.
+const HINT_DEPTH: u32 = 4;
+const HINT_COLOR: Color = Color::rgb(0.3, 0.55, 0.85);
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const KING_FONT_SIZE: f32 = 34.0;
+const LAST_MOVE_COLOR: Color = Color::rgb(0.95, 0.85, 0.2);
+const LIGHT_SQUARE_COLOR: Color = Color::rgb(0.93, 0.87, 0.74);
+const MARGIN: f32 = 30.0;
+const OUTLINE_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const PIECE_FONT_SIZE: f32 = 76.0;
+const RED_PIECE_COLOR: Color = Color::rgb(0.8, 0.15, 0.12);
+const SELECTED_COLOR: Color = Color::rgb(0.35, 0.75, 0.3);
+const SQUARE_SIZE: f32 = 80.0;
+const WHITE_PIECE_COLOR: Color = Color::rgb(0.96, 0.94, 0.88);
+
+pub const WINDOW_HEIGHT: f32 = BOARD_SIZE + HUD_HEIGHT + 2.0 * MARGIN;
+pub const WINDOW_WIDTH: f32 = BOARD_SIZE + 2.0 * MARGIN;
+
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, WINDOW_HEIGHT / 2.0 - HUD_HEIGHT - BOARD_SIZE / 2.0]);
+
+/// Everything drawn for a game in progress, removed when returning to the menu.
+#[derive(Component)]
+struct BoardEntity;
+
+#[derive(Component)]
+struct BoardSquare(u8);
+
+/// The game in progress and every earlier position for undo.
+struct Game {
+    history: Vec<Position>,
+    level: usize,
+    moves: Vec<Move>,
+    opponent: Opponent,
+    outcome: Option<Outcome>,
+    position: Position,
+}
+
+impl Game {
+    fn new(opponent: Opponent, level: usize) -> Self {
+        Self {
+            history: Vec::new(),
+            level,
+            moves: Vec::new(),
+            opponent,
+            outcome: None,
+            position: Position::start(),
+        }
+    }
+
+    fn ai_side(&self) -> Option<Side> {
+        match self.opponent {
+            Opponent::Ai(side) => Some(side),
+            Opponent::Human => None,
+        }
+    }
+
+    /// White sits at the bottom when the AI plays red.
+    fn flipped(&self) -> bool {
+        self.ai_side() == Some(Side::Red)
+    }
+
+    fn human_to_move(&self) -> bool {
+        self.outcome.is_none() && self.ai_side() != Some(self.position.side)
+    }
+
+    fn play(&mut self, mv: Move) {
+        let next = self.position.play(&mv);
+        let previous = std::mem::replace(&mut self.position, next);
+        self.history.push(previous);
+        self.moves.push(mv);
+        self.outcome = self.position.outcome();
+    }
+
+    /// Takes moves back until a human is to move again, i.e. two plies against the AI.
+    fn undo(&mut self) -> bool {
+        let mut undone = false;
+        while let Some(previous) = self.history.pop() {
+            self.position = previous;
+            self.moves.pop();
+            undone = true;
+            if self.ai_side() != Some(self.position.side) {
+                break;
+            }
+        }
+        if undone {
+            self.outcome = None;
+        }
+        undone
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+/// With hints on, the pieces that can move and a suggested move are highlighted.
+struct Hints {
+    enabled: bool,
+    suggestion: Option<Move>,
+}
+
+#[derive(Component)]
+struct HudText;
+
+struct MenuChoice {
+    level: usize,
+    opponent: Opponent,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Opponent {
+    /// The AI plays the given side.
+    Ai(Side),
+    Human,
+}
+
+impl Opponent {
+    const ALL: [Opponent; 3] = [Self::Ai(Side::White), Self::Ai(Side::Red), Self::Human];
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Ai(Side::White) => "Play red against the computer",
+            Self::Ai(Side::Red) => "Play white against the computer",
+            Self::Human => "Two players at one board",
+        }
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct PieceGlyph;
+
+/// The squares of the move being entered: the piece, then every square landed on so far.
+#[derive(Default)]
+struct Selection(Vec<u8>);
+
+struct Sounds {
+    capture: Handle<AudioSource>,
+    crown: Handle<AudioSource>,
+    end: Handle<AudioSource>,
+    invalid: Handle<AudioSource>,
+    step: Handle<AudioSource>,
+}
+
+/// The AI searches on its own thread and leaves its move here when done.
+struct Thinking {
+    delay: Timer,
+    result: Option<Arc<Mutex<Option<Move>>>>,
+}
+
+pub struct CheckersPlugin;
+
+impl Plugin for CheckersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(MenuChoice {
+                level: 1,
+                opponent: Opponent::Ai(Side::White),
+            })
+            .insert_resource(Game::new(Opponent::Ai(Side::White), 1))
+            .insert_resource(Selection::default())
+            .insert_resource(Hints {
+                enabled: false,
+                suggestion: None,
+            })
+            .insert_resource(Thinking {
+                delay: Timer::from_seconds(AI_DELAY, false),
+                result: None,
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(despawn_all::<BoardEntity>)
+                    .with_system(menu_enter),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu)
+                    .with_system(despawn_all::<MenuText>)
+                    .with_system(board_spawn),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(mouse_input)
+                    .with_system(keyboard_input)
+                    .with_system(ai_turn)
+                    .with_system(hint_update)
+                    .with_system(pieces_render)
+                    .with_system(outcome_check),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(board_visuals)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Checkers".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Starts a search when the AI is to move and plays its move once the search thread is done.
+fn ai_turn(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut thinking: ResMut<Thinking>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.outcome.is_some() || game.ai_side() != Some(game.position.side) {
+        return;
+    }
+    if thinking.result.is_none() {
+        let result = Arc::new(Mutex::new(None));
+        let found = result.clone();
+        let position = game.position.clone();
+        let depth = ai::LEVELS[game.level];
+        thread::spawn(move || {
+            *found.lock().unwrap() = ai::best_move(&position, depth);
+        });
+        thinking.result = Some(result);
+        thinking.delay.reset();
+        return;
+    }
+    if !thinking.delay.tick(time.delta()).finished() {
+        return;
+    }
+    let found = thinking
+        .result
+        .as_ref()
+        .and_then(|result| result.lock().unwrap().take());
+    if let Some(mv) = found {
+        thinking.result = None;
+        play(&mut game, mv, &audio, &sounds);
+    }
+}
+
+fn board_spawn(mut commands: Commands, game: Res<Game>) {
+    for square in 0..64 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(SQUARE_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(
+                    square_center(square, game.flipped()).extend(0.0),
+                ),
+                ..Default::default()
+            })
+            .insert(BoardSquare(square))
+            .insert(BoardEntity);
+    }
+}
+
+/// Colors the squares: the last move, the move being entered with its next targets, and hints.
+fn board_visuals(
+    game: Res<Game>,
+    selection: Res<Selection>,
+    hints: Res<Hints>,
+    mut squares: Query<(&BoardSquare, &mut Sprite)>,
+) {
+    let moves = game.position.legal_moves();
+    let targets = next_squares(&moves, &selection.0);
+    let hinting = hints.enabled && selection.0.is_empty() && game.human_to_move();
+    for (BoardSquare(square), mut sprite) in squares.iter_mut() {
+        let base = if draughts::is_dark(*square) {
+            DARK_SQUARE_COLOR
+        } else {
+            LIGHT_SQUARE_COLOR
+        };
+        sprite.color = if selection.0.contains(square) {
+            mix(base, SELECTED_COLOR, 0.6)
+        } else if targets.contains(square) {
+            mix(base, SELECTED_COLOR, 0.35)
+        } else if hinting
+            && hints
+                .suggestion
+                .as_ref()
+                .map_or(false, |mv| mv.path.contains(square))
+        {
+            mix(base, HINT_COLOR, 0.6)
+        } else if hinting && moves.iter().any(|mv| mv.from() == *square) {
+            mix(base, HINT_COLOR, 0.3)
+        } else if game
+            .moves
+            .last()
+            .map_or(false, |mv| mv.path.contains(square))
+        {
+            mix(base, LAST_MOVE_COLOR, 0.4)
+        } else {
+            base
+        };
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let outcome = match game.outcome {
+        Some(outcome) => outcome,
+        None => return,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(BOARD_SIZE, 200.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(BOARD_CENTER.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText)
+        .insert(BoardEntity);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("{}\n\nEnter: menu   U: undo", outcome_text(outcome)),
+            28.0,
+            HUD_COLOR,
+            BOARD_CENTER.extend(100.0),
+        ))
+        .insert(OverlayText)
+        .insert(BoardEntity);
+}
+
+fn game_over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) && game.undo() {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Looks for a suggested move whenever it is a human's turn in a new position.
+fn hint_update(game: Res<Game>, mut hints: ResMut<Hints>) {
+    if game.is_changed() {
+        hints.suggestion = None;
+    }
+    if hints.enabled && hints.suggestion.is_none() && game.human_to_move() {
+        hints.suggestion = ai::best_move(&game.position, HINT_DEPTH);
+    }
+}
+
+fn hud_update(
+    game: Res<Game>,
+    hints: Res<Hints>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    if !game.is_changed() && !hints.is_changed() && !state.is_changed() {
+        return;
+    }
+    let value = if *state.current() == GameState::Menu {
+        String::new()
+    } else {
+        let pieces = |side: Side| {
+            let (men, kings) = game.position.count(side);
+            format!("{} {} + {}K", side.name().to_uppercase(), men, kings)
+        };
+        let status = match game.outcome {
+            Some(outcome) => outcome_text(outcome),
+            None if game.position.legal_moves()[0].captured.is_empty() => {
+                format!("{} to move", game.position.side.name())
+            }
+            None => format!("{} to move, must capture", game.position.side.name()),
+        };
+        format!(
+            "{}   {}   {}   HINTS {}",
+            status,
+            pieces(Side::Red),
+            pieces(Side::White),
+            if hints.enabled { "ON" } else { "OFF" }
+        )
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn keyboard_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    mut thinking: ResMut<Thinking>,
+    mut hints: ResMut<Hints>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) {
+        // A search still running belongs to the position being taken back.
+        thinking.result = None;
+        selection.0.clear();
+        game.undo();
+    } else if keyboard_input.just_pressed(KeyCode::H) {
+        hints.enabled = !hints.enabled;
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, choice: Res<MenuChoice>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&choice),
+            26.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    menu: Res<MenuInput>,
+    mut choice: ResMut<MenuChoice>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    mut thinking: ResMut<Thinking>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let index = Opponent::ALL
+        .iter()
+        .position(|opponent| *opponent == choice.opponent)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) {
+        choice.opponent = Opponent::ALL[index.saturating_sub(1)];
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.opponent = Opponent::ALL[(index + 1).min(Opponent::ALL.len() - 1)];
+    } else if menu.just_pressed(Navigate::Left) {
+        choice.level = choice.level.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Right) {
+        choice.level = (choice.level + 1).min(ai::LEVELS.len() - 1);
+    } else if menu.just_pressed(Navigate::Confirm) {
+        *game = Game::new(choice.opponent, choice.level);
+        selection.0.clear();
+        thinking.result = None;
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&choice);
+    }
+}
+
+fn menu_label(choice: &MenuChoice) -> String {
+    let mut label = "CHECKERS\n\n".to_string();
+    for opponent in Opponent::ALL {
+        let marker = if opponent == choice.opponent {
+            ">"
+        } else {
+            " "
+        };
+        label.push_str(&format!("{} {}\n", marker, opponent.description()));
+    }
+    label.push_str(&format!(
+        "\nComputer level < {} >\n\nUp/Down: opponent\nLeft/Right: level\nEnter: start",
+        choice.level + 1
+    ));
+    label
+}
+
+/// Blends `color` over `base` by `amount`.
+fn mix(base: Color, color: Color, amount: f32) -> Color {
+    Color::rgb(
+        base.r() + (color.r() - base.r()) * amount,
+        base.g() + (color.g() - base.g()) * amount,
+        base.b() + (color.b() - base.b()) * amount,
+    )
+}
+
+/// Moves are entered square by square: the piece, then each landing square of a jump.
+fn mouse_input(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.human_to_move() {
+        return;
+    }
+    if mouse_input.just_pressed(MouseButton::Right) {
+        selection.0.clear();
+        return;
+    }
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let square = match game_hud::cursor_world_position(&windows)
+        .and_then(|cursor| square_at(cursor, game.flipped()))
+    {
+        Some(square) => square,
+        None => return,
+    };
+    let moves = game.position.legal_moves();
+    let movable = moves.iter().any(|mv| mv.from() == square);
+    if next_squares(&moves, &selection.0).contains(&square) {
+        selection.0.push(square);
+        let matching: Vec<&Move> = moves
+            .iter()
+            .filter(|mv| mv.path.starts_with(&selection.0))
+            .collect();
+        // Once the squares so far leave only one way to go on, the rest of the jump is played too.
+        if let [mv] = matching.as_slice() {
+            let mv = (*mv).clone();
+            selection.0.clear();
+            play(&mut game, mv, &audio, &sounds);
+        }
+    } else if movable && selection.0.len() <= 1 {
+        selection.0 = vec![square];
+    } else if selection.0.len() <= 1 {
+        selection.0.clear();
+        if game.position.squares[square as usize]
+            .map_or(false, |piece| piece.side == game.position.side)
+        {
+            // An own piece that cannot move, usually because another one has to capture.
+            audio.play(sounds.invalid.clone());
+        }
+    } else {
+        audio.play(sounds.invalid.clone());
+    }
+}
+
+/// The squares that continue one of `moves` after the squares already entered.
+fn next_squares(moves: &[Move], entered: &[u8]) -> Vec<u8> {
+    if entered.is_empty() {
+        return Vec::new();
+    }
+    moves
+        .iter()
+        .filter(|mv| mv.path.len() > entered.len() && mv.path.starts_with(entered))
+        .map(|mv| mv.path[entered.len()])
+        .collect()
+}
+
+fn outcome_check(game: Res<Game>, mut state: ResMut<State<GameState>>) {
+    if game.outcome.is_some() {
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn outcome_text(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Win(side) => format!("{} wins", side.name()),
+        Outcome::Draw => "Draw, 40 moves without progress".to_string(),
+    }
+}
+
+/// Rebuilds the piece glyphs whenever the game changes: a colored disc with an outline, crowned for kings.
+fn pieces_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    glyphs: Query<Entity, With<PieceGlyph>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+    for entity in glyphs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (square, piece) in game.position.squares.iter().enumerate() {
+        let piece = match piece {
+            Some(piece) => piece,
+            None => continue,
+        };
+        let center = square_center(square as u8, game.flipped());
+        let (color, crown_color) = match piece.side {
+            Side::Red => (RED_PIECE_COLOR, WHITE_PIECE_COLOR),
+            Side::White => (WHITE_PIECE_COLOR, RED_PIECE_COLOR),
+        };
+        let mut glyphs = vec![
+            ('\u{25CF}', PIECE_FONT_SIZE, color, 1.0),
+            ('\u{25CB}', PIECE_FONT_SIZE, OUTLINE_COLOR, 1.1),
+        ];
+        if piece.king {
+            glyphs.push(('\u{265B}', KING_FONT_SIZE, crown_color, 1.2));
+        }
+        for (glyph, size, color, z) in glyphs {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    glyph.to_string(),
+                    size,
+                    color,
+                    center.extend(z),
+                ))
+                .insert(PieceGlyph)
+                .insert(BoardEntity);
+        }
+    }
+}
+
+fn play(game: &mut Game, mv: Move, audio: &Audio, sounds: &Sounds) {
+    let crowned = game.position.squares[mv.from() as usize].map_or(false, |piece| {
+        !piece.king && draughts::row(mv.to()) == piece.side.crown_row()
+    });
+    let capture = !mv.captured.is_empty();
+    game.play(mv);
+    let sound = if game.outcome.is_some() {
+        &sounds.end
+    } else if crowned {
+        &sounds.crown
+    } else if capture {
+        &sounds.capture
+    } else {
+        &sounds.step
+    };
+    audio.play(sound.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(MARGIN),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Click a piece, then where it goes   Right click: cancel   H: hints   U: undo   Esc: menu",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(6.0),
+            left: Val::Px(MARGIN),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        capture: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.12)
+                .fade()
+                .volume(0.45)
+                .into(),
+        ),
+        crown: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 659.0, 0.08),
+            Tone::new(Waveform::Triangle, 988.0, 0.15),
+        ])),
+        end: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.15),
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.4),
+        ])),
+        invalid: audio_sources.add(Tone::new(Waveform::Square, 160.0, 0.15).volume(0.15).into()),
+        step: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.05)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+fn square_at(point: Vec2, flipped: bool) -> Option<u8> {
+    let cell = ((point - BOARD_CENTER) / SQUARE_SIZE + Vec2::splat(4.0)).floor();
+    if cell.x < 0.0 || cell.y < 0.0 || cell.x >= 8.0 || cell.y >= 8.0 {
+        return None;
+    }
+    let (column, row) = if flipped {
+        (7 - cell.x as u8, 7 - cell.y as u8)
+    } else {
+        (cell.x as u8, cell.y as u8)
+    };
+    Some(row * 8 + column)
+}
+
+fn square_center(square: u8, flipped: bool) -> Vec2 {
+    let (column, row) = (
+        draughts::column(square) as f32,
+        draughts::row(square) as f32,
+    );
+    let cell = if flipped {
+        Vec2::new(7.0 - column, 7.0 - row)
+    } else {
+        Vec2::new(column, row)
+    };
+    BOARD_CENTER + (cell - Vec2::splat(3.5)) * SQUARE_SIZE
+}
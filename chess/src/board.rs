@@ -0,0 +1,699 @@
+//! Chess rules without any bevy dependency: positions, legal moves and algebraic notation.
+
+const BISHOP_DIRECTIONS: [(i8, i8); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KING_STEPS: [(i8, i8); 8] = [
+    (1, 0),
+    (-1, 0),
+    (0, 1),
+    (0, -1),
+    (1, 1),
+    (1, -1),
+    (-1, 1),
+    (-1, -1),
+];
+const KNIGHT_STEPS: [(i8, i8); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const ROOK_DIRECTIONS: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+pub const START_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+/// Castling rights in FEN order: white king side, white queen side, black king side, black queen side.
+const WHITE_KING_SIDE: usize = 0;
+const WHITE_QUEEN_SIDE: usize = 1;
+const BLACK_KING_SIDE: usize = 2;
+const BLACK_QUEEN_SIDE: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    White,
+    Black,
+}
+
+impl Side {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::White => "White",
+            Self::Black => "Black",
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::White => Self::Black,
+            Self::Black => Self::White,
+        }
+    }
+
+    /// Direction pawns of this side move in, as a rank offset.
+    fn forward(self) -> i8 {
+        match self {
+            Self::White => 1,
+            Self::Black => -1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King,
+}
+
+impl Kind {
+    pub const PROMOTIONS: [Kind; 4] = [Self::Queen, Self::Rook, Self::Bishop, Self::Knight];
+
+    pub fn letter(self) -> char {
+        match self {
+            Self::Pawn => 'P',
+            Self::Knight => 'N',
+            Self::Bishop => 'B',
+            Self::Rook => 'R',
+            Self::Queen => 'Q',
+            Self::King => 'K',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Piece {
+    pub kind: Kind,
+    pub side: Side,
+}
+
+/// Squares are numbered from a1 = 0 to h8 = 63.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Move {
+    pub from: u8,
+    pub to: u8,
+    pub promotion: Option<Kind>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Checkmate(Side),
+    Stalemate,
+    FiftyMoves,
+    InsufficientMaterial,
+    Repetition,
+}
+
+impl Outcome {
+    /// The PGN result tag.
+    pub fn result(self) -> &'static str {
+        match self {
+            Self::Checkmate(Side::White) => "1-0",
+            Self::Checkmate(Side::Black) => "0-1",
+            _ => "1/2-1/2",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub castling: [bool; 4],
+    /// The square a pawn skipped over with its double step in the last move.
+    pub en_passant: Option<u8>,
+    pub fullmove: u32,
+    /// Plies since the last capture or pawn move, for the fifty move rule.
+    pub halfmove: u32,
+    pub side: Side,
+    pub squares: [Option<Piece>; 64],
+}
+
+pub fn file(square: u8) -> i8 {
+    (square % 8) as i8
+}
+
+pub fn rank(square: u8) -> i8 {
+    (square / 8) as i8
+}
+
+pub fn square_name(square: u8) -> String {
+    format!(
+        "{}{}",
+        (b'a' + file(square) as u8) as char,
+        rank(square) + 1
+    )
+}
+
+fn offset(square: u8, (file_step, rank_step): (i8, i8)) -> Option<u8> {
+    let (file, rank) = (file(square) + file_step, rank(square) + rank_step);
+    if (0..8).contains(&file) && (0..8).contains(&rank) {
+        Some((rank * 8 + file) as u8)
+    } else {
+        None
+    }
+}
+
+impl Position {
+    pub fn start() -> Self {
+        Self::from_fen(START_FEN).expect("start position is valid")
+    }
+
+    pub fn from_fen(fen: &str) -> Option<Self> {
+        let mut fields = fen.split_whitespace();
+        let mut squares = [None; 64];
+        for (row, rank_text) in fields.next()?.split('/').enumerate() {
+            let mut file = 0;
+            for symbol in rank_text.chars() {
+                if let Some(empty) = symbol.to_digit(10) {
+                    file += empty as usize;
+                    continue;
+                }
+                let kind = match symbol.to_ascii_lowercase() {
+                    'p' => Kind::Pawn,
+                    'n' => Kind::Knight,
+                    'b' => Kind::Bishop,
+                    'r' => Kind::Rook,
+                    'q' => Kind::Queen,
+                    'k' => Kind::King,
+                    _ => return None,
+                };
+                let side = if symbol.is_ascii_uppercase() {
+                    Side::White
+                } else {
+                    Side::Black
+                };
+                let square = (7 - row) * 8 + file;
+                *squares.get_mut(square)? = Some(Piece { kind, side });
+                file += 1;
+            }
+        }
+        let side = match fields.next()? {
+            "w" => Side::White,
+            "b" => Side::Black,
+            _ => return None,
+        };
+        let castling_text = fields.next()?;
+        let castling = [
+            castling_text.contains('K'),
+            castling_text.contains('Q'),
+            castling_text.contains('k'),
+            castling_text.contains('q'),
+        ];
+        let en_passant = match fields.next()? {
+            "-" => None,
+            name => {
+                let bytes = name.as_bytes();
+                if bytes.len() != 2 {
+                    return None;
+                }
+                Some((bytes[1].checked_sub(b'1')? * 8 + bytes[0].checked_sub(b'a')?) as u8)
+            }
+        };
+        let halfmove = fields
+            .next()
+            .and_then(|text| text.parse().ok())
+            .unwrap_or(0);
+        let fullmove = fields
+            .next()
+            .and_then(|text| text.parse().ok())
+            .unwrap_or(1);
+        Some(Self {
+            castling,
+            en_passant,
+            fullmove,
+            halfmove,
+            side,
+            squares,
+        })
+    }
+
+    /// Whether any piece of `by` attacks `square`.
+    pub fn attacked(&self, square: u8, by: Side) -> bool {
+        let is = |target: Option<u8>, kinds: &[Kind]| {
+            target
+                .and_then(|target| self.squares[target as usize])
+                .map_or(false, |piece| {
+                    piece.side == by && kinds.contains(&piece.kind)
+                })
+        };
+        // Pawns attack diagonally forward, so look backwards from the target square.
+        let pawn_rank = -by.forward();
+        if is(offset(square, (1, pawn_rank)), &[Kind::Pawn])
+            || is(offset(square, (-1, pawn_rank)), &[Kind::Pawn])
+        {
+            return true;
+        }
+        if KNIGHT_STEPS
+            .iter()
+            .any(|step| is(offset(square, *step), &[Kind::Knight]))
+            || KING_STEPS
+                .iter()
+                .any(|step| is(offset(square, *step), &[Kind::King]))
+        {
+            return true;
+        }
+        let slides = |directions: &[(i8, i8)], kinds: &[Kind]| {
+            directions.iter().any(|direction| {
+                let mut current = square;
+                while let Some(next) = offset(current, *direction) {
+                    if let Some(piece) = self.squares[next as usize] {
+                        return piece.side == by && kinds.contains(&piece.kind);
+                    }
+                    current = next;
+                }
+                false
+            })
+        };
+        slides(&ROOK_DIRECTIONS, &[Kind::Rook, Kind::Queen])
+            || slides(&BISHOP_DIRECTIONS, &[Kind::Bishop, Kind::Queen])
+    }
+
+    pub fn in_check(&self, side: Side) -> bool {
+        self.king(side)
+            .map_or(false, |king| self.attacked(king, side.opposite()))
+    }
+
+    pub fn is_capture(&self, mv: Move) -> bool {
+        self.squares[mv.to as usize].is_some() || self.is_en_passant(mv)
+    }
+
+    fn is_en_passant(&self, mv: Move) -> bool {
+        Some(mv.to) == self.en_passant
+            && self.squares[mv.from as usize].map(|piece| piece.kind) == Some(Kind::Pawn)
+    }
+
+    fn king(&self, side: Side) -> Option<u8> {
+        self.squares
+            .iter()
+            .position(|piece| {
+                *piece
+                    == Some(Piece {
+                        kind: Kind::King,
+                        side,
+                    })
+            })
+            .map(|square| square as u8)
+    }
+
+    pub fn legal_moves(&self) -> Vec<Move> {
+        self.pseudo_moves()
+            .into_iter()
+            .filter(|mv| !self.play(*mv).in_check(self.side))
+            .collect()
+    }
+
+    /// Game end by the rules, given every earlier position of the game for repetitions.
+    pub fn outcome(&self, history: &[Position]) -> Option<Outcome> {
+        if self.legal_moves().is_empty() {
+            return Some(if self.in_check(self.side) {
+                Outcome::Checkmate(self.side.opposite())
+            } else {
+                Outcome::Stalemate
+            });
+        }
+        if self.halfmove >= 100 {
+            return Some(Outcome::FiftyMoves);
+        }
+        if self.insufficient_material() {
+            return Some(Outcome::InsufficientMaterial);
+        }
+        let repetitions = history
+            .iter()
+            .filter(|earlier| earlier.same_placement(self))
+            .count();
+        if repetitions >= 2 {
+            return Some(Outcome::Repetition);
+        }
+        None
+    }
+
+    fn insufficient_material(&self) -> bool {
+        let pieces: Vec<Kind> = self
+            .squares
+            .iter()
+            .flatten()
+            .map(|piece| piece.kind)
+            .filter(|kind| *kind != Kind::King)
+            .collect();
+        match pieces.as_slice() {
+            [] => true,
+            [Kind::Knight] | [Kind::Bishop] => true,
+            _ => false,
+        }
+    }
+
+    /// Positions count as repeated when the same side is to move with the same pieces and rights.
+    fn same_placement(&self, other: &Position) -> bool {
+        self.squares == other.squares
+            && self.side == other.side
+            && self.castling == other.castling
+            && self.en_passant == other.en_passant
+    }
+
+    /// The position after `mv`, which is assumed to be at least pseudo legal.
+    pub fn play(&self, mv: Move) -> Position {
+        let mut next = self.clone();
+        let piece = match self.squares[mv.from as usize] {
+            Some(piece) => piece,
+            None => return next,
+        };
+        let capture = self.is_capture(mv);
+        if self.is_en_passant(mv) {
+            let captured = (mv.to as i8 - 8 * self.side.forward()) as usize;
+            next.squares[captured] = None;
+        }
+        next.squares[mv.to as usize] = Some(Piece {
+            kind: mv.promotion.unwrap_or(piece.kind),
+            side: piece.side,
+        });
+        next.squares[mv.from as usize] = None;
+        if piece.kind == Kind::King && (file(mv.to) - file(mv.from)).abs() == 2 {
+            let (rook_from, rook_to) = if mv.to > mv.from {
+                (mv.from + 3, mv.from + 1)
+            } else {
+                (mv.from - 4, mv.from - 1)
+            };
+            next.squares[rook_to as usize] = next.squares[rook_from as usize].take();
+        }
+        next.en_passant = if piece.kind == Kind::Pawn && (rank(mv.to) - rank(mv.from)).abs() == 2 {
+            Some((mv.from + mv.to) / 2)
+        } else {
+            None
+        };
+        // Moving the king or a rook, or capturing a rook on its corner, loses the matching rights.
+        for (square, right) in [
+            (4, WHITE_KING_SIDE),
+            (4, WHITE_QUEEN_SIDE),
+            (7, WHITE_KING_SIDE),
+            (0, WHITE_QUEEN_SIDE),
+            (60, BLACK_KING_SIDE),
+            (60, BLACK_QUEEN_SIDE),
+            (63, BLACK_KING_SIDE),
+            (56, BLACK_QUEEN_SIDE),
+        ] {
+            if mv.from == square || mv.to == square {
+                next.castling[right] = false;
+            }
+        }
+        next.halfmove = if capture || piece.kind == Kind::Pawn {
+            0
+        } else {
+            self.halfmove + 1
+        };
+        if self.side == Side::Black {
+            next.fullmove += 1;
+        }
+        next.side = self.side.opposite();
+        next
+    }
+
+    /// Moves that follow the piece movement rules but may leave the own king in check.
+    fn pseudo_moves(&self) -> Vec<Move> {
+        let mut moves = Vec::new();
+        for from in 0..64u8 {
+            let piece = match self.squares[from as usize] {
+                Some(piece) if piece.side == self.side => piece,
+                _ => continue,
+            };
+            match piece.kind {
+                Kind::Pawn => self.pawn_moves(from, &mut moves),
+                Kind::Knight => self.step_moves(from, &KNIGHT_STEPS, &mut moves),
+                Kind::Bishop => self.slide_moves(from, &BISHOP_DIRECTIONS, &mut moves),
+                Kind::Rook => self.slide_moves(from, &ROOK_DIRECTIONS, &mut moves),
+                Kind::Queen => {
+                    self.slide_moves(from, &ROOK_DIRECTIONS, &mut moves);
+                    self.slide_moves(from, &BISHOP_DIRECTIONS, &mut moves);
+                }
+                Kind::King => {
+                    self.step_moves(from, &KING_STEPS, &mut moves);
+                    self.castling_moves(from, &mut moves);
+                }
+            }
+        }
+        moves
+    }
+
+    fn castling_moves(&self, from: u8, moves: &mut Vec<Move>) {
+        let (king_side, queen_side, home) = match self.side {
+            Side::White => (WHITE_KING_SIDE, WHITE_QUEEN_SIDE, 4),
+            Side::Black => (BLACK_KING_SIDE, BLACK_QUEEN_SIDE, 60),
+        };
+        let enemy = self.side.opposite();
+        if from != home || self.attacked(home, enemy) {
+            return;
+        }
+        let empty = |squares: &[u8]| {
+            squares
+                .iter()
+                .all(|square| self.squares[*square as usize].is_none())
+        };
+        if self.castling[king_side]
+            && empty(&[home + 1, home + 2])
+            && !self.attacked(home + 1, enemy)
+        {
+            moves.push(Move {
+                from,
+                to: home + 2,
+                promotion: None,
+            });
+        }
+        if self.castling[queen_side]
+            && empty(&[home - 1, home - 2, home - 3])
+            && !self.attacked(home - 1, enemy)
+        {
+            moves.push(Move {
+                from,
+                to: home - 2,
+                promotion: None,
+            });
+        }
+    }
+
+    fn pawn_moves(&self, from: u8, moves: &mut Vec<Move>) {
+        let forward = self.side.forward();
+        let last_rank = if self.side == Side::White { 7 } else { 0 };
+        let start_rank = if self.side == Side::White { 1 } else { 6 };
+        let mut push = |to: u8| {
+            if rank(to) == last_rank {
+                for kind in Kind::PROMOTIONS {
+                    moves.push(Move {
+                        from,
+                        to,
+                        promotion: Some(kind),
+                    });
+                }
+            } else {
+                moves.push(Move {
+                    from,
+                    to,
+                    promotion: None,
+                });
+            }
+        };
+        if let Some(one) = offset(from, (0, forward)) {
+            if self.squares[one as usize].is_none() {
+                push(one);
+                if rank(from) == start_rank {
+                    if let Some(two) = offset(one, (0, forward)) {
+                        if self.squares[two as usize].is_none() {
+                            push(two);
+                        }
+                    }
+                }
+            }
+        }
+        for file_step in [-1, 1] {
+            if let Some(to) = offset(from, (file_step, forward)) {
+                let enemy =
+                    self.squares[to as usize].map_or(false, |piece| piece.side != self.side);
+                if enemy || Some(to) == self.en_passant {
+                    push(to);
+                }
+            }
+        }
+    }
+
+    fn slide_moves(&self, from: u8, directions: &[(i8, i8)], moves: &mut Vec<Move>) {
+        for direction in directions {
+            let mut current = from;
+            while let Some(to) = offset(current, *direction) {
+                match self.squares[to as usize] {
+                    None => moves.push(Move {
+                        from,
+                        to,
+                        promotion: None,
+                    }),
+                    Some(piece) => {
+                        if piece.side != self.side {
+                            moves.push(Move {
+                                from,
+                                to,
+                                promotion: None,
+                            });
+                        }
+                        break;
+                    }
+                }
+                current = to;
+            }
+        }
+    }
+
+    fn step_moves(&self, from: u8, steps: &[(i8, i8)], moves: &mut Vec<Move>) {
+        for step in steps {
+            if let Some(to) = offset(from, *step) {
+                if self.squares[to as usize].map_or(true, |piece| piece.side != self.side) {
+                    moves.push(Move {
+                        from,
+                        to,
+                        promotion: None,
+                    });
+                }
+            }
+        }
+    }
+
+    /// Standard algebraic notation of a legal move, e.g. `Nbd7`, `exd5`, `O-O`, `e8=Q+`.
+    pub fn san(&self, mv: Move) -> String {
+        let piece = match self.squares[mv.from as usize] {
+            Some(piece) => piece,
+            None => return String::new(),
+        };
+        let mut text = if piece.kind == Kind::King && (file(mv.to) - file(mv.from)).abs() == 2 {
+            if mv.to > mv.from { "O-O" } else { "O-O-O" }.to_string()
+        } else {
+            let mut text = String::new();
+            let capture = self.is_capture(mv);
+            if piece.kind == Kind::Pawn {
+                if capture {
+                    text.push((b'a' + file(mv.from) as u8) as char);
+                }
+            } else {
+                text.push(piece.kind.letter());
+                // Name the file, rank or whole square when another piece of the same kind could go there too.
+                let rivals: Vec<u8> = self
+                    .legal_moves()
+                    .into_iter()
+                    .filter(|other| {
+                        other.to == mv.to
+                            && other.from != mv.from
+                            && self.squares[other.from as usize] == Some(piece)
+                    })
+                    .map(|other| other.from)
+                    .collect();
+                if !rivals.is_empty() {
+                    let square = square_name(mv.from);
+                    if rivals.iter().all(|rival| file(*rival) != file(mv.from)) {
+                        text.push_str(&square[..1]);
+                    } else if rivals.iter().all(|rival| rank(*rival) != rank(mv.from)) {
+                        text.push_str(&square[1..]);
+                    } else {
+                        text.push_str(&square);
+                    }
+                }
+            }
+            if capture {
+                text.push('x');
+            }
+            text.push_str(&square_name(mv.to));
+            if let Some(kind) = mv.promotion {
+                text.push('=');
+                text.push(kind.letter());
+            }
+            text
+        };
+        let next = self.play(mv);
+        if next.in_check(next.side) {
+            text.push(if next.legal_moves().is_empty() {
+                '#'
+            } else {
+                '+'
+            });
+        }
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn perft(position: &Position, depth: u32) -> u64 {
+        if depth == 0 {
+            return 1;
+        }
+        position
+            .legal_moves()
+            .into_iter()
+            .map(|mv| perft(&position.play(mv), depth - 1))
+            .sum()
+    }
+
+    fn find(position: &Position, from: &str, to: &str) -> Move {
+        *position
+            .legal_moves()
+            .iter()
+            .find(|mv| square_name(mv.from) == from && square_name(mv.to) == to)
+            .unwrap()
+    }
+
+    #[test]
+    fn perft_start_position() {
+        let start = Position::start();
+        assert_eq!(perft(&start, 1), 20);
+        assert_eq!(perft(&start, 2), 400);
+        assert_eq!(perft(&start, 3), 8_902);
+    }
+
+    #[test]
+    fn perft_castling_en_passant_and_promotions() {
+        // "Kiwipete", a position known for exercising every special move.
+        let kiwipete = Position::from_fen(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        )
+        .unwrap();
+        assert_eq!(perft(&kiwipete, 1), 48);
+        assert_eq!(perft(&kiwipete, 2), 2_039);
+        let endgame = Position::from_fen("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1").unwrap();
+        assert_eq!(perft(&endgame, 3), 2_812);
+        let promotions =
+            Position::from_fen("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1")
+                .unwrap();
+        assert_eq!(perft(&promotions, 2), 264);
+    }
+
+    #[test]
+    fn notation() {
+        let start = Position::start();
+        assert_eq!(start.san(find(&start, "g1", "f3")), "Nf3");
+        assert_eq!(start.san(find(&start, "e2", "e4")), "e4");
+        let position = Position::from_fen("r3k2r/8/8/3pP3/8/8/8/R3K2R w KQkq d6 0 1").unwrap();
+        assert_eq!(position.san(find(&position, "e1", "g1")), "O-O");
+        assert_eq!(position.san(find(&position, "e1", "c1")), "O-O-O");
+        assert_eq!(position.san(find(&position, "e5", "d6")), "exd6");
+        assert_eq!(position.san(find(&position, "a1", "a8")), "Rxa8+");
+        let knights = Position::from_fen("4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1").unwrap();
+        assert_eq!(knights.san(find(&knights, "b1", "d2")), "Nbd2");
+        let mate = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        assert_eq!(mate.san(find(&mate, "a1", "a8")), "Ra8#");
+    }
+
+    #[test]
+    fn outcomes() {
+        let mated = Position::from_fen("R5k1/5ppp/8/8/8/8/8/6K1 b - - 0 1").unwrap();
+        assert_eq!(mated.outcome(&[]), Some(Outcome::Checkmate(Side::White)));
+        let stalemate = Position::from_fen("7k/5Q2/6K1/8/8/8/8/8 b - - 0 1").unwrap();
+        assert_eq!(stalemate.outcome(&[]), Some(Outcome::Stalemate));
+        let bare_kings = Position::from_fen("8/8/4k3/8/8/3K4/8/8 w - - 0 1").unwrap();
+        assert_eq!(bare_kings.outcome(&[]), Some(Outcome::InsufficientMaterial));
+        let start = Position::start();
+        assert_eq!(start.outcome(&[]), None);
+        assert_eq!(
+            start.outcome(&[start.clone(), start.clone()]),
+            Some(Outcome::Repetition)
+        );
+    }
+}
@@ -0,0 +1,189 @@
+//! A small alpha-beta engine: material and piece placement, searched to a fixed depth.
+
+use crate::board::{self, Kind, Move, Position, Side};
+use game_ai::{Difficulty, Game};
+use game_rng::Stream;
+
+/// Difficulty levels, searching one to four plies deep.
+pub const LEVELS: usize = 4;
+const MATE: i32 = 100_000;
+
+impl Game for Position {
+    type Move = Move;
+
+    /// Drawn by the fifty move rule.
+    fn decided(&self) -> Option<i32> {
+        (self.halfmove >= 100).then_some(0)
+    }
+
+    /// Captures are always followed to the end on top of the search depth.
+    fn evaluate(&self, alpha: i32, beta: i32) -> i32 {
+        quiescence(self, alpha, beta)
+    }
+
+    fn moves(&self) -> Vec<Move> {
+        self.legal_moves()
+    }
+
+    fn order(&self, moves: &mut [Move]) {
+        order(self, moves);
+    }
+
+    fn play(&self, mv: Move) -> Self {
+        Position::play(self, mv)
+    }
+
+    /// Checkmate or stalemate; quicker mates score higher, so the engine does not dawdle with a
+    /// won game.
+    fn stuck(&self, ply: u32) -> i32 {
+        if self.in_check(self.side) {
+            -MATE + ply as i32
+        } else {
+            0
+        }
+    }
+}
+
+fn value(kind: Kind) -> i32 {
+    match kind {
+        Kind::Pawn => 100,
+        Kind::Knight => 320,
+        Kind::Bishop => 330,
+        Kind::Rook => 500,
+        Kind::Queen => 900,
+        Kind::King => 0,
+    }
+}
+
+/// Bonus for where a piece stands, seen from its own side of the board.
+fn placement(kind: Kind, square: u8, side: Side, endgame: bool) -> i32 {
+    let file = board::file(square) as i32;
+    let rank = match side {
+        Side::White => board::rank(square) as i32,
+        Side::Black => 7 - board::rank(square) as i32,
+    };
+    // 0 on the four center squares, 6 in the corners.
+    let distance = (2 * file - 7).abs() / 2 + (2 * rank - 7).abs() / 2;
+    match kind {
+        // Central pawns are worth pushing, edge pawns mostly stay home.
+        Kind::Pawn => rank * if (2..=5).contains(&file) { 10 } else { 3 },
+        Kind::Knight => 24 - distance * 8,
+        Kind::Bishop | Kind::Queen => 12 - distance * 4,
+        Kind::Rook => {
+            if rank == 6 {
+                20
+            } else {
+                0
+            }
+        }
+        // The king hides on its back rank until most pieces are gone, then heads for the center.
+        Kind::King if endgame => 24 - distance * 8,
+        Kind::King => {
+            if rank == 0 && file != 3 && file != 4 {
+                20
+            } else {
+                -rank * 15
+            }
+        }
+    }
+}
+
+/// Score of the position for the side to move.
+pub fn evaluate(position: &Position) -> i32 {
+    let heavy_material: i32 = position
+        .squares
+        .iter()
+        .flatten()
+        .filter(|piece| piece.kind != Kind::Pawn)
+        .map(|piece| value(piece.kind))
+        .sum();
+    let endgame = heavy_material <= 2 * value(Kind::Rook) + 2 * value(Kind::Bishop);
+    let mut score = 0;
+    for (square, piece) in position.squares.iter().enumerate() {
+        if let Some(piece) = piece {
+            let points =
+                value(piece.kind) + placement(piece.kind, square as u8, piece.side, endgame);
+            score += if piece.side == position.side {
+                points
+            } else {
+                -points
+            };
+        }
+    }
+    score
+}
+
+/// The best move found at `depth`, picking randomly between equally good moves.
+pub fn best_move(position: &Position, depth: u32) -> Option<Move> {
+    game_ai::best_move(position, depth, None, &mut Stream::new(rand::random()))
+}
+
+/// The search depth of a difficulty level.
+pub fn depth(level: usize) -> u32 {
+    Difficulty::level(level, LEVELS).depth(1, 4)
+}
+
+/// Captures of valuable pieces by cheap ones first, then promotions, so alpha-beta cuts early.
+fn order(position: &Position, moves: &mut [Move]) {
+    moves.sort_by_key(|mv| {
+        let victim = if position.is_capture(*mv) {
+            position.squares[mv.to as usize].map_or(value(Kind::Pawn), |piece| value(piece.kind))
+        } else {
+            0
+        };
+        let attacker = position.squares[mv.from as usize].map_or(0, |piece| value(piece.kind));
+        let promotion = mv.promotion.map_or(0, value);
+        -(victim * 10 - attacker / 10 + promotion)
+    });
+}
+
+/// Only captures are searched until the position is quiet, so exchanges are never cut off halfway.
+fn quiescence(position: &Position, mut alpha: i32, beta: i32) -> i32 {
+    let standing = evaluate(position);
+    if standing >= beta {
+        return standing;
+    }
+    alpha = alpha.max(standing);
+    let mut captures: Vec<Move> = position
+        .legal_moves()
+        .into_iter()
+        .filter(|mv| position.is_capture(*mv))
+        .collect();
+    order(position, &mut captures);
+    for mv in captures {
+        let score = -quiescence(&position.play(mv), -beta, -alpha);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_mate_in_one() {
+        let position = Position::from_fen("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1").unwrap();
+        let mv = best_move(&position, 2).unwrap();
+        assert_eq!(board::square_name(mv.to), "a8");
+    }
+
+    #[test]
+    fn takes_hanging_queen() {
+        let position = Position::from_fen("4k3/8/8/3q4/8/8/3R4/4K3 w - - 0 1").unwrap();
+        let mv = best_move(&position, 1).unwrap();
+        assert_eq!(board::square_name(mv.to), "d5");
+    }
+
+    #[test]
+    fn evaluation_is_symmetric() {
+        let start = Position::start();
+        assert_eq!(evaluate(&start), 0);
+        let mut black_to_move = start.clone();
+        black_to_move.side = Side::Black;
+        assert_eq!(evaluate(&black_to_move), 0);
+    }
+}
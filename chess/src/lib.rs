@@ -0,0 +1,975 @@
+use bevy::{math::const_vec2, prelude::*};
+use board::{Kind, Move, Outcome, Position, Side};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+pub mod board;
+mod engine;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.16, 0.15, 0.13);
+const BLACK_PIECE_COLOR: Color = Color::rgb(0.08, 0.08, 0.08);
+const BOARD_SIZE: f32 = 8.0 * SQUARE_SIZE;
+const CHECK_COLOR: Color = Color::rgb(0.9, 0.2, 0.15);
+const DARK_SQUARE_COLOR: Color = Color::rgb(0.71, 0.53, 0.39);
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const LAST_MOVE_COLOR: Color = Color::rgb(0.95, 0.85, 0.2);
+const LIGHT_SQUARE_COLOR: Color = Color::rgb(0.94, 0.85, 0.71);
+const MARGIN: f32 = 30.0;
+const MOVE_LIST_ROWS: usize = 30;
+const NOTICE_SECONDS: f32 = 4.0;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const PANEL_WIDTH: f32 = 220.0;
+const PIECE_FONT_SIZE: f32 = 64.0;
+const SELECTED_COLOR: Color = Color::rgb(0.35, 0.65, 0.3);
+const SQUARE_SIZE: f32 = 80.0;
+const TARGET_COLOR: Color = Color::rgb(0.3, 0.55, 0.85);
+const WHITE_PIECE_COLOR: Color = Color::rgb(0.98, 0.97, 0.94);
+
+pub const WINDOW_HEIGHT: f32 = BOARD_SIZE + HUD_HEIGHT + 2.0 * MARGIN;
+pub const WINDOW_WIDTH: f32 = BOARD_SIZE + PANEL_WIDTH + 3.0 * MARGIN;
+
+const BOARD_CENTER: Vec2 = const_vec2!([
+    -WINDOW_WIDTH / 2.0 + MARGIN + BOARD_SIZE / 2.0,
+    WINDOW_HEIGHT / 2.0 - HUD_HEIGHT - BOARD_SIZE / 2.0
+]);
+
+/// Everything drawn for a game in progress, removed when returning to the menu.
+#[derive(Component)]
+struct BoardEntity;
+
+#[derive(Component)]
+struct BoardSquare(u8);
+
+/// The game in progress with its notation and every earlier position.
+struct Game {
+    /// Positions before each move, for undo and the repetition rule.
+    history: Vec<Position>,
+    level: usize,
+    moves: Vec<Move>,
+    notation: Vec<String>,
+    opponent: Opponent,
+    outcome: Option<Outcome>,
+    position: Position,
+}
+
+impl Game {
+    fn new(opponent: Opponent, level: usize) -> Self {
+        Self {
+            history: Vec::new(),
+            level,
+            moves: Vec::new(),
+            notation: Vec::new(),
+            opponent,
+            outcome: None,
+            position: Position::start(),
+        }
+    }
+
+    fn engine_side(&self) -> Option<Side> {
+        match self.opponent {
+            Opponent::Engine(side) => Some(side),
+            Opponent::Human => None,
+        }
+    }
+
+    /// Black sits at the bottom when the engine plays white.
+    fn flipped(&self) -> bool {
+        self.engine_side() == Some(Side::White)
+    }
+
+    fn human_to_move(&self) -> bool {
+        self.outcome.is_none() && self.engine_side() != Some(self.position.side)
+    }
+
+    fn player_names(&self) -> (String, String) {
+        let engine = format!("Engine (level {})", self.level + 1);
+        match self.opponent {
+            Opponent::Engine(Side::White) => (engine, "Player".to_string()),
+            Opponent::Engine(Side::Black) => ("Player".to_string(), engine),
+            Opponent::Human => ("Player 1".to_string(), "Player 2".to_string()),
+        }
+    }
+
+    fn play(&mut self, mv: Move) {
+        let next = self.position.play(mv);
+        self.notation.push(self.position.san(mv));
+        self.moves.push(mv);
+        let previous = std::mem::replace(&mut self.position, next);
+        self.history.push(previous);
+        self.outcome = self.position.outcome(&self.history);
+    }
+
+    /// The game in Portable Game Notation, with the movetext wrapped below 80 columns.
+    fn pgn(&self) -> String {
+        let (white, black) = self.player_names();
+        let result = self.outcome.map_or("*", Outcome::result);
+        let mut text = format!(
+            "[Event \"Casual game\"]\n[Site \"rust_games\"]\n[Date \"{}\"]\n[Round \"-\"]\n[White \"{}\"]\n[Black \"{}\"]\n[Result \"{}\"]\n\n",
+            pgn_date(),
+            white,
+            black,
+            result
+        );
+        let tokens = self
+            .notation
+            .iter()
+            .enumerate()
+            .map(|(index, san)| {
+                if index % 2 == 0 {
+                    format!("{}. {}", index / 2 + 1, san)
+                } else {
+                    san.clone()
+                }
+            })
+            .chain(std::iter::once(result.to_string()));
+        let mut line = String::new();
+        for token in tokens {
+            if !line.is_empty() && line.len() + 1 + token.len() >= 80 {
+                text.push_str(&line);
+                text.push('\n');
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&token);
+        }
+        text.push_str(&line);
+        text.push('\n');
+        text
+    }
+
+    /// Takes moves back until a human is to move again, i.e. two plies against the engine.
+    fn undo(&mut self) -> bool {
+        let mut undone = false;
+        while let Some(previous) = self.history.pop() {
+            self.position = previous;
+            self.moves.pop();
+            self.notation.pop();
+            undone = true;
+            if self.engine_side() != Some(self.position.side) {
+                break;
+            }
+        }
+        if undone {
+            self.outcome = None;
+        }
+        undone
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HudText;
+
+struct MenuChoice {
+    level: usize,
+    opponent: Opponent,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Component)]
+struct MoveListText;
+
+/// A short message on the HUD, e.g. where a PGN export went.
+struct Notice {
+    text: String,
+    timer: Timer,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Opponent {
+    /// The engine plays the given side.
+    Engine(Side),
+    Human,
+}
+
+impl Opponent {
+    const ALL: [Opponent; 3] = [
+        Self::Engine(Side::Black),
+        Self::Engine(Side::White),
+        Self::Human,
+    ];
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Engine(Side::Black) => "Play white against the engine",
+            Self::Engine(Side::White) => "Play black against the engine",
+            Self::Human => "Two players at one board",
+        }
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct PieceGlyph(u8);
+
+/// The piece picked up by the player, and a promotion waiting for the choice of piece.
+#[derive(Default)]
+struct Selection {
+    dragging: bool,
+    promotion: Option<(u8, u8)>,
+    square: Option<u8>,
+}
+
+struct Sounds {
+    capture: Handle<AudioSource>,
+    check: Handle<AudioSource>,
+    end: Handle<AudioSource>,
+    invalid: Handle<AudioSource>,
+    move_piece: Handle<AudioSource>,
+}
+
+/// The engine searches on its own thread and leaves its move here when done.
+struct Thinking(Option<Arc<Mutex<Option<Move>>>>);
+
+pub struct ChessPlugin;
+
+impl Plugin for ChessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(MenuChoice {
+                level: 1,
+                opponent: Opponent::Engine(Side::Black),
+            })
+            .insert_resource(Game::new(Opponent::Engine(Side::Black), 1))
+            .insert_resource(Selection::default())
+            .insert_resource(Thinking(None))
+            .insert_resource(Notice {
+                text: String::new(),
+                timer: Timer::from_seconds(0.0, false),
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(despawn_all::<BoardEntity>)
+                    .with_system(menu_enter),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu)
+                    .with_system(despawn_all::<MenuText>)
+                    .with_system(board_spawn),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(mouse_input)
+                    .with_system(keyboard_input)
+                    .with_system(engine_turn)
+                    .with_system(pieces_render)
+                    .with_system(outcome_check),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(board_visuals)
+            .add_system(drag_follow)
+            .add_system(hud_update)
+            .add_system(move_list_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Chess".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn board_spawn(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let flipped = game.flipped();
+    for square in 0..64 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(SQUARE_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(square_center(square, flipped).extend(0.0)),
+                ..Default::default()
+            })
+            .insert(BoardSquare(square))
+            .insert(BoardEntity);
+    }
+    for index in 0..8 {
+        let file_label = (b'a' + index) as char;
+        let rank_label = (b'1' + index) as char;
+        let file_x = square_center(index, flipped).x;
+        let rank_y = square_center(index * 8, flipped).y;
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                file_label.to_string(),
+                16.0,
+                HUD_COLOR,
+                Vec3::new(file_x, BOARD_CENTER.y - BOARD_SIZE / 2.0 - 12.0, 1.0),
+            ))
+            .insert(BoardEntity);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                rank_label.to_string(),
+                16.0,
+                HUD_COLOR,
+                Vec3::new(BOARD_CENTER.x - BOARD_SIZE / 2.0 - 12.0, rank_y, 1.0),
+            ))
+            .insert(BoardEntity);
+    }
+}
+
+/// Colors the squares: last move, selection with its legal targets, and a king in check.
+fn board_visuals(
+    game: Res<Game>,
+    selection: Res<Selection>,
+    mut squares: Query<(&BoardSquare, &mut Sprite)>,
+) {
+    let targets: Vec<u8> = match selection.square {
+        Some(from) => game
+            .position
+            .legal_moves()
+            .into_iter()
+            .filter(|mv| mv.from == from)
+            .map(|mv| mv.to)
+            .collect(),
+        None => Vec::new(),
+    };
+    let side = game.position.side;
+    let king_in_check = game.position.in_check(side);
+    for (BoardSquare(square), mut sprite) in squares.iter_mut() {
+        let light = (board::file(*square) + board::rank(*square)) % 2 == 1;
+        let base = if light {
+            LIGHT_SQUARE_COLOR
+        } else {
+            DARK_SQUARE_COLOR
+        };
+        let piece = game.position.squares[*square as usize];
+        sprite.color = if king_in_check
+            && piece.map_or(false, |piece| {
+                piece.kind == Kind::King && piece.side == side
+            }) {
+            mix(base, CHECK_COLOR, 0.7)
+        } else if selection.square == Some(*square) {
+            mix(base, SELECTED_COLOR, 0.6)
+        } else if targets.contains(square) {
+            mix(base, TARGET_COLOR, 0.45)
+        } else if game
+            .moves
+            .last()
+            .map_or(false, |mv| mv.from == *square || mv.to == *square)
+        {
+            mix(base, LAST_MOVE_COLOR, 0.4)
+        } else {
+            base
+        };
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn drag_follow(
+    windows: Res<Windows>,
+    selection: Res<Selection>,
+    mut glyphs: Query<(&PieceGlyph, &mut Transform)>,
+) {
+    let (square, cursor) = match (selection.square, game_hud::cursor_world_position(&windows)) {
+        (Some(square), Some(cursor)) if selection.dragging => (square, cursor),
+        _ => return,
+    };
+    for (PieceGlyph(glyph_square), mut transform) in glyphs.iter_mut() {
+        if *glyph_square == square {
+            // Lifted above every other piece, keeping the outline on top of the fill.
+            let z = transform.translation.z;
+            transform.translation = cursor.extend(if z < 50.0 { z + 50.0 } else { z });
+        }
+    }
+}
+
+/// Starts a search when the engine is to move and plays its move once the search thread is done.
+fn engine_turn(
+    mut game: ResMut<Game>,
+    mut thinking: ResMut<Thinking>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.outcome.is_some() || game.engine_side() != Some(game.position.side) {
+        return;
+    }
+    if thinking.0.is_none() {
+        let result = Arc::new(Mutex::new(None));
+        let found = result.clone();
+        let position = game.position.clone();
+        let depth = engine::depth(game.level);
+        thread::spawn(move || {
+            *found.lock().unwrap() = engine::best_move(&position, depth);
+        });
+        thinking.0 = Some(result);
+        return;
+    }
+    let found = thinking
+        .0
+        .as_ref()
+        .and_then(|result| result.lock().unwrap().take());
+    if let Some(mv) = found {
+        thinking.0 = None;
+        play(&mut game, mv, &audio, &sounds);
+    }
+}
+
+fn export_pgn(game: &Game) -> Result<PathBuf, Box<dyn Error>> {
+    let directory = game_persistence::directory("chess")?;
+    fs::create_dir_all(&directory)?;
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let path = directory.join(format!("game-{}.pgn", seconds));
+    fs::write(&path, game.pgn())?;
+    Ok(path)
+}
+
+fn export_notice(game: &Game, notice: &mut Notice) {
+    notice.text = match export_pgn(game) {
+        Ok(path) => format!("Saved {}", path.display()),
+        Err(error) => format!("Could not save the game: {}", error),
+    };
+    notice.timer = Timer::from_seconds(NOTICE_SECONDS, false);
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let outcome = match game.outcome {
+        Some(outcome) => outcome,
+        None => return,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(BOARD_SIZE, 200.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(BOARD_CENTER.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText)
+        .insert(BoardEntity);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "{}\n\nEnter: menu   U: undo   P: save PGN",
+                outcome_text(outcome)
+            ),
+            28.0,
+            HUD_COLOR,
+            BOARD_CENTER.extend(100.0),
+        ))
+        .insert(OverlayText)
+        .insert(BoardEntity);
+}
+
+fn game_over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut notice: ResMut<Notice>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) {
+        if game.undo() {
+            state.set(GameState::Playing).unwrap();
+        }
+    } else if keyboard_input.just_pressed(KeyCode::P) {
+        export_notice(&game, &mut notice);
+    }
+}
+
+fn hud_update(
+    time: Res<Time>,
+    game: Res<Game>,
+    selection: Res<Selection>,
+    thinking: Res<Thinking>,
+    state: Res<State<GameState>>,
+    mut notice: ResMut<Notice>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    notice.timer.tick(time.delta());
+    let status = if *state.current() == GameState::Menu {
+        String::new()
+    } else if let Some(outcome) = game.outcome {
+        outcome_text(outcome)
+    } else if selection.promotion.is_some() {
+        "Promote to: Q R B N   Esc: cancel".to_string()
+    } else if thinking.0.is_some() {
+        "Engine is thinking...".to_string()
+    } else if game.position.in_check(game.position.side) {
+        format!("{} to move, check!", game.position.side.name())
+    } else {
+        format!("{} to move", game.position.side.name())
+    };
+    let value = if notice.timer.finished() {
+        status
+    } else {
+        format!("{}   {}", status, notice.text)
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn keyboard_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    mut thinking: ResMut<Thinking>,
+    mut notice: ResMut<Notice>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if let Some((from, to)) = selection.promotion {
+        let kind = [
+            (KeyCode::Q, Kind::Queen),
+            (KeyCode::R, Kind::Rook),
+            (KeyCode::B, Kind::Bishop),
+            (KeyCode::N, Kind::Knight),
+        ]
+        .into_iter()
+        .find(|(key, _)| keyboard_input.just_pressed(*key))
+        .map(|(_, kind)| kind);
+        if let Some(kind) = kind {
+            *selection = Selection::default();
+            play(
+                &mut game,
+                Move {
+                    from,
+                    to,
+                    promotion: Some(kind),
+                },
+                &audio,
+                &sounds,
+            );
+        } else if keyboard_input.just_pressed(KeyCode::Escape) {
+            *selection = Selection::default();
+            game.set_changed();
+        }
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) {
+        // A search still running belongs to the position being taken back.
+        thinking.0 = None;
+        *selection = Selection::default();
+        game.undo();
+    } else if keyboard_input.just_pressed(KeyCode::P) {
+        export_notice(&game, &mut notice);
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, choice: Res<MenuChoice>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&choice),
+            28.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    menu: Res<MenuInput>,
+    mut choice: ResMut<MenuChoice>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    mut thinking: ResMut<Thinking>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let index = Opponent::ALL
+        .iter()
+        .position(|opponent| *opponent == choice.opponent)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) {
+        choice.opponent = Opponent::ALL[index.saturating_sub(1)];
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.opponent = Opponent::ALL[(index + 1).min(Opponent::ALL.len() - 1)];
+    } else if menu.just_pressed(Navigate::Left) {
+        choice.level = choice.level.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Right) {
+        choice.level = (choice.level + 1).min(engine::LEVELS - 1);
+    } else if menu.just_pressed(Navigate::Confirm) {
+        *game = Game::new(choice.opponent, choice.level);
+        *selection = Selection::default();
+        thinking.0 = None;
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&choice);
+    }
+}
+
+fn menu_label(choice: &MenuChoice) -> String {
+    let mut label = "CHESS\n\n".to_string();
+    for opponent in Opponent::ALL {
+        let marker = if opponent == choice.opponent {
+            ">"
+        } else {
+            " "
+        };
+        label.push_str(&format!("{} {}\n", marker, opponent.description()));
+    }
+    label.push_str(&format!(
+        "\nEngine level < {} >\n\nUp/Down: opponent   Left/Right: level   Enter: start",
+        choice.level + 1
+    ));
+    label
+}
+
+/// Blends `color` over `base` by `amount`.
+fn mix(base: Color, color: Color, amount: f32) -> Color {
+    Color::rgb(
+        base.r() + (color.r() - base.r()) * amount,
+        base.g() + (color.g() - base.g()) * amount,
+        base.b() + (color.b() - base.b()) * amount,
+    )
+}
+
+/// Pieces are dragged with the left mouse button; clicking a piece and then a square works too.
+fn mouse_input(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut selection: ResMut<Selection>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.human_to_move() || selection.promotion.is_some() {
+        return;
+    }
+    let flipped = game.flipped();
+    let square =
+        game_hud::cursor_world_position(&windows).and_then(|cursor| square_at(cursor, flipped));
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let own_piece = square
+            .and_then(|square| game.position.squares[square as usize])
+            .map_or(false, |piece| piece.side == game.position.side);
+        match (selection.square, square) {
+            (_, Some(square)) if own_piece => {
+                selection.square = Some(square);
+                selection.dragging = true;
+            }
+            (Some(from), Some(to)) => {
+                try_move(&mut game, &mut selection, from, to, &audio, &sounds)
+            }
+            _ => selection.square = None,
+        }
+    } else if mouse_input.just_released(MouseButton::Left) && selection.dragging {
+        selection.dragging = false;
+        if let (Some(from), Some(to)) = (selection.square, square) {
+            if from != to {
+                try_move(&mut game, &mut selection, from, to, &audio, &sounds);
+            }
+        }
+        // Puts the dragged piece back on its square unless it moved.
+        game.set_changed();
+    }
+}
+
+fn move_list_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<MoveListText>>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    let value = if *state.current() == GameState::Menu {
+        String::new()
+    } else {
+        let rows: Vec<String> = game
+            .notation
+            .chunks(2)
+            .enumerate()
+            .map(|(index, pair)| {
+                format!(
+                    "{:>3}. {:<8}{}",
+                    index + 1,
+                    pair[0],
+                    pair.get(1).map_or("", String::as_str)
+                )
+            })
+            .collect();
+        let first = rows.len().saturating_sub(MOVE_LIST_ROWS);
+        format!("MOVES\n\n{}", rows[first..].join("\n"))
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn outcome_check(game: Res<Game>, mut state: ResMut<State<GameState>>) {
+    if game.outcome.is_some() {
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn outcome_text(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Checkmate(side) => format!("Checkmate, {} wins", side.name()),
+        Outcome::Stalemate => "Draw by stalemate".to_string(),
+        Outcome::FiftyMoves => "Draw by the fifty move rule".to_string(),
+        Outcome::InsufficientMaterial => "Draw, insufficient material".to_string(),
+        Outcome::Repetition => "Draw by threefold repetition".to_string(),
+    }
+}
+
+/// Today's date in UTC as `YYYY.MM.DD`, from days since the epoch to the proleptic Gregorian calendar.
+fn pgn_date() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400) as i64;
+    // Counts in 400 year eras starting on March 1st, so the leap day is the last day of a year.
+    let shifted = days + 719_468;
+    let era = shifted.div_euclid(146_097);
+    let day_of_era = shifted.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = if month_from_march < 10 {
+        month_from_march + 3
+    } else {
+        month_from_march - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+    format!("{}.{:02}.{:02}", year, month, day)
+}
+
+/// Chess glyphs of the embedded font; white pieces are a white fill under a black outline.
+fn piece_glyphs(kind: Kind) -> (char, char) {
+    match kind {
+        Kind::King => ('\u{265A}', '\u{2654}'),
+        Kind::Queen => ('\u{265B}', '\u{2655}'),
+        Kind::Rook => ('\u{265C}', '\u{2656}'),
+        Kind::Bishop => ('\u{265D}', '\u{2657}'),
+        Kind::Knight => ('\u{265E}', '\u{2658}'),
+        Kind::Pawn => ('\u{265F}', '\u{2659}'),
+    }
+}
+
+/// Rebuilds the piece glyphs whenever the game changes.
+fn pieces_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    glyphs: Query<Entity, With<PieceGlyph>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+    for entity in glyphs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let flipped = game.flipped();
+    for (square, piece) in game.position.squares.iter().enumerate() {
+        let piece = match piece {
+            Some(piece) => piece,
+            None => continue,
+        };
+        let square = square as u8;
+        let center = square_center(square, flipped);
+        let (fill, outline) = piece_glyphs(piece.kind);
+        let fill_color = match piece.side {
+            Side::White => WHITE_PIECE_COLOR,
+            Side::Black => BLACK_PIECE_COLOR,
+        };
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                fill.to_string(),
+                PIECE_FONT_SIZE,
+                fill_color,
+                center.extend(1.0),
+            ))
+            .insert(PieceGlyph(square))
+            .insert(BoardEntity);
+        if piece.side == Side::White {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    outline.to_string(),
+                    PIECE_FONT_SIZE,
+                    BLACK_PIECE_COLOR,
+                    center.extend(1.1),
+                ))
+                .insert(PieceGlyph(square))
+                .insert(BoardEntity);
+        }
+    }
+}
+
+fn play(game: &mut Game, mv: Move, audio: &Audio, sounds: &Sounds) {
+    let capture = game.position.is_capture(mv);
+    game.play(mv);
+    let sound = if game.outcome.is_some() {
+        &sounds.end
+    } else if game.position.in_check(game.position.side) {
+        &sounds.check
+    } else if capture {
+        &sounds.capture
+    } else {
+        &sounds.move_piece
+    };
+    audio.play(sound.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(MARGIN),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(HUD_HEIGHT),
+                left: Val::Px(2.0 * MARGIN + BOARD_SIZE),
+                ..Default::default()
+            },
+        ))
+        .insert(MoveListText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Drag or click pieces   U: undo   P: save PGN   Esc: menu",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(6.0),
+            left: Val::Px(MARGIN),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        capture: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.1)
+                .fade()
+                .volume(0.45)
+                .into(),
+        ),
+        check: audio_sources.add(Tone::new(Waveform::Square, 880.0, 0.12).volume(0.15).into()),
+        end: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.15),
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.4),
+        ])),
+        invalid: audio_sources.add(Tone::new(Waveform::Square, 160.0, 0.15).volume(0.15).into()),
+        move_piece: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.05)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+fn square_at(point: Vec2, flipped: bool) -> Option<u8> {
+    let cell = ((point - BOARD_CENTER) / SQUARE_SIZE + Vec2::splat(4.0)).floor();
+    if cell.x < 0.0 || cell.y < 0.0 || cell.x >= 8.0 || cell.y >= 8.0 {
+        return None;
+    }
+    let (file, rank) = if flipped {
+        (7 - cell.x as u8, 7 - cell.y as u8)
+    } else {
+        (cell.x as u8, cell.y as u8)
+    };
+    Some(rank * 8 + file)
+}
+
+fn square_center(square: u8, flipped: bool) -> Vec2 {
+    let (file, rank) = (board::file(square) as f32, board::rank(square) as f32);
+    let cell = if flipped {
+        Vec2::new(7.0 - file, 7.0 - rank)
+    } else {
+        Vec2::new(file, rank)
+    };
+    BOARD_CENTER + (cell - Vec2::splat(3.5)) * SQUARE_SIZE
+}
+
+/// Plays the move from `from` to `to` if it is legal, asking for the piece first on a promotion.
+fn try_move(
+    game: &mut Game,
+    selection: &mut Selection,
+    from: u8,
+    to: u8,
+    audio: &Audio,
+    sounds: &Sounds,
+) {
+    let candidates: Vec<Move> = game
+        .position
+        .legal_moves()
+        .into_iter()
+        .filter(|mv| mv.from == from && mv.to == to)
+        .collect();
+    match candidates.as_slice() {
+        [] => {
+            selection.square = None;
+            audio.play(sounds.invalid.clone());
+        }
+        [mv] => {
+            selection.square = None;
+            play(game, *mv, audio, sounds);
+        }
+        _ => selection.promotion = Some((from, to)),
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use chess::ChessPlugin;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(chess::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(ChessPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,874 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use mine::{Mine, Tile, LAYERS};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+pub mod mine;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.12);
+/// Side of a cell in pixels.
+const CELL: f32 = 36.0;
+const COLUMNS: i32 = 14;
+/// Points for the first, second, ... enemy crushed by the same rock.
+const CRUSH_SCORES: [u32; 6] = [1000, 2500, 4000, 6000, 8000, 10000];
+const EARTH_COLORS: [Color; LAYERS] = [
+    Color::rgb(0.85, 0.65, 0.25),
+    Color::rgb(0.8, 0.45, 0.15),
+    Color::rgb(0.65, 0.25, 0.12),
+    Color::rgb(0.45, 0.12, 0.1),
+];
+const FIELD_CENTER: Vec2 = const_vec2!([0.0, -20.0]);
+const FIRE_COLOR: Color = Color::rgb(1.0, 0.55, 0.1);
+/// How far a dragon's fire reaches, in cells, through earth too.
+const FIRE_REACH: i32 = 2;
+/// Seconds a dragon breathes fire.
+const FIRE_TIME: f32 = 0.8;
+const GAME: &str = "digger";
+/// Seconds an enemy spends drifting through earth.
+const GHOST_TIME: f32 = 3.0;
+/// Pumps that burst an enemy.
+const INFLATION: u8 = 4;
+const LEADERBOARD_SIZE: usize = 5;
+const LIVES: u32 = 3;
+/// Points for bursting an enemy, by the layer it is in.
+const POP_SCORES: [u32; LAYERS] = [200, 300, 400, 500];
+/// Cells the pump's hose reaches along a tunnel.
+const PUMP_REACH: i32 = 3;
+const PLAYER_COLOR: Color = Color::rgb(0.95, 0.95, 1.0);
+/// Cells per second.
+const PLAYER_SPEED: f32 = 4.0;
+const ROCK_COLOR: Color = Color::rgb(0.55, 0.55, 0.6);
+/// Cells per second a rock falls, after wobbling for `ROCK_WOBBLE` seconds.
+const ROCK_SPEED: f32 = 7.0;
+const ROCK_WOBBLE: f32 = 0.6;
+const ROWS: i32 = 16;
+const SKY_COLOR: Color = Color::rgb(0.1, 0.12, 0.3);
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const TUNNEL_COLOR: Color = Color::rgb(0.06, 0.04, 0.04);
+
+pub const WINDOW_HEIGHT: f32 = 680.0;
+pub const WINDOW_WIDTH: f32 = 560.0;
+
+/// Everything that moves, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+struct Enemy {
+    dragon: bool,
+    /// Seconds until the enemy loses a pump of air.
+    deflate: f32,
+    /// Seconds of fire left, and until a dragon may breathe again.
+    fire: f32,
+    fire_wait: f32,
+    /// Seconds left drifting through earth, 0 when in the tunnels.
+    ghost: f32,
+    inflation: u8,
+    mover: Mover,
+    start: (i32, i32),
+}
+
+impl Enemy {
+    /// The cells its fire covers while it breathes.
+    fn fire_cells(&self) -> Vec<(i32, i32)> {
+        if self.fire <= 0.0 {
+            return Vec::new();
+        }
+        let (x, y) = self.mover.cell;
+        let facing = self.mover.facing.0;
+        (1..=FIRE_REACH)
+            .map(|step| (x + facing * step, y))
+            .collect()
+    }
+}
+
+/// Tiles of the mine, rebuilt whenever it changes.
+#[derive(Component)]
+struct FieldSprite;
+
+struct Game {
+    enemies: Vec<Enemy>,
+    lives: u32,
+    mine: Mine,
+    player: Mover,
+    /// The hose shot last, to be drawn for a moment: where it ends and how long it shows.
+    pump: Option<((i32, i32), f32)>,
+    /// The mine has changed since it was last drawn.
+    redraw: bool,
+    rocks: Vec<Rock>,
+    round: u32,
+    score: u32,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut game = Self {
+            enemies: Vec::new(),
+            lives: LIVES,
+            mine: Mine::generate(COLUMNS, ROWS, 0, 0, &mut thread_rng()).0,
+            player: Mover::at(player_start()),
+            pump: None,
+            redraw: true,
+            rocks: Vec::new(),
+            round: 1,
+            score: 0,
+        };
+        game.start_round(1);
+        game
+    }
+
+    fn enemy_speed(&self) -> f32 {
+        (2.2 + 0.2 * self.round as f32).min(3.6)
+    }
+
+    /// Puts the player and the enemies back where they started, as after losing a life.
+    fn reset_positions(&mut self) {
+        self.player = Mover::at(player_start());
+        self.pump = None;
+        for enemy in &mut self.enemies {
+            enemy.mover = Mover::at(enemy.start);
+            enemy.ghost = 0.0;
+            enemy.inflation = 0;
+            enemy.fire = 0.0;
+            enemy.fire_wait = 2.0;
+        }
+    }
+
+    fn start_round(&mut self, round: u32) {
+        let mut rng = thread_rng();
+        let count = (3 + round as usize / 2).min(8);
+        let rocks = (3 + round as usize / 3).min(6);
+        let (mine, starts) = Mine::generate(COLUMNS, ROWS, count, rocks, &mut rng);
+        self.mine = mine;
+        self.round = round;
+        self.rocks.clear();
+        self.enemies = starts
+            .into_iter()
+            .enumerate()
+            .map(|(index, start)| Enemy {
+                dragon: index % 3 == 2,
+                deflate: 0.0,
+                fire: 0.0,
+                fire_wait: 2.0,
+                ghost: 0.0,
+                inflation: 0,
+                mover: Mover::at(start),
+                start,
+            })
+            .collect();
+        self.reset_positions();
+        self.redraw = true;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Ready,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The round banner, or the result at the end.
+#[derive(Component)]
+struct MenuText;
+
+/// Something moving from cell to cell, drawn part way between the two.
+struct Mover {
+    cell: (i32, i32),
+    facing: (i32, i32),
+    from: (i32, i32),
+    /// How far along from `from` to `cell`, from 0 to 1.
+    progress: f32,
+}
+
+impl Mover {
+    fn at(cell: (i32, i32)) -> Self {
+        Self {
+            cell,
+            facing: (1, 0),
+            from: cell,
+            progress: 1.0,
+        }
+    }
+
+    /// Heads for the next cell.
+    fn go(&mut self, cell: (i32, i32)) {
+        self.facing = (cell.0 - self.cell.0, cell.1 - self.cell.1);
+        self.from = self.cell;
+        self.cell = cell;
+        self.progress = 0.0;
+    }
+
+    fn position(&self) -> Vec2 {
+        let from = cell_center(self.from);
+        from + (cell_center(self.cell) - from) * self.progress
+    }
+}
+
+struct PlayerHitEvent;
+
+/// A rock that has started to wobble or fall; resting rocks are tiles of the mine.
+struct Rock {
+    /// Enemies crushed so far.
+    crushed: usize,
+    mover: Mover,
+    /// Seconds of wobbling left before it falls.
+    wobble: f32,
+}
+
+struct Sounds {
+    crush: Handle<AudioSource>,
+    dig: Handle<AudioSource>,
+    player_death: Handle<AudioSource>,
+    pop: Handle<AudioSource>,
+    pump: Handle<AudioSource>,
+    round: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Movement,
+    Collision,
+}
+
+pub struct DiggerPlugin;
+
+impl Plugin for DiggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Ready)
+            .add_event::<PlayerHitEvent>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Ready).with_system(ready_enter))
+            .add_system_set(SystemSet::on_update(GameState::Ready).with_system(ready_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_update.label(Step::Movement))
+                    .with_system(enemies_update.label(Step::Movement))
+                    .with_system(rocks_update.label(Step::Movement))
+                    .with_system(collisions.label(Step::Collision).after(Step::Movement))
+                    .with_system(player_hit.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(restart_input))
+            .add_system(actors_render)
+            .add_system(mine_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Digger".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The player, the hose, the enemies and their fire, and the loose rocks.
+fn actors_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    time: Res<Time>,
+    game: Res<Game>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut sprite = |color: Color, size: Vec2, position: Vec2, z: f32| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(z)),
+                ..Default::default()
+            })
+            .insert(Actor);
+    };
+
+    let player = game.player.position();
+    sprite(PLAYER_COLOR, Vec2::splat(CELL * 0.7), player, 5.0);
+    let facing = Vec2::new(game.player.facing.0 as f32, -game.player.facing.1 as f32);
+    sprite(
+        Color::rgb(0.2, 0.4, 0.9),
+        Vec2::splat(CELL * 0.25),
+        player + facing * CELL * 0.2,
+        6.0,
+    );
+    if let Some((end, _)) = game.pump {
+        let end = cell_center(end);
+        let middle = (player + end) / 2.0;
+        let size = (end - player).abs() + Vec2::splat(3.0);
+        sprite(Color::WHITE, size, middle, 4.0);
+    }
+
+    for rock in &game.rocks {
+        let shake = if rock.wobble > 0.0 {
+            (time.seconds_since_startup() as f32 * 40.0).sin() * 3.0
+        } else {
+            0.0
+        };
+        sprite(
+            ROCK_COLOR,
+            Vec2::splat(CELL * 0.85),
+            rock.mover.position() + Vec2::new(shake, 0.0),
+            3.0,
+        );
+    }
+
+    for cell in game.enemies.iter().flat_map(Enemy::fire_cells) {
+        sprite(
+            FIRE_COLOR,
+            Vec2::new(CELL, CELL * 0.4),
+            cell_center(cell),
+            7.0,
+        );
+    }
+    for enemy in &game.enemies {
+        let (glyph, color) = if enemy.ghost > 0.0 {
+            ("\u{25CE}", Color::rgba(1.0, 1.0, 1.0, 0.6))
+        } else if enemy.dragon {
+            ("\u{25C6}", Color::rgb(0.3, 0.85, 0.35))
+        } else {
+            ("\u{25CF}", Color::rgb(0.95, 0.3, 0.25))
+        };
+        let size = CELL * (0.9 + 0.3 * enemy.inflation as f32);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                glyph,
+                size,
+                color,
+                enemy.mover.position().extend(8.0),
+            ))
+            .insert(Actor);
+    }
+}
+
+fn cell_center(cell: (i32, i32)) -> Vec2 {
+    let top_left = FIELD_CENTER + Vec2::new(-(COLUMNS as f32), ROWS as f32) * CELL / 2.0;
+    top_left + Vec2::new(cell.0 as f32 + 0.5, -(cell.1 as f32) - 0.5) * CELL
+}
+
+/// Enemies and fire against the player, and the round ending when no enemy is left.
+fn collisions(
+    mut game: ResMut<Game>,
+    mut hit_writer: EventWriter<PlayerHitEvent>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if game.enemies.is_empty() {
+        let round = game.round + 1;
+        game.start_round(round);
+        state.set(GameState::Ready).unwrap();
+        return;
+    }
+    let player = game.player.position();
+    let cell = game.player.cell;
+    let caught = game.enemies.iter().any(|enemy| {
+        (enemy.inflation == 0 && enemy.mover.position().distance(player) < CELL * 0.6)
+            || enemy.fire_cells().contains(&cell)
+    });
+    if caught {
+        hit_writer.send(PlayerHitEvent);
+    }
+}
+
+/// Enemies chase the player through the tunnels, now and then drifting through the earth
+/// instead; dragons breathe fire along their row.
+fn enemies_update(time: Res<Time>, mut game: ResMut<Game>) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    let mut rng = thread_rng();
+    let speed = game.enemy_speed();
+    let target = game.player.cell;
+    for enemy in &mut game.enemies {
+        enemy.fire_wait -= delta;
+        if enemy.fire > 0.0 {
+            enemy.fire -= delta;
+            continue;
+        }
+        if enemy.inflation > 0 {
+            enemy.deflate -= delta;
+            if enemy.deflate <= 0.0 {
+                enemy.inflation -= 1;
+                enemy.deflate = 1.0;
+            }
+            continue;
+        }
+        if enemy.ghost > 0.0 {
+            enemy.ghost -= delta;
+        }
+        let pace = if enemy.ghost > 0.0 {
+            speed * 0.6
+        } else {
+            speed
+        };
+        enemy.mover.progress += pace * delta;
+        if enemy.mover.progress < 1.0 {
+            continue;
+        }
+        enemy.mover.progress = 1.0;
+        let cell = enemy.mover.cell;
+
+        let row_gap = (target.0 - cell.0).abs();
+        let facing_player = (target.0 - cell.0).signum() == enemy.mover.facing.0;
+        if enemy.dragon
+            && enemy.ghost <= 0.0
+            && target.1 == cell.1
+            && row_gap <= FIRE_REACH + 1
+            && facing_player
+            && enemy.fire_wait <= 0.0
+        {
+            enemy.fire = FIRE_TIME;
+            enemy.fire_wait = 4.0;
+            continue;
+        }
+
+        let in_tunnel = game.mine.get(cell) == Tile::Tunnel;
+        if enemy.ghost <= 0.0 && !in_tunnel {
+            // Drift on until back in a tunnel.
+            enemy.ghost = 0.1;
+        }
+        let path = game.mine.path_step(cell, target);
+        if enemy.ghost <= 0.0 {
+            let odds = if path.is_some() { 0.04 } else { 0.2 };
+            if rng.gen_bool(odds) {
+                enemy.ghost = GHOST_TIME;
+            }
+        }
+        let next = if enemy.ghost > 0.0 {
+            // Closer to the player through anything but rock.
+            let distance = |(x, y): (i32, i32)| (target.0 - x).abs() + (target.1 - y).abs();
+            [(0, -1), (1, 0), (0, 1), (-1, 0)]
+                .iter()
+                .map(|(dx, dy)| (cell.0 + dx, cell.1 + dy))
+                .filter(|step| game.mine.get(*step) != Tile::Rock)
+                .min_by_key(|step| distance(*step))
+        } else {
+            path.or_else(|| {
+                let around = game.mine.tunnels_around(cell);
+                let ahead = (cell.0 + enemy.mover.facing.0, cell.1 + enemy.mover.facing.1);
+                if around.contains(&ahead) && rng.gen_bool(0.8) {
+                    Some(ahead)
+                } else {
+                    around.choose(&mut rng).copied()
+                }
+            })
+        };
+        if let Some(next) = next {
+            enemy.mover.go(next);
+        }
+    }
+}
+
+fn game_over(
+    game: Res<Game>,
+    mut scores: ResMut<Scores>,
+    platform: Res<Platform>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.player_death.clone());
+    if scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// The sky, the bands of earth, the tunnels dug through them and the resting rocks.
+fn mine_render(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    sprites: Query<Entity, With<FieldSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mine = &game.mine;
+    let mut sprite = |color: Color, size: Vec2, position: Vec2, z: f32| {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position.extend(z)),
+                ..Default::default()
+            })
+            .insert(FieldSprite);
+    };
+    for y in 0..mine.rows {
+        let color = if y == 0 {
+            SKY_COLOR
+        } else {
+            EARTH_COLORS[mine.layer(y)]
+        };
+        let middle = (cell_center((0, y)) + cell_center((mine.columns - 1, y))) / 2.0;
+        sprite(
+            color,
+            Vec2::new(mine.columns as f32 * CELL, CELL),
+            middle,
+            0.0,
+        );
+        for x in 0..mine.columns {
+            match mine.get((x, y)) {
+                Tile::Tunnel if y > 0 => {
+                    sprite(TUNNEL_COLOR, Vec2::splat(CELL), cell_center((x, y)), 1.0)
+                }
+                Tile::Rock => sprite(
+                    ROCK_COLOR,
+                    Vec2::splat(CELL * 0.85),
+                    cell_center((x, y)),
+                    2.0,
+                ),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn player_hit(
+    mut events: EventReader<PlayerHitEvent>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    game.lives -= 1;
+    if game.lives == 0 {
+        // A rock may land on the player just as the last enemy goes.
+        state.overwrite_set(GameState::Over).unwrap();
+        return;
+    }
+    audio.play(sounds.player_death.clone());
+    game.reset_positions();
+    state.overwrite_set(GameState::Ready).unwrap();
+}
+
+fn player_start() -> (i32, i32) {
+    (COLUMNS / 2, ROWS / 2)
+}
+
+/// Arrow keys dig on from cell to cell; Space shoots the pump's hose along the tunnel ahead
+/// and pumps up whatever it holds.
+fn player_update(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    if let Some((_, seconds)) = &mut game.pump {
+        *seconds -= delta;
+        if *seconds <= 0.0 {
+            game.pump = None;
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        let (x, y) = game.player.cell;
+        let (dx, dy) = game.player.facing;
+        let mut end = (x, y);
+        let mut held = None;
+        for step in 1..=PUMP_REACH {
+            let cell = (x + dx * step, y + dy * step);
+            if game.mine.get(cell) != Tile::Tunnel {
+                break;
+            }
+            end = cell;
+            held = game.enemies.iter().position(|enemy| {
+                enemy.ghost <= 0.0 && (enemy.mover.cell == cell || enemy.mover.from == cell)
+            });
+            if held.is_some() {
+                break;
+            }
+        }
+        game.pump = Some((end, 0.15));
+        audio.play(sounds.pump.clone());
+        if let Some(index) = held {
+            let enemy = &mut game.enemies[index];
+            enemy.inflation += 1;
+            enemy.deflate = 1.0;
+            enemy.fire = 0.0;
+            if enemy.inflation >= INFLATION {
+                let enemy = game.enemies.remove(index);
+                let mut points = POP_SCORES[game.mine.layer(enemy.mover.cell.1)];
+                // Dragons are harder to get at from the side, where they breathe fire.
+                if enemy.dragon && dy == 0 {
+                    points *= 2;
+                }
+                game.score += points;
+                audio.play(sounds.pop.clone());
+            }
+        }
+        return;
+    }
+
+    game.player.progress = (game.player.progress + PLAYER_SPEED * delta).min(1.0);
+    if game.player.progress < 1.0 {
+        return;
+    }
+    let direction = [
+        (KeyCode::Left, (-1, 0)),
+        (KeyCode::Right, (1, 0)),
+        (KeyCode::Up, (0, -1)),
+        (KeyCode::Down, (0, 1)),
+    ]
+    .iter()
+    .find(|(key, _)| keyboard_input.pressed(*key))
+    .map(|(_, direction)| *direction);
+    if let Some((dx, dy)) = direction {
+        let (x, y) = game.player.cell;
+        let next = (x + dx, y + dy);
+        if game.mine.get(next) == Tile::Rock {
+            game.player.facing = (dx, dy);
+            return;
+        }
+        if game.mine.dig(next) {
+            game.redraw = true;
+            audio.play(sounds.dig.clone());
+        }
+        game.player.go(next);
+    }
+}
+
+fn ready_enter(audio: Res<Audio>, sounds: Res<Sounds>) {
+    audio.play(sounds.round.clone());
+}
+
+fn ready_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn restart_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new();
+        state.set(GameState::Ready).unwrap();
+    }
+}
+
+/// Rocks with a tunnel under them wobble, then fall until earth stops them, crushing whatever
+/// they fall on.
+fn rocks_update(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut hit_writer: EventWriter<PlayerHitEvent>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    for y in (1..game.mine.rows).rev() {
+        for x in 0..game.mine.columns {
+            let below = (x, y + 1);
+            let loose = game.mine.get((x, y)) == Tile::Rock
+                && game.mine.get(below) == Tile::Tunnel
+                && game.player.cell != below;
+            if loose {
+                game.mine.set((x, y), Tile::Tunnel);
+                game.rocks.push(Rock {
+                    crushed: 0,
+                    mover: Mover::at((x, y)),
+                    wobble: ROCK_WOBBLE,
+                });
+                game.redraw = true;
+            }
+        }
+    }
+
+    for rock in &mut game.rocks {
+        if rock.wobble > 0.0 {
+            rock.wobble -= delta;
+            continue;
+        }
+        rock.mover.progress += ROCK_SPEED * delta;
+        if rock.mover.progress < 1.0 {
+            continue;
+        }
+        rock.mover.progress = 1.0;
+        let (x, y) = rock.mover.cell;
+        if game.mine.get((x, y + 1)) == Tile::Tunnel {
+            rock.mover.go((x, y + 1));
+        } else {
+            // Landed: the rock breaks apart.
+            rock.wobble = -1.0;
+        }
+        let cell = rock.mover.cell;
+        let before = game.enemies.len();
+        game.enemies
+            .retain(|enemy| enemy.mover.cell != cell && enemy.mover.from != cell);
+        for _ in game.enemies.len()..before {
+            game.score += CRUSH_SCORES[rock.crushed.min(CRUSH_SCORES.len() - 1)];
+            rock.crushed += 1;
+            audio.play(sounds.crush.clone());
+        }
+        if rock.mover.position().distance(game.player.position()) < CELL * 0.6 {
+            hit_writer.send(PlayerHitEvent);
+        }
+    }
+    game.rocks.retain(|rock| rock.wobble > -1.0);
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(6.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 40.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        crush: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.3)
+                .fade()
+                .volume(0.4)
+                .into(),
+        ),
+        dig: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.03)
+                .fade()
+                .volume(0.1)
+                .into(),
+        ),
+        player_death: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 400.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 300.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 200.0, 0.3).fade().volume(0.2),
+        ])),
+        pop: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 900.0, 0.03).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.12).fade().volume(0.35),
+        ])),
+        pump: audio_sources.add(
+            Tone::new(Waveform::Triangle, 300.0, 0.06)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        round: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.2).fade(),
+        ])),
+    });
+}
+
+fn texts_update(
+    game: Res<Game>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let hud = format!(
+        "Score {}   High {}   Lives {}   Round {}",
+        game.score,
+        scores
+            .best("")
+            .map_or(0, |entry| entry.score)
+            .max(game.score),
+        game.lives,
+        game.round
+    );
+    let (help, banner) = match state.current() {
+        GameState::Ready => ("Enter: start".to_string(), format!("ROUND {}", game.round)),
+        GameState::Playing => (
+            "Arrows: dig   Space: pump   Drop rocks on enemies for big points".to_string(),
+            String::new(),
+        ),
+        GameState::Over => {
+            let scores = scores
+                .table("")
+                .iter()
+                .take(LEADERBOARD_SIZE)
+                .enumerate()
+                .map(|(place, entry)| format!("{}. {:>7}", place + 1, entry.score))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Enter: new game".to_string(),
+                format!("GAME OVER\n\nScore {}\n\n{}", game.score, scores),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
@@ -0,0 +1,183 @@
+//! The ground: a grid of earth, tunnels and rocks with the open surface as its top row. Rows
+//! count down from the surface.
+
+use game_ai::path;
+use game_grid::Position;
+use rand::Rng;
+
+/// Bands of earth, each harder and worth more than the one above.
+pub const LAYERS: usize = 4;
+
+const DIRECTIONS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Earth,
+    Tunnel,
+    Rock,
+}
+
+pub struct Mine {
+    pub columns: i32,
+    pub rows: i32,
+    tiles: Vec<Tile>,
+}
+
+impl Mine {
+    /// A mine with a short tunnel for each of `dens` enemies to start in and `rocks` rocks
+    /// buried in solid earth. Returns the mine and the cell each enemy starts on.
+    pub fn generate(
+        columns: i32,
+        rows: i32,
+        dens: usize,
+        rocks: usize,
+        rng: &mut impl Rng,
+    ) -> (Self, Vec<(i32, i32)>) {
+        let mut mine = Self {
+            columns,
+            rows,
+            tiles: vec![Tile::Earth; (columns * rows) as usize],
+        };
+        for x in 0..columns {
+            mine.set((x, 0), Tile::Tunnel);
+        }
+        // The shaft the player starts at the bottom of.
+        let middle = columns / 2;
+        for y in 1..=rows / 2 {
+            mine.set((middle, y), Tile::Tunnel);
+        }
+        let mut starts = Vec::new();
+        while starts.len() < dens {
+            let length = rng.gen_range(3, 5);
+            let (x, y) = (
+                rng.gen_range(0, columns - length),
+                rng.gen_range(3, rows - 1),
+            );
+            let cells: Vec<(i32, i32)> = (x..x + length).map(|x| (x, y)).collect();
+            // Dens keep a row of earth between them and the tunnels around.
+            let crowded = cells.iter().any(|(x, y)| {
+                (-1..=1).any(|dy| (-1..=1).any(|dx| mine.get((x + dx, y + dy)) == Tile::Tunnel))
+            });
+            if crowded {
+                continue;
+            }
+            for cell in &cells {
+                mine.set(*cell, Tile::Tunnel);
+            }
+            starts.push(cells[length as usize / 2]);
+        }
+        let mut placed = 0;
+        while placed < rocks {
+            let cell = (rng.gen_range(0, columns), rng.gen_range(2, rows - 2));
+            let solid = [(0, 0), (0, 1), (0, -1)]
+                .iter()
+                .all(|(dx, dy)| mine.get((cell.0 + dx, cell.1 + dy)) == Tile::Earth);
+            if solid {
+                mine.set(cell, Tile::Rock);
+                placed += 1;
+            }
+        }
+        (mine, starts)
+    }
+
+    pub fn contains(&self, cell: (i32, i32)) -> bool {
+        (0..self.columns).contains(&cell.0) && (0..self.rows).contains(&cell.1)
+    }
+
+    /// Digs a cell out; true if there was earth to dig.
+    pub fn dig(&mut self, cell: (i32, i32)) -> bool {
+        if self.get(cell) != Tile::Earth {
+            return false;
+        }
+        self.set(cell, Tile::Tunnel);
+        true
+    }
+
+    /// Cells off the mine count as rock, so nothing leaves it.
+    pub fn get(&self, cell: (i32, i32)) -> Tile {
+        if self.contains(cell) {
+            self.tiles[(cell.1 * self.columns + cell.0) as usize]
+        } else {
+            Tile::Rock
+        }
+    }
+
+    /// The band of earth a row belongs to, 0 right under the surface.
+    pub fn layer(&self, row: i32) -> usize {
+        ((row - 1).max(0) as usize * LAYERS / (self.rows - 1) as usize).min(LAYERS - 1)
+    }
+
+    /// The first cell on the shortest way through the tunnels from `from` to `to`.
+    pub fn path_step(&self, from: (i32, i32), to: (i32, i32)) -> Option<(i32, i32)> {
+        let tunnel = |cell: Position| self.get((cell.x, cell.y)) == Tile::Tunnel;
+        let start = Position::new(from.0, from.1);
+        if !tunnel(start) {
+            return None;
+        }
+        let goal = Position::new(to.0, to.1);
+        let path = path::bfs(start, |cell| cell == goal, tunnel)?;
+        path.first().map(|step| (step.x, step.y))
+    }
+
+    pub fn set(&mut self, cell: (i32, i32), tile: Tile) {
+        let index = (cell.1 * self.columns + cell.0) as usize;
+        self.tiles[index] = tile;
+    }
+
+    /// Tunnel cells next to `cell`, for wandering about.
+    pub fn tunnels_around(&self, cell: (i32, i32)) -> Vec<(i32, i32)> {
+        DIRECTIONS
+            .iter()
+            .map(|(dx, dy)| (cell.0 + dx, cell.1 + dy))
+            .filter(|neighbour| self.get(*neighbour) == Tile::Tunnel)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn generates_dens_and_rocks() {
+        let (mine, starts) = Mine::generate(14, 16, 5, 4, &mut StdRng::seed_from_u64(3));
+        assert_eq!(starts.len(), 5);
+        for start in &starts {
+            assert_eq!(mine.get(*start), Tile::Tunnel);
+            assert!(start.1 >= 3);
+        }
+        let rocks = (0..16)
+            .flat_map(|y| (0..14).map(move |x| (x, y)))
+            .filter(|cell| mine.get(*cell) == Tile::Rock)
+            .count();
+        assert_eq!(rocks, 4);
+        assert_eq!(mine.get((3, 0)), Tile::Tunnel);
+        assert_eq!(mine.get((7, 8)), Tile::Tunnel);
+        assert_eq!(mine.get((-1, 0)), Tile::Rock);
+    }
+
+    #[test]
+    fn paths_follow_the_tunnels() {
+        let (mut mine, _) = Mine::generate(8, 8, 0, 0, &mut StdRng::seed_from_u64(1));
+        // The shaft runs down column 4 to row 4; dig from its foot over to column 1 and down.
+        for cell in [(3, 4), (2, 4), (1, 4), (1, 5), (1, 6)] {
+            assert!(mine.dig(cell));
+        }
+        assert!(!mine.dig((1, 6)));
+        assert_eq!(mine.path_step((1, 6), (4, 0)), Some((1, 5)));
+        assert_eq!(mine.path_step((4, 1), (1, 6)), Some((4, 2)));
+        assert_eq!(mine.path_step((1, 6), (6, 6)), None);
+        assert_eq!(mine.path_step((1, 6), (1, 6)), None);
+        assert_eq!(mine.tunnels_around((1, 5)).len(), 2);
+    }
+
+    #[test]
+    fn layers_deepen() {
+        let (mine, _) = Mine::generate(8, 17, 0, 0, &mut StdRng::seed_from_u64(1));
+        assert_eq!(mine.layer(0), 0);
+        assert_eq!(mine.layer(1), 0);
+        assert_eq!(mine.layer(5), 1);
+        assert_eq!(mine.layer(16), 3);
+    }
+}
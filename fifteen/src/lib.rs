@@ -0,0 +1,588 @@
+use bevy::{
+    math::const_vec2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use puzzle::Puzzle;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod picture;
+pub mod puzzle;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.09, 0.12);
+/// Side of the board in pixels, whatever its size in tiles.
+const BOARD: f32 = 440.0;
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, 20.0]);
+const BOARD_COLOR: Color = Color::rgb(0.05, 0.05, 0.07);
+const GAME: &str = "fifteen";
+/// Pixels between two tiles.
+const GAP: f32 = 4.0;
+/// Pixels across the picture made up when there is none of one's own.
+const GENERATED_PICTURE: usize = 480;
+/// A picture of one's own, looked for in the game's data directory.
+const PICTURE_FILE: &str = "picture.png";
+const RECORDS_FILE: &str = "records";
+/// Tiles across the board to choose from.
+const SIZES: [usize; 3] = [3, 4, 5];
+/// Pixels per second a tile slides.
+const SLIDE_SPEED: f32 = 1400.0;
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const TILE_COLOR: Color = Color::rgb(0.8, 0.45, 0.2);
+const TILE_TEXT_COLOR: Color = Color::rgb(1.0, 0.96, 0.9);
+
+pub const WINDOW_HEIGHT: f32 = 620.0;
+pub const WINDOW_WIDTH: f32 = 520.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Solved,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+struct Menu {
+    /// Index into `SIZES` of the board size chosen.
+    index: usize,
+    picture: bool,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+/// The picture of one's own, if there is one.
+struct OwnPicture(Option<Handle<Image>>);
+
+/// The fastest time for each board size, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records(HashMap<String, f32>);
+
+/// The time and moves once solved, under the board.
+#[derive(Component)]
+struct ResultText;
+
+struct Session {
+    elapsed: f32,
+    moves: u32,
+    /// Record set by this solve.
+    new_record: bool,
+    puzzle: Puzzle,
+    /// The clock runs from the first move on.
+    started: bool,
+}
+
+impl Session {
+    fn new(size: usize) -> Self {
+        Self {
+            elapsed: 0.0,
+            moves: 0,
+            new_record: false,
+            puzzle: Puzzle::shuffled(size, &mut thread_rng()),
+            started: false,
+        }
+    }
+}
+
+/// The shade behind the menu.
+#[derive(Component)]
+struct Shade;
+
+struct Sounds {
+    slide: Handle<AudioSource>,
+    solved: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+struct Tile(u8);
+
+pub struct FifteenPlugin;
+
+impl Plugin for FifteenPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Menu {
+                index: 1,
+                picture: false,
+            })
+            .insert_resource(Session::new(SIZES[1]))
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(tiles_spawn))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(clock_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Solved).with_system(solved_enter))
+            .add_system_set(SystemSet::on_update(GameState::Solved).with_system(solved_input))
+            .add_system(tiles_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Fifteen".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The cell under a point of the window, if on the board.
+fn cell_at(point: Vec2, size: usize) -> Option<(usize, usize)> {
+    let corner = BOARD_CENTER + Vec2::new(-BOARD, BOARD) / 2.0;
+    let cell = BOARD / size as f32;
+    let x = ((point.x - corner.x) / cell).floor();
+    let y = ((corner.y - point.y) / cell).floor();
+    if x < 0.0 || y < 0.0 || x >= size as f32 || y >= size as f32 {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+fn cell_center((x, y): (usize, usize), size: usize) -> Vec2 {
+    let cell = BOARD / size as f32;
+    BOARD_CENTER
+        + Vec2::new(
+            (x as f32 + 0.5) * cell - BOARD / 2.0,
+            BOARD / 2.0 - (y as f32 + 0.5) * cell,
+        )
+}
+
+/// The clock runs from the first move until the puzzle is solved.
+fn clock_update(time: Res<Time>, mut session: ResMut<Session>) {
+    if session.started {
+        session.elapsed += time.delta_seconds();
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Left and right pick the board size, P switches between numbers and a picture, Enter starts.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    mut menu: ResMut<Menu>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if navigation.just_pressed(Navigate::Left) {
+        menu.index = menu.index.saturating_sub(1);
+    } else if navigation.just_pressed(Navigate::Right) {
+        menu.index = (menu.index + 1).min(SIZES.len() - 1);
+    } else if keyboard_input.just_pressed(KeyCode::P) {
+        menu.picture = !menu.picture;
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *session = Session::new(SIZES[menu.index]);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// The picture of one's own, or else a made up one, cut into tiles as images.
+fn picture_tiles(
+    images: &mut Assets<Image>,
+    own_picture: &OwnPicture,
+    size: usize,
+) -> Vec<Handle<Image>> {
+    let own = own_picture
+        .0
+        .as_ref()
+        .and_then(|handle| images.get(handle))
+        .filter(|image| {
+            let extent = image.texture_descriptor.size;
+            image.texture_descriptor.format == TextureFormat::Rgba8UnormSrgb
+                && image.data.len() == (extent.width * extent.height * 4) as usize
+                && extent.width.min(extent.height) as usize >= size
+        })
+        .map(|image| {
+            let extent = image.texture_descriptor.size;
+            picture::slice(
+                &image.data,
+                extent.width as usize,
+                extent.height as usize,
+                size,
+            )
+        });
+    let tiles = own.unwrap_or_else(|| {
+        let data = picture::generate(GENERATED_PICTURE);
+        picture::slice(&data, GENERATED_PICTURE, GENERATED_PICTURE, size)
+    });
+    tiles
+        .into_iter()
+        .map(|(side, data)| {
+            images.add(Image::new(
+                Extent3d {
+                    width: side as u32,
+                    height: side as u32,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                data,
+                TextureFormat::Rgba8UnormSrgb,
+            ))
+        })
+        .collect()
+}
+
+/// A click slides the tiles between it and the gap, the arrow keys slide the tile next to the
+/// gap into it, and Esc gives up.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    let size = session.puzzle.size;
+    let (x, y) = session.puzzle.gap();
+    let cell = if mouse_input.just_pressed(MouseButton::Left) {
+        game_hud::cursor_world_position(&windows).and_then(|point| cell_at(point, size))
+    } else if keyboard_input.just_pressed(KeyCode::Left) && x + 1 < size {
+        Some((x + 1, y))
+    } else if keyboard_input.just_pressed(KeyCode::Right) && x > 0 {
+        Some((x - 1, y))
+    } else if keyboard_input.just_pressed(KeyCode::Up) && y + 1 < size {
+        Some((x, y + 1))
+    } else if keyboard_input.just_pressed(KeyCode::Down) && y > 0 {
+        Some((x, y - 1))
+    } else {
+        None
+    };
+    let moved = match cell {
+        Some(cell) => session.puzzle.slide(cell),
+        None => return,
+    };
+    if moved.is_empty() {
+        return;
+    }
+    session.moves += moved.len() as u32;
+    session.started = true;
+    audio.play(sounds.slide.clone());
+    if session.puzzle.is_solved() {
+        state.set(GameState::Solved).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    asset_server: Res<AssetServer>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: BOARD_COLOR,
+            custom_size: Some(Vec2::splat(BOARD + GAP)),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(BOARD_CENTER.extend(0.0)),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, BOARD_CENTER.y - BOARD / 2.0 - 34.0, 900.0),
+        ))
+        .insert(ResultText);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.75),
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 850.0),
+            ..Default::default()
+        })
+        .insert(Shade);
+
+    let own = game_persistence::directory(GAME)
+        .map(|directory| directory.join(PICTURE_FILE))
+        .ok()
+        .filter(|path| path.exists())
+        .map(|path| asset_server.load(path));
+    commands.insert_resource(OwnPicture(own));
+
+    commands.insert_resource(Sounds {
+        slide: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.05)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        solved: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4).fade(),
+        ])),
+    });
+}
+
+fn size_name(size: usize) -> String {
+    format!("{}x{}", size, size)
+}
+
+/// Saves the time if it is the fastest on this size yet.
+fn solved_enter(
+    mut session: ResMut<Session>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.solved.clone());
+    let name = size_name(session.puzzle.size);
+    session.new_record = match records.0.get(&name) {
+        Some(record) => session.elapsed < *record,
+        None => true,
+    };
+    if !session.new_record {
+        return;
+    }
+    records.0.insert(name, session.elapsed);
+    if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, &*records) {
+        eprintln!("could not save records: {}", error);
+    }
+}
+
+/// Enter goes back to the menu.
+fn solved_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// A tile in its cell; a numbered one unless it has a piece of the picture. Tile 0 is the
+/// picture's missing corner.
+fn spawn_tile(
+    commands: &mut Commands,
+    font: &HudFont,
+    puzzle: &Puzzle,
+    number: u8,
+    texture: Option<Handle<Image>>,
+) {
+    let size = puzzle.size;
+    let side = BOARD / size as f32 - GAP;
+    let sprite = Sprite {
+        color: if texture.is_some() {
+            Color::WHITE
+        } else {
+            TILE_COLOR
+        },
+        custom_size: Some(Vec2::splat(side)),
+        ..Default::default()
+    };
+    let mut tile = commands.spawn_bundle(SpriteBundle {
+        sprite,
+        texture: texture.clone().unwrap_or_default(),
+        transform: Transform::from_translation(
+            cell_center(puzzle.cell_of(number), size).extend(1.0),
+        ),
+        ..Default::default()
+    });
+    tile.insert(Tile(number));
+    if texture.is_none() {
+        tile.with_children(|parent| {
+            parent.spawn_bundle(game_hud::world_text(
+                font,
+                number.to_string(),
+                side * 0.45,
+                TILE_TEXT_COLOR,
+                Vec3::new(0.0, 0.0, 1.0),
+            ));
+        });
+    }
+}
+
+fn texts_update(
+    menu: Res<Menu>,
+    records: Res<Records>,
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+        QueryState<&mut Text, With<ResultText>>,
+    )>,
+) {
+    let hud = match state.current() {
+        GameState::Menu => String::new(),
+        _ => format!(
+            "Moves {}   Time {}",
+            session.moves,
+            format_time(session.elapsed)
+        ),
+    };
+    let (help, banner, result) = match state.current() {
+        GameState::Menu => {
+            let size = SIZES[menu.index];
+            let best = match records.0.get(&size_name(size)) {
+                Some(seconds) => format!("Best time {}", format_time(*seconds)),
+                None => "Not solved yet".to_string(),
+            };
+            (
+                "Left/Right: board size   P: numbers or picture   Enter: start",
+                format!(
+                    "FIFTEEN\n\nBoard  < {} >\nTiles  {}\n\n{}",
+                    size_name(size),
+                    if menu.picture { "picture" } else { "numbers" },
+                    best
+                ),
+                String::new(),
+            )
+        }
+        GameState::Playing => (
+            "Click: slide tiles   Arrows: slide into the gap   Esc: menu",
+            String::new(),
+            String::new(),
+        ),
+        GameState::Solved => (
+            "Enter: menu",
+            String::new(),
+            format!(
+                "Solved in {} with {} moves{}",
+                format_time(session.elapsed),
+                session.moves,
+                if session.new_record { "  NEW BEST" } else { "" }
+            ),
+        ),
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.to_string();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+    for mut text in texts.q3().iter_mut() {
+        text.sections[0].value = result.clone();
+    }
+}
+
+/// Lays out the tiles of the new puzzle, cut from the picture in picture mode.
+fn tiles_spawn(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut images: ResMut<Assets<Image>>,
+    own_picture: Res<OwnPicture>,
+    menu: Res<Menu>,
+    session: Res<Session>,
+    tiles: Query<Entity, With<Tile>>,
+) {
+    for entity in tiles.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let puzzle = &session.puzzle;
+    let pictures = if menu.picture {
+        picture_tiles(&mut images, &own_picture, puzzle.size)
+    } else {
+        Vec::new()
+    };
+    let count = (puzzle.size * puzzle.size) as u8;
+    for number in 1..count {
+        let texture = pictures.get(number as usize - 1).cloned();
+        spawn_tile(&mut commands, &font, puzzle, number, texture);
+    }
+    if let Some(last) = pictures.last() {
+        // The missing corner, hidden until the picture is complete.
+        spawn_tile(&mut commands, &font, puzzle, 0, Some(last.clone()));
+    }
+}
+
+/// Slides every tile towards its cell, shows the picture's missing corner once solved and the
+/// shade over the board in the menu.
+fn tiles_update(
+    time: Res<Time>,
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    mut tiles: Query<(&Tile, &mut Transform, &mut Visibility)>,
+    mut shades: Query<&mut Visibility, (With<Shade>, Without<Tile>)>,
+) {
+    let step = SLIDE_SPEED * time.delta_seconds();
+    let size = session.puzzle.size;
+    for (tile, mut transform, mut visibility) in tiles.iter_mut() {
+        if tile.0 == 0 {
+            visibility.is_visible = *state.current() == GameState::Solved;
+        }
+        let target = cell_center(session.puzzle.cell_of(tile.0), size).extend(1.0);
+        let offset = target - transform.translation;
+        transform.translation = if offset.length() <= step {
+            target
+        } else {
+            transform.translation + offset.normalize() * step
+        };
+    }
+    for mut visibility in shades.iter_mut() {
+        visibility.is_visible = *state.current() == GameState::Menu;
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use fifteen::FifteenPlugin;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(fifteen::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(FifteenPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,83 @@
+//! Pictures for the picture mode as raw RGBA pixels, rows from the top, cut into square tiles.
+
+/// A landscape to play with when no picture of one's own is given: a sky fading into the
+/// evening, a sun, two rows of hills and a lake.
+pub fn generate(side: usize) -> Vec<u8> {
+    let mut data = Vec::with_capacity(side * side * 4);
+    let sun = (side as f32 * 0.68, side as f32 * 0.34, side as f32 * 0.13);
+    for y in 0..side {
+        for x in 0..side {
+            let (u, v) = (x as f32 / side as f32, y as f32 / side as f32);
+            let far = 0.55 + 0.06 * (u * 9.0).sin() + 0.03 * (u * 23.0).cos();
+            let near = 0.68 + 0.08 * (u * 5.0 + 1.0).sin();
+            let distance = ((x as f32 - sun.0).powi(2) + (y as f32 - sun.1).powi(2)).sqrt();
+            let color = if v > 0.82 {
+                let ripple = 0.05 * ((v * 120.0).sin() * (u * 7.0).cos());
+                [0.15 + ripple, 0.35 + ripple, 0.55 + ripple]
+            } else if v > near {
+                [0.15, 0.42 - 0.15 * v, 0.18]
+            } else if v > far {
+                [0.35, 0.3, 0.45]
+            } else if distance < sun.2 {
+                [1.0, 0.85 - distance / sun.2 * 0.25, 0.35]
+            } else {
+                [0.95 - 0.5 * v, 0.45 + 0.2 * v, 0.35 + 0.55 * (1.0 - v)]
+            };
+            data.extend(
+                color
+                    .iter()
+                    .map(|channel| (channel.clamp(0.0, 1.0) * 255.0) as u8),
+            );
+            data.push(255);
+        }
+    }
+    data
+}
+
+/// Cuts the largest centered square out of a `width` by `height` picture into `size` by `size`
+/// tiles in reading order, each `(side, data)` with `side` pixels across.
+pub fn slice(data: &[u8], width: usize, height: usize, size: usize) -> Vec<(usize, Vec<u8>)> {
+    let square = width.min(height);
+    let side = square / size;
+    let left = (width - square) / 2;
+    let top = (height - square) / 2;
+    let mut tiles = Vec::with_capacity(size * size);
+    for row in 0..size {
+        for column in 0..size {
+            let mut tile = Vec::with_capacity(side * side * 4);
+            for y in 0..side {
+                let start = ((top + row * side + y) * width + left + column * side) * 4;
+                tile.extend_from_slice(&data[start..start + side * 4]);
+            }
+            tiles.push((side, tile));
+        }
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn slices_the_centered_square() {
+        // 6 by 4 pixels whose red channel is the column and green channel the row.
+        let (width, height) = (6, 4);
+        let mut data = Vec::new();
+        for y in 0..height {
+            for x in 0..width {
+                data.extend([x as u8, y as u8, 0, 255]);
+            }
+        }
+        let tiles = slice(&data, width, height, 2);
+        assert_eq!(tiles.len(), 4);
+        assert!(tiles
+            .iter()
+            .all(|(side, tile)| *side == 2 && tile.len() == 16));
+        // The square runs from column 1 to 4.
+        assert_eq!(&tiles[0].1[..2], &[1, 0]);
+        assert_eq!(&tiles[3].1[..2], &[3, 2]);
+        assert_eq!(&tiles[3].1[12..14], &[4, 3]);
+        assert_eq!(generate(40).len(), 40 * 40 * 4);
+    }
+}
@@ -0,0 +1,165 @@
+//! The tiles on a square board, numbered from 1 with 0 for the gap. Cells are (column, row),
+//! rows counting down from the top; solved, the numbers run in reading order with the gap last.
+
+use rand::{seq::SliceRandom, Rng};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub size: usize,
+    tiles: Vec<u8>,
+}
+
+impl Puzzle {
+    pub fn solved(size: usize) -> Self {
+        let count = size * size;
+        let mut tiles: Vec<u8> = (1..count as u8).collect();
+        tiles.push(0);
+        Self { size, tiles }
+    }
+
+    /// A random order that can be solved and is not solved already. Half of all orders cannot
+    /// be, so an unsolvable one has two tiles swapped to fix it.
+    pub fn shuffled(size: usize, rng: &mut impl Rng) -> Self {
+        loop {
+            let mut puzzle = Self::solved(size);
+            puzzle.tiles.shuffle(rng);
+            if !puzzle.is_solvable() {
+                let (first, second) = match puzzle.tiles.iter().position(|tile| *tile == 0) {
+                    Some(0) | Some(1) => (2, 3),
+                    _ => (0, 1),
+                };
+                puzzle.tiles.swap(first, second);
+            }
+            if !puzzle.is_solved() {
+                return puzzle;
+            }
+        }
+    }
+
+    /// Where the gap is.
+    pub fn gap(&self) -> (usize, usize) {
+        self.cell_of(0)
+    }
+
+    /// Where a tile is.
+    pub fn cell_of(&self, tile: u8) -> (usize, usize) {
+        let index = self
+            .tiles
+            .iter()
+            .position(|other| *other == tile)
+            .unwrap_or(0);
+        (index % self.size, index / self.size)
+    }
+
+    pub fn get(&self, (x, y): (usize, usize)) -> u8 {
+        self.tiles[y * self.size + x]
+    }
+
+    /// Counting the pairs of tiles in the wrong order: on boards an odd number of cells across
+    /// that count has to be even, on the others it has to differ in parity from the row the gap
+    /// is in counted from the bottom.
+    pub fn is_solvable(&self) -> bool {
+        let numbers: Vec<u8> = self
+            .tiles
+            .iter()
+            .copied()
+            .filter(|tile| *tile != 0)
+            .collect();
+        let inversions = (0..numbers.len())
+            .map(|first| {
+                numbers[first + 1..]
+                    .iter()
+                    .filter(|later| **later < numbers[first])
+                    .count()
+            })
+            .sum::<usize>();
+        if self.size % 2 == 1 {
+            inversions % 2 == 0
+        } else {
+            let row_from_bottom = self.size - self.gap().1;
+            (inversions + row_from_bottom) % 2 == 1
+        }
+    }
+
+    pub fn is_solved(&self) -> bool {
+        *self == Self::solved(self.size)
+    }
+
+    /// Slides the tiles between `cell` and the gap one step towards the gap, if it is in the same
+    /// row or column. Returns the tiles that moved.
+    pub fn slide(&mut self, cell: (usize, usize)) -> Vec<u8> {
+        let gap = self.gap();
+        if cell == gap || (cell.0 != gap.0 && cell.1 != gap.1) {
+            return Vec::new();
+        }
+        let mut moved = Vec::new();
+        let mut current = gap;
+        while current != cell {
+            let next = (
+                step_towards(current.0, cell.0),
+                step_towards(current.1, cell.1),
+            );
+            let tile = self.get(next);
+            self.set(current, tile);
+            self.set(next, 0);
+            moved.push(tile);
+            current = next;
+        }
+        moved
+    }
+
+    fn set(&mut self, (x, y): (usize, usize), tile: u8) {
+        self.tiles[y * self.size + x] = tile;
+    }
+}
+
+fn step_towards(from: usize, to: usize) -> usize {
+    if from < to {
+        from + 1
+    } else if from > to {
+        from - 1
+    } else {
+        from
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn slides_a_line_of_tiles_into_the_gap() {
+        let mut puzzle = Puzzle::solved(4);
+        assert!(puzzle.slide((1, 1)).is_empty());
+        assert_eq!(puzzle.slide((0, 3)), vec![15, 14, 13]);
+        assert_eq!(puzzle.gap(), (0, 3));
+        assert_eq!(puzzle.get((3, 3)), 15);
+        assert_eq!(puzzle.slide((0, 1)), vec![9, 5]);
+        assert_eq!(puzzle.cell_of(5), (0, 2));
+        assert!(puzzle.is_solvable());
+        assert!(!puzzle.is_solved());
+    }
+
+    #[test]
+    fn swapping_two_tiles_makes_it_unsolvable() {
+        for size in 3..=5 {
+            let mut puzzle = Puzzle::solved(size);
+            assert!(puzzle.is_solvable());
+            puzzle.tiles.swap(0, 1);
+            assert!(!puzzle.is_solvable());
+        }
+    }
+
+    #[test]
+    fn shuffles_are_solvable() {
+        let mut rng = StdRng::seed_from_u64(8);
+        for size in 3..=5 {
+            for _ in 0..20 {
+                let puzzle = Puzzle::shuffled(size, &mut rng);
+                assert!(puzzle.is_solvable());
+                assert!(!puzzle.is_solved());
+            }
+        }
+    }
+}
@@ -0,0 +1,586 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.08);
+const BOARD_COLUMNS: i32 = 13;
+const BOARD_ROWS: i32 = 13;
+const CELL: f32 = 48.0;
+const FROG_COLOR: Color = Color::rgb(0.3, 0.95, 0.3);
+const HOME_COLOR: Color = Color::rgb(0.1, 0.3, 0.1);
+const HOME_COLUMNS: [i32; 5] = [1, 3, 6, 9, 11];
+const HOME_ROW: i32 = 12;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const LIFE_TIME: f32 = 30.0;
+const LOG_COLOR: Color = Color::rgb(0.55, 0.35, 0.15);
+const RIVER_COLOR: Color = Color::rgb(0.05, 0.1, 0.45);
+const ROAD_COLOR: Color = Color::rgb(0.12, 0.12, 0.12);
+const ROUND_SPEEDUP: f32 = 0.15;
+const SAFE_COLOR: Color = Color::rgb(0.35, 0.15, 0.45);
+const STARTING_LIVES: u32 = 3;
+const TRUCK_COLOR: Color = Color::rgb(0.85, 0.85, 0.8);
+const TURTLE_COLOR: Color = Color::rgb(0.8, 0.2, 0.2);
+/// Objects leave the board and re-enter on the other side once they are this far off-screen.
+const WRAP_MARGIN: f32 = 3.0 * CELL;
+
+pub const WINDOW_HEIGHT: f32 = BOARD_ROWS as f32 * CELL + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = BOARD_COLUMNS as f32 * CELL;
+
+/// Every lane on the board: row, what travels in it, speed in cells per second, length in cells and how many.
+const LANES: [(i32, LaneKind, f32, f32, usize); 10] = [
+    (1, LaneKind::Car(Color::rgb(0.95, 0.85, 0.2)), -1.0, 1.0, 3),
+    (2, LaneKind::Car(Color::rgb(0.9, 0.3, 0.9)), 1.3, 1.0, 3),
+    (3, LaneKind::Car(Color::rgb(0.3, 0.7, 1.0)), -1.7, 1.0, 4),
+    (4, LaneKind::Car(Color::rgb(1.0, 1.0, 1.0)), 2.6, 1.0, 1),
+    (5, LaneKind::Car(TRUCK_COLOR), -0.9, 2.0, 2),
+    (7, LaneKind::Turtle, -1.0, 3.0, 3),
+    (8, LaneKind::Log, 0.8, 3.0, 3),
+    (9, LaneKind::Log, 1.8, 5.0, 2),
+    (10, LaneKind::Turtle, -1.2, 2.0, 4),
+    (11, LaneKind::Log, 1.1, 4.0, 3),
+];
+
+/// Turtles surface for most of the cycle, start sinking and are briefly fully under water.
+#[derive(Component)]
+struct Diving {
+    phase: u32,
+    timer: Timer,
+}
+
+impl Diving {
+    const CYCLE: u32 = 8;
+
+    fn submerged(&self) -> bool {
+        self.phase == Self::CYCLE - 1
+    }
+}
+
+#[derive(Component)]
+struct Frog {
+    best_row: i32,
+    row: i32,
+}
+
+struct FrogDiedEvent;
+
+#[derive(Default)]
+struct Game {
+    lives: u32,
+    round: u32,
+    score: u32,
+}
+
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HomeSlot {
+    column: i32,
+    filled: bool,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Clone, Copy, PartialEq)]
+enum LaneKind {
+    Car(Color),
+    Log,
+    Turtle,
+}
+
+struct LifeTimer(Timer);
+
+#[derive(Component)]
+struct Mover {
+    length: f32,
+    row: i32,
+    speed: f32,
+}
+
+struct Sounds {
+    home: Handle<AudioSource>,
+    hop: Handle<AudioSource>,
+    squash: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Movement,
+    Collision,
+}
+
+pub struct FroggerPlugin;
+
+impl Plugin for FroggerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::default())
+            .insert_resource(LifeTimer(Timer::from_seconds(LIFE_TIME, false)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_event::<FrogDiedEvent>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(frog_input.label(Step::Movement))
+                    .with_system(mover_movement.label(Step::Movement))
+                    .with_system(turtle_diving.label(Step::Movement))
+                    .with_system(frog_collisions.label(Step::Collision).after(Step::Movement))
+                    .with_system(life_countdown.label(Step::Collision))
+                    .with_system(frog_died.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(restart_input))
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Frogger".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn board_spawn(commands: &mut Commands) {
+    for row in 0..BOARD_ROWS {
+        let color = match row {
+            0 | 6 => SAFE_COLOR,
+            1..=5 => ROAD_COLOR,
+            HOME_ROW => HOME_COLOR,
+            _ => RIVER_COLOR,
+        };
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, CELL)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, row_y(row), 0.0),
+            ..Default::default()
+        });
+    }
+}
+
+fn column_x(column: i32) -> f32 {
+    -WINDOW_WIDTH / 2.0 + (column as f32 + 0.5) * CELL
+}
+
+fn frog_collisions(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut life_timer: ResMut<LifeTimer>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut died_writer: EventWriter<FrogDiedEvent>,
+    mut frogs: Query<(&mut Frog, &mut Transform)>,
+    movers: Query<(&Mover, &Transform, Option<&Diving>), Without<Frog>>,
+    mut homes: Query<(&mut HomeSlot, &mut Sprite)>,
+) {
+    let (mut frog, mut transform) = match frogs.iter_mut().next() {
+        Some(frog) => frog,
+        None => return,
+    };
+    let x = transform.translation.x;
+    // Cars hit as soon as they touch the frog, while logs and turtles need it standing on them.
+    let reach = if frog.row < 6 { CELL * 0.5 } else { 0.0 };
+    let under_frog = movers.iter().find(|(mover, mover_transform, diving)| {
+        mover.row == frog.row
+            && (mover_transform.translation.x - x).abs() < (mover.length * CELL + reach) / 2.0
+            && !diving.map_or(false, |diving| diving.submerged())
+    });
+    match frog.row {
+        1..=5 => {
+            if under_frog.is_some() {
+                died_writer.send(FrogDiedEvent);
+            }
+        }
+        7..=11 => match under_frog {
+            Some((mover, _, _)) => {
+                // Logs and turtles carry the frog; being carried off the board is fatal.
+                transform.translation.x +=
+                    mover.speed * speedup(game.round) * CELL * time.delta_seconds();
+                if transform.translation.x.abs() > WINDOW_WIDTH / 2.0 {
+                    died_writer.send(FrogDiedEvent);
+                }
+            }
+            None => died_writer.send(FrogDiedEvent),
+        },
+        HOME_ROW => {
+            let mut landed = false;
+            for (mut home, mut sprite) in homes.iter_mut() {
+                if !home.filled && (column_x(home.column) - x).abs() < CELL * 0.5 {
+                    home.filled = true;
+                    sprite.color = FROG_COLOR;
+                    landed = true;
+                }
+            }
+            if !landed {
+                died_writer.send(FrogDiedEvent);
+                return;
+            }
+            let remaining = life_timer.0.duration().as_secs_f32() - life_timer.0.elapsed_secs();
+            game.score += 50 + remaining as u32 * 10;
+            audio.play(sounds.home.clone());
+            if homes.iter().all(|(home, _)| home.filled) {
+                game.round += 1;
+                game.score += 1000;
+                for (mut home, mut sprite) in homes.iter_mut() {
+                    home.filled = false;
+                    sprite.color = RIVER_COLOR;
+                }
+            }
+            frog_reset(&mut frog, &mut transform);
+            life_timer.0.reset();
+        }
+        _ => {}
+    }
+}
+
+fn frog_died(
+    mut died_reader: EventReader<FrogDiedEvent>,
+    mut game: ResMut<Game>,
+    mut life_timer: ResMut<LifeTimer>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut frogs: Query<(&mut Frog, &mut Transform)>,
+) {
+    if died_reader.iter().next().is_none() {
+        return;
+    }
+    audio.play(sounds.squash.clone());
+    game.lives = game.lives.saturating_sub(1);
+    if game.lives == 0 {
+        state.set(GameState::GameOver).unwrap();
+        return;
+    }
+    life_timer.0.reset();
+    for (mut frog, mut transform) in frogs.iter_mut() {
+        frog_reset(&mut frog, &mut transform);
+    }
+}
+
+fn frog_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut frogs: Query<(&mut Frog, &mut Transform)>,
+) {
+    if let Some((mut frog, mut transform)) = frogs.iter_mut().next() {
+        let (dx, dy) = if keyboard_input.any_just_pressed([KeyCode::Up, KeyCode::W]) {
+            (0.0, 1)
+        } else if keyboard_input.any_just_pressed([KeyCode::Down, KeyCode::S]) {
+            (0.0, -1)
+        } else if keyboard_input.any_just_pressed([KeyCode::Left, KeyCode::A]) {
+            (-CELL, 0)
+        } else if keyboard_input.any_just_pressed([KeyCode::Right, KeyCode::D]) {
+            (CELL, 0)
+        } else {
+            return;
+        };
+        let limit = WINDOW_WIDTH / 2.0 - CELL / 2.0;
+        frog.row = (frog.row + dy).max(0).min(HOME_ROW);
+        transform.translation.x = (transform.translation.x + dx).max(-limit).min(limit);
+        transform.translation.y = row_y(frog.row);
+        if frog.row > frog.best_row {
+            frog.best_row = frog.row;
+            game.score += 10;
+        }
+        audio.play(sounds.hop.clone());
+    }
+}
+
+fn frog_reset(frog: &mut Frog, transform: &mut Transform) {
+    frog.row = 0;
+    frog.best_row = 0;
+    transform.translation = Vec3::new(column_x(BOARD_COLUMNS / 2), row_y(0), 2.0);
+}
+
+fn frog_spawn(commands: &mut Commands) {
+    let mut frog = Frog {
+        best_row: 0,
+        row: 0,
+    };
+    let mut transform = Transform::default();
+    frog_reset(&mut frog, &mut transform);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: FROG_COLOR,
+                custom_size: Some(Vec2::splat(CELL * 0.6)),
+                ..Default::default()
+            },
+            transform,
+            ..Default::default()
+        })
+        .insert(frog)
+        .with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::WHITE,
+                        custom_size: Some(Vec2::splat(CELL * 0.15)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(side * CELL * 0.18, CELL * 0.22, 0.1),
+                    ..Default::default()
+                });
+            }
+        });
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("GAME OVER\nScore {}\nPress Enter", game.score),
+            40.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(GameOverText);
+}
+
+fn homes_spawn(commands: &mut Commands) {
+    for column in HOME_COLUMNS {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: RIVER_COLOR,
+                    custom_size: Some(Vec2::splat(CELL * 0.9)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(column_x(column), row_y(HOME_ROW), 1.0),
+                ..Default::default()
+            })
+            .insert(HomeSlot {
+                column,
+                filled: false,
+            });
+    }
+}
+
+fn hud_update(
+    game: Res<Game>,
+    life_timer: Res<LifeTimer>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let remaining = life_timer.0.duration().as_secs_f32() - life_timer.0.elapsed_secs();
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "SCORE {:05}   LIVES {}   ROUND {}   TIME {:>2}",
+            game.score,
+            game.lives,
+            game.round,
+            remaining.ceil() as u32
+        );
+    }
+}
+
+fn life_countdown(
+    time: Res<Time>,
+    mut life_timer: ResMut<LifeTimer>,
+    mut died_writer: EventWriter<FrogDiedEvent>,
+) {
+    if life_timer.0.tick(time.delta()).just_finished() {
+        died_writer.send(FrogDiedEvent);
+    }
+}
+
+fn mover_movement(time: Res<Time>, game: Res<Game>, mut movers: Query<(&Mover, &mut Transform)>) {
+    let speedup = speedup(game.round);
+    let span = WINDOW_WIDTH + 2.0 * WRAP_MARGIN;
+    for (mover, mut transform) in movers.iter_mut() {
+        transform.translation.x += mover.speed * speedup * CELL * time.delta_seconds();
+        if transform.translation.x > WINDOW_WIDTH / 2.0 + WRAP_MARGIN {
+            transform.translation.x -= span;
+        } else if transform.translation.x < -WINDOW_WIDTH / 2.0 - WRAP_MARGIN {
+            transform.translation.x += span;
+        }
+    }
+}
+
+fn movers_spawn(commands: &mut Commands) {
+    let span = WINDOW_WIDTH + 2.0 * WRAP_MARGIN;
+    for (row, kind, speed, length, count) in LANES {
+        for index in 0..count {
+            let x = -span / 2.0 + span * index as f32 / count as f32;
+            let color = match kind {
+                LaneKind::Car(color) => color,
+                LaneKind::Log => LOG_COLOR,
+                LaneKind::Turtle => TURTLE_COLOR,
+            };
+            let mut entity = commands.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(length * CELL - 6.0, CELL * 0.75)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(x, row_y(row), 1.0),
+                ..Default::default()
+            });
+            entity.insert(Mover { length, row, speed });
+            // Only every other turtle group dives, so the river stays crossable.
+            if kind == LaneKind::Turtle && index % 2 == 0 {
+                entity.insert(Diving {
+                    phase: 0,
+                    timer: Timer::from_seconds(0.6, true),
+                });
+            }
+        }
+    }
+}
+
+fn new_game(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut life_timer: ResMut<LifeTimer>,
+    leftovers: Query<Entity, Or<(With<Frog>, With<Mover>, With<HomeSlot>, With<GameOverText>)>>,
+) {
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    *game = Game {
+        lives: STARTING_LIVES,
+        round: 1,
+        score: 0,
+    };
+    life_timer.0.reset();
+    homes_spawn(&mut commands);
+    movers_spawn(&mut commands);
+    frog_spawn(&mut commands);
+}
+
+fn restart_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn row_y(row: i32) -> f32 {
+    -WINDOW_HEIGHT / 2.0 + (row as f32 + 0.5) * CELL
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    board_spawn(&mut commands);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.insert_resource(Sounds {
+        home: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.1).volume(0.25),
+            Tone::new(Waveform::Square, 659.0, 0.1).volume(0.25),
+            Tone::new(Waveform::Square, 784.0, 0.2).volume(0.25),
+        ])),
+        hop: audio_sources.add(
+            Tone::new(Waveform::Square, 300.0, 0.05)
+                .slide(500.0)
+                .volume(0.15)
+                .into(),
+        ),
+        squash: audio_sources.add(Tone::new(Waveform::Noise, 0.0, 0.4).fade().into()),
+    });
+}
+
+fn speedup(round: u32) -> f32 {
+    1.0 + ROUND_SPEEDUP * round.saturating_sub(1) as f32
+}
+
+fn turtle_diving(time: Res<Time>, mut turtles: Query<(&mut Diving, &mut Sprite)>) {
+    for (mut diving, mut sprite) in turtles.iter_mut() {
+        if diving.timer.tick(time.delta()).just_finished() {
+            diving.phase = (diving.phase + 1) % Diving::CYCLE;
+            sprite.color = if diving.submerged() {
+                RIVER_COLOR
+            } else if diving.phase >= Diving::CYCLE - 3 {
+                Color::rgb(0.45, 0.15, 0.3)
+            } else {
+                TURTLE_COLOR
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cars_drive_on_the_road_and_the_rest_float_on_the_river() {
+        for (row, kind, speed, length, count) in LANES {
+            match kind {
+                LaneKind::Car(_) => assert!((1..=5).contains(&row)),
+                LaneKind::Log | LaneKind::Turtle => assert!((7..=11).contains(&row)),
+            }
+            assert!(speed != 0.0);
+            assert!(length * CELL * count as f32 <= WINDOW_WIDTH + 2.0 * WRAP_MARGIN);
+        }
+        let mut rows: Vec<i32> = LANES.iter().map(|lane| lane.0).collect();
+        rows.dedup();
+        assert_eq!(rows.len(), LANES.len());
+    }
+
+    #[test]
+    fn cells_are_centred_on_the_board() {
+        assert_eq!(column_x(BOARD_COLUMNS / 2), 0.0);
+        assert_eq!(column_x(0) + column_x(BOARD_COLUMNS - 1), 0.0);
+        assert_eq!(row_y(1) - row_y(0), CELL);
+        assert_eq!(
+            row_y(HOME_ROW) + CELL / 2.0,
+            WINDOW_HEIGHT / 2.0 - HUD_HEIGHT
+        );
+        assert!(HOME_COLUMNS
+            .iter()
+            .all(|column| (0..BOARD_COLUMNS).contains(column)));
+    }
+
+    #[test]
+    fn turtles_dive_once_a_cycle() {
+        let submerged = (0..Diving::CYCLE)
+            .filter(|phase| {
+                Diving {
+                    phase: *phase,
+                    timer: Timer::from_seconds(1.0, true),
+                }
+                .submerged()
+            })
+            .count();
+        assert_eq!(submerged, 1);
+    }
+
+    #[test]
+    fn rounds_speed_everything_up() {
+        assert_eq!(speedup(0), 1.0);
+        assert_eq!(speedup(1), 1.0);
+        assert!((speedup(3) - (1.0 + 2.0 * ROUND_SPEEDUP)).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,964 @@
+//! A stage of the battle: enemies flying in along their entry paths to take their place in the
+//! formation, diving at the player, bosses catching the player's fighter with a tractor beam,
+//! and the challenging stages where the enemies only fly past.
+
+use crate::path::Path;
+use bevy::math::Vec2;
+use rand::{seq::SliceRandom, Rng};
+
+/// How wide the arena is, and where its top and bottom are.
+pub const ARENA_HALF_WIDTH: f32 = 240.0;
+pub const ARENA_TOP: f32 = 330.0;
+const ARENA_BOTTOM: f32 = -350.0;
+/// Half the width of the tractor beam where the player flies, fully open.
+pub const BEAM_HALF_WIDTH: f32 = 44.0;
+/// Seconds a boss keeps its tractor beam on; it opens over the first quarter.
+pub const BEAM_TIME: f32 = 3.0;
+/// Where a boss hovers to beam down.
+pub const BEAM_Y: f32 = -120.0;
+const BULLET_SPEED: f32 = 700.0;
+/// Points for wrecking the captured fighter while its boss is in formation.
+const CAPTIVE_POINTS: u32 = 1000;
+/// Enemies in a challenging stage.
+pub const CHALLENGE_COUNT: usize = 40;
+/// Points for each enemy hit in a challenging stage, and for hitting all of them.
+pub const CHALLENGE_HIT: u32 = 100;
+pub const CHALLENGE_PERFECT: u32 = 10000;
+/// How far the second fighter of a dual fighter flies to the right of the first.
+pub const DUAL_OFFSET: f32 = 30.0;
+const ENEMY_BULLET_SPEED: f32 = 320.0;
+pub const ENEMY_RADIUS: f32 = 13.0;
+/// Scores at which an extra fighter is given: the first, then every this many more.
+const EXTRA_LIVES: (u32, u32) = (20000, 70000);
+const FORMATION_COLUMNS: f32 = 10.0;
+pub const FORMATION_SPACING: Vec2 = bevy::math::const_vec2!([34.0, 30.0]);
+const FORMATION_TOP: f32 = 250.0;
+/// Seconds the stage number shows before the first enemies arrive.
+pub const INTRO_TIME: f32 = 2.5;
+/// Player bullets in the air at a time, for a single fighter.
+const MAX_BULLETS: usize = 2;
+pub const PLAYER_Y: f32 = -300.0;
+/// Seconds after losing a fighter before the next one comes.
+const RESPAWN_TIME: f32 = 2.0;
+pub const SHIP_HALF_WIDTH: f32 = 13.0;
+const SHIP_SPEED: f32 = 300.0;
+const STARTING_LIVES: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bullet {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Controls {
+    pub fire: bool,
+    pub left: bool,
+    pub right: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enemy {
+    /// Whether it holds the player's captured fighter, which flies just above it.
+    pub captive: bool,
+    /// Whether a boss has been hit once already.
+    pub damaged: bool,
+    /// Seconds before it may fire again.
+    fire: f32,
+    pub flight: Flight,
+    pub heading: Vec2,
+    pub kind: Kind,
+    pub position: Vec2,
+    /// Column and row of its place in the formation.
+    pub slot: (usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// A boss started its tractor beam.
+    Beam,
+    /// A boss took its first hit.
+    BossDamaged,
+    /// The player's fighter was caught by a tractor beam.
+    Captured,
+    /// The captured fighter was shot down.
+    CaptiveLost,
+    /// A challenging stage is over, with this many enemies hit.
+    ChallengeOver(u32),
+    Destroyed(Kind),
+    Dive,
+    ExtraLife,
+    GameOver,
+    PlayerDestroyed,
+    /// The captured fighter was set free and joined the player's.
+    Rescued,
+    Shot,
+    StageClear,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Flight {
+    /// A boss on its way to beam, or beaming with this many seconds left.
+    Beaming {
+        path: Path,
+        distance: f32,
+        left: f32,
+    },
+    /// On an attack run down the arena, leaving it at the bottom.
+    Diving {
+        path: Path,
+        distance: f32,
+    },
+    /// Swooping in along its entry path, or past in a challenging stage.
+    Entering {
+        path: Path,
+        distance: f32,
+    },
+    /// Flying straight back to its place in the formation.
+    Homing,
+    InFormation,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Bee,
+    /// Takes two hits and can capture the player's fighter.
+    Boss,
+    Butterfly,
+}
+
+impl Kind {
+    /// Points for shooting it down in formation and on an attack run.
+    pub fn points(self) -> (u32, u32) {
+        match self {
+            Self::Bee => (50, 100),
+            Self::Boss => (150, 400),
+            Self::Butterfly => (80, 160),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Player {
+    /// Whether a rescued fighter flies alongside, to the right.
+    pub dual: bool,
+    pub x: f32,
+}
+
+impl Player {
+    /// Where the fighters are.
+    pub fn ships(&self) -> Vec<f32> {
+        if self.dual {
+            vec![self.x, self.x + DUAL_OFFSET]
+        } else {
+            vec![self.x]
+        }
+    }
+}
+
+/// An enemy still to come on, with the seconds into the stage it does.
+#[derive(Clone, Debug)]
+struct Spawn {
+    at: f32,
+    enemy: Enemy,
+}
+
+pub struct Battle {
+    pub bullets: Vec<Bullet>,
+    /// Whether the current stage is a challenging stage.
+    pub challenge: bool,
+    /// Seconds before the next dive.
+    dive: f32,
+    pub enemies: Vec<Enemy>,
+    pub enemy_bullets: Vec<Bullet>,
+    /// Enemies hit this challenging stage.
+    pub hits: u32,
+    /// Seconds the stage banner has left.
+    pub intro: f32,
+    /// Fighters in reserve, the one in play not counted.
+    pub lives: u32,
+    /// Score at which the next extra fighter is given.
+    next_extra: u32,
+    pub over: bool,
+    /// None while the next fighter is on its way.
+    pub player: Option<Player>,
+    /// Seconds before the next fighter comes.
+    respawn: f32,
+    pub score: u32,
+    spawns: Vec<Spawn>,
+    pub stage: u32,
+    /// Seconds into the stage, after the banner.
+    stage_time: f32,
+    /// Seconds since the battle began, which the formation sways by.
+    pub time: f32,
+}
+
+impl Battle {
+    pub fn new() -> Self {
+        let mut battle = Self {
+            bullets: Vec::new(),
+            challenge: false,
+            dive: 0.0,
+            enemies: Vec::new(),
+            enemy_bullets: Vec::new(),
+            hits: 0,
+            intro: 0.0,
+            lives: STARTING_LIVES,
+            next_extra: EXTRA_LIVES.0,
+            over: false,
+            player: Some(Player {
+                dual: false,
+                x: 0.0,
+            }),
+            respawn: 0.0,
+            score: 0,
+            spawns: Vec::new(),
+            stage: 0,
+            stage_time: 0.0,
+            time: 0.0,
+        };
+        battle.next_stage();
+        battle
+    }
+
+    fn add_points(&mut self, points: u32, events: &mut Vec<Event>) {
+        self.score += points;
+        if self.score >= self.next_extra {
+            self.next_extra += EXTRA_LIVES.1;
+            self.lives += 1;
+            events.push(Event::ExtraLife);
+        }
+    }
+
+    /// Sends enemies from the formation on attack runs, now and then: a boss either beams or
+    /// takes escorts along.
+    fn dive(&mut self, delta: f32, rng: &mut impl Rng, events: &mut Vec<Event>) {
+        if self.challenge || self.player.is_none() {
+            return;
+        }
+        self.dive -= delta;
+        if self.dive > 0.0 {
+            return;
+        }
+        self.dive = (3.0 - self.stage as f32 * 0.15).max(1.0) * rng.gen_range(0.6, 1.4);
+        let waiting: Vec<usize> = (0..self.enemies.len())
+            .filter(|index| self.enemies[*index].flight == Flight::InFormation)
+            .collect();
+        let index = match waiting.choose(rng) {
+            Some(index) => *index,
+            None => return,
+        };
+        let player = self.player.unwrap();
+        let start = self.enemies[index].position;
+        let beaming = self
+            .enemies
+            .iter()
+            .any(|enemy| matches!(enemy.flight, Flight::Beaming { .. }) || enemy.captive);
+        if self.enemies[index].kind == Kind::Boss && !player.dual && !beaming && rng.gen_bool(0.5) {
+            let x = player
+                .x
+                .clamp(-ARENA_HALF_WIDTH + 60.0, ARENA_HALF_WIDTH - 60.0);
+            let side = start.x.signum();
+            self.enemies[index].flight = Flight::Beaming {
+                path: Path::through(&[
+                    start,
+                    start + Vec2::new(side * 40.0, 30.0),
+                    Vec2::new((start.x + x) / 2.0, 40.0),
+                    Vec2::new(x, BEAM_Y),
+                ]),
+                distance: 0.0,
+                left: BEAM_TIME,
+            };
+            events.push(Event::Dive);
+            return;
+        }
+        let mut divers = vec![index];
+        if self.enemies[index].kind == Kind::Boss {
+            // Up to two butterflies from just below fly along.
+            let (column, _) = self.enemies[index].slot;
+            divers.extend(waiting.iter().copied().filter(|other| {
+                let enemy = &self.enemies[*other];
+                enemy.kind == Kind::Butterfly
+                    && enemy.slot.1 == 1
+                    && (enemy.slot.0 as i32 - column as i32).abs() <= 1
+            }));
+            divers.truncate(3);
+        }
+        for diver in divers {
+            let offset = self.enemies[diver].position - start;
+            let path = dive_path(start, player.x, rng);
+            let waypoints: Vec<Vec2> = (0..=8)
+                .map(|step| path.at(path.length() * step as f32 / 8.0) + offset)
+                .collect();
+            self.enemies[diver].flight = Flight::Diving {
+                path: Path::through(&waypoints),
+                distance: 0.0,
+            };
+            self.enemies[diver].fire = rng.gen_range(0.3, 1.2);
+        }
+        events.push(Event::Dive);
+    }
+
+    /// Moves the enemies along their flights and lets the diving ones fire.
+    fn fly(&mut self, delta: f32, rng: &mut impl Rng, events: &mut Vec<Event>) {
+        let speed = self.speed();
+        let player = self.player;
+        let (time, challenge, stage) = (self.time, self.challenge, self.stage);
+        let mut beams = Vec::new();
+        let mut shots = Vec::new();
+        self.enemies.retain_mut(|enemy| {
+            let before = enemy.position;
+            let slot = slot_position(enemy.slot, time);
+            let mut keep = true;
+            match &mut enemy.flight {
+                Flight::Entering { path, distance } => {
+                    *distance += speed * delta;
+                    enemy.position = path.at(*distance);
+                    if *distance >= path.length() {
+                        if challenge {
+                            keep = false;
+                        } else {
+                            enemy.flight = Flight::Homing;
+                        }
+                    }
+                }
+                Flight::Homing => {
+                    let step = speed * delta;
+                    if enemy.position.distance(slot) <= step {
+                        enemy.position = slot;
+                        enemy.flight = Flight::InFormation;
+                    } else {
+                        enemy.position += (slot - enemy.position).normalize() * step;
+                    }
+                }
+                Flight::InFormation => enemy.position = slot,
+                Flight::Diving { path, distance } => {
+                    *distance += speed * delta;
+                    enemy.position = path.at(*distance);
+                    if *distance >= path.length() {
+                        // Round from the top again, back to its place.
+                        enemy.position = Vec2::new(enemy.position.x, ARENA_TOP + 30.0);
+                        enemy.flight = Flight::Homing;
+                    }
+                }
+                Flight::Beaming {
+                    path,
+                    distance,
+                    left,
+                } => {
+                    if *distance < path.length() {
+                        *distance += speed * delta;
+                        enemy.position = path.at(*distance);
+                        if *distance >= path.length() {
+                            beams.push(enemy.position.x);
+                        }
+                    } else {
+                        *left -= delta;
+                        if *left <= 0.0 {
+                            enemy.flight = Flight::Homing;
+                        }
+                    }
+                }
+            }
+            let moved = enemy.position - before;
+            if moved.length() > 0.01 {
+                enemy.heading = moved.normalize();
+            } else if enemy.flight == Flight::InFormation {
+                enemy.heading = Vec2::Y;
+            }
+            if matches!(enemy.flight, Flight::Diving { .. }) && !challenge {
+                enemy.fire -= delta;
+                if let Some(player) = player {
+                    if enemy.fire <= 0.0 && enemy.position.y > PLAYER_Y + 150.0 {
+                        enemy.fire = (2.0 - stage as f32 * 0.1).max(0.6) * rng.gen_range(0.7, 1.3);
+                        let aim = Vec2::new(player.x, PLAYER_Y) - enemy.position;
+                        let aim = Vec2::new(aim.x.clamp(-aim.y.abs(), aim.y.abs()) * 0.5, aim.y);
+                        shots.push(Bullet {
+                            position: enemy.position,
+                            velocity: aim.normalize_or_zero() * ENEMY_BULLET_SPEED,
+                        });
+                    }
+                }
+            }
+            keep
+        });
+        for _ in beams {
+            events.push(Event::Beam);
+        }
+        self.enemy_bullets.extend(shots);
+    }
+
+    /// Checks the player's bullets against the enemies and the captured fighters.
+    fn hit_enemies(&mut self, events: &mut Vec<Event>) {
+        let mut index = 0;
+        while index < self.bullets.len() {
+            let bullet = self.bullets[index].position;
+            let captive = self.enemies.iter().position(|enemy| {
+                enemy.captive && bullet.distance(captive_position(enemy)) < ENEMY_RADIUS
+            });
+            if let Some(holder) = captive {
+                self.bullets.remove(index);
+                self.enemies[holder].captive = false;
+                events.push(Event::CaptiveLost);
+                continue;
+            }
+            let target = self
+                .enemies
+                .iter()
+                .position(|enemy| bullet.distance(enemy.position) < ENEMY_RADIUS);
+            let target = match target {
+                Some(target) => target,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            };
+            self.bullets.remove(index);
+            let enemy = &mut self.enemies[target];
+            if enemy.kind == Kind::Boss && !enemy.damaged {
+                enemy.damaged = true;
+                events.push(Event::BossDamaged);
+                continue;
+            }
+            let enemy = self.enemies.remove(target);
+            events.push(Event::Destroyed(enemy.kind));
+            if self.challenge {
+                self.hits += 1;
+                self.add_points(CHALLENGE_HIT, events);
+                continue;
+            }
+            let (resting, attacking) = enemy.kind.points();
+            let in_formation = matches!(enemy.flight, Flight::InFormation | Flight::Homing);
+            self.add_points(if in_formation { resting } else { attacking }, events);
+            if enemy.captive {
+                match self.player.as_mut() {
+                    Some(player) if !in_formation && !player.dual => {
+                        player.dual = true;
+                        player.x = player
+                            .x
+                            .min(ARENA_HALF_WIDTH - SHIP_HALF_WIDTH - DUAL_OFFSET);
+                        events.push(Event::Rescued);
+                    }
+                    _ => {
+                        self.add_points(CAPTIVE_POINTS, events);
+                        events.push(Event::CaptiveLost);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks enemy bullets, diving enemies and tractor beams against the player's fighters.
+    fn hit_player(&mut self, events: &mut Vec<Event>) {
+        let player = match self.player {
+            Some(player) => player,
+            None => return,
+        };
+        let mut lost = Vec::new();
+        for ship in player.ships() {
+            let near = |position: Vec2, reach: f32| {
+                (position.x - ship).abs() < reach && (position.y - PLAYER_Y).abs() < reach
+            };
+            if let Some(bullet) = self
+                .enemy_bullets
+                .iter()
+                .position(|bullet| near(bullet.position, SHIP_HALF_WIDTH))
+            {
+                self.enemy_bullets.remove(bullet);
+                lost.push(ship);
+            } else if let Some(enemy) = self
+                .enemies
+                .iter()
+                .position(|enemy| near(enemy.position, SHIP_HALF_WIDTH + ENEMY_RADIUS))
+            {
+                let enemy = self.enemies.remove(enemy);
+                events.push(Event::Destroyed(enemy.kind));
+                self.add_points(enemy.kind.points().1, events);
+                lost.push(ship);
+            }
+        }
+        if !lost.is_empty() {
+            if player.dual && lost.len() == 1 {
+                // One of the two is gone; the other flies on alone.
+                self.player = Some(Player {
+                    dual: false,
+                    x: player
+                        .ships()
+                        .into_iter()
+                        .find(|ship| *ship != lost[0])
+                        .unwrap(),
+                });
+                events.push(Event::PlayerDestroyed);
+                return;
+            }
+            events.push(Event::PlayerDestroyed);
+            self.lose_fighter(events);
+            return;
+        }
+
+        if player.dual {
+            return;
+        }
+        let captor = self.enemies.iter().position(|enemy| match &enemy.flight {
+            Flight::Beaming {
+                path,
+                distance,
+                left,
+            } if *distance >= path.length() => {
+                let open = ((BEAM_TIME - left) / (BEAM_TIME / 4.0)).min(1.0);
+                (enemy.position.x - player.x).abs() < BEAM_HALF_WIDTH * open
+            }
+            _ => false,
+        });
+        if let Some(captor) = captor {
+            let boss = &mut self.enemies[captor];
+            boss.captive = true;
+            boss.flight = Flight::Homing;
+            events.push(Event::Captured);
+            self.lose_fighter(events);
+        }
+    }
+
+    /// Whether `stage` is a challenging stage: the third, then every fourth.
+    pub fn is_challenge(stage: u32) -> bool {
+        stage % 4 == 3
+    }
+
+    /// Takes away the fighter in play; the next comes after a moment, if there is one.
+    fn lose_fighter(&mut self, events: &mut Vec<Event>) {
+        self.player = None;
+        self.bullets.clear();
+        if self.lives == 0 {
+            self.over = true;
+            events.push(Event::GameOver);
+        } else {
+            self.lives -= 1;
+            self.respawn = RESPAWN_TIME;
+        }
+    }
+
+    /// Moves on to the next stage and lines up its enemies.
+    fn next_stage(&mut self) {
+        self.stage += 1;
+        self.challenge = Self::is_challenge(self.stage);
+        self.enemy_bullets.clear();
+        self.hits = 0;
+        self.intro = INTRO_TIME;
+        self.stage_time = 0.0;
+        self.dive = 4.0;
+        self.spawns = stage_spawns(self.challenge, self.time);
+    }
+
+    fn speed(&self) -> f32 {
+        (220.0 + self.stage as f32 * 12.0).min(360.0)
+    }
+
+    /// Moves the fighter, fires, and brings on the next fighter once it is time.
+    fn steer(&mut self, controls: Controls, delta: f32, events: &mut Vec<Event>) {
+        let player = match self.player.as_mut() {
+            Some(player) => player,
+            None => {
+                self.respawn -= delta;
+                let formed = self.enemies.iter().all(|enemy| {
+                    !matches!(enemy.flight, Flight::Diving { .. } | Flight::Beaming { .. })
+                });
+                if self.respawn <= 0.0 && formed {
+                    self.player = Some(Player {
+                        dual: false,
+                        x: 0.0,
+                    });
+                }
+                return;
+            }
+        };
+        let direction = controls.right as i32 - controls.left as i32;
+        let right_edge = if player.dual {
+            ARENA_HALF_WIDTH - SHIP_HALF_WIDTH - DUAL_OFFSET
+        } else {
+            ARENA_HALF_WIDTH - SHIP_HALF_WIDTH
+        };
+        player.x = (player.x + direction as f32 * SHIP_SPEED * delta)
+            .clamp(-ARENA_HALF_WIDTH + SHIP_HALF_WIDTH, right_edge);
+        let ships = player.ships();
+        if controls.fire && self.bullets.len() + ships.len() <= MAX_BULLETS * ships.len() {
+            for ship in ships {
+                self.bullets.push(Bullet {
+                    position: Vec2::new(ship, PLAYER_Y + 12.0),
+                    velocity: Vec2::new(0.0, BULLET_SPEED),
+                });
+            }
+            events.push(Event::Shot);
+        }
+    }
+
+    /// Plays `delta` seconds.
+    pub fn update(&mut self, controls: Controls, delta: f32, rng: &mut impl Rng) -> Vec<Event> {
+        let mut events = Vec::new();
+        if self.over {
+            return events;
+        }
+        self.time += delta;
+        self.steer(controls, delta, &mut events);
+        for bullet in self.bullets.iter_mut().chain(self.enemy_bullets.iter_mut()) {
+            bullet.position += bullet.velocity * delta;
+        }
+        self.bullets
+            .retain(|bullet| bullet.position.y < ARENA_TOP + 20.0);
+        self.enemy_bullets
+            .retain(|bullet| bullet.position.y > ARENA_BOTTOM);
+
+        if self.intro > 0.0 {
+            self.intro -= delta;
+            return events;
+        }
+        self.stage_time += delta;
+        let now = self.stage_time;
+        while self.spawns.first().map(|spawn| spawn.at <= now) == Some(true) {
+            self.enemies.push(self.spawns.remove(0).enemy);
+        }
+        self.fly(delta, rng, &mut events);
+        self.dive(delta, rng, &mut events);
+        self.hit_enemies(&mut events);
+        self.hit_player(&mut events);
+
+        if self.enemies.is_empty() && self.spawns.is_empty() && !self.over {
+            if self.challenge {
+                events.push(Event::ChallengeOver(self.hits));
+                if self.hits == CHALLENGE_COUNT as u32 {
+                    self.add_points(CHALLENGE_PERFECT, &mut events);
+                }
+            } else {
+                events.push(Event::StageClear);
+            }
+            self.next_stage();
+        }
+        events
+    }
+}
+
+impl Default for Battle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Where the captured fighter flies, above its boss.
+pub fn captive_position(boss: &Enemy) -> Vec2 {
+    boss.position + Vec2::new(0.0, FORMATION_SPACING.y * 0.9)
+}
+
+/// An attack run from `start`: a roll outwards, a swoop down at the player and out at the bottom.
+fn dive_path(start: Vec2, target: f32, rng: &mut impl Rng) -> Path {
+    let side = if start.x < 0.0 { -1.0 } else { 1.0 };
+    let sway = rng.gen_range(60.0, 140.0) * -side;
+    Path::through(&[
+        start,
+        start + Vec2::new(side * 30.0, 30.0),
+        start + Vec2::new(side * 60.0, 0.0),
+        Vec2::new((start.x + target) / 2.0, 0.0),
+        Vec2::new(target, PLAYER_Y + 80.0),
+        Vec2::new(target + sway, ARENA_BOTTOM - 40.0),
+    ])
+}
+
+/// The four entry paths: a loop from the top left or right, or a sweep in from the lower left
+/// or right side.
+fn entry_waypoints(entry: usize) -> Vec<Vec2> {
+    let from_top = [
+        Vec2::new(-40.0, ARENA_TOP + 30.0),
+        Vec2::new(-30.0, 140.0),
+        Vec2::new(60.0, -20.0),
+        Vec2::new(130.0, -110.0),
+        Vec2::new(50.0, -170.0),
+        Vec2::new(-30.0, -100.0),
+        Vec2::new(0.0, 40.0),
+    ];
+    let from_side = [
+        Vec2::new(-ARENA_HALF_WIDTH - 30.0, -140.0),
+        Vec2::new(-120.0, -80.0),
+        Vec2::new(-10.0, -30.0),
+        Vec2::new(70.0, 50.0),
+        Vec2::new(20.0, 130.0),
+        Vec2::new(-60.0, 90.0),
+    ];
+    let (points, mirrored): (&[Vec2], bool) = match entry {
+        0 => (&from_top, false),
+        1 => (&from_top, true),
+        2 => (&from_side, false),
+        _ => (&from_side, true),
+    };
+    points
+        .iter()
+        .map(|point| {
+            if mirrored {
+                Vec2::new(-point.x, point.y)
+            } else {
+                *point
+            }
+        })
+        .collect()
+}
+
+/// Where a place in the formation is: it sways from side to side and breathes in and out.
+pub fn slot_position((column, row): (usize, usize), time: f32) -> Vec2 {
+    let spread = 1.0 + (time * 1.6).sin() * 0.08;
+    let sway = (time * 0.7).sin() * 20.0;
+    Vec2::new(
+        (column as f32 - (FORMATION_COLUMNS - 1.0) / 2.0) * FORMATION_SPACING.x * spread + sway,
+        FORMATION_TOP - row as f32 * FORMATION_SPACING.y,
+    )
+}
+
+/// The enemies of a stage, group by group: five groups of eight, each along its own entry
+/// path. In a normal stage they take their places in the formation: bosses in the top row,
+/// butterflies in the two below and bees at the bottom. In a challenging stage they only loop
+/// through the arena and leave.
+fn stage_spawns(challenge: bool, time: f32) -> Vec<Spawn> {
+    // Entry path, then the places filled as (column, row).
+    let groups: [(usize, [(usize, usize); 8]); 5] = [
+        (
+            0,
+            [
+                (3, 1),
+                (3, 3),
+                (4, 1),
+                (4, 3),
+                (5, 1),
+                (5, 3),
+                (6, 1),
+                (6, 3),
+            ],
+        ),
+        (
+            2,
+            [
+                (3, 0),
+                (1, 1),
+                (4, 0),
+                (2, 1),
+                (5, 0),
+                (7, 1),
+                (6, 0),
+                (8, 1),
+            ],
+        ),
+        (
+            3,
+            [
+                (1, 2),
+                (2, 2),
+                (3, 2),
+                (4, 2),
+                (5, 2),
+                (6, 2),
+                (7, 2),
+                (8, 2),
+            ],
+        ),
+        (
+            1,
+            [
+                (0, 3),
+                (1, 3),
+                (2, 3),
+                (7, 3),
+                (8, 3),
+                (9, 3),
+                (4, 4),
+                (5, 4),
+            ],
+        ),
+        (
+            0,
+            [
+                (0, 4),
+                (1, 4),
+                (2, 4),
+                (3, 4),
+                (6, 4),
+                (7, 4),
+                (8, 4),
+                (9, 4),
+            ],
+        ),
+    ];
+    let mut spawns = Vec::new();
+    for (group_index, (entry, places)) in groups.iter().enumerate() {
+        let start = group_index as f32 * 2.8;
+        for (index, slot) in places.iter().enumerate() {
+            let kind = match slot.1 {
+                0 => Kind::Boss,
+                1 | 2 => Kind::Butterfly,
+                _ => Kind::Bee,
+            };
+            let mut waypoints = entry_waypoints(*entry);
+            if challenge {
+                // Out the other side instead of into the formation.
+                let last = *waypoints.last().unwrap();
+                waypoints.push(Vec2::new(-last.x.signum() * 400.0, last.y + 300.0));
+            } else {
+                waypoints.push(slot_position(*slot, time + start + 4.0));
+            }
+            let kind = if challenge && index % 2 == 1 {
+                Kind::Butterfly
+            } else if challenge {
+                Kind::Bee
+            } else {
+                kind
+            };
+            let path = Path::through(&waypoints);
+            spawns.push(Spawn {
+                at: start + index as f32 * 0.14,
+                enemy: Enemy {
+                    captive: false,
+                    damaged: false,
+                    fire: 0.0,
+                    flight: Flight::Entering {
+                        path,
+                        distance: 0.0,
+                    },
+                    heading: -Vec2::Y,
+                    kind,
+                    position: waypoints[0],
+                    slot: *slot,
+                },
+            });
+        }
+    }
+    spawns
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Plays until every enemy of the stage sits in the formation.
+    fn formed() -> (Battle, StdRng) {
+        let mut battle = Battle::new();
+        let mut rng = StdRng::seed_from_u64(3);
+        battle.lives = 0;
+        battle.dive = 1000.0;
+        for _ in 0..60 * 30 {
+            battle.update(Controls::default(), 1.0 / 60.0, &mut rng);
+        }
+        (battle, rng)
+    }
+
+    fn shoot_at(battle: &mut Battle, position: Vec2) {
+        battle.bullets.push(Bullet {
+            position,
+            velocity: Vec2::ZERO,
+        });
+    }
+
+    #[test]
+    fn stages_fill_the_formation() {
+        let (battle, _) = formed();
+        assert_eq!(battle.enemies.len(), 40);
+        assert!(battle
+            .enemies
+            .iter()
+            .all(|enemy| enemy.flight == Flight::InFormation));
+        let bosses = battle
+            .enemies
+            .iter()
+            .filter(|enemy| enemy.kind == Kind::Boss);
+        assert_eq!(bosses.count(), 4);
+        let challenges: Vec<u32> = (1..=12)
+            .filter(|stage| Battle::is_challenge(*stage))
+            .collect();
+        assert_eq!(challenges, vec![3, 7, 11]);
+    }
+
+    #[test]
+    fn shooting_scores_and_bosses_take_two_hits() {
+        let (mut battle, mut rng) = formed();
+        let bee = battle
+            .enemies
+            .iter()
+            .find(|enemy| enemy.kind == Kind::Bee)
+            .unwrap()
+            .position;
+        shoot_at(&mut battle, bee);
+        let events = battle.update(Controls::default(), 0.0, &mut rng);
+        assert_eq!(events, vec![Event::Destroyed(Kind::Bee)]);
+        assert_eq!(battle.score, 50);
+
+        let boss = battle
+            .enemies
+            .iter()
+            .find(|enemy| enemy.kind == Kind::Boss)
+            .unwrap()
+            .position;
+        shoot_at(&mut battle, boss);
+        assert_eq!(
+            battle.update(Controls::default(), 0.0, &mut rng),
+            vec![Event::BossDamaged]
+        );
+        shoot_at(&mut battle, boss);
+        assert_eq!(
+            battle.update(Controls::default(), 0.0, &mut rng),
+            vec![Event::Destroyed(Kind::Boss)]
+        );
+        assert_eq!((battle.score, battle.enemies.len()), (200, 38));
+    }
+
+    #[test]
+    fn captured_fighters_are_rescued_into_a_dual_fighter() {
+        let (mut battle, mut rng) = formed();
+        battle.lives = 1;
+        let boss = battle
+            .enemies
+            .iter()
+            .position(|enemy| enemy.kind == Kind::Boss)
+            .unwrap();
+        battle.enemies[boss].position = Vec2::new(0.0, BEAM_Y);
+        battle.enemies[boss].flight = Flight::Beaming {
+            path: Path::through(&[Vec2::new(0.0, BEAM_Y)]),
+            distance: 0.0,
+            left: BEAM_TIME,
+        };
+        let mut events = Vec::new();
+        for _ in 0..60 {
+            events.extend(battle.update(Controls::default(), 1.0 / 60.0, &mut rng));
+        }
+        assert!(events.contains(&Event::Captured));
+        assert!(battle.enemies[boss].captive);
+        assert_eq!((battle.player, battle.lives), (None, 0));
+
+        // Shot down on an attack run, the boss lets the fighter go.
+        for _ in 0..60 * 5 {
+            battle.update(Controls::default(), 1.0 / 60.0, &mut rng);
+        }
+        assert!(battle.player.is_some());
+        let boss = battle
+            .enemies
+            .iter()
+            .position(|enemy| enemy.captive)
+            .unwrap();
+        battle.enemies[boss].damaged = true;
+        battle.enemies[boss].flight = Flight::Diving {
+            path: Path::through(&[Vec2::new(0.0, 0.0), Vec2::new(0.0, -100.0)]),
+            distance: 0.0,
+        };
+        battle.enemies[boss].position = Vec2::ZERO;
+        shoot_at(&mut battle, Vec2::ZERO);
+        let events = battle.update(Controls::default(), 0.0, &mut rng);
+        assert!(events.contains(&Event::Rescued));
+        assert_eq!(battle.player.unwrap().ships().len(), 2);
+        assert_eq!(battle.score, 400);
+    }
+
+    #[test]
+    fn losing_the_last_fighter_ends_the_game() {
+        let (mut battle, mut rng) = formed();
+        battle.enemy_bullets.push(Bullet {
+            position: Vec2::new(0.0, PLAYER_Y),
+            velocity: Vec2::ZERO,
+        });
+        let events = battle.update(Controls::default(), 0.0, &mut rng);
+        assert_eq!(events, vec![Event::PlayerDestroyed, Event::GameOver]);
+        assert!(battle.over);
+    }
+}
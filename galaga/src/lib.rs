@@ -0,0 +1,623 @@
+use battle::{
+    Battle, Controls, Enemy, Event, Flight, Kind, ARENA_HALF_WIDTH, BEAM_HALF_WIDTH, BEAM_TIME,
+    CHALLENGE_COUNT, CHALLENGE_HIT, CHALLENGE_PERFECT, ENEMY_RADIUS, PLAYER_Y,
+};
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use rand::{seq::SliceRandom, thread_rng, Rng};
+
+pub mod battle;
+pub mod path;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.0, 0.0, 0.03);
+/// Seconds a message stays on screen.
+const BANNER_TIME: f32 = 2.5;
+const BEAM_COLOR: Color = Color::rgba(0.4, 0.6, 1.0, 0.35);
+/// Strips a tractor beam is drawn with, widening downwards.
+const BEAM_STRIPS: usize = 12;
+const BEE_COLORS: [Color; 2] = [Color::rgb(1.0, 0.85, 0.15), Color::rgb(0.2, 0.45, 1.0)];
+const BOSS_COLORS: [Color; 2] = [Color::rgb(0.2, 0.75, 0.35), Color::rgb(0.95, 0.95, 0.3)];
+const BULLET_COLOR: Color = Color::rgb(0.95, 0.95, 1.0);
+const BUTTERFLY_COLORS: [Color; 2] = [Color::rgb(0.9, 0.15, 0.2), Color::rgb(0.95, 0.95, 1.0)];
+/// The second color of a boss once it has been hit.
+const DAMAGED_COLOR: Color = Color::rgb(0.55, 0.3, 0.85);
+const ENEMY_BULLET_COLOR: Color = Color::rgb(1.0, 0.4, 0.3);
+const FIGHTER_COLORS: [Color; 2] = [Color::rgb(0.95, 0.95, 0.95), Color::rgb(0.9, 0.15, 0.15)];
+const FIRE_KEYS: [KeyCode; 2] = [KeyCode::Space, KeyCode::Up];
+const GAME: &str = "galaga";
+const LEADERBOARD_SIZE: usize = 5;
+const LEFT_KEYS: [KeyCode; 2] = [KeyCode::Left, KeyCode::A];
+const RIGHT_KEYS: [KeyCode; 2] = [KeyCode::Right, KeyCode::D];
+const STAR_COLORS: [Color; 3] = [
+    Color::rgb(0.9, 0.9, 1.0),
+    Color::rgb(1.0, 0.5, 0.5),
+    Color::rgb(0.5, 0.7, 1.0),
+];
+const STARS: usize = 70;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+
+pub const WINDOW_HEIGHT: f32 = 720.0;
+pub const WINDOW_WIDTH: f32 = ARENA_HALF_WIDTH * 2.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+/// The last thing that happened worth telling, shown for a moment.
+#[derive(Default)]
+struct Banner {
+    text: String,
+    time: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+struct Sounds {
+    beam: Handle<AudioSource>,
+    captured: Handle<AudioSource>,
+    dive: Handle<AudioSource>,
+    enemy_destroyed: Handle<AudioSource>,
+    extra_life: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    player_destroyed: Handle<AudioSource>,
+    rescued: Handle<AudioSource>,
+    shot: Handle<AudioSource>,
+    stage_clear: Handle<AudioSource>,
+}
+
+/// A star of the background, scrolling down at its own speed.
+#[derive(Component)]
+struct Star(f32);
+
+pub struct GalagaPlugin;
+
+impl Plugin for GalagaPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Battle::default())
+            .insert_resource(Banner::default())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(play_update))
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over_enter))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system(battle_render)
+            .add_system(stars_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Galaga".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws the fighters, enemies, captured fighters, tractor beams and bullets every frame.
+fn battle_render(
+    mut commands: Commands,
+    time: Res<Time>,
+    battle: Res<Battle>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    let mut sprites = Vec::new();
+    let flap = (time.seconds_since_startup() * 2.0).fract() < 0.5;
+
+    if let Some(player) = battle.player {
+        for x in player.ships() {
+            sprites.extend(shape(
+                &fighter_parts(),
+                Vec2::new(x, PLAYER_Y),
+                Vec2::Y,
+                3.0,
+            ));
+        }
+    }
+    for enemy in battle.enemies.iter() {
+        if let Flight::Beaming {
+            path,
+            distance,
+            left,
+        } = &enemy.flight
+        {
+            if *distance >= path.length() {
+                sprites.extend(beam(enemy.position, *left, flap));
+            }
+        }
+        if enemy.captive {
+            // Held by the boss, and drawn in red until it is set free.
+            let parts: Vec<_> = fighter_parts()
+                .into_iter()
+                .map(|(color, size, offset)| {
+                    let color = if color == FIGHTER_COLORS[0] {
+                        FIGHTER_COLORS[1]
+                    } else {
+                        color
+                    };
+                    (color, size, offset)
+                })
+                .collect();
+            sprites.extend(shape(
+                &parts,
+                battle::captive_position(enemy),
+                enemy.heading,
+                2.0,
+            ));
+        }
+        sprites.extend(shape(
+            &enemy_parts(enemy, flap),
+            enemy.position,
+            enemy.heading,
+            2.0,
+        ));
+    }
+    for bullet in battle.bullets.iter() {
+        sprites.push(rectangle(
+            BULLET_COLOR,
+            Vec2::new(2.0, 10.0),
+            bullet.position,
+            4.0,
+        ));
+    }
+    for bullet in battle.enemy_bullets.iter() {
+        sprites.push(rectangle(
+            ENEMY_BULLET_COLOR,
+            Vec2::new(3.0, 8.0),
+            bullet.position,
+            4.0,
+        ));
+    }
+    for sprite in sprites {
+        commands.spawn_bundle(sprite).insert(Actor);
+    }
+}
+
+/// The tractor beam of a boss with `left` seconds of it to go, opening over the first quarter.
+fn beam(boss: Vec2, left: f32, flicker: bool) -> Vec<SpriteBundle> {
+    let open = ((BEAM_TIME - left) / (BEAM_TIME / 4.0)).min(1.0);
+    let bottom = PLAYER_Y - 20.0;
+    let height = (boss.y - ENEMY_RADIUS - bottom) / BEAM_STRIPS as f32;
+    (0..BEAM_STRIPS)
+        .filter(|strip| flicker || strip % 2 == 0)
+        .map(|strip| {
+            let share = (strip as f32 + 1.0) / BEAM_STRIPS as f32;
+            let y = boss.y - ENEMY_RADIUS - (strip as f32 + 0.5) * height;
+            rectangle(
+                BEAM_COLOR,
+                Vec2::new(BEAM_HALF_WIDTH * 2.0 * share * open, height - 2.0),
+                Vec2::new(boss.x, y),
+                1.0,
+            )
+        })
+        .collect()
+}
+
+/// The rectangles an enemy is drawn with, as color, size and offset with the enemy facing up;
+/// the wings flap.
+fn enemy_parts(enemy: &Enemy, flap: bool) -> Vec<(Color, Vec2, Vec2)> {
+    let wing = if flap { 4.0 } else { 0.0 };
+    match enemy.kind {
+        Kind::Bee => vec![
+            (BEE_COLORS[0], Vec2::new(8.0, 16.0), Vec2::ZERO),
+            (BEE_COLORS[1], Vec2::new(8.0, 3.0), Vec2::new(0.0, -2.0)),
+            (
+                BEE_COLORS[1],
+                Vec2::new(7.0, 10.0 - wing),
+                Vec2::new(-8.0, 3.0),
+            ),
+            (
+                BEE_COLORS[1],
+                Vec2::new(7.0, 10.0 - wing),
+                Vec2::new(8.0, 3.0),
+            ),
+        ],
+        Kind::Boss => {
+            let second = if enemy.damaged {
+                DAMAGED_COLOR
+            } else {
+                BOSS_COLORS[1]
+            };
+            vec![
+                (BOSS_COLORS[0], Vec2::new(12.0, 18.0), Vec2::ZERO),
+                (second, Vec2::new(6.0, 6.0), Vec2::new(0.0, 5.0)),
+                (
+                    BOSS_COLORS[0],
+                    Vec2::new(8.0, 14.0 - wing),
+                    Vec2::new(-10.0, -1.0),
+                ),
+                (
+                    BOSS_COLORS[0],
+                    Vec2::new(8.0, 14.0 - wing),
+                    Vec2::new(10.0, -1.0),
+                ),
+                (second, Vec2::new(4.0, 4.0), Vec2::new(-10.0, -5.0)),
+                (second, Vec2::new(4.0, 4.0), Vec2::new(10.0, -5.0)),
+            ]
+        }
+        Kind::Butterfly => vec![
+            (BUTTERFLY_COLORS[1], Vec2::new(4.0, 16.0), Vec2::ZERO),
+            (
+                BUTTERFLY_COLORS[0],
+                Vec2::new(10.0, 12.0 - wing),
+                Vec2::new(-7.0, 2.0),
+            ),
+            (
+                BUTTERFLY_COLORS[0],
+                Vec2::new(10.0, 12.0 - wing),
+                Vec2::new(7.0, 2.0),
+            ),
+            (
+                BUTTERFLY_COLORS[1],
+                Vec2::new(6.0, 5.0),
+                Vec2::new(-6.0, -6.0),
+            ),
+            (
+                BUTTERFLY_COLORS[1],
+                Vec2::new(6.0, 5.0),
+                Vec2::new(6.0, -6.0),
+            ),
+        ],
+    }
+}
+
+/// The rectangles the fighter is drawn with, facing up.
+fn fighter_parts() -> Vec<(Color, Vec2, Vec2)> {
+    vec![
+        (FIGHTER_COLORS[0], Vec2::new(4.0, 22.0), Vec2::ZERO),
+        (
+            FIGHTER_COLORS[0],
+            Vec2::new(18.0, 6.0),
+            Vec2::new(0.0, -4.0),
+        ),
+        (
+            FIGHTER_COLORS[1],
+            Vec2::new(3.0, 8.0),
+            Vec2::new(-10.0, -2.0),
+        ),
+        (
+            FIGHTER_COLORS[1],
+            Vec2::new(3.0, 8.0),
+            Vec2::new(10.0, -2.0),
+        ),
+        (FIGHTER_COLORS[1], Vec2::new(4.0, 4.0), Vec2::new(0.0, 3.0)),
+    ]
+}
+
+/// Keeps the score on the leaderboard once the last fighter is gone.
+fn game_over_enter(battle: Res<Battle>, mut scores: ResMut<Scores>, platform: Res<Platform>) {
+    if scores.add_unnamed("", battle.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// Enter goes back to the menu.
+fn game_over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Enter starts a game.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut battle: ResMut<Battle>,
+    mut banner: ResMut<Banner>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *battle = Battle::default();
+        *banner = Banner::default();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Flies the fighter, plays the battle on and tells what happened.
+fn play_update(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut battle: ResMut<Battle>,
+    mut banner: ResMut<Banner>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    let controls = Controls {
+        fire: keyboard_input.any_just_pressed(FIRE_KEYS),
+        left: keyboard_input.any_pressed(LEFT_KEYS),
+        right: keyboard_input.any_pressed(RIGHT_KEYS),
+    };
+    let delta = time.delta_seconds().min(1.0 / 30.0);
+    banner.time = (banner.time - delta).max(0.0);
+    let mut played = Vec::new();
+    for event in battle.update(controls, delta, &mut thread_rng()) {
+        let (sound, message) = match event {
+            Event::Beam => (&sounds.beam, None),
+            Event::BossDamaged => (&sounds.hit, None),
+            Event::Captured => (&sounds.captured, Some("FIGHTER CAPTURED".to_string())),
+            Event::CaptiveLost => (&sounds.enemy_destroyed, None),
+            Event::ChallengeOver(hits) => (
+                &sounds.stage_clear,
+                Some(if hits == CHALLENGE_COUNT as u32 {
+                    format!(
+                        "NUMBER OF HITS {}\n\nPERFECT!\nSPECIAL BONUS {}",
+                        hits, CHALLENGE_PERFECT
+                    )
+                } else {
+                    format!("NUMBER OF HITS {}\n\nBONUS {}", hits, hits * CHALLENGE_HIT)
+                }),
+            ),
+            Event::Destroyed(_) => (&sounds.enemy_destroyed, None),
+            Event::Dive => (&sounds.dive, None),
+            Event::ExtraLife => (&sounds.extra_life, Some("EXTRA FIGHTER".to_string())),
+            Event::GameOver => (&sounds.game_over, None),
+            Event::PlayerDestroyed => (&sounds.player_destroyed, None),
+            Event::Rescued => (&sounds.rescued, Some("DUAL FIGHTER".to_string())),
+            Event::Shot => (&sounds.shot, None),
+            Event::StageClear => (&sounds.stage_clear, None),
+        };
+        // Several hits in one frame make one sound.
+        if !played.contains(&event) {
+            audio.play(sound.clone());
+            played.push(event);
+        }
+        if let Some(message) = message {
+            *banner = Banner {
+                text: message,
+                time: BANNER_TIME,
+            };
+        }
+    }
+    if battle.over {
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MessageText);
+
+    let mut rng = thread_rng();
+    for _ in 0..STARS {
+        let position = Vec2::new(
+            rng.gen_range(-WINDOW_WIDTH / 2.0, WINDOW_WIDTH / 2.0),
+            rng.gen_range(-WINDOW_HEIGHT / 2.0, WINDOW_HEIGHT / 2.0),
+        );
+        let color = *STAR_COLORS.choose(&mut rng).unwrap();
+        commands
+            .spawn_bundle(rectangle(color, Vec2::splat(2.0), position, 0.0))
+            .insert(Star(rng.gen_range(30.0, 120.0)));
+    }
+
+    commands.insert_resource(Sounds {
+        beam: audio_sources.add(
+            Tone::new(Waveform::Sine, 300.0, 1.2)
+                .slide(600.0)
+                .volume(0.25)
+                .into(),
+        ),
+        captured: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 392.0, 0.15).volume(0.2),
+            Tone::new(Waveform::Square, 349.0, 0.15).volume(0.2),
+            Tone::new(Waveform::Square, 294.0, 0.4).fade().volume(0.2),
+        ])),
+        dive: audio_sources.add(
+            Tone::new(Waveform::Triangle, 900.0, 0.35)
+                .slide(300.0)
+                .volume(0.15)
+                .into(),
+        ),
+        enemy_destroyed: audio_sources.add(
+            Tone::new(Waveform::Noise, 300.0, 0.2)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        extra_life: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 784.0, 0.08).volume(0.2),
+            Tone::new(Waveform::Square, 988.0, 0.08).volume(0.2),
+            Tone::new(Waveform::Square, 1175.0, 0.2).fade().volume(0.2),
+        ])),
+        game_over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Square, 1200.0, 0.05)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        player_destroyed: audio_sources.add(
+            Tone::new(Waveform::Noise, 120.0, 0.9)
+                .fade()
+                .volume(0.5)
+                .into(),
+        ),
+        rescued: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 659.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 784.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1047.0, 0.3).fade().volume(0.2),
+        ])),
+        shot: audio_sources.add(
+            Tone::new(Waveform::Square, 1400.0, 0.08)
+                .slide(500.0)
+                .volume(0.1)
+                .into(),
+        ),
+        stage_clear: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.25).fade(),
+        ])),
+    });
+}
+
+/// Sprites for rectangles given as color, size and offset for a shape facing up, turned to face
+/// `heading` at `center`.
+fn shape(parts: &[(Color, Vec2, Vec2)], center: Vec2, heading: Vec2, z: f32) -> Vec<SpriteBundle> {
+    let rotation = Quat::from_rotation_z(heading.y.atan2(heading.x) - std::f32::consts::FRAC_PI_2);
+    parts
+        .iter()
+        .map(|(color, size, offset)| {
+            let mut sprite = rectangle(*color, *size, center, z);
+            sprite.transform.translation += rotation * offset.extend(0.0);
+            sprite.transform.rotation = rotation;
+            sprite
+        })
+        .collect()
+}
+
+/// Scrolls the starfield down, faster while the fighter is in play.
+fn stars_update(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut stars: Query<(&mut Transform, &Star)>,
+) {
+    let pace = if *state.current() == GameState::Playing {
+        1.0
+    } else {
+        0.3
+    };
+    for (mut transform, star) in stars.iter_mut() {
+        transform.translation.y -= star.0 * pace * time.delta_seconds();
+        if transform.translation.y < -WINDOW_HEIGHT / 2.0 {
+            transform.translation.y += WINDOW_HEIGHT;
+        }
+    }
+}
+
+fn texts_update(
+    battle: Res<Battle>,
+    banner: Res<Banner>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+    )>,
+) {
+    let best = scores
+        .best("")
+        .map_or(0, |entry| entry.score)
+        .max(battle.score);
+    let scores = scores
+        .table("")
+        .iter()
+        .take(LEADERBOARD_SIZE)
+        .enumerate()
+        .map(|(place, entry)| format!("{}. {:>8}", place + 1, entry.score))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let hud = match state.current() {
+        GameState::Menu => format!("HIGH SCORE {}", best),
+        _ => format!(
+            "SCORE {}   HIGH {}   STAGE {}   FIGHTERS {}",
+            battle.score, best, battle.stage, battle.lives
+        ),
+    };
+    let message = match state.current() {
+        GameState::Menu => format!("GALAGA\n\nPress Enter to play\n\nHigh scores\n{}", scores),
+        GameState::Playing => {
+            let mut lines = Vec::new();
+            if banner.time > 0.0 {
+                lines.push(banner.text.clone());
+            }
+            if battle.intro > 0.0 && battle.challenge {
+                lines.push("CHALLENGING STAGE".to_string());
+            } else if battle.intro > 0.0 {
+                lines.push(format!("STAGE {}", battle.stage));
+            } else if battle.player.is_none() {
+                lines.push("READY".to_string());
+            }
+            lines.join("\n\n")
+        }
+        GameState::GameOver => format!(
+            "GAME OVER\n\nScore {}\n\nHigh scores\n{}\n\nEnter: menu",
+            battle.score, scores
+        ),
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
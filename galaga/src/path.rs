@@ -0,0 +1,82 @@
+//! Smooth flight paths through waypoints, for the enemies' entries and dives.
+
+use bevy::math::Vec2;
+
+/// Points sampled per stretch between two waypoints.
+const SAMPLES: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Path {
+    /// Distance along the path at each point.
+    distances: Vec<f32>,
+    points: Vec<Vec2>,
+}
+
+impl Path {
+    /// A Catmull-Rom curve through every waypoint, in order.
+    pub fn through(waypoints: &[Vec2]) -> Self {
+        let mut points = vec![waypoints[0]];
+        for index in 0..waypoints.len().saturating_sub(1) {
+            let before = waypoints[index.saturating_sub(1)];
+            let (from, to) = (waypoints[index], waypoints[index + 1]);
+            let after = waypoints[(index + 2).min(waypoints.len() - 1)];
+            for sample in 1..=SAMPLES {
+                let t = sample as f32 / SAMPLES as f32;
+                let (t2, t3) = (t * t, t * t * t);
+                points.push(
+                    (from * 2.0
+                        + (to - before) * t
+                        + (before * 2.0 - from * 5.0 + to * 4.0 - after) * t2
+                        + (from * 3.0 - before - to * 3.0 + after) * t3)
+                        * 0.5,
+                );
+            }
+        }
+        let mut distances = vec![0.0];
+        for pair in points.windows(2) {
+            let last = *distances.last().unwrap();
+            distances.push(last + pair[0].distance(pair[1]));
+        }
+        Self { distances, points }
+    }
+
+    /// The point `distance` along the path, or its end past that.
+    pub fn at(&self, distance: f32) -> Vec2 {
+        let index = self.distances.partition_point(|along| *along <= distance);
+        if index == 0 {
+            return self.points[0];
+        }
+        if index >= self.points.len() {
+            return *self.points.last().unwrap();
+        }
+        let (from, to) = (self.distances[index - 1], self.distances[index]);
+        let share = (distance - from) / (to - from);
+        self.points[index - 1].lerp(self.points[index], share)
+    }
+
+    pub fn length(&self) -> f32 {
+        *self.distances.last().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_through_the_waypoints() {
+        let waypoints = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(100.0, 0.0),
+            Vec2::new(100.0, 100.0),
+        ];
+        let path = Path::through(&waypoints);
+        assert_eq!(path.at(-5.0), waypoints[0]);
+        assert_eq!(path.at(path.length() + 5.0), waypoints[2]);
+        // The curve passes the corner, a little longer than the straight lines.
+        assert!(path.points.contains(&waypoints[1]));
+        assert!(path.length() > 200.0 && path.length() < 240.0);
+        let halfway = path.at(path.length() / 2.0);
+        assert!(halfway.distance(waypoints[1]) < 20.0);
+    }
+}
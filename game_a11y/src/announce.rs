@@ -0,0 +1,134 @@
+//! Captions for what happens in a game, shown one at a time at the bottom of the window, for
+//! players who miss a sound or an effect.
+
+use crate::Accessibility;
+use bevy::prelude::*;
+use game_hud::HudFont;
+use std::collections::VecDeque;
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const FONT_SIZE: f32 = 22.0;
+/// Seconds each announcement stays up.
+const SECONDS: f32 = 2.5;
+
+/// The announcement shown and those waiting for it. Games call `announce`; the announcements
+/// are only shown while the player has them turned on.
+#[derive(Default)]
+pub struct Announcements {
+    queue: VecDeque<String>,
+    shown: Option<(String, f32)>,
+}
+
+impl Announcements {
+    /// The announcement to show after `delta` seconds, the next one once its time is up.
+    pub fn advance(&mut self, delta: f32) -> Option<&str> {
+        if let Some((_, remaining)) = &mut self.shown {
+            *remaining -= delta;
+            if *remaining <= 0.0 {
+                self.shown = None;
+            }
+        }
+        if self.shown.is_none() {
+            self.shown = self.queue.pop_front().map(|text| (text, SECONDS));
+        }
+        self.shown.as_ref().map(|(text, _)| text.as_str())
+    }
+
+    pub fn announce(&mut self, text: impl Into<String>) {
+        self.queue.push_back(text.into());
+    }
+
+    /// Drops every announcement, such as when they are turned off.
+    pub fn clear(&mut self) {
+        self.queue.clear();
+        self.shown = None;
+    }
+}
+
+/// The caption's background, which holds its text.
+#[derive(Component)]
+pub(crate) struct AnnouncementPanel;
+
+#[derive(Component)]
+pub(crate) struct AnnouncementText;
+
+/// Shows the current announcement, or nothing.
+pub(crate) fn announcement_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    accessibility: Res<Accessibility>,
+    font: Res<HudFont>,
+    mut announcements: ResMut<Announcements>,
+    panels: Query<Entity, With<AnnouncementPanel>>,
+    mut texts: Query<&mut Text, With<AnnouncementText>>,
+) {
+    if !accessibility.announcements {
+        announcements.clear();
+    }
+    let shown = announcements
+        .advance(time.delta_seconds())
+        .map(str::to_string);
+    match (shown, panels.get_single()) {
+        (Some(shown), Ok(_)) => {
+            for mut text in texts.iter_mut() {
+                if text.sections[0].value != shown {
+                    text.sections[0].value = shown.clone();
+                }
+            }
+        }
+        (Some(shown), Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BACKGROUND),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            bottom: Val::Px(20.0),
+                            left: Val::Px(0.0),
+                            right: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        justify_content: JustifyContent::Center,
+                        padding: Rect::all(Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(AnnouncementPanel)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                shown,
+                                font.style(FONT_SIZE, Color::WHITE),
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(AnnouncementText);
+                });
+        }
+        (None, _) => {
+            for panel in panels.iter() {
+                commands.entity(panel).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shows_announcements_one_after_another() {
+        let mut announcements = Announcements::default();
+        assert_eq!(announcements.advance(0.1), None);
+        announcements.announce("Level 2");
+        announcements.announce("Extra life");
+        assert_eq!(announcements.advance(0.1), Some("Level 2"));
+        assert_eq!(announcements.advance(SECONDS - 0.5), Some("Level 2"));
+        assert_eq!(announcements.advance(0.5), Some("Extra life"));
+        assert_eq!(announcements.advance(SECONDS), None);
+    }
+}
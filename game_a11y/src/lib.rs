@@ -0,0 +1,276 @@
+//! Accessibility settings shared by every game: colorblind palettes, high contrast text,
+//! reduced motion, larger text and announcements, chosen once on one settings screen.
+
+use bevy::prelude::*;
+use game_settings::{Settings, SettingsPlugin};
+use serde::{Deserialize, Serialize};
+
+pub mod announce;
+pub mod palette;
+
+pub use announce::Announcements;
+pub use palette::{contrast, legible, Palette, Swatch, HIGH_CONTRAST};
+
+/// The `game_persistence` directory of the settings, the same for every game.
+pub const GAME: &str = "accessibility";
+
+/// The player's accessibility settings, a resource in every game with the `A11yPlugin`.
+#[derive(Debug, Deserialize, PartialEq, Serialize, Settings)]
+#[serde(default)]
+pub struct Accessibility {
+    pub announcements: bool,
+    pub high_contrast: bool,
+    pub palette: Palette,
+    pub reduced_motion: bool,
+    #[setting(label = "Text size", min = 0.75, max = 2, step = 0.25)]
+    pub text_scale: f32,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            announcements: false,
+            high_contrast: false,
+            palette: Palette::Standard,
+            reduced_motion: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+impl Accessibility {
+    /// The color of `swatch` in the player's palette.
+    pub fn color(&self, swatch: Swatch) -> Color {
+        self.palette.color(swatch)
+    }
+
+    /// `amount` of motion, such as a screen shake or a bounce, or none with reduced motion.
+    pub fn motion(&self, amount: f32) -> f32 {
+        if self.reduced_motion {
+            0.0
+        } else {
+            amount
+        }
+    }
+
+    /// `color` for text or lines on `background`, pushed to high contrast if the player wants it.
+    pub fn readable(&self, color: Color, background: Color) -> Color {
+        if self.high_contrast {
+            legible(color, background, HIGH_CONTRAST)
+        } else {
+            color
+        }
+    }
+}
+
+/// Loads the `Accessibility` settings, shared by every game and reloaded when they change,
+/// applies the text size, palette and high contrast to all UI text and the palette to every
+/// sprite. Needs the `HudPlugin` of `game_hud`; `ThemePlugin` of `game_theme` adds it.
+pub struct A11yPlugin;
+
+impl Plugin for A11yPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(SettingsPlugin::<Accessibility>::new(GAME))
+            .init_resource::<Announcements>()
+            .add_system(announce::announcement_update)
+            .add_system_to_stage(CoreStage::PostUpdate, sprite_update)
+            .add_system_to_stage(CoreStage::PostUpdate, text_update);
+    }
+}
+
+/// The font sizes and colors of a text's sections as its game set them, and as they are shown.
+#[derive(Component)]
+struct Adjusted {
+    original: Vec<(f32, Color)>,
+    shown: Vec<(f32, Color)>,
+}
+
+/// A sprite's color as its game set it, and as it is shown.
+#[derive(Component)]
+struct Recolored {
+    original: Color,
+    shown: Color,
+}
+
+/// Shows sprites in the player's palette. A color the game changed since is taken as its new
+/// original.
+fn sprite_update(
+    mut commands: Commands,
+    accessibility: Res<Accessibility>,
+    mut sprites: Query<(Entity, &mut Sprite, Option<&mut Recolored>)>,
+) {
+    for (entity, mut sprite, recolored) in sprites.iter_mut() {
+        let original = match &recolored {
+            Some(recolored) if recolored.shown == sprite.color => {
+                if !accessibility.is_changed() {
+                    continue;
+                }
+                recolored.original
+            }
+            _ => sprite.color,
+        };
+        let shown = accessibility.palette.recolor(original);
+        if sprite.color != shown {
+            sprite.color = shown;
+        }
+        match recolored {
+            Some(mut recolored) => *recolored = Recolored { original, shown },
+            None => {
+                commands
+                    .entity(entity)
+                    .insert(Recolored { original, shown });
+            }
+        }
+    }
+}
+
+fn sections(text: &Text) -> Vec<(f32, Color)> {
+    text.sections
+        .iter()
+        .map(|section| (section.style.font_size, section.style.color))
+        .collect()
+}
+
+/// Scales UI text, shows it in the player's palette and raises its contrast with the background. A section the game changed since
+/// is taken as its new original.
+fn text_update(
+    mut commands: Commands,
+    accessibility: Res<Accessibility>,
+    clear_color: Option<Res<ClearColor>>,
+    mut texts: Query<(Entity, &mut Text, Option<&mut Adjusted>), With<Node>>,
+) {
+    let background = clear_color.map_or(ClearColor::default().0, |clear_color| clear_color.0);
+    for (entity, mut text, adjusted) in texts.iter_mut() {
+        let current = sections(&text);
+        if let Some(adjusted) = &adjusted {
+            if adjusted.shown == current && !accessibility.is_changed() {
+                continue;
+            }
+        }
+        let original: Vec<(f32, Color)> = current
+            .iter()
+            .enumerate()
+            .map(|(index, section)| match &adjusted {
+                Some(adjusted) if adjusted.shown.get(index) == Some(section) => {
+                    adjusted.original[index]
+                }
+                _ => *section,
+            })
+            .collect();
+        let shown: Vec<(f32, Color)> = original
+            .iter()
+            .map(|(size, color)| {
+                (
+                    size * accessibility.text_scale,
+                    accessibility.readable(accessibility.palette.recolor(*color), background),
+                )
+            })
+            .collect();
+        if shown != current {
+            for (section, (size, color)) in text.sections.iter_mut().zip(&shown) {
+                section.style.font_size = *size;
+                section.style.color = *color;
+            }
+        }
+        match adjusted {
+            Some(mut adjusted) => *adjusted = Adjusted { original, shown },
+            None => {
+                commands.entity(entity).insert(Adjusted { original, shown });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_hud::HudPlugin;
+    use game_settings::screen::{settings_text, SettingsScreen};
+    use game_test::TestApp;
+
+    struct TextPlugin;
+
+    impl Plugin for TextPlugin {
+        fn build(&self, app: &mut App) {
+            app.add_plugin(HudPlugin)
+                .add_plugin(A11yPlugin)
+                .add_startup_system(spawn_text);
+        }
+    }
+
+    fn spawn_text(mut commands: Commands) {
+        commands.spawn_bundle(TextBundle {
+            text: Text::with_section("Score", TextStyle::default(), Default::default()),
+            ..Default::default()
+        });
+    }
+
+    fn font_sizes(app: &mut TestApp) -> Vec<f32> {
+        app.components::<Text>()
+            .iter()
+            .map(|text| text.sections[0].style.font_size)
+            .collect()
+    }
+
+    #[test]
+    fn lists_every_setting_on_one_screen() {
+        let screen = SettingsScreen {
+            open: true,
+            selected: 5,
+        };
+        let text = settings_text(&Accessibility::default(), &screen);
+        for line in [
+            "Announcements     Off",
+            "High contrast     Off",
+            "Palette           Standard",
+            "Reduced motion    Off",
+            "Text size         1.0",
+        ] {
+            assert!(text.contains(line), "{} missing from\n{}", line, text);
+        }
+    }
+
+    #[test]
+    fn scales_text_the_game_sets() {
+        let mut app = TestApp::new(WindowDescriptor::default(), TextPlugin);
+        app.resource_mut::<Accessibility>().text_scale = 2.0;
+        app.step();
+        let original = TextStyle::default().font_size;
+        assert_eq!(font_sizes(&mut app), [original * 2.0]);
+        // Scaled once, not again every frame.
+        app.step();
+        assert_eq!(font_sizes(&mut app), [original * 2.0]);
+        for mut text in app
+            .app
+            .world
+            .query::<&mut Text>()
+            .iter_mut(&mut app.app.world)
+        {
+            text.sections[0].style.font_size = 10.0;
+        }
+        app.step();
+        assert_eq!(font_sizes(&mut app), [20.0]);
+        app.resource_mut::<Accessibility>().text_scale = 1.0;
+        app.step();
+        assert_eq!(font_sizes(&mut app), [10.0]);
+    }
+
+    #[test]
+    fn shows_sprites_in_the_palette() {
+        let mut app = TestApp::new(WindowDescriptor::default(), TextPlugin);
+        let scarlet = Color::rgb(0.9, 0.1, 0.1);
+        app.app.world.spawn().insert(Sprite {
+            color: scarlet,
+            ..Default::default()
+        });
+        app.step();
+        assert_eq!(app.components::<Sprite>()[0].color, scarlet);
+        app.resource_mut::<Accessibility>().palette = Palette::RedGreen;
+        app.step();
+        let red = Palette::RedGreen.color(Swatch::Red);
+        assert_eq!(app.components::<Sprite>()[0].color, red);
+        app.resource_mut::<Accessibility>().palette = Palette::Standard;
+        app.step();
+        assert_eq!(app.components::<Sprite>()[0].color, scarlet);
+    }
+}
@@ -0,0 +1,204 @@
+//! Colors that stay apart for colorblind players, and contrast between colors as WCAG measures it.
+
+use bevy::prelude::*;
+use game_settings::Choice;
+use serde::{Deserialize, Serialize};
+
+/// The contrast high contrast mode keeps text at, WCAG's AAA level.
+pub const HIGH_CONTRAST: f32 = 7.0;
+/// How saturated a color must be for `recolor` to take it for a swatch rather than a gray.
+const MIN_SATURATION: f32 = 0.25;
+
+/// The set of colors games draw their swatches from.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize, Choice)]
+pub enum Palette {
+    /// For tritanopia, telling blue from green and yellow from purple by lightness.
+    #[setting(label = "Blue-yellow safe")]
+    BlueYellow,
+    /// The Okabe-Ito colors, for deuteranopia and protanopia.
+    #[setting(label = "Red-green safe")]
+    RedGreen,
+    #[default]
+    Standard,
+}
+
+impl Palette {
+    pub fn color(self, swatch: Swatch) -> Color {
+        let hex = match (self, swatch) {
+            (Self::BlueYellow, Swatch::Blue) => "115588",
+            (Self::BlueYellow, Swatch::Green) => "66ccaa",
+            (Self::BlueYellow, Swatch::Orange) => "ff8844",
+            (Self::BlueYellow, Swatch::Purple) => "882255",
+            (Self::BlueYellow, Swatch::Red) => "dd2222",
+            (Self::BlueYellow, Swatch::Yellow) => "ffeebb",
+            (Self::RedGreen, Swatch::Blue) => "0072b2",
+            (Self::RedGreen, Swatch::Green) => "009e73",
+            (Self::RedGreen, Swatch::Orange) => "e69f00",
+            (Self::RedGreen, Swatch::Purple) => "cc79a7",
+            (Self::RedGreen, Swatch::Red) => "d55e00",
+            (Self::RedGreen, Swatch::Yellow) => "f0e442",
+            (Self::Standard, Swatch::Blue) => "3060ff",
+            (Self::Standard, Swatch::Green) => "30c040",
+            (Self::Standard, Swatch::Orange) => "ff9020",
+            (Self::Standard, Swatch::Purple) => "a040e0",
+            (Self::Standard, Swatch::Red) => "e02828",
+            (Self::Standard, Swatch::Yellow) => "f0e030",
+        };
+        Color::hex(hex).expect("palette colors are valid hex")
+    }
+
+    /// `color` in this palette: a saturated color becomes the palette's swatch for the standard
+    /// swatch nearest to it in hue, so games that pick their own shades are recolored too. Grays,
+    /// near black, near white and the palette's own colors keep their color, and so does
+    /// everything in the standard palette.
+    pub fn recolor(self, color: Color) -> Color {
+        if self == Self::Standard {
+            return color;
+        }
+        let opaque = |color: Color| [color.r(), color.g(), color.b()];
+        if Swatch::ALL
+            .iter()
+            .any(|swatch| opaque(self.color(*swatch)) == opaque(color))
+        {
+            return color;
+        }
+        let (hue, saturation, lightness) = hsl(color);
+        if saturation < MIN_SATURATION || !(0.1..=0.9).contains(&lightness) {
+            return color;
+        }
+        let distance = |swatch: &Swatch| {
+            let apart = (hue - hsl(Self::Standard.color(*swatch)).0).abs() % 360.0;
+            apart.min(360.0 - apart)
+        };
+        let nearest = Swatch::ALL
+            .iter()
+            .min_by(|first, second| distance(first).total_cmp(&distance(second)))
+            .expect("there are swatches");
+        let mut recolored = self.color(*nearest);
+        recolored.set_a(color.a());
+        recolored
+    }
+}
+
+/// A color a game asks for by what it stands for, rather than by its exact shade.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Swatch {
+    Blue,
+    Green,
+    Orange,
+    Purple,
+    Red,
+    Yellow,
+}
+
+impl Swatch {
+    pub const ALL: [Swatch; 6] = [
+        Self::Blue,
+        Self::Green,
+        Self::Orange,
+        Self::Purple,
+        Self::Red,
+        Self::Yellow,
+    ];
+}
+
+/// The contrast ratio of two colors, from 1 for the same lightness to 21 for black on white.
+pub fn contrast(first: Color, second: Color) -> f32 {
+    let (first, second) = (luminance(first), luminance(second));
+    (first.max(second) + 0.05) / (first.min(second) + 0.05)
+}
+
+/// `foreground`, lightened or darkened away from `background` until their contrast is at least
+/// `ratio`, as far as white or black allow.
+pub fn legible(foreground: Color, background: Color, ratio: f32) -> Color {
+    if contrast(foreground, background) >= ratio {
+        return foreground;
+    }
+    let extreme = if contrast(Color::WHITE, background) >= contrast(Color::BLACK, background) {
+        Color::WHITE
+    } else {
+        Color::BLACK
+    };
+    let mut color = foreground;
+    for step in 1..=10 {
+        let amount = step as f32 / 10.0;
+        color = Color::rgba(
+            foreground.r() + (extreme.r() - foreground.r()) * amount,
+            foreground.g() + (extreme.g() - foreground.g()) * amount,
+            foreground.b() + (extreme.b() - foreground.b()) * amount,
+            foreground.a(),
+        );
+        if contrast(color, background) >= ratio {
+            break;
+        }
+    }
+    color
+}
+
+/// The hue in degrees, saturation and lightness of a color.
+fn hsl(color: Color) -> (f32, f32, f32) {
+    match color.as_hsla() {
+        Color::Hsla {
+            hue,
+            saturation,
+            lightness,
+            ..
+        } => (hue, saturation, lightness),
+        _ => unreachable!("as_hsla returns Hsla"),
+    }
+}
+
+/// Relative luminance of an sRGB color.
+fn luminance(color: Color) -> f32 {
+    let linear = |channel: f32| {
+        if channel <= 0.03928 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(color.r()) + 0.7152 * linear(color.g()) + 0.0722 * linear(color.b())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn black_on_white_is_the_most_contrast() {
+        assert!((contrast(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast(Color::WHITE, Color::WHITE) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn legible_brightens_dim_text_on_dark() {
+        let background = Color::rgb(0.05, 0.05, 0.08);
+        let dim = Color::rgb(0.3, 0.3, 0.3);
+        let brightened = legible(dim, background, HIGH_CONTRAST);
+        assert!(contrast(brightened, background) >= HIGH_CONTRAST);
+        assert!(brightened.r() > dim.r());
+        assert_eq!(
+            legible(Color::WHITE, background, HIGH_CONTRAST),
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn recolor_moves_saturated_colors_to_the_palette() {
+        let scarlet = Color::rgba(0.9, 0.1, 0.1, 0.5);
+        let mut expected = Palette::RedGreen.color(Swatch::Red);
+        expected.set_a(0.5);
+        assert_eq!(Palette::RedGreen.recolor(scarlet), expected);
+        for swatch in Swatch::ALL {
+            let blue_yellow = Palette::BlueYellow.color(swatch);
+            assert_eq!(
+                Palette::BlueYellow.recolor(Palette::Standard.color(swatch)),
+                blue_yellow
+            );
+            assert_eq!(Palette::BlueYellow.recolor(blue_yellow), blue_yellow);
+        }
+        let gray = Color::rgb(0.5, 0.5, 0.5);
+        assert_eq!(Palette::RedGreen.recolor(gray), gray);
+        assert_eq!(Palette::Standard.recolor(scarlet), scarlet);
+    }
+}
@@ -0,0 +1,161 @@
+//! A screen listing the game's achievements and those spanning games, with the progress on the
+//! ones still locked. It needs the `HudPlugin` of `game_hud` for its font.
+
+use crate::{meta::META, Achievement, Achievements, Record};
+use bevy::prelude::*;
+use game_hud::{HudFont, MenuInput, Navigate};
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+const FONT_SIZE: f32 = 20.0;
+/// Width of the column of names.
+const NAME_WIDTH: usize = 18;
+
+/// Whether the gallery is shown; open it from a menu.
+#[derive(Default)]
+pub struct AchievementsScreen {
+    pub open: bool,
+}
+
+#[derive(Component)]
+pub(crate) struct GalleryPanel;
+
+#[derive(Component)]
+pub(crate) struct GalleryText;
+
+/// One line per achievement: whether it is unlocked, its name, what it takes and how far the
+/// player is.
+fn lines(achievements: &[Achievement], record: &Record) -> Vec<String> {
+    achievements
+        .iter()
+        .map(|achievement| {
+            if record.is_unlocked(achievement) {
+                format!(
+                    "[x] {:<width$}{}",
+                    achievement.name,
+                    achievement.description,
+                    width = NAME_WIDTH
+                )
+            } else {
+                format!(
+                    "[ ] {:<width$}{}  {}/{}",
+                    achievement.name,
+                    achievement.description,
+                    record.value(achievement.stat).min(achievement.goal),
+                    achievement.goal,
+                    width = NAME_WIDTH
+                )
+            }
+        })
+        .collect()
+}
+
+pub fn gallery_text(achievements: &Achievements) -> String {
+    let unlocked = achievements
+        .definitions
+        .iter()
+        .filter(|achievement| achievements.record.is_unlocked(achievement))
+        .count();
+    let mut text = vec![
+        format!(
+            "ACHIEVEMENTS  {}/{}",
+            unlocked,
+            achievements.definitions.len()
+        ),
+        String::new(),
+    ];
+    text.extend(lines(achievements.definitions, &achievements.record));
+    text.push(String::new());
+    text.push("ALL GAMES".to_string());
+    text.extend(lines(META, &achievements.meta));
+    text.push(String::new());
+    text.push("Esc back".to_string());
+    text.join("\n")
+}
+
+pub(crate) fn gallery_input(
+    mut screen: ResMut<AchievementsScreen>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+) {
+    if screen.open && menu.just_pressed(Navigate::Back) {
+        keyboard.clear_just_pressed(KeyCode::Escape);
+        screen.open = false;
+    }
+}
+
+/// Shows the gallery while it is open and keeps it up to date.
+pub(crate) fn gallery_update(
+    mut commands: Commands,
+    screen: Res<AchievementsScreen>,
+    achievements: Res<Achievements>,
+    font: Res<HudFont>,
+    panels: Query<Entity, With<GalleryPanel>>,
+    mut texts: Query<&mut Text, With<GalleryText>>,
+) {
+    if !screen.is_changed() && !achievements.is_changed() {
+        return;
+    }
+    let value = gallery_text(&achievements);
+    match (screen.open, panels.get_single()) {
+        (true, Ok(_)) => {
+            for mut text in texts.iter_mut() {
+                text.sections[0].value = value.clone();
+            }
+        }
+        (true, Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BACKGROUND),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(GalleryPanel)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(game_hud::screen_text(
+                            &font,
+                            value,
+                            FONT_SIZE,
+                            Color::WHITE,
+                            Rect {
+                                top: Val::Px(30.0),
+                                left: Val::Px(30.0),
+                                ..Default::default()
+                            },
+                        ))
+                        .insert(GalleryText);
+                });
+        }
+        (false, _) => {
+            for entity in panels.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Progress;
+
+    #[test]
+    fn lists_unlocked_and_locked_achievements() {
+        let mut record = Record::default();
+        record.apply(Progress::Reach("played", 2), META, 0);
+        record.apply(Progress::Reach("completed", 3), META, 0);
+        let lines = lines(META, &record);
+        assert_eq!(
+            lines[1],
+            "[x] Completionist     Unlock every achievement of 3 games"
+        );
+        assert_eq!(
+            lines[2],
+            "[ ] Explorer          Play 5 different games  2/5"
+        );
+    }
+}
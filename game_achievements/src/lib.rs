@@ -0,0 +1,198 @@
+use bevy::prelude::*;
+use game_hud::HudFont;
+use std::{collections::VecDeque, fs};
+
+pub mod gallery;
+pub mod meta;
+pub mod record;
+
+pub use gallery::AchievementsScreen;
+pub use record::{Achievement, Progress, Record};
+
+/// The `game_persistence` directory every game's record is saved in, so the achievements
+/// spanning games can read them all.
+const DIRECTORY: &str = "achievements";
+const TOAST_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+const TOAST_FONT_SIZE: f32 = 22.0;
+/// Seconds each unlock is announced for.
+const TOAST_SECONDS: f32 = 3.0;
+
+/// The game's achievements and the player's record in it and across games.
+pub struct Achievements {
+    pub definitions: &'static [Achievement],
+    game: &'static str,
+    pub meta: Record,
+    pub record: Record,
+}
+
+impl Achievements {
+    /// Brings the achievements spanning games up to date from the saved records; returns those
+    /// just unlocked.
+    fn refresh_meta(&mut self) -> Vec<&'static Achievement> {
+        let records = records();
+        let mut unlocked = Vec::new();
+        for progress in meta::progress(&records) {
+            unlocked.extend(self.meta.apply(progress, meta::META, now()));
+        }
+        if let Err(error) = game_persistence::save(DIRECTORY, meta::FILE, &self.meta) {
+            eprintln!("could not save the achievements: {}", error);
+        }
+        unlocked
+    }
+
+    fn save(&mut self) {
+        self.record.total = self.definitions.len() as u32;
+        if let Err(error) = game_persistence::save(DIRECTORY, self.game, &self.record) {
+            eprintln!("could not save the achievements: {}", error);
+        }
+    }
+}
+
+/// Adds the game's `Achievements`, counting the `Progress` events it sends, announcing unlocks
+/// and the `AchievementsScreen` gallery. Needs the `HudPlugin` of `game_hud` for its font.
+pub struct AchievementsPlugin {
+    definitions: &'static [Achievement],
+    game: &'static str,
+}
+
+impl AchievementsPlugin {
+    /// `game` names its record, as the directory of its other data does for `game_persistence`.
+    pub fn new(game: &'static str, definitions: &'static [Achievement]) -> Self {
+        Self { definitions, game }
+    }
+}
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Achievements {
+            definitions: self.definitions,
+            game: self.game,
+            meta: game_persistence::load_or_default(DIRECTORY, meta::FILE),
+            record: game_persistence::load_or_default(DIRECTORY, self.game),
+        })
+        .init_resource::<AchievementsScreen>()
+        .init_resource::<Toasts>()
+        .add_event::<Progress>()
+        .add_startup_system(setup)
+        .add_system(progress_update)
+        .add_system(toast_update)
+        .add_system(gallery::gallery_input)
+        .add_system(gallery::gallery_update);
+    }
+}
+
+/// Unlocks waiting to be announced, and how long the first one has been shown.
+#[derive(Default)]
+struct Toasts {
+    queue: VecDeque<&'static Achievement>,
+    shown: f32,
+}
+
+#[derive(Component)]
+struct ToastText;
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Browsers have no clock for `SystemTime`, so the time of an unlock comes from JavaScript.
+#[cfg(target_arch = "wasm32")]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// The records of every game, without the one spanning games.
+fn records() -> Vec<Record> {
+    let entries = game_persistence::directory(DIRECTORY)
+        .ok()
+        .and_then(|directory| fs::read_dir(directory).ok());
+    let mut records = Vec::new();
+    for path in entries
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+    {
+        let game = match path.file_stem().and_then(|stem| stem.to_str()) {
+            Some(game) if game != meta::FILE && path.extension() == Some("ron".as_ref()) => game,
+            _ => continue,
+        };
+        match game_persistence::load::<Record>(DIRECTORY, game) {
+            Ok(record) => records.extend(record),
+            Err(error) => eprintln!("could not load the achievements of {}: {}", game, error),
+        }
+    }
+    records
+}
+
+/// Counts the progress, and saves and announces what it unlocks. The achievements spanning
+/// games only change with an unlock or the first progress in a game.
+fn progress_update(
+    mut events: EventReader<Progress>,
+    mut achievements: ResMut<Achievements>,
+    mut toasts: ResMut<Toasts>,
+) {
+    let first = achievements.record.stats.is_empty();
+    let queued = toasts.queue.len();
+    let mut changed = false;
+    for progress in events.iter() {
+        let definitions = achievements.definitions;
+        let unlocked = achievements.record.apply(*progress, definitions, now());
+        toasts.queue.extend(unlocked);
+        changed = true;
+    }
+    if !changed {
+        return;
+    }
+    achievements.save();
+    if first || toasts.queue.len() > queued {
+        let unlocked = achievements.refresh_meta();
+        toasts.queue.extend(unlocked);
+    }
+}
+
+fn setup(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            TOAST_FONT_SIZE,
+            TOAST_COLOR,
+            Rect {
+                bottom: Val::Px(10.0),
+                right: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(ToastText);
+}
+
+/// Shows each unlock in turn for a few seconds.
+fn toast_update(
+    time: Res<Time>,
+    mut toasts: ResMut<Toasts>,
+    mut texts: Query<&mut Text, With<ToastText>>,
+) {
+    if toasts.queue.is_empty() {
+        return;
+    }
+    toasts.shown += time.delta_seconds();
+    if toasts.shown > TOAST_SECONDS {
+        toasts.shown = 0.0;
+        toasts.queue.pop_front();
+    }
+    let value = match toasts.queue.front() {
+        Some(achievement) => format!(
+            "Achievement unlocked: {}\n{}",
+            achievement.name, achievement.description
+        ),
+        None => String::new(),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
@@ -0,0 +1,74 @@
+//! Achievements spanning every game, counted from the records of all of them.
+
+use crate::record::{Achievement, Progress, Record};
+
+/// Where their record is saved, next to those of the games.
+pub const FILE: &str = "all_games";
+pub const META: &[Achievement] = &[
+    Achievement {
+        description: "Unlock 25 achievements in any games",
+        goal: 25,
+        id: "collector",
+        name: "Collector",
+        stat: "unlocked",
+    },
+    Achievement {
+        description: "Unlock every achievement of 3 games",
+        goal: 3,
+        id: "completionist",
+        name: "Completionist",
+        stat: "completed",
+    },
+    Achievement {
+        description: "Play 5 different games",
+        goal: 5,
+        id: "explorer",
+        name: "Explorer",
+        stat: "played",
+    },
+];
+
+/// The statistics of the achievements spanning games, from the records of every game.
+pub fn progress(records: &[Record]) -> [Progress; 3] {
+    let played = records
+        .iter()
+        .filter(|record| !record.stats.is_empty())
+        .count();
+    let unlocked: usize = records.iter().map(|record| record.unlocked.len()).sum();
+    let completed = records
+        .iter()
+        .filter(|record| record.total > 0 && record.unlocked.len() >= record.total as usize)
+        .count();
+    [
+        Progress::Reach("completed", completed as u32),
+        Progress::Reach("played", played as u32),
+        Progress::Reach("unlocked", unlocked as u32),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_across_games() {
+        let record = |stats: &[&str], total, unlocked: &[&str]| Record {
+            stats: stats.iter().map(|stat| (stat.to_string(), 1)).collect(),
+            total,
+            unlocked: unlocked.iter().map(|id| (id.to_string(), 0)).collect(),
+        };
+        let records = [
+            record(&["apples"], 2, &["a", "b"]),
+            record(&["lines"], 3, &["c"]),
+            record(&[], 4, &[]),
+        ];
+        assert_eq!(
+            progress(&records),
+            [
+                Progress::Reach("completed", 1),
+                Progress::Reach("played", 2),
+                Progress::Reach("unlocked", 3)
+            ]
+        );
+    }
+}
@@ -0,0 +1,110 @@
+//! Achievement definitions, the statistics they count and what a player has unlocked.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Unlocked once the statistic `stat` reaches `goal`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Achievement {
+    pub description: &'static str,
+    pub goal: u32,
+    /// Stays the same when the name changes, as unlocks are saved under it.
+    pub id: &'static str,
+    pub name: &'static str,
+    pub stat: &'static str,
+}
+
+/// Sent by a game as the player makes progress on a statistic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Progress {
+    /// Adds to a count, e.g. apples eaten over all games.
+    Add(&'static str, u32),
+    /// Reports a value that counts when it is the best so far, e.g. the length of a snake.
+    Reach(&'static str, u32),
+}
+
+/// A player's statistics and unlocks in one game.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Record {
+    pub stats: BTreeMap<String, u32>,
+    /// How many achievements the game has, for the achievements spanning games.
+    pub total: u32,
+    /// When each achievement was unlocked, in seconds since the Unix epoch.
+    pub unlocked: BTreeMap<String, u64>,
+}
+
+impl Record {
+    /// Counts the progress and unlocks what it reaches; returns the achievements just unlocked.
+    pub fn apply(
+        &mut self,
+        progress: Progress,
+        achievements: &'static [Achievement],
+        now: u64,
+    ) -> Vec<&'static Achievement> {
+        let (stat, value) = match progress {
+            Progress::Add(stat, amount) => (stat, self.value(stat).saturating_add(amount)),
+            Progress::Reach(stat, value) => (stat, self.value(stat).max(value)),
+        };
+        self.stats.insert(stat.to_string(), value);
+        let mut unlocked = Vec::new();
+        for achievement in achievements {
+            if achievement.stat == stat
+                && value >= achievement.goal
+                && !self.is_unlocked(achievement)
+            {
+                self.unlocked.insert(achievement.id.to_string(), now);
+                unlocked.push(achievement);
+            }
+        }
+        unlocked
+    }
+
+    pub fn is_unlocked(&self, achievement: &Achievement) -> bool {
+        self.unlocked.contains_key(achievement.id)
+    }
+
+    pub fn value(&self, stat: &str) -> u32 {
+        self.stats.get(stat).copied().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACHIEVEMENTS: &[Achievement] = &[
+        Achievement {
+            description: "Eat 10 apples",
+            goal: 10,
+            id: "apples",
+            name: "Hungry",
+            stat: "apples",
+        },
+        Achievement {
+            description: "Grow to 20",
+            goal: 20,
+            id: "length",
+            name: "Long",
+            stat: "length",
+        },
+    ];
+
+    #[test]
+    fn unlocks_once_goals_are_reached() {
+        let mut record = Record::default();
+        assert!(record
+            .apply(Progress::Add("apples", 6), ACHIEVEMENTS, 1)
+            .is_empty());
+        let unlocked = record.apply(Progress::Add("apples", 4), ACHIEVEMENTS, 2);
+        assert_eq!(unlocked, [&ACHIEVEMENTS[0]]);
+        assert!(record
+            .apply(Progress::Add("apples", 1), ACHIEVEMENTS, 3)
+            .is_empty());
+        assert_eq!(record.unlocked["apples"], 2);
+        // Only the best value counts.
+        record.apply(Progress::Reach("length", 15), ACHIEVEMENTS, 4);
+        record.apply(Progress::Reach("length", 3), ACHIEVEMENTS, 5);
+        assert_eq!(record.value("length"), 15);
+        assert_eq!(record.value("unknown"), 0);
+    }
+}
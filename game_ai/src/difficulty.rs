@@ -0,0 +1,52 @@
+//! How well a computer player plays, picked as a level and turned into whatever the game tunes.
+
+use game_rng::Stream;
+
+/// From 0 for the easiest level to 1 for the hardest.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difficulty(f32);
+
+impl Difficulty {
+    /// Level `level` of `levels`, counting from 0 for the easiest.
+    pub fn level(level: usize, levels: usize) -> Self {
+        if levels < 2 {
+            return Self(1.0);
+        }
+        Self((level.min(levels - 1) as f32 / (levels - 1) as f32).clamp(0.0, 1.0))
+    }
+
+    /// Whether to make a deliberate mistake this time: with `easiest` as the chance at the
+    /// easiest level, and never at the hardest.
+    pub fn blunder(self, stream: &mut Stream, easiest: f32) -> bool {
+        stream.chance(self.scale(easiest, 0.0))
+    }
+
+    /// A search depth between `shallowest` and `deepest` plies.
+    pub fn depth(self, shallowest: u32, deepest: u32) -> u32 {
+        self.scale(shallowest as f32, deepest as f32).round() as u32
+    }
+
+    /// A value between the one for the easiest level and the one for the hardest, e.g. a speed
+    /// or a reaction time.
+    pub fn scale(self, easiest: f32, hardest: f32) -> f32 {
+        easiest + (hardest - easiest) * self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_spread_between_easiest_and_hardest() {
+        let depths: Vec<u32> = (0..4)
+            .map(|level| Difficulty::level(level, 4).depth(1, 4))
+            .collect();
+        assert_eq!(depths, [1, 2, 3, 4]);
+        assert_eq!(Difficulty::level(1, 3).scale(0.3, 0.1), 0.2);
+        assert_eq!(Difficulty::level(9, 3), Difficulty::level(2, 3));
+        let mut stream = Stream::new(3);
+        assert!(!(0..100).any(|_| Difficulty::level(2, 3).blunder(&mut stream, 0.5)));
+        assert!((0..100).all(|_| Difficulty::level(0, 3).blunder(&mut stream, 1.0)));
+    }
+}
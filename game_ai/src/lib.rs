@@ -0,0 +1,10 @@
+//! Building blocks for computer players: pathfinding on grids, game tree search, steering and
+//! difficulty levels.
+
+pub mod difficulty;
+pub mod path;
+pub mod search;
+pub mod steer;
+
+pub use difficulty::Difficulty;
+pub use search::{best_move, Game};
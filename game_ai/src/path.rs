@@ -0,0 +1,155 @@
+//! Shortest ways across an arena, moving up, down, left and right. The callbacks saying which
+//! cells can be entered also bound the search, e.g. by refusing cells outside the arena.
+
+use game_grid::{Direction, Position};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+};
+
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// The cheapest way from `start` to `goal`, not counting `start`. `cost` is what entering a cell
+/// costs, at least 1, or `None` for a wall.
+pub fn a_star(
+    start: Position,
+    goal: Position,
+    cost: impl Fn(Position) -> Option<u32>,
+) -> Option<Vec<Position>> {
+    let estimate =
+        |position: Position| ((position.x - goal.x).abs() + (position.y - goal.y).abs()) as u32;
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut spent = HashMap::from([(start, 0)]);
+    let mut open = BinaryHeap::from([Reverse((estimate(start), 0, start.x, start.y))]);
+    while let Some(Reverse((_, so_far, x, y))) = open.pop() {
+        let position = Position::new(x, y);
+        if position == goal {
+            return Some(walk_back(&came_from, start, goal));
+        }
+        if matches!(spent.get(&position), Some(best) if *best < so_far) {
+            continue;
+        }
+        for direction in DIRECTIONS {
+            let next = position.step(direction);
+            let total = match cost(next) {
+                Some(cost) => so_far + cost,
+                None => continue,
+            };
+            if !matches!(spent.get(&next), Some(best) if *best <= total) {
+                spent.insert(next, total);
+                came_from.insert(next, position);
+                open.push(Reverse((total + estimate(next), total, next.x, next.y)));
+            }
+        }
+    }
+    None
+}
+
+/// The shortest way from `start` to the nearest cell `is_goal` accepts, through cells `passable`
+/// accepts, not counting `start`; empty if `start` is a goal.
+pub fn bfs(
+    start: Position,
+    is_goal: impl Fn(Position) -> bool,
+    passable: impl Fn(Position) -> bool,
+) -> Option<Vec<Position>> {
+    let mut came_from = HashMap::from([(start, start)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(position) = queue.pop_front() {
+        if is_goal(position) {
+            return Some(walk_back(&came_from, start, position));
+        }
+        for direction in DIRECTIONS {
+            let next = position.step(direction);
+            if !came_from.contains_key(&next) && passable(next) {
+                came_from.insert(next, position);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// How many cells can be reached from `start`, itself included, counting up to `limit`; 0 if
+/// `start` cannot be entered.
+pub fn reachable(start: Position, limit: usize, passable: impl Fn(Position) -> bool) -> usize {
+    if !passable(start) {
+        return 0;
+    }
+    let mut seen = HashSet::from([start]);
+    let mut queue = VecDeque::from([start]);
+    let mut count = 0;
+    while let Some(position) = queue.pop_front() {
+        count += 1;
+        if count >= limit {
+            break;
+        }
+        for direction in DIRECTIONS {
+            let next = position.step(direction);
+            if !seen.contains(&next) && passable(next) {
+                seen.insert(next);
+                queue.push_back(next);
+            }
+        }
+    }
+    count
+}
+
+fn walk_back(
+    came_from: &HashMap<Position, Position>,
+    start: Position,
+    goal: Position,
+) -> Vec<Position> {
+    let mut path = Vec::new();
+    let mut position = goal;
+    while position != start {
+        path.push(position);
+        position = came_from[&position];
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 5 x 5 arena with a wall down column 2, open at the top.
+    fn open(position: Position) -> bool {
+        position.in_arena(5, 5) && !(position.x == 2 && position.y < 4)
+    }
+
+    #[test]
+    fn breadth_first_goes_round_walls() {
+        let path = bfs(Position::new(0, 0), |p| p == Position::new(4, 0), open).unwrap();
+        assert_eq!(path.len(), 12);
+        assert!(path.iter().all(|position| open(*position)));
+        assert_eq!(bfs(Position::new(1, 1), |p| p.x == 1, open), Some(vec![]));
+        assert_eq!(bfs(Position::new(0, 0), |p| p.x == 9, open), None);
+        assert_eq!(reachable(Position::new(0, 0), 100, open), 21);
+        assert_eq!(reachable(Position::new(0, 0), 5, open), 5);
+        assert_eq!(reachable(Position::new(2, 0), 5, open), 0);
+    }
+
+    #[test]
+    fn a_star_avoids_costly_cells() {
+        // Mud along row 1 makes the way through row 0 cheaper although it is as long.
+        let cost = |position: Position| {
+            if !position.in_arena(5, 3) {
+                None
+            } else if position.y == 1 && position.x > 0 {
+                Some(5)
+            } else {
+                Some(1)
+            }
+        };
+        let path = a_star(Position::new(0, 1), Position::new(4, 1), cost).unwrap();
+        assert_eq!(path.len(), 6);
+        assert!(path.iter().filter(|position| position.y == 1).count() == 1);
+        assert_eq!(a_star(Position::new(0, 0), Position::new(9, 9), cost), None);
+    }
+}
@@ -0,0 +1,163 @@
+//! Negamax with alpha-beta pruning for two player turn-based games, deepened one ply at a time so
+//! a time budget can stop it with the best move of the last finished depth.
+
+use game_rng::Stream;
+use std::time::{Duration, Instant};
+
+/// Nodes searched between looks at the clock.
+const CHECK_EVERY: u64 = 1024;
+/// Beyond every score, and safe to negate.
+const INFINITY: i32 = i32::MAX / 2;
+
+/// A position of a game, scored for the side to move.
+pub trait Game: Sized {
+    type Move: Copy;
+
+    /// The score when the game is over although there are moves, e.g. a draw by rule.
+    fn decided(&self) -> Option<i32> {
+        None
+    }
+
+    /// The score where the search stops. Between `alpha` and `beta` a game may search on, e.g.
+    /// until no captures are left.
+    fn evaluate(&self, alpha: i32, beta: i32) -> i32;
+
+    fn moves(&self) -> Vec<Self::Move>;
+
+    /// Puts the likely best moves first, so more of the search is cut off.
+    fn order(&self, _moves: &mut [Self::Move]) {}
+
+    fn play(&self, mv: Self::Move) -> Self;
+
+    /// The score with no moves left, `ply` moves into the search, e.g. lost, the sooner the worse.
+    fn stuck(&self, ply: u32) -> i32;
+}
+
+/// The best move found searching up to `depth` plies, or as deep as `budget` allows. Equally good
+/// moves are picked between with `stream`.
+pub fn best_move<G: Game>(
+    game: &G,
+    depth: u32,
+    budget: Option<Duration>,
+    stream: &mut Stream,
+) -> Option<G::Move> {
+    let mut moves = game.moves();
+    stream.shuffle(&mut moves);
+    game.order(&mut moves);
+    let mut search = Search {
+        deadline: budget.map(|budget| Instant::now() + budget),
+        nodes: 0,
+        out_of_time: false,
+    };
+    let mut best = moves.first().copied();
+    for depth in 1..=depth.max(1) {
+        let mut alpha = -INFINITY;
+        let mut found = None;
+        for (index, mv) in moves.iter().enumerate() {
+            let score = -search.negamax(&game.play(*mv), depth - 1, -INFINITY, -alpha, 1);
+            if search.out_of_time {
+                return best;
+            }
+            if score > alpha {
+                alpha = score;
+                found = Some(index);
+            }
+        }
+        // The best move so far is searched first at the next depth.
+        if let Some(index) = found {
+            let mv = moves.remove(index);
+            moves.insert(0, mv);
+            best = Some(mv);
+        }
+    }
+    best
+}
+
+struct Search {
+    deadline: Option<Instant>,
+    /// Nodes searched since the last look at the clock.
+    nodes: u64,
+    out_of_time: bool,
+}
+
+impl Search {
+    fn negamax<G: Game>(
+        &mut self,
+        game: &G,
+        depth: u32,
+        mut alpha: i32,
+        beta: i32,
+        ply: u32,
+    ) -> i32 {
+        self.nodes += 1;
+        if self.nodes >= CHECK_EVERY {
+            self.nodes = 0;
+            if let Some(deadline) = self.deadline {
+                self.out_of_time = Instant::now() >= deadline;
+            }
+        }
+        if self.out_of_time {
+            return 0;
+        }
+        let mut moves = game.moves();
+        if moves.is_empty() {
+            return game.stuck(ply);
+        }
+        if let Some(score) = game.decided() {
+            return score;
+        }
+        if depth == 0 {
+            return game.evaluate(alpha, beta);
+        }
+        game.order(&mut moves);
+        for mv in moves {
+            let score = -self.negamax(&game.play(mv), depth - 1, -beta, -alpha, ply + 1);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Take one to three from a pile; whoever takes the last one wins.
+    #[derive(Clone, Copy)]
+    struct Pile(u32);
+
+    impl Game for Pile {
+        type Move = u32;
+
+        fn evaluate(&self, _alpha: i32, _beta: i32) -> i32 {
+            0
+        }
+
+        fn moves(&self) -> Vec<u32> {
+            (1..=self.0.min(3)).collect()
+        }
+
+        fn play(&self, take: u32) -> Self {
+            Pile(self.0 - take)
+        }
+
+        fn stuck(&self, ply: u32) -> i32 {
+            -100 + ply as i32
+        }
+    }
+
+    #[test]
+    fn leaves_a_multiple_of_four() {
+        let mut stream = Stream::new(1);
+        for (pile, take) in [(5, 1), (6, 2), (7, 3), (3, 3)] {
+            assert_eq!(best_move(&Pile(pile), 8, None, &mut stream), Some(take));
+        }
+        assert_eq!(best_move(&Pile(0), 8, None, &mut stream), None);
+        // Out of time at once, it still has a move to make.
+        let quick = best_move(&Pile(40), 60, Some(Duration::ZERO), &mut stream);
+        assert!(quick.is_some());
+    }
+}
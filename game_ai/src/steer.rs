@@ -0,0 +1,98 @@
+//! Steering for things moving freely, such as ships, mallets or monsters. Each behavior returns
+//! the change of velocity it wants, for the game to cap by how quickly the thing can turn.
+
+use bevy::math::Vec2;
+
+/// Heads for `target`, slowing down within `slowing` of it so it stops there.
+pub fn arrive(position: Vec2, velocity: Vec2, target: Vec2, speed: f32, slowing: f32) -> Vec2 {
+    let offset = target - position;
+    let distance = offset.length();
+    let wanted = if distance < slowing {
+        speed * distance / slowing
+    } else {
+        speed
+    };
+    offset.normalize_or_zero() * wanted - velocity
+}
+
+/// Flees from where a target moving at `target_velocity` is about to be.
+pub fn evade(
+    position: Vec2,
+    velocity: Vec2,
+    target: Vec2,
+    target_velocity: Vec2,
+    speed: f32,
+) -> Vec2 {
+    flee(
+        position,
+        velocity,
+        ahead(position, target, target_velocity, speed),
+        speed,
+    )
+}
+
+/// Heads away from `target` at full `speed`.
+pub fn flee(position: Vec2, velocity: Vec2, target: Vec2, speed: f32) -> Vec2 {
+    (position - target).normalize_or_zero() * speed - velocity
+}
+
+/// Heads for where a target moving at `target_velocity` is about to be.
+pub fn pursue(
+    position: Vec2,
+    velocity: Vec2,
+    target: Vec2,
+    target_velocity: Vec2,
+    speed: f32,
+) -> Vec2 {
+    seek(
+        position,
+        velocity,
+        ahead(position, target, target_velocity, speed),
+        speed,
+    )
+}
+
+/// Heads for `target` at full `speed`.
+pub fn seek(position: Vec2, velocity: Vec2, target: Vec2, speed: f32) -> Vec2 {
+    (target - position).normalize_or_zero() * speed - velocity
+}
+
+/// Where the target is by the time something at `speed` could get to where it is now.
+fn ahead(position: Vec2, target: Vec2, target_velocity: Vec2, speed: f32) -> Vec2 {
+    let time = (target - position).length() / speed.max(f32::EPSILON);
+    target + target_velocity * time
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steers_towards_and_away() {
+        let origin = Vec2::ZERO;
+        let target = Vec2::new(100.0, 0.0);
+        assert_eq!(seek(origin, Vec2::ZERO, target, 10.0), Vec2::new(10.0, 0.0));
+        assert_eq!(
+            flee(origin, Vec2::ZERO, target, 10.0),
+            Vec2::new(-10.0, 0.0)
+        );
+        // Halfway into the slowing radius, it wants half the speed.
+        assert_eq!(
+            arrive(
+                origin,
+                Vec2::new(10.0, 0.0),
+                Vec2::new(25.0, 0.0),
+                10.0,
+                50.0
+            ),
+            Vec2::new(-5.0, 0.0)
+        );
+        // A target crossing at the same speed is met 100 further along.
+        let pursuing = pursue(origin, Vec2::ZERO, target, Vec2::new(0.0, 10.0), 10.0);
+        assert!((pursuing - Vec2::new(10.0, 10.0).normalize() * 10.0).length() < 1e-4);
+        assert_eq!(
+            evade(origin, Vec2::ZERO, target, Vec2::ZERO, 10.0),
+            Vec2::new(-10.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::audio::AudioSource;
+use std::f32::consts::PI;
+
+pub const SAMPLE_RATE: u32 = 22_050;
+const ATTACK: f32 = 0.005;
+const RELEASE: f32 = 0.03;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Waveform {
+    Noise,
+    Saw,
+    Sine,
+    Square,
+    Triangle,
+}
+
+/// A single synthesized note. Sounds are built from these at startup instead of shipping audio files.
+#[derive(Clone, Copy, Debug)]
+pub struct Tone {
+    pub waveform: Waveform,
+    pub frequency: f32,
+    pub end_frequency: f32,
+    pub duration: f32,
+    pub volume: f32,
+    pub fade: bool,
+}
+
+impl Tone {
+    pub fn new(waveform: Waveform, frequency: f32, duration: f32) -> Self {
+        Self {
+            waveform,
+            frequency,
+            end_frequency: frequency,
+            duration,
+            volume: 0.5,
+            fade: false,
+        }
+    }
+
+    pub fn rest(duration: f32) -> Self {
+        Self::new(Waveform::Sine, 0.0, duration).volume(0.0)
+    }
+
+    /// Fades the volume out linearly over the whole tone.
+    pub fn fade(mut self) -> Self {
+        self.fade = true;
+        self
+    }
+
+    /// Slides the pitch linearly towards `end_frequency`.
+    pub fn slide(mut self, end_frequency: f32) -> Self {
+        self.end_frequency = end_frequency;
+        self
+    }
+
+    pub fn volume(mut self, volume: f32) -> Self {
+        self.volume = volume;
+        self
+    }
+
+    fn render(&self, samples: &mut Vec<f32>, noise_seed: &mut u32) {
+        let count = (self.duration * SAMPLE_RATE as f32) as usize;
+        let mut phase = 0.0_f32;
+        for i in 0..count {
+            let t = i as f32 / SAMPLE_RATE as f32;
+            let progress = t / self.duration;
+            let frequency = self.frequency + (self.end_frequency - self.frequency) * progress;
+            phase = (phase + frequency / SAMPLE_RATE as f32).fract();
+            let value = match self.waveform {
+                Waveform::Noise => {
+                    *noise_seed = noise_seed
+                        .wrapping_mul(1_664_525)
+                        .wrapping_add(1_013_904_223);
+                    (*noise_seed >> 8) as f32 / (1 << 23) as f32 - 1.0
+                }
+                Waveform::Saw => phase * 2.0 - 1.0,
+                Waveform::Sine => (phase * 2.0 * PI).sin(),
+                Waveform::Square => {
+                    if phase < 0.5 {
+                        1.0
+                    } else {
+                        -1.0
+                    }
+                }
+                Waveform::Triangle => 1.0 - 4.0 * (phase - 0.5).abs(),
+            };
+            let envelope = if self.fade {
+                1.0 - progress
+            } else {
+                (t / ATTACK).min(1.0).min((self.duration - t) / RELEASE)
+            };
+            samples.push(value * envelope.max(0.0) * self.volume);
+        }
+    }
+}
+
+impl From<Tone> for AudioSource {
+    fn from(tone: Tone) -> Self {
+        sequence(&[tone])
+    }
+}
+
+/// Renders the tones one after another into a single playable source.
+pub fn sequence(tones: &[Tone]) -> AudioSource {
+    let mut samples = Vec::new();
+    let mut noise_seed = 0x2545_f491;
+    for tone in tones {
+        tone.render(&mut samples, &mut noise_seed);
+    }
+    AudioSource {
+        bytes: encode_wav(&samples).into(),
+    }
+}
+
+/// Mixes several sequences on top of each other, e.g. a melody and a bass line.
+pub fn chord(voices: &[&[Tone]]) -> AudioSource {
+    let mut mixed: Vec<f32> = Vec::new();
+    let mut noise_seed = 0x2545_f491;
+    for voice in voices {
+        let mut samples = Vec::new();
+        for tone in voice.iter() {
+            tone.render(&mut samples, &mut noise_seed);
+        }
+        if samples.len() > mixed.len() {
+            mixed.resize(samples.len(), 0.0);
+        }
+        for (target, sample) in mixed.iter_mut().zip(samples) {
+            *target += sample;
+        }
+    }
+    AudioSource {
+        bytes: encode_wav(&mixed).into(),
+    }
+}
+
+fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let data_length = samples.len() as u32 * 2;
+    let mut bytes = Vec::with_capacity(44 + data_length as usize);
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_length).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes());
+    bytes.extend_from_slice(&SAMPLE_RATE.to_le_bytes());
+    bytes.extend_from_slice(&(SAMPLE_RATE * 2).to_le_bytes());
+    bytes.extend_from_slice(&2_u16.to_le_bytes());
+    bytes.extend_from_slice(&16_u16.to_le_bytes());
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_length.to_le_bytes());
+    for sample in samples {
+        let value = (sample.max(-1.0).min(1.0) * i16::MAX as f32) as i16;
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
@@ -0,0 +1,132 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+use game_hud::HudFont;
+use rand::{seq::SliceRandom, Rng};
+
+const BACK_COLOR: Color = Color::rgb(0.2, 0.3, 0.65);
+const BORDER: f32 = 2.0;
+const BORDER_COLOR: Color = Color::rgb(0.15, 0.15, 0.15);
+const FACE_COLOR: Color = Color::rgb(0.98, 0.98, 0.95);
+const BLACK_SUIT_COLOR: Color = Color::rgb(0.05, 0.05, 0.05);
+const RED_SUIT_COLOR: Color = Color::rgb(0.8, 0.05, 0.1);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Suit {
+    Clubs,
+    Diamonds,
+    Hearts,
+    Spades,
+}
+
+impl Suit {
+    pub const ALL: [Suit; 4] = [Self::Clubs, Self::Diamonds, Self::Hearts, Self::Spades];
+
+    pub fn is_red(self) -> bool {
+        matches!(self, Self::Diamonds | Self::Hearts)
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            Self::Clubs => '♣',
+            Self::Diamonds => '♦',
+            Self::Hearts => '♥',
+            Self::Spades => '♠',
+        }
+    }
+}
+
+/// A playing card; `rank` runs from 1 (ace) to 13 (king).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Card {
+    pub rank: u8,
+    pub suit: Suit,
+}
+
+impl Card {
+    pub fn new(rank: u8, suit: Suit) -> Self {
+        Self { rank, suit }
+    }
+
+    pub fn rank_label(self) -> &'static str {
+        const LABELS: [&str; 13] = [
+            "A", "2", "3", "4", "5", "6", "7", "8", "9", "10", "J", "Q", "K",
+        ];
+        LABELS[(self.rank - 1) as usize]
+    }
+
+    pub fn label(self) -> String {
+        format!("{}{}", self.rank_label(), self.suit.symbol())
+    }
+}
+
+/// The 52 cards in suit and rank order.
+pub fn standard_deck() -> Vec<Card> {
+    Suit::ALL
+        .iter()
+        .flat_map(|suit| (1..=13).map(move |rank| Card::new(rank, *suit)))
+        .collect()
+}
+
+/// `decks` standard decks shuffled together, e.g. for a blackjack shoe.
+pub fn shuffled_decks(decks: usize, rng: &mut impl Rng) -> Vec<Card> {
+    let mut cards: Vec<Card> = (0..decks).flat_map(|_| standard_deck()).collect();
+    cards.shuffle(rng);
+    cards
+}
+
+/// Spawns a card of `size` centered on `translation`; `None` shows the back.
+pub fn spawn_card<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    font: &HudFont,
+    card: Option<Card>,
+    size: Vec2,
+    translation: Vec3,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut entity = commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: BORDER_COLOR,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    });
+    entity.with_children(|parent| {
+        parent.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: if card.is_some() {
+                    FACE_COLOR
+                } else {
+                    BACK_COLOR
+                },
+                custom_size: Some(size - Vec2::splat(BORDER * 2.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 0.01),
+            ..Default::default()
+        });
+        if let Some(card) = card {
+            let color = if card.suit.is_red() {
+                RED_SUIT_COLOR
+            } else {
+                BLACK_SUIT_COLOR
+            };
+            // Rank and suit in the corner stay readable when cards are fanned out on top of each other.
+            let corner = Vec3::new(-size.x * 0.28, size.y * 0.36, 0.02);
+            parent.spawn_bundle(game_hud::world_text(
+                font,
+                card.label(),
+                size.x * 0.26,
+                color,
+                corner,
+            ));
+            parent.spawn_bundle(game_hud::world_text(
+                font,
+                card.suit.symbol().to_string(),
+                size.x * 0.55,
+                color,
+                Vec3::new(0.0, -size.y * 0.1, 0.02),
+            ));
+        }
+    });
+    entity
+}
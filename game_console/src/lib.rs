@@ -0,0 +1,360 @@
+//! A drop-down developer console for debug builds: `~` opens it, and commands the games register
+//! run against their world, e.g. `spawn_food 5 5` or `seed 1234`.
+
+use bevy::prelude::*;
+use game_rng::{Rng, Seed};
+use std::{collections::BTreeMap, str::FromStr};
+
+mod ui;
+
+/// Commands kept to go back to with Up.
+const HISTORY_LENGTH: usize = 100;
+/// Lines the console keeps of what was typed and answered.
+const LOG_LENGTH: usize = 200;
+
+/// Runs a command with its arguments, answering with a line to print or what went wrong.
+pub type Handler = fn(&mut World, &[&str]) -> Result<String, String>;
+
+/// A command the console knows.
+#[derive(Clone)]
+pub struct Command {
+    handler: Handler,
+    pub help: &'static str,
+    /// The arguments, e.g. `<x> <y>`.
+    pub usage: &'static str,
+}
+
+/// The commands of the game and what was typed into the console, a resource in every game
+/// that registers a command or has the `ConsolePlugin`.
+pub struct Console {
+    commands: BTreeMap<&'static str, Command>,
+    history: Vec<String>,
+    /// The entry of `history` shown while going back through it.
+    history_index: Option<usize>,
+    /// The line being typed.
+    pub input: String,
+    log: Vec<String>,
+    pub open: bool,
+    /// Lines entered since the last frame, run with access to the whole world.
+    submitted: Vec<String>,
+}
+
+impl Default for Console {
+    /// A console with the commands every game has: `help`, `clear` and `seed`.
+    fn default() -> Self {
+        let mut console = Self {
+            commands: BTreeMap::new(),
+            history: Vec::new(),
+            history_index: None,
+            input: String::new(),
+            log: Vec::new(),
+            open: false,
+            submitted: Vec::new(),
+        };
+        console.register("clear", "", "Empties the console", clear);
+        console.register("help", "", "Lists the commands", help);
+        console.register(
+            "seed",
+            "[seed]",
+            "Shows the seed, or starts the random numbers over from a number or code",
+            seed,
+        );
+        console
+    }
+}
+
+impl Console {
+    /// Adds a command, replacing one of the same name.
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        help: &'static str,
+        handler: Handler,
+    ) {
+        self.commands.insert(
+            name,
+            Command {
+                handler,
+                help,
+                usage,
+            },
+        );
+    }
+
+    pub fn commands(&self) -> impl Iterator<Item = (&'static str, &Command)> {
+        self.commands.iter().map(|(name, command)| (*name, command))
+    }
+
+    /// Completes the command name being typed, as far as the commands starting with it agree,
+    /// and lists them if there are several.
+    pub fn complete(&mut self) {
+        if self.input.contains(' ') {
+            return;
+        }
+        let matches: Vec<&'static str> = self
+            .commands
+            .keys()
+            .copied()
+            .filter(|name| name.starts_with(self.input.as_str()))
+            .collect();
+        match matches.as_slice() {
+            [] => {}
+            [name] => self.input = format!("{} ", name),
+            [first, rest @ ..] => {
+                let common = rest.iter().fold(first.len(), |common, name| {
+                    first
+                        .bytes()
+                        .zip(name.bytes())
+                        .take(common)
+                        .take_while(|(a, b)| a == b)
+                        .count()
+                });
+                self.input = first[..common].to_string();
+                self.print(matches.join("  "));
+            }
+        }
+    }
+
+    /// The lines printed so far, oldest first.
+    pub fn log(&self) -> &[String] {
+        &self.log
+    }
+
+    /// Shows the command entered after the one shown, or an empty line after the last.
+    pub fn next(&mut self) {
+        match self.history_index {
+            Some(index) if index + 1 < self.history.len() => {
+                self.history_index = Some(index + 1);
+                self.input = self.history[index + 1].clone();
+            }
+            Some(_) => {
+                self.history_index = None;
+                self.input.clear();
+            }
+            None => {}
+        }
+    }
+
+    /// Shows the command entered before the one shown, starting with the last.
+    pub fn previous(&mut self) {
+        let index = match self.history_index {
+            Some(index) => index.saturating_sub(1),
+            None if self.history.is_empty() => return,
+            None => self.history.len() - 1,
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    pub fn print(&mut self, line: impl Into<String>) {
+        self.log.push(line.into());
+        if self.log.len() > LOG_LENGTH {
+            self.log.remove(0);
+        }
+    }
+
+    /// Enters the line typed, to be run at the start of the next frame.
+    pub fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        self.history_index = None;
+        if line.trim().is_empty() {
+            return;
+        }
+        self.print(format!("> {}", line));
+        if self.history.last() != Some(&line) {
+            self.history.push(line.clone());
+            if self.history.len() > HISTORY_LENGTH {
+                self.history.remove(0);
+            }
+        }
+        self.submitted.push(line);
+    }
+}
+
+/// Registers a command for the console in debug builds, for a game's plugin to call in `build`;
+/// release builds, which have no console, leave it out.
+pub fn add_command(
+    app: &mut App,
+    name: &'static str,
+    usage: &'static str,
+    help: &'static str,
+    handler: Handler,
+) {
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    app.world
+        .get_resource_or_insert_with(Console::default)
+        .register(name, usage, help, handler);
+}
+
+/// The argument at `index`, read as a `T`, or what is wrong with it, calling it `name`.
+pub fn arg<T: FromStr>(args: &[&str], index: usize, name: &str) -> Result<T, String> {
+    let value = args.get(index).ok_or(format!("missing <{}>", name))?;
+    value
+        .parse()
+        .map_err(|_| format!("<{}> cannot be {}", name, value))
+}
+
+/// Runs a line as its command would be, printing the answer to the console.
+pub fn run(world: &mut World, line: &str) {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let (name, args) = match words.split_first() {
+        Some(split) => split,
+        None => return,
+    };
+    let handler = world
+        .get_resource::<Console>()
+        .and_then(|console| console.commands.get(name))
+        .map(|command| command.handler);
+    let answer = match handler {
+        Some(handler) => handler(world, args),
+        None => Err(format!("no command {}; help lists them", name)),
+    };
+    let mut console = world.get_resource_or_insert_with(Console::default);
+    match answer {
+        Ok(answer) if answer.is_empty() => {}
+        Ok(answer) => console.print(answer),
+        Err(error) => console.print(format!("error: {}", error)),
+    }
+}
+
+fn clear(world: &mut World, _: &[&str]) -> Result<String, String> {
+    if let Some(mut console) = world.get_resource_mut::<Console>() {
+        console.log.clear();
+    }
+    Ok(String::new())
+}
+
+fn help(world: &mut World, _: &[&str]) -> Result<String, String> {
+    let console = world
+        .get_resource::<Console>()
+        .ok_or("there is no console")?;
+    Ok(console
+        .commands()
+        .map(|(name, command)| format!("{} {}: {}", name, command.usage, command.help))
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+fn seed(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let mut rng = world
+        .get_resource_mut::<Rng>()
+        .ok_or("this game has no seeded random numbers")?;
+    let code = match args.first() {
+        Some(code) => code,
+        None => return Ok(format!("seed {}", rng.seed())),
+    };
+    let seed = code
+        .parse()
+        .map(Seed)
+        .ok()
+        .or_else(|| Seed::parse(code))
+        .ok_or(format!("<seed> cannot be {}", code))?;
+    rng.reseed(seed);
+    Ok(format!("seed {}", seed))
+}
+
+/// The console, opened and closed with `~`, in debug builds; release builds leave it out. While
+/// it is open the game sees no keys. Games add their commands with `add_command`. Needs the
+/// `HudPlugin` of `game_hud`.
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        app.world.get_resource_or_insert_with(Console::default);
+        ui::build(app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn teleport(world: &mut World, args: &[&str]) -> Result<String, String> {
+        let x: i32 = arg(args, 0, "x")?;
+        let y: i32 = arg(args, 1, "y")?;
+        world.insert_resource((x, y));
+        Ok(format!("at {}, {}", x, y))
+    }
+
+    fn world() -> World {
+        let mut world = World::new();
+        let mut console = Console::default();
+        console.register("teleport", "<x> <y>", "Moves the player", teleport);
+        console.register("tell", "", "Says hello", |_, _| Ok("hello".to_string()));
+        world.insert_resource(console);
+        world
+    }
+
+    fn last_line(world: &World) -> &str {
+        world
+            .get_resource::<Console>()
+            .unwrap()
+            .log()
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn runs_commands_with_their_arguments() {
+        let mut world = world();
+        run(&mut world, "teleport 2 7");
+        assert_eq!(world.get_resource::<(i32, i32)>(), Some(&(2, 7)));
+        assert_eq!(last_line(&world), "at 2, 7");
+        run(&mut world, "teleport 2");
+        assert_eq!(last_line(&world), "error: missing <y>");
+        run(&mut world, "teleport 2 north");
+        assert_eq!(last_line(&world), "error: <y> cannot be north");
+        run(&mut world, "fly");
+        assert_eq!(last_line(&world), "error: no command fly; help lists them");
+    }
+
+    #[test]
+    fn reseeds_the_random_numbers() {
+        let mut world = world();
+        world.insert_resource(Rng::new(Seed(1)));
+        run(&mut world, "seed 1234");
+        assert_eq!(world.get_resource::<Rng>().unwrap().seed(), Seed(1234));
+        run(&mut world, "seed");
+        assert_eq!(last_line(&world), format!("seed {}", Seed(1234)));
+    }
+
+    #[test]
+    fn completes_command_names() {
+        let mut console = world().remove_resource::<Console>().unwrap();
+        console.input = "te".to_string();
+        console.complete();
+        assert_eq!(console.input, "tel");
+        assert_eq!(console.log().last().unwrap(), "teleport  tell");
+        console.input = "tele".to_string();
+        console.complete();
+        assert_eq!(console.input, "teleport ");
+        console.input = "teleport 2".to_string();
+        console.complete();
+        assert_eq!(console.input, "teleport 2");
+    }
+
+    #[test]
+    fn goes_back_through_the_history() {
+        let mut console = Console::default();
+        for line in ["seed 1", "seed 1", "help"] {
+            console.input = line.to_string();
+            console.submit();
+        }
+        console.previous();
+        assert_eq!(console.input, "help");
+        console.previous();
+        console.previous();
+        assert_eq!(console.input, "seed 1");
+        console.next();
+        assert_eq!(console.input, "help");
+        console.next();
+        assert_eq!(console.input, "");
+        assert_eq!(console.submitted.len(), 3);
+    }
+}
@@ -0,0 +1,153 @@
+//! Typing into the console and showing it across the top of the window.
+
+use crate::Console;
+use bevy::{app::Events, input::InputSystem, prelude::*, window::ReceivedCharacter};
+use game_hud::HudFont;
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.85);
+const FONT_SIZE: f32 = 16.0;
+/// Lines of the log shown above the one being typed.
+const LINES: usize = 12;
+const TEXT_COLOR: Color = Color::rgb(0.6, 1.0, 0.6);
+/// The key with `~` on it, which opens and closes the console.
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+
+#[derive(Component)]
+struct ConsolePanel;
+
+#[derive(Component)]
+struct ConsoleText;
+
+pub(crate) fn build(app: &mut App) {
+    app.add_system_to_stage(CoreStage::PreUpdate, console_input.after(InputSystem))
+        .add_system_to_stage(
+            CoreStage::PreUpdate,
+            console_run.exclusive_system().at_end(),
+        )
+        .add_system(console_update);
+}
+
+/// Opens and closes the console, and edits the line while it is open. Keys and characters
+/// typed into it are taken away from the game.
+fn console_input(
+    mut console: ResMut<Console>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut characters: ResMut<Events<ReceivedCharacter>>,
+) {
+    if keyboard.just_pressed(TOGGLE_KEY) {
+        console.open = !console.open;
+        keyboard.reset(TOGGLE_KEY);
+        characters.clear();
+        return;
+    }
+    if !console.open {
+        return;
+    }
+    for event in characters.drain() {
+        if !event.char.is_control() && event.char != '`' && event.char != '~' {
+            console.input.push(event.char);
+        }
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        console.input.pop();
+    }
+    if keyboard.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        console.submit();
+    }
+    if keyboard.just_pressed(KeyCode::Tab) {
+        console.complete();
+    }
+    if keyboard.just_pressed(KeyCode::Up) {
+        console.previous();
+    }
+    if keyboard.just_pressed(KeyCode::Down) {
+        console.next();
+    }
+    if keyboard.just_pressed(KeyCode::Escape) {
+        console.open = false;
+    }
+    let pressed: Vec<KeyCode> = keyboard.get_pressed().copied().collect();
+    for key in pressed {
+        keyboard.reset(key);
+    }
+}
+
+/// Runs the lines entered, before the game's systems see the frame.
+fn console_run(world: &mut World) {
+    let submitted = match world.get_resource_mut::<Console>() {
+        Some(mut console) if !console.submitted.is_empty() => {
+            std::mem::take(&mut console.submitted)
+        }
+        _ => return,
+    };
+    for line in submitted {
+        crate::run(world, &line);
+    }
+}
+
+/// The last lines of the log and the one being typed.
+fn shown(console: &Console) -> String {
+    let log = console.log();
+    let mut lines: Vec<&str> = log[log.len().saturating_sub(LINES)..]
+        .iter()
+        .map(String::as_str)
+        .collect();
+    let prompt = format!("> {}_", console.input);
+    lines.push(&prompt);
+    lines.join("\n")
+}
+
+fn console_update(
+    mut commands: Commands,
+    console: Res<Console>,
+    font: Res<HudFont>,
+    panels: Query<Entity, With<ConsolePanel>>,
+    mut texts: Query<&mut Text, With<ConsoleText>>,
+) {
+    if !console.is_changed() {
+        return;
+    }
+    match (console.open, panels.get_single()) {
+        (true, Ok(_)) => {
+            for mut text in texts.iter_mut() {
+                text.sections[0].value = shown(&console);
+            }
+        }
+        (true, Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BACKGROUND),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            top: Val::Px(0.0),
+                            left: Val::Px(0.0),
+                            right: Val::Px(0.0),
+                            ..Default::default()
+                        },
+                        padding: Rect::all(Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(ConsolePanel)
+                .with_children(|parent| {
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section(
+                                shown(&console),
+                                font.style(FONT_SIZE, TEXT_COLOR),
+                                Default::default(),
+                            ),
+                            ..Default::default()
+                        })
+                        .insert(ConsoleText);
+                });
+        }
+        (false, _) => {
+            for panel in panels.iter() {
+                commands.entity(panel).despawn_recursive();
+            }
+        }
+    }
+}
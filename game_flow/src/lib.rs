@@ -0,0 +1,350 @@
+use bevy::{app::AppExit, prelude::*, window::WindowFocused};
+use game_hud::HudFont;
+use game_i18n::Localization;
+
+pub mod transition;
+
+use transition::Change;
+pub use transition::Press;
+
+const FONT_SIZE: f32 = 28.0;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+/// The keys and gamepad button of each press.
+const PRESSES: [(Press, &[KeyCode], GamepadButtonType); 4] = [
+    (
+        Press::Confirm,
+        &[KeyCode::Return, KeyCode::Space],
+        GamepadButtonType::South,
+    ),
+    (
+        Press::Pause,
+        &[KeyCode::Escape, KeyCode::P],
+        GamepadButtonType::Start,
+    ),
+    (Press::Quit, &[KeyCode::Q], GamepadButtonType::East),
+    (Press::Restart, &[KeyCode::R], GamepadButtonType::North),
+];
+const TEXT_COLOR: Color = Color::WHITE;
+
+/// Sent by the game when it is over, with what to tell the player, e.g. the final score.
+pub struct EndGame(pub String);
+
+/// What the overlay shows, and the countdown.
+pub struct Flow {
+    /// Whether confirming pauses play as well.
+    confirm_pauses: bool,
+    /// Seconds counted down before play starts.
+    countdown: f32,
+    instructions: String,
+    /// Whether the flow listens to its own keys and buttons, rather than only to the `Press`
+    /// events the game sends.
+    keys: bool,
+    /// What the game said when it ended.
+    pub message: String,
+    remaining: f32,
+    title: String,
+}
+
+/// The menu, countdown, pause and game over screens around a game, so it only implements
+/// `GameState::Playing`. Play pauses when the window loses focus. Needs the `HudPlugin` of
+/// `game_hud` for its font and the `I18nPlugin` of `game_i18n` for its strings.
+pub struct FlowPlugin {
+    confirm_pauses: bool,
+    countdown: f32,
+    instructions: String,
+    keys: bool,
+    title: String,
+}
+
+impl FlowPlugin {
+    /// Counts down three seconds before play starts.
+    pub fn new(title: &str) -> Self {
+        Self {
+            confirm_pauses: false,
+            countdown: 3.0,
+            instructions: String::new(),
+            keys: true,
+            title: title.to_string(),
+        }
+    }
+
+    /// Lets Enter, Space and the gamepad's South button pause play too, for games that do not
+    /// use them while playing.
+    pub fn confirm_pauses(mut self) -> Self {
+        self.confirm_pauses = true;
+        self
+    }
+
+    /// Seconds counted down before play starts; 0 starts at once.
+    pub fn countdown(mut self, seconds: f32) -> Self {
+        self.countdown = seconds;
+        self
+    }
+
+    /// How to play, shown on the menu.
+    pub fn instructions(mut self, instructions: &str) -> Self {
+        self.instructions = instructions.to_string();
+        self
+    }
+
+    /// Leaves the keys and buttons to the game, which sends a `Press` for each from bindings
+    /// of its own, e.g. ones the player can change.
+    pub fn without_keys(mut self) -> Self {
+        self.keys = false;
+        self
+    }
+}
+
+impl Plugin for FlowPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Flow {
+            confirm_pauses: self.confirm_pauses,
+            countdown: self.countdown,
+            instructions: self.instructions.clone(),
+            keys: self.keys,
+            message: String::new(),
+            remaining: 0.0,
+            title: self.title.clone(),
+        })
+        .add_event::<EndGame>()
+        .add_event::<NewGame>()
+        .add_event::<Press>()
+        .add_state(GameState::Menu)
+        .add_startup_system(setup)
+        .add_system_set(SystemSet::on_update(GameState::Countdown).with_system(countdown_update))
+        .add_system_set(SystemSet::on_update(GameState::Playing).with_system(end_game_update))
+        .add_system_set(SystemSet::on_update(GameState::Playing).with_system(focus_update))
+        .add_system(flow_input.label(FlowInput))
+        .add_system(overlay_update);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameState {
+    Countdown,
+    GameOver,
+    Menu,
+    /// Pushed on top of `Playing`, so the game's systems pause and pick up where they left off.
+    Paused,
+    Playing,
+}
+
+/// Reads the presses, for a game's systems sending `Press` events to run before.
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+pub struct FlowInput;
+
+/// Sent when a game starts, from the menu, the game over screen or a restart, for the game to
+/// set itself up. It comes before the countdown, so read it outside the game's states.
+pub struct NewGame;
+
+/// The darkened background, which holds the text.
+#[derive(Component)]
+struct Overlay;
+
+#[derive(Component)]
+struct OverlayText;
+
+/// What the overlay says in a state, in the player's language.
+pub fn overlay_text(state: GameState, flow: &Flow, localization: &Localization) -> String {
+    match state {
+        GameState::Countdown => format!("{}", flow.remaining.ceil().max(1.0)),
+        GameState::GameOver => format!(
+            "{}\n\n{}\n\n{}",
+            localization.get("game-over"),
+            flow.message,
+            localization.get("hint-game-over")
+        ),
+        GameState::Menu => {
+            let mut text = flow.title.to_uppercase();
+            if !flow.instructions.is_empty() {
+                text.push_str("\n\n");
+                text.push_str(&flow.instructions);
+            }
+            text.push_str("\n\n");
+            text.push_str(&localization.get("hint-menu"));
+            text
+        }
+        GameState::Paused => format!(
+            "{}\n\n{}",
+            localization.get("paused"),
+            localization.get("hint-paused")
+        ),
+        GameState::Playing => String::new(),
+    }
+}
+
+fn countdown_update(time: Res<Time>, mut flow: ResMut<Flow>, mut state: ResMut<State<GameState>>) {
+    flow.remaining -= time.delta_seconds();
+    if flow.remaining <= 0.0 {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn end_game_update(
+    mut events: EventReader<EndGame>,
+    mut flow: ResMut<Flow>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if let Some(EndGame(message)) = events.iter().last() {
+        flow.message = message.clone();
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+/// Pauses play when the window loses focus, e.g. to another window.
+fn focus_update(mut events: EventReader<WindowFocused>, mut state: ResMut<State<GameState>>) {
+    if events.iter().any(|event| !event.focused) {
+        // Another change of state may be queued this frame already.
+        let _ = state.push(GameState::Paused);
+    }
+}
+
+/// Starts, pauses, resumes and leaves games; the keys used are cleared so the game does not
+/// see them too.
+fn flow_input(
+    mut keyboard: ResMut<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    mut state: ResMut<State<GameState>>,
+    touches: Res<Touches>,
+    mouse: Res<Input<MouseButton>>,
+    mut presses: EventReader<Press>,
+    mut flow: ResMut<Flow>,
+    mut new_games: EventWriter<NewGame>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // A tap confirms as well, except in play, where touches steer; browsers pass taps on as
+    // clicks.
+    let tapped = *state.current() != GameState::Playing
+        && (touches.iter_just_pressed().next().is_some()
+            || cfg!(target_arch = "wasm32") && mouse.just_pressed(MouseButton::Left));
+    let sent = presses.iter().next().copied();
+    let pressed = PRESSES.iter().find(|(_, keys, button)| {
+        flow.keys
+            && (keyboard.any_just_pressed(keys.iter().copied())
+                || buttons
+                    .get_just_pressed()
+                    .any(|GamepadButton(_, pressed)| pressed == button))
+    });
+    let (press, keys) = match (pressed, sent) {
+        (Some((press, keys, _)), _) => (*press, *keys),
+        (None, Some(press)) => (press, &[][..]),
+        (None, None) if tapped => (Press::Confirm, &[][..]),
+        (None, None) => return,
+    };
+    let change = match transition::respond(*state.current(), press) {
+        Some(change) => change,
+        None if flow.confirm_pauses
+            && press == Press::Confirm
+            && *state.current() == GameState::Playing =>
+        {
+            Change::Pause
+        }
+        None => return,
+    };
+    for key in keys.iter() {
+        keyboard.clear_just_pressed(*key);
+    }
+    match change {
+        Change::Exit => exit.send(AppExit),
+        Change::Pause => state.push(GameState::Paused).unwrap(),
+        Change::Resume => state.pop().unwrap(),
+        Change::Start => {
+            new_games.send(NewGame);
+            flow.remaining = flow.countdown;
+            let next = if flow.countdown > 0.0 {
+                GameState::Countdown
+            } else {
+                GameState::Playing
+            };
+            state.replace(next).unwrap();
+        }
+        Change::ToMenu => state.replace(GameState::Menu).unwrap(),
+    }
+}
+
+fn overlay_update(
+    state: Res<State<GameState>>,
+    flow: Res<Flow>,
+    localization: Res<Localization>,
+    mut overlays: Query<&mut UiColor, With<Overlay>>,
+    mut texts: Query<&mut Text, With<OverlayText>>,
+) {
+    if !state.is_changed() && !flow.is_changed() && !localization.is_changed() {
+        return;
+    }
+    let state = *state.current();
+    let color = match state {
+        GameState::Countdown | GameState::Playing => Color::NONE,
+        GameState::GameOver | GameState::Menu | GameState::Paused => OVERLAY_COLOR,
+    };
+    for mut overlay in overlays.iter_mut() {
+        overlay.0 = color;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = overlay_text(state, &flow, &localization);
+    }
+}
+
+fn setup(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(OVERLAY_COLOR),
+            style: Style {
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Overlay)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "",
+                        font.style(FONT_SIZE, TEXT_COLOR),
+                        TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            vertical: VerticalAlign::Center,
+                        },
+                    ),
+                    ..Default::default()
+                })
+                .insert(OverlayText);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn overlay_follows_the_state() {
+        let mut flow = Flow {
+            confirm_pauses: false,
+            countdown: 3.0,
+            instructions: "Arrows: move".to_string(),
+            message: "Score 120".to_string(),
+            remaining: 2.2,
+            title: "Snake".to_string(),
+        };
+        let mut localization = Localization::new(&[], "en");
+        assert!(overlay_text(GameState::Menu, &flow, &localization)
+            .starts_with("SNAKE\n\nArrows: move\n\n"));
+        assert_eq!(
+            overlay_text(GameState::Countdown, &flow, &localization),
+            "3"
+        );
+        flow.remaining = 0.1;
+        assert_eq!(
+            overlay_text(GameState::Countdown, &flow, &localization),
+            "1"
+        );
+        assert!(overlay_text(GameState::GameOver, &flow, &localization).contains("\nScore 120\n"));
+        assert_eq!(overlay_text(GameState::Playing, &flow, &localization), "");
+        localization.set_language("de");
+        assert!(overlay_text(GameState::Paused, &flow, &localization).starts_with("PAUSE\n"));
+    }
+}
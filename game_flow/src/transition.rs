@@ -0,0 +1,76 @@
+//! What each button does in each state.
+
+use crate::GameState;
+
+/// A change of state asked for by the player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// Leaves the game.
+    Exit,
+    Pause,
+    /// Starts a new game, from the menu, the game over screen or the pause screen.
+    Start,
+    Resume,
+    ToMenu,
+}
+
+/// The buttons the flow listens to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Press {
+    /// Enter, Space or the gamepad's South button.
+    Confirm,
+    /// Escape, P or the gamepad's Start button.
+    Pause,
+    /// Q or the gamepad's East button.
+    Quit,
+    /// R or the gamepad's North button.
+    Restart,
+}
+
+/// The change a press makes in a state, if any.
+pub fn respond(state: GameState, press: Press) -> Option<Change> {
+    match (state, press) {
+        (GameState::Menu, Press::Confirm) => Some(Change::Start),
+        (GameState::Menu, Press::Pause | Press::Quit) => Some(Change::Exit),
+        (GameState::Playing, Press::Pause) => Some(Change::Pause),
+        (GameState::Paused, Press::Confirm | Press::Pause) => Some(Change::Resume),
+        (GameState::Paused | GameState::GameOver, Press::Quit) => Some(Change::ToMenu),
+        (GameState::Paused | GameState::GameOver, Press::Restart) => Some(Change::Start),
+        (GameState::GameOver, Press::Confirm) => Some(Change::Start),
+        (GameState::GameOver, Press::Pause) => Some(Change::ToMenu),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn presses_change_states() {
+        assert_eq!(
+            respond(GameState::Menu, Press::Confirm),
+            Some(Change::Start)
+        );
+        assert_eq!(respond(GameState::Menu, Press::Pause), Some(Change::Exit));
+        assert_eq!(
+            respond(GameState::Playing, Press::Pause),
+            Some(Change::Pause)
+        );
+        // Play keeps its own use of the other buttons, and the countdown cannot be skipped.
+        assert_eq!(respond(GameState::Playing, Press::Confirm), None);
+        assert_eq!(respond(GameState::Countdown, Press::Pause), None);
+        assert_eq!(
+            respond(GameState::Paused, Press::Pause),
+            Some(Change::Resume)
+        );
+        assert_eq!(
+            respond(GameState::Paused, Press::Restart),
+            Some(Change::Start)
+        );
+        assert_eq!(
+            respond(GameState::GameOver, Press::Pause),
+            Some(Change::ToMenu)
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Direction {
+    None,
+    Left,
+    Up,
+    Right,
+    Down,
+}
+
+impl Direction {
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+        }
+    }
+
+    /// Cell offset of one step, with `y` growing upwards like bevy's world space.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Self::None => (0, 0),
+            Self::Left => (-1, 0),
+            Self::Up => (0, 1),
+            Self::Right => (1, 0),
+            Self::Down => (0, -1),
+        }
+    }
+
+    /// The direction to head in next: anything but reversing straight into the own body.
+    pub fn turn(self, wanted: Self) -> Self {
+        if wanted == self.opposite() {
+            self
+        } else {
+            wanted
+        }
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    pub fn step(self, direction: Direction) -> Self {
+        let (dx, dy) = direction.offset();
+        Self {
+            x: self.x + dx,
+            y: self.y + dy,
+        }
+    }
+
+    pub fn in_arena(self, width: u32, height: u32) -> bool {
+        self.x >= 0 && self.y >= 0 && (self.x as u32) < width && (self.y as u32) < height
+    }
+}
+
+/// Converts a cell of a `width` x `height` arena into window coordinates, so the arena fills the window.
+pub fn cell_to_window(position: Position, width: u32, height: u32, window: Vec2) -> Vec2 {
+    fn convert(pos: f32, bound_window: f32, bound_game: f32) -> f32 {
+        let tile_size = bound_window / bound_game;
+        pos / bound_game * bound_window - (bound_window / 2.) + (tile_size / 2.)
+    }
+
+    Vec2::new(
+        convert(position.x as f32, window.x, width as f32),
+        convert(position.y as f32, window.y, height as f32),
+    )
+}
+
+/// A value for every cell of a `width` x `height` arena, row by row from the bottom left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    height: u32,
+    width: u32,
+}
+
+impl<T: Clone> Grid<T> {
+    pub fn new(width: u32, height: u32, value: T) -> Self {
+        Self {
+            cells: vec![value; (width * height) as usize],
+            height,
+            width,
+        }
+    }
+
+    /// Sets every cell to `value`.
+    pub fn fill(&mut self, value: T) {
+        self.cells.iter_mut().for_each(|cell| *cell = value.clone());
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn get(&self, position: Position) -> Option<&T> {
+        self.index(position).map(|index| &self.cells[index])
+    }
+
+    pub fn get_mut(&mut self, position: Position) -> Option<&mut T> {
+        self.index(position)
+            .map(move |index| &mut self.cells[index])
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Where `position` is in `cells`, if it is in the arena.
+    fn index(&self, position: Position) -> Option<usize> {
+        position
+            .in_arena(self.width, self.height)
+            .then(|| (position.y as u32 * self.width + position.x as u32) as usize)
+    }
+
+    /// Every cell with its position, row by row from the bottom left.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        let width = self.width as usize;
+        self.cells.iter().enumerate().map(move |(index, cell)| {
+            let position = Position::new((index % width) as i32, (index / width) as i32);
+            (position, cell)
+        })
+    }
+
+    /// Sets the cell at `position`; does nothing outside the arena.
+    pub fn set(&mut self, position: Position, value: T) {
+        if let Some(cell) = self.get_mut(position) {
+            *cell = value;
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_hold_a_value_per_cell() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid.set(Position::new(2, 1), 7);
+        grid.set(Position::new(3, 0), 9);
+        assert_eq!(grid.get(Position::new(2, 1)), Some(&7));
+        assert_eq!(grid.get(Position::new(-1, 0)), None);
+        let set: Vec<Position> = grid
+            .iter()
+            .filter(|(_, cell)| **cell != 0)
+            .map(|(position, _)| position)
+            .collect();
+        assert_eq!(set, [Position::new(2, 1)]);
+        grid.fill(1);
+        assert!(grid.iter().all(|(_, cell)| *cell == 1));
+    }
+}
@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+
+pub mod menu;
+
+pub use menu::{MenuInput, Navigate};
+
+const FONT_BYTES: &[u8] = include_bytes!("../assets/DejaVuSansMono.ttf");
+
+/// Handle to the embedded font, available from the `Startup` stage onwards.
+pub struct HudFont(pub Handle<Font>);
+
+impl HudFont {
+    pub fn style(&self, font_size: f32, color: Color) -> TextStyle {
+        TextStyle {
+            font: self.0.clone(),
+            font_size,
+            color,
+        }
+    }
+}
+
+/// Loads the shared font, spawns the UI camera needed for screen-space text and adds the
+/// `MenuInput`.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system_to_stage(StartupStage::PreStartup, load_font)
+            .add_startup_system(setup_ui_camera);
+        menu::build(app);
+    }
+}
+
+fn load_font(mut commands: Commands, mut fonts: ResMut<Assets<Font>>) {
+    let font = Font::try_from_bytes(FONT_BYTES.to_vec()).expect("embedded font is valid");
+    commands.insert_resource(HudFont(fonts.add(font)));
+}
+
+fn setup_ui_camera(mut commands: Commands) {
+    commands.spawn_bundle(UiCameraBundle::default());
+}
+
+/// Text pinned to the window, e.g. `Rect { top: Val::Px(5.), left: Val::Px(5.), ..Default::default() }`.
+pub fn screen_text(
+    font: &HudFont,
+    value: impl Into<String>,
+    font_size: f32,
+    color: Color,
+    position: Rect<Val>,
+) -> TextBundle {
+    TextBundle {
+        text: Text::with_section(value, font.style(font_size, color), Default::default()),
+        style: Style {
+            position_type: PositionType::Absolute,
+            position,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// Text centered on a point in world space, e.g. labels on a board or a game over banner.
+pub fn world_text(
+    font: &HudFont,
+    value: impl Into<String>,
+    font_size: f32,
+    color: Color,
+    translation: Vec3,
+) -> Text2dBundle {
+    Text2dBundle {
+        text: Text::with_section(
+            value,
+            font.style(font_size, color),
+            TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        ),
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+/// Cursor position in world space for the default 2D camera centered on the window, if the cursor is inside it.
+pub fn cursor_world_position(windows: &Windows) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    let cursor = window.cursor_position()?;
+    Some(cursor - Vec2::new(window.width(), window.height()) / 2.0)
+}
@@ -0,0 +1,203 @@
+//! Menu navigation shared by keyboard and gamepad: arrows, the D-pad or the left stick move the
+//! focus, Return or A confirm and Escape or B go back. The focused line of a menu is highlighted.
+
+use bevy::{input::InputSystem, prelude::*};
+
+const BINDINGS: [(Navigate, KeyCode, GamepadButtonType); 6] = [
+    (Navigate::Back, KeyCode::Escape, GamepadButtonType::East),
+    (Navigate::Confirm, KeyCode::Return, GamepadButtonType::South),
+    (Navigate::Down, KeyCode::Down, GamepadButtonType::DPadDown),
+    (Navigate::Left, KeyCode::Left, GamepadButtonType::DPadLeft),
+    (
+        Navigate::Right,
+        KeyCode::Right,
+        GamepadButtonType::DPadRight,
+    ),
+    (Navigate::Up, KeyCode::Up, GamepadButtonType::DPadUp),
+];
+pub const FOCUS_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+/// Seconds the stick is held before the focus starts moving on its own, and between moves then.
+const REPEAT_DELAY: f32 = 0.4;
+const REPEAT_INTERVAL: f32 = 0.12;
+/// How far the stick has to be pushed to move the focus.
+const STICK_THRESHOLD: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Navigate {
+    Back,
+    Confirm,
+    Down,
+    Left,
+    Right,
+    Up,
+}
+
+/// What the player did in menus this frame, from the keyboard and every gamepad.
+#[derive(Default)]
+pub struct MenuInput {
+    pressed: Vec<Navigate>,
+    /// The direction the stick is held in, and the seconds until it moves the focus again.
+    stick: Option<(Navigate, f32)>,
+}
+
+impl MenuInput {
+    /// Follows the stick held in `direction` for `delta` seconds; returns the direction when it
+    /// moves the focus: at once, then repeatedly while it is held.
+    fn hold(&mut self, direction: Option<Navigate>, delta: f32) -> Option<Navigate> {
+        match (direction, self.stick) {
+            (None, _) => {
+                self.stick = None;
+                None
+            }
+            (Some(direction), Some((held, remaining))) if held == direction => {
+                let remaining = remaining - delta;
+                if remaining <= 0.0 {
+                    self.stick = Some((direction, remaining + REPEAT_INTERVAL));
+                    Some(direction)
+                } else {
+                    self.stick = Some((direction, remaining));
+                    None
+                }
+            }
+            (Some(direction), _) => {
+                self.stick = Some((direction, REPEAT_DELAY));
+                Some(direction)
+            }
+        }
+    }
+
+    /// -1 for Left, 1 for Right, otherwise 0.
+    pub fn horizontal(&self) -> isize {
+        self.just_pressed(Navigate::Right) as isize - self.just_pressed(Navigate::Left) as isize
+    }
+
+    pub fn just_pressed(&self, navigate: Navigate) -> bool {
+        self.pressed.contains(&navigate)
+    }
+
+    /// -1 for Up, 1 for Down, otherwise 0.
+    pub fn vertical(&self) -> isize {
+        self.just_pressed(Navigate::Down) as isize - self.just_pressed(Navigate::Up) as isize
+    }
+}
+
+/// Adds the `MenuInput`, updated before the game's systems run.
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<MenuInput>()
+        .add_system_to_stage(CoreStage::PreUpdate, menu_input_update.after(InputSystem));
+}
+
+/// A menu's text as sections, its line `focused` highlighted in `FOCUS_COLOR`.
+pub fn focus_sections(style: &TextStyle, text: &str, focused: usize) -> Vec<TextSection> {
+    let lines: Vec<&str> = text.split('\n').collect();
+    let mut sections: Vec<TextSection> = Vec::new();
+    for (index, line) in lines.iter().enumerate() {
+        let color = if index == focused {
+            FOCUS_COLOR
+        } else {
+            style.color
+        };
+        let value = if index + 1 < lines.len() {
+            format!("{}\n", line)
+        } else {
+            line.to_string()
+        };
+        match sections.last_mut() {
+            Some(section) if section.style.color == color => section.value.push_str(&value),
+            _ => sections.push(TextSection {
+                value,
+                style: TextStyle {
+                    color,
+                    ..style.clone()
+                },
+            }),
+        }
+    }
+    sections
+}
+
+fn menu_input_update(
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time>,
+    mut menu: ResMut<MenuInput>,
+) {
+    menu.pressed = BINDINGS
+        .iter()
+        .filter(|(_, key, button)| {
+            keyboard.just_pressed(*key)
+                || buttons
+                    .get_just_pressed()
+                    .any(|GamepadButton(_, pressed)| pressed == button)
+        })
+        .map(|(navigate, _, _)| *navigate)
+        .collect();
+    let stick = gamepads.iter().find_map(|gamepad| {
+        let axis = |kind| axes.get(GamepadAxis(*gamepad, kind)).unwrap_or(0.0);
+        stick_direction(
+            axis(GamepadAxisType::LeftStickX),
+            axis(GamepadAxisType::LeftStickY),
+        )
+    });
+    if let Some(direction) = menu.hold(stick, time.delta_seconds()) {
+        menu.pressed.push(direction);
+    }
+}
+
+/// Moves `selected` by `step` through `count` items, wrapping around.
+pub fn step(selected: usize, count: usize, step: isize) -> usize {
+    (selected as isize + step).rem_euclid(count.max(1) as isize) as usize
+}
+
+/// The direction the stick points in, if it is pushed far enough.
+fn stick_direction(x: f32, y: f32) -> Option<Navigate> {
+    if x.abs().max(y.abs()) < STICK_THRESHOLD {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x > 0.0 {
+            Navigate::Right
+        } else {
+            Navigate::Left
+        })
+    } else {
+        Some(if y > 0.0 {
+            Navigate::Up
+        } else {
+            Navigate::Down
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_while_the_stick_is_held() {
+        assert_eq!(stick_direction(0.2, -0.3), None);
+        assert_eq!(stick_direction(0.6, -0.9), Some(Navigate::Down));
+        let mut menu = MenuInput::default();
+        let moves: Vec<bool> = [0.0, 0.3, 0.15, 0.05, 0.1]
+            .iter()
+            .map(|delta| menu.hold(Some(Navigate::Up), *delta).is_some())
+            .collect();
+        assert_eq!(moves, [true, false, true, false, true]);
+        assert_eq!(menu.hold(None, 0.1), None);
+        assert_eq!(menu.hold(Some(Navigate::Up), 0.1), Some(Navigate::Up));
+    }
+
+    #[test]
+    fn highlights_the_focused_line() {
+        let style = TextStyle::default();
+        let sections = focus_sections(&style, "TITLE\n\n  One\n> Two\n  Three", 3);
+        let values: Vec<&str> = sections.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(values, ["TITLE\n\n  One\n", "> Two\n", "  Three"]);
+        assert_eq!(sections[1].style.color, FOCUS_COLOR);
+        let last = focus_sections(&style, "One\nTwo", 1);
+        let values: Vec<&str> = last.iter().map(|s| s.value.as_str()).collect();
+        assert_eq!(values, ["One\n", "Two"]);
+        assert_eq!(step(0, 3, -1), 2);
+    }
+}
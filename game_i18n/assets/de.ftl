@@ -0,0 +1,21 @@
+# Strings every game shares, translated once.
+game-over = SPIEL VORBEI
+high-scores = Bestenliste
+level = Level { $level }
+lives = Leben { $lives }
+menu = Menü
+new-best = Neuer Rekord!
+pause = Pause
+paused = PAUSE
+play = Spielen
+play-again = Nochmal spielen
+quit = Beenden
+restart = Neu starten
+resume = Weiter
+score = Punkte
+score-value = Punkte { $score }
+
+# The keys of the screens around a game.
+hint-game-over = Enter: nochmal   Esc: Menü
+hint-menu = Enter: spielen   Esc: beenden
+hint-paused = Esc: weiter   R: neu starten   Q: Menü
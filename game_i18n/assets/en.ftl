@@ -0,0 +1,21 @@
+# Strings every game shares, translated once.
+game-over = GAME OVER
+high-scores = High scores
+level = Level { $level }
+lives = Lives { $lives }
+menu = Menu
+new-best = New best!
+pause = Pause
+paused = PAUSED
+play = Play
+play-again = Play again
+quit = Quit
+restart = Restart
+resume = Resume
+score = Score
+score-value = Score { $score }
+
+# The keys of the screens around a game.
+hint-game-over = Enter: play again   Esc: menu
+hint-menu = Enter: play   Esc: quit
+hint-paused = Esc: resume   R: restart   Q: menu
@@ -0,0 +1,21 @@
+# Strings every game shares, translated once.
+game-over = PARTIE TERMINÉE
+high-scores = Meilleurs scores
+level = Niveau { $level }
+lives = Vies { $lives }
+menu = Menu
+new-best = Nouveau record !
+pause = Pause
+paused = PAUSE
+play = Jouer
+play-again = Rejouer
+quit = Quitter
+restart = Recommencer
+resume = Reprendre
+score = Score
+score-value = Score { $score }
+
+# The keys of the screens around a game.
+hint-game-over = Entrée : rejouer   Échap : menu
+hint-menu = Entrée : jouer   Échap : quitter
+hint-paused = Échap : reprendre   R : recommencer   Q : menu
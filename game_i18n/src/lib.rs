@@ -0,0 +1,331 @@
+//! Translations for the games in this repository as Fluent files: the strings every game shares
+//! are translated once here, and each game adds its own.
+
+use bevy::prelude::*;
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use fluent_syntax::ast::Entry;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Mutex,
+};
+use unic_langid::LanguageIdentifier;
+
+pub use fluent_bundle::FluentValue;
+
+/// The `game_persistence` directory and file of the chosen language, the same for every game.
+pub const GAME: &str = "language";
+const FILE: &str = "language";
+/// The language every string is written in first, used for those missing in another.
+pub const FALLBACK: &str = "en";
+
+/// Strings every game shares, such as Pause, Quit and Score.
+const COMMON: [(&str, &str); 3] = [
+    ("de", include_str!("../assets/de.ftl")),
+    ("en", include_str!("../assets/en.ftl")),
+    ("fr", include_str!("../assets/fr.ftl")),
+];
+
+/// The strings of every language and the one they are shown in, a resource in every game with
+/// the `I18nPlugin`.
+pub struct Localization {
+    bundles: BTreeMap<String, FluentBundle<FluentResource>>,
+    /// Ids of the messages in each language.
+    ids: BTreeMap<String, BTreeSet<String>>,
+    language: String,
+    /// Ids looked up that were missing, each reported once.
+    missing: Mutex<BTreeSet<(String, String)>>,
+}
+
+impl Localization {
+    /// The common strings and the game's, given as its Fluent files by language, shown in
+    /// `language` or in English if there are none in it. A game's string replaces a common
+    /// one of the same id.
+    pub fn new(strings: &[(&str, &str)], language: &str) -> Self {
+        let mut bundles = BTreeMap::new();
+        let mut ids = BTreeMap::new();
+        for (code, source) in COMMON.iter().chain(strings) {
+            let bundle = bundles.entry(code.to_string()).or_insert_with(|| {
+                let identifier: LanguageIdentifier =
+                    code.parse().expect("languages are valid identifiers");
+                let mut bundle = FluentBundle::new_concurrent(vec![identifier]);
+                // Bidi isolation marks would show as boxes in the HUD font.
+                bundle.set_use_isolating(false);
+                bundle
+            });
+            let resource = match FluentResource::try_new(source.to_string()) {
+                Ok(resource) => resource,
+                Err((resource, errors)) => {
+                    eprintln!("could not read all {} strings: {:?}", code, errors);
+                    resource
+                }
+            };
+            let language_ids: &mut BTreeSet<String> = ids.entry(code.to_string()).or_default();
+            for entry in resource.entries() {
+                if let Entry::Message(message) = entry {
+                    language_ids.insert(message.id.name.to_string());
+                }
+            }
+            bundle.add_resource_overriding(resource);
+        }
+        let mut localization = Self {
+            bundles,
+            ids,
+            language: FALLBACK.to_string(),
+            missing: Mutex::default(),
+        };
+        localization.set_language(language);
+        localization
+    }
+
+    /// Shown in the language the player chose last, or else in the system's.
+    pub fn load(strings: &[(&str, &str)]) -> Self {
+        let language = game_persistence::load_or_default::<String>(GAME, FILE);
+        let language = if language.is_empty() {
+            system_language().unwrap_or_else(|| FALLBACK.to_string())
+        } else {
+            language
+        };
+        Self::new(strings, &language)
+    }
+
+    /// The string of `id`, with the `args` it refers to filled in, in the language shown; failing
+    /// that in English, and failing that the id itself.
+    pub fn format(&self, id: &str, args: &[(&str, FluentValue)]) -> String {
+        let mut fluent_args = FluentArgs::new();
+        for (name, value) in args {
+            fluent_args.set(*name, value.clone());
+        }
+        for language in [self.language.as_str(), FALLBACK] {
+            let bundle = &self.bundles[language];
+            let pattern = match bundle.get_message(id).and_then(|message| message.value()) {
+                Some(pattern) => pattern,
+                None => {
+                    self.report(language, id);
+                    continue;
+                }
+            };
+            let mut errors = Vec::new();
+            let value = bundle.format_pattern(pattern, Some(&fluent_args), &mut errors);
+            if !errors.is_empty() {
+                eprintln!("could not format {} in {}: {:?}", id, language, errors);
+            }
+            return value.into_owned();
+        }
+        id.to_string()
+    }
+
+    /// The string of `id`; see `format`.
+    pub fn get(&self, id: &str) -> String {
+        self.format(id, &[])
+    }
+
+    pub fn language(&self) -> &str {
+        &self.language
+    }
+
+    /// The languages there are strings in, by their codes.
+    pub fn languages(&self) -> impl Iterator<Item = &str> {
+        self.bundles.keys().map(String::as_str)
+    }
+
+    /// The ids looked up but missing so far, with the language they were missing in.
+    pub fn missing(&self) -> Vec<(String, String)> {
+        self.missing
+            .lock()
+            .expect("missing ids are never poisoned")
+            .iter()
+            .cloned()
+            .collect()
+    }
+
+    /// Switches to the next language, in the order of their codes.
+    pub fn next_language(&mut self) {
+        let languages: Vec<&str> = self.languages().collect();
+        let index = languages
+            .iter()
+            .position(|language| *language == self.language)
+            .unwrap_or(0);
+        let next = languages[(index + 1) % languages.len()].to_string();
+        self.set_language(&next);
+    }
+
+    /// Reports a missing id on the console, once per language.
+    fn report(&self, language: &str, id: &str) {
+        let mut missing = self.missing.lock().expect("missing ids are never poisoned");
+        if missing.insert((language.to_string(), id.to_string())) {
+            eprintln!("no {} string for {}", language, id);
+        }
+    }
+
+    /// Keeps the language for every game, to be shown in the next one started.
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save(GAME, FILE, &self.language) {
+            eprintln!("could not save the language: {}", error);
+        }
+    }
+
+    /// Shows the strings in `language`, or in its base language if there are none for its
+    /// region, e.g. `de` for `de-AT`; false if there are neither.
+    pub fn set_language(&mut self, language: &str) -> bool {
+        let base = base_language(language);
+        let found = [language, base.as_str()]
+            .into_iter()
+            .find(|language| self.bundles.contains_key(*language));
+        match found {
+            Some(language) => {
+                self.language = language.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The ids in English that `language` has no string for, for translators.
+    pub fn untranslated(&self, language: &str) -> Vec<&str> {
+        let translated = self.ids.get(language);
+        self.ids[FALLBACK]
+            .iter()
+            .filter(|id| !translated.is_some_and(|translated| translated.contains(*id)))
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// The language without its region or script, e.g. `pt` for `pt-BR` or `de` for `de_DE.UTF-8`.
+fn base_language(language: &str) -> String {
+    language
+        .split(|character| matches!(character, '-' | '_' | '.' | '@'))
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+}
+
+/// The base language of the system, if it tells.
+pub fn system_language() -> Option<String> {
+    sys_locale::get_locale()
+        .map(|locale| base_language(&locale))
+        .filter(|language| !language.is_empty())
+}
+
+/// A UI text showing the string of an id, translated again when the language changes.
+#[derive(Component)]
+pub struct Localized(pub &'static str);
+
+/// Adds the `Localization` of the common strings and the game's, in the language the player
+/// chose, and keeps `Localized` texts in it.
+#[derive(Default)]
+pub struct I18nPlugin {
+    strings: Vec<(&'static str, &'static str)>,
+}
+
+impl I18nPlugin {
+    /// The game's Fluent files by language, e.g.
+    /// `&[("en", include_str!("../assets/en.ftl")), ("de", include_str!("../assets/de.ftl"))]`.
+    pub fn new(strings: &[(&'static str, &'static str)]) -> Self {
+        Self {
+            strings: strings.to_vec(),
+        }
+    }
+}
+
+impl Plugin for I18nPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Localization::load(&self.strings))
+            .add_system(localized_update);
+    }
+}
+
+fn localized_update(
+    localization: Res<Localization>,
+    mut texts: Query<(ChangeTrackers<Localized>, &Localized, &mut Text)>,
+) {
+    for (tracker, Localized(id), mut text) in texts.iter_mut() {
+        if localization.is_changed() || tracker.is_changed() {
+            text.sections[0].value = localization.get(id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAME_STRINGS: [(&str, &str); 2] = [
+        (
+            "en",
+            "apples = { $count ->\n [one] One apple\n *[other] { $count } apples\n}\nscore = Points\ntitle = Snake",
+        ),
+        ("de", "apples = { $count ->\n [one] Ein Apfel\n *[other] { $count } Äpfel\n}"),
+    ];
+
+    #[test]
+    fn shares_common_strings_between_games() {
+        let mut localization = Localization::new(&[], "en");
+        assert_eq!(localization.get("quit"), "Quit");
+        assert_eq!(
+            localization.format("score-value", &[("score", 120_u32.into())]),
+            "Score 120"
+        );
+        assert!(localization.set_language("de"));
+        assert_eq!(localization.get("quit"), "Beenden");
+        for language in localization.languages() {
+            assert!(
+                localization.untranslated(language).is_empty(),
+                "{} misses {:?}",
+                language,
+                localization.untranslated(language)
+            );
+        }
+    }
+
+    #[test]
+    fn games_add_and_replace_strings() {
+        let mut localization = Localization::new(&GAME_STRINGS, "en");
+        assert_eq!(localization.get("score"), "Points");
+        assert_eq!(
+            localization.format("apples", &[("count", 1_u32.into())]),
+            "One apple"
+        );
+        localization.set_language("de");
+        assert_eq!(
+            localization.format("apples", &[("count", 3_u32.into())]),
+            "3 Äpfel"
+        );
+        // German has no string of its own for the game's score, so the common one shows.
+        assert_eq!(localization.get("score"), "Punkte");
+    }
+
+    #[test]
+    fn reports_missing_strings_and_falls_back() {
+        let mut localization = Localization::new(&GAME_STRINGS, "de");
+        assert_eq!(localization.untranslated("de"), ["title"]);
+        assert_eq!(localization.get("title"), "Snake");
+        assert_eq!(localization.get("no-such-id"), "no-such-id");
+        assert_eq!(
+            localization.missing(),
+            [
+                ("de".to_string(), "no-such-id".to_string()),
+                ("en".to_string(), "no-such-id".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn chooses_and_switches_languages() {
+        let mut localization = Localization::new(&[], "de-AT");
+        assert_eq!(localization.language(), "de");
+        assert!(!localization.set_language("tlh"));
+        assert_eq!(localization.language(), "de");
+        localization.next_language();
+        assert_eq!(localization.language(), "en");
+        localization.next_language();
+        localization.next_language();
+        assert_eq!(localization.language(), "de");
+        assert_eq!(
+            Localization::new(&[], "ja").language(),
+            FALLBACK,
+            "unknown languages show English"
+        );
+        assert_eq!(base_language("pt_BR.UTF-8"), "pt");
+    }
+}
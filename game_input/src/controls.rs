@@ -0,0 +1,232 @@
+//! A screen listing the actions and their bindings, where the player rebinds them. It needs the
+//! `HudPlugin` of `game_hud` for its font.
+
+use crate::{
+    map::{Binding, Sign, STICK_AXES},
+    Action, InputMap, TouchZones,
+};
+use bevy::prelude::*;
+use game_hud::{HudFont, MenuInput, Navigate};
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+const FONT_SIZE: f32 = 22.0;
+const HINT: &str = "Up/Down choose  Enter/A rebind  Delete/X clear  R/Y defaults  Esc/B back";
+/// Width of the column of action names.
+const NAME_WIDTH: usize = 16;
+/// Lines of the text above the first action.
+const TITLE_LINES: usize = 2;
+
+/// Whether the screen is shown, and where the player is on it. Open it from a menu; closing it
+/// saves the bindings.
+#[derive(Default)]
+pub struct ControlsScreen {
+    /// Whether the next key or button pressed becomes the selected action's binding.
+    pub listening: bool,
+    pub open: bool,
+    pub selected: usize,
+}
+
+impl ControlsScreen {
+    pub fn open(&mut self) {
+        *self = Self {
+            listening: false,
+            open: true,
+            selected: 0,
+        };
+    }
+
+    /// Moves the selection by `step` through `count` actions, wrapping around.
+    pub fn step(&mut self, count: usize, step: isize) {
+        self.selected = game_hud::menu::step(self.selected, count, step);
+    }
+}
+
+/// The full-screen background, which holds the text.
+#[derive(Component)]
+pub(crate) struct ControlsPanel;
+
+#[derive(Component)]
+pub(crate) struct ControlsText;
+
+/// The screen's text: every action with its bindings, the selected one marked.
+pub fn controls_text<A: Action>(map: &InputMap<A>, screen: &ControlsScreen) -> String {
+    let mut lines = vec!["CONTROLS".to_string(), String::new()];
+    for (index, action) in A::ALL.iter().enumerate() {
+        let selected = index == screen.selected;
+        let bindings = if selected && screen.listening {
+            "press a key or button, Esc cancels".to_string()
+        } else {
+            let labels: Vec<String> = map
+                .bindings(*action)
+                .iter()
+                .map(|binding| binding.label())
+                .collect();
+            if labels.is_empty() {
+                "-".to_string()
+            } else {
+                labels.join(", ")
+            }
+        };
+        lines.push(format!(
+            "{} {:<width$}{}",
+            if selected { '>' } else { ' ' },
+            action.name(),
+            bindings,
+            width = NAME_WIDTH
+        ));
+    }
+    lines.push(String::new());
+    lines.push(HINT.to_string());
+    lines.join("\n")
+}
+
+/// Navigates the screen, or takes the first key, button, stick or touch pressed as the new
+/// binding while listening.
+pub(crate) fn controls_input<A: Action>(
+    mut screen: ResMut<ControlsScreen>,
+    mut map: ResMut<InputMap<A>>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mouse: Res<Input<MouseButton>>,
+    zones: Res<TouchZones>,
+    menu: Res<MenuInput>,
+) {
+    if !screen.open {
+        return;
+    }
+    let action = A::ALL[screen.selected.min(A::ALL.len() - 1)];
+    let pad = |button| {
+        buttons
+            .get_just_pressed()
+            .any(|GamepadButton(_, pressed)| *pressed == button)
+    };
+
+    if screen.listening {
+        if keyboard.just_pressed(KeyCode::Escape) {
+            keyboard.clear_just_pressed(KeyCode::Escape);
+            screen.listening = false;
+            return;
+        }
+        let binding = keyboard
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| {
+                let GamepadButton(_, button) = buttons.get_just_pressed().next()?;
+                Some(Binding::Gamepad(*button))
+            })
+            .or_else(|| {
+                gamepads.iter().find_map(|gamepad| {
+                    STICK_AXES.iter().find_map(|axis| {
+                        let value = axes.get(GamepadAxis(*gamepad, *axis))?;
+                        let sign = Sign::BOTH.into_iter().find(|sign| sign.pushed(value))?;
+                        Some(Binding::Axis(*axis, sign))
+                    })
+                })
+            })
+            .or_else(|| mouse.get_just_pressed().next().map(|b| Binding::Mouse(*b)))
+            .or_else(|| zones.swiped.first().map(|swipe| Binding::Swipe(*swipe)))
+            .or_else(|| zones.tapped.first().map(|zone| Binding::Touch(*zone)));
+        if let Some(binding) = binding {
+            map.rebind(action, binding);
+            screen.listening = false;
+        }
+        return;
+    }
+
+    if menu.vertical() != 0 {
+        screen.step(A::ALL.len(), menu.vertical());
+    } else if menu.just_pressed(Navigate::Confirm) {
+        screen.listening = true;
+    } else if keyboard.any_just_pressed([KeyCode::Delete, KeyCode::Back])
+        || pad(GamepadButtonType::West)
+    {
+        map.clear(action);
+    } else if keyboard.just_pressed(KeyCode::R) || pad(GamepadButtonType::North) {
+        map.reset();
+    } else if menu.just_pressed(Navigate::Back) {
+        keyboard.clear_just_pressed(KeyCode::Escape);
+        screen.open = false;
+        map.save();
+    }
+}
+
+/// Shows the screen while it is open and keeps its text up to date.
+pub(crate) fn controls_update<A: Action>(
+    mut commands: Commands,
+    screen: Res<ControlsScreen>,
+    map: Res<InputMap<A>>,
+    font: Res<HudFont>,
+    panels: Query<Entity, With<ControlsPanel>>,
+    mut texts: Query<&mut Text, With<ControlsText>>,
+) {
+    if !screen.is_changed() && !map.is_changed() {
+        return;
+    }
+    let sections = game_hud::menu::focus_sections(
+        &font.style(FONT_SIZE, Color::WHITE),
+        &controls_text(&map, &screen),
+        TITLE_LINES + screen.selected,
+    );
+    match (screen.open, panels.get_single()) {
+        (true, Ok(_)) => {
+            for mut text in texts.iter_mut() {
+                text.sections = sections.clone();
+            }
+        }
+        (true, Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BACKGROUND),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(ControlsPanel)
+                .with_children(|parent| {
+                    let mut text = game_hud::screen_text(
+                        &font,
+                        "",
+                        FONT_SIZE,
+                        Color::WHITE,
+                        Rect {
+                            top: Val::Px(30.0),
+                            left: Val::Px(30.0),
+                            ..Default::default()
+                        },
+                    );
+                    text.text.sections = sections;
+                    parent.spawn_bundle(text).insert(ControlsText);
+                });
+        }
+        (false, _) => {
+            for entity in panels.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::Test;
+
+    #[test]
+    fn lists_the_bindings() {
+        let mut map = InputMap::<Test>::new("game_input_test");
+        map.clear(Test::Fire);
+        let mut screen = ControlsScreen::default();
+        screen.step(2, -1);
+        assert_eq!(screen.selected, 1);
+        let text = controls_text(&map, &screen);
+        assert!(text.contains("\n  Jump            Space, Pad South\n> Fire            -\n"));
+        screen.listening = true;
+        assert!(controls_text(&map, &screen).contains("> Fire            press a key"));
+    }
+}
@@ -0,0 +1,212 @@
+use bevy::{input::InputSystem, prelude::*};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{collections::HashMap, fmt::Debug, marker::PhantomData};
+
+pub mod controls;
+pub mod map;
+pub mod state;
+
+pub use controls::ControlsScreen;
+pub use map::{Binding, InputMap, Sign, Swipe, Zone};
+pub use state::ActionState;
+
+/// What a game's controls do, e.g. an enum with `Left`, `Right` and `Jump`. Games read
+/// `ActionState<A>` instead of the keyboard and gamepads, so every binding can be changed.
+pub trait Action:
+    Copy + Debug + Ord + Send + Sync + Serialize + DeserializeOwned + 'static
+{
+    /// Every action, in the order the controls screen lists them.
+    const ALL: &'static [Self];
+
+    fn default_bindings(self) -> Vec<Binding>;
+
+    /// The name on the controls screen.
+    fn name(self) -> &'static str;
+}
+
+/// Adds the `InputMap<A>` of the game, loaded with its saved bindings, the `ActionState<A>`
+/// following it each frame and the `ControlsScreen`.
+pub struct InputPlugin<A> {
+    game: &'static str,
+    marker: PhantomData<A>,
+}
+
+impl<A> InputPlugin<A> {
+    /// `game` is the directory the bindings are saved in, as for `game_persistence`.
+    pub fn new(game: &'static str) -> Self {
+        Self {
+            game,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<A: Action> Plugin for InputPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(InputMap::<A>::load(self.game))
+            .init_resource::<ActionState<A>>()
+            .init_resource::<ControlsScreen>()
+            .init_resource::<TouchZones>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                touch_update.label(TouchSystem).after(InputSystem),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, actions_update::<A>.after(TouchSystem))
+            .add_system(controls::controls_input::<A>)
+            .add_system(controls::controls_update::<A>);
+    }
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, SystemLabel)]
+struct TouchSystem;
+
+/// The zones touched and the swipes made this frame.
+#[derive(Default)]
+pub(crate) struct TouchZones {
+    pressed: Vec<Zone>,
+    /// Where each touch's next swipe is measured from, and whether it has swiped yet; `None`
+    /// is the mouse standing in for a touch in browsers.
+    starts: HashMap<Option<u64>, (Vec2, bool)>,
+    swiped: Vec<Swipe>,
+    /// The zones of touches let go of without swiping.
+    tapped: Vec<Zone>,
+}
+
+/// Presses the actions bound to what is held; nothing is pressed while the controls screen is
+/// open.
+fn actions_update<A: Action>(
+    map: Res<InputMap<A>>,
+    mut actions: ResMut<ActionState<A>>,
+    screen: Res<ControlsScreen>,
+    keyboard: Res<Input<KeyCode>>,
+    buttons: Res<Input<GamepadButton>>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    mouse: Res<Input<MouseButton>>,
+    zones: Res<TouchZones>,
+) {
+    let held = |binding| match binding {
+        Binding::Axis(axis, sign) => gamepads
+            .iter()
+            .any(|gamepad| sign.pushed(axes.get(GamepadAxis(*gamepad, axis)).unwrap_or(0.0))),
+        Binding::Gamepad(button) => buttons
+            .get_pressed()
+            .any(|GamepadButton(_, pressed)| *pressed == button),
+        Binding::Key(key) => keyboard.pressed(key),
+        Binding::Mouse(button) => mouse.pressed(button),
+        Binding::Swipe(swipe) => zones.swiped.contains(&swipe),
+        Binding::Touch(zone) => zones.pressed.contains(&zone),
+    };
+    if screen.open {
+        actions.release_held(&map, held);
+    } else {
+        actions.update(&map, held);
+    }
+}
+
+fn touch_update(
+    touches: Res<Touches>,
+    mouse: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut zones: ResMut<TouchZones>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let size = Vec2::new(window.width(), window.height());
+    // Each finger as its id, where it came down, where it is and whether it was just let go of.
+    let mut fingers: Vec<(Option<u64>, Vec2, Vec2, bool)> = touches
+        .iter()
+        .map(|touch| {
+            (
+                Some(touch.id()),
+                touch.start_position(),
+                touch.position(),
+                false,
+            )
+        })
+        .chain(touches.iter_just_released().map(|touch| {
+            (
+                Some(touch.id()),
+                touch.start_position(),
+                touch.position(),
+                true,
+            )
+        }))
+        .collect();
+    // Browsers pass touches on as the left mouse button, held at the cursor.
+    let released = mouse.just_released(MouseButton::Left);
+    if cfg!(target_arch = "wasm32") && (released || mouse.pressed(MouseButton::Left)) {
+        if let Some(cursor) = window.cursor_position() {
+            let cursor = Vec2::new(cursor.x, size.y - cursor.y);
+            fingers.push((None, cursor, cursor, released));
+        }
+    }
+
+    let TouchZones {
+        pressed,
+        starts,
+        swiped,
+        tapped,
+    } = &mut *zones;
+    pressed.clear();
+    swiped.clear();
+    tapped.clear();
+    for (id, start, position, released) in &fingers {
+        let (from, moved) = starts.entry(*id).or_insert((*start, false));
+        // A finger that keeps going after a swipe can swipe again, e.g. up and then left, and
+        // a flick may be let go of in the frame it gets far enough.
+        if let Some(swipe) = Swipe::of(*position - *from, size) {
+            swiped.push(swipe);
+            *from = *position;
+            *moved = true;
+        }
+        if !released {
+            pressed.push(Zone::at(*position, size));
+        } else if !*moved {
+            tapped.push(Zone::at(*from, size));
+        }
+    }
+    starts.retain(|id, _| {
+        fingers
+            .iter()
+            .any(|(finger, _, _, released)| finger == id && !released)
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+    pub enum Test {
+        Fire,
+        Jump,
+    }
+
+    impl Action for Test {
+        const ALL: &'static [Self] = &[Self::Jump, Self::Fire];
+
+        fn default_bindings(self) -> Vec<Binding> {
+            match self {
+                Self::Fire => vec![
+                    Binding::Key(KeyCode::X),
+                    Binding::Gamepad(GamepadButtonType::West),
+                ],
+                Self::Jump => vec![
+                    Binding::Key(KeyCode::Space),
+                    Binding::Gamepad(GamepadButtonType::South),
+                ],
+            }
+        }
+
+        fn name(self) -> &'static str {
+            match self {
+                Self::Fire => "Fire",
+                Self::Jump => "Jump",
+            }
+        }
+    }
+}
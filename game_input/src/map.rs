@@ -0,0 +1,259 @@
+//! Which keys, buttons, touch zones and swipes trigger each action, and rebinding them.
+
+use crate::Action;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// How far a stick has to be pushed to press what it is bound to.
+const AXIS_THRESHOLD: f32 = 0.5;
+const FILE: &str = "controls";
+/// The axes a stick pushed while rebinding is looked for on.
+pub(crate) const STICK_AXES: [GamepadAxisType; 4] = [
+    GamepadAxisType::LeftStickX,
+    GamepadAxisType::LeftStickY,
+    GamepadAxisType::RightStickX,
+    GamepadAxisType::RightStickY,
+];
+
+/// Something that can trigger an action.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Binding {
+    /// A stick of any connected gamepad, pushed toward one end of the axis.
+    Axis(GamepadAxisType, Sign),
+    /// The button on any connected gamepad.
+    Gamepad(GamepadButtonType),
+    Key(KeyCode),
+    Mouse(MouseButton),
+    /// A finger moved across the screen that way, pressed for the frame it gets far enough.
+    Swipe(Swipe),
+    Touch(Zone),
+}
+
+impl Binding {
+    /// Whether the bindings come from the same kind of device, so one replaces the other when
+    /// rebinding.
+    fn same_device(self, other: Self) -> bool {
+        std::mem::discriminant(&self) == std::mem::discriminant(&other)
+    }
+
+    /// A short name to show on screen, e.g. `Space` or `Pad South`.
+    pub fn label(self) -> String {
+        match self {
+            Self::Axis(axis, sign) => format!("Pad {:?}{}", axis, sign.symbol()),
+            Self::Gamepad(button) => format!("Pad {:?}", button),
+            Self::Key(key) => format!("{:?}", key),
+            Self::Mouse(button) => format!("Mouse {:?}", button),
+            Self::Swipe(swipe) => format!("Swipe {:?}", swipe),
+            Self::Touch(zone) => format!("Touch {:?}", zone),
+        }
+    }
+}
+
+/// The end of a gamepad axis a binding is pushed toward.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Sign {
+    Negative,
+    Positive,
+}
+
+impl Sign {
+    pub const BOTH: [Sign; 2] = [Self::Negative, Self::Positive];
+
+    /// Whether an axis at `value` is pushed far enough toward this end.
+    pub fn pushed(self, value: f32) -> bool {
+        match self {
+            Self::Negative => value <= -AXIS_THRESHOLD,
+            Self::Positive => value >= AXIS_THRESHOLD,
+        }
+    }
+
+    fn symbol(self) -> char {
+        match self {
+            Self::Negative => '-',
+            Self::Positive => '+',
+        }
+    }
+}
+
+/// The part of the screen a touch lands in: the left and right thirds, or the top, middle or
+/// bottom of the center third.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Zone {
+    Bottom,
+    Center,
+    Left,
+    Right,
+    Top,
+}
+
+impl Zone {
+    /// The zone of a position in a window of `size`, measured from the top left.
+    pub fn at(position: Vec2, size: Vec2) -> Self {
+        let third = size / 3.0;
+        if position.x < third.x {
+            Self::Left
+        } else if position.x >= 2.0 * third.x {
+            Self::Right
+        } else if position.y < third.y {
+            Self::Top
+        } else if position.y >= 2.0 * third.y {
+            Self::Bottom
+        } else {
+            Self::Center
+        }
+    }
+}
+
+/// The way a touch moved.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum Swipe {
+    Down,
+    Left,
+    Right,
+    Up,
+}
+
+impl Swipe {
+    /// The swipe of a touch that moved by `moved` in a window of `size`, measured from the top
+    /// left, if it went far enough; the longer of its two sides decides the way.
+    pub fn of(moved: Vec2, size: Vec2) -> Option<Self> {
+        if moved.length() < SWIPE_DISTANCE * size.x.min(size.y) {
+            None
+        } else if moved.x.abs() > moved.y.abs() {
+            Some(if moved.x < 0.0 {
+                Self::Left
+            } else {
+                Self::Right
+            })
+        } else {
+            Some(if moved.y < 0.0 { Self::Up } else { Self::Down })
+        }
+    }
+}
+
+/// The bindings of every action of a game, loaded from and saved to its `controls` file.
+pub struct InputMap<A: Action> {
+    bindings: BTreeMap<A, Vec<Binding>>,
+    game: &'static str,
+}
+
+impl<A: Action> InputMap<A> {
+    /// The default bindings, with the ones the player changed and saved on top.
+    pub fn load(game: &'static str) -> Self {
+        let mut map = Self::new(game);
+        let saved: BTreeMap<A, Vec<Binding>> = game_persistence::load_or_default(game, FILE);
+        map.bindings.extend(saved);
+        map
+    }
+
+    /// The default bindings.
+    pub fn new(game: &'static str) -> Self {
+        Self {
+            bindings: A::ALL
+                .iter()
+                .map(|action| (*action, action.default_bindings()))
+                .collect(),
+            game,
+        }
+    }
+
+    pub fn bindings(&self, action: A) -> &[Binding] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Takes the bindings off the action.
+    pub fn clear(&mut self, action: A) {
+        self.bindings.insert(action, Vec::new());
+    }
+
+    /// Binds the action to `binding` in place of its binding on the same kind of device, and
+    /// takes `binding` off any other action so it never triggers two.
+    pub fn rebind(&mut self, action: A, binding: Binding) {
+        for bindings in self.bindings.values_mut() {
+            bindings.retain(|other| *other != binding);
+        }
+        let bindings = self.bindings.entry(action).or_default();
+        match bindings.iter().position(|other| other.same_device(binding)) {
+            Some(index) => bindings[index] = binding,
+            None => bindings.push(binding),
+        }
+    }
+
+    /// Goes back to the default bindings.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.game);
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save(self.game, FILE, &self.bindings) {
+            eprintln!("could not save the controls: {}", error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::Test;
+
+    #[test]
+    fn rebinding_moves_bindings() {
+        let mut map = InputMap::<Test>::new("game_input_test");
+        assert_eq!(
+            map.bindings(Test::Jump),
+            [
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::South)
+            ]
+        );
+        map.rebind(Test::Jump, Binding::Key(KeyCode::Up));
+        map.rebind(Test::Fire, Binding::Mouse(MouseButton::Left));
+        map.rebind(Test::Fire, Binding::Gamepad(GamepadButtonType::South));
+        assert_eq!(map.bindings(Test::Jump), [Binding::Key(KeyCode::Up)]);
+        assert_eq!(
+            map.bindings(Test::Fire),
+            [
+                Binding::Key(KeyCode::X),
+                Binding::Gamepad(GamepadButtonType::South),
+                Binding::Mouse(MouseButton::Left)
+            ]
+        );
+        map.clear(Test::Jump);
+        assert_eq!(map.bindings(Test::Jump), []);
+        map.reset();
+        assert_eq!(map.bindings(Test::Fire).len(), 2);
+    }
+
+    #[test]
+    fn binds_sticks_pushed_far_enough() {
+        assert!(Sign::Positive.pushed(0.8));
+        assert!(!Sign::Positive.pushed(0.3));
+        assert!(!Sign::Positive.pushed(-0.8));
+        assert!(Sign::Negative.pushed(-0.5));
+        assert_eq!(
+            Binding::Axis(GamepadAxisType::LeftStickX, Sign::Negative).label(),
+            "Pad LeftStickX-"
+        );
+    }
+
+    #[test]
+    fn finds_touch_zones() {
+        let size = Vec2::new(300.0, 600.0);
+        assert_eq!(Zone::at(Vec2::new(10.0, 300.0), size), Zone::Left);
+        assert_eq!(Zone::at(Vec2::new(250.0, 10.0), size), Zone::Right);
+        assert_eq!(Zone::at(Vec2::new(150.0, 10.0), size), Zone::Top);
+        assert_eq!(Zone::at(Vec2::new(150.0, 300.0), size), Zone::Center);
+        assert_eq!(Zone::at(Vec2::new(150.0, 590.0), size), Zone::Bottom);
+    }
+
+    #[test]
+    fn finds_swipes_long_enough() {
+        let size = Vec2::new(300.0, 600.0);
+        assert_eq!(Swipe::of(Vec2::new(10.0, -5.0), size), None);
+        assert_eq!(Swipe::of(Vec2::new(-40.0, 10.0), size), Some(Swipe::Left));
+        assert_eq!(Swipe::of(Vec2::new(5.0, 30.0), size), Some(Swipe::Down));
+        assert_eq!(Swipe::of(Vec2::new(20.0, -60.0), size), Some(Swipe::Up));
+        assert_eq!(Swipe::of(Vec2::new(90.0, 80.0), size), Some(Swipe::Right));
+    }
+}
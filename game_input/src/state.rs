@@ -0,0 +1,119 @@
+//! Which actions are held this frame, and which were just pressed or released.
+
+use crate::{map::Binding, Action, InputMap};
+use std::collections::BTreeSet;
+
+pub struct ActionState<A: Action> {
+    /// Actions held while everything was released, which only count once they are let go of.
+    held_over: BTreeSet<A>,
+    just_pressed: BTreeSet<A>,
+    just_released: BTreeSet<A>,
+    pressed: BTreeSet<A>,
+}
+
+impl<A: Action> Default for ActionState<A> {
+    fn default() -> Self {
+        Self {
+            held_over: BTreeSet::new(),
+            just_pressed: BTreeSet::new(),
+            just_released: BTreeSet::new(),
+            pressed: BTreeSet::new(),
+        }
+    }
+}
+
+impl<A: Action> ActionState<A> {
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.just_pressed.contains(&action)
+    }
+
+    pub fn just_released(&self, action: A) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    pub fn pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    /// Presses `action` until the next update, as a computer player would.
+    pub fn press(&mut self, action: A) {
+        if self.pressed.insert(action) {
+            self.just_pressed.insert(action);
+        }
+    }
+
+    /// Releases everything, e.g. while the controls screen is open.
+    pub fn release_all(&mut self) {
+        self.just_pressed.clear();
+        self.just_released = std::mem::take(&mut self.pressed);
+    }
+
+    /// Releases everything like `release_all`, and keeps the actions with a binding that is
+    /// held from being pressed until it is let go of, so e.g. the key closing the controls
+    /// screen does nothing more.
+    pub fn release_held(&mut self, map: &InputMap<A>, held: impl Fn(Binding) -> bool) {
+        self.release_all();
+        self.held_over = held_actions(map, held);
+    }
+
+    /// Takes the actions with a binding that is held as pressed, and compares with the frame
+    /// before.
+    pub fn update(&mut self, map: &InputMap<A>, held: impl Fn(Binding) -> bool) {
+        let held = held_actions(map, held);
+        self.held_over.retain(|action| held.contains(action));
+        let pressed: BTreeSet<A> = held.difference(&self.held_over).copied().collect();
+        self.just_pressed = pressed.difference(&self.pressed).copied().collect();
+        self.just_released = self.pressed.difference(&pressed).copied().collect();
+        self.pressed = pressed;
+    }
+}
+
+fn held_actions<A: Action>(map: &InputMap<A>, held: impl Fn(Binding) -> bool) -> BTreeSet<A> {
+    A::ALL
+        .iter()
+        .copied()
+        .filter(|action| map.bindings(*action).iter().any(|binding| held(*binding)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::Test;
+    use bevy::prelude::*;
+
+    #[test]
+    fn tracks_presses_and_releases() {
+        let map = InputMap::<Test>::new("game_input_test");
+        let mut state = ActionState::default();
+        state.update(&map, |binding| binding == Binding::Key(KeyCode::Space));
+        assert!(state.pressed(Test::Jump) && state.just_pressed(Test::Jump));
+        // The gamepad keeps it held when the key is let go.
+        state.update(&map, |binding| {
+            binding == Binding::Gamepad(GamepadButtonType::South)
+                || binding == Binding::Key(KeyCode::X)
+        });
+        assert!(state.pressed(Test::Jump) && !state.just_pressed(Test::Jump));
+        assert!(state.just_pressed(Test::Fire));
+        state.release_all();
+        assert!(state.just_released(Test::Jump) && state.just_released(Test::Fire));
+        assert!(!state.pressed(Test::Fire));
+        state.press(Test::Fire);
+        assert!(state.pressed(Test::Fire) && state.just_pressed(Test::Fire));
+        state.update(&map, |_| false);
+        assert!(!state.pressed(Test::Fire) && state.just_released(Test::Fire));
+    }
+
+    #[test]
+    fn holds_over_what_is_held_while_released() {
+        let map = InputMap::<Test>::new("game_input_test");
+        let mut state = ActionState::default();
+        let space = |binding| binding == Binding::Key(KeyCode::Space);
+        state.release_held(&map, space);
+        state.update(&map, space);
+        assert!(!state.pressed(Test::Jump) && !state.just_pressed(Test::Jump));
+        state.update(&map, |_| false);
+        state.update(&map, space);
+        assert!(state.just_pressed(Test::Jump));
+    }
+}
@@ -0,0 +1,494 @@
+//! Connections that never block: a host taking in players, and a connection per player.
+
+use crate::{
+    decode, encode,
+    frame::{self, FrameReader},
+    websocket::{self, Frame},
+    Latency, NetError,
+};
+use game_rng::Stream;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    io::{self, Read, Write},
+    mem,
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    time::{Duration, Instant},
+};
+
+/// How long a player waits for a host to answer, when connecting and in a WebSocket handshake.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A connection to the other side, sending and receiving messages of any serde type.
+pub struct Connection {
+    closed: bool,
+    framing: Framing,
+    latency: Option<Latency>,
+    /// Framed bytes the socket has not taken yet.
+    outgoing: Vec<u8>,
+    started: Instant,
+    stream: TcpStream,
+}
+
+/// How the messages of a connection are framed on its stream.
+enum Framing {
+    /// Each message after its length, as the games' own executables send them.
+    Length(FrameReader),
+    /// WebSocket frames; a player masks its own with `masks`, as the protocol asks.
+    WebSocket {
+        masks: Option<Stream>,
+        reader: websocket::FrameReader,
+    },
+}
+
+impl Connection {
+    fn close(&mut self, error: NetError) -> NetError {
+        self.closed = true;
+        error
+    }
+
+    /// Connects to a host; waits until it answers, for a few seconds at most, then never blocks
+    /// again.
+    pub fn connect(address: impl ToSocketAddrs) -> Result<Self, NetError> {
+        Self::new(connect(address)?, Framing::Length(FrameReader::default()))
+    }
+
+    /// Connects over WebSocket to a host listening for it, at an address such as
+    /// `ws://192.168.1.20:7778`; waits until it answers the handshake, then never blocks again.
+    pub fn connect_websocket(address: &str) -> Result<Self, NetError> {
+        let address = address.strip_prefix(websocket::SCHEME).unwrap_or(address);
+        let address = address.trim_end_matches('/');
+        let mut stream = connect(address)?;
+        let mut masks = websocket::masks();
+        let key = websocket::key(&mut masks);
+        stream.write_all(websocket::request(address, &key).as_bytes())?;
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        let mut received = Vec::new();
+        let mut buffer = [0; 1024];
+        let end = loop {
+            if let Some(end) = websocket::header_end(&received)? {
+                break end;
+            }
+            match stream.read(&mut buffer)? {
+                0 => return Err(NetError::Closed),
+                read => received.extend_from_slice(&buffer[..read]),
+            }
+        };
+        websocket::check_answer(&received[..end], &key)?;
+        stream.set_read_timeout(None)?;
+        let mut reader = websocket::FrameReader::default();
+        reader.extend(&received[end..]);
+        Self::new(
+            stream,
+            Framing::WebSocket {
+                masks: Some(masks),
+                reader,
+            },
+        )
+    }
+
+    /// Writes what the socket takes of what was sent, including messages the latency simulator
+    /// held back until now.
+    pub fn flush(&mut self) -> Result<(), NetError> {
+        let now = self.started.elapsed().as_secs_f32();
+        if let Some(latency) = &mut self.latency {
+            for frame in latency.arrived(now) {
+                self.outgoing.extend(frame);
+            }
+        }
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(self.close(NetError::Closed)),
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(self.close(error.into())),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// `bytes` framed for the other side.
+    fn frame(&mut self, bytes: &[u8]) -> Result<Vec<u8>, NetError> {
+        match &mut self.framing {
+            Framing::Length(_) => frame::frame(bytes),
+            Framing::WebSocket { masks, .. } => {
+                websocket::frame(bytes, masks.as_mut().map(websocket::mask))
+            }
+        }
+    }
+
+    fn new(stream: TcpStream, framing: Framing) -> Result<Self, NetError> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            closed: false,
+            framing,
+            latency: None,
+            outgoing: Vec::new(),
+            started: Instant::now(),
+            stream,
+        })
+    }
+
+    pub fn peer_address(&self) -> Option<SocketAddr> {
+        self.stream.peer_addr().ok()
+    }
+
+    /// The messages that have arrived since the last call.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> Result<Vec<T>, NetError> {
+        self.flush()?;
+        let mut buffer = [0; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    self.closed = true;
+                    break;
+                }
+                Ok(read) => match &mut self.framing {
+                    Framing::Length(reader) => reader.extend(&buffer[..read]),
+                    Framing::WebSocket { reader, .. } => reader.extend(&buffer[..read]),
+                },
+                Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => return Err(self.close(error.into())),
+            }
+        }
+        let mut messages = Vec::new();
+        match &mut self.framing {
+            Framing::Length(reader) => {
+                while let Some(frame) = reader.next_frame()? {
+                    messages.push(decode(&frame)?);
+                }
+            }
+            Framing::WebSocket { masks, reader } => {
+                while let Some(frame) = reader.next_frame()? {
+                    match frame {
+                        Frame::Close => {
+                            self.closed = true;
+                            break;
+                        }
+                        Frame::Message(bytes) => messages.push(decode(&bytes)?),
+                        Frame::Ping(bytes) => {
+                            let pong =
+                                websocket::pong(&bytes, masks.as_mut().map(websocket::mask))?;
+                            self.outgoing.extend(pong);
+                        }
+                    }
+                }
+            }
+        }
+        if self.closed && messages.is_empty() {
+            return Err(NetError::Closed);
+        }
+        Ok(messages)
+    }
+
+    pub fn send<T: Serialize>(&mut self, message: &T) -> Result<(), NetError> {
+        let frame = self.frame(&encode(message)?)?;
+        self.send_frame(frame)
+    }
+
+    fn send_frame(&mut self, frame: Vec<u8>) -> Result<(), NetError> {
+        if self.closed {
+            return Err(NetError::Closed);
+        }
+        match &mut self.latency {
+            Some(latency) => latency.send(self.started.elapsed().as_secs_f32(), frame),
+            None => self.outgoing.extend(frame),
+        }
+        self.flush()
+    }
+
+    /// Holds back everything sent from now on as `latency` says.
+    pub fn simulate(&mut self, latency: Latency) {
+        self.latency = Some(latency);
+    }
+}
+
+/// What happened at a host since it was last polled.
+#[derive(Debug, PartialEq)]
+pub enum Event<T> {
+    Joined(PeerId),
+    /// Disconnected, or sent something the host could not read.
+    Left(PeerId),
+    Message(PeerId, T),
+}
+
+/// Listens for players and keeps a connection to each.
+pub struct Host {
+    /// Players who have connected over WebSocket, with what they sent of their handshake.
+    handshaking: Vec<(TcpStream, Vec<u8>)>,
+    listener: TcpListener,
+    next_id: u32,
+    peers: Vec<(PeerId, Connection)>,
+    websocket: Option<TcpListener>,
+}
+
+impl Host {
+    /// Takes in a player, who joins at the next poll.
+    fn add(&mut self, connection: Connection) -> PeerId {
+        let id = PeerId(self.next_id);
+        self.next_id += 1;
+        self.peers.push((id, connection));
+        id
+    }
+
+    pub fn bind(address: impl ToSocketAddrs) -> Result<Self, NetError> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            handshaking: Vec::new(),
+            listener,
+            next_id: 0,
+            peers: Vec::new(),
+            websocket: None,
+        })
+    }
+
+    /// Sends a message to every peer. Peers it cannot reach leave at the next poll.
+    pub fn broadcast<T: Serialize>(&mut self, message: &T) -> Result<(), NetError> {
+        let bytes = encode(message)?;
+        if bytes.len() > frame::MAX_LENGTH {
+            return Err(NetError::TooLong(bytes.len()));
+        }
+        for (_, connection) in &mut self.peers {
+            if let Ok(frame) = connection.frame(&bytes) {
+                connection.send_frame(frame).ok();
+            }
+        }
+        Ok(())
+    }
+
+    pub fn connection_mut(&mut self, peer: PeerId) -> Option<&mut Connection> {
+        self.peers
+            .iter_mut()
+            .find(|(id, _)| *id == peer)
+            .map(|(_, connection)| connection)
+    }
+
+    /// Takes in players over WebSocket as well, such as those in a browser, on a second address.
+    /// Returns the address they connect to, with the port picked when listening on port 0.
+    pub fn listen_websocket(
+        &mut self,
+        address: impl ToSocketAddrs,
+    ) -> Result<SocketAddr, NetError> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        let address = listener.local_addr()?;
+        self.websocket = Some(listener);
+        Ok(address)
+    }
+
+    /// The address players connect to, with the port picked when binding to port 0.
+    pub fn local_address(&self) -> Result<SocketAddr, NetError> {
+        Ok(self.listener.local_addr()?)
+    }
+
+    pub fn peers(&self) -> impl Iterator<Item = PeerId> + '_ {
+        self.peers.iter().map(|(id, _)| *id)
+    }
+
+    /// Takes in new players, collects the messages of every peer and lets go of peers that left.
+    pub fn poll<T: DeserializeOwned>(&mut self) -> Vec<Event<T>> {
+        let mut events = Vec::new();
+        for stream in accept(&self.listener) {
+            if let Ok(connection) = Connection::new(stream, Framing::Length(FrameReader::default()))
+            {
+                events.push(Event::Joined(self.add(connection)));
+            }
+        }
+        if let Some(listener) = &self.websocket {
+            for stream in accept(listener) {
+                if stream.set_nonblocking(true).is_ok() {
+                    self.handshaking.push((stream, Vec::new()));
+                }
+            }
+        }
+        for (mut stream, mut received) in mem::take(&mut self.handshaking) {
+            match handshake(&mut stream, &mut received) {
+                Ok(Some((answer, end))) => {
+                    let mut reader = websocket::FrameReader::default();
+                    reader.extend(&received[end..]);
+                    let framing = Framing::WebSocket {
+                        masks: None,
+                        reader,
+                    };
+                    if let Ok(mut connection) = Connection::new(stream, framing) {
+                        // Sent before anything else, when the connection is first flushed.
+                        connection.outgoing.extend(answer.into_bytes());
+                        events.push(Event::Joined(self.add(connection)));
+                    }
+                }
+                Ok(None) => self.handshaking.push((stream, received)),
+                Err(error) => eprintln!("could not take in a player over WebSocket: {}", error),
+            }
+        }
+        self.peers
+            .retain_mut(|(id, connection)| match connection.receive() {
+                Ok(messages) => {
+                    events.extend(
+                        messages
+                            .into_iter()
+                            .map(|message| Event::Message(*id, message)),
+                    );
+                    true
+                }
+                Err(_) => {
+                    events.push(Event::Left(*id));
+                    false
+                }
+            });
+        events
+    }
+
+    pub fn send<T: Serialize>(&mut self, peer: PeerId, message: &T) -> Result<(), NetError> {
+        match self.connection_mut(peer) {
+            Some(connection) => connection.send(message),
+            None => Err(NetError::Closed),
+        }
+    }
+}
+
+/// The players waiting to be taken in by `listener`.
+fn accept(listener: &TcpListener) -> Vec<TcpStream> {
+    let mut streams = Vec::new();
+    loop {
+        match listener.accept() {
+            Ok((stream, _)) => streams.push(stream),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) => {
+                eprintln!("could not accept a player: {}", error);
+                break;
+            }
+        }
+    }
+    streams
+}
+
+/// A stream to the first of the addresses `address` stands for that answers within `TIMEOUT`.
+fn connect(address: impl ToSocketAddrs) -> io::Result<TcpStream> {
+    let mut last_error = io::Error::new(io::ErrorKind::InvalidInput, "no address");
+    for address in address.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&address, TIMEOUT) {
+            Ok(stream) => return Ok(stream),
+            Err(error) => last_error = error,
+        }
+    }
+    Err(last_error)
+}
+
+/// Reads what has arrived of a player's WebSocket handshake. Once it is whole, returns the
+/// host's answer and where the handshake ends in `received`.
+fn handshake(
+    stream: &mut TcpStream,
+    received: &mut Vec<u8>,
+) -> Result<Option<(String, usize)>, NetError> {
+    let mut buffer = [0; 1024];
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => return Err(NetError::Closed),
+            Ok(read) => received.extend_from_slice(&buffer[..read]),
+            Err(error) if error.kind() == io::ErrorKind::WouldBlock => break,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Err(error) => return Err(error.into()),
+        }
+    }
+    match websocket::header_end(received)? {
+        Some(end) => Ok(Some((websocket::answer(&received[..end])?, end))),
+        None => Ok(None),
+    }
+}
+
+/// A player connected to a host, numbered in the order they joined.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerId(pub u32);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{thread, time::Duration};
+
+    /// Polls until `done` says so, for at most two seconds.
+    fn wait_for(mut done: impl FnMut() -> bool) {
+        for _ in 0..200 {
+            if done() {
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("timed out");
+    }
+
+    #[test]
+    fn host_and_player_talk_over_loopback() {
+        let mut host = Host::bind("127.0.0.1:0").unwrap();
+        let mut player = Connection::connect(host.local_address().unwrap()).unwrap();
+        player.send(&("hello".to_string(), 1)).unwrap();
+        let mut events = Vec::new();
+        wait_for(|| {
+            events.extend(host.poll::<(String, u32)>());
+            events.len() == 2
+        });
+        assert_eq!(
+            events,
+            [
+                Event::Joined(PeerId(0)),
+                Event::Message(PeerId(0), ("hello".to_string(), 1))
+            ]
+        );
+        host.broadcast(&vec![3, 4]).unwrap();
+        let mut received: Vec<Vec<u8>> = Vec::new();
+        wait_for(|| {
+            received.extend(player.receive::<Vec<u8>>().unwrap());
+            !received.is_empty()
+        });
+        assert_eq!(received, [vec![3, 4]]);
+        drop(player);
+        let mut left = false;
+        wait_for(|| {
+            left |= host
+                .poll::<(String, u32)>()
+                .contains(&Event::Left(PeerId(0)));
+            left
+        });
+        assert_eq!(host.peers().count(), 0);
+    }
+
+    #[test]
+    fn players_join_over_websocket_too() {
+        let mut host = Host::bind("127.0.0.1:0").unwrap();
+        let address = host.listen_websocket("127.0.0.1:0").unwrap();
+        let mut events = Vec::new();
+        // The handshake waits for the host to poll, so it runs beside the player.
+        let player = thread::spawn(move || {
+            let mut player = Connection::connect_websocket(&format!("ws://{}", address)).unwrap();
+            player.send(&"hello".to_string()).unwrap();
+            player
+        });
+        wait_for(|| {
+            events.extend(host.poll::<String>());
+            events.len() == 2
+        });
+        let mut player = player.join().unwrap();
+        assert_eq!(
+            events,
+            [
+                Event::Joined(PeerId(0)),
+                Event::Message(PeerId(0), "hello".to_string())
+            ]
+        );
+        host.broadcast(&(1, 2)).unwrap();
+        let mut received: Vec<(u8, u8)> = Vec::new();
+        wait_for(|| {
+            received.extend(player.receive::<(u8, u8)>().unwrap());
+            !received.is_empty()
+        });
+        assert_eq!(received, [(1, 2)]);
+    }
+}
@@ -0,0 +1,70 @@
+//! Messages on a byte stream, each sent as its length in four bytes and then its bytes.
+
+use crate::NetError;
+
+/// The longest frame accepted, far beyond any message of a game.
+pub const MAX_LENGTH: usize = 1 << 20;
+
+/// Collects bytes as they arrive and hands out the frames they complete.
+#[derive(Default)]
+pub struct FrameReader {
+    buffer: Vec<u8>,
+}
+
+impl FrameReader {
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// The next complete frame, if it has arrived.
+    pub fn next_frame(&mut self) -> Result<Option<Vec<u8>>, NetError> {
+        if self.buffer.len() < 4 {
+            return Ok(None);
+        }
+        let mut header = [0; 4];
+        header.copy_from_slice(&self.buffer[..4]);
+        let length = u32::from_be_bytes(header) as usize;
+        if length > MAX_LENGTH {
+            return Err(NetError::TooLong(length));
+        }
+        if self.buffer.len() < 4 + length {
+            return Ok(None);
+        }
+        let frame = self.buffer[4..4 + length].to_vec();
+        self.buffer.drain(..4 + length);
+        Ok(Some(frame))
+    }
+}
+
+/// `bytes` as a frame.
+pub fn frame(bytes: &[u8]) -> Result<Vec<u8>, NetError> {
+    if bytes.len() > MAX_LENGTH {
+        return Err(NetError::TooLong(bytes.len()));
+    }
+    let mut framed = (bytes.len() as u32).to_be_bytes().to_vec();
+    framed.extend_from_slice(bytes);
+    Ok(framed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_survive_arriving_in_pieces() {
+        let mut stream = frame(b"hello").unwrap();
+        stream.extend(frame(b"").unwrap());
+        stream.extend(frame(b"world").unwrap());
+        let mut reader = FrameReader::default();
+        let mut frames = Vec::new();
+        for byte in stream {
+            reader.extend(&[byte]);
+            while let Some(frame) = reader.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(frames, [b"hello".to_vec(), vec![], b"world".to_vec()]);
+        reader.extend(&u32::MAX.to_be_bytes());
+        assert!(matches!(reader.next_frame(), Err(NetError::TooLong(_))));
+    }
+}
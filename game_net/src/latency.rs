@@ -0,0 +1,54 @@
+//! Holding sent messages back as a slow connection would, to try games on one.
+
+use game_rng::Stream;
+
+/// Delays frames by `delay` seconds plus up to `jitter` more. Frames keep their order, as they do
+/// over TCP.
+pub struct Latency {
+    delay: f32,
+    jitter: f32,
+    /// Frames on their way, with the second they arrive, in order.
+    queue: Vec<(f32, Vec<u8>)>,
+    stream: Stream,
+}
+
+impl Latency {
+    pub fn new(delay: f32, jitter: f32, seed: u64) -> Self {
+        Self {
+            delay,
+            jitter,
+            queue: Vec::new(),
+            stream: Stream::new(seed),
+        }
+    }
+
+    /// The frames that have arrived by second `now`.
+    pub fn arrived(&mut self, now: f32) -> Vec<Vec<u8>> {
+        let count = self.queue.iter().take_while(|(at, _)| *at <= now).count();
+        self.queue.drain(..count).map(|(_, frame)| frame).collect()
+    }
+
+    /// Sends a frame at second `now`.
+    pub fn send(&mut self, now: f32, frame: Vec<u8>) {
+        let at = now + self.delay + self.jitter * self.stream.unit();
+        let after_last = self.queue.last().map_or(at, |(last, _)| at.max(*last));
+        self.queue.push((after_last, frame));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_arrive_late_and_in_order() {
+        let mut latency = Latency::new(0.1, 0.05, 9);
+        for (index, now) in [0.0, 0.01, 0.02].iter().enumerate() {
+            latency.send(*now, vec![index as u8]);
+        }
+        assert!(latency.arrived(0.09).is_empty());
+        let mut arrived = latency.arrived(0.13);
+        arrived.extend(latency.arrived(0.18));
+        assert_eq!(arrived, [vec![0], vec![1], vec![2]]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Connections between players over TCP or WebSocket, messages of any serde type, state kept in
+//! sync by deltas, and a latency simulator for trying games on a bad connection.
+//!
+//! TCP keeps messages in order without a protocol of its own on top, as UDP would need. A host
+//! takes in players over WebSocket as well, the only way a browser has of reaching it; the
+//! connections themselves are made with sockets, though, so none of this works in a `wasm32`
+//! build.
+
+use serde::{de::DeserializeOwned, Serialize};
+use std::{error::Error, fmt, io};
+
+pub mod connection;
+pub mod frame;
+pub mod latency;
+pub mod sync;
+pub mod websocket;
+
+pub use connection::{Connection, Event, Host, PeerId};
+pub use latency::Latency;
+pub use sync::{Replica, Replicator, Update};
+
+#[derive(Debug)]
+pub enum NetError {
+    /// The other side closed the connection.
+    Closed,
+    /// A message that does not decode as what was expected, e.g. from another version.
+    Corrupt(ron::Error),
+    /// A WebSocket handshake that went wrong, such as a request that is not one.
+    Handshake(String),
+    Io(io::Error),
+    /// A frame longer than `frame::MAX_LENGTH`, which no game sends.
+    TooLong(usize),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Closed => write!(f, "the connection was closed"),
+            Self::Corrupt(error) => write!(f, "corrupt message: {}", error),
+            Self::Handshake(error) => write!(f, "WebSocket handshake failed: {}", error),
+            Self::Io(error) => write!(f, "{}", error),
+            Self::TooLong(length) => write!(f, "a message of {} bytes is too long", length),
+        }
+    }
+}
+
+impl Error for NetError {}
+
+impl From<io::Error> for NetError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::Error> for NetError {
+    fn from(error: ron::Error) -> Self {
+        Self::Corrupt(error)
+    }
+}
+
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, NetError> {
+    Ok(ron::de::from_bytes(bytes)?)
+}
+
+pub fn encode<T: Serialize>(message: &T) -> Result<Vec<u8>, NetError> {
+    Ok(ron::to_string(message)?.into_bytes())
+}
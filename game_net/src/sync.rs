@@ -0,0 +1,169 @@
+//! Keeping a game state, a map from ids to values, in sync between a host and its players. The
+//! host sends the changes since a state the player acknowledged, or the whole state when there is
+//! none.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+
+/// States kept on both sides for deltas to be based on.
+const HISTORY: usize = 32;
+
+/// A state for a player, whole or as the changes from an earlier one.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Update<K: Ord, V> {
+    Delta {
+        /// The tick of the state the changes are from.
+        base: u32,
+        changed: Vec<(K, V)>,
+        removed: Vec<K>,
+        tick: u32,
+    },
+    Full {
+        state: BTreeMap<K, V>,
+        tick: u32,
+    },
+}
+
+/// A player's copy of the host's state.
+pub struct Replica<K, V> {
+    /// The latest states, oldest first.
+    received: VecDeque<(u32, BTreeMap<K, V>)>,
+}
+
+impl<K, V> Default for Replica<K, V> {
+    fn default() -> Self {
+        Self {
+            received: VecDeque::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Replica<K, V> {
+    /// Applies an update; returns the tick to acknowledge to the host, or `None` if it was older
+    /// than the state or based on one this replica no longer has.
+    pub fn apply(&mut self, update: Update<K, V>) -> Option<u32> {
+        let (tick, state) = match update {
+            Update::Delta {
+                base,
+                changed,
+                removed,
+                tick,
+            } => {
+                let (_, base) = self.received.iter().find(|(tick, _)| *tick == base)?;
+                let mut state = base.clone();
+                for key in removed {
+                    state.remove(&key);
+                }
+                state.extend(changed);
+                (tick, state)
+            }
+            Update::Full { state, tick } => (tick, state),
+        };
+        if matches!(self.tick(), Some(latest) if tick <= latest) {
+            return None;
+        }
+        self.received.push_back((tick, state));
+        if self.received.len() > HISTORY {
+            self.received.pop_front();
+        }
+        Some(tick)
+    }
+
+    pub fn state(&self) -> Option<&BTreeMap<K, V>> {
+        self.received.back().map(|(_, state)| state)
+    }
+
+    pub fn tick(&self) -> Option<u32> {
+        self.received.back().map(|(tick, _)| *tick)
+    }
+}
+
+/// The host's side of keeping one player in sync.
+pub struct Replicator<K, V> {
+    acknowledged: Option<u32>,
+    /// The latest states sent, oldest first.
+    sent: VecDeque<(u32, BTreeMap<K, V>)>,
+}
+
+impl<K, V> Default for Replicator<K, V> {
+    fn default() -> Self {
+        Self {
+            acknowledged: None,
+            sent: VecDeque::new(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone + PartialEq> Replicator<K, V> {
+    /// The player has the state of `tick`, so later updates can be based on it.
+    pub fn acknowledge(&mut self, tick: u32) {
+        if !matches!(self.acknowledged, Some(latest) if tick <= latest) {
+            self.acknowledged = Some(tick);
+        }
+    }
+
+    /// The update bringing the player to `state`, the state of `tick`.
+    pub fn update(&mut self, tick: u32, state: &BTreeMap<K, V>) -> Update<K, V> {
+        let base = self
+            .acknowledged
+            .and_then(|acknowledged| self.sent.iter().find(|(tick, _)| *tick == acknowledged));
+        let update = match base {
+            Some((base, old)) => Update::Delta {
+                base: *base,
+                changed: state
+                    .iter()
+                    .filter(|(key, value)| old.get(key) != Some(value))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect(),
+                removed: old
+                    .keys()
+                    .filter(|key| !state.contains_key(key))
+                    .cloned()
+                    .collect(),
+                tick,
+            },
+            None => Update::Full {
+                state: state.clone(),
+                tick,
+            },
+        };
+        self.sent.push_back((tick, state.clone()));
+        if self.sent.len() > HISTORY {
+            self.sent.pop_front();
+        }
+        update
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sends_only_changes_once_acknowledged() {
+        let mut replicator = Replicator::default();
+        let mut replica = Replica::default();
+        let mut state = BTreeMap::from([(1, (0, 0)), (2, (5, 5))]);
+        let first = replicator.update(0, &state);
+        assert!(matches!(first, Update::Full { .. }));
+        replicator.acknowledge(replica.apply(first).unwrap());
+        state.insert(1, (1, 0));
+        state.remove(&2);
+        state.insert(3, (9, 9));
+        let second = replicator.update(1, &state);
+        assert_eq!(
+            second,
+            Update::Delta {
+                base: 0,
+                changed: vec![(1, (1, 0)), (3, (9, 9))],
+                removed: vec![2],
+                tick: 1,
+            }
+        );
+        assert_eq!(replica.apply(second.clone()), Some(1));
+        assert_eq!(replica.state(), Some(&state));
+        // A repeated or stale update changes nothing.
+        assert_eq!(replica.apply(second), None);
+        assert_eq!(replica.tick(), Some(1));
+    }
+}
@@ -0,0 +1,360 @@
+//! The WebSocket protocol over a TCP stream: the HTTP handshake opening it and messages in its
+//! frames, which browsers send and receive though they have no sockets.
+
+use crate::{frame::MAX_LENGTH, NetError};
+use game_rng::Stream;
+use std::{
+    mem,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const CLOSE: u8 = 0x8;
+/// The first bit of a frame, set on the last frame of a message.
+const FIN: u8 = 0x80;
+/// Appended to a player's key by the host to prove it speaks WebSocket.
+const GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+/// The set bit of the length byte of a masked frame.
+const MASKED: u8 = 0x80;
+/// The longest handshake accepted, far beyond what a browser sends.
+const MAX_HEADER: usize = 8192;
+const PING: u8 = 0x9;
+const PONG: u8 = 0xA;
+/// A connection's address may start with it, as browsers write them.
+pub const SCHEME: &str = "ws://";
+const TEXT: u8 = 0x1;
+
+/// What a frame from the other side says.
+#[derive(Debug, PartialEq)]
+pub enum Frame {
+    Close,
+    /// A whole message, however many frames it came in.
+    Message(Vec<u8>),
+    /// Asks for a pong with the same bytes.
+    Ping(Vec<u8>),
+}
+
+/// Collects bytes as they arrive and hands out what the frames they complete say.
+#[derive(Default)]
+pub struct FrameReader {
+    buffer: Vec<u8>,
+    /// The frames of a message sent in pieces, until its last one arrives.
+    partial: Vec<u8>,
+}
+
+impl FrameReader {
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// What the next complete frame says, if it has arrived. Pongs are skipped.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, NetError> {
+        loop {
+            if self.buffer.len() < 2 {
+                return Ok(None);
+            }
+            let last = self.buffer[0] & FIN != 0;
+            let opcode = self.buffer[0] & 0x0f;
+            let (length, mut start) = match self.buffer[1] & !MASKED {
+                126 if self.buffer.len() < 4 => return Ok(None),
+                126 => (
+                    u16::from_be_bytes([self.buffer[2], self.buffer[3]]) as u64,
+                    4,
+                ),
+                127 if self.buffer.len() < 10 => return Ok(None),
+                127 => {
+                    let mut length = [0; 8];
+                    length.copy_from_slice(&self.buffer[2..10]);
+                    (u64::from_be_bytes(length), 10)
+                }
+                length => (length as u64, 2),
+            };
+            if length > MAX_LENGTH as u64 {
+                return Err(NetError::TooLong(length as usize));
+            }
+            let length = length as usize;
+            let mask = if self.buffer[1] & MASKED != 0 {
+                if self.buffer.len() < start + 4 {
+                    return Ok(None);
+                }
+                let mut mask = [0; 4];
+                mask.copy_from_slice(&self.buffer[start..start + 4]);
+                start += 4;
+                Some(mask)
+            } else {
+                None
+            };
+            if self.buffer.len() < start + length {
+                return Ok(None);
+            }
+            let mut payload = self.buffer[start..start + length].to_vec();
+            self.buffer.drain(..start + length);
+            if let Some(mask) = mask {
+                apply_mask(&mut payload, mask);
+            }
+            match opcode {
+                CLOSE => return Ok(Some(Frame::Close)),
+                PING => return Ok(Some(Frame::Ping(payload))),
+                PONG => {}
+                _ => {
+                    self.partial.extend(payload);
+                    if self.partial.len() > MAX_LENGTH {
+                        return Err(NetError::TooLong(self.partial.len()));
+                    }
+                    if last {
+                        return Ok(Some(Frame::Message(mem::take(&mut self.partial))));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The `Sec-WebSocket-Accept` a host answers a player's `Sec-WebSocket-Key` with.
+fn accept(key: &str) -> String {
+    base64(&sha1(format!("{}{}", key, GUID).as_bytes()))
+}
+
+/// The host's answer to a player's whole handshake request, switching to WebSocket.
+pub fn answer(request: &[u8]) -> Result<String, NetError> {
+    let request = String::from_utf8_lossy(request);
+    if !request.starts_with("GET ") {
+        return Err(NetError::Handshake("not a WebSocket request".to_string()));
+    }
+    let key = header(&request, "Sec-WebSocket-Key")
+        .ok_or_else(|| NetError::Handshake("no Sec-WebSocket-Key".to_string()))?;
+    Ok(format!(
+        "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Accept: {}\r\n\r\n",
+        accept(key)
+    ))
+}
+
+fn apply_mask(bytes: &mut [u8], mask: [u8; 4]) {
+    for (index, byte) in bytes.iter_mut().enumerate() {
+        *byte ^= mask[index % 4];
+    }
+}
+
+fn base64(bytes: &[u8]) -> String {
+    let mut text = String::new();
+    for chunk in bytes.chunks(3) {
+        let byte = |index: usize| chunk.get(index).copied().unwrap_or(0) as u32;
+        let bits = (byte(0) << 16) | (byte(1) << 8) | byte(2);
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(ALPHABET[((bits >> (18 - 6 * index)) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Whether the host's whole answer to a handshake with `key` switched to WebSocket.
+pub fn check_answer(answer: &[u8], key: &str) -> Result<(), NetError> {
+    let answer = String::from_utf8_lossy(answer);
+    if answer.split_whitespace().nth(1) != Some("101") {
+        let status = answer.lines().next().unwrap_or_default();
+        return Err(NetError::Handshake(format!("the host answered {}", status)));
+    }
+    if header(&answer, "Sec-WebSocket-Accept") != Some(accept(key).as_str()) {
+        return Err(NetError::Handshake("the host's key is wrong".to_string()));
+    }
+    Ok(())
+}
+
+/// `bytes` as the frame of a message, masked with `mask` as the frames of a player must be.
+pub fn frame(bytes: &[u8], mask: Option<[u8; 4]>) -> Result<Vec<u8>, NetError> {
+    // Messages are RON, which is text a browser reads as a string.
+    frame_of(TEXT, bytes, mask)
+}
+
+fn frame_of(opcode: u8, bytes: &[u8], mask: Option<[u8; 4]>) -> Result<Vec<u8>, NetError> {
+    if bytes.len() > MAX_LENGTH {
+        return Err(NetError::TooLong(bytes.len()));
+    }
+    let mut frame = vec![FIN | opcode];
+    let masked = if mask.is_some() { MASKED } else { 0 };
+    match bytes.len() {
+        length @ 0..=125 => frame.push(masked | length as u8),
+        length @ 126..=0xffff => {
+            frame.push(masked | 126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(masked | 127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    if let Some(mask) = mask {
+        frame.extend(mask);
+    }
+    let start = frame.len();
+    frame.extend_from_slice(bytes);
+    if let Some(mask) = mask {
+        apply_mask(&mut frame[start..], mask);
+    }
+    Ok(frame)
+}
+
+/// The value of the header `name` in a handshake.
+fn header<'a>(handshake: &'a str, name: &str) -> Option<&'a str> {
+    handshake.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+    })
+}
+
+/// Where the header of a handshake ends, once it has arrived whole.
+pub fn header_end(received: &[u8]) -> Result<Option<usize>, NetError> {
+    match received.windows(4).position(|window| window == b"\r\n\r\n") {
+        Some(end) => Ok(Some(end + 4)),
+        None if received.len() > MAX_HEADER => {
+            Err(NetError::Handshake("the handshake is too long".to_string()))
+        }
+        None => Ok(None),
+    }
+}
+
+/// A new `Sec-WebSocket-Key` for a handshake.
+pub fn key(masks: &mut Stream) -> String {
+    let mut bytes = masks.next_u64().to_be_bytes().to_vec();
+    bytes.extend(masks.next_u64().to_be_bytes());
+    base64(&bytes)
+}
+
+/// A mask for the next frame of a player.
+pub fn mask(masks: &mut Stream) -> [u8; 4] {
+    (masks.next_u64() as u32).to_be_bytes()
+}
+
+/// The keys and masks of a player's connection, different every time.
+pub fn masks() -> Stream {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_nanos() as u64);
+    Stream::new(now)
+}
+
+/// The pong answering a ping with `bytes`.
+pub fn pong(bytes: &[u8], mask: Option<[u8; 4]>) -> Result<Vec<u8>, NetError> {
+    frame_of(PONG, bytes, mask)
+}
+
+/// The handshake request a player opens a WebSocket to `host` with.
+pub fn request(host: &str, key: &str) -> String {
+    format!(
+        "GET / HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        host, key
+    )
+}
+
+fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend((bytes.len() as u64 * 8).to_be_bytes());
+    for block in message.chunks(64) {
+        let mut words = [0u32; 80];
+        for (word, bytes) in words.iter_mut().zip(block.chunks(4)) {
+            *word = u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        }
+        for index in 16..80 {
+            words[index] =
+                (words[index - 3] ^ words[index - 8] ^ words[index - 14] ^ words[index - 16])
+                    .rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (index, word) in words.iter().enumerate() {
+            let (f, k) = match index {
+                0..=19 => ((b & c) | (!b & d), 0x5A827999),
+                20..=39 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let next = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = next;
+        }
+        for (value, added) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(added);
+        }
+    }
+    let mut digest = [0; 20];
+    for (bytes, value) in digest.chunks_mut(4).zip(state) {
+        bytes.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn answers_the_key_of_the_protocol_example() {
+        // The example of RFC 6455, section 1.3.
+        assert_eq!(
+            accept("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+        assert_eq!(base64(b"any carnal pleas"), "YW55IGNhcm5hbCBwbGVhcw==");
+        assert_eq!(base64(b"any carnal pleasu"), "YW55IGNhcm5hbCBwbGVhc3U=");
+        let request = request("localhost:7777", "dGhlIHNhbXBsZSBub25jZQ==");
+        let end = header_end(request.as_bytes()).unwrap().unwrap();
+        assert_eq!(end, request.len());
+        let reply = answer(request.as_bytes()).unwrap();
+        check_answer(reply.as_bytes(), "dGhlIHNhbXBsZSBub25jZQ==").unwrap();
+        assert!(check_answer(reply.as_bytes(), "another key").is_err());
+        assert!(answer(b"\0\0\0\x05hello").is_err());
+    }
+
+    #[test]
+    fn frames_survive_masks_lengths_and_pieces() {
+        let long = vec![7; 70_000];
+        let mut stream = frame(b"hello", Some([1, 2, 3, 4])).unwrap();
+        stream.extend(frame(&long, None).unwrap());
+        stream.extend(frame(&[3; 300], Some([9, 8, 7, 6])).unwrap());
+        stream.extend(frame_of(PONG, b"", None).unwrap());
+        stream.extend(frame_of(PING, b"ping", None).unwrap());
+        // A message in two frames, the first without its last bit.
+        let mut first = frame(b"wor", None).unwrap();
+        first[0] &= !FIN;
+        stream.extend(first);
+        let mut second = frame(b"ld", None).unwrap();
+        second[0] = FIN;
+        stream.extend(second);
+        stream.extend(frame_of(CLOSE, b"", None).unwrap());
+        let mut reader = FrameReader::default();
+        let mut frames = Vec::new();
+        for piece in stream.chunks(1000) {
+            reader.extend(piece);
+            while let Some(frame) = reader.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+        assert_eq!(
+            frames,
+            [
+                Frame::Message(b"hello".to_vec()),
+                Frame::Message(long),
+                Frame::Message(vec![3; 300]),
+                Frame::Ping(b"ping".to_vec()),
+                Frame::Message(b"world".to_vec()),
+                Frame::Close,
+            ]
+        );
+    }
+}
@@ -0,0 +1,304 @@
+use bevy::prelude::*;
+use game_a11y::Accessibility;
+use rand::random;
+
+pub mod preset;
+
+pub use preset::{Preset, Presets};
+
+/// Particles spawned up front; more are spawned when they run out, and reused too.
+const POOL_SIZE: usize = 256;
+/// In front of the game's sprites.
+const Z: f32 = 10.0;
+
+/// Sends the particles of a preset from a point in the world.
+pub struct Emit {
+    /// Colors every particle instead of the preset's colors, e.g. as what burst.
+    pub color: Option<Color>,
+    pub position: Vec2,
+    pub preset: &'static str,
+}
+
+/// Adds the `Presets`, extended by the game's data file if it has one, and the pool of
+/// particles. Games send `Emit` events or give moving entities a `Trail`.
+#[derive(Default)]
+pub struct ParticlePlugin {
+    presets: Option<&'static str>,
+}
+
+impl ParticlePlugin {
+    /// With the presets of a RON file, e.g. `include_str!("../assets/particles.ron")`.
+    pub fn with_presets(presets: &'static str) -> Self {
+        Self {
+            presets: Some(presets),
+        }
+    }
+}
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        let mut presets = Presets::default();
+        if let Some(text) = self.presets {
+            if let Err(error) = presets.extend_from_ron(text) {
+                eprintln!("could not read the particle presets: {}", error);
+            }
+        }
+        app.insert_resource(presets)
+            .init_resource::<Pool>()
+            .add_event::<Emit>()
+            .add_startup_system(setup)
+            .add_system(trail_update.label(Trails))
+            .add_system(emit_update.after(Trails))
+            .add_system(particles_update);
+    }
+}
+
+/// Leaves particles of a preset behind the entity as it moves.
+#[derive(Component)]
+pub struct Trail {
+    /// Emits owed from the frames before.
+    pending: f32,
+    pub preset: &'static str,
+}
+
+impl Trail {
+    pub fn new(preset: &'static str) -> Self {
+        Self {
+            pending: 0.0,
+            preset,
+        }
+    }
+}
+
+/// A particle in flight, or waiting in the pool once its lifetime is over.
+#[derive(Component, Default)]
+struct Particle {
+    age: f32,
+    /// Of its color when it was emitted.
+    alpha: f32,
+    fade: bool,
+    gravity: f32,
+    lifetime: f32,
+    shrink: bool,
+    size: f32,
+    spin: f32,
+    velocity: Vec2,
+}
+
+impl Particle {
+    /// A particle of the preset, flying off in a random direction within its spread.
+    fn new(preset: &Preset) -> Self {
+        let angle = preset.direction + (random::<f32>() - 0.5) * preset.spread;
+        let speed = between(preset.speed);
+        Self {
+            age: 0.0,
+            alpha: 1.0,
+            fade: preset.fade,
+            gravity: preset.gravity,
+            lifetime: between(preset.lifetime),
+            shrink: preset.shrink,
+            size: preset.size,
+            spin: (random::<f32>() * 2.0 - 1.0) * preset.spin,
+            velocity: Vec2::new(angle.cos(), angle.sin()) * speed,
+        }
+    }
+
+    /// Ages it by `delta` seconds; returns how far it moved, or `None` once its life is over.
+    fn advance(&mut self, delta: f32) -> Option<Vec2> {
+        self.age += delta;
+        if self.age >= self.lifetime {
+            return None;
+        }
+        self.velocity.y -= self.gravity * delta;
+        Some(self.velocity * delta)
+    }
+
+    /// The alpha of its color as it ages.
+    fn current_alpha(&self) -> f32 {
+        if self.fade {
+            self.alpha * (1.0 - self.age / self.lifetime).max(0.0)
+        } else {
+            self.alpha
+        }
+    }
+
+    /// Its size as it ages.
+    fn current_size(&self) -> f32 {
+        if self.shrink {
+            self.size * (1.0 - self.age / self.lifetime).max(0.0)
+        } else {
+            self.size
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.age < self.lifetime
+    }
+}
+
+/// Particles whose lifetime is over, to reuse.
+#[derive(Default)]
+struct Pool(Vec<Entity>);
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+struct Trails;
+
+fn between((low, high): (f32, f32)) -> f32 {
+    low + (high - low) * random::<f32>()
+}
+
+/// Starts the particles of every emit, from the pool as far as it goes. Emits are dropped while
+/// the player wants reduced motion.
+fn emit_update(
+    mut commands: Commands,
+    accessibility: Option<Res<Accessibility>>,
+    mut emits: EventReader<Emit>,
+    presets: Res<Presets>,
+    mut pool: ResMut<Pool>,
+    mut particles: Query<(&mut Particle, &mut Sprite, &mut Transform, &mut Visibility)>,
+) {
+    if matches!(accessibility, Some(accessibility) if accessibility.reduced_motion) {
+        emits.iter().last();
+        return;
+    }
+    for emit in emits.iter() {
+        let preset = match presets.get(emit.preset) {
+            Some(preset) => preset,
+            None => {
+                eprintln!("no particle preset {}", emit.preset);
+                continue;
+            }
+        };
+        for _ in 0..preset.count {
+            let mut particle = Particle::new(preset);
+            let color = emit.color.unwrap_or_else(|| {
+                preset
+                    .colors
+                    .get(random::<usize>() % preset.colors.len().max(1))
+                    .copied()
+                    .unwrap_or(Color::WHITE)
+            });
+            particle.alpha = color.a();
+            let translation = emit.position.extend(Z);
+            let reused = pool
+                .0
+                .pop()
+                .and_then(|entity| particles.get_mut(entity).ok());
+            match reused {
+                Some((mut old, mut sprite, mut transform, mut visibility)) => {
+                    sprite.color = color;
+                    sprite.custom_size = Some(Vec2::splat(particle.size));
+                    *transform = Transform::from_translation(translation);
+                    visibility.is_visible = true;
+                    *old = particle;
+                }
+                None => {
+                    commands
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                color,
+                                custom_size: Some(Vec2::splat(particle.size)),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(translation),
+                            ..Default::default()
+                        })
+                        .insert(particle);
+                }
+            }
+        }
+    }
+}
+
+/// Moves the particles, and puts them back in the pool when their life is over.
+fn particles_update(
+    time: Res<Time>,
+    mut pool: ResMut<Pool>,
+    mut particles: Query<(
+        Entity,
+        &mut Particle,
+        &mut Sprite,
+        &mut Transform,
+        &mut Visibility,
+    )>,
+) {
+    let delta = time.delta_seconds();
+    for (entity, mut particle, mut sprite, mut transform, mut visibility) in particles.iter_mut() {
+        if !particle.is_alive() {
+            continue;
+        }
+        match particle.advance(delta) {
+            Some(step) => {
+                transform.translation += step.extend(0.0);
+                transform.rotate(Quat::from_rotation_z(particle.spin * delta));
+                sprite.custom_size = Some(Vec2::splat(particle.current_size()));
+                sprite.color.set_a(particle.current_alpha());
+            }
+            None => {
+                visibility.is_visible = false;
+                pool.0.push(entity);
+            }
+        }
+    }
+}
+
+fn setup(mut commands: Commands, mut pool: ResMut<Pool>) {
+    for _ in 0..POOL_SIZE {
+        let entity = commands
+            .spawn_bundle(SpriteBundle {
+                visibility: Visibility { is_visible: false },
+                ..Default::default()
+            })
+            .insert(Particle::default())
+            .id();
+        pool.0.push(entity);
+    }
+}
+
+/// Emits from every entity with a `Trail`, as often as its preset's rate says.
+fn trail_update(
+    time: Res<Time>,
+    presets: Res<Presets>,
+    mut trails: Query<(&mut Trail, &GlobalTransform)>,
+    mut emits: EventWriter<Emit>,
+) {
+    for (mut trail, transform) in trails.iter_mut() {
+        let rate = presets.get(trail.preset).map_or(0.0, |preset| preset.rate);
+        trail.pending += rate * time.delta_seconds();
+        while trail.pending >= 1.0 {
+            trail.pending -= 1.0;
+            emits.send(Emit {
+                color: None,
+                position: transform.translation.truncate(),
+                preset: trail.preset,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn particles_fall_and_expire() {
+        let mut particle = Particle {
+            alpha: 0.8,
+            fade: true,
+            gravity: 100.0,
+            lifetime: 1.0,
+            shrink: true,
+            size: 4.0,
+            velocity: Vec2::new(10.0, 0.0),
+            ..Default::default()
+        };
+        assert_eq!(particle.advance(0.5), Some(Vec2::new(5.0, -25.0)));
+        assert_eq!(particle.current_size(), 2.0);
+        assert_eq!(particle.current_alpha(), 0.4);
+        assert!(particle.is_alive());
+        assert_eq!(particle.advance(0.5), None);
+        assert!(!particle.is_alive());
+        // A new particle of the pool is not alive until it is emitted.
+        assert!(!Particle::default().is_alive());
+    }
+}
@@ -0,0 +1,131 @@
+//! How the particles of an effect look and move, built in or read from a RON data file.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    f32::consts::{FRAC_PI_2, TAU},
+};
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Preset {
+    /// Each particle gets one of them.
+    pub colors: Vec<Color>,
+    /// Particles sent at once.
+    pub count: u32,
+    /// The middle of the directions the particles fly in, in radians; 0 is to the right.
+    pub direction: f32,
+    /// Whether the particles fade out as they age.
+    pub fade: bool,
+    /// Pulls the particles down, in pixels per second squared.
+    pub gravity: f32,
+    /// Seconds a particle lives, between the two.
+    pub lifetime: (f32, f32),
+    /// Emits per second of a `Trail`.
+    pub rate: f32,
+    /// Whether the particles shrink away as they age.
+    pub shrink: bool,
+    /// Width and height of a particle, in pixels.
+    pub size: f32,
+    /// Pixels per second, between the two.
+    pub speed: (f32, f32),
+    /// Radians per second a particle turns at most, e.g. for confetti.
+    pub spin: f32,
+    /// How far the directions spread around `direction`, in radians; `TAU` is every way.
+    pub spread: f32,
+}
+
+impl Default for Preset {
+    fn default() -> Self {
+        Self {
+            colors: vec![Color::WHITE],
+            count: 24,
+            direction: 0.0,
+            fade: false,
+            gravity: 0.0,
+            lifetime: (0.3, 0.6),
+            rate: 0.0,
+            shrink: true,
+            size: 4.0,
+            speed: (80.0, 220.0),
+            spin: 0.0,
+            spread: TAU,
+        }
+    }
+}
+
+/// The presets by name: `burst`, `confetti` and `trail`, and those of the game's data file.
+pub struct Presets(HashMap<String, Preset>);
+
+impl Default for Presets {
+    fn default() -> Self {
+        let confetti = Preset {
+            colors: vec![
+                Color::rgb(1.0, 0.3, 0.3),
+                Color::rgb(1.0, 0.85, 0.2),
+                Color::rgb(0.3, 0.8, 1.0),
+                Color::rgb(0.5, 1.0, 0.4),
+                Color::rgb(0.9, 0.4, 1.0),
+            ],
+            count: 60,
+            direction: FRAC_PI_2,
+            gravity: 500.0,
+            lifetime: (1.0, 1.8),
+            shrink: false,
+            size: 6.0,
+            speed: (200.0, 420.0),
+            spin: 10.0,
+            spread: 1.2,
+            ..Default::default()
+        };
+        let trail = Preset {
+            count: 1,
+            lifetime: (0.2, 0.4),
+            rate: 40.0,
+            size: 3.0,
+            speed: (0.0, 20.0),
+            ..Default::default()
+        };
+        Self(HashMap::from([
+            ("burst".to_string(), Preset::default()),
+            ("confetti".to_string(), confetti),
+            ("trail".to_string(), trail),
+        ]))
+    }
+}
+
+impl Presets {
+    /// Adds the presets of a RON map from names to presets, replacing built in ones of the
+    /// same name. Fields left out keep the values of `burst`.
+    pub fn extend_from_ron(&mut self, text: &str) -> Result<(), ron::Error> {
+        let presets: HashMap<String, Preset> = ron::from_str(text)?;
+        self.0.extend(presets);
+        Ok(())
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Preset> {
+        self.0.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_presets_from_data_files() {
+        let mut presets = Presets::default();
+        presets
+            .extend_from_ron("{\"sparks\": (count: 8, gravity: 300.0), \"trail\": (rate: 10.0)}")
+            .unwrap();
+        let sparks = presets.get("sparks").unwrap();
+        assert_eq!((sparks.count, sparks.gravity), (8, 300.0));
+        assert_eq!(sparks.speed, Preset::default().speed);
+        assert_eq!(presets.get("trail").unwrap().rate, 10.0);
+        assert!(presets.get("confetti").is_some());
+        assert!(presets
+            .extend_from_ron("{\"broken\": (count: -1)}")
+            .is_err());
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{de::DeserializeOwned, Serialize};
+use std::{env, error::Error, fmt, fs, io, path::PathBuf};
+
+pub mod schema;
+
+pub use schema::{decode, encode, Schema};
+
+const ROOT_DIRECTORY: &str = "rust_games";
+/// The environment variable that, when set, holds the directory to keep everything in instead.
+pub const DIRECTORY_VARIABLE: &str = "RUST_GAMES_DATA";
+
+#[derive(Debug)]
+pub enum PersistenceError {
+    /// The platform has no data directory (e.g. no home directory is set).
+    NoDataDirectory,
+    Io(io::Error),
+    /// The file exists but does not contain what was expected, e.g. after a crash mid-write.
+    Corrupt(ron::Error),
+    /// The file was saved at an older version of its schema that has no migration.
+    NoMigration(u32),
+    /// The file was saved at a newer version of its schema, by a newer build of the game.
+    Newer(u32),
+}
+
+impl fmt::Display for PersistenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoDataDirectory => write!(f, "no data directory available"),
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Corrupt(error) => write!(f, "corrupt save data: {}", error),
+            Self::NoMigration(version) => write!(f, "no migration from version {}", version),
+            Self::Newer(version) => write!(f, "saved by a newer version ({})", version),
+        }
+    }
+}
+
+impl Error for PersistenceError {}
+
+impl From<io::Error> for PersistenceError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<ron::Error> for PersistenceError {
+    fn from(error: ron::Error) -> Self {
+        Self::Corrupt(error)
+    }
+}
+
+/// The data directory of `game`: `<data dir>/rust_games/<game>`, or `$RUST_GAMES_DATA/<game>`
+/// when that is set, for files that are not RON saves.
+pub fn directory(game: &str) -> Result<PathBuf, PersistenceError> {
+    let mut path = match env::var_os(DIRECTORY_VARIABLE) {
+        Some(root) => PathBuf::from(root),
+        None => dirs::data_dir()
+            .ok_or(PersistenceError::NoDataDirectory)?
+            .join(ROOT_DIRECTORY),
+    };
+    path.push(game);
+    Ok(path)
+}
+
+/// Where `file` of `game` is stored: `<data dir>/rust_games/<game>/<file>.ron`.
+pub fn path(game: &str, file: &str) -> Result<PathBuf, PersistenceError> {
+    Ok(directory(game)?.join(format!("{}.ron", file)))
+}
+
+/// Loads a saved value, `Ok(None)` if it was never saved.
+pub fn load<T: DeserializeOwned>(game: &str, file: &str) -> Result<Option<T>, PersistenceError> {
+    load_with(game, file, &Schema::default())
+}
+
+/// Loads a saved value, falling back to the default when it is missing or unreadable.
+pub fn load_or_default<T: DeserializeOwned + Default>(game: &str, file: &str) -> T {
+    load_or_default_with(game, file, &Schema::default())
+}
+
+/// Loads a saved value of any version of its schema, `Ok(None)` if it was never saved.
+pub fn load_with<T: DeserializeOwned>(
+    game: &str,
+    file: &str,
+    schema: &Schema<T>,
+) -> Result<Option<T>, PersistenceError> {
+    let path = path(game, file)?;
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(error) => return Err(error.into()),
+    };
+    Ok(Some(decode(&contents, schema)?))
+}
+
+/// Loads a saved value of any version of its schema, falling back to the default when it is
+/// missing or unreadable. An unreadable file is kept as `<file>.ron.bak`, so saving the default
+/// over it never loses what it held.
+pub fn load_or_default_with<T: DeserializeOwned + Default>(
+    game: &str,
+    file: &str,
+    schema: &Schema<T>,
+) -> T {
+    match load_with(game, file, schema) {
+        Ok(value) => value.unwrap_or_default(),
+        Err(error) => {
+            eprintln!("could not load {}/{}: {}", game, file, error);
+            if let Ok(path) = path(game, file) {
+                if let Err(error) = fs::copy(&path, path.with_extension("ron.bak")) {
+                    eprintln!("could not back up {}: {}", path.display(), error);
+                }
+            }
+            T::default()
+        }
+    }
+}
+
+/// Saves a value at version 0 of its schema; see `save_with`.
+pub fn save<T: Serialize>(game: &str, file: &str, value: &T) -> Result<(), PersistenceError> {
+    save_with(game, file, value, &Schema::default())
+}
+
+/// Saves a value with the version of its schema. Writes to a temporary file first, so a crash
+/// never leaves a half written save behind.
+pub fn save_with<T: Serialize>(
+    game: &str,
+    file: &str,
+    value: &T,
+    schema: &Schema<T>,
+) -> Result<(), PersistenceError> {
+    let path = path(game, file)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let contents = encode(value, schema)?;
+    let temporary = path.with_extension("ron.tmp");
+    fs::write(&temporary, contents)?;
+    fs::rename(temporary, path)?;
+    Ok(())
+}
+
+/// Deletes a saved value; removing something that was never saved is not an error.
+pub fn remove(game: &str, file: &str) -> Result<(), PersistenceError> {
+    match fs::remove_file(path(game, file)?) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,196 @@
+//! Versions of saved files, and the migrations that read files of older versions.
+
+use crate::PersistenceError;
+use serde::{
+    de::{DeserializeOwned, IgnoredAny},
+    Deserialize, Serialize,
+};
+
+/// What is written to a file: the version of its schema and the value.
+#[derive(Deserialize, Serialize)]
+struct Versioned<T> {
+    version: u32,
+    data: T,
+}
+
+/// Only the version of a file, whatever its data.
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+    #[allow(dead_code)]
+    data: IgnoredAny,
+}
+
+/// Reads a file of an older version as the type saved now.
+type Migration<T> = fn(&str) -> Result<T, ron::Error>;
+
+/// Reads `T` from a file of any version that has it, with or without the version.
+fn read<T: DeserializeOwned>(contents: &str) -> Result<T, ron::Error> {
+    match ron::from_str::<Versioned<T>>(contents) {
+        Ok(versioned) => Ok(versioned.data),
+        Err(error) => ron::from_str(contents).map_err(|_| error),
+    }
+}
+
+/// The version of the layout of `T` saved now, and how files of older versions are read.
+/// Files from before files carried a version are version 0, so a type whose layout never
+/// changed needs no schema of its own: `Schema::default()` is version 0 without migrations.
+///
+/// When the layout changes, keep the old type, bump the version and register the old one:
+/// `Schema::new(1).migrate::<ScoresV0>(0)`, where `ScoresV0: Into<Scores>`.
+pub struct Schema<T> {
+    migrations: Vec<(u32, Migration<T>)>,
+    version: u32,
+}
+
+impl<T> Default for Schema<T> {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl<T> Schema<T> {
+    pub fn new(version: u32) -> Self {
+        Self {
+            migrations: Vec::new(),
+            version,
+        }
+    }
+
+    /// Reads files saved at `version` as `Old`, upgraded to `T` by its `Into`.
+    pub fn migrate<Old: DeserializeOwned + Into<T>>(mut self, version: u32) -> Self {
+        self.migrations
+            .push((version, |contents| read::<Old>(contents).map(Into::into)));
+        self
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+/// Reads a file's contents, upgrading them if they were saved at an older version.
+pub fn decode<T: DeserializeOwned>(
+    contents: &str,
+    schema: &Schema<T>,
+) -> Result<T, PersistenceError> {
+    let version = ron::from_str::<Header>(contents).map_or(0, |header| header.version);
+    if version == schema.version {
+        return Ok(read(contents)?);
+    }
+    if version > schema.version {
+        return Err(PersistenceError::Newer(version));
+    }
+    match schema.migrations.iter().find(|(from, _)| *from == version) {
+        Some((_, migration)) => Ok(migration(contents)?),
+        None => Err(PersistenceError::NoMigration(version)),
+    }
+}
+
+/// A file's contents: `value` with the version of its schema.
+pub fn encode<T: Serialize>(value: &T, schema: &Schema<T>) -> Result<String, PersistenceError> {
+    let versioned = Versioned {
+        version: schema.version,
+        data: value,
+    };
+    Ok(ron::ser::to_string_pretty(
+        &versioned,
+        ron::ser::PrettyConfig::default(),
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Version 0: only the best score.
+    #[derive(Deserialize)]
+    struct RecordV0(u32);
+
+    /// Version 1: the best score and who got it.
+    #[derive(Deserialize)]
+    struct RecordV1 {
+        best: u32,
+        name: String,
+    }
+
+    /// Version 2: every score by name, and a difficulty.
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Record {
+        difficulty: Difficulty,
+        scores: Vec<(String, u32)>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    enum Difficulty {
+        Easy,
+        Normal,
+    }
+
+    impl From<RecordV0> for RecordV1 {
+        fn from(RecordV0(best): RecordV0) -> Self {
+            Self {
+                best,
+                name: "???".to_string(),
+            }
+        }
+    }
+
+    impl From<RecordV0> for Record {
+        fn from(record: RecordV0) -> Self {
+            RecordV1::from(record).into()
+        }
+    }
+
+    impl From<RecordV1> for Record {
+        fn from(record: RecordV1) -> Self {
+            Self {
+                difficulty: Difficulty::Normal,
+                scores: vec![(record.name, record.best)],
+            }
+        }
+    }
+
+    fn schema() -> Schema<Record> {
+        Schema::new(2).migrate::<RecordV0>(0).migrate::<RecordV1>(1)
+    }
+
+    #[test]
+    fn upgrades_every_older_version() {
+        let expected = |name: &str| Record {
+            difficulty: Difficulty::Normal,
+            scores: vec![(name.to_string(), 40)],
+        };
+        // Saved before files carried a version.
+        assert_eq!(decode("(40)", &schema()).unwrap(), expected("???"));
+        assert_eq!(
+            decode("(version: 0, data: (40))", &schema()).unwrap(),
+            expected("???")
+        );
+        assert_eq!(
+            decode("(version: 1, data: (best: 40, name: \"ANN\"))", &schema()).unwrap(),
+            expected("ANN")
+        );
+    }
+
+    #[test]
+    fn reads_back_what_it_writes() {
+        let record = Record {
+            difficulty: Difficulty::Easy,
+            scores: vec![("BOB".to_string(), 7)],
+        };
+        let contents = encode(&record, &schema()).unwrap();
+        assert!(contents.contains("version: 2"));
+        assert_eq!(decode(&contents, &schema()).unwrap(), record);
+    }
+
+    #[test]
+    fn refuses_files_it_cannot_read() {
+        let newer = decode::<Record>("(version: 3, data: ())", &schema());
+        assert!(matches!(newer, Err(PersistenceError::Newer(3))));
+        let unknown = decode::<Record>("(version: 1, data: ())", &Schema::new(2));
+        assert!(matches!(unknown, Err(PersistenceError::NoMigration(1))));
+        let corrupt = decode::<Record>("(version: 2, data: (difficulty: Hard", &schema());
+        assert!(matches!(corrupt, Err(PersistenceError::Corrupt(_))));
+    }
+}
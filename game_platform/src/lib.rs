@@ -0,0 +1,258 @@
+//! What a store platform such as Steam offers the games, behind one API that does nothing when
+//! the game runs without it: achievements, cloud saves, rich presence and the overlay.
+
+use bevy::prelude::*;
+use game_achievements::Achievements;
+use game_persistence::{decode, encode, PersistenceError, Schema};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeSet;
+
+#[cfg(feature = "steam")]
+mod steam;
+
+/// A platform the game runs on.
+pub trait Backend: Send + Sync + 'static {
+    /// The contents of a file in the player's cloud storage, if it is there.
+    fn cloud_read(&self, name: &str) -> Option<Vec<u8>>;
+    fn cloud_write(&self, name: &str, contents: &[u8]);
+    /// Whether the platform's overlay is open over the game.
+    fn overlay_active(&self) -> bool;
+    /// Shows friends what the player is doing, e.g. `Level 3`.
+    fn set_presence(&self, status: &str);
+    fn unlock(&self, achievement: &str);
+}
+
+/// Running without a platform: nothing is mirrored and nothing is in the cloud.
+pub struct Offline;
+
+impl Backend for Offline {
+    fn cloud_read(&self, _name: &str) -> Option<Vec<u8>> {
+        None
+    }
+
+    fn cloud_write(&self, _name: &str, _contents: &[u8]) {}
+
+    fn overlay_active(&self) -> bool {
+        false
+    }
+
+    fn set_presence(&self, _status: &str) {}
+
+    fn unlock(&self, _achievement: &str) {}
+}
+
+/// Whether the platform's overlay is open, for games to pause while it is.
+#[derive(Default)]
+pub struct Overlay {
+    pub active: bool,
+}
+
+/// The platform the game runs on.
+pub struct Platform(Box<dyn Backend>);
+
+/// `Offline`, for games to save through until the `PlatformPlugin` puts the real one in place.
+impl Default for Platform {
+    fn default() -> Self {
+        Self::offline()
+    }
+}
+
+impl Platform {
+    pub fn new(backend: impl Backend) -> Self {
+        Self(Box::new(backend))
+    }
+
+    pub fn offline() -> Self {
+        Self::new(Offline)
+    }
+
+    /// Like `game_persistence::load_with`, but prefers the copy in the cloud, which is the newest
+    /// when the player switched computers.
+    pub fn load<T: DeserializeOwned>(
+        &self,
+        game: &str,
+        file: &str,
+        schema: &Schema<T>,
+    ) -> Result<Option<T>, PersistenceError> {
+        match self.0.cloud_read(&cloud_name(game, file)) {
+            Some(contents) => Ok(Some(decode(&String::from_utf8_lossy(&contents), schema)?)),
+            None => game_persistence::load_with(game, file, schema),
+        }
+    }
+
+    /// Like `game_persistence::load_or_default_with`, but prefers a readable copy in the cloud.
+    pub fn load_or_default<T: DeserializeOwned + Default>(
+        &self,
+        game: &str,
+        file: &str,
+        schema: &Schema<T>,
+    ) -> T {
+        if let Some(contents) = self.0.cloud_read(&cloud_name(game, file)) {
+            match decode(&String::from_utf8_lossy(&contents), schema) {
+                Ok(value) => return value,
+                Err(error) => {
+                    eprintln!("could not load {}/{} from the cloud: {}", game, file, error)
+                }
+            }
+        }
+        game_persistence::load_or_default_with(game, file, schema)
+    }
+
+    /// Like `game_persistence::save_with`, and copies the file to the cloud.
+    pub fn save<T: Serialize>(
+        &self,
+        game: &str,
+        file: &str,
+        value: &T,
+        schema: &Schema<T>,
+    ) -> Result<(), PersistenceError> {
+        game_persistence::save_with(game, file, value, schema)?;
+        let contents = encode(value, schema)?;
+        self.0
+            .cloud_write(&cloud_name(game, file), contents.as_bytes());
+        Ok(())
+    }
+
+    pub fn set_presence(&self, status: &str) {
+        self.0.set_presence(status);
+    }
+
+    pub fn unlock(&self, achievement: &str) {
+        self.0.unlock(achievement);
+    }
+}
+
+/// Adds the `Platform`: Steam with the `steam` feature when the Steam client runs, otherwise
+/// `Offline`. Keeps the `Overlay` up to date and unlocks the achievements of `game_achievements`
+/// on the platform too.
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        let platform = platform(app);
+        app.insert_resource(platform)
+            .init_resource::<Overlay>()
+            .add_system(achievements_mirror)
+            .add_system(overlay_update);
+    }
+}
+
+/// Unlocks on the platform what the player has unlocked in the game, including what was
+/// unlocked before the game ran with it.
+fn achievements_mirror(
+    platform: Res<Platform>,
+    achievements: Option<Res<Achievements>>,
+    mut mirrored: Local<BTreeSet<String>>,
+) {
+    let achievements = match achievements {
+        Some(achievements) if achievements.is_changed() => achievements,
+        _ => return,
+    };
+    let unlocked = achievements
+        .record
+        .unlocked
+        .keys()
+        .chain(achievements.meta.unlocked.keys());
+    for id in unlocked {
+        if mirrored.insert(id.clone()) {
+            platform.unlock(id);
+        }
+    }
+}
+
+/// The name of a save in the cloud, which has no directories.
+fn cloud_name(game: &str, file: &str) -> String {
+    format!("{}_{}.ron", game, file)
+}
+
+fn overlay_update(platform: Res<Platform>, mut overlay: ResMut<Overlay>) {
+    let active = platform.0.overlay_active();
+    if overlay.active != active {
+        overlay.active = active;
+    }
+}
+
+#[cfg(feature = "steam")]
+fn platform(app: &mut App) -> Platform {
+    steam::start(app).unwrap_or_else(Platform::offline)
+}
+
+#[cfg(not(feature = "steam"))]
+fn platform(_app: &mut App) -> Platform {
+    Platform::offline()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    /// A cloud holding one save, of the version before the progress had a level name.
+    struct Cloud(Mutex<Vec<u8>>);
+
+    impl Backend for Cloud {
+        fn cloud_read(&self, name: &str) -> Option<Vec<u8>> {
+            (name == "platform_test_progress.ron").then(|| self.0.lock().unwrap().clone())
+        }
+
+        fn cloud_write(&self, _name: &str, contents: &[u8]) {
+            *self.0.lock().unwrap() = contents.to_vec();
+        }
+
+        fn overlay_active(&self) -> bool {
+            true
+        }
+
+        fn set_presence(&self, _status: &str) {}
+
+        fn unlock(&self, _achievement: &str) {}
+    }
+
+    #[derive(serde::Deserialize)]
+    struct ProgressV0(u32);
+
+    impl From<ProgressV0> for (u32, String) {
+        fn from(ProgressV0(level): ProgressV0) -> Self {
+            (level, "forest".to_string())
+        }
+    }
+
+    fn schema() -> Schema<(u32, String)> {
+        Schema::new(1).migrate::<ProgressV0>(0)
+    }
+
+    #[test]
+    fn prefers_saves_in_the_cloud() {
+        game_test::isolate_saves();
+        let platform = Platform::new(Cloud(Mutex::new(b"(3)".to_vec())));
+        let progress = platform
+            .load("platform_test", "progress", &schema())
+            .unwrap();
+        assert_eq!(progress, Some((3, "forest".to_string())));
+        assert!(matches!(
+            platform.load("platform_test", "progress", &Schema::<String>::default()),
+            Err(PersistenceError::Corrupt(_))
+        ));
+        // An unreadable copy in the cloud gives way to the one on disk, here none.
+        let name: String =
+            platform.load_or_default("platform_test", "progress", &Schema::default());
+        assert_eq!(name, "");
+        assert!(!Platform::default().0.overlay_active());
+    }
+
+    #[test]
+    fn saves_the_version_to_the_cloud() {
+        game_test::isolate_saves();
+        let platform = Platform::new(Cloud(Mutex::new(Vec::new())));
+        let progress = (4, "cave".to_string());
+        platform
+            .save("platform_test", "progress", &progress, &schema())
+            .unwrap();
+        let cloud = platform.0.cloud_read("platform_test_progress.ron").unwrap();
+        assert!(String::from_utf8(cloud).unwrap().contains("version: 1"));
+        let local = game_persistence::load_with("platform_test", "progress", &schema());
+        assert_eq!(local.unwrap(), Some(progress.clone()));
+        let loaded = platform.load("platform_test", "progress", &schema());
+        assert_eq!(loaded.unwrap(), Some(progress));
+    }
+}
@@ -0,0 +1,91 @@
+//! Steam through Steamworks. The game has to be started by Steam or have a `steam_appid.txt`
+//! next to it.
+
+use crate::{Backend, Platform};
+use bevy::prelude::*;
+use std::{
+    io::{Read, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use steamworks::{CallbackHandle, Client, GameOverlayActivated, SingleClient};
+
+/// The rich presence key Steam shows in the friends list.
+const PRESENCE_KEY: &str = "status";
+
+struct Steam {
+    client: Client,
+    overlay: Arc<AtomicBool>,
+    /// Keeps the overlay callback registered.
+    _overlay_callback: CallbackHandle,
+}
+
+impl Backend for Steam {
+    fn cloud_read(&self, name: &str) -> Option<Vec<u8>> {
+        let file = self.client.remote_storage().file(name);
+        if !file.exists() {
+            return None;
+        }
+        let mut contents = Vec::new();
+        match file.read().read_to_end(&mut contents) {
+            Ok(_) => Some(contents),
+            Err(error) => {
+                eprintln!("could not load {} from Steam Cloud: {}", name, error);
+                None
+            }
+        }
+    }
+
+    fn cloud_write(&self, name: &str, contents: &[u8]) {
+        let file = self.client.remote_storage().file(name);
+        if let Err(error) = file.write().write_all(contents) {
+            eprintln!("could not save {} to Steam Cloud: {}", name, error);
+        }
+    }
+
+    fn overlay_active(&self) -> bool {
+        self.overlay.load(Ordering::Relaxed)
+    }
+
+    fn set_presence(&self, status: &str) {
+        self.client
+            .friends()
+            .set_rich_presence(PRESENCE_KEY, Some(status));
+    }
+
+    fn unlock(&self, achievement: &str) {
+        let stats = self.client.user_stats();
+        if stats.achievement(achievement).set().is_err() || stats.store_stats().is_err() {
+            eprintln!("could not unlock {} on Steam", achievement);
+        }
+    }
+}
+
+/// Connects to the Steam client, if it runs, and has its callbacks run every frame.
+pub(crate) fn start(app: &mut App) -> Option<Platform> {
+    let (client, single) = match Client::init() {
+        Ok(steam) => steam,
+        Err(error) => {
+            eprintln!("playing without Steam: {}", error);
+            return None;
+        }
+    };
+    let overlay = Arc::new(AtomicBool::new(false));
+    let active = overlay.clone();
+    let overlay_callback = client.register_callback(move |event: GameOverlayActivated| {
+        active.store(event.active, Ordering::Relaxed);
+    });
+    app.insert_non_send_resource(single)
+        .add_system(callbacks_update);
+    Some(Platform::new(Steam {
+        client,
+        overlay,
+        _overlay_callback: overlay_callback,
+    }))
+}
+
+fn callbacks_update(single: NonSend<SingleClient>) {
+    single.run_callbacks();
+}
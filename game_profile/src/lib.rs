@@ -0,0 +1,155 @@
+//! The player's profile across games: how often and how long each was played, when it was
+//! played last and which are favorites.
+
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The `game_persistence` directory and file the profile is kept in.
+const DIRECTORY: &str = "profile";
+const FILE: &str = "profile";
+
+/// What the profile knows about one game.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Played {
+    pub favorite: bool,
+    /// When it was last started, in seconds since the Unix epoch.
+    pub last_played: u64,
+    pub launches: u32,
+    /// Seconds played in all.
+    pub playtime: u64,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Profile {
+    /// By crate name.
+    pub games: BTreeMap<String, Played>,
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        game_persistence::load_or_default(DIRECTORY, FILE)
+    }
+
+    pub fn get(&self, game: &str) -> Option<&Played> {
+        self.games.get(game)
+    }
+
+    pub fn is_favorite(&self, game: &str) -> bool {
+        self.get(game).is_some_and(|played| played.favorite)
+    }
+
+    /// The game started last, to continue with.
+    pub fn last_played(&self) -> Option<&str> {
+        self.games
+            .iter()
+            .filter(|(_, played)| played.launches > 0)
+            .max_by_key(|(_, played)| played.last_played)
+            .map(|(game, _)| game.as_str())
+    }
+
+    /// Counts a start of the game.
+    pub fn launched(&mut self, game: &str) {
+        let played = self.games.entry(game.to_string()).or_default();
+        played.launches += 1;
+        played.last_played = now();
+    }
+
+    /// Up to `count` games that were played longest, longest first.
+    pub fn most_played(&self, count: usize) -> Vec<(&str, &Played)> {
+        let mut games: Vec<(&str, &Played)> = self
+            .games
+            .iter()
+            .filter(|(_, played)| played.playtime > 0)
+            .map(|(game, played)| (game.as_str(), played))
+            .collect();
+        games.sort_by_key(|(_, played)| Reverse(played.playtime));
+        games.truncate(count);
+        games
+    }
+
+    /// Adds to the time the game was played.
+    pub fn played(&mut self, game: &str, seconds: u64) {
+        self.games.entry(game.to_string()).or_default().playtime += seconds;
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save(DIRECTORY, FILE, self) {
+            eprintln!("could not save the profile: {}", error);
+        }
+    }
+
+    /// Makes the game a favorite or not any more; returns whether it is one now.
+    pub fn toggle_favorite(&mut self, game: &str) -> bool {
+        let played = self.games.entry(game.to_string()).or_default();
+        played.favorite = !played.favorite;
+        played.favorite
+    }
+}
+
+/// A playtime in hours and minutes, e.g. `3h 05m`, or minutes alone under an hour.
+pub fn format_playtime(seconds: u64) -> String {
+    let minutes = seconds / 60;
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_games() {
+        let mut profile = Profile::default();
+        assert_eq!(profile.last_played(), None);
+        profile.launched("snake");
+        profile.played("snake", 600);
+        profile.launched("tetris");
+        profile.played("tetris", 4000);
+        profile.games.get_mut("tetris").unwrap().last_played -= 10;
+        assert!(profile.toggle_favorite("chess"));
+        assert_eq!(profile.last_played(), Some("snake"));
+        let most: Vec<&str> = profile
+            .most_played(5)
+            .iter()
+            .map(|(game, _)| *game)
+            .collect();
+        assert_eq!(most, ["tetris", "snake"]);
+        assert!(profile.is_favorite("chess") && !profile.is_favorite("snake"));
+        assert_eq!(profile.get("snake").unwrap().launches, 1);
+    }
+
+    #[test]
+    fn reads_every_saved_version() {
+        use game_persistence::{decode, encode, Schema};
+        // Saved before files carried a version.
+        let unversioned = "(games: {\"snake\": (favorite: true, last_played: 1700000000, launches: 3, playtime: 600)})";
+        let profile: Profile = decode(unversioned, &Schema::default()).unwrap();
+        assert!(profile.is_favorite("snake"));
+        assert_eq!(profile.get("snake").unwrap().playtime, 600);
+        let saved = encode(&profile, &Schema::default()).unwrap();
+        assert_eq!(
+            decode::<Profile>(&saved, &Schema::default()).unwrap(),
+            profile
+        );
+    }
+
+    #[test]
+    fn formats_playtimes() {
+        assert_eq!(format_playtime(59), "0m");
+        assert_eq!(format_playtime(25 * 60), "25m");
+        assert_eq!(format_playtime(3 * 3600 + 5 * 60 + 30), "3h 05m");
+    }
+}
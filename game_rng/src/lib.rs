@@ -0,0 +1,183 @@
+//! Seeded, serializable random numbers, split into a stream deciding the game and one for its
+//! looks, so the same seed plays out the same way every time.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::{env, ops::Range};
+
+pub mod seed;
+
+pub use seed::{Seed, SeedEntry};
+
+/// Mixed into the seed so the streams of one seed differ.
+const COSMETIC: u64 = 0xC05E_711C;
+const GAMEPLAY: u64 = 0x6A3E_B1A7;
+/// The command line flag giving the seed code of a run, e.g. `--seed 3KQ-7ZD1`.
+const SEED_FLAG: &str = "--seed";
+/// The environment variable with a seed code to start games with.
+const SEED_VARIABLE: &str = "RUST_GAMES_SEED";
+
+/// The game's random numbers. `gameplay` decides what happens; `cosmetic` is for looks only, so
+/// effects never change what a seed plays out.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rng {
+    pub cosmetic: Stream,
+    pub gameplay: Stream,
+    seed: Seed,
+}
+
+impl Rng {
+    pub fn new(seed: Seed) -> Self {
+        Self {
+            cosmetic: Stream::new(seed.0 as u64 ^ COSMETIC),
+            gameplay: Stream::new(seed.0 as u64 ^ GAMEPLAY),
+            seed,
+        }
+    }
+
+    /// Starts both streams over from `seed`, e.g. for a new round with a seed the player entered.
+    pub fn reseed(&mut self, seed: Seed) {
+        *self = Self::new(seed);
+    }
+
+    pub fn seed(&self) -> Seed {
+        self.seed
+    }
+}
+
+/// Adds the `Rng`, seeded with the seed given, else the code after `--seed` on the command line,
+/// else the code in `RUST_GAMES_SEED`, else a random one.
+#[derive(Default)]
+pub struct RngPlugin {
+    seed: Option<Seed>,
+}
+
+impl RngPlugin {
+    pub fn seeded(seed: Seed) -> Self {
+        Self { seed: Some(seed) }
+    }
+}
+
+impl Plugin for RngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = self
+            .seed
+            .or_else(|| flag_seed(env::args()))
+            .or_else(|| {
+                env::var(SEED_VARIABLE)
+                    .ok()
+                    .and_then(|code| Seed::parse(&code))
+            })
+            .unwrap_or_else(Seed::random);
+        app.insert_resource(Rng::new(seed));
+    }
+}
+
+/// The seed whose code follows `--seed` in `args`, if it can be read.
+fn flag_seed(args: impl Iterator<Item = String>) -> Option<Seed> {
+    let code = args.skip_while(|arg| arg != SEED_FLAG).nth(1)?;
+    let seed = Seed::parse(&code);
+    if seed.is_none() {
+        eprintln!("{} is no seed code", code);
+    }
+    seed
+}
+
+/// A sequence of random numbers (SplitMix64), the same for the same start on every platform and
+/// version.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stream {
+    state: u64,
+}
+
+impl Stream {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// A number below `bound`, or 0 if it is 0.
+    pub fn below(&mut self, bound: u32) -> u32 {
+        (((self.next_u64() >> 32) * bound as u64) >> 32) as u32
+    }
+
+    /// Whether something with the `probability` happens.
+    pub fn chance(&mut self, probability: f32) -> bool {
+        self.unit() < probability
+    }
+
+    /// A stream of its own, e.g. for a level or a player, so drawing from it leaves this one be.
+    pub fn fork(&mut self) -> Stream {
+        Stream::new(self.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> Option<&'a T> {
+        items.get(self.below(items.len() as u32) as usize)
+    }
+
+    /// A number in `range`, or its start if it is empty.
+    pub fn range(&mut self, range: Range<i32>) -> i32 {
+        let width = (range.end as i64 - range.start as i64).max(0) as u32;
+        (range.start as i64 + self.below(width) as i64) as i32
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for index in (1..items.len()).rev() {
+            items.swap(index, self.below(index as u32 + 1) as usize);
+        }
+    }
+
+    /// A number from 0 up to, not including, 1.
+    pub fn unit(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeds_replay_the_same_numbers() {
+        let mut first = Rng::new(Seed(42));
+        let mut second = Rng::new(Seed(42));
+        first.cosmetic.unit();
+        let rolls: Vec<i32> = (0..20).map(|_| first.gameplay.range(1..7)).collect();
+        let again: Vec<i32> = (0..20).map(|_| second.gameplay.range(1..7)).collect();
+        assert_eq!(rolls, again);
+        assert!(rolls.iter().all(|roll| (1..7).contains(roll)));
+        assert_ne!(Rng::new(Seed(43)).gameplay, second.gameplay);
+        assert_eq!(Stream::new(0).next_u64(), 0xE220_A839_7B1D_CDAF);
+    }
+
+    #[test]
+    fn reads_the_seed_flag() {
+        let args = |line: &str| line.split(' ').map(str::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            flag_seed(args("snake --seed 000-0042").into_iter()),
+            Seed::parse("42")
+        );
+        assert_eq!(flag_seed(args("snake --arena 20x20").into_iter()), None);
+        assert_eq!(flag_seed(args("snake --seed").into_iter()), None);
+    }
+
+    #[test]
+    fn helpers_stay_in_bounds() {
+        let mut stream = Stream::new(7);
+        assert_eq!(stream.below(0), 0);
+        assert_eq!(stream.range(5..5), 5);
+        assert_eq!(stream.pick::<u8>(&[]), None);
+        let mut items: Vec<u32> = (0..10).collect();
+        stream.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, (0..10).collect::<Vec<_>>());
+        assert!((0..100).all(|_| (0.0..1.0).contains(&stream.unit())));
+    }
+}
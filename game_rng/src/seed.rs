@@ -0,0 +1,140 @@
+//! Seeds as short codes players can read out and type back, with the keyboard or a gamepad.
+
+use bevy::{input::keyboard::KeyCode, prelude::*, window::ReceivedCharacter};
+use game_hud::{MenuInput, Navigate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Crockford's base 32: no I, L, O or U, which are easily mistaken.
+const DIGITS: &str = "0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+/// Digits in a code; enough for every `u32`.
+pub const LENGTH: usize = 7;
+
+/// What a game's random numbers start from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Seed(pub u32);
+
+impl Seed {
+    /// Reads a code, ignoring case, dashes and spaces, and reading O as 0 and I or L as 1.
+    pub fn parse(code: &str) -> Option<Self> {
+        let mut value: u64 = 0;
+        let mut digits = 0;
+        for character in code.chars().filter(|c| !c.is_whitespace() && *c != '-') {
+            value = value * 32 + digit(character)? as u64;
+            digits += 1;
+        }
+        if digits == 0 || digits > LENGTH || value > u32::MAX as u64 {
+            return None;
+        }
+        Some(Self(value as u32))
+    }
+
+    pub fn random() -> Self {
+        Self(rand::random())
+    }
+}
+
+/// The code, e.g. `3KQ-7ZD1`.
+impl fmt::Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digits: Vec<char> = DIGITS.chars().collect();
+        let code: String = (0..LENGTH)
+            .rev()
+            .map(|place| digits[(self.0 as u64 >> (place * 5)) as usize % 32])
+            .collect();
+        write!(f, "{}-{}", &code[..3], &code[3..])
+    }
+}
+
+/// A seed code being typed; `done` once it is confirmed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SeedEntry {
+    pub code: String,
+    pub done: bool,
+}
+
+impl SeedEntry {
+    /// Changes the last digit to the one `step` digits along, for entry without a keyboard.
+    pub fn cycle(&mut self, step: isize) {
+        let digits: Vec<char> = DIGITS.chars().collect();
+        let current = self.code.pop().and_then(digit).unwrap_or(0);
+        let next = (current as isize + step).rem_euclid(digits.len() as isize);
+        self.code.push(digits[next as usize]);
+    }
+
+    /// Adds a typed character if it is a digit of a code and the code has room for it.
+    pub fn push(&mut self, character: char) {
+        if digit(character).is_some() && self.code.chars().count() < LENGTH {
+            self.code.push(character.to_ascii_uppercase());
+        }
+    }
+
+    /// The seed typed so far, if it is a whole one.
+    pub fn seed(&self) -> Option<Seed> {
+        Seed::parse(&self.code)
+    }
+
+    /// Types this frame's characters into the entry, or with a gamepad: Up and Down change the
+    /// last digit, Right adds one and Left takes it away. Return or A confirm it.
+    pub fn update(
+        &mut self,
+        characters: &mut EventReader<ReceivedCharacter>,
+        keyboard: &Input<KeyCode>,
+        menu: &MenuInput,
+    ) {
+        for event in characters.iter() {
+            self.push(event.char);
+        }
+        if keyboard.just_pressed(KeyCode::Back) || menu.just_pressed(Navigate::Left) {
+            self.code.pop();
+        }
+        if menu.just_pressed(Navigate::Right) {
+            self.push('0');
+        }
+        if menu.vertical() != 0 {
+            self.cycle(-menu.vertical());
+        }
+        if keyboard.just_pressed(KeyCode::NumpadEnter) || menu.just_pressed(Navigate::Confirm) {
+            self.done = self.seed().is_some();
+        }
+    }
+}
+
+/// The value of a digit of a code.
+fn digit(character: char) -> Option<usize> {
+    let character = match character.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        character => character,
+    };
+    DIGITS.chars().position(|other| other == character)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_read_back_forgivingly() {
+        for seed in [Seed(0), Seed(123_456_789), Seed(u32::MAX)] {
+            let code = seed.to_string();
+            assert_eq!(code.len(), LENGTH + 1);
+            assert_eq!(Seed::parse(&code), Some(seed));
+            assert_eq!(Seed::parse(&code.to_lowercase()), Some(seed));
+        }
+        assert_eq!(Seed::parse("o0i-l 10"), Seed::parse("000-1110"));
+        assert_eq!(Seed::parse("ZZZ-ZZZZ"), None);
+        assert_eq!(Seed::parse("U"), None);
+        assert_eq!(Seed::parse(""), None);
+    }
+
+    #[test]
+    fn entry_takes_code_digits_only() {
+        let mut entry = SeedEntry::default();
+        "3kq-7zd1x".chars().for_each(|c| entry.push(c));
+        assert_eq!(entry.code, "3KQ7ZD1");
+        entry.cycle(1);
+        assert_eq!(entry.code, "3KQ7ZD2");
+        assert_eq!(entry.seed(), Seed::parse("3KQ-7ZD2"));
+    }
+}
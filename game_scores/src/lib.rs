@@ -0,0 +1,376 @@
+use game_persistence::Schema;
+use game_platform::Platform;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub mod name;
+#[cfg(feature = "online")]
+pub mod online;
+pub mod screen;
+
+pub use name::NameEntry;
+pub use screen::{Leaderboard, ScoresPlugin};
+
+const FILE: &str = "leaderboards";
+/// The name scores are entered under until a name has been entered.
+pub const PLAYER_NAME: &str = "PLAYER";
+/// Entries a table keeps.
+pub const TABLE_SIZE: usize = 10;
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Entry {
+    /// When it was played, in seconds since the Unix epoch.
+    pub date: u64,
+    pub name: String,
+    pub score: u32,
+    /// The code of the seed it was played with, if the game has one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<String>,
+}
+
+/// Which scores are better: the highest, or the lowest such as times and moves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Order {
+    Highest,
+    Lowest,
+}
+
+impl Order {
+    /// Whether `score` beats `other`; equal scores do not.
+    pub fn beats(self, score: u32, other: u32) -> bool {
+        match self {
+            Self::Highest => score > other,
+            Self::Lowest => score < other,
+        }
+    }
+}
+
+/// What is saved: the tables and everyone's personal bests, by mode.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+struct Records {
+    bests: BTreeMap<String, BTreeMap<String, u32>>,
+    /// The name entered last, offered again next time.
+    last_name: String,
+    tables: BTreeMap<String, Vec<Entry>>,
+}
+
+impl Records {
+    /// Version 1 added the seed to the entries.
+    fn schema() -> Schema<Self> {
+        Schema::new(1).migrate::<RecordsV0>(0)
+    }
+}
+
+/// The records as saved at version 0, before entries had a seed.
+#[derive(Deserialize)]
+struct RecordsV0 {
+    bests: BTreeMap<String, BTreeMap<String, u32>>,
+    last_name: String,
+    tables: BTreeMap<String, Vec<EntryV0>>,
+}
+
+#[derive(Deserialize)]
+struct EntryV0 {
+    date: u64,
+    name: String,
+    score: u32,
+}
+
+impl From<RecordsV0> for Records {
+    fn from(records: RecordsV0) -> Self {
+        let entry = |entry: EntryV0| Entry {
+            date: entry.date,
+            name: entry.name,
+            score: entry.score,
+            seed: None,
+        };
+        Self {
+            bests: records.bests,
+            last_name: records.last_name,
+            tables: records
+                .tables
+                .into_iter()
+                .map(|(mode, table)| (mode, table.into_iter().map(entry).collect()))
+                .collect(),
+        }
+    }
+}
+
+/// The high score tables of a game, one per mode, and the personal best of every name entered.
+/// Games with a single mode use `""` as its name.
+pub struct Scores {
+    #[cfg(feature = "online")]
+    fetches: online::Fetches,
+    game: &'static str,
+    /// The leaderboard server's tables, by mode, as last fetched.
+    global: BTreeMap<String, Vec<Entry>>,
+    order: Order,
+    records: Records,
+    /// The seed of the games entered from now on.
+    seed: Option<String>,
+}
+
+impl Scores {
+    /// Empty tables of `game`, until `load` reads the saved ones; `ScoresPlugin` does both.
+    pub fn new(game: &'static str, order: Order) -> Self {
+        Self {
+            #[cfg(feature = "online")]
+            fetches: online::Fetches::default(),
+            game,
+            global: BTreeMap::new(),
+            order,
+            records: Records::default(),
+            seed: None,
+        }
+    }
+
+    /// Enters a score played now; returns its place in the table if it made it. The personal
+    /// best of `name` is updated either way.
+    pub fn add(&mut self, mode: &str, name: &str, score: u32) -> Option<usize> {
+        let entry = Entry {
+            date: now(),
+            name: name.trim().to_string(),
+            score,
+            seed: self.seed.clone(),
+        };
+        self.records.last_name = entry.name.clone();
+        let bests = self.records.bests.entry(mode.to_string()).or_default();
+        let best = bests.entry(entry.name.clone()).or_insert(score);
+        if self.order.beats(score, *best) {
+            *best = score;
+        }
+        #[cfg(feature = "online")]
+        self.fetches.start(self.game, mode, Some(&entry));
+
+        let place = self.place(mode, score)?;
+        let table = self.records.tables.entry(mode.to_string()).or_default();
+        table.insert(place, entry);
+        table.truncate(TABLE_SIZE);
+        Some(place)
+    }
+
+    /// Enters a score for a game that asks for no name, under the name entered last, or
+    /// `PLAYER_NAME` before any was.
+    pub fn add_unnamed(&mut self, mode: &str, score: u32) -> Option<usize> {
+        let name = match self.last_name() {
+            "" => PLAYER_NAME.to_string(),
+            name => name.to_string(),
+        };
+        self.add(mode, &name, score)
+    }
+
+    /// The best entry of the mode.
+    pub fn best(&self, mode: &str) -> Option<&Entry> {
+        self.table(mode).first()
+    }
+
+    /// Fetches the leaderboard server's table of the mode in the background, as adding an entry
+    /// does, for `global` to have it once it is there.
+    #[cfg(feature = "online")]
+    pub fn fetch_global(&self, mode: &str) {
+        self.fetches.start(self.game, mode, None);
+    }
+
+    /// The leaderboard server's table of the mode, once it has been fetched.
+    pub fn global(&self, mode: &str) -> Option<&[Entry]> {
+        self.global.get(mode).map(Vec::as_slice)
+    }
+
+    /// The name entered last, for the name entry to start with.
+    pub fn last_name(&self) -> &str {
+        &self.records.last_name
+    }
+
+    /// Reads the saved tables, or empty ones if there are none, preferring the platform's copy
+    /// in the cloud.
+    pub fn load(&mut self, platform: &Platform) {
+        self.records = platform.load_or_default(self.game, FILE, &Records::schema());
+    }
+
+    /// The modes that have a table, in order.
+    pub fn modes(&self) -> impl Iterator<Item = &str> {
+        self.records.tables.keys().map(String::as_str)
+    }
+
+    /// The best score `name` ever entered in the mode, whether it is in the table or not.
+    pub fn personal_best(&self, mode: &str, name: &str) -> Option<u32> {
+        self.records.bests.get(mode)?.get(name.trim()).copied()
+    }
+
+    /// The place `score` would take in the table, after the equal scores already there; `None`
+    /// if it would not make it.
+    pub fn place(&self, mode: &str, score: u32) -> Option<usize> {
+        let table = self.table(mode);
+        let place = table
+            .iter()
+            .take_while(|entry| !self.order.beats(score, entry.score))
+            .count();
+        if place < TABLE_SIZE {
+            Some(place)
+        } else {
+            None
+        }
+    }
+
+    /// The code of the seed the next entries are played with, kept and sent to the leaderboard
+    /// server along with them.
+    pub fn set_seed(&mut self, seed: &str) {
+        self.seed = Some(seed.to_string());
+    }
+
+    pub fn save(&self, platform: &Platform) {
+        if let Err(error) = platform.save(self.game, FILE, &self.records, &Records::schema()) {
+            eprintln!("could not save high scores: {}", error);
+        }
+    }
+
+    pub fn table(&self, mode: &str) -> &[Entry] {
+        self.records.tables.get(mode).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// The saved table of a mode of any game, for showing it outside the game, as the launcher
+/// does.
+pub fn saved_table(game: &str, mode: &str) -> Vec<Entry> {
+    let mut records = game_persistence::load_or_default_with(game, FILE, &Records::schema());
+    records.tables.remove(mode).unwrap_or_default()
+}
+
+/// A date as year, month and day, e.g. `2022-01-31`.
+pub fn format_date(seconds: u64) -> String {
+    // Days to a civil date, after Howard Hinnant's `civil_from_days`.
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs())
+}
+
+/// Browsers have no clock for `SystemTime`, so the date comes from JavaScript.
+#[cfg(target_arch = "wasm32")]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(order: Order) -> Scores {
+        Scores {
+            #[cfg(feature = "online")]
+            fetches: online::Fetches::default(),
+            game: "game_scores_test",
+            global: BTreeMap::new(),
+            order,
+            records: Records::default(),
+            seed: None,
+        }
+    }
+
+    #[test]
+    fn reads_every_saved_version() {
+        use game_persistence::{decode, encode};
+        let expected = |seed: Option<&str>| Records {
+            bests: BTreeMap::from([(String::new(), BTreeMap::from([("ANN".to_string(), 300)]))]),
+            last_name: "ANN".to_string(),
+            tables: BTreeMap::from([(
+                String::new(),
+                vec![Entry {
+                    date: 1700000000,
+                    name: "ANN".to_string(),
+                    score: 300,
+                    seed: seed.map(str::to_string),
+                }],
+            )]),
+        };
+        let old = "(bests: {\"\": {\"ANN\": 300}}, last_name: \"ANN\", tables: {\"\": [(date: 1700000000, name: \"ANN\", score: 300)]})";
+        // Saved before files carried a version.
+        assert_eq!(decode(old, &Records::schema()).unwrap(), expected(None));
+        let versioned = format!("(version: 0, data: {})", old);
+        assert_eq!(
+            decode(&versioned, &Records::schema()).unwrap(),
+            expected(None)
+        );
+        let records = expected(Some("3KQ-7ZD1"));
+        let saved = encode(&records, &Records::schema()).unwrap();
+        assert!(saved.contains("version: 1"));
+        assert_eq!(decode(&saved, &Records::schema()).unwrap(), records);
+    }
+
+    #[test]
+    fn unnamed_scores_go_under_the_last_name() {
+        let mut scores = scores(Order::Highest);
+        assert_eq!(scores.add_unnamed("", 300), Some(0));
+        assert_eq!(scores.table("")[0].name, PLAYER_NAME);
+        scores.add("", "ANN", 100);
+        assert_eq!(scores.add_unnamed("", 200), Some(1));
+        assert_eq!(scores.table("")[1].name, "ANN");
+    }
+
+    #[test]
+    fn tables_keep_the_best_in_order() {
+        let mut scores = scores(Order::Highest);
+        assert_eq!(scores.add("", "ANN", 300), Some(0));
+        assert_eq!(scores.add("", "BOB", 500), Some(0));
+        // Ties go after the scores that were there first.
+        assert_eq!(scores.add("", "CY", 300), Some(2));
+        for score in 0..10 {
+            scores.add("", "DEE", 1000 + score);
+        }
+        assert_eq!(scores.table("").len(), TABLE_SIZE);
+        assert_eq!(scores.add("", "ANN", 400), None);
+        assert_eq!(scores.best("").unwrap().score, 1009);
+        // Off the table, but still a personal best.
+        assert_eq!(scores.personal_best("", "ANN"), Some(400));
+        assert_eq!(scores.last_name(), "ANN");
+    }
+
+    #[test]
+    fn modes_and_orders_are_separate() {
+        let mut scores = scores(Order::Lowest);
+        scores.add("easy", "ANN", 90);
+        scores.add("easy", "ANN", 60);
+        scores.add("hard", "ANN", 200);
+        assert_eq!(scores.best("easy").unwrap().score, 60);
+        assert_eq!(scores.personal_best("easy", "ANN"), Some(60));
+        assert_eq!(scores.personal_best("hard", " ANN "), Some(200));
+        assert_eq!(scores.modes().collect::<Vec<_>>(), ["easy", "hard"]);
+        assert_eq!(scores.table("medium"), []);
+    }
+
+    #[test]
+    fn entries_keep_the_seed() {
+        let mut scores = scores(Order::Highest);
+        scores.add("", "ANN", 300);
+        scores.set_seed("3KQ-7ZD1");
+        scores.add("", "BOB", 200);
+        assert_eq!(scores.table("")[0].seed, None);
+        assert_eq!(scores.table("")[1].seed.as_deref(), Some("3KQ-7ZD1"));
+    }
+
+    #[test]
+    fn formats_dates() {
+        assert_eq!(format_date(0), "1970-01-01");
+        assert_eq!(format_date(951782400), "2000-02-29");
+        assert_eq!(format_date(1643587200 + 86399), "2022-01-31");
+    }
+}
@@ -0,0 +1,110 @@
+//! Typing a name for the table, with the keyboard or by picking letters with a gamepad.
+
+use bevy::{input::keyboard::KeyCode, prelude::*, window::ReceivedCharacter};
+use game_hud::{MenuInput, Navigate};
+
+/// Letters a gamepad cycles through, in order.
+const LETTERS: &str = "ABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789 ";
+pub const MAX_LENGTH: usize = 12;
+
+/// A name being typed; `done` once it is confirmed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NameEntry {
+    pub done: bool,
+    pub name: String,
+}
+
+impl NameEntry {
+    /// An entry starting with the name entered last time.
+    pub fn new(last_name: &str) -> Self {
+        Self {
+            done: false,
+            name: last_name.chars().take(MAX_LENGTH).collect(),
+        }
+    }
+
+    /// Changes the last letter to the one `step` letters along, for entry without a keyboard.
+    pub fn cycle(&mut self, step: isize) {
+        let letters: Vec<char> = LETTERS.chars().collect();
+        let current = match self.name.pop() {
+            Some(letter) => letters
+                .iter()
+                .position(|other| *other == letter.to_ascii_uppercase())
+                .unwrap_or(0),
+            None => 0,
+        };
+        let next = (current as isize + step).rem_euclid(letters.len() as isize);
+        self.name.push(letters[next as usize]);
+    }
+
+    /// The name to enter: what was typed, or `fallback` if that is blank.
+    pub fn finish<'a>(&'a self, fallback: &'a str) -> &'a str {
+        match self.name.trim() {
+            "" => fallback,
+            name => name,
+        }
+    }
+
+    /// Adds a typed character if it fits and is a letter, a digit, a space or one of `-_.`.
+    pub fn push(&mut self, character: char) {
+        if (character.is_alphanumeric() || " -_.".contains(character))
+            && self.name.chars().count() < MAX_LENGTH
+        {
+            self.name.push(character);
+        }
+    }
+
+    /// Types this frame's characters into the entry, or with a gamepad: Up and Down change the
+    /// last letter, Right adds one and Left takes it away. Return or A confirm it.
+    pub fn update(
+        &mut self,
+        characters: &mut EventReader<ReceivedCharacter>,
+        keyboard: &Input<KeyCode>,
+        menu: &MenuInput,
+    ) {
+        for event in characters.iter() {
+            self.push(event.char);
+        }
+        if keyboard.just_pressed(KeyCode::Back) || menu.just_pressed(Navigate::Left) {
+            self.name.pop();
+        }
+        if menu.just_pressed(Navigate::Right) {
+            self.push('A');
+        }
+        if menu.vertical() != 0 {
+            self.cycle(-menu.vertical());
+        }
+        if keyboard.just_pressed(KeyCode::NumpadEnter) || menu.just_pressed(Navigate::Confirm) {
+            self.done = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn types_names() {
+        let mut entry = NameEntry::new("A VERY LONG NAME INDEED");
+        assert_eq!(entry.name, "A VERY LONG ");
+        entry.name.clear();
+        assert_eq!(entry.finish("PLAYER"), "PLAYER");
+        for character in "Zoë!\u{8}_1".chars() {
+            entry.push(character);
+        }
+        assert_eq!(entry.finish("PLAYER"), "Zoë_1");
+    }
+
+    #[test]
+    fn cycles_letters() {
+        let mut entry = NameEntry::default();
+        entry.cycle(1);
+        assert_eq!(entry.name, "B");
+        entry.cycle(-2);
+        assert_eq!(entry.name, " ");
+        entry.name.push('z');
+        entry.cycle(1);
+        assert_eq!(entry.name, " 0");
+    }
+}
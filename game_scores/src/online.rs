@@ -0,0 +1,286 @@
+//! Sends new entries to the leaderboard server named by `RUST_GAMES_LEADERBOARD`, as a JSON
+//! `POST` over plain HTTP, and fetches its tables with a `GET`. Nothing is sent while the
+//! variable is unset.
+
+use crate::Entry;
+use serde::Serialize;
+use std::{
+    env, fmt,
+    io::{self, Read, Write},
+    net::TcpStream,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// How long to wait for the server before giving up.
+const TIMEOUT: Duration = Duration::from_secs(10);
+pub const URL_VARIABLE: &str = "RUST_GAMES_LEADERBOARD";
+
+/// What went wrong talking to the server.
+#[derive(Debug)]
+pub enum SubmitError {
+    /// The server's table is not a JSON list of entries.
+    Body(serde_json::Error),
+    Io(io::Error),
+    /// The server answered with something other than a 2xx status.
+    Status(String),
+    /// Not an `http://host[:port][/path]` address.
+    Url(String),
+}
+
+impl fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Body(error) => write!(f, "the server sent {}", error),
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Status(status) => write!(f, "the server answered {}", status),
+            Self::Url(url) => write!(f, "\"{}\" is not an http:// address", url),
+        }
+    }
+}
+
+impl From<io::Error> for SubmitError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// The server's tables on their way from background threads, by mode.
+#[derive(Default)]
+pub struct Fetches(Mutex<Vec<(String, Receiver<Vec<Entry>>)>>);
+
+impl Fetches {
+    /// The tables that have come in since the last call.
+    pub fn arrived(&self) -> Vec<(String, Vec<Entry>)> {
+        let mut arrived = Vec::new();
+        self.0
+            .lock()
+            .unwrap()
+            .retain(|(mode, receiver)| match receiver.try_recv() {
+                Ok(table) => {
+                    arrived.push((mode.clone(), table));
+                    false
+                }
+                Err(TryRecvError::Empty) => true,
+                Err(TryRecvError::Disconnected) => false,
+            });
+        arrived
+    }
+
+    /// Fetches the server's table of the mode on a thread of its own, after submitting `entry`
+    /// if there is one, so the game does not wait for the network; failures are only logged.
+    /// Nothing happens unless a server is configured.
+    pub fn start(&self, game: &str, mode: &str, entry: Option<&Entry>) {
+        let url = match env::var(URL_VARIABLE) {
+            Ok(url) if !url.is_empty() => url,
+            _ => return,
+        };
+        let body = entry.map(|entry| body(game, mode, entry));
+        let table_url = format!("{}?game={}&mode={}", url, encode(game), encode(mode));
+        let (sender, receiver) = mpsc::channel();
+        self.0.lock().unwrap().push((mode.to_string(), receiver));
+        thread::spawn(move || {
+            if let Some(body) = body {
+                if let Err(error) = submit(&url, &body) {
+                    eprintln!("could not submit the score: {}", error);
+                }
+            }
+            match fetch(&table_url) {
+                Ok(table) => {
+                    sender.send(table).ok();
+                }
+                Err(error) => eprintln!("could not fetch the global scores: {}", error),
+            }
+        });
+    }
+}
+
+#[derive(Serialize)]
+struct Submission<'a> {
+    date: u64,
+    game: &'a str,
+    mode: &'a str,
+    name: &'a str,
+    score: u32,
+    /// The seed the game was played with, for others to play it too.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    seed: Option<&'a str>,
+}
+
+/// Posts a JSON body to the URL and checks that it was accepted.
+pub fn submit(url: &str, body: &str) -> Result<(), SubmitError> {
+    exchange(url, |host, path| {
+        format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            host,
+            body.len(),
+            body
+        )
+    })?;
+    Ok(())
+}
+
+/// Gets the table at the URL, a JSON list of entries, best first. Asking over HTTP/1.0 keeps
+/// the server from sending it in chunks.
+pub fn fetch(url: &str) -> Result<Vec<Entry>, SubmitError> {
+    let table = exchange(url, |host, path| {
+        format!("GET {} HTTP/1.0\r\nHost: {}\r\n\r\n", path, host)
+    })?;
+    serde_json::from_str(&table).map_err(SubmitError::Body)
+}
+
+fn body(game: &str, mode: &str, entry: &Entry) -> String {
+    serde_json::to_string(&Submission {
+        date: entry.date,
+        game,
+        mode,
+        name: &entry.name,
+        score: entry.score,
+        seed: entry.seed.as_deref(),
+    })
+    .expect("submissions serialize")
+}
+
+/// Escapes everything but letters, digits and `-._~` in a query's value.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Sends the request written for the URL's host and path, and returns the body of a 2xx answer.
+fn exchange(url: &str, request: impl FnOnce(&str, &str) -> String) -> Result<String, SubmitError> {
+    let (host, port, path) = parse_url(url).ok_or_else(|| SubmitError::Url(url.to_string()))?;
+    let mut stream = TcpStream::connect((host, port))?;
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+    stream.write_all(request(host, path).as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let status = response.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some(code) if code.starts_with('2') => Ok(response
+            .split_once("\r\n\r\n")
+            .map_or("", |(_, body)| body)
+            .to_string()),
+        _ => Err(SubmitError::Status(status.to_string())),
+    }
+}
+
+/// The host, port and path of an `http://` URL.
+fn parse_url(url: &str) -> Option<(&str, u16, &str)> {
+    let rest = url.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(slash) => rest.split_at(slash),
+        None => (rest, "/"),
+    };
+    let (host, port) = match authority.split_once(':') {
+        Some((host, port)) => (host, port.parse().ok()?),
+        None => (authority, 80),
+    };
+    if host.is_empty() {
+        None
+    } else {
+        Some((host, port, path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    #[test]
+    fn parses_urls() {
+        assert_eq!(
+            parse_url("http://localhost:8080/scores"),
+            Some(("localhost", 8080, "/scores"))
+        );
+        assert_eq!(
+            parse_url("http://example.com"),
+            Some(("example.com", 80, "/"))
+        );
+        assert_eq!(parse_url("https://example.com/"), None);
+        assert_eq!(parse_url("http://:80/"), None);
+    }
+
+    #[test]
+    fn posts_entries() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/scores", listener.local_addr().unwrap());
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !String::from_utf8_lossy(&request).ends_with('}') {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream.write_all(b"HTTP/1.1 201 Created\r\n\r\n").unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let entry = Entry {
+            date: 1643587200,
+            name: "A \"B\"".to_string(),
+            score: 420,
+            seed: None,
+        };
+        submit(&url, &body("snake", "", &entry)).unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("POST /scores HTTP/1.1\r\n"));
+        assert!(request.ends_with(
+            "\r\n\r\n{\"date\":1643587200,\"game\":\"snake\",\"mode\":\"\",\
+             \"name\":\"A \\\"B\\\"\",\"score\":420}"
+        ));
+    }
+
+    #[test]
+    fn fetches_tables() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/scores?game=snake&mode={}",
+            listener.local_addr().unwrap(),
+            encode("two players")
+        );
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buffer = [0; 1024];
+            while !String::from_utf8_lossy(&request).ends_with("\r\n\r\n") {
+                let read = stream.read(&mut buffer).unwrap();
+                request.extend_from_slice(&buffer[..read]);
+            }
+            stream
+                .write_all(
+                    b"HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n\
+                      [{\"date\":1643587200,\"name\":\"ANN\",\"score\":420}]",
+                )
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        let table = fetch(&url).unwrap();
+        let request = server.join().unwrap();
+        assert!(request.starts_with("GET /scores?game=snake&mode=two%20players HTTP/1.0\r\n"));
+        assert_eq!(
+            table,
+            [Entry {
+                date: 1643587200,
+                name: "ANN".to_string(),
+                score: 420,
+                seed: None,
+            }]
+        );
+    }
+}
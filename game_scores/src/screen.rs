@@ -0,0 +1,163 @@
+//! A leaderboard text any game can put on its menu or game over screen; it follows the `Scores`
+//! resource as entries are added.
+
+use crate::{format_date, Entry, Order, Scores};
+use bevy::prelude::*;
+use game_hud::HudFont;
+use game_platform::Platform;
+
+const COLOR: Color = Color::WHITE;
+const FONT_SIZE: f32 = 20.0;
+
+/// Adds the `Scores` of a game, read through the `Platform` before the game's startup systems
+/// run, and keeps the text of leaderboards up to date.
+pub struct ScoresPlugin {
+    game: &'static str,
+    order: Order,
+}
+
+impl ScoresPlugin {
+    pub fn new(game: &'static str, order: Order) -> Self {
+        Self { game, order }
+    }
+}
+
+impl Plugin for ScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Platform>()
+            .insert_resource(Scores::new(self.game, self.order))
+            .add_startup_system_to_stage(StartupStage::PreStartup, scores_load)
+            .add_system(leaderboard_update);
+        #[cfg(feature = "online")]
+        app.add_system(global_update);
+    }
+}
+
+/// Shows the table of a mode in its entity's text.
+#[derive(Component)]
+pub struct Leaderboard {
+    /// How scores read, e.g. as points or as a time.
+    pub format: fn(u32) -> String,
+    /// Whether to show the leaderboard server's table, unmarked, once it has been fetched.
+    pub global: bool,
+    /// The place of the entry just added, which is marked.
+    pub highlight: Option<usize>,
+    pub mode: String,
+}
+
+impl Leaderboard {
+    /// The table of the mode with scores as plain numbers.
+    pub fn new(mode: &str) -> Self {
+        Self {
+            format: |score| score.to_string(),
+            global: false,
+            highlight: None,
+            mode: mode.to_string(),
+        }
+    }
+
+    /// A text bundle for it at `position`, to spawn together with it.
+    pub fn bundle(self, font: &HudFont, position: Rect<Val>) -> (TextBundle, Self) {
+        (
+            game_hud::screen_text(font, "", FONT_SIZE, COLOR, position),
+            self,
+        )
+    }
+}
+
+/// One line per place: the place, the name, the score and the date, in columns.
+pub fn leaderboard_text(
+    entries: &[Entry],
+    highlight: Option<usize>,
+    format: fn(u32) -> String,
+) -> String {
+    if entries.is_empty() {
+        return "No scores yet".to_string();
+    }
+    let scores: Vec<String> = entries.iter().map(|entry| format(entry.score)).collect();
+    let width = scores.iter().map(String::len).max().unwrap_or(0);
+    entries
+        .iter()
+        .zip(&scores)
+        .enumerate()
+        .map(|(place, (entry, score))| {
+            format!(
+                "{}{:>2}. {:<width$} {:>score_width$}  {}",
+                if highlight == Some(place) { '>' } else { ' ' },
+                place + 1,
+                entry.name,
+                score,
+                format_date(entry.date),
+                width = crate::name::MAX_LENGTH,
+                score_width = width,
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn leaderboard_update(
+    scores: Option<Res<Scores>>,
+    mut query: Query<(&mut Text, ChangeTrackers<Leaderboard>, &Leaderboard)>,
+) {
+    let scores = match scores {
+        Some(scores) => scores,
+        None => return,
+    };
+    for (mut text, tracker, leaderboard) in query.iter_mut() {
+        if scores.is_changed() || tracker.is_changed() {
+            let global = scores
+                .global(&leaderboard.mode)
+                .filter(|_| leaderboard.global);
+            text.sections[0].value = match global {
+                Some(entries) => leaderboard_text(entries, None, leaderboard.format),
+                None => leaderboard_text(
+                    scores.table(&leaderboard.mode),
+                    leaderboard.highlight,
+                    leaderboard.format,
+                ),
+            };
+        }
+    }
+}
+
+fn scores_load(platform: Res<Platform>, mut scores: ResMut<Scores>) {
+    scores.load(&platform);
+}
+
+/// Keeps the tables fetched from the leaderboard server as they come in.
+#[cfg(feature = "online")]
+fn global_update(scores: Option<ResMut<Scores>>) {
+    let mut scores = match scores {
+        Some(scores) => scores,
+        None => return,
+    };
+    let arrived = scores.fetches.arrived();
+    if !arrived.is_empty() {
+        scores.global.extend(arrived);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_up_the_columns() {
+        let entry = |name: &str, score| Entry {
+            date: 1643587200,
+            name: name.to_string(),
+            score,
+            seed: None,
+        };
+        let entries = [entry("ANN", 12000), entry("BOB", 900)];
+        assert_eq!(
+            leaderboard_text(&entries, Some(1), |score| score.to_string()),
+            "  1. ANN          12000  2022-01-31\n> 2. BOB            900  2022-01-31"
+        );
+        assert_eq!(
+            leaderboard_text(&[], None, |_| String::new()),
+            "No scores yet"
+        );
+    }
+}
@@ -0,0 +1,330 @@
+use bevy::prelude::*;
+use game_persistence::PersistenceError;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{error::Error, fmt, fs, io, marker::PhantomData, path::PathBuf, time::SystemTime};
+
+// Lets the derives name `::game_settings` within this crate's own tests.
+extern crate self as game_settings;
+
+pub mod screen;
+
+pub use game_settings_derive::{Choice, Settings};
+pub use screen::SettingsScreen;
+
+const FILE: &str = "settings.toml";
+/// Seconds between checks for edits to the settings file.
+const RELOAD_INTERVAL: f32 = 1.0;
+
+/// How far a number may go, and by how much a step changes it.
+pub struct Bounds {
+    pub max: Option<f64>,
+    pub min: Option<f64>,
+    pub step: f64,
+}
+
+/// A game's settings, usually derived with `#[derive(Settings)]`; see the readme.
+pub trait Settings: Default + Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// Changes the field at `field` in the order of `describe` by `step` steps.
+    fn adjust(&mut self, field: usize, step: i32);
+
+    /// The label and the value of every field on the settings screen.
+    fn describe(&self) -> Vec<(&'static str, String)>;
+}
+
+#[derive(Debug)]
+pub enum SettingsError {
+    Io(io::Error),
+    Persistence(PersistenceError),
+    /// The file is not TOML, or not the shape of the settings.
+    Syntax(toml::de::Error),
+    /// The settings cannot be written as TOML, e.g. a table before a plain value.
+    Unrepresentable(toml::ser::Error),
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(error) => write!(f, "{}", error),
+            Self::Persistence(error) => write!(f, "{}", error),
+            Self::Syntax(error) => write!(f, "invalid settings: {}", error),
+            Self::Unrepresentable(error) => write!(f, "settings not writable as TOML: {}", error),
+        }
+    }
+}
+
+impl Error for SettingsError {}
+
+impl From<io::Error> for SettingsError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<PersistenceError> for SettingsError {
+    fn from(error: PersistenceError) -> Self {
+        Self::Persistence(error)
+    }
+}
+
+/// Where a game's settings are kept, and when the file last changed.
+pub struct SettingsFile<S> {
+    game: &'static str,
+    marker: PhantomData<S>,
+    modified: Option<SystemTime>,
+    timer: Timer,
+}
+
+impl<S: Settings> SettingsFile<S> {
+    fn new(game: &'static str) -> Self {
+        let mut file = Self {
+            game,
+            marker: PhantomData,
+            modified: None,
+            timer: Timer::from_seconds(RELOAD_INTERVAL, true),
+        };
+        file.modified = file.modified_now();
+        file
+    }
+
+    /// The settings in the file, the defaults if there is none.
+    pub fn load(&self) -> S {
+        match load(self.game) {
+            Ok(settings) => settings.unwrap_or_default(),
+            Err(error) => {
+                eprintln!("could not load the settings of {}: {}", self.game, error);
+                S::default()
+            }
+        }
+    }
+
+    fn modified_now(&self) -> Option<SystemTime> {
+        fs::metadata(path(self.game).ok()?).ok()?.modified().ok()
+    }
+
+    /// Writes the settings, without reloading them on the next check.
+    pub fn save(&mut self, settings: &S) {
+        match save(self.game, settings) {
+            Ok(()) => self.modified = self.modified_now(),
+            Err(error) => eprintln!("could not save the settings of {}: {}", self.game, error),
+        }
+    }
+}
+
+/// Adds the game's settings as a resource of type `S`, loaded from its `settings.toml` and
+/// reloaded when the file is edited, along with the `SettingsScreen`.
+pub struct SettingsPlugin<S> {
+    game: &'static str,
+    marker: PhantomData<S>,
+}
+
+impl<S> SettingsPlugin<S> {
+    /// `game` is the directory the settings are kept in, as for `game_persistence`.
+    pub fn new(game: &'static str) -> Self {
+        Self {
+            game,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<S: Settings> Plugin for SettingsPlugin<S> {
+    fn build(&self, app: &mut App) {
+        let file = SettingsFile::<S>::new(self.game);
+        app.insert_resource(file.load())
+            .insert_resource(file)
+            .init_resource::<SettingsScreen>()
+            .add_system(reload_update::<S>)
+            .add_system(screen::settings_input::<S>)
+            .add_system(screen::settings_update::<S>);
+    }
+}
+
+/// One field of a settings struct, as the screen shows and changes it.
+pub trait Value {
+    fn adjust(&mut self, step: i32, bounds: &Bounds);
+
+    fn display(&self) -> String;
+}
+
+impl Value for bool {
+    fn adjust(&mut self, step: i32, _: &Bounds) {
+        if step % 2 != 0 {
+            *self = !*self;
+        }
+    }
+
+    fn display(&self) -> String {
+        if *self { "On" } else { "Off" }.to_string()
+    }
+}
+
+macro_rules! float_value {
+    ($($type:ty),*) => {
+        $(impl Value for $type {
+            fn adjust(&mut self, step: i32, bounds: &Bounds) {
+                let value = f64::from(*self) + f64::from(step) * bounds.step;
+                let value = (value / bounds.step).round() * bounds.step;
+                *self = value.clamp(
+                    bounds.min.unwrap_or(f64::MIN),
+                    bounds.max.unwrap_or(f64::MAX),
+                ) as $type;
+            }
+
+            fn display(&self) -> String {
+                format!("{:.1}", self)
+            }
+        })*
+    };
+}
+
+macro_rules! integer_value {
+    ($($type:ty),*) => {
+        $(impl Value for $type {
+            fn adjust(&mut self, step: i32, bounds: &Bounds) {
+                let value = *self as f64 + f64::from(step) * bounds.step;
+                *self = value.round().clamp(
+                    bounds.min.unwrap_or(<$type>::MIN as f64),
+                    bounds.max.unwrap_or(<$type>::MAX as f64),
+                ) as $type;
+            }
+
+            fn display(&self) -> String {
+                self.to_string()
+            }
+        })*
+    };
+}
+
+float_value!(f32, f64);
+integer_value!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+/// The saved settings of `game`, `Ok(None)` if there are none.
+pub fn load<S: Settings>(game: &str) -> Result<Option<S>, SettingsError> {
+    match fs::read_to_string(path(game)?) {
+        Ok(text) => toml::from_str(&text)
+            .map(Some)
+            .map_err(SettingsError::Syntax),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.into()),
+    }
+}
+
+/// Where the settings of `game` are kept: `settings.toml` in its `game_persistence` directory,
+/// where players may edit them by hand.
+pub fn path(game: &str) -> Result<PathBuf, SettingsError> {
+    Ok(game_persistence::directory(game)?.join(FILE))
+}
+
+/// Writes to a temporary file first, so a crash never leaves half the settings behind.
+pub fn save<S: Settings>(game: &str, settings: &S) -> Result<(), SettingsError> {
+    let path = path(game)?;
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory)?;
+    }
+    let text = toml::to_string(settings).map_err(SettingsError::Unrepresentable)?;
+    let temporary = path.with_extension("toml.tmp");
+    fs::write(&temporary, text)?;
+    fs::rename(temporary, path)?;
+    Ok(())
+}
+
+/// Reloads the settings when their file changed since they were loaded or saved.
+fn reload_update<S: Settings>(
+    time: Res<Time>,
+    mut file: ResMut<SettingsFile<S>>,
+    mut settings: ResMut<S>,
+) {
+    if !file.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = file.modified_now();
+    if modified.is_some() && modified != file.modified {
+        file.modified = modified;
+        *settings = file.load();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize, Choice)]
+    pub enum Difficulty {
+        Easy,
+        #[setting(label = "Tricky")]
+        Hard,
+        VeryHard,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize, Settings)]
+    #[serde(default)]
+    pub struct Test {
+        pub difficulty: Difficulty,
+        #[setting(skip)]
+        pub last_name: String,
+        #[setting(label = "Pegs", min = 3, max = 6)]
+        pub length: u8,
+        pub sound: bool,
+        #[setting(min = 0, max = 1, step = 0.1)]
+        pub volume: f32,
+    }
+
+    impl Default for Test {
+        fn default() -> Self {
+            Self {
+                difficulty: Difficulty::Easy,
+                last_name: String::new(),
+                length: 4,
+                sound: true,
+                volume: 0.5,
+            }
+        }
+    }
+
+    #[test]
+    fn derived_settings_describe_and_adjust() {
+        let mut settings = Test::default();
+        assert_eq!(
+            settings.describe(),
+            [
+                ("Difficulty", "Easy".to_string()),
+                ("Pegs", "4".to_string()),
+                ("Sound", "On".to_string()),
+                ("Volume", "0.5".to_string())
+            ]
+        );
+        settings.adjust(0, -1);
+        settings.adjust(1, 5);
+        settings.adjust(2, 1);
+        for _ in 0..3 {
+            settings.adjust(3, 1);
+        }
+        assert_eq!(
+            settings.describe(),
+            [
+                ("Difficulty", "Very hard".to_string()),
+                ("Pegs", "6".to_string()),
+                ("Sound", "Off".to_string()),
+                ("Volume", "0.8".to_string())
+            ]
+        );
+        settings.adjust(0, 2);
+        assert_eq!(settings.difficulty.display(), "Tricky");
+    }
+
+    #[test]
+    fn reads_and_writes_toml() {
+        let settings = Test {
+            difficulty: Difficulty::Hard,
+            ..Test::default()
+        };
+        let text = toml::to_string(&settings).unwrap();
+        assert!(text.contains("difficulty = \"Hard\"\n"));
+        assert_eq!(toml::from_str::<Test>(&text).unwrap(), settings);
+        // Fields left out keep their defaults.
+        let edited: Test = toml::from_str("length = 5").unwrap();
+        assert_eq!(edited.length, 5);
+        assert!(edited.sound);
+    }
+}
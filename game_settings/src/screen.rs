@@ -0,0 +1,154 @@
+//! A screen generated from the settings struct, listing every setting and changing the selected
+//! one with Left and Right. It needs the `HudPlugin` of `game_hud` for its font.
+
+use crate::{Settings, SettingsFile};
+use bevy::prelude::*;
+use game_hud::{HudFont, MenuInput, Navigate};
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+const FONT_SIZE: f32 = 22.0;
+const HINT: &str = "Up/Down choose  Left/Right change  Esc or B back";
+/// Width of the column of labels.
+const LABEL_WIDTH: usize = 16;
+/// Lines of the text above the first setting.
+const TITLE_LINES: usize = 2;
+
+/// Whether the screen is shown, and which setting is selected. Open it from a menu; closing it
+/// saves the settings.
+#[derive(Default)]
+pub struct SettingsScreen {
+    pub open: bool,
+    pub selected: usize,
+}
+
+impl SettingsScreen {
+    pub fn open(&mut self) {
+        *self = Self {
+            open: true,
+            selected: 0,
+        };
+    }
+}
+
+/// The full-screen background, which holds the text.
+#[derive(Component)]
+pub(crate) struct SettingsPanel;
+
+#[derive(Component)]
+pub(crate) struct SettingsText;
+
+/// The screen's text: every setting with its value, the selected one marked.
+pub fn settings_text<S: Settings>(settings: &S, screen: &SettingsScreen) -> String {
+    let mut lines = vec!["SETTINGS".to_string(), String::new()];
+    for (index, (label, value)) in settings.describe().into_iter().enumerate() {
+        lines.push(if index == screen.selected {
+            format!("> {:<width$}< {} >", label, value, width = LABEL_WIDTH)
+        } else {
+            format!("  {:<width$}  {}", label, value, width = LABEL_WIDTH)
+        });
+    }
+    lines.push(String::new());
+    lines.push(HINT.to_string());
+    lines.join("\n")
+}
+
+pub(crate) fn settings_input<S: Settings>(
+    mut screen: ResMut<SettingsScreen>,
+    mut settings: ResMut<S>,
+    mut file: ResMut<SettingsFile<S>>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+) {
+    if !screen.open {
+        return;
+    }
+    let selected = screen.selected;
+    if menu.vertical() != 0 {
+        screen.selected =
+            game_hud::menu::step(selected, settings.describe().len(), menu.vertical());
+    } else if menu.just_pressed(Navigate::Left) {
+        settings.adjust(selected, -1);
+    } else if menu.just_pressed(Navigate::Right) || menu.just_pressed(Navigate::Confirm) {
+        settings.adjust(selected, 1);
+    } else if menu.just_pressed(Navigate::Back) {
+        keyboard.clear_just_pressed(KeyCode::Escape);
+        screen.open = false;
+        file.save(&settings);
+    }
+}
+
+/// Shows the screen while it is open and keeps its text up to date.
+pub(crate) fn settings_update<S: Settings>(
+    mut commands: Commands,
+    screen: Res<SettingsScreen>,
+    settings: Res<S>,
+    font: Res<HudFont>,
+    panels: Query<Entity, With<SettingsPanel>>,
+    mut texts: Query<&mut Text, With<SettingsText>>,
+) {
+    if !screen.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let sections = game_hud::menu::focus_sections(
+        &font.style(FONT_SIZE, Color::WHITE),
+        &settings_text(&*settings, &screen),
+        TITLE_LINES + screen.selected,
+    );
+    match (screen.open, panels.get_single()) {
+        (true, Ok(_)) => {
+            for mut text in texts.iter_mut() {
+                text.sections = sections.clone();
+            }
+        }
+        (true, Err(_)) => {
+            commands
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(BACKGROUND),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(SettingsPanel)
+                .with_children(|parent| {
+                    let mut text = game_hud::screen_text(
+                        &font,
+                        "",
+                        FONT_SIZE,
+                        Color::WHITE,
+                        Rect {
+                            top: Val::Px(30.0),
+                            left: Val::Px(30.0),
+                            ..Default::default()
+                        },
+                    );
+                    text.text.sections = sections;
+                    parent.spawn_bundle(text).insert(SettingsText);
+                });
+        }
+        (false, _) => {
+            for entity in panels.iter() {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::Test;
+
+    #[test]
+    fn marks_the_selected_setting() {
+        let screen = SettingsScreen {
+            open: true,
+            selected: 1,
+        };
+        let text = settings_text(&Test::default(), &screen);
+        assert!(text.contains("\n  Difficulty        Easy\n> Pegs            < 4 >\n"));
+        assert!(text.ends_with(HINT));
+    }
+}
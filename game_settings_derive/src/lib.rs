@@ -0,0 +1,210 @@
+//! The derives of `game_settings`; use them through that crate.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as Tokens;
+use quote::quote;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Lit, Meta, NestedMeta, Result,
+};
+
+/// What `#[setting(...)]` says about a field or variant.
+#[derive(Default)]
+struct Options {
+    label: Option<String>,
+    max: Option<f64>,
+    min: Option<f64>,
+    skip: bool,
+    step: Option<f64>,
+}
+
+/// Makes a unit enum a setting that cycles through its variants, labelled by
+/// `#[setting(label = "...")]` or their names split into words.
+#[proc_macro_derive(Choice, attributes(setting))]
+pub fn derive_choice(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    choice(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Puts every named field of a struct on the settings screen, labelled by
+/// `#[setting(label = "...")]` or its name split into words and bounded by `min`, `max` and
+/// `step`; `#[setting(skip)]` keeps a field off the screen.
+#[proc_macro_derive(Settings, attributes(setting))]
+pub fn derive_settings(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    settings(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn choice(input: &DeriveInput) -> Result<Tokens> {
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => return Err(Error::new_spanned(input, "Choice needs an enum")),
+    };
+    let mut idents = Vec::new();
+    let mut labels = Vec::new();
+    for variant in variants {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(
+                variant,
+                "Choice variants cannot have fields",
+            ));
+        }
+        let options = options(&variant.attrs)?;
+        idents.push(&variant.ident);
+        labels.push(
+            options
+                .label
+                .unwrap_or_else(|| words(&variant.ident.to_string())),
+        );
+    }
+    let indices: Vec<usize> = (0..idents.len()).collect();
+    let count = idents.len() as i32;
+    let name = &input.ident;
+    Ok(quote! {
+        impl ::game_settings::Value for #name {
+            fn adjust(&mut self, step: i32, _: &::game_settings::Bounds) {
+                let index = match self {
+                    #(Self::#idents => #indices,)*
+                };
+                *self = match (index as i32 + step).rem_euclid(#count) as usize {
+                    #(#indices => Self::#idents,)*
+                    _ => unreachable!(),
+                };
+            }
+
+            fn display(&self) -> String {
+                match self {
+                    #(Self::#idents => #labels,)*
+                }
+                .to_string()
+            }
+        }
+    })
+}
+
+/// A bound given as a number, or as a string for negative ones.
+fn number(literal: &Lit) -> Result<f64> {
+    match literal {
+        Lit::Float(float) => float.base10_parse(),
+        Lit::Int(int) => int.base10_parse(),
+        Lit::Str(text) => text
+            .value()
+            .parse()
+            .map_err(|_| Error::new_spanned(literal, "expected a number")),
+        _ => Err(Error::new_spanned(literal, "expected a number")),
+    }
+}
+
+fn options(attributes: &[Attribute]) -> Result<Options> {
+    let mut options = Options::default();
+    for attribute in attributes
+        .iter()
+        .filter(|attribute| attribute.path.is_ident("setting"))
+    {
+        let list = match attribute.parse_meta()? {
+            Meta::List(list) => list,
+            meta => return Err(Error::new_spanned(meta, "expected #[setting(...)]")),
+        };
+        for nested in list.nested {
+            match nested {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => options.skip = true,
+                NestedMeta::Meta(Meta::NameValue(pair)) => {
+                    let key = pair.path.get_ident().map(ToString::to_string);
+                    match (key.as_deref(), &pair.lit) {
+                        (Some("label"), Lit::Str(label)) => options.label = Some(label.value()),
+                        (Some("max"), literal) => options.max = Some(number(literal)?),
+                        (Some("min"), literal) => options.min = Some(number(literal)?),
+                        (Some("step"), literal) => options.step = Some(number(literal)?),
+                        _ => return Err(Error::new_spanned(pair, "unknown setting option")),
+                    }
+                }
+                nested => {
+                    return Err(Error::new_spanned(
+                        nested,
+                        "expected label, min, max, step or skip",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(options)
+}
+
+fn settings(input: &DeriveInput) -> Result<Tokens> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => return Err(Error::new_spanned(input, "Settings needs named fields")),
+        },
+        _ => return Err(Error::new_spanned(input, "Settings needs a struct")),
+    };
+    let mut idents = Vec::new();
+    let mut labels = Vec::new();
+    let mut bounds = Vec::new();
+    for field in fields {
+        let options = options(&field.attrs)?;
+        if options.skip {
+            continue;
+        }
+        let ident = field.ident.as_ref().expect("named fields have names");
+        let optional = |bound: Option<f64>| match bound {
+            Some(bound) => quote!(Some(#bound)),
+            None => quote!(None),
+        };
+        let (max, min) = (optional(options.max), optional(options.min));
+        let step = options.step.unwrap_or(1.0);
+        idents.push(ident);
+        labels.push(options.label.unwrap_or_else(|| words(&ident.to_string())));
+        bounds.push(quote!(::game_settings::Bounds { max: #max, min: #min, step: #step }));
+    }
+    let indices: Vec<usize> = (0..idents.len()).collect();
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::game_settings::Settings for #name #type_generics #where_clause {
+            fn adjust(&mut self, field: usize, step: i32) {
+                match field {
+                    #(#indices => ::game_settings::Value::adjust(&mut self.#idents, step, &#bounds),)*
+                    _ => {}
+                }
+            }
+
+            fn describe(&self) -> Vec<(&'static str, String)> {
+                vec![#((#labels, ::game_settings::Value::display(&self.#idents)),)*]
+            }
+        }
+    })
+}
+
+/// A field or variant name as words: `sound_volume` and `SoundVolume` become `Sound volume`.
+fn words(name: &str) -> String {
+    let mut words = String::new();
+    for (index, character) in name.chars().enumerate() {
+        if character == '_' {
+            words.push(' ');
+        } else if index == 0 {
+            words.extend(character.to_uppercase());
+        } else if character.is_uppercase() {
+            words.push(' ');
+            words.extend(character.to_lowercase());
+        } else {
+            words.push(character);
+        }
+    }
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_names_into_words() {
+        assert_eq!(words("sound_volume"), "Sound volume");
+        assert_eq!(words("VeryHard"), "Very hard");
+        assert_eq!(words("demo"), "Demo");
+    }
+}
@@ -0,0 +1,68 @@
+//! Comparing text snapshots of a game's state with files kept next to its tests.
+
+use std::{env, fs, path::PathBuf};
+
+/// Set to rewrite golden files with what the tests produce, after a deliberate change.
+pub const UPDATE_VARIABLE: &str = "UPDATE_GOLDEN";
+
+/// Compares `actual` with `golden/<name>` in the crate under test, writing the file if it does not
+/// exist yet or `UPDATE_GOLDEN` is set.
+pub fn assert_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    let expected = fs::read_to_string(&path).ok();
+    match expected {
+        Some(expected) if env::var_os(UPDATE_VARIABLE).is_none() => {
+            if expected != actual {
+                panic!(
+                    "{} differs from the golden file; set {} to update it\n{}",
+                    path.display(),
+                    UPDATE_VARIABLE,
+                    difference(&expected, actual)
+                );
+            }
+        }
+        _ => {
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory).expect("could not create the golden directory");
+            }
+            fs::write(&path, actual).expect("could not write the golden file");
+        }
+    }
+}
+
+/// The lines that differ, marked `-` for expected and `+` for actual.
+fn difference(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut difference = String::new();
+    for line in 0..expected.len().max(actual.len()) {
+        let (old, new) = (expected.get(line), actual.get(line));
+        if old != new {
+            if let Some(old) = old {
+                difference += &format!("{:>4} - {}\n", line + 1, old);
+            }
+            if let Some(new) = new {
+                difference += &format!("{:>4} + {}\n", line + 1, new);
+            }
+        }
+    }
+    difference
+}
+
+fn golden_path(name: &str) -> PathBuf {
+    let root = env::var_os("CARGO_MANIFEST_DIR").unwrap_or_else(|| ".".into());
+    PathBuf::from(root).join("golden").join(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_the_differing_lines() {
+        assert_eq!(
+            difference("a\nb\nc\n", "a\nB\nc\nd\n"),
+            "   2 - b\n   2 + B\n   4 + d\n"
+        );
+    }
+}
@@ -0,0 +1,341 @@
+//! Running a game's plugin headless for integration tests: input as a player would give it, frames
+//! stepped one at a time, and the resulting state checked directly or against golden files.
+
+use bevy::{
+    app::Events,
+    asset::AssetPlugin,
+    audio::AudioSource,
+    ecs::{component::Component, schedule::StateData},
+    input::{
+        gamepad::{Gamepad, GamepadButton, GamepadButtonType, GamepadEventRaw, GamepadEventType},
+        keyboard::KeyboardInput,
+        mouse::MouseButtonInput,
+        ElementState, InputPlugin,
+    },
+    prelude::*,
+    text::Font,
+    transform::TransformPlugin,
+    window::{CursorMoved, ReceivedCharacter, WindowId, WindowPlugin},
+};
+use raw_window_handle::{RawWindowHandle, WebHandle};
+use std::{
+    env,
+    fmt::Debug,
+    fs, io, process,
+    sync::Once,
+    thread,
+    time::{Duration, Instant},
+};
+
+mod golden;
+
+pub use golden::{assert_golden, UPDATE_VARIABLE};
+
+/// The gamepad tests play with.
+const GAMEPAD: Gamepad = Gamepad(0);
+
+/// Keeps the saves of the games tested in a directory of this test run's own, emptied when it
+/// starts, so tests never read or overwrite the player's data. `TestApp::new` does it too; call it
+/// first when a game loads saved data before that, as its window descriptor may.
+pub fn isolate_saves() {
+    static ISOLATE: Once = Once::new();
+    ISOLATE.call_once(|| {
+        let directory = env::temp_dir().join(format!("rust_games_test_{}", process::id()));
+        if let Err(error) = fs::remove_dir_all(&directory) {
+            if error.kind() != io::ErrorKind::NotFound {
+                eprintln!("could not empty {}: {}", directory.display(), error);
+            }
+        }
+        env::set_var(game_persistence::DIRECTORY_VARIABLE, directory);
+    });
+}
+
+/// A game running without a window or renderer.
+pub struct TestApp {
+    pub app: App,
+    gamepad_connected: bool,
+    last_update: Option<Instant>,
+}
+
+impl TestApp {
+    /// Builds `plugin` on the headless parts of bevy, with a primary window described by `window`
+    /// that is never opened.
+    pub fn new(window: WindowDescriptor, plugin: impl Plugin) -> Self {
+        isolate_saves();
+        let mut app = App::new();
+        app.insert_resource(window.clone())
+            .add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(AssetPlugin)
+            .add_asset::<AudioSource>()
+            .add_asset::<Font>()
+            .add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .init_resource::<Audio>();
+        app.world
+            .resource_mut_or_panic::<Windows>()
+            .add(Window::new(
+                WindowId::primary(),
+                &window,
+                window.width as u32,
+                window.height as u32,
+                1.0,
+                None,
+                RawWindowHandle::Web(WebHandle::empty()),
+            ));
+        app.add_plugin(plugin);
+        Self {
+            app,
+            gamepad_connected: false,
+            last_update: None,
+        }
+    }
+
+    /// Runs frames until `seconds` of real time have passed since the last one. Bevy's clock
+    /// cannot be set, so this waits; keep it to fractions of a second.
+    pub fn advance(&mut self, seconds: f32) {
+        let until =
+            self.last_update.unwrap_or_else(Instant::now) + Duration::from_secs_f32(seconds);
+        let now = Instant::now();
+        if until > now {
+            thread::sleep(until - now);
+        }
+        self.step();
+    }
+
+    pub fn assert_state<S: StateData>(&self, expected: S) {
+        assert_eq!(self.resource::<State<S>>().current(), &expected);
+    }
+
+    /// Whether `button` of the first gamepad is held, as the game sees it.
+    pub fn button_pressed(&self, button: GamepadButtonType) -> bool {
+        self.resource::<Input<GamepadButton>>()
+            .pressed(GamepadButton(GAMEPAD, button))
+    }
+
+    /// Types `text`, as the window would report it.
+    pub fn characters(&mut self, text: &str) {
+        let mut events = self
+            .app
+            .world
+            .resource_mut_or_panic::<Events<ReceivedCharacter>>();
+        for char in text.chars() {
+            events.send(ReceivedCharacter {
+                id: WindowId::primary(),
+                char,
+            });
+        }
+    }
+
+    /// Moves the cursor to `position` in window coordinates and clicks `button` there.
+    pub fn click(&mut self, button: MouseButton, position: Vec2) {
+        if let Some(window) = self
+            .app
+            .world
+            .resource_mut_or_panic::<Windows>()
+            .get_primary_mut()
+        {
+            window.update_cursor_physical_position_from_backend(Some(position.as_dvec2()));
+        }
+        self.app
+            .world
+            .resource_mut_or_panic::<Events<CursorMoved>>()
+            .send(CursorMoved {
+                id: WindowId::primary(),
+                position,
+            });
+        self.mouse(button, ElementState::Pressed);
+        self.step();
+        self.mouse(button, ElementState::Released);
+    }
+
+    /// Every `C` in the world, in no particular order.
+    pub fn components<C: Component + Clone>(&mut self) -> Vec<C> {
+        let mut query = self.app.world.query::<&C>();
+        query.iter(&self.app.world).cloned().collect()
+    }
+
+    /// Every `C` on an entity that also has an `M`, such as the position of a marker component.
+    pub fn components_with<C: Component + Clone, M: Component>(&mut self) -> Vec<C> {
+        let mut query = self.app.world.query_filtered::<&C, With<M>>();
+        query.iter(&self.app.world).cloned().collect()
+    }
+
+    pub fn count<C: Component>(&mut self) -> usize {
+        let mut query = self.app.world.query::<&C>();
+        query.iter(&self.app.world).count()
+    }
+
+    fn gamepad(&mut self, event: GamepadEventType) {
+        let mut events = self
+            .app
+            .world
+            .resource_mut_or_panic::<Events<GamepadEventRaw>>();
+        if !self.gamepad_connected {
+            self.gamepad_connected = true;
+            events.send(GamepadEventRaw(GAMEPAD, GamepadEventType::Connected));
+        }
+        events.send(GamepadEventRaw(GAMEPAD, event));
+    }
+
+    fn key(&mut self, key_code: KeyCode, state: ElementState) {
+        self.app
+            .world
+            .resource_mut_or_panic::<Events<KeyboardInput>>()
+            .send(KeyboardInput {
+                scan_code: 0,
+                key_code: Some(key_code),
+                state,
+            });
+    }
+
+    fn mouse(&mut self, button: MouseButton, state: ElementState) {
+        self.app
+            .world
+            .resource_mut_or_panic::<Events<MouseButtonInput>>()
+            .send(MouseButtonInput { button, state });
+    }
+
+    /// Holds `key` down from the next frame on.
+    pub fn press(&mut self, key: KeyCode) {
+        self.key(key, ElementState::Pressed);
+    }
+
+    /// Holds `button` of the first gamepad down from the next frame on, connecting the gamepad
+    /// first if needed.
+    pub fn press_button(&mut self, button: GamepadButtonType) {
+        self.gamepad(GamepadEventType::ButtonChanged(button, 1.0));
+    }
+
+    pub fn release(&mut self, key: KeyCode) {
+        self.key(key, ElementState::Released);
+    }
+
+    pub fn release_button(&mut self, button: GamepadButtonType) {
+        self.gamepad(GamepadEventType::ButtonChanged(button, 0.0));
+    }
+
+    /// A resource of the game, panicking with its name if the game has none.
+    pub fn resource<R: 'static + Send + Sync>(&self) -> &R {
+        self.app.world.resource_or_panic::<R>()
+    }
+
+    pub fn resource_mut<R: 'static + Send + Sync>(&mut self) -> Mut<'_, R> {
+        self.app.world.resource_mut_or_panic::<R>()
+    }
+
+    /// Every `C` in the world, one per line in `Debug` form and sorted, for `assert_golden`.
+    pub fn snapshot<C: Component + Debug>(&mut self) -> String {
+        let mut query = self.app.world.query::<&C>();
+        let mut lines: Vec<String> = query
+            .iter(&self.app.world)
+            .map(|component| format!("{:?}\n", component))
+            .collect();
+        lines.sort();
+        lines.concat()
+    }
+
+    /// Runs one frame.
+    pub fn step(&mut self) {
+        self.app.update();
+        self.last_update = Some(Instant::now());
+    }
+
+    pub fn steps(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.step();
+        }
+    }
+
+    /// Presses `key` for a single frame.
+    pub fn tap(&mut self, key: KeyCode) {
+        self.press(key);
+        self.step();
+        self.release(key);
+    }
+
+    /// Presses `button` of the first gamepad for a single frame.
+    pub fn tap_button(&mut self, button: GamepadButtonType) {
+        self.press_button(button);
+        self.step();
+        self.release_button(button);
+    }
+}
+
+/// Getting resources out of a world, saying which one is missing instead of unwrapping a `None`.
+trait WorldExt {
+    fn resource_mut_or_panic<R: 'static + Send + Sync>(&mut self) -> Mut<'_, R>;
+    fn resource_or_panic<R: 'static + Send + Sync>(&self) -> &R;
+}
+
+impl WorldExt for World {
+    fn resource_mut_or_panic<R: 'static + Send + Sync>(&mut self) -> Mut<'_, R> {
+        self.get_resource_mut::<R>()
+            .unwrap_or_else(|| panic!("no resource {}", std::any::type_name::<R>()))
+    }
+
+    fn resource_or_panic<R: 'static + Send + Sync>(&self) -> &R {
+        self.get_resource::<R>()
+            .unwrap_or_else(|| panic!("no resource {}", std::any::type_name::<R>()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+    enum Phase {
+        Playing,
+        Paused,
+    }
+
+    #[derive(Clone, Component, Debug)]
+    struct Presses(u32);
+
+    struct PausePlugin;
+
+    impl Plugin for PausePlugin {
+        fn build(&self, app: &mut App) {
+            app.add_state(Phase::Playing)
+                .add_startup_system(spawn)
+                .add_system(pause);
+        }
+    }
+
+    fn pause(
+        keyboard_input: Res<Input<KeyCode>>,
+        mut phase: ResMut<State<Phase>>,
+        mut presses: Query<&mut Presses>,
+    ) {
+        if keyboard_input.just_pressed(KeyCode::P) {
+            presses.single_mut().0 += 1;
+            let next = match phase.current() {
+                Phase::Playing => Phase::Paused,
+                Phase::Paused => Phase::Playing,
+            };
+            phase.set(next).unwrap();
+        }
+    }
+
+    fn spawn(mut commands: Commands) {
+        commands.spawn().insert(Presses(0));
+    }
+
+    #[test]
+    fn taps_reach_the_game() {
+        let mut app = TestApp::new(WindowDescriptor::default(), PausePlugin);
+        app.step();
+        app.assert_state(Phase::Playing);
+        app.tap(KeyCode::P);
+        app.step();
+        app.assert_state(Phase::Paused);
+        app.tap(KeyCode::P);
+        app.step();
+        app.assert_state(Phase::Playing);
+        assert_eq!(app.snapshot::<Presses>(), "Presses(2)\n");
+        assert!(app.resource::<Windows>().get_primary().is_some());
+    }
+}
@@ -0,0 +1,321 @@
+//! Themes for the games in this repository: colors, a font, a sprite skin and the color of UI
+//! panels, defined in a data file and chosen once in the launcher for every game.
+
+use bevy::prelude::*;
+use game_a11y::A11yPlugin;
+use game_hud::HudFont;
+use serde::Deserialize;
+use std::{collections::BTreeMap, fs, path::Path};
+
+/// The `game_persistence` directory and file of the chosen theme, the same for every game.
+pub const GAME: &str = "theme";
+const FILE: &str = "theme";
+/// The theme that keeps every game's own look.
+pub const DEFAULT: &str = "classic";
+const THEMES: &str = include_str!("../assets/themes.ron");
+
+/// How a theme looks. Fields left out keep the game's own look.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Theme {
+    /// Titles, highlights and the selection.
+    pub accent: Option<Color>,
+    pub background: Option<Color>,
+    /// A TrueType font shown instead of the HUD font, relative to the `assets` of this crate.
+    pub font: Option<String>,
+    /// The background of overlays and menus.
+    pub panel: Option<Color>,
+    /// The directory in a game's `assets/skins` its sprites are loaded from.
+    pub skin: Option<String>,
+    pub text: Option<Color>,
+}
+
+impl Theme {
+    /// The theme's color for `role`, or the game's own.
+    pub fn color(&self, role: Role, own: Color) -> Color {
+        let color = match role {
+            Role::Accent => self.accent,
+            Role::Background => self.background,
+            Role::Panel => self.panel,
+            Role::Text => self.text,
+        };
+        color.unwrap_or(own)
+    }
+
+    /// `self`, with the fields `other` sets taken from it.
+    pub fn merge(&self, other: &Theme) -> Theme {
+        Theme {
+            accent: other.accent.or(self.accent),
+            background: other.background.or(self.background),
+            font: other.font.clone().or_else(|| self.font.clone()),
+            panel: other.panel.or(self.panel),
+            skin: other.skin.clone().or_else(|| self.skin.clone()),
+            text: other.text.or(self.text),
+        }
+    }
+
+    /// The asset path of a sprite in the theme's skin, `skins/<skin>/<name>`, or `name` itself
+    /// without a skin.
+    pub fn sprite(&self, name: &str) -> String {
+        match &self.skin {
+            Some(skin) => format!("skins/{}/{}", skin, name),
+            None => name.to_string(),
+        }
+    }
+}
+
+/// What a color is used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Accent,
+    Background,
+    Panel,
+    Text,
+}
+
+/// A UI text or node the theme colors: the sections of its text or its background take the
+/// theme's color for the role, or the game's own color for themes that leave it.
+#[derive(Component)]
+pub struct Themed {
+    pub own: Color,
+    pub role: Role,
+}
+
+impl Themed {
+    pub fn new(role: Role, own: Color) -> Self {
+        Self { own, role }
+    }
+}
+
+/// Every theme by name, the one chosen and the game's own changes to them.
+pub struct Themes {
+    chosen: String,
+    overrides: BTreeMap<String, Theme>,
+    themes: BTreeMap<String, Theme>,
+}
+
+impl Default for Themes {
+    fn default() -> Self {
+        Self {
+            chosen: DEFAULT.to_string(),
+            overrides: BTreeMap::new(),
+            themes: ron::from_str(THEMES).expect("built in themes are valid"),
+        }
+    }
+}
+
+impl Themes {
+    /// The theme the player chose last.
+    pub fn load() -> Self {
+        let mut themes = Self::default();
+        let chosen = game_persistence::load_or_default::<String>(GAME, FILE);
+        if !chosen.is_empty() && !themes.select(&chosen) {
+            eprintln!("no theme {}, showing {}", chosen, DEFAULT);
+        }
+        themes
+    }
+
+    pub fn chosen(&self) -> &str {
+        &self.chosen
+    }
+
+    /// The chosen theme, with the game's changes to it.
+    pub fn current(&self) -> Theme {
+        let theme = &self.themes[&self.chosen];
+        match self.overrides.get(&self.chosen) {
+            Some(overrides) => theme.merge(overrides),
+            None => theme.clone(),
+        }
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.themes.keys().map(String::as_str)
+    }
+
+    /// Chooses the next theme, in the order of their names.
+    pub fn next(&mut self) {
+        let names: Vec<&str> = self.names().collect();
+        let index = names.iter().position(|name| *name == self.chosen);
+        let next = names[index.map_or(0, |index| (index + 1) % names.len())].to_string();
+        self.chosen = next;
+    }
+
+    /// Reads the game's changes to themes from a RON map from theme names to the fields it
+    /// changes. A theme that is not built in is added for the game alone.
+    pub fn override_from_ron(&mut self, text: &str) -> Result<(), ron::Error> {
+        let overrides: BTreeMap<String, Theme> = ron::from_str(text)?;
+        for name in overrides.keys() {
+            self.themes.entry(name.clone()).or_default();
+        }
+        self.overrides.extend(overrides);
+        Ok(())
+    }
+
+    /// Keeps the theme for every game, to be shown in the next one started.
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save(GAME, FILE, &self.chosen) {
+            eprintln!("could not save the theme: {}", error);
+        }
+    }
+
+    /// False if there is no theme of that name.
+    pub fn select(&mut self, name: &str) -> bool {
+        let found = self.themes.contains_key(name);
+        if found {
+            self.chosen = name.to_string();
+        }
+        found
+    }
+}
+
+/// Adds the `Themes`, changed by the game's data file if it has one, and shows the chosen theme:
+/// the clear color, the HUD font and every `Themed` text and node. Adds the `A11yPlugin` of
+/// `game_a11y` too, so the player's palette, high contrast and text size apply over the theme.
+/// Needs the `HudPlugin` of `game_hud`. Added again, it keeps the first one's overrides.
+#[derive(Default)]
+pub struct ThemePlugin {
+    overrides: Option<&'static str>,
+}
+
+impl ThemePlugin {
+    /// With the game's changes to themes from a RON file, e.g.
+    /// `include_str!("../assets/theme.ron")`.
+    pub fn with_overrides(overrides: &'static str) -> Self {
+        Self {
+            overrides: Some(overrides),
+        }
+    }
+}
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        // The binaries add the plugin for every game, after the game's own with its overrides.
+        if app.world.contains_resource::<Themes>() {
+            return;
+        }
+        let mut themes = Themes::load();
+        if let Some(text) = self.overrides {
+            if let Err(error) = themes.override_from_ron(text) {
+                eprintln!("could not read the theme overrides: {}", error);
+            }
+        }
+        app.insert_resource(themes)
+            .add_plugin(A11yPlugin)
+            .add_startup_system_to_stage(StartupStage::PostStartup, font_setup)
+            .add_system(theme_update)
+            .add_system(themed_update);
+    }
+}
+
+/// Shows the chosen theme's font from the start, in place of the HUD font. Fonts are only
+/// swapped before any text is drawn, so a theme chosen later shows its font on the next start.
+fn font_setup(
+    themes: Res<Themes>,
+    hud_font: Option<Res<HudFont>>,
+    mut fonts: ResMut<Assets<Font>>,
+) {
+    let (file, hud_font) = match (themes.current().font, hud_font) {
+        (Some(file), Some(hud_font)) => (file, hud_font),
+        _ => return,
+    };
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join(file);
+    let font = fs::read(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|bytes| Font::try_from_bytes(bytes).map_err(|error| error.to_string()));
+    match font {
+        // Replacing the font behind the HUD font's handle changes every text at once.
+        Ok(font) => fonts.set_untracked(&hud_font.0, font),
+        Err(error) => eprintln!("could not load the font {}: {}", path.display(), error),
+    }
+}
+
+/// Shows the chosen theme's background whenever the theme changes.
+fn theme_update(
+    themes: Res<Themes>,
+    mut own: Local<Option<Color>>,
+    clear_color: Option<ResMut<ClearColor>>,
+) {
+    let mut clear_color = match clear_color {
+        Some(clear_color) if themes.is_changed() => clear_color,
+        _ => return,
+    };
+    let own = *own.get_or_insert(clear_color.0);
+    let background = themes.current().color(Role::Background, own);
+    if clear_color.0 != background {
+        clear_color.0 = background;
+    }
+}
+
+/// Colors `Themed` texts and nodes when they appear and whenever the theme changes.
+fn themed_update(
+    themes: Res<Themes>,
+    mut themed: Query<(
+        ChangeTrackers<Themed>,
+        &Themed,
+        Option<&mut Text>,
+        Option<&mut UiColor>,
+    )>,
+) {
+    let theme = themes.current();
+    for (tracker, themed, text, ui_color) in themed.iter_mut() {
+        if !themes.is_changed() && !tracker.is_changed() {
+            continue;
+        }
+        let color = theme.color(themed.role, themed.own);
+        if let Some(mut text) = text {
+            for section in &mut text.sections {
+                section.style.color = color;
+            }
+        }
+        if let Some(mut ui_color) = ui_color {
+            ui_color.0 = color;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn games_change_themes_for_themselves() {
+        let mut themes = Themes::default();
+        assert_eq!(
+            themes.current(),
+            Theme::default(),
+            "classic keeps the game's look"
+        );
+        themes
+            .override_from_ron(
+                "#![enable(implicit_some)]\n{\"paper\": (skin: \"ink\"), \"forest\": (background: Rgba(red: 0.0, green: 0.3, blue: 0.0, alpha: 1.0))}",
+            )
+            .unwrap();
+        assert!(themes.select("paper"));
+        let paper = themes.current();
+        assert_eq!(paper.sprite("head.png"), "skins/ink/head.png");
+        assert_eq!(paper.text, Themes::default().themes["paper"].text);
+        assert!(themes.select("forest"));
+        assert_eq!(
+            themes.current().color(Role::Background, Color::BLACK),
+            Color::rgb(0.0, 0.3, 0.0)
+        );
+        assert_eq!(
+            themes.current().color(Role::Text, Color::WHITE),
+            Color::WHITE
+        );
+    }
+
+    #[test]
+    fn cycles_through_the_themes() {
+        let mut themes = Themes::default();
+        assert!(!themes.select("no such theme"));
+        let mut seen = vec![themes.chosen().to_string()];
+        for _ in 0..4 {
+            themes.next();
+            seen.push(themes.chosen().to_string());
+        }
+        assert_eq!(seen, ["classic", "midnight", "neon", "paper", "classic"]);
+    }
+}
@@ -0,0 +1,241 @@
+//! Draws a `Grid<T>` with one sprite per cell, spawned once and redrawn only where the grid
+//! looks different, instead of a sprite entity per game object.
+
+use bevy::{prelude::*, transform::TransformSystem};
+use game_grid::{Grid, Position};
+use std::marker::PhantomData;
+
+/// How a cell looks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Tile {
+    /// Tints the atlas image, or is the color of the square without an atlas.
+    pub color: Color,
+    /// The image in the atlas; unused without one.
+    pub index: usize,
+    /// How much of its cell the tile fills.
+    pub scale: f32,
+}
+
+impl Tile {
+    /// A square of `color`, filling `scale` of its cell.
+    pub fn colored(color: Color, scale: f32) -> Self {
+        Self {
+            color,
+            index: 0,
+            scale,
+        }
+    }
+}
+
+/// A grid drawn around the entity's `Transform`. Changing the grid or the tile size redraws the
+/// cells that look different.
+#[derive(Component)]
+pub struct Tilemap<T> {
+    /// The images of the tiles, set before the tilemap is first drawn.
+    pub atlas: Option<Handle<TextureAtlas>>,
+    pub grid: Grid<T>,
+    /// How a value looks, or `None` for an empty cell.
+    pub tile: fn(&T) -> Option<Tile>,
+    /// Width and height of a cell, in pixels.
+    pub tile_size: Vec2,
+}
+
+impl<T> Tilemap<T> {
+    pub fn new(grid: Grid<T>, tile_size: Vec2, tile: fn(&T) -> Option<Tile>) -> Self {
+        Self {
+            atlas: None,
+            grid,
+            tile,
+            tile_size,
+        }
+    }
+
+    pub fn with_atlas(self, atlas: Handle<TextureAtlas>) -> Self {
+        Self {
+            atlas: Some(atlas),
+            ..self
+        }
+    }
+}
+
+/// Draws the `Tilemap<T>`s, before transforms are propagated.
+pub struct TilesPlugin<T>(PhantomData<T>);
+
+impl<T> Default for TilesPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Send + Sync + 'static> Plugin for TilesPlugin<T> {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PostUpdate,
+            tilemap_update::<T>.before(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+/// The sprites of a tilemap and how they were last drawn.
+#[derive(Component)]
+struct Cells {
+    drawn: Vec<Option<Tile>>,
+    entities: Vec<Entity>,
+    /// Width and height of the grid, in cells.
+    size: (u32, u32),
+    tile_size: Vec2,
+}
+
+/// Where the sprite of a cell goes, relative to the middle of the grid.
+fn cell_translation(position: Position, (width, height): (u32, u32), tile_size: Vec2) -> Vec3 {
+    let corner = Vec2::new(width as f32, height as f32) * tile_size / 2.0;
+    let cell = Vec2::new(position.x as f32 + 0.5, position.y as f32 + 0.5) * tile_size;
+    (cell - corner).extend(0.0)
+}
+
+/// Updates a sprite of either kind to show `tile`.
+fn draw(
+    tile: Option<Tile>,
+    tile_size: Vec2,
+    visibility: &mut Visibility,
+    sprite: Option<Mut<Sprite>>,
+    atlas_sprite: Option<Mut<TextureAtlasSprite>>,
+) {
+    visibility.is_visible = tile.is_some();
+    let tile = match tile {
+        Some(tile) => tile,
+        None => return,
+    };
+    let size = Some(tile_size * tile.scale);
+    if let Some(mut sprite) = sprite {
+        sprite.color = tile.color;
+        sprite.custom_size = size;
+    }
+    if let Some(mut sprite) = atlas_sprite {
+        sprite.color = tile.color;
+        sprite.custom_size = size;
+        sprite.index = tile.index;
+    }
+}
+
+/// Spawns the sprites of a tilemap, drawn as it is now.
+fn spawn_cells<T>(commands: &mut Commands, entity: Entity, tilemap: &Tilemap<T>) -> Cells {
+    let size = (tilemap.grid.width(), tilemap.grid.height());
+    let drawn: Vec<Option<Tile>> = tilemap
+        .grid
+        .iter()
+        .map(|(_, value)| (tilemap.tile)(value))
+        .collect();
+    let mut entities = Vec::with_capacity(drawn.len());
+    commands.entity(entity).with_children(|parent| {
+        for ((position, _), tile) in tilemap.grid.iter().zip(&drawn) {
+            let transform =
+                Transform::from_translation(cell_translation(position, size, tilemap.tile_size));
+            let visibility = Visibility {
+                is_visible: tile.is_some(),
+            };
+            let tile = tile.unwrap_or_else(|| Tile::colored(Color::NONE, 1.0));
+            let custom_size = Some(tilemap.tile_size * tile.scale);
+            let cell = match &tilemap.atlas {
+                Some(atlas) => parent.spawn_bundle(SpriteSheetBundle {
+                    sprite: TextureAtlasSprite {
+                        color: tile.color,
+                        custom_size,
+                        index: tile.index,
+                        ..Default::default()
+                    },
+                    texture_atlas: atlas.clone(),
+                    transform,
+                    visibility,
+                    ..Default::default()
+                }),
+                None => parent.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: tile.color,
+                        custom_size,
+                        ..Default::default()
+                    },
+                    transform,
+                    visibility,
+                    ..Default::default()
+                }),
+            };
+            entities.push(cell.id());
+        }
+    });
+    Cells {
+        drawn,
+        entities,
+        size,
+        tile_size: tilemap.tile_size,
+    }
+}
+
+/// Redraws the cells of changed tilemaps that look different, or every cell when the tile size
+/// changed. Respawns the sprites when the grid changed size.
+fn tilemap_update<T: Send + Sync + 'static>(
+    mut commands: Commands,
+    tilemaps: Query<(Entity, &Tilemap<T>), Changed<Tilemap<T>>>,
+    mut cells: Query<&mut Cells>,
+    mut sprites: Query<(
+        &mut Transform,
+        &mut Visibility,
+        Option<&mut Sprite>,
+        Option<&mut TextureAtlasSprite>,
+    )>,
+) {
+    for (entity, tilemap) in tilemaps.iter() {
+        let size = (tilemap.grid.width(), tilemap.grid.height());
+        let mut cells = match cells.get_mut(entity).ok() {
+            Some(cells) if cells.size == size => cells,
+            cells => {
+                for old in cells.iter().flat_map(|cells| &cells.entities) {
+                    commands.entity(*old).despawn();
+                }
+                let cells = spawn_cells(&mut commands, entity, tilemap);
+                commands.entity(entity).insert(cells);
+                continue;
+            }
+        };
+        let moved = cells.tile_size != tilemap.tile_size;
+        cells.tile_size = tilemap.tile_size;
+        for (index, (position, value)) in tilemap.grid.iter().enumerate() {
+            let tile = (tilemap.tile)(value);
+            if !moved && cells.drawn[index] == tile {
+                continue;
+            }
+            cells.drawn[index] = tile;
+            if let Ok((mut transform, mut visibility, sprite, atlas_sprite)) =
+                sprites.get_mut(cells.entities[index])
+            {
+                transform.translation = cell_translation(position, size, tilemap.tile_size);
+                draw(
+                    tile,
+                    tilemap.tile_size,
+                    &mut visibility,
+                    sprite,
+                    atlas_sprite,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn centers_the_grid() {
+        let size = (4, 2);
+        let tile_size = Vec2::new(10.0, 20.0);
+        assert_eq!(
+            cell_translation(Position::new(0, 0), size, tile_size),
+            Vec3::new(-15.0, -10.0, 0.0)
+        );
+        assert_eq!(
+            cell_translation(Position::new(3, 1), size, tile_size),
+            Vec3::new(15.0, 10.0, 0.0)
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Transitions covering the screen and uncovering it again, so a change of state or game is no
+//! instant cut: a fade, a slide or the screen filling up block by block.
+
+use bevy::{ecs::schedule::StateData, prelude::*};
+use std::marker::PhantomData;
+
+/// Blocks across and down the screen for `Kind::Pixelate`.
+const BLOCKS: (usize, usize) = (16, 9);
+const COLOR: Color = Color::BLACK;
+/// Steps through the blocks in a scattered order; shares no factor with their count.
+const SCATTER: usize = 37;
+
+/// Sent when the screen is fully covered, halfway through a transition.
+pub struct Covered;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Fade,
+    /// Fills the screen block by block, then empties it the same way.
+    Pixelate,
+    /// Slides a curtain in from the left and out to the right.
+    Slide,
+}
+
+/// Changes the state to the one sent once the screen is covered. Needs a
+/// `StateTransitionPlugin` for the state.
+pub struct TransitionTo<S>(pub S);
+
+/// The running transition, if any, and how the next one looks.
+pub struct Transitions {
+    /// Seconds from the start until the screen is uncovered again.
+    pub duration: f32,
+    pub kind: Kind,
+    /// The kind of the running transition and how far it is: up to 1 while covering, up to 2
+    /// while uncovering.
+    running: Option<(Kind, f32)>,
+}
+
+impl Transitions {
+    pub fn is_running(&self) -> bool {
+        self.running.is_some()
+    }
+
+    /// Starts a transition, unless one is running already.
+    pub fn start(&mut self) {
+        if self.running.is_none() {
+            self.running = Some((self.kind, 0.0));
+        }
+    }
+}
+
+/// Adds the `Transitions` with their look and duration; games call `Transitions::start` and wait
+/// for `Covered`.
+pub struct TransitionPlugin {
+    duration: f32,
+    kind: Kind,
+}
+
+impl TransitionPlugin {
+    pub fn new(kind: Kind, duration: f32) -> Self {
+        Self { duration, kind }
+    }
+}
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Transitions {
+            duration: self.duration,
+            kind: self.kind,
+            running: None,
+        })
+        .add_event::<Covered>()
+        .add_system(transition_update.label(Running))
+        .add_system(overlay_update.after(Running));
+    }
+}
+
+/// Lets `TransitionTo` change the state `S`.
+pub struct StateTransitionPlugin<S>(PhantomData<S>);
+
+impl<S> Default for StateTransitionPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: StateData> Plugin for StateTransitionPlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Pending::<S>(None))
+            .add_event::<TransitionTo<S>>()
+            .add_system(transition_to_start::<S>)
+            .add_system(covered_switch::<S>);
+    }
+}
+
+/// A block of `Kind::Pixelate`, by its place in the order they appear in.
+#[derive(Component)]
+struct Block(usize);
+
+/// The node covering the screen.
+#[derive(Component)]
+struct Overlay;
+
+/// The state to change to once the screen is covered.
+struct Pending<S>(Option<S>);
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+struct Running;
+
+/// Whether the block in place `order` shows when `coverage` of the screen is covered.
+fn block_shown(order: usize, count: usize, coverage: f32) -> bool {
+    (order as f32) < coverage * count as f32
+}
+
+/// How much of the screen is covered `progress` into a transition.
+fn coverage(progress: f32) -> f32 {
+    if progress < 1.0 {
+        progress.max(0.0)
+    } else {
+        (2.0 - progress).max(0.0)
+    }
+}
+
+fn covered_switch<S: StateData>(
+    mut covered: EventReader<Covered>,
+    mut pending: ResMut<Pending<S>>,
+    mut state: ResMut<State<S>>,
+) {
+    if covered.iter().next().is_none() {
+        return;
+    }
+    if let Some(next) = pending.0.take() {
+        if let Err(error) = state.set(next) {
+            eprintln!("could not change the state: {:?}", error);
+        }
+    }
+}
+
+/// Shows how far the transition is.
+fn overlay_update(
+    transitions: Res<Transitions>,
+    mut overlays: Query<(&mut UiColor, &mut Style), With<Overlay>>,
+    mut blocks: Query<(&Block, &mut UiColor), Without<Overlay>>,
+) {
+    let (kind, progress) = match transitions.running {
+        Some(running) => running,
+        None => return,
+    };
+    let covered = coverage(progress);
+    for (mut color, mut style) in overlays.iter_mut() {
+        match kind {
+            Kind::Fade => {
+                color.0 = COLOR;
+                color.0.set_a(covered);
+            }
+            Kind::Pixelate => color.0 = Color::NONE,
+            Kind::Slide => style.position.left = Val::Percent((progress - 1.0) * 100.0),
+        }
+    }
+    let count = BLOCKS.0 * BLOCKS.1;
+    for (block, mut color) in blocks.iter_mut() {
+        color.0 = if block_shown(block.0, count, covered) {
+            COLOR
+        } else {
+            Color::NONE
+        };
+    }
+}
+
+fn spawn_overlay(commands: &mut Commands, kind: Kind) {
+    let full = Size::new(Val::Percent(100.0), Val::Percent(100.0));
+    let color = match kind {
+        Kind::Fade | Kind::Pixelate => Color::NONE,
+        Kind::Slide => COLOR,
+    };
+    let mut overlay = commands.spawn_bundle(NodeBundle {
+        color: UiColor(color),
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: Rect {
+                left: Val::Percent(if kind == Kind::Slide { -100.0 } else { 0.0 }),
+                top: Val::Percent(0.0),
+                ..Default::default()
+            },
+            size: full,
+            ..Default::default()
+        },
+        ..Default::default()
+    });
+    overlay.insert(Overlay);
+    if kind != Kind::Pixelate {
+        return;
+    }
+    let (columns, rows) = BLOCKS;
+    let count = columns * rows;
+    overlay.with_children(|parent| {
+        for index in 0..count {
+            let (column, row) = (index % columns, index / columns);
+            parent
+                .spawn_bundle(NodeBundle {
+                    color: UiColor(Color::NONE),
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        position: Rect {
+                            left: Val::Percent(column as f32 * 100.0 / columns as f32),
+                            top: Val::Percent(row as f32 * 100.0 / rows as f32),
+                            ..Default::default()
+                        },
+                        // A little larger than a block, so no seams show between them.
+                        size: Size::new(
+                            Val::Percent(100.0 / columns as f32 + 0.5),
+                            Val::Percent(100.0 / rows as f32 + 0.5),
+                        ),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(Block(index * SCATTER % count));
+        }
+    });
+}
+
+/// Runs the transition: covers the screen, says so, uncovers it and cleans up.
+fn transition_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut transitions: ResMut<Transitions>,
+    mut covered: EventWriter<Covered>,
+    overlays: Query<Entity, With<Overlay>>,
+) {
+    let (kind, progress) = match transitions.running {
+        Some(running) => running,
+        None => return,
+    };
+    if progress == 0.0 && overlays.is_empty() {
+        spawn_overlay(&mut commands, kind);
+    }
+    let next = progress + time.delta_seconds() * 2.0 / transitions.duration.max(0.01);
+    if progress < 1.0 && next >= 1.0 {
+        covered.send(Covered);
+    }
+    if next >= 2.0 {
+        transitions.running = None;
+        for entity in overlays.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+    } else {
+        transitions.running = Some((kind, next));
+    }
+}
+
+fn transition_to_start<S: StateData>(
+    mut events: EventReader<TransitionTo<S>>,
+    mut transitions: ResMut<Transitions>,
+    mut pending: ResMut<Pending<S>>,
+) {
+    if let Some(TransitionTo(state)) = events.iter().last() {
+        pending.0 = Some(state.clone());
+        transitions.start();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_then_uncovers() {
+        assert_eq!(coverage(0.0), 0.0);
+        assert_eq!(coverage(0.5), 0.5);
+        assert_eq!(coverage(1.0), 1.0);
+        assert_eq!(coverage(1.75), 0.25);
+        assert_eq!(coverage(2.5), 0.0);
+    }
+
+    #[test]
+    fn shows_every_block_once_covered() {
+        let count = BLOCKS.0 * BLOCKS.1;
+        let orders: Vec<usize> = (0..count).map(|index| index * SCATTER % count).collect();
+        let mut sorted = orders.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, (0..count).collect::<Vec<_>>());
+        assert!(orders.iter().all(|order| block_shown(*order, count, 1.0)));
+        assert!(!orders.iter().any(|order| block_shown(*order, count, 0.0)));
+        assert_eq!(
+            orders
+                .iter()
+                .filter(|order| block_shown(**order, count, 0.5))
+                .count(),
+            count / 2
+        );
+    }
+}
@@ -0,0 +1,136 @@
+//! One round: the hidden word and the letters guessed so far.
+
+/// Wrong guesses until the drawing is complete: head, body, two arms and two legs.
+pub const MAX_MISSES: usize = 6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Guess {
+    Hit,
+    Miss,
+    /// Guessed before, or not a letter; nothing changes.
+    Ignored,
+}
+
+#[derive(Clone, Debug)]
+pub struct Hangman {
+    /// In the order they were guessed.
+    guessed: Vec<char>,
+    word: String,
+}
+
+impl Hangman {
+    pub fn new(word: &str) -> Self {
+        Self {
+            guessed: Vec::new(),
+            word: word.to_lowercase(),
+        }
+    }
+
+    pub fn guess(&mut self, letter: char) -> Guess {
+        let letter = letter.to_ascii_lowercase();
+        if !letter.is_ascii_lowercase() || self.guessed.contains(&letter) || self.is_over() {
+            return Guess::Ignored;
+        }
+        self.guessed.push(letter);
+        if self.word.contains(letter) {
+            Guess::Hit
+        } else {
+            Guess::Miss
+        }
+    }
+
+    /// `Some(true)` for a letter guessed that is in the word, `Some(false)` for a miss.
+    pub fn guessed(&self, letter: char) -> Option<bool> {
+        self.guessed
+            .contains(&letter)
+            .then(|| self.word.contains(letter))
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.misses() >= MAX_MISSES
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.is_lost()
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.word
+            .chars()
+            .all(|letter| self.guessed.contains(&letter))
+    }
+
+    pub fn misses(&self) -> usize {
+        self.guessed
+            .iter()
+            .filter(|letter| !self.word.contains(**letter))
+            .count()
+    }
+
+    /// The word with every letter not guessed yet as `_`, spaced out for display.
+    pub fn revealed(&self) -> String {
+        self.word
+            .chars()
+            .map(|letter| {
+                if self.guessed.contains(&letter) {
+                    letter.to_ascii_uppercase()
+                } else {
+                    '_'
+                }
+            })
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+}
+
+/// Whether a word entered by a player can be played: at least two letters and nothing else.
+pub fn is_playable(word: &str) -> bool {
+    word.len() >= 2 && word.chars().all(|letter| letter.is_ascii_alphabetic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hits_reveal_every_occurrence() {
+        let mut hangman = Hangman::new("Banana");
+        assert_eq!(hangman.revealed(), "_ _ _ _ _ _");
+        assert_eq!(hangman.guess('A'), Guess::Hit);
+        assert_eq!(hangman.revealed(), "_ A _ A _ A");
+        assert_eq!(hangman.guess('a'), Guess::Ignored);
+        assert_eq!(hangman.guess('?'), Guess::Ignored);
+        assert_eq!(hangman.guess('x'), Guess::Miss);
+        assert_eq!(hangman.guessed('a'), Some(true));
+        assert_eq!(hangman.guessed('x'), Some(false));
+        assert_eq!(hangman.guessed('b'), None);
+        hangman.guess('b');
+        hangman.guess('n');
+        assert!(hangman.is_won());
+        assert_eq!(hangman.misses(), 1);
+        assert_eq!(hangman.guess('z'), Guess::Ignored, "the round is over");
+    }
+
+    #[test]
+    fn too_many_misses_lose() {
+        let mut hangman = Hangman::new("ox");
+        for letter in "abcdef".chars() {
+            assert!(!hangman.is_over());
+            assert_eq!(hangman.guess(letter), Guess::Miss);
+        }
+        assert!(hangman.is_lost() && !hangman.is_won());
+    }
+
+    #[test]
+    fn players_enter_plain_words() {
+        assert!(is_playable("Gallows"));
+        assert!(!is_playable("a"));
+        assert!(!is_playable("two words"));
+        assert!(!is_playable("café"));
+    }
+}
@@ -0,0 +1,600 @@
+use bevy::prelude::*;
+use game::{Guess, Hangman, MAX_MISSES};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use words::{Difficulty, Theme};
+
+pub mod game;
+pub mod words;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.93, 0.9, 0.82);
+const DRAWING_COLOR: Color = Color::rgb(0.15, 0.12, 0.1);
+const GAP: f32 = 6.0;
+const HIT_COLOR: Color = Color::rgb(0.3, 0.6, 0.3);
+const KEY_COLOR: Color = Color::rgb(0.35, 0.32, 0.3);
+const KEY_ROWS: [&str; 2] = ["abcdefghijklm", "nopqrstuvwxyz"];
+const KEY_SIZE: f32 = 44.0;
+const KEYBOARD_TOP: f32 = -165.0;
+const LINE_WIDTH: f32 = 6.0;
+const MISS_COLOR: Color = Color::rgb(0.75, 0.7, 0.62);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const STATS_FILE: &str = "stats";
+const TEXT_COLOR: Color = Color::rgb(0.15, 0.12, 0.1);
+const WHITE: Color = Color::rgb(0.97, 0.97, 0.97);
+/// Where the word is shown, right of the gallows.
+const WORD_CENTER: Vec2 = bevy::math::const_vec2!([130.0, 60.0]);
+
+pub const WINDOW_HEIGHT: f32 = 600.0;
+pub const WINDOW_WIDTH: f32 = 800.0;
+
+/// The gallows, the word and the keyboard, rebuilt whenever the round changes.
+#[derive(Component)]
+struct Board;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    /// The first player types the word for the second one.
+    Entering,
+    Guessing,
+    Over,
+}
+
+/// The choices made in the menu and the round being played.
+struct Game {
+    difficulty: Difficulty,
+    /// The word typed so far in two player mode.
+    entry: String,
+    hangman: Hangman,
+    theme: usize,
+    themes: Vec<Theme>,
+    two_players: bool,
+}
+
+impl Game {
+    fn theme_name(&self) -> &'static str {
+        self.themes[self.theme].name
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Component)]
+struct OverlayText;
+
+struct Sounds {
+    hit: Handle<AudioSource>,
+    lost: Handle<AudioSource>,
+    miss: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+/// Results of the one player rounds, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Stats {
+    best_streak: u32,
+    lost: u32,
+    streak: u32,
+    won: u32,
+}
+
+impl Stats {
+    fn record(&mut self, won: bool) {
+        if won {
+            self.won += 1;
+            self.streak += 1;
+            self.best_streak = self.best_streak.max(self.streak);
+        } else {
+            self.lost += 1;
+            self.streak = 0;
+        }
+    }
+
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("hangman", STATS_FILE, self) {
+            eprintln!("could not save stats: {}", error);
+        }
+    }
+}
+
+pub struct HangmanPlugin;
+
+impl Plugin for HangmanPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game {
+                difficulty: Difficulty::Easy,
+                entry: String::new(),
+                hangman: Hangman::new(""),
+                theme: 0,
+                themes: words::themes(),
+                two_players: false,
+            })
+            .insert_resource(game_persistence::load_or_default::<Stats>(
+                "hangman", STATS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu).with_system(despawn_all::<MenuText>),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Entering).with_system(entry_input))
+            .add_system_set(SystemSet::on_update(GameState::Guessing).with_system(guess_input))
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(round_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Over).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(board_render)
+            .add_system(hud_update)
+            .add_system(menu_render);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Hangman".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Draws the gallows with one more body part per miss, the word so far and the letters used.
+fn board_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    board: Query<Entity, With<Board>>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    for entity in board.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if matches!(state.current(), GameState::Menu | GameState::Entering) {
+        return;
+    }
+    let hangman = &game.hangman;
+    let gallows = [
+        ((-340.0, -110.0), (-140.0, -110.0)),
+        ((-300.0, -110.0), (-300.0, 200.0)),
+        ((-300.0, 200.0), (-180.0, 200.0)),
+        ((-300.0, 160.0), (-260.0, 200.0)),
+        ((-180.0, 200.0), (-180.0, 160.0)),
+    ];
+    // Head, body, arms and legs, in the order they appear.
+    let body = [
+        ((-180.0, 105.0), (-180.0, 40.0)),
+        ((-180.0, 95.0), (-215.0, 60.0)),
+        ((-180.0, 95.0), (-145.0, 60.0)),
+        ((-180.0, 40.0), (-210.0, -20.0)),
+        ((-180.0, 40.0), (-150.0, -20.0)),
+    ];
+    let parts = gallows
+        .iter()
+        .chain(body.iter().take(hangman.misses().saturating_sub(1)));
+    for ((x1, y1), (x2, y2)) in parts {
+        commands
+            .spawn_bundle(line(Vec2::new(*x1, *y1), Vec2::new(*x2, *y2)))
+            .insert(Board);
+    }
+    if hangman.misses() > 0 {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                "\u{25CB}",
+                76.0,
+                DRAWING_COLOR,
+                Vec3::new(-180.0, 134.0, 1.0),
+            ))
+            .insert(Board);
+    }
+
+    let word = if hangman.is_over() {
+        // Show the whole word at the end, without the underscores.
+        hangman
+            .word()
+            .to_uppercase()
+            .chars()
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" ")
+    } else {
+        hangman.revealed()
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            word,
+            40.0,
+            TEXT_COLOR,
+            WORD_CENTER.extend(1.0),
+        ))
+        .insert(Board);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("Misses {}/{}", hangman.misses(), MAX_MISSES),
+            22.0,
+            TEXT_COLOR,
+            (WORD_CENTER - Vec2::new(0.0, 70.0)).extend(1.0),
+        ))
+        .insert(Board);
+
+    for (letter, center) in keys() {
+        let color = match hangman.guessed(letter) {
+            Some(true) => HIT_COLOR,
+            Some(false) => MISS_COLOR,
+            None => KEY_COLOR,
+        };
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(KEY_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center.extend(0.0)),
+                ..Default::default()
+            })
+            .insert(Board);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                letter.to_ascii_uppercase().to_string(),
+                22.0,
+                WHITE,
+                center.extend(1.0),
+            ))
+            .insert(Board);
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// The first player types a word while the second one looks away; it is shown as stars.
+fn entry_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for event in characters.iter() {
+        if event.char.is_ascii_alphabetic() && game.entry.len() < 20 {
+            game.entry.push(event.char.to_ascii_lowercase());
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        game.entry.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Return) && game::is_playable(&game.entry) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let word = std::mem::take(&mut game.entry);
+        game.hangman = Hangman::new(&word);
+        state.set(GameState::Guessing).unwrap();
+    }
+}
+
+/// Guesses come from typing or from clicking the on-screen keyboard.
+fn guess_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let mut letters: Vec<char> = characters.iter().map(|event| event.char).collect();
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+            let clicked = keys().into_iter().find(|(_, center)| {
+                let offset = (cursor - *center).abs();
+                offset.x <= KEY_SIZE / 2.0 && offset.y <= KEY_SIZE / 2.0
+            });
+            letters.extend(clicked.map(|(letter, _)| letter));
+        }
+    }
+    for letter in letters {
+        match game.hangman.guess(letter) {
+            Guess::Hit => audio.play(sounds.hit.clone()),
+            Guess::Miss => audio.play(sounds.miss.clone()),
+            Guess::Ignored => continue,
+        };
+        if game.hangman.is_over() {
+            state.set(GameState::Over).unwrap();
+            return;
+        }
+    }
+}
+
+fn hud_update(
+    game: Res<Game>,
+    stats: Res<Stats>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    if !game.is_changed() && !stats.is_changed() && !state.is_changed() {
+        return;
+    }
+    let value = match state.current() {
+        GameState::Menu => String::new(),
+        _ if game.two_players => "TWO PLAYERS".to_string(),
+        _ => format!(
+            "{} - {}   WON {}   LOST {}   STREAK {}",
+            game.theme_name().to_uppercase(),
+            game.difficulty.name().to_uppercase(),
+            stats.won,
+            stats.lost,
+            stats.streak,
+        ),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// The letter keys with their centers.
+fn keys() -> Vec<(char, Vec2)> {
+    let mut keys = Vec::new();
+    for (row, letters) in KEY_ROWS.iter().enumerate() {
+        let width = letters.len() as f32 * (KEY_SIZE + GAP) - GAP;
+        for (column, letter) in letters.chars().enumerate() {
+            keys.push((
+                letter,
+                Vec2::new(
+                    -width / 2.0 + KEY_SIZE / 2.0 + column as f32 * (KEY_SIZE + GAP),
+                    KEYBOARD_TOP - KEY_SIZE / 2.0 - row as f32 * (KEY_SIZE + GAP),
+                ),
+            ));
+        }
+    }
+    keys
+}
+
+/// A stroke of the drawing from one point to another.
+fn line(from: Vec2, to: Vec2) -> SpriteBundle {
+    let offset = to - from;
+    let mut transform = Transform::from_translation(((from + to) / 2.0).extend(0.0));
+    transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+    SpriteBundle {
+        sprite: Sprite {
+            color: DRAWING_COLOR,
+            custom_size: Some(Vec2::new(offset.length() + LINE_WIDTH, LINE_WIDTH)),
+            ..Default::default()
+        },
+        transform,
+        ..Default::default()
+    }
+}
+
+/// Left/Right picks the theme, Up/Down the word length; Enter starts one player, 2 two players.
+fn menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let themes = game.themes.len();
+    if menu.just_pressed(Navigate::Left) || keyboard_input.just_pressed(KeyCode::A) {
+        game.theme = (game.theme + themes - 1) % themes;
+    }
+    if menu.just_pressed(Navigate::Right) || keyboard_input.just_pressed(KeyCode::D) {
+        game.theme = (game.theme + 1) % themes;
+    }
+    let difficulty = Difficulty::ALL
+        .iter()
+        .position(|difficulty| *difficulty == game.difficulty)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) || keyboard_input.just_pressed(KeyCode::W) {
+        game.difficulty = Difficulty::ALL[difficulty.saturating_sub(1)];
+    }
+    if menu.just_pressed(Navigate::Down) || keyboard_input.just_pressed(KeyCode::S) {
+        game.difficulty = Difficulty::ALL[(difficulty + 1).min(Difficulty::ALL.len() - 1)];
+    }
+    if menu.just_pressed(Navigate::Confirm) {
+        start_round(&mut game, false, &mut state);
+    } else if keyboard_input.any_just_pressed([KeyCode::Key2, KeyCode::Numpad2]) {
+        start_round(&mut game, true, &mut state);
+    }
+}
+
+fn menu_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    stats: Res<Stats>,
+    state: Res<State<GameState>>,
+    texts: Query<Entity, With<MenuText>>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    for entity in texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let (shortest, longest) = game.difficulty.lengths();
+    let lengths = if longest == usize::MAX {
+        format!("{}+ letters", shortest)
+    } else {
+        format!("{}-{} letters", shortest, longest)
+    };
+    let value = match state.current() {
+        GameState::Menu => format!(
+            "HANGMAN\n\n\
+             Theme  < {} >\n\
+             Words  < {}, {} >\n\n\
+             Left/Right: theme   Up/Down: word length\n\n\
+             Enter: one player   2: two players\n\n\n\
+             Won {}   Lost {}   Streak {}   Best streak {}",
+            game.theme_name(),
+            game.difficulty.name(),
+            lengths,
+            stats.won,
+            stats.lost,
+            stats.streak,
+            stats.best_streak,
+        ),
+        GameState::Entering => format!(
+            "Player 1, type a word for player 2 to guess\n\n{}\n\n\
+             Letters only   Backspace: erase   Enter: start   Esc: menu",
+            if game.entry.is_empty() {
+                "_".to_string()
+            } else {
+                "*".repeat(game.entry.len())
+            },
+        ),
+        _ => return,
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            value,
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 1.0),
+        ))
+        .insert(MenuText);
+}
+
+/// Enter plays another round with the same settings, Escape goes back to the menu.
+fn over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        let two_players = game.two_players;
+        start_round(&mut game, two_players, &mut state);
+    }
+}
+
+fn round_over(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    mut stats: ResMut<Stats>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let won = game.hangman.is_won();
+    let sound = if won { &sounds.won } else { &sounds.lost };
+    audio.play(sound.clone());
+    if !game.two_players {
+        stats.record(won);
+        stats.save();
+    }
+    let result = match (won, game.two_players) {
+        (true, false) => "You got it!".to_string(),
+        (true, true) => "Player 2 wins!".to_string(),
+        (false, false) => format!(
+            "Hanged! The word was {}",
+            game.hangman.word().to_uppercase()
+        ),
+        (false, true) => format!(
+            "Player 1 wins! The word was {}",
+            game.hangman.word().to_uppercase()
+        ),
+    };
+    let center = Vec3::new(0.0, 250.0, 90.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, 90.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center),
+            ..Default::default()
+        })
+        .insert(OverlayText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("{}\nEnter: play again   Esc: menu", result),
+            24.0,
+            WHITE,
+            center + Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(OverlayText);
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+
+    commands.insert_resource(Sounds {
+        hit: audio_sources.add(
+            Tone::new(Waveform::Sine, 880.0, 0.1)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        lost: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 330.0, 0.25),
+            Tone::new(Waveform::Triangle, 294.0, 0.25),
+            Tone::new(Waveform::Triangle, 220.0, 0.6),
+        ])),
+        miss: audio_sources.add(
+            Tone::new(Waveform::Square, 180.0, 0.15)
+                .slide(120.0)
+                .volume(0.2)
+                .into(),
+        ),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35),
+        ])),
+    });
+}
+
+/// A one player round gets a random word of the chosen theme and length; two players first enter one.
+fn start_round(game: &mut Game, two_players: bool, state: &mut State<GameState>) {
+    game.two_players = two_players;
+    if two_players {
+        game.entry.clear();
+        state.set(GameState::Entering).unwrap();
+    } else {
+        let word = game.themes[game.theme].pick(game.difficulty, &mut thread_rng());
+        game.hangman = Hangman::new(word);
+        state.set(GameState::Guessing).unwrap();
+    }
+}
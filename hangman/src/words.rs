@@ -0,0 +1,125 @@
+//! Themed word lists and picking a word of the chosen difficulty.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+const THEMES: [(&str, &str); 3] = [
+    ("Animals", include_str!("../assets/themes/animals.txt")),
+    ("Countries", include_str!("../assets/themes/countries.txt")),
+    ("Food", include_str!("../assets/themes/food.txt")),
+];
+
+/// Longer words are harder to finish, as every letter has to be found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Self; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    pub fn fits(self, word: &str) -> bool {
+        let (shortest, longest) = self.lengths();
+        (shortest..=longest).contains(&word.len())
+    }
+
+    /// The shortest and longest word length, inclusive.
+    pub fn lengths(self) -> (usize, usize) {
+        match self {
+            Self::Easy => (3, 5),
+            Self::Medium => (6, 8),
+            Self::Hard => (9, usize::MAX),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+pub struct Theme {
+    pub name: &'static str,
+    words: Vec<String>,
+}
+
+impl Theme {
+    /// One word per line; blank lines are skipped.
+    pub fn parse(name: &'static str, text: &str) -> Self {
+        Self {
+            name,
+            words: text
+                .lines()
+                .map(|line| line.trim().to_lowercase())
+                .filter(|line| !line.is_empty())
+                .collect(),
+        }
+    }
+
+    /// A random word of the theme that fits `difficulty`, any word if none fits.
+    pub fn pick(&self, difficulty: Difficulty, rng: &mut impl Rng) -> &str {
+        let fitting: Vec<&String> = self
+            .words
+            .iter()
+            .filter(|word| difficulty.fits(word))
+            .collect();
+        let pool = if fitting.is_empty() {
+            self.words.iter().collect()
+        } else {
+            fitting
+        };
+        pool.choose(rng).expect("themes are not empty")
+    }
+}
+
+pub fn themes() -> Vec<Theme> {
+    THEMES
+        .iter()
+        .map(|(name, text)| Theme::parse(name, text))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::is_playable;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn picks_words_of_the_difficulty() {
+        let theme = Theme::parse("Test", "cat\n\nzebra\nkangaroo\nhippopotamus\n");
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            assert!(["cat", "zebra"].contains(&theme.pick(Difficulty::Easy, &mut rng)));
+            assert_eq!(theme.pick(Difficulty::Medium, &mut rng), "kangaroo");
+        }
+        let short = Theme::parse("Short", "ox\n");
+        assert_eq!(short.pick(Difficulty::Hard, &mut rng), "ox");
+    }
+
+    #[test]
+    fn bundled_themes_have_words_of_every_difficulty() {
+        for theme in themes() {
+            for difficulty in Difficulty::ALL {
+                assert!(
+                    theme
+                        .words
+                        .iter()
+                        .filter(|word| difficulty.fits(word))
+                        .count()
+                        >= 5,
+                    "{} {:?}",
+                    theme.name,
+                    difficulty
+                );
+            }
+            assert!(theme.words.iter().all(|word| is_playable(word)));
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! What the cannon's keys, buttons and stick do, rebindable through `game_input` on the controls
+//! screen.
+
+use bevy::prelude::*;
+use game_input::{Action, Binding, Sign};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum InvadersAction {
+    Fire,
+    Left,
+    /// Starts a new game after game over.
+    Restart,
+    Right,
+}
+
+impl Action for InvadersAction {
+    const ALL: &'static [Self] = &[Self::Left, Self::Right, Self::Fire, Self::Restart];
+
+    fn default_bindings(self) -> Vec<Binding> {
+        match self {
+            Self::Fire => vec![
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::South),
+            ],
+            Self::Left => vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Negative),
+            ],
+            Self::Restart => vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            Self::Right => vec![
+                Binding::Key(KeyCode::Right),
+                Binding::Key(KeyCode::D),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Positive),
+            ],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Fire => "Fire",
+            Self::Left => "Left",
+            Self::Restart => "New game",
+            Self::Right => "Right",
+        }
+    }
+}
@@ -0,0 +1,849 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_input::{ActionState, ControlsScreen, InputPlugin};
+use rand::{random, seq::SliceRandom, thread_rng};
+use std::time::Duration;
+
+pub mod controls;
+
+pub use controls::InvadersAction;
+
+pub const ARENA_HEIGHT: f32 = 720.0;
+pub const ARENA_WIDTH: f32 = 640.0;
+const ALIEN_BULLET_COLOR: Color = Color::rgb(1.0, 0.9, 0.4);
+const ALIEN_BULLET_SPEED: f32 = 220.0;
+const ALIEN_COLUMNS: usize = 11;
+const ALIEN_DROP: f32 = 16.0;
+const ALIEN_FIRE_INTERVAL: f32 = 0.9;
+const ALIEN_ROWS: usize = 5;
+const ALIEN_SIZE: Vec2 = const_vec2!([30.0, 20.0]);
+const ALIEN_SPACING: Vec2 = const_vec2!([44.0, 36.0]);
+const ALIEN_STEP: f32 = 8.0;
+const ALIEN_TOP: f32 = 240.0;
+const BACKGROUND_COLOR: Color = Color::rgb(0.0, 0.0, 0.0);
+const BUNKER_BLOCK: f32 = 6.0;
+const BUNKER_COLOR: Color = Color::rgb(0.2, 0.9, 0.2);
+const BUNKER_COUNT: usize = 4;
+const BUNKER_Y: f32 = -220.0;
+const FORMATION_FASTEST_STEP: f32 = 0.03;
+const FORMATION_SLOWEST_STEP: f32 = 0.7;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const PLAYER_BULLET_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+const PLAYER_BULLET_SPEED: f32 = 520.0;
+const PLAYER_COLOR: Color = Color::rgb(0.3, 1.0, 0.3);
+const PLAYER_SIZE: Vec2 = const_vec2!([36.0, 16.0]);
+const PLAYER_SPEED: f32 = 260.0;
+const PLAYER_Y: f32 = -300.0;
+const STARTING_LIVES: u32 = 3;
+const UFO_COLOR: Color = Color::rgb(1.0, 0.2, 0.2);
+const UFO_SCORES: [u32; 4] = [50, 100, 150, 300];
+const UFO_SIZE: Vec2 = const_vec2!([44.0, 16.0]);
+const UFO_SPAWN_INTERVAL: f32 = 22.0;
+const UFO_SPEED: f32 = 120.0;
+const UFO_Y: f32 = 290.0;
+
+#[derive(Component)]
+struct Alien {
+    column: usize,
+    kind: AlienKind,
+}
+
+struct AlienFireTimer(Timer);
+
+#[derive(Clone, Copy)]
+enum AlienKind {
+    Crab,
+    Octopus,
+    Squid,
+}
+
+impl AlienKind {
+    fn color(self) -> Color {
+        match self {
+            Self::Crab => Color::rgb(0.3, 0.8, 1.0),
+            Self::Octopus => Color::rgb(1.0, 0.4, 1.0),
+            Self::Squid => Color::rgb(1.0, 1.0, 1.0),
+        }
+    }
+
+    fn for_row(row: usize) -> Self {
+        match row {
+            0 => Self::Squid,
+            1 | 2 => Self::Crab,
+            _ => Self::Octopus,
+        }
+    }
+
+    fn score(self) -> u32 {
+        match self {
+            Self::Crab => 20,
+            Self::Octopus => 10,
+            Self::Squid => 30,
+        }
+    }
+
+    fn width(self) -> f32 {
+        match self {
+            Self::Crab => ALIEN_SIZE.x * 0.85,
+            Self::Octopus => ALIEN_SIZE.x,
+            Self::Squid => ALIEN_SIZE.x * 0.65,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Bullet {
+    from_alien: bool,
+    speed: f32,
+}
+
+#[derive(Component)]
+struct BunkerBlock;
+
+/// The whole alien block moves as one: sideways in steps, dropping and turning at the edges.
+struct Formation {
+    direction: f32,
+    drop_next: bool,
+    note: usize,
+    step_timer: Timer,
+}
+
+#[derive(Default)]
+struct Game {
+    high_score: u32,
+    lives: u32,
+    score: u32,
+    wave: u32,
+}
+
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct Hitbox(Vec2);
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct Player;
+
+struct PlayerHitEvent;
+
+struct Sounds {
+    alien_death: Handle<AudioSource>,
+    march: [Handle<AudioSource>; 4],
+    player_death: Handle<AudioSource>,
+    shoot: Handle<AudioSource>,
+    ufo: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Movement,
+    Collision,
+}
+
+#[derive(Component)]
+struct Ufo {
+    direction: f32,
+    score: u32,
+}
+
+struct UfoTimer(Timer);
+
+pub struct InvadersPlugin;
+
+impl Plugin for InvadersPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::default())
+            .insert_resource(Formation {
+                direction: 1.0,
+                drop_next: false,
+                note: 0,
+                step_timer: Timer::from_seconds(FORMATION_SLOWEST_STEP, true),
+            })
+            .insert_resource(AlienFireTimer(Timer::from_seconds(
+                ALIEN_FIRE_INTERVAL,
+                true,
+            )))
+            .insert_resource(UfoTimer(Timer::from_seconds(UFO_SPAWN_INTERVAL, true)))
+            .add_plugin(HudPlugin)
+            .add_plugin(InputPlugin::<InvadersAction>::new("invaders"))
+            .add_state(GameState::Playing)
+            .add_event::<PlayerHitEvent>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_input.label(Step::Movement))
+                    .with_system(formation_movement.label(Step::Movement))
+                    .with_system(bullet_movement.label(Step::Movement))
+                    .with_system(ufo_movement.label(Step::Movement))
+                    .with_system(alien_fire.after(Step::Movement))
+                    .with_system(ufo_spawner.after(Step::Movement))
+                    .with_system(
+                        bullet_collisions
+                            .label(Step::Collision)
+                            .after(Step::Movement),
+                    )
+                    .with_system(alien_bunker_collisions.after(Step::Movement))
+                    .with_system(alien_landing.after(Step::Movement))
+                    .with_system(player_hit.after(Step::Collision))
+                    .with_system(next_wave.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(restart_input))
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Space Invaders".to_string(),
+        width: ARENA_WIDTH,
+        height: ARENA_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn alien_bunker_collisions(
+    mut commands: Commands,
+    aliens: Query<(&Transform, &Hitbox), With<Alien>>,
+    blocks: Query<(Entity, &Transform), With<BunkerBlock>>,
+) {
+    for (alien_transform, alien_hitbox) in aliens.iter() {
+        for (block, block_transform) in blocks.iter() {
+            if overlaps(
+                alien_transform.translation,
+                alien_hitbox.0,
+                block_transform.translation,
+                Vec2::splat(BUNKER_BLOCK),
+            ) {
+                commands.entity(block).despawn();
+            }
+        }
+    }
+}
+
+fn alien_fire(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut timer: ResMut<AlienFireTimer>,
+    aliens: Query<(&Alien, &Transform)>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    // Only the lowest alien of a column may shoot, so shots never pass through the formation.
+    let mut shooters: Vec<Vec3> = Vec::new();
+    for column in 0..ALIEN_COLUMNS {
+        let lowest = aliens
+            .iter()
+            .filter(|(alien, _)| alien.column == column)
+            .map(|(_, transform)| transform.translation)
+            .min_by(|a, b| a.y.partial_cmp(&b.y).unwrap());
+        if let Some(translation) = lowest {
+            shooters.push(translation);
+        }
+    }
+    if let Some(translation) = shooters.choose(&mut thread_rng()) {
+        bullet_spawn(
+            &mut commands,
+            *translation - Vec3::new(0.0, ALIEN_SIZE.y / 2.0, 0.0),
+            true,
+        );
+    }
+}
+
+fn alien_landing(
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    aliens: Query<&Transform, With<Alien>>,
+) {
+    if aliens
+        .iter()
+        .any(|transform| transform.translation.y - ALIEN_SIZE.y / 2.0 <= PLAYER_Y + PLAYER_SIZE.y)
+    {
+        game.lives = 0;
+        // A last life lost to a bullet in the same frame may already have queued the transition.
+        let _ = state.set(GameState::GameOver);
+    }
+}
+
+fn aliens_spawn(commands: &mut Commands, wave: u32) {
+    let top = ALIEN_TOP - (wave.saturating_sub(1).min(6) as f32) * ALIEN_DROP;
+    let left = -(ALIEN_COLUMNS as f32 - 1.0) * ALIEN_SPACING.x / 2.0;
+    for row in 0..ALIEN_ROWS {
+        let kind = AlienKind::for_row(row);
+        for column in 0..ALIEN_COLUMNS {
+            let size = Vec2::new(kind.width(), ALIEN_SIZE.y * 0.7);
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: kind.color(),
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(
+                        left + column as f32 * ALIEN_SPACING.x,
+                        top - row as f32 * ALIEN_SPACING.y,
+                        0.0,
+                    ),
+                    ..Default::default()
+                })
+                .insert(Alien { column, kind })
+                .insert(Hitbox(Vec2::new(kind.width(), ALIEN_SIZE.y)))
+                .with_children(|parent| {
+                    for side in [-1.0, 1.0] {
+                        parent.spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                color: kind.color(),
+                                custom_size: Some(Vec2::new(4.0, ALIEN_SIZE.y * 0.4)),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(
+                                side * kind.width() * 0.3,
+                                -ALIEN_SIZE.y * 0.45,
+                                0.0,
+                            ),
+                            ..Default::default()
+                        });
+                    }
+                });
+        }
+    }
+}
+
+fn bullet_collisions(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut hit_writer: EventWriter<PlayerHitEvent>,
+    bullets: Query<(Entity, &Bullet, &Transform)>,
+    aliens: Query<(Entity, &Alien, &Transform, &Hitbox)>,
+    blocks: Query<(Entity, &Transform), With<BunkerBlock>>,
+    ufos: Query<(Entity, &Ufo, &Transform)>,
+    players: Query<&Transform, With<Player>>,
+) {
+    let mut destroyed: Vec<Entity> = Vec::new();
+    let bullet_size = Vec2::new(3.0, 12.0);
+    for (bullet_entity, bullet, bullet_transform) in bullets.iter() {
+        let position = bullet_transform.translation;
+        let mut hit = blocks
+            .iter()
+            .find(|(block, transform)| {
+                !destroyed.contains(block)
+                    && overlaps(
+                        position,
+                        bullet_size,
+                        transform.translation,
+                        Vec2::splat(BUNKER_BLOCK),
+                    )
+            })
+            .map(|(block, _)| block);
+        if bullet.from_alien {
+            if hit.is_none()
+                && players.iter().any(|transform| {
+                    overlaps(position, bullet_size, transform.translation, PLAYER_SIZE)
+                })
+            {
+                hit_writer.send(PlayerHitEvent);
+                destroyed.push(bullet_entity);
+            }
+        } else {
+            if hit.is_none() {
+                hit = aliens
+                    .iter()
+                    .find(|(alien, _, transform, hitbox)| {
+                        !destroyed.contains(alien)
+                            && overlaps(position, bullet_size, transform.translation, hitbox.0)
+                    })
+                    .map(|(entity, alien, _, _)| {
+                        game.score += alien.kind.score();
+                        audio.play(sounds.alien_death.clone());
+                        entity
+                    });
+            }
+            if hit.is_none() {
+                hit = ufos
+                    .iter()
+                    .find(|(_, _, transform)| {
+                        overlaps(position, bullet_size, transform.translation, UFO_SIZE)
+                    })
+                    .map(|(entity, ufo, _)| {
+                        game.score += ufo.score;
+                        audio.play(sounds.alien_death.clone());
+                        entity
+                    });
+            }
+            if hit.is_none() {
+                // Shots can cancel each other out mid-air.
+                hit = bullets
+                    .iter()
+                    .find(|(entity, other, transform)| {
+                        other.from_alien
+                            && !destroyed.contains(entity)
+                            && overlaps(position, bullet_size, transform.translation, bullet_size)
+                    })
+                    .map(|(entity, _, _)| entity);
+            }
+        }
+        if let Some(entity) = hit {
+            destroyed.push(entity);
+            destroyed.push(bullet_entity);
+        }
+    }
+    let mut despawned: Vec<Entity> = Vec::new();
+    for entity in destroyed {
+        if !despawned.contains(&entity) {
+            commands.entity(entity).despawn_recursive();
+            despawned.push(entity);
+        }
+    }
+    game.high_score = game.high_score.max(game.score);
+}
+
+fn bullet_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut bullets: Query<(Entity, &Bullet, &mut Transform)>,
+) {
+    for (entity, bullet, mut transform) in bullets.iter_mut() {
+        transform.translation.y += bullet.speed * time.delta_seconds();
+        if transform.translation.y.abs() > ARENA_HEIGHT / 2.0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn bullet_spawn(commands: &mut Commands, translation: Vec3, from_alien: bool) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: if from_alien {
+                    ALIEN_BULLET_COLOR
+                } else {
+                    PLAYER_BULLET_COLOR
+                },
+                custom_size: Some(Vec2::new(3.0, 12.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        })
+        .insert(Bullet {
+            from_alien,
+            speed: if from_alien {
+                -ALIEN_BULLET_SPEED
+            } else {
+                PLAYER_BULLET_SPEED
+            },
+        });
+}
+
+fn bunkers_spawn(commands: &mut Commands) {
+    // Each bunker is a block of small destructible cells with a notch cut out at the bottom.
+    let shape = [
+        "..######..",
+        ".########.",
+        "##########",
+        "##########",
+        "##########",
+        "###....###",
+        "##......##",
+    ];
+    let spacing = ARENA_WIDTH / BUNKER_COUNT as f32;
+    for bunker in 0..BUNKER_COUNT {
+        let center_x = -ARENA_WIDTH / 2.0 + spacing * (bunker as f32 + 0.5);
+        for (row, line) in shape.iter().enumerate() {
+            for (column, cell) in line.chars().enumerate() {
+                if cell != '#' {
+                    continue;
+                }
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: BUNKER_COLOR,
+                            custom_size: Some(Vec2::splat(BUNKER_BLOCK)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            center_x + (column as f32 - line.len() as f32 / 2.0) * BUNKER_BLOCK,
+                            BUNKER_Y - row as f32 * BUNKER_BLOCK,
+                            0.0,
+                        ),
+                        ..Default::default()
+                    })
+                    .insert(BunkerBlock);
+            }
+        }
+    }
+}
+
+fn formation_movement(
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut formation: ResMut<Formation>,
+    mut aliens: Query<&mut Transform, With<Alien>>,
+) {
+    if !formation.step_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    let alive = aliens.iter().count();
+    if alive == 0 {
+        return;
+    }
+    formation
+        .step_timer
+        .set_duration(Duration::from_secs_f32(march_step(alive)));
+    let direction = formation.direction;
+    let drop = formation.drop_next;
+    let mut at_edge = false;
+    for mut transform in aliens.iter_mut() {
+        if drop {
+            transform.translation.y -= ALIEN_DROP;
+        } else {
+            transform.translation.x += direction * ALIEN_STEP;
+        }
+        transform.scale.x = if transform.scale.x < 1.0 { 1.0 } else { 0.85 };
+        let next_x = transform.translation.x + direction * ALIEN_STEP;
+        if next_x.abs() > ARENA_WIDTH / 2.0 - ALIEN_SIZE.x {
+            at_edge = true;
+        }
+    }
+    if drop {
+        formation.direction = -direction;
+        formation.drop_next = false;
+    } else {
+        formation.drop_next = at_edge;
+    }
+    audio.play(sounds.march[formation.note].clone());
+    formation.note = (formation.note + 1) % sounds.march.len();
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "GAME OVER\nScore {}\nPress Enter, or C for the controls",
+                game.score
+            ),
+            40.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(GameOverText);
+}
+
+fn hud_update(game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if game.is_changed() {
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = format!(
+                "SCORE {:04}  HI-SCORE {:04}  LIVES {}  WAVE {}",
+                game.score, game.high_score, game.lives, game.wave
+            );
+        }
+    }
+}
+
+/// Seconds between the formation's steps with `alive` aliens left: the fewer are left, the
+/// faster the survivors march.
+fn march_step(alive: usize) -> f32 {
+    let fraction = alive as f32 / (ALIEN_ROWS * ALIEN_COLUMNS) as f32;
+    FORMATION_FASTEST_STEP + (FORMATION_SLOWEST_STEP - FORMATION_FASTEST_STEP) * fraction
+}
+
+fn new_game(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    leftovers: Query<
+        Entity,
+        Or<(
+            With<Alien>,
+            With<Bullet>,
+            With<BunkerBlock>,
+            With<GameOverText>,
+            With<Player>,
+            With<Ufo>,
+        )>,
+    >,
+) {
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    game.lives = STARTING_LIVES;
+    game.score = 0;
+    game.wave = 0;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: PLAYER_COLOR,
+                custom_size: Some(PLAYER_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, PLAYER_Y, 0.0),
+            ..Default::default()
+        })
+        .insert(Player)
+        .with_children(|parent| {
+            parent.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: PLAYER_COLOR,
+                    custom_size: Some(Vec2::new(6.0, 10.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, PLAYER_SIZE.y * 0.75, 0.0),
+                ..Default::default()
+            });
+        });
+}
+
+fn next_wave(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut formation: ResMut<Formation>,
+    aliens: Query<Entity, With<Alien>>,
+    leftovers: Query<Entity, Or<(With<Bullet>, With<BunkerBlock>)>>,
+) {
+    if !aliens.is_empty() {
+        return;
+    }
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn();
+    }
+    game.wave += 1;
+    formation.direction = 1.0;
+    formation.drop_next = false;
+    formation.step_timer = Timer::from_seconds(FORMATION_SLOWEST_STEP, true);
+    aliens_spawn(&mut commands, game.wave);
+    bunkers_spawn(&mut commands);
+}
+
+fn overlaps(a: Vec3, a_size: Vec2, b: Vec3, b_size: Vec2) -> bool {
+    let distance = (a - b).truncate().abs();
+    let reach = (a_size + b_size) / 2.0;
+    distance.x < reach.x && distance.y < reach.y
+}
+
+fn player_hit(
+    mut commands: Commands,
+    mut hit_reader: EventReader<PlayerHitEvent>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    bullets: Query<Entity, With<Bullet>>,
+    mut players: Query<&mut Transform, With<Player>>,
+) {
+    if hit_reader.iter().next().is_none() {
+        return;
+    }
+    audio.play(sounds.player_death.clone());
+    for entity in bullets.iter() {
+        commands.entity(entity).despawn();
+    }
+    game.lives = game.lives.saturating_sub(1);
+    if game.lives == 0 {
+        let _ = state.set(GameState::GameOver);
+    } else {
+        for mut transform in players.iter_mut() {
+            transform.translation.x = 0.0;
+        }
+    }
+}
+
+fn player_input(
+    mut commands: Commands,
+    actions: Res<ActionState<InvadersAction>>,
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    bullets: Query<&Bullet>,
+    mut players: Query<&mut Transform, With<Player>>,
+) {
+    if let Some(mut transform) = players.iter_mut().next() {
+        let mut direction = 0.0;
+        if actions.pressed(InvadersAction::Left) {
+            direction -= 1.0;
+        }
+        if actions.pressed(InvadersAction::Right) {
+            direction += 1.0;
+        }
+        let limit = ARENA_WIDTH / 2.0 - PLAYER_SIZE.x / 2.0;
+        transform.translation.x = (transform.translation.x
+            + direction * PLAYER_SPEED * time.delta_seconds())
+        .max(-limit)
+        .min(limit);
+        // Like the original cabinet, only one player shot can be on screen at a time.
+        let shot_in_flight = bullets.iter().any(|bullet| !bullet.from_alien);
+        if actions.just_pressed(InvadersAction::Fire) && !shot_in_flight {
+            bullet_spawn(
+                &mut commands,
+                transform.translation + Vec3::new(0.0, PLAYER_SIZE.y, 0.0),
+                false,
+            );
+            audio.play(sounds.shoot.clone());
+        }
+    }
+}
+
+/// The restart action starts a new game and C opens the controls screen, which has the keys to
+/// itself while it is open.
+fn restart_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<ActionState<InvadersAction>>,
+    mut controls: ResMut<ControlsScreen>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if controls.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::C) {
+        controls.open();
+    } else if actions.just_pressed(InvadersAction::Restart) {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    let march = |frequency: f32| Tone::new(Waveform::Square, frequency, 0.08).volume(0.25);
+    commands.insert_resource(Sounds {
+        alien_death: audio_sources.add(Tone::new(Waveform::Noise, 0.0, 0.25).fade().into()),
+        march: [
+            audio_sources.add(march(98.0).into()),
+            audio_sources.add(march(87.0).into()),
+            audio_sources.add(march(78.0).into()),
+            audio_sources.add(march(73.0).into()),
+        ],
+        player_death: audio_sources.add(
+            Tone::new(Waveform::Saw, 400.0, 0.8)
+                .slide(60.0)
+                .fade()
+                .into(),
+        ),
+        shoot: audio_sources.add(
+            Tone::new(Waveform::Square, 1200.0, 0.12)
+                .slide(300.0)
+                .volume(0.2)
+                .into(),
+        ),
+        ufo: audio_sources.add(
+            Tone::new(Waveform::Sine, 700.0, 0.5)
+                .slide(900.0)
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+fn ufo_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut ufos: Query<(Entity, &Ufo, &mut Transform)>,
+) {
+    for (entity, ufo, mut transform) in ufos.iter_mut() {
+        transform.translation.x += ufo.direction * UFO_SPEED * time.delta_seconds();
+        if transform.translation.x.abs() > ARENA_WIDTH / 2.0 + UFO_SIZE.x {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn ufo_spawner(
+    mut commands: Commands,
+    time: Res<Time>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut timer: ResMut<UfoTimer>,
+) {
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let direction = if random::<bool>() { 1.0 } else { -1.0 };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: UFO_COLOR,
+                custom_size: Some(UFO_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(
+                -direction * (ARENA_WIDTH / 2.0 + UFO_SIZE.x / 2.0),
+                UFO_Y,
+                0.0,
+            ),
+            ..Default::default()
+        })
+        .insert(Ufo {
+            direction,
+            score: *UFO_SCORES.choose(&mut thread_rng()).unwrap(),
+        });
+    audio.play(sounds.ufo.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn higher_rows_are_worth_more() {
+        let scores: Vec<u32> = (0..ALIEN_ROWS)
+            .map(|row| AlienKind::for_row(row).score())
+            .collect();
+        assert_eq!(scores, vec![30, 20, 20, 10, 10]);
+    }
+
+    #[test]
+    fn formation_speeds_up_as_aliens_fall() {
+        let full = ALIEN_ROWS * ALIEN_COLUMNS;
+        assert!((march_step(full) - FORMATION_SLOWEST_STEP).abs() < 1e-6);
+        assert!(march_step(full / 2) < march_step(full));
+        assert!(march_step(1) < march_step(2));
+        assert!(march_step(1) > FORMATION_FASTEST_STEP);
+    }
+
+    #[test]
+    fn hitboxes_overlap_only_when_they_touch() {
+        let size = Vec2::new(10.0, 4.0);
+        assert!(overlaps(Vec3::ZERO, size, Vec3::new(9.0, 3.0, 5.0), size));
+        assert!(!overlaps(Vec3::ZERO, size, Vec3::new(10.0, 0.0, 0.0), size));
+        assert!(!overlaps(Vec3::ZERO, size, Vec3::new(0.0, -4.0, 0.0), size));
+        assert!(overlaps(
+            Vec3::ZERO,
+            Vec2::new(2.0, 2.0),
+            Vec3::new(0.0, 20.0, 0.0),
+            Vec2::new(2.0, 40.0)
+        ));
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+use invaders::InvadersPlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(invaders::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(InvadersPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,61 @@
+//! What the lander's keys, buttons and stick do, rebindable through `game_input` on the controls
+//! screen.
+
+use bevy::prelude::*;
+use game_input::{Action, Binding, Sign};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum LanderAction {
+    /// Flies on to a new surface after a landing, or starts a new game after a crash.
+    Continue,
+    Thrust,
+    TurnLeft,
+    TurnRight,
+}
+
+impl Action for LanderAction {
+    const ALL: &'static [Self] = &[
+        Self::TurnLeft,
+        Self::TurnRight,
+        Self::Thrust,
+        Self::Continue,
+    ];
+
+    fn default_bindings(self) -> Vec<Binding> {
+        match self {
+            Self::Continue => vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            Self::Thrust => vec![
+                Binding::Key(KeyCode::Up),
+                Binding::Key(KeyCode::W),
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::South),
+            ],
+            Self::TurnLeft => vec![
+                Binding::Key(KeyCode::Left),
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Negative),
+            ],
+            Self::TurnRight => vec![
+                Binding::Key(KeyCode::Right),
+                Binding::Key(KeyCode::D),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Positive),
+            ],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Continue => "Continue",
+            Self::Thrust => "Engine",
+            Self::TurnLeft => "Turn left",
+            Self::TurnRight => "Turn right",
+        }
+    }
+}
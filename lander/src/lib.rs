@@ -0,0 +1,449 @@
+use bevy::math::const_vec2;
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_input::{ActionState, ControlsScreen, InputPlugin};
+use rand::{thread_rng, Rng};
+use ship::{Controls, Lander, Outcome, FEET, MAX_ANGLE, MAX_HORIZONTAL_SPEED, MAX_VERTICAL_SPEED};
+use terrain::Terrain;
+
+pub mod controls;
+pub mod ship;
+pub mod terrain;
+
+pub use controls::LanderAction;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.0, 0.0, 0.0);
+/// Seconds between two bursts of engine noise while thrusting.
+const ENGINE_INTERVAL: f32 = 0.12;
+const FLAME_COLOR: Color = Color::rgb(1.0, 0.6, 0.15);
+const GROUND_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
+const LANDER_COLOR: Color = Color::rgb(0.9, 0.9, 0.95);
+const LINE_WIDTH: f32 = 2.0;
+const PAD_COLOR: Color = Color::rgb(0.3, 1.0, 0.4);
+const START: Vec2 = const_vec2!([-320.0, 240.0]);
+const START_VELOCITY: Vec2 = const_vec2!([45.0, 0.0]);
+const STAR_COLOR: Color = Color::rgb(0.5, 0.5, 0.55);
+const STARS: usize = 80;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const WARNING_COLOR: Color = Color::rgb(1.0, 0.35, 0.3);
+
+pub const WINDOW_HEIGHT: f32 = 600.0;
+pub const WINDOW_WIDTH: f32 = terrain::WIDTH;
+
+struct EngineSound(Timer);
+
+#[derive(Component)]
+struct Flame;
+
+struct Game {
+    /// Whether the engine fired this frame.
+    burning: bool,
+    lander: Lander,
+    landings: u32,
+    outcome: Option<Outcome>,
+    score: u32,
+    terrain: Terrain,
+}
+
+impl Game {
+    fn new() -> Self {
+        Self {
+            burning: false,
+            lander: Lander::new(START, START_VELOCITY, ship::FUEL),
+            landings: 0,
+            outcome: None,
+            score: 0,
+            terrain: Terrain::generate(&mut thread_rng()),
+        }
+    }
+
+    /// A new surface for the next landing, with the fuel that is left.
+    fn next_landing(&mut self) {
+        self.lander = Lander::new(START, START_VELOCITY, self.lander.fuel);
+        self.outcome = None;
+        self.terrain = Terrain::generate(&mut thread_rng());
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Flying,
+    /// Landed or crashed; the outcome is shown.
+    Down,
+}
+
+#[derive(Component)]
+struct Ground;
+
+/// One section per line of the HUD, so each line can turn red on its own.
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct LanderSprite;
+
+#[derive(Component)]
+struct MessageText;
+
+struct Sounds {
+    crash: Handle<AudioSource>,
+    engine: Handle<AudioSource>,
+    landed: Handle<AudioSource>,
+}
+
+pub struct LanderPlugin;
+
+impl Plugin for LanderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new())
+            .insert_resource(EngineSound(Timer::from_seconds(ENGINE_INTERVAL, true)))
+            .add_plugin(HudPlugin)
+            .add_plugin(InputPlugin::<LanderAction>::new("lander"))
+            .add_state(GameState::Flying)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Flying).with_system(ground_spawn))
+            .add_system_set(SystemSet::on_update(GameState::Flying).with_system(flight))
+            .add_system_set(SystemSet::on_enter(GameState::Down).with_system(touchdown))
+            .add_system_set(SystemSet::on_update(GameState::Down).with_system(continue_input))
+            .add_system(hud_update)
+            .add_system(lander_render);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Lunar Lander".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// After a landing Enter flies on to a new surface, after a crash it starts a new game. C opens
+/// the controls screen, which has the keys to itself while it is open.
+fn continue_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    actions: Res<ActionState<LanderAction>>,
+    mut controls: ResMut<ControlsScreen>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MessageText>>,
+) {
+    if controls.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::C) {
+        controls.open();
+        return;
+    }
+    if !actions.just_pressed(LanderAction::Continue) {
+        return;
+    }
+    match game.outcome {
+        Some(Outcome::Landed(_)) => game.next_landing(),
+        _ => *game = Game::new(),
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value.clear();
+    }
+    state.set(GameState::Flying).unwrap();
+}
+
+/// Left/Right or A/D turn the lander, Up, W or Space fire the engine, unless rebound.
+fn flight(
+    time: Res<Time>,
+    actions: Res<ActionState<LanderAction>>,
+    mut engine_sound: ResMut<EngineSound>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let mut controls = Controls {
+        rotate: 0.0,
+        thrust: actions.pressed(LanderAction::Thrust),
+    };
+    if actions.pressed(LanderAction::TurnLeft) {
+        controls.rotate -= 1.0;
+    }
+    if actions.pressed(LanderAction::TurnRight) {
+        controls.rotate += 1.0;
+    }
+    game.burning = game.lander.update(controls, time.delta_seconds());
+    if game.burning && engine_sound.0.tick(time.delta()).just_finished() {
+        audio.play(sounds.engine.clone());
+    }
+
+    // The sides of the window are walls.
+    let half_width = terrain::WIDTH / 2.0;
+    let lander = &mut game.lander;
+    if lander.position.x.abs() > half_width {
+        lander.position.x = lander.position.x.clamp(-half_width, half_width);
+        lander.velocity.x = 0.0;
+    }
+
+    if let Some(outcome) = game.lander.touchdown(&game.terrain) {
+        game.burning = false;
+        game.outcome = Some(outcome);
+        state.set(GameState::Down).unwrap();
+    }
+}
+
+/// Redraws the surface for a new landing: a line with the pads highlighted and their multipliers.
+fn ground_spawn(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    ground: Query<Entity, With<Ground>>,
+) {
+    for entity in ground.iter() {
+        commands.entity(entity).despawn();
+    }
+    for pair in game.terrain.points.windows(2) {
+        commands
+            .spawn_bundle(line(GROUND_COLOR, pair[0], pair[1], LINE_WIDTH))
+            .insert(Ground);
+    }
+    for pad in game.terrain.pads.iter() {
+        let left = Vec2::new(pad.left, pad.y);
+        let right = Vec2::new(pad.right, pad.y);
+        commands
+            .spawn_bundle(line(PAD_COLOR, left, right, 2.0 * LINE_WIDTH))
+            .insert(Ground);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                format!("x{}", pad.multiplier),
+                16.0,
+                PAD_COLOR,
+                Vec3::new((pad.left + pad.right) / 2.0, pad.y - 14.0, 1.0),
+            ))
+            .insert(Ground);
+    }
+}
+
+fn hud_update(game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if !game.is_changed() {
+        return;
+    }
+    let lander = &game.lander;
+    let horizontal = lander.velocity.x;
+    let vertical = lander.velocity.y;
+    let values = [
+        format!("SCORE {}   LANDINGS {}\n", game.score, game.landings),
+        format!("FUEL {:.0}\n", lander.fuel),
+        format!("ALTITUDE {:.0}\n", lander.altitude(&game.terrain).max(0.0)),
+        format!(
+            "HORIZONTAL SPEED {:.0} {}\n",
+            horizontal.abs(),
+            if horizontal < 0.0 { "<" } else { ">" }
+        ),
+        format!(
+            "VERTICAL SPEED {:.0} {}\n",
+            vertical.abs(),
+            if vertical < 0.0 { "v" } else { "^" }
+        ),
+        format!("ANGLE {:.0}", lander.angle.to_degrees().abs()),
+    ];
+    // Values a landing would not survive are shown in red.
+    let warnings = [
+        false,
+        lander.fuel < ship::FUEL_BURN * 3.0,
+        false,
+        horizontal.abs() > MAX_HORIZONTAL_SPEED,
+        -vertical > MAX_VERTICAL_SPEED,
+        lander.angle.abs() > MAX_ANGLE,
+    ];
+    for mut text in texts.iter_mut() {
+        for (index, section) in text.sections.iter_mut().enumerate() {
+            section.value = values[index].clone();
+            section.style.color = if warnings[index] {
+                WARNING_COLOR
+            } else {
+                TEXT_COLOR
+            };
+        }
+    }
+}
+
+fn lander_render(
+    game: Res<Game>,
+    mut landers: Query<&mut Transform, With<LanderSprite>>,
+    mut flames: Query<&mut Visibility, With<Flame>>,
+) {
+    for mut transform in landers.iter_mut() {
+        transform.translation = game.lander.position.extend(2.0);
+        transform.rotation = Quat::from_rotation_z(game.lander.angle);
+    }
+    for mut visibility in flames.iter_mut() {
+        visibility.is_visible = game.burning;
+    }
+}
+
+/// A straight stroke between two points.
+fn line(color: Color, from: Vec2, to: Vec2, width: f32) -> SpriteBundle {
+    let offset = to - from;
+    let mut transform = Transform::from_translation(((from + to) / 2.0).extend(0.0));
+    transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(offset.length() + width, width)),
+            ..Default::default()
+        },
+        transform,
+        ..Default::default()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    let mut rng = thread_rng();
+    for _ in 0..STARS {
+        let position = Vec2::new(
+            rng.gen_range(-WINDOW_WIDTH / 2.0, WINDOW_WIDTH / 2.0),
+            rng.gen_range(terrain::TOP, WINDOW_HEIGHT / 2.0),
+        );
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: STAR_COLOR,
+                custom_size: Some(Vec2::splat(2.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(position.extend(-1.0)),
+            ..Default::default()
+        });
+    }
+
+    // The lander: a cabin on a descent stage, two legs and the flame below.
+    let parts = [
+        (Vec2::new(0.0, 4.0), Vec2::new(14.0, 12.0)),
+        (Vec2::new(0.0, -5.0), Vec2::new(22.0, 6.0)),
+        (FEET[0] + Vec2::new(2.0, 3.0), Vec2::new(2.0, 8.0)),
+        (FEET[1] + Vec2::new(-2.0, 3.0), Vec2::new(2.0, 8.0)),
+        (FEET[0], Vec2::new(6.0, 2.0)),
+        (FEET[1], Vec2::new(6.0, 2.0)),
+    ];
+    commands
+        .spawn_bundle((
+            Transform::from_translation(START.extend(2.0)),
+            GlobalTransform::default(),
+        ))
+        .insert(LanderSprite)
+        .with_children(|lander| {
+            for (center, size) in parts {
+                lander.spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: LANDER_COLOR,
+                        custom_size: Some(size),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(center.extend(0.0)),
+                    ..Default::default()
+                });
+            }
+            lander
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: FLAME_COLOR,
+                        custom_size: Some(Vec2::new(6.0, 12.0)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0.0, -14.0, -0.1),
+                    ..Default::default()
+                })
+                .insert(Flame);
+        });
+
+    let hud_style = font.style(18.0, TEXT_COLOR);
+    commands
+        .spawn_bundle(TextBundle {
+            text: Text {
+                sections: vec![
+                    TextSection {
+                        value: String::new(),
+                        style: hud_style,
+                    };
+                    6
+                ],
+                ..Default::default()
+            },
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 120.0, 10.0),
+        ))
+        .insert(MessageText);
+
+    commands.insert_resource(Sounds {
+        crash: audio_sources.add(
+            Tone::new(Waveform::Noise, 80.0, 1.0)
+                .fade()
+                .volume(0.6)
+                .into(),
+        ),
+        engine: audio_sources.add(
+            Tone::new(Waveform::Noise, 60.0, ENGINE_INTERVAL)
+                .volume(0.15)
+                .into(),
+        ),
+        landed: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 523.0, 0.12),
+            Tone::new(Waveform::Sine, 784.0, 0.3),
+        ])),
+    });
+}
+
+/// Scores a landing or reports the crash.
+fn touchdown(
+    mut game: ResMut<Game>,
+    mut texts: Query<&mut Text, With<MessageText>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let message = match game.outcome {
+        Some(Outcome::Landed(points)) => {
+            game.score += points;
+            game.landings += 1;
+            audio.play(sounds.landed.clone());
+            format!(
+                "The eagle has landed!\n{} points\n\nEnter: next landing\nC: controls",
+                points
+            )
+        }
+        Some(Outcome::Crashed(crash)) => {
+            audio.play(sounds.crash.clone());
+            format!(
+                "{}\nFinal score {}\n\nEnter: new game\nC: controls",
+                crash.message(),
+                game.score
+            )
+        }
+        None => return,
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
@@ -0,0 +1,223 @@
+//! The lander's flight and what happens when it touches the ground.
+
+use crate::terrain::Terrain;
+use bevy::math::{const_vec2, Vec2};
+
+/// Where the feet are, relative to the center of the lander when it is upright.
+pub const FEET: [Vec2; 2] = [const_vec2!([-11.0, -14.0]), const_vec2!([11.0, -14.0])];
+pub const FUEL: f32 = 1000.0;
+/// Fuel burned per second of thrust.
+pub const FUEL_BURN: f32 = 60.0;
+pub const GRAVITY: f32 = 22.0;
+pub const LANDING_SCORE: u32 = 50;
+/// The steepest tilt a landing survives, in radians.
+pub const MAX_ANGLE: f32 = 0.2;
+pub const MAX_HORIZONTAL_SPEED: f32 = 14.0;
+pub const MAX_VERTICAL_SPEED: f32 = 28.0;
+pub const ROTATION_SPEED: f32 = 2.4;
+pub const THRUST: f32 = 55.0;
+/// Corners of the hull, which must not touch the ground.
+const HULL: [Vec2; 2] = [const_vec2!([-9.0, 10.0]), const_vec2!([9.0, 10.0])];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Controls {
+    /// -1 turns left (counterclockwise), 1 right.
+    pub rotate: f32,
+    pub thrust: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Outcome {
+    /// Touched down on a pad; the points earned.
+    Landed(u32),
+    Crashed(Crash),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crash {
+    /// Touched the ground away from a pad.
+    Missed,
+    Tilted,
+    TooFast,
+}
+
+impl Crash {
+    pub fn message(self) -> &'static str {
+        match self {
+            Self::Missed => "You missed the landing pads",
+            Self::Tilted => "You came down at too steep an angle",
+            Self::TooFast => "You came down too fast",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lander {
+    /// Tilt in radians, counterclockwise; 0 is upright.
+    pub angle: f32,
+    pub fuel: f32,
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+impl Lander {
+    pub fn new(position: Vec2, velocity: Vec2, fuel: f32) -> Self {
+        Self {
+            angle: 0.0,
+            fuel,
+            position,
+            velocity,
+        }
+    }
+
+    /// Height of the feet above the ground straight below.
+    pub fn altitude(&self, terrain: &Terrain) -> f32 {
+        self.position.y + FEET[0].y - terrain.height_at(self.position.x)
+    }
+
+    /// Applies the controls and gravity; returns whether the engine fired.
+    pub fn update(&mut self, controls: Controls, delta: f32) -> bool {
+        self.angle = (self.angle - controls.rotate * ROTATION_SPEED * delta)
+            .clamp(-std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2);
+        let burning = controls.thrust && self.fuel > 0.0;
+        let mut acceleration = Vec2::new(0.0, -GRAVITY);
+        if burning {
+            self.fuel = (self.fuel - FUEL_BURN * delta).max(0.0);
+            acceleration += Vec2::new(-self.angle.sin(), self.angle.cos()) * THRUST;
+        }
+        self.velocity += acceleration * delta;
+        self.position += self.velocity * delta;
+        burning
+    }
+
+    /// `None` while nothing touches the ground; a landing needs both feet on the same pad,
+    /// little speed and little tilt.
+    pub fn touchdown(&self, terrain: &Terrain) -> Option<Outcome> {
+        let feet = FEET.map(|foot| self.point(foot));
+        let touching = feet
+            .iter()
+            .chain(HULL.map(|corner| self.point(corner)).iter())
+            .any(|point| point.y <= terrain.height_at(point.x));
+        if !touching {
+            return None;
+        }
+        let crash = match (terrain.pad_at(feet[0].x), terrain.pad_at(feet[1].x)) {
+            (Some(left), Some(right)) if left == right => {
+                if self.angle.abs() > MAX_ANGLE {
+                    Crash::Tilted
+                } else if -self.velocity.y > MAX_VERTICAL_SPEED
+                    || self.velocity.x.abs() > MAX_HORIZONTAL_SPEED
+                {
+                    Crash::TooFast
+                } else {
+                    return Some(Outcome::Landed(LANDING_SCORE * left.multiplier));
+                }
+            }
+            _ => Crash::Missed,
+        };
+        Some(Outcome::Crashed(crash))
+    }
+
+    /// A point given relative to the upright lander, in world space.
+    fn point(&self, offset: Vec2) -> Vec2 {
+        let (sin, cos) = self.angle.sin_cos();
+        self.position
+            + Vec2::new(
+                offset.x * cos - offset.y * sin,
+                offset.x * sin + offset.y * cos,
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::terrain::Pad;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    fn flat() -> Terrain {
+        Terrain {
+            pads: vec![Pad {
+                left: -40.0,
+                multiplier: 3,
+                right: 40.0,
+                y: -100.0,
+            }],
+            points: vec![Vec2::new(-400.0, -100.0), Vec2::new(400.0, -100.0)],
+        }
+    }
+
+    #[test]
+    fn thrust_burns_fuel_until_it_runs_out() {
+        let mut lander = Lander::new(Vec2::ZERO, Vec2::ZERO, FUEL_BURN / 2.0);
+        let thrust = Controls {
+            rotate: 0.0,
+            thrust: true,
+        };
+        for _ in 0..30 {
+            assert!(lander.update(thrust, STEP));
+        }
+        assert!(lander.velocity.y > 0.0, "thrust beats gravity");
+        assert!(lander.fuel < 1e-3);
+        assert!(!lander.update(thrust, STEP));
+        let before = lander.velocity.y;
+        lander.update(thrust, STEP);
+        assert!(lander.velocity.y < before);
+    }
+
+    #[test]
+    fn tilted_thrust_pushes_sideways() {
+        let mut lander = Lander::new(Vec2::ZERO, Vec2::ZERO, FUEL);
+        let right = Controls {
+            rotate: 1.0,
+            thrust: false,
+        };
+        for _ in 0..10 {
+            lander.update(right, STEP);
+        }
+        assert!(lander.angle < 0.0, "clockwise");
+        lander.update(
+            Controls {
+                rotate: 0.0,
+                thrust: true,
+            },
+            STEP,
+        );
+        assert!(lander.velocity.x > 0.0);
+    }
+
+    #[test]
+    fn only_soft_landings_on_a_pad_count() {
+        let terrain = flat();
+        let landing = |x: f32, velocity: Vec2, angle: f32| {
+            let mut lander = Lander::new(Vec2::new(x, -100.0 - FEET[0].y - 0.5), velocity, FUEL);
+            lander.angle = angle;
+            lander.touchdown(&terrain)
+        };
+        assert_eq!(
+            landing(0.0, Vec2::new(0.0, -10.0), 0.0),
+            Some(Outcome::Landed(3 * LANDING_SCORE))
+        );
+        assert_eq!(
+            landing(0.0, Vec2::new(0.0, -40.0), 0.0),
+            Some(Outcome::Crashed(Crash::TooFast))
+        );
+        assert_eq!(
+            landing(0.0, Vec2::new(20.0, -5.0), 0.0),
+            Some(Outcome::Crashed(Crash::TooFast))
+        );
+        assert_eq!(
+            landing(0.0, Vec2::new(0.0, -5.0), 0.4),
+            Some(Outcome::Crashed(Crash::Tilted))
+        );
+        assert_eq!(
+            landing(35.0, Vec2::new(0.0, -5.0), 0.0),
+            Some(Outcome::Crashed(Crash::Missed)),
+            "one foot off the pad"
+        );
+        let high = Lander::new(Vec2::new(0.0, 0.0), Vec2::ZERO, FUEL);
+        assert_eq!(high.touchdown(&terrain), None);
+        assert!((high.altitude(&terrain) - (100.0 + FEET[0].y)).abs() < 1e-3);
+    }
+}
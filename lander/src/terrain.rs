@@ -0,0 +1,138 @@
+//! The moon's surface: a random line of hills with a few flat landing pads.
+
+use bevy::math::Vec2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const BOTTOM: f32 = -280.0;
+/// Horizontal distance between two points of the surface.
+pub const SEGMENT: f32 = 20.0;
+pub const TOP: f32 = -20.0;
+pub const WIDTH: f32 = 800.0;
+
+/// Pad widths in segments and their score multipliers; the narrower, the more it is worth.
+const PADS: [(usize, u32); 3] = [(2, 5), (3, 3), (5, 2)];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pad {
+    pub left: f32,
+    pub multiplier: u32,
+    pub right: f32,
+    pub y: f32,
+}
+
+impl Pad {
+    pub fn contains(&self, x: f32) -> bool {
+        x >= self.left && x <= self.right
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Terrain {
+    pub pads: Vec<Pad>,
+    /// From left to right, one every `SEGMENT`.
+    pub points: Vec<Vec2>,
+}
+
+impl Terrain {
+    /// Hills from a random walk, with one pad of each size in its own third of the surface.
+    pub fn generate(rng: &mut impl Rng) -> Self {
+        let count = (WIDTH / SEGMENT) as usize + 1;
+        let mut heights = Vec::with_capacity(count);
+        let mut height = rng.gen_range(BOTTOM, TOP);
+        for _ in 0..count {
+            height = (height + rng.gen_range(-45.0, 45.0)).clamp(BOTTOM, TOP);
+            heights.push(height);
+        }
+
+        let mut sizes = PADS.to_vec();
+        sizes.shuffle(rng);
+        let third = (count - 1) / 3;
+        let mut pads = Vec::new();
+        for (part, (segments, multiplier)) in sizes.into_iter().enumerate() {
+            // Keep a segment of hills on both sides of the pad within its third.
+            let first = part * third + 1;
+            let start = rng.gen_range(first, first + third - segments - 1);
+            let y = heights[start];
+            for height in heights[start..=start + segments].iter_mut() {
+                *height = y;
+            }
+            pads.push(Pad {
+                left: x_of(start),
+                multiplier,
+                right: x_of(start + segments),
+                y,
+            });
+        }
+
+        Self {
+            pads,
+            points: heights
+                .into_iter()
+                .enumerate()
+                .map(|(index, height)| Vec2::new(x_of(index), height))
+                .collect(),
+        }
+    }
+
+    /// The surface height at `x`, interpolated between the points around it.
+    pub fn height_at(&self, x: f32) -> f32 {
+        let offset = ((x + WIDTH / 2.0) / SEGMENT).clamp(0.0, (self.points.len() - 1) as f32);
+        let index = (offset as usize).min(self.points.len() - 2);
+        let (left, right) = (self.points[index], self.points[index + 1]);
+        left.y + (right.y - left.y) * (offset - index as f32)
+    }
+
+    pub fn pad_at(&self, x: f32) -> Option<&Pad> {
+        self.pads.iter().find(|pad| pad.contains(x))
+    }
+}
+
+fn x_of(index: usize) -> f32 {
+    index as f32 * SEGMENT - WIDTH / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn pads_are_flat_and_apart() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for _ in 0..50 {
+            let terrain = Terrain::generate(&mut rng);
+            assert_eq!(terrain.pads.len(), 3);
+            for pad in terrain.pads.iter() {
+                let mut x = pad.left;
+                while x <= pad.right {
+                    assert_eq!(terrain.height_at(x), pad.y);
+                    x += SEGMENT / 4.0;
+                }
+            }
+            for pair in terrain.pads.windows(2) {
+                assert!(pair[0].right < pair[1].left);
+            }
+            assert!(terrain
+                .points
+                .iter()
+                .all(|point| (BOTTOM..=TOP).contains(&point.y)));
+        }
+    }
+
+    #[test]
+    fn heights_are_interpolated() {
+        let terrain = Terrain {
+            pads: Vec::new(),
+            points: vec![
+                Vec2::new(-WIDTH / 2.0, -100.0),
+                Vec2::new(-WIDTH / 2.0 + SEGMENT, -200.0),
+            ],
+        };
+        assert_eq!(terrain.height_at(-WIDTH / 2.0), -100.0);
+        assert_eq!(terrain.height_at(-WIDTH / 2.0 + SEGMENT / 4.0), -125.0);
+        assert_eq!(terrain.height_at(-WIDTH / 2.0 + SEGMENT), -200.0);
+        assert_eq!(terrain.height_at(WIDTH), -200.0, "clamped at the edges");
+    }
+}
@@ -0,0 +1,20 @@
+language-name = Deutsch
+continue = Weiterspielen: { $game }
+enter-play = Enter: spielen
+most-played = Meistgespielt
+played = { $launches ->
+        [one] Einmal
+       *[other] { $launches } Mal
+    } gespielt, { $playtime }
+playing =
+    { $game } läuft...
+    Schließe sein Fenster, um zurückzukehren.
+help =
+    Pfeile, Steuerkreuz oder Stick: auswählen
+    Enter, Leertaste oder A: spielen
+    F oder Y: Favorit
+    C oder X: weiterspielen
+    L: Sprache ({ language-name })
+    T: Design ({ $theme })
+    Tab oder Select: Barrierefreiheit
+    Esc: beenden
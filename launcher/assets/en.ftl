@@ -0,0 +1,20 @@
+language-name = English
+continue = Continue: { $game }
+enter-play = Enter: play
+most-played = Most played
+played = Played { $launches ->
+        [one] once
+       *[other] { $launches } times
+    }, { $playtime }
+playing =
+    Playing { $game }...
+    Close its window to come back.
+help =
+    Arrows, D-pad or stick: choose
+    Enter, Space or A: play
+    F or Y: favorite
+    C or X: continue
+    L: language ({ language-name })
+    T: theme ({ $theme })
+    Tab or Select: accessibility
+    Esc: quit
@@ -0,0 +1,20 @@
+language-name = Français
+continue = Continuer : { $game }
+enter-play = Entrée : jouer
+most-played = Les plus joués
+played = Joué { $launches ->
+        [one] une fois
+       *[other] { $launches } fois
+    }, { $playtime }
+playing =
+    { $game } en cours...
+    Fermez sa fenêtre pour revenir.
+help =
+    Flèches, croix ou stick : choisir
+    Entrée, Espace ou A : jouer
+    F ou Y : favori
+    C ou X : continuer
+    L : langue ({ language-name })
+    T : thème ({ $theme })
+    Tab ou Select : accessibilité
+    Échap : quitter
@@ -0,0 +1,149 @@
+//! Finds the games next to the launcher: every crate with a `src/main.rs` and a readme, titled
+//! and described by the readme's heading and first paragraph.
+
+use std::{
+    env::consts::EXE_SUFFIX,
+    fs, io,
+    path::{Path, PathBuf},
+    process::{Child, Command},
+};
+
+/// An optional picture of a game for its tile, in its directory.
+const THUMBNAIL: &str = "thumbnail.png";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Game {
+    pub description: String,
+    pub directory: PathBuf,
+    /// The crate name, which is also the name of its executable and save directory.
+    pub name: String,
+    pub title: String,
+}
+
+impl Game {
+    /// Its best scores in the table `game_scores` keeps of its single mode, if it keeps any.
+    pub fn high_scores(&self) -> Vec<u32> {
+        game_scores::saved_table(&self.name, "")
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    }
+
+    /// Starts it: the release build if there is one, otherwise through `cargo run --release`,
+    /// which builds it first.
+    pub fn launch(&self) -> io::Result<Child> {
+        let executable = self
+            .directory
+            .join("target")
+            .join("release")
+            .join(format!("{}{}", self.name, EXE_SUFFIX));
+        let mut command = if executable.is_file() {
+            Command::new(executable)
+        } else {
+            let mut command = Command::new("cargo");
+            command.args(["run", "--release"]);
+            command
+        };
+        command.current_dir(&self.directory).spawn()
+    }
+
+    /// Starts it through a binary holding several games, which takes the game's name.
+    pub fn launch_with(&self, executable: &Path) -> io::Result<Child> {
+        Command::new(executable).arg(&self.name).spawn()
+    }
+
+    /// The picture for its tile, if it has one.
+    pub fn thumbnail(&self) -> Option<Vec<u8>> {
+        fs::read(self.directory.join(THUMBNAIL)).ok()
+    }
+}
+
+/// The games in the directories under `root`, by title; those in `skip` are left out.
+pub fn games(root: &Path, skip: &[&str]) -> io::Result<Vec<Game>> {
+    let mut games = Vec::new();
+    for entry in fs::read_dir(root)? {
+        let directory = entry?.path();
+        let name = match directory.file_name().and_then(|name| name.to_str()) {
+            Some(name) if !skip.contains(&name) => name.to_string(),
+            _ => continue,
+        };
+        if !directory.join("Cargo.toml").is_file() || !directory.join("src/main.rs").is_file() {
+            continue;
+        }
+        let readme = fs::read_to_string(directory.join("README.md")).unwrap_or_default();
+        let (title, description) =
+            parse_readme(&readme).unwrap_or_else(|| (name.clone(), String::new()));
+        games.push(Game {
+            description,
+            directory,
+            name,
+            title,
+        });
+    }
+    games.sort_by_key(|game| game.title.to_lowercase());
+    Ok(games)
+}
+
+/// The heading and the first paragraph of a readme, without markdown links and emphasis.
+pub fn parse_readme(text: &str) -> Option<(String, String)> {
+    let mut lines = text.lines().map(str::trim);
+    let title = lines.find_map(|line| line.strip_prefix("# "))?.to_string();
+    let paragraph: Vec<&str> = lines
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect();
+    Some((title, plain(&paragraph.join(" "))))
+}
+
+/// Markdown with `[text](link)` turned into text and `*` and `` ` `` left out.
+fn plain(markdown: &str) -> String {
+    let mut text = String::new();
+    let mut rest = markdown;
+    while let Some(start) = rest.find('[') {
+        let link = rest[start..]
+            .find("](")
+            .and_then(|middle| Some((middle, rest[start + middle..].find(')')?)));
+        let (middle, end) = match link {
+            Some(link) => link,
+            None => break,
+        };
+        text.push_str(&rest[..start]);
+        text.push_str(&rest[start + 1..start + middle]);
+        rest = &rest[start + middle + end + 1..];
+    }
+    text.push_str(rest);
+    text.replace(['*', '`'], "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_titles_and_descriptions() {
+        let readme = "# Snake\n\nThe basegame is developed by using [this tutorial](https://example.com/).\nIt *grows*.\n\n## Installation\n\nRun it.";
+        assert_eq!(
+            parse_readme(readme),
+            Some((
+                "Snake".to_string(),
+                "The basegame is developed by using this tutorial. It grows.".to_string()
+            ))
+        );
+        assert_eq!(parse_readme("no heading"), None);
+    }
+
+    #[test]
+    fn finds_the_games_of_the_repository() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("..");
+        let games = games(&root, &["launcher"]).unwrap();
+        let names: Vec<&str> = games.iter().map(|game| game.name.as_str()).collect();
+        assert!(names.contains(&"snake") && names.contains(&"galaga"));
+        // Neither the shared crates nor the launcher itself.
+        assert!(!names.contains(&"game_hud") && !names.contains(&"launcher"));
+        let rogue = games.iter().find(|game| game.name == "rogue").unwrap();
+        assert_eq!(rogue.title, "Rogue");
+        assert!(rogue
+            .description
+            .starts_with("A turn-based dungeon crawler"));
+    }
+}
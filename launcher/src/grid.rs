@@ -0,0 +1,55 @@
+//! Moving the selection around a grid of tiles, and scrolling it into view.
+
+/// The tile reached from `selected` by moving `columns` and `rows` in a grid of `count` tiles
+/// `width` wide; moves past the edges stop there, and down into a short last row goes to its
+/// last tile.
+pub fn step(selected: usize, count: usize, width: usize, (columns, rows): (isize, isize)) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    let column = (selected % width) as isize + columns;
+    let row = (selected / width) as isize + rows;
+    let last_row = ((count - 1) / width) as isize;
+    if column < 0 || column >= width as isize || row < 0 || row > last_row {
+        return selected;
+    }
+    ((row * width as isize + column) as usize).min(count - 1)
+}
+
+/// The first row on screen after scrolling just enough for `selected` to show, with `height`
+/// rows on screen.
+pub fn scroll_to(selected: usize, top: usize, width: usize, height: usize) -> usize {
+    let row = selected / width;
+    if row < top {
+        row
+    } else if row >= top + height {
+        row + 1 - height
+    } else {
+        top
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_within_the_grid() {
+        // 11 tiles, 4 wide: rows of 4, 4 and 3.
+        assert_eq!(step(0, 11, 4, (1, 0)), 1);
+        assert_eq!(step(3, 11, 4, (1, 0)), 3);
+        assert_eq!(step(0, 11, 4, (-1, 0)), 0);
+        assert_eq!(step(1, 11, 4, (0, 1)), 5);
+        assert_eq!(step(7, 11, 4, (0, 1)), 10);
+        assert_eq!(step(9, 11, 4, (0, 1)), 9);
+        assert_eq!(step(9, 11, 4, (0, -1)), 5);
+        assert_eq!(step(0, 0, 4, (1, 0)), 0);
+    }
+
+    #[test]
+    fn scrolls_the_selection_into_view() {
+        assert_eq!(scroll_to(5, 0, 4, 2), 0);
+        assert_eq!(scroll_to(9, 0, 4, 2), 1);
+        assert_eq!(scroll_to(1, 2, 4, 2), 0);
+    }
+}
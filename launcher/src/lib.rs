@@ -0,0 +1,658 @@
+use bevy::{
+    app::AppExit, ecs::schedule::ShouldRun, math::const_vec2, prelude::*,
+    render::texture::ImageType,
+};
+use catalog::Game;
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use game_i18n::{I18nPlugin, Localization};
+use game_profile::Profile;
+use game_settings::SettingsScreen;
+use game_theme::{Role, ThemePlugin, Themes};
+use game_transitions::{Covered, TransitionPlugin, Transitions};
+use std::{env, path::PathBuf, process::Child, time::Instant};
+
+pub mod catalog;
+pub mod grid;
+
+const ACCESSIBILITY_KEY: KeyCode = KeyCode::Tab;
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.08);
+/// The crate of the binary holding every game, which is no game itself.
+const BUNDLE: &str = "rust_games";
+/// Distance between the middles of two tiles.
+const CELL: Vec2 = const_vec2!([132.0, 124.0]);
+const COLUMNS: usize = 5;
+const CONTINUE_KEY: KeyCode = KeyCode::C;
+/// Characters a line of the description holds.
+const DESCRIPTION_WIDTH: usize = 30;
+const FAVORITE_KEY: KeyCode = KeyCode::F;
+/// Where the middle of the top left tile is.
+const GRID_ORIGIN: Vec2 = const_vec2!([-420.0, 270.0]);
+const HIGH_SCORES: usize = 5;
+const LANGUAGE_KEY: KeyCode = KeyCode::L;
+const LAUNCH_KEYS: [KeyCode; 2] = [KeyCode::Return, KeyCode::Space];
+/// Games listed as the most played.
+const MOST_PLAYED: usize = 3;
+/// Rows of tiles on screen.
+/// The command line flag naming the repository to look for games in, e.g. `--root ../rust_games`.
+const ROOT_FLAG: &str = "--root";
+const ROWS: usize = 5;
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.95);
+const THEME_KEY: KeyCode = KeyCode::T;
+const TILE: Vec2 = const_vec2!([116.0, 80.0]);
+const TITLE_COLOR: Color = Color::rgb(1.0, 0.8, 0.3);
+/// Seconds of the fade when a game starts or closes.
+const TRANSITION_SECONDS: f32 = 0.6;
+
+pub const WINDOW_HEIGHT: f32 = 700.0;
+pub const WINDOW_WIDTH: f32 = 1000.0;
+
+#[derive(Component)]
+struct DetailText;
+
+/// The games, the one selected and how far the grid is scrolled.
+struct Launcher {
+    /// Games held by the running executable, which it starts instead of their own.
+    builtin: Vec<&'static str>,
+    games: Vec<Game>,
+    /// The game to start once the screen has faded out.
+    launching: Option<usize>,
+    /// Text shown under the details, such as why a game would not start.
+    message: String,
+    /// Best scores of each game.
+    scores: Vec<Vec<u32>>,
+    selected: usize,
+    /// The first row of tiles on screen.
+    top: usize,
+}
+
+/// The game being played, which the launcher waits for, and when it started.
+#[derive(Default)]
+struct Running(Option<(usize, Child, Instant)>);
+
+/// Pictures for the tiles of the games that have them.
+struct Thumbnails(Vec<Option<Handle<Image>>>);
+
+/// Something drawn for a tile, replaced whenever the grid scrolls or the selection moves.
+#[derive(Component)]
+struct Tile;
+
+#[derive(Default)]
+pub struct LauncherPlugin {
+    builtin: Vec<&'static str>,
+}
+
+impl LauncherPlugin {
+    /// For a binary holding the named games: starts them through it, and lists them even
+    /// without the repository next to it.
+    pub fn builtin(games: Vec<&'static str>) -> Self {
+        Self { builtin: games }
+    }
+}
+
+impl Plugin for LauncherPlugin {
+    fn build(&self, app: &mut App) {
+        // The repository is the launcher's parent directory, unless another is named.
+        let root = env::args()
+            .skip_while(|arg| arg != ROOT_FLAG)
+            .nth(1)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(env!("CARGO_MANIFEST_DIR")).join(".."));
+        let (mut games, mut message) =
+            match catalog::games(&root, &[env!("CARGO_PKG_NAME"), BUNDLE]) {
+                Ok(games) => (games, String::new()),
+                Err(error) => (
+                    Vec::new(),
+                    format!("Could not look for games in {}: {}", root.display(), error),
+                ),
+            };
+        for name in &self.builtin {
+            if !games.iter().any(|game| game.name == *name) {
+                games.push(Game {
+                    description: String::new(),
+                    directory: root.join(name),
+                    name: name.to_string(),
+                    title: name.to_string(),
+                });
+            }
+        }
+        if games.is_empty() && message.is_empty() {
+            message = format!("No games in {}", root.display());
+        }
+        games.sort_by_key(|game| game.title.to_lowercase());
+        let scores = games.iter().map(Game::high_scores).collect();
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Launcher {
+                builtin: self.builtin.clone(),
+                games,
+                launching: None,
+                message,
+                scores,
+                selected: 0,
+                top: 0,
+            })
+            .insert_resource(Profile::load())
+            .insert_resource(Running::default())
+            .add_plugin(HudPlugin)
+            .add_plugin(I18nPlugin::new(&[
+                ("de", include_str!("../assets/de.ftl")),
+                ("en", include_str!("../assets/en.ftl")),
+                ("fr", include_str!("../assets/fr.ftl")),
+            ]))
+            .add_plugin(ThemePlugin::default())
+            .add_plugin(TransitionPlugin::new(
+                game_transitions::Kind::Fade,
+                TRANSITION_SECONDS,
+            ))
+            .add_startup_system(setup)
+            .add_system(accessibility_input)
+            .add_system_set(
+                SystemSet::new()
+                    .with_run_criteria(settings_closed)
+                    .with_system(exit_input)
+                    .with_system(language_input)
+                    .with_system(theme_input)
+                    .with_system(navigate_input),
+            )
+            .add_system(launch_update)
+            .add_system(running_update)
+            .add_system(details_update)
+            .add_system(tiles_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Rust Games".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Tab or Select open the accessibility settings, which every game shares.
+fn accessibility_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    running: Res<Running>,
+    transitions: Res<Transitions>,
+    mut screen: ResMut<SettingsScreen>,
+) {
+    if screen.open || running.0.is_some() || transitions.is_running() {
+        return;
+    }
+    let select = gamepad_input
+        .get_just_pressed()
+        .any(|GamepadButton(_, pressed)| *pressed == GamepadButtonType::Select);
+    if keyboard_input.just_pressed(ACCESSIBILITY_KEY) || select {
+        screen.open();
+    }
+}
+
+/// Shows the title, description, best scores and playtime of the selected game, and the games
+/// to continue with or played most.
+fn details_update(
+    launcher: Res<Launcher>,
+    localization: Res<Localization>,
+    profile: Res<Profile>,
+    running: Res<Running>,
+    themes: Res<Themes>,
+    mut texts: Query<&mut Text, With<DetailText>>,
+) {
+    if !launcher.is_changed()
+        && !localization.is_changed()
+        && !profile.is_changed()
+        && !running.is_changed()
+        && !themes.is_changed()
+    {
+        return;
+    }
+    let theme = themes.current();
+    let text_color = theme.color(Role::Text, TEXT_COLOR);
+    let mut sections = Vec::new();
+    if let Some(game) = launcher.games.get(launcher.selected) {
+        let favorite = if profile.is_favorite(&game.name) {
+            " *"
+        } else {
+            ""
+        };
+        sections.push((
+            format!("{}{}", game.title.to_uppercase(), favorite),
+            theme.color(Role::Accent, TITLE_COLOR),
+        ));
+        let mut details = format!("\n\n{}", wrap(&game.description, DESCRIPTION_WIDTH));
+        if let Some(played) = profile.get(&game.name).filter(|played| played.launches > 0) {
+            details.push_str("\n\n");
+            details.push_str(&localization.format(
+                "played",
+                &[
+                    ("launches", played.launches.into()),
+                    (
+                        "playtime",
+                        game_profile::format_playtime(played.playtime).into(),
+                    ),
+                ],
+            ));
+        }
+        let scores = &launcher.scores[launcher.selected];
+        if !scores.is_empty() {
+            details.push_str("\n\n");
+            details.push_str(&localization.get("high-scores"));
+            for (place, score) in scores.iter().take(HIGH_SCORES).enumerate() {
+                details.push_str(&format!("\n{}. {:>8}", place + 1, score));
+            }
+        }
+        details.push_str("\n\n");
+        details.push_str(&match &running.0 {
+            Some((running, _, _)) => localization.format(
+                "playing",
+                &[("game", launcher.games[*running].title.clone().into())],
+            ),
+            None => localization.get("enter-play"),
+        });
+        sections.push((details, text_color));
+    }
+    let mut history = String::new();
+    if let Some(game) = profile
+        .last_played()
+        .and_then(|name| title(&launcher, name))
+    {
+        history.push_str("\n\n");
+        history.push_str(&localization.format("continue", &[("game", game.into())]));
+    }
+    let most_played = profile.most_played(MOST_PLAYED);
+    if !most_played.is_empty() {
+        history.push_str("\n\n");
+        history.push_str(&localization.get("most-played"));
+        for (name, played) in most_played {
+            if let Some(game) = title(&launcher, name) {
+                history.push_str(&format!(
+                    "\n{:<16}{:>8}",
+                    game,
+                    game_profile::format_playtime(played.playtime)
+                ));
+            }
+        }
+    }
+    if !history.is_empty() {
+        sections.push((history, text_color));
+    }
+    if !launcher.message.is_empty() {
+        sections.push((
+            format!("\n\n{}", wrap(&launcher.message, DESCRIPTION_WIDTH)),
+            theme.color(Role::Accent, SELECTED_COLOR),
+        ));
+    }
+    sections.push((
+        format!(
+            "\n\n\n{}",
+            localization.format("help", &[("theme", themes.chosen().into())])
+        ),
+        text_color,
+    ));
+    for mut text in texts.iter_mut() {
+        let style = text.sections[0].style.clone();
+        text.sections = sections
+            .iter()
+            .map(|(value, color)| TextSection {
+                value: value.clone(),
+                style: TextStyle {
+                    color: *color,
+                    ..style.clone()
+                },
+            })
+            .collect();
+    }
+}
+
+/// Esc quits, unless a game is being played.
+fn exit_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    running: Res<Running>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if running.0.is_none() && keyboard_input.just_pressed(KeyCode::Escape) {
+        exit.send(AppExit);
+    }
+}
+
+/// L or the left bumper switch to the next language, for the launcher and every game started
+/// after.
+fn language_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    running: Res<Running>,
+    mut localization: ResMut<Localization>,
+) {
+    let bumper = gamepad_input
+        .get_just_pressed()
+        .any(|GamepadButton(_, pressed)| *pressed == GamepadButtonType::LeftTrigger);
+    if running.0.is_none() && (keyboard_input.just_pressed(LANGUAGE_KEY) || bumper) {
+        localization.next_language();
+        localization.save();
+    }
+}
+
+/// T or the right bumper switch to the next theme, for the launcher and every game started after.
+fn theme_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    running: Res<Running>,
+    mut themes: ResMut<Themes>,
+) {
+    let bumper = gamepad_input
+        .get_just_pressed()
+        .any(|GamepadButton(_, pressed)| *pressed == GamepadButtonType::RightTrigger);
+    if running.0.is_none() && (keyboard_input.just_pressed(THEME_KEY) || bumper) {
+        themes.next();
+        themes.save();
+    }
+}
+
+/// Starts the game and counts it in the profile, or tells why it would not start.
+fn launch(launcher: &mut Launcher, running: &mut Running, profile: &mut Profile, index: usize) {
+    let game = &launcher.games[index];
+    let child = if launcher.builtin.contains(&game.name.as_str()) {
+        env::current_exe().and_then(|executable| game.launch_with(&executable))
+    } else {
+        game.launch()
+    };
+    match child {
+        Ok(child) => {
+            running.0 = Some((index, child, Instant::now()));
+            launcher.message.clear();
+            profile.launched(&launcher.games[index].name);
+            profile.save();
+        }
+        Err(error) => {
+            launcher.message =
+                format!("Could not start {}: {}", launcher.games[index].title, error);
+        }
+    }
+}
+
+/// Starts the game chosen once the screen has faded out.
+fn launch_update(
+    mut covered: EventReader<Covered>,
+    mut launcher: ResMut<Launcher>,
+    mut profile: ResMut<Profile>,
+    mut running: ResMut<Running>,
+) {
+    if covered.iter().next().is_none() {
+        return;
+    }
+    if let Some(index) = launcher.launching.take() {
+        launch(&mut launcher, &mut running, &mut profile, index);
+    }
+}
+
+/// Arrows, WASD, the D-pad or the left stick move the selection; Enter, Space, A or Start
+/// launch the game; F or Y make it a favorite; C or X continue with the game played last.
+fn navigate_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    gamepad_input: Res<Input<GamepadButton>>,
+    menu: Res<MenuInput>,
+    mut launcher: ResMut<Launcher>,
+    mut profile: ResMut<Profile>,
+    running: Res<Running>,
+    mut transitions: ResMut<Transitions>,
+) {
+    if running.0.is_some() || transitions.is_running() {
+        return;
+    }
+    let button = |kind: GamepadButtonType| {
+        gamepad_input
+            .get_just_pressed()
+            .any(|GamepadButton(_, pressed)| *pressed == kind)
+    };
+    let pressed = |key: KeyCode, navigate: Navigate| {
+        keyboard_input.just_pressed(key) || menu.just_pressed(navigate)
+    };
+    let mut movement = (0, 0);
+    if pressed(KeyCode::A, Navigate::Left) {
+        movement.0 -= 1;
+    }
+    if pressed(KeyCode::D, Navigate::Right) {
+        movement.0 += 1;
+    }
+    if pressed(KeyCode::W, Navigate::Up) {
+        movement.1 -= 1;
+    }
+    if pressed(KeyCode::S, Navigate::Down) {
+        movement.1 += 1;
+    }
+    if movement != (0, 0) {
+        let selected = grid::step(launcher.selected, launcher.games.len(), COLUMNS, movement);
+        if selected != launcher.selected {
+            launcher.selected = selected;
+            launcher.top = grid::scroll_to(selected, launcher.top, COLUMNS, ROWS);
+            launcher.message.clear();
+        }
+    }
+
+    if launcher.games.is_empty() {
+        return;
+    }
+    if keyboard_input.just_pressed(FAVORITE_KEY) || button(GamepadButtonType::North) {
+        let name = &launcher.games[launcher.selected].name;
+        profile.toggle_favorite(name);
+        profile.save();
+    }
+    if keyboard_input.just_pressed(CONTINUE_KEY) || button(GamepadButtonType::West) {
+        let last = profile
+            .last_played()
+            .and_then(|name| launcher.games.iter().position(|game| game.name == name));
+        if let Some(index) = last {
+            launcher.selected = index;
+            launcher.top = grid::scroll_to(index, launcher.top, COLUMNS, ROWS);
+            launcher.launching = Some(index);
+            transitions.start();
+        }
+        return;
+    }
+    let launch_pressed = keyboard_input.any_just_pressed(LAUNCH_KEYS)
+        || button(GamepadButtonType::South)
+        || button(GamepadButtonType::Start);
+    if launch_pressed {
+        launcher.launching = Some(launcher.selected);
+        transitions.start();
+    }
+}
+
+/// A color for a game without a thumbnail, the same every time for the same game.
+fn placeholder_color(name: &str) -> Color {
+    let hue = name
+        .bytes()
+        .fold(0u32, |hash, byte| hash * 31 + byte as u32)
+        % 360;
+    Color::hsl(hue as f32, 0.45, 0.32)
+}
+
+/// Waits for the game being played to close, then adds the time played to the profile and
+/// reads its scores again.
+fn running_update(
+    mut launcher: ResMut<Launcher>,
+    mut profile: ResMut<Profile>,
+    mut running: ResMut<Running>,
+) {
+    let (index, child, started) = match running.0.as_mut() {
+        Some((index, child, started)) => (*index, child, *started),
+        None => return,
+    };
+    let title = &launcher.games[index].title;
+    let message = match child.try_wait() {
+        Ok(None) => return,
+        Ok(Some(status)) if status.success() => String::new(),
+        Ok(Some(status)) => format!("{} stopped with an error ({})", title, status),
+        Err(error) => format!("Lost track of {}: {}", title, error),
+    };
+    running.0 = None;
+    profile.played(&launcher.games[index].name, started.elapsed().as_secs());
+    profile.save();
+    launcher.message = message;
+    launcher.scores[index] = launcher.games[index].high_scores();
+}
+
+/// Keeps the launcher's own input from acting while the settings are shown.
+fn settings_closed(screen: Res<SettingsScreen>) -> ShouldRun {
+    if screen.open {
+        ShouldRun::No
+    } else {
+        ShouldRun::Yes
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    launcher: Res<Launcher>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(30.0),
+                left: Val::Px(700.0),
+                ..Default::default()
+            },
+        ))
+        .insert(DetailText);
+
+    let thumbnails = launcher
+        .games
+        .iter()
+        .map(|game| {
+            let bytes = game.thumbnail()?;
+            match Image::from_buffer(&bytes, ImageType::Extension("png")) {
+                Ok(image) => Some(images.add(image)),
+                Err(error) => {
+                    eprintln!("could not read the thumbnail of {}: {:?}", game.name, error);
+                    None
+                }
+            }
+        })
+        .collect();
+    commands.insert_resource(Thumbnails(thumbnails));
+}
+
+/// Redraws the tiles on screen when the selection moves, a game closes or the favorites change.
+fn tiles_update(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    launcher: Res<Launcher>,
+    profile: Res<Profile>,
+    themes: Res<Themes>,
+    thumbnails: Res<Thumbnails>,
+    tiles: Query<Entity, With<Tile>>,
+) {
+    if !launcher.is_changed() && !profile.is_changed() && !themes.is_changed() {
+        return;
+    }
+    let theme = themes.current();
+    let selected_color = theme.color(Role::Accent, SELECTED_COLOR);
+    for entity in tiles.iter() {
+        commands.entity(entity).despawn();
+    }
+    let first = launcher.top * COLUMNS;
+    let last = (first + ROWS * COLUMNS).min(launcher.games.len());
+    for index in first..last {
+        let game = &launcher.games[index];
+        let (column, row) = (index % COLUMNS, index / COLUMNS - launcher.top);
+        let center = GRID_ORIGIN + Vec2::new(column as f32, -(row as f32)) * CELL;
+        if index == launcher.selected {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: selected_color,
+                        custom_size: Some(TILE + Vec2::splat(8.0)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(center.extend(0.0)),
+                    ..Default::default()
+                })
+                .insert(Tile);
+        }
+        let tile = match &thumbnails.0[index] {
+            Some(thumbnail) => SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(TILE),
+                    ..Default::default()
+                },
+                texture: thumbnail.clone(),
+                transform: Transform::from_translation(center.extend(1.0)),
+                ..Default::default()
+            },
+            None => {
+                let initial: String = game.title.chars().take(1).collect();
+                commands
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        initial,
+                        40.0,
+                        TEXT_COLOR,
+                        center.extend(2.0),
+                    ))
+                    .insert(Tile);
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: placeholder_color(&game.name),
+                        custom_size: Some(TILE),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(center.extend(1.0)),
+                    ..Default::default()
+                }
+            }
+        };
+        commands.spawn_bundle(tile).insert(Tile);
+        let caption = if index == launcher.selected {
+            selected_color
+        } else {
+            theme.color(Role::Text, TEXT_COLOR)
+        };
+        let title = if profile.is_favorite(&game.name) {
+            format!("* {}", game.title)
+        } else {
+            game.title.clone()
+        };
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                title,
+                14.0,
+                caption,
+                (center - Vec2::new(0.0, TILE.y / 2.0 + 14.0)).extend(2.0),
+            ))
+            .insert(Tile);
+    }
+}
+
+/// The title of the game with the crate name, if it is still in the catalog.
+fn title<'a>(launcher: &'a Launcher, name: &str) -> Option<&'a str> {
+    launcher
+        .games
+        .iter()
+        .find(|game| game.name == name)
+        .map(|game| game.title.as_str())
+}
+
+/// Breaks text into lines of at most `width` characters at spaces.
+fn wrap(text: &str, width: usize) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines.join("\n")
+}
@@ -0,0 +1,12 @@
+use bevy::prelude::*;
+use game_platform::PlatformPlugin;
+use launcher::LauncherPlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(launcher::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(LauncherPlugin::default())
+        .add_plugin(PlatformPlugin)
+        .run();
+}
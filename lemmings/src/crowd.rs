@@ -0,0 +1,298 @@
+//! The units: they walk on by themselves, turning at walls and blockers, until they are handed a
+//! skill. Positions are terrain pixels, `y` being the row their feet are in.
+
+use crate::terrain::{Pixel, Terrain};
+
+/// Bricks a builder lays before walking on.
+pub const BRICKS: u32 = 12;
+/// Height of a unit in pixels.
+pub const UNIT_HEIGHT: i32 = 8;
+
+/// Pixels either side of a blocker that walkers turn at.
+const BLOCK_REACH: i32 = 3;
+/// Ticks between two bricks.
+const BUILD_TICKS: u32 = 6;
+/// Ticks between two rows dug.
+const DIG_TICKS: u32 = 4;
+/// How far a unit may fall without dying, in pixels.
+const FALL_LIMIT: i32 = 40;
+/// Pixels a unit falls per tick.
+const FALL_SPEED: i32 = 3;
+/// How high a step walkers climb without turning round.
+const STEP: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Activity {
+    Walking,
+    /// Falling, and how far so far.
+    Falling(i32),
+    Blocking,
+    /// Digging, and ticks since the last row.
+    Digging(u32),
+    /// Building, with the bricks laid and ticks since the last one.
+    Building(u32, u32),
+    Saved,
+    Dead,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Skill {
+    /// Stands still and turns everyone else round.
+    Blocker,
+    /// Digs straight down until it falls through or hits steel.
+    Digger,
+    /// Lays a staircase of bricks up ahead.
+    Builder,
+}
+
+impl Skill {
+    pub const ALL: [Skill; 3] = [Self::Blocker, Self::Digger, Self::Builder];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Blocker => "Blocker",
+            Self::Digger => "Digger",
+            Self::Builder => "Builder",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Unit {
+    /// -1 walking left, 1 walking right.
+    pub direction: i32,
+    pub state: Activity,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Unit {
+    /// A unit dropping out of the hatch.
+    pub fn drop_at(x: i32, y: i32) -> Self {
+        Self {
+            direction: 1,
+            state: Activity::Falling(0),
+            x,
+            y,
+        }
+    }
+
+    /// Hands the unit a skill; only walkers take one.
+    pub fn assign(&mut self, skill: Skill) -> bool {
+        if self.state != Activity::Walking {
+            return false;
+        }
+        self.state = match skill {
+            Skill::Blocker => Activity::Blocking,
+            Skill::Digger => Activity::Digging(0),
+            Skill::Builder => Activity::Building(0, 0),
+        };
+        true
+    }
+
+    /// Still in the level, as opposed to saved or dead.
+    pub fn is_active(self) -> bool {
+        !matches!(self.state, Activity::Saved | Activity::Dead)
+    }
+
+    /// One tick of whatever the unit is doing. `blockers` are where the blockers stand and
+    /// `exit` is where the exit takes units in.
+    pub fn step(&mut self, terrain: &mut Terrain, blockers: &[(i32, i32)], exit: (i32, i32)) {
+        match self.state {
+            Activity::Walking => self.walk(terrain, blockers, exit),
+            Activity::Falling(distance) => self.fall(terrain, distance),
+            Activity::Digging(ticks) if ticks + 1 < DIG_TICKS => {
+                self.state = Activity::Digging(ticks + 1)
+            }
+            Activity::Digging(_) => self.dig(terrain),
+            Activity::Building(bricks, ticks) if ticks + 1 < BUILD_TICKS => {
+                self.state = Activity::Building(bricks, ticks + 1)
+            }
+            Activity::Building(bricks, _) => self.build(terrain, bricks),
+            Activity::Blocking | Activity::Saved | Activity::Dead => {}
+        }
+    }
+
+    /// Lays a brick at the feet and steps up onto it, unless something is in the way.
+    fn build(&mut self, terrain: &mut Terrain, bricks: u32) {
+        if bricks == BRICKS {
+            self.state = Activity::Walking;
+            return;
+        }
+        for dx in -1..5 {
+            terrain.fill(self.x + self.direction * dx, self.y);
+        }
+        let (x, y) = (self.x + self.direction * 2, self.y - 1);
+        if terrain.solid(x, y) || terrain.solid(x, y - UNIT_HEIGHT + 1) {
+            self.direction = -self.direction;
+            self.state = Activity::Walking;
+            return;
+        }
+        self.x = x;
+        self.y = y;
+        self.state = Activity::Building(bricks + 1, 0);
+    }
+
+    /// Digs out the row under the feet and sinks into it.
+    fn dig(&mut self, terrain: &mut Terrain) {
+        let below = self.y + 1;
+        let row: Vec<i32> = (self.x - 4..self.x + 4).collect();
+        if row.iter().any(|x| terrain.get(*x, below) == Pixel::Steel) {
+            self.state = Activity::Walking;
+            return;
+        }
+        let dug = row
+            .iter()
+            .filter(|x| terrain.clear(**x, below) == Pixel::Dirt)
+            .count();
+        if dug == 0 {
+            self.state = Activity::Falling(0);
+            return;
+        }
+        self.y = below;
+        self.state = Activity::Digging(0);
+    }
+
+    fn fall(&mut self, terrain: &Terrain, mut distance: i32) {
+        for _ in 0..FALL_SPEED {
+            if terrain.solid(self.x, self.y + 1) {
+                self.state = if distance > FALL_LIMIT {
+                    Activity::Dead
+                } else {
+                    Activity::Walking
+                };
+                return;
+            }
+            self.y += 1;
+            distance += 1;
+            if self.y >= terrain.height {
+                self.state = Activity::Dead;
+                return;
+            }
+        }
+        self.state = Activity::Falling(distance);
+    }
+
+    /// A pixel on, up or down small steps, turning at walls and at blockers ahead.
+    fn walk(&mut self, terrain: &Terrain, blockers: &[(i32, i32)], exit: (i32, i32)) {
+        if (self.x - exit.0).abs() <= 2 && (self.y - exit.1).abs() <= 3 {
+            self.state = Activity::Saved;
+            return;
+        }
+        let next = self.x + self.direction;
+        let blocked = blockers.iter().any(|(x, y)| {
+            (x - self.x).signum() == self.direction
+                && (x - next).abs() <= BLOCK_REACH
+                && (y - self.y).abs() < UNIT_HEIGHT
+        });
+        let rise = (0..=STEP).find(|rise| !terrain.solid(next, self.y - rise));
+        match rise {
+            Some(rise) if !blocked => {
+                self.x = next;
+                self.y -= rise;
+            }
+            _ => {
+                self.direction = -self.direction;
+                return;
+            }
+        }
+        if !terrain.solid(self.x, self.y + 1) {
+            match (1..=STEP).find(|drop| terrain.solid(self.x, self.y + drop + 1)) {
+                Some(drop) => self.y += drop,
+                None => self.state = Activity::Falling(0),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Pixel::{Air, Dirt, Steel};
+
+    /// A 40x40 pixel terrain with a floor of dirt from row 30 down.
+    fn ground() -> Terrain {
+        let mut map = vec![vec![Air; 10]; 10];
+        for row in &mut map[..] {
+            row[0] = Steel;
+        }
+        map[7] = vec![Dirt; 10];
+        map[8] = vec![Dirt; 10];
+        map[9] = vec![Steel; 10];
+        Terrain::from_map(&map, 4)
+    }
+
+    fn walker(x: i32) -> Unit {
+        Unit {
+            direction: 1,
+            state: Activity::Walking,
+            x,
+            y: 27,
+        }
+    }
+
+    fn run(unit: &mut Unit, terrain: &mut Terrain, ticks: usize) {
+        for _ in 0..ticks {
+            unit.step(terrain, &[], (100, 100));
+        }
+    }
+
+    #[test]
+    fn walkers_climb_steps_and_turn_at_walls() {
+        let mut terrain = ground();
+        for x in 20..24 {
+            for y in 25..28 {
+                terrain.fill(x, y);
+            }
+        }
+        let mut unit = walker(16);
+        run(&mut unit, &mut terrain, 5);
+        assert_eq!((unit.x, unit.y), (21, 24));
+        run(&mut unit, &mut terrain, 18);
+        assert_eq!((unit.x, unit.y, unit.direction), (39, 27, 1));
+        run(&mut unit, &mut terrain, 1);
+        assert_eq!((unit.x, unit.direction), (39, -1));
+        let mut blocked = walker(10);
+        blocked.step(&mut terrain, &[(14, 27)], (100, 100));
+        assert_eq!((blocked.x, blocked.direction), (10, -1));
+    }
+
+    #[test]
+    fn falls_land_or_kill() {
+        let mut terrain = ground();
+        let mut unit = Unit::drop_at(20, 0);
+        run(&mut unit, &mut terrain, 10);
+        assert_eq!((unit.y, unit.state), (27, Activity::Walking));
+        let mut terrain = Terrain::from_map(&vec![vec![Air; 4]; 20], 4);
+        for x in 0..16 {
+            terrain.fill(x, 79);
+        }
+        let mut unit = Unit::drop_at(8, 0);
+        run(&mut unit, &mut terrain, 30);
+        assert_eq!(unit.state, Activity::Dead);
+    }
+
+    #[test]
+    fn diggers_stop_at_steel_and_builders_climb() {
+        let mut terrain = ground();
+        let mut digger = walker(20);
+        assert!(digger.assign(Skill::Digger));
+        assert!(!digger.assign(Skill::Blocker));
+        run(&mut digger, &mut terrain, DIG_TICKS as usize * 9);
+        assert_eq!((digger.y, digger.state), (35, Activity::Walking));
+        assert!(!terrain.solid(20, 35));
+
+        let mut terrain = ground();
+        let mut builder = walker(10);
+        builder.assign(Skill::Builder);
+        run(
+            &mut builder,
+            &mut terrain,
+            BUILD_TICKS as usize * BRICKS as usize,
+        );
+        assert_eq!((builder.x, builder.y), (34, 15));
+        assert!(terrain.solid(34, 16));
+        run(&mut builder, &mut terrain, BUILD_TICKS as usize);
+        assert_eq!(builder.state, Activity::Walking);
+    }
+}
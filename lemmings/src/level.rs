@@ -0,0 +1,222 @@
+//! Levels are text files: the name on the first line, then `<key> <number>` lines for `quota`,
+//! `units`, `interval` (seconds between units), `time` (seconds) and how many `blockers`,
+//! `diggers` and `builders` may be handed out, and last a `map` line followed by the map itself:
+//! `#` for dirt, `=` for steel, `S` for the hatch the units drop from, `E` for the exit they
+//! walk out through and anything else for air.
+
+use crate::terrain::Pixel;
+use std::fmt;
+
+/// Smallest and largest map in blocks, as (columns, rows).
+pub const MAP_SIZES: ((usize, usize), (usize, usize)) = ((20, 10), (60, 30));
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    Empty,
+    /// Not a known key, or not a number after it.
+    Line {
+        line: usize,
+    },
+    /// A key that has to be there is not.
+    Missing(&'static str),
+    /// A quota of nobody, or of more units than there are.
+    Quota,
+    /// No map, or one outside `MAP_SIZES`.
+    Map,
+    /// Not exactly one hatch.
+    Hatch,
+    /// Not exactly one exit.
+    Exit,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no name"),
+            Self::Line { line } => write!(f, "line {} is not understood", line + 1),
+            Self::Missing(key) => write!(f, "no {}", key),
+            Self::Quota => write!(f, "the quota has to be between 1 and the units"),
+            Self::Map => write!(
+                f,
+                "the map has to be {}x{} to {}x{} blocks",
+                MAP_SIZES.0 .0, MAP_SIZES.0 .1, MAP_SIZES.1 .0, MAP_SIZES.1 .1
+            ),
+            Self::Hatch => write!(f, "the map needs exactly one hatch"),
+            Self::Exit => write!(f, "the map needs exactly one exit"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Level {
+    /// Block the exit stands on top of, as (column, row).
+    pub exit: (i32, i32),
+    /// Block the units drop from.
+    pub hatch: (i32, i32),
+    /// Seconds between two units dropping.
+    pub interval: f32,
+    pub map: Vec<Vec<Pixel>>,
+    pub name: String,
+    /// Units to bring home.
+    pub quota: u32,
+    /// Blockers, diggers and builders to hand out.
+    pub skills: [u32; 3],
+    /// Seconds before the level ends.
+    pub time: f32,
+    pub units: u32,
+}
+
+impl Level {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text.lines().enumerate();
+        let name = loop {
+            match lines.next() {
+                Some((_, line)) if line.trim().is_empty() => continue,
+                Some((_, line)) => break line.trim().to_string(),
+                None => return Err(ParseError::Empty),
+            }
+        };
+        let mut values: [Option<f32>; 7] = [None; 7];
+        const KEYS: [&str; 7] = [
+            "quota", "units", "interval", "time", "blockers", "diggers", "builders",
+        ];
+        let mut rows = Vec::new();
+        while let Some((line, text)) = lines.next() {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            match words.as_slice() {
+                [] => {}
+                ["map"] => {
+                    rows = lines.by_ref().map(|(_, text)| text.trim_end()).collect();
+                    break;
+                }
+                [key, value] => {
+                    let index = KEYS
+                        .iter()
+                        .position(|known| known == key)
+                        .ok_or(ParseError::Line { line })?;
+                    let value: f32 = value.parse().map_err(|_| ParseError::Line { line })?;
+                    if value < 0.0 {
+                        return Err(ParseError::Line { line });
+                    }
+                    values[index] = Some(value);
+                }
+                _ => return Err(ParseError::Line { line }),
+            }
+        }
+        let mut numbers = [0.0; 7];
+        for (index, value) in values.iter().enumerate() {
+            numbers[index] = value.ok_or(ParseError::Missing(KEYS[index]))?;
+        }
+        let [quota, units, interval, time, blockers, diggers, builders] = numbers;
+        if quota < 1.0 || quota > units {
+            return Err(ParseError::Quota);
+        }
+
+        while rows.last() == Some(&"") {
+            rows.pop();
+        }
+        let columns = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0);
+        let (smallest, largest) = MAP_SIZES;
+        if !(smallest.0..=largest.0).contains(&columns)
+            || !(smallest.1..=largest.1).contains(&rows.len())
+        {
+            return Err(ParseError::Map);
+        }
+        let (mut hatches, mut exits) = (Vec::new(), Vec::new());
+        let mut map = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            let mut blocks = vec![Pixel::Air; columns];
+            for (x, symbol) in row.chars().enumerate() {
+                let cell = (x as i32, y as i32);
+                match symbol {
+                    '#' => blocks[x] = Pixel::Dirt,
+                    '=' => blocks[x] = Pixel::Steel,
+                    'S' => hatches.push(cell),
+                    'E' => exits.push(cell),
+                    _ => {}
+                }
+            }
+            map.push(blocks);
+        }
+        let hatch = match hatches.as_slice() {
+            [hatch] => *hatch,
+            _ => return Err(ParseError::Hatch),
+        };
+        let exit = match exits.as_slice() {
+            [exit] => *exit,
+            _ => return Err(ParseError::Exit),
+        };
+        Ok(Self {
+            exit,
+            hatch,
+            interval,
+            map,
+            name,
+            quota: quota as u32,
+            skills: [blockers as u32, diggers as u32, builders as u32],
+            time,
+            units: units as u32,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn level(map: &str) -> String {
+        format!(
+            "Test\nquota 2\nunits 3\ninterval 1.5\ntime 60\nblockers 1\ndiggers 2\nbuilders 0\nmap\n{}",
+            map
+        )
+    }
+
+    fn map(rows: &[&str]) -> String {
+        rows.join("\n")
+    }
+
+    #[test]
+    fn parses_a_level() {
+        let mut rows = vec!["...................."; 10];
+        rows[2] = "..S................=";
+        rows[5] = "...............E....";
+        rows[6] = "####################";
+        let level = Level::parse(&level(&map(&rows))).unwrap();
+        assert_eq!(level.name, "Test");
+        assert_eq!((level.quota, level.units, level.interval), (2, 3, 1.5));
+        assert_eq!(level.skills, [1, 2, 0]);
+        assert_eq!((level.hatch, level.exit), ((2, 2), (15, 5)));
+        assert_eq!(level.map[2][19], Pixel::Steel);
+        assert_eq!(level.map[6][0], Pixel::Dirt);
+        assert_eq!(level.map[5][15], Pixel::Air);
+        for text in [
+            include_str!("../assets/levels/1-dig-down.txt"),
+            include_str!("../assets/levels/2-bridge.txt"),
+            include_str!("../assets/levels/3-the-wall.txt"),
+        ] {
+            assert!(Level::parse(text).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_broken_levels() {
+        let mut rows = vec!["...................."; 10];
+        rows[2] = "..S.................";
+        let parse = |text: &str| Level::parse(text).unwrap_err();
+        assert_eq!(parse(""), ParseError::Empty);
+        assert_eq!(parse(&level(&map(&rows))), ParseError::Exit);
+        rows[3] = "..S.............E...";
+        assert_eq!(parse(&level(&map(&rows))), ParseError::Hatch);
+        assert_eq!(parse(&level(&map(&rows[..4]))), ParseError::Map);
+        assert_eq!(parse("Test\nquota 1\nmap"), ParseError::Missing("units"));
+        assert_eq!(parse("Test\nspeed 3"), ParseError::Line { line: 1 });
+        assert_eq!(
+            parse(&level("").replace("quota 2", "quota 4")),
+            ParseError::Quota
+        );
+    }
+}
@@ -0,0 +1,741 @@
+use bevy::{
+    math::const_vec2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use crowd::{Activity, Skill, Unit, UNIT_HEIGHT};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use level::Level;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+use terrain::{Pixel, Terrain};
+
+pub mod crowd;
+pub mod level;
+pub mod terrain;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.04, 0.04, 0.1);
+/// Pixels each block of a level map turns into.
+const BLOCK: i32 = 4;
+const EXIT_COLOR: Color = Color::rgb(0.95, 0.8, 0.3);
+/// Ticks run per tick of time when fast-forwarding.
+const FAST: u32 = 3;
+const GAME: &str = "lemmings";
+const HATCH_COLOR: Color = Color::rgb(0.45, 0.3, 0.2);
+/// Levels that come with the game; more are read from `LEVELS_DIRECTORY`.
+const LEVELS: [&str; 3] = [
+    include_str!("../assets/levels/1-dig-down.txt"),
+    include_str!("../assets/levels/2-bridge.txt"),
+    include_str!("../assets/levels/3-the-wall.txt"),
+];
+const LEVELS_DIRECTORY: &str = "levels";
+/// Screen pixels per terrain pixel.
+const PIXEL: f32 = 3.0;
+const RECORDS_FILE: &str = "records";
+const TERRAIN_CENTER: Vec2 = const_vec2!([0.0, 10.0]);
+const TEXT_COLOR: Color = Color::rgb(0.93, 0.95, 0.92);
+/// Seconds per tick of the units.
+const TICK: f32 = 1.0 / 24.0;
+
+pub const WINDOW_HEIGHT: f32 = 480.0;
+pub const WINDOW_WIDTH: f32 = 760.0;
+
+/// Units, the hatch and the exit, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+struct Game {
+    /// Where the exit takes units in, in terrain pixels.
+    exit: (i32, i32),
+    fast: bool,
+    /// Where units drop from, in terrain pixels.
+    hatch: (i32, i32),
+    /// Index of the level in the library.
+    index: usize,
+    level: Level,
+    paused: bool,
+    /// The terrain has changed since it was last drawn.
+    redraw: bool,
+    /// Seconds until the next unit drops.
+    release: f32,
+    released: u32,
+    selected: Skill,
+    /// Skills left to hand out, in `Skill::ALL` order.
+    skills: [u32; 3],
+    terrain: Terrain,
+    /// Seconds not yet run as ticks.
+    ticks: f32,
+    time_left: f32,
+    units: Vec<Unit>,
+}
+
+impl Game {
+    fn new(level: &Level, index: usize) -> Self {
+        let pixel = |(x, y): (i32, i32)| (x * BLOCK + BLOCK / 2, y * BLOCK + BLOCK - 1);
+        Self {
+            exit: pixel(level.exit),
+            fast: false,
+            hatch: pixel(level.hatch),
+            index,
+            level: level.clone(),
+            paused: false,
+            redraw: true,
+            release: 1.0,
+            released: 0,
+            selected: Skill::Blocker,
+            skills: level.skills,
+            terrain: Terrain::from_map(&level.map, BLOCK),
+            ticks: 0.0,
+            time_left: level.time,
+            units: Vec::new(),
+        }
+    }
+
+    fn count(&self, state: Activity) -> u32 {
+        self.units.iter().filter(|unit| unit.state == state).count() as u32
+    }
+
+    /// Screen position of a terrain pixel's center.
+    fn position(&self, x: f32, y: f32) -> Vec2 {
+        let size = Vec2::new(self.terrain.width as f32, self.terrain.height as f32);
+        TERRAIN_CENTER + (Vec2::new(x + 0.5, -y - 0.5) - Vec2::new(size.x, -size.y) / 2.0) * PIXEL
+    }
+
+    /// The unit under a screen position, walkers first.
+    fn unit_at(&self, position: Vec2) -> Option<usize> {
+        let size = Vec2::new(self.terrain.width as f32, self.terrain.height as f32);
+        let pixel = (position - TERRAIN_CENTER) / PIXEL + Vec2::new(size.x, -size.y) / 2.0;
+        let (x, y) = (pixel.x, -pixel.y);
+        let hits = |unit: &Unit| {
+            unit.is_active()
+                && (unit.x as f32 + 0.5 - x).abs() <= 3.5
+                && y >= (unit.y - UNIT_HEIGHT) as f32
+                && y <= unit.y as f32 + 2.0
+        };
+        let found: Vec<usize> = self
+            .units
+            .iter()
+            .enumerate()
+            .filter(|(_, unit)| hits(unit))
+            .map(|(index, _)| index)
+            .collect();
+        found
+            .iter()
+            .find(|index| self.units[**index].state == Activity::Walking)
+            .or_else(|| found.first())
+            .copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// Levels to choose from: the bundled ones, then those in the data directory.
+struct Library(Vec<Level>);
+
+/// The level picked in the menu.
+struct Menu {
+    index: usize,
+}
+
+/// The menu, or the result of a level.
+#[derive(Component)]
+struct MenuText;
+
+/// The most units brought home in each level by name, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records(HashMap<String, u32>);
+
+struct Sounds {
+    assign: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    failed: Handle<AudioSource>,
+    passed: Handle<AudioSource>,
+    release: Handle<AudioSource>,
+    saved: Handle<AudioSource>,
+}
+
+/// The terrain bitmap, redrawn whenever it changes.
+#[derive(Component)]
+struct TerrainSprite;
+
+pub struct LemmingsPlugin;
+
+impl Plugin for LemmingsPlugin {
+    fn build(&self, app: &mut App) {
+        let first = Level::parse(LEVELS[0]).expect("bundled levels are valid");
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Library(Vec::new()))
+            .insert_resource(Menu { index: 0 })
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .insert_resource(Game::new(&first, 0))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_startup_system(library_load)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(controls_input)
+                    .with_system(assign_click)
+                    .with_system(simulate),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(level_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(actors_render)
+            .add_system(terrain_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Lemmings".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The hatch, the exit and the units, the one under the cursor outlined.
+fn actors_render(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() != GameState::Playing {
+        // Shades the level behind the banner.
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(0.0, 0.0, 0.0, 0.7),
+                    custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 850.0),
+                ..Default::default()
+            })
+            .insert(Actor);
+    }
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    // A box of terrain pixels from (left, top) to (right, bottom), inclusive.
+    let mut draw = |color: Color, (left, top): (i32, i32), (right, bottom): (i32, i32), z: f32| {
+        let center = game.position((left + right) as f32 / 2.0, (top + bottom) as f32 / 2.0);
+        let size = Vec2::new((right - left + 1) as f32, (bottom - top + 1) as f32) * PIXEL;
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(center.extend(z)),
+                ..Default::default()
+            })
+            .insert(Actor);
+    };
+
+    let (x, y) = game.hatch;
+    draw(HATCH_COLOR, (x - 6, y - 3), (x + 5, y), 2.0);
+    draw(Color::BLACK, (x - 4, y - 1), (x + 3, y), 2.1);
+    let (x, y) = game.exit;
+    draw(EXIT_COLOR, (x - 5, y - 11), (x + 4, y), 2.0);
+    draw(Color::BLACK, (x - 3, y - 9), (x + 2, y), 2.1);
+
+    let hovered = game_hud::cursor_world_position(&windows).and_then(|cursor| game.unit_at(cursor));
+    for (index, unit) in game.units.iter().enumerate() {
+        if !unit.is_active() {
+            continue;
+        }
+        let (x, y) = (unit.x, unit.y);
+        let body = match unit.state {
+            Activity::Blocking => Color::rgb(0.9, 0.25, 0.25),
+            Activity::Digging(_) => Color::rgb(0.9, 0.75, 0.2),
+            Activity::Building(..) => Color::rgb(0.95, 0.5, 0.15),
+            _ => Color::rgb(0.3, 0.45, 1.0),
+        };
+        if hovered == Some(index) {
+            draw(Color::WHITE, (x - 3, y - UNIT_HEIGHT), (x + 2, y + 1), 3.0);
+        }
+        draw(Color::rgb(0.3, 0.9, 0.3), (x - 1, y - 7), (x, y - 6), 4.0);
+        draw(Color::rgb(0.95, 0.8, 0.65), (x - 1, y - 5), (x, y - 4), 4.0);
+        draw(body, (x - 1, y - 3), (x, y), 4.0);
+        if unit.state == Activity::Blocking {
+            draw(body, (x - 3, y - 3), (x + 2, y - 3), 4.0);
+        }
+    }
+}
+
+/// A left click hands the selected skill to the unit under the cursor.
+fn assign_click(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let skill = game.selected;
+    let slot = Skill::ALL.iter().position(|known| *known == skill).unwrap();
+    if game.skills[slot] == 0 {
+        return;
+    }
+    let index =
+        match game_hud::cursor_world_position(&windows).and_then(|cursor| game.unit_at(cursor)) {
+            Some(index) => index,
+            None => return,
+        };
+    if game.units[index].assign(skill) {
+        game.skills[slot] -= 1;
+        audio.play(sounds.assign.clone());
+    }
+}
+
+/// 1 to 3 pick a skill, Space pauses, F fast-forwards, R starts over and Esc gives up.
+fn controls_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    library: Res<Library>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for (key, skill) in [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3]
+        .iter()
+        .zip(Skill::ALL)
+    {
+        if keyboard_input.just_pressed(*key) {
+            game.selected = skill;
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        game.paused = !game.paused;
+    }
+    if keyboard_input.just_pressed(KeyCode::F) {
+        game.fast = !game.fast;
+    }
+    if keyboard_input.just_pressed(KeyCode::R) {
+        *game = Game::new(&library.0[game.index], game.index);
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn level_over(
+    game: Res<Game>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let saved = game.count(Activity::Saved);
+    if saved >= game.level.quota {
+        audio.play(sounds.passed.clone());
+    } else {
+        audio.play(sounds.failed.clone());
+    }
+    let best = records.0.entry(game.level.name.clone()).or_insert(0);
+    if saved > *best {
+        *best = saved;
+        if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, &*records) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+/// Reads the bundled levels and those in the data directory.
+fn library_load(mut library: ResMut<Library>) {
+    library.0 = LEVELS
+        .iter()
+        .map(|text| Level::parse(text).expect("bundled levels are valid"))
+        .collect();
+    match load_custom() {
+        Ok(levels) => library.0.extend(levels),
+        Err(error) => eprintln!("could not load levels: {}", error),
+    }
+}
+
+fn load_custom() -> Result<Vec<Level>, Box<dyn Error>> {
+    let directory = game_persistence::directory(GAME)?.join(LEVELS_DIRECTORY);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("txt"))
+        .collect();
+    paths.sort();
+    let mut levels = Vec::new();
+    for path in paths {
+        match Level::parse(&fs::read_to_string(&path)?) {
+            Ok(level) => levels.push(level),
+            Err(error) => eprintln!("skipping {}: {}", path.display(), error),
+        }
+    }
+    Ok(levels)
+}
+
+/// Up/Down picks a level, Enter starts it.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    library: Res<Library>,
+    mut menu: ResMut<Menu>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let count = library.0.len();
+    if navigation.just_pressed(Navigate::Up) {
+        menu.index = (menu.index + count - 1) % count;
+    } else if navigation.just_pressed(Navigate::Down) {
+        menu.index = (menu.index + 1) % count;
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(&library.0[menu.index], menu.index);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Enter goes on to the next level once the quota was met and tries again otherwise; Esc goes
+/// back to the menu.
+fn over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    library: Res<Library>,
+    mut menu: ResMut<Menu>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let mut index = game.index;
+        if game.count(Activity::Saved) >= game.level.quota {
+            index = (index + 1) % library.0.len();
+        }
+        menu.index = index;
+        *game = Game::new(&library.0[index], index);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 10.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        assign: audio_sources.add(Tone::new(Waveform::Square, 880.0, 0.04).volume(0.2).into()),
+        death: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.15)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        failed: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.4).fade(),
+        ])),
+        passed: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.3).fade(),
+        ])),
+        release: audio_sources.add(
+            Tone::new(Waveform::Sine, 300.0, 0.08)
+                .slide(200.0)
+                .volume(0.2)
+                .into(),
+        ),
+        saved: audio_sources.add(
+            Tone::new(Waveform::Sine, 660.0, 0.12)
+                .slide(990.0)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+/// Drops units from the hatch and runs the ticks the time passed covers, ending the level when
+/// the clock runs out or nobody is left walking.
+fn simulate(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.paused {
+        return;
+    }
+    let game = &mut *game;
+    let speed = if game.fast { FAST as f32 } else { 1.0 };
+    let delta = time.delta_seconds() * speed;
+    game.time_left = (game.time_left - delta).max(0.0);
+    game.ticks += delta;
+
+    let (saved, dead) = (game.count(Activity::Saved), game.count(Activity::Dead));
+    while game.ticks >= TICK {
+        game.ticks -= TICK;
+        if game.released < game.level.units {
+            game.release -= TICK;
+            if game.release <= 0.0 {
+                game.release = game.level.interval;
+                game.released += 1;
+                game.units.push(Unit::drop_at(game.hatch.0, game.hatch.1));
+                audio.play(sounds.release.clone());
+            }
+        }
+        let blockers: Vec<(i32, i32)> = game
+            .units
+            .iter()
+            .filter(|unit| unit.state == Activity::Blocking)
+            .map(|unit| (unit.x, unit.y))
+            .collect();
+        for unit in &mut game.units {
+            let digging = matches!(unit.state, Activity::Digging(_) | Activity::Building(..));
+            unit.step(&mut game.terrain, &blockers, game.exit);
+            game.redraw |= digging;
+        }
+    }
+    if game.count(Activity::Saved) > saved {
+        audio.play(sounds.saved.clone());
+    }
+    if game.count(Activity::Dead) > dead {
+        audio.play(sounds.death.clone());
+    }
+
+    // Blockers stand for good, so the level is over once only they are left.
+    let all_out = game.released == game.level.units
+        && game
+            .units
+            .iter()
+            .all(|unit| !unit.is_active() || unit.state == Activity::Blocking);
+    if all_out || game.time_left <= 0.0 {
+        state.set(GameState::Over).unwrap();
+    }
+}
+
+/// Paints the terrain into its image, making a new one when the size changes.
+fn terrain_render(
+    mut commands: Commands,
+    mut images: ResMut<Assets<Image>>,
+    mut game: ResMut<Game>,
+    sprites: Query<(Entity, &Handle<Image>), With<TerrainSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    let terrain = &game.terrain;
+    let mut data = Vec::with_capacity((terrain.width * terrain.height * 4) as usize);
+    for y in 0..terrain.height {
+        for x in 0..terrain.width {
+            // A little grain so the ground does not look flat.
+            let grain = ((x * 7 + y * 13) % 5) as u8 * 8;
+            let pixel = match terrain.get(x, y) {
+                Pixel::Air => [0, 0, 0, 0],
+                Pixel::Dirt => [130 + grain, 80 + grain / 2, 40, 255],
+                Pixel::Steel if x % BLOCK == 0 || y % BLOCK == 0 => [170, 170, 180, 255],
+                Pixel::Steel => [120, 120, 130, 255],
+            };
+            data.extend_from_slice(&pixel);
+        }
+    }
+    let size = Extent3d {
+        width: terrain.width as u32,
+        height: terrain.height as u32,
+        depth_or_array_layers: 1,
+    };
+    for (entity, handle) in sprites.iter() {
+        match images.get_mut(handle) {
+            Some(image) if image.texture_descriptor.size == size => {
+                image.data = data;
+                return;
+            }
+            _ => commands.entity(entity).despawn(),
+        }
+    }
+    let image = Image::new(
+        size,
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(size.width as f32, size.height as f32) * PIXEL),
+                ..Default::default()
+            },
+            texture: images.add(image),
+            transform: Transform::from_translation(TERRAIN_CENTER.extend(1.0)),
+            ..Default::default()
+        })
+        .insert(TerrainSprite);
+}
+
+fn texts_update(
+    game: Res<Game>,
+    library: Res<Library>,
+    menu: Res<Menu>,
+    records: Res<Records>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let saved = game.count(Activity::Saved);
+    let out = game.units.iter().filter(|unit| unit.is_active()).count();
+    let seconds = game.time_left.ceil() as u32;
+    let (hud, help, banner) = match state.current() {
+        GameState::Menu => {
+            let levels = library
+                .0
+                .iter()
+                .enumerate()
+                .map(|(index, level)| {
+                    let marker = if index == menu.index { ">" } else { " " };
+                    let best = match records.0.get(&level.name) {
+                        Some(best) => format!("best {}/{}", best, level.units),
+                        None => String::new(),
+                    };
+                    format!(
+                        "{} {:<18} save {:>2} of {:>2}  {:<12}",
+                        marker, level.name, level.quota, level.units, best
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                String::new(),
+                "Up/Down: choose   Enter: start".to_string(),
+                format!("LEMMINGS\n\n{}", levels),
+            )
+        }
+        GameState::Playing | GameState::Over => {
+            let mut hud = format!(
+                "{}   Out {}   Saved {}/{}   Dropped {}/{}   Time {}:{:02}",
+                game.level.name,
+                out,
+                saved,
+                game.level.quota,
+                game.released,
+                game.level.units,
+                seconds / 60,
+                seconds % 60
+            );
+            if game.fast {
+                hud.push_str("   FAST");
+            }
+            if game.paused {
+                hud.push_str("   PAUSED");
+            }
+            let skills = Skill::ALL
+                .iter()
+                .zip(game.skills)
+                .enumerate()
+                .map(|(index, (skill, left))| {
+                    let label = format!("{} {} {}", index + 1, skill.name(), left);
+                    if *skill == game.selected {
+                        format!("[{}]", label)
+                    } else {
+                        format!(" {} ", label)
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("  ");
+            let help = format!(
+                "{}\nClick: hand out   Space: pause   F: fast   R: restart   Esc: menu",
+                skills
+            );
+            let banner = if *state.current() == GameState::Over {
+                let (headline, next) = if saved >= game.level.quota {
+                    ("LEVEL DONE", "next level")
+                } else {
+                    ("NOT ENOUGH", "try again")
+                };
+                format!(
+                    "{}\n\nSaved {} of {}, {} needed\n\nEnter: {}   Esc: menu",
+                    headline, saved, game.level.units, game.level.quota, next
+                )
+            } else {
+                String::new()
+            };
+            (hud, help, banner)
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
@@ -0,0 +1,109 @@
+//! The ground as a bitmap of pixels that can be dug away and built on. Rows count down from the
+//! top.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pixel {
+    Air,
+    /// Ground that can be dug.
+    Dirt,
+    /// Ground nothing gets through.
+    Steel,
+}
+
+pub struct Terrain {
+    pub height: i32,
+    pixels: Vec<Pixel>,
+    pub width: i32,
+}
+
+impl Terrain {
+    /// Blows up a map of blocks, each turning into `scale` by `scale` pixels.
+    pub fn from_map(map: &[Vec<Pixel>], scale: i32) -> Self {
+        let columns = map.iter().map(Vec::len).max().unwrap_or(0) as i32;
+        let (width, height) = (columns * scale, map.len() as i32 * scale);
+        let mut pixels = vec![Pixel::Air; (width * height) as usize];
+        for (row, blocks) in map.iter().enumerate() {
+            for (column, block) in blocks.iter().enumerate() {
+                for dy in 0..scale {
+                    for dx in 0..scale {
+                        let x = column as i32 * scale + dx;
+                        let y = row as i32 * scale + dy;
+                        pixels[(y * width + x) as usize] = *block;
+                    }
+                }
+            }
+        }
+        Self {
+            height,
+            pixels,
+            width,
+        }
+    }
+
+    /// Digs a pixel away; returns what was there, so steel stays put.
+    pub fn clear(&mut self, x: i32, y: i32) -> Pixel {
+        let pixel = self.get(x, y);
+        if pixel == Pixel::Dirt {
+            let index = self.index(x, y);
+            self.pixels[index] = Pixel::Air;
+        }
+        pixel
+    }
+
+    /// Fills a pixel of air with dirt; true if it was air on the map.
+    pub fn fill(&mut self, x: i32, y: i32) -> bool {
+        if !self.contains(x, y) || self.get(x, y) != Pixel::Air {
+            return false;
+        }
+        let index = self.index(x, y);
+        self.pixels[index] = Pixel::Dirt;
+        true
+    }
+
+    /// Off the sides is steel, so nobody walks out; above and below is open air.
+    pub fn get(&self, x: i32, y: i32) -> Pixel {
+        if !(0..self.width).contains(&x) {
+            Pixel::Steel
+        } else if self.contains(x, y) {
+            self.pixels[self.index(x, y)]
+        } else {
+            Pixel::Air
+        }
+    }
+
+    pub fn solid(&self, x: i32, y: i32) -> bool {
+        self.get(x, y) != Pixel::Air
+    }
+
+    fn contains(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scales_the_map_and_digs() {
+        use Pixel::{Air, Dirt, Steel};
+        let mut terrain = Terrain::from_map(&[vec![Air, Air], vec![Dirt, Steel]], 3);
+        assert_eq!((terrain.width, terrain.height), (6, 6));
+        assert!(!terrain.solid(2, 2));
+        assert_eq!(terrain.get(2, 3), Dirt);
+        assert_eq!(terrain.get(3, 5), Steel);
+        assert_eq!(terrain.get(-1, 0), Steel);
+        assert_eq!(terrain.get(0, 6), Air);
+        assert_eq!(terrain.clear(1, 4), Dirt);
+        assert_eq!(terrain.clear(4, 4), Steel);
+        assert!(!terrain.solid(1, 4));
+        assert!(terrain.solid(4, 4));
+        assert!(terrain.fill(1, 4));
+        assert!(!terrain.fill(1, 4));
+        assert!(!terrain.fill(0, 9));
+    }
+}
@@ -0,0 +1,665 @@
+use bevy::prelude::*;
+use game_ai::path;
+use game_audio::{Tone, Waveform};
+use game_grid::{Direction, Position};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::random;
+
+const AI_LOOKAHEAD: usize = 300;
+/// Chance per step that an AI rider turns although going straight is just as good.
+const AI_WANDER: f32 = 0.04;
+const ARENA_HEIGHT: u32 = 44;
+const ARENA_WIDTH: u32 = 64;
+const ARROWS: [KeyCode; 4] = [KeyCode::Up, KeyCode::Left, KeyCode::Down, KeyCode::Right];
+const BACKGROUND_COLOR: Color = Color::rgb(0.02, 0.02, 0.06);
+const BEST_OF_CHOICES: [u32; 4] = [1, 3, 5, 7];
+const CELL: f32 = 12.0;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const STEP_TIME: f32 = 0.06;
+const WASD: [KeyCode; 4] = [KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D];
+
+pub const WINDOW_HEIGHT: f32 = ARENA_HEIGHT as f32 * CELL + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = ARENA_WIDTH as f32 * CELL;
+
+/// Start cell, heading and color of each rider slot.
+const SLOTS: [(i32, i32, Direction, Color); 4] = [
+    (
+        8,
+        ARENA_HEIGHT as i32 / 2,
+        Direction::Right,
+        Color::rgb(0.2, 0.9, 1.0),
+    ),
+    (
+        ARENA_WIDTH as i32 - 9,
+        ARENA_HEIGHT as i32 / 2,
+        Direction::Left,
+        Color::rgb(1.0, 0.55, 0.1),
+    ),
+    (
+        ARENA_WIDTH as i32 / 2,
+        ARENA_HEIGHT as i32 - 7,
+        Direction::Down,
+        Color::rgb(0.95, 0.2, 0.9),
+    ),
+    (
+        ARENA_WIDTH as i32 / 2,
+        6,
+        Direction::Up,
+        Color::rgb(0.4, 1.0, 0.3),
+    ),
+];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Controller {
+    Ai,
+    /// Up, left, down and right keys; a lone player may steer with several sets.
+    Keyboard(&'static [[KeyCode; 4]]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    RoundOver,
+}
+
+struct Match {
+    best_of: u32,
+    mode: Mode,
+    round: u32,
+    /// Winner of the last round, `None` when every rider crashed in the same step.
+    round_winner: Option<usize>,
+    wins: Vec<u32>,
+}
+
+impl Match {
+    fn wins_needed(&self) -> u32 {
+        self.best_of / 2 + 1
+    }
+
+    fn champion(&self) -> Option<usize> {
+        self.wins
+            .iter()
+            .position(|wins| *wins >= self.wins_needed())
+    }
+}
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    VersusAi,
+    TwoPlayers,
+    FreeForAll,
+}
+
+impl Mode {
+    const ALL: [Mode; 3] = [Self::VersusAi, Self::TwoPlayers, Self::FreeForAll];
+
+    fn controllers(self) -> Vec<Controller> {
+        match self {
+            Self::VersusAi => vec![Controller::Keyboard(&[WASD, ARROWS]), Controller::Ai],
+            Self::TwoPlayers => vec![
+                Controller::Keyboard(&[WASD]),
+                Controller::Keyboard(&[ARROWS]),
+            ],
+            Self::FreeForAll => vec![
+                Controller::Keyboard(&[WASD, ARROWS]),
+                Controller::Ai,
+                Controller::Ai,
+                Controller::Ai,
+            ],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::VersusAi => "1 player vs AI",
+            Self::TwoPlayers => "2 players (WASD vs arrows)",
+            Self::FreeForAll => "1 player vs 3 AI",
+        }
+    }
+}
+
+#[derive(Component)]
+struct Rider {
+    alive: bool,
+    controller: Controller,
+    direction: Direction,
+    index: usize,
+    wanted: Direction,
+}
+
+#[derive(Component)]
+struct RoundOverText;
+
+struct Sounds {
+    crash: Handle<AudioSource>,
+    round_won: Handle<AudioSource>,
+    start: Handle<AudioSource>,
+}
+
+struct StepTimer(Timer);
+
+#[derive(Component)]
+struct TrailCell;
+
+/// Which rider's trail covers each cell of the arena.
+struct Trails(Vec<Option<usize>>);
+
+impl Trails {
+    fn empty() -> Self {
+        Self(vec![None; (ARENA_WIDTH * ARENA_HEIGHT) as usize])
+    }
+
+    fn index(position: Position) -> usize {
+        (position.y as u32 * ARENA_WIDTH + position.x as u32) as usize
+    }
+
+    fn free(&self, position: Position) -> bool {
+        position.in_arena(ARENA_WIDTH, ARENA_HEIGHT) && self.0[Self::index(position)].is_none()
+    }
+
+    fn mark(&mut self, position: Position, rider: usize) {
+        self.0[Self::index(position)] = Some(rider);
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Input,
+    Movement,
+}
+
+pub struct LightCyclesPlugin;
+
+impl Plugin for LightCyclesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Match {
+                best_of: 3,
+                mode: Mode::VersusAi,
+                round: 0,
+                round_winner: None,
+                wins: Vec::new(),
+            })
+            .insert_resource(StepTimer(Timer::from_seconds(STEP_TIME, true)))
+            .insert_resource(Trails::empty())
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(menu_enter))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu).with_system(despawn_all::<MenuText>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(round_start))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(rider_input.label(Step::Input))
+                    .with_system(ai_steering.label(Step::Input))
+                    .with_system(rider_movement.label(Step::Movement).after(Step::Input))
+                    .with_system(round_end.after(Step::Movement)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::RoundOver).with_system(round_over))
+            .add_system_set(
+                SystemSet::on_update(GameState::RoundOver).with_system(round_over_input),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::RoundOver).with_system(despawn_all::<RoundOverText>),
+            )
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Light Cycles".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// AI riders pick the legal direction leading into the largest open area.
+fn ai_steering(trails: Res<Trails>, mut riders: Query<(&mut Rider, &Position)>) {
+    for (mut rider, position) in riders.iter_mut() {
+        if rider.controller != Controller::Ai || !rider.alive {
+            continue;
+        }
+        let straight = rider.direction;
+        let mut best = (straight, open_area(&trails, position.step(straight)));
+        for direction in [
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+            Direction::Right,
+        ] {
+            if direction == straight || direction == straight.opposite() {
+                continue;
+            }
+            let area = open_area(&trails, position.step(direction));
+            if area > best.1 || (area == best.1 && area > 0 && random::<f32>() < AI_WANDER) {
+                best = (direction, area);
+            }
+        }
+        rider.wanted = best.0;
+    }
+}
+
+fn cell_translation(position: Position, z: f32) -> Vec3 {
+    game_grid::cell_to_window(
+        position,
+        ARENA_WIDTH,
+        ARENA_HEIGHT,
+        Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT - HUD_HEIGHT),
+    )
+    .extend(z)
+        - Vec3::new(0.0, HUD_HEIGHT / 2.0, 0.0)
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn hud_update(
+    game: Res<Match>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let value = if *state.current() == GameState::Menu {
+        String::new()
+    } else {
+        let scores = game
+            .wins
+            .iter()
+            .enumerate()
+            .map(|(index, wins)| format!("P{} {}", index + 1, wins))
+            .collect::<Vec<_>>()
+            .join("  ");
+        format!(
+            "ROUND {}   BEST OF {}   {}",
+            game.round, game.best_of, scores
+        )
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, game: Res<Match>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&game),
+            28.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Match>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let mode_index = Mode::ALL
+        .iter()
+        .position(|mode| *mode == game.mode)
+        .unwrap_or(0);
+    let best_of_index = BEST_OF_CHOICES
+        .iter()
+        .position(|best_of| *best_of == game.best_of)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) || keyboard_input.just_pressed(KeyCode::W) {
+        game.mode = Mode::ALL[(mode_index + Mode::ALL.len() - 1) % Mode::ALL.len()];
+    } else if menu.just_pressed(Navigate::Down) || keyboard_input.just_pressed(KeyCode::S) {
+        game.mode = Mode::ALL[(mode_index + 1) % Mode::ALL.len()];
+    } else if menu.just_pressed(Navigate::Left) || keyboard_input.just_pressed(KeyCode::A) {
+        game.best_of = BEST_OF_CHOICES[best_of_index.saturating_sub(1)];
+    } else if menu.just_pressed(Navigate::Right) || keyboard_input.just_pressed(KeyCode::D) {
+        game.best_of = BEST_OF_CHOICES[(best_of_index + 1).min(BEST_OF_CHOICES.len() - 1)];
+    } else if menu.just_pressed(Navigate::Confirm) {
+        game.round = 0;
+        game.wins = vec![0; game.mode.controllers().len()];
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&game);
+    }
+}
+
+fn menu_label(game: &Match) -> String {
+    let modes = Mode::ALL
+        .iter()
+        .map(|mode| {
+            let marker = if *mode == game.mode { ">" } else { " " };
+            format!("{} {}", marker, mode.name())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "LIGHT CYCLES\n\n{}\n\n< Best of {} >\n\nEnter to start",
+        modes, game.best_of
+    )
+}
+
+/// Counts the free cells reachable from `start`, stopping at `AI_LOOKAHEAD`.
+fn open_area(trails: &Trails, start: Position) -> usize {
+    path::reachable(start, AI_LOOKAHEAD, |position| trails.free(position))
+}
+
+fn rider_input(keyboard_input: Res<Input<KeyCode>>, mut riders: Query<&mut Rider>) {
+    for mut rider in riders.iter_mut() {
+        if let Controller::Keyboard(key_sets) = rider.controller {
+            for [up, left, down, right] in key_sets {
+                if keyboard_input.just_pressed(*up) {
+                    rider.wanted = Direction::Up;
+                } else if keyboard_input.just_pressed(*left) {
+                    rider.wanted = Direction::Left;
+                } else if keyboard_input.just_pressed(*down) {
+                    rider.wanted = Direction::Down;
+                } else if keyboard_input.just_pressed(*right) {
+                    rider.wanted = Direction::Right;
+                }
+            }
+        }
+    }
+}
+
+fn rider_movement(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut step_timer: ResMut<StepTimer>,
+    mut trails: ResMut<Trails>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut riders: Query<(&mut Rider, &mut Position, &mut Transform, &mut Sprite)>,
+) {
+    if !step_timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let targets: Vec<Position> = riders
+        .iter_mut()
+        .map(|(mut rider, position, _, _)| {
+            rider.direction = rider.direction.turn(rider.wanted);
+            position.step(rider.direction)
+        })
+        .collect();
+    let alive: Vec<bool> = riders.iter().map(|(rider, _, _, _)| rider.alive).collect();
+    for (index, (mut rider, mut position, mut transform, mut sprite)) in
+        riders.iter_mut().enumerate()
+    {
+        if !rider.alive {
+            continue;
+        }
+        let target = targets[index];
+        // Two riders entering the same cell in the same step both crash.
+        let head_on = targets
+            .iter()
+            .enumerate()
+            .any(|(other, other_target)| other != index && alive[other] && *other_target == target);
+        if head_on || !trails.free(target) {
+            rider.alive = false;
+            sprite.color = Color::rgb(0.35, 0.35, 0.35);
+            audio.play(sounds.crash.clone());
+            continue;
+        }
+        *position = target;
+        trails.mark(target, rider.index);
+        transform.translation = cell_translation(target, 2.0);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: SLOTS[rider.index].3 * 0.7,
+                    custom_size: Some(Vec2::splat(CELL * 0.8)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(cell_translation(target, 1.0)),
+                ..Default::default()
+            })
+            .insert(TrailCell);
+    }
+}
+
+fn round_end(mut game: ResMut<Match>, mut state: ResMut<State<GameState>>, riders: Query<&Rider>) {
+    // Riders spawned when the round starts only show up once the spawn commands are applied.
+    if riders.is_empty() {
+        return;
+    }
+    let survivors: Vec<usize> = riders
+        .iter()
+        .filter(|rider| rider.alive)
+        .map(|rider| rider.index)
+        .collect();
+    if survivors.len() > 1 {
+        return;
+    }
+    game.round_winner = survivors.first().copied();
+    if let Some(winner) = game.round_winner {
+        game.wins[winner] += 1;
+    }
+    state.set(GameState::RoundOver).unwrap();
+}
+
+fn round_over(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Match>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let headline = match game.round_winner {
+        Some(winner) => {
+            audio.play(sounds.round_won.clone());
+            format!("P{} wins round {}", winner + 1, game.round)
+        }
+        None => format!("Round {} is a draw", game.round),
+    };
+    let footer = match game.champion() {
+        Some(champion) => format!("P{} WINS THE MATCH\nPress Enter for the menu", champion + 1),
+        None => "Press Enter for the next round".to_string(),
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("{}\n{}", headline, footer),
+            32.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(RoundOverText);
+}
+
+fn round_over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    game: Res<Match>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let next = if game.champion().is_some() {
+            GameState::Menu
+        } else {
+            GameState::Playing
+        };
+        state.set(next).unwrap();
+    }
+}
+
+fn round_start(
+    mut commands: Commands,
+    mut game: ResMut<Match>,
+    mut trails: ResMut<Trails>,
+    mut step_timer: ResMut<StepTimer>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    leftovers: Query<Entity, Or<(With<Rider>, With<TrailCell>)>>,
+) {
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    game.round += 1;
+    *trails = Trails::empty();
+    step_timer.0.reset();
+    for (index, controller) in game.mode.controllers().into_iter().enumerate() {
+        let (x, y, direction, color) = SLOTS[index];
+        let position = Position::new(x, y);
+        trails.mark(position, index);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(CELL)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(cell_translation(position, 2.0)),
+                ..Default::default()
+            })
+            .insert(position)
+            .insert(Rider {
+                alive: true,
+                controller,
+                direction,
+                index,
+                wanted: direction,
+            });
+    }
+    audio.play(sounds.start.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    // Thin border around the arena so its walls are visible.
+    let arena = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT - HUD_HEIGHT);
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: Color::rgb(0.25, 0.25, 0.4),
+            custom_size: Some(arena),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0.0, -HUD_HEIGHT / 2.0, 0.0),
+        ..Default::default()
+    });
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: BACKGROUND_COLOR,
+            custom_size: Some(arena - Vec2::splat(4.0)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0.0, -HUD_HEIGHT / 2.0, 0.1),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.insert_resource(Sounds {
+        crash: audio_sources.add(Tone::new(Waveform::Noise, 0.0, 0.5).fade().into()),
+        round_won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 392.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 523.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 659.0, 0.3).volume(0.25),
+        ])),
+        start: audio_sources.add(
+            Tone::new(Waveform::Saw, 110.0, 0.6)
+                .slide(440.0)
+                .volume(0.2)
+                .into(),
+        ),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_match_goes_to_whoever_wins_most_rounds() {
+        let mut game = Match {
+            best_of: 5,
+            mode: Mode::TwoPlayers,
+            round: 4,
+            round_winner: None,
+            wins: vec![2, 2],
+        };
+        assert_eq!(game.wins_needed(), 3);
+        assert_eq!(game.champion(), None);
+        game.wins[1] += 1;
+        assert_eq!(game.champion(), Some(1));
+    }
+
+    #[test]
+    fn trails_and_walls_block_riders() {
+        let mut trails = Trails::empty();
+        let position = Position::new(3, 4);
+        assert!(trails.free(position));
+        trails.mark(position, 1);
+        assert!(!trails.free(position));
+        assert!(!trails.free(Position::new(-1, 0)));
+        assert!(!trails.free(Position::new(ARENA_WIDTH as i32, 0)));
+        assert_eq!(open_area(&trails, position), 0);
+        assert_eq!(open_area(&trails, Position::new(0, 0)), AI_LOOKAHEAD);
+    }
+
+    #[test]
+    fn ai_turns_away_from_walls_and_trails() {
+        let position = Position::new(ARENA_WIDTH as i32 - 1, ARENA_HEIGHT as i32 / 2);
+        let mut trails = Trails::empty();
+        trails.mark(position.step(Direction::Up), 0);
+        let mut app = App::new();
+        app.insert_resource(trails).add_system(ai_steering);
+        let rider = app
+            .world
+            .spawn()
+            .insert(Rider {
+                alive: true,
+                controller: Controller::Ai,
+                direction: Direction::Right,
+                index: 1,
+                wanted: Direction::Right,
+            })
+            .insert(position)
+            .id();
+        app.update();
+        assert_eq!(
+            app.world.get::<Rider>(rider).unwrap().wanted,
+            Direction::Down
+        );
+    }
+
+    #[test]
+    fn every_mode_fits_the_slots() {
+        for mode in Mode::ALL {
+            let controllers = mode.controllers();
+            assert!(controllers.len() <= SLOTS.len());
+            assert!(matches!(controllers[0], Controller::Keyboard(_)));
+        }
+    }
+}
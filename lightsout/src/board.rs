@@ -0,0 +1,196 @@
+//! A square of lights. Pressing one toggles it and its neighbours above, below and to either
+//! side. Cells are (column, row), rows counting down from the top.
+
+use rand::{seq::SliceRandom, Rng};
+
+/// Most free presses the solver tries every combination of; the classic sizes have at most 8.
+const MAX_FREE: usize = 16;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    lights: Vec<bool>,
+    pub size: usize,
+}
+
+impl Board {
+    /// All lights off.
+    pub fn new(size: usize) -> Self {
+        Self {
+            lights: vec![false; size * size],
+            size,
+        }
+    }
+
+    /// Scrambles a dark board by pressing `presses` different cells, so it always has a
+    /// solution, and never one with nothing to do.
+    pub fn generate(size: usize, presses: usize, rng: &mut impl Rng) -> Self {
+        let mut cells: Vec<(usize, usize)> = (0..size)
+            .flat_map(|y| (0..size).map(move |x| (x, y)))
+            .collect();
+        loop {
+            let mut board = Self::new(size);
+            cells.shuffle(rng);
+            for cell in cells.iter().take(presses.max(1)) {
+                board.press(*cell);
+            }
+            if board.lit() > 0 {
+                return board;
+            }
+        }
+    }
+
+    pub fn is_lit(&self, (x, y): (usize, usize)) -> bool {
+        self.lights[y * self.size + x]
+    }
+
+    /// How many lights are on.
+    pub fn lit(&self) -> usize {
+        self.lights.iter().filter(|light| **light).count()
+    }
+
+    pub fn press(&mut self, (x, y): (usize, usize)) {
+        for cell in self.cross((x, y)) {
+            let index = cell.1 * self.size + cell.0;
+            self.lights[index] = !self.lights[index];
+        }
+    }
+
+    /// The fewest presses that turn every light off, found by Gaussian elimination over GF(2)
+    /// and then the cheapest combination of the presses left free. None if there is none.
+    pub fn solve(&self) -> Option<Vec<(usize, usize)>> {
+        let count = self.size * self.size;
+        // Row `i`: which presses toggle light `i`, and last whether it is on.
+        let mut rows: Vec<Vec<bool>> = (0..count)
+            .map(|light| {
+                let mut row = vec![false; count + 1];
+                let cell = (light % self.size, light / self.size);
+                for (x, y) in self.cross(cell) {
+                    row[y * self.size + x] = true;
+                }
+                row[count] = self.lights[light];
+                row
+            })
+            .collect();
+        let mut pivots = Vec::new();
+        let mut rank = 0;
+        for column in 0..count {
+            let pivot = match (rank..count).find(|row| rows[*row][column]) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            rows.swap(rank, pivot);
+            let pivot_row = rows[rank].clone();
+            for (index, row) in rows.iter_mut().enumerate() {
+                if index != rank && row[column] {
+                    for (bit, other) in row.iter_mut().zip(&pivot_row) {
+                        *bit ^= other;
+                    }
+                }
+            }
+            pivots.push(column);
+            rank += 1;
+        }
+        if rows[rank..].iter().any(|row| row[count]) {
+            return None;
+        }
+
+        let free: Vec<usize> = (0..count)
+            .filter(|column| !pivots.contains(column))
+            .collect();
+        if free.len() > MAX_FREE {
+            return None;
+        }
+        let mut best: Option<Vec<bool>> = None;
+        for combination in 0..1u32 << free.len() {
+            let mut presses = vec![false; count];
+            for (bit, column) in free.iter().enumerate() {
+                presses[*column] = combination >> bit & 1 == 1;
+            }
+            for (row, pivot) in pivots.iter().enumerate() {
+                let mut value = rows[row][count];
+                for column in &free {
+                    value ^= rows[row][*column] && presses[*column];
+                }
+                presses[*pivot] = value;
+            }
+            let total = presses.iter().filter(|press| **press).count();
+            let fewer = match &best {
+                Some(best) => total < best.iter().filter(|press| **press).count(),
+                None => true,
+            };
+            if fewer {
+                best = Some(presses);
+            }
+        }
+        best.map(|presses| {
+            (0..count)
+                .filter(|index| presses[*index])
+                .map(|index| (index % self.size, index / self.size))
+                .collect()
+        })
+    }
+
+    /// The cell and its neighbours on the board.
+    fn cross(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut cells = vec![(x, y)];
+        if x > 0 {
+            cells.push((x - 1, y));
+        }
+        if x + 1 < self.size {
+            cells.push((x + 1, y));
+        }
+        if y > 0 {
+            cells.push((x, y - 1));
+        }
+        if y + 1 < self.size {
+            cells.push((x, y + 1));
+        }
+        cells
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn presses_toggle_a_cross() {
+        let mut board = Board::new(3);
+        board.press((0, 0));
+        assert_eq!(board.lit(), 3);
+        assert!(board.is_lit((1, 0)) && board.is_lit((0, 1)));
+        board.press((1, 1));
+        assert_eq!(board.lit(), 4);
+        assert!(!board.is_lit((1, 0)));
+        assert!(board.is_lit((2, 1)));
+    }
+
+    #[test]
+    fn solves_generated_boards_in_the_fewest_presses() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for size in 3..=9 {
+            let presses = size + 1;
+            let mut board = Board::generate(size, presses, &mut rng);
+            let solution = board.solve().unwrap();
+            assert!(solution.len() <= presses);
+            for cell in solution {
+                board.press(cell);
+            }
+            assert_eq!(board.lit(), 0);
+        }
+        let mut board = Board::new(5);
+        board.press((2, 2));
+        board.press((0, 4));
+        assert_eq!(board.solve().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn some_boards_have_no_solution() {
+        // On a 5x5 board a lone corner light cannot be turned off.
+        let mut board = Board::new(5);
+        board.lights[0] = true;
+        assert_eq!(board.solve(), None);
+        assert_eq!(Board::new(4).solve(), Some(Vec::new()));
+    }
+}
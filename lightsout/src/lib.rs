@@ -0,0 +1,437 @@
+use bevy::{math::const_vec2, prelude::*};
+use board::Board;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod board;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.06, 0.06, 0.09);
+/// Side of the board in pixels, whatever its size in cells.
+const BOARD: f32 = 440.0;
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, -10.0]);
+const GAME: &str = "lightsout";
+/// The light the hint points at, lit and dark.
+const HINT_COLORS: [Color; 2] = [Color::rgb(1.0, 0.55, 0.45), Color::rgb(0.45, 0.2, 0.2)];
+const LIT_COLOR: Color = Color::rgb(1.0, 0.85, 0.3);
+const OFF_COLOR: Color = Color::rgb(0.18, 0.18, 0.24);
+const RECORDS_FILE: &str = "records";
+/// Cells across the board to choose from.
+const SIZES: [usize; 7] = [3, 4, 5, 6, 7, 8, 9];
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+
+pub const WINDOW_HEIGHT: f32 = 560.0;
+pub const WINDOW_WIDTH: f32 = 520.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Solved,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct Light((usize, usize));
+
+/// Index into `SIZES` of the board size chosen.
+struct Menu {
+    index: usize,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+/// The highest level cleared on each board size, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records(HashMap<String, u32>);
+
+/// The shade behind the menu and the solved banner.
+#[derive(Component)]
+struct Shade;
+
+struct Session {
+    board: Board,
+    /// The light the last hint pointed at, until the next press.
+    hint: Option<(usize, usize)>,
+    hints: u32,
+    level: u32,
+    moves: u32,
+    /// The fewest presses the level can be solved in.
+    par: usize,
+    /// The board as the level started, to start over from.
+    start: Board,
+}
+
+impl Session {
+    fn new(size: usize, level: u32) -> Self {
+        let presses = (level as usize + 2).min(size * size / 2);
+        let board = Board::generate(size, presses, &mut thread_rng());
+        let par = board.solve().map(|presses| presses.len()).unwrap_or(0);
+        Self {
+            start: board.clone(),
+            board,
+            hint: None,
+            hints: 0,
+            level,
+            moves: 0,
+            par,
+        }
+    }
+}
+
+struct Sounds {
+    hint: Handle<AudioSource>,
+    press: Handle<AudioSource>,
+    solved: Handle<AudioSource>,
+}
+
+pub struct LightsOutPlugin;
+
+impl Plugin for LightsOutPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Menu { index: 2 })
+            .insert_resource(Session::new(SIZES[2], 1))
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(play_input))
+            .add_system_set(SystemSet::on_enter(GameState::Solved).with_system(solved_enter))
+            .add_system_set(SystemSet::on_update(GameState::Solved).with_system(solved_input))
+            .add_system(lights_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Lights Out".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The light under a point of the window, if on the board.
+fn cell_at(point: Vec2, size: usize) -> Option<(usize, usize)> {
+    let corner = BOARD_CENTER + Vec2::new(-BOARD, BOARD) / 2.0;
+    let cell = BOARD / size as f32;
+    let x = ((point.x - corner.x) / cell).floor();
+    let y = ((corner.y - point.y) / cell).floor();
+    if x < 0.0 || y < 0.0 || x >= size as f32 || y >= size as f32 {
+        return None;
+    }
+    Some((x as usize, y as usize))
+}
+
+fn cell_center((x, y): (usize, usize), size: usize) -> Vec2 {
+    let cell = BOARD / size as f32;
+    BOARD_CENTER
+        + Vec2::new(
+            (x as f32 + 0.5) * cell - BOARD / 2.0,
+            BOARD / 2.0 - (y as f32 + 0.5) * cell,
+        )
+}
+
+/// Colours the lights, and shows the shade over them outside of play.
+fn lights_update(
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    mut lights: Query<(&Light, &mut Sprite)>,
+    mut shades: Query<&mut Visibility, With<Shade>>,
+) {
+    for (light, mut sprite) in lights.iter_mut() {
+        let lit = session.board.is_lit(light.0);
+        sprite.color = match (session.hint == Some(light.0), lit) {
+            (true, true) => HINT_COLORS[0],
+            (true, false) => HINT_COLORS[1],
+            (false, true) => LIT_COLOR,
+            (false, false) => OFF_COLOR,
+        };
+    }
+    for mut visibility in shades.iter_mut() {
+        visibility.is_visible = *state.current() != GameState::Playing;
+    }
+}
+
+/// Left and right pick the board size, Enter starts at the first level.
+fn menu_input(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    mut menu: ResMut<Menu>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+    lights: Query<Entity, With<Light>>,
+) {
+    if navigation.just_pressed(Navigate::Left) {
+        menu.index = menu.index.saturating_sub(1);
+    } else if navigation.just_pressed(Navigate::Right) {
+        menu.index = (menu.index + 1).min(SIZES.len() - 1);
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *session = Session::new(SIZES[menu.index], 1);
+        spawn_lights(&mut commands, SIZES[menu.index], &lights);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// A click presses a light, H asks for a hint, R starts the level over and Esc goes back to the
+/// menu.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::R) {
+        session.board = session.start.clone();
+        session.moves = 0;
+        session.hint = None;
+    } else if keyboard_input.just_pressed(KeyCode::H) {
+        session.hint = session
+            .board
+            .solve()
+            .and_then(|presses| presses.first().copied());
+        session.hints += 1;
+        audio.play(sounds.hint.clone());
+    } else if mouse_input.just_pressed(MouseButton::Left) {
+        let size = session.board.size;
+        let cell = match game_hud::cursor_world_position(&windows)
+            .and_then(|point| cell_at(point, size))
+        {
+            Some(cell) => cell,
+            None => return,
+        };
+        session.board.press(cell);
+        session.moves += 1;
+        session.hint = None;
+        audio.play(sounds.press.clone());
+        if session.board.lit() == 0 {
+            state.set(GameState::Solved).unwrap();
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.0, 0.0, 0.0, 0.75),
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 850.0),
+            ..Default::default()
+        })
+        .insert(Shade);
+
+    commands.insert_resource(Sounds {
+        hint: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 880.0, 0.06).volume(0.3),
+            Tone::new(Waveform::Sine, 1175.0, 0.1).fade().volume(0.3),
+        ])),
+        press: audio_sources.add(
+            Tone::new(Waveform::Square, 440.0, 0.05)
+                .slide(660.0)
+                .volume(0.1)
+                .into(),
+        ),
+        solved: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4).fade(),
+        ])),
+    });
+}
+
+fn size_name(size: usize) -> String {
+    format!("{}x{}", size, size)
+}
+
+/// Saves the level as cleared if it is the highest on this size yet.
+fn solved_enter(
+    session: Res<Session>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.solved.clone());
+    let name = size_name(session.board.size);
+    if records.0.get(&name).copied().unwrap_or(0) >= session.level {
+        return;
+    }
+    records.0.insert(name, session.level);
+    if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, &*records) {
+        eprintln!("could not save records: {}", error);
+    }
+}
+
+/// Enter goes on to the next level, Esc back to the menu.
+fn solved_input(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+    lights: Query<Entity, With<Light>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let size = session.board.size;
+        *session = Session::new(size, session.level + 1);
+        spawn_lights(&mut commands, size, &lights);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Replaces the lights with a board of `size` by `size`.
+fn spawn_lights(commands: &mut Commands, size: usize, lights: &Query<Entity, With<Light>>) {
+    for entity in lights.iter() {
+        commands.entity(entity).despawn();
+    }
+    let cell = BOARD / size as f32;
+    for y in 0..size {
+        for x in 0..size {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: OFF_COLOR,
+                        custom_size: Some(Vec2::splat(cell * 0.9)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(cell_center((x, y), size).extend(0.0)),
+                    ..Default::default()
+                })
+                .insert(Light((x, y)));
+        }
+    }
+}
+
+fn texts_update(
+    menu: Res<Menu>,
+    records: Res<Records>,
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let (hud, help, banner) = match state.current() {
+        GameState::Menu => {
+            let size = SIZES[menu.index];
+            let best = match records.0.get(&size_name(size)) {
+                Some(level) => format!("Highest level cleared {}", level),
+                None => "No level cleared yet".to_string(),
+            };
+            (
+                String::new(),
+                "Left/Right: board size   Enter: start".to_string(),
+                format!("LIGHTS OUT\n\nBoard  < {} >\n\n{}", size_name(size), best),
+            )
+        }
+        GameState::Playing | GameState::Solved => {
+            let hud = format!(
+                "Level {}   Moves {}   Fewest possible {}   Hints {}   Lit {}",
+                session.level,
+                session.moves,
+                session.par,
+                session.hints,
+                session.board.lit()
+            );
+            if *state.current() == GameState::Playing {
+                (
+                    hud,
+                    "Click: press a light   H: hint   R: start over   Esc: menu".to_string(),
+                    String::new(),
+                )
+            } else {
+                let rating = if session.moves as usize <= session.par {
+                    "Perfect!"
+                } else {
+                    ""
+                };
+                (
+                    hud,
+                    "Enter: next level   Esc: menu".to_string(),
+                    format!(
+                        "LEVEL {} SOLVED\n\n{} moves, {} at best\n{}",
+                        session.level, session.moves, session.par, rating
+                    ),
+                )
+            }
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
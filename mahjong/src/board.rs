@@ -0,0 +1,281 @@
+//! The tiles of a game, which of them are free, and dealing so that every game can be won.
+
+use crate::layout::{Layout, Position};
+use rand::{seq::SliceRandom, Rng};
+
+/// Tries at a deal that can be won before settling for any deal.
+const ATTEMPTS: usize = 200;
+
+/// A tile face: three suits of nine, four winds, three dragons, four flowers and four seasons.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Face(pub u8);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Dots,
+    Bamboo,
+    Characters,
+    Wind,
+    Dragon,
+    Flower,
+    Season,
+}
+
+impl Face {
+    pub fn kind(self) -> Kind {
+        match self.0 {
+            0..=8 => Kind::Dots,
+            9..=17 => Kind::Bamboo,
+            18..=26 => Kind::Characters,
+            27..=30 => Kind::Wind,
+            31..=33 => Kind::Dragon,
+            34..=37 => Kind::Flower,
+            _ => Kind::Season,
+        }
+    }
+
+    /// Any two flowers match, as do any two seasons; other tiles only match their twins.
+    pub fn matches(self, other: Face) -> bool {
+        self.group() == other.group()
+    }
+
+    /// The number of the face within its kind, from 1.
+    pub fn rank(self) -> u8 {
+        let first = match self.kind() {
+            Kind::Dots => 0,
+            Kind::Bamboo => 9,
+            Kind::Characters => 18,
+            Kind::Wind => 27,
+            Kind::Dragon => 31,
+            Kind::Flower => 34,
+            Kind::Season => 38,
+        };
+        self.0 - first + 1
+    }
+
+    fn group(self) -> u8 {
+        match self.kind() {
+            Kind::Flower => 34,
+            Kind::Season => 38,
+            _ => self.0,
+        }
+    }
+}
+
+/// A full set of 144 tiles as 72 matching pairs.
+pub fn full_set() -> Vec<(Face, Face)> {
+    let mut pairs = Vec::new();
+    for face in 0..34 {
+        pairs.push((Face(face), Face(face)));
+        pairs.push((Face(face), Face(face)));
+    }
+    for first in [34, 36, 38, 40] {
+        pairs.push((Face(first), Face(first + 1)));
+    }
+    pairs
+}
+
+pub struct Board {
+    pub faces: Vec<Face>,
+    pub positions: Vec<Position>,
+    /// Tiles not removed yet.
+    pub present: Vec<bool>,
+}
+
+impl Board {
+    /// Pairs from a shuffled full set laid out on the layout, so that the game can be won.
+    pub fn deal(layout: &Layout, rng: &mut impl Rng) -> Self {
+        let mut pairs = full_set();
+        pairs.shuffle(rng);
+        pairs.truncate(layout.positions.len() / 2);
+        let mut board = Self {
+            faces: Vec::new(),
+            positions: layout.positions.clone(),
+            present: vec![true; layout.positions.len()],
+        };
+        board.faces = board.place(&pairs, rng);
+        board
+    }
+
+    /// Every pair of free tiles that match.
+    pub fn free_pairs(&self) -> Vec<(usize, usize)> {
+        let free: Vec<usize> = (0..self.positions.len())
+            .filter(|index| self.present[*index] && self.is_free(*index))
+            .collect();
+        let mut pairs = Vec::new();
+        for (at, first) in free.iter().enumerate() {
+            for second in &free[at + 1..] {
+                if self.faces[*first].matches(self.faces[*second]) {
+                    pairs.push((*first, *second));
+                }
+            }
+        }
+        pairs
+    }
+
+    /// Free means nothing lies on top and the left or the right side is open.
+    pub fn is_free(&self, index: usize) -> bool {
+        is_free(&self.positions, &self.present, index)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.present.iter().filter(|present| **present).count()
+    }
+
+    pub fn remove(&mut self, first: usize, second: usize) {
+        self.present[first] = false;
+        self.present[second] = false;
+    }
+
+    /// Deals the faces left over the tiles left, again so that the game can be won.
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        let mut faces: Vec<Face> = (0..self.faces.len())
+            .filter(|index| self.present[*index])
+            .map(|index| self.faces[index])
+            .collect();
+        // Matching faces end up next to each other.
+        faces.sort_by_key(|face| (face.group(), face.0));
+        let mut pairs: Vec<(Face, Face)> = faces.chunks(2).map(|pair| (pair[0], pair[1])).collect();
+        pairs.shuffle(rng);
+        let placed = self.place(&pairs, rng);
+        for (index, face) in placed.into_iter().enumerate() {
+            if self.present[index] {
+                self.faces[index] = face;
+            }
+        }
+    }
+
+    /// Faces for the present tiles from `pairs`, found by taking away pairs of free tiles one
+    /// after another, so that removing them in the same order wins. Falls back to a plain
+    /// shuffle should every try get stuck.
+    fn place(&self, pairs: &[(Face, Face)], rng: &mut impl Rng) -> Vec<Face> {
+        let count = self.positions.len();
+        'attempts: for _ in 0..ATTEMPTS {
+            let mut left = self.present.clone();
+            let mut faces = vec![Face(0); count];
+            for (first, second) in pairs {
+                let free: Vec<usize> = (0..count)
+                    .filter(|index| left[*index] && is_free(&self.positions, &left, *index))
+                    .collect();
+                if free.len() < 2 {
+                    continue 'attempts;
+                }
+                let chosen: Vec<usize> = free.choose_multiple(rng, 2).copied().collect();
+                for (index, face) in chosen.iter().zip([*first, *second]) {
+                    faces[*index] = face;
+                    left[*index] = false;
+                }
+            }
+            return faces;
+        }
+        let mut faces: Vec<Face> = pairs
+            .iter()
+            .flat_map(|(first, second)| [*first, *second])
+            .collect();
+        faces.shuffle(rng);
+        let mut faces = faces.into_iter();
+        (0..count)
+            .map(|index| {
+                if self.present[index] {
+                    faces.next().unwrap()
+                } else {
+                    Face(0)
+                }
+            })
+            .collect()
+    }
+}
+
+fn is_free(positions: &[Position], present: &[bool], index: usize) -> bool {
+    let tile = positions[index];
+    let (mut left, mut right) = (false, false);
+    for (other, position) in positions.iter().enumerate() {
+        if other == index || !present[other] {
+            continue;
+        }
+        if position.layer > tile.layer && position.overlaps(tile) {
+            return false;
+        }
+        if position.layer == tile.layer && (position.y - tile.y).abs() < 2 {
+            left |= position.x == tile.x - 2;
+            right |= position.x == tile.x + 2;
+        }
+    }
+    !(left && right)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// Removes free pairs until none are left, always taking the first one found.
+    fn solve_greedily(board: &mut Board) {
+        while let Some((first, second)) = board.free_pairs().first().copied() {
+            board.remove(first, second);
+        }
+    }
+
+    #[test]
+    fn free_tiles() {
+        let layout = Layout::parse("Row\n\n#.#.#.\n\n..#...").unwrap();
+        let mut board = Board::deal(&layout, &mut StdRng::seed_from_u64(1));
+        // The middle tile is covered and the two ends are open on the outside.
+        assert!(board.is_free(0));
+        assert!(!board.is_free(1));
+        assert!(board.is_free(2));
+        assert!(board.is_free(3));
+        board.present[3] = false;
+        board.present[0] = false;
+        assert!(board.is_free(1));
+    }
+
+    #[test]
+    fn flowers_and_seasons_match_among_themselves() {
+        assert!(Face(34).matches(Face(37)));
+        assert!(Face(38).matches(Face(41)));
+        assert!(!Face(37).matches(Face(38)));
+        assert!(Face(5).matches(Face(5)));
+        assert!(!Face(5).matches(Face(6)));
+        assert_eq!(Face(14).kind(), Kind::Bamboo);
+        assert_eq!(Face(14).rank(), 6);
+    }
+
+    #[test]
+    fn deals_and_shuffles_keep_the_set() {
+        let layout = Layout::parse(include_str!("../assets/layouts/1-turtle.txt")).unwrap();
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut board = Board::deal(&layout, &mut rng);
+        let mut faces = board.faces.clone();
+        faces.sort_by_key(|face| face.0);
+        let mut expected: Vec<Face> = full_set()
+            .into_iter()
+            .flat_map(|(first, second)| [first, second])
+            .collect();
+        expected.sort_by_key(|face| face.0);
+        assert_eq!(faces, expected);
+
+        let (first, second) = board.free_pairs()[0];
+        board.remove(first, second);
+        let before: Vec<Face> = board.faces.clone();
+        board.shuffle(&mut rng);
+        assert_ne!(board.faces, before);
+        assert_eq!(board.remaining(), 142);
+    }
+
+    #[test]
+    fn shuffling_opens_a_pair() {
+        // Greedy play can still lose a winnable deal, so only check that a deal never starts
+        // blocked and that shuffling always opens a pair again.
+        let layout = Layout::parse(include_str!("../assets/layouts/2-pyramid.txt")).unwrap();
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut board = Board::deal(&layout, &mut rng);
+        assert!(!board.free_pairs().is_empty());
+        solve_greedily(&mut board);
+        while board.remaining() > 0 {
+            board.shuffle(&mut rng);
+            assert!(!board.free_pairs().is_empty());
+            solve_greedily(&mut board);
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Layouts are text files: the name on the first line, then one block per layer from the bottom
+//! up, separated by blank lines. Each character of a block is half a tile wide and half a tile
+//! high, and a `#` marks the top left corner of a tile, so tiles can sit half a tile apart.
+
+use std::fmt;
+
+/// Tiles in a full set, the most a layout may hold.
+pub const MAX_TILES: usize = 144;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    /// A block holds something other than `#` and `.`.
+    Character {
+        layer: usize,
+        row: usize,
+        character: char,
+    },
+    /// Two tiles of a layer take up the same space.
+    Overlap {
+        layer: usize,
+        row: usize,
+    },
+    /// The tiles do not pair up, or there are more than a full set.
+    Count(usize),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no name or no tiles"),
+            Self::Character {
+                layer,
+                row,
+                character,
+            } => write!(
+                f,
+                "unexpected {:?} in row {} of layer {}",
+                character,
+                row + 1,
+                layer + 1
+            ),
+            Self::Overlap { layer, row } => {
+                write!(f, "tiles overlap in row {} of layer {}", row + 1, layer + 1)
+            }
+            Self::Count(count) => write!(
+                f,
+                "{} tiles; it takes an even number up to {}",
+                count, MAX_TILES
+            ),
+        }
+    }
+}
+
+/// Where a tile sits, in half tiles from the top left and layers from the bottom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub layer: usize,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    /// Whether the two tiles share some of their area, seen from above.
+    pub fn overlaps(self, other: Position) -> bool {
+        (self.x - other.x).abs() < 2 && (self.y - other.y).abs() < 2
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    pub name: String,
+    pub positions: Vec<Position>,
+}
+
+impl Layout {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text.lines().map(str::trim);
+        let name = lines
+            .by_ref()
+            .find(|line| !line.is_empty())
+            .ok_or(ParseError::Empty)?;
+        let mut positions: Vec<Position> = Vec::new();
+        // A blank line after some rows starts the next layer.
+        let (mut layer, mut row) = (0, 0);
+        for line in lines {
+            if line.is_empty() {
+                if row > 0 {
+                    layer += 1;
+                    row = 0;
+                }
+                continue;
+            }
+            for (column, character) in line.chars().enumerate() {
+                match character {
+                    '#' => {}
+                    '.' => continue,
+                    _ => {
+                        return Err(ParseError::Character {
+                            layer,
+                            row,
+                            character,
+                        })
+                    }
+                }
+                let position = Position {
+                    layer,
+                    x: column as i32,
+                    y: row as i32,
+                };
+                let taken = positions
+                    .iter()
+                    .any(|other| other.layer == layer && other.overlaps(position));
+                if taken {
+                    return Err(ParseError::Overlap { layer, row });
+                }
+                positions.push(position);
+            }
+            row += 1;
+        }
+        if positions.is_empty() {
+            return Err(ParseError::Empty);
+        }
+        if positions.len() % 2 == 1 || positions.len() > MAX_TILES {
+            return Err(ParseError::Count(positions.len()));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            positions,
+        })
+    }
+
+    /// Width and height in half tiles.
+    pub fn size(&self) -> (i32, i32) {
+        let width = self.positions.iter().map(|position| position.x + 2).max();
+        let height = self.positions.iter().map(|position| position.y + 2).max();
+        (width.unwrap_or(0), height.unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_layers() {
+        let layout = Layout::parse("Small\n\n#.#.\n....\n#.#.\n\n.#.#\n....").unwrap();
+        assert_eq!(layout.name, "Small");
+        assert_eq!(layout.positions.len(), 6);
+        assert_eq!(
+            layout.positions[4],
+            Position {
+                layer: 1,
+                x: 1,
+                y: 0
+            }
+        );
+        assert_eq!(layout.size(), (5, 4));
+        for text in [
+            include_str!("../assets/layouts/1-turtle.txt"),
+            include_str!("../assets/layouts/2-pyramid.txt"),
+            include_str!("../assets/layouts/3-towers.txt"),
+        ] {
+            assert!(Layout::parse(text).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_broken_layouts() {
+        let parse = |text: &str| Layout::parse(text).unwrap_err();
+        assert_eq!(parse("Name"), ParseError::Empty);
+        assert_eq!(parse("Odd\n\n#.#.#."), ParseError::Count(3));
+        assert_eq!(
+            parse("Tight\n\n##"),
+            ParseError::Overlap { layer: 0, row: 0 }
+        );
+        assert_eq!(
+            parse("Odd\n\n#.x"),
+            ParseError::Character {
+                layer: 0,
+                row: 0,
+                character: 'x'
+            }
+        );
+    }
+}
@@ -0,0 +1,619 @@
+use bevy::{math::const_vec2, prelude::*};
+use board::{Board, Face, Kind};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use layout::Layout;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, error::Error, fs, path::PathBuf};
+
+pub mod board;
+pub mod layout;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.3, 0.25);
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, -12.0]);
+const FACE_COLOR: Color = Color::rgb(0.96, 0.94, 0.86);
+const GAME: &str = "mahjong";
+/// Half a tile; tiles sit on a grid of half tiles.
+const HALF_TILE: Vec2 = const_vec2!([22.0, 29.0]);
+/// Seconds added to the clock for a hint.
+const HINT_PENALTY: f32 = 15.0;
+const HINT_COLOR: Color = Color::rgb(0.7, 0.95, 0.7);
+/// How far each layer is drawn up and to the left of the one below.
+const LAYER_OFFSET: Vec2 = const_vec2!([-4.0, 5.0]);
+/// Layouts that come with the game; more are read from `LAYOUTS_DIRECTORY`.
+const LAYOUTS: [&str; 3] = [
+    include_str!("../assets/layouts/1-turtle.txt"),
+    include_str!("../assets/layouts/2-pyramid.txt"),
+    include_str!("../assets/layouts/3-towers.txt"),
+];
+const LAYOUTS_DIRECTORY: &str = "layouts";
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const RECORDS_FILE: &str = "records";
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+/// Seconds added to the clock for a shuffle.
+const SHUFFLE_PENALTY: f32 = 30.0;
+const SIDE_COLOR: Color = Color::rgb(0.7, 0.58, 0.4);
+const TEXT_COLOR: Color = Color::rgb(0.93, 0.95, 0.92);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Seconds played, penalties included.
+struct Clock(f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Won,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// Layouts to choose from: the bundled ones, then those in the data directory.
+struct Library(Vec<Layout>);
+
+struct Menu {
+    index: usize,
+}
+
+/// The menu, or the result over the cleared board.
+#[derive(Component)]
+struct MenuText;
+
+/// The fastest time for each layout by name, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records(HashMap<String, f32>);
+
+struct Session {
+    board: Board,
+    hint: Option<(usize, usize)>,
+    layout: String,
+    message: String,
+    /// Matching free pairs left, 0 when stuck.
+    open_pairs: usize,
+    selected: Option<usize>,
+    /// Width and height of the layout in half tiles.
+    size: (i32, i32),
+}
+
+impl Session {
+    fn new(layout: &Layout) -> Self {
+        let mut session = Self {
+            board: Board::deal(layout, &mut thread_rng()),
+            hint: None,
+            layout: layout.name.clone(),
+            message: String::new(),
+            open_pairs: 0,
+            selected: None,
+            size: layout.size(),
+        };
+        session.count_pairs();
+        session
+    }
+
+    /// Counts the pairs that can be removed and says so when there are none.
+    fn count_pairs(&mut self) {
+        self.open_pairs = self.board.free_pairs().len();
+        if self.open_pairs == 0 && self.board.remaining() > 0 {
+            self.message = "No more moves: S to shuffle".to_string();
+        }
+    }
+}
+
+struct Sounds {
+    blocked: Handle<AudioSource>,
+    matched: Handle<AudioSource>,
+    select: Handle<AudioSource>,
+    shuffle: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+/// Everything drawn for a tile, rebuilt whenever the board changes.
+#[derive(Component)]
+struct TileSprite;
+
+pub struct MahjongPlugin;
+
+impl Plugin for MahjongPlugin {
+    fn build(&self, app: &mut App) {
+        let first = Layout::parse(LAYOUTS[0]).expect("bundled layouts are valid");
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Library(Vec::new()))
+            .insert_resource(Menu { index: 0 })
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .insert_resource(Session::new(&first))
+            .insert_resource(Clock(0.0))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_startup_system(library_load)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(clock_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Won).with_system(won_enter))
+            .add_system_set(SystemSet::on_update(GameState::Won).with_system(won_input))
+            .add_system(escape_input)
+            .add_system(texts_update)
+            .add_system(tiles_render);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Mahjong".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn clock_update(time: Res<Time>, mut clock: ResMut<Clock>) {
+    clock.0 += time.delta_seconds();
+}
+
+/// Escape leaves a game for the menu at any time.
+fn escape_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() != GameState::Menu {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// The text on a tile and its color.
+fn face_label(face: Face) -> (String, Color) {
+    const SEASONS: [&str; 4] = ["\u{2618}", "\u{2600}", "\u{2660}", "\u{2744}"];
+    const FLOWERS: [&str; 4] = ["\u{273F}", "\u{2740}", "\u{273E}", "\u{2698}"];
+    let rank = face.rank() as usize;
+    match face.kind() {
+        Kind::Dots => (format!("{}\n\u{25CF}", rank), Color::rgb(0.15, 0.3, 0.75)),
+        Kind::Bamboo => (format!("{}\n\u{25B2}", rank), Color::rgb(0.1, 0.5, 0.2)),
+        Kind::Characters => (format!("{}\n\u{2715}", rank), Color::rgb(0.75, 0.1, 0.1)),
+        Kind::Wind => (
+            ["E", "S", "W", "N"][rank - 1].to_string(),
+            Color::rgb(0.1, 0.1, 0.1),
+        ),
+        Kind::Dragon => match rank {
+            1 => ("\u{25C6}".to_string(), Color::rgb(0.8, 0.1, 0.1)),
+            2 => ("\u{25C6}".to_string(), Color::rgb(0.1, 0.55, 0.2)),
+            _ => ("\u{25A1}".to_string(), Color::rgb(0.15, 0.3, 0.75)),
+        },
+        Kind::Flower => (FLOWERS[rank - 1].to_string(), Color::rgb(0.75, 0.2, 0.6)),
+        Kind::Season => (SEASONS[rank - 1].to_string(), Color::rgb(0.85, 0.45, 0.1)),
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Reads the bundled layouts and those in the data directory.
+fn library_load(mut library: ResMut<Library>) {
+    library.0 = LAYOUTS
+        .iter()
+        .map(|text| Layout::parse(text).expect("bundled layouts are valid"))
+        .collect();
+    match load_custom() {
+        Ok(layouts) => library.0.extend(layouts),
+        Err(error) => eprintln!("could not load layouts: {}", error),
+    }
+}
+
+fn load_custom() -> Result<Vec<Layout>, Box<dyn Error>> {
+    let directory = game_persistence::directory(GAME)?.join(LAYOUTS_DIRECTORY);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("txt"))
+        .collect();
+    paths.sort();
+    let mut layouts = Vec::new();
+    for path in paths {
+        match Layout::parse(&fs::read_to_string(&path)?) {
+            Ok(layout) => layouts.push(layout),
+            Err(error) => eprintln!("skipping {}: {}", path.display(), error),
+        }
+    }
+    Ok(layouts)
+}
+
+/// Up/Down picks a layout, Enter deals it.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    library: Res<Library>,
+    mut menu: ResMut<Menu>,
+    mut session: ResMut<Session>,
+    mut clock: ResMut<Clock>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if navigation.just_pressed(Navigate::Up) {
+        menu.index = menu.index.saturating_sub(1);
+    } else if navigation.just_pressed(Navigate::Down) {
+        menu.index = (menu.index + 1).min(library.0.len() - 1);
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *session = Session::new(&library.0[menu.index]);
+        clock.0 = 0.0;
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn menu_label(library: &Library, menu: &Menu, records: &Records) -> String {
+    let layouts = library
+        .0
+        .iter()
+        .enumerate()
+        .map(|(index, layout)| {
+            let marker = if index == menu.index { ">" } else { " " };
+            let record = match records.0.get(&layout.name) {
+                Some(seconds) => format!("  best {}", format_time(*seconds)),
+                None => String::new(),
+            };
+            format!(
+                "{} {:<16}{:>4} tiles{}",
+                marker,
+                layout.name,
+                layout.positions.len(),
+                record
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("MAHJONG\n\n{}\n\nUp/Down: layout   Enter: play", layouts)
+}
+
+/// A click picks a free tile and removes it with a matching one; H shows a pair, S shuffles
+/// and N deals the layout anew.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    library: Res<Library>,
+    menu: Res<Menu>,
+    mut session: ResMut<Session>,
+    mut clock: ResMut<Clock>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::N) {
+        *session = Session::new(&library.0[menu.index]);
+        clock.0 = 0.0;
+        audio.play(sounds.shuffle.clone());
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::H) {
+        session.hint = session.board.free_pairs().first().copied();
+        if session.hint.is_some() {
+            clock.0 += HINT_PENALTY;
+        }
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::S) {
+        session.board.shuffle(&mut thread_rng());
+        session.selected = None;
+        session.hint = None;
+        session.message.clear();
+        session.count_pairs();
+        clock.0 += SHUFFLE_PENALTY;
+        audio.play(sounds.shuffle.clone());
+        return;
+    }
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let index = match game_hud::cursor_world_position(&windows)
+        .and_then(|cursor| tile_at(&session, cursor))
+    {
+        Some(index) => index,
+        None => return,
+    };
+    if !session.board.is_free(index) {
+        session.message = "That tile is not free".to_string();
+        audio.play(sounds.blocked.clone());
+        return;
+    }
+    session.message.clear();
+    match session.selected {
+        Some(selected) if selected == index => session.selected = None,
+        Some(selected) if session.board.faces[selected].matches(session.board.faces[index]) => {
+            session.board.remove(selected, index);
+            session.selected = None;
+            session.hint = None;
+            session.count_pairs();
+            audio.play(sounds.matched.clone());
+            if session.board.remaining() == 0 {
+                state.set(GameState::Won).unwrap();
+            }
+        }
+        _ => {
+            session.selected = Some(index);
+            audio.play(sounds.select.clone());
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        blocked: audio_sources.add(Tone::new(Waveform::Square, 140.0, 0.12).volume(0.15).into()),
+        matched: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 784.0, 0.06),
+            Tone::new(Waveform::Triangle, 1047.0, 0.12).fade(),
+        ])),
+        select: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.04)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        shuffle: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.3),
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.3),
+            Tone::new(Waveform::Noise, 0.0, 0.1).fade().volume(0.25),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.15),
+            Tone::new(Waveform::Triangle, 784.0, 0.15),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4),
+        ])),
+    });
+}
+
+fn texts_update(
+    session: Res<Session>,
+    clock: Res<Clock>,
+    state: Res<State<GameState>>,
+    library: Res<Library>,
+    menu: Res<Menu>,
+    records: Res<Records>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let (hud, help, overlay) = match state.current() {
+        GameState::Menu => (
+            String::new(),
+            String::new(),
+            menu_label(&library, &menu, &records),
+        ),
+        GameState::Playing => (
+            format!(
+                "{}   Tiles {}   Open pairs {}   Time {}",
+                session.layout,
+                session.board.remaining(),
+                session.open_pairs,
+                format_time(clock.0)
+            ),
+            if session.message.is_empty() {
+                "Click two matching free tiles   H: hint   S: shuffle   N: new deal   Esc: menu"
+                    .to_string()
+            } else {
+                session.message.clone()
+            },
+            String::new(),
+        ),
+        GameState::Won => {
+            let best = records.0.get(&session.layout).copied().unwrap_or(clock.0);
+            (
+                String::new(),
+                "Enter: menu".to_string(),
+                format!(
+                    "CLEARED\n\n{} in {}\nBest {}",
+                    session.layout,
+                    format_time(clock.0),
+                    format_time(best)
+                ),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = overlay.clone();
+    }
+}
+
+/// The topmost tile still on the board under `position`.
+fn tile_at(session: &Session, position: Vec2) -> Option<usize> {
+    let mut best: Option<(usize, f32)> = None;
+    for index in 0..session.board.positions.len() {
+        if !session.board.present[index] {
+            continue;
+        }
+        let center = tile_center(session, index);
+        let offset = (position - center.truncate()).abs();
+        let inside = offset.x <= HALF_TILE.x && offset.y <= HALF_TILE.y;
+        let above = match best {
+            Some((_, z)) => center.z > z,
+            None => true,
+        };
+        if inside && above {
+            best = Some((index, center.z));
+        }
+    }
+    best.map(|(index, _)| index)
+}
+
+/// Center of the face of tile `index`, and its depth so that tiles further right, further
+/// down and higher up are drawn over the others.
+fn tile_center(session: &Session, index: usize) -> Vec3 {
+    let (width, height) = session.size;
+    let position = session.board.positions[index];
+    let grid = Vec2::new(
+        (position.x + 1) as f32 - width as f32 / 2.0,
+        height as f32 / 2.0 - (position.y + 1) as f32,
+    );
+    let center = BOARD_CENTER + grid * HALF_TILE + LAYER_OFFSET * position.layer as f32;
+    let z = position.layer as f32 * 100.0 + (position.x + position.y) as f32;
+    center.extend(z)
+}
+
+/// Rebuilds the tiles with their sides and faces whenever the board changes.
+fn tiles_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    sprites: Query<Entity, With<TileSprite>>,
+) {
+    if !session.is_changed() && !state.is_changed() {
+        return;
+    }
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    let board = &session.board;
+    let face_size = HALF_TILE * 2.0 - Vec2::splat(2.0);
+    let side = -LAYER_OFFSET;
+    for index in 0..board.positions.len() {
+        if !board.present[index] {
+            continue;
+        }
+        let center = tile_center(&session, index);
+        let hinted = match session.hint {
+            Some((first, second)) => first == index || second == index,
+            None => false,
+        };
+        let color = if session.selected == Some(index) {
+            SELECTED_COLOR
+        } else if hinted {
+            HINT_COLOR
+        } else {
+            FACE_COLOR
+        };
+        let sprite = |color: Color, translation: Vec3| SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(face_size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(translation),
+            ..Default::default()
+        };
+        commands
+            .spawn_bundle(sprite(SIDE_COLOR, center + side.extend(0.0)))
+            .insert(TileSprite);
+        commands
+            .spawn_bundle(sprite(color, center + Vec3::new(0.0, 0.0, 0.1)))
+            .insert(TileSprite);
+        let (label, label_color) = face_label(board.faces[index]);
+        let size = if label.contains('\n') { 17.0 } else { 28.0 };
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                label,
+                size,
+                label_color,
+                center + Vec3::new(0.0, 0.0, 0.2),
+            ))
+            .insert(TileSprite);
+    }
+    if *state.current() == GameState::Won {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: OVERLAY_COLOR,
+                    custom_size: Some(Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0.0, 0.0, 800.0),
+                ..Default::default()
+            })
+            .insert(TileSprite);
+    }
+}
+
+/// Keeps the time if it is the best for the layout.
+fn won_enter(
+    session: Res<Session>,
+    clock: Res<Clock>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.won.clone());
+    let new_record = match records.0.get(&session.layout) {
+        Some(record) => clock.0 < *record,
+        None => true,
+    };
+    if new_record {
+        records.0.insert(session.layout.clone(), clock.0);
+        if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, &*records) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+fn won_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
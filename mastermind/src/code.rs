@@ -0,0 +1,153 @@
+//! Codes are rows of colours, each a number below the count of colours in play. A guess is
+//! answered with how many pegs are the right colour in the right place, and how many more are
+//! a right colour in the wrong place.
+
+use rand::{seq::SliceRandom, Rng};
+
+pub type Code = Vec<u8>;
+
+/// Above this many candidates left the solver guesses one at random instead of searching for
+/// the guess that leaves the fewest in the worst case.
+const MINIMAX_LIMIT: usize = 1500;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Feedback {
+    /// Right colour in the right place.
+    pub exact: usize,
+    /// Right colour in the wrong place.
+    pub partial: usize,
+}
+
+/// Every code of `length` pegs in `colors` colours.
+pub fn all(length: usize, colors: u8) -> Vec<Code> {
+    let mut codes = vec![Vec::new()];
+    for _ in 0..length {
+        codes = codes
+            .into_iter()
+            .flat_map(|code| {
+                (0..colors).map(move |color| {
+                    let mut longer = code.clone();
+                    longer.push(color);
+                    longer
+                })
+            })
+            .collect();
+    }
+    codes
+}
+
+pub fn feedback(secret: &[u8], guess: &[u8]) -> Feedback {
+    let exact = secret.iter().zip(guess).filter(|(a, b)| a == b).count();
+    let mut counts = [0i32; 256];
+    for color in secret {
+        counts[*color as usize] += 1;
+    }
+    let mut common = 0;
+    for color in guess {
+        if counts[*color as usize] > 0 {
+            counts[*color as usize] -= 1;
+            common += 1;
+        }
+    }
+    Feedback {
+        exact,
+        partial: common - exact,
+    }
+}
+
+pub fn random(length: usize, colors: u8, rng: &mut impl Rng) -> Code {
+    (0..length).map(|_| rng.gen_range(0, colors)).collect()
+}
+
+/// Breaks codes by keeping only the codes every answer so far agrees with.
+pub struct Solver {
+    pub candidates: Vec<Code>,
+    colors: u8,
+    guesses: usize,
+}
+
+impl Solver {
+    pub fn new(length: usize, colors: u8) -> Self {
+        Self {
+            candidates: all(length, colors),
+            colors,
+            guesses: 0,
+        }
+    }
+
+    /// Opens with pairs of colours, then picks the candidate whose worst answer leaves the
+    /// fewest candidates.
+    pub fn guess(&self, rng: &mut impl Rng) -> Code {
+        if self.guesses == 0 {
+            let length = self.candidates[0].len();
+            return (0..length)
+                .map(|index| (index / 2) as u8 % self.colors)
+                .collect();
+        }
+        if self.candidates.len() > MINIMAX_LIMIT {
+            return self.candidates.choose(rng).cloned().unwrap_or_default();
+        }
+        let length = self.candidates[0].len();
+        self.candidates
+            .iter()
+            .min_by_key(|guess| {
+                let mut counts = vec![0usize; (length + 1) * (length + 1)];
+                for secret in &self.candidates {
+                    let answer = feedback(secret, guess);
+                    counts[answer.exact * (length + 1) + answer.partial] += 1;
+                }
+                counts.into_iter().max().unwrap_or(0)
+            })
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn learn(&mut self, guess: &[u8], answer: Feedback) {
+        self.guesses += 1;
+        self.candidates
+            .retain(|candidate| feedback(candidate, guess) == answer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn answer(exact: usize, partial: usize) -> Feedback {
+        Feedback { exact, partial }
+    }
+
+    #[test]
+    fn counts_exact_and_partial_matches() {
+        assert_eq!(feedback(&[0, 1, 2, 3], &[0, 1, 2, 3]), answer(4, 0));
+        assert_eq!(feedback(&[0, 1, 2, 3], &[3, 2, 1, 0]), answer(0, 4));
+        assert_eq!(feedback(&[0, 0, 1, 1], &[0, 1, 0, 5]), answer(1, 2));
+        // A colour guessed twice only counts as often as it is in the code.
+        assert_eq!(feedback(&[2, 4, 4, 4], &[2, 2, 2, 4]), answer(2, 0));
+        assert_eq!(all(3, 4).len(), 64);
+    }
+
+    #[test]
+    fn solver_breaks_codes() {
+        let mut rng = StdRng::seed_from_u64(4);
+        for (length, colors, most) in [(4, 6, 6), (3, 8, 8), (5, 5, 8)] {
+            for _ in 0..5 {
+                let secret = random(length, colors, &mut rng);
+                let mut solver = Solver::new(length, colors);
+                let mut guesses = 0;
+                loop {
+                    let guess = solver.guess(&mut rng);
+                    guesses += 1;
+                    let result = feedback(&secret, &guess);
+                    if result.exact == length {
+                        break;
+                    }
+                    solver.learn(&guess, result);
+                    assert!(solver.candidates.contains(&secret));
+                }
+                assert!(guesses <= most, "{} guesses for {:?}", guesses, secret);
+            }
+        }
+    }
+}
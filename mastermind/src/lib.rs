@@ -0,0 +1,618 @@
+use bevy::prelude::*;
+use code::{Code, Feedback, Solver};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use stats::{Stats, MAX_GUESSES};
+
+pub mod code;
+pub mod stats;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.16, 0.11, 0.08);
+/// Height of the lowest guess row, the first one played.
+const BOARD_BOTTOM: f32 = -220.0;
+/// The peg colours, picked with the keys 1 to 8.
+const COLORS: [Color; 8] = [
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.2, 0.75, 0.3),
+    Color::rgb(0.25, 0.45, 0.95),
+    Color::rgb(0.95, 0.85, 0.2),
+    Color::rgb(0.65, 0.3, 0.85),
+    Color::rgb(1.0, 0.55, 0.15),
+    Color::rgb(0.3, 0.85, 0.9),
+    Color::rgb(0.95, 0.5, 0.75),
+];
+/// Smallest and largest number of colours in play.
+const COLOR_RANGE: (u8, u8) = (4, 8);
+/// Seconds between two guesses of the demonstration.
+const DEMO_DELAY: f32 = 1.2;
+const EMPTY_COLOR: Color = Color::rgb(0.4, 0.33, 0.28);
+const EXACT_COLOR: Color = Color::rgb(0.95, 0.15, 0.15);
+/// Left of the feedback pegs.
+const FEEDBACK_LEFT: f32 = 150.0;
+/// Smallest and largest number of pegs in a code.
+const LENGTH_RANGE: (usize, usize) = (3, 6);
+const PALETTE_Y: f32 = -290.0;
+const PARTIAL_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const PEG_SPACING: f32 = 50.0;
+/// Pixels between two guess rows.
+const ROW_HEIGHT: f32 = 48.0;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.92, 0.86);
+
+pub const WINDOW_HEIGHT: f32 = 720.0;
+pub const WINDOW_WIDTH: f32 = 560.0;
+
+/// The guesses, the code and the palette, rebuilt whenever they change.
+#[derive(Component)]
+struct BoardSprite;
+
+struct Game {
+    colors: u8,
+    /// The guess being put together.
+    current: Code,
+    /// The solver when it is breaking the code instead of the player.
+    demo: Option<Solver>,
+    /// Seconds until the demonstration guesses again.
+    demo_wait: f32,
+    length: usize,
+    redraw: bool,
+    rows: Vec<(Code, Feedback)>,
+    secret: Code,
+}
+
+impl Game {
+    fn new(settings: &Settings) -> Self {
+        Self {
+            colors: settings.colors,
+            current: Vec::new(),
+            demo: if settings.demo {
+                Some(Solver::new(settings.length, settings.colors))
+            } else {
+                None
+            },
+            demo_wait: DEMO_DELAY,
+            length: settings.length,
+            redraw: true,
+            rows: Vec::new(),
+            secret: code::random(settings.length, settings.colors, &mut thread_rng()),
+        }
+    }
+
+    fn is_won(&self) -> bool {
+        matches!(self.rows.last(), Some((_, answer)) if answer.exact == self.length)
+    }
+
+    fn is_over(&self) -> bool {
+        self.is_won() || self.rows.len() >= MAX_GUESSES
+    }
+
+    fn submit(&mut self, guess: Code) -> Feedback {
+        let answer = code::feedback(&self.secret, &guess);
+        self.rows.push((guess, answer));
+        self.current.clear();
+        self.redraw = true;
+        answer
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The menu, or the result and the statistics at the end.
+#[derive(Component)]
+struct MenuText;
+
+/// The panel behind the menu and the result.
+#[derive(Component)]
+struct Panel;
+
+/// The choices in the menu.
+struct Settings {
+    colors: u8,
+    /// The solver breaks the code, for the player to watch.
+    demo: bool,
+    length: usize,
+    /// The menu row picked: pegs, colours or mode.
+    row: usize,
+}
+
+struct Sounds {
+    lost: Handle<AudioSource>,
+    peg: Handle<AudioSource>,
+    submit: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct MastermindPlugin;
+
+impl Plugin for MastermindPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings {
+            colors: 6,
+            demo: false,
+            length: 4,
+            row: 0,
+        };
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(&settings))
+            .insert_resource(settings)
+            .insert_resource(Stats::load())
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(guess_input)
+                    .with_system(demo_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(over_enter))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(board_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Mastermind".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The guess rows from the bottom up with their answers, the code on top and the palette below.
+fn board_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut game: ResMut<Game>,
+    state: Res<State<GameState>>,
+    sprites: Query<Entity, With<BoardSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut glyph = |glyph: &str, size: f32, color: Color, at: Vec2| {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                glyph,
+                size,
+                color,
+                at.extend(1.0),
+            ))
+            .insert(BoardSprite);
+    };
+
+    for row in 0..MAX_GUESSES {
+        let y = BOARD_BOTTOM + row as f32 * ROW_HEIGHT;
+        let (pegs, answer) = match game.rows.get(row) {
+            Some((guess, answer)) => (guess.clone(), Some(*answer)),
+            None if row == game.rows.len() => (game.current.clone(), None),
+            None => (Vec::new(), None),
+        };
+        glyph(
+            &(row + 1).to_string(),
+            16.0,
+            EMPTY_COLOR,
+            Vec2::new(-220.0, y),
+        );
+        for index in 0..game.length {
+            let at = Vec2::new(peg_x(index, game.length), y);
+            match pegs.get(index) {
+                Some(color) => glyph("●", 40.0, COLORS[*color as usize], at),
+                None => glyph("○", 40.0, EMPTY_COLOR, at),
+            }
+        }
+        if let Some(answer) = answer {
+            for index in 0..game.length {
+                let color = if index < answer.exact {
+                    EXACT_COLOR
+                } else if index < answer.exact + answer.partial {
+                    PARTIAL_COLOR
+                } else {
+                    EMPTY_COLOR
+                };
+                let at = Vec2::new(FEEDBACK_LEFT + index as f32 * 16.0, y);
+                glyph("●", 16.0, color, at);
+            }
+        }
+    }
+
+    let secret_y = BOARD_BOTTOM + MAX_GUESSES as f32 * ROW_HEIGHT + 10.0;
+    for index in 0..game.length {
+        let at = Vec2::new(peg_x(index, game.length), secret_y);
+        if *state.current() == GameState::Over {
+            glyph("●", 40.0, COLORS[game.secret[index] as usize], at);
+        } else {
+            glyph("?", 30.0, TEXT_COLOR, at);
+        }
+    }
+
+    for color in 0..game.colors {
+        let x = palette_x(color, game.colors);
+        glyph("●", 40.0, COLORS[color as usize], Vec2::new(x, PALETTE_Y));
+        let label = (color + 1).to_string();
+        glyph(&label, 14.0, TEXT_COLOR, Vec2::new(x, PALETTE_Y - 26.0));
+    }
+}
+
+/// The solver makes a guess every so often, learning from each answer.
+fn demo_update(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.demo.is_none() {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    game.demo_wait -= time.delta_seconds();
+    if game.demo_wait > 0.0 {
+        return;
+    }
+    game.demo_wait = DEMO_DELAY;
+    let guess = match &game.demo {
+        Some(solver) => solver.guess(&mut thread_rng()),
+        None => return,
+    };
+    let answer = game.submit(guess.clone());
+    if let Some(solver) = game.demo.as_mut() {
+        solver.learn(&guess, answer);
+    }
+    audio.play(sounds.submit.clone());
+    if game.is_over() {
+        state.set(GameState::Over).unwrap();
+    }
+}
+
+/// 1 to 8 or a click on the palette add a peg, Backspace takes one back, Enter makes the guess
+/// and Esc gives up.
+fn guess_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.demo.is_some() {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    const KEYS: [KeyCode; 8] = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+        KeyCode::Key6,
+        KeyCode::Key7,
+        KeyCode::Key8,
+    ];
+    let mut picked = KEYS[..game.colors as usize]
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+        .map(|color| color as u8);
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let colors = game.colors;
+        if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+            picked = (0..colors).find(|color| {
+                (cursor - Vec2::new(palette_x(*color, colors), PALETTE_Y)).length() < 22.0
+            });
+        }
+    }
+    if let Some(color) = picked {
+        if game.current.len() < game.length {
+            game.current.push(color);
+            game.redraw = true;
+            audio.play(sounds.peg.clone());
+        }
+    } else if keyboard_input.just_pressed(KeyCode::Back) {
+        game.current.pop();
+        game.redraw = true;
+    } else if keyboard_input.just_pressed(KeyCode::Return) && game.current.len() == game.length {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let guess = game.current.clone();
+        game.submit(guess);
+        audio.play(sounds.submit.clone());
+        if game.is_over() {
+            state.set(GameState::Over).unwrap();
+        }
+    }
+}
+
+/// Up and down pick a setting, left and right change it, Enter starts.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut settings: ResMut<Settings>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let change = if menu.just_pressed(Navigate::Left) {
+        -1
+    } else if menu.just_pressed(Navigate::Right) {
+        1
+    } else {
+        0
+    };
+    if menu.just_pressed(Navigate::Up) {
+        settings.row = settings.row.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Down) {
+        settings.row = (settings.row + 1).min(2);
+    } else if change != 0 {
+        match settings.row {
+            0 => {
+                let length = settings.length as i32 + change;
+                settings.length =
+                    length.clamp(LENGTH_RANGE.0 as i32, LENGTH_RANGE.1 as i32) as usize;
+            }
+            1 => {
+                let colors = settings.colors as i32 + change;
+                settings.colors = colors.clamp(COLOR_RANGE.0 as i32, COLOR_RANGE.1 as i32) as u8;
+            }
+            _ => settings.demo = !settings.demo,
+        }
+    } else if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(&settings);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Shows the code and counts the game in the statistics, unless the solver played it.
+fn over_enter(
+    mut game: ResMut<Game>,
+    mut stats: ResMut<Stats>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    game.redraw = true;
+    let won = game.is_won();
+    audio.play(if won {
+        sounds.won.clone()
+    } else {
+        sounds.lost.clone()
+    });
+    if game.demo.is_none() {
+        stats.record(won, game.rows.len());
+        stats.save();
+    }
+}
+
+/// Enter goes back to the menu.
+fn over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        game.redraw = true;
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn palette_x(color: u8, colors: u8) -> f32 {
+    (color as f32 - (colors - 1) as f32 / 2.0) * PEG_SPACING
+}
+
+fn peg_x(index: usize, length: usize) -> f32 {
+    (index as f32 - (length - 1) as f32 / 2.0) * PEG_SPACING - 40.0
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(0.05, 0.03, 0.02, 0.92),
+                custom_size: Some(Vec2::new(WINDOW_WIDTH - 60.0, 500.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, -20.0, 850.0),
+            ..Default::default()
+        })
+        .insert(Panel);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, -20.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        lost: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.4).fade(),
+        ])),
+        peg: audio_sources.add(
+            Tone::new(Waveform::Sine, 520.0, 0.05)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        submit: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.04).volume(0.1),
+            Tone::new(Waveform::Square, 440.0, 0.06).volume(0.1),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35).fade(),
+        ])),
+    });
+}
+
+/// The statistics with how the broken codes are spread over the guesses.
+fn stats_summary(stats: &Stats) -> String {
+    let mut summary = format!(
+        "Played {}   Broken {}%   Average {:.1} guesses\nStreak {}   Best {}\n",
+        stats.played,
+        stats.win_percentage(),
+        stats.average_guesses(),
+        stats.current_streak,
+        stats.max_streak
+    );
+    let most = stats.distribution.iter().copied().max().unwrap_or(0).max(1);
+    for (index, count) in stats.distribution.iter().enumerate() {
+        let bar = "\u{2588}".repeat((*count * 12 / most) as usize);
+        summary.push_str(&format!("\n{:>2} {:<12} {:>3}", index + 1, bar, count));
+    }
+    summary
+}
+
+fn texts_update(
+    game: Res<Game>,
+    settings: Res<Settings>,
+    stats: Res<Stats>,
+    state: Res<State<GameState>>,
+    mut panels: Query<&mut Visibility, With<Panel>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let (hud, help, banner) = match state.current() {
+        GameState::Menu => {
+            let rows = [
+                format!("Pegs     < {} >", settings.length),
+                format!("Colours  < {} >", settings.colors),
+                format!(
+                    "Mode     < {} >",
+                    if settings.demo {
+                        "watch the solver"
+                    } else {
+                        "play"
+                    }
+                ),
+            ];
+            let menu = rows
+                .iter()
+                .enumerate()
+                .map(|(row, text)| {
+                    let marker = if row == settings.row { ">" } else { " " };
+                    format!("{} {}", marker, text)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                String::new(),
+                "Up/Down: pick   Left/Right: change   Enter: start".to_string(),
+                format!("MASTERMIND\n\n{}\n\n{}", menu, stats_summary(&stats)),
+            )
+        }
+        GameState::Playing => {
+            let hud = match &game.demo {
+                Some(solver) => format!(
+                    "Guess {} of {}   Codes still possible {}",
+                    game.rows.len() + 1,
+                    MAX_GUESSES,
+                    solver.candidates.len()
+                ),
+                None => format!("Guess {} of {}", game.rows.len() + 1, MAX_GUESSES),
+            };
+            let help = if game.demo.is_some() {
+                "Esc: menu".to_string()
+            } else {
+                format!(
+                    "1-{}/click: add a peg   Backspace: take back   Enter: guess   Esc: menu",
+                    game.colors
+                )
+            };
+            (hud, help, String::new())
+        }
+        GameState::Over => {
+            let result = if game.is_won() {
+                format!("Broken in {} of {}!", game.rows.len(), MAX_GUESSES)
+            } else {
+                "The code held".to_string()
+            };
+            let summary = if game.demo.is_some() {
+                "The solver played this one.".to_string()
+            } else {
+                stats_summary(&stats)
+            };
+            (
+                String::new(),
+                "Enter: menu".to_string(),
+                format!("{}\n\n{}", result, summary),
+            )
+        }
+    };
+    for mut visibility in panels.iter_mut() {
+        visibility.is_visible = *state.current() != GameState::Playing;
+    }
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
@@ -0,0 +1,88 @@
+//! Statistics over the codes the player set out to break, kept between sessions.
+
+use serde::{Deserialize, Serialize};
+
+/// Guesses to break a code in.
+pub const MAX_GUESSES: usize = 10;
+
+const STATS_FILE: &str = "stats";
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub current_streak: u32,
+    /// How many codes were broken in one guess, two guesses and so on.
+    pub distribution: [u32; MAX_GUESSES],
+    pub max_streak: u32,
+    pub played: u32,
+    pub won: u32,
+}
+
+impl Stats {
+    /// Average guesses over the codes broken.
+    pub fn average_guesses(&self) -> f32 {
+        let total: u32 = self
+            .distribution
+            .iter()
+            .enumerate()
+            .map(|(index, count)| (index as u32 + 1) * count)
+            .sum();
+        if self.won == 0 {
+            0.0
+        } else {
+            total as f32 / self.won as f32
+        }
+    }
+
+    pub fn load() -> Self {
+        game_persistence::load_or_default("mastermind", STATS_FILE)
+    }
+
+    pub fn record(&mut self, won: bool, guesses: usize) {
+        self.played += 1;
+        if !won {
+            self.current_streak = 0;
+            return;
+        }
+        self.won += 1;
+        self.distribution[guesses.clamp(1, MAX_GUESSES) - 1] += 1;
+        self.current_streak += 1;
+        self.max_streak = self.max_streak.max(self.current_streak);
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save("mastermind", STATS_FILE, self) {
+            eprintln!("could not save statistics: {}", error);
+        }
+    }
+
+    /// Share of the codes that were broken, in percent.
+    pub fn win_percentage(&self) -> u32 {
+        (self.won * 100).checked_div(self.played).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_wins_streaks_and_guesses() {
+        let mut stats = Stats::default();
+        stats.record(true, 4);
+        stats.record(true, 6);
+        stats.record(false, MAX_GUESSES);
+        stats.record(true, 5);
+        assert_eq!(
+            (
+                stats.played,
+                stats.won,
+                stats.current_streak,
+                stats.max_streak
+            ),
+            (4, 3, 1, 2)
+        );
+        assert_eq!(stats.win_percentage(), 75);
+        assert_eq!(stats.average_guesses(), 5.0);
+        assert_eq!(stats.distribution[3..6], [1, 1, 1]);
+    }
+}
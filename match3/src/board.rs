@@ -0,0 +1,420 @@
+//! The gem grid: swapping, finding matches, special gems and refilling.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const COLORS: u8 = 6;
+pub const HEIGHT: usize = 8;
+/// Points per gem cleared, multiplied by how far into a cascade the clear happened.
+pub const POINTS: u32 = 10;
+pub const WIDTH: usize = 8;
+
+/// Column and row, counted from the bottom left.
+pub type Cell = (usize, usize);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Special {
+    None,
+    /// Made by matching four; clears its whole row or column when cleared.
+    Line {
+        horizontal: bool,
+    },
+    /// Made by matching five; swapping it with a gem clears every gem of that color.
+    Rainbow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gem {
+    /// Rainbow gems have no color of their own and never match.
+    pub color: u8,
+    /// Stays the same while the gem moves, so it can be animated.
+    pub id: u32,
+    pub special: Special,
+}
+
+/// The gems removed by one step of resolving the board.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Clear {
+    pub gems: Vec<(Cell, Gem)>,
+    pub points: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Board {
+    /// `cells[y][x]`.
+    cells: [[Option<Gem>; WIDTH]; HEIGHT],
+    next_id: u32,
+    /// A rainbow gem that was swapped and the color it takes out, `None` for another rainbow.
+    rainbow: Option<(Cell, Option<u8>)>,
+    /// Where the last swap happened; special gems made by it appear there.
+    swapped: Option<[Cell; 2]>,
+}
+
+impl Board {
+    /// A full board without matches that has at least one move.
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut board = Self {
+            cells: [[None; WIDTH]; HEIGHT],
+            next_id: 0,
+            rainbow: None,
+            swapped: None,
+        };
+        board.refill(rng);
+        loop {
+            let runs = board.runs();
+            if runs.is_empty() && board.has_moves() {
+                return board;
+            }
+            for (run, _) in runs {
+                let (x, y) = run[0];
+                board.cells[y][x] = Some(board.random_gem(rng));
+            }
+            if board.runs().is_empty() && !board.has_moves() {
+                board.shuffle(rng);
+            }
+        }
+    }
+
+    pub fn get(&self, (x, y): Cell) -> Option<Gem> {
+        self.cells.get(y)?.get(x).copied().flatten()
+    }
+
+    pub fn gems(&self) -> impl Iterator<Item = (Cell, Gem)> + '_ {
+        self.cells.iter().enumerate().flat_map(|(y, row)| {
+            row.iter()
+                .enumerate()
+                .filter_map(move |(x, gem)| gem.map(|gem| ((x, y), gem)))
+        })
+    }
+
+    /// Whether any swap would make a match.
+    pub fn has_moves(&self) -> bool {
+        (0..HEIGHT).any(|y| {
+            (0..WIDTH).any(|x| {
+                [(x + 1, y), (x, y + 1)]
+                    .into_iter()
+                    .any(|other| self.get(other).is_some() && self.clone().swap((x, y), other))
+            })
+        })
+    }
+
+    /// Mixes up the gems until there is no match and at least one move.
+    pub fn shuffle(&mut self, rng: &mut impl Rng) {
+        let mut gems: Vec<Gem> = self.gems().map(|(_, gem)| gem).collect();
+        loop {
+            gems.shuffle(rng);
+            for (index, gem) in gems.iter().enumerate() {
+                self.cells[index / WIDTH][index % WIDTH] = Some(*gem);
+            }
+            if self.runs().is_empty() && self.has_moves() {
+                return;
+            }
+        }
+    }
+
+    /// Resolves one round of matches: clears them, makes special gems, lets the gems above fall
+    /// and refills from the top. `chain` counts the rounds since the swap, starting at 1.
+    pub fn step(&mut self, chain: u32, rng: &mut impl Rng) -> Option<Clear> {
+        let mut cleared: Vec<Cell> = Vec::new();
+        let mut created: Vec<(Cell, Gem)> = Vec::new();
+        let swapped_rainbow = self.rainbow.map(|(cell, _)| cell);
+        if let Some((cell, color)) = self.rainbow.take() {
+            cleared.push(cell);
+            cleared.extend(
+                self.gems()
+                    .filter(|(_, gem)| match color {
+                        Some(color) => gem.color == color,
+                        None => true,
+                    })
+                    .map(|(cell, _)| cell),
+            );
+        }
+        for (run, horizontal) in self.runs() {
+            cleared.extend(run.iter().copied());
+            if run.len() < 4 {
+                continue;
+            }
+            let pivot = self
+                .swapped
+                .and_then(|swapped| swapped.into_iter().find(|cell| run.contains(cell)))
+                .unwrap_or(run[run.len() / 2]);
+            if created.iter().any(|(cell, _)| *cell == pivot) {
+                continue;
+            }
+            let special = if run.len() >= 5 {
+                Special::Rainbow
+            } else {
+                Special::Line { horizontal }
+            };
+            let color = match special {
+                Special::Rainbow => COLORS,
+                _ => self.get(pivot).map_or(0, |gem| gem.color),
+            };
+            let gem = self.new_gem(color, special);
+            created.push((pivot, gem));
+        }
+        self.swapped = None;
+        if cleared.is_empty() {
+            return None;
+        }
+
+        // Special gems set off by the clear add to it, which can set off more of them.
+        let mut index = 0;
+        while index < cleared.len() {
+            let (x, y) = cleared[index];
+            index += 1;
+            if swapped_rainbow == Some((x, y)) {
+                continue;
+            }
+            let extra: Vec<Cell> = match self.get((x, y)).map(|gem| gem.special) {
+                Some(Special::Line { horizontal: true }) => (0..WIDTH).map(|x| (x, y)).collect(),
+                Some(Special::Line { horizontal: false }) => (0..HEIGHT).map(|y| (x, y)).collect(),
+                Some(Special::Rainbow) => {
+                    let color = rng.gen_range(0, COLORS);
+                    self.gems()
+                        .filter(|(_, gem)| gem.color == color)
+                        .map(|(cell, _)| cell)
+                        .collect()
+                }
+                _ => Vec::new(),
+            };
+            for cell in extra {
+                if !cleared.contains(&cell) {
+                    cleared.push(cell);
+                }
+            }
+        }
+
+        let mut gems = Vec::new();
+        for (x, y) in cleared {
+            if let Some(gem) = self.cells[y][x].take() {
+                gems.push(((x, y), gem));
+            }
+        }
+        for ((x, y), gem) in created {
+            self.cells[y][x] = Some(gem);
+        }
+        self.collapse();
+        self.refill(rng);
+        Some(Clear {
+            points: gems.len() as u32 * POINTS * chain,
+            gems,
+        })
+    }
+
+    /// Swaps two neighbouring gems if that makes a match or sets off a rainbow gem;
+    /// returns whether it did.
+    pub fn swap(&mut self, a: Cell, b: Cell) -> bool {
+        let (first, second) = match (self.get(a), self.get(b)) {
+            (Some(first), Some(second)) if a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1 => {
+                (first, second)
+            }
+            _ => return false,
+        };
+        self.cells[a.1][a.0] = Some(second);
+        self.cells[b.1][b.0] = Some(first);
+        self.rainbow = match (first.special, second.special) {
+            (Special::Rainbow, Special::Rainbow) => Some((b, None)),
+            (Special::Rainbow, _) => Some((b, Some(second.color))),
+            (_, Special::Rainbow) => Some((a, Some(first.color))),
+            _ => None,
+        };
+        if self.rainbow.is_none() && self.runs().is_empty() {
+            self.cells[a.1][a.0] = Some(first);
+            self.cells[b.1][b.0] = Some(second);
+            return false;
+        }
+        self.swapped = Some([a, b]);
+        true
+    }
+
+    /// Lets gems fall into the gaps below them.
+    fn collapse(&mut self) {
+        for x in 0..WIDTH {
+            let column: Vec<Gem> = (0..HEIGHT).filter_map(|y| self.cells[y][x]).collect();
+            for y in 0..HEIGHT {
+                self.cells[y][x] = column.get(y).copied();
+            }
+        }
+    }
+
+    fn new_gem(&mut self, color: u8, special: Special) -> Gem {
+        self.next_id += 1;
+        Gem {
+            color,
+            id: self.next_id,
+            special,
+        }
+    }
+
+    fn random_gem(&mut self, rng: &mut impl Rng) -> Gem {
+        self.new_gem(rng.gen_range(0, COLORS), Special::None)
+    }
+
+    fn refill(&mut self, rng: &mut impl Rng) {
+        for y in 0..HEIGHT {
+            for x in 0..WIDTH {
+                if self.cells[y][x].is_none() {
+                    self.cells[y][x] = Some(self.random_gem(rng));
+                }
+            }
+        }
+    }
+
+    /// Lines of three or more gems of the same color, and whether they are horizontal.
+    fn runs(&self) -> Vec<(Vec<Cell>, bool)> {
+        let color = |cell: Cell| {
+            self.get(cell)
+                .filter(|gem| gem.special != Special::Rainbow)
+                .map(|gem| gem.color)
+        };
+        let mut runs = Vec::new();
+        for horizontal in [true, false] {
+            let (lines, length) = if horizontal {
+                (HEIGHT, WIDTH)
+            } else {
+                (WIDTH, HEIGHT)
+            };
+            for line in 0..lines {
+                let cell = |index: usize| {
+                    if horizontal {
+                        (index, line)
+                    } else {
+                        (line, index)
+                    }
+                };
+                let mut start = 0;
+                for end in 1..=length {
+                    if end < length
+                        && color(cell(end)).is_some()
+                        && color(cell(end)) == color(cell(start))
+                    {
+                        continue;
+                    }
+                    if end - start >= 3 && color(cell(start)).is_some() {
+                        runs.push(((start..end).map(cell).collect(), horizontal));
+                    }
+                    start = end;
+                }
+            }
+        }
+        runs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    /// A board from rows of color letters, top row first; `*` is a rainbow gem, capitals are
+    /// horizontal line gems.
+    fn parse(rows: [&str; HEIGHT]) -> Board {
+        let mut board = Board {
+            cells: [[None; WIDTH]; HEIGHT],
+            next_id: 0,
+            rainbow: None,
+            swapped: None,
+        };
+        for (row, letters) in rows.iter().enumerate() {
+            for (x, letter) in letters.chars().enumerate() {
+                let (color, special) = match letter {
+                    '*' => (COLORS, Special::Rainbow),
+                    'A'..='F' => (letter as u8 - b'A', Special::Line { horizontal: true }),
+                    _ => (letter as u8 - b'a', Special::None),
+                };
+                let gem = board.new_gem(color, special);
+                board.cells[HEIGHT - 1 - row][x] = Some(gem);
+            }
+        }
+        board
+    }
+
+    const QUIET: [&str; HEIGHT] = [
+        "abcdefab", "cdefabcd", "efabcdef", "abcdefab", "cdefabcd", "efabcdef", "abcdefab",
+        "cdefabcd",
+    ];
+
+    #[test]
+    fn new_boards_have_no_matches_but_moves() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let board = Board::new(&mut rng);
+            assert!(board.runs().is_empty());
+            assert!(board.has_moves());
+            assert_eq!(board.gems().count(), WIDTH * HEIGHT);
+        }
+    }
+
+    #[test]
+    fn swaps_must_make_a_match() {
+        let mut rows = QUIET;
+        rows[7] = "aabaefab";
+        let mut board = parse(rows);
+        assert!(!board.swap((0, 0), (0, 1)), "no match");
+        assert!(!board.swap((0, 0), (2, 0)), "not neighbours");
+        assert_eq!(board.get((2, 0)).unwrap().color, 1);
+        assert!(board.swap((2, 0), (3, 0)));
+        let clear = board.step(1, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(clear.gems.len(), 3);
+        assert_eq!(clear.points, 3 * POINTS);
+        assert_eq!(board.gems().count(), WIDTH * HEIGHT, "refilled");
+    }
+
+    #[test]
+    fn four_make_a_line_gem_and_five_a_rainbow() {
+        let mut rows = QUIET;
+        rows[7] = "aabaafab";
+        rows[6] = "cdaefbcd";
+        let mut board = parse(rows);
+        assert!(board.swap((2, 0), (2, 1)));
+        let clear = board.step(1, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(clear.gems.len(), 5);
+        assert_eq!(
+            board.get((2, 0)).map(|gem| gem.special),
+            Some(Special::Rainbow)
+        );
+
+        let mut rows = QUIET;
+        rows[7] = "aabacfab";
+        rows[6] = "cdaefbcd";
+        let mut board = parse(rows);
+        assert!(board.swap((2, 0), (2, 1)));
+        board.step(1, &mut StdRng::seed_from_u64(1));
+        assert_eq!(
+            board.get((2, 0)).map(|gem| gem.special),
+            Some(Special::Line { horizontal: true })
+        );
+    }
+
+    #[test]
+    fn line_gems_clear_their_row() {
+        let mut rows = QUIET;
+        rows[7] = "aAbaefab";
+        let mut board = parse(rows);
+        assert!(board.swap((2, 0), (3, 0)));
+        let clear = board.step(2, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(clear.gems.len(), WIDTH);
+        assert_eq!(
+            clear.points,
+            WIDTH as u32 * POINTS * 2,
+            "second in the cascade"
+        );
+    }
+
+    #[test]
+    fn rainbows_clear_the_color_they_are_swapped_with() {
+        let mut rows = QUIET;
+        rows[7] = "*dcdefab";
+        let mut board = parse(rows);
+        let blues = board.gems().filter(|(_, gem)| gem.color == 3).count();
+        assert!(board.swap((0, 0), (1, 0)));
+        let clear = board.step(1, &mut StdRng::seed_from_u64(1)).unwrap();
+        assert_eq!(clear.gems.len(), blues + 1);
+        assert!(clear
+            .gems
+            .iter()
+            .all(|(_, gem)| gem.color == 3 || gem.special == Special::Rainbow));
+    }
+}
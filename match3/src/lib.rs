@@ -0,0 +1,734 @@
+use bevy::math::const_vec2;
+use bevy::prelude::*;
+use board::{Board, Cell, Gem, Special, COLORS, HEIGHT, WIDTH};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+pub mod board;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.12, 0.08, 0.16);
+const BEST_FILE: &str = "best";
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, -40.0]);
+const CELL_COLORS: [Color; 2] = [Color::rgb(0.2, 0.15, 0.26), Color::rgb(0.24, 0.18, 0.3)];
+const CELL_SIZE: f32 = 64.0;
+/// Seconds a cleared gem takes to shrink away.
+const CLEAR_DURATION: f32 = 0.3;
+const FLASH_DURATION: f32 = 0.8;
+const GEM_COLORS: [Color; COLORS as usize] = [
+    Color::rgb(0.9, 0.2, 0.2),
+    Color::rgb(0.95, 0.55, 0.1),
+    Color::rgb(0.95, 0.85, 0.15),
+    Color::rgb(0.2, 0.75, 0.3),
+    Color::rgb(0.2, 0.5, 0.95),
+    Color::rgb(0.7, 0.3, 0.9),
+];
+const GEM_SIZE: f32 = 50.0;
+/// Pixels per second gems move towards their cell.
+const GEM_SPEED: f32 = 700.0;
+const MOVES: u32 = 30;
+/// Seconds a swap that makes no match takes to go there and back.
+const REJECT_DURATION: f32 = 0.35;
+const SELECTION_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+/// Pause once the gems have settled before the board is checked for matches again.
+const STEP_PAUSE: f32 = 0.12;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const TIME_LIMIT: f32 = 90.0;
+
+pub const WINDOW_HEIGHT: f32 = 720.0;
+pub const WINDOW_WIDTH: f32 = 600.0;
+
+/// The best score of each mode, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Best {
+    moves: u32,
+    timed: u32,
+}
+
+impl Best {
+    fn get(&self, mode: Mode) -> u32 {
+        match mode {
+            Mode::Moves => self.moves,
+            Mode::Timed => self.timed,
+        }
+    }
+
+    fn record(&mut self, mode: Mode, score: u32) {
+        let best = match mode {
+            Mode::Moves => &mut self.moves,
+            Mode::Timed => &mut self.timed,
+        };
+        if score > *best {
+            *best = score;
+            if let Err(error) = game_persistence::save("match3", BEST_FILE, self) {
+                eprintln!("could not save best scores: {}", error);
+            }
+        }
+    }
+}
+
+/// A cleared gem shrinking away.
+#[derive(Component)]
+struct Dying(Timer);
+
+/// Points popping up where gems were cleared.
+#[derive(Component)]
+struct Flash(Timer);
+
+struct Game {
+    board: Board,
+    /// Rounds of matches since the last swap, 0 once the board has settled.
+    chain: u32,
+    mode: Mode,
+    moves: u32,
+    /// Time since the gems settled, counting towards the next round of matches.
+    pause: f32,
+    /// A swap that made no match and how far its animation is.
+    rejected: Option<([Cell; 2], f32)>,
+    score: u32,
+    /// The gem picked by a click, or where a drag started.
+    selected: Option<Cell>,
+    time_left: f32,
+}
+
+impl Game {
+    fn new(mode: Mode) -> Self {
+        Self {
+            board: Board::new(&mut thread_rng()),
+            chain: 0,
+            mode,
+            moves: MOVES,
+            pause: 0.0,
+            rejected: None,
+            score: 0,
+            selected: None,
+            time_left: TIME_LIMIT,
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        self.chain > 0 || self.rejected.is_some()
+    }
+
+    fn is_finished(&self) -> bool {
+        match self.mode {
+            Mode::Moves => self.moves == 0,
+            Mode::Timed => self.time_left <= 0.0,
+        }
+    }
+
+    /// Where the gem in a cell is drawn; gems of a rejected swap trade places and come back.
+    fn position(&self, cell: Cell) -> Vec2 {
+        if let Some(([a, b], elapsed)) = self.rejected {
+            let amount = (elapsed / REJECT_DURATION * PI).sin();
+            if cell == a {
+                return cell_center(a).lerp(cell_center(b), amount);
+            }
+            if cell == b {
+                return cell_center(b).lerp(cell_center(a), amount);
+            }
+        }
+        cell_center(cell)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+}
+
+/// The sprite of the gem with this id.
+#[derive(Component)]
+struct GemSprite(u32);
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// A limited number of swaps.
+    Moves,
+    /// As many swaps as fit in the time limit.
+    Timed,
+}
+
+impl Mode {
+    fn name(self) -> String {
+        match self {
+            Mode::Moves => format!("{} moves", MOVES),
+            Mode::Timed => format!("{} seconds", TIME_LIMIT),
+        }
+    }
+
+    fn toggled(self) -> Self {
+        match self {
+            Mode::Moves => Mode::Timed,
+            Mode::Timed => Mode::Moves,
+        }
+    }
+}
+
+#[derive(Component)]
+struct Selection;
+
+/// Whether every gem has reached its cell.
+struct Settled(bool);
+
+struct Sounds {
+    /// One per round of a cascade, each higher than the last.
+    clears: Vec<Handle<AudioSource>>,
+    shuffle: Handle<AudioSource>,
+    special: Handle<AudioSource>,
+    swap: Handle<AudioSource>,
+    wrong: Handle<AudioSource>,
+}
+
+pub struct Match3Plugin;
+
+impl Plugin for Match3Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(game_persistence::load_or_default::<Best>(
+                "match3", BEST_FILE,
+            ))
+            .insert_resource(Game::new(Mode::Moves))
+            .insert_resource(Settled(false))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(resolve)
+                    .with_system(swap_input)
+                    .with_system(time_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(dying_update)
+            .add_system(flash_update)
+            .add_system(gems_update)
+            .add_system(hud_update)
+            .add_system(message_update)
+            .add_system(selection_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Match 3".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn cell_at(position: Vec2) -> Option<Cell> {
+    let corner = BOARD_CENTER - Vec2::new(WIDTH as f32, HEIGHT as f32) * CELL_SIZE / 2.0;
+    let offset = (position - corner) / CELL_SIZE;
+    if offset.x < 0.0 || offset.y < 0.0 {
+        return None;
+    }
+    let cell = (offset.x as usize, offset.y as usize);
+    if cell.0 < WIDTH && cell.1 < HEIGHT {
+        Some(cell)
+    } else {
+        None
+    }
+}
+
+fn cell_center((x, y): Cell) -> Vec2 {
+    let column = x as f32 - (WIDTH - 1) as f32 / 2.0;
+    let row = y as f32 - (HEIGHT - 1) as f32 / 2.0;
+    BOARD_CENTER + Vec2::new(column, row) * CELL_SIZE
+}
+
+/// Cleared gems spin and shrink away.
+fn dying_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut gems: Query<(Entity, &mut Dying, &mut Transform)>,
+) {
+    for (entity, mut dying, mut transform) in gems.iter_mut() {
+        dying.0.tick(time.delta());
+        if dying.0.finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let left = 1.0 - dying.0.percent();
+        transform.scale = Vec3::splat(left * (1.0 + dying.0.percent()));
+        transform.rotation = Quat::from_rotation_z(dying.0.percent() * PI);
+    }
+}
+
+/// Points float up and fade out.
+fn flash_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut Flash, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut flash, mut transform, mut text) in flashes.iter_mut() {
+        flash.0.tick(time.delta());
+        if flash.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += 50.0 * time.delta_seconds();
+        text.sections[0].style.color.set_a(1.0 - flash.0.percent());
+    }
+}
+
+fn game_over(game: Res<Game>, mut best: ResMut<Best>) {
+    best.record(game.mode, game.score);
+}
+
+/// Moves every gem sprite towards its cell, starts shrinking the ones that were cleared and
+/// drops new ones in from above the board.
+fn gems_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    mut settled: ResMut<Settled>,
+    mut sprites: Query<(Entity, &GemSprite, &mut Transform)>,
+) {
+    let mut gems: HashMap<u32, (Cell, Gem)> = game
+        .board
+        .gems()
+        .map(|(cell, gem)| (gem.id, (cell, gem)))
+        .collect();
+    let step = GEM_SPEED * time.delta_seconds();
+    let mut moving = false;
+    for (entity, sprite, mut transform) in sprites.iter_mut() {
+        let cell = match gems.remove(&sprite.0) {
+            Some((cell, _)) => cell,
+            None => {
+                commands
+                    .entity(entity)
+                    .remove::<GemSprite>()
+                    .insert(Dying(Timer::from_seconds(CLEAR_DURATION, false)));
+                continue;
+            }
+        };
+        let current = transform.translation.truncate();
+        let offset = game.position(cell) - current;
+        if offset.length() <= step {
+            transform.translation = game.position(cell).extend(1.0);
+        } else {
+            transform.translation = (current + offset.normalize() * step).extend(1.0);
+            moving = true;
+        }
+    }
+    // New gems start stacked above their column in the order they fill it.
+    for (cell, gem) in gems.values() {
+        let above = gems.values().filter(|(other, _)| other.0 == cell.0).count();
+        let start = cell_center(*cell) + Vec2::new(0.0, above as f32 * CELL_SIZE);
+        spawn_gem(&mut commands, *gem, start);
+        moving = true;
+    }
+    settled.0 = !moving;
+}
+
+fn hud_update(best: Res<Best>, game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if !best.is_changed() && !game.is_changed() {
+        return;
+    }
+    let left = match game.mode {
+        Mode::Moves => format!("MOVES {}", game.moves),
+        Mode::Timed => format!("TIME {}", game.time_left.ceil()),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "{}   SCORE {}   BEST {}",
+            left,
+            game.score,
+            best.get(game.mode)
+        );
+    }
+}
+
+fn is_neighbour(a: Cell, b: Cell) -> bool {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1
+}
+
+/// Left/Right switches the mode, Enter or a click starts.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut mouse_input: ResMut<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.horizontal() != 0 {
+        game.mode = game.mode.toggled();
+    }
+    if menu.just_pressed(Navigate::Confirm)
+        || keyboard_input.just_pressed(KeyCode::Space)
+        || mouse_input.just_pressed(MouseButton::Left)
+        || touches.iter_just_pressed().next().is_some()
+    {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        mouse_input.clear_just_pressed(MouseButton::Left);
+        *game = Game::new(game.mode);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn message_update(
+    best: Res<Best>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<MessageText>>,
+) {
+    if !state.is_changed() && !game.is_changed() {
+        return;
+    }
+    let value = match state.current() {
+        GameState::Menu => format!(
+            "Mode  < {} >   Enter: start\nSwap neighbouring gems to line up three",
+            game.mode.name()
+        ),
+        GameState::Playing => String::new(),
+        GameState::Over => format!(
+            "{} Score {} (best {})\nEnter: play again   Esc: menu",
+            match game.mode {
+                Mode::Moves => "Out of moves!",
+                Mode::Timed => "Time's up!",
+            },
+            game.score,
+            best.get(game.mode)
+        ),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        *game = Game::new(game.mode);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+/// Clears one round of matches each time the gems have settled, until the board is quiet;
+/// then shuffles a board without moves and ends a finished game.
+fn resolve(
+    mut commands: Commands,
+    time: Res<Time>,
+    font: Res<HudFont>,
+    settled: Res<Settled>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.chain == 0 {
+        if game.rejected.is_none() && game.is_finished() {
+            state.set(GameState::Over).unwrap();
+        }
+        return;
+    }
+    if !settled.0 {
+        return;
+    }
+    game.pause += time.delta_seconds();
+    if game.pause < STEP_PAUSE {
+        return;
+    }
+    game.pause = 0.0;
+    let chain = game.chain;
+    let clear = match game.board.step(chain, &mut thread_rng()) {
+        Some(clear) => clear,
+        None => {
+            game.chain = 0;
+            if !game.board.has_moves() {
+                game.board.shuffle(&mut thread_rng());
+                audio.play(sounds.shuffle.clone());
+            }
+            return;
+        }
+    };
+    game.chain += 1;
+    game.score += clear.points;
+
+    let center = clear
+        .gems
+        .iter()
+        .map(|(cell, _)| cell_center(*cell))
+        .fold(Vec2::ZERO, |sum, center| sum + center)
+        / clear.gems.len() as f32;
+    let value = if chain > 1 {
+        format!("+{}  x{}", clear.points, chain)
+    } else {
+        format!("+{}", clear.points)
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            &value,
+            20.0 + 4.0 * chain.min(5) as f32,
+            TEXT_COLOR,
+            center.extend(10.0),
+        ))
+        .insert(Flash(Timer::from_seconds(FLASH_DURATION, false)));
+
+    let index = (chain as usize - 1).min(sounds.clears.len() - 1);
+    audio.play(sounds.clears[index].clone());
+    if clear
+        .gems
+        .iter()
+        .any(|(_, gem)| gem.special != Special::None)
+    {
+        audio.play(sounds.special.clone());
+    }
+}
+
+/// Highlights the picked gem.
+fn selection_update(
+    game: Res<Game>,
+    mut selections: Query<(&mut Transform, &mut Visibility), With<Selection>>,
+) {
+    for (mut transform, mut visibility) in selections.iter_mut() {
+        visibility.is_visible = game.selected.is_some();
+        if let Some(cell) = game.selected {
+            transform.translation = cell_center(cell).extend(0.5);
+        }
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            commands.spawn_bundle(rectangle(
+                CELL_COLORS[(x + y) % 2],
+                Vec2::splat(CELL_SIZE),
+                cell_center((x, y)),
+                0.0,
+            ));
+        }
+    }
+    commands
+        .spawn_bundle(rectangle(
+            SELECTION_COLOR,
+            Vec2::splat(CELL_SIZE - 4.0),
+            Vec2::ZERO,
+            0.5,
+        ))
+        .insert(Selection);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 270.0, 10.0),
+        ))
+        .insert(MessageText);
+
+    commands.insert_resource(Sounds {
+        clears: (0..6)
+            .map(|step| {
+                audio_sources.add(
+                    Tone::new(Waveform::Triangle, 440.0 * 1.19f32.powi(step), 0.18)
+                        .slide(660.0 * 1.19f32.powi(step))
+                        .fade()
+                        .volume(0.35)
+                        .into(),
+                )
+            })
+            .collect(),
+        shuffle: audio_sources.add(
+            Tone::new(Waveform::Noise, 400.0, 0.5)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        special: audio_sources.add(
+            Tone::new(Waveform::Saw, 220.0, 0.35)
+                .slide(880.0)
+                .fade()
+                .volume(0.25)
+                .into(),
+        ),
+        swap: audio_sources.add(
+            Tone::new(Waveform::Sine, 500.0, 0.08)
+                .slide(700.0)
+                .volume(0.25)
+                .into(),
+        ),
+        wrong: audio_sources.add(
+            Tone::new(Waveform::Square, 140.0, 0.2)
+                .slide(100.0)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+    });
+}
+
+/// A gem is a colored square with a shine; line gems carry a stripe, rainbow gems a patch of
+/// every color.
+fn spawn_gem(commands: &mut Commands, gem: Gem, position: Vec2) {
+    let color = match gem.special {
+        Special::Rainbow => Color::rgb(0.15, 0.15, 0.15),
+        _ => GEM_COLORS[gem.color as usize],
+    };
+    commands
+        .spawn_bundle(rectangle(color, Vec2::splat(GEM_SIZE), position, 1.0))
+        .insert(GemSprite(gem.id))
+        .with_children(|parent| {
+            let shine = Vec2::splat(GEM_SIZE * 0.2);
+            parent.spawn_bundle(rectangle(
+                Color::rgba(1.0, 1.0, 1.0, 0.5),
+                shine,
+                Vec2::new(-GEM_SIZE * 0.25, GEM_SIZE * 0.25),
+                0.1,
+            ));
+            match gem.special {
+                Special::None => {}
+                Special::Line { horizontal } => {
+                    let size = if horizontal {
+                        Vec2::new(GEM_SIZE, GEM_SIZE * 0.16)
+                    } else {
+                        Vec2::new(GEM_SIZE * 0.16, GEM_SIZE)
+                    };
+                    parent.spawn_bundle(rectangle(TEXT_COLOR, size, Vec2::ZERO, 0.2));
+                }
+                Special::Rainbow => {
+                    let patch = GEM_SIZE / 4.0;
+                    for (index, color) in GEM_COLORS.iter().enumerate() {
+                        let offset = Vec2::new((index % 3) as f32 - 1.0, (index / 3) as f32 - 0.5)
+                            * patch
+                            * 1.2;
+                        parent.spawn_bundle(rectangle(*color, Vec2::splat(patch), offset, 0.2));
+                    }
+                }
+            }
+        });
+}
+
+/// Clicking two neighbouring gems one after another, or dragging one onto a neighbour, swaps
+/// them. Swaps that make no match are shown and undone.
+fn swap_input(
+    time: Res<Time>,
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if let Some((cells, elapsed)) = game.rejected {
+        let elapsed = elapsed + time.delta_seconds();
+        game.rejected = if elapsed < REJECT_DURATION {
+            Some((cells, elapsed))
+        } else {
+            None
+        };
+    }
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let half_size = Vec2::new(window.width(), window.height()) / 2.0;
+    let mut pressed: Vec<Vec2> = touches
+        .iter_just_pressed()
+        .map(|touch| touch.position() - half_size)
+        .collect();
+    let mut released: Vec<Vec2> = touches
+        .iter_just_released()
+        .map(|touch| touch.position() - half_size)
+        .collect();
+    if mouse_input.just_pressed(MouseButton::Left) {
+        pressed.extend(game_hud::cursor_world_position(&windows));
+    }
+    if mouse_input.just_released(MouseButton::Left) {
+        released.extend(game_hud::cursor_world_position(&windows));
+    }
+    if game.is_busy() || game.is_finished() {
+        return;
+    }
+
+    let mut swap = None;
+    for cell in pressed.into_iter().filter_map(cell_at) {
+        match game.selected {
+            Some(selected) if is_neighbour(selected, cell) => swap = Some([selected, cell]),
+            _ => game.selected = Some(cell),
+        }
+    }
+    for cell in released.into_iter().filter_map(cell_at) {
+        if let Some(selected) = game.selected {
+            if is_neighbour(selected, cell) {
+                swap = Some([selected, cell]);
+            }
+        }
+    }
+    let [a, b] = match swap {
+        Some(cells) => cells,
+        None => return,
+    };
+    game.selected = None;
+    if game.board.swap(a, b) {
+        game.chain = 1;
+        game.pause = 0.0;
+        if game.mode == Mode::Moves {
+            game.moves -= 1;
+        }
+        audio.play(sounds.swap.clone());
+    } else {
+        game.rejected = Some(([a, b], 0.0));
+        audio.play(sounds.wrong.clone());
+    }
+}
+
+fn time_update(time: Res<Time>, mut game: ResMut<Game>) {
+    if game.mode == Mode::Timed {
+        game.time_left = (game.time_left - time.delta_seconds()).max(0.0);
+    }
+}
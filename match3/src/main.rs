@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+use match3::Match3Plugin;
+
+fn main() {
+    App::new()
+        .insert_resource(match3::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(Match3Plugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
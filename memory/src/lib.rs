@@ -0,0 +1,531 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::{seq::SliceRandom, thread_rng};
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.12, 0.1);
+const BOARD_HEIGHT: f32 = 680.0;
+const BOARD_SIZE: Vec2 = const_vec2!([WINDOW_WIDTH, BOARD_HEIGHT]);
+const CARD_BACK_COLOR: Color = Color::rgb(0.25, 0.35, 0.7);
+const FLIP_BACK_DELAY: f32 = 1.0;
+/// Columns and rows of the selectable boards; every board has an even number of cards.
+const GRID_SIZES: [(u32, u32); 5] = [(4, 3), (4, 4), (6, 4), (6, 5), (6, 6)];
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const MATCHED_ALPHA: f32 = 0.35;
+const SYMBOLS: [char; 18] = [
+    'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'J', 'K', 'L', 'M', 'N', 'P', 'R', 'S', 'T', 'W',
+];
+
+pub const WINDOW_HEIGHT: f32 = BOARD_HEIGHT + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = 720.0;
+
+#[derive(Component)]
+struct Card {
+    face: CardFace,
+    symbol: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum CardFace {
+    Down,
+    Up,
+    Matched,
+}
+
+#[derive(Component)]
+struct CardLabel;
+
+/// Cards turned over this turn; a mismatched pair stays visible until the timer runs out.
+struct Flipped {
+    cards: Vec<Entity>,
+    flip_back: Timer,
+}
+
+struct Game {
+    current_player: usize,
+    elapsed: f32,
+    grid: usize,
+    moves: u32,
+    pairs: [u32; 2],
+    two_players: bool,
+}
+
+impl Game {
+    fn size(&self) -> (u32, u32) {
+        GRID_SIZES[self.grid]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Finished,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Component)]
+struct ResultText;
+
+struct Sounds {
+    flip: Handle<AudioSource>,
+    matched: Handle<AudioSource>,
+    mismatch: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct MemoryPlugin;
+
+impl Plugin for MemoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game {
+                current_player: 0,
+                elapsed: 0.0,
+                grid: 1,
+                moves: 0,
+                pairs: [0; 2],
+                two_players: false,
+            })
+            .insert_resource(Flipped {
+                cards: Vec::new(),
+                flip_back: Timer::from_seconds(FLIP_BACK_DELAY, false),
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(menu_enter))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu).with_system(despawn_all::<MenuText>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(card_click)
+                    .with_system(flip_back)
+                    .with_system(game_clock),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Finished).with_system(finished))
+            .add_system_set(SystemSet::on_update(GameState::Finished).with_system(finished_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Finished)
+                    .with_system(despawn_all::<ResultText>)
+                    .with_system(despawn_all::<Card>),
+            )
+            .add_system(card_visuals)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Memory".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn card_click(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut flipped: ResMut<Flipped>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut cards: Query<(Entity, &mut Card, &Transform, &Sprite)>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) || flipped.cards.len() >= 2 {
+        return;
+    }
+    let cursor = match game_hud::cursor_world_position(&windows) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+    let clicked = cards.iter_mut().find(|(_, card, transform, sprite)| {
+        let half = sprite.custom_size.unwrap_or(Vec2::ZERO) / 2.0;
+        let offset = (cursor - transform.translation.truncate()).abs();
+        card.face == CardFace::Down && offset.x < half.x && offset.y < half.y
+    });
+    let (entity, mut card, _, _) = match clicked {
+        Some(clicked) => clicked,
+        None => return,
+    };
+    card.face = CardFace::Up;
+    let symbol = card.symbol;
+    flipped.cards.push(entity);
+    if flipped.cards.len() < 2 {
+        audio.play(sounds.flip.clone());
+        return;
+    }
+    game.moves += 1;
+    let first_symbol = cards
+        .get(flipped.cards[0])
+        .ok()
+        .map(|(_, card, _, _)| card.symbol);
+    if first_symbol != Some(symbol) {
+        audio.play(sounds.mismatch.clone());
+        flipped.flip_back.reset();
+        return;
+    }
+    for entity in flipped.cards.drain(..) {
+        if let Ok((_, mut card, _, _)) = cards.get_mut(entity) {
+            card.face = CardFace::Matched;
+        }
+    }
+    // A found pair lets the same player go again.
+    let player = game.current_player;
+    game.pairs[player] += 1;
+    if cards
+        .iter()
+        .all(|(_, card, _, _)| card.face == CardFace::Matched)
+    {
+        audio.play(sounds.won.clone());
+        state.set(GameState::Finished).unwrap();
+    } else {
+        audio.play(sounds.matched.clone());
+    }
+}
+
+fn card_visuals(
+    mut cards: Query<(&Card, &mut Sprite, &Children), Changed<Card>>,
+    mut labels: Query<&mut Text, With<CardLabel>>,
+) {
+    for (card, mut sprite, children) in cards.iter_mut() {
+        let mut color = symbol_color(card.symbol);
+        match card.face {
+            CardFace::Down => color = CARD_BACK_COLOR,
+            CardFace::Up => {}
+            CardFace::Matched => {
+                color.set_a(MATCHED_ALPHA);
+            }
+        }
+        sprite.color = color;
+        for child in children.iter() {
+            if let Ok(mut text) = labels.get_mut(*child) {
+                text.sections[0].value = if card.face == CardFace::Down {
+                    String::new()
+                } else {
+                    SYMBOLS[card.symbol].to_string()
+                };
+            }
+        }
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn finished(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let headline = if !game.two_players {
+        format!(
+            "Cleared in {} moves and {}",
+            game.moves,
+            format_time(game.elapsed)
+        )
+    } else if game.pairs[0] == game.pairs[1] {
+        format!("Draw, {} pairs each", game.pairs[0])
+    } else {
+        let winner = if game.pairs[0] > game.pairs[1] { 1 } else { 2 };
+        format!(
+            "Player {} wins {} to {}",
+            winner,
+            game.pairs[0].max(game.pairs[1]),
+            game.pairs[0].min(game.pairs[1])
+        )
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("{}\nPress Enter", headline),
+            36.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(ResultText);
+}
+
+fn finished_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn flip_back(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut flipped: ResMut<Flipped>,
+    mut cards: Query<&mut Card>,
+) {
+    if flipped.cards.len() < 2 || !flipped.flip_back.tick(time.delta()).just_finished() {
+        return;
+    }
+    for entity in flipped.cards.drain(..) {
+        if let Ok(mut card) = cards.get_mut(entity) {
+            card.face = CardFace::Down;
+        }
+    }
+    if game.two_players {
+        game.current_player = 1 - game.current_player;
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn game_clock(time: Res<Time>, mut game: ResMut<Game>) {
+    game.elapsed += time.delta_seconds();
+}
+
+fn hud_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let value = match state.current() {
+        GameState::Menu => String::new(),
+        _ if game.two_players => format!(
+            "{}P1 {}   {}P2 {}   MOVES {}   TIME {}",
+            if game.current_player == 0 { ">" } else { " " },
+            game.pairs[0],
+            if game.current_player == 1 { ">" } else { " " },
+            game.pairs[1],
+            game.moves,
+            format_time(game.elapsed)
+        ),
+        _ => format!(
+            "PAIRS {}   MOVES {}   TIME {}",
+            game.pairs[0],
+            game.moves,
+            format_time(game.elapsed)
+        ),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&game),
+            30.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    if menu.just_pressed(Navigate::Left) || keyboard_input.just_pressed(KeyCode::A) {
+        game.grid = game.grid.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Right) || keyboard_input.just_pressed(KeyCode::D) {
+        game.grid = (game.grid + 1).min(GRID_SIZES.len() - 1);
+    } else if keyboard_input.just_pressed(KeyCode::Tab) {
+        game.two_players = !game.two_players;
+    } else if menu.just_pressed(Navigate::Confirm) {
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&game);
+    }
+}
+
+fn menu_label(game: &Game) -> String {
+    let (columns, rows) = game.size();
+    format!(
+        "MEMORY\n\n< {} x {} cards >\n{}\n\nLeft/Right: board size\nTab: toggle players\nEnter to start",
+        columns,
+        rows,
+        if game.two_players {
+            "Two players"
+        } else {
+            "One player"
+        }
+    )
+}
+
+fn new_game(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut game: ResMut<Game>,
+    mut flipped: ResMut<Flipped>,
+) {
+    game.current_player = 0;
+    game.elapsed = 0.0;
+    game.moves = 0;
+    game.pairs = [0; 2];
+    flipped.cards.clear();
+    let (columns, rows) = game.size();
+    let pairs = (columns * rows / 2) as usize;
+    let mut symbols: Vec<usize> = (0..pairs).chain(0..pairs).collect();
+    symbols.shuffle(&mut thread_rng());
+    let pitch = (BOARD_SIZE.x / columns as f32).min(BOARD_SIZE.y / rows as f32);
+    let card_size = Vec2::new(pitch * 0.8, pitch * 0.9);
+    for (index, symbol) in symbols.into_iter().enumerate() {
+        let column = index as u32 % columns;
+        let row = index as u32 / columns;
+        let x = (column as f32 - (columns as f32 - 1.0) / 2.0) * pitch;
+        let y = ((rows as f32 - 1.0) / 2.0 - row as f32) * pitch - HUD_HEIGHT / 2.0;
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: CARD_BACK_COLOR,
+                    custom_size: Some(card_size),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(x, y, 1.0),
+                ..Default::default()
+            })
+            .insert(Card {
+                face: CardFace::Down,
+                symbol,
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        "",
+                        pitch * 0.5,
+                        Color::BLACK,
+                        Vec3::new(0.0, 0.0, 0.1),
+                    ))
+                    .insert(CardLabel);
+            });
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.insert_resource(Sounds {
+        flip: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.06)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        matched: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 660.0, 0.08),
+            Tone::new(Waveform::Triangle, 990.0, 0.15),
+        ])),
+        mismatch: audio_sources.add(
+            Tone::new(Waveform::Square, 220.0, 0.25)
+                .slide(160.0)
+                .volume(0.15)
+                .into(),
+        ),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 659.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 784.0, 0.12).volume(0.25),
+            Tone::new(Waveform::Square, 1047.0, 0.35).volume(0.25),
+        ])),
+    });
+}
+
+/// Face colors spread around the color wheel, one per symbol.
+fn symbol_color(symbol: usize) -> Color {
+    Color::hsl(symbol as f32 * 360.0 / SYMBOLS.len() as f32, 0.75, 0.65)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_board_has_pairs_of_distinct_symbols() {
+        for (columns, rows) in GRID_SIZES {
+            assert_eq!(columns * rows % 2, 0);
+            assert!((columns * rows / 2) as usize <= SYMBOLS.len());
+        }
+    }
+
+    #[test]
+    fn mismatched_cards_turn_back_and_pass_the_turn() {
+        let mut app = App::new();
+        app.insert_resource(Time::default())
+            .insert_resource(Game {
+                current_player: 0,
+                elapsed: 0.0,
+                grid: 0,
+                moves: 1,
+                pairs: [0; 2],
+                two_players: true,
+            })
+            .add_system(flip_back);
+        let cards: Vec<Entity> = (0..2)
+            .map(|symbol| {
+                app.world
+                    .spawn()
+                    .insert(Card {
+                        face: CardFace::Up,
+                        symbol,
+                    })
+                    .id()
+            })
+            .collect();
+        app.insert_resource(Flipped {
+            cards: cards.clone(),
+            flip_back: Timer::from_seconds(0.0, false),
+        });
+        app.update();
+        for card in cards {
+            assert!(app.world.get::<Card>(card).unwrap().face == CardFace::Down);
+        }
+        assert!(app
+            .world
+            .get_resource::<Flipped>()
+            .unwrap()
+            .cards
+            .is_empty());
+        assert_eq!(app.world.get_resource::<Game>().unwrap().current_player, 1);
+    }
+
+    #[test]
+    fn times_show_minutes_and_seconds() {
+        assert_eq!(format_time(0.0), "0:00");
+        assert_eq!(format_time(59.9), "0:59");
+        assert_eq!(format_time(754.0), "12:34");
+    }
+}
@@ -0,0 +1,383 @@
+//! Incoming missiles, the counter-missiles fired at them and what is left standing.
+
+use bevy::math::Vec2;
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const AMMO: u32 = 10;
+pub const AMMO_BONUS: u32 = 5;
+pub const BLAST_RADIUS: f32 = 44.0;
+/// How long a blast grows, and then shrinks again.
+pub const BLAST_TIME: f32 = 0.5;
+/// A destroyed city is rebuilt for every this many points.
+pub const BONUS_CITY_SCORE: u32 = 10_000;
+pub const CITY_BONUS: u32 = 100;
+pub const CITY_XS: [f32; 6] = [-270.0, -190.0, -110.0, 110.0, 190.0, 270.0];
+pub const COUNTER_SPEED: f32 = 520.0;
+pub const GROUND: f32 = -250.0;
+pub const MISSILE_SCORE: u32 = 25;
+pub const SILO_XS: [f32; 3] = [-350.0, 0.0, 350.0];
+pub const SKY: f32 = 300.0;
+pub const WIDTH: f32 = 800.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Blast {
+    pub age: f32,
+    pub center: Vec2,
+}
+
+impl Blast {
+    pub fn radius(&self) -> f32 {
+        let grown = if self.age < BLAST_TIME {
+            self.age / BLAST_TIME
+        } else {
+            2.0 - self.age / BLAST_TIME
+        };
+        BLAST_RADIUS * grown.max(0.0)
+    }
+}
+
+/// A counter-missile on its way to where the player aimed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Counter {
+    pub from: Vec2,
+    pub position: Vec2,
+    pub target: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    CityDestroyed,
+    /// A counter-missile reached its target, or an incoming missile was caught in a blast.
+    Explosion,
+    SiloDestroyed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Missile {
+    pub from: Vec2,
+    pub position: Vec2,
+    pub speed: f32,
+    pub target: Target,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Silo {
+    pub alive: bool,
+    pub ammo: u32,
+}
+
+/// What an incoming missile is headed for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    City(usize),
+    Silo(usize),
+}
+
+impl Target {
+    pub fn position(self) -> Vec2 {
+        match self {
+            Self::City(index) => Vec2::new(CITY_XS[index], GROUND),
+            Self::Silo(index) => Vec2::new(SILO_XS[index], GROUND),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Battle {
+    pub blasts: Vec<Blast>,
+    pub cities: [bool; 6],
+    pub counters: Vec<Counter>,
+    pub missiles: Vec<Missile>,
+    /// Missiles of this wave not launched yet.
+    remaining: u32,
+    pub score: u32,
+    pub silos: [Silo; 3],
+    until_launch: f32,
+    pub wave: u32,
+}
+
+impl Default for Battle {
+    fn default() -> Self {
+        let mut battle = Self {
+            blasts: Vec::new(),
+            cities: [true; 6],
+            counters: Vec::new(),
+            missiles: Vec::new(),
+            remaining: 0,
+            score: 0,
+            silos: [Silo {
+                alive: true,
+                ammo: AMMO,
+            }; 3],
+            until_launch: 0.0,
+            wave: 0,
+        };
+        battle.next_wave();
+        battle
+    }
+}
+
+impl Battle {
+    /// Points for what survived the wave: cities standing and counter-missiles left.
+    pub fn bonus(&self) -> u32 {
+        self.cities_left() as u32 * CITY_BONUS * self.multiplier()
+            + self.silos.iter().map(|silo| silo.ammo).sum::<u32>() * AMMO_BONUS * self.multiplier()
+    }
+
+    pub fn cities_left(&self) -> usize {
+        self.cities.iter().filter(|alive| **alive).count()
+    }
+
+    /// Launches a counter-missile from `silo`; returns whether it had one.
+    pub fn fire(&mut self, silo: usize, target: Vec2) -> bool {
+        let from = Vec2::new(SILO_XS[silo], GROUND);
+        if !self.silos[silo].alive || self.silos[silo].ammo == 0 || target.y <= GROUND {
+            return false;
+        }
+        self.silos[silo].ammo -= 1;
+        self.counters.push(Counter {
+            from,
+            position: from,
+            target,
+        });
+        true
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.cities_left() == 0
+    }
+
+    /// Every two waves are worth more, up to six times as much as the first two.
+    pub fn multiplier(&self) -> u32 {
+        (self.wave.saturating_sub(1) / 2 + 1).min(6)
+    }
+
+    /// The silo closest to `x` that can still fire.
+    pub fn nearest_silo(&self, x: f32) -> Option<usize> {
+        (0..SILO_XS.len())
+            .filter(|index| self.silos[*index].alive && self.silos[*index].ammo > 0)
+            .min_by(|a, b| {
+                (SILO_XS[*a] - x)
+                    .abs()
+                    .partial_cmp(&(SILO_XS[*b] - x).abs())
+                    .unwrap()
+            })
+    }
+
+    /// Awards the wave bonus and starts the next wave; silos are rebuilt and refilled.
+    pub fn next_wave(&mut self) {
+        if self.wave > 0 {
+            let before = self.score;
+            self.score += self.bonus();
+            let earned = self.score / BONUS_CITY_SCORE - before / BONUS_CITY_SCORE;
+            for _ in 0..earned {
+                if let Some(city) = self.cities.iter_mut().find(|alive| !**alive) {
+                    *city = true;
+                }
+            }
+        }
+        self.wave += 1;
+        self.remaining = 8 + 2 * self.wave;
+        self.until_launch = 1.0;
+        for silo in self.silos.iter_mut() {
+            *silo = Silo {
+                alive: true,
+                ammo: AMMO,
+            };
+        }
+    }
+
+    pub fn update(&mut self, delta: f32, rng: &mut impl Rng) -> Vec<Event> {
+        let mut events = Vec::new();
+        self.launch(delta, rng);
+
+        let mut arrived = Vec::new();
+        for counter in self.counters.iter_mut() {
+            let left = counter.target - counter.position;
+            let step = COUNTER_SPEED * delta;
+            if left.length() <= step {
+                arrived.push(counter.target);
+            } else {
+                counter.position += left.normalize() * step;
+            }
+        }
+        self.counters
+            .retain(|counter| !arrived.contains(&counter.target));
+        for center in arrived {
+            self.blasts.push(Blast { age: 0.0, center });
+            events.push(Event::Explosion);
+        }
+
+        for blast in self.blasts.iter_mut() {
+            blast.age += delta;
+        }
+        self.blasts.retain(|blast| blast.age < 2.0 * BLAST_TIME);
+
+        let mut flying = Vec::new();
+        for mut missile in std::mem::take(&mut self.missiles) {
+            let target = missile.target.position();
+            let left = target - missile.position;
+            let step = missile.speed * delta;
+            if left.length() <= step {
+                // Hits the ground where it was headed.
+                self.blasts.push(Blast {
+                    age: 0.0,
+                    center: target,
+                });
+                match missile.target {
+                    Target::City(index) if self.cities[index] => {
+                        self.cities[index] = false;
+                        events.push(Event::CityDestroyed);
+                    }
+                    Target::Silo(index) if self.silos[index].alive => {
+                        self.silos[index] = Silo {
+                            alive: false,
+                            ammo: 0,
+                        };
+                        events.push(Event::SiloDestroyed);
+                    }
+                    _ => events.push(Event::Explosion),
+                }
+                continue;
+            }
+            missile.position += left.normalize() * step;
+            let caught = self
+                .blasts
+                .iter()
+                .any(|blast| blast.center.distance(missile.position) <= blast.radius());
+            if caught {
+                // Caught missiles explode in turn, which can take out others nearby.
+                self.blasts.push(Blast {
+                    age: 0.0,
+                    center: missile.position,
+                });
+                self.score += MISSILE_SCORE * self.multiplier();
+                events.push(Event::Explosion);
+            } else {
+                flying.push(missile);
+            }
+        }
+        self.missiles = flying;
+        events
+    }
+
+    /// Everything launched this wave has come down and the blasts have faded.
+    pub fn wave_over(&self) -> bool {
+        self.remaining == 0
+            && self.missiles.is_empty()
+            && self.counters.is_empty()
+            && self.blasts.is_empty()
+    }
+
+    /// Launches the wave's missiles one after another from random points in the sky.
+    fn launch(&mut self, delta: f32, rng: &mut impl Rng) {
+        if self.remaining == 0 {
+            return;
+        }
+        self.until_launch -= delta;
+        if self.until_launch > 0.0 {
+            return;
+        }
+        self.until_launch += rng.gen_range(0.4, 1.6) * (6.0 / (5.0 + self.wave as f32));
+        let targets: Vec<Target> = (0..CITY_XS.len())
+            .filter(|index| self.cities[*index])
+            .map(Target::City)
+            .chain(
+                (0..SILO_XS.len())
+                    .filter(|index| self.silos[*index].alive)
+                    .map(Target::Silo),
+            )
+            .collect();
+        let target = match targets.choose(rng) {
+            Some(target) => *target,
+            None => return,
+        };
+        let from = Vec2::new(rng.gen_range(-WIDTH / 2.0, WIDTH / 2.0), SKY);
+        self.missiles.push(Missile {
+            from,
+            position: from,
+            speed: 30.0 + 8.0 * self.wave as f32,
+            target,
+        });
+        self.remaining -= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    fn incoming(battle: &mut Battle, target: Target, height: f32, speed: f32) {
+        let from = Vec2::new(target.position().x, height);
+        battle.missiles.push(Missile {
+            from,
+            position: from,
+            speed,
+            target,
+        });
+    }
+
+    #[test]
+    fn blasts_destroy_missiles_and_chain() {
+        let mut battle = Battle::default();
+        battle.remaining = 0;
+        incoming(&mut battle, Target::City(1), 185.0, 10.0);
+        // Out of reach of the counter-missile, but not of the first missile's blast.
+        incoming(&mut battle, Target::City(1), 220.0, 10.0);
+        assert!(battle.fire(0, Vec2::new(CITY_XS[1], 150.0)));
+        assert_eq!(battle.silos[0].ammo, AMMO - 1);
+        let mut rng = StdRng::seed_from_u64(1);
+        for _ in 0..120 {
+            battle.update(STEP, &mut rng);
+        }
+        assert!(battle.missiles.is_empty());
+        assert_eq!(battle.score, 2 * MISSILE_SCORE);
+        assert!(battle.cities[1]);
+        for _ in 0..120 {
+            battle.update(STEP, &mut rng);
+        }
+        assert!(battle.wave_over());
+    }
+
+    #[test]
+    fn missiles_that_land_destroy_their_target() {
+        let mut battle = Battle::default();
+        battle.remaining = 0;
+        incoming(&mut battle, Target::City(4), GROUND + 10.0, 100.0);
+        incoming(&mut battle, Target::Silo(2), GROUND + 10.0, 100.0);
+        let events = battle.update(0.2, &mut StdRng::seed_from_u64(1));
+        assert!(events.contains(&Event::CityDestroyed));
+        assert!(events.contains(&Event::SiloDestroyed));
+        assert!(!battle.cities[4]);
+        assert!(!battle.fire(2, Vec2::new(300.0, 0.0)));
+        assert_eq!(battle.nearest_silo(340.0), Some(1));
+        assert!(
+            !battle.fire(1, Vec2::new(0.0, GROUND - 5.0)),
+            "not below ground"
+        );
+    }
+
+    #[test]
+    fn surviving_cities_and_ammo_earn_a_bonus() {
+        let mut battle = Battle::default();
+        battle.cities = [true, false, true, false, false, false];
+        battle.silos[0].ammo = 4;
+        battle.silos[1].ammo = 0;
+        battle.silos[2].alive = false;
+        battle.silos[2].ammo = 0;
+        assert_eq!(battle.bonus(), 2 * CITY_BONUS + 4 * AMMO_BONUS);
+        battle.score = BONUS_CITY_SCORE - 10;
+        battle.next_wave();
+        assert_eq!(battle.wave, 2);
+        assert_eq!(battle.cities_left(), 3, "a city was rebuilt");
+        assert!(battle
+            .silos
+            .iter()
+            .all(|silo| silo.alive && silo.ammo == AMMO));
+    }
+}
@@ -0,0 +1,423 @@
+use battle::{Battle, Event, CITY_XS, GROUND, SILO_XS};
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use rand::thread_rng;
+
+pub mod battle;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.0, 0.0, 0.05);
+/// Colors a blast cycles through while it burns.
+const BLAST_COLORS: [Color; 3] = [
+    Color::rgb(1.0, 1.0, 1.0),
+    Color::rgb(1.0, 0.85, 0.2),
+    Color::rgb(1.0, 0.35, 0.2),
+];
+/// Horizontal strips a blast circle is drawn with.
+const BLAST_STRIPS: usize = 14;
+const CITY_COLOR: Color = Color::rgb(0.2, 0.6, 1.0);
+const COUNTER_COLOR: Color = Color::rgb(0.4, 0.9, 1.0);
+const CROSSHAIR_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const GROUND_COLOR: Color = Color::rgb(0.75, 0.6, 0.15);
+const MISSILE_COLOR: Color = Color::rgb(0.95, 0.2, 0.2);
+const RUBBLE_COLOR: Color = Color::rgb(0.3, 0.25, 0.2);
+const SILO_COLOR: Color = Color::rgb(0.85, 0.7, 0.25);
+const SILO_KEYS: [[KeyCode; 2]; 3] = [
+    [KeyCode::A, KeyCode::Key1],
+    [KeyCode::S, KeyCode::Key2],
+    [KeyCode::D, KeyCode::Key3],
+];
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const TRAIL_WIDTH: f32 = 2.0;
+/// Seconds the bonus of a finished wave is shown.
+const WAVE_PAUSE: f32 = 3.0;
+
+pub const WINDOW_HEIGHT: f32 = 600.0;
+pub const WINDOW_WIDTH: f32 = battle::WIDTH;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    /// The bonus for the cities and ammo left is shown before the next wave.
+    WaveOver,
+    Over,
+}
+
+/// The best score of the session.
+#[derive(Default)]
+struct HighScore(u32);
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+struct Sounds {
+    city: Handle<AudioSource>,
+    empty: Handle<AudioSource>,
+    explosion: Handle<AudioSource>,
+    launch: Handle<AudioSource>,
+    wave: Handle<AudioSource>,
+}
+
+struct WavePause(Timer);
+
+pub struct MissileCommandPlugin;
+
+impl Plugin for MissileCommandPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Battle::default())
+            .insert_resource(HighScore::default())
+            .insert_resource(WavePause(Timer::from_seconds(WAVE_PAUSE, false)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(fire_input)
+                    .with_system(battle_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::WaveOver).with_system(wave_over))
+            .add_system_set(SystemSet::on_update(GameState::WaveOver).with_system(wave_pause))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(restart_input))
+            .add_system(battle_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Missile Command".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws the ground, cities, silos, missile trails and blasts every frame.
+fn battle_render(
+    mut commands: Commands,
+    time: Res<Time>,
+    windows: Res<Windows>,
+    battle: Res<Battle>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut sprites = Vec::new();
+    let mut draw = |color: Color, size: Vec2, center: Vec2, z: f32| {
+        sprites.push(rectangle(color, size, center, z));
+    };
+
+    draw(
+        GROUND_COLOR,
+        Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT / 2.0 + GROUND),
+        Vec2::new(0.0, (GROUND - WINDOW_HEIGHT / 2.0) / 2.0),
+        0.0,
+    );
+    for (index, x) in CITY_XS.iter().enumerate() {
+        if battle.cities[index] {
+            for (dx, height) in [(-14.0, 14.0), (-4.0, 24.0), (7.0, 18.0), (16.0, 10.0)] {
+                draw(
+                    CITY_COLOR,
+                    Vec2::new(9.0, height),
+                    Vec2::new(x + dx, GROUND + height / 2.0),
+                    1.0,
+                );
+            }
+        } else {
+            draw(
+                RUBBLE_COLOR,
+                Vec2::new(40.0, 5.0),
+                Vec2::new(*x, GROUND + 2.5),
+                1.0,
+            );
+        }
+    }
+    for (index, x) in SILO_XS.iter().enumerate() {
+        let silo = battle.silos[index];
+        let color = if silo.alive { SILO_COLOR } else { RUBBLE_COLOR };
+        draw(
+            color,
+            Vec2::new(60.0, 16.0),
+            Vec2::new(*x, GROUND + 8.0),
+            1.0,
+        );
+        draw(
+            color,
+            Vec2::new(30.0, 10.0),
+            Vec2::new(*x, GROUND + 21.0),
+            1.0,
+        );
+        // One dot per counter-missile left, in rows below the silo.
+        for shot in 0..silo.ammo {
+            let (row, column) = (shot / 5, shot % 5);
+            draw(
+                COUNTER_COLOR,
+                Vec2::splat(4.0),
+                Vec2::new(
+                    x - 16.0 + column as f32 * 8.0,
+                    GROUND - 12.0 - row as f32 * 8.0,
+                ),
+                1.0,
+            );
+        }
+    }
+
+    let mut trails = Vec::new();
+    for missile in battle.missiles.iter() {
+        trails.push(trail(MISSILE_COLOR, missile.from, missile.position));
+        draw(TEXT_COLOR, Vec2::splat(4.0), missile.position, 3.0);
+    }
+    for counter in battle.counters.iter() {
+        trails.push(trail(COUNTER_COLOR, counter.from, counter.position));
+        draw(TEXT_COLOR, Vec2::splat(4.0), counter.position, 3.0);
+        for size in [Vec2::new(10.0, 2.0), Vec2::new(2.0, 10.0)] {
+            draw(COUNTER_COLOR, size, counter.target, 3.0);
+        }
+    }
+    let flicker = (time.seconds_since_startup() * 20.0) as usize;
+    for (index, blast) in battle.blasts.iter().enumerate() {
+        let radius = blast.radius();
+        let color = BLAST_COLORS[(flicker + index) % BLAST_COLORS.len()];
+        let height = 2.0 * radius / BLAST_STRIPS as f32;
+        for strip in 0..BLAST_STRIPS {
+            let y = -radius + (strip as f32 + 0.5) * height;
+            let width = 2.0 * (radius * radius - y * y).max(0.0).sqrt();
+            draw(
+                color,
+                Vec2::new(width, height + 0.5),
+                blast.center + Vec2::new(0.0, y),
+                4.0,
+            );
+        }
+    }
+
+    if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+        for size in [Vec2::new(16.0, 2.0), Vec2::new(2.0, 16.0)] {
+            draw(CROSSHAIR_COLOR, size, cursor, 5.0);
+        }
+    }
+    for sprite in sprites.into_iter().chain(trails) {
+        commands.spawn_bundle(sprite).insert(Actor);
+    }
+}
+
+fn battle_update(
+    time: Res<Time>,
+    mut battle: ResMut<Battle>,
+    mut high_score: ResMut<HighScore>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    for event in battle.update(time.delta_seconds(), &mut thread_rng()) {
+        let sound = match event {
+            Event::CityDestroyed | Event::SiloDestroyed => &sounds.city,
+            Event::Explosion => &sounds.explosion,
+        };
+        audio.play(sound.clone());
+    }
+    high_score.0 = high_score.0.max(battle.score);
+    if battle.is_lost() && battle.missiles.is_empty() && battle.blasts.is_empty() {
+        state.set(GameState::Over).unwrap();
+    } else if battle.wave_over() {
+        state.set(GameState::WaveOver).unwrap();
+    }
+}
+
+/// A trail from where a missile was launched to where it is now.
+fn trail(color: Color, from: Vec2, to: Vec2) -> SpriteBundle {
+    let offset = to - from;
+    let mut sprite = rectangle(
+        color,
+        Vec2::new(offset.length(), TRAIL_WIDTH),
+        (from + to) / 2.0,
+        2.0,
+    );
+    sprite.transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+    sprite
+}
+
+/// The mouse aims; a click fires from the nearest silo, A/S/D or 1/2/3 from a given one.
+fn fire_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut battle: ResMut<Battle>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let cursor = match game_hud::cursor_world_position(&windows) {
+        Some(cursor) => cursor,
+        None => return,
+    };
+    let silo = if mouse_input.just_pressed(MouseButton::Left) {
+        battle.nearest_silo(cursor.x)
+    } else if let Some(silo) =
+        (0..SILO_KEYS.len()).find(|silo| keyboard_input.any_just_pressed(SILO_KEYS[*silo]))
+    {
+        Some(silo)
+    } else {
+        return;
+    };
+    let fired = match silo {
+        Some(silo) => battle.fire(silo, cursor),
+        None => false,
+    };
+    let sound = if fired { &sounds.launch } else { &sounds.empty };
+    audio.play(sound.clone());
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn restart_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut battle: ResMut<Battle>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        *battle = Battle::default();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 60.0, 10.0),
+        ))
+        .insert(MessageText);
+
+    commands.insert_resource(Sounds {
+        city: audio_sources.add(
+            Tone::new(Waveform::Noise, 60.0, 0.8)
+                .fade()
+                .volume(0.6)
+                .into(),
+        ),
+        empty: audio_sources.add(Tone::new(Waveform::Square, 110.0, 0.06).volume(0.15).into()),
+        explosion: audio_sources.add(
+            Tone::new(Waveform::Noise, 200.0, 0.4)
+                .fade()
+                .volume(0.35)
+                .into(),
+        ),
+        launch: audio_sources.add(
+            Tone::new(Waveform::Saw, 300.0, 0.15)
+                .slide(900.0)
+                .volume(0.15)
+                .into(),
+        ),
+        wave: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 440.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 660.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 880.0, 0.2).volume(0.2),
+        ])),
+    });
+}
+
+fn texts_update(
+    battle: Res<Battle>,
+    high_score: Res<HighScore>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+    )>,
+) {
+    if !battle.is_changed() && !state.is_changed() {
+        return;
+    }
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = format!(
+            "SCORE {}   HIGH {}   WAVE {}   x{}",
+            battle.score,
+            high_score.0,
+            battle.wave,
+            battle.multiplier()
+        );
+    }
+    let message = match state.current() {
+        GameState::Playing => String::new(),
+        GameState::WaveOver => {
+            let ammo: u32 = battle.silos.iter().map(|silo| silo.ammo).sum();
+            format!(
+                "WAVE {} CLEARED\n\n{} cities and {} missiles left\nBonus {}",
+                battle.wave,
+                battle.cities_left(),
+                ammo,
+                battle.bonus()
+            )
+        }
+        GameState::Over => format!("THE END\n\nScore {}\n\nEnter: play again", battle.score),
+    };
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+fn wave_over(
+    battle: Res<Battle>,
+    mut pause: ResMut<WavePause>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    pause.0.reset();
+    if !battle.is_lost() {
+        audio.play(sounds.wave.clone());
+    }
+}
+
+/// Shows the bonus for a moment, then adds it and starts the next wave.
+fn wave_pause(
+    time: Res<Time>,
+    mut battle: ResMut<Battle>,
+    mut pause: ResMut<WavePause>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if pause.0.tick(time.delta()).finished() {
+        battle.next_wave();
+        state.set(GameState::Playing).unwrap();
+    }
+}
@@ -0,0 +1,1050 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use rand::{seq::SliceRandom, thread_rng};
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.0, 0.0, 0.0);
+const DOOR_COLOR: Color = Color::rgb(1.0, 0.7, 0.8);
+const EATEN_SPEED_FACTOR: f32 = 2.0;
+const FRIGHTENED_COLOR: Color = Color::rgb(0.15, 0.2, 0.9);
+const FRIGHTENED_FLASH_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const FRIGHTENED_SPEED_FACTOR: f32 = 0.5;
+const FRUIT_COLOR: Color = Color::rgb(1.0, 0.2, 0.3);
+const FRUIT_LIFETIME: f32 = 9.5;
+/// Fruit bonus per level; later levels keep using the last value.
+const FRUIT_SCORES: [u32; 8] = [100, 300, 500, 700, 1000, 2000, 3000, 5000];
+/// A fruit appears after this many pellets have been eaten on a level.
+const FRUIT_TRIGGERS: [u32; 2] = [70, 170];
+const GHOST_SPEED: f32 = 7.0;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const LEVEL_SPEEDUP: f32 = 0.05;
+const MAZE_LAYOUT: &str = include_str!("../assets/maze.txt");
+const PELLET_COLOR: Color = Color::rgb(1.0, 0.85, 0.7);
+const PLAYER_COLOR: Color = Color::rgb(1.0, 0.95, 0.1);
+const PLAYER_SPEED: f32 = 8.0;
+const STARTING_LIVES: u32 = 3;
+const TILE: f32 = 20.0;
+const WALL_COLOR: Color = Color::rgb(0.1, 0.15, 0.8);
+
+/// Alternating scatter and chase phases in seconds; the last chase phase lasts forever.
+const MODE_SCHEDULE: [(GhostMode, f32); 7] = [
+    (GhostMode::Scatter, 7.0),
+    (GhostMode::Chase, 20.0),
+    (GhostMode::Scatter, 7.0),
+    (GhostMode::Chase, 20.0),
+    (GhostMode::Scatter, 5.0),
+    (GhostMode::Chase, 20.0),
+    (GhostMode::Scatter, 5.0),
+];
+
+/// Anything moving through the maze tile by tile: `progress` is how far it is from `tile` towards the next one.
+#[derive(Component)]
+struct Actor {
+    direction: Direction,
+    progress: f32,
+    tile: IVec2,
+}
+
+impl Actor {
+    fn new(tile: IVec2) -> Self {
+        Self {
+            direction: Direction::None,
+            progress: 0.0,
+            tile,
+        }
+    }
+
+    fn position(&self) -> Vec2 {
+        self.tile.as_vec2() + self.direction.offset().as_vec2() * self.progress
+    }
+
+    fn reverse(&mut self, maze: &Maze) {
+        if self.direction != Direction::None {
+            self.tile = maze.wrap(self.tile + self.direction.offset());
+            self.direction = self.direction.opposite();
+            self.progress = 1.0 - self.progress;
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Direction {
+    None,
+    Left,
+    Up,
+    Right,
+    Down,
+}
+
+impl Direction {
+    /// Ghosts break ties between equally good directions in this order.
+    const ALL: [Direction; 4] = [Self::Up, Self::Left, Self::Down, Self::Right];
+
+    fn offset(self) -> IVec2 {
+        match self {
+            Self::None => IVec2::ZERO,
+            Self::Left => IVec2::new(-1, 0),
+            Self::Up => IVec2::new(0, -1),
+            Self::Right => IVec2::new(1, 0),
+            Self::Down => IVec2::new(0, 1),
+        }
+    }
+
+    fn opposite(self) -> Self {
+        match self {
+            Self::None => Self::None,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+            Self::Up => Self::Down,
+        }
+    }
+}
+
+struct FrightenedTimer {
+    combo: u32,
+    timer: Timer,
+}
+
+impl FrightenedTimer {
+    fn idle() -> Self {
+        Self {
+            combo: 0,
+            timer: Timer::from_seconds(0.0, false),
+        }
+    }
+
+    fn active(&self) -> bool {
+        self.timer.elapsed() < self.timer.duration()
+    }
+}
+
+#[derive(Component)]
+struct Fruit(Timer);
+
+#[derive(Default)]
+struct Game {
+    high_score: u32,
+    level: u32,
+    lives: u32,
+    pellets_eaten: u32,
+    score: u32,
+}
+
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct Ghost {
+    mode: GhostMode,
+    personality: Personality,
+    /// Seconds into a life after which the ghost leaves the house.
+    release_after: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum GhostMode {
+    InHouse,
+    Scatter,
+    Chase,
+    Frightened,
+    Eaten,
+}
+
+#[derive(Component)]
+struct GhostBody;
+
+#[derive(Component)]
+struct HudText;
+
+/// Seconds since the current life (or level) started; drives releasing ghosts from the house.
+struct LifeClock(f32);
+
+struct Maze {
+    door_exit: IVec2,
+    fruit: IVec2,
+    ghosts: [IVec2; 4],
+    height: i32,
+    pellets: Vec<(IVec2, bool)>,
+    player: IVec2,
+    tiles: Vec<Tile>,
+    width: i32,
+}
+
+impl Maze {
+    fn parse(layout: &str) -> Self {
+        let lines: Vec<&str> = layout.lines().filter(|line| !line.is_empty()).collect();
+        let height = lines.len() as i32;
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+        let mut maze = Self {
+            door_exit: IVec2::ZERO,
+            fruit: IVec2::ZERO,
+            ghosts: [IVec2::ZERO; 4],
+            height,
+            pellets: Vec::new(),
+            player: IVec2::ZERO,
+            tiles: vec![Tile::Open; (width * height) as usize],
+            width,
+        };
+        for (y, line) in lines.iter().enumerate() {
+            for (x, symbol) in line.chars().enumerate() {
+                let tile = IVec2::new(x as i32, y as i32);
+                let index = (y as i32 * width + x as i32) as usize;
+                match symbol {
+                    '#' => maze.tiles[index] = Tile::Wall,
+                    '-' => maze.tiles[index] = Tile::Door,
+                    '.' => maze.pellets.push((tile, false)),
+                    'o' => maze.pellets.push((tile, true)),
+                    'S' => maze.player = tile,
+                    'F' => maze.fruit = tile,
+                    'B' => {
+                        maze.ghosts[0] = tile;
+                        maze.door_exit = tile;
+                    }
+                    'P' => maze.ghosts[1] = tile,
+                    'I' => maze.ghosts[2] = tile,
+                    'C' => maze.ghosts[3] = tile,
+                    _ => {}
+                }
+            }
+        }
+        maze
+    }
+
+    fn tile(&self, position: IVec2) -> Tile {
+        if position.y < 0 || position.y >= self.height {
+            return Tile::Wall;
+        }
+        let position = self.wrap(position);
+        self.tiles[(position.y * self.width + position.x) as usize]
+    }
+
+    fn passable(&self, position: IVec2, through_door: bool) -> bool {
+        match self.tile(position) {
+            Tile::Open => true,
+            Tile::Door => through_door,
+            Tile::Wall => false,
+        }
+    }
+
+    /// Tunnels: leaving the maze on one side enters it on the other.
+    fn wrap(&self, position: IVec2) -> IVec2 {
+        IVec2::new(position.x.rem_euclid(self.width), position.y)
+    }
+
+    fn to_world(&self, position: Vec2) -> Vec3 {
+        Vec3::new(
+            (position.x - self.width as f32 / 2.0 + 0.5) * TILE,
+            (self.height as f32 / 2.0 - position.y - 0.5) * TILE - HUD_HEIGHT / 2.0,
+            0.0,
+        )
+    }
+}
+
+#[derive(Component)]
+struct Pellet {
+    power: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Personality {
+    Blinky,
+    Pinky,
+    Inky,
+    Clyde,
+}
+
+impl Personality {
+    const ALL: [Personality; 4] = [Self::Blinky, Self::Pinky, Self::Inky, Self::Clyde];
+
+    /// The tile a ghost at `tile` heads for while chasing a player at `player` moving `facing`,
+    /// with Blinky at `blinky`.
+    fn chase_target(
+        self,
+        tile: IVec2,
+        player: IVec2,
+        facing: Direction,
+        blinky: IVec2,
+        maze: &Maze,
+    ) -> IVec2 {
+        match self {
+            Self::Blinky => player,
+            Self::Pinky => player + facing.offset() * 4,
+            Self::Inky => {
+                let pivot = player + facing.offset() * 2;
+                pivot * 2 - blinky
+            }
+            Self::Clyde => {
+                if (player - tile).as_vec2().length() > 8.0 {
+                    player
+                } else {
+                    self.scatter_corner(maze)
+                }
+            }
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Self::Blinky => Color::rgb(1.0, 0.0, 0.0),
+            Self::Pinky => Color::rgb(1.0, 0.7, 0.85),
+            Self::Inky => Color::rgb(0.0, 1.0, 1.0),
+            Self::Clyde => Color::rgb(1.0, 0.7, 0.3),
+        }
+    }
+
+    fn scatter_corner(self, maze: &Maze) -> IVec2 {
+        match self {
+            Self::Blinky => IVec2::new(maze.width - 3, -3),
+            Self::Pinky => IVec2::new(2, -3),
+            Self::Inky => IVec2::new(maze.width - 1, maze.height),
+            Self::Clyde => IVec2::new(0, maze.height),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Player {
+    wanted: Direction,
+}
+
+struct PlayerCaughtEvent;
+
+/// Position in `MODE_SCHEDULE`; paused while the ghosts are frightened.
+struct Schedule {
+    elapsed: f32,
+    index: usize,
+}
+
+impl Schedule {
+    fn mode(&self) -> GhostMode {
+        MODE_SCHEDULE
+            .get(self.index)
+            .map_or(GhostMode::Chase, |(mode, _)| *mode)
+    }
+}
+
+struct Sounds {
+    death: Handle<AudioSource>,
+    eat_ghost: Handle<AudioSource>,
+    fruit: Handle<AudioSource>,
+    pellet: Handle<AudioSource>,
+    power: Handle<AudioSource>,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+enum Step {
+    Modes,
+    Movement,
+    Collision,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Tile {
+    Door,
+    Open,
+    Wall,
+}
+
+pub struct PacmanPlugin;
+
+impl Plugin for PacmanPlugin {
+    fn build(&self, app: &mut App) {
+        let maze = Maze::parse(MAZE_LAYOUT);
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(maze)
+            .insert_resource(Game::default())
+            .insert_resource(LifeClock(0.0))
+            .insert_resource(Schedule {
+                elapsed: 0.0,
+                index: 0,
+            })
+            .insert_resource(FrightenedTimer::idle())
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_event::<PlayerCaughtEvent>()
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_input.before(Step::Movement))
+                    .with_system(mode_schedule.label(Step::Modes))
+                    .with_system(player_movement.label(Step::Movement).after(Step::Modes))
+                    .with_system(ghost_movement.label(Step::Movement).after(Step::Modes))
+                    .with_system(eating.label(Step::Collision).after(Step::Movement))
+                    .with_system(
+                        ghost_collisions
+                            .label(Step::Collision)
+                            .after(Step::Movement),
+                    )
+                    .with_system(fruit_expiry.after(Step::Collision))
+                    .with_system(player_caught.after(Step::Collision))
+                    .with_system(level_complete.after(Step::Collision))
+                    .with_system(ghost_colors.after(Step::Collision))
+                    .with_system(actor_translation.after(Step::Collision)),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(restart_input))
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    let maze = Maze::parse(MAZE_LAYOUT);
+    WindowDescriptor {
+        title: "Pac-Man".to_string(),
+        width: maze.width as f32 * TILE,
+        height: maze.height as f32 * TILE + HUD_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn actor_translation(maze: Res<Maze>, mut actors: Query<(&Actor, &mut Transform)>) {
+    for (actor, mut transform) in actors.iter_mut() {
+        let z = transform.translation.z;
+        transform.translation = maze.to_world(actor.position());
+        transform.translation.z = z;
+    }
+}
+
+fn actors_reset(
+    maze: &Maze,
+    players: &mut Query<(&mut Actor, &mut Player), Without<Ghost>>,
+    ghosts: &mut Query<(&mut Actor, &mut Ghost), Without<Player>>,
+) {
+    for (mut actor, mut player) in players.iter_mut() {
+        *actor = Actor::new(maze.player);
+        player.wanted = Direction::None;
+    }
+    for (mut actor, mut ghost) in ghosts.iter_mut() {
+        let index = Personality::ALL
+            .iter()
+            .position(|personality| *personality == ghost.personality)
+            .unwrap_or(0);
+        *actor = Actor::new(maze.ghosts[index]);
+        ghost.mode = GhostMode::InHouse;
+    }
+}
+
+/// Moves an actor along its direction, calling `choose` every time it reaches the center of a tile.
+fn advance(
+    actor: &mut Actor,
+    maze: &Maze,
+    distance: f32,
+    mut choose: impl FnMut(&Actor) -> Direction,
+) {
+    if actor.direction == Direction::None {
+        actor.direction = choose(actor);
+        actor.progress = 0.0;
+        if actor.direction == Direction::None {
+            return;
+        }
+    }
+    actor.progress += distance;
+    while actor.progress >= 1.0 {
+        actor.tile = maze.wrap(actor.tile + actor.direction.offset());
+        actor.progress -= 1.0;
+        actor.direction = choose(actor);
+        if actor.direction == Direction::None {
+            actor.progress = 0.0;
+        }
+    }
+}
+
+fn eating(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut frightened: ResMut<FrightenedTimer>,
+    maze: Res<Maze>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    players: Query<&Actor, With<Player>>,
+    pellets: Query<(Entity, &Pellet, &Transform)>,
+    fruits: Query<(Entity, &Transform), With<Fruit>>,
+    mut ghosts: Query<(&mut Ghost, &mut Actor), Without<Player>>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player,
+        None => return,
+    };
+    let position = maze.to_world(player.position()).truncate();
+    for (entity, pellet, transform) in pellets.iter() {
+        if transform.translation.truncate().distance(position) >= TILE * 0.5 {
+            continue;
+        }
+        commands.entity(entity).despawn();
+        game.pellets_eaten += 1;
+        if pellet.power {
+            game.score += 50;
+            audio.play(sounds.power.clone());
+            let seconds = (6.0 - game.level as f32).max(1.0);
+            frightened.timer = Timer::from_seconds(seconds, false);
+            frightened.combo = 0;
+            for (mut ghost, mut actor) in ghosts.iter_mut() {
+                if matches!(ghost.mode, GhostMode::Scatter | GhostMode::Chase) {
+                    ghost.mode = GhostMode::Frightened;
+                    actor.reverse(&maze);
+                }
+            }
+        } else {
+            game.score += 10;
+            audio.play(sounds.pellet.clone());
+        }
+        if FRUIT_TRIGGERS.contains(&game.pellets_eaten) {
+            fruit_spawn(&mut commands, &maze);
+        }
+    }
+    for (entity, transform) in fruits.iter() {
+        if transform.translation.truncate().distance(position) < TILE * 0.5 {
+            commands.entity(entity).despawn();
+            let index = (game.level as usize)
+                .saturating_sub(1)
+                .min(FRUIT_SCORES.len() - 1);
+            game.score += FRUIT_SCORES[index];
+            audio.play(sounds.fruit.clone());
+        }
+    }
+    game.high_score = game.high_score.max(game.score);
+}
+
+fn fruit_expiry(mut commands: Commands, time: Res<Time>, mut fruits: Query<(Entity, &mut Fruit)>) {
+    for (entity, mut fruit) in fruits.iter_mut() {
+        if fruit.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn fruit_spawn(commands: &mut Commands, maze: &Maze) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: FRUIT_COLOR,
+                custom_size: Some(Vec2::splat(TILE * 0.7)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(maze.to_world(maze.fruit.as_vec2()) + Vec3::Z),
+            ..Default::default()
+        })
+        .insert(Fruit(Timer::from_seconds(FRUIT_LIFETIME, false)));
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!("GAME OVER\nScore {}\nPress Enter", game.score),
+            36.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(GameOverText);
+}
+
+fn ghost_collisions(
+    mut game: ResMut<Game>,
+    mut frightened: ResMut<FrightenedTimer>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut caught_writer: EventWriter<PlayerCaughtEvent>,
+    players: Query<&Actor, With<Player>>,
+    mut ghosts: Query<(&Actor, &mut Ghost), Without<Player>>,
+) {
+    let player = match players.iter().next() {
+        Some(player) => player.position(),
+        None => return,
+    };
+    for (actor, mut ghost) in ghosts.iter_mut() {
+        if actor.position().distance(player) > 0.6 {
+            continue;
+        }
+        match ghost.mode {
+            GhostMode::Frightened => {
+                ghost.mode = GhostMode::Eaten;
+                game.score += 200 * 2_u32.pow(frightened.combo.min(3));
+                frightened.combo += 1;
+                audio.play(sounds.eat_ghost.clone());
+            }
+            GhostMode::Scatter | GhostMode::Chase => caught_writer.send(PlayerCaughtEvent),
+            GhostMode::InHouse | GhostMode::Eaten => {}
+        }
+    }
+}
+
+fn ghost_colors(
+    frightened: Res<FrightenedTimer>,
+    ghosts: Query<(&Ghost, &Children)>,
+    mut bodies: Query<&mut Sprite, With<GhostBody>>,
+) {
+    let remaining = frightened.timer.duration().as_secs_f32() - frightened.timer.elapsed_secs();
+    let flashing = remaining < 2.0 && (remaining * 4.0) as u32 % 2 == 0;
+    for (ghost, children) in ghosts.iter() {
+        let color = match ghost.mode {
+            GhostMode::Frightened if flashing => FRIGHTENED_FLASH_COLOR,
+            GhostMode::Frightened => FRIGHTENED_COLOR,
+            GhostMode::Eaten => Color::NONE,
+            _ => ghost.personality.color(),
+        };
+        for child in children.iter() {
+            if let Ok(mut sprite) = bodies.get_mut(*child) {
+                sprite.color = color;
+            }
+        }
+    }
+}
+
+fn ghost_movement(
+    time: Res<Time>,
+    maze: Res<Maze>,
+    game: Res<Game>,
+    life_clock: Res<LifeClock>,
+    schedule: Res<Schedule>,
+    players: Query<&Actor, (With<Player>, Without<Ghost>)>,
+    mut ghosts: Query<(&mut Actor, &mut Ghost), Without<Player>>,
+) {
+    let (player_tile, player_direction) = match players.iter().next() {
+        Some(player) => (player.tile, player.direction),
+        None => return,
+    };
+    let blinky = ghosts
+        .iter()
+        .find(|(_, ghost)| ghost.personality == Personality::Blinky)
+        .map_or(player_tile, |(actor, _)| actor.tile);
+    let level_speed = GHOST_SPEED * (1.0 + LEVEL_SPEEDUP * game.level as f32);
+    for (mut actor, mut ghost) in ghosts.iter_mut() {
+        if ghost.mode == GhostMode::InHouse {
+            if life_clock.0 >= ghost.release_after {
+                *actor = Actor::new(maze.door_exit);
+                actor.direction = Direction::Left;
+                ghost.mode = schedule.mode();
+            }
+            continue;
+        }
+        let speed = match ghost.mode {
+            GhostMode::Frightened => level_speed * FRIGHTENED_SPEED_FACTOR,
+            GhostMode::Eaten => level_speed * EATEN_SPEED_FACTOR,
+            _ => level_speed,
+        };
+        let target = match ghost.mode {
+            GhostMode::Scatter => ghost.personality.scatter_corner(&maze),
+            GhostMode::Eaten => maze.door_exit,
+            _ => ghost.personality.chase_target(
+                actor.tile,
+                player_tile,
+                player_direction,
+                blinky,
+                &maze,
+            ),
+        };
+        let mode = ghost.mode;
+        advance(&mut actor, &maze, speed * time.delta_seconds(), |actor| {
+            let options: Vec<Direction> = Direction::ALL
+                .iter()
+                .copied()
+                .filter(|direction| {
+                    *direction != actor.direction.opposite()
+                        && maze.passable(actor.tile + direction.offset(), false)
+                })
+                .collect();
+            if options.is_empty() {
+                return actor.direction.opposite();
+            }
+            if mode == GhostMode::Frightened {
+                return *options.choose(&mut thread_rng()).unwrap();
+            }
+            // Classic ghost logic: take the step that ends closest to the target tile.
+            *options
+                .iter()
+                .min_by_key(|direction| {
+                    let next = actor.tile + direction.offset();
+                    (next - target).x.pow(2) + (next - target).y.pow(2)
+                })
+                .unwrap()
+        });
+        if ghost.mode == GhostMode::Eaten && actor.tile == maze.door_exit {
+            ghost.mode = schedule.mode();
+        }
+    }
+}
+
+fn hud_update(game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if game.is_changed() {
+        for mut text in texts.iter_mut() {
+            text.sections[0].value = format!(
+                "SCORE {:06}  HIGH {:06}  LIVES {}  LEVEL {}",
+                game.score, game.high_score, game.lives, game.level
+            );
+        }
+    }
+}
+
+fn level_complete(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut frightened: ResMut<FrightenedTimer>,
+    mut life_clock: ResMut<LifeClock>,
+    mut schedule: ResMut<Schedule>,
+    maze: Res<Maze>,
+    pellets: Query<Entity, With<Pellet>>,
+    fruits: Query<Entity, With<Fruit>>,
+    mut players: Query<(&mut Actor, &mut Player), Without<Ghost>>,
+    mut ghosts: Query<(&mut Actor, &mut Ghost), Without<Player>>,
+) {
+    // Pellets eaten this frame are only despawned at the end of the stage, so count what is left.
+    if !pellets.is_empty() || game.pellets_eaten == 0 {
+        return;
+    }
+    game.level += 1;
+    game.pellets_eaten = 0;
+    life_clock.0 = 0.0;
+    *frightened = FrightenedTimer::idle();
+    *schedule = Schedule {
+        elapsed: 0.0,
+        index: 0,
+    };
+    for entity in fruits.iter() {
+        commands.entity(entity).despawn();
+    }
+    pellets_spawn(&mut commands, &maze);
+    actors_reset(&maze, &mut players, &mut ghosts);
+}
+
+fn mode_schedule(
+    time: Res<Time>,
+    maze: Res<Maze>,
+    mut life_clock: ResMut<LifeClock>,
+    mut schedule: ResMut<Schedule>,
+    mut frightened: ResMut<FrightenedTimer>,
+    mut ghosts: Query<(&mut Ghost, &mut Actor)>,
+) {
+    life_clock.0 += time.delta_seconds();
+    if frightened.active() {
+        if frightened.timer.tick(time.delta()).just_finished() {
+            for (mut ghost, _) in ghosts.iter_mut() {
+                if ghost.mode == GhostMode::Frightened {
+                    ghost.mode = schedule.mode();
+                }
+            }
+        }
+        return;
+    }
+    let duration = match MODE_SCHEDULE.get(schedule.index) {
+        Some((_, duration)) => *duration,
+        None => return,
+    };
+    schedule.elapsed += time.delta_seconds();
+    if schedule.elapsed >= duration {
+        schedule.elapsed = 0.0;
+        schedule.index += 1;
+        // Every switch between scatter and chase makes the ghosts turn around.
+        for (mut ghost, mut actor) in ghosts.iter_mut() {
+            if matches!(ghost.mode, GhostMode::Scatter | GhostMode::Chase) {
+                ghost.mode = schedule.mode();
+                actor.reverse(&maze);
+            }
+        }
+    }
+}
+
+fn new_game(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut frightened: ResMut<FrightenedTimer>,
+    mut life_clock: ResMut<LifeClock>,
+    mut schedule: ResMut<Schedule>,
+    maze: Res<Maze>,
+    leftovers: Query<Entity, Or<(With<Actor>, With<Pellet>, With<Fruit>, With<GameOverText>)>>,
+) {
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    game.level = 1;
+    game.lives = STARTING_LIVES;
+    game.pellets_eaten = 0;
+    game.score = 0;
+    life_clock.0 = 0.0;
+    *frightened = FrightenedTimer::idle();
+    *schedule = Schedule {
+        elapsed: 0.0,
+        index: 0,
+    };
+    pellets_spawn(&mut commands, &maze);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: PLAYER_COLOR,
+                custom_size: Some(Vec2::splat(TILE * 0.9)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, 3.0),
+            ..Default::default()
+        })
+        .insert(Actor::new(maze.player))
+        .insert(Player {
+            wanted: Direction::None,
+        });
+    for (index, personality) in Personality::ALL.iter().enumerate() {
+        commands
+            .spawn_bundle((
+                Transform::from_xyz(0.0, 0.0, 2.0),
+                GlobalTransform::identity(),
+            ))
+            .insert(Actor::new(maze.ghosts[index]))
+            .insert(Ghost {
+                mode: GhostMode::InHouse,
+                personality: *personality,
+                release_after: index as f32 * 4.0,
+            })
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: personality.color(),
+                            custom_size: Some(Vec2::splat(TILE * 0.9)),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    })
+                    .insert(GhostBody);
+                for side in [-1.0, 1.0] {
+                    parent.spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::WHITE,
+                            custom_size: Some(Vec2::splat(TILE * 0.25)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(side * TILE * 0.2, TILE * 0.15, 0.1),
+                        ..Default::default()
+                    });
+                }
+            });
+    }
+}
+
+fn pellets_spawn(commands: &mut Commands, maze: &Maze) {
+    for (tile, power) in maze.pellets.iter() {
+        let size = if *power { TILE * 0.6 } else { TILE * 0.2 };
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: PELLET_COLOR,
+                    custom_size: Some(Vec2::splat(size)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(maze.to_world(tile.as_vec2())),
+                ..Default::default()
+            })
+            .insert(Pellet { power: *power });
+    }
+}
+
+fn player_caught(
+    mut caught_reader: EventReader<PlayerCaughtEvent>,
+    mut game: ResMut<Game>,
+    mut frightened: ResMut<FrightenedTimer>,
+    mut life_clock: ResMut<LifeClock>,
+    mut state: ResMut<State<GameState>>,
+    maze: Res<Maze>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut players: Query<(&mut Actor, &mut Player), Without<Ghost>>,
+    mut ghosts: Query<(&mut Actor, &mut Ghost), Without<Player>>,
+) {
+    if caught_reader.iter().next().is_none() {
+        return;
+    }
+    audio.play(sounds.death.clone());
+    game.lives = game.lives.saturating_sub(1);
+    if game.lives == 0 {
+        state.set(GameState::GameOver).unwrap();
+        return;
+    }
+    life_clock.0 = 0.0;
+    *frightened = FrightenedTimer::idle();
+    actors_reset(&maze, &mut players, &mut ghosts);
+}
+
+fn player_input(keyboard_input: Res<Input<KeyCode>>, mut players: Query<&mut Player>) {
+    if let Some(mut player) = players.iter_mut().next() {
+        if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+            player.wanted = Direction::Left;
+        } else if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+            player.wanted = Direction::Right;
+        } else if keyboard_input.any_pressed([KeyCode::Up, KeyCode::W]) {
+            player.wanted = Direction::Up;
+        } else if keyboard_input.any_pressed([KeyCode::Down, KeyCode::S]) {
+            player.wanted = Direction::Down;
+        }
+    }
+}
+
+fn player_movement(
+    time: Res<Time>,
+    maze: Res<Maze>,
+    game: Res<Game>,
+    mut players: Query<(&mut Actor, &Player)>,
+) {
+    let speed = PLAYER_SPEED * (1.0 + LEVEL_SPEEDUP * game.level as f32);
+    for (mut actor, player) in players.iter_mut() {
+        if player.wanted == actor.direction.opposite() {
+            actor.reverse(&maze);
+        }
+        let wanted = player.wanted;
+        advance(&mut actor, &maze, speed * time.delta_seconds(), |actor| {
+            // The last pressed direction is remembered until the maze allows taking it.
+            if maze.passable(actor.tile + wanted.offset(), false) {
+                wanted
+            } else if maze.passable(actor.tile + actor.direction.offset(), false) {
+                actor.direction
+            } else {
+                Direction::None
+            }
+        });
+    }
+}
+
+fn restart_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    maze: Res<Maze>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    for y in 0..maze.height {
+        for x in 0..maze.width {
+            let tile = IVec2::new(x, y);
+            let (color, size) = match maze.tile(tile) {
+                Tile::Wall => (WALL_COLOR, Vec2::splat(TILE)),
+                Tile::Door => (DOOR_COLOR, Vec2::new(TILE, TILE * 0.2)),
+                Tile::Open => continue,
+            };
+            commands.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(size),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(maze.to_world(tile.as_vec2())),
+                ..Default::default()
+            });
+        }
+    }
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.insert_resource(Sounds {
+        death: audio_sources.add(
+            Tone::new(Waveform::Triangle, 800.0, 1.2)
+                .slide(100.0)
+                .fade()
+                .into(),
+        ),
+        eat_ghost: audio_sources.add(
+            Tone::new(Waveform::Square, 200.0, 0.3)
+                .slide(1200.0)
+                .volume(0.25)
+                .into(),
+        ),
+        fruit: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 880.0, 0.08),
+            Tone::new(Waveform::Triangle, 1320.0, 0.12),
+        ])),
+        pellet: audio_sources.add(
+            Tone::new(Waveform::Triangle, 500.0, 0.05)
+                .slide(700.0)
+                .volume(0.15)
+                .into(),
+        ),
+        power: audio_sources.add(
+            Tone::new(Waveform::Saw, 150.0, 0.4)
+                .slide(400.0)
+                .volume(0.25)
+                .into(),
+        ),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maze_parses_the_layout() {
+        let maze = Maze::parse(MAZE_LAYOUT);
+        assert_eq!((maze.width, maze.height), (28, 31));
+        assert_eq!(maze.player, IVec2::new(13, 23));
+        assert_eq!(maze.ghosts[0], maze.door_exit);
+        assert_eq!(maze.pellets.iter().filter(|(_, power)| *power).count(), 4);
+        assert!(maze.pellets.len() > FRUIT_TRIGGERS[1] as usize);
+        assert!(maze.tile(maze.player) == Tile::Open);
+        assert!(maze.tile(IVec2::new(13, 12)) == Tile::Door);
+        assert!(maze.passable(IVec2::new(13, 12), true));
+        assert!(!maze.passable(IVec2::new(13, 12), false));
+        assert!(!maze.passable(IVec2::new(0, 0), true));
+    }
+
+    #[test]
+    fn tunnels_wrap_around_and_the_outside_is_wall() {
+        let maze = Maze::parse(MAZE_LAYOUT);
+        assert_eq!(maze.wrap(IVec2::new(-1, 14)), IVec2::new(27, 14));
+        assert_eq!(maze.wrap(IVec2::new(28, 14)), IVec2::new(0, 14));
+        assert!(maze.passable(IVec2::new(-1, 14), false));
+        assert!(!maze.passable(IVec2::new(1, -1), false));
+    }
+
+    #[test]
+    fn ghosts_chase_their_own_targets() {
+        let maze = Maze::parse(MAZE_LAYOUT);
+        let player = IVec2::new(10, 20);
+        let blinky = IVec2::new(8, 18);
+        let target = |personality: Personality, tile: IVec2| {
+            personality.chase_target(tile, player, Direction::Right, blinky, &maze)
+        };
+        assert_eq!(target(Personality::Blinky, blinky), player);
+        assert_eq!(target(Personality::Pinky, blinky), IVec2::new(14, 20));
+        assert_eq!(target(Personality::Inky, blinky), IVec2::new(16, 22));
+        assert_eq!(target(Personality::Clyde, IVec2::new(20, 5)), player);
+        assert_eq!(
+            target(Personality::Clyde, IVec2::new(12, 20)),
+            Personality::Clyde.scatter_corner(&maze)
+        );
+    }
+
+    #[test]
+    fn schedule_ends_in_chase() {
+        let schedule = Schedule {
+            elapsed: 0.0,
+            index: 0,
+        };
+        assert_eq!(schedule.mode(), GhostMode::Scatter);
+        let schedule = Schedule {
+            elapsed: 0.0,
+            index: MODE_SCHEDULE.len(),
+        };
+        assert_eq!(schedule.mode(), GhostMode::Chase);
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+use pacman::PacmanPlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(pacman::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(PacmanPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,125 @@
+//! The player's marks on a puzzle.
+
+use crate::puzzle::{self, Puzzle};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mark {
+    Empty,
+    Filled,
+    /// Ruled out by the player.
+    Crossed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Checking {
+    /// Nothing is checked until every clue is met.
+    Off,
+    /// Filling a cell that is not part of the picture crosses it instead and counts a mistake.
+    Mistakes,
+}
+
+impl Checking {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Off => "No checking",
+            Self::Mistakes => "Mistakes shown",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Off => Self::Mistakes,
+            Self::Mistakes => Self::Off,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Grid {
+    pub checking: Checking,
+    marks: Vec<Mark>,
+    pub mistakes: u32,
+    pub puzzle: Puzzle,
+}
+
+impl Grid {
+    pub fn new(puzzle: Puzzle, checking: Checking) -> Self {
+        Self {
+            checking,
+            marks: vec![Mark::Empty; puzzle.width * puzzle.height],
+            mistakes: 0,
+            puzzle,
+        }
+    }
+
+    pub fn column_done(&self, x: usize) -> bool {
+        let marks = (0..self.puzzle.height).map(|y| self.mark(x, y) == Mark::Filled);
+        puzzle::clue(marks) == self.puzzle.column_clue(x)
+    }
+
+    /// Whether the filled cells meet every clue; another picture with the same clues counts too.
+    pub fn is_solved(&self) -> bool {
+        (0..self.puzzle.height).all(|y| self.row_done(y))
+            && (0..self.puzzle.width).all(|x| self.column_done(x))
+    }
+
+    pub fn mark(&self, x: usize, y: usize) -> Mark {
+        self.marks[y * self.puzzle.width + x]
+    }
+
+    pub fn row_done(&self, y: usize) -> bool {
+        let marks = (0..self.puzzle.width).map(|x| self.mark(x, y) == Mark::Filled);
+        puzzle::clue(marks) == self.puzzle.row_clue(y)
+    }
+
+    /// Marks a cell; returns false if checking turned a fill into a mistake.
+    pub fn set(&mut self, x: usize, y: usize, mark: Mark) -> bool {
+        let index = y * self.puzzle.width + x;
+        if mark == Mark::Filled
+            && self.checking == Checking::Mistakes
+            && !self.puzzle.is_filled(x, y)
+        {
+            self.marks[index] = Mark::Crossed;
+            self.mistakes += 1;
+            return false;
+        }
+        self.marks[index] = mark;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plus(checking: Checking) -> Grid {
+        Grid::new(Puzzle::parse("Plus\n.#.\n###\n.#.\n").unwrap(), checking)
+    }
+
+    #[test]
+    fn solved_once_the_clues_are_met() {
+        let mut grid = plus(Checking::Off);
+        for (x, y) in [(1, 0), (0, 1), (1, 1), (2, 1)] {
+            grid.set(x, y, Mark::Filled);
+        }
+        assert!(grid.row_done(1));
+        assert!(!grid.column_done(1));
+        assert!(!grid.is_solved());
+        grid.set(0, 0, Mark::Crossed);
+        grid.set(1, 2, Mark::Filled);
+        assert!(grid.is_solved(), "crosses do not matter");
+    }
+
+    #[test]
+    fn checking_turns_wrong_fills_into_crosses() {
+        let mut grid = plus(Checking::Mistakes);
+        assert!(!grid.set(0, 0, Mark::Filled));
+        assert_eq!(grid.mark(0, 0), Mark::Crossed);
+        assert_eq!(grid.mistakes, 1);
+        assert!(grid.set(1, 0, Mark::Filled));
+
+        let mut grid = plus(Checking::Off);
+        assert!(grid.set(0, 0, Mark::Filled));
+        assert_eq!(grid.mistakes, 0);
+    }
+}
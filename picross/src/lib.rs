@@ -0,0 +1,985 @@
+use bevy::app::Events;
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use grid::{Checking, Grid, Mark};
+use puzzle::Puzzle;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::f32::consts::PI;
+use std::fs;
+use std::path::PathBuf;
+
+pub mod grid;
+pub mod puzzle;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.14, 0.15, 0.18);
+/// Cells alternate between two shades in blocks of five, which makes counting easier.
+const BLOCK: usize = 5;
+/// Size of the area the clues and the grid are fitted into.
+const BOARD_HEIGHT: f32 = 660.0;
+const BOARD_WIDTH: f32 = 720.0;
+const CELL_COLORS: [Color; 2] = [Color::rgb(0.93, 0.92, 0.88), Color::rgb(0.85, 0.85, 0.82)];
+const CLUE_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const CLUE_DONE_COLOR: Color = Color::rgb(0.45, 0.47, 0.52);
+/// Space taken by one number of a clue, in cells.
+const CLUE_SLOT: f32 = 0.6;
+const CROSS_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+const CURSOR_COLOR: Color = Color::rgba(1.0, 0.75, 0.2, 0.45);
+/// Sizes the editor cycles through.
+const EDITOR_SIZES: [(usize, usize); 4] = [(5, 5), (10, 10), (15, 15), (20, 15)];
+const EXPORT_DIRECTORY: &str = "puzzles";
+const FILL_COLOR: Color = Color::rgb(0.16, 0.2, 0.3);
+const FOOTER_HEIGHT: f32 = 30.0;
+const GAME: &str = "picross";
+const HUD_HEIGHT: f32 = 40.0;
+const MAX_CELL: f32 = 56.0;
+/// Puzzles listed in the menu at once.
+const MENU_ROWS: usize = 12;
+const MISTAKE_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+/// Seconds a wrongly filled cell flashes.
+const MISTAKE_FLASH: f32 = 0.4;
+const NAME_LENGTH: usize = 20;
+const NOTICE_SECONDS: f32 = 4.0;
+const PUZZLES: [&str; 7] = [
+    include_str!("../assets/puzzles/1-smile.txt"),
+    include_str!("../assets/puzzles/2-arrow.txt"),
+    include_str!("../assets/puzzles/3-heart.txt"),
+    include_str!("../assets/puzzles/4-cat.txt"),
+    include_str!("../assets/puzzles/5-house.txt"),
+    include_str!("../assets/puzzles/6-tree.txt"),
+    include_str!("../assets/puzzles/7-mushroom.txt"),
+];
+const RECORDS_FILE: &str = "records";
+/// Seconds each cell of the solved picture takes to pop in.
+const REVEAL_POP: f32 = 0.35;
+const REVEAL_COLOR: Color = Color::rgb(0.95, 0.55, 0.2);
+/// Delay between one diagonal of the solved picture and the next.
+const REVEAL_WAVE: f32 = 0.05;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+
+pub const WINDOW_HEIGHT: f32 = BOARD_HEIGHT + HUD_HEIGHT + FOOTER_HEIGHT + 30.0;
+pub const WINDOW_WIDTH: f32 = BOARD_WIDTH + 40.0;
+
+/// Everything drawn for the grid that is shown, despawned with it.
+#[derive(Component)]
+struct BoardPart;
+
+#[derive(Component)]
+struct CellSprite {
+    x: usize,
+    y: usize,
+}
+
+/// One number of a clue; slot 0 is the one next to the grid.
+#[derive(Component)]
+struct Clue {
+    line: Line,
+    slot: usize,
+}
+
+#[derive(Component)]
+struct CrossMark;
+
+#[derive(Component)]
+struct Cursor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    /// The picture is revealed.
+    Solved,
+    Editor,
+}
+
+#[derive(Component)]
+struct FooterText;
+
+#[derive(Component)]
+struct HudText;
+
+/// Where the grid is drawn.
+#[derive(Clone, Copy, Default)]
+struct Layout {
+    cell: f32,
+    /// Top left corner of the grid.
+    corner: Vec2,
+}
+
+impl Layout {
+    fn new(width: usize, height: usize) -> Self {
+        let clue_columns = (width as f32 / 2.0).ceil() * CLUE_SLOT;
+        let clue_rows = (height as f32 / 2.0).ceil() * CLUE_SLOT;
+        let cell = (BOARD_WIDTH / (width as f32 + clue_columns))
+            .min(BOARD_HEIGHT / (height as f32 + clue_rows))
+            .min(MAX_CELL);
+        let size = Vec2::new(width as f32 + clue_columns, height as f32 + clue_rows) * cell;
+        let center = Vec2::new(0.0, (FOOTER_HEIGHT - HUD_HEIGHT) / 2.0);
+        Self {
+            cell,
+            corner: center
+                + Vec2::new(
+                    -size.x / 2.0 + clue_columns * cell,
+                    size.y / 2.0 - clue_rows * cell,
+                ),
+        }
+    }
+
+    fn cell_at(&self, position: Vec2, width: usize, height: usize) -> Option<(usize, usize)> {
+        let offset = (position - self.corner) / self.cell;
+        if offset.x < 0.0 || offset.y > 0.0 {
+            return None;
+        }
+        let (x, y) = (offset.x as usize, -offset.y as usize);
+        if x < width && y < height {
+            Some((x, y))
+        } else {
+            None
+        }
+    }
+
+    fn cell_center(&self, x: usize, y: usize) -> Vec2 {
+        self.corner + Vec2::new(x as f32 + 0.5, -(y as f32) - 0.5) * self.cell
+    }
+}
+
+/// The bundled puzzles followed by the ones made with the editor.
+struct Library(Vec<Puzzle>);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Line {
+    Row(usize),
+    Column(usize),
+}
+
+struct Menu {
+    checking: Checking,
+    index: usize,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+/// A message in the footer that disappears after a while.
+struct Notice {
+    text: String,
+    timer: Timer,
+}
+
+/// The fastest time for each puzzle by name, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records(HashMap<String, f32>);
+
+/// Seconds since the puzzle was solved.
+struct Reveal(f32);
+
+struct Session {
+    cursor: (usize, usize),
+    editing: bool,
+    elapsed: f32,
+    grid: Grid,
+    /// A wrongly filled cell and how much longer it flashes.
+    mistake: Option<((usize, usize), f32)>,
+}
+
+impl Session {
+    fn new(grid: Grid, editing: bool) -> Self {
+        Self {
+            cursor: (0, 0),
+            editing,
+            elapsed: 0.0,
+            grid,
+            mistake: None,
+        }
+    }
+
+    /// Marks a cell; in the editor a filled cell becomes part of the picture.
+    fn paint(&mut self, (x, y): (usize, usize), mark: Mark) -> bool {
+        if self.editing {
+            self.grid.puzzle.set(x, y, mark == Mark::Filled);
+        }
+        let correct = self.grid.set(x, y, mark);
+        if !correct {
+            self.mistake = Some(((x, y), MISTAKE_FLASH));
+        }
+        correct
+    }
+}
+
+#[derive(Component)]
+struct SolvedText;
+
+struct Sounds {
+    cross: Handle<AudioSource>,
+    export: Handle<AudioSource>,
+    fill: Handle<AudioSource>,
+    mistake: Handle<AudioSource>,
+    solved: Handle<AudioSource>,
+}
+
+/// A drag that marks every cell it passes over that had the same mark as the first one.
+#[derive(Clone, Copy)]
+struct Stroke {
+    from: Mark,
+    to: Mark,
+}
+
+pub struct PicrossPlugin;
+
+impl Plugin for PicrossPlugin {
+    fn build(&self, app: &mut App) {
+        let first = Puzzle::parse(PUZZLES[0]).expect("bundled puzzles are valid");
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Library(Vec::new()))
+            .insert_resource(Layout::default())
+            .insert_resource(Menu {
+                checking: Checking::Mistakes,
+                index: 0,
+            })
+            .insert_resource(Notice {
+                text: String::new(),
+                timer: Timer::from_seconds(0.0, false),
+            })
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .insert_resource(Reveal(0.0))
+            .insert_resource(Session::new(Grid::new(first, Checking::Off), false))
+            .insert_resource(None::<Stroke>)
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(despawn_all::<BoardPart>)
+                    .with_system(despawn_all::<SolvedText>)
+                    .with_system(library_load),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu).with_system(despawn_all::<MenuText>),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(board_input)
+                    .with_system(board_visuals)
+                    .with_system(puzzle_clock),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Solved).with_system(solved))
+            .add_system_set(
+                SystemSet::on_update(GameState::Solved)
+                    .with_system(reveal)
+                    .with_system(solved_input),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(board_input)
+                    .with_system(board_visuals)
+                    .with_system(editor_input),
+            )
+            .add_system(hud_update)
+            .add_system(notice_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Picross".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Left click fills, right click crosses (erases in the editor), dragging carries on with the
+/// same mark. Arrows move the cursor, Z or Space fills and X crosses.
+fn board_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    layout: Res<Layout>,
+    mut session: ResMut<Session>,
+    mut stroke: ResMut<Option<Stroke>>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let (width, height) = (session.grid.puzzle.width, session.grid.puzzle.height);
+    let hovered = game_hud::cursor_world_position(&windows)
+        .and_then(|cursor| layout.cell_at(cursor, width, height));
+    if let Some(cell) = hovered {
+        if session.cursor != cell {
+            session.cursor = cell;
+        }
+    }
+    if !session.editing {
+        let (x, y) = session.cursor;
+        if keyboard_input.just_pressed(KeyCode::Left) {
+            session.cursor = ((x + width - 1) % width, y);
+        } else if keyboard_input.just_pressed(KeyCode::Right) {
+            session.cursor = ((x + 1) % width, y);
+        } else if keyboard_input.just_pressed(KeyCode::Up) {
+            session.cursor = (x, (y + height - 1) % height);
+        } else if keyboard_input.just_pressed(KeyCode::Down) {
+            session.cursor = (x, (y + 1) % height);
+        }
+    }
+
+    let (x, y) = session.cursor;
+    let current = session.grid.mark(x, y);
+    let toggle = |mark: Mark| if current == mark { Mark::Empty } else { mark };
+    let cross = if session.editing {
+        Mark::Empty
+    } else {
+        toggle(Mark::Crossed)
+    };
+    let keys = !session.editing;
+    let started = if (hovered.is_some() && mouse_input.just_pressed(MouseButton::Left))
+        || (keys && keyboard_input.any_just_pressed([KeyCode::Z, KeyCode::Space]))
+    {
+        Some(toggle(Mark::Filled))
+    } else if (hovered.is_some() && mouse_input.just_pressed(MouseButton::Right))
+        || (keys && keyboard_input.just_pressed(KeyCode::X))
+    {
+        Some(cross)
+    } else {
+        None
+    };
+    if let Some(to) = started {
+        *stroke = Some(Stroke { from: current, to });
+    }
+    if !mouse_input.any_pressed([MouseButton::Left, MouseButton::Right]) && started.is_none() {
+        *stroke = None;
+    }
+
+    let to = match *stroke {
+        Some(stroke) if current == stroke.from && current != stroke.to => stroke.to,
+        _ => return,
+    };
+    if started.is_none() && hovered.is_none() {
+        return;
+    }
+    if session.paint((x, y), to) {
+        let sound = if to == Mark::Crossed {
+            &sounds.cross
+        } else {
+            &sounds.fill
+        };
+        audio.play(sound.clone());
+    } else {
+        audio.play(sounds.mistake.clone());
+    }
+    if !session.editing && session.grid.is_solved() {
+        audio.play(sounds.solved.clone());
+        state.set(GameState::Solved).unwrap();
+    }
+}
+
+/// Colors the cells and clues after the marks, and flashes wrongly filled cells.
+fn board_visuals(
+    time: Res<Time>,
+    layout: Res<Layout>,
+    mut session: ResMut<Session>,
+    mut cells: Query<(&CellSprite, &mut Sprite, &Children)>,
+    mut crosses: Query<&mut Visibility, With<CrossMark>>,
+    mut clues: Query<(&Clue, &mut Text)>,
+    mut cursors: Query<&mut Transform, With<Cursor>>,
+) {
+    if let Some((cell, left)) = session.mistake {
+        let left = left - time.delta_seconds();
+        session.mistake = if left > 0.0 { Some((cell, left)) } else { None };
+    }
+    let grid = &session.grid;
+    for (cell, mut sprite, children) in cells.iter_mut() {
+        let mark = grid.mark(cell.x, cell.y);
+        sprite.color = if session.mistake.map(|(mistake, _)| mistake) == Some((cell.x, cell.y)) {
+            MISTAKE_COLOR
+        } else if mark == Mark::Filled {
+            FILL_COLOR
+        } else {
+            cell_color(cell.x, cell.y)
+        };
+        sprite.custom_size = Some(Vec2::splat(layout.cell - 2.0));
+        for child in children.iter() {
+            if let Ok(mut visibility) = crosses.get_mut(*child) {
+                visibility.is_visible = mark == Mark::Crossed;
+            }
+        }
+    }
+    for (clue, mut text) in clues.iter_mut() {
+        let (numbers, done) = match clue.line {
+            Line::Row(y) => (grid.puzzle.row_clue(y), grid.row_done(y)),
+            Line::Column(x) => (grid.puzzle.column_clue(x), grid.column_done(x)),
+        };
+        let value = if numbers.is_empty() && clue.slot == 0 {
+            "0".to_string()
+        } else if clue.slot < numbers.len() {
+            numbers[numbers.len() - 1 - clue.slot].to_string()
+        } else {
+            String::new()
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+        text.sections[0].style.color = if done && !session.editing {
+            CLUE_DONE_COLOR
+        } else {
+            CLUE_COLOR
+        };
+    }
+    let (x, y) = session.cursor;
+    for mut transform in cursors.iter_mut() {
+        transform.translation = layout.cell_center(x, y).extend(3.0);
+    }
+}
+
+fn cell_color(x: usize, y: usize) -> Color {
+    CELL_COLORS[(x / BLOCK + y / BLOCK) % 2]
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Typing names the puzzle, Tab changes the size, Delete clears it and Enter exports it.
+fn editor_input(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    mut session: ResMut<Session>,
+    mut notice: ResMut<Notice>,
+    mut state: ResMut<State<GameState>>,
+    parts: Query<Entity, With<BoardPart>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    for event in characters.iter() {
+        let name = &mut session.grid.puzzle.name;
+        if (event.char.is_ascii_alphanumeric() || event.char == ' ' || event.char == '-')
+            && name.len() < NAME_LENGTH
+        {
+            name.push(event.char);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        session.grid.puzzle.name.pop();
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Return) {
+        notice.text = match export(&session.grid.puzzle) {
+            Ok(path) => {
+                audio.play(sounds.export.clone());
+                format!("Saved {}", path.display())
+            }
+            Err(error) => format!("Could not export: {}", error),
+        };
+        notice.timer = Timer::from_seconds(NOTICE_SECONDS, false);
+    } else if keyboard_input.any_just_pressed([KeyCode::Tab, KeyCode::Delete]) {
+        let puzzle = &session.grid.puzzle;
+        let mut size = (puzzle.width, puzzle.height);
+        if keyboard_input.just_pressed(KeyCode::Tab) {
+            let index = EDITOR_SIZES.iter().position(|editor| *editor == size);
+            size = EDITOR_SIZES[index.map_or(0, |index| (index + 1) % EDITOR_SIZES.len())];
+        }
+        let blank = Puzzle::blank(&puzzle.name, size.0, size.1);
+        *session = Session::new(Grid::new(blank, Checking::Off), true);
+        for entity in parts.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        spawn_board(&mut commands, &font, &session.grid.puzzle);
+    }
+}
+
+/// Writes the puzzle to `<data dir>/rust_games/picross/puzzles/<name>.txt`, where the menu
+/// picks it up.
+fn export(puzzle: &Puzzle) -> Result<PathBuf, Box<dyn Error>> {
+    if puzzle.name.trim().is_empty() {
+        return Err("type a name first".into());
+    }
+    if (0..puzzle.height).all(|y| puzzle.row_clue(y).is_empty()) {
+        return Err("draw a picture first".into());
+    }
+    let directory = game_persistence::directory(GAME)?.join(EXPORT_DIRECTORY);
+    fs::create_dir_all(&directory)?;
+    let file: String = puzzle
+        .name
+        .trim()
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    let path = directory.join(format!("{}.txt", file));
+    fs::write(&path, puzzle.to_text())?;
+    Ok(path)
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn hud_update(
+    session: Res<Session>,
+    state: Res<State<GameState>>,
+    mut huds: Query<&mut Text, (With<HudText>, Without<FooterText>)>,
+    mut footers: Query<&mut Text, (With<FooterText>, Without<HudText>)>,
+    notice: Res<Notice>,
+) {
+    if !session.is_changed() && !state.is_changed() && !notice.is_changed() {
+        return;
+    }
+    let puzzle = &session.grid.puzzle;
+    let (hud, footer) = match state.current() {
+        GameState::Menu => (
+            String::new(),
+            "Up/Down choose   Left/Right checking   Enter play   E editor".to_string(),
+        ),
+        GameState::Playing | GameState::Solved => {
+            let mistakes = match session.grid.checking {
+                Checking::Mistakes => format!("   MISTAKES {}", session.grid.mistakes),
+                Checking::Off => String::new(),
+            };
+            (
+                format!(
+                    "{}x{}   TIME {}{}",
+                    puzzle.width,
+                    puzzle.height,
+                    format_time(session.elapsed),
+                    mistakes
+                ),
+                "Click/Z fill   Right click/X cross   Drag to mark many   Esc menu".to_string(),
+            )
+        }
+        GameState::Editor => (
+            format!(
+                "EDITOR   NAME {}_   {}x{}   {}",
+                puzzle.name,
+                puzzle.width,
+                puzzle.height,
+                if puzzle.is_line_solvable() {
+                    "solvable without guessing"
+                } else {
+                    "NEEDS GUESSING"
+                }
+            ),
+            "Click draw   Right click erase   Type to name   Tab size   Del clear   Enter export"
+                .to_string(),
+        ),
+    };
+    for mut text in huds.iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    let footer = if notice.timer.finished() {
+        footer
+    } else {
+        notice.text.clone()
+    };
+    for mut text in footers.iter_mut() {
+        text.sections[0].value = footer.clone();
+    }
+}
+
+/// Reloads the puzzles, so ones exported from the editor show up in the menu.
+fn library_load(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    records: Res<Records>,
+    mut library: ResMut<Library>,
+    mut menu: ResMut<Menu>,
+) {
+    library.0 = PUZZLES
+        .iter()
+        .map(|text| Puzzle::parse(text).expect("bundled puzzles are valid"))
+        .collect();
+    match load_exported() {
+        Ok(puzzles) => library.0.extend(puzzles),
+        Err(error) => eprintln!("could not load exported puzzles: {}", error),
+    }
+    menu.index = menu.index.min(library.0.len() - 1);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&library, &menu, &records),
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn load_exported() -> Result<Vec<Puzzle>, Box<dyn Error>> {
+    let directory = game_persistence::directory(GAME)?.join(EXPORT_DIRECTORY);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("txt"))
+        .collect();
+    paths.sort();
+    let mut puzzles = Vec::new();
+    for path in paths {
+        match Puzzle::parse(&fs::read_to_string(&path)?) {
+            Ok(puzzle) => puzzles.push(puzzle),
+            Err(error) => eprintln!("skipping {}: {}", path.display(), error),
+        }
+    }
+    Ok(puzzles)
+}
+
+fn menu_input(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    keyboard_input: Res<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    mut characters: ResMut<Events<ReceivedCharacter>>,
+    library: Res<Library>,
+    records: Res<Records>,
+    mut menu: ResMut<Menu>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let last = library.0.len() - 1;
+    if navigation.just_pressed(Navigate::Up) {
+        menu.index = menu.index.saturating_sub(1);
+    } else if navigation.just_pressed(Navigate::Down) {
+        menu.index = (menu.index + 1).min(last);
+    } else if navigation.horizontal() != 0 {
+        menu.checking = menu.checking.toggled();
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        let puzzle = library.0[menu.index].clone();
+        *session = Session::new(Grid::new(puzzle, menu.checking), false);
+        spawn_board(&mut commands, &font, &session.grid.puzzle);
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else if keyboard_input.just_pressed(KeyCode::E) {
+        // The E would otherwise also be typed into the name.
+        characters.clear();
+        let blank = Puzzle::blank("", EDITOR_SIZES[1].0, EDITOR_SIZES[1].1);
+        *session = Session::new(Grid::new(blank, Checking::Off), true);
+        spawn_board(&mut commands, &font, &session.grid.puzzle);
+        state.set(GameState::Editor).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&library, &menu, &records);
+    }
+}
+
+fn menu_label(library: &Library, menu: &Menu, records: &Records) -> String {
+    let first = (menu.index + 1).saturating_sub(MENU_ROWS);
+    let puzzles = library
+        .0
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MENU_ROWS)
+        .map(|(index, puzzle)| {
+            let marker = if index == menu.index { ">" } else { " " };
+            let record = match records.0.get(&puzzle.name) {
+                Some(seconds) => format!("  best {}", format_time(*seconds)),
+                None => String::new(),
+            };
+            format!(
+                "{} {:<20} {:>2}x{:<2}{}",
+                marker, puzzle.name, puzzle.width, puzzle.height, record
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "PICROSS\n\n{}\n\nChecking  < {} >",
+        puzzles,
+        menu.checking.name()
+    )
+}
+
+fn notice_update(time: Res<Time>, mut notice: ResMut<Notice>) {
+    if notice.timer.finished() {
+        return;
+    }
+    notice.timer.tick(time.delta());
+    if notice.timer.just_finished() {
+        notice.text.clear();
+    }
+}
+
+fn puzzle_clock(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut session: ResMut<Session>,
+    mut state: ResMut<State<GameState>>,
+) {
+    session.elapsed += time.delta_seconds();
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Crosses and clues fade, the grid lines close and the picture pops in diagonal by diagonal.
+fn reveal(
+    time: Res<Time>,
+    layout: Res<Layout>,
+    session: Res<Session>,
+    mut reveal: ResMut<Reveal>,
+    mut cells: Query<(&CellSprite, &mut Sprite, &mut Transform)>,
+    mut crosses: Query<&mut Visibility, (With<CrossMark>, Without<Cursor>)>,
+    mut cursors: Query<&mut Visibility, (With<Cursor>, Without<CrossMark>)>,
+    mut clues: Query<&mut Text, (With<Clue>, Without<SolvedText>)>,
+    mut solved_texts: Query<&mut Text, (With<SolvedText>, Without<Clue>)>,
+) {
+    reveal.0 += time.delta_seconds();
+    for mut visibility in crosses.iter_mut().chain(cursors.iter_mut()) {
+        visibility.is_visible = false;
+    }
+    let grid = &session.grid;
+    let (width, height) = (grid.puzzle.width, grid.puzzle.height);
+    for (cell, mut sprite, mut transform) in cells.iter_mut() {
+        let delay = (cell.x + cell.y) as f32 * REVEAL_WAVE;
+        let progress = ((reveal.0 - delay) / REVEAL_POP).clamp(0.0, 1.0);
+        sprite.custom_size = Some(Vec2::splat(layout.cell - 2.0 + 2.0 * progress));
+        if grid.mark(cell.x, cell.y) == Mark::Filled {
+            sprite.color = mix(FILL_COLOR, REVEAL_COLOR, progress);
+            transform.scale = Vec3::splat(1.0 + 0.3 * (progress * PI).sin());
+        } else {
+            sprite.color = mix(cell_color(cell.x, cell.y), BACKGROUND_COLOR, progress);
+        }
+    }
+    let total = (width + height) as f32 * REVEAL_WAVE + REVEAL_POP;
+    let progress = (reveal.0 / total).min(1.0);
+    for mut text in clues.iter_mut() {
+        text.sections[0].style.color.set_a(1.0 - progress);
+    }
+    for mut text in solved_texts.iter_mut() {
+        text.sections[0].style.color.set_a(progress);
+    }
+}
+
+fn mix(from: Color, to: Color, amount: f32) -> Color {
+    let [r, g, b, a] = from.as_rgba_f32();
+    let [to_r, to_g, to_b, to_a] = to.as_rgba_f32();
+    Color::rgba(
+        r + (to_r - r) * amount,
+        g + (to_g - g) * amount,
+        b + (to_b - b) * amount,
+        a + (to_a - a) * amount,
+    )
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            14.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(FooterText);
+    commands.insert_resource(Sounds {
+        cross: audio_sources.add(Tone::new(Waveform::Noise, 800.0, 0.04).volume(0.1).into()),
+        export: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 660.0, 0.08).volume(0.3),
+            Tone::new(Waveform::Sine, 990.0, 0.12).volume(0.3),
+        ])),
+        fill: audio_sources.add(
+            Tone::new(Waveform::Triangle, 520.0, 0.05)
+                .volume(0.2)
+                .into(),
+        ),
+        mistake: audio_sources.add(
+            Tone::new(Waveform::Square, 150.0, 0.25)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        solved: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.5).fade(),
+        ])),
+    });
+}
+
+fn solved(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    session: Res<Session>,
+    mut records: ResMut<Records>,
+    mut reveal: ResMut<Reveal>,
+) {
+    reveal.0 = 0.0;
+    let name = session.grid.puzzle.name.clone();
+    let new_record = match records.0.get(&name) {
+        Some(record) => session.elapsed < *record,
+        None => true,
+    };
+    if new_record {
+        records.0.insert(name.clone(), session.elapsed);
+        if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, &*records) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+    let mistakes = match session.grid.checking {
+        Checking::Mistakes => format!(", {} mistakes", session.grid.mistakes),
+        Checking::Off => String::new(),
+    };
+    // Fades in while the picture is revealed.
+    let mut color = TEXT_COLOR;
+    color.set_a(0.0);
+    let text = game_hud::world_text(
+        &font,
+        format!(
+            "{}\nSolved in {}{}{}\nPress Enter",
+            name.to_uppercase(),
+            format_time(session.elapsed),
+            mistakes,
+            if new_record { "  NEW BEST" } else { "" }
+        ),
+        30.0,
+        color,
+        Vec3::new(0.0, -WINDOW_HEIGHT / 2.0 + FOOTER_HEIGHT + 60.0, 20.0),
+    );
+    commands.spawn_bundle(text).insert(SolvedText);
+}
+
+fn solved_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Escape]) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Spawns the cells, the clue numbers and the cursor for a puzzle and lays them out.
+fn spawn_board(commands: &mut Commands, font: &HudFont, puzzle: &Puzzle) {
+    let layout = Layout::new(puzzle.width, puzzle.height);
+    let cell = layout.cell;
+    for y in 0..puzzle.height {
+        for x in 0..puzzle.width {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: cell_color(x, y),
+                        custom_size: Some(Vec2::splat(cell - 2.0)),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(layout.cell_center(x, y).extend(1.0)),
+                    ..Default::default()
+                })
+                .insert(CellSprite { x, y })
+                .insert(BoardPart)
+                .with_children(|parent| {
+                    for angle in [PI / 4.0, -PI / 4.0] {
+                        parent
+                            .spawn_bundle(SpriteBundle {
+                                sprite: Sprite {
+                                    color: CROSS_COLOR,
+                                    custom_size: Some(Vec2::new(cell * 0.7, cell * 0.1)),
+                                    ..Default::default()
+                                },
+                                transform: Transform {
+                                    translation: Vec3::new(0.0, 0.0, 0.1),
+                                    rotation: Quat::from_rotation_z(angle),
+                                    ..Default::default()
+                                },
+                                visibility: Visibility { is_visible: false },
+                                ..Default::default()
+                            })
+                            .insert(CrossMark);
+                    }
+                });
+        }
+    }
+
+    let size = cell * 0.45;
+    let slot = cell * CLUE_SLOT;
+    for y in 0..puzzle.height {
+        let center = layout.cell_center(0, y) - Vec2::new(cell / 2.0 + slot / 2.0, 0.0);
+        for index in 0..(puzzle.width as f32 / 2.0).ceil() as usize {
+            let position = center - Vec2::new(index as f32 * slot, 0.0);
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    font,
+                    "",
+                    size,
+                    CLUE_COLOR,
+                    position.extend(2.0),
+                ))
+                .insert(Clue {
+                    line: Line::Row(y),
+                    slot: index,
+                })
+                .insert(BoardPart);
+        }
+    }
+    for x in 0..puzzle.width {
+        let center = layout.cell_center(x, 0) + Vec2::new(0.0, cell / 2.0 + slot / 2.0);
+        for index in 0..(puzzle.height as f32 / 2.0).ceil() as usize {
+            let position = center + Vec2::new(0.0, index as f32 * slot);
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    font,
+                    "",
+                    size,
+                    CLUE_COLOR,
+                    position.extend(2.0),
+                ))
+                .insert(Clue {
+                    line: Line::Column(x),
+                    slot: index,
+                })
+                .insert(BoardPart);
+        }
+    }
+
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: CURSOR_COLOR,
+                custom_size: Some(Vec2::splat(cell)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(layout.cell_center(0, 0).extend(3.0)),
+            ..Default::default()
+        })
+        .insert(Cursor)
+        .insert(BoardPart);
+    commands.insert_resource(layout);
+}
@@ -0,0 +1,277 @@
+//! Puzzles are text files: the name on the first line, then one line per row with `#` for a
+//! filled cell and `.` for an empty one.
+
+use std::fmt;
+
+/// Largest width and height a puzzle may have.
+pub const MAX_SIZE: usize = 20;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    /// A row holds something other than `#` and `.`.
+    Character {
+        row: usize,
+        character: char,
+    },
+    /// A row is not as long as the first one.
+    Ragged {
+        row: usize,
+    },
+    TooLarge,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no name or no rows"),
+            Self::Character { row, character } => {
+                write!(f, "unexpected {:?} in row {}", character, row + 1)
+            }
+            Self::Ragged { row } => write!(f, "row {} has a different length", row + 1),
+            Self::TooLarge => write!(f, "more than {} rows or columns", MAX_SIZE),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Puzzle {
+    pub name: String,
+    pub width: usize,
+    pub height: usize,
+    /// Row by row from the top left.
+    cells: Vec<bool>,
+}
+
+impl Puzzle {
+    pub fn blank(name: &str, width: usize, height: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            width,
+            height,
+            cells: vec![false; width * height],
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text.lines().map(str::trim).filter(|line| !line.is_empty());
+        let name = lines.next().ok_or(ParseError::Empty)?;
+        let mut puzzle = Self::blank(name, 0, 0);
+        for (row, line) in lines.enumerate() {
+            if row == 0 {
+                puzzle.width = line.chars().count();
+            } else if line.chars().count() != puzzle.width {
+                return Err(ParseError::Ragged { row });
+            }
+            for character in line.chars() {
+                puzzle.cells.push(match character {
+                    '#' => true,
+                    '.' => false,
+                    _ => return Err(ParseError::Character { row, character }),
+                });
+            }
+            puzzle.height += 1;
+        }
+        if puzzle.height == 0 {
+            return Err(ParseError::Empty);
+        }
+        if puzzle.width > MAX_SIZE || puzzle.height > MAX_SIZE {
+            return Err(ParseError::TooLarge);
+        }
+        Ok(puzzle)
+    }
+
+    pub fn column_clue(&self, x: usize) -> Vec<usize> {
+        clue((0..self.height).map(|y| self.is_filled(x, y)))
+    }
+
+    pub fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.cells[y * self.width + x]
+    }
+
+    /// Whether the clues alone settle every cell without guessing, which also means the
+    /// picture is the only solution.
+    pub fn is_line_solvable(&self) -> bool {
+        let rows: Vec<Vec<usize>> = (0..self.height).map(|y| self.row_clue(y)).collect();
+        let columns: Vec<Vec<usize>> = (0..self.width).map(|x| self.column_clue(x)).collect();
+        let mut cells = vec![None; self.width * self.height];
+        loop {
+            let before = cells.clone();
+            for (y, clue) in rows.iter().enumerate() {
+                let indices: Vec<usize> = (0..self.width).map(|x| y * self.width + x).collect();
+                if !settle_line(clue, &indices, &mut cells) {
+                    return false;
+                }
+            }
+            for (x, clue) in columns.iter().enumerate() {
+                let indices: Vec<usize> = (0..self.height).map(|y| y * self.width + x).collect();
+                if !settle_line(clue, &indices, &mut cells) {
+                    return false;
+                }
+            }
+            if cells.iter().all(Option::is_some) {
+                return true;
+            }
+            if cells == before {
+                return false;
+            }
+        }
+    }
+
+    pub fn row_clue(&self, y: usize) -> Vec<usize> {
+        clue((0..self.width).map(|x| self.is_filled(x, y)))
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, filled: bool) {
+        self.cells[y * self.width + x] = filled;
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", self.name);
+        for row in self.cells.chunks(self.width) {
+            text.extend(row.iter().map(|filled| if *filled { '#' } else { '.' }));
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// The lengths of the runs of filled cells, in order; empty for a line without any.
+pub fn clue(cells: impl IntoIterator<Item = bool>) -> Vec<usize> {
+    let mut clue = Vec::new();
+    let mut run = 0;
+    for filled in cells {
+        if filled {
+            run += 1;
+        } else if run > 0 {
+            clue.push(run);
+            run = 0;
+        }
+    }
+    if run > 0 {
+        clue.push(run);
+    }
+    clue
+}
+
+/// Tries every way to lay out the runs of `clue` from `start` on that agrees with the known
+/// cells, and merges each into `found`: cells all of them agree on keep their value.
+fn arrange(
+    clue: &[usize],
+    line: &[Option<bool>],
+    start: usize,
+    cells: &mut Vec<bool>,
+    found: &mut Option<Vec<Option<bool>>>,
+) {
+    let (run, rest) = match clue.split_first() {
+        Some(split) => split,
+        None => {
+            if line[start..].contains(&Some(true)) {
+                return;
+            }
+            cells.truncate(start);
+            cells.resize(line.len(), false);
+            match found {
+                Some(found) => {
+                    for (merged, cell) in found.iter_mut().zip(cells.iter()) {
+                        if *merged != Some(*cell) {
+                            *merged = None;
+                        }
+                    }
+                }
+                None => *found = Some(cells.iter().map(|cell| Some(*cell)).collect()),
+            }
+            return;
+        }
+    };
+    let needed: usize = rest.iter().map(|run| run + 1).sum();
+    if start + run + needed > line.len() {
+        return;
+    }
+    for position in start..=line.len() - run - needed {
+        if line[start..position].contains(&Some(true)) {
+            break;
+        }
+        let end = position + run;
+        if line[position..end].contains(&Some(false)) || line.get(end) == Some(&Some(true)) {
+            continue;
+        }
+        cells.truncate(start);
+        cells.resize(position, false);
+        cells.resize(end, true);
+        if end < line.len() {
+            cells.push(false);
+        }
+        arrange(rest, line, cells.len(), cells, found);
+    }
+}
+
+/// Settles what can be settled of one line; false if its clue cannot fit at all.
+fn settle_line(clue: &[usize], indices: &[usize], cells: &mut [Option<bool>]) -> bool {
+    let line: Vec<Option<bool>> = indices.iter().map(|index| cells[*index]).collect();
+    let mut found = None;
+    arrange(clue, &line, 0, &mut Vec::new(), &mut found);
+    match found {
+        Some(found) => {
+            for (index, cell) in indices.iter().zip(found) {
+                cells[*index] = cell;
+            }
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clues_count_runs() {
+        assert_eq!(clue([true, true, false, true]), vec![2, 1]);
+        assert_eq!(clue([false, false]), Vec::<usize>::new());
+        let puzzle = Puzzle::parse("L\n#..\n#..\n###\n").unwrap();
+        assert_eq!(puzzle.row_clue(2), vec![3]);
+        assert_eq!(puzzle.column_clue(0), vec![3]);
+        assert_eq!(puzzle.column_clue(2), vec![1]);
+    }
+
+    #[test]
+    fn text_round_trips() {
+        let text = "Dot\n.#.\n...\n";
+        let puzzle = Puzzle::parse(text).unwrap();
+        assert_eq!((puzzle.width, puzzle.height), (3, 2));
+        assert_eq!(puzzle.to_text(), text);
+        assert_eq!(
+            Puzzle::parse("Bad\n..\n...\n"),
+            Err(ParseError::Ragged { row: 1 })
+        );
+        assert_eq!(
+            Puzzle::parse("Bad\n.x\n"),
+            Err(ParseError::Character {
+                row: 0,
+                character: 'x'
+            })
+        );
+        assert_eq!(Puzzle::parse("Name only"), Err(ParseError::Empty));
+    }
+
+    #[test]
+    fn line_solving_needs_a_single_solution() {
+        assert!(Puzzle::parse("Plus\n.#.\n###\n.#.\n")
+            .unwrap()
+            .is_line_solvable());
+        // Both diagonals give the same clues.
+        assert!(!Puzzle::parse("Diagonal\n#.\n.#\n")
+            .unwrap()
+            .is_line_solvable());
+    }
+
+    #[test]
+    fn bundled_puzzles_are_line_solvable() {
+        for text in crate::PUZZLES {
+            let puzzle = Puzzle::parse(text).unwrap();
+            assert!(puzzle.is_line_solvable(), "{}", puzzle.name);
+        }
+    }
+}
@@ -0,0 +1,437 @@
+//! The rules: balls in play and waiting at the plunger, scoring with the bonus multiplier, the
+//! ball save after a launch and multi-ball once every lock holds a ball.
+
+use crate::{
+    physics::{Ball, Flipper, BALL_RADIUS},
+    table::Table,
+};
+use bevy::math::Vec2;
+
+/// Balls to a game.
+pub const BALLS: u32 = 3;
+/// Seconds after a new ball is launched in which a drained ball comes back.
+pub const BALL_SAVE: f32 = 8.0;
+/// Speed a bumper adds to the ball, in pixels per second.
+const BUMPER_KICK: f32 = 450.0;
+/// How long bumpers and slingshots stay lit after a hit, in seconds.
+const FLASH: f32 = 0.15;
+const FLIPPER_RESTITUTION: f32 = 0.2;
+/// Launch speeds with the plunger barely and fully pulled, in pixels per second.
+const LAUNCH_SPEEDS: (f32, f32) = (600.0, 1700.0);
+/// Speed the balls leave the locks with when multi-ball starts.
+const LOCK_KICK: f32 = 350.0;
+const LOCK_POINTS: u32 = 2500;
+/// Seconds after multi-ball starts before the locks hold balls again.
+const LOCKS_CLOSED: f32 = 3.0;
+pub const MAX_MULTIPLIER: u32 = 5;
+/// Seconds to pull the plunger all the way.
+const PLUNGER_PULL: f32 = 1.0;
+/// A ball rolling back to the plunger slower than this waits there to be launched again.
+const RETURN_SPEED: f32 = 40.0;
+const SLING_KICK: f32 = 350.0;
+/// Physics steps per frame, so a fast ball cannot slip through a wall.
+const SUBSTEPS: usize = 8;
+const WALL_RESTITUTION: f32 = 0.45;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Controls {
+    pub left: bool,
+    pub plunger: bool,
+    pub right: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// Every drop target is down: the multiplier went up and the targets are back.
+    Bank,
+    Bumper,
+    /// The last ball in play drained.
+    Drained,
+    GameOver,
+    Launch,
+    Locked,
+    Multiball,
+    /// A ball drained during the ball save and is back at the plunger.
+    Saved,
+    Sling,
+    Target,
+}
+
+pub struct Game {
+    pub balls: Vec<Ball>,
+    /// Balls left, the one in play included.
+    pub balls_left: u32,
+    pub flippers: Vec<Flipper>,
+    /// Whether the next launch is a new ball, which starts the ball save.
+    fresh: bool,
+    /// Seconds each bumper and slingshot stays lit.
+    pub lit_bumpers: Vec<f32>,
+    pub lit_slings: Vec<f32>,
+    /// Which locks hold a ball.
+    pub locked: Vec<bool>,
+    /// Seconds before the locks hold balls again.
+    locks_closed: f32,
+    pub multiplier: u32,
+    pub over: bool,
+    /// How far the plunger is pulled, from 0 to 1.
+    pub plunger: f32,
+    /// Balls waiting at the plunger.
+    pub queued: u32,
+    /// Seconds of ball save left.
+    pub save: f32,
+    pub score: u32,
+    pub table: Table,
+    pub targets_down: Vec<bool>,
+}
+
+impl Game {
+    pub fn new(table: Table) -> Self {
+        Self {
+            balls: Vec::new(),
+            balls_left: BALLS,
+            flippers: table
+                .flippers
+                .iter()
+                .map(|spec| Flipper::new(spec.pivot, spec.length, spec.left))
+                .collect(),
+            fresh: true,
+            lit_bumpers: vec![0.0; table.bumpers.len()],
+            lit_slings: vec![0.0; table.slings.len()],
+            locked: vec![false; table.locks.len()],
+            locks_closed: 0.0,
+            multiplier: 1,
+            over: false,
+            plunger: 0.0,
+            queued: 1,
+            save: 0.0,
+            score: 0,
+            targets_down: vec![false; table.targets.len()],
+            table,
+        }
+    }
+
+    fn add_points(&mut self, points: u32) {
+        self.score += points * self.multiplier;
+    }
+
+    /// Bounces a ball off everything on the table for one step.
+    fn collide(&mut self, index: usize, events: &mut Vec<Event>) {
+        let table = &self.table;
+        let mut ball = self.balls[index];
+        let mut points = 0;
+        for wall in &table.walls {
+            ball.bounce(
+                wall.closest(ball.position),
+                BALL_RADIUS,
+                Vec2::ZERO,
+                WALL_RESTITUTION,
+            );
+        }
+        for gate in &table.gates {
+            if gate.is_left(ball.position) {
+                ball.bounce(
+                    gate.closest(ball.position),
+                    BALL_RADIUS,
+                    Vec2::ZERO,
+                    WALL_RESTITUTION,
+                );
+            }
+        }
+        for (bumper_index, bumper) in table.bumpers.iter().enumerate() {
+            let reach = BALL_RADIUS + bumper.radius;
+            if let Some(normal) = ball.bounce(bumper.center, reach, Vec2::ZERO, WALL_RESTITUTION) {
+                ball.velocity += normal * BUMPER_KICK;
+                self.lit_bumpers[bumper_index] = FLASH;
+                points += bumper.points;
+                events.push(Event::Bumper);
+            }
+        }
+        for (sling_index, sling) in table.slings.iter().enumerate() {
+            let closest = sling.segment.closest(ball.position);
+            if let Some(normal) = ball.bounce(closest, BALL_RADIUS, Vec2::ZERO, WALL_RESTITUTION) {
+                // Only a real hit kicks, not the ball rolling along it.
+                if self.lit_slings[sling_index] <= 0.0 {
+                    ball.velocity += normal * SLING_KICK;
+                    self.lit_slings[sling_index] = FLASH;
+                    points += sling.points;
+                    events.push(Event::Sling);
+                }
+            }
+        }
+        for (target_index, target) in table.targets.iter().enumerate() {
+            if self.targets_down[target_index] {
+                continue;
+            }
+            let closest = target.segment.closest(ball.position);
+            if ball
+                .bounce(closest, BALL_RADIUS, Vec2::ZERO, WALL_RESTITUTION)
+                .is_some()
+            {
+                self.targets_down[target_index] = true;
+                points += target.points;
+                events.push(Event::Target);
+            }
+        }
+        self.add_points(points);
+        if !self.targets_down.is_empty() && self.targets_down.iter().all(|down| *down) {
+            self.multiplier = (self.multiplier + 1).min(MAX_MULTIPLIER);
+            self.targets_down.iter_mut().for_each(|down| *down = false);
+            events.push(Event::Bank);
+        }
+        for flipper in &self.flippers {
+            flipper.hit(&mut ball, FLIPPER_RESTITUTION);
+        }
+        self.balls[index] = ball;
+    }
+
+    /// Takes off the balls that fell past the flippers, or rolled back down to the plunger.
+    fn drain(&mut self, events: &mut Vec<Event>) {
+        let bottom = -self.table.size.y / 2.0 - BALL_RADIUS;
+        let plunger = self.table.plunger;
+        let before = self.balls.len();
+        let mut drained = 0;
+        self.balls.retain(|ball| {
+            let returned = ball.position.distance(plunger) < BALL_RADIUS
+                && ball.velocity.length() < RETURN_SPEED;
+            if ball.position.y < bottom {
+                drained += 1;
+            }
+            ball.position.y >= bottom && !returned
+        });
+        self.queued += (before - self.balls.len() - drained) as u32;
+        if drained > 0 && self.save > 0.0 {
+            self.queued += drained as u32;
+            events.push(Event::Saved);
+        }
+        if drained == 0 || !self.balls.is_empty() || self.queued > 0 {
+            return;
+        }
+        events.push(Event::Drained);
+        self.balls_left -= 1;
+        self.multiplier = 1;
+        if self.balls_left == 0 {
+            self.over = true;
+            events.push(Event::GameOver);
+        } else {
+            self.queued = 1;
+            self.fresh = true;
+        }
+    }
+
+    /// Holds balls that roll into a free lock, and sets them all free once every lock holds one.
+    fn lock(&mut self, events: &mut Vec<Event>) {
+        if self.locks_closed > 0.0 {
+            return;
+        }
+        let mut index = 0;
+        while index < self.balls.len() {
+            let position = self.balls[index].position;
+            let free = self
+                .table
+                .locks
+                .iter()
+                .zip(&self.locked)
+                .position(|(lock, held)| !held && position.distance(lock.center) < lock.radius);
+            match free {
+                Some(lock) => {
+                    self.balls.remove(index);
+                    self.locked[lock] = true;
+                    self.add_points(LOCK_POINTS);
+                    events.push(Event::Locked);
+                    if self.locked.iter().all(|held| *held) {
+                        self.release(events);
+                        return;
+                    }
+                    self.queued += 1;
+                }
+                None => index += 1,
+            }
+        }
+    }
+
+    /// Pulls the plunger while it is held and launches the waiting ball once it is let go.
+    fn plunge(&mut self, pulled: bool, delta: f32, events: &mut Vec<Event>) {
+        if self.queued == 0 {
+            self.plunger = 0.0;
+            return;
+        }
+        if pulled {
+            self.plunger = (self.plunger + delta / PLUNGER_PULL).min(1.0);
+            return;
+        }
+        if self.plunger == 0.0 {
+            return;
+        }
+        let (weakest, strongest) = LAUNCH_SPEEDS;
+        self.balls.push(Ball {
+            position: self.table.plunger,
+            velocity: Vec2::new(0.0, weakest + (strongest - weakest) * self.plunger),
+        });
+        self.queued -= 1;
+        self.plunger = 0.0;
+        if self.fresh {
+            self.fresh = false;
+            self.save = BALL_SAVE;
+        }
+        events.push(Event::Launch);
+    }
+
+    /// Multi-ball: every lock lets its ball go, with the ball that filled the last lock among
+    /// them.
+    fn release(&mut self, events: &mut Vec<Event>) {
+        for (lock, held) in self.table.locks.iter().zip(self.locked.iter_mut()) {
+            *held = false;
+            self.balls.push(Ball {
+                position: lock.center,
+                velocity: Vec2::new(0.0, -LOCK_KICK),
+            });
+        }
+        self.locks_closed = LOCKS_CLOSED;
+        events.push(Event::Multiball);
+    }
+
+    /// Plays `delta` seconds.
+    pub fn update(&mut self, controls: Controls, delta: f32) -> Vec<Event> {
+        let mut events = Vec::new();
+        if self.over {
+            return events;
+        }
+        self.plunge(controls.plunger, delta, &mut events);
+        self.save = (self.save - delta).max(0.0);
+        self.locks_closed = (self.locks_closed - delta).max(0.0);
+        for lit in self
+            .lit_bumpers
+            .iter_mut()
+            .chain(self.lit_slings.iter_mut())
+        {
+            *lit = (*lit - delta).max(0.0);
+        }
+
+        let step = delta / SUBSTEPS as f32;
+        for _ in 0..SUBSTEPS {
+            for flipper in self.flippers.iter_mut() {
+                let pressed = if flipper.left {
+                    controls.left
+                } else {
+                    controls.right
+                };
+                flipper.swing(pressed, step);
+            }
+            for index in 0..self.balls.len() {
+                self.balls[index].roll(step);
+                self.collide(index, &mut events);
+            }
+            for first in 0..self.balls.len() {
+                let (before, after) = self.balls.split_at_mut(first + 1);
+                for other in after {
+                    before[first].collide(other);
+                }
+            }
+        }
+        self.lock(&mut events);
+        self.drain(&mut events);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game() -> Game {
+        Game::new(
+            Table::parse(
+                "name Test\nsize 400 600\nplunger 180 -280\n\
+                 flipper left -60 -250 70\nflipper right 60 -250 70\n\
+                 target -20 0 20 0 500\ntarget -20 100 20 100 500\nbumper 0 -100 20 100\n\
+                 lock -100 200 12\nlock 100 200 12",
+            )
+            .unwrap(),
+        )
+    }
+
+    fn launch(game: &mut Game) -> Vec<Event> {
+        let pulled = Controls {
+            plunger: true,
+            ..Default::default()
+        };
+        game.update(pulled, 0.5);
+        game.update(Controls::default(), 0.0)
+    }
+
+    fn drop_ball(game: &mut Game) -> Vec<Event> {
+        game.balls[0] = Ball {
+            position: Vec2::new(0.0, -400.0),
+            velocity: Vec2::new(0.0, -500.0),
+        };
+        game.update(Controls::default(), 0.01)
+    }
+
+    #[test]
+    fn saves_then_loses_balls() {
+        let mut game = game();
+        assert_eq!(launch(&mut game), vec![Event::Launch]);
+        assert_eq!((game.balls.len(), game.queued), (1, 0));
+        assert_eq!(game.balls[0].velocity.y, 1150.0);
+        assert_eq!(game.save, BALL_SAVE);
+
+        assert_eq!(drop_ball(&mut game), vec![Event::Saved]);
+        assert_eq!((game.balls_left, game.queued), (3, 1));
+        // A saved ball does not start the save again.
+        launch(&mut game);
+        assert!(game.save < BALL_SAVE);
+        game.save = 0.0;
+        assert_eq!(drop_ball(&mut game), vec![Event::Drained]);
+        assert_eq!((game.balls_left, game.queued), (2, 1));
+
+        for _ in 0..2 {
+            launch(&mut game);
+            game.save = 0.0;
+            drop_ball(&mut game);
+        }
+        assert!(game.over);
+        assert_eq!(game.update(Controls::default(), 0.1), vec![]);
+    }
+
+    #[test]
+    fn scores_with_the_multiplier() {
+        let mut game = game();
+        launch(&mut game);
+        let hit = |game: &mut Game, y: f32| {
+            game.balls[0] = Ball {
+                position: Vec2::new(0.0, y - 8.0),
+                velocity: Vec2::new(0.0, 300.0),
+            };
+            game.update(Controls::default(), 0.001)
+        };
+        assert_eq!(hit(&mut game, 0.0), vec![Event::Target]);
+        assert_eq!(hit(&mut game, 0.0), vec![]);
+        assert_eq!(hit(&mut game, 100.0), vec![Event::Target, Event::Bank]);
+        assert_eq!((game.score, game.multiplier), (1000, 2));
+        assert_eq!(game.targets_down, vec![false, false]);
+        assert_eq!(hit(&mut game, -120.0), vec![Event::Bumper]);
+        assert_eq!(game.score, 1200);
+        assert!(game.lit_bumpers[0] > 0.0);
+    }
+
+    #[test]
+    fn locks_start_multiball() {
+        let mut game = game();
+        launch(&mut game);
+        game.balls[0].position = Vec2::new(-100.0, 200.0);
+        assert_eq!(game.update(Controls::default(), 0.0), vec![Event::Locked]);
+        assert_eq!((game.balls.len(), game.queued), (0, 1));
+        launch(&mut game);
+        game.balls[0].position = Vec2::new(100.0, 200.0);
+        assert_eq!(
+            game.update(Controls::default(), 0.0),
+            vec![Event::Locked, Event::Multiball]
+        );
+        assert_eq!((game.balls.len(), game.queued), (2, 0));
+        assert_eq!(game.locked, vec![false, false]);
+        // Losing one of the two is no lost ball.
+        game.save = 0.0;
+        assert_eq!(drop_ball(&mut game), vec![]);
+        assert_eq!((game.balls.len(), game.balls_left), (1, 3));
+    }
+}
@@ -0,0 +1,607 @@
+use bevy::{
+    math::const_vec2,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use game::{Controls, Event, Game, BALLS, MAX_MULTIPLIER};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use physics::{Segment, BALL_RADIUS, FLIPPER_THICKNESS};
+use table::Table;
+
+pub mod game;
+pub mod physics;
+pub mod table;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.09);
+const BALL_COLOR: Color = Color::rgb(0.85, 0.87, 0.9);
+/// Seconds a message stays in the panel.
+const BANNER_TIME: f32 = 2.0;
+const BUMPER_COLOR: Color = Color::rgb(0.85, 0.3, 0.55);
+const FLIPPER_COLOR: Color = Color::rgb(0.95, 0.75, 0.25);
+const GAME: &str = "pinball";
+const GATE_COLOR: Color = Color::rgb(0.4, 0.45, 0.6);
+const LEADERBOARD_SIZE: usize = 5;
+const LEFT_KEYS: [KeyCode; 3] = [KeyCode::LShift, KeyCode::Z, KeyCode::Left];
+const LIT_COLOR: Color = Color::rgb(1.0, 0.95, 0.7);
+const LOCK_COLOR: Color = Color::rgb(0.2, 0.22, 0.32);
+const PLAYFIELD_COLOR: Color = Color::rgb(0.1, 0.13, 0.25);
+const PLUNGER_KEYS: [KeyCode; 2] = [KeyCode::Space, KeyCode::Down];
+const RIGHT_KEYS: [KeyCode; 3] = [KeyCode::RShift, KeyCode::Slash, KeyCode::Right];
+const SLING_COLOR: Color = Color::rgb(0.3, 0.75, 0.9);
+/// Where the middle of the table is on screen, leaving room for the panel on the right.
+const TABLE_OFFSET: Vec2 = const_vec2!([-80.0, 0.0]);
+const TABLE_TEXT: &str = include_str!("../assets/table.txt");
+const TARGET_COLOR: Color = Color::rgb(0.45, 0.85, 0.4);
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const WALL_COLOR: Color = Color::rgb(0.6, 0.65, 0.8);
+
+pub const WINDOW_HEIGHT: f32 = 800.0;
+pub const WINDOW_WIDTH: f32 = 640.0;
+
+#[derive(Component)]
+struct BallSprite;
+
+/// The last thing that happened worth telling, shown in the panel for a moment.
+#[derive(Default)]
+struct Banner {
+    text: String,
+    time: f32,
+}
+
+/// The disc texture the balls, bumpers and locks are drawn with.
+struct Disc(Handle<Image>);
+
+#[derive(Component)]
+struct FlipperSprite(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// A bumper or slingshot, lit for a moment when it is hit.
+#[derive(Component)]
+enum Lamp {
+    Bumper(usize),
+    Sling(usize),
+}
+
+#[derive(Component)]
+struct PlungerSprite;
+
+/// A drop target that is only there while it is up, or a ball held in a lock.
+#[derive(Component)]
+enum Shown {
+    Lock(usize),
+    Target(usize),
+}
+
+struct Sounds {
+    bank: Handle<AudioSource>,
+    bumper: Handle<AudioSource>,
+    drained: Handle<AudioSource>,
+    flipper: Handle<AudioSource>,
+    game_over: Handle<AudioSource>,
+    launch: Handle<AudioSource>,
+    locked: Handle<AudioSource>,
+    multiball: Handle<AudioSource>,
+    saved: Handle<AudioSource>,
+    sling: Handle<AudioSource>,
+    target: Handle<AudioSource>,
+}
+
+pub struct PinballPlugin;
+
+impl Plugin for PinballPlugin {
+    fn build(&self, app: &mut App) {
+        let table = Table::parse(TABLE_TEXT).expect("bundled table is valid");
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(table))
+            .insert_resource(Banner::default())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(play_update))
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over_enter))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system(balls_update)
+            .add_system(flippers_update)
+            .add_system(lamps_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Pinball".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Respawns the balls in play, the one waiting at the plunger and those held in the locks.
+fn balls_update(
+    mut commands: Commands,
+    game: Res<Game>,
+    disc: Res<Disc>,
+    sprites: Query<Entity, With<BallSprite>>,
+) {
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let mut positions: Vec<Vec2> = game.balls.iter().map(|ball| ball.position).collect();
+    if game.queued > 0 && !game.over {
+        // Drawn on the plunger, pulled down with it.
+        positions.push(game.table.plunger - Vec2::new(0.0, game.plunger * BALL_RADIUS * 2.0));
+    }
+    for position in positions {
+        commands
+            .spawn_bundle(disc_sprite(
+                &disc.0,
+                BALL_COLOR,
+                BALL_RADIUS * 2.0,
+                on_screen(position, 30.0),
+            ))
+            .insert(BallSprite);
+    }
+}
+
+/// A white disc with a soft edge, tinted per sprite.
+fn disc_image() -> Image {
+    const SIZE: u32 = 64;
+    let center = SIZE as f32 / 2.0;
+    let mut data = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..SIZE {
+            let distance = Vec2::new(x as f32 + 0.5 - center, y as f32 + 0.5 - center).length();
+            let alpha = (center - distance).clamp(0.0, 1.0);
+            data.extend_from_slice(&[255, 255, 255, (alpha * 255.0) as u8]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+fn disc_sprite(
+    disc: &Handle<Image>,
+    color: Color,
+    diameter: f32,
+    translation: Vec3,
+) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(diameter)),
+            ..Default::default()
+        },
+        texture: disc.clone(),
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+/// Turns the flippers and pulls the plunger down.
+fn flippers_update(
+    game: Res<Game>,
+    mut flippers: Query<(&mut Transform, &FlipperSprite)>,
+    mut plungers: Query<&mut Transform, (With<PlungerSprite>, Without<FlipperSprite>)>,
+) {
+    for (mut transform, flipper) in flippers.iter_mut() {
+        if let Some(flipper) = game.flippers.get(flipper.0) {
+            *transform = segment_transform(flipper.segment(), transform.translation.z);
+        }
+    }
+    for mut transform in plungers.iter_mut() {
+        let top =
+            game.table.plunger - Vec2::new(0.0, BALL_RADIUS + game.plunger * BALL_RADIUS * 2.0);
+        transform.translation = on_screen(top - Vec2::new(0.0, 20.0), 20.0);
+    }
+}
+
+/// Keeps the score on the leaderboard once the last ball is gone.
+fn game_over_enter(game: Res<Game>, mut scores: ResMut<Scores>, platform: Res<Platform>) {
+    if scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// Enter goes back to the menu.
+fn game_over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Lights the bumpers and slingshots just hit, drops the targets and shows the locked balls.
+fn lamps_update(
+    game: Res<Game>,
+    mut lamps: Query<(&mut Sprite, &Lamp)>,
+    mut shown: Query<(&mut Visibility, &Shown)>,
+) {
+    for (mut sprite, lamp) in lamps.iter_mut() {
+        let (lit, color) = match lamp {
+            Lamp::Bumper(index) => (game.lit_bumpers[*index], BUMPER_COLOR),
+            Lamp::Sling(index) => (game.lit_slings[*index], SLING_COLOR),
+        };
+        sprite.color = if lit > 0.0 { LIT_COLOR } else { color };
+    }
+    for (mut visibility, shown) in shown.iter_mut() {
+        visibility.is_visible = match shown {
+            Shown::Lock(index) => game.locked[*index],
+            Shown::Target(index) => !game.targets_down[*index],
+        };
+    }
+}
+
+/// Enter starts a game.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut banner: ResMut<Banner>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        let table = game.table.clone();
+        *game = Game::new(table);
+        *banner = Banner::default();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// A point of the table on screen, at depth `z`.
+fn on_screen(point: Vec2, z: f32) -> Vec3 {
+    (point + TABLE_OFFSET).extend(z)
+}
+
+/// Works the flippers and the plunger, plays the table on and tells what happened.
+fn play_update(
+    keyboard_input: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut banner: ResMut<Banner>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    if keyboard_input.any_just_pressed(LEFT_KEYS) || keyboard_input.any_just_pressed(RIGHT_KEYS) {
+        audio.play(sounds.flipper.clone());
+    }
+    let controls = Controls {
+        left: keyboard_input.any_pressed(LEFT_KEYS),
+        plunger: keyboard_input.any_pressed(PLUNGER_KEYS),
+        right: keyboard_input.any_pressed(RIGHT_KEYS),
+    };
+    let delta = time.delta_seconds().min(1.0 / 30.0);
+    banner.time = (banner.time - delta).max(0.0);
+    let mut played = Vec::new();
+    for event in game.update(controls, delta) {
+        let (sound, message) = match event {
+            Event::Bank => (
+                &sounds.bank,
+                Some(format!("Multiplier x{}", game.multiplier)),
+            ),
+            Event::Bumper => (&sounds.bumper, None),
+            Event::Drained => (&sounds.drained, Some("Ball lost".to_string())),
+            Event::GameOver => (&sounds.game_over, None),
+            Event::Launch => (&sounds.launch, None),
+            Event::Locked => (&sounds.locked, Some("Ball locked".to_string())),
+            Event::Multiball => (&sounds.multiball, Some("MULTI-BALL!".to_string())),
+            Event::Saved => (&sounds.saved, Some("Ball saved".to_string())),
+            Event::Sling => (&sounds.sling, None),
+            Event::Target => (&sounds.target, None),
+        };
+        // Several hits in one frame make one sound.
+        if !played.contains(&event) {
+            audio.play(sound.clone());
+            played.push(event);
+        }
+        if let Some(message) = message {
+            *banner = Banner {
+                text: message,
+                time: BANNER_TIME,
+            };
+        }
+    }
+    if game.over {
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn segment_sprite(segment: Segment, thickness: f32, color: Color, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(1.0, thickness)),
+            ..Default::default()
+        },
+        transform: segment_transform(segment, z),
+        ..Default::default()
+    }
+}
+
+/// Stretches and turns a sprite one pixel long to cover a segment.
+fn segment_transform(segment: Segment, z: f32) -> Transform {
+    let along = segment.to - segment.from;
+    Transform {
+        translation: on_screen((segment.from + segment.to) / 2.0, z),
+        rotation: Quat::from_rotation_z(along.y.atan2(along.x)),
+        scale: Vec3::new(along.length(), 1.0, 1.0),
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    mut images: ResMut<Assets<Image>>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(20.0),
+                left: Val::Px(480.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            14.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(20.0),
+                left: Val::Px(480.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+
+    let table = &game.table;
+    let disc = images.add(disc_image());
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: PLAYFIELD_COLOR,
+            custom_size: Some(table.size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(on_screen(Vec2::ZERO, 0.0)),
+        ..Default::default()
+    });
+    for wall in &table.walls {
+        commands.spawn_bundle(segment_sprite(*wall, 4.0, WALL_COLOR, 5.0));
+    }
+    for gate in &table.gates {
+        commands.spawn_bundle(segment_sprite(*gate, 2.0, GATE_COLOR, 5.0));
+    }
+    for (index, sling) in table.slings.iter().enumerate() {
+        commands
+            .spawn_bundle(segment_sprite(sling.segment, 6.0, SLING_COLOR, 6.0))
+            .insert(Lamp::Sling(index));
+    }
+    for (index, target) in table.targets.iter().enumerate() {
+        commands
+            .spawn_bundle(segment_sprite(target.segment, 6.0, TARGET_COLOR, 6.0))
+            .insert(Shown::Target(index));
+    }
+    for (index, bumper) in table.bumpers.iter().enumerate() {
+        commands
+            .spawn_bundle(disc_sprite(
+                &disc,
+                BUMPER_COLOR,
+                bumper.radius * 2.0,
+                on_screen(bumper.center, 6.0),
+            ))
+            .insert(Lamp::Bumper(index));
+    }
+    for (index, lock) in table.locks.iter().enumerate() {
+        commands.spawn_bundle(disc_sprite(
+            &disc,
+            LOCK_COLOR,
+            lock.radius * 2.0 + BALL_RADIUS,
+            on_screen(lock.center, 4.0),
+        ));
+        commands
+            .spawn_bundle(disc_sprite(
+                &disc,
+                BALL_COLOR,
+                BALL_RADIUS * 2.0,
+                on_screen(lock.center, 30.0),
+            ))
+            .insert(Shown::Lock(index));
+    }
+    for (index, flipper) in game.flippers.iter().enumerate() {
+        commands
+            .spawn_bundle(segment_sprite(
+                flipper.segment(),
+                FLIPPER_THICKNESS * 2.0,
+                FLIPPER_COLOR,
+                20.0,
+            ))
+            .insert(FlipperSprite(index));
+    }
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: WALL_COLOR,
+                custom_size: Some(Vec2::new(BALL_RADIUS * 2.0, 40.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(PlungerSprite);
+    commands.insert_resource(Disc(disc));
+
+    commands.insert_resource(Sounds {
+        bank: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.08),
+            Tone::new(Waveform::Triangle, 659.0, 0.08),
+            Tone::new(Waveform::Triangle, 784.0, 0.2).fade(),
+        ])),
+        bumper: audio_sources.add(
+            Tone::new(Waveform::Square, 660.0, 0.06)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        drained: audio_sources.add(
+            Tone::new(Waveform::Saw, 300.0, 0.6)
+                .slide(80.0)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        flipper: audio_sources.add(
+            Tone::new(Waveform::Noise, 600.0, 0.04)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        game_over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        launch: audio_sources.add(
+            Tone::new(Waveform::Noise, 200.0, 0.25)
+                .slide(800.0)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        locked: audio_sources.add(
+            Tone::new(Waveform::Sine, 220.0, 0.3)
+                .slide(440.0)
+                .fade()
+                .into(),
+        ),
+        multiball: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 659.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 784.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1047.0, 0.35).fade().volume(0.2),
+        ])),
+        saved: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 784.0, 0.1),
+            Tone::new(Waveform::Sine, 1047.0, 0.2).fade(),
+        ])),
+        sling: audio_sources.add(
+            Tone::new(Waveform::Square, 440.0, 0.05)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        target: audio_sources.add(
+            Tone::new(Waveform::Triangle, 880.0, 0.08)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+fn texts_update(
+    game: Res<Game>,
+    banner: Res<Banner>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+    )>,
+) {
+    let scores = scores
+        .table("")
+        .iter()
+        .take(LEADERBOARD_SIZE)
+        .enumerate()
+        .map(|(place, entry)| format!("{}. {:>8}", place + 1, entry.score))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let hud = match state.current() {
+        GameState::Menu => format!(
+            "{}\n\nPress Enter\nto play\n\nHigh scores\n{}",
+            game.table.name.to_uppercase(),
+            scores
+        ),
+        _ => {
+            let ball = (BALLS - game.balls_left + 1).min(BALLS);
+            let mut lines = vec![
+                format!("Score\n{}", game.score),
+                format!("Ball {}/{}", ball, BALLS),
+                format!("Multiplier x{}", game.multiplier),
+            ];
+            if game.multiplier < MAX_MULTIPLIER {
+                let up = game.targets_down.iter().filter(|down| !**down).count();
+                lines.push(format!("{} targets to\nthe next", up));
+            }
+            if game.save > 0.0 {
+                lines.push(format!("Ball save {:.0}", game.save.ceil()));
+            }
+            if game.balls.len() > 1 {
+                lines.push(format!("{} balls!", game.balls.len()));
+            }
+            if banner.time > 0.0 {
+                lines.push(banner.text.clone());
+            }
+            if *state.current() == GameState::GameOver {
+                lines.push(format!("GAME OVER\n\nHigh scores\n{}", scores));
+            }
+            lines.join("\n\n")
+        }
+    };
+    let help = match state.current() {
+        GameState::Menu => "Enter: play",
+        GameState::Playing => {
+            "Shift, Z or Left:\n left flipper\nShift, / or Right:\n right flipper\n\
+             Space or Down:\n pull the plunger\nEsc: give up"
+        }
+        GameState::GameOver => "Enter: menu",
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.to_string();
+    }
+}
@@ -0,0 +1,242 @@
+//! The ball rolling down the tilted table and what it bounces off: segments, round bumpers and
+//! the flippers, which hand their own speed on to the ball.
+
+use bevy::math::Vec2;
+
+pub const BALL_RADIUS: f32 = 10.0;
+/// How far the flippers swing either side of level, in radians.
+const FLIPPER_SWING: f32 = 0.5;
+/// How fast the flippers swing, in radians per second.
+const FLIPPER_SPEED: f32 = 24.0;
+/// Half the thickness of a flipper.
+pub const FLIPPER_THICKNESS: f32 = 6.0;
+/// Pull down the table, in pixels per second squared.
+const GRAVITY: f32 = 900.0;
+/// The ball never goes faster, in pixels per second.
+const MAX_SPEED: f32 = 2000.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Ball {
+    pub position: Vec2,
+    pub velocity: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Flipper {
+    pub angle: f32,
+    /// Radians per second, counterclockwise.
+    pub angular_velocity: f32,
+    /// A left flipper points right and swings up counterclockwise; a right one is its mirror.
+    pub left: bool,
+    pub length: f32,
+    pub pivot: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Segment {
+    pub from: Vec2,
+    pub to: Vec2,
+}
+
+impl Ball {
+    /// Pushes the ball off `closest`, the nearest point of something it may touch, when it is
+    /// nearer than `reach`, and bounces it off a surface moving at `surface`. `restitution` is
+    /// the share of the speed into the surface that comes back out. Returns the direction it was
+    /// pushed off in.
+    pub fn bounce(
+        &mut self,
+        closest: Vec2,
+        reach: f32,
+        surface: Vec2,
+        restitution: f32,
+    ) -> Option<Vec2> {
+        let offset = self.position - closest;
+        let distance = offset.length();
+        if distance >= reach {
+            return None;
+        }
+        let normal = if distance > 0.0 {
+            offset / distance
+        } else {
+            Vec2::Y
+        };
+        self.position = closest + normal * reach;
+        let relative = self.velocity - surface;
+        let along = relative.dot(normal);
+        if along < 0.0 {
+            self.velocity -= normal * along * (1.0 + restitution);
+        }
+        self.velocity = self.velocity.clamp_length_max(MAX_SPEED);
+        Some(normal)
+    }
+
+    /// Pushes two touching balls apart and swaps their speeds along the line between them.
+    pub fn collide(&mut self, other: &mut Ball) -> bool {
+        let offset = self.position - other.position;
+        let distance = offset.length();
+        if distance >= BALL_RADIUS * 2.0 || distance == 0.0 {
+            return false;
+        }
+        let normal = offset / distance;
+        let overlap = BALL_RADIUS * 2.0 - distance;
+        self.position += normal * overlap / 2.0;
+        other.position -= normal * overlap / 2.0;
+        let along = (self.velocity - other.velocity).dot(normal);
+        if along < 0.0 {
+            self.velocity -= normal * along;
+            other.velocity += normal * along;
+        }
+        true
+    }
+
+    /// Rolls the ball on for `delta` seconds.
+    pub fn roll(&mut self, delta: f32) {
+        self.velocity.y -= GRAVITY * delta;
+        self.velocity = self.velocity.clamp_length_max(MAX_SPEED);
+        self.position += self.velocity * delta;
+    }
+}
+
+impl Flipper {
+    pub fn new(pivot: Vec2, length: f32, left: bool) -> Self {
+        let mut flipper = Self {
+            angle: 0.0,
+            angular_velocity: 0.0,
+            left,
+            length,
+            pivot,
+        };
+        flipper.angle = flipper.rest_angle();
+        flipper
+    }
+
+    /// Knocks the ball away if it touches the flipper, with the flipper's speed where it hit.
+    pub fn hit(&self, ball: &mut Ball, restitution: f32) -> bool {
+        let closest = self.segment().closest(ball.position);
+        let surface = (closest - self.pivot).perp() * self.angular_velocity;
+        ball.bounce(
+            closest,
+            BALL_RADIUS + FLIPPER_THICKNESS,
+            surface,
+            restitution,
+        )
+        .is_some()
+    }
+
+    fn rest_angle(&self) -> f32 {
+        if self.left {
+            -FLIPPER_SWING
+        } else {
+            std::f32::consts::PI + FLIPPER_SWING
+        }
+    }
+
+    pub fn segment(&self) -> Segment {
+        Segment {
+            from: self.pivot,
+            to: self.pivot + Vec2::new(self.angle.cos(), self.angle.sin()) * self.length,
+        }
+    }
+
+    /// Swings up while `pressed` and falls back otherwise, for `delta` seconds.
+    pub fn swing(&mut self, pressed: bool, delta: f32) {
+        let rest = self.rest_angle();
+        let up = if self.left {
+            FLIPPER_SWING
+        } else {
+            std::f32::consts::PI - FLIPPER_SWING
+        };
+        let target = if pressed { up } else { rest };
+        let step = (target - self.angle).clamp(-FLIPPER_SPEED * delta, FLIPPER_SPEED * delta);
+        self.angle += step;
+        self.angular_velocity = if delta > 0.0 { step / delta } else { 0.0 };
+    }
+}
+
+impl Segment {
+    pub fn new(from: Vec2, to: Vec2) -> Self {
+        Self { from, to }
+    }
+
+    /// The point of the segment nearest to `point`.
+    pub fn closest(&self, point: Vec2) -> Vec2 {
+        let along = self.to - self.from;
+        let length = along.length_squared();
+        if length == 0.0 {
+            return self.from;
+        }
+        let share = ((point - self.from).dot(along) / length).clamp(0.0, 1.0);
+        self.from + along * share
+    }
+
+    /// Whether `point` is on the left of the segment, looking from its start to its end.
+    pub fn is_left(&self, point: Vec2) -> bool {
+        (self.to - self.from).perp_dot(point - self.from) > 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounces_off_walls_and_balls() {
+        let wall = Segment::new(Vec2::new(-100.0, 0.0), Vec2::new(100.0, 0.0));
+        let mut ball = Ball {
+            position: Vec2::new(0.0, 8.0),
+            velocity: Vec2::new(100.0, -400.0),
+        };
+        let normal = ball.bounce(wall.closest(ball.position), BALL_RADIUS, Vec2::ZERO, 0.5);
+        assert_eq!(normal, Some(Vec2::Y));
+        assert_eq!(ball.position, Vec2::new(0.0, BALL_RADIUS));
+        assert_eq!(ball.velocity, Vec2::new(100.0, 200.0));
+        assert!(wall.is_left(ball.position));
+        // Past the end, it only touches the corner.
+        assert_eq!(wall.closest(Vec2::new(150.0, 5.0)), Vec2::new(100.0, 0.0));
+
+        let mut other = Ball {
+            position: Vec2::new(15.0, BALL_RADIUS),
+            velocity: Vec2::ZERO,
+        };
+        assert!(ball.collide(&mut other));
+        assert_eq!(ball.velocity, Vec2::new(0.0, 200.0));
+        assert_eq!(other.velocity, Vec2::new(100.0, 0.0));
+    }
+
+    #[test]
+    fn falls_down_the_table() {
+        let mut ball = Ball::default();
+        for _ in 0..60 {
+            ball.roll(1.0 / 60.0);
+        }
+        assert!((ball.velocity.y + GRAVITY).abs() < 0.1);
+        assert!(ball.position.y < -400.0);
+    }
+
+    #[test]
+    fn flippers_swing_and_shoot_the_ball() {
+        let mut flipper = Flipper::new(Vec2::ZERO, 70.0, true);
+        let rest = flipper.segment().to;
+        assert!(rest.x > 0.0 && rest.y < 0.0);
+        let mut mirrored = Flipper::new(Vec2::ZERO, 70.0, false);
+        assert!((mirrored.segment().to - Vec2::new(-rest.x, rest.y)).length() < 0.001);
+
+        // A ball lying on the tip at rest is sent up the table.
+        let mut ball = Ball {
+            position: rest + Vec2::new(0.0, BALL_RADIUS + FLIPPER_THICKNESS - 1.0),
+            velocity: Vec2::ZERO,
+        };
+        flipper.swing(true, 1.0 / 120.0);
+        assert!(flipper.angular_velocity > 0.0);
+        assert!(flipper.hit(&mut ball, 0.3));
+        assert!(ball.velocity.y > 1000.0);
+        mirrored.swing(true, 1.0 / 120.0);
+        assert!(mirrored.angular_velocity < 0.0);
+
+        for _ in 0..60 {
+            flipper.swing(true, 1.0 / 120.0);
+        }
+        assert_eq!(flipper.angle, FLIPPER_SWING);
+        assert_eq!(flipper.angular_velocity, 0.0);
+    }
+}
@@ -0,0 +1,237 @@
+//! Tables are text files with one element per line, `#` starting a comment. Coordinates are in
+//! pixels from the middle of the table, with y pointing up the table:
+//!
+//! - `name <words>` and `size <width> <height>`
+//! - `plunger <x> <y>`: where a ball waits to be launched
+//! - `wall <x> <y> <x> <y> ...`: a line through two or more points
+//! - `arc <x> <y> <radius> <from> <to>`: a wall along a circle, counterclockwise between two
+//!   angles in degrees
+//! - `gate <x> <y> <x> <y>`: a wall the ball only passes from right to left, looking from the
+//!   first point to the second
+//! - `bumper <x> <y> <radius> <points>`
+//! - `sling <x> <y> <x> <y> <points>`: a wall that kicks the ball away
+//! - `target <x> <y> <x> <y> <points>`: a drop target
+//! - `lock <x> <y> <radius>`: a hole that holds a ball for multi-ball
+//! - `flipper <left|right> <x> <y> <length>`: a flipper turning about the point
+
+use crate::physics::Segment;
+use bevy::math::Vec2;
+use std::fmt;
+
+/// Walls an arc is drawn with per quarter circle.
+const ARC_STEPS: f32 = 8.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bumper {
+    pub center: Vec2,
+    pub points: u32,
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlipperSpec {
+    pub left: bool,
+    pub length: f32,
+    pub pivot: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lock {
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// Not a known element, or not the right numbers after it.
+    Line { line: usize },
+    /// An element every table needs is not there.
+    Missing(&'static str),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Line { line } => write!(f, "line {} is not understood", line + 1),
+            Self::Missing(element) => write!(f, "no {}", element),
+        }
+    }
+}
+
+/// A wall that scores when the ball hits it: a slingshot or a drop target.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scorer {
+    pub points: u32,
+    pub segment: Segment,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Table {
+    pub bumpers: Vec<Bumper>,
+    pub flippers: Vec<FlipperSpec>,
+    pub gates: Vec<Segment>,
+    pub locks: Vec<Lock>,
+    pub name: String,
+    pub plunger: Vec2,
+    pub size: Vec2,
+    pub slings: Vec<Scorer>,
+    pub targets: Vec<Scorer>,
+    pub walls: Vec<Segment>,
+}
+
+impl Table {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut table = Table::default();
+        let (mut named, mut sized, mut plunger) = (false, false, false);
+        for (line, text) in text.lines().enumerate() {
+            let text = text.split('#').next().unwrap_or_default();
+            let mut words = text.split_whitespace();
+            let keyword = match words.next() {
+                Some(keyword) => keyword,
+                None => continue,
+            };
+            if keyword == "name" {
+                table.name = words.collect::<Vec<_>>().join(" ");
+                named = !table.name.is_empty();
+                continue;
+            }
+            let error = ParseError::Line { line };
+            let side = match keyword {
+                "flipper" => match words.next() {
+                    Some("left") => Some(true),
+                    Some("right") => Some(false),
+                    _ => return Err(error),
+                },
+                _ => None,
+            };
+            let numbers = words
+                .map(|word| word.parse::<f32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ParseError::Line { line })?;
+            let point = |index: usize| Vec2::new(numbers[index], numbers[index + 1]);
+            match (keyword, numbers.len()) {
+                ("size", 2) => {
+                    table.size = point(0);
+                    sized = table.size.min_element() > 0.0;
+                }
+                ("plunger", 2) => {
+                    table.plunger = point(0);
+                    plunger = true;
+                }
+                ("wall", count) if count >= 4 && count % 2 == 0 => {
+                    for index in (0..count - 2).step_by(2) {
+                        table
+                            .walls
+                            .push(Segment::new(point(index), point(index + 2)));
+                    }
+                }
+                ("arc", 5) if numbers[2] > 0.0 && numbers[4] > numbers[3] => {
+                    let (from, to) = (numbers[3].to_radians(), numbers[4].to_radians());
+                    let steps = ((to - from) / std::f32::consts::FRAC_PI_2 * ARC_STEPS).ceil();
+                    let at = |step: f32| {
+                        let angle = from + (to - from) * step / steps;
+                        point(0) + Vec2::new(angle.cos(), angle.sin()) * numbers[2]
+                    };
+                    for step in 0..steps as usize {
+                        let step = step as f32;
+                        table.walls.push(Segment::new(at(step), at(step + 1.0)));
+                    }
+                }
+                ("gate", 4) => table.gates.push(Segment::new(point(0), point(2))),
+                ("bumper", 4) if numbers[2] > 0.0 => table.bumpers.push(Bumper {
+                    center: point(0),
+                    points: numbers[3] as u32,
+                    radius: numbers[2],
+                }),
+                ("sling", 5) => table.slings.push(Scorer {
+                    points: numbers[4] as u32,
+                    segment: Segment::new(point(0), point(2)),
+                }),
+                ("target", 5) => table.targets.push(Scorer {
+                    points: numbers[4] as u32,
+                    segment: Segment::new(point(0), point(2)),
+                }),
+                ("lock", 3) if numbers[2] > 0.0 => table.locks.push(Lock {
+                    center: point(0),
+                    radius: numbers[2],
+                }),
+                ("flipper", 3) if numbers[2] > 0.0 => table.flippers.push(FlipperSpec {
+                    left: side == Some(true),
+                    length: numbers[2],
+                    pivot: point(0),
+                }),
+                _ => return Err(error),
+            }
+        }
+        if !named {
+            return Err(ParseError::Missing("name"));
+        }
+        if !sized {
+            return Err(ParseError::Missing("size"));
+        }
+        if !plunger {
+            return Err(ParseError::Missing("plunger"));
+        }
+        if table.flippers.is_empty() {
+            return Err(ParseError::Missing("flipper"));
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_tables() {
+        let table = Table::parse(
+            "name Test Table\nsize 400 600\nplunger 180 -280 # in the lane\n\n\
+             wall -200 -300 -200 300 200 300\narc 0 0 100 0 90\ngate 0 0 10 0\n\
+             bumper 0 100 20 100\nsling -50 -100 -20 -150 10\ntarget 0 200 30 200 500\n\
+             lock 100 200 12\nflipper left -60 -250 70\nflipper right 60 -250 70",
+        )
+        .unwrap();
+        assert_eq!(table.name, "Test Table");
+        assert_eq!(table.size, Vec2::new(400.0, 600.0));
+        assert_eq!(table.plunger, Vec2::new(180.0, -280.0));
+        // Two walls for the line and eight for the quarter circle.
+        assert_eq!(table.walls.len(), 10);
+        let arc = &table.walls[2..];
+        assert!((arc[0].from - Vec2::new(100.0, 0.0)).length() < 0.001);
+        assert!((arc[7].to - Vec2::new(0.0, 100.0)).length() < 0.001);
+        assert_eq!(table.bumpers[0].points, 100);
+        assert_eq!(table.targets[0].segment.to, Vec2::new(30.0, 200.0));
+        assert_eq!((table.gates.len(), table.slings.len()), (1, 1));
+        assert_eq!(table.locks[0].radius, 12.0);
+        assert!(table.flippers[0].left && !table.flippers[1].left);
+    }
+
+    #[test]
+    fn rejects_broken_tables() {
+        let parse = |text: &str| Table::parse(text).unwrap_err();
+        assert_eq!(parse(""), ParseError::Missing("name"));
+        assert_eq!(parse("name A\nplunger 0 0"), ParseError::Missing("size"));
+        assert_eq!(
+            parse("name A\nsize 10 10\nplunger 0 0"),
+            ParseError::Missing("flipper")
+        );
+        assert_eq!(parse("name A\nwall 0 0 1"), ParseError::Line { line: 1 });
+        assert_eq!(parse("name A\n\nramp 0 0"), ParseError::Line { line: 2 });
+        assert_eq!(
+            parse("name A\nflipper up 0 0 50"),
+            ParseError::Line { line: 1 }
+        );
+        assert_eq!(
+            parse("name A\nbumper 0 x 20 5"),
+            ParseError::Line { line: 1 }
+        );
+    }
+
+    #[test]
+    fn bundled_table_is_valid() {
+        let table = Table::parse(include_str!("../assets/table.txt")).unwrap();
+        assert_eq!(table.flippers.len(), 2);
+        assert!(!table.targets.is_empty() && table.locks.len() >= 2);
+    }
+}
@@ -0,0 +1,324 @@
+//! The board: pipe pieces laid on a grid of cells, a source the fluid comes out of, and
+//! obstacles nothing can be laid on. Cells are (column, row), rows counting down from the top.
+
+use rand::{seq::SliceRandom, Rng};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Side {
+    pub const ALL: [Side; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// Cell offset of a step out through this side.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Right => Self::Left,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Piece {
+    Horizontal,
+    Vertical,
+    /// Straight through both ways; the fluid may pass it twice.
+    Cross,
+    UpRight,
+    RightDown,
+    DownLeft,
+    LeftUp,
+}
+
+impl Piece {
+    pub const ALL: [Piece; 7] = [
+        Self::Horizontal,
+        Self::Vertical,
+        Self::Cross,
+        Self::UpRight,
+        Self::RightDown,
+        Self::DownLeft,
+        Self::LeftUp,
+    ];
+
+    /// Where fluid coming in through `side` goes out, if the piece opens on that side at all.
+    pub fn exit(self, side: Side) -> Option<Side> {
+        if !self.opens(side) {
+            return None;
+        }
+        match self {
+            Self::Horizontal | Self::Vertical | Self::Cross => Some(side.opposite()),
+            _ => Side::ALL
+                .iter()
+                .copied()
+                .find(|other| *other != side && self.opens(*other)),
+        }
+    }
+
+    pub fn opens(self, side: Side) -> bool {
+        use Side::*;
+        match self {
+            Self::Horizontal => matches!(side, Left | Right),
+            Self::Vertical => matches!(side, Up | Down),
+            Self::Cross => true,
+            Self::UpRight => matches!(side, Up | Right),
+            Self::RightDown => matches!(side, Right | Down),
+            Self::DownLeft => matches!(side, Down | Left),
+            Self::LeftUp => matches!(side, Left | Up),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Obstacle,
+    Pipe(Piece),
+    /// Where the fluid comes from, out through the one side.
+    Source(Side),
+}
+
+/// What laying a piece did.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Placement {
+    Laid,
+    /// Swapped for a piece that was there, which costs points.
+    Replaced,
+    /// The cell takes no piece: off the grid, the source, an obstacle or already full.
+    Refused,
+}
+
+/// The fluid front: the cell it is filling and the side it came in through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Flow {
+    pub cell: (i32, i32),
+    pub entered: Option<Side>,
+    /// Pipes filled so far, counting a cross passed twice as two.
+    pub length: u32,
+}
+
+pub struct Grid {
+    pub columns: i32,
+    /// Sides of each cell the fluid has gone through.
+    filled: Vec<u8>,
+    pub rows: i32,
+    tiles: Vec<Tile>,
+}
+
+impl Grid {
+    pub fn new(columns: i32, rows: i32) -> Self {
+        let size = (columns * rows) as usize;
+        Self {
+            columns,
+            filled: vec![0; size],
+            rows,
+            tiles: vec![Tile::Empty; size],
+        }
+    }
+
+    /// A grid with the source somewhere inside, facing into the grid, and `obstacles` blocked
+    /// cells anywhere but right in front of it.
+    pub fn generate(columns: i32, rows: i32, obstacles: usize, rng: &mut impl Rng) -> Self {
+        let mut grid = Self::new(columns, rows);
+        let source = (rng.gen_range(1, columns - 1), rng.gen_range(1, rows - 1));
+        let side = *Side::ALL.choose(rng).unwrap();
+        grid.set(source, Tile::Source(side));
+        let (dx, dy) = side.offset();
+        let front = (source.0 + dx, source.1 + dy);
+        let mut free: Vec<(i32, i32)> = (0..rows)
+            .flat_map(|y| (0..columns).map(move |x| (x, y)))
+            .filter(|cell| *cell != source && *cell != front)
+            .collect();
+        free.shuffle(rng);
+        for cell in free.into_iter().take(obstacles) {
+            grid.set(cell, Tile::Obstacle);
+        }
+        grid
+    }
+
+    /// Moves the fluid on out of the cell it has filled. False if it spills: out of the grid,
+    /// into something that does not open towards it, or into a pipe it has been through.
+    pub fn advance(&mut self, flow: &mut Flow) -> bool {
+        let out = match (self.get(flow.cell), flow.entered) {
+            (Tile::Source(side), _) => side,
+            (Tile::Pipe(piece), Some(entered)) => match piece.exit(entered) {
+                Some(out) => out,
+                None => return false,
+            },
+            _ => return false,
+        };
+        let (dx, dy) = out.offset();
+        let next = (flow.cell.0 + dx, flow.cell.1 + dy);
+        let entered = out.opposite();
+        let exit = match self.get(next) {
+            Tile::Pipe(piece) => piece.exit(entered),
+            _ => None,
+        };
+        let exit = match exit {
+            Some(exit) if self.filled(next) & (entered.bit() | exit.bit()) == 0 => exit,
+            _ => return false,
+        };
+        let index = self.index(next);
+        self.filled[index] |= entered.bit() | exit.bit();
+        flow.cell = next;
+        flow.entered = Some(entered);
+        flow.length += 1;
+        true
+    }
+
+    pub fn contains(&self, (x, y): (i32, i32)) -> bool {
+        (0..self.columns).contains(&x) && (0..self.rows).contains(&y)
+    }
+
+    /// Sides of the cell the fluid has gone through.
+    pub fn filled(&self, cell: (i32, i32)) -> u8 {
+        if self.contains(cell) {
+            self.filled[self.index(cell)]
+        } else {
+            0
+        }
+    }
+
+    /// The fluid has gone through the cell on this side.
+    pub fn filled_on(&self, cell: (i32, i32), side: Side) -> bool {
+        self.filled(cell) & side.bit() != 0
+    }
+
+    /// Off the grid counts as an obstacle.
+    pub fn get(&self, cell: (i32, i32)) -> Tile {
+        if self.contains(cell) {
+            self.tiles[self.index(cell)]
+        } else {
+            Tile::Obstacle
+        }
+    }
+
+    /// Lays a piece, over one already there if the fluid has not reached it.
+    pub fn place(&mut self, cell: (i32, i32), piece: Piece) -> Placement {
+        let placement = match self.get(cell) {
+            Tile::Empty => Placement::Laid,
+            Tile::Pipe(_) if self.filled(cell) == 0 => Placement::Replaced,
+            _ => return Placement::Refused,
+        };
+        self.set(cell, Tile::Pipe(piece));
+        placement
+    }
+
+    /// Where the fluid starts, once it starts.
+    pub fn source(&self) -> Flow {
+        let index = self
+            .tiles
+            .iter()
+            .position(|tile| matches!(tile, Tile::Source(_)))
+            .unwrap_or(0) as i32;
+        Flow {
+            cell: (index % self.columns, index / self.columns),
+            entered: None,
+            length: 0,
+        }
+    }
+
+    fn index(&self, (x, y): (i32, i32)) -> usize {
+        (y * self.columns + x) as usize
+    }
+
+    fn set(&mut self, cell: (i32, i32), tile: Tile) {
+        let index = self.index(cell);
+        self.tiles[index] = tile;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn run(grid: &mut Grid) -> Flow {
+        let mut flow = grid.source();
+        while grid.advance(&mut flow) {}
+        flow
+    }
+
+    #[test]
+    fn pieces_turn_the_flow() {
+        assert_eq!(Piece::Horizontal.exit(Side::Left), Some(Side::Right));
+        assert_eq!(Piece::Horizontal.exit(Side::Up), None);
+        assert_eq!(Piece::Cross.exit(Side::Down), Some(Side::Up));
+        assert_eq!(Piece::UpRight.exit(Side::Up), Some(Side::Right));
+        assert_eq!(Piece::RightDown.exit(Side::Down), Some(Side::Right));
+        assert_eq!(Piece::LeftUp.exit(Side::Right), None);
+    }
+
+    #[test]
+    fn flows_until_it_spills() {
+        let mut grid = Grid::new(5, 3);
+        grid.set((0, 1), Tile::Source(Side::Right));
+        assert_eq!(grid.place((1, 1), Piece::Horizontal), Placement::Laid);
+        grid.place((2, 1), Piece::DownLeft);
+        grid.place((2, 2), Piece::Vertical);
+        assert_eq!(grid.place((2, 2), Piece::UpRight), Placement::Replaced);
+        grid.place((3, 2), Piece::Vertical);
+        let flow = run(&mut grid);
+        assert_eq!((flow.cell, flow.length), ((2, 2), 3));
+        assert!(grid.filled_on((2, 1), Side::Down));
+        assert_eq!(grid.place((1, 1), Piece::Cross), Placement::Refused);
+        assert_eq!(grid.place((0, 1), Piece::Cross), Placement::Refused);
+    }
+
+    #[test]
+    fn crosses_are_passed_twice_but_not_along_the_same_way() {
+        // Up from the source, round a loop and back through the cross sideways.
+        let mut grid = Grid::new(3, 4);
+        grid.set((1, 3), Tile::Source(Side::Up));
+        grid.place((1, 2), Piece::Cross);
+        grid.place((1, 1), Piece::Vertical);
+        grid.place((1, 0), Piece::DownLeft);
+        grid.place((0, 0), Piece::RightDown);
+        grid.place((0, 1), Piece::Vertical);
+        grid.place((0, 2), Piece::UpRight);
+        grid.place((2, 2), Piece::LeftUp);
+        grid.place((2, 1), Piece::DownLeft);
+        let flow = run(&mut grid);
+        assert_eq!((flow.cell, flow.length), ((2, 1), 9));
+        assert_eq!(grid.filled((1, 2)), 0b1111);
+    }
+
+    #[test]
+    fn generates_a_source_with_room_in_front() {
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..20 {
+            let mut grid = Grid::generate(6, 5, 20, &mut rng);
+            let flow = grid.source();
+            let side = match grid.get(flow.cell) {
+                Tile::Source(side) => side,
+                tile => panic!("no source but {:?}", tile),
+            };
+            let (dx, dy) = side.offset();
+            let front = (flow.cell.0 + dx, flow.cell.1 + dy);
+            assert_eq!(grid.get(front), Tile::Empty);
+            assert_eq!(grid.place(front, Piece::Cross), Placement::Laid);
+            let obstacles = grid.tiles.iter().filter(|tile| **tile == Tile::Obstacle);
+            assert_eq!(obstacles.count(), 20);
+        }
+    }
+}
@@ -0,0 +1,690 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use grid::{Flow, Grid, Piece, Placement, Side, Tile};
+use rand::{seq::SliceRandom, thread_rng};
+use std::collections::VecDeque;
+
+pub mod grid;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.09, 0.11);
+/// Side of a cell in pixels.
+const CELL: f32 = 64.0;
+const CELL_COLOR: Color = Color::rgb(0.2, 0.22, 0.26);
+const COLUMNS: i32 = 10;
+/// Points for the second pass through a cross.
+const CROSS_BONUS: u32 = 500;
+/// How many times faster the fluid runs once F is pressed.
+const FAST: f32 = 8.0;
+const FLUID_COLOR: Color = Color::rgb(0.2, 0.85, 0.35);
+const GAME: &str = "pipes";
+const GRID_CENTER: Vec2 = const_vec2!([60.0, -10.0]);
+const HOVER_COLOR: Color = Color::rgb(0.3, 0.33, 0.4);
+const LEADERBOARD_SIZE: usize = 5;
+const OBSTACLE_COLOR: Color = Color::rgb(0.45, 0.3, 0.22);
+const PIPE_COLOR: Color = Color::rgb(0.62, 0.66, 0.72);
+/// Points for each pipe the fluid fills.
+const PIPE_POINTS: u32 = 100;
+/// Thickness of a pipe in pixels; the fluid runs half as thick.
+const PIPE_WIDTH: f32 = 22.0;
+/// Where the next piece is shown, with the ones after it stacked above.
+const QUEUE_BOTTOM: Vec2 = const_vec2!([-330.0, -210.0]);
+const QUEUE_SIZE: usize = 5;
+/// Points lost for laying a piece over another.
+const REPLACE_PENALTY: u32 = 50;
+const ROWS: i32 = 7;
+const SOURCE_COLOR: Color = Color::rgb(0.25, 0.45, 0.75);
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+
+pub const WINDOW_HEIGHT: f32 = 560.0;
+pub const WINDOW_WIDTH: f32 = 800.0;
+
+/// The fluid front and the hovered cell, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+/// The cells, pipes and filled fluid, rebuilt whenever the grid changes.
+#[derive(Component)]
+struct BoardSprite;
+
+struct Game {
+    /// Seconds until the fluid starts.
+    countdown: f32,
+    /// The fluid was let go fast.
+    fast: bool,
+    flow: Flow,
+    grid: Grid,
+    level: u32,
+    /// How far through its cell the fluid front is, from 0 to 1.
+    progress: f32,
+    /// Pieces to lay, the next one first.
+    queue: VecDeque<Piece>,
+    /// The grid has changed since it was last drawn.
+    redraw: bool,
+    score: u32,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut game = Self {
+            countdown: 0.0,
+            fast: false,
+            flow: Grid::new(COLUMNS, ROWS).source(),
+            grid: Grid::new(COLUMNS, ROWS),
+            level: 1,
+            progress: 0.0,
+            queue: VecDeque::new(),
+            redraw: true,
+            score: 0,
+        };
+        game.start_level(1);
+        game
+    }
+
+    /// Seconds the fluid takes through one cell.
+    fn cell_seconds(&self) -> f32 {
+        (2.5 - 0.15 * (self.level - 1) as f32).max(0.8)
+    }
+
+    /// Pipes the fluid has to run through to clear the level.
+    fn distance(&self) -> u32 {
+        (6 + 2 * self.level).min(24)
+    }
+
+    fn start_level(&mut self, level: u32) {
+        let mut rng = thread_rng();
+        let obstacles = (2 * (level - 1)).min(14) as usize;
+        self.level = level;
+        self.grid = Grid::generate(COLUMNS, ROWS, obstacles, &mut rng);
+        self.flow = self.grid.source();
+        self.countdown = (20.0 - 2.0 * (level - 1) as f32).max(8.0);
+        self.fast = false;
+        self.progress = 0.0;
+        self.queue = (0..QUEUE_SIZE)
+            .map(|_| *Piece::ALL.choose(&mut rng).unwrap())
+            .collect();
+        self.redraw = true;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Ready,
+    Playing,
+    Cleared,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The level banner, or the result at the end.
+#[derive(Component)]
+struct MenuText;
+
+struct Sounds {
+    cleared: Handle<AudioSource>,
+    fill: Handle<AudioSource>,
+    over: Handle<AudioSource>,
+    place: Handle<AudioSource>,
+    refused: Handle<AudioSource>,
+    replace: Handle<AudioSource>,
+}
+
+pub struct PipesPlugin;
+
+impl Plugin for PipesPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Ready)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Ready).with_system(ready_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(place_input)
+                    .with_system(flow_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Cleared).with_system(cleared_enter))
+            .add_system_set(SystemSet::on_update(GameState::Cleared).with_system(next_input))
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(over_enter))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(next_input))
+            .add_system(actors_render)
+            .add_system(board_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Pipes".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The fluid front creeping through its cell, and the hovered cell while playing or the shade
+/// behind banners otherwise.
+fn actors_render(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() != GameState::Playing {
+        // Shades the grid behind the banner.
+        commands
+            .spawn_bundle(sprite(
+                Color::rgba(0.0, 0.0, 0.0, 0.7),
+                Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT),
+                Vec3::new(0.0, 0.0, 850.0),
+            ))
+            .insert(Actor);
+    } else if let Some(cell) = game_hud::cursor_world_position(&windows).and_then(cell_at) {
+        commands
+            .spawn_bundle(sprite(
+                HOVER_COLOR,
+                Vec2::splat(CELL - 2.0),
+                cell_center(cell).extend(0.5),
+            ))
+            .insert(Actor);
+    }
+
+    // The side the fluid came in through fills first, then the side it goes out through.
+    let flow = game.flow;
+    let center = cell_center(flow.cell);
+    let exit = match game.grid.get(flow.cell) {
+        Tile::Source(side) => Some(side),
+        Tile::Pipe(piece) => flow.entered.and_then(|side| piece.exit(side)),
+        _ => None,
+    };
+    let fill = if flow.entered.is_some() {
+        game.progress * 2.0
+    } else {
+        1.0 + game.progress
+    };
+    let half = CELL / 2.0;
+    if let Some(entered) = flow.entered {
+        let length = fill.min(1.0) * half;
+        commands
+            .spawn_bundle(arm(
+                center,
+                entered,
+                half - length,
+                half,
+                PIPE_WIDTH / 2.0,
+                FLUID_COLOR,
+                3.0,
+            ))
+            .insert(Actor);
+    }
+    if let Some(exit) = exit.filter(|_| fill > 1.0) {
+        let from = -PIPE_WIDTH / 4.0;
+        let to = from + (fill - 1.0).min(1.0) * (half - from);
+        commands
+            .spawn_bundle(arm(
+                center,
+                exit,
+                from,
+                to,
+                PIPE_WIDTH / 2.0,
+                FLUID_COLOR,
+                3.0,
+            ))
+            .insert(Actor);
+    }
+}
+
+/// A stretch of pipe out from `center` towards `side`, from `from` to `to` pixels off center.
+fn arm(
+    center: Vec2,
+    side: Side,
+    from: f32,
+    to: f32,
+    width: f32,
+    color: Color,
+    z: f32,
+) -> SpriteBundle {
+    let (dx, dy) = side.offset();
+    let direction = Vec2::new(dx as f32, -dy as f32);
+    let length = (to - from).max(0.0);
+    let size = if dx == 0 {
+        Vec2::new(width, length)
+    } else {
+        Vec2::new(length, width)
+    };
+    sprite(
+        color,
+        size,
+        (center + direction * (from + to) / 2.0).extend(z),
+    )
+}
+
+/// The cells, the pipes laid on them and the fluid that has gone through.
+fn board_render(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    sprites: Query<Entity, With<BoardSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let grid = &game.grid;
+    let flow = game.flow;
+    let half = CELL / 2.0;
+    for y in 0..grid.rows {
+        for x in 0..grid.columns {
+            let cell = (x, y);
+            let center = cell_center(cell);
+            let tile = grid.get(cell);
+            let color = if tile == Tile::Obstacle {
+                OBSTACLE_COLOR
+            } else {
+                CELL_COLOR
+            };
+            let mut bundles = vec![sprite(color, Vec2::splat(CELL - 2.0), center.extend(0.0))];
+            match tile {
+                Tile::Pipe(piece) => {
+                    bundles.extend(piece_sprites(piece, center, CELL));
+                    // The front's own pass is drawn creeping in by `actors_render`.
+                    let passing = match flow.entered {
+                        Some(entered) if flow.cell == cell => {
+                            vec![Some(entered), piece.exit(entered)]
+                        }
+                        _ => Vec::new(),
+                    };
+                    for side in Side::ALL {
+                        if grid.filled_on(cell, side) && !passing.contains(&Some(side)) {
+                            let from = -PIPE_WIDTH / 4.0;
+                            bundles.push(arm(
+                                center,
+                                side,
+                                from,
+                                half,
+                                PIPE_WIDTH / 2.0,
+                                FLUID_COLOR,
+                                2.0,
+                            ));
+                        }
+                    }
+                }
+                Tile::Source(side) => {
+                    bundles.push(sprite(
+                        SOURCE_COLOR,
+                        Vec2::splat(CELL * 0.6),
+                        center.extend(1.0),
+                    ));
+                    bundles.push(arm(center, side, 0.0, half, PIPE_WIDTH, PIPE_COLOR, 1.0));
+                    if flow.cell != cell {
+                        bundles.push(arm(
+                            center,
+                            side,
+                            0.0,
+                            half,
+                            PIPE_WIDTH / 2.0,
+                            FLUID_COLOR,
+                            2.0,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+            for bundle in bundles {
+                commands.spawn_bundle(bundle).insert(BoardSprite);
+            }
+        }
+    }
+    for (index, piece) in game.queue.iter().enumerate() {
+        let center = QUEUE_BOTTOM + Vec2::new(0.0, index as f32 * CELL * 1.1);
+        let color = if index == 0 { HOVER_COLOR } else { CELL_COLOR };
+        commands
+            .spawn_bundle(sprite(color, Vec2::splat(CELL - 2.0), center.extend(0.0)))
+            .insert(BoardSprite);
+        for bundle in piece_sprites(*piece, center, CELL) {
+            commands.spawn_bundle(bundle).insert(BoardSprite);
+        }
+    }
+}
+
+/// The cell under a point of the window, if on the grid.
+fn cell_at(point: Vec2) -> Option<(i32, i32)> {
+    let corner = GRID_CENTER + Vec2::new(-(COLUMNS as f32), ROWS as f32) * CELL / 2.0;
+    let x = ((point.x - corner.x) / CELL).floor() as i32;
+    let y = ((corner.y - point.y) / CELL).floor() as i32;
+    if (0..COLUMNS).contains(&x) && (0..ROWS).contains(&y) {
+        Some((x, y))
+    } else {
+        None
+    }
+}
+
+fn cell_center((x, y): (i32, i32)) -> Vec2 {
+    GRID_CENTER
+        + Vec2::new(
+            (x as f32 + 0.5 - COLUMNS as f32 / 2.0) * CELL,
+            (ROWS as f32 / 2.0 - y as f32 - 0.5) * CELL,
+        )
+}
+
+fn cleared_enter(audio: Res<Audio>, sounds: Res<Sounds>) {
+    audio.play(sounds.cleared.clone());
+}
+
+/// Counts down, then moves the fluid on a cell at a time. Once it spills the level is over:
+/// cleared if it ran far enough.
+fn flow_update(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    if keyboard_input.just_pressed(KeyCode::F) {
+        game.fast = true;
+        game.countdown = 0.0;
+    }
+    let mut delta = time.delta_seconds();
+    if game.countdown > 0.0 {
+        game.countdown -= delta;
+        if game.countdown > 0.0 {
+            return;
+        }
+        delta = -game.countdown;
+        game.countdown = 0.0;
+    }
+    let speed = if game.fast { FAST } else { 1.0 };
+    game.progress += delta * speed / game.cell_seconds();
+    while game.progress >= 1.0 {
+        game.progress -= 1.0;
+        game.redraw = true;
+        if !game.grid.advance(&mut game.flow) {
+            game.progress = 1.0;
+            if game.flow.length >= game.distance() {
+                state.set(GameState::Cleared).unwrap();
+            } else {
+                state.set(GameState::Over).unwrap();
+            }
+            return;
+        }
+        let points = if game.grid.filled(game.flow.cell) == 0b1111 {
+            PIPE_POINTS + CROSS_BONUS
+        } else {
+            PIPE_POINTS
+        };
+        game.score += if game.fast { points * 2 } else { points };
+        audio.play(sounds.fill.clone());
+    }
+}
+
+/// Enter moves on to the next level once one is cleared, or to a new game once it is over.
+fn next_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard_input.clear_just_pressed(KeyCode::Return);
+    if *state.current() == GameState::Over {
+        *game = Game::new();
+    } else {
+        let level = game.level + 1;
+        game.start_level(level);
+    }
+    state.set(GameState::Ready).unwrap();
+}
+
+fn over_enter(
+    game: Res<Game>,
+    mut scores: ResMut<Scores>,
+    platform: Res<Platform>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.over.clone());
+    if scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// The sprites of a pipe piece centered on `center` in a cell `size` wide.
+fn piece_sprites(piece: Piece, center: Vec2, size: f32) -> Vec<SpriteBundle> {
+    Side::ALL
+        .iter()
+        .filter(|side| piece.opens(**side))
+        .map(|side| {
+            arm(
+                center,
+                *side,
+                -PIPE_WIDTH / 2.0,
+                size / 2.0,
+                PIPE_WIDTH,
+                PIPE_COLOR,
+                1.0,
+            )
+        })
+        .collect()
+}
+
+/// A click lays the next piece from the queue on the cell, over any piece the fluid has not
+/// reached yet.
+fn place_input(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let cell = match game_hud::cursor_world_position(&windows).and_then(cell_at) {
+        Some(cell) => cell,
+        None => return,
+    };
+    let piece = game.queue[0];
+    match game.grid.place(cell, piece) {
+        Placement::Laid => audio.play(sounds.place.clone()),
+        Placement::Replaced => {
+            game.score = game.score.saturating_sub(REPLACE_PENALTY);
+            audio.play(sounds.replace.clone());
+        }
+        Placement::Refused => {
+            audio.play(sounds.refused.clone());
+            return;
+        }
+    }
+    game.queue.pop_front();
+    game.queue
+        .push_back(*Piece::ALL.choose(&mut thread_rng()).unwrap());
+    game.redraw = true;
+}
+
+/// Enter starts the level.
+fn ready_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        cleared: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35).fade(),
+        ])),
+        fill: audio_sources.add(
+            Tone::new(Waveform::Sine, 220.0, 0.12)
+                .slide(330.0)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.3).fade().volume(0.3),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        place: audio_sources.add(Tone::new(Waveform::Square, 660.0, 0.04).volume(0.1).into()),
+        refused: audio_sources.add(Tone::new(Waveform::Square, 110.0, 0.12).volume(0.15).into()),
+        replace: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.06).volume(0.2),
+            Tone::new(Waveform::Square, 660.0, 0.04).volume(0.1),
+        ])),
+    });
+}
+
+fn sprite(color: Color, size: Vec2, translation: Vec3) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(translation),
+        ..Default::default()
+    }
+}
+
+fn texts_update(
+    game: Res<Game>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let flow = if game.countdown > 0.0 {
+        format!("Flow in {:.0}s", game.countdown.ceil())
+    } else {
+        "Flowing".to_string()
+    };
+    let hud = format!(
+        "Score {}   High {}   Level {}   Distance {} of {}   {}",
+        game.score,
+        scores
+            .best("")
+            .map_or(0, |entry| entry.score)
+            .max(game.score),
+        game.level,
+        game.flow.length,
+        game.distance(),
+        flow
+    );
+    let (help, banner) = match state.current() {
+        GameState::Ready => (
+            "Enter: start".to_string(),
+            format!(
+                "LEVEL {}\n\nRun the fluid through {} pipes",
+                game.level,
+                game.distance()
+            ),
+        ),
+        GameState::Playing => (
+            "Click: lay the next piece   F: let the fluid go fast for double points".to_string(),
+            String::new(),
+        ),
+        GameState::Cleared => (
+            "Enter: next level".to_string(),
+            format!(
+                "LEVEL {} CLEARED\n\nThe fluid ran through {} pipes",
+                game.level, game.flow.length
+            ),
+        ),
+        GameState::Over => {
+            let scores = scores
+                .table("")
+                .iter()
+                .take(LEADERBOARD_SIZE)
+                .enumerate()
+                .map(|(place, entry)| format!("{}. {:>7}", place + 1, entry.score))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Enter: new game".to_string(),
+                format!(
+                    "SPILLED AFTER {} OF {} PIPES\n\nScore {}\n\n{}",
+                    game.flow.length,
+                    game.distance(),
+                    game.score,
+                    scores
+                ),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
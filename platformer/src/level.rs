@@ -0,0 +1,245 @@
+//! Levels are text files with one character per tile, see the readme for the symbols.
+//! Positions are in tiles with the origin at the bottom left corner of the level and y pointing up.
+
+use bevy::math::Vec2;
+
+/// Tiles per second.
+const PLATFORM_SPEED: f32 = 3.0;
+/// Moving platforms are the top slice of their tile.
+pub const PLATFORM_THICKNESS: f32 = 0.4;
+/// Spikes fill the lower part of their tile.
+pub const SPIKES_HEIGHT: f32 = 0.45;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Empty,
+    Solid,
+    Spikes,
+    /// Invisible; turns moving platforms around.
+    Stop,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Platform {
+    /// Unit vector of the current direction of travel.
+    pub direction: Vec2,
+    /// Bottom left corner of the tile row or column the platform occupies.
+    pub position: Vec2,
+    pub width: f32,
+}
+
+impl Platform {
+    /// The walkable top surface.
+    pub fn top(&self) -> f32 {
+        self.position.y + 1.0
+    }
+
+    /// Moves along its path and turns around at solid or stop tiles; returns how far it moved.
+    pub fn update(&mut self, level: &Level, delta: f32) -> Vec2 {
+        let step = self.direction * PLATFORM_SPEED * delta;
+        let next = self.position + step;
+        let blocked = level.any_tile(next, next + Vec2::new(self.width, 1.0), |tile| {
+            matches!(tile, Tile::Solid | Tile::Stop)
+        });
+        if blocked {
+            self.direction = -self.direction;
+            Vec2::ZERO
+        } else {
+            self.position = next;
+            step
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Level {
+    pub checkpoints: Vec<Vec2>,
+    /// Centers of the coins.
+    pub coins: Vec<Vec2>,
+    pub exit: Vec2,
+    pub height: i32,
+    pub platforms: Vec<Platform>,
+    /// Where the player's feet are placed at the start.
+    pub start: Vec2,
+    tiles: Vec<Tile>,
+    pub width: i32,
+}
+
+impl Level {
+    pub fn parse(layout: &str) -> Self {
+        let lines: Vec<&str> = layout.lines().filter(|line| !line.is_empty()).collect();
+        let height = lines.len() as i32;
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+        let mut level = Self {
+            checkpoints: Vec::new(),
+            coins: Vec::new(),
+            exit: Vec2::ZERO,
+            height,
+            platforms: Vec::new(),
+            start: Vec2::ZERO,
+            tiles: vec![Tile::Empty; (width * height) as usize],
+            width,
+        };
+        for (row, line) in lines.iter().enumerate() {
+            let y = height - 1 - row as i32;
+            let mut platform: Option<Platform> = None;
+            for (x, symbol) in line.chars().enumerate() {
+                let x = x as i32;
+                let corner = Vec2::new(x as f32, y as f32);
+                let center = corner + Vec2::splat(0.5);
+                let direction = match symbol {
+                    '>' => Some(Vec2::X),
+                    'v' => Some(-Vec2::Y),
+                    _ => None,
+                };
+                // Neighbouring platform symbols of the same kind form one wider platform.
+                match (&mut platform, direction) {
+                    (Some(current), Some(direction)) if current.direction == direction => {
+                        current.width += 1.0;
+                        continue;
+                    }
+                    _ => {
+                        level.platforms.extend(platform.take());
+                        platform = direction.map(|direction| Platform {
+                            direction,
+                            position: corner,
+                            width: 1.0,
+                        });
+                    }
+                }
+                let index = (y * width + x) as usize;
+                match symbol {
+                    '#' => level.tiles[index] = Tile::Solid,
+                    '^' => level.tiles[index] = Tile::Spikes,
+                    '|' => level.tiles[index] = Tile::Stop,
+                    '*' => level.coins.push(center),
+                    'C' => level.checkpoints.push(Vec2::new(center.x, corner.y)),
+                    'E' => level.exit = center,
+                    'S' => level.start = Vec2::new(center.x, corner.y),
+                    _ => {}
+                }
+            }
+            level.platforms.extend(platform);
+        }
+        level
+    }
+
+    /// Whether any tile overlapping the rectangle from `min` to `max` matches; outside the level is empty.
+    pub fn any_tile(&self, min: Vec2, max: Vec2, matches: impl Fn(Tile) -> bool) -> bool {
+        // Touching a tile edge is not overlapping it.
+        const EPSILON: f32 = 0.001;
+        let (first_x, last_x) = (
+            (min.x + EPSILON).floor() as i32,
+            (max.x - EPSILON).floor() as i32,
+        );
+        let (first_y, last_y) = (
+            (min.y + EPSILON).floor() as i32,
+            (max.y - EPSILON).floor() as i32,
+        );
+        (first_y..=last_y).any(|y| (first_x..=last_x).any(|x| matches(self.tile(x, y))))
+    }
+
+    /// The sides and the bottom of the level are open; falling out of it is deadly.
+    pub fn tile(&self, x: i32, y: i32) -> Tile {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            Tile::Empty
+        } else {
+            self.tiles[(y * self.width + x) as usize]
+        }
+    }
+
+    /// Whether the rectangle from `min` to `max` touches the spiky part of a spikes tile.
+    pub fn touches_spikes(&self, min: Vec2, max: Vec2) -> bool {
+        let (first_x, last_x) = (min.x.floor() as i32, max.x.floor() as i32);
+        let (first_y, last_y) = (min.y.floor() as i32, max.y.floor() as i32);
+        (first_y..=last_y).any(|y| {
+            (first_x..=last_x).any(|x| {
+                self.tile(x, y) == Tile::Spikes
+                    && min.x < x as f32 + 0.9
+                    && max.x > x as f32 + 0.1
+                    && min.y < y as f32 + SPIKES_HEIGHT
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = "
+#     E  #
+#  >>> | #
+#S * C^^ #
+##########
+";
+
+    #[test]
+    fn parses_symbols_bottom_up() {
+        let level = Level::parse(LAYOUT);
+        assert_eq!((level.width, level.height), (10, 4));
+        assert_eq!(level.tile(0, 0), Tile::Solid);
+        assert_eq!(level.tile(7, 1), Tile::Spikes);
+        assert_eq!(level.tile(7, 2), Tile::Stop);
+        assert_eq!(level.tile(-1, 0), Tile::Empty);
+        assert_eq!(level.start, Vec2::new(1.5, 1.0));
+        assert_eq!(level.coins, vec![Vec2::new(3.5, 1.5)]);
+        assert_eq!(level.checkpoints, vec![Vec2::new(5.5, 1.0)]);
+        assert_eq!(level.exit, Vec2::new(6.5, 3.5));
+        assert_eq!(
+            level.platforms,
+            vec![Platform {
+                direction: Vec2::X,
+                position: Vec2::new(3.0, 2.0),
+                width: 3.0,
+            }]
+        );
+    }
+
+    #[test]
+    fn platforms_turn_around_at_stops() {
+        let mut level = Level::parse(LAYOUT);
+        let mut platform = level.platforms.remove(0);
+        let mut turns = 0;
+        for _ in 0..100 {
+            let direction = platform.direction;
+            platform.update(&level, 0.05);
+            if platform.direction != direction {
+                turns += 1;
+            }
+            // Between the wall on the left and the stop on the right.
+            assert!(platform.position.x > 0.99 && platform.position.x + platform.width < 7.01);
+        }
+        assert!(turns >= 3);
+        assert_eq!(platform.position.y, 2.0);
+    }
+
+    #[test]
+    fn spikes_only_hurt_near_the_ground() {
+        let level = Level::parse(LAYOUT);
+        assert!(level.touches_spikes(Vec2::new(6.2, 1.0), Vec2::new(6.9, 1.9)));
+        assert!(!level.touches_spikes(Vec2::new(6.2, 1.5), Vec2::new(6.9, 2.4)));
+        assert!(!level.touches_spikes(Vec2::new(5.0, 1.0), Vec2::new(5.95, 1.9)));
+    }
+
+    #[test]
+    fn bundled_levels_are_complete() {
+        for layout in [
+            include_str!("../assets/levels/1.txt"),
+            include_str!("../assets/levels/2.txt"),
+            include_str!("../assets/levels/3.txt"),
+        ] {
+            let level = Level::parse(layout);
+            assert_eq!(level.height, 16);
+            assert_ne!(level.start, Vec2::ZERO);
+            assert_ne!(level.exit, Vec2::ZERO);
+            assert!(!level.coins.is_empty());
+            let (x, y) = (level.start.x as i32, level.start.y as i32);
+            assert_eq!(
+                level.tile(x, y - 1),
+                Tile::Solid,
+                "the start is on the ground"
+            );
+        }
+    }
+}
@@ -0,0 +1,555 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use level::{Level, Platform, Tile, PLATFORM_THICKNESS, SPIKES_HEIGHT};
+use physics::{Controls, Player, PLAYER_SIZE};
+
+pub mod level;
+pub mod physics;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.45, 0.7, 0.9);
+const CHECKPOINT_ACTIVE_COLOR: Color = Color::rgb(0.2, 0.9, 0.3);
+const CHECKPOINT_COLOR: Color = Color::rgb(0.85, 0.2, 0.2);
+const COIN_COLOR: Color = Color::rgb(1.0, 0.85, 0.15);
+const EXIT_COLOR: Color = Color::rgb(0.35, 0.2, 0.1);
+/// Below the bottom of the level by this many tiles, the player is lost.
+const FALL_LIMIT: f32 = 2.0;
+const HUD_COLOR: Color = Color::rgb(0.05, 0.05, 0.1);
+const HUD_HEIGHT: f32 = 40.0;
+const LEVELS: [&str; 3] = [
+    include_str!("../assets/levels/1.txt"),
+    include_str!("../assets/levels/2.txt"),
+    include_str!("../assets/levels/3.txt"),
+];
+/// Long frames are cut short so the player cannot fall through the floor after a hiccup.
+const MAX_STEP: f32 = 1.0 / 30.0;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.7);
+const OVERLAY_TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const PLATFORM_COLOR: Color = Color::rgb(0.55, 0.35, 0.2);
+const PLAYER_COLOR: Color = Color::rgb(0.9, 0.25, 0.3);
+const SOLID_COLOR: Color = Color::rgb(0.3, 0.5, 0.25);
+const SPIKES_COLOR: Color = Color::rgb(0.75, 0.75, 0.8);
+const TILE_SIZE: f32 = 32.0;
+
+pub const WINDOW_HEIGHT: f32 = 16.0 * TILE_SIZE + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = 960.0;
+
+#[derive(Component)]
+struct Checkpoint(usize);
+
+#[derive(Component)]
+struct Coin(usize);
+
+/// The current level and everything that happened on it so far.
+struct Game {
+    /// Index into `checkpoints` of the level, `None` while respawning at the start.
+    checkpoint: Option<usize>,
+    /// Per coin of the level, whether it was picked up; collected coins stay collected after a death.
+    collected: Vec<bool>,
+    deaths: u32,
+    level: Level,
+    level_index: usize,
+    /// The moving platforms, taken out of the level so they can move while it is read.
+    platforms: Vec<Platform>,
+    player: Player,
+    time: f32,
+    /// Totals of the levels already completed.
+    totals: Totals,
+}
+
+impl Game {
+    fn new(level_index: usize, totals: Totals) -> Self {
+        let mut level = Level::parse(LEVELS[level_index]);
+        Self {
+            platforms: std::mem::take(&mut level.platforms),
+            checkpoint: None,
+            collected: vec![false; level.coins.len()],
+            deaths: 0,
+            player: Player::new(level.start),
+            level,
+            level_index,
+            time: 0.0,
+            totals,
+        }
+    }
+
+    fn respawn_point(&self) -> Vec2 {
+        self.checkpoint
+            .map_or(self.level.start, |index| self.level.checkpoints[index])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    LevelComplete,
+}
+
+#[derive(Component)]
+struct HudText;
+
+/// The 2D camera following the player, as opposed to the UI camera.
+#[derive(Component)]
+struct MainCamera;
+
+/// Everything spawned for the current level, removed when it ends.
+#[derive(Component)]
+struct LevelEntity;
+
+#[derive(Component)]
+struct OverlayText;
+
+#[derive(Component)]
+struct PlatformSprite(usize);
+
+#[derive(Component)]
+struct PlayerSprite;
+
+struct Sounds {
+    checkpoint: Handle<AudioSource>,
+    coin: Handle<AudioSource>,
+    complete: Handle<AudioSource>,
+    death: Handle<AudioSource>,
+    jump: Handle<AudioSource>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Totals {
+    coins: usize,
+    deaths: u32,
+    time: f32,
+}
+
+pub struct PlatformerPlugin;
+
+impl Plugin for PlatformerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(0, Totals::default()))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(level_spawn))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_movement.label("movement"))
+                    .with_system(pickups.after("movement"))
+                    .with_system(keyboard_input),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::LevelComplete).with_system(level_complete),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::LevelComplete).with_system(level_complete_input),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::LevelComplete)
+                    .with_system(despawn_all::<LevelEntity>)
+                    .with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(sprites_sync.after("movement"))
+            .add_system(camera_follow.after("movement"))
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Platformer".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Keeps the player in view without showing anything beyond the edges of the level.
+fn camera_follow(game: Res<Game>, mut cameras: Query<&mut Transform, With<MainCamera>>) {
+    let view = Vec2::new(WINDOW_WIDTH, WINDOW_HEIGHT - HUD_HEIGHT);
+    let extent = Vec2::new(game.level.width as f32, game.level.height as f32) * TILE_SIZE;
+    let target = (game.player.position + PLAYER_SIZE / 2.0) * TILE_SIZE;
+    let follow = |target: f32, view: f32, extent: f32| {
+        if extent <= view {
+            extent / 2.0
+        } else {
+            target.max(view / 2.0).min(extent - view / 2.0)
+        }
+    };
+    let center = Vec2::new(
+        follow(target.x, view.x, extent.x),
+        // The HUD covers the top of the window.
+        follow(target.y, view.y, extent.y) + HUD_HEIGHT / 2.0,
+    );
+    for mut transform in cameras.iter_mut() {
+        transform.translation.x = center.x;
+        transform.translation.y = center.y;
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn hud_update(game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    let collected = game.collected.iter().filter(|coin| **coin).count();
+    let value = format!(
+        "LEVEL {}/{}   COINS {}/{}   DEATHS {}   TIME {:.1}",
+        game.level_index + 1,
+        LEVELS.len(),
+        collected,
+        game.collected.len(),
+        game.deaths,
+        game.time,
+    );
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+/// Going back to the last checkpoint counts as a death, so getting stuck never ends a run.
+fn keyboard_input(keyboard_input: Res<Input<KeyCode>>, mut game: ResMut<Game>) {
+    if keyboard_input.just_pressed(KeyCode::R) {
+        game.deaths += 1;
+        game.player = Player::new(game.respawn_point());
+    }
+}
+
+/// Shows the results of the level; after the last level, the totals of the whole run.
+fn level_complete(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut game: ResMut<Game>,
+    cameras: Query<&Transform, With<MainCamera>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.complete.clone());
+    let collected = game.collected.iter().filter(|coin| **coin).count();
+    game.totals.coins += collected;
+    game.totals.deaths += game.deaths;
+    game.totals.time += game.time;
+    let last = game.level_index + 1 == LEVELS.len();
+    let mut message = format!(
+        "Level {} complete\n\nCoins {}/{}   Deaths {}   Time {:.1}s",
+        game.level_index + 1,
+        collected,
+        game.collected.len(),
+        game.deaths,
+        game.time,
+    );
+    if last {
+        message.push_str(&format!(
+            "\n\nAll levels done!\nCoins {}   Deaths {}   Time {:.1}s\n\nEnter: play again",
+            game.totals.coins, game.totals.deaths, game.totals.time,
+        ));
+    } else {
+        message.push_str("\n\nEnter: next level");
+    }
+    let center = cameras
+        .iter()
+        .next()
+        .map_or(Vec2::ZERO, |transform| transform.translation.truncate());
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, if last { 300.0 } else { 180.0 })),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            message,
+            26.0,
+            OVERLAY_TEXT_COLOR,
+            center.extend(100.0),
+        ))
+        .insert(OverlayText);
+}
+
+fn level_complete_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        *game = if game.level_index + 1 < LEVELS.len() {
+            Game::new(game.level_index + 1, game.totals)
+        } else {
+            Game::new(0, Totals::default())
+        };
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Spawns the tiles, coins, checkpoints, exit, moving platforms and the player of the level.
+fn level_spawn(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let level = &game.level;
+    for y in 0..level.height {
+        for x in 0..level.width {
+            let corner = Vec2::new(x as f32, y as f32);
+            let bundle = match level.tile(x, y) {
+                Tile::Solid => sprite(SOLID_COLOR, Vec2::ONE, corner + Vec2::splat(0.5)),
+                Tile::Spikes => sprite(
+                    SPIKES_COLOR,
+                    Vec2::new(0.8, SPIKES_HEIGHT),
+                    corner + Vec2::new(0.5, SPIKES_HEIGHT / 2.0),
+                ),
+                Tile::Empty | Tile::Stop => continue,
+            };
+            commands.spawn_bundle(bundle).insert(LevelEntity);
+        }
+    }
+    commands
+        .spawn_bundle(sprite(
+            EXIT_COLOR,
+            Vec2::new(0.9, 1.6),
+            level.exit + Vec2::new(0.0, 0.3),
+        ))
+        .insert(LevelEntity);
+    for (index, checkpoint) in level.checkpoints.iter().enumerate() {
+        commands
+            .spawn_bundle(sprite(
+                OVERLAY_TEXT_COLOR,
+                Vec2::new(0.08, 1.5),
+                *checkpoint + Vec2::new(-0.05, 0.75),
+            ))
+            .insert(LevelEntity);
+        commands
+            .spawn_bundle(sprite(
+                CHECKPOINT_COLOR,
+                Vec2::new(0.5, 0.3),
+                *checkpoint + Vec2::new(0.2, 1.35),
+            ))
+            .insert(Checkpoint(index))
+            .insert(LevelEntity);
+    }
+    for (index, platform) in game.platforms.iter().enumerate() {
+        commands
+            .spawn_bundle(sprite(
+                PLATFORM_COLOR,
+                Vec2::new(platform.width, PLATFORM_THICKNESS),
+                platform_center(platform),
+            ))
+            .insert(PlatformSprite(index))
+            .insert(LevelEntity);
+    }
+    for (index, coin) in level.coins.iter().enumerate() {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                "\u{25CF}",
+                TILE_SIZE * 0.7,
+                COIN_COLOR,
+                (*coin * TILE_SIZE).extend(1.0),
+            ))
+            .insert(Coin(index))
+            .insert(LevelEntity);
+    }
+    commands
+        .spawn_bundle(sprite(
+            PLAYER_COLOR,
+            PLAYER_SIZE,
+            game.player.position + Vec2::new(0.0, PLAYER_SIZE.y / 2.0),
+        ))
+        .insert(PlayerSprite)
+        .insert(LevelEntity);
+}
+
+/// Coins, checkpoints, hazards and the exit the player touches after moving.
+fn pickups(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    coins: Query<(Entity, &Coin)>,
+    mut flags: Query<(&Checkpoint, &mut Sprite)>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let center = game.player.position + Vec2::new(0.0, PLAYER_SIZE.y / 2.0);
+    let touches = |point: Vec2, reach: Vec2| {
+        let offset = (point - center).abs();
+        offset.x < reach.x && offset.y < reach.y
+    };
+    for (entity, Coin(index)) in coins.iter() {
+        if touches(
+            game.level.coins[*index],
+            PLAYER_SIZE / 2.0 + Vec2::splat(0.3),
+        ) {
+            game.collected[*index] = true;
+            commands.entity(entity).despawn_recursive();
+            audio.play(sounds.coin.clone());
+        }
+    }
+    let reached = (0..game.level.checkpoints.len()).find(|index| {
+        Some(*index) != game.checkpoint
+            && touches(
+                game.level.checkpoints[*index] + Vec2::new(0.0, 0.75),
+                PLAYER_SIZE / 2.0 + Vec2::new(0.1, 0.5),
+            )
+    });
+    if let Some(index) = reached {
+        game.checkpoint = Some(index);
+        audio.play(sounds.checkpoint.clone());
+        for (Checkpoint(flag), mut sprite) in flags.iter_mut() {
+            sprite.color = if *flag == index {
+                CHECKPOINT_ACTIVE_COLOR
+            } else {
+                CHECKPOINT_COLOR
+            };
+        }
+    }
+    let (min, max) = (game.player.min(), game.player.max());
+    if game.level.touches_spikes(min, max) || max.y < -FALL_LIMIT {
+        game.deaths += 1;
+        game.player = Player::new(game.respawn_point());
+        audio.play(sounds.death.clone());
+        return;
+    }
+    if touches(game.level.exit, PLAYER_SIZE / 2.0 + Vec2::new(0.2, 0.5)) {
+        state.set(GameState::LevelComplete).unwrap();
+    }
+}
+
+fn platform_center(platform: &Platform) -> Vec2 {
+    Vec2::new(
+        platform.position.x + platform.width / 2.0,
+        platform.top() - PLATFORM_THICKNESS / 2.0,
+    )
+}
+
+fn player_movement(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let delta = time.delta_seconds().min(MAX_STEP);
+    let mut direction = 0.0;
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        direction -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        direction += 1.0;
+    }
+    let jump_keys = [KeyCode::Space, KeyCode::Up, KeyCode::W, KeyCode::Z];
+    let controls = Controls {
+        direction,
+        jump_held: keyboard_input.any_pressed(jump_keys),
+        jump_pressed: keyboard_input.any_just_pressed(jump_keys),
+    };
+    let game = &mut *game;
+    game.time += delta;
+    let carried: Vec<Vec2> = game
+        .platforms
+        .iter_mut()
+        .map(|platform| platform.update(&game.level, delta))
+        .collect();
+    if game
+        .player
+        .update(controls, &game.level, &game.platforms, &carried, delta)
+    {
+        audio.play(sounds.jump.clone());
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MainCamera);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Arrows/AD: run   Space/Up/W: jump (hold for higher)   R: back to checkpoint",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            top: Val::Px(12.0),
+            right: Val::Px(10.0),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        checkpoint: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.08).volume(0.2),
+            Tone::new(Waveform::Square, 784.0, 0.15).volume(0.2),
+        ])),
+        coin: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 988.0, 0.05).volume(0.2),
+            Tone::new(Waveform::Square, 1319.0, 0.12).volume(0.2),
+        ])),
+        complete: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35),
+        ])),
+        death: audio_sources.add(
+            Tone::new(Waveform::Square, 392.0, 0.4)
+                .slide(98.0)
+                .volume(0.25)
+                .into(),
+        ),
+        jump: audio_sources.add(
+            Tone::new(Waveform::Square, 330.0, 0.12)
+                .slide(660.0)
+                .volume(0.15)
+                .into(),
+        ),
+    });
+}
+
+/// A plain rectangle; sizes and positions are in tiles.
+fn sprite(color: Color, size: Vec2, center: Vec2) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size * TILE_SIZE),
+            ..Default::default()
+        },
+        transform: Transform::from_translation((center * TILE_SIZE).extend(0.0)),
+        ..Default::default()
+    }
+}
+
+/// Moves the sprites of the player and the moving platforms to where the simulation has them.
+fn sprites_sync(
+    game: Res<Game>,
+    mut player: Query<&mut Transform, With<PlayerSprite>>,
+    mut platforms: Query<(&PlatformSprite, &mut Transform), Without<PlayerSprite>>,
+) {
+    for mut transform in player.iter_mut() {
+        let center = game.player.position + Vec2::new(0.0, PLAYER_SIZE.y / 2.0);
+        transform.translation = (center * TILE_SIZE).extend(2.0);
+    }
+    for (PlatformSprite(index), mut transform) in platforms.iter_mut() {
+        if let Some(platform) = game.platforms.get(*index) {
+            transform.translation = (platform_center(platform) * TILE_SIZE).extend(0.5);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+//! The player's movement: running, gravity and a jump that forgives pressing a little too late or too early.
+
+use crate::level::{Level, Platform, Tile, PLATFORM_THICKNESS};
+use bevy::math::{const_vec2, Vec2};
+
+const AIR_ACCELERATION: f32 = 35.0;
+/// A jump still works this long after running off a ledge.
+pub const COYOTE_TIME: f32 = 0.1;
+const GRAVITY: f32 = 50.0;
+const GROUND_ACCELERATION: f32 = 70.0;
+/// A jump pressed this long before landing is carried out on landing.
+pub const JUMP_BUFFER_TIME: f32 = 0.12;
+/// Letting go of jump while still rising slows the player down to this, for short hops.
+const JUMP_RELEASE_SPEED: f32 = 6.0;
+const JUMP_SPEED: f32 = 16.5;
+const MAX_FALL_SPEED: f32 = 22.0;
+const RUN_SPEED: f32 = 8.0;
+
+pub const PLAYER_SIZE: Vec2 = const_vec2!([0.7, 0.9]);
+
+/// What the player asks for this frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Controls {
+    /// -1 for left, 1 for right, 0 to stop.
+    pub direction: f32,
+    pub jump_held: bool,
+    pub jump_pressed: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct Player {
+    pub grounded: bool,
+    /// Bottom center, where the feet are.
+    pub position: Vec2,
+    /// Seconds since jump was last pressed, while a buffered jump is waiting.
+    since_jump_pressed: Option<f32>,
+    /// Seconds since the player last stood on something, `None` once that chance to jump is used up.
+    since_grounded: Option<f32>,
+    /// The moving platform stood on, which carries the player along.
+    pub standing_on: Option<usize>,
+    pub velocity: Vec2,
+}
+
+impl Player {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            grounded: false,
+            position,
+            since_jump_pressed: None,
+            since_grounded: None,
+            standing_on: None,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn min(&self) -> Vec2 {
+        self.position - Vec2::new(PLAYER_SIZE.x / 2.0, 0.0)
+    }
+
+    pub fn max(&self) -> Vec2 {
+        self.position + Vec2::new(PLAYER_SIZE.x / 2.0, PLAYER_SIZE.y)
+    }
+
+    /// Advances the player by `delta` seconds; `carried` is how far each platform moved this frame.
+    /// Returns whether a jump started.
+    pub fn update(
+        &mut self,
+        controls: Controls,
+        level: &Level,
+        platforms: &[Platform],
+        carried: &[Vec2],
+        delta: f32,
+    ) -> bool {
+        if let Some(moved) = self.standing_on.and_then(|index| carried.get(index)) {
+            self.position += *moved;
+        }
+        if controls.jump_pressed {
+            self.since_jump_pressed = Some(0.0);
+        }
+        let can_jump = self
+            .since_grounded
+            .map_or(false, |time| time <= COYOTE_TIME);
+        let wants_jump = self
+            .since_jump_pressed
+            .map_or(false, |time| time <= JUMP_BUFFER_TIME);
+        let jumped = can_jump && wants_jump;
+        if jumped {
+            self.velocity.y = JUMP_SPEED;
+            self.since_jump_pressed = None;
+            self.since_grounded = None;
+        } else if !controls.jump_held {
+            self.velocity.y = self.velocity.y.min(JUMP_RELEASE_SPEED);
+        }
+
+        let target = controls.direction.max(-1.0).min(1.0) * RUN_SPEED;
+        let acceleration = if self.grounded {
+            GROUND_ACCELERATION
+        } else {
+            AIR_ACCELERATION
+        };
+        let change = (target - self.velocity.x)
+            .max(-acceleration * delta)
+            .min(acceleration * delta);
+        self.velocity.x += change;
+        self.velocity.y = (self.velocity.y - GRAVITY * delta).max(-MAX_FALL_SPEED);
+
+        self.move_horizontally(level, self.velocity.x * delta);
+        self.move_vertically(level, platforms, self.velocity.y * delta);
+
+        if self.grounded {
+            self.since_grounded = Some(0.0);
+        } else if let Some(time) = &mut self.since_grounded {
+            *time += delta;
+        }
+        if let Some(time) = &mut self.since_jump_pressed {
+            *time += delta;
+        }
+        jumped
+    }
+
+    fn move_horizontally(&mut self, level: &Level, distance: f32) {
+        self.position.x += distance;
+        if !level.any_tile(self.min(), self.max(), |tile| tile == Tile::Solid) {
+            return;
+        }
+        // Back out to the edge of the tile that was run into.
+        self.position.x = if distance > 0.0 {
+            self.max().x.floor() - PLAYER_SIZE.x / 2.0
+        } else {
+            self.min().x.ceil() + PLAYER_SIZE.x / 2.0
+        };
+        self.velocity.x = 0.0;
+    }
+
+    fn move_vertically(&mut self, level: &Level, platforms: &[Platform], distance: f32) {
+        let previous_bottom = self.position.y;
+        self.position.y += distance;
+        self.grounded = false;
+        self.standing_on = None;
+        if level.any_tile(self.min(), self.max(), |tile| tile == Tile::Solid) {
+            if distance > 0.0 {
+                self.position.y = self.max().y.floor() - PLAYER_SIZE.y;
+            } else {
+                self.position.y = self.min().y.ceil();
+                self.grounded = true;
+            }
+            self.velocity.y = 0.0;
+            return;
+        }
+        if distance > 0.0 {
+            return;
+        }
+        // Moving platforms can be jumped through from below and only hold the player up.
+        for (index, platform) in platforms.iter().enumerate() {
+            let top = platform.top();
+            let overlaps = self.max().x > platform.position.x
+                && self.min().x < platform.position.x + platform.width;
+            if overlaps
+                && previous_bottom >= top - 0.01
+                && self.position.y <= top
+                && self.position.y > top - PLATFORM_THICKNESS
+            {
+                self.position.y = top;
+                self.velocity.y = 0.0;
+                self.grounded = true;
+                self.standing_on = Some(index);
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    /// A floor with a ledge dropping off at x = 6 and a moving platform beyond it.
+    fn level() -> Level {
+        Level::parse(
+            "
+|            |
+|            |
+|            |
+|      >>    |
+|            |
+|            |
+######
+",
+        )
+    }
+
+    fn settle(player: &mut Player, level: &Level) {
+        for _ in 0..60 {
+            player.update(Controls::default(), level, &level.platforms, &[], STEP);
+        }
+    }
+
+    #[test]
+    fn lands_on_the_floor() {
+        let level = level();
+        let mut player = Player::new(Vec2::new(2.5, 3.0));
+        settle(&mut player, &level);
+        assert!(player.grounded);
+        assert_eq!(player.position.y, 1.0);
+    }
+
+    #[test]
+    fn walls_stop_the_player() {
+        let level = Level::parse("#    \n#    \n#####");
+        let mut player = Player::new(Vec2::new(2.5, 1.0));
+        let left = Controls {
+            direction: -1.0,
+            ..Default::default()
+        };
+        for _ in 0..60 {
+            player.update(left, &level, &[], &[], STEP);
+        }
+        assert!((player.min().x - 1.0).abs() < 0.001);
+        assert_eq!(player.velocity.x, 0.0);
+    }
+
+    #[test]
+    fn coyote_time_allows_a_late_jump() {
+        let level = level();
+        let jump = |wait: f32| {
+            let mut player = Player::new(Vec2::new(5.5, 1.0));
+            settle(&mut player, &level);
+            // Walk off the ledge, then keep falling for `wait` seconds before pressing jump.
+            let right = Controls {
+                direction: 1.0,
+                ..Default::default()
+            };
+            while player.grounded {
+                player.update(right, &level, &[], &[], STEP);
+            }
+            let mut time = 0.0;
+            while time < wait {
+                player.update(Controls::default(), &level, &[], &[], STEP);
+                time += STEP;
+            }
+            let press = Controls {
+                jump_held: true,
+                jump_pressed: true,
+                ..Default::default()
+            };
+            player.update(press, &level, &[], &[], STEP)
+        };
+        assert!(jump(COYOTE_TIME / 2.0));
+        assert!(!jump(COYOTE_TIME * 2.0));
+    }
+
+    #[test]
+    fn an_early_jump_is_buffered_until_landing() {
+        let level = level();
+        let mut player = Player::new(Vec2::new(2.5, 1.3));
+        player.velocity.y = -5.0;
+        let press = Controls {
+            jump_held: true,
+            jump_pressed: true,
+            ..Default::default()
+        };
+        assert!(!player.update(press, &level, &[], &[], STEP));
+        let hold = Controls {
+            jump_held: true,
+            ..Default::default()
+        };
+        let mut jumped = false;
+        for _ in 0..6 {
+            jumped |= player.update(hold, &level, &[], &[], STEP);
+        }
+        assert!(jumped);
+        assert!(player.velocity.y > 0.0);
+    }
+
+    #[test]
+    fn releasing_jump_early_makes_a_lower_jump() {
+        let level = level();
+        let peak = |held_frames: usize| {
+            let mut player = Player::new(Vec2::new(2.5, 1.0));
+            settle(&mut player, &level);
+            let mut highest: f32 = 0.0;
+            for frame in 0..90 {
+                let controls = Controls {
+                    jump_held: frame < held_frames,
+                    jump_pressed: frame == 0,
+                    ..Default::default()
+                };
+                player.update(controls, &level, &[], &[], STEP);
+                highest = highest.max(player.position.y);
+            }
+            highest
+        };
+        assert!(peak(3) < peak(60) - 1.0);
+    }
+
+    #[test]
+    fn platforms_hold_and_carry_the_player() {
+        let mut level = level();
+        let mut platforms = std::mem::take(&mut level.platforms);
+        let mut player = Player::new(Vec2::new(7.5, 5.0));
+        for _ in 0..60 {
+            let carried: Vec<Vec2> = platforms
+                .iter_mut()
+                .map(|platform| platform.update(&level, STEP))
+                .collect();
+            player.update(Controls::default(), &level, &platforms, &carried, STEP);
+        }
+        assert_eq!(player.standing_on, Some(0));
+        assert_eq!(player.position.y, platforms[0].top());
+        let platform = &platforms[0];
+        assert!(player.max().x > platform.position.x);
+        assert!(player.min().x < platform.position.x + platform.width);
+        assert!(player.position.x > 9.0, "carried along to the right");
+    }
+}
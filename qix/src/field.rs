@@ -0,0 +1,227 @@
+//! The playing field: a grid of cells that are open, claimed, or part of the line being drawn.
+//! Cells count from the bottom left corner; the outermost ring is claimed from the start.
+
+use rand::{seq::SliceRandom, Rng};
+use std::collections::VecDeque;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Open,
+    Claimed,
+    Trail,
+}
+
+pub struct Field {
+    cells: Vec<Cell>,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl Field {
+    pub fn new(width: i32, height: i32) -> Self {
+        let mut field = Self {
+            cells: vec![Cell::Open; (width * height) as usize],
+            height,
+            width,
+        };
+        for x in 0..width {
+            field.set(x, 0, Cell::Claimed);
+            field.set(x, height - 1, Cell::Claimed);
+        }
+        for y in 0..height {
+            field.set(0, y, Cell::Claimed);
+            field.set(width - 1, y, Cell::Claimed);
+        }
+        field
+    }
+
+    /// Turns the trail into claimed cells, then claims every open area without an enemy in it.
+    /// Returns the number of cells claimed.
+    pub fn claim(&mut self, enemies: &[(i32, i32)]) -> usize {
+        let mut claimed = 0;
+        for cell in &mut self.cells {
+            if *cell == Cell::Trail {
+                *cell = Cell::Claimed;
+                claimed += 1;
+            }
+        }
+        let mut seen = vec![false; self.cells.len()];
+        for start in 0..self.cells.len() {
+            if seen[start] || self.cells[start] != Cell::Open {
+                continue;
+            }
+            let area = self.area(start, &mut seen);
+            let guarded = enemies
+                .iter()
+                .any(|(x, y)| self.contains(*x, *y) && area.contains(&self.index(*x, *y)));
+            if !guarded {
+                claimed += area.len();
+                for index in area {
+                    self.cells[index] = Cell::Claimed;
+                }
+            }
+        }
+        claimed
+    }
+
+    /// Claimed cells inside the border, in percent of the cells inside the border.
+    pub fn claimed_percent(&self) -> f32 {
+        let border = 2 * (self.width + self.height) - 4;
+        let claimed = self
+            .cells
+            .iter()
+            .filter(|cell| **cell == Cell::Claimed)
+            .count() as i32;
+        let inside = (self.width - 2) * (self.height - 2);
+        (claimed - border) as f32 * 100.0 / inside as f32
+    }
+
+    pub fn clear_trail(&mut self) {
+        for cell in &mut self.cells {
+            if *cell == Cell::Trail {
+                *cell = Cell::Open;
+            }
+        }
+    }
+
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        (0..self.width).contains(&x) && (0..self.height).contains(&y)
+    }
+
+    /// The next cell along the edges from `at`, not going back to `from` unless it has to.
+    pub fn edge_step(&self, at: (i32, i32), from: (i32, i32), rng: &mut impl Rng) -> (i32, i32) {
+        let ways: Vec<(i32, i32)> = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .map(|(dx, dy)| (at.0 + dx, at.1 + dy))
+            .filter(|next| *next != from && self.is_edge(next.0, next.1))
+            .collect();
+        ways.choose(rng).copied().unwrap_or(from)
+    }
+
+    /// Cells outside the field count as claimed.
+    pub fn get(&self, x: i32, y: i32) -> Cell {
+        if self.contains(x, y) {
+            self.cells[self.index(x, y)]
+        } else {
+            Cell::Claimed
+        }
+    }
+
+    /// A claimed cell next to open space, diagonals included: where the player and the sparks
+    /// can go without drawing.
+    pub fn is_edge(&self, x: i32, y: i32) -> bool {
+        if !self.contains(x, y) || self.get(x, y) != Cell::Claimed {
+            return false;
+        }
+        (-1..=1).any(|dx: i32| {
+            (-1..=1).any(|dy: i32| {
+                self.contains(x + dx, y + dy) && self.get(x + dx, y + dy) != Cell::Claimed
+            })
+        })
+    }
+
+    /// The edge cell closest to a cell, for whatever stands on a cell that stopped being one.
+    pub fn nearest_edge(&self, x: i32, y: i32) -> (i32, i32) {
+        let mut nearest = (0, 0);
+        let mut distance = i32::MAX;
+        for other_y in 0..self.height {
+            for other_x in 0..self.width {
+                let to = (other_x - x).abs() + (other_y - y).abs();
+                if to < distance && self.is_edge(other_x, other_y) {
+                    nearest = (other_x, other_y);
+                    distance = to;
+                }
+            }
+        }
+        nearest
+    }
+
+    pub fn set(&mut self, x: i32, y: i32, cell: Cell) {
+        let index = self.index(x, y);
+        self.cells[index] = cell;
+    }
+
+    /// The open cells reachable from `start` without crossing claimed cells or the trail.
+    fn area(&self, start: usize, seen: &mut [bool]) -> Vec<usize> {
+        let mut area = Vec::new();
+        let mut queue = VecDeque::from([start]);
+        seen[start] = true;
+        while let Some(index) = queue.pop_front() {
+            area.push(index);
+            let (x, y) = (index as i32 % self.width, index as i32 / self.width);
+            for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                if !self.contains(nx, ny) {
+                    continue;
+                }
+                let next = self.index(nx, ny);
+                if !seen[next] && self.cells[next] == Cell::Open {
+                    seen[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        area
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        (y * self.width + x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn starts_with_a_claimed_border() {
+        let field = Field::new(12, 8);
+        assert_eq!(field.claimed_percent(), 0.0);
+        assert!(field.is_edge(0, 0));
+        assert!(field.is_edge(5, 7));
+        assert!(!field.is_edge(5, 5));
+        assert_eq!(field.get(-1, 3), Cell::Claimed);
+        assert_eq!(field.nearest_edge(5, 5), (5, 7));
+    }
+
+    #[test]
+    fn claims_the_side_without_the_enemy() {
+        // A line straight up at x = 4 splits the inside into 3 columns on the left and 6 on
+        // the right.
+        let mut field = Field::new(12, 8);
+        for y in 1..7 {
+            field.set(4, y, Cell::Trail);
+        }
+        assert_eq!(field.claim(&[(8, 3)]), 6 + 3 * 6);
+        assert_eq!(field.claimed_percent(), 40.0);
+        assert!(!field.is_edge(2, 3));
+        assert!(field.is_edge(4, 3));
+        assert_eq!(field.get(8, 3), Cell::Open);
+    }
+
+    #[test]
+    fn trails_can_be_taken_back() {
+        let mut field = Field::new(12, 8);
+        field.set(4, 1, Cell::Trail);
+        field.set(4, 2, Cell::Trail);
+        field.clear_trail();
+        assert_eq!(field.claim(&[(8, 3)]), 0);
+        assert_eq!(field.claimed_percent(), 0.0);
+    }
+
+    #[test]
+    fn sparks_follow_the_edges() {
+        let field = Field::new(12, 8);
+        let mut rng = StdRng::seed_from_u64(2);
+        let (mut at, mut from) = ((3, 0), (2, 0));
+        for _ in 0..20 {
+            let next = field.edge_step(at, from, &mut rng);
+            assert!(field.is_edge(next.0, next.1));
+            assert_ne!(next, from);
+            from = at;
+            at = next;
+        }
+        // Along the bottom, up the right side and back along the top.
+        assert_eq!(at, (6, 7));
+    }
+}
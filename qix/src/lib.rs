@@ -0,0 +1,722 @@
+use bevy::{math::const_vec2, prelude::*};
+use field::{Cell, Field};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use rand::{thread_rng, Rng};
+use std::f32::consts::TAU;
+
+pub mod field;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.03, 0.03, 0.06);
+/// Side of a cell in pixels.
+const CELL: f32 = 7.0;
+/// Fill colors of the claimed area, one level after another.
+const CLAIMED_COLORS: [Color; 4] = [
+    Color::rgb(0.15, 0.3, 0.55),
+    Color::rgb(0.45, 0.2, 0.45),
+    Color::rgb(0.15, 0.42, 0.3),
+    Color::rgb(0.5, 0.32, 0.12),
+];
+const EDGE_COLOR: Color = Color::rgb(0.85, 0.9, 1.0);
+/// Cells across and up the field.
+const FIELD_CELLS: (i32, i32) = (96, 64);
+const FIELD_CENTER: Vec2 = const_vec2!([0.0, -16.0]);
+const GAME: &str = "qix";
+const LEADERBOARD_SIZE: usize = 5;
+const LIVES: u32 = 3;
+const OPEN_COLOR: Color = Color::rgb(0.07, 0.07, 0.1);
+const PLAYER_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+/// Cells per second walking the edges or drawing fast; slow drawing goes half as fast.
+const PLAYER_SPEED: f32 = 24.0;
+/// Length of the lines a qix is drawn with.
+const QIX_LENGTH: f32 = 30.0;
+/// How close in cells a qix may get to the line being drawn.
+const QIX_REACH: i32 = 2;
+const SPARK_COLOR: Color = Color::rgb(1.0, 0.6, 0.15);
+/// Seconds before the first spark comes out, and between one spark and the next.
+const SPARK_DELAY: f32 = 2.0;
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const TRAIL_COLOR: Color = Color::rgb(1.0, 0.35, 0.35);
+
+pub const WINDOW_HEIGHT: f32 = 560.0;
+pub const WINDOW_WIDTH: f32 = 760.0;
+
+/// Everything that moves, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+/// A row of claimed or edge cells, rebuilt whenever the field changes.
+#[derive(Component)]
+struct FieldSprite;
+
+struct Game {
+    field: Field,
+    level: u32,
+    lives: u32,
+    player: Player,
+    qixes: Vec<Qix>,
+    /// The field has changed since it was last drawn.
+    redraw: bool,
+    score: u32,
+    sparks: Vec<Spark>,
+}
+
+impl Game {
+    fn new() -> Self {
+        let mut game = Self {
+            field: Field::new(FIELD_CELLS.0, FIELD_CELLS.1),
+            level: 1,
+            lives: LIVES,
+            player: Player::default(),
+            qixes: Vec::new(),
+            redraw: true,
+            score: 0,
+            sparks: Vec::new(),
+        };
+        game.start_level(1);
+        game
+    }
+
+    /// Cells of the qixes, which keep the areas they are in open.
+    fn enemy_cells(&self) -> Vec<(i32, i32)> {
+        self.qixes
+            .iter()
+            .map(|qix| (qix.position.x as i32, qix.position.y as i32))
+            .collect()
+    }
+
+    /// Takes back the line being drawn and sends the sparks back to the top.
+    fn lose_life(&mut self) {
+        self.lives = self.lives.saturating_sub(1);
+        self.field.clear_trail();
+        if let Some(start) = self.player.drawing_from.take() {
+            self.player.cell = start;
+        }
+        self.reset_sparks();
+    }
+
+    fn reset_sparks(&mut self) {
+        let count = (1 + self.level / 2).min(4) as usize;
+        let top = self.field.height - 1;
+        self.sparks = (0..count)
+            .map(|index| {
+                let x = self.field.width * (index as i32 + 1) / (count as i32 + 1);
+                // Every other spark sets off the other way round.
+                let from = if index % 2 == 0 { x - 1 } else { x + 1 };
+                Spark {
+                    at: (x, top),
+                    from: (from, top),
+                    wait: SPARK_DELAY * (index + 1) as f32,
+                }
+            })
+            .collect();
+    }
+
+    fn start_level(&mut self, level: u32) {
+        let mut rng = thread_rng();
+        self.level = level;
+        self.field = Field::new(FIELD_CELLS.0, FIELD_CELLS.1);
+        self.player = Player {
+            cell: (self.field.width / 2, 0),
+            ..Default::default()
+        };
+        let count = (1 + (level - 1) / 2).min(3);
+        self.qixes = (0..count)
+            .map(|_| Qix {
+                angle: 0.0,
+                position: Vec2::new(
+                    rng.gen_range(20.0, self.field.width as f32 - 20.0),
+                    rng.gen_range(20.0, self.field.height as f32 - 20.0),
+                ),
+                turn: 0.0,
+                velocity: Vec2::ZERO,
+            })
+            .collect();
+        self.reset_sparks();
+        self.redraw = true;
+    }
+
+    /// Share of the field to claim for the level, in percent.
+    fn target(&self) -> f32 {
+        (65 + 5 * (self.level - 1)).min(85) as f32
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Ready,
+    Playing,
+    Cleared,
+    Over,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The level banner, or the result at the end.
+#[derive(Component)]
+struct MenuText;
+
+#[derive(Default)]
+struct Player {
+    cell: (i32, i32),
+    /// Where the line being drawn left the edge.
+    drawing_from: Option<(i32, i32)>,
+    /// Part of the line being drawn was drawn fast, so it does not score double.
+    fast: bool,
+    /// Seconds until the next step.
+    wait: f32,
+}
+
+/// The enemy roaming the open area, which must not touch the line being drawn.
+struct Qix {
+    /// Of the lines it is drawn with, in radians.
+    angle: f32,
+    /// In cells.
+    position: Vec2,
+    /// Seconds until it picks a new heading.
+    turn: f32,
+    velocity: Vec2,
+}
+
+struct Sounds {
+    claim: Handle<AudioSource>,
+    cleared: Handle<AudioSource>,
+    died: Handle<AudioSource>,
+    over: Handle<AudioSource>,
+    step: Handle<AudioSource>,
+}
+
+/// An enemy running along the edges.
+struct Spark {
+    at: (i32, i32),
+    from: (i32, i32),
+    /// Seconds until the next step, long at the start before it comes out.
+    wait: f32,
+}
+
+pub struct QixPlugin;
+
+impl Plugin for QixPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Ready)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Ready).with_system(ready_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(player_update)
+                    .with_system(enemies_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Cleared).with_system(cleared_enter))
+            .add_system_set(SystemSet::on_update(GameState::Cleared).with_system(next_input))
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(over_enter))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(next_input))
+            .add_system(actors_render)
+            .add_system(field_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Qix".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The trail, the player, the qixes and the sparks.
+fn actors_render(
+    mut commands: Commands,
+    time: Res<Time>,
+    game: Res<Game>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    for y in 0..game.field.height {
+        for (start, length) in runs(&game.field, |x| game.field.get(x, y) == Cell::Trail) {
+            commands
+                .spawn_bundle(run_sprite(start, length, y, TRAIL_COLOR, 3.0))
+                .insert(Actor);
+        }
+    }
+
+    let player_color = if game.player.drawing_from.is_some() {
+        TRAIL_COLOR
+    } else {
+        PLAYER_COLOR
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: player_color,
+                custom_size: Some(Vec2::splat(CELL * 1.6)),
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: cell_center(game.player.cell.0, game.player.cell.1).extend(6.0),
+                rotation: Quat::from_rotation_z(TAU / 8.0),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(Actor);
+
+    let hue = time.seconds_since_startup() as f32 * 120.0;
+    for qix in &game.qixes {
+        let center = FIELD_CENTER + (qix.position - field_half()) * CELL;
+        for line in 0..3 {
+            let angle = qix.angle + line as f32 * TAU / 6.0;
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::hsl((hue + line as f32 * 40.0) % 360.0, 0.9, 0.6),
+                        custom_size: Some(Vec2::new(QIX_LENGTH, 3.0)),
+                        ..Default::default()
+                    },
+                    transform: Transform {
+                        translation: center.extend(5.0),
+                        rotation: Quat::from_rotation_z(angle),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                })
+                .insert(Actor);
+        }
+    }
+
+    let spin = time.seconds_since_startup() as f32 * 8.0;
+    for spark in game.sparks.iter().filter(|spark| spark.wait < SPARK_DELAY) {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: SPARK_COLOR,
+                    custom_size: Some(Vec2::splat(CELL * 1.4)),
+                    ..Default::default()
+                },
+                transform: Transform {
+                    translation: cell_center(spark.at.0, spark.at.1).extend(7.0),
+                    rotation: Quat::from_rotation_z(spin),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .insert(Actor);
+    }
+}
+
+fn cell_center(x: i32, y: i32) -> Vec2 {
+    FIELD_CENTER + (Vec2::new(x as f32 + 0.5, y as f32 + 0.5) - field_half()) * CELL
+}
+
+/// Scores what is left over the target, a thousand a percent.
+fn cleared_enter(mut game: ResMut<Game>, audio: Res<Audio>, sounds: Res<Sounds>) {
+    let over = (game.field.claimed_percent() - game.target()).max(0.0) as u32;
+    game.score += over * 1000 * game.level;
+    audio.play(sounds.cleared.clone());
+}
+
+/// Moves the qixes and the sparks; a qix touching the line or a spark catching the player costs
+/// a life.
+fn enemies_update(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    let delta = time.delta_seconds();
+    let mut rng = thread_rng();
+    let speed = 12.0 + 2.0 * game.level as f32;
+    let mut caught = false;
+    for qix in &mut game.qixes {
+        qix.turn -= delta;
+        if qix.turn <= 0.0 {
+            qix.turn = rng.gen_range(0.4, 1.6);
+            let heading = rng.gen_range(0.0, TAU);
+            qix.velocity = Vec2::new(heading.cos(), heading.sin()) * speed;
+        }
+        qix.angle += delta * 3.0;
+        let next = qix.position + qix.velocity * delta;
+        if game.field.get(next.x as i32, qix.position.y as i32) == Cell::Claimed {
+            qix.velocity.x = -qix.velocity.x;
+        } else {
+            qix.position.x = next.x;
+        }
+        if game.field.get(qix.position.x as i32, next.y as i32) == Cell::Claimed {
+            qix.velocity.y = -qix.velocity.y;
+        } else {
+            qix.position.y = next.y;
+        }
+        let (x, y) = (qix.position.x as i32, qix.position.y as i32);
+        for dy in -QIX_REACH..=QIX_REACH {
+            for dx in -QIX_REACH..=QIX_REACH {
+                caught |= game.field.get(x + dx, y + dy) == Cell::Trail;
+            }
+        }
+    }
+
+    let step = 1.0 / speed;
+    for spark in &mut game.sparks {
+        spark.wait -= delta;
+        while spark.wait <= 0.0 {
+            spark.wait += step;
+            if !game.field.is_edge(spark.at.0, spark.at.1) {
+                spark.at = game.field.nearest_edge(spark.at.0, spark.at.1);
+            }
+            let next = game.field.edge_step(spark.at, spark.from, &mut rng);
+            spark.from = spark.at;
+            spark.at = next;
+            caught |= spark.at == game.player.cell || spark.from == game.player.cell;
+        }
+    }
+
+    if caught {
+        game.lose_life();
+        audio.play(sounds.died.clone());
+        if game.lives == 0 {
+            state.set(GameState::Over).unwrap();
+        }
+    }
+}
+
+/// Half the field in cells.
+fn field_half() -> Vec2 {
+    Vec2::new(FIELD_CELLS.0 as f32, FIELD_CELLS.1 as f32) / 2.0
+}
+
+/// The open field, the claimed area over it and the edges on top, drawn a row at a time.
+fn field_render(
+    mut commands: Commands,
+    mut game: ResMut<Game>,
+    sprites: Query<Entity, With<FieldSprite>>,
+) {
+    if !game.redraw {
+        return;
+    }
+    game.redraw = false;
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+    let field = &game.field;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OPEN_COLOR,
+                custom_size: Some(field_half() * 2.0 * CELL),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(FIELD_CENTER.extend(0.0)),
+            ..Default::default()
+        })
+        .insert(FieldSprite);
+    let claimed_color = CLAIMED_COLORS[(game.level as usize - 1) % CLAIMED_COLORS.len()];
+    for y in 0..field.height {
+        for (start, length) in runs(field, |x| field.get(x, y) == Cell::Claimed) {
+            commands
+                .spawn_bundle(run_sprite(start, length, y, claimed_color, 1.0))
+                .insert(FieldSprite);
+        }
+        for (start, length) in runs(field, |x| field.is_edge(x, y)) {
+            commands
+                .spawn_bundle(run_sprite(start, length, y, EDGE_COLOR, 2.0))
+                .insert(FieldSprite);
+        }
+    }
+}
+
+/// Enter moves on to the next level once one is cleared, or to a new game once it is over.
+fn next_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard_input.clear_just_pressed(KeyCode::Return);
+    if game.lives == 0 {
+        *game = Game::new();
+    } else {
+        let level = game.level + 1;
+        game.start_level(level);
+    }
+    state.set(GameState::Ready).unwrap();
+}
+
+fn over_enter(
+    game: Res<Game>,
+    mut scores: ResMut<Scores>,
+    platform: Res<Platform>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.over.clone());
+    if scores.add_unnamed("", game.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+/// The arrow keys walk the edges; with Space held they draw a line into the open, with Shift
+/// held a slow one that scores double. Closing a line claims the side without a qix.
+fn player_update(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let game = &mut *game;
+    let player = &mut game.player;
+    player.wait -= time.delta_seconds();
+    let direction = [
+        (KeyCode::Left, (-1, 0)),
+        (KeyCode::Right, (1, 0)),
+        (KeyCode::Up, (0, 1)),
+        (KeyCode::Down, (0, -1)),
+    ]
+    .iter()
+    .find(|(key, _)| keyboard_input.pressed(*key))
+    .map(|(_, direction)| *direction);
+    let (dx, dy) = match direction {
+        Some(direction) if player.wait <= 0.0 => direction,
+        _ => return,
+    };
+    let fast = keyboard_input.pressed(KeyCode::Space);
+    let slow = keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    let drawing = fast || slow;
+    let speed = if drawing && !fast {
+        PLAYER_SPEED / 2.0
+    } else {
+        PLAYER_SPEED
+    };
+    player.wait = player.wait.max(0.0) + 1.0 / speed;
+
+    let target = (player.cell.0 + dx, player.cell.1 + dy);
+    let field = &mut game.field;
+    match field.get(target.0, target.1) {
+        Cell::Open if drawing => {
+            if player.drawing_from.is_none() {
+                player.drawing_from = Some(player.cell);
+                player.fast = false;
+            }
+            player.fast |= fast;
+            field.set(target.0, target.1, Cell::Trail);
+            player.cell = target;
+            audio.play(sounds.step.clone());
+        }
+        Cell::Claimed if field.is_edge(target.0, target.1) => {
+            player.cell = target;
+            if player.drawing_from.take().is_none() {
+                return;
+            }
+            let double = !player.fast;
+            let enemies = game.enemy_cells();
+            let claimed = game.field.claim(&enemies) as u32;
+            let points = claimed * game.level;
+            game.score += if double { points * 2 } else { points };
+            let cell = game.player.cell;
+            if !game.field.is_edge(cell.0, cell.1) {
+                game.player.cell = game.field.nearest_edge(cell.0, cell.1);
+            }
+            game.redraw = true;
+            audio.play(sounds.claim.clone());
+            if game.field.claimed_percent() >= game.target() {
+                state.set(GameState::Cleared).unwrap();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Enter starts the level.
+fn ready_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// A sprite covering `length` cells of row `y` from `start` on.
+fn run_sprite(start: i32, length: i32, y: i32, color: Color, z: f32) -> SpriteBundle {
+    let left = cell_center(start, y);
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::new(length as f32 * CELL, CELL)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(left.x + (length - 1) as f32 * CELL / 2.0, left.y, z),
+        ..Default::default()
+    }
+}
+
+/// Start and length of each run of cells in a row, by column, that `covered` holds for.
+fn runs(field: &Field, covered: impl Fn(i32) -> bool) -> Vec<(i32, i32)> {
+    let mut runs = Vec::new();
+    let mut start = None;
+    for x in 0..=field.width {
+        match (start, x < field.width && covered(x)) {
+            (None, true) => start = Some(x),
+            (Some(from), false) => {
+                runs.push((from, x - from));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    runs
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        claim: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.06),
+            Tone::new(Waveform::Triangle, 659.0, 0.06),
+            Tone::new(Waveform::Triangle, 784.0, 0.12).fade(),
+        ])),
+        cleared: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35).fade(),
+        ])),
+        died: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 220.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.3).fade().volume(0.3),
+        ])),
+        over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        step: audio_sources.add(
+            Tone::new(Waveform::Square, 880.0, 0.015)
+                .volume(0.05)
+                .into(),
+        ),
+    });
+}
+
+fn texts_update(
+    game: Res<Game>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let hud = format!(
+        "Score {}   High {}   Lives {}   Level {}   Claimed {:.0}% of {:.0}%",
+        game.score,
+        scores
+            .best("")
+            .map_or(0, |entry| entry.score)
+            .max(game.score),
+        game.lives,
+        game.level,
+        game.field.claimed_percent(),
+        game.target()
+    );
+    let (help, banner) = match state.current() {
+        GameState::Ready => (
+            "Enter: start".to_string(),
+            format!("LEVEL {}\n\nClaim {:.0}% of the field", game.level, game.target()),
+        ),
+        GameState::Playing => (
+            "Arrows: move along the edges   Hold Space: draw   Hold Shift: draw slowly for double points"
+                .to_string(),
+            String::new(),
+        ),
+        GameState::Cleared => (
+            "Enter: next level".to_string(),
+            format!(
+                "LEVEL {} CLEARED\n\n{:.0}% claimed",
+                game.level,
+                game.field.claimed_percent()
+            ),
+        ),
+        GameState::Over => {
+            let scores = scores
+                .table("")
+                .iter()
+                .take(LEADERBOARD_SIZE)
+                .enumerate()
+                .map(|(place, entry)| format!("{}. {:>7}", place + 1, entry.score))
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Enter: new game".to_string(),
+                format!("GAME OVER\n\nScore {}\n\n{}", game.score, scores),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
@@ -0,0 +1,188 @@
+//! The car's handling: it accelerates along its heading and only slowly loses sideways speed, so it drifts.
+
+use crate::track::{Surface, Track};
+use bevy::math::{const_vec2, Vec2};
+
+const ACCELERATION: f32 = 9.0;
+/// Share of the speed kept when bouncing off a barrier, reversed.
+const BOUNCE: f32 = 0.3;
+const BRAKING: f32 = 16.0;
+const REVERSE_ACCELERATION: f32 = 4.0;
+/// Radians per second at full steering and full speed.
+const STEERING: f32 = 3.2;
+/// Below this speed in tiles per second, steering fades out.
+const STEERING_SPEED: f32 = 3.0;
+
+pub const CAR_SIZE: Vec2 = const_vec2!([0.8, 0.45]);
+
+/// What the driver asks for this frame.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Controls {
+    /// 1 to accelerate, -1 to brake and then reverse.
+    pub throttle: f32,
+    /// 1 to turn left, -1 to turn right.
+    pub steer: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Car {
+    /// Radians, 0 facing right.
+    pub heading: f32,
+    /// Center of the car in tiles.
+    pub position: Vec2,
+    /// Tiles per second.
+    pub velocity: Vec2,
+}
+
+impl Car {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            heading: 0.0,
+            position,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn forward(&self) -> Vec2 {
+        Vec2::new(self.heading.cos(), self.heading.sin())
+    }
+
+    /// Speed along the heading, negative when reversing.
+    pub fn speed(&self) -> f32 {
+        self.velocity.dot(self.forward())
+    }
+
+    /// Advances the car by `delta` seconds; returns whether it hit a barrier.
+    pub fn update(&mut self, controls: Controls, track: &Track, delta: f32) -> bool {
+        let surface = track.surface(self.position);
+        let (drag, grip) = match surface {
+            Surface::Road | Surface::Barrier => (0.6, 6.0),
+            // Off the track, the car slows down a lot and slides more.
+            Surface::Grass => (3.0, 3.0),
+        };
+
+        let speed = self.speed();
+        let turn = controls.steer.clamp(-1.0, 1.0)
+            * STEERING
+            * (speed.abs() / STEERING_SPEED).min(1.0)
+            * speed.signum();
+        self.heading = (self.heading + turn * delta).rem_euclid(std::f32::consts::TAU);
+
+        let forward = self.forward();
+        let mut along = self.velocity.dot(forward);
+        let mut across = self.velocity - forward * along;
+        let throttle = controls.throttle.clamp(-1.0, 1.0);
+        along += if throttle > 0.0 {
+            throttle * ACCELERATION
+        } else if along > 0.0 {
+            throttle * BRAKING
+        } else {
+            throttle * REVERSE_ACCELERATION
+        } * delta;
+        along -= along * drag * delta;
+        across -= across * (grip * delta).min(1.0);
+        self.velocity = forward * along + across;
+
+        let next = self.position + self.velocity * delta;
+        if track.surface(next) == Surface::Barrier {
+            self.velocity *= -BOUNCE;
+            true
+        } else {
+            self.position = next;
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    const LAYOUT: &str = "
+############
+#          #
+#C.........#
+#          #
+############
+";
+
+    fn drive(car: &mut Car, track: &Track, controls: Controls, seconds: f32) -> bool {
+        let mut hit = false;
+        for _ in 0..(seconds / STEP) as usize {
+            hit |= car.update(controls, track, STEP);
+        }
+        hit
+    }
+
+    const FULL_THROTTLE: Controls = Controls {
+        throttle: 1.0,
+        steer: 0.0,
+    };
+
+    #[test]
+    fn grass_slows_the_car_down() {
+        let track = Track::parse(LAYOUT);
+        let mut on_road = Car::new(track.start);
+        drive(&mut on_road, &track, FULL_THROTTLE, 0.8);
+        let mut on_grass = Car::new(track.start - Vec2::new(0.0, 1.0));
+        drive(&mut on_grass, &track, FULL_THROTTLE, 0.8);
+        assert!(on_road.speed() > on_grass.speed() * 1.5);
+    }
+
+    #[test]
+    fn barriers_stop_and_bounce_the_car() {
+        let track = Track::parse(LAYOUT);
+        let mut car = Car::new(track.start);
+        assert!(drive(&mut car, &track, FULL_THROTTLE, 4.0));
+        assert!(car.position.x < 11.0);
+    }
+
+    #[test]
+    fn the_car_only_turns_when_moving() {
+        let track = Track::parse(LAYOUT);
+        let mut car = Car::new(track.start);
+        let left = Controls {
+            throttle: 0.0,
+            steer: 1.0,
+        };
+        drive(&mut car, &track, left, 0.5);
+        assert_eq!(car.heading, 0.0);
+        car.velocity = Vec2::new(6.0, 0.0);
+        car.update(left, &track, STEP);
+        assert!(car.heading > 0.0);
+    }
+
+    #[test]
+    fn turning_at_speed_drifts() {
+        let track = Track::parse(LAYOUT);
+        let mut car = Car::new(track.start);
+        car.velocity = Vec2::new(8.0, 0.0);
+        let left = Controls {
+            throttle: 1.0,
+            steer: 1.0,
+        };
+        car.update(left, &track, STEP);
+        car.update(left, &track, STEP);
+        // The car points further left than it moves.
+        let direction = car.velocity.y.atan2(car.velocity.x);
+        assert!(car.heading > direction + 0.01);
+        assert!(direction > 0.0);
+    }
+
+    #[test]
+    fn braking_turns_into_reversing() {
+        let track = Track::parse(LAYOUT);
+        let mut car = Car::new(track.start + Vec2::new(4.0, 0.0));
+        car.velocity = Vec2::new(4.0, 0.0);
+        let back = Controls {
+            throttle: -1.0,
+            steer: 0.0,
+        };
+        drive(&mut car, &track, back, 0.2);
+        assert!(car.speed() > 0.0 && car.speed() < 2.0);
+        drive(&mut car, &track, back, 1.0);
+        assert!(car.speed() < 0.0);
+    }
+}
@@ -0,0 +1,59 @@
+//! Recording a lap and playing it back as a ghost car.
+
+use crate::car::Car;
+use bevy::math::Vec2;
+use std::f32::consts::{PI, TAU};
+
+/// Seconds between two recorded samples.
+pub const INTERVAL: f32 = 0.05;
+
+/// x, y and heading of the car, as a plain array so it stores compactly.
+pub type Sample = [f32; 3];
+
+/// Adds samples of `car` until the recording covers `time` seconds into the lap.
+pub fn record(samples: &mut Vec<Sample>, time: f32, car: &Car) {
+    while samples.len() as f32 * INTERVAL <= time {
+        samples.push([car.position.x, car.position.y, car.heading]);
+    }
+}
+
+/// Position and heading `time` seconds into the recorded lap, `None` once it is over.
+pub fn replay(samples: &[Sample], time: f32) -> Option<(Vec2, f32)> {
+    let step = time.max(0.0) / INTERVAL;
+    let index = step.floor() as usize;
+    let [x, y, heading] = *samples.get(index)?;
+    let [next_x, next_y, next_heading] = *samples.get(index + 1).unwrap_or(&samples[index]);
+    let t = step.fract();
+    let position = Vec2::new(x, y).lerp(Vec2::new(next_x, next_y), t);
+    // Turn the short way round when the heading wraps past zero.
+    let turn = (next_heading - heading + PI).rem_euclid(TAU) - PI;
+    Some((position, (heading + turn * t).rem_euclid(TAU)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn records_one_sample_per_interval() {
+        let mut samples = Vec::new();
+        let mut car = Car::new(Vec2::ZERO);
+        record(&mut samples, 0.0, &car);
+        car.position.x = 1.0;
+        record(&mut samples, INTERVAL * 2.5, &car);
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[2], [1.0, 0.0, 0.0]);
+        record(&mut samples, INTERVAL * 2.7, &car);
+        assert_eq!(samples.len(), 3);
+    }
+
+    #[test]
+    fn replays_between_samples() {
+        let samples = [[0.0, 0.0, TAU - 0.1], [2.0, 1.0, 0.1]];
+        let (position, heading) = replay(&samples, INTERVAL / 2.0).unwrap();
+        assert!((position - Vec2::new(1.0, 0.5)).length() < 0.001);
+        assert!(heading < 0.001 || heading > TAU - 0.001);
+        assert_eq!(replay(&samples, INTERVAL), Some((Vec2::new(2.0, 1.0), 0.1)));
+        assert_eq!(replay(&samples, INTERVAL * 2.0), None);
+    }
+}
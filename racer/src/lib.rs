@@ -0,0 +1,367 @@
+use bevy::prelude::*;
+use car::{Car, Controls, CAR_SIZE};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use ghost::Sample;
+use serde::{Deserialize, Serialize};
+use track::{Gate, Surface, Timing, Track};
+
+pub mod car;
+pub mod ghost;
+pub mod track;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.1);
+const BARRIER_COLORS: [Color; 2] = [Color::rgb(0.85, 0.15, 0.15), Color::rgb(0.95, 0.95, 0.95)];
+/// Hitting a barrier slower than this makes no sound.
+const BUMP_SPEED: f32 = 2.0;
+const CAR_COLOR: Color = Color::rgb(0.95, 0.75, 0.1);
+const FINISH_COLORS: [Color; 2] = [Color::rgb(0.95, 0.95, 0.95), Color::rgb(0.1, 0.1, 0.1)];
+const GHOST_COLOR: Color = Color::rgba(0.6, 0.8, 1.0, 0.4);
+const GRASS_COLOR: Color = Color::rgb(0.25, 0.55, 0.2);
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+/// How many lap times the leaderboard keeps.
+const LEADERBOARD_SIZE: usize = 5;
+/// Long frames are cut short so the car cannot skip over a barrier after a hiccup.
+const MAX_STEP: f32 = 1.0 / 30.0;
+const RECORDS_FILE: &str = "records";
+const ROAD_COLOR: Color = Color::rgb(0.35, 0.35, 0.38);
+const TILE_SIZE: f32 = 32.0;
+const TRACK: &str = include_str!("../assets/track.txt");
+
+pub const WINDOW_HEIGHT: f32 = 20.0 * TILE_SIZE + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = 30.0 * TILE_SIZE;
+
+#[derive(Component)]
+struct CarSprite;
+
+#[derive(Component)]
+struct GhostSprite;
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct LeaderboardText;
+
+/// The car on the track and the lap in progress.
+struct Race {
+    car: Car,
+    /// The gate the car is on, so driving over it counts once.
+    gate: Option<Gate>,
+    /// The time of the last completed lap.
+    last: Option<f32>,
+    /// The current lap so far, which becomes the ghost if it is the best one.
+    recording: Vec<Sample>,
+    timing: Timing,
+    track: Track,
+}
+
+impl Race {
+    fn new() -> Self {
+        let track = Track::parse(TRACK);
+        Self {
+            car: Car::new(track.start),
+            gate: None,
+            last: None,
+            recording: Vec::new(),
+            timing: Timing::default(),
+            track,
+        }
+    }
+}
+
+/// The fastest laps and the ghost of the best one, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records {
+    /// Fastest first.
+    best_laps: Vec<f32>,
+    ghost: Vec<Sample>,
+}
+
+impl Records {
+    /// Adds a lap to the leaderboard; returns whether it is the new best.
+    fn add(&mut self, time: f32, recording: &[Sample]) -> bool {
+        let best = !self.best_laps.iter().any(|best| *best <= time);
+        if best {
+            self.ghost = recording.to_vec();
+        }
+        self.best_laps.push(time);
+        self.best_laps.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        self.best_laps.truncate(LEADERBOARD_SIZE);
+        best
+    }
+
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("racer", RECORDS_FILE, self) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+struct Sounds {
+    best: Handle<AudioSource>,
+    bump: Handle<AudioSource>,
+    checkpoint: Handle<AudioSource>,
+    lap: Handle<AudioSource>,
+}
+
+pub struct RacerPlugin;
+
+impl Plugin for RacerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Race::new())
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                "racer",
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_startup_system(setup)
+            .add_system(car_movement.label("movement"))
+            .add_system(keyboard_input)
+            .add_system(sprites_sync.after("movement"))
+            .add_system(hud_update)
+            .add_system(leaderboard_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Racer".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Drives the car, times the lap and records it for the ghost.
+fn car_movement(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut race: ResMut<Race>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let delta = time.delta_seconds().min(MAX_STEP);
+    let mut controls = Controls::default();
+    if keyboard_input.any_pressed([KeyCode::Up, KeyCode::W]) {
+        controls.throttle += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Down, KeyCode::S]) {
+        controls.throttle -= 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Left, KeyCode::A]) {
+        controls.steer += 1.0;
+    }
+    if keyboard_input.any_pressed([KeyCode::Right, KeyCode::D]) {
+        controls.steer -= 1.0;
+    }
+    let race = &mut *race;
+    let speed = race.car.velocity.length();
+    if race.car.update(controls, &race.track, delta) && speed > BUMP_SPEED {
+        audio.play(sounds.bump.clone());
+    }
+    race.timing.tick(delta);
+    if let Some(time) = race.timing.current {
+        ghost::record(&mut race.recording, time, &race.car);
+    }
+
+    let gate = race.track.gate(race.car.position);
+    if gate == race.gate {
+        return;
+    }
+    race.gate = gate;
+    let gate = match gate {
+        Some(gate) => gate,
+        None => return,
+    };
+    let next = race.timing.next;
+    match race.timing.pass(gate, race.track.checkpoints) {
+        Some(lap) => {
+            race.last = Some(lap);
+            let sound = if records.add(lap, &race.recording) {
+                &sounds.best
+            } else {
+                &sounds.lap
+            };
+            audio.play(sound.clone());
+            records.save();
+        }
+        None if race.timing.next > next => audio.play(sounds.checkpoint.clone()),
+        None => {}
+    }
+    // A lap starts on the finish line, so the recording does too.
+    if gate == Gate::Finish && race.timing.current == Some(0.0) {
+        race.recording.clear();
+        ghost::record(&mut race.recording, 0.0, &race.car);
+    }
+}
+
+fn format_time(time: Option<f32>) -> String {
+    time.map_or_else(|| "--.--".to_string(), |time| format!("{:.2}", time))
+}
+
+fn hud_update(race: Res<Race>, mut texts: Query<&mut Text, With<HudText>>) {
+    let value = format!(
+        "LAP {}   TIME {}   LAST {}",
+        race.timing.laps + 1,
+        format_time(race.timing.current),
+        format_time(race.last),
+    );
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+/// R puts the car back on the start and abandons the lap.
+fn keyboard_input(keyboard_input: Res<Input<KeyCode>>, mut race: ResMut<Race>) {
+    if keyboard_input.just_pressed(KeyCode::R) {
+        *race = Race::new();
+    }
+}
+
+fn leaderboard_update(records: Res<Records>, mut texts: Query<&mut Text, With<LeaderboardText>>) {
+    if !records.is_changed() {
+        return;
+    }
+    let mut value = "TOP LAPS".to_string();
+    for (place, time) in records.best_laps.iter().enumerate() {
+        value.push_str(&format!("   {}. {:.2}", place + 1, time));
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    race: Res<Race>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    let track = &race.track;
+    let extent = Vec2::new(track.width as f32, track.height as f32) * TILE_SIZE;
+    let mut camera = OrthographicCameraBundle::new_2d();
+    // The HUD covers the top of the window.
+    camera.transform.translation.x = extent.x / 2.0;
+    camera.transform.translation.y = extent.y / 2.0 + HUD_HEIGHT / 2.0;
+    commands.spawn_bundle(camera);
+
+    for y in 0..track.height {
+        for x in 0..track.width {
+            let cell = IVec2::new(x, y);
+            let checker = ((x + y) % 2) as usize;
+            let color = match (track.surface_at(cell), track.gate(cell.as_vec2())) {
+                (_, Some(Gate::Finish)) => FINISH_COLORS[checker],
+                (Surface::Barrier, _) => BARRIER_COLORS[checker],
+                (Surface::Grass, _) => GRASS_COLOR,
+                (Surface::Road, _) => ROAD_COLOR,
+            };
+            commands.spawn_bundle(sprite(color, Vec2::ONE, cell.as_vec2() + Vec2::splat(0.5)));
+        }
+    }
+    commands
+        .spawn_bundle(sprite(GHOST_COLOR, CAR_SIZE, track.start))
+        .insert(GhostSprite);
+    commands
+        .spawn_bundle(sprite(CAR_COLOR, CAR_SIZE, track.start))
+        .insert(CarSprite);
+
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(12.0),
+                right: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(LeaderboardText);
+    commands.spawn_bundle(game_hud::world_text(
+        &font,
+        "Up/W: gas   Down/S: brake, reverse   Left/Right, A/D: steer   R: restart",
+        14.0,
+        HUD_COLOR,
+        Vec3::new(extent.x / 2.0, 6.5 * TILE_SIZE, 1.0),
+    ));
+
+    commands.insert_resource(Sounds {
+        best: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.3),
+        ])),
+        bump: audio_sources.add(
+            Tone::new(Waveform::Noise, 200.0, 0.12)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        checkpoint: audio_sources.add(Tone::new(Waveform::Square, 880.0, 0.06).volume(0.15).into()),
+        lap: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 659.0, 0.08).volume(0.2),
+            Tone::new(Waveform::Square, 988.0, 0.15).volume(0.2),
+        ])),
+    });
+}
+
+/// A plain rectangle; sizes and positions are in tiles.
+fn sprite(color: Color, size: Vec2, center: Vec2) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size * TILE_SIZE),
+            ..Default::default()
+        },
+        transform: Transform::from_translation((center * TILE_SIZE).extend(0.0)),
+        ..Default::default()
+    }
+}
+
+/// Moves the car to where the simulation has it and the ghost to where the best lap was at this time.
+fn sprites_sync(
+    race: Res<Race>,
+    records: Res<Records>,
+    mut cars: Query<&mut Transform, With<CarSprite>>,
+    mut ghosts: Query<(&mut Transform, &mut Visibility), (With<GhostSprite>, Without<CarSprite>)>,
+) {
+    let place = |transform: &mut Transform, position: Vec2, heading: f32, z: f32| {
+        transform.translation = (position * TILE_SIZE).extend(z);
+        transform.rotation = Quat::from_rotation_z(heading);
+    };
+    for mut transform in cars.iter_mut() {
+        place(&mut transform, race.car.position, race.car.heading, 3.0);
+    }
+    let ghost = race
+        .timing
+        .current
+        .and_then(|time| ghost::replay(&records.ghost, time));
+    for (mut transform, mut visibility) in ghosts.iter_mut() {
+        visibility.is_visible = ghost.is_some();
+        if let Some((position, heading)) = ghost {
+            place(&mut transform, position, heading, 2.0);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! The track is a text file with one character per tile, see the readme for the symbols.
+//! Positions are in tiles with the origin at the bottom left corner and y pointing up.
+
+use bevy::math::{IVec2, Vec2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Surface {
+    /// Tyre walls the car bounces off.
+    Barrier,
+    Grass,
+    Road,
+}
+
+/// Lines across the road that a lap has to pass in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Gate {
+    Checkpoint(usize),
+    Finish,
+}
+
+#[derive(Clone, Debug)]
+pub struct Track {
+    /// Number of checkpoints between two crossings of the finish line.
+    pub checkpoints: usize,
+    gates: Vec<Option<Gate>>,
+    pub height: i32,
+    /// Center of the tile the car starts on, facing right.
+    pub start: Vec2,
+    surfaces: Vec<Surface>,
+    pub width: i32,
+}
+
+impl Track {
+    pub fn parse(layout: &str) -> Self {
+        let lines: Vec<&str> = layout.lines().filter(|line| !line.is_empty()).collect();
+        let height = lines.len() as i32;
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+        let mut track = Self {
+            checkpoints: 0,
+            gates: vec![None; (width * height) as usize],
+            height,
+            start: Vec2::ZERO,
+            surfaces: vec![Surface::Grass; (width * height) as usize],
+            width,
+        };
+        for (row, line) in lines.iter().enumerate() {
+            let y = height - 1 - row as i32;
+            for (x, symbol) in line.chars().enumerate() {
+                let index = (y * width + x as i32) as usize;
+                track.surfaces[index] = match symbol {
+                    '#' => Surface::Barrier,
+                    '.' | 'C' | 'S' | '1'..='9' => Surface::Road,
+                    _ => Surface::Grass,
+                };
+                track.gates[index] = match symbol {
+                    'S' => Some(Gate::Finish),
+                    '1'..='9' => {
+                        let number = symbol as usize - '0' as usize;
+                        track.checkpoints = track.checkpoints.max(number);
+                        Some(Gate::Checkpoint(number - 1))
+                    }
+                    _ => None,
+                };
+                if symbol == 'C' {
+                    track.start = Vec2::new(x as f32, y as f32) + Vec2::splat(0.5);
+                }
+            }
+        }
+        track
+    }
+
+    pub fn cell(position: Vec2) -> IVec2 {
+        position.floor().as_ivec2()
+    }
+
+    pub fn gate(&self, position: Vec2) -> Option<Gate> {
+        self.index(Self::cell(position))
+            .and_then(|index| self.gates[index])
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        if cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.height {
+            Some((cell.y * self.width + cell.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Everything outside the track is a barrier.
+    pub fn surface(&self, position: Vec2) -> Surface {
+        self.surface_at(Self::cell(position))
+    }
+
+    pub fn surface_at(&self, cell: IVec2) -> Surface {
+        self.index(cell)
+            .map_or(Surface::Barrier, |index| self.surfaces[index])
+    }
+}
+
+/// Lap timing: a lap starts on the finish line and counts once every checkpoint was passed in order.
+#[derive(Clone, Debug, Default)]
+pub struct Timing {
+    /// Seconds into the current lap, `None` before the finish line was first crossed.
+    pub current: Option<f32>,
+    pub laps: u32,
+    /// The checkpoint to pass next; the finish line once all are passed.
+    pub next: usize,
+}
+
+impl Timing {
+    pub fn tick(&mut self, delta: f32) {
+        if let Some(time) = &mut self.current {
+            *time += delta;
+        }
+    }
+
+    /// Called when the car drives onto a gate; returns the lap time when this completes a lap.
+    pub fn pass(&mut self, gate: Gate, checkpoints: usize) -> Option<f32> {
+        match gate {
+            Gate::Checkpoint(index) if index == self.next && self.current.is_some() => {
+                self.next += 1;
+                None
+            }
+            Gate::Checkpoint(_) => None,
+            Gate::Finish => {
+                let completed = match self.current {
+                    Some(time) if self.next == checkpoints => Some(time),
+                    // Crossing the line again without a full lap, e.g. backwards, changes nothing.
+                    Some(_) => return None,
+                    None => None,
+                };
+                if completed.is_some() {
+                    self.laps += 1;
+                }
+                self.current = Some(0.0);
+                self.next = 0;
+                completed
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = "
+#######
+#C.S.1#
+#.   .#
+#..2..#
+#######
+";
+
+    #[test]
+    fn parses_surfaces_and_gates() {
+        let track = Track::parse(LAYOUT);
+        assert_eq!((track.width, track.height), (7, 5));
+        assert_eq!(track.checkpoints, 2);
+        assert_eq!(track.start, Vec2::new(1.5, 3.5));
+        assert_eq!(track.surface(Vec2::new(2.5, 2.5)), Surface::Grass);
+        assert_eq!(track.surface(Vec2::new(2.5, 3.5)), Surface::Road);
+        assert_eq!(track.surface(Vec2::new(0.5, 3.5)), Surface::Barrier);
+        assert_eq!(track.surface(Vec2::new(-3.0, 1.0)), Surface::Barrier);
+        assert_eq!(track.gate(Vec2::new(3.2, 3.9)), Some(Gate::Finish));
+        assert_eq!(track.gate(Vec2::new(5.5, 3.5)), Some(Gate::Checkpoint(0)));
+        assert_eq!(track.gate(Vec2::new(3.5, 1.5)), Some(Gate::Checkpoint(1)));
+    }
+
+    #[test]
+    fn laps_need_every_checkpoint_in_order() {
+        let mut timing = Timing::default();
+        timing.tick(5.0);
+        assert_eq!(timing.pass(Gate::Checkpoint(0), 2), None);
+        assert_eq!(timing.pass(Gate::Finish, 2), None);
+        assert_eq!(timing.current, Some(0.0));
+        timing.tick(3.0);
+        // The second checkpoint before the first does not count.
+        timing.pass(Gate::Checkpoint(1), 2);
+        assert_eq!(timing.pass(Gate::Finish, 2), None);
+        timing.pass(Gate::Checkpoint(0), 2);
+        timing.pass(Gate::Checkpoint(1), 2);
+        timing.tick(4.0);
+        assert_eq!(timing.pass(Gate::Finish, 2), Some(7.0));
+        assert_eq!(timing.laps, 1);
+        assert_eq!(timing.current, Some(0.0));
+    }
+}
@@ -0,0 +1,132 @@
+//! Minimax with alpha-beta pruning over square weights and mobility.
+
+use crate::othello::Position;
+use rand::{seq::SliceRandom, thread_rng};
+
+/// The corners and the squares next to them, which stop being risky once the corner is taken.
+const CORNERS: [(usize, [usize; 3]); 4] = [
+    (0, [1, 8, 9]),
+    (7, [6, 14, 15]),
+    (56, [48, 49, 57]),
+    (63, [54, 55, 62]),
+];
+/// Each move the side to move has more than the opponent is worth this much.
+const MOBILITY: i32 = 8;
+/// Square values from the top left: corners are safe for good, the squares next to them hand
+/// corners to the opponent.
+const WEIGHTS: [i32; 64] = [
+    100, -20, 10, 5, 5, 10, -20, 100, //
+    -20, -50, -2, -2, -2, -2, -50, -20, //
+    10, -2, 1, 1, 1, 1, -2, 10, //
+    5, -2, 1, 0, 0, 1, -2, 5, //
+    5, -2, 1, 0, 0, 1, -2, 5, //
+    10, -2, 1, 1, 1, 1, -2, 10, //
+    -20, -50, -2, -2, -2, -2, -50, -20, //
+    100, -20, 10, 5, 5, 10, -20, 100, //
+];
+const WIN: i32 = 100_000;
+
+/// Search depth in plies per difficulty level.
+pub const LEVELS: [u32; 4] = [1, 2, 4, 6];
+
+/// Score of the position for the side to move.
+pub fn evaluate(position: &Position) -> i32 {
+    let mut weights = WEIGHTS;
+    for (corner, neighbours) in CORNERS {
+        if position.squares[corner].is_some() {
+            for square in neighbours {
+                weights[square] = 0;
+            }
+        }
+    }
+    let mut score = 0;
+    for (square, disc) in position.squares.iter().enumerate() {
+        match disc {
+            Some(side) if *side == position.side => score += weights[square],
+            Some(_) => score -= weights[square],
+            None => {}
+        }
+    }
+    let mobility = position.legal_moves().len() as i32 - position.pass().legal_moves().len() as i32;
+    score + mobility * MOBILITY
+}
+
+/// The best move found at `depth`, picking randomly between equally good moves.
+pub fn best_move(position: &Position, depth: u32) -> Option<u8> {
+    let mut moves = position.legal_moves();
+    moves.shuffle(&mut thread_rng());
+    let mut best = None;
+    let mut alpha = -WIN - 64;
+    for square in moves {
+        let score = -search(
+            &position.play(square),
+            depth.saturating_sub(1),
+            -WIN - 64,
+            -alpha,
+        );
+        if score > alpha {
+            alpha = score;
+            best = Some(square);
+        }
+    }
+    best
+}
+
+/// The final disc difference decides finished games, scaled far beyond any evaluation.
+fn final_score(position: &Position) -> i32 {
+    let own = position.count(position.side) as i32;
+    let other = position.count(position.side.opposite()) as i32;
+    match own.cmp(&other) {
+        std::cmp::Ordering::Greater => WIN + own - other,
+        std::cmp::Ordering::Less => -WIN + own - other,
+        std::cmp::Ordering::Equal => 0,
+    }
+}
+
+fn search(position: &Position, depth: u32, mut alpha: i32, beta: i32) -> i32 {
+    let moves = position.legal_moves();
+    if moves.is_empty() {
+        let passed = position.pass();
+        if passed.legal_moves().is_empty() {
+            return final_score(position);
+        }
+        return -search(&passed, depth, -beta, -alpha);
+    }
+    if depth == 0 {
+        return evaluate(position);
+    }
+    for square in moves {
+        let score = -search(&position.play(square), depth - 1, -beta, -alpha);
+        if score >= beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::othello::Side;
+
+    #[test]
+    fn start_is_balanced() {
+        assert_eq!(evaluate(&Position::start()), 0);
+    }
+
+    #[test]
+    fn takes_a_corner() {
+        // Black can take the corner at 0 or make one of the usual opening moves in the middle.
+        let mut squares = Position::start().squares;
+        squares[9] = Some(Side::White);
+        squares[18] = Some(Side::Black);
+        let position = Position {
+            side: Side::Black,
+            squares,
+        };
+        for depth in [1, 2] {
+            assert_eq!(best_move(&position, depth), Some(0));
+        }
+    }
+}
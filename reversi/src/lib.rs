@@ -0,0 +1,693 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use othello::{Outcome, Position, Side};
+use std::{
+    sync::{Arc, Mutex},
+    thread,
+};
+
+mod ai;
+pub mod othello;
+
+/// The AI's move is shown no sooner than this, so the flips of the last move can play out.
+const AI_DELAY: f32 = 0.8;
+const BACKGROUND_COLOR: Color = Color::rgb(0.12, 0.14, 0.12);
+const BLACK_COLOR: Color = Color::rgb(0.08, 0.08, 0.08);
+const BOARD_SIZE: f32 = 8.0 * SQUARE_SIZE;
+const DISC_FONT_SIZE: f32 = 84.0;
+/// Seconds a disc takes to turn over.
+const FLIP_DURATION: f32 = 0.3;
+/// Delay between the flips of one square and the next further from the move.
+const FLIP_STAGGER: f32 = 0.06;
+/// Opacity of the disc shown on a legal square under the mouse.
+const GHOST_ALPHA: f32 = 0.35;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const LAST_MOVE_COLOR: Color = Color::rgb(0.95, 0.85, 0.2);
+const LEGAL_COLOR: Color = Color::rgb(0.45, 0.8, 0.5);
+const MARGIN: f32 = 30.0;
+const NOTICE_SECONDS: f32 = 1.8;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.75);
+const SQUARE_COLOR: Color = Color::rgb(0.12, 0.48, 0.25);
+const SQUARE_GAP: f32 = 2.0;
+const SQUARE_SIZE: f32 = 70.0;
+const WHITE_COLOR: Color = Color::rgb(0.95, 0.95, 0.92);
+
+pub const WINDOW_HEIGHT: f32 = BOARD_SIZE + HUD_HEIGHT + 2.0 * MARGIN;
+pub const WINDOW_WIDTH: f32 = BOARD_SIZE + 2.0 * MARGIN;
+
+const BOARD_CENTER: Vec2 = const_vec2!([0.0, WINDOW_HEIGHT / 2.0 - HUD_HEIGHT - BOARD_SIZE / 2.0]);
+
+/// Everything drawn for a game in progress, removed when returning to the menu.
+#[derive(Component)]
+struct BoardEntity;
+
+#[derive(Component)]
+struct BoardSquare(u8);
+
+/// The disc drawn on a square, which lags behind the position while it turns over.
+#[derive(Component)]
+struct Disc {
+    /// Seconds into turning over, negative while waiting for its turn.
+    flip: Option<f32>,
+    shown: Option<Side>,
+    square: u8,
+}
+
+struct Game {
+    last_move: Option<u8>,
+    level: usize,
+    opponent: Opponent,
+    outcome: Option<Outcome>,
+    position: Position,
+}
+
+impl Game {
+    fn new(opponent: Opponent, level: usize) -> Self {
+        Self {
+            last_move: None,
+            level,
+            opponent,
+            outcome: None,
+            position: Position::start(),
+        }
+    }
+
+    fn ai_side(&self) -> Option<Side> {
+        match self.opponent {
+            Opponent::Ai(side) => Some(side),
+            Opponent::Human => None,
+        }
+    }
+
+    fn human_to_move(&self) -> bool {
+        self.outcome.is_none() && self.ai_side() != Some(self.position.side)
+    }
+
+    /// Plays a move and passes for the next side if it cannot move; returns the side that passed.
+    fn play(&mut self, square: u8) -> Option<Side> {
+        self.position = self.position.play(square);
+        self.last_move = Some(square);
+        self.outcome = self.position.outcome();
+        if self.outcome.is_none() && self.position.legal_moves().is_empty() {
+            let passed = self.position.side;
+            self.position = self.position.pass();
+            return Some(passed);
+        }
+        None
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    GameOver,
+}
+
+#[derive(Component)]
+struct HudText;
+
+struct MenuChoice {
+    level: usize,
+    opponent: Opponent,
+}
+
+#[derive(Component)]
+struct MenuText;
+
+/// A message over the board that fades out, e.g. when a side has to pass.
+#[derive(Component)]
+struct Notice(Timer);
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Opponent {
+    /// The AI plays the given side.
+    Ai(Side),
+    Human,
+}
+
+impl Opponent {
+    const ALL: [Opponent; 3] = [Self::Ai(Side::White), Self::Ai(Side::Black), Self::Human];
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::Ai(Side::White) => "Play black against the computer",
+            Self::Ai(Side::Black) => "Play white against the computer",
+            Self::Human => "Two players at one board",
+        }
+    }
+}
+
+#[derive(Component)]
+struct OverlayText;
+
+struct Sounds {
+    end: Handle<AudioSource>,
+    flip: Handle<AudioSource>,
+    invalid: Handle<AudioSource>,
+    pass: Handle<AudioSource>,
+    place: Handle<AudioSource>,
+}
+
+/// The AI searches on its own thread and leaves its move here when done.
+struct Thinking {
+    delay: Timer,
+    result: Option<Arc<Mutex<Option<u8>>>>,
+}
+
+pub struct ReversiPlugin;
+
+impl Plugin for ReversiPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(MenuChoice {
+                level: 1,
+                opponent: Opponent::Ai(Side::White),
+            })
+            .insert_resource(Game::new(Opponent::Ai(Side::White), 1))
+            .insert_resource(Thinking {
+                delay: Timer::from_seconds(AI_DELAY, false),
+                result: None,
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_enter(GameState::Menu)
+                    .with_system(despawn_all::<BoardEntity>)
+                    .with_system(menu_enter),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu)
+                    .with_system(despawn_all::<MenuText>)
+                    .with_system(board_spawn),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(mouse_input)
+                    .with_system(ai_turn)
+                    .with_system(outcome_check),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(game_over_input))
+            .add_system(board_visuals)
+            .add_system(discs_update)
+            .add_system(escape_input)
+            .add_system(hud_update)
+            .add_system(notice_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Reversi".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Starts a search when the AI is to move and plays its move once the search thread is done.
+fn ai_turn(
+    mut commands: Commands,
+    time: Res<Time>,
+    font: Res<HudFont>,
+    mut game: ResMut<Game>,
+    mut thinking: ResMut<Thinking>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.outcome.is_some() || game.ai_side() != Some(game.position.side) {
+        return;
+    }
+    if thinking.result.is_none() {
+        let result = Arc::new(Mutex::new(None));
+        let found = result.clone();
+        let position = game.position.clone();
+        let depth = ai::LEVELS[game.level];
+        thread::spawn(move || {
+            *found.lock().unwrap() = ai::best_move(&position, depth);
+        });
+        thinking.result = Some(result);
+        thinking.delay.reset();
+        return;
+    }
+    if !thinking.delay.tick(time.delta()).finished() {
+        return;
+    }
+    let found = thinking
+        .result
+        .as_ref()
+        .and_then(|result| result.lock().unwrap().take());
+    if let Some(square) = found {
+        thinking.result = None;
+        play(&mut commands, &font, &mut game, square, &audio, &sounds);
+    }
+}
+
+fn board_spawn(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: BLACK_COLOR,
+                custom_size: Some(Vec2::splat(BOARD_SIZE + SQUARE_GAP)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(BOARD_CENTER.extend(0.0)),
+            ..Default::default()
+        })
+        .insert(BoardEntity);
+    for square in 0..64 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: SQUARE_COLOR,
+                    custom_size: Some(Vec2::splat(SQUARE_SIZE - SQUARE_GAP)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(square_center(square).extend(1.0)),
+                ..Default::default()
+            })
+            .insert(BoardSquare(square))
+            .insert(BoardEntity);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                "\u{25CF}",
+                DISC_FONT_SIZE,
+                BLACK_COLOR,
+                square_center(square).extend(2.0),
+            ))
+            .insert(Disc {
+                flip: None,
+                shown: None,
+                square,
+            })
+            .insert(BoardEntity);
+    }
+}
+
+/// Marks the last move and, on a human's turn, the squares they may play.
+fn board_visuals(game: Res<Game>, mut squares: Query<(&BoardSquare, &mut Sprite)>) {
+    if !game.is_changed() {
+        return;
+    }
+    let legal = if game.human_to_move() {
+        game.position.legal_moves()
+    } else {
+        Vec::new()
+    };
+    for (BoardSquare(square), mut sprite) in squares.iter_mut() {
+        sprite.color = if game.last_move == Some(*square) {
+            mix(SQUARE_COLOR, LAST_MOVE_COLOR, 0.45)
+        } else if legal.contains(square) {
+            mix(SQUARE_COLOR, LEGAL_COLOR, 0.35)
+        } else {
+            SQUARE_COLOR
+        };
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn disc_color(side: Side) -> Color {
+    match side {
+        Side::Black => BLACK_COLOR,
+        Side::White => WHITE_COLOR,
+    }
+}
+
+/// New discs pop in and outflanked ones turn over one after another, spreading out from the
+/// move. On a human's turn a faint disc previews the move under the mouse.
+fn discs_update(
+    time: Res<Time>,
+    windows: Res<Windows>,
+    game: Res<Game>,
+    mut discs: Query<(&mut Disc, &mut Transform, &mut Text, &mut Visibility)>,
+) {
+    let hovered = game_hud::cursor_world_position(&windows)
+        .and_then(square_at)
+        .filter(|square| game.human_to_move() && !game.position.flips(*square).is_empty());
+    for (mut disc, mut transform, mut text, mut visibility) in discs.iter_mut() {
+        let actual = game.position.squares[disc.square as usize];
+        if actual.is_none() {
+            disc.shown = None;
+            disc.flip = None;
+        } else if disc.shown != actual && disc.flip.is_none() {
+            // A new disc appears at once, turned discs wait for the ones closer to the move.
+            let distance = match (disc.shown, game.last_move) {
+                (Some(_), Some(last_move)) => {
+                    let columns = (othello::column(last_move) - othello::column(disc.square)).abs();
+                    let rows = (othello::row(last_move) - othello::row(disc.square)).abs();
+                    columns.max(rows) as f32
+                }
+                _ => 0.0,
+            };
+            disc.flip = Some(-distance * FLIP_STAGGER);
+        }
+
+        let mut scale = Vec3::ONE;
+        if let Some(elapsed) = disc.flip {
+            let elapsed = elapsed + time.delta_seconds();
+            let progress = (elapsed / FLIP_DURATION).clamp(0.0, 1.0);
+            if disc.shown.is_none() {
+                disc.shown = actual;
+                scale = Vec3::splat(0.0);
+            } else if progress >= 0.5 {
+                disc.shown = actual;
+            }
+            // Squeezed to an edge halfway through, like a coin turning over.
+            scale.x = (1.0 - 2.0 * progress).abs();
+            if elapsed >= FLIP_DURATION {
+                disc.flip = None;
+                scale = Vec3::ONE;
+            } else {
+                disc.flip = Some(elapsed);
+            }
+        }
+        transform.scale = scale;
+
+        match disc.shown {
+            Some(side) => {
+                visibility.is_visible = true;
+                text.sections[0].style.color = disc_color(side);
+            }
+            None if hovered == Some(disc.square) => {
+                visibility.is_visible = true;
+                let mut color = disc_color(game.position.side);
+                color.set_a(GHOST_ALPHA);
+                text.sections[0].style.color = color;
+            }
+            None => visibility.is_visible = false,
+        }
+    }
+}
+
+/// Escape leaves a game for the menu at any time.
+fn escape_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() != GameState::Menu {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let outcome = match game.outcome {
+        Some(outcome) => outcome,
+        None => return,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(BOARD_SIZE, 200.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(BOARD_CENTER.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText)
+        .insert(BoardEntity);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "{}\nBlack {}  -  White {}\n\nEnter: menu",
+                outcome_text(outcome),
+                game.position.count(Side::Black),
+                game.position.count(Side::White)
+            ),
+            28.0,
+            HUD_COLOR,
+            BOARD_CENTER.extend(100.0),
+        ))
+        .insert(OverlayText)
+        .insert(BoardEntity);
+}
+
+fn game_over_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn hud_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    let value = if *state.current() == GameState::Menu {
+        String::new()
+    } else {
+        let status = match game.outcome {
+            Some(outcome) => outcome_text(outcome),
+            None => format!("{} to move", game.position.side.name()),
+        };
+        format!(
+            "{}   BLACK {}   WHITE {}",
+            status,
+            game.position.count(Side::Black),
+            game.position.count(Side::White)
+        )
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, choice: Res<MenuChoice>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&choice),
+            26.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    menu: Res<MenuInput>,
+    mut choice: ResMut<MenuChoice>,
+    mut game: ResMut<Game>,
+    mut thinking: ResMut<Thinking>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let index = Opponent::ALL
+        .iter()
+        .position(|opponent| *opponent == choice.opponent)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) {
+        choice.opponent = Opponent::ALL[index.saturating_sub(1)];
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.opponent = Opponent::ALL[(index + 1).min(Opponent::ALL.len() - 1)];
+    } else if menu.just_pressed(Navigate::Left) {
+        choice.level = choice.level.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Right) {
+        choice.level = (choice.level + 1).min(ai::LEVELS.len() - 1);
+    } else if menu.just_pressed(Navigate::Confirm) {
+        *game = Game::new(choice.opponent, choice.level);
+        thinking.result = None;
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&choice);
+    }
+}
+
+fn menu_label(choice: &MenuChoice) -> String {
+    let mut label = "REVERSI\n\n".to_string();
+    for opponent in Opponent::ALL {
+        let marker = if opponent == choice.opponent {
+            ">"
+        } else {
+            " "
+        };
+        label.push_str(&format!("{} {}\n", marker, opponent.description()));
+    }
+    label.push_str(&format!(
+        "\nComputer level < {} >\n\nUp/Down: opponent\nLeft/Right: level\nEnter: start",
+        choice.level + 1
+    ));
+    label
+}
+
+/// Blends `color` over `base` by `amount`.
+fn mix(base: Color, color: Color, amount: f32) -> Color {
+    Color::rgb(
+        base.r() + (color.r() - base.r()) * amount,
+        base.g() + (color.g() - base.g()) * amount,
+        base.b() + (color.b() - base.b()) * amount,
+    )
+}
+
+fn mouse_input(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.human_to_move() || !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let square = match game_hud::cursor_world_position(&windows).and_then(square_at) {
+        Some(square) => square,
+        None => return,
+    };
+    if game.position.flips(square).is_empty() {
+        audio.play(sounds.invalid.clone());
+        return;
+    }
+    play(&mut commands, &font, &mut game, square, &audio, &sounds);
+}
+
+fn notice_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut notices: Query<(Entity, &mut Notice, &mut Text)>,
+) {
+    for (entity, mut notice, mut text) in notices.iter_mut() {
+        notice.0.tick(time.delta());
+        if notice.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        text.sections[0]
+            .style
+            .color
+            .set_a((1.0 - notice.0.percent()) * 2.0);
+    }
+}
+
+fn outcome_check(game: Res<Game>, mut state: ResMut<State<GameState>>) {
+    if game.outcome.is_some() {
+        state.set(GameState::GameOver).unwrap();
+    }
+}
+
+fn outcome_text(outcome: Outcome) -> String {
+    match outcome {
+        Outcome::Win(side) => format!("{} wins", side.name()),
+        Outcome::Draw => "Draw".to_string(),
+    }
+}
+
+fn play(
+    commands: &mut Commands,
+    font: &HudFont,
+    game: &mut Game,
+    square: u8,
+    audio: &Audio,
+    sounds: &Sounds,
+) {
+    let flips = game.position.flips(square).len();
+    let passed = game.play(square);
+    audio.play(sounds.place.clone());
+    if flips > 0 {
+        audio.play(sounds.flip.clone());
+    }
+    if game.outcome.is_some() {
+        audio.play(sounds.end.clone());
+    } else if let Some(side) = passed {
+        audio.play(sounds.pass.clone());
+        commands
+            .spawn_bundle(game_hud::world_text(
+                font,
+                format!("{} has no move and passes", side.name()),
+                30.0,
+                HUD_COLOR,
+                BOARD_CENTER.extend(50.0),
+            ))
+            .insert(Notice(Timer::from_seconds(NOTICE_SECONDS, false)))
+            .insert(BoardEntity);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(MARGIN),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Click a highlighted square to place a disc   Esc: menu",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(6.0),
+            left: Val::Px(MARGIN),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        end: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.15),
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.4),
+        ])),
+        flip: audio_sources.add(
+            Tone::new(Waveform::Sine, 900.0, 0.12)
+                .slide(1400.0)
+                .fade()
+                .volume(0.15)
+                .into(),
+        ),
+        invalid: audio_sources.add(Tone::new(Waveform::Square, 160.0, 0.15).volume(0.15).into()),
+        pass: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 440.0, 0.12),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+        ])),
+        place: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.05)
+                .fade()
+                .volume(0.35)
+                .into(),
+        ),
+    });
+}
+
+fn square_at(point: Vec2) -> Option<u8> {
+    let cell = ((point - BOARD_CENTER) / SQUARE_SIZE + Vec2::splat(4.0)).floor();
+    if cell.x < 0.0 || cell.y < 0.0 || cell.x >= 8.0 || cell.y >= 8.0 {
+        return None;
+    }
+    // Rows count down from the top.
+    Some((7 - cell.y as u8) * 8 + cell.x as u8)
+}
+
+fn square_center(square: u8) -> Vec2 {
+    let (column, row) = (othello::column(square) as f32, othello::row(square) as f32);
+    BOARD_CENTER + Vec2::new(column - 3.5, 3.5 - row) * SQUARE_SIZE
+}
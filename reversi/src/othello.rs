@@ -0,0 +1,205 @@
+//! Squares are numbered row by row from the top left, `row * 8 + column`.
+
+const DIRECTIONS: [(i8, i8); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Side {
+    Black,
+    White,
+}
+
+impl Side {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Black => "Black",
+            Self::White => "White",
+        }
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Black => Self::White,
+            Self::White => Self::Black,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win(Side),
+    Draw,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Position {
+    pub side: Side,
+    pub squares: [Option<Side>; 64],
+}
+
+pub fn column(square: u8) -> i8 {
+    (square % 8) as i8
+}
+
+pub fn row(square: u8) -> i8 {
+    (square / 8) as i8
+}
+
+fn offset(square: u8, (column_step, row_step): (i8, i8)) -> Option<u8> {
+    let column = column(square) + column_step;
+    let row = row(square) + row_step;
+    if (0..8).contains(&column) && (0..8).contains(&row) {
+        Some((row * 8 + column) as u8)
+    } else {
+        None
+    }
+}
+
+impl Position {
+    /// The four discs in the middle, black to move.
+    pub fn start() -> Self {
+        let mut squares = [None; 64];
+        squares[27] = Some(Side::White);
+        squares[28] = Some(Side::Black);
+        squares[35] = Some(Side::Black);
+        squares[36] = Some(Side::White);
+        Self {
+            side: Side::Black,
+            squares,
+        }
+    }
+
+    pub fn count(&self, side: Side) -> usize {
+        self.squares
+            .iter()
+            .filter(|square| **square == Some(side))
+            .count()
+    }
+
+    /// The discs a disc of the side to move on `square` would turn over; empty if the move is
+    /// not legal.
+    pub fn flips(&self, square: u8) -> Vec<u8> {
+        if self.squares[square as usize].is_some() {
+            return Vec::new();
+        }
+        let mut flips = Vec::new();
+        for direction in DIRECTIONS {
+            let mut line = Vec::new();
+            let mut next = offset(square, direction);
+            while let Some(current) = next {
+                match self.squares[current as usize] {
+                    Some(side) if side == self.side => {
+                        flips.extend(line);
+                        break;
+                    }
+                    Some(_) => line.push(current),
+                    None => break,
+                }
+                next = offset(current, direction);
+            }
+        }
+        flips
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.legal_moves().is_empty() && self.pass().legal_moves().is_empty()
+    }
+
+    pub fn legal_moves(&self) -> Vec<u8> {
+        (0..64)
+            .filter(|square| !self.flips(*square).is_empty())
+            .collect()
+    }
+
+    /// Once neither side can move, whoever has more discs wins.
+    pub fn outcome(&self) -> Option<Outcome> {
+        if !self.is_over() {
+            return None;
+        }
+        let (black, white) = (self.count(Side::Black), self.count(Side::White));
+        Some(if black > white {
+            Outcome::Win(Side::Black)
+        } else if white > black {
+            Outcome::Win(Side::White)
+        } else {
+            Outcome::Draw
+        })
+    }
+
+    /// The same discs with the other side to move, for when the side to move has no move.
+    pub fn pass(&self) -> Position {
+        Position {
+            side: self.side.opposite(),
+            squares: self.squares,
+        }
+    }
+
+    /// Places a disc and turns over the discs it outflanks. The move must be legal.
+    pub fn play(&self, square: u8) -> Position {
+        let mut next = self.pass();
+        next.squares[square as usize] = Some(self.side);
+        for flipped in self.flips(square) {
+            next.squares[flipped as usize] = Some(self.side);
+        }
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn start_has_four_moves() {
+        let position = Position::start();
+        assert_eq!(position.legal_moves(), vec![19, 26, 37, 44]);
+        let next = position.play(19);
+        assert_eq!(next.side, Side::White);
+        assert_eq!(next.count(Side::Black), 4);
+        assert_eq!(next.count(Side::White), 1);
+    }
+
+    #[test]
+    fn flips_every_outflanked_line() {
+        let mut squares = [None; 64];
+        // A white disc surrounded by black lines in two directions from square 0.
+        for square in [1, 8, 9] {
+            squares[square] = Some(Side::White);
+        }
+        for square in [2, 16, 18] {
+            squares[square] = Some(Side::Black);
+        }
+        let position = Position {
+            side: Side::Black,
+            squares,
+        };
+        let mut flips = position.flips(0);
+        flips.sort_unstable();
+        assert_eq!(flips, vec![1, 8, 9]);
+        assert!(position.flips(3).is_empty());
+    }
+
+    #[test]
+    fn a_side_without_moves_passes_and_the_game_ends_when_neither_can_move() {
+        let mut squares = [None; 64];
+        squares[0] = Some(Side::Black);
+        squares[1] = Some(Side::White);
+        let position = Position {
+            side: Side::White,
+            squares,
+        };
+        assert!(position.legal_moves().is_empty());
+        assert!(!position.is_over(), "black can still move");
+        let next = position.pass().play(2);
+        assert!(next.is_over());
+        assert_eq!(next.outcome(), Some(Outcome::Win(Side::Black)));
+    }
+}
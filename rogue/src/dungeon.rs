@@ -0,0 +1,242 @@
+//! Dungeon floors: rectangular rooms joined by corridors, generated without any bevy systems.
+
+use bevy::math::{const_ivec2, IVec2};
+use rand::Rng;
+use std::collections::VecDeque;
+
+const MAX_ROOMS: usize = 14;
+const ROOM_ATTEMPTS: usize = 200;
+const ROOM_HEIGHT: (i32, i32) = (4, 8);
+const ROOM_WIDTH: (i32, i32) = (5, 13);
+
+/// The eight neighbours of a cell, orthogonal first.
+pub const NEIGHBOURS: [IVec2; 8] = [
+    const_ivec2!([1, 0]),
+    const_ivec2!([-1, 0]),
+    const_ivec2!([0, 1]),
+    const_ivec2!([0, -1]),
+    const_ivec2!([1, 1]),
+    const_ivec2!([1, -1]),
+    const_ivec2!([-1, 1]),
+    const_ivec2!([-1, -1]),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    Floor,
+    Stairs,
+    Wall,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Room {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+impl Room {
+    pub fn center(&self) -> IVec2 {
+        (self.min + self.max) / 2
+    }
+
+    /// Keeps at least one wall between rooms.
+    fn overlaps(&self, other: &Room) -> bool {
+        self.min.x <= other.max.x + 1
+            && self.max.x + 1 >= other.min.x
+            && self.min.y <= other.max.y + 1
+            && self.max.y + 1 >= other.min.y
+    }
+
+    pub fn random_point(&self, rng: &mut impl Rng) -> IVec2 {
+        IVec2::new(
+            rng.gen_range(self.min.x, self.max.x + 1),
+            rng.gen_range(self.min.y, self.max.y + 1),
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Map {
+    pub height: i32,
+    pub tiles: Vec<Tile>,
+    pub width: i32,
+}
+
+impl Map {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            height,
+            tiles: vec![Tile::Wall; (width * height) as usize],
+            width,
+        }
+    }
+
+    /// Steps needed to reach every cell from `from`, moving diagonally too; `None` where it cannot be reached.
+    pub fn distances(&self, from: IVec2) -> Vec<Option<u32>> {
+        let mut distances = vec![None; self.tiles.len()];
+        let mut queue = VecDeque::new();
+        if let Some(start) = self.index(from) {
+            distances[start] = Some(0);
+            queue.push_back(from);
+        }
+        while let Some(cell) = queue.pop_front() {
+            let distance = distances[self.index(cell).unwrap()].unwrap();
+            for neighbour in NEIGHBOURS.iter().map(|offset| cell + *offset) {
+                if let Some(index) = self.index(neighbour) {
+                    if distances[index].is_none() && self.walkable(neighbour) {
+                        distances[index] = Some(distance + 1);
+                        queue.push_back(neighbour);
+                    }
+                }
+            }
+        }
+        distances
+    }
+
+    /// Everything outside the map is solid wall.
+    pub fn get(&self, cell: IVec2) -> Tile {
+        self.index(cell)
+            .map_or(Tile::Wall, |index| self.tiles[index])
+    }
+
+    pub fn index(&self, cell: IVec2) -> Option<usize> {
+        if cell.x >= 0 && cell.y >= 0 && cell.x < self.width && cell.y < self.height {
+            Some((cell.y * self.width + cell.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn point(&self, index: usize) -> IVec2 {
+        IVec2::new(index as i32 % self.width, index as i32 / self.width)
+    }
+
+    fn set(&mut self, cell: IVec2, tile: Tile) {
+        if let Some(index) = self.index(cell) {
+            self.tiles[index] = tile;
+        }
+    }
+
+    pub fn walkable(&self, cell: IVec2) -> bool {
+        self.get(cell) != Tile::Wall
+    }
+}
+
+pub struct Floor {
+    pub map: Map,
+    /// In order of creation; the first one holds the start, the last one the stairs.
+    pub rooms: Vec<Room>,
+    pub stairs: IVec2,
+    pub start: IVec2,
+}
+
+/// Places non-overlapping rooms at random and joins every room to the previous one with an L-shaped corridor.
+pub fn generate(width: i32, height: i32, rng: &mut impl Rng) -> Floor {
+    let mut map = Map::new(width, height);
+    let mut rooms: Vec<Room> = Vec::new();
+    for _ in 0..ROOM_ATTEMPTS {
+        if rooms.len() >= MAX_ROOMS {
+            break;
+        }
+        let size = IVec2::new(
+            rng.gen_range(ROOM_WIDTH.0, ROOM_WIDTH.1 + 1),
+            rng.gen_range(ROOM_HEIGHT.0, ROOM_HEIGHT.1 + 1),
+        );
+        // The outermost ring of the map always stays wall.
+        let min = IVec2::new(
+            rng.gen_range(1, width - size.x - 1),
+            rng.gen_range(1, height - size.y - 1),
+        );
+        let room = Room {
+            min,
+            max: min + size - IVec2::ONE,
+        };
+        if rooms.iter().any(|other| room.overlaps(other)) {
+            continue;
+        }
+        for y in room.min.y..=room.max.y {
+            for x in room.min.x..=room.max.x {
+                map.set(IVec2::new(x, y), Tile::Floor);
+            }
+        }
+        if let Some(previous) = rooms.last() {
+            dig_corridor(&mut map, previous.center(), room.center(), rng.gen());
+        }
+        rooms.push(room);
+    }
+    let start = rooms[0].center();
+    let stairs = rooms[rooms.len() - 1].center();
+    map.set(stairs, Tile::Stairs);
+    Floor {
+        map,
+        rooms,
+        stairs,
+        start,
+    }
+}
+
+fn dig_corridor(map: &mut Map, from: IVec2, to: IVec2, horizontal_first: bool) {
+    let corner = if horizontal_first {
+        IVec2::new(to.x, from.y)
+    } else {
+        IVec2::new(from.x, to.y)
+    };
+    for (start, end) in [(from, corner), (corner, to)] {
+        let step = (end - start).signum();
+        let mut cell = start;
+        map.set(cell, Tile::Floor);
+        while cell != end {
+            cell += step;
+            map.set(cell, Tile::Floor);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn rooms_are_separate_and_inside_the_map() {
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..20 {
+            let floor = generate(64, 38, &mut rng);
+            assert!(floor.rooms.len() >= 2);
+            for (index, room) in floor.rooms.iter().enumerate() {
+                assert!(room.min.x >= 1 && room.min.y >= 1);
+                assert!(room.max.x <= 62 && room.max.y <= 36);
+                assert!(floor.rooms[index + 1..]
+                    .iter()
+                    .all(|other| !room.overlaps(other)));
+            }
+        }
+    }
+
+    #[test]
+    fn every_floor_tile_is_reachable_from_the_start() {
+        let mut rng = StdRng::seed_from_u64(11);
+        for _ in 0..20 {
+            let floor = generate(64, 38, &mut rng);
+            let distances = floor.map.distances(floor.start);
+            for (index, tile) in floor.map.tiles.iter().enumerate() {
+                if *tile != Tile::Wall {
+                    assert!(distances[index].is_some());
+                }
+            }
+            assert_eq!(floor.map.get(floor.stairs), Tile::Stairs);
+            assert_ne!(floor.start, floor.stairs);
+        }
+    }
+
+    #[test]
+    fn outside_is_wall() {
+        let map = Map::new(4, 4);
+        assert_eq!(map.get(IVec2::new(-1, 2)), Tile::Wall);
+        assert_eq!(map.index(IVec2::new(4, 0)), None);
+        assert_eq!(
+            map.point(map.index(IVec2::new(3, 2)).unwrap()),
+            IVec2::new(3, 2)
+        );
+    }
+}
@@ -0,0 +1,97 @@
+//! Field of view: a cell is visible when a straight line from the viewer reaches it without crossing a wall.
+
+use crate::dungeon::{Map, Tile};
+use bevy::math::IVec2;
+
+/// Whether each cell of `map` can be seen from `origin` within `radius`; walls themselves are visible.
+pub fn visible(map: &Map, origin: IVec2, radius: i32) -> Vec<bool> {
+    let mut visible = vec![false; map.tiles.len()];
+    for y in origin.y - radius..=origin.y + radius {
+        for x in origin.x - radius..=origin.x + radius {
+            let target = IVec2::new(x, y);
+            let index = match map.index(target) {
+                Some(index) => index,
+                None => continue,
+            };
+            let offset = target - origin;
+            if offset.x * offset.x + offset.y * offset.y > radius * radius {
+                continue;
+            }
+            visible[index] = line(origin, target)
+                .iter()
+                .skip(1)
+                .take_while(|cell| **cell != target)
+                .all(|cell| map.get(*cell) != Tile::Wall);
+        }
+    }
+    visible
+}
+
+/// The cells of a Bresenham line from `from` to `to`, both included.
+pub fn line(from: IVec2, to: IVec2) -> Vec<IVec2> {
+    let delta = (to - from).abs();
+    let step = (to - from).signum();
+    let mut error = delta.x - delta.y;
+    let mut cell = from;
+    let mut cells = vec![cell];
+    while cell != to {
+        let doubled = 2 * error;
+        if doubled > -delta.y {
+            error -= delta.y;
+            cell.x += step.x;
+        }
+        if doubled < delta.x {
+            error += delta.x;
+            cell.y += step.y;
+        }
+        cells.push(cell);
+    }
+    cells
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 9x9 open room surrounded by wall, with a pillar at (6, 4).
+    fn room() -> Map {
+        let mut map = Map::new(11, 11);
+        for y in 1..10 {
+            for x in 1..10 {
+                let index = map.index(IVec2::new(x, y)).unwrap();
+                map.tiles[index] = Tile::Floor;
+            }
+        }
+        let pillar = map.index(IVec2::new(6, 4)).unwrap();
+        map.tiles[pillar] = Tile::Wall;
+        map
+    }
+
+    #[test]
+    fn line_ends_on_both_points() {
+        let cells = line(IVec2::new(0, 0), IVec2::new(5, 2));
+        assert_eq!(cells.first(), Some(&IVec2::new(0, 0)));
+        assert_eq!(cells.last(), Some(&IVec2::new(5, 2)));
+        assert_eq!(cells.len(), 6);
+    }
+
+    #[test]
+    fn walls_block_sight() {
+        let map = room();
+        let visible = visible(&map, IVec2::new(4, 4), 8);
+        let seen = |x, y| visible[map.index(IVec2::new(x, y)).unwrap()];
+        assert!(seen(4, 4));
+        assert!(seen(1, 9));
+        assert!(seen(0, 4), "the room's own wall is visible");
+        assert!(seen(6, 4), "the pillar itself is visible");
+        assert!(!seen(8, 4), "the cell behind the pillar is hidden");
+    }
+
+    #[test]
+    fn radius_limits_sight() {
+        let map = room();
+        let visible = visible(&map, IVec2::new(1, 1), 3);
+        assert!(visible[map.index(IVec2::new(3, 3)).unwrap()]);
+        assert!(!visible[map.index(IVec2::new(9, 9)).unwrap()]);
+    }
+}
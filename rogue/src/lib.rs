@@ -0,0 +1,510 @@
+use bevy::prelude::*;
+use dungeon::Tile;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use rand::thread_rng;
+use run::{Action, ItemKind, MonsterKind, Run, MAP_HEIGHT, MAP_WIDTH};
+use serde::{Deserialize, Serialize};
+
+pub mod dungeon;
+pub mod fov;
+pub mod run;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.02, 0.02, 0.03);
+const FLOOR_COLOR: Color = Color::rgb(0.2, 0.19, 0.17);
+const GLYPH_FONT_SIZE: f32 = 15.0;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const LOG_HEIGHT: f32 = 110.0;
+const MAP_PIXEL_HEIGHT: f32 = MAP_HEIGHT as f32 * TILE_SIZE;
+const MAP_PIXEL_WIDTH: f32 = MAP_WIDTH as f32 * TILE_SIZE;
+const MARGIN: f32 = 20.0;
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const PLAYER_COLOR: Color = Color::rgb(1.0, 1.0, 1.0);
+/// How much of its lit color a remembered but unseen cell keeps.
+const REMEMBERED_BRIGHTNESS: f32 = 0.4;
+const RECORDS_FILE: &str = "records";
+const STAIRS_COLOR: Color = Color::rgb(0.95, 0.85, 0.3);
+const TILE_SIZE: f32 = 14.0;
+const WALL_COLOR: Color = Color::rgb(0.5, 0.44, 0.36);
+
+pub const WINDOW_HEIGHT: f32 = MAP_PIXEL_HEIGHT + HUD_HEIGHT + LOG_HEIGHT;
+pub const WINDOW_WIDTH: f32 = MAP_PIXEL_WIDTH + 2.0 * MARGIN;
+
+const MAP_LEFT: f32 = -MAP_PIXEL_WIDTH / 2.0;
+const MAP_TOP: f32 = WINDOW_HEIGHT / 2.0 - HUD_HEIGHT;
+
+/// The player, monsters, items and stairs, redrawn after every turn.
+#[derive(Component)]
+struct Glyph;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    Dead,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct LogText;
+
+#[derive(Component)]
+struct OverlayText;
+
+/// Best results over all runs, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records {
+    deepest: u32,
+    most_gold: u32,
+    runs: u32,
+}
+
+impl Records {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("rogue", RECORDS_FILE, self) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+struct Sounds {
+    death: Handle<AudioSource>,
+    descend: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    hurt: Handle<AudioSource>,
+    pick_up: Handle<AudioSource>,
+}
+
+/// The map cell a background sprite stands for.
+#[derive(Component)]
+struct TileSprite(usize);
+
+pub struct RoguePlugin;
+
+impl Plugin for RoguePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Run::new(&mut thread_rng()))
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                "rogue",
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_startup_system(setup)
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(keyboard_input)
+                    .with_system(death_check),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Dead).with_system(run_summary))
+            .add_system_set(SystemSet::on_update(GameState::Dead).with_system(dead_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Dead).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(tiles_render)
+            .add_system(glyphs_render)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Rogue".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn cell_center(cell: IVec2) -> Vec2 {
+    Vec2::new(
+        MAP_LEFT + (cell.x as f32 + 0.5) * TILE_SIZE,
+        MAP_TOP - (cell.y as f32 + 0.5) * TILE_SIZE,
+    )
+}
+
+fn dead_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut run: ResMut<Run>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        *run = Run::new(&mut thread_rng());
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn death_check(run: Res<Run>, mut state: ResMut<State<GameState>>) {
+    if run.is_over() {
+        state.set(GameState::Dead).unwrap();
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Rebuilds the glyphs after every turn: remembered stairs and items, monsters in sight and the player.
+fn glyphs_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    run: Res<Run>,
+    glyphs: Query<Entity, With<Glyph>>,
+) {
+    if !run.is_changed() {
+        return;
+    }
+    for entity in glyphs.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let mut spawn = |glyph: char, color: Color, cell: IVec2| {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                glyph.to_string(),
+                GLYPH_FONT_SIZE,
+                color,
+                cell_center(cell).extend(1.0),
+            ))
+            .insert(Glyph);
+    };
+    let seen = |cell: IVec2| {
+        run.map
+            .index(cell)
+            .map_or(false, |index| run.visible[index])
+    };
+    let remembered = |cell: IVec2| {
+        run.map
+            .index(cell)
+            .map_or(false, |index| run.explored[index])
+    };
+    for (index, tile) in run.map.tiles.iter().enumerate() {
+        let cell = run.map.point(index);
+        if *tile == Tile::Stairs && remembered(cell) {
+            spawn('>', lit(STAIRS_COLOR, seen(cell)), cell);
+        }
+    }
+    for item in &run.items {
+        if remembered(item.position) {
+            spawn(
+                item.kind.glyph(),
+                lit(item_color(item.kind), seen(item.position)),
+                item.position,
+            );
+        }
+    }
+    for monster in &run.monsters {
+        if seen(monster.position) {
+            spawn(
+                monster.kind.glyph(),
+                monster_color(monster.kind),
+                monster.position,
+            );
+        }
+    }
+    spawn('@', PLAYER_COLOR, run.player);
+}
+
+fn hud_update(
+    run: Res<Run>,
+    records: Res<Records>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<LogText>>,
+    )>,
+) {
+    if !run.is_changed() && !records.is_changed() {
+        return;
+    }
+    let status = format!(
+        "DEPTH {}   HP {}/{}   ATK {}   DEF {}   POTIONS {}   SCROLLS {}   GOLD {}   TURN {}   DEEPEST {}",
+        run.depth,
+        run.stats.hp.max(0),
+        run.stats.max_hp,
+        run.stats.attack,
+        run.stats.defense,
+        run.potions,
+        run.scrolls,
+        run.gold,
+        run.turns,
+        records.deepest.max(run.depth),
+    );
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = status.clone();
+    }
+    let log = run.messages.join("\n");
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = log.clone();
+    }
+}
+
+fn item_color(kind: ItemKind) -> Color {
+    match kind {
+        ItemKind::Armor | ItemKind::Weapon => Color::rgb(0.5, 0.8, 1.0),
+        ItemKind::Gold(_) => Color::rgb(1.0, 0.85, 0.1),
+        ItemKind::Potion => Color::rgb(1.0, 0.35, 0.8),
+        ItemKind::Scroll => Color::rgb(0.9, 0.9, 0.75),
+    }
+}
+
+/// One key press is one action: arrows, numpad or vi keys to move and attack, the rest as listed in the help.
+fn keyboard_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut run: ResMut<Run>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let directions = [
+        (
+            [KeyCode::Left, KeyCode::Numpad4, KeyCode::H],
+            IVec2::new(-1, 0),
+        ),
+        (
+            [KeyCode::Right, KeyCode::Numpad6, KeyCode::L],
+            IVec2::new(1, 0),
+        ),
+        (
+            [KeyCode::Up, KeyCode::Numpad8, KeyCode::K],
+            IVec2::new(0, -1),
+        ),
+        (
+            [KeyCode::Down, KeyCode::Numpad2, KeyCode::J],
+            IVec2::new(0, 1),
+        ),
+        (
+            [KeyCode::Home, KeyCode::Numpad7, KeyCode::Y],
+            IVec2::new(-1, -1),
+        ),
+        (
+            [KeyCode::PageUp, KeyCode::Numpad9, KeyCode::U],
+            IVec2::new(1, -1),
+        ),
+        (
+            [KeyCode::End, KeyCode::Numpad1, KeyCode::B],
+            IVec2::new(-1, 1),
+        ),
+        (
+            [KeyCode::PageDown, KeyCode::Numpad3, KeyCode::N],
+            IVec2::new(1, 1),
+        ),
+    ];
+    let action = if let Some((_, direction)) = directions
+        .iter()
+        .find(|(keys, _)| keyboard_input.any_just_pressed(*keys))
+    {
+        Action::Move(*direction)
+    } else if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Numpad5, KeyCode::Period]) {
+        Action::Wait
+    } else if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        Action::Descend
+    } else if keyboard_input.just_pressed(KeyCode::Q) {
+        Action::Quaff
+    } else if keyboard_input.just_pressed(KeyCode::R) {
+        Action::Read
+    } else {
+        return;
+    };
+    let (depth, hp, items, player) = (run.depth, run.stats.hp, run.items.len(), run.player);
+    if !run.act(action, &mut thread_rng()) {
+        return;
+    }
+    let sound = if run.is_over() {
+        &sounds.death
+    } else if run.depth > depth {
+        &sounds.descend
+    } else if run.stats.hp < hp {
+        &sounds.hurt
+    } else if run.items.len() < items {
+        &sounds.pick_up
+    } else if matches!(action, Action::Move(_)) && run.player == player {
+        // A step that did not move the player was an attack.
+        &sounds.hit
+    } else {
+        return;
+    };
+    audio.play(sound.clone());
+}
+
+/// Cells that are remembered but not in sight are drawn darker.
+fn lit(color: Color, seen: bool) -> Color {
+    if seen {
+        color
+    } else {
+        Color::rgb(
+            color.r() * REMEMBERED_BRIGHTNESS,
+            color.g() * REMEMBERED_BRIGHTNESS,
+            color.b() * REMEMBERED_BRIGHTNESS,
+        )
+    }
+}
+
+fn monster_color(kind: MonsterKind) -> Color {
+    match kind {
+        MonsterKind::Rat => Color::rgb(0.7, 0.55, 0.4),
+        MonsterKind::Goblin => Color::rgb(0.4, 0.85, 0.3),
+        MonsterKind::Orc => Color::rgb(0.75, 0.75, 0.2),
+        MonsterKind::Troll => Color::rgb(0.3, 0.6, 0.5),
+        MonsterKind::Dragon => Color::rgb(1.0, 0.25, 0.15),
+    }
+}
+
+/// Permadeath: the run is gone, only its summary and the records are left.
+fn run_summary(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    run: Res<Run>,
+    mut records: ResMut<Records>,
+) {
+    let deepest = run.depth > records.deepest;
+    records.runs += 1;
+    records.deepest = records.deepest.max(run.depth);
+    records.most_gold = records.most_gold.max(run.gold);
+    records.save();
+    let center = Vec2::new(0.0, MAP_TOP - MAP_PIXEL_HEIGHT / 2.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(MAP_PIXEL_WIDTH, 260.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "Killed by a {} on depth {}{}\n\n{} turns   {} kills   {} gold\n\nRun {}   Deepest {}   Most gold {}\n\nEnter: new run",
+                run.killer.unwrap_or("mystery"),
+                run.depth,
+                if deepest { ", a new record!" } else { "" },
+                run.turns,
+                run.kills,
+                run.gold,
+                records.runs,
+                records.deepest,
+                records.most_gold,
+            ),
+            22.0,
+            HUD_COLOR,
+            center.extend(100.0),
+        ))
+        .insert(OverlayText);
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    // Every floor has the same size, so the background sprites are spawned once and only recolored.
+    for index in 0..(MAP_WIDTH * MAP_HEIGHT) as usize {
+        let cell = IVec2::new(index as i32 % MAP_WIDTH, index as i32 / MAP_WIDTH);
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: BACKGROUND_COLOR,
+                    custom_size: Some(Vec2::splat(TILE_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(cell_center(cell).extend(0.0)),
+                ..Default::default()
+            })
+            .insert(TileSprite(index));
+    }
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            14.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(12.0),
+                left: Val::Px(MARGIN),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            14.0,
+            HUD_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(MARGIN),
+                ..Default::default()
+            },
+        ))
+        .insert(LogText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Move/attack: arrows, numpad, hjklyubn\nWait: Space   Quaff potion: Q\nRead scroll: R   Descend: Enter",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(8.0),
+            right: Val::Px(MARGIN),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        death: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.2).volume(0.25),
+            Tone::new(Waveform::Square, 247.0, 0.2).volume(0.25),
+            Tone::new(Waveform::Square, 165.0, 0.5)
+                .slide(82.0)
+                .volume(0.25),
+        ])),
+        descend: audio_sources.add(
+            Tone::new(Waveform::Triangle, 523.0, 0.35)
+                .slide(196.0)
+                .volume(0.4)
+                .into(),
+        ),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.08)
+                .fade()
+                .volume(0.35)
+                .into(),
+        ),
+        hurt: audio_sources.add(
+            Tone::new(Waveform::Square, 140.0, 0.12)
+                .slide(90.0)
+                .volume(0.25)
+                .into(),
+        ),
+        pick_up: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 784.0, 0.06),
+            Tone::new(Waveform::Triangle, 1047.0, 0.1),
+        ])),
+    });
+}
+
+/// Recolors the map after every turn: unexplored cells stay dark, remembered ones are dimmed.
+fn tiles_render(run: Res<Run>, mut sprites: Query<(&TileSprite, &mut Sprite)>) {
+    if !run.is_changed() {
+        return;
+    }
+    for (TileSprite(index), mut sprite) in sprites.iter_mut() {
+        sprite.color = if !run.explored[*index] {
+            BACKGROUND_COLOR
+        } else {
+            let color = match run.map.tiles[*index] {
+                Tile::Floor | Tile::Stairs => FLOOR_COLOR,
+                Tile::Wall => WALL_COLOR,
+            };
+            lit(color, run.visible[*index])
+        };
+    }
+}
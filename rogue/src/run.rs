@@ -0,0 +1,576 @@
+//! One run through the dungeon: the player, the monsters and items of the current floor, one turn at a time.
+
+use crate::dungeon::{self, Map, Tile, NEIGHBOURS};
+use crate::fov;
+use bevy::math::IVec2;
+use rand::Rng;
+
+pub const MAP_HEIGHT: i32 = 38;
+pub const MAP_WIDTH: i32 = 64;
+
+/// Messages kept in the log; older ones are dropped.
+const LOG_LENGTH: usize = 6;
+const POTION_HEALING: i32 = 10;
+/// The player gets one hit point back every this many turns.
+const REGENERATION_TURNS: u32 = 12;
+const SIGHT_RADIUS: i32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Stats {
+    pub attack: i32,
+    pub defense: i32,
+    pub hp: i32,
+    pub max_hp: i32,
+}
+
+impl Stats {
+    fn new(hp: i32, attack: i32, defense: i32) -> Self {
+        Self {
+            attack,
+            defense,
+            hp,
+            max_hp: hp,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MonsterKind {
+    Rat,
+    Goblin,
+    Orc,
+    Troll,
+    Dragon,
+}
+
+impl MonsterKind {
+    const ALL: [MonsterKind; 5] = [
+        Self::Rat,
+        Self::Goblin,
+        Self::Orc,
+        Self::Troll,
+        Self::Dragon,
+    ];
+
+    /// The shallowest floor it is found on.
+    fn depth(self) -> u32 {
+        match self {
+            Self::Rat | Self::Goblin => 1,
+            Self::Orc => 3,
+            Self::Troll => 5,
+            Self::Dragon => 8,
+        }
+    }
+
+    pub fn glyph(self) -> char {
+        match self {
+            Self::Rat => 'r',
+            Self::Goblin => 'g',
+            Self::Orc => 'o',
+            Self::Troll => 'T',
+            Self::Dragon => 'D',
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rat => "rat",
+            Self::Goblin => "goblin",
+            Self::Orc => "orc",
+            Self::Troll => "troll",
+            Self::Dragon => "dragon",
+        }
+    }
+
+    fn stats(self) -> Stats {
+        match self {
+            Self::Rat => Stats::new(4, 2, 0),
+            Self::Goblin => Stats::new(7, 3, 0),
+            Self::Orc => Stats::new(12, 5, 1),
+            Self::Troll => Stats::new(20, 7, 2),
+            Self::Dragon => Stats::new(32, 10, 4),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Monster {
+    /// Monsters sleep until they first see the player, then hunt them down.
+    pub awake: bool,
+    pub kind: MonsterKind,
+    pub position: IVec2,
+    pub stats: Stats,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Armor,
+    Gold(u32),
+    Potion,
+    /// Reveals the whole floor when read.
+    Scroll,
+    Weapon,
+}
+
+impl ItemKind {
+    pub fn glyph(self) -> char {
+        match self {
+            Self::Armor => '[',
+            Self::Gold(_) => '$',
+            Self::Potion => '!',
+            Self::Scroll => '?',
+            Self::Weapon => '/',
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Item {
+    pub kind: ItemKind,
+    pub position: IVec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Descend,
+    /// A step to a neighbouring cell, attacking whatever stands there.
+    Move(IVec2),
+    Quaff,
+    Read,
+    Wait,
+}
+
+pub struct Run {
+    pub depth: u32,
+    /// Cells seen at some point on this floor, drawn from memory when out of sight.
+    pub explored: Vec<bool>,
+    pub gold: u32,
+    pub items: Vec<Item>,
+    /// What killed the player; the run is over once this is set.
+    pub killer: Option<&'static str>,
+    pub kills: u32,
+    pub map: Map,
+    pub messages: Vec<String>,
+    pub monsters: Vec<Monster>,
+    pub player: IVec2,
+    pub potions: u32,
+    pub scrolls: u32,
+    pub stats: Stats,
+    pub turns: u32,
+    pub visible: Vec<bool>,
+}
+
+impl Run {
+    pub fn new(rng: &mut impl Rng) -> Self {
+        let mut run = Self {
+            depth: 0,
+            explored: Vec::new(),
+            gold: 0,
+            items: Vec::new(),
+            killer: None,
+            kills: 0,
+            map: Map::new(MAP_WIDTH, MAP_HEIGHT),
+            messages: Vec::new(),
+            monsters: Vec::new(),
+            player: IVec2::ZERO,
+            potions: 1,
+            scrolls: 0,
+            stats: Stats::new(20, 4, 1),
+            turns: 0,
+            visible: Vec::new(),
+        };
+        run.descend(rng);
+        run.log("Find the stairs and go as deep as you can.");
+        run
+    }
+
+    /// Plays the player's action and then the monsters' turn; returns whether a turn passed.
+    pub fn act(&mut self, action: Action, rng: &mut impl Rng) -> bool {
+        if self.killer.is_some() {
+            return false;
+        }
+        match action {
+            Action::Descend => {
+                if self.map.get(self.player) != Tile::Stairs {
+                    self.log("There are no stairs here.");
+                    return false;
+                }
+                self.descend(rng);
+                // A new floor starts with the player to move.
+                return true;
+            }
+            Action::Move(direction) => {
+                let target = self.player + direction;
+                if let Some(index) = self.monster_at(target) {
+                    self.attack_monster(index, rng);
+                } else if self.map.walkable(target) {
+                    self.player = target;
+                    self.pick_up();
+                } else {
+                    return false;
+                }
+            }
+            Action::Quaff => {
+                if self.potions == 0 {
+                    self.log("You have no potions.");
+                    return false;
+                }
+                self.potions -= 1;
+                self.stats.hp = (self.stats.hp + POTION_HEALING).min(self.stats.max_hp);
+                self.log("You feel better.");
+            }
+            Action::Read => {
+                if self.scrolls == 0 {
+                    self.log("You have no scrolls.");
+                    return false;
+                }
+                self.scrolls -= 1;
+                for index in 0..self.map.tiles.len() {
+                    let cell = self.map.point(index);
+                    // Walls are only worth showing where they border something walkable.
+                    if NEIGHBOURS
+                        .iter()
+                        .chain(&[IVec2::ZERO])
+                        .any(|offset| self.map.walkable(cell + *offset))
+                    {
+                        self.explored[index] = true;
+                    }
+                }
+                self.log("The scroll reveals the layout of this floor.");
+            }
+            Action::Wait => {}
+        }
+        self.turns += 1;
+        if self.turns % REGENERATION_TURNS == 0 {
+            self.stats.hp = (self.stats.hp + 1).min(self.stats.max_hp);
+        }
+        self.look();
+        self.monsters_act(rng);
+        true
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.killer.is_some()
+    }
+
+    pub fn monster_at(&self, cell: IVec2) -> Option<usize> {
+        self.monsters
+            .iter()
+            .position(|monster| monster.position == cell)
+    }
+
+    fn attack_monster(&mut self, index: usize, rng: &mut impl Rng) {
+        let damage = damage(&self.stats, &self.monsters[index].stats, rng);
+        let monster = &mut self.monsters[index];
+        monster.stats.hp -= damage;
+        monster.awake = true;
+        let name = monster.kind.name();
+        if monster.stats.hp <= 0 {
+            self.monsters.remove(index);
+            self.kills += 1;
+            self.log(format!("You kill the {}.", name));
+        } else {
+            self.log(format!("You hit the {} for {}.", name, damage));
+        }
+    }
+
+    /// Generates the next floor and fills it with monsters and items that get stronger with depth.
+    fn descend(&mut self, rng: &mut impl Rng) {
+        self.depth += 1;
+        let floor = dungeon::generate(MAP_WIDTH, MAP_HEIGHT, rng);
+        self.map = floor.map;
+        self.player = floor.start;
+        self.explored = vec![false; self.map.tiles.len()];
+        self.monsters.clear();
+        self.items.clear();
+        let kinds: Vec<MonsterKind> = MonsterKind::ALL
+            .iter()
+            .copied()
+            .filter(|kind| kind.depth() <= self.depth)
+            .collect();
+        // The first room, where the player arrives, is left empty.
+        for room in &floor.rooms[1..] {
+            for _ in 0..rng.gen_range(0, 3) {
+                let position = room.random_point(rng);
+                if self.monster_at(position).is_some() {
+                    continue;
+                }
+                // The newest kinds are the most common on their first floors.
+                let kind = kinds[rng.gen_range(kinds.len().saturating_sub(3), kinds.len())];
+                let mut stats = kind.stats();
+                let extra = (self.depth - kind.depth()) as i32;
+                stats.max_hp += extra * 2;
+                stats.hp = stats.max_hp;
+                stats.attack += extra / 2;
+                self.monsters.push(Monster {
+                    awake: false,
+                    kind,
+                    position,
+                    stats,
+                });
+            }
+            if rng.gen_bool(0.6) {
+                let kind = match rng.gen_range(0, 20) {
+                    0..=6 => ItemKind::Potion,
+                    7..=12 => ItemKind::Gold(rng.gen_range(5, 10 + 5 * self.depth)),
+                    13..=15 => ItemKind::Scroll,
+                    16..=17 => ItemKind::Weapon,
+                    _ => ItemKind::Armor,
+                };
+                let position = room.random_point(rng);
+                if position != floor.stairs && self.item_at(position).is_none() {
+                    self.items.push(Item { kind, position });
+                }
+            }
+        }
+        if self.depth > 1 {
+            self.log(format!("You descend to depth {}.", self.depth));
+        }
+        self.look();
+    }
+
+    fn item_at(&self, cell: IVec2) -> Option<usize> {
+        self.items.iter().position(|item| item.position == cell)
+    }
+
+    pub fn log(&mut self, message: impl Into<String>) {
+        self.messages.push(message.into());
+        if self.messages.len() > LOG_LENGTH {
+            self.messages.remove(0);
+        }
+    }
+
+    /// Updates what the player sees and remembers.
+    fn look(&mut self) {
+        self.visible = fov::visible(&self.map, self.player, SIGHT_RADIUS);
+        for (explored, visible) in self.explored.iter_mut().zip(&self.visible) {
+            *explored |= *visible;
+        }
+    }
+
+    /// Awake monsters next to the player attack, the others step along the shortest path towards them.
+    fn monsters_act(&mut self, rng: &mut impl Rng) {
+        let distances = self.map.distances(self.player);
+        for index in 0..self.monsters.len() {
+            let position = self.monsters[index].position;
+            let seen = self
+                .map
+                .index(position)
+                .map_or(false, |cell| self.visible[cell]);
+            if !self.monsters[index].awake {
+                if !seen {
+                    continue;
+                }
+                self.monsters[index].awake = true;
+            }
+            let offset = self.player - position;
+            if offset.x.abs() <= 1 && offset.y.abs() <= 1 {
+                let monster = &self.monsters[index];
+                let damage = damage(&monster.stats, &self.stats, rng);
+                let name = monster.kind.name();
+                self.stats.hp -= damage;
+                if self.stats.hp <= 0 {
+                    self.killer = Some(name);
+                    self.log(format!("The {} kills you.", name));
+                    return;
+                }
+                self.log(format!("The {} hits you for {}.", name, damage));
+                continue;
+            }
+            let distance = |cell: IVec2| {
+                self.map
+                    .index(cell)
+                    .and_then(|index| distances[index])
+                    .unwrap_or(u32::MAX)
+            };
+            let step = NEIGHBOURS
+                .iter()
+                .map(|neighbour| position + *neighbour)
+                .filter(|cell| self.monster_at(*cell).is_none())
+                .min_by_key(|cell| distance(*cell));
+            if let Some(step) = step {
+                if distance(step) < distance(position) {
+                    self.monsters[index].position = step;
+                }
+            }
+        }
+    }
+
+    fn pick_up(&mut self) {
+        let item = match self.item_at(self.player) {
+            Some(index) => self.items.remove(index),
+            None => return,
+        };
+        match item.kind {
+            ItemKind::Armor => {
+                self.stats.defense += 1;
+                self.log("You put on better armor.");
+            }
+            ItemKind::Gold(amount) => {
+                self.gold += amount;
+                self.log(format!("You pick up {} gold.", amount));
+            }
+            ItemKind::Potion => {
+                self.potions += 1;
+                self.log("You pick up a healing potion.");
+            }
+            ItemKind::Scroll => {
+                self.scrolls += 1;
+                self.log("You pick up a scroll of mapping.");
+            }
+            ItemKind::Weapon => {
+                self.stats.attack += 1;
+                self.log("You wield a sharper weapon.");
+            }
+        }
+    }
+}
+
+/// A hit between half and all of the attack, reduced by the defense but never below one.
+fn damage(attacker: &Stats, defender: &Stats, rng: &mut impl Rng) -> i32 {
+    (rng.gen_range(attacker.attack / 2, attacker.attack + 1) - defender.defense).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    /// A run on a single open room from (1, 1) to (10, 10) with stairs at (10, 10) and nothing else in it.
+    fn open_run(rng: &mut StdRng) -> Run {
+        let mut run = Run::new(rng);
+        run.map = Map::new(MAP_WIDTH, MAP_HEIGHT);
+        for y in 1..=10 {
+            for x in 1..=10 {
+                let index = run.map.index(IVec2::new(x, y)).unwrap();
+                run.map.tiles[index] = Tile::Floor;
+            }
+        }
+        let stairs = run.map.index(IVec2::new(10, 10)).unwrap();
+        run.map.tiles[stairs] = Tile::Stairs;
+        run.explored = vec![false; run.map.tiles.len()];
+        run.monsters.clear();
+        run.items.clear();
+        run.player = IVec2::new(2, 2);
+        run.look();
+        run
+    }
+
+    fn rat(position: IVec2) -> Monster {
+        Monster {
+            awake: false,
+            kind: MonsterKind::Rat,
+            position,
+            stats: MonsterKind::Rat.stats(),
+        }
+    }
+
+    #[test]
+    fn walls_do_not_take_a_turn() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut run = open_run(&mut rng);
+        run.player = IVec2::new(1, 1);
+        assert!(!run.act(Action::Move(IVec2::new(-1, 0)), &mut rng));
+        assert_eq!(run.turns, 0);
+        assert!(run.act(Action::Move(IVec2::new(1, 1)), &mut rng));
+        assert_eq!(run.player, IVec2::new(2, 2));
+        assert_eq!(run.turns, 1);
+    }
+
+    #[test]
+    fn bumping_attacks_until_the_monster_dies() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut run = open_run(&mut rng);
+        run.stats.max_hp = 1000;
+        run.stats.hp = 1000;
+        run.monsters.push(rat(IVec2::new(3, 2)));
+        for _ in 0..10 {
+            run.act(Action::Move(IVec2::new(1, 0)), &mut rng);
+            if run.monsters.is_empty() {
+                break;
+            }
+            assert_eq!(run.player, IVec2::new(2, 2), "attacking does not move");
+        }
+        assert!(run.monsters.is_empty());
+        assert_eq!(run.kills, 1);
+    }
+
+    #[test]
+    fn monsters_wake_up_and_chase() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let mut run = open_run(&mut rng);
+        run.monsters.push(rat(IVec2::new(7, 7)));
+        run.act(Action::Wait, &mut rng);
+        assert!(run.monsters[0].awake);
+        assert_eq!(run.monsters[0].position, IVec2::new(6, 6));
+        let unseen = IVec2::new(40, 30);
+        run.monsters.push(rat(unseen));
+        run.act(Action::Wait, &mut rng);
+        assert!(!run.monsters[1].awake);
+        assert_eq!(run.monsters[1].position, unseen);
+    }
+
+    #[test]
+    fn dying_ends_the_run() {
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut run = open_run(&mut rng);
+        run.stats.hp = 1;
+        run.stats.defense = 0;
+        run.monsters.push(rat(IVec2::new(3, 3)));
+        run.act(Action::Wait, &mut rng);
+        assert!(run.is_over());
+        assert_eq!(run.killer, Some("rat"));
+        assert!(!run.act(Action::Wait, &mut rng));
+    }
+
+    #[test]
+    fn items_are_picked_up_and_used() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut run = open_run(&mut rng);
+        run.potions = 0;
+        run.items.push(Item {
+            kind: ItemKind::Potion,
+            position: IVec2::new(3, 2),
+        });
+        run.items.push(Item {
+            kind: ItemKind::Weapon,
+            position: IVec2::new(4, 2),
+        });
+        let attack = run.stats.attack;
+        run.act(Action::Move(IVec2::new(1, 0)), &mut rng);
+        run.act(Action::Move(IVec2::new(1, 0)), &mut rng);
+        assert!(run.items.is_empty());
+        assert_eq!(run.stats.attack, attack + 1);
+        run.stats.hp = run.stats.max_hp - 3;
+        assert!(run.act(Action::Quaff, &mut rng));
+        assert_eq!(run.stats.hp, run.stats.max_hp);
+        assert!(!run.act(Action::Quaff, &mut rng));
+    }
+
+    #[test]
+    fn scroll_maps_the_floor() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let mut run = Run::new(&mut rng);
+        run.scrolls = 1;
+        let stairs = run.map.tiles.iter().position(|tile| *tile == Tile::Stairs);
+        assert!(run.act(Action::Read, &mut rng));
+        assert!(run.explored[stairs.unwrap()]);
+        let explored = run.explored.iter().filter(|explored| **explored).count();
+        assert!(
+            explored < run.map.tiles.len(),
+            "walls away from the rooms stay hidden"
+        );
+    }
+
+    #[test]
+    fn stairs_lead_one_floor_deeper() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut run = open_run(&mut rng);
+        assert!(!run.act(Action::Descend, &mut rng));
+        run.player = IVec2::new(10, 10);
+        assert!(run.act(Action::Descend, &mut rng));
+        assert_eq!(run.depth, 2);
+        assert_eq!(run.map.get(run.player), Tile::Floor);
+        assert!(run.visible[run.map.index(run.player).unwrap()]);
+    }
+}
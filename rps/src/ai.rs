@@ -0,0 +1,110 @@
+//! The computer's side: it guesses the player's next move from the moves so far and plays
+//! something that beats it.
+
+use crate::rules::{Move, Variant};
+use rand::{seq::SliceRandom, Rng};
+use std::collections::HashMap;
+
+/// Chance of a random move instead of the counter, so the computer cannot be led by the nose.
+const NOISE: f64 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    Random,
+    /// Expects the move the player has made most.
+    Frequency,
+    /// Expects what most often followed the player's last this many moves, falling back to
+    /// shorter runs and then to the most frequent move.
+    Markov(usize),
+}
+
+impl Strategy {
+    /// What the computer plays next.
+    pub fn choose(self, variant: Variant, history: &[Move], rng: &mut impl Rng) -> Move {
+        let predicted = match self.predict(history, rng) {
+            Some(predicted) if !rng.gen_bool(NOISE) => predicted,
+            _ => return *variant.moves().choose(rng).unwrap(),
+        };
+        *predicted.beaten_by(variant).choose(rng).unwrap()
+    }
+
+    /// The player's likely next move, if there is anything to go on.
+    pub fn predict(self, history: &[Move], rng: &mut impl Rng) -> Option<Move> {
+        match self {
+            Self::Random => None,
+            Self::Frequency => most_common(history.iter().copied(), rng),
+            Self::Markov(order) => (1..=order)
+                .rev()
+                .find_map(|length| follow_up(history, length, rng))
+                .or_else(|| most_common(history.iter().copied(), rng)),
+        }
+    }
+}
+
+/// What most often came after the last `length` moves, the last time round excluded.
+fn follow_up(history: &[Move], length: usize, rng: &mut impl Rng) -> Option<Move> {
+    if history.len() <= length {
+        return None;
+    }
+    let context = &history[history.len() - length..];
+    let followers = history
+        .windows(length + 1)
+        .filter(|window| &window[..length] == context)
+        .map(|window| window[length]);
+    most_common(followers, rng)
+}
+
+/// The move seen most, ties broken at random.
+fn most_common(moves: impl Iterator<Item = Move>, rng: &mut impl Rng) -> Option<Move> {
+    let mut counts: HashMap<Move, usize> = HashMap::new();
+    for played in moves {
+        *counts.entry(played).or_default() += 1;
+    }
+    let most = *counts.values().max()?;
+    let mut tied: Vec<Move> = counts
+        .into_iter()
+        .filter(|(_, count)| *count == most)
+        .map(|(played, _)| played)
+        .collect();
+    // The map's order is not fixed; sorting keeps seeded runs repeatable.
+    tied.sort_by_key(|played| *played as usize);
+    tied.choose(rng).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+    use Move::*;
+
+    #[test]
+    fn predicts_from_frequency_and_patterns() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let history = [Rock, Rock, Paper, Rock];
+        assert_eq!(Strategy::Frequency.predict(&history, &mut rng), Some(Rock));
+        assert_eq!(Strategy::Random.predict(&history, &mut rng), None);
+        assert_eq!(Strategy::Frequency.predict(&[], &mut rng), None);
+        // Rock has been followed by paper twice and by rock once.
+        let cycle = [Rock, Paper, Scissors, Rock, Paper, Scissors, Rock, Rock];
+        assert_eq!(Strategy::Markov(1).predict(&cycle, &mut rng), Some(Paper));
+        let pairs = [Rock, Rock, Paper, Paper, Rock, Rock, Paper, Paper, Rock];
+        assert_eq!(Strategy::Markov(2).predict(&pairs, &mut rng), Some(Rock));
+    }
+
+    #[test]
+    fn markov_beats_a_player_stuck_in_a_cycle() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let cycle = [Rock, Paper, Scissors];
+        let mut history = Vec::new();
+        let mut wins = 0;
+        for round in 0..60 {
+            let player = cycle[round % 3];
+            let computer = Strategy::Markov(2).choose(Variant::Classic, &history, &mut rng);
+            if computer.verb(player).is_some() {
+                wins += 1;
+            }
+            history.push(player);
+        }
+        assert!(wins > 40, "only {} wins", wins);
+    }
+}
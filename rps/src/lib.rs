@@ -0,0 +1,488 @@
+use ai::Strategy;
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use rules::{Move, Outcome, Variant};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub mod ai;
+pub mod rules;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.1, 0.14);
+const DRAW_COLOR: Color = Color::rgb(0.85, 0.85, 0.6);
+const GAME: &str = "rps";
+/// Keys for the moves, in the order of `Variant::moves`, next to the number keys.
+const KEYS: [(KeyCode, KeyCode); 5] = [
+    (KeyCode::Key1, KeyCode::R),
+    (KeyCode::Key2, KeyCode::P),
+    (KeyCode::Key3, KeyCode::S),
+    (KeyCode::Key4, KeyCode::L),
+    (KeyCode::Key5, KeyCode::K),
+];
+const LOSS_COLOR: Color = Color::rgb(0.95, 0.35, 0.3);
+const RECORDS_FILE: &str = "records";
+/// The opponents to beat one after another, each in a longer match.
+const RUNGS: [Rung; 5] = [
+    Rung {
+        best_of: 3,
+        name: "The Rookie",
+        strategy: Strategy::Random,
+    },
+    Rung {
+        best_of: 3,
+        name: "The Counter",
+        strategy: Strategy::Frequency,
+    },
+    Rung {
+        best_of: 5,
+        name: "The Pattern Reader",
+        strategy: Strategy::Markov(1),
+    },
+    Rung {
+        best_of: 5,
+        name: "The Mind Reader",
+        strategy: Strategy::Markov(2),
+    },
+    Rung {
+        best_of: 7,
+        name: "The Grandmaster",
+        strategy: Strategy::Markov(3),
+    },
+];
+const TEXT_COLOR: Color = Color::rgb(0.92, 0.93, 0.96);
+const WIN_COLOR: Color = Color::rgb(0.4, 0.9, 0.45);
+
+pub const WINDOW_HEIGHT: f32 = 480.0;
+pub const WINDOW_WIDTH: f32 = 720.0;
+
+struct Game {
+    /// Rounds won in a row.
+    best_streak: u32,
+    computer_wins: u32,
+    /// Every move the player has made on the ladder, for the computer to learn from.
+    history: Vec<Move>,
+    /// The player's and the computer's move in the last round.
+    last: Option<(Move, Move)>,
+    player_wins: u32,
+    rung: usize,
+    streak: u32,
+    variant: Variant,
+}
+
+impl Game {
+    fn new(variant: Variant) -> Self {
+        Self {
+            best_streak: 0,
+            computer_wins: 0,
+            history: Vec::new(),
+            last: None,
+            player_wins: 0,
+            rung: 0,
+            streak: 0,
+            variant,
+        }
+    }
+
+    /// Rounds to win the match.
+    fn needed(&self) -> u32 {
+        RUNGS[self.rung].best_of / 2 + 1
+    }
+
+    fn play(&mut self, player: Move) -> Outcome {
+        let strategy = RUNGS[self.rung].strategy;
+        let computer = strategy.choose(self.variant, &self.history, &mut thread_rng());
+        self.history.push(player);
+        self.last = Some((player, computer));
+        let outcome = player.against(computer);
+        match outcome {
+            Outcome::Win => {
+                self.player_wins += 1;
+                self.streak += 1;
+                self.best_streak = self.best_streak.max(self.streak);
+            }
+            Outcome::Loss => {
+                self.computer_wins += 1;
+                self.streak = 0;
+            }
+            Outcome::Draw => {}
+        }
+        outcome
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    MatchOver,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+/// The moves of the last round and the result, or the menu.
+#[derive(Component)]
+struct MenuText;
+
+/// The rungs climbed in each variant and the longest run of rounds won, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Records {
+    rungs: HashMap<String, usize>,
+    streak: u32,
+}
+
+impl Records {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save(GAME, RECORDS_FILE, self) {
+            eprintln!("could not save records: {}", error);
+        }
+    }
+}
+
+struct Rung {
+    best_of: u32,
+    name: &'static str,
+    strategy: Strategy,
+}
+
+struct Sounds {
+    draw: Handle<AudioSource>,
+    loss: Handle<AudioSource>,
+    lost: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct RpsPlugin;
+
+impl Plugin for RpsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Game::new(Variant::Classic))
+            .insert_resource(game_persistence::load_or_default::<Records>(
+                GAME,
+                RECORDS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(move_input))
+            .add_system_set(SystemSet::on_enter(GameState::MatchOver).with_system(match_over_enter))
+            .add_system_set(
+                SystemSet::on_update(GameState::MatchOver).with_system(match_over_input),
+            )
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Rock Paper Scissors".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Plays the fanfare and keeps the records once a match is decided.
+fn match_over_enter(
+    game: Res<Game>,
+    mut records: ResMut<Records>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let won = game.player_wins > game.computer_wins;
+    audio.play(if won {
+        sounds.won.clone()
+    } else {
+        sounds.lost.clone()
+    });
+    let climbed = if won { game.rung + 1 } else { game.rung };
+    let name = game.variant.name().to_string();
+    let best = records.rungs.get(&name).copied().unwrap_or(0);
+    if climbed > best || game.best_streak > records.streak {
+        records.rungs.insert(name, climbed.max(best));
+        records.streak = records.streak.max(game.best_streak);
+        records.save();
+    }
+}
+
+/// Enter takes on the next opponent after a win, or goes back to the menu otherwise.
+fn match_over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    keyboard_input.clear_just_pressed(KeyCode::Return);
+    let won = game.player_wins > game.computer_wins;
+    if won && game.rung + 1 < RUNGS.len() {
+        game.rung += 1;
+        game.player_wins = 0;
+        game.computer_wins = 0;
+        game.last = None;
+        state.set(GameState::Playing).unwrap();
+    } else {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Left, right or V switch the variant, Enter starts a climb up the ladder.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.horizontal() != 0 || keyboard_input.just_pressed(KeyCode::V) {
+        game.variant = match game.variant {
+            Variant::Classic => Variant::Extended,
+            Variant::Extended => Variant::Classic,
+        };
+    } else if menu.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(game.variant);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// A move key plays a round; Esc gives up the climb.
+fn move_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+    let moves = game.variant.moves();
+    let picked = KEYS[..moves.len()]
+        .iter()
+        .position(|(number, letter)| keyboard_input.any_just_pressed([*number, *letter]));
+    let player = match picked {
+        Some(index) => moves[index],
+        None => return,
+    };
+    let sound = match game.play(player) {
+        Outcome::Win => &sounds.win,
+        Outcome::Draw => &sounds.draw,
+        Outcome::Loss => &sounds.loss,
+    };
+    audio.play(sound.clone());
+    let needed = game.needed();
+    if game.player_wins >= needed || game.computer_wins >= needed {
+        state.set(GameState::MatchOver).unwrap();
+    }
+}
+
+/// The last round: both moves and how one beat the other.
+fn round_text(game: &Game) -> (String, Color) {
+    let (player, computer) = match game.last {
+        Some(last) => last,
+        None => return ("Make your move".to_string(), TEXT_COLOR),
+    };
+    let moves = format!("{}  vs  {}", player.name(), computer.name());
+    let (result, color) = match player.against(computer) {
+        Outcome::Win => (
+            format!(
+                "{} {} {}. You win the round!",
+                player.name(),
+                player.verb(computer).unwrap_or("beats"),
+                computer.name().to_lowercase()
+            ),
+            WIN_COLOR,
+        ),
+        Outcome::Loss => (
+            format!(
+                "{} {} {}. You lose the round.",
+                computer.name(),
+                computer.verb(player).unwrap_or("beats"),
+                player.name().to_lowercase()
+            ),
+            LOSS_COLOR,
+        ),
+        Outcome::Draw => ("A draw.".to_string(), DRAW_COLOR),
+    };
+    (format!("{}\n\n{}", moves, result), color)
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            28.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        draw: audio_sources.add(
+            Tone::new(Waveform::Sine, 440.0, 0.1)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        loss: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 330.0, 0.08).volume(0.15),
+            Tone::new(Waveform::Square, 220.0, 0.14).volume(0.15),
+        ])),
+        lost: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.5).fade(),
+        ])),
+        win: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.08),
+            Tone::new(Waveform::Triangle, 784.0, 0.14).fade(),
+        ])),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4).fade(),
+        ])),
+    });
+}
+
+fn texts_update(
+    game: Res<Game>,
+    records: Res<Records>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let rung = &RUNGS[game.rung];
+    let hud = match state.current() {
+        GameState::Menu => String::new(),
+        _ => format!(
+            "Rung {}/{} {} (best of {})   You {} - {}   Streak {}",
+            game.rung + 1,
+            RUNGS.len(),
+            rung.name,
+            rung.best_of,
+            game.player_wins,
+            game.computer_wins,
+            game.streak
+        ),
+    };
+    let (help, banner, color) = match state.current() {
+        GameState::Menu => {
+            let climbed = records.rungs.get(game.variant.name()).copied().unwrap_or(0);
+            let ladder = RUNGS
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(index, rung)| {
+                    let mark = if index < climbed { "*" } else { " " };
+                    format!(
+                        "{} {}. {:<20} best of {}",
+                        mark,
+                        index + 1,
+                        rung.name,
+                        rung.best_of
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            (
+                "Left/Right: variant   Enter: start the climb".to_string(),
+                format!(
+                    "< {} >\n\n{}\n\nBest streak {}",
+                    game.variant.name(),
+                    ladder,
+                    records.streak
+                ),
+                TEXT_COLOR,
+            )
+        }
+        GameState::Playing => {
+            let keys = game
+                .variant
+                .moves()
+                .iter()
+                .enumerate()
+                .map(|(index, played)| format!("{}: {}", index + 1, played.name()))
+                .collect::<Vec<_>>()
+                .join("   ");
+            let (round, color) = round_text(&game);
+            (format!("{}   Esc: give up", keys), round, color)
+        }
+        GameState::MatchOver => {
+            let (round, _) = round_text(&game);
+            let won = game.player_wins > game.computer_wins;
+            let (result, help, color) = if won && game.rung + 1 == RUNGS.len() {
+                ("THE LADDER IS CLIMBED!", "Enter: menu", WIN_COLOR)
+            } else if won {
+                ("MATCH WON", "Enter: take on the next opponent", WIN_COLOR)
+            } else {
+                ("MATCH LOST", "Enter: menu", LOSS_COLOR)
+            };
+            (
+                help.to_string(),
+                format!(
+                    "{}\n\n{}\n\n{} - {} against {}",
+                    round, result, game.player_wins, game.computer_wins, rung.name
+                ),
+                color,
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+        text.sections[0].style.color = color;
+    }
+}
@@ -0,0 +1,131 @@
+//! The moves and what beats what, in the classic game and with lizard and Spock added.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Move {
+    Rock,
+    Paper,
+    Scissors,
+    Lizard,
+    Spock,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Variant {
+    Classic,
+    /// Rock, paper, scissors, lizard, Spock: every move beats two and loses to two.
+    Extended,
+}
+
+/// Who beats whom, and how.
+const WINS: [(Move, Move, &str); 10] = [
+    (Move::Scissors, Move::Paper, "cuts"),
+    (Move::Paper, Move::Rock, "covers"),
+    (Move::Rock, Move::Lizard, "crushes"),
+    (Move::Lizard, Move::Spock, "poisons"),
+    (Move::Spock, Move::Scissors, "smashes"),
+    (Move::Scissors, Move::Lizard, "decapitates"),
+    (Move::Lizard, Move::Paper, "eats"),
+    (Move::Paper, Move::Spock, "disproves"),
+    (Move::Spock, Move::Rock, "vaporizes"),
+    (Move::Rock, Move::Scissors, "crushes"),
+];
+
+impl Move {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Rock => "Rock",
+            Self::Paper => "Paper",
+            Self::Scissors => "Scissors",
+            Self::Lizard => "Lizard",
+            Self::Spock => "Spock",
+        }
+    }
+
+    /// How this move plays out against another, from this move's side.
+    pub fn against(self, other: Move) -> Outcome {
+        if self == other {
+            Outcome::Draw
+        } else if self.verb(other).is_some() {
+            Outcome::Win
+        } else {
+            Outcome::Loss
+        }
+    }
+
+    /// The moves that beat this one in a variant.
+    pub fn beaten_by(self, variant: Variant) -> Vec<Move> {
+        variant
+            .moves()
+            .iter()
+            .copied()
+            .filter(|other| other.against(self) == Outcome::Win)
+            .collect()
+    }
+
+    /// How this move beats the other, e.g. "covers", if it does.
+    pub fn verb(self, other: Move) -> Option<&'static str> {
+        WINS.iter()
+            .find(|(winner, loser, _)| *winner == self && *loser == other)
+            .map(|(_, _, verb)| *verb)
+    }
+}
+
+impl Variant {
+    pub fn moves(self) -> &'static [Move] {
+        const ALL: [Move; 5] = [
+            Move::Rock,
+            Move::Paper,
+            Move::Scissors,
+            Move::Lizard,
+            Move::Spock,
+        ];
+        match self {
+            Self::Classic => &ALL[..3],
+            Self::Extended => &ALL,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Rock Paper Scissors",
+            Self::Extended => "Rock Paper Scissors Lizard Spock",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_move_beats_half_of_the_others() {
+        for variant in [Variant::Classic, Variant::Extended] {
+            let moves = variant.moves();
+            for one in moves {
+                let wins = moves
+                    .iter()
+                    .filter(|other| one.against(**other) == Outcome::Win)
+                    .count();
+                assert_eq!(wins, (moves.len() - 1) / 2);
+                assert_eq!(one.beaten_by(variant).len(), wins);
+                for other in moves {
+                    let reverse = match one.against(*other) {
+                        Outcome::Win => Outcome::Loss,
+                        Outcome::Draw => Outcome::Draw,
+                        Outcome::Loss => Outcome::Win,
+                    };
+                    assert_eq!(other.against(*one), reverse);
+                }
+            }
+        }
+        assert_eq!(Move::Paper.verb(Move::Rock), Some("covers"));
+        assert_eq!(Move::Rock.beaten_by(Variant::Classic), vec![Move::Paper]);
+    }
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_platform::PlatformPlugin;
+use game_theme::ThemePlugin;
+use launcher::LauncherPlugin;
+use std::{env, process};
+
+/// The games the binary can hold, by the name that starts them; each is compiled in only with
+/// its feature.
+macro_rules! games {
+    ($($name:literal => $game:ident::$plugin:ident,)*) => {
+        /// The names of the games compiled in.
+        fn names() -> Vec<&'static str> {
+            [$(($name, cfg!(feature = $name))),*]
+                .into_iter()
+                .filter(|(_, compiled)| *compiled)
+                .map(|(name, _)| name)
+                .collect()
+        }
+
+        /// Plays the game with the name; returns false if it is not compiled in.
+        fn play(name: &str) -> bool {
+            match name {
+                $(
+                    #[cfg(feature = $name)]
+                    $name => {
+                        run($game::window_descriptor(), $game::$plugin);
+                        true
+                    }
+                )*
+                _ => false,
+            }
+        }
+    };
+}
+
+games! {
+    "airhockey" => airhockey::AirHockeyPlugin,
+    "asteroids" => asteroids::AsteroidsPlugin,
+    "backgammon" => backgammon::BackgammonPlugin,
+    "battleship" => battleship::BattleshipPlugin,
+    "blackjack" => blackjack::BlackjackPlugin,
+    "bomber" => bomber::BomberPlugin,
+    "centipede" => centipede::CentipedePlugin,
+    "checkers" => checkers::CheckersPlugin,
+    "chess" => chess::ChessPlugin,
+    "digger" => digger::DiggerPlugin,
+    "fifteen" => fifteen::FifteenPlugin,
+    "frogger" => frogger::FroggerPlugin,
+    "galaga" => galaga::GalagaPlugin,
+    "hangman" => hangman::HangmanPlugin,
+    "invaders" => invaders::InvadersPlugin,
+    "lander" => lander::LanderPlugin,
+    "lemmings" => lemmings::LemmingsPlugin,
+    "lightcycles" => lightcycles::LightCyclesPlugin,
+    "lightsout" => lightsout::LightsOutPlugin,
+    "mahjong" => mahjong::MahjongPlugin,
+    "mastermind" => mastermind::MastermindPlugin,
+    "match3" => match3::Match3Plugin,
+    "memory" => memory::MemoryPlugin,
+    "missilecommand" => missilecommand::MissileCommandPlugin,
+    "pacman" => pacman::PacmanPlugin,
+    "picross" => picross::PicrossPlugin,
+    "pinball" => pinball::PinballPlugin,
+    "pipes" => pipes::PipesPlugin,
+    "platformer" => platformer::PlatformerPlugin,
+    "qix" => qix::QixPlugin,
+    "racer" => racer::RacerPlugin,
+    "reversi" => reversi::ReversiPlugin,
+    "rogue" => rogue::RoguePlugin,
+    "rps" => rps::RpsPlugin,
+    "simon" => simon::SimonPlugin,
+    "snake" => snake::SnakePlugin,
+    "snakesladders" => snakesladders::SnakesLaddersPlugin,
+    "solitaire" => solitaire::SolitairePlugin,
+    "sudoku" => sudoku::SudokuPlugin,
+    "textadventure" => textadventure::TextAdventurePlugin,
+    "towerdefense" => towerdefense::TowerDefensePlugin,
+    "typing" => typing::TypingPlugin,
+    "videopoker" => videopoker::VideoPokerPlugin,
+    "whack" => whack::WhackPlugin,
+    "wordle" => wordle::WordlePlugin,
+    "yahtzee" => yahtzee::YahtzeePlugin,
+}
+
+/// `rust_games <game>` plays the game, `rust_games` alone opens the launcher. Flags after the
+/// name are left to the game, and flags without a name to the launcher.
+fn main() {
+    let name = match game_name(env::args()) {
+        Some(name) => name,
+        None => {
+            return run(
+                launcher::window_descriptor(),
+                LauncherPlugin::builtin(names()),
+            )
+        }
+    };
+    if !play(&name) {
+        eprintln!("No game {}; the games are: {}", name, names().join(", "));
+        process::exit(2);
+    }
+}
+
+/// The game named on the command line `args`: the first argument, unless it is a flag.
+fn game_name(mut args: impl Iterator<Item = String>) -> Option<String> {
+    args.nth(1).filter(|arg| !arg.starts_with("--"))
+}
+
+fn run(window: WindowDescriptor, plugin: impl Plugin) {
+    App::new()
+        .insert_resource(window)
+        .add_plugins(DefaultPlugins)
+        .add_plugin(plugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(PlatformPlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args<'a>(args: &'a [&'a str]) -> impl Iterator<Item = String> + 'a {
+        args.iter().map(|arg| arg.to_string())
+    }
+
+    #[test]
+    fn finds_the_game_before_its_flags() {
+        assert_eq!(game_name(args(&["rust_games"])), None);
+        assert_eq!(
+            game_name(args(&["rust_games", "textadventure"])),
+            Some("textadventure".to_string())
+        );
+        assert_eq!(
+            game_name(args(&["rust_games", "textadventure", "--story", "a.ron"])),
+            Some("textadventure".to_string())
+        );
+        assert_eq!(game_name(args(&["rust_games", "--root", ".."])), None);
+    }
+}
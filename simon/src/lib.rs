@@ -0,0 +1,392 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use simon::{Mode, Press, Simon, PADS};
+
+pub mod simon;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.08, 0.1);
+const BEST_FILE: &str = "best";
+const GAP: f32 = 16.0;
+/// Share of a step the pad stays lit while the sequence is shown.
+const LIT_SHARE: f32 = 0.7;
+/// How long a pad pressed by the player stays lit.
+const PRESS_DURATION: f32 = 0.25;
+const PAD_COLORS: [Color; PADS] = [
+    Color::rgb(0.1, 0.75, 0.25),
+    Color::rgb(0.85, 0.15, 0.15),
+    Color::rgb(0.9, 0.8, 0.1),
+    Color::rgb(0.15, 0.35, 0.9),
+];
+const PAD_KEYS: [[KeyCode; 2]; PADS] = [
+    [KeyCode::Q, KeyCode::Key1],
+    [KeyCode::W, KeyCode::Key2],
+    [KeyCode::A, KeyCode::Key3],
+    [KeyCode::S, KeyCode::Key4],
+];
+const PAD_SIZE: f32 = 200.0;
+/// The classic Simon pitches of the green, red, yellow and blue pads.
+const PAD_TONES: [f32; PADS] = [415.0, 310.0, 252.0, 209.0];
+/// Pause before the sequence is shown.
+const PAUSE: f32 = 0.8;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 600.0;
+
+/// The longest sequence repeated in each mode, kept between sessions.
+#[derive(Default, Serialize, Deserialize)]
+struct Best {
+    practice: usize,
+    strict: usize,
+}
+
+impl Best {
+    fn get(&self, mode: Mode) -> usize {
+        match mode {
+            Mode::Practice => self.practice,
+            Mode::Strict => self.strict,
+        }
+    }
+
+    fn record(&mut self, mode: Mode, streak: usize) {
+        let best = match mode {
+            Mode::Practice => &mut self.practice,
+            Mode::Strict => &mut self.strict,
+        };
+        if streak > *best {
+            *best = streak;
+            if let Err(error) = game_persistence::save("simon", BEST_FILE, self) {
+                eprintln!("could not save best streaks: {}", error);
+            }
+        }
+    }
+}
+
+struct Game {
+    mode: Mode,
+    simon: Simon,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    /// The sequence is played back.
+    Showing,
+    Repeating,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+/// Seconds each pad stays lit.
+struct Lights([f32; PADS]);
+
+#[derive(Component)]
+struct Pad(usize);
+
+/// Progress of showing the sequence.
+#[derive(Default)]
+struct Playback {
+    elapsed: f32,
+    /// Pads lit so far.
+    shown: usize,
+}
+
+struct Sounds {
+    pads: Vec<Handle<AudioSource>>,
+    wrong: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+struct StatusText;
+
+pub struct SimonPlugin;
+
+impl Plugin for SimonPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(game_persistence::load_or_default::<Best>(
+                "simon", BEST_FILE,
+            ))
+            .insert_resource(Game {
+                mode: Mode::Strict,
+                simon: Simon::default(),
+            })
+            .insert_resource(Lights([0.0; PADS]))
+            .insert_resource(Playback::default())
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_enter(GameState::Showing).with_system(playback_start))
+            .add_system_set(SystemSet::on_update(GameState::Showing).with_system(playback))
+            .add_system_set(SystemSet::on_update(GameState::Repeating).with_system(pad_input))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(hud_update)
+            .add_system(lights_update)
+            .add_system(status_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Simon".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn hud_update(best: Res<Best>, game: Res<Game>, mut texts: Query<&mut Text, With<HudText>>) {
+    if !best.is_changed() && !game.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "{}   Length {}   Best {}",
+            game.mode.name().to_uppercase(),
+            game.simon.len(),
+            best.get(game.mode)
+        );
+    }
+}
+
+/// Lit pads show their full color, the others a dim one.
+fn lights_update(
+    time: Res<Time>,
+    mut lights: ResMut<Lights>,
+    mut pads: Query<(&Pad, &mut Sprite)>,
+) {
+    for light in lights.0.iter_mut() {
+        *light = (*light - time.delta_seconds()).max(0.0);
+    }
+    for (pad, mut sprite) in pads.iter_mut() {
+        let color = PAD_COLORS[pad.0];
+        sprite.color = if lights.0[pad.0] > 0.0 {
+            color
+        } else {
+            color * 0.35
+        };
+    }
+}
+
+/// Left/Right switches the mode, Enter starts.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if menu.horizontal() != 0 {
+        game.mode = game.mode.toggled();
+    }
+    if menu.just_pressed(Navigate::Confirm) || keyboard_input.just_pressed(KeyCode::Space) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        start(&mut game, &mut state);
+    }
+}
+
+fn over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    } else if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        start(&mut game, &mut state);
+    }
+}
+
+/// The player repeats the sequence with the keys or by clicking the pads.
+fn pad_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut best: ResMut<Best>,
+    mut game: ResMut<Game>,
+    mut lights: ResMut<Lights>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let mut pressed = (0..PADS).find(|pad| keyboard_input.any_just_pressed(PAD_KEYS[*pad]));
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+            pressed = pressed.or_else(|| {
+                (0..PADS).find(|pad| {
+                    let offset = (cursor - pad_center(*pad)).abs();
+                    offset.x <= PAD_SIZE / 2.0 && offset.y <= PAD_SIZE / 2.0
+                })
+            });
+        }
+    }
+    let pad = match pressed {
+        Some(pad) => pad,
+        None => return,
+    };
+    match game.simon.press(pad) {
+        Press::Correct => {}
+        Press::Completed => {
+            let mode = game.mode;
+            best.record(mode, game.simon.len());
+            game.simon.extend(&mut thread_rng());
+            state.set(GameState::Showing).unwrap();
+        }
+        Press::Wrong => {
+            audio.play(sounds.wrong.clone());
+            lights.0 = [0.0; PADS];
+            let next = match game.mode {
+                Mode::Practice => GameState::Showing,
+                Mode::Strict => GameState::Over,
+            };
+            state.set(next).unwrap();
+            return;
+        }
+    }
+    lights.0[pad] = PRESS_DURATION;
+    audio.play(sounds.pads[pad].clone());
+}
+
+/// Green top left, red top right, yellow bottom left, blue bottom right.
+fn pad_center(pad: usize) -> Vec2 {
+    let offset = (PAD_SIZE + GAP) / 2.0;
+    let x = [-offset, offset][pad % 2];
+    let y = [offset, -offset][pad / 2];
+    Vec2::new(x, y - 30.0)
+}
+
+/// Lights the pads of the sequence one after another, then hands over to the player.
+fn playback(
+    time: Res<Time>,
+    game: Res<Game>,
+    mut lights: ResMut<Lights>,
+    mut playback: ResMut<Playback>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    playback.elapsed += time.delta_seconds();
+    let step = simon::step_duration(game.simon.len());
+    let started = playback.elapsed - PAUSE;
+    if started < 0.0 {
+        return;
+    }
+    let due = (started / step) as usize + 1;
+    if playback.shown >= game.simon.len() {
+        if due > game.simon.len() {
+            state.set(GameState::Repeating).unwrap();
+        }
+    } else if due > playback.shown {
+        let pad = game.simon.sequence()[playback.shown];
+        lights.0[pad] = step * LIT_SHARE;
+        audio.play(sounds.pads[pad].clone());
+        playback.shown += 1;
+    }
+}
+
+fn playback_start(mut playback: ResMut<Playback>) {
+    *playback = Playback::default();
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    for (pad, color) in PAD_COLORS.iter().enumerate() {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: *color * 0.35,
+                    custom_size: Some(Vec2::splat(PAD_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(pad_center(pad).extend(0.0)),
+                ..Default::default()
+            })
+            .insert(Pad(pad));
+    }
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 260.0, 1.0),
+        ))
+        .insert(StatusText);
+
+    commands.insert_resource(Sounds {
+        pads: PAD_TONES
+            .iter()
+            .map(|frequency| {
+                audio_sources.add(
+                    Tone::new(Waveform::Triangle, *frequency, 0.3)
+                        .fade()
+                        .volume(0.4)
+                        .into(),
+                )
+            })
+            .collect(),
+        wrong: audio_sources.add(
+            Tone::new(Waveform::Square, 90.0, 0.8)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+/// Starts a game with a single pad to repeat.
+fn start(game: &mut Game, state: &mut State<GameState>) {
+    game.simon = Simon::default();
+    game.simon.extend(&mut thread_rng());
+    state.set(GameState::Showing).unwrap();
+}
+
+fn status_update(
+    best: Res<Best>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<StatusText>>,
+) {
+    if !state.is_changed() && !game.is_changed() {
+        return;
+    }
+    let value = match state.current() {
+        GameState::Menu => format!(
+            "Mode  < {} >   Enter: start\nRepeat with Q W A S, 1-4 or the mouse",
+            game.mode.name()
+        ),
+        GameState::Showing => "Watch...".to_string(),
+        GameState::Repeating => "Your turn".to_string(),
+        GameState::Over => format!(
+            "Wrong pad! You repeated {} (best {})\nEnter: play again   Esc: menu",
+            game.simon.streak(),
+            best.get(game.mode)
+        ),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
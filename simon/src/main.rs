@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+use simon::SimonPlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(simon::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(SimonPlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
@@ -0,0 +1,142 @@
+//! The sequence to repeat and how far the player got through it.
+
+use rand::Rng;
+
+pub const PADS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// A wrong pad shows the sequence again.
+    Practice,
+    /// A wrong pad ends the game.
+    Strict,
+}
+
+impl Mode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Practice => "Practice",
+            Self::Strict => "Strict",
+        }
+    }
+
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Practice => Self::Strict,
+            Self::Strict => Self::Practice,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Press {
+    Correct,
+    /// The last pad of the sequence was right.
+    Completed,
+    Wrong,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Simon {
+    /// Pads repeated correctly in the current round.
+    entered: usize,
+    sequence: Vec<usize>,
+}
+
+impl Simon {
+    /// Adds a random pad to the end of the sequence and starts a new round.
+    pub fn extend(&mut self, rng: &mut impl Rng) {
+        self.sequence.push(rng.gen_range(0, PADS));
+        self.entered = 0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+
+    /// Checks the next pad; a wrong one starts the round over.
+    pub fn press(&mut self, pad: usize) -> Press {
+        if self.sequence.get(self.entered) != Some(&pad) {
+            self.entered = 0;
+            return Press::Wrong;
+        }
+        self.entered += 1;
+        if self.entered == self.sequence.len() {
+            Press::Completed
+        } else {
+            Press::Correct
+        }
+    }
+
+    pub fn sequence(&self) -> &[usize] {
+        &self.sequence
+    }
+
+    /// Sequences repeated without a mistake, the current one not counted.
+    pub fn streak(&self) -> usize {
+        self.sequence.len().saturating_sub(1)
+    }
+}
+
+/// Seconds between two pads of the sequence shown; it gets faster as the sequence grows.
+pub fn step_duration(length: usize) -> f32 {
+    match length {
+        0..=5 => 0.6,
+        6..=9 => 0.48,
+        10..=13 => 0.38,
+        _ => 0.3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    #[test]
+    fn repeating_the_sequence_completes_the_round() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut simon = Simon::default();
+        for length in 1..=5 {
+            simon.extend(&mut rng);
+            assert_eq!(simon.len(), length);
+            let sequence = simon.sequence().to_vec();
+            for (index, pad) in sequence.iter().enumerate() {
+                let expected = if index + 1 == length {
+                    Press::Completed
+                } else {
+                    Press::Correct
+                };
+                assert_eq!(simon.press(*pad), expected);
+            }
+        }
+        assert!(simon.sequence().iter().all(|pad| *pad < PADS));
+        assert_eq!(simon.streak(), 4);
+    }
+
+    #[test]
+    fn a_wrong_pad_starts_the_round_over() {
+        let mut simon = Simon {
+            entered: 0,
+            sequence: vec![2, 0, 3],
+        };
+        assert_eq!(simon.press(2), Press::Correct);
+        assert_eq!(simon.press(1), Press::Wrong);
+        assert_eq!(simon.press(0), Press::Wrong, "back at the first pad");
+        assert_eq!(simon.press(2), Press::Correct);
+        assert_eq!(simon.press(0), Press::Correct);
+        assert_eq!(simon.press(3), Press::Completed);
+    }
+
+    #[test]
+    fn longer_sequences_play_faster() {
+        let durations: Vec<f32> = (1..20).map(step_duration).collect();
+        assert!(durations.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(step_duration(14) < step_duration(1));
+    }
+}
@@ -0,0 +1,101 @@
+//! The snake's achievements, which `game_achievements` unlocks from the progress the first
+//! player's runs make, announces and keeps between sessions.
+
+use crate::{RunStats, GAME};
+use bevy::prelude::*;
+use game_achievements::{Achievement, AchievementsPlugin, Progress};
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement {
+        description: "Eat 100 pieces of food",
+        goal: 100,
+        id: "glutton",
+        name: "Glutton",
+        stat: "food",
+    },
+    Achievement {
+        description: "Eat 3 golden apples in one game",
+        goal: 3,
+        id: "gold_rush",
+        name: "Gold rush",
+        stat: "golden_in_a_game",
+    },
+    Achievement {
+        description: "Grow to a length of 20",
+        goal: 20,
+        id: "long_snake",
+        name: "Long snake",
+        stat: "length",
+    },
+    Achievement {
+        description: "Survive 5 minutes in one game",
+        goal: 5,
+        id: "survivor",
+        name: "Survivor",
+        stat: "minutes_in_a_game",
+    },
+];
+
+pub(crate) fn build(app: &mut App) {
+    app.add_plugin(AchievementsPlugin::new(GAME, ACHIEVEMENTS))
+        .add_system(progress_update);
+}
+
+/// Sends the progress the run has made since it was last sent, which is nothing for a new run.
+/// The time counts in whole minutes, so the record is not saved every frame.
+fn progress_update(
+    stats: Res<RunStats>,
+    mut last: Local<RunStats>,
+    mut progress: EventWriter<Progress>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    if stats.food > last.food {
+        progress.send(Progress::Add("food", stats.food - last.food));
+    }
+    if stats.golden > last.golden {
+        progress.send(Progress::Reach("golden_in_a_game", stats.golden));
+    }
+    if stats.longest > last.longest {
+        progress.send(Progress::Reach("length", stats.longest as u32));
+    }
+    let minutes = |stats: &RunStats| (stats.seconds / 60.0) as u32;
+    if minutes(&stats) > minutes(&last) {
+        progress.send(Progress::Reach("minutes_in_a_game", minutes(&stats)));
+    }
+    *last = *stats;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::app::Events;
+
+    #[test]
+    fn sends_what_the_run_made_since_the_last_frame() {
+        let mut app = App::new();
+        app.add_event::<Progress>()
+            .insert_resource(RunStats::default())
+            .add_system(progress_update);
+        app.update();
+        let mut stats = app.world.get_resource_mut::<RunStats>().unwrap();
+        stats.food = 2;
+        stats.golden = 1;
+        stats.seconds = 61.0;
+        app.update();
+        // A new run sends nothing until it gets further.
+        *app.world.get_resource_mut::<RunStats>().unwrap() = RunStats::default();
+        app.update();
+        let events = app.world.get_resource::<Events<Progress>>().unwrap();
+        let sent: Vec<Progress> = events.get_reader().iter(events).copied().collect();
+        assert_eq!(
+            sent,
+            [
+                Progress::Add("food", 2),
+                Progress::Reach("golden_in_a_game", 1),
+                Progress::Reach("minutes_in_a_game", 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! The snake's sounds, rendered by `game_audio` when it starts, and the music looping while a
+//! game is played.
+
+use crate::{AudioSettings, ClearedEvent, GameOverEvent, GrowthEvent, PenaltyEvent, ShrinkEvent};
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_flow::GameState;
+use game_hud::{MenuInput, Navigate};
+
+/// The bass line of the music, a note every two beats.
+const BASS: [f32; 8] = [131.0, 131.0, 147.0, 147.0, 131.0, 131.0, 196.0, 196.0];
+/// Seconds of a beat of the music.
+const BEAT: f32 = 0.25;
+/// The melody of the music, a note a beat.
+const MELODY: [f32; 16] = [
+    523.0, 659.0, 784.0, 659.0, 587.0, 698.0, 880.0, 698.0, 523.0, 659.0, 784.0, 1047.0, 988.0,
+    784.0, 587.0, 494.0,
+];
+
+/// Something to be heard, sent where it happens.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Sound {
+    Crash,
+    Eat,
+    Menu,
+    PowerUp,
+    Shrink,
+    /// The last level cleared.
+    Win,
+}
+
+/// Starts the music again each time it has played through, while a game is on. Bevy cannot
+/// stop a sound, so it plays to its end when the game does.
+struct Music {
+    playing: bool,
+    timer: Timer,
+}
+
+struct Sounds {
+    crash: Handle<AudioSource>,
+    eat: Handle<AudioSource>,
+    menu: Handle<AudioSource>,
+    music: Handle<AudioSource>,
+    power_up: Handle<AudioSource>,
+    shrink: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+}
+
+/// Adds the sounds, played at the volumes of the `AudioSettings`.
+pub(crate) fn build(app: &mut App) {
+    app.insert_resource(Music {
+        playing: false,
+        timer: Timer::from_seconds(BEAT * MELODY.len() as f32, false),
+    })
+    .add_event::<Sound>()
+    .add_startup_system(setup_sounds)
+    .add_system(music_update)
+    .add_system(sound_bridge)
+    .add_system(sound_playback);
+}
+
+fn music_update(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    mut music: ResMut<Music>,
+) {
+    match state.current() {
+        GameState::Playing => {}
+        GameState::Paused => return,
+        _ => {
+            music.playing = false;
+            return;
+        }
+    }
+    if !music.playing || music.timer.tick(time.delta()).just_finished() {
+        music.playing = true;
+        music.timer.reset();
+        audio.play(sounds.music.clone());
+    }
+}
+
+/// `tones` one after another, at `volume` of their own.
+fn render(tones: &[Tone], volume: f32) -> AudioSource {
+    game_audio::sequence(&scaled(tones, volume))
+}
+
+fn scaled(tones: &[Tone], volume: f32) -> Vec<Tone> {
+    tones
+        .iter()
+        .map(|tone| tone.volume(tone.volume * volume))
+        .collect()
+}
+
+fn setup_sounds(
+    mut commands: Commands,
+    settings: Res<AudioSettings>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    let effects = settings.effects_volume;
+    let melody: Vec<Tone> = MELODY
+        .iter()
+        .map(|frequency| Tone::new(Waveform::Triangle, *frequency, BEAT).volume(0.15))
+        .collect();
+    let bass: Vec<Tone> = BASS
+        .iter()
+        .map(|frequency| Tone::new(Waveform::Sine, *frequency, 2.0 * BEAT).volume(0.2))
+        .collect();
+    let music = game_audio::chord(&[
+        &scaled(&melody, settings.music_volume),
+        &scaled(&bass, settings.music_volume),
+    ]);
+    commands.insert_resource(Sounds {
+        crash: audio_sources.add(render(
+            &[Tone::new(Waveform::Noise, 0.0, 0.35).fade().volume(0.4)],
+            effects,
+        )),
+        eat: audio_sources.add(render(
+            &[Tone::new(Waveform::Square, 440.0, 0.08)
+                .slide(880.0)
+                .volume(0.15)],
+            effects,
+        )),
+        menu: audio_sources.add(render(
+            &[Tone::new(Waveform::Sine, 660.0, 0.05).fade().volume(0.3)],
+            effects,
+        )),
+        music: audio_sources.add(music),
+        power_up: audio_sources.add(render(
+            &[
+                Tone::new(Waveform::Triangle, 523.0, 0.06),
+                Tone::new(Waveform::Triangle, 784.0, 0.06),
+                Tone::new(Waveform::Triangle, 1047.0, 0.15).fade(),
+            ],
+            effects,
+        )),
+        shrink: audio_sources.add(render(
+            &[Tone::new(Waveform::Square, 330.0, 0.2)
+                .slide(165.0)
+                .fade()
+                .volume(0.15)],
+            effects,
+        )),
+        win: audio_sources.add(render(
+            &[
+                Tone::new(Waveform::Triangle, 523.0, 0.12),
+                Tone::new(Waveform::Triangle, 659.0, 0.12),
+                Tone::new(Waveform::Triangle, 784.0, 0.12),
+                Tone::new(Waveform::Triangle, 1047.0, 0.4).fade(),
+            ],
+            effects,
+        )),
+    });
+}
+
+/// Turns what happens in the game into sounds: food eaten, crashes, the levels cleared and the
+/// menus moved through.
+fn sound_bridge(
+    state: Res<State<GameState>>,
+    menu: Res<MenuInput>,
+    mut growth_reader: EventReader<GrowthEvent>,
+    mut penalty_reader: EventReader<PenaltyEvent>,
+    mut shrink_reader: EventReader<ShrinkEvent>,
+    mut game_over_reader: EventReader<GameOverEvent>,
+    mut cleared_reader: EventReader<ClearedEvent>,
+    mut sounds: EventWriter<Sound>,
+) {
+    if growth_reader.iter().count() + penalty_reader.iter().count() > 0 {
+        sounds.send(Sound::Eat);
+    }
+    if shrink_reader.iter().count() > 0 {
+        sounds.send(Sound::Shrink);
+    }
+    if game_over_reader.iter().count() > 0 {
+        sounds.send(Sound::Crash);
+    }
+    if cleared_reader.iter().count() > 0 {
+        sounds.send(Sound::Win);
+    }
+    let navigated = [
+        Navigate::Back,
+        Navigate::Confirm,
+        Navigate::Down,
+        Navigate::Left,
+        Navigate::Right,
+        Navigate::Up,
+    ]
+    .into_iter()
+    .any(|navigate| menu.just_pressed(navigate));
+    if navigated && *state.current() != GameState::Playing {
+        sounds.send(Sound::Menu);
+    }
+}
+
+fn sound_playback(audio: Res<Audio>, sounds: Res<Sounds>, mut reader: EventReader<Sound>) {
+    for sound in reader.iter() {
+        audio.play(match sound {
+            Sound::Crash => sounds.crash.clone(),
+            Sound::Eat => sounds.eat.clone(),
+            Sound::Menu => sounds.menu.clone(),
+            Sound::PowerUp => sounds.power_up.clone(),
+            Sound::Shrink => sounds.shrink.clone(),
+            Sound::Win => sounds.win.clone(),
+        });
+    }
+}
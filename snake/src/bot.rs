@@ -0,0 +1,77 @@
+//! The computer steering the first player's snake toward food, picked on the menu or with
+//! `--bot`.
+
+use crate::ArenaConfig;
+use game_ai::path;
+use game_grid::{Direction, Position};
+use std::env;
+
+/// The command line flag starting with the computer steering, e.g. `cargo run -- --bot`.
+const BOT_FLAG: &str = "--bot";
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Left,
+    Direction::Down,
+    Direction::Right,
+];
+
+/// Whether the computer steers the first player's snake instead of the keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bot(pub bool);
+
+impl Bot {
+    /// On when the game is run with `--bot`.
+    pub fn load() -> Self {
+        Self(env::args().any(|arg| arg == BOT_FLAG))
+    }
+}
+
+/// Where a snake of `length` segments with its head at `head` heads next: along the shortest way
+/// to the nearest `food` through the cells left free by `blocked`, unless that leaves it too
+/// little room, else toward the most room.
+pub fn steer(
+    arena: &ArenaConfig,
+    head: Position,
+    length: usize,
+    blocked: &[Position],
+    food: &[Position],
+) -> Option<Direction> {
+    let free = |cell: Position| arena.contains(cell) && !blocked.contains(&cell);
+    let room = |direction: Direction| path::reachable(head.step(direction), length, free);
+    let toward_food = path::bfs(head, |cell| food.contains(&cell), free)
+        .and_then(|way| way.first().copied())
+        .and_then(|next| {
+            DIRECTIONS
+                .into_iter()
+                .find(|direction| head.step(*direction) == next)
+        })
+        .filter(|direction| room(*direction) >= length);
+    toward_food.or_else(|| {
+        DIRECTIONS
+            .into_iter()
+            .filter(|direction| free(head.step(*direction)))
+            .max_by_key(|direction| room(*direction))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heads_for_food_with_room_to_spare() {
+        let arena = ArenaConfig::new(10, 10).unwrap();
+        let head = Position::new(3, 3);
+        let body = [Position::new(2, 3)];
+        assert_eq!(
+            steer(&arena, head, 2, &body, &[Position::new(3, 7)]),
+            Some(Direction::Up)
+        );
+        // Walls round the food leave it in a pocket too small to turn in.
+        let mut blocked = vec![head, Position::new(2, 3)];
+        blocked.extend((0..10).map(|y| Position::new(5, y)));
+        blocked.extend([Position::new(4, 2), Position::new(4, 4)]);
+        let direction = steer(&arena, head, 8, &blocked, &[Position::new(4, 3)]);
+        assert!(matches!(direction, Some(Direction::Up | Direction::Down)));
+    }
+}
@@ -0,0 +1,490 @@
+//! The snake's settings, edited by hand in its `settings.toml`, and the arena, colors and
+//! difficulty they make.
+
+use crate::{FoodKind, PowerUp, GAME};
+use bevy::prelude::*;
+use game_a11y::{Palette, Swatch};
+use game_grid::Position;
+use game_settings::{Choice, Settings};
+use serde::{Deserialize, Serialize};
+use std::env;
+
+/// The command line flag giving the arena's size for one run, e.g. `--arena 40x30`.
+const ARENA_FLAG: &str = "--arena";
+const MAX_SIDE: u32 = 60;
+/// Small enough for the snake to start in.
+const MIN_SIDE: u32 = 5;
+/// Pixels a side of the window has at the least.
+const MIN_WINDOW_SIDE: u32 = 200;
+/// The second player's head and segment colors, which the settings leave alone.
+const SECOND_PLAYER_COLORS: (Color, Color) =
+    (Color::rgb(1.0, 0.6, 0.2), Color::rgb(0.55, 0.3, 0.1));
+/// Segments of a new snake, which moves at the slowest speed.
+const START_LENGTH: usize = 2;
+
+/// What the player can set in the snake's `settings.toml`, written with the defaults when the
+/// game first starts. The keys are bound in the `controls` file of `game_input` instead.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, Settings)]
+#[serde(default)]
+pub struct SnakeSettings {
+    #[setting(min = 5, max = 60)]
+    pub arena_height: u32,
+    #[setting(min = 5, max = 60)]
+    pub arena_width: u32,
+    /// Red, green and blue from 0 to 1, as are the other colors.
+    #[setting(skip)]
+    pub background_color: [f32; 3],
+    /// The colors the game is drawn in, the ones below or a built-in set.
+    pub color_scheme: ColorScheme,
+    /// How loud eating, crashing and the menu sound, from 0 for silent to 1.
+    #[setting(min = 0, max = 1, step = 0.1)]
+    pub effects_volume: f32,
+    /// Seconds between two steps of the longest snakes.
+    #[setting(min = 0.03, max = 0.5, step = 0.01)]
+    pub fastest_step: f32,
+    /// The color of plain apples.
+    #[setting(skip)]
+    pub food_color: [f32; 3],
+    /// How loud the music plays during a game, from 0 for silent to 1.
+    #[setting(min = 0, max = 1, step = 0.1)]
+    pub music_volume: f32,
+    /// Cells of the custom obstacle layout, e.g. `[[4, 4], [4, 5]]`.
+    #[setting(skip)]
+    pub obstacles: Vec<[i32; 2]>,
+    /// Seconds between two steps of a new snake.
+    #[setting(min = 0.03, max = 0.5, step = 0.01)]
+    pub slowest_step: f32,
+    /// The first player's segments.
+    #[setting(skip)]
+    pub snake_color: [f32; 3],
+    /// The first player's head.
+    #[setting(skip)]
+    pub snake_head_color: [f32; 3],
+    /// Seconds a step gets shorter for each segment the snake grows.
+    #[setting(min = 0, max = 0.02, step = 0.001)]
+    pub speed_up: f32,
+    /// The height of the window when the game starts, in pixels.
+    #[setting(min = 200, max = 2000, step = 50)]
+    pub window_height: u32,
+    #[setting(min = 200, max = 2000, step = 50)]
+    pub window_width: u32,
+}
+
+impl Default for SnakeSettings {
+    fn default() -> Self {
+        Self {
+            arena_height: 10,
+            arena_width: 10,
+            background_color: [0.04, 0.04, 0.04],
+            color_scheme: ColorScheme::Classic,
+            effects_volume: 0.8,
+            fastest_step: 0.06,
+            food_color: [1.0, 0.0, 1.0],
+            music_volume: 0.5,
+            obstacles: Vec::new(),
+            slowest_step: 0.15,
+            snake_color: [0.3, 0.3, 0.3],
+            snake_head_color: [0.7, 0.7, 0.7],
+            speed_up: 0.005,
+            window_height: 500,
+            window_width: 500,
+        }
+    }
+}
+
+impl SnakeSettings {
+    /// The saved settings, or the defaults if there are none or they cannot be read. Missing
+    /// settings are saved as the defaults, for players to find and edit.
+    pub fn load() -> Self {
+        match game_settings::load(GAME) {
+            Ok(Some(settings)) => settings,
+            Ok(None) => {
+                let settings = Self::default();
+                if let Err(error) = game_settings::save(GAME, &settings) {
+                    eprintln!("could not write the snake's settings: {}", error);
+                }
+                settings
+            }
+            Err(error) => {
+                eprintln!("could not read the snake's settings: {}", error);
+                Self::default()
+            }
+        }
+    }
+
+    /// The size of the window, in pixels.
+    pub fn window_size(&self) -> Vec2 {
+        Vec2::new(
+            self.window_width.max(MIN_WINDOW_SIDE) as f32,
+            self.window_height.max(MIN_WINDOW_SIDE) as f32,
+        )
+    }
+
+    /// The cells of the custom obstacle layout.
+    pub fn custom_obstacles(&self) -> Vec<Position> {
+        self.obstacles
+            .iter()
+            .map(|[x, y]| Position::new(*x, *y))
+            .collect()
+    }
+}
+
+/// The size of the arena in cells, fixed when the game starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ArenaConfig {
+    pub height: u32,
+    pub width: u32,
+}
+
+impl ArenaConfig {
+    /// The size given with `--arena` on the command line, else the one in the settings.
+    pub fn load(settings: &SnakeSettings) -> Self {
+        let mut args = env::args().skip_while(|arg| arg != ARENA_FLAG).skip(1);
+        match args.next() {
+            Some(size) => Self::parse(&size).unwrap_or_else(|| {
+                eprintln!(
+                    "{} is no arena size; give it as <width>x<height>, each from {} to {}",
+                    size, MIN_SIDE, MAX_SIDE
+                );
+                Self::from(settings)
+            }),
+            None => Self::from(settings),
+        }
+    }
+
+    /// An arena of the given size, if each side is from `MIN_SIDE` to `MAX_SIDE`.
+    pub fn new(width: u32, height: u32) -> Option<Self> {
+        let sides = MIN_SIDE..=MAX_SIDE;
+        if sides.contains(&width) && sides.contains(&height) {
+            Some(Self { height, width })
+        } else {
+            None
+        }
+    }
+
+    /// A size written as `<width>x<height>`, e.g. `20x20`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (width, height) = text.split_once(['x', 'X'])?;
+        Self::new(width.trim().parse().ok()?, height.trim().parse().ok()?)
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.in_arena(self.width, self.height)
+    }
+
+    /// The side of the square cells, as large as the arena fits in `window`.
+    pub fn tile_size(&self, window: Vec2) -> f32 {
+        (window.x / self.width as f32).min(window.y / self.height as f32)
+    }
+
+    /// The middle of a cell in the world, with the arena centered in `window`.
+    pub fn world_position(&self, position: Position, window: Vec2) -> Vec2 {
+        let tile_size = self.tile_size(window);
+        let cell = Vec2::new(position.x as f32 + 0.5, position.y as f32 + 0.5);
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        (cell - size / 2.0) * tile_size
+    }
+}
+
+impl From<&SnakeSettings> for ArenaConfig {
+    fn from(settings: &SnakeSettings) -> Self {
+        Self {
+            height: settings.arena_height.clamp(MIN_SIDE, MAX_SIDE),
+            width: settings.arena_width.clamp(MIN_SIDE, MAX_SIDE),
+        }
+    }
+}
+
+/// The set of colors the background, the snakes and the food are drawn in, picked on the menu.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq, Serialize, Choice)]
+pub enum ColorScheme {
+    /// The colors in the settings.
+    #[default]
+    Classic,
+    /// Bright snakes and food on black.
+    #[setting(label = "High contrast")]
+    HighContrast,
+    /// The Okabe-Ito colors of `game_a11y`, which stay apart for deuteranopia and protanopia.
+    #[setting(label = "Red-green safe")]
+    RedGreen,
+}
+
+impl ColorScheme {
+    pub const ALL: [Self; 3] = [Self::Classic, Self::HighContrast, Self::RedGreen];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Classic => "Classic",
+            Self::HighContrast => "High contrast",
+            Self::RedGreen => "Red-green safe",
+        }
+    }
+
+    /// The scheme after this one on the menu, back to the first after the last.
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|scheme| *scheme == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Keeps the scheme in the settings, for the game to start with next time.
+    pub fn save(self) {
+        let settings = SnakeSettings {
+            color_scheme: self,
+            ..SnakeSettings::load()
+        };
+        if let Err(error) = game_settings::save(GAME, &settings) {
+            eprintln!("could not write the snake's settings: {}", error);
+        }
+    }
+}
+
+/// The colors of the arena, the snakes, the food and the power-ups in the chosen scheme.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Colors {
+    /// The classic colors, from the settings.
+    background: Color,
+    /// Of plain apples; the other food keeps its own colors, which tell what it does.
+    food: Color,
+    pub scheme: ColorScheme,
+    /// Each player's head and segment colors.
+    snakes: [(Color, Color); 2],
+}
+
+impl Colors {
+    pub fn background(&self) -> Color {
+        match self.scheme {
+            ColorScheme::Classic => self.background,
+            ColorScheme::HighContrast => Color::BLACK,
+            ColorScheme::RedGreen => Color::rgb(0.05, 0.05, 0.05),
+        }
+    }
+
+    /// The color food of `kind` is drawn in.
+    pub fn food(&self, kind: FoodKind) -> Color {
+        match (self.scheme, kind) {
+            (ColorScheme::Classic, FoodKind::Normal) => self.food,
+            (ColorScheme::Classic, _) => kind.color(),
+            (ColorScheme::HighContrast, FoodKind::Normal) => Color::rgb(0.0, 0.9, 1.0),
+            (ColorScheme::HighContrast, FoodKind::Golden) => Color::rgb(1.0, 1.0, 0.0),
+            (ColorScheme::HighContrast, FoodKind::Poison) => Color::rgb(1.0, 0.25, 0.25),
+            (ColorScheme::HighContrast, FoodKind::Rotten) => Color::rgb(0.7, 0.75, 0.2),
+            (ColorScheme::HighContrast, FoodKind::Timed) => Color::rgb(0.35, 0.45, 1.0),
+            (ColorScheme::RedGreen, FoodKind::Normal) => Palette::RedGreen.color(Swatch::Red),
+            (ColorScheme::RedGreen, FoodKind::Golden) => Palette::RedGreen.color(Swatch::Yellow),
+            (ColorScheme::RedGreen, FoodKind::Poison) => Palette::RedGreen.color(Swatch::Purple),
+            // Grey-brown, apart from every swatch.
+            (ColorScheme::RedGreen, FoodKind::Rotten) => Color::rgb(0.55, 0.5, 0.4),
+            (ColorScheme::RedGreen, FoodKind::Timed) => Palette::RedGreen.color(Swatch::Green),
+        }
+    }
+
+    /// The color of a power-up, and of the head of a snake it works on.
+    pub fn power_up(&self, power_up: PowerUp) -> Color {
+        match (self.scheme, power_up) {
+            (ColorScheme::RedGreen, PowerUp::Speed) => Palette::RedGreen.color(Swatch::Orange),
+            (ColorScheme::RedGreen, PowerUp::Multiplier) => Palette::RedGreen.color(Swatch::Blue),
+            _ => power_up.color(),
+        }
+    }
+
+    /// The head and segment colors of `player`'s snake.
+    pub fn snake(&self, player: usize) -> (Color, Color) {
+        match self.scheme {
+            ColorScheme::Classic => self.snakes[player],
+            ColorScheme::HighContrast => [
+                (Color::WHITE, Color::rgb(0.7, 0.7, 0.7)),
+                (Color::rgb(1.0, 0.55, 0.0), Color::rgb(0.75, 0.4, 0.0)),
+            ][player],
+            ColorScheme::RedGreen => [
+                (Color::WHITE, Palette::RedGreen.color(Swatch::Blue)),
+                (
+                    Palette::RedGreen.color(Swatch::Yellow),
+                    Palette::RedGreen.color(Swatch::Orange),
+                ),
+            ][player],
+        }
+    }
+}
+
+impl From<&SnakeSettings> for Colors {
+    fn from(settings: &SnakeSettings) -> Self {
+        let color = |[red, green, blue]: [f32; 3]| {
+            Color::rgb(
+                red.clamp(0.0, 1.0),
+                green.clamp(0.0, 1.0),
+                blue.clamp(0.0, 1.0),
+            )
+        };
+        Self {
+            background: color(settings.background_color),
+            food: color(settings.food_color),
+            scheme: settings.color_scheme,
+            snakes: [
+                (
+                    color(settings.snake_head_color),
+                    color(settings.snake_color),
+                ),
+                SECOND_PLAYER_COLORS,
+            ],
+        }
+    }
+}
+
+/// How loud the sounds and the music play, each from 0 for silent to 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AudioSettings {
+    pub effects_volume: f32,
+    pub music_volume: f32,
+}
+
+impl From<&SnakeSettings> for AudioSettings {
+    fn from(settings: &SnakeSettings) -> Self {
+        Self {
+            effects_volume: settings.effects_volume.clamp(0.0, 1.0),
+            music_volume: settings.music_volume.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// How fast the snake moves, from the slowest step of a new snake to the fastest of a long one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Difficulty {
+    pub fastest_step: f32,
+    pub slowest_step: f32,
+    pub speed_up: f32,
+}
+
+impl Difficulty {
+    /// Seconds between two steps of a snake with `length` segments.
+    pub fn step_time(&self, length: usize) -> f32 {
+        let grown = length.saturating_sub(START_LENGTH) as f32;
+        (self.slowest_step - grown * self.speed_up).max(self.fastest_step)
+    }
+}
+
+impl From<&SnakeSettings> for Difficulty {
+    fn from(settings: &SnakeSettings) -> Self {
+        let slowest_step = settings.slowest_step.max(f32::EPSILON);
+        Self {
+            fastest_step: settings.fastest_step.clamp(f32::EPSILON, slowest_step),
+            slowest_step,
+            speed_up: settings.speed_up.max(0.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_arena_sizes() {
+        assert_eq!(
+            ArenaConfig::parse("40x30"),
+            Some(ArenaConfig {
+                height: 30,
+                width: 40
+            })
+        );
+        assert_eq!(
+            ArenaConfig::parse("20 X 20").map(|arena| arena.width),
+            Some(20)
+        );
+        assert_eq!(ArenaConfig::parse("4x20"), None);
+        assert_eq!(ArenaConfig::parse("20"), None);
+        assert_eq!(ArenaConfig::parse("ax20"), None);
+    }
+
+    #[test]
+    fn reads_settings_written_before_the_color_schemes() {
+        game_test::isolate_saves();
+        let game = "snake_settings_test";
+        let settings = SnakeSettings {
+            arena_width: 30,
+            ..Default::default()
+        };
+        game_settings::save(game, &settings).unwrap();
+        let path = game_settings::path(game).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+        // A missing setting is read as its default, so no migration is needed.
+        let old = text.replace("color_scheme = \"Classic\"\n", "");
+        assert!(!old.contains("color_scheme"));
+        std::fs::write(&path, old).unwrap();
+        assert_eq!(game_settings::load(game).unwrap(), Some(settings));
+    }
+
+    #[test]
+    fn speeds_up_as_the_snake_grows() {
+        let difficulty = Difficulty::from(&SnakeSettings::default());
+        assert_eq!(difficulty.step_time(START_LENGTH), 0.15);
+        assert!(difficulty.step_time(START_LENGTH + 4) < difficulty.step_time(START_LENGTH + 2));
+        assert_eq!(difficulty.step_time(100), 0.06);
+        let reversed = Difficulty::from(&SnakeSettings {
+            fastest_step: 0.3,
+            ..Default::default()
+        });
+        assert_eq!(reversed.step_time(100), 0.15);
+    }
+
+    #[test]
+    fn colors_plain_apples_and_the_first_snake() {
+        let colors = Colors::from(&SnakeSettings {
+            background_color: [1.5, 0.2, -1.0],
+            food_color: [0.0, 1.0, 0.0],
+            snake_head_color: [0.0, 0.0, 1.0],
+            ..Default::default()
+        });
+        assert_eq!(colors.background(), Color::rgb(1.0, 0.2, 0.0));
+        assert_eq!(colors.food(FoodKind::Normal), Color::rgb(0.0, 1.0, 0.0));
+        assert_eq!(colors.food(FoodKind::Poison), FoodKind::Poison.color());
+        assert_eq!(colors.snake(0).0, Color::rgb(0.0, 0.0, 1.0));
+        assert_eq!(colors.snake(1), SECOND_PLAYER_COLORS);
+    }
+
+    #[test]
+    fn keeps_food_apart_in_every_scheme() {
+        let settings = SnakeSettings::default();
+        for scheme in ColorScheme::ALL {
+            let colors = Colors {
+                scheme,
+                ..Colors::from(&settings)
+            };
+            let food: Vec<Color> = [
+                FoodKind::Golden,
+                FoodKind::Normal,
+                FoodKind::Poison,
+                FoodKind::Rotten,
+                FoodKind::Timed,
+            ]
+            .iter()
+            .map(|kind| colors.food(*kind))
+            .collect();
+            for (index, color) in food.iter().enumerate() {
+                assert!(!food[index + 1..].contains(color), "{:?}", scheme);
+                assert_ne!(*color, colors.background(), "{:?}", scheme);
+            }
+        }
+        assert_eq!(ColorScheme::RedGreen.next(), ColorScheme::Classic);
+    }
+
+    #[test]
+    fn centers_the_arena_in_the_window() {
+        let arena = ArenaConfig {
+            height: 30,
+            width: 40,
+        };
+        let window = Vec2::new(500.0, 500.0);
+        assert_eq!(arena.tile_size(window), 12.5);
+        assert_eq!(
+            arena.world_position(Position::new(0, 0), window),
+            Vec2::new(-243.75, -181.25)
+        );
+        assert_eq!(
+            arena.world_position(Position::new(39, 29), window),
+            Vec2::new(243.75, 181.25)
+        );
+    }
+}
@@ -0,0 +1,123 @@
+//! What the snake's keys, d-pad, stick and swipes do, rebindable through `game_input` on the
+//! controls screen.
+
+use bevy::prelude::*;
+use game_input::{Action, Binding, Sign, Swipe};
+use serde::{Deserialize, Serialize};
+
+/// Turns of the first player's snake, and of the second's, which a lone player may use either
+/// of, and the presses of `game_flow`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum SnakeAction {
+    /// Starts a game, and pauses and resumes it.
+    Confirm,
+    Down,
+    Left,
+    Pause,
+    /// Goes back to the menu.
+    Quit,
+    Restart,
+    Right,
+    SecondDown,
+    SecondLeft,
+    SecondRight,
+    SecondUp,
+    Up,
+}
+
+impl SnakeAction {
+    /// The first player's up, left, down and right.
+    pub const FIRST: [Self; 4] = [Self::Up, Self::Left, Self::Down, Self::Right];
+    /// The second player's up, left, down and right.
+    pub const SECOND: [Self; 4] = [
+        Self::SecondUp,
+        Self::SecondLeft,
+        Self::SecondDown,
+        Self::SecondRight,
+    ];
+}
+
+impl Action for SnakeAction {
+    const ALL: &'static [Self] = &[
+        Self::Up,
+        Self::Left,
+        Self::Down,
+        Self::Right,
+        Self::SecondUp,
+        Self::SecondLeft,
+        Self::SecondDown,
+        Self::SecondRight,
+        Self::Confirm,
+        Self::Pause,
+        Self::Restart,
+        Self::Quit,
+    ];
+
+    fn default_bindings(self) -> Vec<Binding> {
+        match self {
+            Self::Confirm => vec![
+                Binding::Key(KeyCode::Return),
+                Binding::Key(KeyCode::Space),
+                Binding::Gamepad(GamepadButtonType::South),
+            ],
+            Self::Down => vec![
+                Binding::Key(KeyCode::S),
+                Binding::Gamepad(GamepadButtonType::DPadDown),
+                Binding::Axis(GamepadAxisType::LeftStickY, Sign::Negative),
+                Binding::Swipe(Swipe::Down),
+            ],
+            Self::Left => vec![
+                Binding::Key(KeyCode::A),
+                Binding::Gamepad(GamepadButtonType::DPadLeft),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Negative),
+                Binding::Swipe(Swipe::Left),
+            ],
+            Self::Pause => vec![
+                Binding::Key(KeyCode::Escape),
+                Binding::Key(KeyCode::P),
+                Binding::Gamepad(GamepadButtonType::Start),
+            ],
+            Self::Quit => vec![
+                Binding::Key(KeyCode::Q),
+                Binding::Gamepad(GamepadButtonType::East),
+            ],
+            Self::Restart => vec![
+                Binding::Key(KeyCode::R),
+                Binding::Gamepad(GamepadButtonType::North),
+            ],
+            Self::Right => vec![
+                Binding::Key(KeyCode::D),
+                Binding::Gamepad(GamepadButtonType::DPadRight),
+                Binding::Axis(GamepadAxisType::LeftStickX, Sign::Positive),
+                Binding::Swipe(Swipe::Right),
+            ],
+            Self::Up => vec![
+                Binding::Key(KeyCode::W),
+                Binding::Gamepad(GamepadButtonType::DPadUp),
+                Binding::Axis(GamepadAxisType::LeftStickY, Sign::Positive),
+                Binding::Swipe(Swipe::Up),
+            ],
+            Self::SecondDown => vec![Binding::Key(KeyCode::Down)],
+            Self::SecondLeft => vec![Binding::Key(KeyCode::Left)],
+            Self::SecondRight => vec![Binding::Key(KeyCode::Right)],
+            Self::SecondUp => vec![Binding::Key(KeyCode::Up)],
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Confirm => "Start",
+            Self::Down => "P1 down",
+            Self::Left => "P1 left",
+            Self::Pause => "Pause",
+            Self::Quit => "Menu",
+            Self::Restart => "Restart",
+            Self::Right => "P1 right",
+            Self::Up => "P1 up",
+            Self::SecondDown => "P2 down",
+            Self::SecondLeft => "P2 left",
+            Self::SecondRight => "P2 right",
+            Self::SecondUp => "P2 up",
+        }
+    }
+}
@@ -0,0 +1,131 @@
+//! The end of a snake that crashed: its segments flash and fade out one by one from the tail
+//! while the camera shakes, and only then does the game over screen come up.
+
+use crate::MainCamera;
+use bevy::prelude::*;
+use game_a11y::Accessibility;
+use game_flow::{EndGame, GameState};
+
+/// Seconds from the crash to the game over screen.
+pub const DEATH_SECONDS: f32 = 1.0;
+/// Seconds each segment takes to flash and fade out.
+const FADE_SECONDS: f32 = 0.5;
+/// Times a second a fading segment turns white and back.
+const FLASH_RATE: f32 = 16.0;
+/// Pixels the camera shakes by at the crash, less and less as the sequence goes on.
+const SHAKE: f32 = 6.0;
+
+/// The game ending once the crashed snakes have faded out, with what to tell the player then.
+pub struct Death {
+    message: String,
+    timer: Timer,
+}
+
+impl Death {
+    pub fn new(message: String) -> Self {
+        Self {
+            message,
+            timer: Timer::from_seconds(DEATH_SECONDS, false),
+        }
+    }
+}
+
+/// The death sequence being played, or played out while the game over screen shows.
+#[derive(Default)]
+pub struct Dying(pub Option<Death>);
+
+impl Dying {
+    /// Seconds into the sequence.
+    pub fn elapsed(&self) -> f32 {
+        self.0
+            .as_ref()
+            .map_or(0.0, |death| death.timer.elapsed_secs())
+    }
+}
+
+/// A segment of a crashed snake, whose turn to fade out comes `delay` seconds into the sequence.
+#[derive(Component)]
+pub struct Fading {
+    delay: f32,
+}
+
+impl Fading {
+    /// `color` as the segment shows it `elapsed` seconds into the sequence: as it was until its
+    /// turn, then flashing white while it fades out.
+    pub fn color(&self, color: Color, elapsed: f32) -> Color {
+        let age = elapsed - self.delay;
+        if age < 0.0 {
+            return color;
+        }
+        let mut color = if (age * FLASH_RATE) as u32 % 2 == 0 {
+            Color::WHITE
+        } else {
+            color
+        };
+        color.set_a((1.0 - age / FADE_SECONDS).max(0.0));
+        color
+    }
+}
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<Dying>().add_system(death_update);
+}
+
+/// Has a crashed snake's segments, head first, fade out in turn from the tail to the head.
+pub(crate) fn fade(commands: &mut Commands, segments: &[Entity]) {
+    let last = segments.len().saturating_sub(1).max(1) as f32;
+    for (index, segment) in segments.iter().enumerate() {
+        let delay = (DEATH_SECONDS - FADE_SECONDS) * (1.0 - index as f32 / last);
+        commands.entity(*segment).insert(Fading { delay });
+    }
+}
+
+/// Plays the death sequence while the game is played, shaking the camera, and ends the game
+/// once it is over. The camera rests while it is paused and after the sequence.
+fn death_update(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    accessibility: Res<Accessibility>,
+    mut dying: ResMut<Dying>,
+    mut end_game: EventWriter<EndGame>,
+    mut cameras: Query<&mut Transform, With<MainCamera>>,
+) {
+    let mut shake = Vec2::ZERO;
+    if let Some(death) = &mut dying.0 {
+        if *state.current() == GameState::Playing && !death.timer.finished() {
+            death.timer.tick(time.delta());
+            if death.timer.just_finished() {
+                end_game.send(EndGame(death.message.clone()));
+            }
+            let elapsed = death.timer.elapsed_secs();
+            let amount = accessibility.motion(SHAKE) * (1.0 - death.timer.percent());
+            shake = Vec2::new((elapsed * 53.0).sin(), (elapsed * 41.0).cos()) * amount;
+        }
+    }
+    for mut transform in cameras.iter_mut() {
+        if transform.translation.truncate() != shake {
+            transform.translation.x = shake.x;
+            transform.translation.y = shake.y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_flash_and_fade_out_in_turn() {
+        let fading = Fading { delay: 0.25 };
+        assert_eq!(fading.color(Color::GREEN, 0.0), Color::GREEN);
+        assert_eq!(
+            fading.color(Color::GREEN, 0.5),
+            Color::rgba(1.0, 1.0, 1.0, 0.5)
+        );
+        assert_eq!(
+            fading.color(Color::GREEN, 0.25 + 1.5 / FLASH_RATE),
+            Color::rgba(0.0, 1.0, 0.0, 1.0 - 1.5 / FLASH_RATE / FADE_SECONDS)
+        );
+        assert_eq!(fading.color(Color::GREEN, DEATH_SECONDS).a(), 0.0);
+    }
+}
@@ -0,0 +1,137 @@
+//! The kinds of food, what they are worth and how often they turn up.
+
+use bevy::prelude::*;
+use game_rng::Stream;
+use serde::{Deserialize, Serialize};
+
+/// Points a golden apple scores on top of the one for growing.
+const GOLDEN_BONUS: u32 = 4;
+/// Steps a poisoned apple lies in the arena before it goes away.
+const POISON_LIFETIME: u32 = 60;
+/// Segments a poisoned apple takes off the tail.
+const POISON_SHRINK: usize = 2;
+/// Steps a rotten apple lies in the arena before it goes away.
+const ROTTEN_LIFETIME: u32 = 60;
+/// Points a rotten apple costs.
+const ROTTEN_PENALTY: u32 = 3;
+/// Points a timed apple scores on top of the one for growing.
+const TIMED_BONUS: u32 = 2;
+/// Steps a timed apple lies in the arena before it goes away.
+const TIMED_LIFETIME: u32 = 25;
+/// How often each kind turns up, out of the sum of all.
+const WEIGHTS: [(FoodKind, u32); 5] = [
+    (FoodKind::Normal, 12),
+    (FoodKind::Golden, 2),
+    (FoodKind::Poison, 2),
+    (FoodKind::Rotten, 3),
+    (FoodKind::Timed, 3),
+];
+
+/// What a piece of food does when eaten, next to its `Food`.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum FoodKind {
+    /// Grows the snake and scores a point.
+    Normal,
+    /// Grows the snake and scores a bonus.
+    Golden,
+    /// Takes segments off the tail, ending the game if that would leave less than the head.
+    Poison,
+    /// Costs points and leaves the snake as it is.
+    Rotten,
+    /// Grows the snake and scores a bonus, if eaten before it goes away.
+    Timed,
+}
+
+impl FoodKind {
+    /// A kind drawn by `WEIGHTS`.
+    pub fn random(stream: &mut Stream) -> Self {
+        let total = WEIGHTS.iter().map(|(_, weight)| weight).sum();
+        let mut roll = stream.below(total);
+        for (kind, weight) in WEIGHTS {
+            if roll < weight {
+                return kind;
+            }
+            roll -= weight;
+        }
+        Self::Normal
+    }
+
+    /// Points scored on top of the one for growing.
+    pub fn bonus(self) -> u32 {
+        match self {
+            Self::Golden => GOLDEN_BONUS,
+            Self::Timed => TIMED_BONUS,
+            Self::Normal | Self::Poison | Self::Rotten => 0,
+        }
+    }
+
+    pub fn color(self) -> Color {
+        match self {
+            Self::Normal => Color::rgb(1.0, 0.0, 1.0),
+            Self::Golden => Color::rgb(1.0, 0.85, 0.1),
+            Self::Poison => Color::rgb(0.5, 0.0, 0.15),
+            Self::Rotten => Color::rgb(0.45, 0.5, 0.15),
+            Self::Timed => Color::rgb(0.2, 0.8, 1.0),
+        }
+    }
+
+    /// Whether eating it grows the snake.
+    pub fn grows(self) -> bool {
+        !matches!(self, Self::Poison | Self::Rotten)
+    }
+
+    /// Steps it lies in the arena before it goes away, if it does.
+    pub fn lifetime(self) -> Option<u32> {
+        match self {
+            Self::Poison => Some(POISON_LIFETIME),
+            Self::Rotten => Some(ROTTEN_LIFETIME),
+            Self::Timed => Some(TIMED_LIFETIME),
+            Self::Normal | Self::Golden => None,
+        }
+    }
+
+    /// The kind named in a console command.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "normal" => Some(Self::Normal),
+            "golden" => Some(Self::Golden),
+            "poison" => Some(Self::Poison),
+            "rotten" => Some(Self::Rotten),
+            "timed" => Some(Self::Timed),
+            _ => None,
+        }
+    }
+
+    /// Points it costs.
+    pub fn penalty(self) -> u32 {
+        match self {
+            Self::Rotten => ROTTEN_PENALTY,
+            Self::Normal | Self::Golden | Self::Poison | Self::Timed => 0,
+        }
+    }
+
+    /// Segments eating it takes off the tail.
+    pub fn shrink(self) -> usize {
+        match self {
+            Self::Poison => POISON_SHRINK,
+            Self::Normal | Self::Golden | Self::Rotten | Self::Timed => 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_kinds_by_weight() {
+        let mut stream = Stream::new(7);
+        let kinds: Vec<FoodKind> = (0..2000).map(|_| FoodKind::random(&mut stream)).collect();
+        let count = |kind| kinds.iter().filter(|drawn| **drawn == kind).count();
+        assert!(count(FoodKind::Normal) > 900);
+        assert!(count(FoodKind::Golden) > 100 && count(FoodKind::Golden) < 300);
+        assert!(count(FoodKind::Poison) > 0);
+        assert!(count(FoodKind::Rotten) > 0);
+        assert!(count(FoodKind::Timed) > 0);
+    }
+}
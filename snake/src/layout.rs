@@ -0,0 +1,145 @@
+//! The obstacles a game can be played with, picked on the menu.
+
+use crate::ArenaConfig;
+use game_grid::Position;
+
+/// Where obstacles stand in the arena, drawn to its size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layout {
+    Open,
+    /// Four square pillars, one in each quarter.
+    Pillars,
+    /// A plus sign across the middle.
+    Cross,
+    /// Two walls down the left and right side, open at both ends.
+    Walls,
+    /// The cells listed as `obstacles` in the snake's `settings.toml`.
+    Custom,
+    /// The levels under `assets/levels`, one after another, each with its own arena.
+    Levels,
+}
+
+impl Layout {
+    pub const ALL: [Self; 6] = [
+        Self::Open,
+        Self::Pillars,
+        Self::Cross,
+        Self::Walls,
+        Self::Custom,
+        Self::Levels,
+    ];
+
+    /// The cells of the obstacles in `arena`, with `custom` the ones from the settings. The
+    /// levels bring their own.
+    pub fn cells(self, arena: &ArenaConfig, custom: &[Position]) -> Vec<Position> {
+        let (width, height) = (arena.width as i32, arena.height as i32);
+        let cells = match self {
+            Self::Open | Self::Levels => Vec::new(),
+            Self::Pillars => {
+                let side = (width.min(height) / 10).max(1);
+                let mut cells = Vec::new();
+                for (x, y) in [
+                    (width / 4, height / 4),
+                    (3 * width / 4, height / 4),
+                    (width / 4, 3 * height / 4),
+                    (3 * width / 4, 3 * height / 4),
+                ] {
+                    for dx in 0..side {
+                        for dy in 0..side {
+                            cells.push(Position::new(x + dx, y + dy));
+                        }
+                    }
+                }
+                cells
+            }
+            Self::Cross => {
+                let horizontal = (width / 4..3 * width / 4).map(|x| Position::new(x, height / 2));
+                let vertical = (height / 4..3 * height / 4).map(|y| Position::new(width / 2, y));
+                horizontal.chain(vertical).collect()
+            }
+            Self::Walls => (height / 4..3 * height / 4)
+                .flat_map(|y| [Position::new(width / 4, y), Position::new(3 * width / 4, y)])
+                .collect(),
+            Self::Custom => custom.to_vec(),
+        };
+        let mut inside: Vec<Position> = Vec::new();
+        for cell in cells {
+            if arena.contains(cell) && !inside.contains(&cell) {
+                inside.push(cell);
+            }
+        }
+        inside
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Open => "Open",
+            Self::Pillars => "Pillars",
+            Self::Cross => "Cross",
+            Self::Walls => "Walls",
+            Self::Custom => "Custom",
+            Self::Levels => "Levels",
+        }
+    }
+
+    /// The layout `step` places further through `ALL`, wrapping around and leaving out the
+    /// `skipped` ones, e.g. the custom one when there are no custom obstacles.
+    pub fn step(self, step: isize, skipped: &[Self]) -> Self {
+        let layouts: Vec<Self> = Self::ALL
+            .into_iter()
+            .filter(|layout| !skipped.contains(layout))
+            .collect();
+        let index = layouts
+            .iter()
+            .position(|layout| *layout == self)
+            .unwrap_or(0);
+        layouts[game_hud::menu::step(index, layouts.len(), step)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arena() -> ArenaConfig {
+        ArenaConfig {
+            height: 10,
+            width: 10,
+        }
+    }
+
+    #[test]
+    fn draws_layouts_to_the_arena() {
+        assert_eq!(Layout::Open.cells(&arena(), &[]), []);
+        assert_eq!(
+            Layout::Pillars.cells(&arena(), &[]),
+            [
+                Position::new(2, 2),
+                Position::new(7, 2),
+                Position::new(2, 7),
+                Position::new(7, 7)
+            ]
+        );
+        let cross = Layout::Cross.cells(&arena(), &[]);
+        assert_eq!(cross.len(), 9);
+        assert!(cross.contains(&Position::new(5, 5)));
+        let custom = [
+            Position::new(1, 1),
+            Position::new(1, 1),
+            Position::new(10, 1),
+        ];
+        assert_eq!(
+            Layout::Custom.cells(&arena(), &custom),
+            [Position::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn skips_layouts_that_cannot_be_played() {
+        let skipped = [Layout::Custom, Layout::Levels];
+        assert_eq!(Layout::Walls.step(1, &skipped), Layout::Open);
+        assert_eq!(Layout::Open.step(-1, &skipped), Layout::Walls);
+        assert_eq!(Layout::Walls.step(1, &[Layout::Custom]), Layout::Levels);
+        assert_eq!(Layout::Levels.step(1, &[]), Layout::Open);
+    }
+}
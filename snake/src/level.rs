@@ -0,0 +1,179 @@
+//! The levels under `assets/levels`, played one after another, and the free play arena picked
+//! on the menu.
+
+use crate::{ArenaConfig, Layout};
+use game_grid::Position;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The levels in the order they are played.
+const BUNDLED: [&str; 3] = [
+    include_str!("../assets/levels/1_garden.ron"),
+    include_str!("../assets/levels/2_courtyard.ron"),
+    include_str!("../assets/levels/3_maze.ron"),
+];
+
+/// An arena with its obstacles and where the snakes start, e.g. one of `assets/levels`.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Level {
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    #[serde(default)]
+    pub obstacles: Vec<(i32, i32)>,
+    /// The head and tail of each player's snake, the first player's first.
+    pub starts: Vec<((i32, i32), (i32, i32))>,
+    /// The score that moves on to the next level, or none to play until a crash.
+    #[serde(default)]
+    pub target: Option<u32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum LevelError {
+    /// Not valid RON, or not the shape of a level.
+    Syntax(String),
+    /// A side of the arena is too short or too long.
+    Size,
+    /// Fewer starts than there can be players.
+    Starts,
+    /// A player's snake starts outside the arena, on an obstacle or another snake, or with its
+    /// tail not next to its head.
+    Start(usize),
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(error) => write!(f, "{}", error),
+            Self::Size => write!(f, "the arena is too small or too large"),
+            Self::Starts => write!(f, "there is no start for each of two players"),
+            Self::Start(player) => write!(f, "player {} cannot start there", player + 1),
+        }
+    }
+}
+
+impl Level {
+    /// The bundled levels, leaving out any that cannot be read.
+    pub fn bundled() -> Vec<Self> {
+        BUNDLED
+            .iter()
+            .enumerate()
+            .filter_map(|(index, text)| {
+                Self::parse(text)
+                    .map_err(|error| eprintln!("could not read level {}: {}", index + 1, error))
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// The arena from the settings with a layout picked on the menu, played until a crash.
+    pub fn free(arena: &ArenaConfig, layout: Layout, custom: &[Position]) -> Self {
+        let (width, height) = (arena.width as i32, arena.height as i32);
+        Self {
+            name: layout.name().to_string(),
+            width: arena.width,
+            height: arena.height,
+            obstacles: layout
+                .cells(arena, custom)
+                .into_iter()
+                .map(|cell| (cell.x, cell.y))
+                .collect(),
+            // The second player in the opposite corner of the first.
+            starts: vec![
+                ((3, 3), (3, 2)),
+                ((width - 4, height - 4), (width - 4, height - 3)),
+            ],
+            target: None,
+        }
+    }
+
+    pub fn parse(text: &str) -> Result<Self, LevelError> {
+        let level: Self =
+            ron::from_str(text).map_err(|error| LevelError::Syntax(error.to_string()))?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn arena(&self) -> ArenaConfig {
+        ArenaConfig {
+            height: self.height,
+            width: self.width,
+        }
+    }
+
+    pub fn obstacles(&self) -> Vec<Position> {
+        self.obstacles
+            .iter()
+            .map(|(x, y)| Position::new(*x, *y))
+            .collect()
+    }
+
+    /// The head and tail of a player's snake.
+    pub fn start(&self, player: usize) -> [Position; 2] {
+        let ((head_x, head_y), (tail_x, tail_y)) = self.starts[player];
+        [Position::new(head_x, head_y), Position::new(tail_x, tail_y)]
+    }
+
+    /// Checks that the arena has a size it can be played at, and that two snakes can start in it.
+    fn validate(&self) -> Result<(), LevelError> {
+        let arena = ArenaConfig::new(self.width, self.height).ok_or(LevelError::Size)?;
+        if self.starts.len() < 2 {
+            return Err(LevelError::Starts);
+        }
+        let obstacles = self.obstacles();
+        let mut taken: Vec<Position> = Vec::new();
+        for player in 0..self.starts.len() {
+            let [head, tail] = self.start(player);
+            let apart = (head.x - tail.x).abs() + (head.y - tail.y).abs();
+            for cell in [head, tail] {
+                if apart != 1
+                    || !arena.contains(cell)
+                    || obstacles.contains(&cell)
+                    || taken.contains(&cell)
+                {
+                    return Err(LevelError::Start(player));
+                }
+                taken.push(cell);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_bundled_levels() {
+        for text in BUNDLED {
+            assert_eq!(Level::parse(text).map(|_| ()), Ok(()));
+        }
+        let levels = Level::bundled();
+        assert!(levels.iter().all(|level| level.target.is_some()));
+    }
+
+    #[test]
+    fn rejects_levels_the_snakes_cannot_start_in() {
+        let level = |starts: &str| {
+            Level::parse(&format!(
+                "(name: \"Test\", width: 10, height: 10, obstacles: [(5, 5)], starts: {})",
+                starts
+            ))
+        };
+        assert!(level("[((3, 3), (3, 2)), ((6, 6), (6, 7))]").is_ok());
+        assert_eq!(level("[((3, 3), (3, 2))]"), Err(LevelError::Starts));
+        assert_eq!(
+            level("[((3, 3), (3, 2)), ((5, 6), (5, 5))]"),
+            Err(LevelError::Start(1))
+        );
+        assert_eq!(
+            level("[((3, 3), (3, 1)), ((6, 6), (6, 7))]"),
+            Err(LevelError::Start(0))
+        );
+        assert_eq!(
+            Level::parse("(name: \"Test\", width: 4, height: 10, starts: [])"),
+            Err(LevelError::Size)
+        );
+    }
+}
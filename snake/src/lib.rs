@@ -0,0 +1,2396 @@
+use audio::Sound;
+use bevy::{ecs::schedule::ShouldRun, prelude::*};
+use death::{Death, Dying, Fading};
+use game_a11y::Announcements;
+use game_achievements::AchievementsScreen;
+use game_flow::{EndGame, FlowInput, FlowPlugin, GameState, NewGame, Press};
+use game_grid::{Direction, Grid, Position};
+use game_hud::{HudFont, HudPlugin, MenuInput};
+use game_i18n::I18nPlugin;
+use game_input::{ActionState, ControlsScreen, InputPlugin};
+use game_particles::{Emit, ParticlePlugin};
+use game_platform::Platform;
+use game_rng::{Rng, RngPlugin};
+use game_scores::{Leaderboard, Order, Scores, ScoresPlugin};
+use game_theme::{Role, ThemePlugin, Themed, Themes};
+use game_tiles::{Tile, Tilemap, TilesPlugin};
+use lobby::Lobby;
+use net::{Hosting, Joined};
+use std::time::Duration;
+
+mod achievements;
+mod audio;
+mod bot;
+mod config;
+mod controls;
+mod death;
+mod food;
+mod layout;
+mod level;
+mod lobby;
+mod net;
+mod power_up;
+mod replay;
+mod simulation;
+mod sprites;
+mod stats;
+
+pub use bot::Bot;
+pub use config::{ArenaConfig, AudioSettings, ColorScheme, Colors, Difficulty, SnakeSettings};
+pub use controls::SnakeAction;
+pub use food::FoodKind;
+pub use layout::Layout;
+pub use level::{Level, LevelError};
+pub use power_up::{ActiveEffects, PowerUp};
+pub use replay::{Replay, Turn};
+pub use simulation::{Observation, Simulation};
+pub use sprites::SegmentShape;
+pub use stats::{Crash, LifetimeStats, RunStats};
+
+/// Seconds a crash takes off the clock of a time attack.
+const CRASH_PENALTY: f32 = 10.0;
+/// The `game_persistence` directory of the snake's data.
+const GAME: &str = "snake";
+const LEADERBOARD_FONT_SIZE: f32 = 12.0;
+/// Times the points a snake scores with a multiplier.
+const MULTIPLIER: u32 = 2;
+const OBSTACLE_COLOR: Color = Color::rgb(0.35, 0.4, 0.55);
+/// Steps without a power-up in the arena before the next turns up, at the least.
+const POWER_UP_INTERVAL: u32 = 40;
+/// Steps a power-up lies in the arena before it goes away.
+const POWER_UP_LIFETIME: u32 = 50;
+/// Points that win a race against the computer.
+const RACE_TARGET: u32 = 10;
+/// The `game_persistence` file the last game is saved to as a replay.
+const REPLAY: &str = "replay";
+const SCORE_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+/// How much of the usual time a step takes during a speed boost.
+const SPEED_BOOST: f32 = 0.6;
+/// Seconds a time attack lasts, unless crashes take them off sooner.
+const TIME_ATTACK_SECONDS: f32 = 90.0;
+
+/// What the arena shows in a cell.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cell {
+    Empty,
+    /// Food of the color its kind is drawn in.
+    Food(Color),
+    Obstacle,
+    /// A power-up, in the color of its kind.
+    PowerUp(Color),
+}
+
+/// Points a snake scores on top of the one for growing.
+struct BonusEvent {
+    head: Entity,
+    points: u32,
+}
+
+/// A player's snake reached the target of the last level, or won a race.
+struct ClearedEvent(Player);
+
+/// The time left of a time attack.
+struct Clock(Timer);
+
+/// Shows the time left of a time attack.
+#[derive(Component)]
+struct ClockText;
+
+/// A piece of food, with a `FoodKind` saying what it does.
+#[derive(Component)]
+struct Food;
+
+/// The arena and custom obstacles from the settings, played unless the levels are picked.
+struct FreePlay {
+    arena: ArenaConfig,
+    obstacles: Vec<Position>,
+}
+
+/// A player's snake crashed, which ends the game but for a time attack.
+struct GameOverEvent(Player, Crash);
+
+/// The cell a segment left on the last step and the one it moved to, which its sprite glides
+/// between while the next step is due.
+#[derive(Component)]
+struct Glide {
+    from: Position,
+    to: Position,
+}
+
+/// The snake with this head ate something that makes it grow.
+struct GrowthEvent(Entity);
+
+/// Where the tail of a snake was before its last step, kept on its head for it to grow into.
+#[derive(Component, Default)]
+struct LastTailPosition(Option<Position>);
+
+/// The bundled levels, and how far through them the game is.
+struct Levels {
+    all: Vec<Level>,
+    current: usize,
+    /// Each player's score when the current level began, which the target is counted from.
+    start_scores: Vec<u32>,
+}
+
+/// Steps left before a piece of food or a power-up goes away.
+#[derive(Component)]
+struct Lifetime(u32);
+
+/// One snake, two on one keyboard, one racing the computer's or one against the clock, chosen
+/// on the menu; or two on different machines, when hosting or joining a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Mode {
+    OnePlayer,
+    TwoPlayers,
+    /// The first player against a second snake the computer steers.
+    Versus,
+    /// One snake eating as much as it can before the clock runs out; crashing costs time.
+    TimeAttack,
+    /// The host's snake against that of the player who joined, who steers it from afar.
+    Online,
+}
+
+impl Mode {
+    const ALL: [Self; 4] = [
+        Self::OnePlayer,
+        Self::TwoPlayers,
+        Self::Versus,
+        Self::TimeAttack,
+    ];
+
+    /// The up, left, down and right actions steering a player's snake; a lone player may use
+    /// either set, and the computer's snake and the one steered from afar have none.
+    fn actions(self, player: Player) -> &'static [[SnakeAction; 4]] {
+        match (self, player) {
+            (Self::OnePlayer | Self::TimeAttack, _) | (Self::Versus | Self::Online, Player(0)) => {
+                &[SnakeAction::FIRST, SnakeAction::SECOND]
+            }
+            (Self::Versus | Self::Online, _) => &[],
+            (Self::TwoPlayers, Player(0)) => &[SnakeAction::FIRST],
+            (Self::TwoPlayers, _) => &[SnakeAction::SECOND],
+        }
+    }
+
+    /// Whether the computer steers a player's snake.
+    fn computer(self, player: Player) -> bool {
+        self == Self::Versus && player != Player(0)
+    }
+
+    /// Who plays, on the menu.
+    fn name(self) -> &'static str {
+        match self {
+            Self::OnePlayer => "1",
+            Self::TwoPlayers => "2",
+            Self::Versus => "1 against the computer",
+            Self::TimeAttack => "1 against the clock",
+            Self::Online => "2 over the network",
+        }
+    }
+
+    fn players(self) -> usize {
+        match self {
+            Self::OnePlayer | Self::TimeAttack => 1,
+            Self::TwoPlayers | Self::Versus | Self::Online => 2,
+        }
+    }
+
+    /// The high score table of a lone player's games.
+    fn table(self) -> Option<&'static str> {
+        match self {
+            Self::OnePlayer => Some(""),
+            Self::TimeAttack => Some("time attack"),
+            Self::TwoPlayers | Self::Versus | Self::Online => None,
+        }
+    }
+
+    /// The name of a player, e.g. for who won.
+    fn player_name(self, Player(player): Player) -> String {
+        match (self, player) {
+            (Self::Versus, 0) => "You".to_string(),
+            (Self::Versus, _) => "Computer".to_string(),
+            _ => format!("P{}", player + 1),
+        }
+    }
+}
+
+/// The players and obstacles picked, shown on the menu.
+#[derive(Component)]
+struct MenuText;
+
+/// The camera of the arena, as opposed to the UI camera.
+#[derive(Component)]
+struct MainCamera;
+
+/// The time between two steps of the snake, shorter as it grows.
+struct MoveTimer(Timer);
+
+/// A cell of the arena a snake crashes into.
+#[derive(Component)]
+struct Obstacle;
+
+/// Points a snake loses for eating something bad.
+struct PenaltyEvent {
+    head: Entity,
+    points: u32,
+}
+
+/// Segments to take off the tail of the snake with this head.
+struct ShrinkEvent {
+    head: Entity,
+    segments: usize,
+}
+
+/// The game being played back from the replay given with `--replay`, instead of the players'
+/// turns.
+struct Playback(Option<Replay>);
+
+/// Which player steers a snake, on each of its segments.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+struct Player(usize);
+
+/// Steps until the next power-up turns up, counted while there is none in the arena.
+struct PowerUpCountdown(u32);
+
+/// The game being played, recorded step by step until it ends.
+struct Recording {
+    replay: Option<Replay>,
+    /// Steps since the game started.
+    step: u32,
+}
+
+/// Food a snake has eaten, kept on its head.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct Score(u32);
+
+#[derive(Component)]
+struct ScoreText;
+
+/// The sprite sheet the arena and the snakes are drawn from.
+struct SnakeAtlas(Handle<TextureAtlas>);
+
+#[derive(Component)]
+struct SnakeHead {
+    direction: Direction,
+}
+
+#[derive(SystemLabel, Debug, Hash, PartialEq, Eq, Clone)]
+pub enum SnakeMovement {
+    Input,
+    Movement,
+    Eating,
+    Growth,
+    Shrinking,
+    /// Food and power-ups whose time is up going away.
+    Expiry,
+    PowerUps,
+    /// The snakes' turns recorded, or played back.
+    Replay,
+}
+
+#[derive(Component)]
+struct SnakeSegment;
+
+/// The segments of a snake, head first, kept on its head.
+#[derive(Component, Default)]
+struct SnakeSegments(Vec<Entity>);
+
+/// The clock of a time attack ran out.
+struct TimeUpEvent;
+
+pub struct SnakePlugin;
+
+impl Plugin for SnakePlugin {
+    fn build(&self, app: &mut App) {
+        let settings = SnakeSettings::load();
+        let arena = ArenaConfig::load(&settings);
+        let difficulty = Difficulty::from(&settings);
+        let colors = Colors::from(&settings);
+        app.add_plugin(HudPlugin)
+            .add_plugin(
+                FlowPlugin::new("Snake")
+                    .instructions(concat!(
+                        "Arrows or WASD: steer\n",
+                        "B: let the computer steer\n",
+                        "P or Space: pause\n",
+                        "1 or 2: players, WASD against arrows\n",
+                        "3: race the computer\n",
+                        "4: play against the clock\n",
+                        "Up or down: obstacles\n",
+                        "T: colors\n",
+                        "C: change the controls\n",
+                        "N: play over the network\n",
+                        "V: view the achievements",
+                    ))
+                    .confirm_pauses()
+                    .countdown(0.0)
+                    .without_keys(),
+            )
+            .add_plugin(I18nPlugin::default())
+            .add_plugin(InputPlugin::<SnakeAction>::new(GAME))
+            .add_plugin(ParticlePlugin::with_presets(include_str!(
+                "../assets/particles.ron"
+            )))
+            .add_plugin(RngPlugin::default())
+            .add_plugin(ScoresPlugin::new(GAME, Order::Highest))
+            .add_plugin(ThemePlugin::with_overrides(include_str!(
+                "../assets/theme.ron"
+            )))
+            .add_plugin(TilesPlugin::<Cell>::default())
+            .insert_resource(ActiveEffects::default())
+            .insert_resource(AudioSettings::from(&settings))
+            .insert_resource(Clock(Timer::from_seconds(TIME_ATTACK_SECONDS, false)))
+            .insert_resource(ClearColor(colors.background()))
+            .insert_resource(colors)
+            .insert_resource(arena)
+            .insert_resource(FreePlay {
+                arena,
+                obstacles: settings.custom_obstacles(),
+            })
+            .insert_resource(Layout::Open)
+            .insert_resource(Level::free(&arena, Layout::Open, &[]))
+            .insert_resource(Levels {
+                all: Level::bundled(),
+                current: 0,
+                start_scores: Vec::new(),
+            })
+            .insert_resource(Mode::OnePlayer)
+            .insert_resource(Bot::load())
+            .insert_resource(Playback(Replay::load()))
+            .insert_resource(PowerUpCountdown(POWER_UP_INTERVAL))
+            .insert_resource(Recording {
+                replay: None,
+                step: 0,
+            })
+            .insert_resource(RunStats::default())
+            .insert_resource(LifetimeStats::default())
+            .init_resource::<Platform>()
+            .insert_resource(MoveTimer(Timer::from_seconds(
+                difficulty.slowest_step,
+                true,
+            )))
+            .insert_resource(difficulty)
+            .add_startup_system_to_stage(StartupStage::PreStartup, stats_load)
+            .add_startup_system(setup_camera)
+            .add_startup_system(setup_leaderboard)
+            .add_startup_system(setup_menu_text)
+            .add_startup_system(setup_score_text)
+            .add_startup_system(setup_tilemap)
+            .add_event::<BonusEvent>()
+            .add_event::<ClearedEvent>()
+            .add_event::<GameOverEvent>()
+            .add_event::<GrowthEvent>()
+            .add_event::<PenaltyEvent>()
+            .add_event::<ShrinkEvent>()
+            .add_event::<TimeUpEvent>()
+            .add_system(flow_presses.before(FlowInput))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(clock_update)
+                    .with_system(level_progress)
+                    .with_system(run_time_update),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(bot_input.before(SnakeMovement::Input))
+                    .with_system(
+                        snake_movement_input
+                            .label(SnakeMovement::Input)
+                            .before(SnakeMovement::Movement),
+                    ),
+            )
+            .add_system(
+                game_over
+                    .after(SnakeMovement::Movement)
+                    .after(SnakeMovement::Shrinking),
+            )
+            .add_system(
+                crash_penalty
+                    .after(SnakeMovement::Movement)
+                    .after(SnakeMovement::Growth)
+                    .after(SnakeMovement::Shrinking),
+            )
+            .add_system(run_reset)
+            .add_system(clock_text_update)
+            .add_system(leaderboard_update)
+            .add_system(menu_text_update)
+            .add_system(new_game)
+            .add_system(score_update)
+            .add_system(segment_orientation)
+            .add_system(segment_sprite_spawn)
+            .add_system(segment_sprite_update)
+            .add_system(position_translation.after(SnakeMovement::Movement))
+            .add_system(
+                speed_update
+                    .after(SnakeMovement::Growth)
+                    .after(SnakeMovement::Shrinking)
+                    .after(SnakeMovement::PowerUps),
+            )
+            .add_system(tilemap_update)
+            .add_system_set(step_systems().with_run_criteria(step_elapsed))
+            // After the theme has shown its background.
+            .add_system_to_stage(CoreStage::PostUpdate, background_update);
+        achievements::build(app);
+        audio::build(app);
+        death::build(app);
+        lobby::build(app);
+        net::build(app);
+        game_console::add_command(
+            app,
+            "set_tick_rate",
+            "<seconds>",
+            "Sets the time between two steps of the snake",
+            set_tick_rate,
+        );
+        game_console::add_command(
+            app,
+            "spawn_food",
+            "<x> <y> [normal|golden|poison|rotten|timed]",
+            "Puts food on a cell",
+            spawn_food,
+        );
+        game_console::add_command(
+            app,
+            "spawn_power_up",
+            "<x> <y> <speed|ghost|multiplier>",
+            "Puts a power-up on a cell",
+            spawn_power_up,
+        );
+        game_console::add_command(
+            app,
+            "teleport",
+            "<x> <y> [player]",
+            "Moves the head of a player's snake to a cell",
+            teleport,
+        );
+    }
+}
+
+/// The window, as large as the settings have it.
+pub fn window_descriptor() -> WindowDescriptor {
+    let size = SnakeSettings::load().window_size();
+    WindowDescriptor {
+        title: "Severus Snek!".to_string(),
+        width: size.x,
+        height: size.y,
+        // In a browser the game draws on the canvas of `web/index.html`.
+        #[cfg(target_arch = "wasm32")]
+        canvas: Some("#snake".to_string()),
+        ..Default::default()
+    }
+}
+
+/// The cell of the arena given by a console command.
+fn arena_cell(world: &World, args: &[&str]) -> Result<Position, String> {
+    let position = Position {
+        x: game_console::arg(args, 0, "x")?,
+        y: game_console::arg(args, 1, "y")?,
+    };
+    if !world
+        .get_resource::<ArenaConfig>()
+        .unwrap()
+        .contains(position)
+    {
+        return Err(format!(
+            "{}, {} is outside the arena",
+            position.x, position.y
+        ));
+    }
+    Ok(position)
+}
+
+/// Shows the background of the color scheme whenever it or the theme changes; the classic
+/// scheme leaves it to the theme.
+fn background_update(
+    colors: Res<Colors>,
+    themes: Res<Themes>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if !colors.is_changed() && !themes.is_changed() {
+        return;
+    }
+    clear_color.0 = match colors.scheme {
+        ColorScheme::Classic => themes
+            .current()
+            .color(Role::Background, colors.background()),
+        _ => colors.background(),
+    };
+}
+
+/// Steers the snakes the computer plays toward food: the first player's while the bot plays,
+/// holding down its turns as the keys would, and the computer's own snake in a race.
+fn bot_input(
+    bot: Res<Bot>,
+    mode: Res<Mode>,
+    playback: Res<Playback>,
+    arena: Res<ArenaConfig>,
+    mut actions: ResMut<ActionState<SnakeAction>>,
+    mut heads: Query<(&Position, &Player, &SnakeSegments, &mut SnakeHead)>,
+    food: Query<(&Position, &FoodKind), With<Food>>,
+    blocked: Query<&Position, Or<(With<SnakeSegment>, With<Obstacle>)>>,
+) {
+    if playback.0.is_some() {
+        return;
+    }
+    let blocked: Vec<Position> = blocked.iter().copied().collect();
+    let food: Vec<Position> = food
+        .iter()
+        .filter(|(_, kind)| kind.grows())
+        .map(|(position, _)| *position)
+        .collect();
+    for (head, player, segments, mut snake) in heads.iter_mut() {
+        if !mode.computer(*player) && !(bot.0 && *player == Player(0)) {
+            continue;
+        }
+        let direction = match bot::steer(&arena, *head, segments.0.len(), &blocked, &food) {
+            Some(direction) => direction,
+            None => continue,
+        };
+        let set = match mode.actions(*player).first() {
+            Some(set) => set,
+            // The computer's own snake has no keys to press.
+            None => {
+                snake.direction = snake.direction.turn(direction);
+                continue;
+            }
+        };
+        // Where each direction's action is in a set of up, left, down and right.
+        let index = match direction {
+            Direction::Up => 0,
+            Direction::Left => 1,
+            Direction::Down => 2,
+            _ => 3,
+        };
+        actions.press(set[index]);
+    }
+}
+
+fn cell_tile(cell: &Cell) -> Option<Tile> {
+    match cell {
+        Cell::Empty => None,
+        Cell::Food(color) => Some(sprite(*color, sprites::FOOD, 0.8)),
+        Cell::Obstacle => Some(sprite(OBSTACLE_COLOR, sprites::BLOCK, 1.0)),
+        Cell::PowerUp(color) => Some(sprite(*color, sprites::FOOD, 0.55)),
+    }
+}
+
+/// Counts down the clock of a time attack, and has the game end once it runs out.
+fn clock_update(
+    time: Res<Time>,
+    mode: Res<Mode>,
+    mut clock: ResMut<Clock>,
+    mut time_up_writer: EventWriter<TimeUpEvent>,
+) {
+    if *mode != Mode::TimeAttack {
+        return;
+    }
+    clock.0.tick(time.delta());
+    if clock.0.just_finished() {
+        time_up_writer.send(TimeUpEvent);
+    }
+}
+
+/// Shows the whole seconds left of a time attack under the score.
+fn clock_text_update(
+    mode: Res<Mode>,
+    clock: Res<Clock>,
+    mut texts: Query<&mut Text, With<ClockText>>,
+) {
+    if !mode.is_changed() && !clock.is_changed() {
+        return;
+    }
+    let value = match *mode {
+        Mode::TimeAttack => {
+            let left = clock.0.duration().saturating_sub(clock.0.elapsed());
+            format!("Time: {}", left.as_secs_f32().ceil())
+        }
+        _ => String::new(),
+    };
+    for mut text in texts.iter_mut() {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+/// In a time attack a crash costs `CRASH_PENALTY` seconds instead of the game: the snake starts
+/// over where the level starts it, keeping its score.
+fn crash_penalty(
+    mut commands: Commands,
+    mut reader: EventReader<GameOverEvent>,
+    mode: Res<Mode>,
+    level: Res<Level>,
+    mut clock: ResMut<Clock>,
+    mut announcements: ResMut<Announcements>,
+    snakes: Query<(&Player, &SnakeSegments, &Score)>,
+) {
+    let crashed: Vec<Player> = reader
+        .iter()
+        .map(|GameOverEvent(player, _)| *player)
+        .collect();
+    if *mode != Mode::TimeAttack {
+        return;
+    }
+    for (player, segments, score) in snakes.iter() {
+        if !crashed.contains(player) {
+            continue;
+        }
+        for segment in &segments.0 {
+            commands.entity(*segment).despawn();
+        }
+        snake_spawn(&mut commands, &level, *player, *score);
+        let elapsed = clock.0.elapsed() + Duration::from_secs_f32(CRASH_PENALTY);
+        let duration = clock.0.duration();
+        clock.0.set_elapsed(elapsed.min(duration));
+        announcements.announce(format!("Crashed, {} seconds off the clock", CRASH_PENALTY));
+    }
+}
+
+/// Takes away the food and power-ups whose time is up, counting down a step.
+fn lifetime_expiry(mut commands: Commands, mut lifetimes: Query<(Entity, &mut Lifetime)>) {
+    for (entity, mut lifetime) in lifetimes.iter_mut() {
+        lifetime.0 = lifetime.0.saturating_sub(1);
+        if lifetime.0 == 0 {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Keeps something to grow on in the arena. A new game starts with a normal apple; once the
+/// food that grows the snake is gone, pieces of random kinds are dropped until one does.
+fn food_spawner(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    mut rng: ResMut<Rng>,
+    food: Query<(&FoodKind, &Position, Option<&Lifetime>), With<Food>>,
+    heads: Query<&Position, With<SnakeHead>>,
+    obstacles: Query<&Position, With<Obstacle>>,
+    power_ups: Query<&Position, With<PowerUp>>,
+    segments: Query<&Position, With<SnakeSegment>>,
+) {
+    if growing_food_left(&food, &heads) {
+        return;
+    }
+    let mut taken: Vec<Position> = segments
+        .iter()
+        .chain(obstacles.iter())
+        .chain(power_ups.iter())
+        .chain(food.iter().map(|(_, position, _)| position))
+        .copied()
+        .collect();
+    let mut kind = FoodKind::Normal;
+    if !food.is_empty() {
+        kind = FoodKind::random(&mut rng.gameplay);
+    }
+    // A full arena has no room for food, and the game goes on until a snake crashes.
+    while let Some(position) = get_available_position(&arena, &mut rng, &taken) {
+        taken.push(position);
+        food_spawn(&mut commands, kind, position);
+        if kind.grows() {
+            return;
+        }
+        kind = FoodKind::random(&mut rng.gameplay);
+    }
+}
+
+fn food_spawn(commands: &mut Commands, kind: FoodKind, position: Position) {
+    let mut food = commands.spawn();
+    food.insert(Food).insert(kind).insert(position);
+    if let Some(steps) = kind.lifetime() {
+        food.insert(Lifetime(steps));
+    }
+}
+
+/// Whether food that grows the snake stays after this step. Food eaten or gone this step is
+/// still there until the commands run.
+fn growing_food_left(
+    food: &Query<(&FoodKind, &Position, Option<&Lifetime>), With<Food>>,
+    heads: &Query<&Position, With<SnakeHead>>,
+) -> bool {
+    food.iter().any(|(kind, position, lifetime)| {
+        kind.grows()
+            && !heads.iter().any(|head| head == position)
+            && lifetime.map_or(true, |lifetime| lifetime.0 > 0)
+    })
+}
+
+/// The direction of an action held in any of the `sets` of up, left, down and right actions.
+fn held_direction(
+    actions: &ActionState<SnakeAction>,
+    sets: &[[SnakeAction; 4]],
+) -> Option<Direction> {
+    // Where each direction's action is in a set, in the order they win when several are held.
+    [
+        (2, Direction::Down),
+        (1, Direction::Left),
+        (3, Direction::Right),
+        (0, Direction::Up),
+    ]
+    .into_iter()
+    .find(|(index, _)| sets.iter().any(|set| actions.pressed(set[*index])))
+    .map(|(_, direction)| direction)
+}
+
+/// A random cell of the arena that is not `taken`, or `None` when every cell is. Cells are drawn
+/// at random until a free one comes up, as seeded games and their replays have always drawn them.
+fn get_available_position(
+    arena: &ArenaConfig,
+    rng: &mut Rng,
+    taken: &[Position],
+) -> Option<Position> {
+    let free = (0..arena.height as i32)
+        .flat_map(|y| (0..arena.width as i32).map(move |x| Position { x, y }))
+        .any(|position| !taken.contains(&position));
+    if !free {
+        return None;
+    }
+    loop {
+        let position = Position {
+            x: rng.gameplay.below(arena.width) as i32,
+            y: rng.gameplay.below(arena.height) as i32,
+        };
+        if !taken.contains(&position) {
+            return Some(position);
+        }
+    }
+}
+
+/// Ends the game when a snake crashes, the clock of a time attack runs out or a snake clears
+/// the last level, once the snakes that crashed have faded out. A lone player's score goes into
+/// the high scores of the mode; of two players, the one who cleared the levels or did not crash
+/// wins.
+fn game_over(
+    mut commands: Commands,
+    mut reader: EventReader<GameOverEvent>,
+    mut cleared_reader: EventReader<ClearedEvent>,
+    mut time_up_reader: EventReader<TimeUpEvent>,
+    mode: Res<Mode>,
+    platform: Res<Platform>,
+    // Together, as a system takes at most 16 parameters.
+    (playback, recording): (Res<Playback>, Res<Recording>),
+    rng: Res<Rng>,
+    mut scores: ResMut<Scores>,
+    mut run: ResMut<RunStats>,
+    mut lifetime: ResMut<LifetimeStats>,
+    mut dying: ResMut<Dying>,
+    snakes: Query<(&Player, &Score, &SnakeSegments)>,
+    mut leaderboards: Query<&mut Leaderboard>,
+    mut announcements: ResMut<Announcements>,
+    mut end_game: EventWriter<EndGame>,
+) {
+    let mut crashed = Vec::new();
+    for GameOverEvent(player, crash) in reader.iter() {
+        if *player == Player(0) {
+            run.crash(*crash);
+        }
+        crashed.push(*player);
+    }
+    // A crash only costs time in a time attack.
+    if *mode == Mode::TimeAttack {
+        crashed.clear();
+    }
+    let cleared = cleared_reader
+        .iter()
+        .next()
+        .map(|ClearedEvent(player)| *player);
+    let time_up = time_up_reader.iter().next().is_some();
+    if crashed.is_empty() && cleared.is_none() && !time_up {
+        return;
+    }
+    if let (None, Some(replay)) = (&playback.0, &recording.replay) {
+        if let Err(error) = platform.save(GAME, REPLAY, replay, &Replay::schema()) {
+            eprintln!("could not save the replay: {}", error);
+        }
+    }
+    lifetime.add(&run);
+    lifetime.save(&platform);
+    let summary = format!("{}\n{}", run.summary(), lifetime.summary());
+    let message = if mode.players() == 2 {
+        let winner = cleared.or_else(|| {
+            snakes
+                .iter()
+                .map(|(player, _, _)| *player)
+                .find(|player| !crashed.contains(player))
+        });
+        let message = match winner {
+            Some(Player(0)) if *mode == Mode::Versus => "You win!".to_string(),
+            Some(_) if *mode == Mode::Versus => "The computer wins!".to_string(),
+            Some(Player(winner)) => format!("Player {} wins!", winner + 1),
+            None => "It's a draw!".to_string(),
+        };
+        announcements.announce(message.clone());
+        message
+    } else {
+        let score = snakes.iter().next().map_or(0, |(_, score, _)| score.0);
+        if cleared.is_some() {
+            announcements.announce(format!("All levels cleared with a score of {}", score));
+        } else if time_up {
+            announcements.announce(format!("Time is up with a score of {}", score));
+        } else {
+            announcements.announce(format!("Game over with a score of {}", score));
+        }
+        let table = mode.table().unwrap_or_default();
+        let mut place = None;
+        scores.set_seed(&rng.seed().to_string());
+        if score > 0 {
+            place = scores.add_unnamed(table, score);
+            scores.save(&platform);
+        }
+        // Adding a score fetches the global table, which is shown all the same without one.
+        #[cfg(feature = "online")]
+        if score == 0 {
+            scores.fetch_global(table);
+        }
+        for mut leaderboard in leaderboards.iter_mut() {
+            leaderboard.highlight = place;
+        }
+        match (cleared, time_up) {
+            (Some(_), _) => format!("All levels cleared!\nScore: {}", score),
+            (None, true) => format!("Time's up!\nScore: {}", score),
+            (None, false) => format!("Score: {}", score),
+        }
+    };
+    let message = format!("{}\n{}", message, summary);
+    if crashed.is_empty() {
+        end_game.send(EndGame(message));
+        return;
+    }
+    for (player, _, segments) in snakes.iter() {
+        if crashed.contains(player) {
+            death::fade(&mut commands, &segments.0);
+        }
+    }
+    dying.0 = Some(Death::new(message));
+}
+
+/// Clears the run's stats and the last death, and fills the clock of a time attack for every new
+/// game.
+fn run_reset(
+    mut new_games: EventReader<NewGame>,
+    mut clock: ResMut<Clock>,
+    mut dying: ResMut<Dying>,
+    mut stats: ResMut<RunStats>,
+) {
+    if new_games.iter().next().is_some() {
+        clock.0.reset();
+        dying.0 = None;
+        *stats = RunStats::default();
+    }
+}
+
+/// Counts the time the run has been played.
+fn run_time_update(time: Res<Time>, mut stats: ResMut<RunStats>) {
+    stats.seconds += time.delta_seconds();
+}
+
+/// Counts the points of the food each snake eats and shows them, with the players apart when
+/// there are two.
+fn score_update(
+    mut growth_reader: EventReader<GrowthEvent>,
+    mut bonus_reader: EventReader<BonusEvent>,
+    mut penalty_reader: EventReader<PenaltyEvent>,
+    mode: Res<Mode>,
+    effects: Res<ActiveEffects>,
+    mut snakes: Query<(&Player, &mut Score)>,
+    mut texts: Query<&mut Text, With<ScoreText>>,
+) {
+    let multiplier = |Player(player): &Player| {
+        if effects.has(*player, PowerUp::Multiplier) {
+            MULTIPLIER
+        } else {
+            1
+        }
+    };
+    for GrowthEvent(head) in growth_reader.iter() {
+        if let Ok((player, mut score)) = snakes.get_mut(*head) {
+            score.0 += multiplier(player);
+        }
+    }
+    for BonusEvent { head, points } in bonus_reader.iter() {
+        if let Ok((player, mut score)) = snakes.get_mut(*head) {
+            score.0 += points * multiplier(player);
+        }
+    }
+    for PenaltyEvent { head, points } in penalty_reader.iter() {
+        if let Ok((_, mut score)) = snakes.get_mut(*head) {
+            score.0 = score.0.saturating_sub(*points);
+        }
+    }
+    if !snakes.iter_mut().any(|(_, score)| score.is_changed()) {
+        return;
+    }
+    let mut scores: Vec<(usize, u32)> = snakes
+        .iter()
+        .map(|(Player(player), score)| (*player, score.0))
+        .collect();
+    scores.sort_unstable();
+    let value = match *mode {
+        Mode::OnePlayer | Mode::TimeAttack => {
+            format!("Score: {}", scores.first().map_or(0, |(_, score)| *score))
+        }
+        Mode::TwoPlayers | Mode::Versus | Mode::Online => scores
+            .iter()
+            .map(|(player, score)| format!("{}: {}", mode.player_name(Player(*player)), score))
+            .collect::<Vec<_>>()
+            .join("  "),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// Turns the head, body and tail sprites of each snake to follow where its segments are.
+fn segment_orientation(
+    snakes: Query<&SnakeSegments>,
+    positions: Query<&Position, With<SnakeSegment>>,
+    mut shapes: Query<&mut SegmentShape>,
+) {
+    for segments in snakes.iter() {
+        // Segments spawned this frame are not there yet.
+        let cells: Option<Vec<Position>> = segments
+            .0
+            .iter()
+            .map(|segment| positions.get(*segment).ok().copied())
+            .collect();
+        let cells = match cells {
+            Some(cells) => cells,
+            None => continue,
+        };
+        for (segment, shape) in segments.0.iter().zip(SegmentShape::of(&cells)) {
+            if let Ok(mut current) = shapes.get_mut(*segment) {
+                if *current != shape {
+                    *current = shape;
+                }
+            }
+        }
+    }
+}
+
+/// Gives new segments a sprite from the sheet, shown once it is in place.
+fn segment_sprite_spawn(
+    mut commands: Commands,
+    atlas: Res<SnakeAtlas>,
+    segments: Query<Entity, Added<SnakeSegment>>,
+) {
+    for segment in segments.iter() {
+        commands.entity(segment).insert_bundle(SpriteSheetBundle {
+            texture_atlas: atlas.0.clone(),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        });
+    }
+}
+
+/// Shows each segment's shape in its player's colors, the head tinted by the power-up picked up
+/// last and a crashed snake fading out, as large as a cell.
+fn segment_sprite_update(
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    colors: Res<Colors>,
+    effects: Res<ActiveEffects>,
+    dying: Res<Dying>,
+    mut segments: Query<(
+        &Player,
+        &SegmentShape,
+        Option<&SnakeHead>,
+        Option<&Fading>,
+        &mut TextureAtlasSprite,
+        &mut Visibility,
+    )>,
+) {
+    let tile_size = windows.get_primary().map_or(Vec2::ZERO, |window| {
+        Vec2::splat(arena.tile_size(Vec2::new(window.width(), window.height())))
+    });
+    for (Player(player), shape, head, fading, mut sprite, mut visibility) in segments.iter_mut() {
+        let (head_color, color) = colors.snake(*player);
+        sprite.color = match (head, effects.latest(*player)) {
+            (Some(_), Some(power_up)) => tint(head_color, colors.power_up(power_up)),
+            (Some(_), None) => head_color,
+            (None, _) => color,
+        };
+        if let Some(fading) = fading {
+            sprite.color = fading.color(sprite.color, dying.elapsed());
+        }
+        sprite.index = shape.index();
+        sprite.custom_size = Some(tile_size);
+        visibility.is_visible = true;
+    }
+}
+
+fn set_tick_rate(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let seconds: f32 = game_console::arg(args, 0, "seconds")?;
+    if seconds <= 0.0 {
+        return Err("<seconds> must be more than 0".to_string());
+    }
+    let mut timer = world.get_resource_mut::<MoveTimer>().unwrap();
+    timer.0.set_duration(Duration::from_secs_f32(seconds));
+    Ok(format!("The snake steps every {} seconds", seconds))
+}
+
+fn setup_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MainCamera);
+}
+
+/// Shows the high scores on the menu and a lone player's game over screen, where the global
+/// ones take their place once they have been fetched. A time attack has a table of its own.
+fn leaderboard_update(
+    state: Res<State<GameState>>,
+    mode: Res<Mode>,
+    scores: Res<Scores>,
+    mut leaderboards: Query<(&mut Visibility, &mut Leaderboard)>,
+) {
+    let shown = match state.current() {
+        GameState::Menu => true,
+        GameState::GameOver => mode.table().is_some(),
+        _ => false,
+    };
+    let table = mode.table().unwrap_or_default();
+    let global = *state.current() == GameState::GameOver && scores.global(table).is_some();
+    for (mut visibility, mut leaderboard) in leaderboards.iter_mut() {
+        if leaderboard.mode != table {
+            leaderboard.mode = table.to_string();
+        }
+        if visibility.is_visible != shown {
+            visibility.is_visible = shown;
+        }
+        if leaderboard.global != global {
+            leaderboard.global = global;
+        }
+    }
+}
+
+/// Starts, pauses, restarts and leaves games with the keys bound to the snake's actions, which
+/// can be changed on the controls screen.
+fn flow_presses(
+    actions: Res<ActionState<SnakeAction>>,
+    gallery: Res<AchievementsScreen>,
+    lobby: Res<Lobby>,
+    mut presses: EventWriter<Press>,
+) {
+    // The gallery and the lobby have the keys to themselves, including the Escape closing them
+    // and the Return starting a game over the network.
+    if gallery.open || gallery.is_changed() || lobby.open || lobby.is_changed() {
+        return;
+    }
+    let bound = [
+        (SnakeAction::Confirm, Press::Confirm),
+        (SnakeAction::Pause, Press::Pause),
+        (SnakeAction::Quit, Press::Quit),
+        (SnakeAction::Restart, Press::Restart),
+    ];
+    if let Some((_, press)) = bound
+        .into_iter()
+        .find(|(action, _)| actions.just_pressed(*action))
+    {
+        presses.send(press);
+    }
+}
+
+/// Picks one or two players on the menu, with 1 and 2 or left and right, and the obstacles with
+/// up and down; C opens the controls screen and V the achievements, each of which has the keys
+/// to itself while it is open, as the lobby does.
+fn menu_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    free_play: Res<FreePlay>,
+    levels: Res<Levels>,
+    mut bot: ResMut<Bot>,
+    mut colors: ResMut<Colors>,
+    mut controls: ResMut<ControlsScreen>,
+    mut gallery: ResMut<AchievementsScreen>,
+    mut layout: ResMut<Layout>,
+    mut mode: ResMut<Mode>,
+    lobby: Res<Lobby>,
+) {
+    if controls.open || gallery.open || lobby.open {
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::C) {
+        controls.open();
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::V) {
+        gallery.open = true;
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::B) {
+        bot.0 = !bot.0;
+    }
+    if keyboard_input.just_pressed(KeyCode::T) {
+        colors.scheme = colors.scheme.next();
+        colors.scheme.save();
+    }
+    if menu.vertical() != 0 {
+        let mut skipped = Vec::new();
+        if free_play.obstacles.is_empty() {
+            skipped.push(Layout::Custom);
+        }
+        if levels.all.is_empty() {
+            skipped.push(Layout::Levels);
+        }
+        *layout = layout.step(menu.vertical(), &skipped);
+    }
+    // Hosting or joining a game fixes the players.
+    if *mode == Mode::Online {
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Key1, KeyCode::Numpad1]) {
+        *mode = Mode::OnePlayer;
+    } else if keyboard_input.any_just_pressed([KeyCode::Key2, KeyCode::Numpad2]) {
+        *mode = Mode::TwoPlayers;
+    } else if keyboard_input.any_just_pressed([KeyCode::Key3, KeyCode::Numpad3]) {
+        *mode = Mode::Versus;
+    } else if keyboard_input.any_just_pressed([KeyCode::Key4, KeyCode::Numpad4]) {
+        *mode = Mode::TimeAttack;
+    } else if menu.horizontal() != 0 {
+        let index = Mode::ALL
+            .iter()
+            .position(|other| other == &*mode)
+            .unwrap_or(0);
+        *mode = Mode::ALL[game_hud::menu::step(index, Mode::ALL.len(), menu.horizontal())];
+    }
+}
+
+/// Shows the players, obstacles and colors picked on the menu, and who is connected when the
+/// game is played over the network.
+fn menu_text_update(
+    state: Res<State<GameState>>,
+    bot: Res<Bot>,
+    colors: Res<Colors>,
+    layout: Res<Layout>,
+    mode: Res<Mode>,
+    hosting: Option<Res<Hosting>>,
+    joined: Option<Res<Joined>>,
+    mut texts: Query<(&mut Text, &mut Visibility), With<MenuText>>,
+) {
+    let shown = *state.current() == GameState::Menu;
+    let mut value = format!(
+        "Players: {}\nObstacles: {}\nBot: {}\nColors: {}",
+        mode.name(),
+        layout.name(),
+        if bot.0 { "On" } else { "Off" },
+        colors.scheme.name()
+    );
+    if let Some(status) = net::status(hosting.as_deref(), joined.as_deref()) {
+        value = format!("{}\n{}", value, status);
+    }
+    for (mut text, mut visibility) in texts.iter_mut() {
+        if visibility.is_visible != shown {
+            visibility.is_visible = shown;
+        }
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+/// Puts up a level's obstacles and a new snake at its start for each player, carrying over
+/// the `scores` from the level before. Obstacles on a snake's start are left out.
+fn level_spawn(commands: &mut Commands, level: &Level, mode: Mode, scores: &[Score]) {
+    let starts: Vec<Position> = (0..mode.players())
+        .flat_map(|player| level.start(player))
+        .collect();
+    for cell in level.obstacles() {
+        if !starts.contains(&cell) {
+            commands.spawn().insert(Obstacle).insert(cell);
+        }
+    }
+    snake_spawner(commands, level, mode, scores);
+}
+
+/// Moves on to the next level once a snake scores the target of the current one, or has the
+/// game end when it was the last.
+fn level_progress(
+    mut commands: Commands,
+    mode: Res<Mode>,
+    mut arena: ResMut<ArenaConfig>,
+    mut level: ResMut<Level>,
+    mut levels: ResMut<Levels>,
+    mut timer: ResMut<MoveTimer>,
+    mut announcements: ResMut<Announcements>,
+    mut cleared_writer: EventWriter<ClearedEvent>,
+    snakes: Query<(&Player, &Score)>,
+    leftovers: Query<
+        Entity,
+        Or<(
+            With<Food>,
+            With<Obstacle>,
+            With<PowerUp>,
+            With<SnakeSegment>,
+        )>,
+    >,
+) {
+    let target = match level.target {
+        Some(target) => target,
+        None => return,
+    };
+    let mut scores = vec![Score::default(); mode.players()];
+    for (Player(player), score) in snakes.iter() {
+        scores[*player] = *score;
+    }
+    let reached = (0..scores.len()).find(|player| {
+        let start = levels.start_scores.get(*player).copied().unwrap_or(0);
+        scores[*player].0.saturating_sub(start) >= target
+    });
+    let player = match reached {
+        Some(player) => player,
+        None => return,
+    };
+    if levels.current + 1 >= levels.all.len() {
+        cleared_writer.send(ClearedEvent(Player(player)));
+        // Nothing left to reach until the game has ended.
+        level.target = None;
+        return;
+    }
+    levels.current += 1;
+    levels.start_scores = scores.iter().map(|score| score.0).collect();
+    *level = levels.all[levels.current].clone();
+    *arena = level.arena();
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn();
+    }
+    timer.0.reset();
+    level_spawn(&mut commands, &level, *mode, &scores);
+    announcements.announce(format!("Level {}: {}", levels.current + 1, level.name));
+}
+
+/// Clears the arena and sets up the layout picked, or the first level, with no power-ups
+/// working. A player who joined a host leaves that to the host.
+fn new_game(
+    mut commands: Commands,
+    mut new_games: EventReader<NewGame>,
+    joined: Option<Res<Joined>>,
+    free_play: Res<FreePlay>,
+    layout: Res<Layout>,
+    playback: Res<Playback>,
+    mut rng: ResMut<Rng>,
+    mut recording: ResMut<Recording>,
+    mut mode: ResMut<Mode>,
+    mut arena: ResMut<ArenaConfig>,
+    mut level: ResMut<Level>,
+    mut levels: ResMut<Levels>,
+    mut timer: ResMut<MoveTimer>,
+    mut effects: ResMut<ActiveEffects>,
+    mut countdown: ResMut<PowerUpCountdown>,
+    leftovers: Query<
+        Entity,
+        Or<(
+            With<Food>,
+            With<Obstacle>,
+            With<PowerUp>,
+            With<SnakeSegment>,
+        )>,
+    >,
+) {
+    if new_games.iter().next().is_none() || joined.is_some() {
+        return;
+    }
+    for entity in leftovers.iter() {
+        commands.entity(entity).despawn();
+    }
+    timer.0.reset();
+    effects.clear();
+    countdown.0 = POWER_UP_INTERVAL;
+    *level = match (*layout, levels.all.first()) {
+        (Layout::Levels, Some(first)) => first.clone(),
+        _ => Level::free(&free_play.arena, *layout, &free_play.obstacles),
+    };
+    if let Some(replay) = &playback.0 {
+        *rng = replay.rng.clone();
+        *mode = match (replay.players, replay.versus) {
+            (2, true) => Mode::Versus,
+            (2, false) => Mode::TwoPlayers,
+            _ => Mode::OnePlayer,
+        };
+        *level = replay.level.clone();
+    }
+    let mut replay = Replay::new(rng.clone(), mode.players(), level.clone());
+    replay.versus = *mode == Mode::Versus;
+    // The clock runs on the time of the frames, which a replay does not keep.
+    recording.replay = Some(replay).filter(|_| *mode != Mode::TimeAttack);
+    recording.step = 0;
+    levels.current = 0;
+    levels.start_scores.clear();
+    *arena = level.arena();
+    level_spawn(&mut commands, &level, *mode, &[]);
+}
+
+/// Starts the power-ups the snakes' heads are on, after counting those working down a step.
+fn power_up_pickup(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    colors: Res<Colors>,
+    mut effects: ResMut<ActiveEffects>,
+    mut emits: EventWriter<Emit>,
+    mut sounds: EventWriter<Sound>,
+    power_ups: Query<(Entity, &PowerUp, &Position)>,
+    heads: Query<(&Player, &Position), With<SnakeHead>>,
+) {
+    if !effects.is_empty() {
+        effects.step();
+    }
+    for (Player(player), head_position) in heads.iter() {
+        for (entity, power_up, position) in power_ups.iter() {
+            if position != head_position {
+                continue;
+            }
+            commands.entity(entity).despawn();
+            effects.add(*player, *power_up);
+            sounds.send(Sound::PowerUp);
+            if let Some(position) = world_position(&windows, &arena, *position) {
+                emits.send(Emit {
+                    color: Some(colors.power_up(*power_up)),
+                    position,
+                    preset: "eat",
+                });
+            }
+        }
+    }
+}
+
+/// Drops a random power-up on a free cell now and then, while there is none in the arena. It
+/// waits for a step the food stays as it is, to keep the two off the same cell.
+fn power_up_spawner(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    mut rng: ResMut<Rng>,
+    mut countdown: ResMut<PowerUpCountdown>,
+    food: Query<(&FoodKind, &Position, Option<&Lifetime>), With<Food>>,
+    heads: Query<&Position, With<SnakeHead>>,
+    obstacles: Query<&Position, With<Obstacle>>,
+    power_ups: Query<(), With<PowerUp>>,
+    segments: Query<&Position, With<SnakeSegment>>,
+) {
+    if !power_ups.is_empty() {
+        return;
+    }
+    if countdown.0 > 0 {
+        countdown.0 -= 1;
+        return;
+    }
+    if !growing_food_left(&food, &heads) {
+        return;
+    }
+    let taken: Vec<Position> = segments
+        .iter()
+        .chain(obstacles.iter())
+        .chain(food.iter().map(|(_, position, _)| position))
+        .copied()
+        .collect();
+    let power_up = PowerUp::random(&mut rng.gameplay);
+    let position = match get_available_position(&arena, &mut rng, &taken) {
+        Some(position) => position,
+        None => return,
+    };
+    commands
+        .spawn()
+        .insert(power_up)
+        .insert(position)
+        .insert(Lifetime(POWER_UP_LIFETIME));
+    countdown.0 = POWER_UP_INTERVAL + rng.gameplay.below(POWER_UP_INTERVAL);
+}
+
+/// Glides the snakes' sprites from the cells they left toward the ones they moved to, as far as
+/// the next step is due, so they move smoothly at any frame rate.
+fn position_translation(
+    windows: Res<Windows>,
+    state: Res<State<GameState>>,
+    arena: Res<ArenaConfig>,
+    timer: Res<MoveTimer>,
+    mut segments: Query<(&Position, &mut Glide, &mut Transform)>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => Vec2::new(window.width(), window.height()),
+        None => return,
+    };
+    // Once the game is over the steps stop, and the snakes rest where they are.
+    let progress = match state.current() {
+        GameState::Playing | GameState::Paused => timer.0.percent(),
+        _ => 1.0,
+    };
+    for (position, mut glide, mut transform) in segments.iter_mut() {
+        if glide.to != *position {
+            // A teleported snake jumps rather than gliding across the arena.
+            let apart = (position.x - glide.to.x).abs() + (position.y - glide.to.y).abs();
+            glide.from = if apart == 1 { glide.to } else { *position };
+            glide.to = *position;
+        }
+        let from = arena.world_position(glide.from, window);
+        let to = arena.world_position(glide.to, window);
+        transform.translation = from.lerp(to, progress).extend(1.0);
+    }
+}
+
+/// The high scores below the overlay's text, small enough to leave it room.
+fn setup_leaderboard(mut commands: Commands, font: Res<HudFont>) {
+    let (mut text, leaderboard) = Leaderboard::new("").bundle(
+        &font,
+        Rect {
+            bottom: Val::Px(8.0),
+            left: Val::Px(20.0),
+            ..Default::default()
+        },
+    );
+    text.text.sections[0].style.font_size = LEADERBOARD_FONT_SIZE;
+    commands.spawn_bundle(text).insert(leaderboard);
+}
+
+fn setup_menu_text(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            24.0,
+            SCORE_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                right: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(MenuText)
+        .insert(Themed::new(Role::Text, SCORE_COLOR));
+}
+
+fn setup_score_text(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "Score: 0",
+            24.0,
+            SCORE_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(ScoreText)
+        .insert(Themed::new(Role::Text, SCORE_COLOR));
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            24.0,
+            SCORE_COLOR,
+            Rect {
+                top: Val::Px(36.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(ClockText)
+        .insert(Themed::new(Role::Text, SCORE_COLOR));
+}
+
+fn setup_tilemap(
+    mut commands: Commands,
+    arena: Res<ArenaConfig>,
+    mut images: ResMut<Assets<Image>>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let atlas = atlases.add(sprites::atlas(images.add(sprites::sheet())));
+    commands.insert_resource(SnakeAtlas(atlas.clone()));
+    commands
+        .spawn()
+        .insert(
+            Tilemap::new(
+                Grid::new(arena.width, arena.height, Cell::Empty),
+                Vec2::ZERO,
+                cell_tile,
+            )
+            .with_atlas(atlas),
+        )
+        .insert(Transform::default())
+        .insert(GlobalTransform::default());
+}
+
+/// Shortens the steps as the longest snake grows and during a speed boost, and starts new snakes
+/// at the slowest.
+fn speed_update(
+    difficulty: Res<Difficulty>,
+    effects: Res<ActiveEffects>,
+    changed: Query<(), Changed<SnakeSegments>>,
+    snakes: Query<&SnakeSegments>,
+    mut timer: ResMut<MoveTimer>,
+) {
+    if changed.is_empty() && !effects.is_changed() {
+        return;
+    }
+    let length = snakes.iter().map(|segments| segments.0.len()).max();
+    let mut step_time = difficulty.step_time(length.unwrap_or_default());
+    if effects.any(PowerUp::Speed) {
+        step_time *= SPEED_BOOST;
+    }
+    timer.0.set_duration(Duration::from_secs_f32(step_time));
+}
+
+/// Reads the lifetime stats through the platform, which the `PlatformPlugin` may add after the
+/// snake's plugin.
+fn stats_load(platform: Res<Platform>, mut lifetime: ResMut<LifetimeStats>) {
+    *lifetime = LifetimeStats::load(&platform);
+}
+
+fn spawn_food(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let position = arena_cell(world, args)?;
+    let kind = match args.get(2) {
+        Some(name) => FoodKind::parse(name).ok_or(format!("there is no {} food", name))?,
+        None => FoodKind::Normal,
+    };
+    let mut food = world.spawn();
+    food.insert(Food).insert(kind).insert(position);
+    if let Some(steps) = kind.lifetime() {
+        food.insert(Lifetime(steps));
+    }
+    Ok(format!("{:?} food at {}, {}", kind, position.x, position.y))
+}
+
+fn spawn_power_up(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let position = arena_cell(world, args)?;
+    let name = game_console::arg::<String>(args, 2, "power-up")?;
+    let power_up = PowerUp::parse(&name).ok_or(format!("there is no {} power-up", name))?;
+    world
+        .spawn()
+        .insert(power_up)
+        .insert(position)
+        .insert(Lifetime(POWER_UP_LIFETIME));
+    Ok(format!("{:?} at {}, {}", power_up, position.x, position.y))
+}
+
+/// Eats the food under each head, which bursts into particles of its color.
+fn snake_eating(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    colors: Res<Colors>,
+    mut growth_writer: EventWriter<GrowthEvent>,
+    mut bonus_writer: EventWriter<BonusEvent>,
+    mut penalty_writer: EventWriter<PenaltyEvent>,
+    mut shrink_writer: EventWriter<ShrinkEvent>,
+    mut emits: EventWriter<Emit>,
+    mut stats: ResMut<RunStats>,
+    food_positions: Query<(Entity, &FoodKind, &Position), With<Food>>,
+    head_positions: Query<(Entity, &Player, &Position), With<SnakeHead>>,
+) {
+    for (head, player, head_position) in head_positions.iter() {
+        for (entity, kind, food_position) in food_positions.iter() {
+            if food_position == head_position {
+                commands.entity(entity).despawn();
+                if *player == Player(0) {
+                    stats.food += 1;
+                    stats.golden += u32::from(*kind == FoodKind::Golden);
+                }
+                match kind {
+                    FoodKind::Normal => growth_writer.send(GrowthEvent(head)),
+                    FoodKind::Golden | FoodKind::Timed => {
+                        growth_writer.send(GrowthEvent(head));
+                        bonus_writer.send(BonusEvent {
+                            head,
+                            points: kind.bonus(),
+                        });
+                    }
+                    FoodKind::Poison => shrink_writer.send(ShrinkEvent {
+                        head,
+                        segments: kind.shrink(),
+                    }),
+                    FoodKind::Rotten => penalty_writer.send(PenaltyEvent {
+                        head,
+                        points: kind.penalty(),
+                    }),
+                }
+                if let Some(position) = world_position(&windows, &arena, *food_position) {
+                    emits.send(Emit {
+                        color: Some(colors.food(*kind)),
+                        position,
+                        preset: "eat",
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn snake_growth(
+    mut commands: Commands,
+    mut growth_reader: EventReader<GrowthEvent>,
+    mut heads: Query<(&Player, &LastTailPosition, &mut SnakeSegments)>,
+) {
+    for GrowthEvent(head) in growth_reader.iter() {
+        if let Ok((player, last_tail_position, mut segments)) = heads.get_mut(*head) {
+            if let Some(position) = last_tail_position.0 {
+                segments
+                    .0
+                    .push(snake_segment_spawn(&mut commands, *player, position));
+            }
+        }
+    }
+}
+
+/// Takes segments off the tail of a snake that ate poison; the tail it grows into next is where
+/// the last segment left was. Poison that would leave no more than the head ends the game.
+fn snake_shrink(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    mut shrink_reader: EventReader<ShrinkEvent>,
+    mut heads: Query<(&Player, &mut SnakeSegments, &mut LastTailPosition)>,
+    positions: Query<&Position>,
+    mut game_over_writer: EventWriter<GameOverEvent>,
+    mut emits: EventWriter<Emit>,
+) {
+    for ShrinkEvent {
+        head,
+        segments: count,
+    } in shrink_reader.iter()
+    {
+        let (player, mut segments, mut last_tail_position) = match heads.get_mut(*head) {
+            Ok(snake) => snake,
+            Err(_) => continue,
+        };
+        if segments.0.len() <= *count {
+            game_over_writer.send(GameOverEvent(*player, Crash::Other));
+            if let Some(position) = positions
+                .get(*head)
+                .ok()
+                .and_then(|head| world_position(&windows, &arena, *head))
+            {
+                emits.send(Emit {
+                    color: None,
+                    position,
+                    preset: "death",
+                });
+            }
+            continue;
+        }
+        let removed = segments.0.split_off(segments.0.len() - count);
+        last_tail_position.0 = positions.get(removed[0]).ok().copied();
+        for segment in removed {
+            commands.entity(segment).despawn();
+        }
+    }
+}
+
+/// Steps every snake that has started moving, counting the first player's moves. A head crashes
+/// into the walls, any snake's segments, or another head stepping into the same cell.
+fn snake_movement(
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    mut heads: Query<(&SnakeHead, &Player, &SnakeSegments, &mut LastTailPosition)>,
+    mut positions: Query<&mut Position, Without<Obstacle>>,
+    obstacles: Query<&Position, With<Obstacle>>,
+    effects: Res<ActiveEffects>,
+    mut stats: ResMut<RunStats>,
+    mut game_over_writer: EventWriter<GameOverEvent>,
+    mut emits: EventWriter<Emit>,
+) {
+    let bodies: Vec<(Player, Position)> = heads
+        .iter()
+        .flat_map(|(_, player, segments, _)| {
+            segments.0.iter().map(move |segment| (*player, segment))
+        })
+        .map(|(player, segment)| (player, *positions.get(*segment).unwrap()))
+        .collect();
+    let targets: Vec<Option<Position>> = heads
+        .iter()
+        .map(|(head, _, segments, _)| match head.direction {
+            Direction::None => None,
+            direction => Some(positions.get(segments.0[0]).unwrap().step(direction)),
+        })
+        .collect();
+    for (index, (_, player, segments, mut last_tail_position)) in heads.iter_mut().enumerate() {
+        let target = match targets[index] {
+            Some(target) => target,
+            None => continue,
+        };
+        let head_on = targets
+            .iter()
+            .enumerate()
+            .any(|(other, other_target)| other != index && *other_target == Some(target));
+        // A ghost passes through its own body, but nothing else.
+        let ghost = effects.has(player.0, PowerUp::Ghost);
+        let hits = |own: bool| {
+            bodies
+                .iter()
+                .any(|(owner, position)| *position == target && (owner == player) == own)
+        };
+        let crash = if !arena.contains(target) {
+            Some(Crash::Wall)
+        } else if !ghost && hits(true) {
+            Some(Crash::Itself)
+        } else if head_on || hits(false) || obstacles.iter().any(|obstacle| *obstacle == target) {
+            Some(Crash::Other)
+        } else {
+            None
+        };
+        if *player == Player(0) {
+            stats.moves += 1;
+            stats.longest = stats.longest.max(segments.0.len());
+        }
+        if let Some(crash) = crash {
+            game_over_writer.send(GameOverEvent(*player, crash));
+            if let Some(position) = world_position(&windows, &arena, target) {
+                emits.send(Emit {
+                    color: None,
+                    position,
+                    preset: "death",
+                });
+            }
+        }
+        let segment_positions = segments
+            .0
+            .iter()
+            .map(|segment| *positions.get(*segment).unwrap())
+            .collect::<Vec<Position>>();
+        *positions.get_mut(segments.0[0]).unwrap() = target;
+        segment_positions
+            .iter()
+            .zip(segments.0.iter().skip(1))
+            .for_each(|(position, segment)| {
+                *positions.get_mut(*segment).unwrap() = *position;
+            });
+        last_tail_position.0 = segment_positions.last().copied();
+    }
+}
+
+/// Turns each snake toward the actions its player holds, from the keyboard or a gamepad.
+fn snake_movement_input(
+    actions: Res<ActionState<SnakeAction>>,
+    mode: Res<Mode>,
+    playback: Res<Playback>,
+    mut heads: Query<(&mut SnakeHead, &Player)>,
+) {
+    if playback.0.is_some() {
+        return;
+    }
+    for (mut head, player) in heads.iter_mut() {
+        let direction = held_direction(&actions, mode.actions(*player)).unwrap_or(head.direction);
+        head.direction = head.direction.turn(direction);
+    }
+}
+
+/// Ends a race against the computer once a snake has the points to win it.
+fn race_progress(
+    mode: Res<Mode>,
+    snakes: Query<(&Player, &Score)>,
+    mut cleared_writer: EventWriter<ClearedEvent>,
+) {
+    if *mode != Mode::Versus {
+        return;
+    }
+    if let Some((player, _)) = snakes.iter().find(|(_, score)| score.0 >= RACE_TARGET) {
+        cleared_writer.send(ClearedEvent(*player));
+    }
+}
+
+/// Records where the snakes head on each step, or turns them as they turned in the replay played
+/// back.
+fn replay_turns(
+    playback: Res<Playback>,
+    mut recording: ResMut<Recording>,
+    mut heads: Query<(&mut SnakeHead, &Player)>,
+) {
+    let step = recording.step;
+    recording.step += 1;
+    if let Some(replay) = &playback.0 {
+        for (player, direction) in replay.turns(step) {
+            for (mut head, _) in heads
+                .iter_mut()
+                .filter(|(_, Player(index))| *index == player)
+            {
+                head.direction = direction;
+            }
+        }
+        return;
+    }
+    let replay = match &mut recording.replay {
+        Some(replay) => replay,
+        None => return,
+    };
+    for (head, Player(player)) in heads.iter_mut() {
+        // A snake put at the start of a new level stands still until it turns again.
+        let heading = if head.is_added() {
+            Direction::None
+        } else {
+            replay.heading(*player)
+        };
+        if head.direction != heading {
+            replay.record(step, *player, head.direction);
+        }
+    }
+}
+
+/// Puts a snake at the level's start for each player, with the score in `scores` if there is
+/// one.
+fn snake_spawner(commands: &mut Commands, level: &Level, mode: Mode, scores: &[Score]) {
+    for index in 0..mode.players() {
+        snake_spawn(
+            commands,
+            level,
+            Player(index),
+            scores.get(index).copied().unwrap_or_default(),
+        );
+    }
+}
+
+/// A player's snake at its start on the level, standing still.
+fn snake_spawn(commands: &mut Commands, level: &Level, player: Player, score: Score) {
+    let [head, tail] = level.start(player.0);
+    let head_entity = snake_segment_spawn(commands, player, head);
+    let segments = vec![head_entity, snake_segment_spawn(commands, player, tail)];
+    commands
+        .entity(head_entity)
+        .insert(SnakeHead {
+            direction: Direction::None,
+        })
+        .insert(SnakeSegments(segments))
+        .insert(LastTailPosition::default())
+        .insert(score);
+}
+
+fn snake_segment_spawn(commands: &mut Commands, player: Player, position: Position) -> Entity {
+    commands
+        .spawn()
+        .insert(SnakeSegment)
+        .insert(SegmentShape::Head(Direction::Up))
+        .insert(Glide {
+            from: position,
+            to: position,
+        })
+        .insert(player)
+        .insert(position)
+        .id()
+}
+
+/// The sprite at `index` in the sheet, tinted `color`.
+fn sprite(color: Color, index: usize, scale: f32) -> Tile {
+    Tile {
+        index,
+        ..Tile::colored(color, scale)
+    }
+}
+
+/// The systems of one step of the snakes, run when it is due, or at once by a `Simulation`.
+fn step_systems() -> SystemSet {
+    SystemSet::new()
+        .with_system(
+            replay_turns
+                .label(SnakeMovement::Replay)
+                .after(SnakeMovement::Input)
+                .before(SnakeMovement::Movement),
+        )
+        .with_system(race_progress.before(SnakeMovement::Movement))
+        .with_system(snake_movement.label(SnakeMovement::Movement))
+        .with_system(
+            snake_eating
+                .label(SnakeMovement::Eating)
+                .after(SnakeMovement::Movement),
+        )
+        .with_system(
+            snake_growth
+                .label(SnakeMovement::Growth)
+                .after(SnakeMovement::Eating),
+        )
+        .with_system(
+            snake_shrink
+                .label(SnakeMovement::Shrinking)
+                .after(SnakeMovement::Growth),
+        )
+        .with_system(
+            lifetime_expiry
+                .label(SnakeMovement::Expiry)
+                .after(SnakeMovement::Eating),
+        )
+        .with_system(food_spawner.after(SnakeMovement::Expiry))
+        .with_system(
+            power_up_pickup
+                .label(SnakeMovement::PowerUps)
+                .after(SnakeMovement::Movement),
+        )
+        .with_system(power_up_spawner.after(SnakeMovement::Expiry))
+}
+
+/// Runs the snake's steps once their time is up, while the game is played and no snake is
+/// dying. A player who joined a host only shows the steps it sends.
+fn step_elapsed(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    dying: Res<Dying>,
+    joined: Option<Res<Joined>>,
+    mut timer: ResMut<MoveTimer>,
+) -> ShouldRun {
+    if *state.current() == GameState::Playing
+        && dying.0.is_none()
+        && timer.0.tick(time.delta()).just_finished()
+        && joined.is_none()
+    {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+fn teleport(world: &mut World, args: &[&str]) -> Result<String, String> {
+    let position = arena_cell(world, args)?;
+    let player: usize = match args.get(2) {
+        Some(_) => game_console::arg(args, 2, "player")?,
+        None => 1,
+    };
+    let mut heads = world.query_filtered::<(&mut Position, &Player), With<SnakeHead>>();
+    let mut head = heads
+        .iter_mut(world)
+        .find(|(_, Player(index))| index + 1 == player)
+        .map(|(head, _)| head)
+        .ok_or(format!("there is no player {}", player))?;
+    *head = position;
+    Ok(format!(
+        "Player {}'s snake is at {}, {}",
+        player, position.x, position.y
+    ))
+}
+
+/// Shows the food, power-ups and obstacles in the arena, as large as it fits in the window.
+fn tilemap_update(
+    windows: Res<Windows>,
+    arena: Res<ArenaConfig>,
+    colors: Res<Colors>,
+    food: Query<(&Position, &FoodKind), With<Food>>,
+    obstacles: Query<&Position, With<Obstacle>>,
+    power_ups: Query<(&Position, &PowerUp)>,
+    mut tilemaps: Query<&mut Tilemap<Cell>>,
+) {
+    let mut grid = Grid::new(arena.width, arena.height, Cell::Empty);
+    for position in obstacles.iter() {
+        grid.set(*position, Cell::Obstacle);
+    }
+    for (position, power_up) in power_ups.iter() {
+        grid.set(*position, Cell::PowerUp(colors.power_up(*power_up)));
+    }
+    for (position, kind) in food.iter() {
+        grid.set(*position, Cell::Food(colors.food(*kind)));
+    }
+    let tile_size = windows.get_primary().map_or(Vec2::ZERO, |window| {
+        Vec2::splat(arena.tile_size(Vec2::new(window.width(), window.height())))
+    });
+    for mut tilemap in tilemaps.iter_mut() {
+        if tilemap.grid != grid {
+            tilemap.grid = grid.clone();
+        }
+        if tilemap.tile_size != tile_size {
+            tilemap.tile_size = tile_size;
+        }
+    }
+}
+
+/// Halfway between `color` and `tint`.
+fn tint(color: Color, tint: Color) -> Color {
+    Color::rgb(
+        (color.r() + tint.r()) / 2.0,
+        (color.g() + tint.g()) / 2.0,
+        (color.b() + tint.b()) / 2.0,
+    )
+}
+
+/// The middle of a cell in the world, where effects start.
+fn world_position(windows: &Windows, arena: &ArenaConfig, position: Position) -> Option<Vec2> {
+    let window = windows.get_primary()?;
+    Some(arena.world_position(position, Vec2::new(window.width(), window.height())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::{
+        app::Events,
+        window::{WindowFocused, WindowId},
+    };
+    use game_flow::Flow;
+    use game_rng::Seed;
+    use game_test::{assert_golden, TestApp};
+
+    /// The game on its menu, with saves of its own; the window descriptor reads the settings.
+    fn test_app() -> TestApp {
+        game_test::isolate_saves();
+        TestApp::new(window_descriptor(), SnakePlugin)
+    }
+
+    /// A game started from the menu.
+    fn snake() -> TestApp {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Return);
+        app.step();
+        app
+    }
+
+    #[test]
+    fn a_full_arena_has_no_cell_to_spawn_on() {
+        let arena = ArenaConfig {
+            height: 2,
+            width: 3,
+        };
+        let mut rng = Rng::new(Seed(5));
+        let mut taken = Vec::new();
+        for _ in 0..6 {
+            let position = get_available_position(&arena, &mut rng, &taken).unwrap();
+            assert!(!taken.contains(&position));
+            taken.push(position);
+        }
+        assert_eq!(get_available_position(&arena, &mut rng, &taken), None);
+    }
+
+    #[test]
+    fn moves_where_the_player_holds() {
+        let mut app = snake();
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 4, y: 3 }]
+        );
+        assert_eq!(app.count::<Food>(), 1);
+        assert_golden("moved_right", &app.snapshot::<Position>());
+    }
+
+    #[test]
+    fn sprites_glide_between_cells() {
+        let mut app = snake();
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        app.advance(0.06);
+        // A 10 by 10 arena in a 500 pixel window, with the head moving from 3, 3 to 4, 3.
+        let head = app.components_with::<Transform, SnakeHead>()[0].translation;
+        assert!(head.x > -75.0 && head.x < -25.0, "{}", head.x);
+        assert_eq!(head.y, -75.0);
+    }
+
+    #[test]
+    fn pauses_on_space_and_when_the_window_loses_focus() {
+        let mut app = snake();
+        app.tap(KeyCode::Space);
+        app.step();
+        app.assert_state(GameState::Paused);
+        app.tap(KeyCode::Space);
+        app.step();
+        app.assert_state(GameState::Playing);
+        app.resource_mut::<Events<WindowFocused>>()
+            .send(WindowFocused {
+                id: WindowId::primary(),
+                focused: false,
+            });
+        app.step();
+        app.assert_state(GameState::Paused);
+    }
+
+    /// Runs frames until the snakes that crashed have faded out and the game over screen is up.
+    fn death_plays_out(app: &mut TestApp) {
+        app.advance(death::DEATH_SECONDS);
+        app.steps(2);
+    }
+
+    /// Runs frames until the game has taken `steps` steps.
+    fn steps_until(app: &mut TestApp, steps: u32) {
+        while app.resource::<Recording>().step < steps {
+            app.advance(0.16);
+        }
+    }
+
+    #[test]
+    fn replays_play_out_the_same() {
+        let mut app = snake();
+        app.press(KeyCode::Right);
+        steps_until(&mut app, 2);
+        app.release(KeyCode::Right);
+        app.press(KeyCode::Up);
+        steps_until(&mut app, 4);
+        let replay = app.resource::<Recording>().replay.clone().unwrap();
+        assert_eq!(replay.turns.len(), 2);
+
+        let mut replayed = test_app();
+        replayed.resource_mut::<Playback>().0 = Some(replay);
+        replayed.step();
+        replayed.tap(KeyCode::Return);
+        replayed.step();
+        steps_until(&mut replayed, 4);
+        assert_eq!(replayed.snapshot::<Position>(), app.snapshot::<Position>());
+    }
+
+    #[test]
+    fn the_bot_steers_to_food() {
+        let mut app = snake();
+        app.resource_mut::<Bot>().0 = true;
+        game_console::run(&mut app.app.world, "spawn_food 5 3");
+        steps_until(&mut app, 3);
+        app.step();
+        assert!(app.count::<SnakeSegment>() > 2);
+    }
+
+    #[test]
+    fn races_against_the_computer_end_at_the_target() {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Key3);
+        app.step();
+        app.tap(KeyCode::Return);
+        app.step();
+        assert_eq!(app.count::<SnakeHead>(), 2);
+        let mut snakes = app.app.world.query::<(&Player, &mut Score)>();
+        for (player, mut score) in snakes.iter_mut(&mut app.app.world) {
+            if *player == Player(0) {
+                score.0 = RACE_TARGET;
+            }
+        }
+        steps_until(&mut app, 1);
+        app.step();
+        app.assert_state(GameState::GameOver);
+        assert!(app.resource::<Flow>().message.contains("You win!"));
+    }
+
+    #[test]
+    fn time_attacks_take_time_off_for_crashes_and_end_on_the_clock() {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Key4);
+        app.step();
+        app.tap(KeyCode::Return);
+        app.step();
+        // The head starts at 3, 3 and runs into the wall on the fourth step.
+        app.press(KeyCode::Left);
+        steps_until(&mut app, 4);
+        app.release(KeyCode::Left);
+        app.step();
+        app.assert_state(GameState::Playing);
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 3, y: 3 }]
+        );
+        assert!(app.resource::<Clock>().0.elapsed() >= Duration::from_secs_f32(CRASH_PENALTY));
+        assert_eq!(app.resource::<RunStats>().moves, 4);
+        assert_eq!(app.resource::<RunStats>().wall_crashes, 1);
+        app.resource_mut::<Clock>()
+            .0
+            .set_elapsed(Duration::from_secs_f32(TIME_ATTACK_SECONDS));
+        app.step();
+        app.step();
+        app.assert_state(GameState::GameOver);
+        assert!(app.resource::<Flow>().message.contains("Time's up!"));
+        assert!(app.resource::<Flow>().message.contains("Crashes: 1 walls"));
+    }
+
+    #[test]
+    fn console_commands_change_the_game() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "teleport 2 7");
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 2, y: 7 }]
+        );
+        game_console::run(&mut app.app.world, "spawn_food 5 5");
+        assert!(app
+            .components_with::<Position, Food>()
+            .contains(&Position { x: 5, y: 5 }));
+        game_console::run(&mut app.app.world, "set_tick_rate 0.1");
+        assert_eq!(
+            app.resource::<MoveTimer>().0.duration(),
+            Duration::from_secs_f32(0.1)
+        );
+        // Outside the arena.
+        game_console::run(&mut app.app.world, "teleport 12 0");
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 2, y: 7 }]
+        );
+    }
+
+    #[test]
+    fn crashing_ends_the_game_until_a_restart() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "teleport 9 3");
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        app.steps(2);
+        // The snake fades out before the game is over.
+        app.assert_state(GameState::Playing);
+        assert_eq!(app.count::<Fading>(), 2);
+        death_plays_out(&mut app);
+        app.assert_state(GameState::GameOver);
+        app.release(KeyCode::Right);
+        app.tap(KeyCode::R);
+        app.step();
+        app.assert_state(GameState::Playing);
+        assert_eq!(app.count::<SnakeSegment>(), 2);
+        assert_eq!(app.count::<Fading>(), 0);
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 3, y: 3 }]
+        );
+    }
+
+    #[test]
+    fn two_players_play_until_one_crashes() {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Key2);
+        app.step();
+        app.tap(KeyCode::Return);
+        app.step();
+        assert_eq!(app.count::<SnakeHead>(), 2);
+        game_console::run(&mut app.app.world, "teleport 8 6 2");
+        app.press(KeyCode::D);
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        app.advance(0.16);
+        death_plays_out(&mut app);
+        app.assert_state(GameState::GameOver);
+        assert!(app
+            .resource::<Flow>()
+            .message
+            .starts_with("Player 1 wins!\n"));
+    }
+
+    #[test]
+    fn levels_move_on_at_their_target() {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Up);
+        app.step();
+        assert_eq!(*app.resource::<Layout>(), Layout::Levels);
+        app.tap(KeyCode::Return);
+        app.step();
+        assert_eq!(app.resource::<ArenaConfig>().width, 12);
+        assert_eq!(app.count::<Obstacle>(), 4);
+        game_console::run(&mut app.app.world, "spawn_food 3 4 golden");
+        app.press(KeyCode::W);
+        app.step();
+        app.advance(0.16);
+        app.release(KeyCode::W);
+        app.steps(2);
+        assert_eq!(app.resource::<Level>().name, "Courtyard");
+        assert_eq!(app.resource::<ArenaConfig>().width, 15);
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 2, y: 3 }]
+        );
+        assert_eq!(app.components_with::<Score, SnakeHead>(), [Score(5)]);
+    }
+
+    #[test]
+    fn obstacles_picked_on_the_menu_end_the_game() {
+        let mut app = test_app();
+        app.resource_mut::<Rng>().reseed(Seed(491));
+        app.step();
+        app.tap(KeyCode::Down);
+        app.step();
+        assert_eq!(*app.resource::<Layout>(), Layout::Pillars);
+        app.tap(KeyCode::Return);
+        app.step();
+        assert_eq!(app.count::<Obstacle>(), 4);
+        game_console::run(&mut app.app.world, "teleport 2 3");
+        app.press(KeyCode::S);
+        app.step();
+        app.advance(0.16);
+        death_plays_out(&mut app);
+        app.assert_state(GameState::GameOver);
+    }
+
+    #[test]
+    fn power_ups_work_for_a_while() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "spawn_power_up 3 4 multiplier");
+        game_console::run(&mut app.app.world, "spawn_food 3 5");
+        app.press(KeyCode::W);
+        app.step();
+        app.advance(0.16);
+        app.advance(0.16);
+        app.step();
+        assert_eq!(app.count::<PowerUp>(), 0);
+        assert!(app.resource::<ActiveEffects>().has(0, PowerUp::Multiplier));
+        assert_eq!(app.components_with::<Score, SnakeHead>(), [Score(2)]);
+    }
+
+    #[test]
+    fn scores_for_each_food_eaten() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "spawn_food 4 3");
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        app.step();
+        assert_eq!(app.components_with::<Score, SnakeHead>(), [Score(1)]);
+        assert!(app.resource::<MoveTimer>().0.duration() < Duration::from_secs_f32(0.15));
+        assert_eq!(
+            app.components_with::<Text, ScoreText>()[0].sections[0].value,
+            "Score: 1"
+        );
+    }
+
+    #[test]
+    fn food_kinds_score_differently() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "spawn_food 4 3 golden");
+        game_console::run(&mut app.app.world, "spawn_food 5 3 rotten");
+        app.press(KeyCode::Right);
+        app.step();
+        app.advance(0.16);
+        app.advance(0.16);
+        app.step();
+        assert_eq!(app.components_with::<Score, SnakeHead>(), [Score(2)]);
+        assert_eq!(app.count::<SnakeSegment>(), 3);
+        assert!(app
+            .components_with::<FoodKind, Food>()
+            .iter()
+            .any(|kind| kind.grows()));
+    }
+
+    #[test]
+    fn poison_shrinks_the_snake() {
+        let mut app = snake();
+        game_console::run(&mut app.app.world, "spawn_food 4 3");
+        game_console::run(&mut app.app.world, "spawn_food 5 3");
+        game_console::run(&mut app.app.world, "spawn_food 6 3 poison");
+        game_console::run(&mut app.app.world, "spawn_food 7 3 poison");
+        app.press(KeyCode::Right);
+        app.step();
+        for _ in 0..3 {
+            app.advance(0.16);
+        }
+        app.step();
+        assert_eq!(app.count::<SnakeSegment>(), 2);
+        app.assert_state(GameState::Playing);
+        app.advance(0.16);
+        death_plays_out(&mut app);
+        app.assert_state(GameState::GameOver);
+    }
+
+    #[test]
+    fn shows_the_high_scores_on_the_menu() {
+        let mut app = test_app();
+        app.step();
+        app.assert_state(GameState::Menu);
+        assert!(app.components_with::<Visibility, Leaderboard>()[0].is_visible);
+        app.tap(KeyCode::Return);
+        app.step();
+        assert!(!app.components_with::<Visibility, Leaderboard>()[0].is_visible);
+    }
+
+    #[test]
+    fn starts_with_a_two_segment_snake() {
+        let mut app = snake();
+        assert_eq!(app.count::<SnakeSegment>(), 2);
+        assert_eq!(
+            app.components_with::<Position, SnakeHead>(),
+            [Position { x: 3, y: 3 }]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! The lobby, opened from the menu with N: hosts a game on a port or joins the one at an address
+//! typed in, as `--host` and `--join` do from the command line.
+
+use crate::{
+    net::{self, Hosting, Joined},
+    Mode,
+};
+use bevy::prelude::*;
+use game_achievements::AchievementsScreen;
+use game_flow::GameState;
+use game_hud::{HudFont, MenuInput, Navigate};
+use game_input::ControlsScreen;
+use game_net::websocket;
+
+const BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+const DEFAULT_PORT: &str = "7777";
+const FONT_SIZE: f32 = 20.0;
+/// The line to host on, and the one to join from.
+const HOST: usize = 0;
+const JOIN: usize = 1;
+const OPEN_KEY: KeyCode = KeyCode::N;
+
+/// What the lobby shows, and whether it is open.
+pub(crate) struct Lobby {
+    /// The host's address, as typed.
+    address: String,
+    /// What went wrong when starting last.
+    message: String,
+    pub(crate) open: bool,
+    port: String,
+    selected: usize,
+}
+
+impl Default for Lobby {
+    fn default() -> Self {
+        Self {
+            address: String::new(),
+            message: String::new(),
+            open: false,
+            port: DEFAULT_PORT.to_string(),
+            selected: HOST,
+        }
+    }
+}
+
+impl Lobby {
+    /// The text of the line picked, which typing changes.
+    fn field_mut(&mut self) -> &mut String {
+        match self.selected {
+            HOST => &mut self.port,
+            _ => &mut self.address,
+        }
+    }
+}
+
+#[derive(Component)]
+struct LobbyPanel;
+
+#[derive(Component)]
+struct LobbyText;
+
+pub(crate) fn build(app: &mut App) {
+    app.init_resource::<Lobby>()
+        .add_system_set(SystemSet::on_update(GameState::Menu).with_system(lobby_input))
+        .add_system(lobby_update);
+}
+
+/// The lobby's lines, with the one picked marked and `status` saying who is connected.
+fn lobby_text(lobby: &Lobby, status: Option<String>) -> String {
+    let line = |index: usize, label: &str, value: &str| {
+        if lobby.selected == index {
+            format!("> {}{}_", label, value)
+        } else {
+            format!("  {}{}", label, value)
+        }
+    };
+    [
+        "PLAY OVER THE NETWORK".to_string(),
+        String::new(),
+        line(HOST, "Host on port ", &lobby.port),
+        line(JOIN, "Join the host at ", &lobby.address),
+        String::new(),
+        "Up/Down pick, type to change, Enter start, Esc back".to_string(),
+        format!(
+            "Start the address with {} to join over WebSocket",
+            websocket::SCHEME
+        ),
+        String::new(),
+        status.unwrap_or_default(),
+        lobby.message.clone(),
+    ]
+    .join("\n")
+}
+
+/// Opens the lobby with N on the menu. While it is open, it has the keys to itself: Up and Down
+/// pick a line, typing changes it, Enter hosts or joins and Escape goes back to the menu.
+fn lobby_input(
+    mut commands: Commands,
+    mut lobby: ResMut<Lobby>,
+    mut keyboard: ResMut<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    menu: Res<MenuInput>,
+    mode: Res<Mode>,
+    controls: Res<ControlsScreen>,
+    gallery: Res<AchievementsScreen>,
+) {
+    // Read whether open or not, so the N opening the lobby is not typed into it.
+    let typed: String = characters
+        .iter()
+        .map(|character| character.char)
+        .filter(|character| !character.is_control())
+        .collect();
+    if !lobby.open {
+        if keyboard.just_pressed(OPEN_KEY) && !controls.open && !gallery.open {
+            lobby.open = true;
+            lobby.message.clear();
+        }
+        return;
+    }
+    if menu.just_pressed(Navigate::Back) {
+        keyboard.clear_just_pressed(KeyCode::Escape);
+        lobby.open = false;
+        return;
+    }
+    if menu.vertical() != 0 {
+        lobby.selected = game_hud::menu::step(lobby.selected, 2, menu.vertical());
+    }
+    if keyboard.just_pressed(KeyCode::Back) {
+        lobby.field_mut().pop();
+    }
+    if !typed.is_empty() {
+        lobby.field_mut().push_str(&typed);
+    }
+    if !menu.just_pressed(Navigate::Confirm) {
+        return;
+    }
+    // Another host or connection would need the port or the one there is.
+    if *mode == Mode::Online {
+        lobby.message = "Already playing over the network; restart to play another".to_string();
+        return;
+    }
+    let started = match lobby.selected {
+        HOST => lobby
+            .port
+            .trim()
+            .parse()
+            .map_err(|_| format!("{} is no port to host on", lobby.port))
+            .and_then(Hosting::bind)
+            .map(|hosting| commands.insert_resource(hosting)),
+        _ if lobby.address.trim().is_empty() => Err("Type the host's address first".to_string()),
+        _ => Joined::connect(lobby.address.trim()).map(|joined| commands.insert_resource(joined)),
+    };
+    match started {
+        Ok(()) => {
+            commands.insert_resource(Mode::Online);
+            lobby.open = false;
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            lobby.message = error;
+        }
+    }
+}
+
+/// Shows the lobby while it is open and keeps it up to date.
+fn lobby_update(
+    mut commands: Commands,
+    lobby: Res<Lobby>,
+    font: Res<HudFont>,
+    hosting: Option<Res<Hosting>>,
+    joined: Option<Res<Joined>>,
+    panels: Query<Entity, With<LobbyPanel>>,
+    mut texts: Query<&mut Text, With<LobbyText>>,
+) {
+    if !lobby.open {
+        for entity in panels.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+    let value = lobby_text(&lobby, net::status(hosting.as_deref(), joined.as_deref()));
+    let sections = game_hud::menu::focus_sections(
+        &font.style(FONT_SIZE, Color::WHITE),
+        &value,
+        2 + lobby.selected,
+    );
+    if panels.get_single().is_ok() {
+        for mut text in texts.iter_mut() {
+            let shown: String = text
+                .sections
+                .iter()
+                .map(|section| section.value.as_str())
+                .collect();
+            if shown != value {
+                text.sections = sections.clone();
+            }
+        }
+        return;
+    }
+    commands
+        .spawn_bundle(NodeBundle {
+            color: UiColor(BACKGROUND),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(LobbyPanel)
+        .with_children(|parent| {
+            let mut text = game_hud::screen_text(
+                &font,
+                "",
+                FONT_SIZE,
+                Color::WHITE,
+                Rect {
+                    top: Val::Px(30.0),
+                    left: Val::Px(30.0),
+                    ..Default::default()
+                },
+            );
+            text.text.sections = sections;
+            parent.spawn_bundle(text).insert(LobbyText);
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn marks_the_line_typed_into() {
+        let mut lobby = Lobby::default();
+        lobby.field_mut().push('8');
+        let text = lobby_text(&lobby, None);
+        assert!(text.contains("> Host on port 77778_"), "{}", text);
+        assert!(text.contains("  Join the host at \n"), "{}", text);
+        lobby.selected = JOIN;
+        lobby.field_mut().push_str("ws://10.0.0.2:7778");
+        lobby.message = "could not join".to_string();
+        let text = lobby_text(&lobby, None);
+        assert!(text.contains("> Join the host at ws://10.0.0.2:7778_"));
+        assert!(text.ends_with("could not join"));
+    }
+}
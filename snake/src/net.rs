@@ -0,0 +1,603 @@
+//! Two players on different machines sharing one arena over `game_net`. The host plays the game
+//! as usual and sends the arena to the player who joined after every step; that player only
+//! shows it, and sends back where they steer the second snake. Games are hosted and joined from
+//! the command line or from the lobby on the menu.
+
+use crate::{
+    held_direction, replay, snake_segment_spawn, ArenaConfig, Food, FoodKind, LastTailPosition,
+    Mode, MoveTimer, Obstacle, Player, PowerUp, Recording, Score, SnakeAction, SnakeHead,
+    SnakeMovement, SnakeSegments,
+};
+use bevy::prelude::*;
+use game_flow::{EndGame, Flow, GameState, NewGame};
+use game_grid::{Direction, Position};
+use game_input::ActionState;
+use game_net::{websocket, Connection, Event, Host, PeerId, Replica, Replicator, Update};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, env};
+
+/// The command line flag hosting a game on a port, e.g. `--host 7777`.
+const HOST_FLAG: &str = "--host";
+/// The command line flag joining a host, e.g. `--join 192.168.1.20:7777`.
+const JOIN_FLAG: &str = "--join";
+
+/// The host's side of a game, with the second snake steered by the first player to join.
+pub(crate) struct Hosting {
+    guest: Option<PeerId>,
+    host: Host,
+    port: u16,
+    replicator: Replicator<u32, Piece>,
+    /// Where the guest last steered.
+    steering: Direction,
+    /// Arenas sent so far, counting on across games for the guest to acknowledge.
+    tick: u32,
+    /// The port players join over WebSocket, if the host could listen on it.
+    websocket: Option<u16>,
+}
+
+impl Hosting {
+    /// Hosts a game on `port`, taking in players over WebSocket as well on the port after it.
+    pub(crate) fn bind(port: u16) -> Result<Self, String> {
+        let mut host = Host::bind(("0.0.0.0", port))
+            .map_err(|error| format!("could not host on port {}: {}", port, error))?;
+        let websocket = port.checked_add(1).and_then(|websocket| {
+            match host.listen_websocket(("0.0.0.0", websocket)) {
+                Ok(address) => Some(address.port()),
+                Err(error) => {
+                    eprintln!("could not take in players over WebSocket: {}", error);
+                    None
+                }
+            }
+        });
+        Ok(Self {
+            guest: None,
+            host,
+            port,
+            replicator: Replicator::default(),
+            steering: Direction::None,
+            tick: 0,
+            websocket,
+        })
+    }
+
+    /// Who is playing, for the menu.
+    fn status(&self) -> String {
+        let ports = match self.websocket {
+            Some(websocket) => format!("port {} (WebSocket {})", self.port, websocket),
+            None => format!("port {}", self.port),
+        };
+        match self.guest {
+            Some(_) => format!("Hosting on {}: P2 joined", ports),
+            None => format!("Hosting on {}: waiting for P2", ports),
+        }
+    }
+}
+
+/// The side of the player who joined a host, showing the arena it sends.
+pub(crate) struct Joined {
+    address: String,
+    /// `None` once the host has left.
+    connection: Option<Connection>,
+    replica: Replica<u32, Piece>,
+    /// The pieces shown, with the entities showing each, a snake's head first.
+    shown: BTreeMap<u32, (Piece, Vec<Entity>)>,
+    /// Where this player last steered, as sent to the host.
+    steering: Direction,
+}
+
+impl Joined {
+    /// Joins the host at `address`, over WebSocket if it starts with `ws://`.
+    pub(crate) fn connect(address: &str) -> Result<Self, String> {
+        let connection = if address.starts_with(websocket::SCHEME) {
+            Connection::connect_websocket(address)
+        } else {
+            Connection::connect(address)
+        };
+        match connection {
+            Ok(connection) => Ok(Self {
+                address: address.to_string(),
+                connection: Some(connection),
+                replica: Replica::default(),
+                shown: BTreeMap::new(),
+                steering: Direction::None,
+            }),
+            Err(error) => Err(format!("could not join {}: {}", address, error)),
+        }
+    }
+
+    /// Whether the host is still there, for the menu.
+    fn status(&self) -> String {
+        match self.connection {
+            Some(_) => format!("Joined {}: waiting for the host", self.address),
+            None => format!("The host at {} left", self.address),
+        }
+    }
+}
+
+/// Something in the arena as the host sends it, keyed by its entity on the host. Cells are
+/// sent as `(x, y)`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+enum Piece {
+    Food {
+        cell: (i32, i32),
+        kind: FoodKind,
+    },
+    Obstacle((i32, i32)),
+    PowerUp {
+        cell: (i32, i32),
+        kind: PowerUp,
+    },
+    /// A player's snake, head first, and its score.
+    Snake {
+        player: usize,
+        cells: Vec<(i32, i32)>,
+        score: u32,
+    },
+}
+
+/// What the host sends the player who joined.
+#[derive(Deserialize, Serialize)]
+enum ToGuest {
+    /// The arena after a step, `width` by `height` cells.
+    Arena {
+        width: u32,
+        height: u32,
+        update: Update<u32, Piece>,
+    },
+    /// The game is over, with what to tell the players.
+    Over(String),
+    Start,
+}
+
+/// What the player who joined sends the host.
+#[derive(Deserialize, Serialize)]
+enum ToHost {
+    /// The tick of the latest arena shown.
+    Acknowledge(u32),
+    /// Where the second snake should head, as the letter of a replay's turn.
+    Steer(char),
+}
+
+/// Adds the systems of both sides, which wait for a game to be hosted or joined from the
+/// lobby, or hosts one with `--host <port>` or joins one with `--join <address>` at once.
+pub(crate) fn build(app: &mut App) {
+    app.add_system(host_receive)
+        .add_system(guest_receive)
+        .add_system_set(
+            SystemSet::on_update(GameState::Playing)
+                .with_system(host_steering.before(SnakeMovement::Input))
+                .with_system(guest_steering),
+        )
+        .add_system_to_stage(CoreStage::PostUpdate, host_send);
+    let args: Vec<String> = env::args().collect();
+    let started = if let Some(port) = flag(&args, HOST_FLAG) {
+        port.parse()
+            .map_err(|_| format!("{} is no port to host on", port))
+            .and_then(Hosting::bind)
+            .map(|hosting| {
+                app.insert_resource(hosting);
+            })
+    } else if let Some(address) = flag(&args, JOIN_FLAG) {
+        Joined::connect(&address).map(|joined| {
+            app.insert_resource(joined);
+        })
+    } else {
+        return;
+    };
+    match started {
+        Ok(()) => {
+            app.insert_resource(Mode::Online);
+        }
+        Err(error) => eprintln!("{}", error),
+    }
+}
+
+/// What the menu says about the network, if the game is played over it.
+pub(crate) fn status(hosting: Option<&Hosting>, joined: Option<&Joined>) -> Option<String> {
+    hosting
+        .map(Hosting::status)
+        .or_else(|| joined.map(Joined::status))
+}
+
+/// The value after `name` on the command line.
+fn flag(args: &[String], name: &str) -> Option<String> {
+    args.iter().skip_while(|arg| *arg != name).nth(1).cloned()
+}
+
+/// Takes in the first player to join, where they steer and the arenas they have shown. A game
+/// they leave ends.
+fn host_receive(
+    hosting: Option<ResMut<Hosting>>,
+    state: Res<State<GameState>>,
+    mut end_game: EventWriter<EndGame>,
+) {
+    let mut hosting = match hosting {
+        Some(hosting) => hosting,
+        None => return,
+    };
+    let hosting = &mut *hosting;
+    for event in hosting.host.poll::<ToHost>() {
+        match event {
+            Event::Joined(peer) if hosting.guest.is_none() => {
+                hosting.guest = Some(peer);
+                hosting.replicator = Replicator::default();
+                hosting.steering = Direction::None;
+                // Joining a game under way starts it on their side too.
+                if matches!(state.current(), GameState::Playing | GameState::Paused) {
+                    hosting.host.send(peer, &ToGuest::Start).ok();
+                }
+            }
+            Event::Left(peer) if hosting.guest == Some(peer) => {
+                hosting.guest = None;
+                if *state.current() == GameState::Playing {
+                    end_game.send(EndGame("P2 left".to_string()));
+                }
+            }
+            Event::Message(peer, message) if hosting.guest == Some(peer) => match message {
+                ToHost::Acknowledge(tick) => hosting.replicator.acknowledge(tick),
+                ToHost::Steer(letter) => hosting.steering = replay::direction(letter),
+            },
+            // Anyone joining after the first player is left waiting.
+            _ => {}
+        }
+    }
+}
+
+/// Tells the player who joined when a game starts and ends, and sends them the arena after each
+/// step, as the changes since the last one they acknowledged.
+fn host_send(
+    hosting: Option<ResMut<Hosting>>,
+    arena: Res<ArenaConfig>,
+    recording: Res<Recording>,
+    mut new_games: EventReader<NewGame>,
+    mut end_games: EventReader<EndGame>,
+    snakes: Query<(Entity, &Player, &SnakeSegments, &Score)>,
+    positions: Query<&Position>,
+    food: Query<(Entity, &Position, &FoodKind), With<Food>>,
+    obstacles: Query<(Entity, &Position), With<Obstacle>>,
+    power_ups: Query<(Entity, &Position, &PowerUp)>,
+) {
+    let mut hosting = match hosting {
+        Some(hosting) => hosting,
+        None => return,
+    };
+    let hosting = &mut *hosting;
+    let guest = match hosting.guest {
+        Some(guest) => guest,
+        None => return,
+    };
+    if new_games.iter().next().is_some() {
+        hosting.steering = Direction::None;
+        hosting.host.send(guest, &ToGuest::Start).ok();
+    }
+    // The recording counts the steps, so it changes with every one and every new game.
+    if recording.is_changed() {
+        let mut state = BTreeMap::new();
+        for (entity, Player(player), segments, score) in snakes.iter() {
+            let cells = segments
+                .0
+                .iter()
+                .filter_map(|segment| positions.get(*segment).ok().map(cell))
+                .collect();
+            let snake = Piece::Snake {
+                player: *player,
+                cells,
+                score: score.0,
+            };
+            state.insert(entity.id(), snake);
+        }
+        for (entity, position, kind) in food.iter() {
+            let food = Piece::Food {
+                cell: cell(position),
+                kind: *kind,
+            };
+            state.insert(entity.id(), food);
+        }
+        for (entity, position) in obstacles.iter() {
+            state.insert(entity.id(), Piece::Obstacle(cell(position)));
+        }
+        for (entity, position, kind) in power_ups.iter() {
+            let power_up = Piece::PowerUp {
+                cell: cell(position),
+                kind: *kind,
+            };
+            state.insert(entity.id(), power_up);
+        }
+        hosting.tick += 1;
+        let arena = ToGuest::Arena {
+            width: arena.width,
+            height: arena.height,
+            update: hosting.replicator.update(hosting.tick, &state),
+        };
+        hosting.host.send(guest, &arena).ok();
+    }
+    if let Some(EndGame(message)) = end_games.iter().last() {
+        hosting
+            .host
+            .send(guest, &ToGuest::Over(message.clone()))
+            .ok();
+    }
+}
+
+/// Turns the second snake where the player who joined steers.
+fn host_steering(hosting: Option<Res<Hosting>>, mut heads: Query<(&mut SnakeHead, &Player)>) {
+    let steering = match hosting {
+        Some(hosting) if hosting.steering != Direction::None => hosting.steering,
+        _ => return,
+    };
+    for (mut head, player) in heads.iter_mut() {
+        if *player == Player(1) {
+            head.direction = head.direction.turn(steering);
+        }
+    }
+}
+
+/// Starts and ends games as the host says, and shows the arenas it sends, gliding the snakes
+/// between them.
+fn guest_receive(
+    mut commands: Commands,
+    joined: Option<ResMut<Joined>>,
+    mut arena: ResMut<ArenaConfig>,
+    mut flow: ResMut<Flow>,
+    mut state: ResMut<State<GameState>>,
+    mut timer: ResMut<MoveTimer>,
+) {
+    let mut joined = match joined {
+        Some(joined) => joined,
+        None => return,
+    };
+    let joined = &mut *joined;
+    let connection = match &mut joined.connection {
+        Some(connection) => connection,
+        None => return,
+    };
+    let messages = match connection.receive::<ToGuest>() {
+        Ok(messages) => messages,
+        Err(error) => {
+            eprintln!("lost the connection to the host: {}", error);
+            joined.connection = None;
+            if *state.current() != GameState::Menu {
+                flow.message = "The host left".to_string();
+                let _ = state.replace(GameState::GameOver);
+            }
+            return;
+        }
+    };
+    for message in messages {
+        match message {
+            ToGuest::Arena {
+                width,
+                height,
+                update,
+            } => {
+                let tick = match joined.replica.apply(update) {
+                    Some(tick) => tick,
+                    None => continue,
+                };
+                connection.send(&ToHost::Acknowledge(tick)).ok();
+                let size = ArenaConfig { height, width };
+                if *arena != size {
+                    *arena = size;
+                }
+                if let Some(pieces) = joined.replica.state() {
+                    show(&mut commands, &mut joined.shown, pieces);
+                }
+                // The sprites glide for as long as a step took, and wait for the next arena.
+                timer.0.set_repeating(false);
+                timer.0.reset();
+            }
+            ToGuest::Over(message) => {
+                flow.message = message;
+                let _ = state.replace(GameState::GameOver);
+            }
+            ToGuest::Start => {
+                joined.steering = Direction::None;
+                if *state.current() != GameState::Playing {
+                    let _ = state.replace(GameState::Playing);
+                }
+            }
+        }
+    }
+}
+
+/// Sends the host where this player steers, with either set of keys, when it changes.
+fn guest_steering(actions: Res<ActionState<SnakeAction>>, joined: Option<ResMut<Joined>>) {
+    let mut joined = match joined {
+        Some(joined) => joined,
+        None => return,
+    };
+    let direction = match held_direction(&actions, &[SnakeAction::FIRST, SnakeAction::SECOND]) {
+        Some(direction) if direction != joined.steering => direction,
+        _ => return,
+    };
+    joined.steering = direction;
+    if let Some(connection) = &mut joined.connection {
+        connection
+            .send(&ToHost::Steer(replay::letter(direction)))
+            .ok();
+    }
+}
+
+fn cell(position: &Position) -> (i32, i32) {
+    (position.x, position.y)
+}
+
+/// Brings the entities showing the host's arena in line with `pieces`. Snakes that are still
+/// there keep their segments, so their sprites glide to where they moved.
+fn show(
+    commands: &mut Commands,
+    shown: &mut BTreeMap<u32, (Piece, Vec<Entity>)>,
+    pieces: &BTreeMap<u32, Piece>,
+) {
+    shown.retain(|key, (_, entities)| {
+        let kept = pieces.contains_key(key);
+        if !kept {
+            for entity in entities.iter() {
+                commands.entity(*entity).despawn();
+            }
+        }
+        kept
+    });
+    for (key, piece) in pieces {
+        let entities = match (shown.remove(key), piece) {
+            (Some((old, entities)), _) if old == *piece => entities,
+            (
+                Some((Piece::Snake { player: old, .. }, segments)),
+                Piece::Snake {
+                    player,
+                    cells,
+                    score,
+                },
+            ) if old == *player => snake_move(commands, segments, *player, cells, *score),
+            (Some((_, entities)), _) => {
+                for entity in entities {
+                    commands.entity(entity).despawn();
+                }
+                spawn(commands, piece)
+            }
+            (None, _) => spawn(commands, piece),
+        };
+        shown.insert(*key, (piece.clone(), entities));
+    }
+}
+
+/// Moves a shown snake's segments to `cells`, growing or shrinking it to as many.
+fn snake_move(
+    commands: &mut Commands,
+    mut segments: Vec<Entity>,
+    player: usize,
+    cells: &[(i32, i32)],
+    score: u32,
+) -> Vec<Entity> {
+    let kept = cells.len().min(segments.len());
+    for segment in segments.split_off(kept) {
+        commands.entity(segment).despawn();
+    }
+    for (segment, (x, y)) in segments.iter().zip(cells) {
+        commands.entity(*segment).insert(Position::new(*x, *y));
+    }
+    for (x, y) in &cells[kept..] {
+        let segment = snake_segment_spawn(commands, Player(player), Position::new(*x, *y));
+        segments.push(segment);
+    }
+    if let Some(head) = segments.first() {
+        commands
+            .entity(*head)
+            .insert(SnakeSegments(segments.clone()))
+            .insert(Score(score));
+    }
+    segments
+}
+
+/// The entities showing a piece the host sent, a snake's head first.
+fn spawn(commands: &mut Commands, piece: &Piece) -> Vec<Entity> {
+    match piece {
+        Piece::Food { cell: (x, y), kind } => {
+            let food = commands
+                .spawn()
+                .insert(Food)
+                .insert(*kind)
+                .insert(Position::new(*x, *y));
+            vec![food.id()]
+        }
+        Piece::Obstacle((x, y)) => {
+            let obstacle = commands
+                .spawn()
+                .insert(Obstacle)
+                .insert(Position::new(*x, *y));
+            vec![obstacle.id()]
+        }
+        Piece::PowerUp { cell: (x, y), kind } => vec![commands
+            .spawn()
+            .insert(*kind)
+            .insert(Position::new(*x, *y))
+            .id()],
+        Piece::Snake {
+            player,
+            cells,
+            score,
+        } => {
+            let segments: Vec<Entity> = cells
+                .iter()
+                .map(|(x, y)| snake_segment_spawn(commands, Player(*player), Position::new(*x, *y)))
+                .collect();
+            if let Some(head) = segments.first() {
+                commands
+                    .entity(*head)
+                    .insert(SnakeHead {
+                        direction: Direction::None,
+                    })
+                    .insert(SnakeSegments(segments.clone()))
+                    .insert(LastTailPosition::default())
+                    .insert(Score(*score));
+            }
+            segments
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::CommandQueue;
+
+    /// Shows `pieces` in `world`, as a guest does with the arena it receives.
+    fn show_in(
+        world: &mut World,
+        shown: &mut BTreeMap<u32, (Piece, Vec<Entity>)>,
+        pieces: &BTreeMap<u32, Piece>,
+    ) {
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, world);
+        show(&mut commands, shown, pieces);
+        queue.apply(world);
+    }
+
+    #[test]
+    fn shown_snakes_move_and_grow_in_place() {
+        let mut world = World::new();
+        let mut shown = BTreeMap::new();
+        let mut pieces = BTreeMap::from([
+            (
+                1,
+                Piece::Snake {
+                    player: 1,
+                    cells: vec![(2, 2), (2, 1)],
+                    score: 0,
+                },
+            ),
+            (
+                4,
+                Piece::Food {
+                    cell: (2, 3),
+                    kind: FoodKind::Normal,
+                },
+            ),
+        ]);
+        show_in(&mut world, &mut shown, &pieces);
+        let head = shown[&1].1[0];
+        assert_eq!(world.query::<&Food>().iter(&world).count(), 1);
+
+        // The snake eats the food and grows into where its tail was.
+        pieces.remove(&4);
+        pieces.insert(
+            1,
+            Piece::Snake {
+                player: 1,
+                cells: vec![(2, 3), (2, 2), (2, 1)],
+                score: 1,
+            },
+        );
+        show_in(&mut world, &mut shown, &pieces);
+        assert_eq!(world.query::<&Food>().iter(&world).count(), 0);
+        assert_eq!(shown[&1].1[0], head);
+        assert_eq!(world.get::<Position>(head), Some(&Position::new(2, 3)));
+        assert_eq!(world.get::<Score>(head), Some(&Score(1)));
+        let segments = world.get::<SnakeSegments>(head).unwrap();
+        assert_eq!(segments.0.len(), 3);
+        assert_eq!(
+            world.get::<Position>(segments.0[2]),
+            Some(&Position::new(2, 1))
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Power-ups turning up in the arena now and then, and how long they work on the snake that
+//! picks one up.
+
+use bevy::prelude::*;
+use game_rng::Stream;
+use serde::{Deserialize, Serialize};
+
+/// Steps a ghost can pass through its own body.
+const GHOST_STEPS: u32 = 40;
+/// Steps the food a snake eats scores double.
+const MULTIPLIER_STEPS: u32 = 60;
+/// Steps a snake moves faster.
+const SPEED_STEPS: u32 = 50;
+
+/// What a power-up lying in the arena does once picked up.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PowerUp {
+    /// Shortens the steps.
+    Speed,
+    /// Lets the snake pass through its own body.
+    Ghost,
+    /// Multiplies the points the snake scores.
+    Multiplier,
+}
+
+impl PowerUp {
+    pub const ALL: [Self; 3] = [Self::Speed, Self::Ghost, Self::Multiplier];
+
+    pub fn random(stream: &mut Stream) -> Self {
+        *stream.pick(&Self::ALL).unwrap_or(&Self::Speed)
+    }
+
+    /// The color of the power-up, and of the head of a snake it works on.
+    pub fn color(self) -> Color {
+        match self {
+            Self::Speed => Color::rgb(1.0, 0.3, 0.2),
+            Self::Ghost => Color::rgb(0.75, 0.85, 1.0),
+            Self::Multiplier => Color::rgb(0.3, 1.0, 0.4),
+        }
+    }
+
+    /// Steps it works for once picked up.
+    pub fn duration(self) -> u32 {
+        match self {
+            Self::Speed => SPEED_STEPS,
+            Self::Ghost => GHOST_STEPS,
+            Self::Multiplier => MULTIPLIER_STEPS,
+        }
+    }
+
+    /// The power-up named in a console command.
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "speed" => Some(Self::Speed),
+            "ghost" => Some(Self::Ghost),
+            "multiplier" => Some(Self::Multiplier),
+            _ => None,
+        }
+    }
+}
+
+/// The power-ups working on each player's snake, with the steps each has left, oldest first.
+#[derive(Debug, Default)]
+pub struct ActiveEffects(Vec<(usize, PowerUp, u32)>);
+
+impl ActiveEffects {
+    /// Starts a power-up on a player's snake, or starts it over if it works there already.
+    pub fn add(&mut self, player: usize, power_up: PowerUp) {
+        self.0
+            .retain(|(other, active, _)| (*other, *active) != (player, power_up));
+        self.0.push((player, power_up, power_up.duration()));
+    }
+
+    /// Whether a power-up works on any snake.
+    pub fn any(&self, power_up: PowerUp) -> bool {
+        self.0.iter().any(|(_, active, _)| *active == power_up)
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    /// Whether a power-up works on a player's snake.
+    pub fn has(&self, player: usize, power_up: PowerUp) -> bool {
+        self.steps(player, power_up) > 0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The power-up a player picked up last of those working, which tints the head.
+    pub fn latest(&self, player: usize) -> Option<PowerUp> {
+        self.0
+            .iter()
+            .rev()
+            .find(|(other, _, _)| *other == player)
+            .map(|(_, power_up, _)| *power_up)
+    }
+
+    /// Counts one step down, ending the power-ups whose time is up.
+    pub fn step(&mut self) {
+        for (_, _, steps) in &mut self.0 {
+            *steps = steps.saturating_sub(1);
+        }
+        self.0.retain(|(_, _, steps)| *steps > 0);
+    }
+
+    /// Steps a power-up has left on a player's snake, 0 if it does not work there.
+    pub fn steps(&self, player: usize, power_up: PowerUp) -> u32 {
+        self.0
+            .iter()
+            .find(|(other, active, _)| (*other, *active) == (player, power_up))
+            .map_or(0, |(_, _, steps)| *steps)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_run_out_and_start_over() {
+        let mut effects = ActiveEffects::default();
+        effects.add(0, PowerUp::Ghost);
+        effects.add(1, PowerUp::Speed);
+        effects.add(0, PowerUp::Multiplier);
+        assert_eq!(effects.latest(0), Some(PowerUp::Multiplier));
+        for _ in 0..GHOST_STEPS {
+            effects.step();
+        }
+        assert!(!effects.has(0, PowerUp::Ghost));
+        assert!(effects.has(0, PowerUp::Multiplier));
+        assert!(effects.any(PowerUp::Speed) && !effects.has(0, PowerUp::Speed));
+        effects.add(0, PowerUp::Multiplier);
+        assert_eq!(effects.steps(0, PowerUp::Multiplier), MULTIPLIER_STEPS);
+        effects.clear();
+        assert_eq!(effects.latest(0), None);
+    }
+}
@@ -0,0 +1,175 @@
+//! Games recorded as the random numbers they started with and the snakes' turns, saved when they
+//! end and played back with `--replay <file>`.
+
+use crate::Level;
+use game_grid::Direction;
+use game_persistence::Schema;
+use game_rng::Rng;
+use serde::{Deserialize, Serialize};
+use std::{env, fs};
+
+/// The command line flag giving a replay to play back, e.g. `--replay replay.ron`.
+const REPLAY_FLAG: &str = "--replay";
+
+/// A game as it was played, enough to play it out the same way again.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Replay {
+    /// The random numbers as they were when the game started.
+    pub rng: Rng,
+    pub players: usize,
+    /// Whether the second snake was the computer's.
+    pub versus: bool,
+    /// The level the game started on.
+    pub level: Level,
+    /// Where the snakes turned, in the order they did.
+    pub turns: Vec<Turn>,
+}
+
+/// A replay as saved at version 0, before the computer could steer the second snake.
+#[derive(Deserialize)]
+struct ReplayV0 {
+    rng: Rng,
+    players: usize,
+    level: Level,
+    turns: Vec<Turn>,
+}
+
+impl From<ReplayV0> for Replay {
+    fn from(replay: ReplayV0) -> Self {
+        Self {
+            rng: replay.rng,
+            players: replay.players,
+            versus: false,
+            level: replay.level,
+            turns: replay.turns,
+        }
+    }
+}
+
+/// A snake heading somewhere new on a step.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub struct Turn {
+    /// Steps since the game started.
+    pub step: u32,
+    pub player: usize,
+    /// `U`, `L`, `D` or `R`, or `N` for standing still.
+    pub direction: char,
+}
+
+impl Replay {
+    pub fn new(rng: Rng, players: usize, level: Level) -> Self {
+        Self {
+            rng,
+            players,
+            versus: false,
+            level,
+            turns: Vec::new(),
+        }
+    }
+
+    /// The replay given with `--replay` on the command line, if it can be read.
+    pub fn load() -> Option<Self> {
+        let path = env::args().skip_while(|arg| arg != REPLAY_FLAG).nth(1)?;
+        fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|text| Self::decode(&text))
+            .map_err(|error| eprintln!("could not read the replay {}: {}", path, error))
+            .ok()
+    }
+
+    /// A replay from the contents of a file `game_persistence` saved, as the game saves them.
+    fn decode(text: &str) -> Result<Self, String> {
+        game_persistence::decode(text, &Self::schema()).map_err(|error| error.to_string())
+    }
+
+    /// Version 1 added `versus`.
+    pub fn schema() -> Schema<Self> {
+        Schema::new(1).migrate::<ReplayV0>(0)
+    }
+
+    /// Where a player's snake was last heading, standing still until it turned.
+    pub fn heading(&self, player: usize) -> Direction {
+        self.turns
+            .iter()
+            .rev()
+            .find(|turn| turn.player == player)
+            .map_or(Direction::None, |turn| direction(turn.direction))
+    }
+
+    pub fn record(&mut self, step: u32, player: usize, heading: Direction) {
+        self.turns.push(Turn {
+            step,
+            player,
+            direction: letter(heading),
+        });
+    }
+
+    /// Where the snakes turn on `step`, each player's snake with its direction.
+    pub fn turns(&self, step: u32) -> impl Iterator<Item = (usize, Direction)> + '_ {
+        self.turns
+            .iter()
+            .filter(move |turn| turn.step == step)
+            .map(|turn| (turn.player, direction(turn.direction)))
+    }
+}
+
+/// The direction of a turn's letter.
+pub(crate) fn direction(letter: char) -> Direction {
+    match letter {
+        'U' => Direction::Up,
+        'L' => Direction::Left,
+        'D' => Direction::Down,
+        'R' => Direction::Right,
+        _ => Direction::None,
+    }
+}
+
+/// The letter of a turn toward `direction`.
+pub(crate) fn letter(direction: Direction) -> char {
+    match direction {
+        Direction::Up => 'U',
+        Direction::Left => 'L',
+        Direction::Down => 'D',
+        Direction::Right => 'R',
+        Direction::None => 'N',
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_rng::Seed;
+
+    #[test]
+    fn turns_are_kept_by_step() {
+        let level = Level::bundled().remove(0);
+        let mut replay = Replay::new(Rng::new(Seed(7)), 2, level);
+        assert_eq!(replay.heading(0), Direction::None);
+        replay.record(0, 0, Direction::Right);
+        replay.record(0, 1, Direction::Left);
+        replay.record(5, 0, Direction::Up);
+        assert_eq!(replay.heading(0), Direction::Up);
+        assert_eq!(replay.heading(1), Direction::Left);
+        assert_eq!(
+            replay.turns(0).collect::<Vec<_>>(),
+            [(0, Direction::Right), (1, Direction::Left)]
+        );
+        assert_eq!(replay.turns(3).count(), 0);
+    }
+
+    #[test]
+    fn saved_replays_load_back() {
+        let level = Level::bundled().remove(0);
+        let mut replay = Replay::new(Rng::new(Seed(7)), 1, level);
+        replay.record(3, 0, Direction::Up);
+        // What `game_persistence::save_with` writes to `replay.ron`.
+        let text = game_persistence::encode(&replay, &Replay::schema()).unwrap();
+        assert_eq!(Replay::decode(&text).unwrap(), replay);
+        // Saved at version 0, without `versus`.
+        let old = text
+            .replace("version: 1", "version: 0")
+            .replace("versus: false,", "");
+        assert!(!old.contains("versus"));
+        assert_eq!(Replay::decode(&old).unwrap(), replay);
+    }
+}
@@ -0,0 +1,212 @@
+//! The snake's rules without a window, stepped by hand: only the systems of a step run, each
+//! time `Simulation::step` is called, for bots and experiments to play thousands of steps a
+//! second.
+
+use crate::{
+    audio::Sound, level_spawn, score_update, step_systems, ActiveEffects, BonusEvent, ClearedEvent,
+    Colors, Food, FoodKind, GameOverEvent, GrowthEvent, Level, Mode, Obstacle, PenaltyEvent,
+    Playback, Player, PowerUp, PowerUpCountdown, Recording, RunStats, Score, ShrinkEvent,
+    SnakeHead, SnakeSegments, SnakeSettings, POWER_UP_INTERVAL,
+};
+use bevy::{
+    app::{Events, ManualEventReader},
+    ecs::system::CommandQueue,
+    prelude::*,
+};
+use game_grid::{Direction, Position};
+use game_particles::Emit;
+use game_rng::{Rng, Seed};
+
+/// A game of one or two snakes on a level, played a step at a time.
+pub struct Simulation {
+    app: App,
+    crashes: ManualEventReader<GameOverEvent>,
+    over: bool,
+    steps: u32,
+}
+
+/// What there is in the arena after a step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Observation {
+    pub width: u32,
+    pub height: u32,
+    /// Each player's snake, head first.
+    pub snakes: Vec<Vec<Position>>,
+    /// Each player's score.
+    pub scores: Vec<u32>,
+    pub food: Vec<(Position, FoodKind)>,
+    pub obstacles: Vec<Position>,
+    pub power_ups: Vec<(Position, PowerUp)>,
+    /// Steps played so far.
+    pub steps: u32,
+    /// Whether a snake has crashed, which ends the game.
+    pub over: bool,
+}
+
+impl Simulation {
+    /// A new game on `level` for one or two `players`, with the food falling as it does for
+    /// `seed`.
+    pub fn new(level: Level, players: usize, seed: Seed) -> Self {
+        let mode = if players == 2 {
+            Mode::TwoPlayers
+        } else {
+            Mode::OnePlayer
+        };
+        let mut app = App::default();
+        app.insert_resource(Windows::default())
+            .insert_resource(ActiveEffects::default())
+            .insert_resource(Colors::from(&SnakeSettings::default()))
+            .insert_resource(level.arena())
+            .insert_resource(mode)
+            .insert_resource(Playback(None))
+            .insert_resource(PowerUpCountdown(POWER_UP_INTERVAL))
+            .insert_resource(Recording {
+                replay: None,
+                step: 0,
+            })
+            .insert_resource(Rng::new(seed))
+            .insert_resource(RunStats::default())
+            .add_event::<BonusEvent>()
+            .add_event::<ClearedEvent>()
+            .add_event::<Emit>()
+            .add_event::<GameOverEvent>()
+            .add_event::<GrowthEvent>()
+            .add_event::<PenaltyEvent>()
+            .add_event::<ShrinkEvent>()
+            .add_event::<Sound>()
+            .add_system(score_update)
+            .add_system_set(step_systems());
+        let mut queue = CommandQueue::default();
+        let mut commands = Commands::new(&mut queue, &app.world);
+        level_spawn(&mut commands, &level, mode, &[]);
+        queue.apply(&mut app.world);
+        app.insert_resource(level);
+        Self {
+            app,
+            crashes: ManualEventReader::default(),
+            over: false,
+            steps: 0,
+        }
+    }
+
+    /// Turns each player's snake toward its direction in `turns`, if it can go that way, and
+    /// plays a step. `Direction::None`, or no direction, keeps a snake heading where it was.
+    /// Once the game is over the snakes stay where they crashed.
+    pub fn step(&mut self, turns: &[Direction]) -> Observation {
+        if !self.over {
+            let mut heads = self.app.world.query::<(&mut SnakeHead, &Player)>();
+            for (mut head, Player(player)) in heads.iter_mut(&mut self.app.world) {
+                match turns.get(*player) {
+                    Some(Direction::None) | None => {}
+                    Some(direction) => head.direction = head.direction.turn(*direction),
+                }
+            }
+            self.app.update();
+            self.steps += 1;
+            let events = self
+                .app
+                .world
+                .get_resource::<Events<GameOverEvent>>()
+                .unwrap();
+            self.over = self.crashes.iter(events).next().is_some();
+        }
+        self.observe()
+    }
+
+    /// The arena as it is now.
+    pub fn observe(&mut self) -> Observation {
+        let world = &mut self.app.world;
+        let level = world.get_resource::<Level>().unwrap();
+        let (width, height) = (level.width, level.height);
+        let mut snakes = world.query::<(&Player, &SnakeSegments, &Score)>();
+        let mut players: Vec<(usize, Vec<Entity>, u32)> = snakes
+            .iter(world)
+            .map(|(Player(player), segments, score)| (*player, segments.0.clone(), score.0))
+            .collect();
+        players.sort_unstable_by_key(|(player, _, _)| *player);
+        let mut positions = world.query::<&Position>();
+        let snakes = players
+            .iter()
+            .map(|(_, segments, _)| {
+                segments
+                    .iter()
+                    .filter_map(|segment| positions.get(world, *segment).ok().copied())
+                    .collect()
+            })
+            .collect();
+        let scores = players.iter().map(|(_, _, score)| *score).collect();
+        let food = world
+            .query_filtered::<(&Position, &FoodKind), With<Food>>()
+            .iter(world)
+            .map(|(position, kind)| (*position, *kind))
+            .collect();
+        let obstacles = world
+            .query_filtered::<&Position, With<Obstacle>>()
+            .iter(world)
+            .copied()
+            .collect();
+        let power_ups = world
+            .query::<(&Position, &PowerUp)>()
+            .iter(world)
+            .map(|(position, power_up)| (*position, *power_up))
+            .collect();
+        Observation {
+            width,
+            height,
+            snakes,
+            scores,
+            food,
+            obstacles,
+            power_ups,
+            steps: self.steps,
+            over: self.over,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ArenaConfig, Layout};
+
+    fn open() -> Level {
+        Level::free(&ArenaConfig::new(10, 10).unwrap(), Layout::Open, &[])
+    }
+
+    #[test]
+    fn plays_the_same_for_the_same_seed() {
+        let mut first = Simulation::new(open(), 1, Seed(5));
+        let mut second = Simulation::new(open(), 1, Seed(5));
+        let turns = [
+            Direction::Right,
+            Direction::Up,
+            Direction::Left,
+            Direction::Down,
+        ];
+        for step in 0..40 {
+            let turn = [turns[step / 3 % turns.len()]];
+            assert_eq!(first.step(&turn), second.step(&turn));
+        }
+    }
+
+    #[test]
+    fn ends_when_a_snake_crashes() {
+        let mut simulation = Simulation::new(open(), 1, Seed(5));
+        let start = simulation.observe();
+        assert_eq!(
+            start.snakes,
+            [vec![Position::new(3, 3), Position::new(3, 2)]]
+        );
+        assert_eq!(start.food.len(), 0);
+        let mut observation = simulation.step(&[Direction::Left]);
+        assert_eq!(observation.snakes[0][0], Position::new(2, 3));
+        assert_eq!(observation.food.len(), 1);
+        while !observation.over {
+            observation = simulation.step(&[]);
+        }
+        // The head runs into the wall on the fourth step.
+        assert_eq!(observation.steps, 4);
+        assert_eq!(observation.snakes[0][0], Position::new(-1, 3));
+        assert_eq!(simulation.step(&[Direction::Up]), observation);
+    }
+}
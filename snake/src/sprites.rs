@@ -0,0 +1,213 @@
+//! The snake's sprite sheet, drawn in white when the game starts and tinted per tile, and which
+//! of its sprites each segment shows.
+
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use game_grid::{Direction, Position};
+
+/// A square block, for obstacles.
+pub const BLOCK: usize = 15;
+/// Sprites in the sheet, side by side.
+const COLUMNS: usize = 16;
+/// The corners joining up and right, right and down, down and left, and left and up.
+const CORNER: usize = 10;
+/// A round piece of food.
+pub const FOOD: usize = 14;
+/// The heads facing up, right, down and left.
+const HEAD: usize = 0;
+/// Side of a sprite, in pixels.
+const SIZE: u32 = 32;
+/// The straight segments, up and down and then left and right.
+const STRAIGHT: usize = 8;
+/// The tails pointing up, right, down and left.
+const TAIL: usize = 4;
+
+/// How a segment looks, from where its neighbours are. Kept on every segment.
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SegmentShape {
+    /// Facing away from the segment behind it.
+    Head(Direction),
+    /// Joining the segments toward the head and toward the tail, in a line or round a corner.
+    Body(Direction, Direction),
+    /// Pointing away from the segment before it.
+    Tail(Direction),
+}
+
+impl SegmentShape {
+    /// The shapes of the segments of a snake at `positions`, head first.
+    pub fn of(positions: &[Position]) -> Vec<Self> {
+        (0..positions.len())
+            .map(|index| {
+                let position = positions[index];
+                let before = index
+                    .checked_sub(1)
+                    .map(|before| toward(position, positions[before]));
+                let after = positions
+                    .get(index + 1)
+                    .map(|after| toward(position, *after));
+                match (before, after) {
+                    (None, Some(after)) => Self::Head(after.opposite()),
+                    (None, None) => Self::Head(Direction::Up),
+                    (Some(before), None) => Self::Tail(before.opposite()),
+                    (Some(before), Some(after)) => Self::Body(before, after),
+                }
+            })
+            .collect()
+    }
+
+    /// The sprite in the sheet.
+    pub fn index(self) -> usize {
+        match self {
+            Self::Head(facing) => HEAD + turns(facing),
+            Self::Tail(pointing) => TAIL + turns(pointing),
+            Self::Body(before, after) => {
+                if before == Direction::None
+                    || after == Direction::None
+                    || before == after
+                    || before == after.opposite()
+                {
+                    let along = if before == Direction::None {
+                        after
+                    } else {
+                        before
+                    };
+                    match along {
+                        Direction::Left | Direction::Right => STRAIGHT + 1,
+                        _ => STRAIGHT,
+                    }
+                } else if (turns(before) + 1) % 4 == turns(after) {
+                    CORNER + turns(before)
+                } else {
+                    CORNER + turns(after)
+                }
+            }
+        }
+    }
+}
+
+/// The sheet as an atlas of its sprites.
+pub fn atlas(sheet: Handle<Image>) -> TextureAtlas {
+    TextureAtlas::from_grid(sheet, Vec2::splat(SIZE as f32), COLUMNS, 1)
+}
+
+/// The sprites side by side, white on transparent. Heads, tails and corners are drawn facing up
+/// and turned a quarter at a time.
+pub fn sheet() -> Image {
+    let width = SIZE * COLUMNS as u32;
+    let mut data = Vec::with_capacity((width * SIZE * 4) as usize);
+    for y in 0..SIZE {
+        for x in 0..width {
+            let column = (x / SIZE) as usize;
+            // -1 to 1 across the sprite, with `v` growing upwards.
+            let u = ((x % SIZE) as f32 + 0.5) / SIZE as f32 * 2.0 - 1.0;
+            let v = 1.0 - (y as f32 + 0.5) / SIZE as f32 * 2.0;
+            let alpha = if covers(column, u, v) { 255 } else { 0 };
+            data.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    Image::new(
+        Extent3d {
+            width,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    )
+}
+
+/// Whether the sprite in `column` covers the point `u`, `v`.
+fn covers(column: usize, u: f32, v: f32) -> bool {
+    let (shape, turns) = match column {
+        HEAD..=3 => (HEAD, column - HEAD),
+        TAIL..=7 => (TAIL, column - TAIL),
+        STRAIGHT..=9 => (STRAIGHT, column - STRAIGHT),
+        CORNER..=13 => (CORNER, column - CORNER),
+        _ => (column, 0),
+    };
+    // Turning the point back by as many quarters as the sprite is turned.
+    let (u, v) = match turns {
+        1 => (-v, u),
+        2 => (-u, -v),
+        3 => (v, -u),
+        _ => (u, v),
+    };
+    const HALF_WIDTH: f32 = 0.45;
+    match shape {
+        HEAD => {
+            let neck = u.abs() <= HALF_WIDTH && v <= 0.0;
+            let crown = u * u + v * v <= 0.36;
+            let eye = (u.abs() - 0.25).powi(2) + (v - 0.2).powi(2) <= 0.01;
+            (neck || crown) && !eye
+        }
+        TAIL => u.abs() <= HALF_WIDTH * (0.8 - v) / 1.8,
+        STRAIGHT => u.abs() <= HALF_WIDTH,
+        CORNER => {
+            (u.abs() <= HALF_WIDTH && v >= -HALF_WIDTH)
+                || (v.abs() <= HALF_WIDTH && u >= -HALF_WIDTH)
+        }
+        FOOD => u * u + v * v <= 0.8,
+        _ => u.abs() <= 0.9 && v.abs() <= 0.9,
+    }
+}
+
+/// Quarter turns clockwise from up.
+fn turns(direction: Direction) -> usize {
+    match direction {
+        Direction::None | Direction::Up => 0,
+        Direction::Right => 1,
+        Direction::Down => 2,
+        Direction::Left => 3,
+    }
+}
+
+/// Where `to` lies from `from`, along the longer way when it is not next to it.
+fn toward(from: Position, to: Position) -> Direction {
+    let (dx, dy) = (to.x - from.x, to.y - from.y);
+    if dx == 0 && dy == 0 {
+        Direction::None
+    } else if dx.abs() >= dy.abs() {
+        if dx > 0 {
+            Direction::Right
+        } else {
+            Direction::Left
+        }
+    } else if dy > 0 {
+        Direction::Up
+    } else {
+        Direction::Down
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_follow_the_neighbours() {
+        // Heading right along the bottom after turning round a corner.
+        let positions = [
+            Position::new(3, 1),
+            Position::new(2, 1),
+            Position::new(1, 1),
+            Position::new(1, 2),
+        ];
+        let shapes = SegmentShape::of(&positions);
+        assert_eq!(shapes[0], SegmentShape::Head(Direction::Right));
+        assert_eq!(shapes[3], SegmentShape::Tail(Direction::Up));
+        let indices: Vec<usize> = shapes.into_iter().map(SegmentShape::index).collect();
+        assert_eq!(indices, [HEAD + 1, STRAIGHT + 1, CORNER, TAIL]);
+    }
+
+    #[test]
+    fn draws_the_sprites_turned() {
+        // Facing right, the head is rounded off on the right and its neck reaches the left.
+        assert!(covers(HEAD + 1, 0.5, 0.0) && !covers(HEAD + 1, 0.9, 0.0));
+        assert!(covers(HEAD + 1, -0.9, 0.0));
+        assert!(covers(CORNER + 2, -0.9, 0.0) && covers(CORNER + 2, 0.0, -0.9));
+        assert!(!covers(CORNER + 2, 0.9, 0.0));
+    }
+}
@@ -0,0 +1,214 @@
+//! What happened in a run of the first player's snake, summed up when the game ends, and the
+//! totals of every run, kept between sessions.
+
+use crate::GAME;
+use game_persistence::Schema;
+use game_platform::Platform;
+use serde::{Deserialize, Serialize};
+
+/// The `game_persistence` file the lifetime totals are saved to.
+const STATS_FILE: &str = "stats";
+
+/// What a snake crashed into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Crash {
+    /// Its own body.
+    Itself,
+    /// An obstacle or another snake, or poison that left too little of it.
+    Other,
+    Wall,
+}
+
+/// The run so far, from the start of the game.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct RunStats {
+    /// Food of every kind eaten.
+    pub food: u32,
+    /// Golden apples among it.
+    pub golden: u32,
+    /// The most segments the snake had.
+    pub longest: usize,
+    /// Steps the snake moved.
+    pub moves: u32,
+    pub other_crashes: u32,
+    /// Seconds played, leaving out the pauses.
+    pub seconds: f32,
+    pub self_crashes: u32,
+    pub wall_crashes: u32,
+}
+
+impl RunStats {
+    pub fn crash(&mut self, crash: Crash) {
+        match crash {
+            Crash::Itself => self.self_crashes += 1,
+            Crash::Other => self.other_crashes += 1,
+            Crash::Wall => self.wall_crashes += 1,
+        }
+    }
+
+    pub fn crashes(&self) -> u32 {
+        self.other_crashes + self.self_crashes + self.wall_crashes
+    }
+
+    /// The lines shown on the game over screen.
+    pub fn summary(&self) -> String {
+        format!(
+            "Food: {}  Moves: {}  Longest: {}\nTime: {}  Crashes: {} walls, {} self, {} other",
+            self.food,
+            self.moves,
+            self.longest,
+            minutes(self.seconds),
+            self.wall_crashes,
+            self.self_crashes,
+            self.other_crashes
+        )
+    }
+}
+
+/// Every run added up.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Serialize)]
+#[serde(default)]
+pub struct LifetimeStats {
+    pub games: u32,
+    /// The runs summed, but for the longest snake, which is the longest of all.
+    pub totals: RunStats,
+}
+
+/// The totals as saved at version 0, before golden apples were counted.
+#[derive(Deserialize)]
+struct LifetimeStatsV0 {
+    games: u32,
+    totals: RunStatsV0,
+}
+
+#[derive(Deserialize)]
+struct RunStatsV0 {
+    food: u32,
+    longest: usize,
+    moves: u32,
+    other_crashes: u32,
+    seconds: f32,
+    self_crashes: u32,
+    wall_crashes: u32,
+}
+
+impl From<LifetimeStatsV0> for LifetimeStats {
+    fn from(stats: LifetimeStatsV0) -> Self {
+        let totals = stats.totals;
+        Self {
+            games: stats.games,
+            totals: RunStats {
+                food: totals.food,
+                golden: 0,
+                longest: totals.longest,
+                moves: totals.moves,
+                other_crashes: totals.other_crashes,
+                seconds: totals.seconds,
+                self_crashes: totals.self_crashes,
+                wall_crashes: totals.wall_crashes,
+            },
+        }
+    }
+}
+
+impl LifetimeStats {
+    /// The saved totals, preferring the platform's copy in the cloud.
+    pub fn load(platform: &Platform) -> Self {
+        platform.load_or_default(GAME, STATS_FILE, &Self::schema())
+    }
+
+    pub fn save(&self, platform: &Platform) {
+        if let Err(error) = platform.save(GAME, STATS_FILE, self, &Self::schema()) {
+            eprintln!("could not save the stats: {}", error);
+        }
+    }
+
+    /// Counts a finished run.
+    pub fn add(&mut self, run: &RunStats) {
+        let totals = &mut self.totals;
+        self.games += 1;
+        totals.food += run.food;
+        totals.golden += run.golden;
+        totals.longest = totals.longest.max(run.longest);
+        totals.moves += run.moves;
+        totals.other_crashes += run.other_crashes;
+        totals.seconds += run.seconds;
+        totals.self_crashes += run.self_crashes;
+        totals.wall_crashes += run.wall_crashes;
+    }
+
+    /// Version 1 added `golden` to the totals.
+    fn schema() -> Schema<Self> {
+        Schema::new(1).migrate::<LifetimeStatsV0>(0)
+    }
+
+    /// The line shown under a run's summary.
+    pub fn summary(&self) -> String {
+        format!(
+            "All {} games: {} food, longest {}, {} played, {} crashes",
+            self.games,
+            self.totals.food,
+            self.totals.longest,
+            minutes(self.totals.seconds),
+            self.totals.crashes()
+        )
+    }
+}
+
+/// Seconds as minutes and seconds, e.g. `1:05`.
+fn minutes(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adds_runs_up() {
+        let mut run = RunStats {
+            food: 4,
+            longest: 6,
+            moves: 80,
+            seconds: 65.5,
+            ..Default::default()
+        };
+        run.crash(Crash::Wall);
+        assert_eq!(
+            run.summary(),
+            "Food: 4  Moves: 80  Longest: 6\nTime: 1:05  Crashes: 1 walls, 0 self, 0 other"
+        );
+        let mut lifetime = LifetimeStats::default();
+        lifetime.add(&run);
+        run.longest = 3;
+        run.crash(Crash::Itself);
+        lifetime.add(&run);
+        assert_eq!(lifetime.games, 2);
+        assert_eq!(lifetime.totals.food, 8);
+        assert_eq!(lifetime.totals.longest, 6);
+        assert_eq!(lifetime.totals.crashes(), 3);
+        assert_eq!(
+            lifetime.summary(),
+            "All 2 games: 8 food, longest 6, 2:11 played, 3 crashes"
+        );
+    }
+
+    #[test]
+    fn reads_every_saved_version() {
+        let old = "(version: 0, data: (games: 3, totals: (food: 12, longest: 9, moves: 400, \
+                   other_crashes: 1, seconds: 95.0, self_crashes: 1, wall_crashes: 1)))";
+        let lifetime: LifetimeStats =
+            game_persistence::decode(old, &LifetimeStats::schema()).unwrap();
+        assert_eq!(lifetime.games, 3);
+        assert_eq!(lifetime.totals.food, 12);
+        assert_eq!(lifetime.totals.golden, 0);
+        assert_eq!(lifetime.totals.crashes(), 3);
+        let saved = game_persistence::encode(&lifetime, &LifetimeStats::schema()).unwrap();
+        assert!(saved.contains("version: 1"));
+        let loaded: LifetimeStats =
+            game_persistence::decode(&saved, &LifetimeStats::schema()).unwrap();
+        assert_eq!(loaded, lifetime);
+    }
+}
@@ -0,0 +1,234 @@
+//! Boards are text files: the name on the first line, `size <n>` for a board of n by n squares,
+//! then one `ladder <from> <to>` or `snake <from> <to>` per line. Squares count from 1 in the
+//! bottom left corner and wind up the board row by row.
+
+use std::fmt;
+
+/// Smallest and largest number of squares along a side.
+pub const SIZES: (u32, u32) = (4, 12);
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ParseError {
+    Empty,
+    /// Something other than a `size`, `ladder` or `snake` line.
+    Line {
+        line: usize,
+    },
+    /// The size is missing or outside `SIZES`.
+    Size,
+    /// A square that is not on the board.
+    Square {
+        line: usize,
+        square: u32,
+    },
+    /// A ladder going down, or a snake going up.
+    Direction {
+        line: usize,
+    },
+    /// A square where two jumps start, or a jump ending where another starts.
+    Overlap {
+        line: usize,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Empty => write!(f, "no name"),
+            Self::Line { line } => write!(f, "line {} is not understood", line + 1),
+            Self::Size => write!(f, "no size between {} and {}", SIZES.0, SIZES.1),
+            Self::Square { line, square } => {
+                write!(f, "square {} in line {} is off the board", square, line + 1)
+            }
+            Self::Direction { line } => {
+                write!(f, "line {}: ladders lead up and snakes down", line + 1)
+            }
+            Self::Overlap { line } => write!(f, "line {} meets another jump", line + 1),
+        }
+    }
+}
+
+/// A ladder when it leads up, a snake when it leads down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Jump {
+    pub from: u32,
+    pub to: u32,
+}
+
+impl Jump {
+    pub fn is_ladder(self) -> bool {
+        self.to > self.from
+    }
+}
+
+/// Where a roll takes a token: the squares it steps on one by one, then the jump at the end.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Move {
+    pub steps: Vec<u32>,
+    pub jump: Option<Jump>,
+}
+
+impl Move {
+    pub fn end(&self) -> u32 {
+        match self.jump {
+            Some(jump) => jump.to,
+            None => *self.steps.last().unwrap(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Board {
+    pub name: String,
+    /// Squares along a side.
+    pub size: u32,
+    pub jumps: Vec<Jump>,
+}
+
+impl Board {
+    pub fn parse(text: &str) -> Result<Self, ParseError> {
+        let mut lines = text
+            .lines()
+            .map(str::trim)
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty());
+        let (_, name) = lines.next().ok_or(ParseError::Empty)?;
+        let mut board = Self {
+            name: name.to_string(),
+            size: 0,
+            jumps: Vec::new(),
+        };
+        for (line, text) in lines {
+            let words: Vec<&str> = text.split_whitespace().collect();
+            let numbers: Vec<u32> = words[1..]
+                .iter()
+                .map(|word| word.parse())
+                .collect::<Result<_, _>>()
+                .map_err(|_| ParseError::Line { line })?;
+            match (words[0], numbers.as_slice()) {
+                ("size", [size]) if (SIZES.0..=SIZES.1).contains(size) => board.size = *size,
+                ("size", _) => return Err(ParseError::Size),
+                (kind @ ("ladder" | "snake"), [from, to]) => {
+                    if board.size == 0 {
+                        return Err(ParseError::Size);
+                    }
+                    let jump = Jump {
+                        from: *from,
+                        to: *to,
+                    };
+                    for square in [jump.from, jump.to] {
+                        if square == 0 || square > board.last() {
+                            return Err(ParseError::Square { line, square });
+                        }
+                    }
+                    if jump.from == jump.to || jump.is_ladder() != (kind == "ladder") {
+                        return Err(ParseError::Direction { line });
+                    }
+                    let overlaps = board.jumps.iter().any(|other| {
+                        other.from == jump.from || other.from == jump.to || other.to == jump.from
+                    });
+                    if overlaps || jump.from == board.last() {
+                        return Err(ParseError::Overlap { line });
+                    }
+                    board.jumps.push(jump);
+                }
+                _ => return Err(ParseError::Line { line }),
+            }
+        }
+        if board.size == 0 {
+            return Err(ParseError::Size);
+        }
+        Ok(board)
+    }
+
+    /// Moves `roll` squares on from `square`, 0 being off the board before the first one. A
+    /// roll past the last square bounces back off it.
+    pub fn advance(&self, square: u32, roll: u32) -> Move {
+        let last = self.last();
+        let mut steps = Vec::new();
+        let mut position = square;
+        let mut forward = true;
+        for _ in 0..roll {
+            if position == last {
+                forward = false;
+            }
+            position = if forward { position + 1 } else { position - 1 };
+            steps.push(position);
+        }
+        let jump = self
+            .jumps
+            .iter()
+            .find(|jump| jump.from == position)
+            .copied();
+        Move { steps, jump }
+    }
+
+    /// The square to reach.
+    pub fn last(&self) -> u32 {
+        self.size * self.size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOARD: &str = "Test\nsize 4\nladder 2 11\nsnake 15 5\n";
+
+    #[test]
+    fn parses_a_board() {
+        let board = Board::parse(BOARD).unwrap();
+        assert_eq!(board.name, "Test");
+        assert_eq!(board.last(), 16);
+        assert_eq!(board.jumps.len(), 2);
+        assert!(board.jumps[0].is_ladder());
+        assert!(!board.jumps[1].is_ladder());
+        for text in [
+            include_str!("../assets/boards/1-classic.txt"),
+            include_str!("../assets/boards/2-quick.txt"),
+        ] {
+            assert!(Board::parse(text).is_ok());
+        }
+    }
+
+    #[test]
+    fn rejects_broken_boards() {
+        let parse = |text: &str| Board::parse(text).unwrap_err();
+        assert_eq!(parse(""), ParseError::Empty);
+        assert_eq!(parse("Test\nladder 2 5"), ParseError::Size);
+        assert_eq!(parse("Test\nsize 40"), ParseError::Size);
+        assert_eq!(
+            parse("Test\nsize 4\nladder 2 17"),
+            ParseError::Square {
+                line: 2,
+                square: 17
+            }
+        );
+        assert_eq!(
+            parse("Test\nsize 4\nsnake 2 9"),
+            ParseError::Direction { line: 2 }
+        );
+        assert_eq!(
+            parse("Test\nsize 4\nladder 2 9\nsnake 9 3"),
+            ParseError::Overlap { line: 3 }
+        );
+        assert_eq!(
+            parse("Test\nsize 4\nbridge 1"),
+            ParseError::Line { line: 2 }
+        );
+    }
+
+    #[test]
+    fn moves_climb_slide_and_bounce() {
+        let board = Board::parse(BOARD).unwrap();
+        let climb = board.advance(0, 2);
+        assert_eq!(climb.steps, [1, 2]);
+        assert_eq!(climb.end(), 11);
+        assert_eq!(board.advance(12, 3).end(), 5);
+        assert_eq!(board.advance(6, 4).end(), 10);
+        // Two past the last square bounces back to 14.
+        let bounce = board.advance(12, 6);
+        assert_eq!(bounce.steps, [13, 14, 15, 16, 15, 14]);
+        assert_eq!(bounce.end(), 14);
+    }
+}
@@ -0,0 +1,851 @@
+use bevy::{math::const_vec2, prelude::*};
+use board::{Board, Jump};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::{thread_rng, Rng};
+use std::{error::Error, f32::consts::PI, fs, path::PathBuf};
+
+pub mod board;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.16, 0.13, 0.2);
+const BOARD_CENTER: Vec2 = const_vec2!([-140.0, 15.0]);
+/// Boards that come with the game; more are read from `BOARDS_DIRECTORY`.
+const BOARDS: [&str; 2] = [
+    include_str!("../assets/boards/1-classic.txt"),
+    include_str!("../assets/boards/2-quick.txt"),
+];
+const BOARDS_DIRECTORY: &str = "boards";
+const BOARD_PIXELS: f32 = 540.0;
+/// Pause before the computer rolls.
+const DELAY: f32 = 0.7;
+const DIE_COLOR: Color = Color::rgb(0.96, 0.95, 0.9);
+const DIE_SIZE: f32 = 64.0;
+const DIE_Y: f32 = -60.0;
+const GAME: &str = "snakesladders";
+/// Pixels per second a token climbs a ladder or slides down a snake.
+const JUMP_SPEED: f32 = 320.0;
+const LADDER_COLOR: Color = Color::rgb(0.6, 0.4, 0.2);
+const LAST_SQUARE_COLOR: Color = Color::rgb(0.95, 0.8, 0.3);
+const NUMBER_COLOR: Color = Color::rgba(0.1, 0.1, 0.1, 0.6);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const PANEL_X: f32 = 295.0;
+const PIP_COLOR: Color = Color::rgb(0.12, 0.1, 0.1);
+const PLAYERS: [(&str, Color); 4] = [
+    ("Red", Color::rgb(0.9, 0.2, 0.2)),
+    ("Blue", Color::rgb(0.2, 0.45, 0.95)),
+    ("Green", Color::rgb(0.2, 0.75, 0.3)),
+    ("Yellow", Color::rgb(0.95, 0.85, 0.15)),
+];
+const SNAKE_COLORS: [Color; 3] = [
+    Color::rgb(0.25, 0.6, 0.2),
+    Color::rgb(0.75, 0.35, 0.15),
+    Color::rgb(0.45, 0.3, 0.7),
+];
+const SQUARE_COLORS: [Color; 2] = [Color::rgb(0.93, 0.88, 0.75), Color::rgb(0.82, 0.9, 0.85)];
+/// Seconds a token takes from one square to the next.
+const STEP: f32 = 0.16;
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.93, 0.9);
+/// Seconds the die tumbles before the token moves.
+const TUMBLE: f32 = 0.5;
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+/// The winner, written large over the board.
+#[derive(Component)]
+struct BannerText;
+
+/// The squares, ladders and snakes, spawned once per game.
+#[derive(Component)]
+struct BoardSprite;
+
+/// Runs out when the computer rolls.
+struct Delay(Timer);
+
+struct Game {
+    board: Board,
+    die: u8,
+    message: String,
+    phase: Phase,
+    players: Vec<Player>,
+    turn: usize,
+    winner: Option<usize>,
+}
+
+impl Game {
+    fn new(board: Board, seats: &[Seat]) -> Self {
+        let start = square_center(&board, 0);
+        let players = seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| **seat != Seat::Empty)
+            .map(|(index, seat)| Player {
+                color: PLAYERS[index].1,
+                computer: *seat == Seat::Computer,
+                name: PLAYERS[index].0,
+                position: start,
+                square: 0,
+            })
+            .collect();
+        Self {
+            board,
+            die: 0,
+            message: String::new(),
+            phase: Phase::Waiting,
+            players,
+            turn: 0,
+            winner: None,
+        }
+    }
+
+    fn player(&self) -> &Player {
+        &self.players[self.turn]
+    }
+
+    fn roll(&mut self) {
+        self.die = thread_rng().gen_range(1, 7);
+        self.phase = Phase::Tumbling(TUMBLE);
+        self.message.clear();
+    }
+
+    /// Sets off the token of the player to move along the squares the die gives.
+    fn start_move(&mut self) {
+        let from = self.player().square;
+        let step = self.board.advance(from, self.die as u32);
+        let mut path: Vec<Segment> = step
+            .steps
+            .iter()
+            .map(|square| Segment {
+                jump: None,
+                seconds: STEP,
+                target: square_center(&self.board, *square),
+            })
+            .collect();
+        if let Some(jump) = step.jump {
+            let target = square_center(&self.board, jump.to);
+            let distance = path.last().unwrap().target.distance(target);
+            path.push(Segment {
+                jump: Some(jump),
+                seconds: distance / JUMP_SPEED,
+                target,
+            });
+        }
+        let turn = self.turn;
+        self.players[turn].square = step.end();
+        self.phase = Phase::Moving {
+            elapsed: 0.0,
+            from: self.player().position,
+            path,
+        };
+    }
+
+    /// Ends the move: the game is won, a six rolls again, or the next player is up.
+    fn finish_turn(&mut self) {
+        self.phase = Phase::Waiting;
+        let player = self.player();
+        if player.square == self.board.last() {
+            self.winner = Some(self.turn);
+        } else if self.die == 6 {
+            self.message = format!("{} rolled a six and goes again", player.name);
+        } else {
+            self.turn = (self.turn + 1) % self.players.len();
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Won,
+}
+
+#[derive(Component)]
+struct HudText;
+
+/// Boards to choose from: the bundled ones, then those in the data directory.
+struct Library(Vec<Board>);
+
+struct MenuChoice {
+    board: usize,
+    /// The row being changed: the board, then the four seats.
+    row: usize,
+    seats: [Seat; 4],
+}
+
+#[derive(Component)]
+struct MessageText;
+
+enum Phase {
+    /// The player to move has yet to roll.
+    Waiting,
+    /// Seconds left until the die settles.
+    Tumbling(f32),
+    Moving {
+        /// Seconds into the first segment of `path`.
+        elapsed: f32,
+        from: Vec2,
+        path: Vec<Segment>,
+    },
+}
+
+struct Player {
+    color: Color,
+    computer: bool,
+    name: &'static str,
+    /// Where the token is drawn, which trails `square` while it moves.
+    position: Vec2,
+    square: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Seat {
+    Human,
+    Computer,
+    Empty,
+}
+
+impl Seat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Human => "Human",
+            Self::Computer => "Computer",
+            Self::Empty => "-",
+        }
+    }
+}
+
+/// A stretch of a token's way: one square on, or along a ladder or snake.
+struct Segment {
+    jump: Option<Jump>,
+    seconds: f32,
+    target: Vec2,
+}
+
+struct Sounds {
+    ladder: Handle<AudioSource>,
+    roll: Handle<AudioSource>,
+    snake: Handle<AudioSource>,
+    step: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+}
+
+pub struct SnakesLaddersPlugin;
+
+impl Plugin for SnakesLaddersPlugin {
+    fn build(&self, app: &mut App) {
+        let first = Board::parse(BOARDS[0]).expect("bundled boards are valid");
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Library(Vec::new()))
+            .insert_resource(MenuChoice {
+                board: 0,
+                row: 1,
+                seats: [Seat::Human, Seat::Computer, Seat::Empty, Seat::Empty],
+            })
+            .insert_resource(Game::new(first, &[Seat::Human]))
+            .insert_resource(Delay(Timer::from_seconds(DELAY, false)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_startup_system(library_load)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(board_despawn))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(board_spawn))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(computer_turn)
+                    .with_system(turn_update),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Won).with_system(won_input))
+            .add_system(escape_input)
+            .add_system(pieces_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Snakes and Ladders".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// A straight piece of `width` from `start` to `end`, for ladder rails and snake bodies.
+fn bar(commands: &mut Commands, color: Color, start: Vec2, end: Vec2, width: f32, z: f32) {
+    let along = end - start;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(along.length(), width)),
+                ..Default::default()
+            },
+            transform: Transform {
+                translation: ((start + end) / 2.0).extend(z),
+                rotation: Quat::from_rotation_z(along.y.atan2(along.x)),
+                ..Default::default()
+            },
+            ..Default::default()
+        })
+        .insert(BoardSprite);
+}
+
+fn board_despawn(mut commands: Commands, sprites: Query<Entity, With<BoardSprite>>) {
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Lays out the squares with their numbers, then the ladders and the snakes over them.
+fn board_spawn(mut commands: Commands, font: Res<HudFont>, game: Res<Game>) {
+    let board = &game.board;
+    let cell = cell_size(board);
+    for square in 1..=board.last() {
+        let center = square_center(board, square);
+        let color = if square == board.last() {
+            LAST_SQUARE_COLOR
+        } else {
+            SQUARE_COLORS[((square - 1) / board.size + (square - 1) % board.size) as usize % 2]
+        };
+        commands
+            .spawn_bundle(rectangle(color, Vec2::splat(cell - 2.0), center, 0.0))
+            .insert(BoardSprite);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                square.to_string(),
+                cell * 0.26,
+                NUMBER_COLOR,
+                (center + Vec2::new(-cell * 0.25, cell * 0.3)).extend(1.0),
+            ))
+            .insert(BoardSprite);
+    }
+
+    let mut snakes = 0;
+    for jump in &board.jumps {
+        let start = square_center(board, jump.from);
+        let end = square_center(board, jump.to);
+        let along = end - start;
+        let across = along.perp().normalize();
+        if jump.is_ladder() {
+            for side in [-1.0, 1.0] {
+                let offset = across * side * cell * 0.16;
+                bar(
+                    &mut commands,
+                    LADDER_COLOR,
+                    start + offset,
+                    end + offset,
+                    4.0,
+                    3.0,
+                );
+            }
+            let rungs = (along.length() / (cell * 0.28)) as usize;
+            for rung in 1..rungs {
+                let middle = start + along * rung as f32 / rungs as f32;
+                let offset = across * cell * 0.16;
+                bar(
+                    &mut commands,
+                    LADDER_COLOR,
+                    middle - offset,
+                    middle + offset,
+                    3.0,
+                    3.0,
+                );
+            }
+            continue;
+        }
+        // A snake wiggles from its head on the higher square down to the tip of its tail.
+        let color = SNAKE_COLORS[snakes % SNAKE_COLORS.len()];
+        snakes += 1;
+        let waves = (along.length() / cell).round().max(2.0);
+        let pieces = (along.length() / (cell * 0.12)) as usize;
+        let point = |t: f32| start + along * t + across * (t * waves * PI).sin() * cell * 0.2;
+        for piece in 0..pieces {
+            let (from, to) = (
+                piece as f32 / pieces as f32,
+                (piece + 1) as f32 / pieces as f32,
+            );
+            let width = cell * (0.22 - 0.16 * from);
+            let shade = if piece % 4 < 2 { 1.0 } else { 0.8 };
+            let striped = Color::rgb(color.r() * shade, color.g() * shade, color.b() * shade);
+            bar(
+                &mut commands,
+                striped,
+                point(from),
+                point(to) + (point(to) - point(from)).normalize() * width * 0.3,
+                width,
+                4.0 + from,
+            );
+        }
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                "\u{25CF}",
+                cell * 0.55,
+                color,
+                start.extend(5.5),
+            ))
+            .insert(BoardSprite);
+        for side in [-1.0, 1.0] {
+            let eye = start + across * side * cell * 0.07 + along.normalize() * -cell * 0.04;
+            commands
+                .spawn_bundle(rectangle(Color::BLACK, Vec2::splat(3.0), eye, 5.6))
+                .insert(BoardSprite);
+        }
+    }
+}
+
+fn cell_size(board: &Board) -> f32 {
+    BOARD_PIXELS / board.size as f32
+}
+
+/// The computer rolls for its seats after a short pause.
+fn computer_turn(
+    time: Res<Time>,
+    mut delay: ResMut<Delay>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.player().computer || !matches!(game.phase, Phase::Waiting) {
+        delay.0.reset();
+        return;
+    }
+    if delay.0.tick(time.delta()).finished() {
+        delay.0.reset();
+        game.roll();
+        audio.play(sounds.roll.clone());
+    }
+}
+
+/// Offsets of the pips on a die face, in steps from its center.
+fn die_pips(die: u8) -> &'static [(i8, i8)] {
+    match die {
+        1 => &[(0, 0)],
+        2 => &[(-1, 1), (1, -1)],
+        3 => &[(-1, 1), (0, 0), (1, -1)],
+        4 => &[(-1, 1), (1, 1), (-1, -1), (1, -1)],
+        5 => &[(-1, 1), (1, 1), (0, 0), (-1, -1), (1, -1)],
+        _ => &[(-1, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (1, -1)],
+    }
+}
+
+/// Escape leaves a game for the menu at any time.
+fn escape_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() != GameState::Menu {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Reads the bundled boards and those in the data directory.
+fn library_load(mut library: ResMut<Library>) {
+    library.0 = BOARDS
+        .iter()
+        .map(|text| Board::parse(text).expect("bundled boards are valid"))
+        .collect();
+    match load_custom() {
+        Ok(boards) => library.0.extend(boards),
+        Err(error) => eprintln!("could not load boards: {}", error),
+    }
+}
+
+fn load_custom() -> Result<Vec<Board>, Box<dyn Error>> {
+    let directory = game_persistence::directory(GAME)?.join(BOARDS_DIRECTORY);
+    if !directory.exists() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|extension| extension.to_str()) == Some("txt"))
+        .collect();
+    paths.sort();
+    let mut boards = Vec::new();
+    for path in paths {
+        match Board::parse(&fs::read_to_string(&path)?) {
+            Ok(board) => boards.push(board),
+            Err(error) => eprintln!("skipping {}: {}", path.display(), error),
+        }
+    }
+    Ok(boards)
+}
+
+/// Up/Down picks a row, Left/Right changes the board or who sits in a seat, Enter starts once
+/// at least two seats are taken.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    library: Res<Library>,
+    mut choice: ResMut<MenuChoice>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    const ORDER: [Seat; 3] = [Seat::Human, Seat::Computer, Seat::Empty];
+    if menu.just_pressed(Navigate::Up) {
+        choice.row = choice.row.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.row = (choice.row + 1).min(4);
+    } else if menu.horizontal() != 0 {
+        let left = menu.just_pressed(Navigate::Left);
+        if choice.row == 0 {
+            let count = library.0.len();
+            let step = if left { count - 1 } else { 1 };
+            choice.board = (choice.board + step) % count;
+            return;
+        }
+        let seat = choice.row - 1;
+        let index = ORDER
+            .iter()
+            .position(|other| *other == choice.seats[seat])
+            .unwrap();
+        let step = if left { ORDER.len() - 1 } else { 1 };
+        choice.seats[seat] = ORDER[(index + step) % ORDER.len()];
+    } else if menu.just_pressed(Navigate::Confirm)
+        && choice
+            .seats
+            .iter()
+            .filter(|seat| **seat != Seat::Empty)
+            .count()
+            >= 2
+    {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(library.0[choice.board].clone(), &choice.seats);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Draws the tokens, side by side where they share a square, and the die.
+fn pieces_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() == GameState::Menu {
+        return;
+    }
+    let cell = cell_size(&game.board);
+    for (index, player) in game.players.iter().enumerate() {
+        let corner = Vec2::new(
+            if index % 2 == 0 { -1.0 } else { 1.0 },
+            if index < 2 { 1.0 } else { -1.0 },
+        );
+        let center = player.position + corner * cell * 0.18;
+        let z = if index == game.turn { 12.0 } else { 10.0 };
+        for (glyph, color, size) in [
+            ("\u{25CF}", Color::BLACK, 0.62),
+            ("\u{25CF}", player.color, 0.5),
+        ] {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    glyph,
+                    cell * size,
+                    color,
+                    center.extend(z + size),
+                ))
+                .insert(Actor);
+        }
+    }
+    if *state.current() == GameState::Won {
+        commands
+            .spawn_bundle(rectangle(
+                OVERLAY_COLOR,
+                Vec2::splat(BOARD_PIXELS),
+                BOARD_CENTER,
+                20.0,
+            ))
+            .insert(Actor);
+    }
+
+    if game.die == 0 {
+        return;
+    }
+    let mut face = game.die;
+    let mut center = Vec2::new(PANEL_X, DIE_Y);
+    if let Phase::Tumbling(_) = game.phase {
+        let mut rng = thread_rng();
+        face = rng.gen_range(1, 7);
+        center += Vec2::new(rng.gen_range(-4.0, 4.0), rng.gen_range(-4.0, 4.0));
+    }
+    commands
+        .spawn_bundle(rectangle(DIE_COLOR, Vec2::splat(DIE_SIZE), center, 2.0))
+        .insert(Actor);
+    for (column, row) in die_pips(face) {
+        let offset = Vec2::new(*column as f32, *row as f32) * DIE_SIZE * 0.27;
+        commands
+            .spawn_bundle(rectangle(
+                PIP_COLOR,
+                Vec2::splat(11.0),
+                center + offset,
+                3.0,
+            ))
+            .insert(Actor);
+    }
+}
+
+/// Space, Enter or a click rolls the die for a human player.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut game: ResMut<Game>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.player().computer || !matches!(game.phase, Phase::Waiting) {
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return])
+        || mouse_input.just_pressed(MouseButton::Left)
+    {
+        game.roll();
+        audio.play(sounds.roll.clone());
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    let mut hud = game_hud::world_text(
+        &font,
+        "",
+        20.0,
+        TEXT_COLOR,
+        Vec3::new(PANEL_X - 120.0, 170.0, 10.0),
+    );
+    hud.text.alignment.horizontal = HorizontalAlign::Left;
+    commands.spawn_bundle(hud).insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Vec3::new(PANEL_X, -180.0, 10.0),
+        ))
+        .insert(MessageText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            48.0,
+            TEXT_COLOR,
+            BOARD_CENTER.extend(25.0),
+        ))
+        .insert(BannerText);
+
+    commands.insert_resource(Sounds {
+        ladder: audio_sources.add(
+            Tone::new(Waveform::Square, 330.0, 0.5)
+                .slide(990.0)
+                .volume(0.15)
+                .into(),
+        ),
+        roll: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.3),
+            Tone::new(Waveform::Noise, 0.0, 0.04).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.08).fade().volume(0.3),
+        ])),
+        snake: audio_sources.add(
+            Tone::new(Waveform::Triangle, 700.0, 0.6)
+                .slide(140.0)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        step: audio_sources.add(Tone::new(Waveform::Sine, 880.0, 0.03).volume(0.2).into()),
+        win: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.15),
+            Tone::new(Waveform::Triangle, 784.0, 0.15),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4),
+        ])),
+    });
+}
+
+/// Center of `square`; square 0 is the start, just below the first one.
+fn square_center(board: &Board, square: u32) -> Vec2 {
+    let cell = cell_size(board);
+    let corner = BOARD_CENTER - Vec2::splat(BOARD_PIXELS / 2.0);
+    if square == 0 {
+        return corner + Vec2::new(cell / 2.0, -cell * 0.45);
+    }
+    let index = square - 1;
+    let row = index / board.size;
+    let mut column = index % board.size;
+    // Every other row runs from right to left.
+    if row % 2 == 1 {
+        column = board.size - 1 - column;
+    }
+    corner + Vec2::new(column as f32 + 0.5, row as f32 + 0.5) * cell
+}
+
+fn texts_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    choice: Res<MenuChoice>,
+    library: Res<Library>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+        QueryState<&mut Text, With<BannerText>>,
+    )>,
+) {
+    if !game.is_changed() && !state.is_changed() && !choice.is_changed() {
+        return;
+    }
+    let (hud, message, banner) = match state.current() {
+        GameState::Menu => {
+            let marker = |row| if row == choice.row { ">" } else { " " };
+            let mut menu = format!(
+                "SNAKES AND LADDERS\n\n{} Board: {}\n\n",
+                marker(0),
+                library
+                    .0
+                    .get(choice.board)
+                    .map_or("", |board| board.name.as_str())
+            );
+            for (index, seat) in choice.seats.iter().enumerate() {
+                menu.push_str(&format!(
+                    "{} {:<7}{}\n",
+                    marker(index + 1),
+                    PLAYERS[index].0,
+                    seat.name()
+                ));
+            }
+            (
+                menu,
+                "Up/Down: row   Left/Right: change\nEnter: start (two players or more)".to_string(),
+                String::new(),
+            )
+        }
+        GameState::Playing | GameState::Won => {
+            let mut standings = format!("{}\n\n", game.board.name);
+            for (index, player) in game.players.iter().enumerate() {
+                let marker = if index == game.turn { ">" } else { " " };
+                let kind = if player.computer { "CPU" } else { "" };
+                standings.push_str(&format!(
+                    "{} {:<7}{:<4}{:>4}\n",
+                    marker, player.name, kind, player.square
+                ));
+            }
+            let help = match (game.winner, &game.phase) {
+                (Some(_), _) => "Enter: menu".to_string(),
+                (None, Phase::Waiting) if !game.player().computer => {
+                    format!("{}: Space / click to roll", game.player().name)
+                }
+                _ => String::new(),
+            };
+            let banner = match game.winner {
+                Some(winner) => format!("{} wins!", game.players[winner].name),
+                None => String::new(),
+            };
+            (standings, format!("{}\n\n{}", game.message, help), banner)
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = banner.clone();
+    }
+}
+
+/// Settles the die, then walks the token square by square and along any ladder or snake.
+fn turn_update(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let delta = time.delta_seconds();
+    let game = &mut *game;
+    let (position, finished) = match &mut game.phase {
+        Phase::Waiting => return,
+        Phase::Tumbling(left) => {
+            *left -= delta;
+            if *left <= 0.0 {
+                game.start_move();
+            }
+            return;
+        }
+        Phase::Moving {
+            elapsed,
+            from,
+            path,
+        } => {
+            *elapsed += delta;
+            let progress = (*elapsed / path[0].seconds).min(1.0);
+            let position = from.lerp(path[0].target, progress);
+            if progress < 1.0 {
+                (position, false)
+            } else {
+                *elapsed = 0.0;
+                *from = position;
+                let done = path.remove(0);
+                if done.jump.is_none() {
+                    audio.play(sounds.step.clone());
+                }
+                if let Some(jump) = path.first().and_then(|next| next.jump) {
+                    audio.play(if jump.is_ladder() {
+                        sounds.ladder.clone()
+                    } else {
+                        sounds.snake.clone()
+                    });
+                }
+                (position, path.is_empty())
+            }
+        }
+    };
+    game.players[game.turn].position = position;
+    if !finished {
+        return;
+    }
+    game.finish_turn();
+    if let Some(winner) = game.winner {
+        game.message = format!(
+            "{} reached square {}",
+            game.players[winner].name,
+            game.board.last()
+        );
+        audio.play(sounds.win.clone());
+        state.set(GameState::Won).unwrap();
+    }
+}
+
+fn won_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
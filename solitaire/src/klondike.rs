@@ -0,0 +1,296 @@
+use game_cards::Card;
+use rand::Rng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pile {
+    Stock,
+    Waste,
+    Foundation(usize),
+    Tableau(usize),
+}
+
+impl Pile {
+    pub fn all() -> impl Iterator<Item = Pile> {
+        [Pile::Stock, Pile::Waste]
+            .into_iter()
+            .chain((0..4).map(Pile::Foundation))
+            .chain((0..7).map(Pile::Tableau))
+    }
+}
+
+/// A tableau column: the first `hidden` cards lie face down.
+#[derive(Clone, Default)]
+pub struct Column {
+    pub cards: Vec<Card>,
+    pub hidden: usize,
+}
+
+#[derive(Clone)]
+pub struct Klondike {
+    pub draw_three: bool,
+    pub foundations: [Vec<Card>; 4],
+    pub moves: u32,
+    pub stock: Vec<Card>,
+    pub tableau: [Column; 7],
+    pub waste: Vec<Card>,
+}
+
+impl Klondike {
+    pub fn deal(draw_three: bool, rng: &mut impl Rng) -> Self {
+        let mut stock = game_cards::shuffled_decks(1, rng);
+        let mut tableau: [Column; 7] = Default::default();
+        for (index, column) in tableau.iter_mut().enumerate() {
+            column.cards = stock.split_off(stock.len() - index - 1);
+            column.hidden = index;
+        }
+        Self {
+            draw_three,
+            foundations: Default::default(),
+            moves: 0,
+            stock,
+            tableau,
+            waste: Vec::new(),
+        }
+    }
+
+    /// The cards of a pile from bottom to top.
+    pub fn cards(&self, pile: Pile) -> &[Card] {
+        match pile {
+            Pile::Stock => &self.stock,
+            Pile::Waste => &self.waste,
+            Pile::Foundation(index) => &self.foundations[index],
+            Pile::Tableau(index) => &self.tableau[index].cards,
+        }
+    }
+
+    /// Whether the cards from `index` to the top of `from` may be put onto `to`.
+    pub fn can_move(&self, from: Pile, index: usize, to: Pile) -> bool {
+        let cards = self.cards(from);
+        if from == to || index >= cards.len() {
+            return false;
+        }
+        let moving = &cards[index..];
+        let movable = match from {
+            Pile::Stock => false,
+            Pile::Waste | Pile::Foundation(_) => moving.len() == 1,
+            Pile::Tableau(column) => index >= self.tableau[column].hidden,
+        };
+        if !movable {
+            return false;
+        }
+        let first = moving[0];
+        match to {
+            Pile::Stock | Pile::Waste => false,
+            Pile::Foundation(foundation) => {
+                moving.len() == 1
+                    && match self.foundations[foundation].last() {
+                        None => first.rank == 1,
+                        Some(top) => top.suit == first.suit && top.rank + 1 == first.rank,
+                    }
+            }
+            Pile::Tableau(column) => match self.tableau[column].cards.last() {
+                None => first.rank == 13,
+                Some(top) => top.suit.is_red() != first.suit.is_red() && top.rank == first.rank + 1,
+            },
+        }
+    }
+
+    /// Turns one or three cards from the stock, or turns the waste over when the stock is empty.
+    pub fn draw(&mut self) -> bool {
+        if self.stock.is_empty() {
+            if self.waste.is_empty() {
+                return false;
+            }
+            self.stock = self.waste.drain(..).rev().collect();
+        } else {
+            let count = if self.draw_three { 3 } else { 1 };
+            for _ in 0..count.min(self.stock.len()) {
+                let card = self.stock.pop().unwrap();
+                self.waste.push(card);
+            }
+        }
+        self.moves += 1;
+        true
+    }
+
+    /// The foundation a single card could go to, if any.
+    pub fn foundation_for(&self, from: Pile) -> Option<Pile> {
+        let index = self.cards(from).len().checked_sub(1)?;
+        (0..4)
+            .map(Pile::Foundation)
+            .find(|foundation| self.can_move(from, index, *foundation))
+    }
+
+    /// Nothing is left to uncover, so every remaining card can go to the foundations in order.
+    pub fn is_trivially_winnable(&self) -> bool {
+        self.stock.is_empty()
+            && self.waste.len() <= 1
+            && self.tableau.iter().all(|column| column.hidden == 0)
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.foundations
+            .iter()
+            .all(|foundation| foundation.len() == 13)
+    }
+
+    /// Moves cards if the move is legal and uncovers the next tableau card.
+    pub fn move_cards(&mut self, from: Pile, index: usize, to: Pile) -> bool {
+        if !self.can_move(from, index, to) {
+            return false;
+        }
+        let moving = match from {
+            Pile::Stock => return false,
+            Pile::Waste => self.waste.split_off(index),
+            Pile::Foundation(foundation) => self.foundations[foundation].split_off(index),
+            Pile::Tableau(column) => {
+                let column = &mut self.tableau[column];
+                let moving = column.cards.split_off(index);
+                column.hidden = column.hidden.min(column.cards.len().saturating_sub(1));
+                moving
+            }
+        };
+        match to {
+            Pile::Foundation(foundation) => self.foundations[foundation].extend(moving),
+            Pile::Tableau(column) => self.tableau[column].cards.extend(moving),
+            Pile::Stock | Pile::Waste => unreachable!("can_move rejects these targets"),
+        }
+        self.moves += 1;
+        true
+    }
+
+    /// The next step of an auto-complete: the lowest card that can go to a foundation.
+    pub fn next_auto_move(&self) -> Option<(Pile, Pile)> {
+        let sources = std::iter::once(Pile::Waste).chain((0..7).map(Pile::Tableau));
+        sources
+            .filter_map(|from| {
+                let card = *self.cards(from).last()?;
+                Some((card.rank, from, self.foundation_for(from)?))
+            })
+            .min_by_key(|(rank, _, _)| *rank)
+            .map(|(_, from, to)| (from, to))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_cards::Suit;
+    use rand::thread_rng;
+
+    fn empty() -> Klondike {
+        Klondike {
+            draw_three: false,
+            foundations: Default::default(),
+            moves: 0,
+            stock: Vec::new(),
+            tableau: Default::default(),
+            waste: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn deal_lays_out_the_tableau() {
+        let game = Klondike::deal(false, &mut thread_rng());
+        for (index, column) in game.tableau.iter().enumerate() {
+            assert_eq!(column.cards.len(), index + 1);
+            assert_eq!(column.hidden, index);
+        }
+        assert_eq!(game.stock.len(), 52 - 28);
+        assert!(game.waste.is_empty());
+    }
+
+    #[test]
+    fn tableau_builds_down_in_alternating_colours() {
+        let mut game = empty();
+        game.tableau[0].cards = vec![Card::new(8, Suit::Spades)];
+        game.waste = vec![Card::new(7, Suit::Hearts)];
+        assert!(game.can_move(Pile::Waste, 0, Pile::Tableau(0)));
+        game.waste = vec![Card::new(7, Suit::Clubs)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Tableau(0)));
+        game.waste = vec![Card::new(6, Suit::Hearts)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Tableau(0)));
+        game.waste = vec![Card::new(9, Suit::Hearts)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Tableau(0)));
+    }
+
+    #[test]
+    fn only_kings_go_to_empty_columns() {
+        let mut game = empty();
+        game.waste = vec![Card::new(12, Suit::Hearts)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Tableau(0)));
+        game.waste = vec![Card::new(13, Suit::Hearts)];
+        assert!(game.move_cards(Pile::Waste, 0, Pile::Tableau(0)));
+        assert_eq!(game.tableau[0].cards, vec![Card::new(13, Suit::Hearts)]);
+        assert_eq!(game.moves, 1);
+    }
+
+    #[test]
+    fn foundations_build_up_by_suit_from_the_ace() {
+        let mut game = empty();
+        game.waste = vec![Card::new(2, Suit::Clubs)];
+        assert_eq!(game.foundation_for(Pile::Waste), None);
+        game.waste = vec![Card::new(1, Suit::Clubs)];
+        assert!(game.move_cards(Pile::Waste, 0, Pile::Foundation(0)));
+        game.waste = vec![Card::new(2, Suit::Spades)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Foundation(0)));
+        game.waste = vec![Card::new(3, Suit::Clubs)];
+        assert!(!game.can_move(Pile::Waste, 0, Pile::Foundation(0)));
+        game.waste = vec![Card::new(2, Suit::Clubs)];
+        assert_eq!(game.foundation_for(Pile::Waste), Some(Pile::Foundation(0)));
+    }
+
+    #[test]
+    fn moving_a_run_uncovers_the_card_below() {
+        let mut game = empty();
+        game.tableau[0] = Column {
+            cards: vec![
+                Card::new(4, Suit::Clubs),
+                Card::new(10, Suit::Hearts),
+                Card::new(9, Suit::Spades),
+            ],
+            hidden: 1,
+        };
+        game.tableau[1].cards = vec![Card::new(11, Suit::Clubs)];
+        assert!(!game.can_move(Pile::Tableau(0), 0, Pile::Tableau(1)));
+        assert!(game.move_cards(Pile::Tableau(0), 1, Pile::Tableau(1)));
+        assert_eq!(game.tableau[1].cards.len(), 3);
+        assert_eq!(game.tableau[0].hidden, 0);
+    }
+
+    #[test]
+    fn draw_turns_the_waste_over_when_the_stock_runs_out() {
+        let mut game = empty();
+        game.draw_three = true;
+        game.stock = (1..=4).map(|rank| Card::new(rank, Suit::Hearts)).collect();
+        assert!(game.draw());
+        assert_eq!(game.waste.len(), 3);
+        assert!(game.draw());
+        assert!(game.stock.is_empty());
+        assert!(game.draw());
+        assert_eq!(game.stock.len(), 4);
+        assert_eq!(game.stock.last(), Some(&Card::new(4, Suit::Hearts)));
+        assert!(!empty().draw());
+    }
+
+    #[test]
+    fn auto_complete_plays_out_the_lowest_cards_first() {
+        let mut game = empty();
+        for (column, suit) in [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades]
+            .into_iter()
+            .enumerate()
+        {
+            game.tableau[column].cards = (1..=13).rev().map(|rank| Card::new(rank, suit)).collect();
+        }
+        assert!(game.is_trivially_winnable());
+        let mut played = 0;
+        while let Some((from, to)) = game.next_auto_move() {
+            let rank = game.cards(from).last().unwrap().rank;
+            assert_eq!(rank as usize, played / 4 + 1);
+            assert!(game.move_cards(from, game.cards(from).len() - 1, to));
+            played += 1;
+        }
+        assert_eq!(played, 52);
+        assert!(game.is_won());
+    }
+}
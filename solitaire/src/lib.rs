@@ -0,0 +1,616 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use klondike::{Klondike, Pile};
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+
+mod klondike;
+
+const AUTO_MOVE_INTERVAL: f32 = 0.12;
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.35, 0.15);
+const CARD_HEIGHT: f32 = 118.0;
+const CARD_SIZE: Vec2 = const_vec2!([84.0, CARD_HEIGHT]);
+const COLUMN_SPACING: f32 = 100.0;
+const FAN_HIDDEN: f32 = 10.0;
+const FAN_SHOWN: f32 = 26.0;
+const FAN_WASTE: f32 = 18.0;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const SLOT_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.2);
+const STATISTICS_FILE: &str = "statistics";
+
+pub const WINDOW_HEIGHT: f32 = 760.0;
+pub const WINDOW_WIDTH: f32 = 7.0 * COLUMN_SPACING + 40.0;
+
+const TOP_ROW_Y: f32 = WINDOW_HEIGHT / 2.0 - HUD_HEIGHT - CARD_HEIGHT / 2.0 - 10.0;
+const TABLEAU_Y: f32 = TOP_ROW_Y - CARD_HEIGHT - 30.0;
+
+struct AutoMoveTimer(Timer);
+
+#[derive(Component)]
+struct CardSprite {
+    index: usize,
+    pile: Pile,
+}
+
+struct Clock(f32);
+
+/// Cards picked up with the mouse: everything from `index` to the top of `from`.
+struct Drag {
+    from: Pile,
+    index: usize,
+    /// From the cursor to the center of the grabbed card.
+    offset: Vec2,
+}
+
+struct Dragging(Option<Drag>);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    Won,
+}
+
+#[derive(Component)]
+struct HudText;
+
+struct Sounds {
+    deal: Handle<AudioSource>,
+    invalid: Handle<AudioSource>,
+    place: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+struct Statistics {
+    best_streak: u32,
+    best_time: Option<f32>,
+    fewest_moves: Option<u32>,
+    played: u32,
+    streak: u32,
+    won: u32,
+}
+
+impl Statistics {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("solitaire", STATISTICS_FILE, self) {
+            eprintln!("could not save statistics: {}", error);
+        }
+    }
+}
+
+/// The game in progress plus every earlier position for undo.
+struct Table {
+    game: Klondike,
+    history: Vec<Klondike>,
+}
+
+impl Table {
+    /// Runs a move on a copy and only keeps it (and the undo step) if it changed anything.
+    fn apply(&mut self, action: impl FnOnce(&mut Klondike) -> bool) -> bool {
+        let before = self.game.clone();
+        if action(&mut self.game) {
+            self.history.push(before);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[derive(Component)]
+struct WonText;
+
+pub struct SolitairePlugin;
+
+impl Plugin for SolitairePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Table {
+                game: Klondike::deal(false, &mut thread_rng()),
+                history: Vec::new(),
+            })
+            .insert_resource(Clock(0.0))
+            .insert_resource(AutoMoveTimer(Timer::from_seconds(AUTO_MOVE_INTERVAL, true)))
+            .insert_resource(Dragging(None))
+            .insert_resource(game_persistence::load_or_default::<Statistics>(
+                "solitaire",
+                STATISTICS_FILE,
+            ))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Playing)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(new_game))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(mouse_input)
+                    .with_system(keyboard_input)
+                    .with_system(auto_complete)
+                    .with_system(game_clock)
+                    .with_system(win_check),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Won).with_system(won))
+            .add_system_set(SystemSet::on_update(GameState::Won).with_system(won_input))
+            .add_system_set(SystemSet::on_exit(GameState::Won).with_system(won_exit))
+            .add_system(table_render)
+            .add_system(drag_follow)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Klondike Solitaire".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn auto_complete(
+    time: Res<Time>,
+    mut timer: ResMut<AutoMoveTimer>,
+    mut table: ResMut<Table>,
+    drag: Res<Dragging>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if drag.0.is_some() || !table.game.is_trivially_winnable() || table.game.is_won() {
+        return;
+    }
+    if timer.0.tick(time.delta()).just_finished() {
+        if let Some((from, to)) = table.game.next_auto_move() {
+            let index = table.game.cards(from).len() - 1;
+            table.apply(|game| game.move_cards(from, index, to));
+            audio.play(sounds.place.clone());
+        }
+    }
+}
+
+fn card_translation(game: &Klondike, pile: Pile, index: usize) -> Vec3 {
+    let z = 1.0 + index as f32 * 0.1;
+    match pile {
+        Pile::Stock => Vec3::new(column_x(0), TOP_ROW_Y, z),
+        Pile::Waste => {
+            // Only the top three waste cards are fanned out.
+            let fanned = (index + 3).saturating_sub(game.waste.len().max(3));
+            Vec3::new(column_x(1) + fanned as f32 * FAN_WASTE, TOP_ROW_Y, z)
+        }
+        Pile::Foundation(foundation) => Vec3::new(column_x(3 + foundation), TOP_ROW_Y, z),
+        Pile::Tableau(column) => {
+            let hidden = game.tableau[column].hidden;
+            let y = TABLEAU_Y
+                - index.min(hidden) as f32 * FAN_HIDDEN
+                - index.saturating_sub(hidden) as f32 * FAN_SHOWN;
+            Vec3::new(column_x(column), y, z)
+        }
+    }
+}
+
+fn column_x(column: usize) -> f32 {
+    (column as f32 - 3.0) * COLUMN_SPACING
+}
+
+fn contains(center: Vec3, point: Vec2) -> bool {
+    let offset = (point - center.truncate()).abs();
+    offset.x < CARD_SIZE.x / 2.0 && offset.y < CARD_SIZE.y / 2.0
+}
+
+fn drag_follow(
+    windows: Res<Windows>,
+    drag: Res<Dragging>,
+    mut sprites: Query<(&CardSprite, &mut Transform)>,
+) {
+    let (drag, cursor) = match (&drag.0, game_hud::cursor_world_position(&windows)) {
+        (Some(drag), Some(cursor)) => (drag, cursor),
+        _ => return,
+    };
+    for (sprite, mut transform) in sprites.iter_mut() {
+        if sprite.pile == drag.from && sprite.index >= drag.index {
+            let fan = (sprite.index - drag.index) as f32 * FAN_SHOWN;
+            transform.translation = (cursor + drag.offset - Vec2::new(0.0, fan))
+                .extend(50.0 + sprite.index as f32 * 0.1);
+        }
+    }
+}
+
+fn game_clock(time: Res<Time>, mut clock: ResMut<Clock>) {
+    clock.0 += time.delta_seconds();
+}
+
+/// The topmost card under the cursor, or the empty slot of a pile.
+fn hit_test(game: &Klondike, cursor: Vec2) -> Option<(Pile, usize)> {
+    for pile in Pile::all() {
+        let cards = game.cards(pile);
+        for index in (0..cards.len()).rev() {
+            if contains(card_translation(game, pile, index), cursor) {
+                return Some((pile, index));
+            }
+        }
+        if cards.is_empty() && contains(card_translation(game, pile, 0), cursor) {
+            return Some((pile, 0));
+        }
+    }
+    None
+}
+
+fn hud_update(
+    table: Res<Table>,
+    clock: Res<Clock>,
+    statistics: Res<Statistics>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let seconds = clock.0 as u32;
+    let value = format!(
+        "DRAW {}   MOVES {}   TIME {}:{:02}   WON {}/{}   STREAK {}",
+        if table.game.draw_three { 3 } else { 1 },
+        table.game.moves,
+        seconds / 60,
+        seconds % 60,
+        statistics.won,
+        statistics.played,
+        statistics.streak
+    );
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn keyboard_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut table: ResMut<Table>,
+    mut clock: ResMut<Clock>,
+    mut statistics: ResMut<Statistics>,
+    mut drag: ResMut<Dragging>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::U, KeyCode::Back]) {
+        if let Some(previous) = table.history.pop() {
+            table.game = previous;
+        }
+    } else if keyboard_input.just_pressed(KeyCode::D) {
+        // Switching the draw mode deals a new game, which counts as giving up the current one.
+        table.game.draw_three = !table.game.draw_three;
+        give_up(&mut statistics, &table);
+        deal(
+            &mut table,
+            &mut clock,
+            &mut statistics,
+            &mut drag,
+            &audio,
+            &sounds,
+        );
+    } else if keyboard_input.just_pressed(KeyCode::N) {
+        give_up(&mut statistics, &table);
+        deal(
+            &mut table,
+            &mut clock,
+            &mut statistics,
+            &mut drag,
+            &audio,
+            &sounds,
+        );
+    }
+}
+
+fn give_up(statistics: &mut Statistics, table: &Table) {
+    if table.game.moves > 0 {
+        statistics.streak = 0;
+        statistics.save();
+    }
+}
+
+fn mouse_input(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut table: ResMut<Table>,
+    mut drag: ResMut<Dragging>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let cursor = match game_hud::cursor_world_position(&windows) {
+        Some(cursor) => cursor,
+        None => {
+            if mouse_input.just_released(MouseButton::Left) {
+                drag.0 = None;
+            }
+            return;
+        }
+    };
+    if mouse_input.just_pressed(MouseButton::Left) {
+        match hit_test(&table.game, cursor) {
+            Some((Pile::Stock, _)) => {
+                if table.apply(Klondike::draw) {
+                    audio.play(sounds.deal.clone());
+                }
+            }
+            Some((pile, index)) if !table.game.cards(pile).is_empty() => {
+                let movable = match pile {
+                    Pile::Tableau(column) => index >= table.game.tableau[column].hidden,
+                    _ => index + 1 == table.game.cards(pile).len(),
+                };
+                if movable {
+                    let center = card_translation(&table.game, pile, index).truncate();
+                    drag.0 = Some(Drag {
+                        from: pile,
+                        index,
+                        offset: center - cursor,
+                    });
+                }
+            }
+            _ => {}
+        }
+    } else if mouse_input.just_pressed(MouseButton::Right) {
+        // Right click sends a card straight to its foundation.
+        if let Some((from, index)) = hit_test(&table.game, cursor) {
+            if let Some(to) = table.game.foundation_for(from) {
+                if index + 1 == table.game.cards(from).len()
+                    && table.apply(|game| game.move_cards(from, index, to))
+                {
+                    audio.play(sounds.place.clone());
+                }
+            }
+        }
+    } else if mouse_input.just_released(MouseButton::Left) {
+        let Drag {
+            from,
+            index,
+            offset,
+        } = match drag.0.take() {
+            Some(drag) => drag,
+            None => return,
+        };
+        let center = cursor + offset;
+        let target = drop_target(&table.game, center);
+        let moved = match target {
+            Some(to) => table.apply(|game| game.move_cards(from, index, to)),
+            None => false,
+        };
+        if moved {
+            audio.play(sounds.place.clone());
+        } else {
+            if target.is_some() {
+                audio.play(sounds.invalid.clone());
+            }
+            // Forces a redraw that puts the dragged cards back.
+            table.set_changed();
+        }
+    }
+}
+
+/// The pile a dragged card is dropped onto: foundations by their slot, tableau columns by their whole height.
+fn drop_target(game: &Klondike, center: Vec2) -> Option<Pile> {
+    if (center.y - TOP_ROW_Y).abs() < CARD_SIZE.y / 2.0 {
+        return (0..4).map(Pile::Foundation).find(|pile| {
+            (center.x - card_translation(game, *pile, 0).x).abs() < COLUMN_SPACING / 2.0
+        });
+    }
+    if center.y > TABLEAU_Y + CARD_SIZE.y / 2.0 {
+        return None;
+    }
+    (0..7)
+        .map(Pile::Tableau)
+        .find(|pile| (center.x - card_translation(game, *pile, 0).x).abs() < COLUMN_SPACING / 2.0)
+}
+
+fn new_game(
+    mut table: ResMut<Table>,
+    mut clock: ResMut<Clock>,
+    mut statistics: ResMut<Statistics>,
+    mut drag: ResMut<Dragging>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    deal(
+        &mut table,
+        &mut clock,
+        &mut statistics,
+        &mut drag,
+        &audio,
+        &sounds,
+    );
+}
+
+/// Starts a new game in place; also used to redeal without leaving the playing state.
+fn deal(
+    table: &mut Table,
+    clock: &mut Clock,
+    statistics: &mut Statistics,
+    drag: &mut Dragging,
+    audio: &Audio,
+    sounds: &Sounds,
+) {
+    let draw_three = table.game.draw_three;
+    *table = Table {
+        game: Klondike::deal(draw_three, &mut thread_rng()),
+        history: Vec::new(),
+    };
+    drag.0 = None;
+    clock.0 = 0.0;
+    statistics.played += 1;
+    statistics.save();
+    audio.play(sounds.deal.clone());
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    table: Res<Table>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    for pile in Pile::all() {
+        commands.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: SLOT_COLOR,
+                custom_size: Some(CARD_SIZE),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(
+                card_translation(&table.game, pile, 0) * Vec3::new(1.0, 1.0, 0.0),
+            ),
+            ..Default::default()
+        });
+    }
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "Drag cards   Right click: to foundation   U: undo   D: draw 1/3   N: new game",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(8.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        deal: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.08)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        invalid: audio_sources.add(Tone::new(Waveform::Square, 160.0, 0.15).volume(0.15).into()),
+        place: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.04)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.5),
+        ])),
+    });
+}
+
+/// Rebuilds the card sprites whenever the game changes.
+fn table_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    table: Res<Table>,
+    sprites: Query<Entity, With<CardSprite>>,
+) {
+    if !table.is_changed() {
+        return;
+    }
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let game = &table.game;
+    for pile in Pile::all() {
+        let cards = game.cards(pile);
+        // Buried stock, waste and foundation cards are never visible.
+        let first_visible = match pile {
+            Pile::Tableau(_) => 0,
+            Pile::Waste => cards.len().saturating_sub(3),
+            _ => cards.len().saturating_sub(1),
+        };
+        for (index, card) in cards.iter().enumerate().skip(first_visible) {
+            let face_up = match pile {
+                Pile::Stock => false,
+                Pile::Tableau(column) => index >= game.tableau[column].hidden,
+                _ => true,
+            };
+            game_cards::spawn_card(
+                &mut commands,
+                &font,
+                face_up.then(|| *card),
+                CARD_SIZE,
+                card_translation(game, pile, index),
+            )
+            .insert(CardSprite { index, pile });
+        }
+    }
+}
+
+fn win_check(
+    table: Res<Table>,
+    clock: Res<Clock>,
+    mut statistics: ResMut<Statistics>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !table.game.is_won() {
+        return;
+    }
+    statistics.won += 1;
+    statistics.streak += 1;
+    statistics.best_streak = statistics.best_streak.max(statistics.streak);
+    statistics.best_time = Some(
+        statistics
+            .best_time
+            .map_or(clock.0, |best| best.min(clock.0)),
+    );
+    statistics.fewest_moves = Some(
+        statistics
+            .fewest_moves
+            .map_or(table.game.moves, |fewest| fewest.min(table.game.moves)),
+    );
+    statistics.save();
+    audio.play(sounds.won.clone());
+    state.set(GameState::Won).unwrap();
+}
+
+fn won(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    table: Res<Table>,
+    clock: Res<Clock>,
+    statistics: Res<Statistics>,
+) {
+    let seconds = clock.0 as u32;
+    let best = statistics.best_time.unwrap_or(clock.0) as u32;
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "YOU WIN\n{} moves in {}:{:02}\n\nGames won {} of {} ({}%)\nBest time {}:{:02}   Fewest moves {}\nBest streak {}\n\nPress Enter",
+                table.game.moves,
+                seconds / 60,
+                seconds % 60,
+                statistics.won,
+                statistics.played,
+                statistics.won * 100 / statistics.played.max(1),
+                best / 60,
+                best % 60,
+                statistics.fewest_moves.unwrap_or(table.game.moves),
+                statistics.best_streak
+            ),
+            28.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 100.0),
+        ))
+        .insert(WonText);
+}
+
+fn won_exit(mut commands: Commands, texts: Query<Entity, With<WonText>>) {
+    for entity in texts.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn won_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Playing).unwrap();
+    }
+}
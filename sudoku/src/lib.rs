@@ -0,0 +1,627 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use puzzle::Difficulty;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+
+pub mod puzzle;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.1, 0.1, 0.12);
+const BOX_GAP: f32 = 4.0;
+const CELL: f32 = 60.0;
+const CELL_COLOR: Color = Color::rgb(0.92, 0.92, 0.88);
+const ENTRY_COLOR: Color = Color::rgb(0.15, 0.3, 0.8);
+const FOOTER_HEIGHT: f32 = 30.0;
+const GIVEN_COLOR: Color = Color::rgb(0.05, 0.05, 0.05);
+const GRID_LINE_COLOR: Color = Color::rgb(0.2, 0.2, 0.25);
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const MARK_COLOR: Color = Color::rgb(0.4, 0.4, 0.45);
+const MISTAKE_COLOR: Color = Color::rgb(0.85, 0.1, 0.1);
+const PEER_COLOR: Color = Color::rgb(0.82, 0.85, 0.92);
+const SAME_DIGIT_COLOR: Color = Color::rgb(0.7, 0.8, 0.95);
+const SAVE_FILE: &str = "puzzle";
+const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+
+const BOARD_SIZE: f32 = 9.0 * CELL + 2.0 * BOX_GAP;
+pub const WINDOW_HEIGHT: f32 = BOARD_SIZE + HUD_HEIGHT + FOOTER_HEIGHT + 20.0;
+pub const WINDOW_WIDTH: f32 = BOARD_SIZE + 40.0;
+
+/// Dark background whose gaps between the cells form the grid lines.
+#[derive(Component)]
+struct BoardFrame;
+
+#[derive(Component)]
+struct Cell(usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Solved,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MarksText;
+
+#[derive(Component)]
+struct MenuText;
+
+/// The selected difficulty and the puzzle that can be resumed from the menu, if any.
+struct MenuChoice {
+    difficulty: Difficulty,
+    saved: Option<Sudoku>,
+}
+
+struct Selection {
+    index: usize,
+    pencil: bool,
+}
+
+#[derive(Component)]
+struct SolvedText;
+
+struct Sounds {
+    enter: Handle<AudioSource>,
+    hint: Handle<AudioSource>,
+    mistake: Handle<AudioSource>,
+    solved: Handle<AudioSource>,
+}
+
+/// Everything needed to resume a puzzle; stored after every change.
+#[derive(Clone, Serialize, Deserialize)]
+struct Sudoku {
+    check_mistakes: bool,
+    difficulty: Difficulty,
+    elapsed: f32,
+    /// Digits of the givens and the player's entries, `0` for empty cells.
+    entries: Vec<u8>,
+    givens: Vec<u8>,
+    hints: u32,
+    /// Pencil marks per cell as a bitmask of digits 1 to 9.
+    marks: Vec<u16>,
+    mistakes: u32,
+    solution: Vec<u8>,
+}
+
+impl Sudoku {
+    fn new(difficulty: Difficulty) -> Self {
+        let puzzle = puzzle::generate(difficulty, &mut thread_rng());
+        Self {
+            check_mistakes: true,
+            difficulty,
+            elapsed: 0.0,
+            entries: puzzle.givens.to_vec(),
+            givens: puzzle.givens.to_vec(),
+            hints: 0,
+            marks: vec![0; 81],
+            mistakes: 0,
+            solution: puzzle.solution.to_vec(),
+        }
+    }
+
+    fn is_given(&self, index: usize) -> bool {
+        self.givens[index] != 0
+    }
+
+    fn is_mistake(&self, index: usize) -> bool {
+        self.entries[index] != 0 && self.entries[index] != self.solution[index]
+    }
+
+    fn is_solved(&self) -> bool {
+        self.entries == self.solution
+    }
+
+    /// Writes a digit and clears it from the pencil marks of every cell that can no longer hold it.
+    fn place(&mut self, index: usize, digit: u8) {
+        self.entries[index] = digit;
+        self.marks[index] = 0;
+        for peer in puzzle::peers(index) {
+            self.marks[peer] &= !(1 << digit);
+        }
+    }
+
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("sudoku", SAVE_FILE, self) {
+            eprintln!("could not save the puzzle: {}", error);
+        }
+    }
+}
+
+#[derive(Component)]
+struct ValueText;
+
+pub struct SudokuPlugin;
+
+impl Plugin for SudokuPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(MenuChoice {
+                difficulty: Difficulty::Easy,
+                saved: None,
+            })
+            .insert_resource(Selection {
+                index: 40,
+                pencil: false,
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Menu).with_system(menu_enter))
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Menu).with_system(despawn_all::<MenuText>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(board_spawn))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(cell_click)
+                    .with_system(playing_input)
+                    .with_system(puzzle_clock),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Playing)
+                    .with_system(despawn_all::<BoardFrame>)
+                    .with_system(despawn_all::<Cell>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Solved).with_system(solved))
+            .add_system_set(SystemSet::on_update(GameState::Solved).with_system(solved_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Solved).with_system(despawn_all::<SolvedText>),
+            )
+            .add_system(board_visuals)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Sudoku".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn board_spawn(mut commands: Commands, font: Res<HudFont>) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: GRID_LINE_COLOR,
+                custom_size: Some(Vec2::splat(BOARD_SIZE + BOX_GAP)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(board_center()),
+            ..Default::default()
+        })
+        .insert(BoardFrame);
+    for index in 0..81 {
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: CELL_COLOR,
+                    custom_size: Some(Vec2::splat(CELL - 2.0)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(cell_center(index) + Vec3::Z),
+                ..Default::default()
+            })
+            .insert(Cell(index))
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        "",
+                        CELL * 0.6,
+                        GIVEN_COLOR,
+                        Vec3::new(0.0, 0.0, 0.1),
+                    ))
+                    .insert(ValueText);
+                parent
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        "",
+                        CELL * 0.24,
+                        MARK_COLOR,
+                        Vec3::new(0.0, 0.0, 0.1),
+                    ))
+                    .insert(MarksText);
+            });
+    }
+}
+
+fn board_center() -> Vec3 {
+    Vec3::new(0.0, (FOOTER_HEIGHT - HUD_HEIGHT) / 2.0, 0.0)
+}
+
+fn board_visuals(
+    sudoku: Option<Res<Sudoku>>,
+    selection: Res<Selection>,
+    mut cells: Query<(&Cell, &mut Sprite, &Children)>,
+    mut values: Query<&mut Text, (With<ValueText>, Without<MarksText>)>,
+    mut marks: Query<&mut Text, (With<MarksText>, Without<ValueText>)>,
+) {
+    let sudoku = match sudoku {
+        Some(sudoku) => sudoku,
+        None => return,
+    };
+    if !sudoku.is_changed() && !selection.is_changed() {
+        return;
+    }
+    let selected_digit = sudoku.entries[selection.index];
+    for (cell, mut sprite, children) in cells.iter_mut() {
+        let index = cell.0;
+        sprite.color = if index == selection.index {
+            SELECTED_COLOR
+        } else if selected_digit != 0 && sudoku.entries[index] == selected_digit {
+            SAME_DIGIT_COLOR
+        } else if puzzle::peers(selection.index).any(|peer| peer == index) {
+            PEER_COLOR
+        } else {
+            CELL_COLOR
+        };
+        let digit = sudoku.entries[index];
+        for child in children.iter() {
+            if let Ok(mut text) = values.get_mut(*child) {
+                text.sections[0].value = if digit == 0 {
+                    String::new()
+                } else {
+                    digit.to_string()
+                };
+                text.sections[0].style.color = if sudoku.is_given(index) {
+                    GIVEN_COLOR
+                } else if sudoku.check_mistakes && sudoku.is_mistake(index) {
+                    MISTAKE_COLOR
+                } else {
+                    ENTRY_COLOR
+                };
+            }
+            if let Ok(mut text) = marks.get_mut(*child) {
+                text.sections[0].value = if digit == 0 {
+                    marks_label(sudoku.marks[index])
+                } else {
+                    String::new()
+                };
+            }
+        }
+    }
+}
+
+fn cell_center(index: usize) -> Vec3 {
+    let (row, column) = ((index / 9) as f32, (index % 9) as f32);
+    let offset =
+        |position: f32| (position - 4.0) * CELL + ((position / 3.0).floor() - 1.0) * BOX_GAP;
+    board_center() + Vec3::new(offset(column), -offset(row), 0.0)
+}
+
+fn cell_click(
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut selection: ResMut<Selection>,
+) {
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+        let clicked = (0..81).find(|index| {
+            let offset = (cursor - cell_center(*index).truncate()).abs();
+            offset.x < CELL / 2.0 && offset.y < CELL / 2.0
+        });
+        if let Some(index) = clicked {
+            selection.index = index;
+        }
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn digit_pressed(keyboard_input: &Input<KeyCode>) -> Option<u8> {
+    const KEYS: [(KeyCode, KeyCode); 9] = [
+        (KeyCode::Key1, KeyCode::Numpad1),
+        (KeyCode::Key2, KeyCode::Numpad2),
+        (KeyCode::Key3, KeyCode::Numpad3),
+        (KeyCode::Key4, KeyCode::Numpad4),
+        (KeyCode::Key5, KeyCode::Numpad5),
+        (KeyCode::Key6, KeyCode::Numpad6),
+        (KeyCode::Key7, KeyCode::Numpad7),
+        (KeyCode::Key8, KeyCode::Numpad8),
+        (KeyCode::Key9, KeyCode::Numpad9),
+    ];
+    KEYS.iter()
+        .position(|(key, numpad)| keyboard_input.any_just_pressed([*key, *numpad]))
+        .map(|position| position as u8 + 1)
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+fn hud_update(
+    sudoku: Option<Res<Sudoku>>,
+    selection: Res<Selection>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<HudText>>,
+) {
+    let value = match (state.current(), sudoku) {
+        (GameState::Menu, _) | (_, None) => String::new(),
+        (_, Some(sudoku)) => format!(
+            "{}   TIME {}   MISTAKES {}   HINTS {}   {}",
+            sudoku.difficulty.name().to_uppercase(),
+            format_time(sudoku.elapsed),
+            sudoku.mistakes,
+            sudoku.hints,
+            if selection.pencil { "PENCIL" } else { "" }
+        ),
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+/// Pencil marks laid out like a phone keypad, 1 to 3 on the top line.
+fn marks_label(marks: u16) -> String {
+    (0..3)
+        .map(|row| {
+            (1..=3)
+                .map(|column| {
+                    let digit = row * 3 + column;
+                    if marks & (1 << digit) != 0 {
+                        digit.to_string()
+                    } else {
+                        " ".to_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn menu_enter(mut commands: Commands, font: Res<HudFont>, mut choice: ResMut<MenuChoice>) {
+    choice.saved = match game_persistence::load::<Sudoku>("sudoku", SAVE_FILE) {
+        Ok(saved) => saved.filter(|saved| saved.entries.len() == 81),
+        Err(error) => {
+            eprintln!("could not load the saved puzzle: {}", error);
+            None
+        }
+    };
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            menu_label(&choice),
+            30.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(MenuText);
+}
+
+fn menu_input(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut choice: ResMut<MenuChoice>,
+    mut state: ResMut<State<GameState>>,
+    mut texts: Query<&mut Text, With<MenuText>>,
+) {
+    let index = Difficulty::ALL
+        .iter()
+        .position(|difficulty| *difficulty == choice.difficulty)
+        .unwrap_or(0);
+    if menu.just_pressed(Navigate::Up) {
+        choice.difficulty = Difficulty::ALL[index.saturating_sub(1)];
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.difficulty = Difficulty::ALL[(index + 1).min(Difficulty::ALL.len() - 1)];
+    } else if menu.just_pressed(Navigate::Confirm) {
+        let sudoku = Sudoku::new(choice.difficulty);
+        sudoku.save();
+        commands.insert_resource(sudoku);
+        state.set(GameState::Playing).unwrap();
+        return;
+    } else if keyboard_input.just_pressed(KeyCode::C) {
+        if let Some(saved) = choice.saved.take() {
+            commands.insert_resource(saved);
+            state.set(GameState::Playing).unwrap();
+        }
+        return;
+    } else {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = menu_label(&choice);
+    }
+}
+
+fn menu_label(choice: &MenuChoice) -> String {
+    let difficulties = Difficulty::ALL
+        .iter()
+        .map(|difficulty| {
+            let marker = if *difficulty == choice.difficulty {
+                ">"
+            } else {
+                " "
+            };
+            format!("{} {}", marker, difficulty.name())
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let resume = match &choice.saved {
+        Some(saved) => format!(
+            "\nC: continue {} puzzle ({})",
+            saved.difficulty.name(),
+            format_time(saved.elapsed)
+        ),
+        None => String::new(),
+    };
+    format!("SUDOKU\n\n{}\n\nEnter: new puzzle{}", difficulties, resume)
+}
+
+fn playing_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut sudoku: ResMut<Sudoku>,
+    mut selection: ResMut<Selection>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let (row, column) = (selection.index / 9, selection.index % 9);
+    if keyboard_input.just_pressed(KeyCode::Up) {
+        selection.index = (row + 8) % 9 * 9 + column;
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        selection.index = (row + 1) % 9 * 9 + column;
+    } else if keyboard_input.just_pressed(KeyCode::Left) {
+        selection.index = row * 9 + (column + 8) % 9;
+    } else if keyboard_input.just_pressed(KeyCode::Right) {
+        selection.index = row * 9 + (column + 1) % 9;
+    } else if keyboard_input.just_pressed(KeyCode::P) {
+        selection.pencil = !selection.pencil;
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        sudoku.save();
+        state.set(GameState::Menu).unwrap();
+        return;
+    }
+
+    let index = selection.index;
+    let pencil = selection.pencil || keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if keyboard_input.just_pressed(KeyCode::M) {
+        sudoku.check_mistakes = !sudoku.check_mistakes;
+    } else if keyboard_input.just_pressed(KeyCode::H) {
+        // Hint the selected cell if it needs one, otherwise the first empty or wrong cell.
+        let target = if sudoku.entries[index] != sudoku.solution[index] {
+            Some(index)
+        } else {
+            (0..81).find(|index| sudoku.entries[*index] != sudoku.solution[*index])
+        };
+        if let Some(target) = target {
+            let digit = sudoku.solution[target];
+            sudoku.place(target, digit);
+            sudoku.hints += 1;
+            selection.index = target;
+            audio.play(sounds.hint.clone());
+        }
+    } else if keyboard_input.any_just_pressed([KeyCode::Back, KeyCode::Delete, KeyCode::Key0]) {
+        if !sudoku.is_given(index) {
+            sudoku.entries[index] = 0;
+            sudoku.marks[index] = 0;
+        }
+    } else if let Some(digit) = digit_pressed(&keyboard_input) {
+        if sudoku.is_given(index) {
+            return;
+        }
+        if pencil {
+            if sudoku.entries[index] == 0 {
+                sudoku.marks[index] ^= 1 << digit;
+            }
+        } else {
+            sudoku.place(index, digit);
+            if sudoku.is_mistake(index) {
+                sudoku.mistakes += 1;
+                if sudoku.check_mistakes {
+                    audio.play(sounds.mistake.clone());
+                }
+            } else {
+                audio.play(sounds.enter.clone());
+            }
+        }
+    } else {
+        return;
+    }
+
+    if sudoku.is_solved() {
+        if let Err(error) = game_persistence::remove("sudoku", SAVE_FILE) {
+            eprintln!("could not remove the saved puzzle: {}", error);
+        }
+        audio.play(sounds.solved.clone());
+        state.set(GameState::Solved).unwrap();
+    } else {
+        sudoku.save();
+    }
+}
+
+fn puzzle_clock(time: Res<Time>, mut sudoku: ResMut<Sudoku>) {
+    sudoku.elapsed += time.delta_seconds();
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands.spawn_bundle(game_hud::screen_text(
+        &font,
+        "1-9 enter   P/Shift pencil   Del clear   H hint   M mistakes   Esc menu",
+        14.0,
+        HUD_COLOR,
+        Rect {
+            bottom: Val::Px(8.0),
+            left: Val::Px(10.0),
+            ..Default::default()
+        },
+    ));
+    commands.insert_resource(Sounds {
+        enter: audio_sources.add(
+            Tone::new(Waveform::Triangle, 700.0, 0.05)
+                .volume(0.2)
+                .into(),
+        ),
+        hint: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Sine, 880.0, 0.08).volume(0.3),
+            Tone::new(Waveform::Sine, 1175.0, 0.12).volume(0.3),
+        ])),
+        mistake: audio_sources.add(Tone::new(Waveform::Square, 180.0, 0.2).volume(0.15).into()),
+        solved: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.12),
+            Tone::new(Waveform::Triangle, 659.0, 0.12),
+            Tone::new(Waveform::Triangle, 784.0, 0.12),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4),
+        ])),
+    });
+}
+
+fn solved(mut commands: Commands, font: Res<HudFont>, sudoku: Res<Sudoku>) {
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            format!(
+                "SOLVED in {}\n{} mistakes, {} hints\nPress Enter",
+                format_time(sudoku.elapsed),
+                sudoku.mistakes,
+                sudoku.hints
+            ),
+            34.0,
+            HUD_COLOR,
+            Vec3::new(0.0, 0.0, 10.0),
+        ))
+        .insert(SolvedText);
+}
+
+fn solved_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
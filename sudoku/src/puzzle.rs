@@ -0,0 +1,273 @@
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// Cells in reading order, `0` for an empty cell.
+pub type Grid = [u8; 81];
+
+const ALL_DIGITS: u16 = 0b11_1111_1110;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Self::Easy, Self::Medium, Self::Hard];
+
+    /// How many givens the generator aims for; it stops early if no more can be removed uniquely.
+    fn clues(self) -> usize {
+        match self {
+            Self::Easy => 40,
+            Self::Medium => 32,
+            Self::Hard => 24,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Medium => "Medium",
+            Self::Hard => "Hard",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Puzzle {
+    pub givens: Grid,
+    pub solution: Grid,
+}
+
+/// Bitmask of the digits (bit 1 to 9) that can still go into `index` without repeating in its row, column or box.
+pub fn candidates(grid: &Grid, index: usize) -> u16 {
+    let mut used = 0;
+    for peer in peers(index) {
+        used |= 1 << grid[peer];
+    }
+    ALL_DIGITS & !used
+}
+
+/// Whether the digit in `index` repeats in its row, column or box.
+pub fn conflicts(grid: &Grid, index: usize) -> bool {
+    grid[index] != 0 && peers(index).any(|peer| grid[peer] == grid[index])
+}
+
+/// Counts solutions up to `limit`; a unique puzzle returns 1 with a limit of 2.
+pub fn count_solutions(grid: &Grid, limit: usize) -> usize {
+    if !consistent(grid) {
+        return 0;
+    }
+    let mut grid = *grid;
+    let mut count = 0;
+    search(&mut grid, &mut |_| {
+        count += 1;
+        count >= limit
+    });
+    count
+}
+
+/// Builds a random puzzle with exactly one solution.
+pub fn generate(difficulty: Difficulty, rng: &mut impl Rng) -> Puzzle {
+    let solution = random_solution(rng);
+    let mut givens = solution;
+    let mut order: Vec<usize> = (0..81).collect();
+    order.shuffle(rng);
+    let mut clues = 81;
+    for index in order {
+        if clues <= difficulty.clues() {
+            break;
+        }
+        let digit = givens[index];
+        givens[index] = 0;
+        if count_solutions(&givens, 2) == 1 {
+            clues -= 1;
+        } else {
+            givens[index] = digit;
+        }
+    }
+    Puzzle { givens, solution }
+}
+
+/// The indices sharing a row, column or box with `index`, excluding `index` itself.
+pub fn peers(index: usize) -> impl Iterator<Item = usize> {
+    let (row, column) = (index / 9, index % 9);
+    let (box_row, box_column) = (row / 3 * 3, column / 3 * 3);
+    (0..81).filter(move |&other| {
+        other != index
+            && (other / 9 == row
+                || other % 9 == column
+                || (other / 9 / 3 * 3 == box_row && other % 9 / 3 * 3 == box_column))
+    })
+}
+
+pub fn solve(grid: &Grid) -> Option<Grid> {
+    if !consistent(grid) {
+        return None;
+    }
+    let mut grid = *grid;
+    let mut solution = None;
+    search(&mut grid, &mut |solved| {
+        solution = Some(*solved);
+        true
+    });
+    solution
+}
+
+fn consistent(grid: &Grid) -> bool {
+    (0..81).all(|index| !conflicts(grid, index))
+}
+
+fn random_solution(rng: &mut impl Rng) -> Grid {
+    let mut grid = [0; 81];
+    fill(&mut grid, rng);
+    grid
+}
+
+fn fill(grid: &mut Grid, rng: &mut impl Rng) -> bool {
+    let index = match grid.iter().position(|digit| *digit == 0) {
+        Some(index) => index,
+        None => return true,
+    };
+    let mut digits: Vec<u8> = (1..=9)
+        .filter(|digit| candidates(grid, index) & (1 << digit) != 0)
+        .collect();
+    digits.shuffle(rng);
+    for digit in digits {
+        grid[index] = digit;
+        if fill(grid, rng) {
+            return true;
+        }
+    }
+    grid[index] = 0;
+    false
+}
+
+/// Backtracking over the empty cell with the fewest candidates; `found` returns `true` to stop the search.
+fn search(grid: &mut Grid, found: &mut impl FnMut(&Grid) -> bool) -> bool {
+    let mut best: Option<(usize, u16)> = None;
+    for index in 0..81 {
+        if grid[index] != 0 {
+            continue;
+        }
+        let options = candidates(grid, index);
+        if options == 0 {
+            return false;
+        }
+        if best.map_or(true, |(_, best_options)| {
+            options.count_ones() < best_options.count_ones()
+        }) {
+            best = Some((index, options));
+        }
+    }
+    let (index, options) = match best {
+        Some(best) => best,
+        None => return found(grid),
+    };
+    for digit in 1..=9 {
+        if options & (1 << digit) == 0 {
+            continue;
+        }
+        grid[index] = digit;
+        if search(grid, found) {
+            grid[index] = 0;
+            return true;
+        }
+    }
+    grid[index] = 0;
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn parse(text: &str) -> Grid {
+        let mut grid = [0; 81];
+        for (cell, symbol) in grid
+            .iter_mut()
+            .zip(text.chars().filter(|c| !c.is_whitespace()))
+        {
+            *cell = symbol.to_digit(10).unwrap_or(0) as u8;
+        }
+        grid
+    }
+
+    fn is_complete(grid: &Grid) -> bool {
+        grid.iter().all(|digit| *digit != 0) && consistent(grid)
+    }
+
+    const PUZZLE: &str = "
+        53..7.... 6..195... .98....6.
+        8...6...3 4..8.3..1 7...2...6
+        .6....28. ...419..5 ....8..79";
+
+    #[test]
+    fn peers_cover_row_column_and_box() {
+        let peers: Vec<usize> = peers(0).collect();
+        assert_eq!(peers.len(), 20);
+        assert!(peers.contains(&8));
+        assert!(peers.contains(&72));
+        assert!(peers.contains(&20));
+        assert!(!peers.contains(&30));
+    }
+
+    #[test]
+    fn solves_known_puzzle() {
+        let solution = solve(&parse(PUZZLE)).unwrap();
+        assert!(is_complete(&solution));
+        assert_eq!(
+            &solution[..9],
+            &[5, 3, 4, 6, 7, 8, 9, 1, 2],
+            "first row of the well known solution"
+        );
+    }
+
+    #[test]
+    fn unsolvable_grid_has_no_solution() {
+        let mut grid = parse(PUZZLE);
+        // A second 5 in the first column makes the puzzle contradictory.
+        grid[9] = 5;
+        assert_eq!(solve(&grid), None);
+        assert_eq!(count_solutions(&grid, 2), 0);
+    }
+
+    #[test]
+    fn counts_multiple_solutions() {
+        assert_eq!(count_solutions(&[0; 81], 2), 2);
+        assert_eq!(count_solutions(&parse(PUZZLE), 2), 1);
+    }
+
+    #[test]
+    fn generated_puzzles_are_unique_and_match_their_solution() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for difficulty in Difficulty::ALL {
+            let puzzle = generate(difficulty, &mut rng);
+            assert!(is_complete(&puzzle.solution));
+            assert_eq!(count_solutions(&puzzle.givens, 2), 1);
+            assert_eq!(solve(&puzzle.givens), Some(puzzle.solution));
+            for (given, solved) in puzzle.givens.iter().zip(puzzle.solution.iter()) {
+                assert!(*given == 0 || given == solved);
+            }
+            let clues = puzzle.givens.iter().filter(|digit| **digit != 0).count();
+            assert!(clues >= difficulty.clues());
+        }
+    }
+
+    #[test]
+    fn harder_puzzles_have_fewer_clues() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut clues = |difficulty| {
+            generate(difficulty, &mut rng)
+                .givens
+                .iter()
+                .filter(|digit| **digit != 0)
+                .count()
+        };
+        let easy = clues(Difficulty::Easy);
+        let hard = clues(Difficulty::Hard);
+        assert!(easy > hard);
+    }
+}
@@ -0,0 +1,308 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use game_hud::{HudFont, HudPlugin};
+use std::{env, fs};
+use story::Story;
+use transcript::Transcript;
+use world::World;
+
+pub mod story;
+pub mod transcript;
+pub mod world;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.06, 0.06, 0.08);
+const BUNDLED_STORY: &str = include_str!("../assets/story.ron");
+const FONT_SIZE: f32 = 16.0;
+const GAME: &str = "textadventure";
+/// Commands kept for Up and Down to bring back.
+const HISTORY_SIZE: usize = 50;
+const INPUT_COLOR: Color = Color::rgb(1.0, 0.85, 0.45);
+/// Height of a line of text, in pixels.
+const LINE_HEIGHT: f32 = 19.0;
+/// Characters a command may have.
+const MAX_INPUT: usize = 70;
+/// Lines of the transcript on screen.
+const SCREEN_LINES: usize = 26;
+const STATUS_COLOR: Color = Color::rgb(0.55, 0.6, 0.7);
+/// The command line flag naming a story to play, e.g. `--story my_story.ron`.
+const STORY_FLAG: &str = "--story";
+const TEXT_COLOR: Color = Color::rgb(0.85, 0.87, 0.9);
+/// Characters a line of the transcript holds.
+const WRAP_WIDTH: usize = 76;
+
+pub const WINDOW_HEIGHT: f32 = 600.0;
+pub const WINDOW_WIDTH: f32 = 800.0;
+
+/// The command being typed, and those typed before.
+#[derive(Default)]
+struct Prompt {
+    history: Vec<String>,
+    input: String,
+    /// The command of the history brought back with Up, counted from the newest.
+    recalled: Option<usize>,
+}
+
+/// Which of the texts on screen an entity shows.
+#[derive(Component)]
+enum Panel {
+    Prompt,
+    Status,
+    Transcript,
+}
+
+pub struct TextAdventurePlugin;
+
+impl Plugin for TextAdventurePlugin {
+    fn build(&self, app: &mut App) {
+        let (story, error) = load_story(env::args());
+        let world = World::new(&story);
+        let mut transcript = Transcript::default();
+        if let Some(error) = error {
+            transcript.push(&error, WRAP_WIDTH);
+        }
+        transcript.push(&story.title.to_uppercase(), WRAP_WIDTH);
+        transcript.push(&story.intro, WRAP_WIDTH);
+        transcript.push(&world.describe(&story), WRAP_WIDTH);
+        transcript.push("(Type HELP for what you can do.)", WRAP_WIDTH);
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(story)
+            .insert_resource(world)
+            .insert_resource(transcript)
+            .insert_resource(Prompt::default())
+            .add_plugin(HudPlugin)
+            .add_startup_system(setup)
+            .add_system(scroll_input)
+            .add_system(type_input)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Text Adventure".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The story named by `--story` in the command line `args`, or the bundled one; with what went
+/// wrong if the named one could not be read.
+fn load_story(args: impl Iterator<Item = String>) -> (Story, Option<String>) {
+    let bundled = || Story::parse(BUNDLED_STORY).expect("bundled story is valid");
+    let path = match args.skip_while(|arg| arg != STORY_FLAG).nth(1) {
+        Some(path) => path,
+        None => return (bundled(), None),
+    };
+    let loaded = fs::read_to_string(&path)
+        .map_err(|error| error.to_string())
+        .and_then(|text| Story::parse(&text).map_err(|error| error.to_string()));
+    match loaded {
+        Ok(story) => (story, None),
+        Err(error) => (
+            bundled(),
+            Some(format!(
+                "Could not read the story {}: {}. Playing the bundled story instead.",
+                path, error
+            )),
+        ),
+    }
+}
+
+/// Plays a command, or saves, loads or restarts the game, and adds the reply to the transcript.
+fn play(command: &str, story: &Story, world: &mut World) -> String {
+    match world::normalize(command).as_str() {
+        "save" => match game_persistence::save(GAME, &story.save_name(), world) {
+            Ok(()) => "Saved.".to_string(),
+            Err(error) => format!("Could not save: {}.", error),
+        },
+        "load" | "restore" => match game_persistence::load::<World>(GAME, &story.save_name()) {
+            Ok(Some(saved)) if story.rooms.contains_key(&saved.room) => {
+                *world = saved;
+                format!("Loaded.\n\n{}", world.describe(story))
+            }
+            Ok(Some(_)) => "The save does not fit this version of the story.".to_string(),
+            Ok(None) => "There is no save yet.".to_string(),
+            Err(error) => format!("Could not load: {}.", error),
+        },
+        "restart" => {
+            *world = World::new(story);
+            format!("{}\n\n{}", story.intro, world.describe(story))
+        }
+        _ => world.command(story, command),
+    }
+}
+
+/// Page Up and Page Down or the mouse wheel scroll through the transcript.
+fn scroll_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut wheel: EventReader<MouseWheel>,
+    mut transcript: ResMut<Transcript>,
+) {
+    let mut lines = 0;
+    if keyboard_input.just_pressed(KeyCode::PageUp) {
+        lines += SCREEN_LINES as isize - 2;
+    }
+    if keyboard_input.just_pressed(KeyCode::PageDown) {
+        lines -= SCREEN_LINES as isize - 2;
+    }
+    for event in wheel.iter() {
+        lines += match event.unit {
+            MouseScrollUnit::Line => (event.y * 3.0) as isize,
+            MouseScrollUnit::Pixel => (event.y / LINE_HEIGHT) as isize,
+        };
+    }
+    if lines != 0 {
+        transcript.scroll(lines, SCREEN_LINES);
+    }
+}
+
+fn setup(mut commands: Commands, font: Res<HudFont>) {
+    let panels = [
+        (Panel::Status, STATUS_COLOR, 8.0),
+        (Panel::Transcript, TEXT_COLOR, 34.0),
+        (
+            Panel::Prompt,
+            INPUT_COLOR,
+            WINDOW_HEIGHT - LINE_HEIGHT - 12.0,
+        ),
+    ];
+    for (panel, color, top) in panels {
+        commands
+            .spawn_bundle(game_hud::screen_text(
+                &font,
+                "",
+                FONT_SIZE,
+                color,
+                Rect {
+                    top: Val::Px(top),
+                    left: Val::Px(20.0),
+                    ..Default::default()
+                },
+            ))
+            .insert(panel);
+    }
+}
+
+fn texts_update(
+    time: Res<Time>,
+    story: Res<Story>,
+    world: Res<World>,
+    transcript: Res<Transcript>,
+    prompt: Res<Prompt>,
+    mut texts: Query<(&mut Text, &Panel)>,
+) {
+    let cursor = if time.seconds_since_startup().fract() < 0.5 {
+        "_"
+    } else {
+        " "
+    };
+    for (mut text, panel) in texts.iter_mut() {
+        let value = match panel {
+            Panel::Prompt => format!("> {}{}", prompt.input, cursor),
+            Panel::Status => {
+                let scrolled = if transcript.scrolled() {
+                    "   (scrolled back, Page Down for more)"
+                } else {
+                    ""
+                };
+                format!("{}   Moves {}{}", story.title, world.moves, scrolled)
+            }
+            Panel::Transcript => transcript.visible(SCREEN_LINES).join("\n"),
+        };
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
+
+/// Typing goes to the prompt; Enter plays the command, Up and Down bring back earlier ones.
+fn type_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    story: Res<Story>,
+    mut world: ResMut<World>,
+    mut transcript: ResMut<Transcript>,
+    mut prompt: ResMut<Prompt>,
+) {
+    for character in characters.iter().map(|event| event.char) {
+        if !character.is_control() && prompt.input.chars().count() < MAX_INPUT {
+            prompt.input.push(character);
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        prompt.input.pop();
+    }
+    let recalled = if keyboard_input.just_pressed(KeyCode::Up) {
+        Some(prompt.recalled.map_or(0, |recalled| recalled + 1))
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        prompt.recalled.and_then(|recalled| recalled.checked_sub(1))
+    } else {
+        prompt.recalled
+    };
+    if recalled != prompt.recalled {
+        match recalled.filter(|recalled| *recalled < prompt.history.len()) {
+            Some(recalled) => {
+                prompt.input = prompt.history[prompt.history.len() - 1 - recalled].clone();
+                prompt.recalled = Some(recalled);
+            }
+            None if recalled.is_none() => {
+                prompt.input.clear();
+                prompt.recalled = None;
+            }
+            None => {}
+        }
+    }
+
+    if !keyboard_input.just_pressed(KeyCode::Return) {
+        return;
+    }
+    let command = std::mem::take(&mut prompt.input);
+    prompt.recalled = None;
+    if command.trim().is_empty() {
+        return;
+    }
+    if prompt.history.last() != Some(&command) {
+        prompt.history.push(command.clone());
+        let excess = prompt.history.len().saturating_sub(HISTORY_SIZE);
+        prompt.history.drain(..excess);
+    }
+    let reply = play(&command, &story, &mut world);
+    transcript.push(&format!("> {}\n{}", command.trim(), reply), WRAP_WIDTH);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args<'a>(args: &'a [&'a str]) -> impl Iterator<Item = String> + 'a {
+        args.iter().map(|arg| arg.to_string())
+    }
+
+    #[test]
+    fn plays_the_bundled_story_when_none_is_named() {
+        let bundled = Story::parse(BUNDLED_STORY).unwrap();
+        for command_line in [&["textadventure"][..], &["rust_games", "textadventure"]] {
+            let (story, error) = load_story(args(command_line));
+            assert_eq!(story, bundled);
+            assert_eq!(error, None);
+        }
+    }
+
+    #[test]
+    fn says_why_a_named_story_cannot_be_played() {
+        let (story, error) = load_story(args(&[
+            "rust_games",
+            "textadventure",
+            STORY_FLAG,
+            "no_such_story.ron",
+        ]));
+        assert_eq!(story, Story::parse(BUNDLED_STORY).unwrap());
+        assert!(error
+            .unwrap()
+            .starts_with("Could not read the story no_such_story.ron"));
+    }
+}
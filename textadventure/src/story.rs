@@ -0,0 +1,262 @@
+//! Stories are RON files: rooms joined by exits, items lying in them, and actions that fire on
+//! typed commands when their conditions hold. See `assets/story.ron` for a complete example.
+
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt};
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Action {
+    /// The commands it answers to, written the way the player would type them; articles are
+    /// ignored, so "open drawer" also answers "open the drawer".
+    pub commands: Vec<String>,
+    /// What has to hold for it to fire; otherwise the next action with the command is tried.
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    pub text: String,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Condition {
+    /// The player carries the item.
+    Has(String),
+    /// The item lies in the player's room, or the player carries it.
+    Here(String),
+    /// The player is in the room.
+    In(String),
+    Set(String),
+    Unset(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub enum Effect {
+    /// Ends the story with the text.
+    End(String),
+    /// Puts the item in the player's hands, wherever it was.
+    Give(String),
+    /// Takes the player to the room.
+    Move(String),
+    /// Puts the item in the room, wherever it was.
+    Place(String, String),
+    /// Takes the item out of the story.
+    Remove(String),
+    Set(String),
+    Unset(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Exit {
+    pub to: String,
+    /// What has to hold to pass.
+    #[serde(default)]
+    pub when: Vec<Condition>,
+    /// Told when the player tries it while the conditions do not hold. Without it the exit is
+    /// hidden until they do, like a secret passage.
+    #[serde(default)]
+    pub blocked: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Item {
+    pub name: String,
+    pub description: String,
+    /// Other words the player may call it by, besides its name.
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Part of the room, so it cannot be taken.
+    #[serde(default)]
+    pub fixed: bool,
+}
+
+/// Text added to a room's description while the conditions hold.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Note {
+    pub when: Vec<Condition>,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Room {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub exits: BTreeMap<String, Exit>,
+    /// Items lying here at the start.
+    #[serde(default)]
+    pub items: Vec<String>,
+    #[serde(default)]
+    pub notes: Vec<Note>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Story {
+    pub title: String,
+    pub intro: String,
+    /// The room the player starts in.
+    pub start: String,
+    pub rooms: BTreeMap<String, Room>,
+    #[serde(default)]
+    pub items: BTreeMap<String, Item>,
+    #[serde(default)]
+    pub actions: Vec<Action>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum StoryError {
+    /// Not valid RON, or not the shape of a story.
+    Syntax(String),
+    /// A room or item is used that the story does not define.
+    Unknown {
+        kind: &'static str,
+        name: String,
+        within: String,
+    },
+    /// An item lies in more than one room at the start.
+    Twice(String),
+}
+
+impl fmt::Display for StoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Syntax(error) => write!(f, "{}", error),
+            Self::Unknown { kind, name, within } => {
+                write!(f, "unknown {} \"{}\" in {}", kind, name, within)
+            }
+            Self::Twice(item) => write!(f, "item \"{}\" starts in more than one room", item),
+        }
+    }
+}
+
+impl Story {
+    pub fn parse(text: &str) -> Result<Self, StoryError> {
+        let story: Story =
+            ron::from_str(text).map_err(|error| StoryError::Syntax(error.to_string()))?;
+        story.validate()?;
+        Ok(story)
+    }
+
+    /// The file name saves of the story go under: its title in lowercase, words joined by `_`.
+    pub fn save_name(&self) -> String {
+        let words: Vec<String> = self
+            .title
+            .split(|character: char| !character.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        format!("save_{}", words.join("_"))
+    }
+
+    /// Checks that every room and item named anywhere is defined.
+    fn validate(&self) -> Result<(), StoryError> {
+        let room = |name: &str, within: &str| {
+            if self.rooms.contains_key(name) {
+                Ok(())
+            } else {
+                Err(StoryError::Unknown {
+                    kind: "room",
+                    name: name.to_string(),
+                    within: within.to_string(),
+                })
+            }
+        };
+        let item = |name: &str, within: &str| {
+            if self.items.contains_key(name) {
+                Ok(())
+            } else {
+                Err(StoryError::Unknown {
+                    kind: "item",
+                    name: name.to_string(),
+                    within: within.to_string(),
+                })
+            }
+        };
+        let conditions = |conditions: &[Condition], within: &str| {
+            conditions.iter().try_for_each(|condition| match condition {
+                Condition::Has(name) | Condition::Here(name) => item(name, within),
+                Condition::In(name) => room(name, within),
+                Condition::Set(_) | Condition::Unset(_) => Ok(()),
+            })
+        };
+
+        room(&self.start, "start")?;
+        let mut placed = Vec::new();
+        for (id, definition) in &self.rooms {
+            let within = format!("room \"{}\"", id);
+            for exit in definition.exits.values() {
+                room(&exit.to, &within)?;
+                conditions(&exit.when, &within)?;
+            }
+            for note in &definition.notes {
+                conditions(&note.when, &within)?;
+            }
+            for name in &definition.items {
+                item(name, &within)?;
+                if placed.contains(name) {
+                    return Err(StoryError::Twice(name.clone()));
+                }
+                placed.push(name.clone());
+            }
+        }
+        for action in &self.actions {
+            let within = format!("action \"{}\"", action.commands.join(" / "));
+            conditions(&action.when, &within)?;
+            for effect in &action.effects {
+                match effect {
+                    Effect::Give(name) | Effect::Remove(name) => item(name, &within)?,
+                    Effect::Move(name) => room(name, &within)?,
+                    Effect::Place(name, to) => {
+                        item(name, &within)?;
+                        room(to, &within)?;
+                    }
+                    Effect::End(_) | Effect::Set(_) | Effect::Unset(_) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_story_is_valid() {
+        let story = Story::parse(include_str!("../assets/story.ron")).unwrap();
+        assert!(story.rooms.contains_key(&story.start));
+        assert!(!story.actions.is_empty());
+        assert_eq!(story.save_name(), "save_the_lighthouse_keeper");
+    }
+
+    #[test]
+    fn rejects_broken_stories() {
+        let parse = |text: &str| Story::parse(text).unwrap_err();
+        assert!(matches!(parse("Story("), StoryError::Syntax(_)));
+        let story = |exit: &str, items: &str| {
+            format!(
+                "(title: \"A\", intro: \"\", start: \"hall\", rooms: {{ \"hall\": (name: \"Hall\", \
+                 description: \"\", exits: {{ \"north\": (to: \"{}\") }}, items: [{}]) }}, \
+                 items: {{ \"key\": (name: \"key\", description: \"\") }})",
+                exit, items
+            )
+        };
+        assert!(Story::parse(&story("hall", "\"key\"")).is_ok());
+        assert_eq!(
+            parse(&story("garden", "")),
+            StoryError::Unknown {
+                kind: "room",
+                name: "garden".to_string(),
+                within: "room \"hall\"".to_string()
+            }
+        );
+        assert!(matches!(
+            parse(&story("hall", "\"lamp\"")),
+            StoryError::Unknown { kind: "item", .. }
+        ));
+        assert_eq!(
+            parse(&story("hall", "\"key\", \"key\"")),
+            StoryError::Twice("key".to_string())
+        );
+    }
+}
@@ -0,0 +1,98 @@
+//! Everything told and typed so far, wrapped into lines to fit the window, and how far back the
+//! player has scrolled.
+
+/// Lines kept at most; older ones are forgotten.
+const MAX_LINES: usize = 2000;
+
+#[derive(Default)]
+pub struct Transcript {
+    lines: Vec<String>,
+    /// Lines scrolled back from the end.
+    scroll: usize,
+}
+
+impl Transcript {
+    /// Adds text, wrapped at `width` characters, followed by an empty line, and scrolls to the
+    /// end.
+    pub fn push(&mut self, text: &str, width: usize) {
+        if text.is_empty() {
+            return;
+        }
+        for paragraph in text.lines() {
+            self.lines.extend(wrap(paragraph, width));
+        }
+        self.lines.push(String::new());
+        let excess = self.lines.len().saturating_sub(MAX_LINES);
+        self.lines.drain(..excess);
+        self.scroll = 0;
+    }
+
+    /// Scrolls back (positive) or forward (negative) by `lines`, within what there is.
+    pub fn scroll(&mut self, lines: isize, height: usize) {
+        let most = self.lines.len().saturating_sub(height) as isize;
+        self.scroll = (self.scroll as isize + lines).clamp(0, most) as usize;
+    }
+
+    /// Whether it is scrolled back from the end.
+    pub fn scrolled(&self) -> bool {
+        self.scroll > 0
+    }
+
+    /// The `height` lines on screen.
+    pub fn visible(&self, height: usize) -> &[String] {
+        let end = self.lines.len() - self.scroll;
+        &self.lines[end.saturating_sub(height)..end]
+    }
+}
+
+/// Breaks a paragraph into lines of at most `width` characters at spaces; a word longer than
+/// that gets a line of its own.
+pub fn wrap(paragraph: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in paragraph.split_whitespace() {
+        let length = line.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+    lines.push(line);
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wraps_at_spaces() {
+        assert_eq!(
+            wrap("the quick brown fox jumps", 10),
+            vec!["the quick", "brown fox", "jumps"]
+        );
+        assert_eq!(
+            wrap("a extraordinarily b", 5),
+            vec!["a", "extraordinarily", "b"]
+        );
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn scrolls_within_the_text() {
+        let mut transcript = Transcript::default();
+        transcript.push("one\ntwo\nthree", 20);
+        transcript.push("four", 20);
+        assert_eq!(transcript.visible(3), ["", "four", ""]);
+        transcript.scroll(10, 3);
+        assert_eq!(transcript.visible(3), ["one", "two", "three"]);
+        assert!(transcript.scrolled());
+        transcript.scroll(-1, 3);
+        assert_eq!(transcript.visible(3), ["two", "three", ""]);
+        transcript.push("five", 20);
+        assert!(!transcript.scrolled());
+    }
+}
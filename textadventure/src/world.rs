@@ -0,0 +1,386 @@
+//! Where the player and the items are and which flags are set, and the commands that change it.
+
+use crate::story::{Condition, Effect, Story};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Words left out of commands before they are matched.
+const ARTICLES: [&str; 3] = ["a", "an", "the"];
+/// Short forms of the directions, and the directions they stand for.
+const DIRECTIONS: [(&str, &str); 10] = [
+    ("d", "down"),
+    ("e", "east"),
+    ("n", "north"),
+    ("ne", "northeast"),
+    ("nw", "northwest"),
+    ("s", "south"),
+    ("se", "southeast"),
+    ("sw", "southwest"),
+    ("u", "up"),
+    ("w", "west"),
+];
+const HELP: &str = "Type what you want to do, such as LOOK, EXAMINE LAMP, TAKE KEY, DROP KEY, \
+                    INVENTORY or GO NORTH (or just N). Other things worth trying depend on the \
+                    story. SAVE, LOAD and RESTART manage the game.";
+
+/// Where an item is.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+enum Place {
+    Carried,
+    Gone,
+    Room(String),
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct World {
+    /// Whether an ending was reached.
+    pub ended: bool,
+    flags: BTreeSet<String>,
+    /// Commands played.
+    pub moves: u32,
+    places: BTreeMap<String, Place>,
+    pub room: String,
+}
+
+impl World {
+    /// The world at the start of the story.
+    pub fn new(story: &Story) -> Self {
+        let mut places = BTreeMap::new();
+        for (id, room) in &story.rooms {
+            for item in &room.items {
+                places.insert(item.clone(), Place::Room(id.clone()));
+            }
+        }
+        Self {
+            ended: false,
+            flags: BTreeSet::new(),
+            moves: 0,
+            places,
+            room: story.start.clone(),
+        }
+    }
+
+    fn apply(&mut self, effect: &Effect, story: &Story, reply: &mut Vec<String>) {
+        match effect {
+            Effect::End(text) => {
+                self.ended = true;
+                reply.push(text.clone());
+            }
+            Effect::Give(item) => {
+                self.places.insert(item.clone(), Place::Carried);
+            }
+            Effect::Move(room) => {
+                self.room = room.clone();
+                reply.push(self.describe(story));
+            }
+            Effect::Place(item, room) => {
+                self.places.insert(item.clone(), Place::Room(room.clone()));
+            }
+            Effect::Remove(item) => {
+                self.places.insert(item.clone(), Place::Gone);
+            }
+            Effect::Set(flag) => {
+                self.flags.insert(flag.clone());
+            }
+            Effect::Unset(flag) => {
+                self.flags.remove(flag);
+            }
+        }
+    }
+
+    fn carries(&self, item: &str) -> bool {
+        self.places.get(item) == Some(&Place::Carried)
+    }
+
+    /// Plays a typed command and tells what happened.
+    pub fn command(&mut self, story: &Story, input: &str) -> String {
+        let input = normalize(input);
+        if input.is_empty() {
+            return String::new();
+        }
+        if self.ended {
+            return "The story is over. Type RESTART to play again or LOAD to go back to your \
+                    last save."
+                .to_string();
+        }
+        self.moves += 1;
+
+        // The story's own actions come first, so it can change what the built-in verbs do.
+        let mut heard = false;
+        for action in &story.actions {
+            if !action
+                .commands
+                .iter()
+                .any(|command| normalize(command) == input)
+            {
+                continue;
+            }
+            heard = true;
+            if self.holds(&action.when) {
+                let mut reply = vec![action.text.clone()];
+                for effect in &action.effects {
+                    self.apply(effect, story, &mut reply);
+                }
+                return reply.join("\n\n");
+            }
+        }
+
+        let (verb, object) = match input.split_once(' ') {
+            Some((verb, object)) => (verb, object),
+            None => (input.as_str(), ""),
+        };
+        let direction = DIRECTIONS
+            .iter()
+            .find(|(short, long)| input == *short || input == *long)
+            .map(|(_, long)| *long);
+        if let Some(direction) = direction {
+            return self.go(story, direction);
+        }
+        match (verb, object) {
+            ("go" | "walk", direction) if !direction.is_empty() => {
+                let direction = DIRECTIONS
+                    .iter()
+                    .find(|(short, _)| *short == direction)
+                    .map_or(direction, |(_, long)| *long);
+                self.go(story, direction)
+            }
+            ("look" | "l", "") => self.describe(story),
+            ("look", object) if object.starts_with("at ") => self.examine(story, &object[3..]),
+            ("examine" | "x" | "read", object) if !object.is_empty() => self.examine(story, object),
+            ("inventory" | "i" | "inv", "") => self.inventory(story),
+            ("take" | "get", object) if !object.is_empty() => self.take(story, object),
+            ("pick", object) if object.starts_with("up ") => self.take(story, &object[3..]),
+            ("drop", object) if !object.is_empty() => self.drop(story, object),
+            ("help", "") => HELP.to_string(),
+            _ if heard => "Nothing happens.".to_string(),
+            _ => "I don't understand that.".to_string(),
+        }
+    }
+
+    /// The room's name, description, notes, the items lying there and the ways out.
+    pub fn describe(&self, story: &Story) -> String {
+        let room = &story.rooms[&self.room];
+        let mut text = room.description.clone();
+        for note in &room.notes {
+            if self.holds(&note.when) {
+                text.push(' ');
+                text.push_str(&note.text);
+            }
+        }
+        let mut lines = vec![room.name.to_uppercase(), text];
+        let items: Vec<&str> = self
+            .items_in(&self.room)
+            .filter(|item| !story.items[*item].fixed)
+            .map(|item| story.items[item].name.as_str())
+            .collect();
+        if !items.is_empty() {
+            lines.push(format!("You see: {}.", items.join(", ")));
+        }
+        let exits: Vec<&str> = room
+            .exits
+            .iter()
+            .filter(|(_, exit)| !exit.blocked.is_empty() || self.holds(&exit.when))
+            .map(|(direction, _)| direction.as_str())
+            .collect();
+        if !exits.is_empty() {
+            lines.push(format!("Exits: {}.", exits.join(", ")));
+        }
+        lines.join("\n")
+    }
+
+    fn drop(&mut self, story: &Story, object: &str) -> String {
+        match self.find(story, object) {
+            Some(item) if self.carries(&item) => {
+                let reply = format!("You drop the {}.", story.items[&item].name);
+                self.places.insert(item, Place::Room(self.room.clone()));
+                reply
+            }
+            Some(item) => format!("You are not carrying the {}.", story.items[&item].name),
+            None => format!("You have no {}.", object),
+        }
+    }
+
+    fn examine(&self, story: &Story, object: &str) -> String {
+        match self.find(story, object) {
+            Some(item) => story.items[&item].description.clone(),
+            None => format!("You see no {} here.", object),
+        }
+    }
+
+    /// The item here or carried that goes by `object`.
+    fn find(&self, story: &Story, object: &str) -> Option<String> {
+        story
+            .items
+            .iter()
+            .filter(|(id, _)| self.carries(id) || self.items_in(&self.room).any(|here| here == *id))
+            .find(|(_, item)| {
+                normalize(&item.name) == object
+                    || item.words.iter().any(|word| normalize(word) == object)
+            })
+            .map(|(id, _)| id.clone())
+    }
+
+    fn go(&mut self, story: &Story, direction: &str) -> String {
+        let exit = match story.rooms[&self.room].exits.get(direction) {
+            Some(exit) => exit,
+            None => return "You can't go that way.".to_string(),
+        };
+        if !self.holds(&exit.when) && exit.blocked.is_empty() {
+            return "You can't go that way.".to_string();
+        }
+        if !self.holds(&exit.when) {
+            return exit.blocked.clone();
+        }
+        self.room = exit.to.clone();
+        self.describe(story)
+    }
+
+    /// Whether every condition holds.
+    pub fn holds(&self, conditions: &[Condition]) -> bool {
+        conditions.iter().all(|condition| match condition {
+            Condition::Has(item) => self.carries(item),
+            Condition::Here(item) => {
+                self.carries(item) || self.places.get(item) == Some(&Place::Room(self.room.clone()))
+            }
+            Condition::In(room) => self.room == *room,
+            Condition::Set(flag) => self.flags.contains(flag),
+            Condition::Unset(flag) => !self.flags.contains(flag),
+        })
+    }
+
+    fn inventory(&self, story: &Story) -> String {
+        let items: Vec<&str> = self
+            .places
+            .iter()
+            .filter(|(_, place)| **place == Place::Carried)
+            .map(|(item, _)| story.items[item].name.as_str())
+            .collect();
+        if items.is_empty() {
+            "You are carrying nothing.".to_string()
+        } else {
+            format!("You are carrying: {}.", items.join(", "))
+        }
+    }
+
+    fn items_in<'a>(&'a self, room: &'a str) -> impl Iterator<Item = &'a String> {
+        self.places
+            .iter()
+            .filter(move |(_, place)| matches!(place, Place::Room(here) if here == room))
+            .map(|(item, _)| item)
+    }
+
+    fn take(&mut self, story: &Story, object: &str) -> String {
+        match self.find(story, object) {
+            Some(item) if self.carries(&item) => {
+                format!("You already have the {}.", story.items[&item].name)
+            }
+            Some(item) if story.items[&item].fixed => {
+                format!("The {} will not move.", story.items[&item].name)
+            }
+            Some(item) => {
+                let reply = format!("You take the {}.", story.items[&item].name);
+                self.places.insert(item, Place::Carried);
+                reply
+            }
+            None => format!("You see no {} here.", object),
+        }
+    }
+}
+
+/// A command in lowercase with single spaces and without articles.
+pub fn normalize(input: &str) -> String {
+    input
+        .split_whitespace()
+        .map(str::to_lowercase)
+        .filter(|word| !ARTICLES.contains(&word.as_str()))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn story() -> Story {
+        Story::parse(include_str!("../assets/story.ron")).unwrap()
+    }
+
+    #[test]
+    fn takes_and_drops_items() {
+        let story = story();
+        let mut world = World::new(&story);
+        assert_eq!(normalize("  Take  THE Matches "), "take matches");
+        assert_eq!(world.command(&story, "xyzzy"), "I don't understand that.");
+        world.command(&story, "n");
+        world.command(&story, "go east");
+        assert_eq!(world.room, "cottage");
+        assert_eq!(
+            world.command(&story, "take matches"),
+            "You take the box of matches."
+        );
+        assert!(world.inventory(&story).contains("box of matches"));
+        assert_eq!(
+            world.command(&story, "take table"),
+            "The table will not move."
+        );
+        world.command(&story, "w");
+        assert_eq!(
+            world.command(&story, "drop box"),
+            "You drop the box of matches."
+        );
+        assert!(world.describe(&story).contains("You see: box of matches."));
+    }
+
+    #[test]
+    fn the_bundled_story_can_be_won() {
+        let story = story();
+        let mut world = World::new(&story);
+        // The door is locked until the key is found.
+        world.command(&story, "north");
+        world.command(&story, "north");
+        assert!(world.command(&story, "n").contains("locked"));
+        assert_eq!(world.room, "yard");
+        let walkthrough = [
+            "s",
+            "e",
+            "open drawer",
+            "take key",
+            "take matches",
+            "w",
+            "n",
+            "unlock door",
+            "n",
+            "e",
+            "move crates",
+            "d",
+            "take can",
+            "u",
+            "w",
+            "u",
+            "light lamp",
+            "fill lamp",
+        ];
+        for command in walkthrough {
+            world.command(&story, command);
+        }
+        assert!(!world.ended);
+        assert!(world.command(&story, "light lamp").contains("THE END"));
+        assert!(world.ended);
+        assert!(world
+            .command(&story, "look")
+            .starts_with("The story is over"));
+    }
+
+    #[test]
+    fn saved_worlds_come_back_the_same() {
+        let story = story();
+        let mut world = World::new(&story);
+        for command in ["n", "e", "open drawer", "take key"] {
+            world.command(&story, command);
+        }
+        let saved = ron::to_string(&world).unwrap();
+        let loaded: World = ron::from_str(&saved).unwrap();
+        assert_eq!(loaded, world);
+        assert!(loaded.carries("key"));
+    }
+}
@@ -0,0 +1,399 @@
+//! The game without any rendering: gold, lives, towers and the enemies of the current wave.
+
+use crate::map::{Map, Tile};
+use crate::towers::{Tower, TowerKind};
+use crate::waves::{EnemyKind, Spawner, Wave};
+use bevy::math::{IVec2, Vec2};
+use std::fmt;
+
+/// Every wave after the first makes enemies this much tougher, as a share of their base health.
+const HEALTH_PER_WAVE: f32 = 0.25;
+/// Tiles per second.
+const PROJECTILE_SPEED: f32 = 9.0;
+pub const START_GOLD: u32 = 80;
+pub const START_LIVES: u32 = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuildError {
+    MaxLevel,
+    NotEnoughGold,
+    /// Not on free ground.
+    Occupied,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Self::MaxLevel => "Already at the top level",
+            Self::NotEnoughGold => "Not enough gold",
+            Self::Occupied => "Towers go on free ground",
+        };
+        f.write_str(message)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Enemy {
+    /// How far along the path it is, in tiles.
+    pub distance: f32,
+    pub health: f32,
+    pub id: u32,
+    pub kind: EnemyKind,
+    pub max_health: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Projectile {
+    pub damage: f32,
+    pub position: Vec2,
+    pub splash: f32,
+    pub target: u32,
+    /// Where the target was last seen, so the shot still lands once it is dead.
+    pub target_position: Vec2,
+}
+
+/// What happened during an update, for sounds and effects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    /// A laser fired from one point to another.
+    Beam(Vec2, Vec2),
+    Explosion(Vec2),
+    Kill(EnemyKind),
+    Leak(EnemyKind),
+    Shot(TowerKind),
+}
+
+#[derive(Clone, Debug)]
+pub struct Defense {
+    pub enemies: Vec<Enemy>,
+    pub gold: u32,
+    pub lives: u32,
+    pub map: Map,
+    next_id: u32,
+    pub projectiles: Vec<Projectile>,
+    spawner: Option<Spawner>,
+    pub towers: Vec<Tower>,
+    /// Waves started so far.
+    pub wave: usize,
+    pub waves: Vec<Wave>,
+}
+
+impl Defense {
+    pub fn new(map: Map, waves: Vec<Wave>) -> Self {
+        Self {
+            enemies: Vec::new(),
+            gold: START_GOLD,
+            lives: START_LIVES,
+            map,
+            next_id: 0,
+            projectiles: Vec::new(),
+            spawner: None,
+            towers: Vec::new(),
+            wave: 0,
+            waves,
+        }
+    }
+
+    pub fn build(&mut self, kind: TowerKind, cell: IVec2) -> Result<(), BuildError> {
+        if self.map.tile(cell) != Tile::Ground || self.tower_at(cell).is_some() {
+            return Err(BuildError::Occupied);
+        }
+        self.gold = self
+            .gold
+            .checked_sub(kind.cost())
+            .ok_or(BuildError::NotEnoughGold)?;
+        self.towers.push(Tower::new(kind, cell));
+        Ok(())
+    }
+
+    pub fn enemy_position(&self, enemy: &Enemy) -> Vec2 {
+        self.map.point_at(enemy.distance)
+    }
+
+    pub fn is_lost(&self) -> bool {
+        self.lives == 0
+    }
+
+    pub fn is_won(&self) -> bool {
+        !self.is_lost() && self.wave == self.waves.len() && self.wave_cleared()
+    }
+
+    /// Sends the next wave; returns false when there is none left.
+    pub fn start_wave(&mut self) -> bool {
+        match self.waves.get(self.wave) {
+            Some(wave) => {
+                self.spawner = Some(Spawner::new(wave.clone()));
+                self.wave += 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn tower_at(&self, cell: IVec2) -> Option<&Tower> {
+        self.towers.iter().find(|tower| tower.cell == cell)
+    }
+
+    /// Advances the wave by `delta` seconds.
+    pub fn update(&mut self, delta: f32) -> Vec<Event> {
+        let mut events = Vec::new();
+        let spawned = self
+            .spawner
+            .as_mut()
+            .map_or_else(Vec::new, |spawner| spawner.update(delta));
+        let toughness = 1.0 + HEALTH_PER_WAVE * self.wave.saturating_sub(1) as f32;
+        for kind in spawned {
+            let health = kind.health() * toughness;
+            self.enemies.push(Enemy {
+                distance: 0.0,
+                health,
+                id: self.next_id,
+                kind,
+                max_health: health,
+            });
+            self.next_id += 1;
+        }
+
+        let length = self.map.length();
+        let mut lives = self.lives;
+        self.enemies.retain(|enemy| {
+            let leaked = enemy.distance >= length;
+            if leaked {
+                lives = lives.saturating_sub(enemy.kind.damage());
+                events.push(Event::Leak(enemy.kind));
+            }
+            !leaked
+        });
+        self.lives = lives;
+        for enemy in &mut self.enemies {
+            enemy.distance += enemy.kind.speed() * delta;
+        }
+
+        self.towers_fire(delta, &mut events);
+        self.projectiles_move(delta, &mut events);
+        self.bury(&mut events);
+        events
+    }
+
+    /// Gold for every killed enemy.
+    fn bury(&mut self, events: &mut Vec<Event>) {
+        let mut gold = 0;
+        self.enemies.retain(|enemy| {
+            let dead = enemy.health <= 0.0;
+            if dead {
+                gold += enemy.kind.reward();
+                events.push(Event::Kill(enemy.kind));
+            }
+            !dead
+        });
+        self.gold += gold;
+    }
+
+    fn projectiles_move(&mut self, delta: f32, events: &mut Vec<Event>) {
+        let mut flying = Vec::new();
+        for mut projectile in std::mem::take(&mut self.projectiles) {
+            if let Some(target) = self
+                .enemies
+                .iter()
+                .find(|enemy| enemy.id == projectile.target)
+            {
+                projectile.target_position = self.enemy_position(target);
+            }
+            let offset = projectile.target_position - projectile.position;
+            let step = PROJECTILE_SPEED * delta;
+            if offset.length() > step {
+                projectile.position += offset.normalize() * step;
+                flying.push(projectile);
+                continue;
+            }
+            let impact = projectile.target_position;
+            if projectile.splash > 0.0 {
+                events.push(Event::Explosion(impact));
+                for enemy in &mut self.enemies {
+                    if self.map.point_at(enemy.distance).distance(impact) <= projectile.splash {
+                        enemy.health -= projectile.damage;
+                    }
+                }
+            } else if let Some(enemy) = self
+                .enemies
+                .iter_mut()
+                .find(|enemy| enemy.id == projectile.target)
+            {
+                enemy.health -= projectile.damage;
+            }
+        }
+        self.projectiles = flying;
+    }
+
+    /// Each tower that is ready aims at the enemy in range that got furthest.
+    fn towers_fire(&mut self, delta: f32, events: &mut Vec<Event>) {
+        for tower in &mut self.towers {
+            tower.cooldown = (tower.cooldown - delta).max(0.0);
+            if tower.cooldown > 0.0 {
+                continue;
+            }
+            let stats = tower.stats();
+            let origin = tower.cell.as_vec2() + Vec2::splat(0.5);
+            let target = self
+                .enemies
+                .iter_mut()
+                .filter(|enemy| enemy.health > 0.0)
+                .map(|enemy| (self.map.point_at(enemy.distance), enemy))
+                .filter(|(position, _)| position.distance(origin) <= stats.range)
+                .max_by(|(_, a), (_, b)| a.distance.partial_cmp(&b.distance).unwrap());
+            let (position, enemy) = match target {
+                Some(target) => target,
+                None => continue,
+            };
+            tower.cooldown = 1.0 / stats.fire_rate;
+            if tower.kind.hitscan() {
+                enemy.health -= stats.damage;
+                events.push(Event::Beam(origin, position));
+            } else {
+                self.projectiles.push(Projectile {
+                    damage: stats.damage,
+                    position: origin,
+                    splash: stats.splash,
+                    target: enemy.id,
+                    target_position: position,
+                });
+                events.push(Event::Shot(tower.kind));
+            }
+        }
+    }
+
+    pub fn upgrade(&mut self, cell: IVec2) -> Result<(), BuildError> {
+        let gold = self.gold;
+        let tower = self
+            .towers
+            .iter_mut()
+            .find(|tower| tower.cell == cell)
+            .ok_or(BuildError::Occupied)?;
+        let cost = tower
+            .kind
+            .upgrade_cost(tower.level)
+            .ok_or(BuildError::MaxLevel)?;
+        self.gold = gold.checked_sub(cost).ok_or(BuildError::NotEnoughGold)?;
+        tower.level += 1;
+        Ok(())
+    }
+
+    /// All enemies of the current wave were sent and are gone.
+    pub fn wave_cleared(&self) -> bool {
+        self.spawner.as_ref().map_or(true, Spawner::is_done)
+            && self.enemies.is_empty()
+            && self.projectiles.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::waves;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    /// A straight path 12 tiles long with ground on both sides.
+    fn defense(waves: &str) -> Defense {
+        let map = Map::parse(
+            "
+.............
+a...........b
+.............
+",
+        )
+        .unwrap();
+        Defense::new(map, waves::parse(waves).unwrap())
+    }
+
+    fn run(defense: &mut Defense, seconds: f32) -> Vec<Event> {
+        let mut events = Vec::new();
+        for _ in 0..(seconds / STEP) as usize {
+            events.extend(defense.update(STEP));
+        }
+        events
+    }
+
+    #[test]
+    fn building_needs_free_ground_and_gold() {
+        let mut defense = defense("1 grunt 1");
+        assert_eq!(
+            defense.build(TowerKind::Arrow, IVec2::new(3, 1)),
+            Err(BuildError::Occupied)
+        );
+        assert_eq!(defense.build(TowerKind::Laser, IVec2::new(3, 0)), Ok(()));
+        assert_eq!(defense.gold, START_GOLD - TowerKind::Laser.cost());
+        assert_eq!(
+            defense.build(TowerKind::Arrow, IVec2::new(3, 0)),
+            Err(BuildError::Occupied)
+        );
+        assert_eq!(
+            defense.build(TowerKind::Laser, IVec2::new(4, 0)),
+            Err(BuildError::NotEnoughGold)
+        );
+        assert_eq!(
+            defense.upgrade(IVec2::new(3, 0)),
+            Err(BuildError::NotEnoughGold)
+        );
+        defense.gold = 1000;
+        assert_eq!(defense.upgrade(IVec2::new(3, 0)), Ok(()));
+        assert_eq!(defense.upgrade(IVec2::new(3, 0)), Ok(()));
+        assert_eq!(defense.upgrade(IVec2::new(3, 0)), Err(BuildError::MaxLevel));
+        assert_eq!(defense.tower_at(IVec2::new(3, 0)).unwrap().level, 3);
+    }
+
+    #[test]
+    fn leaking_enemies_cost_lives() {
+        let mut defense = defense("3 runner 0.5");
+        assert!(defense.start_wave());
+        let events = run(&mut defense, 8.0);
+        assert_eq!(defense.lives, START_LIVES - 3);
+        assert_eq!(
+            events,
+            vec![Event::Leak(EnemyKind::Runner); 3],
+            "nothing else happens without towers"
+        );
+        assert!(defense.wave_cleared());
+        assert!(defense.is_won());
+    }
+
+    #[test]
+    fn towers_kill_for_gold() {
+        let mut defense = defense("2 grunt 1, 1 tank 1");
+        defense.gold = 1000;
+        for x in [2, 5, 8] {
+            defense.build(TowerKind::Cannon, IVec2::new(x, 0)).unwrap();
+            defense.build(TowerKind::Laser, IVec2::new(x, 2)).unwrap();
+        }
+        let gold = defense.gold;
+        defense.start_wave();
+        let events = run(&mut defense, 20.0);
+        assert_eq!(defense.lives, START_LIVES);
+        let kills = events
+            .iter()
+            .filter(|event| matches!(event, Event::Kill(_)))
+            .count();
+        assert_eq!(kills, 3);
+        let reward = 2 * EnemyKind::Grunt.reward() + EnemyKind::Tank.reward();
+        assert_eq!(defense.gold, gold + reward);
+        assert!(events.iter().any(|event| matches!(event, Event::Beam(..))));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, Event::Explosion(_))));
+        assert!(defense.wave_cleared());
+        assert!(!defense.start_wave(), "there was only one wave");
+    }
+
+    #[test]
+    fn later_waves_are_tougher() {
+        let mut defense = defense("1 grunt 1\n1 grunt 1");
+        defense.start_wave();
+        defense.update(STEP);
+        let first = defense.enemies[0].max_health;
+        run(&mut defense, 10.0);
+        defense.start_wave();
+        defense.update(STEP);
+        assert!(defense.enemies[0].max_health > first);
+        assert_eq!(defense.lives, START_LIVES - 1);
+    }
+}
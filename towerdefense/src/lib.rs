@@ -0,0 +1,566 @@
+use bevy::prelude::*;
+use defense::{Defense, Event};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use map::{Map, Tile};
+use towers::TowerKind;
+use waves::EnemyKind;
+
+pub mod defense;
+pub mod map;
+pub mod towers;
+pub mod waves;
+
+const ARROW_COLOR: Color = Color::rgb(0.55, 0.35, 0.15);
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.05, 0.1);
+/// Seconds a laser beam stays visible.
+const BEAM_TIME: f32 = 0.08;
+const CANNON_COLOR: Color = Color::rgb(0.25, 0.25, 0.3);
+const EXPLOSION_COLOR: Color = Color::rgba(1.0, 0.6, 0.1, 0.6);
+const GROUND_COLOR: Color = Color::rgb(0.3, 0.55, 0.25);
+const HEALTH_COLOR: Color = Color::rgb(0.2, 0.9, 0.2);
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 60.0;
+const LASER_COLOR: Color = Color::rgb(0.3, 0.8, 1.0);
+const MAP: &str = include_str!("../assets/map.txt");
+const MAP_HEIGHT: f32 = 14.0;
+const MAP_WIDTH: f32 = 24.0;
+/// Long frames are cut short so a hiccup does not let enemies skip past towers.
+const MAX_STEP: f32 = 1.0 / 30.0;
+const MISSING_HEALTH_COLOR: Color = Color::rgb(0.6, 0.1, 0.1);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.8);
+const PATH_COLOR: Color = Color::rgb(0.75, 0.65, 0.45);
+const PROJECTILE_COLOR: Color = Color::rgb(0.1, 0.1, 0.1);
+const TILE_SIZE: f32 = 40.0;
+const WATER_COLOR: Color = Color::rgb(0.2, 0.4, 0.75);
+const WAVES: &str = include_str!("../assets/waves.txt");
+
+pub const WINDOW_HEIGHT: f32 = MAP_HEIGHT * TILE_SIZE + HUD_HEIGHT;
+pub const WINDOW_WIDTH: f32 = MAP_WIDTH * TILE_SIZE;
+
+/// Towers, enemies and projectiles, redrawn every frame.
+#[derive(Component)]
+struct Actor;
+
+/// A laser beam or an explosion, fading out over the seconds left.
+#[derive(Component)]
+struct Flash(f32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    /// Between waves, towers can be built and upgraded.
+    Build,
+    Defend,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct InfoText;
+
+#[derive(Component)]
+struct OverlayText;
+
+/// The tower kind to build and the answer to the last click.
+struct Selection {
+    kind: TowerKind,
+    message: Option<String>,
+}
+
+struct Sounds {
+    build: Handle<AudioSource>,
+    cannon: Handle<AudioSource>,
+    denied: Handle<AudioSource>,
+    kill: Handle<AudioSource>,
+    laser: Handle<AudioSource>,
+    leak: Handle<AudioSource>,
+    shot: Handle<AudioSource>,
+    wave: Handle<AudioSource>,
+}
+
+pub struct TowerDefensePlugin;
+
+impl Plugin for TowerDefensePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(new_defense())
+            .insert_resource(Selection {
+                kind: TowerKind::Arrow,
+                message: None,
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Build)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Build).with_system(build_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Defend).with_system(defend.label("simulation")),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(game_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(game_over_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Over).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(tower_select)
+            .add_system(actors_render.after("simulation"))
+            .add_system(flashes_fade)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Tower Defense".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws towers with a pip per level, enemies with a health bar and the projectiles in flight.
+fn actors_render(
+    mut commands: Commands,
+    defense: Res<Defense>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for tower in &defense.towers {
+        let center = tower.cell.as_vec2() + Vec2::splat(0.5);
+        commands
+            .spawn_bundle(sprite(
+                tower_color(tower.kind),
+                Vec2::splat(0.75),
+                center,
+                1.0,
+            ))
+            .insert(Actor);
+        for pip in 0..tower.level {
+            let offset = Vec2::new((pip as f32 - (tower.level - 1) as f32 / 2.0) * 0.2, 0.0);
+            commands
+                .spawn_bundle(sprite(HUD_COLOR, Vec2::splat(0.12), center + offset, 2.0))
+                .insert(Actor);
+        }
+    }
+    for enemy in &defense.enemies {
+        let center = defense.enemy_position(enemy);
+        let (color, size) = match enemy.kind {
+            EnemyKind::Grunt => (Color::rgb(0.85, 0.2, 0.2), 0.45),
+            EnemyKind::Runner => (Color::rgb(0.95, 0.85, 0.2), 0.32),
+            EnemyKind::Tank => (Color::rgb(0.5, 0.25, 0.6), 0.65),
+        };
+        commands
+            .spawn_bundle(sprite(color, Vec2::splat(size), center, 3.0))
+            .insert(Actor);
+        let bar = center + Vec2::new(0.0, size / 2.0 + 0.1);
+        let share = (enemy.health / enemy.max_health).max(0.0);
+        commands
+            .spawn_bundle(sprite(MISSING_HEALTH_COLOR, Vec2::new(0.6, 0.08), bar, 4.0))
+            .insert(Actor);
+        commands
+            .spawn_bundle(sprite(
+                HEALTH_COLOR,
+                Vec2::new(0.6 * share, 0.08),
+                bar - Vec2::new(0.3 * (1.0 - share), 0.0),
+                5.0,
+            ))
+            .insert(Actor);
+    }
+    for projectile in &defense.projectiles {
+        commands
+            .spawn_bundle(sprite(
+                PROJECTILE_COLOR,
+                Vec2::splat(if projectile.splash > 0.0 { 0.2 } else { 0.1 }),
+                projectile.position,
+                6.0,
+            ))
+            .insert(Actor);
+    }
+}
+
+/// 1, 2 and 3 pick the tower to build; a click builds it or upgrades the tower under the cursor; Space sends the next wave.
+fn build_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut defense: ResMut<Defense>,
+    mut selection: ResMut<Selection>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::Return]) && defense.start_wave() {
+        selection.message = None;
+        audio.play(sounds.wave.clone());
+        state.set(GameState::Defend).unwrap();
+        return;
+    }
+    if !mouse_input.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let cell = match hovered_cell(&windows) {
+        Some(cell) => cell,
+        None => return,
+    };
+    let result = if defense.tower_at(cell).is_some() {
+        defense.upgrade(cell)
+    } else {
+        defense.build(selection.kind, cell)
+    };
+    match result {
+        Ok(()) => {
+            selection.message = None;
+            audio.play(sounds.build.clone());
+        }
+        Err(error) => {
+            selection.message = Some(error.to_string());
+            audio.play(sounds.denied.clone());
+        }
+    }
+}
+
+/// Runs the wave; back to building once it is cleared.
+fn defend(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut defense: ResMut<Defense>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let events = defense.update(time.delta_seconds().min(MAX_STEP));
+    for event in events {
+        match event {
+            Event::Beam(from, to) => {
+                let offset = to - from;
+                let mut beam = sprite(
+                    LASER_COLOR,
+                    Vec2::new(offset.length(), 0.06),
+                    (from + to) / 2.0,
+                    7.0,
+                );
+                beam.transform.rotation = Quat::from_rotation_z(offset.y.atan2(offset.x));
+                commands.spawn_bundle(beam).insert(Flash(BEAM_TIME));
+                audio.play(sounds.laser.clone());
+            }
+            Event::Explosion(at) => {
+                commands
+                    .spawn_bundle(sprite(EXPLOSION_COLOR, Vec2::splat(1.2), at, 7.0))
+                    .insert(Flash(0.2));
+            }
+            Event::Kill(_) => {
+                audio.play(sounds.kill.clone());
+            }
+            Event::Leak(_) => {
+                audio.play(sounds.leak.clone());
+            }
+            Event::Shot(kind) => {
+                let sound = if kind == TowerKind::Cannon {
+                    &sounds.cannon
+                } else {
+                    &sounds.shot
+                };
+                audio.play(sound.clone());
+            }
+        }
+    }
+    if defense.is_lost() || defense.is_won() {
+        state.set(GameState::Over).unwrap();
+    } else if defense.wave_cleared() {
+        state.set(GameState::Build).unwrap();
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn flashes_fade(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut Flash, &mut Sprite)>,
+) {
+    for (entity, mut flash, mut sprite) in flashes.iter_mut() {
+        flash.0 -= time.delta_seconds();
+        if flash.0 <= 0.0 {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            let alpha = sprite.color.a();
+            sprite.color.set_a(alpha * 0.85);
+        }
+    }
+}
+
+fn game_over(mut commands: Commands, font: Res<HudFont>, defense: Res<Defense>) {
+    let message = if defense.is_won() {
+        format!(
+            "All {} waves held off!\n\n{} of {} lives left   {} towers   {} gold\n\nEnter: play again",
+            defense.waves.len(),
+            defense.lives,
+            defense::START_LIVES,
+            defense.towers.len(),
+            defense.gold,
+        )
+    } else {
+        format!(
+            "The enemies broke through in wave {} of {}\n\nEnter: try again",
+            defense.wave,
+            defense.waves.len(),
+        )
+    };
+    let center = Vec2::new(0.0, -HUD_HEIGHT / 2.0);
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH, 200.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            message,
+            26.0,
+            HUD_COLOR,
+            center.extend(100.0),
+        ))
+        .insert(OverlayText);
+}
+
+fn game_over_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut defense: ResMut<Defense>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        *defense = new_defense();
+        state.set(GameState::Build).unwrap();
+    }
+}
+
+/// The map cell under the mouse cursor, if any.
+fn hovered_cell(windows: &Windows) -> Option<IVec2> {
+    let cursor = game_hud::cursor_world_position(windows)?;
+    let position = to_tiles(cursor);
+    let inside =
+        position.x >= 0.0 && position.y >= 0.0 && position.x < MAP_WIDTH && position.y < MAP_HEIGHT;
+    inside.then(|| Map::cell(position))
+}
+
+/// The first line shows the state of the game, the second what the cursor points at or what to do next.
+fn hud_update(
+    defense: Res<Defense>,
+    selection: Res<Selection>,
+    state: Res<State<GameState>>,
+    windows: Res<Windows>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<InfoText>>,
+    )>,
+) {
+    let kinds: Vec<String> = TowerKind::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, kind)| {
+            let label = format!("{} {} {}g", index + 1, kind.name(), kind.cost());
+            if *kind == selection.kind {
+                format!("[{}]", label)
+            } else {
+                format!(" {} ", label)
+            }
+        })
+        .collect();
+    let hud = format!(
+        "WAVE {}/{}   GOLD {}   LIVES {}    {}",
+        defense.wave,
+        defense.waves.len(),
+        defense.gold,
+        defense.lives,
+        kinds.join(" "),
+    );
+    let hovered = hovered_cell(&windows).and_then(|cell| defense.tower_at(cell));
+    let info = if let Some(tower) = hovered {
+        let stats = tower.stats();
+        let upgrade = match tower.kind.upgrade_cost(tower.level) {
+            Some(cost) => format!("click to upgrade for {} gold", cost),
+            None => "fully upgraded".to_string(),
+        };
+        format!(
+            "{} level {}: {:.0} damage, {:.1} shots/s, range {:.1} - {}",
+            tower.kind.name(),
+            tower.level,
+            stats.damage,
+            stats.fire_rate,
+            stats.range,
+            upgrade,
+        )
+    } else if let Some(message) = &selection.message {
+        message.clone()
+    } else if *state.current() == GameState::Defend {
+        format!("Wave {} is coming!", defense.wave)
+    } else {
+        "Click: build / upgrade   1-3: choose a tower   Space: send the next wave".to_string()
+    };
+    for mut text in texts.q0().iter_mut() {
+        if text.sections[0].value != hud {
+            text.sections[0].value = hud.clone();
+        }
+    }
+    for mut text in texts.q1().iter_mut() {
+        if text.sections[0].value != info {
+            text.sections[0].value = info.clone();
+        }
+    }
+}
+
+fn new_defense() -> Defense {
+    let map = Map::parse(MAP).expect("the bundled map is valid");
+    let waves = waves::parse(WAVES).expect("the bundled waves are valid");
+    Defense::new(map, waves)
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    defense: Res<Defense>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    let map = &defense.map;
+    for y in 0..map.height {
+        for x in 0..map.width {
+            let cell = IVec2::new(x, y);
+            let color = match map.tile(cell) {
+                Tile::Ground => GROUND_COLOR,
+                Tile::Path => PATH_COLOR,
+                Tile::Water => WATER_COLOR,
+            };
+            commands.spawn_bundle(sprite(
+                color,
+                Vec2::splat(0.96),
+                cell.as_vec2() + Vec2::splat(0.5),
+                0.0,
+            ));
+        }
+    }
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            18.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(6.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(34.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(InfoText);
+
+    commands.insert_resource(Sounds {
+        build: audio_sources.add(
+            Tone::new(Waveform::Square, 440.0, 0.08)
+                .slide(660.0)
+                .volume(0.2)
+                .into(),
+        ),
+        cannon: audio_sources.add(
+            Tone::new(Waveform::Noise, 120.0, 0.2)
+                .fade()
+                .volume(0.25)
+                .into(),
+        ),
+        denied: audio_sources.add(Tone::new(Waveform::Square, 110.0, 0.15).volume(0.2).into()),
+        kill: audio_sources.add(
+            Tone::new(Waveform::Triangle, 880.0, 0.06)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        laser: audio_sources.add(
+            Tone::new(Waveform::Sine, 1400.0, 0.04)
+                .slide(900.0)
+                .volume(0.05)
+                .into(),
+        ),
+        leak: audio_sources.add(
+            Tone::new(Waveform::Saw, 220.0, 0.3)
+                .slide(110.0)
+                .volume(0.25)
+                .into(),
+        ),
+        shot: audio_sources.add(
+            Tone::new(Waveform::Noise, 2000.0, 0.04)
+                .fade()
+                .volume(0.1)
+                .into(),
+        ),
+        wave: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 392.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 392.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 523.0, 0.25).volume(0.2),
+        ])),
+    });
+}
+
+/// A plain rectangle; sizes and positions are in tiles.
+fn sprite(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size * TILE_SIZE),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(to_world(center).extend(z)),
+        ..Default::default()
+    }
+}
+
+/// Converts world coordinates to tiles; the map fills the window below the HUD.
+fn to_tiles(world: Vec2) -> Vec2 {
+    (world + Vec2::new(0.0, HUD_HEIGHT / 2.0)) / TILE_SIZE + Vec2::new(MAP_WIDTH, MAP_HEIGHT) / 2.0
+}
+
+fn to_world(tiles: Vec2) -> Vec2 {
+    (tiles - Vec2::new(MAP_WIDTH, MAP_HEIGHT) / 2.0) * TILE_SIZE - Vec2::new(0.0, HUD_HEIGHT / 2.0)
+}
+
+fn tower_color(kind: TowerKind) -> Color {
+    match kind {
+        TowerKind::Arrow => ARROW_COLOR,
+        TowerKind::Cannon => CANNON_COLOR,
+        TowerKind::Laser => LASER_COLOR,
+    }
+}
+
+fn tower_select(keyboard_input: Res<Input<KeyCode>>, mut selection: ResMut<Selection>) {
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3];
+    for (key, kind) in keys.into_iter().zip(TowerKind::ALL) {
+        if keyboard_input.just_pressed(key) {
+            selection.kind = kind;
+            selection.message = None;
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! The map is a text file with one character per tile, see the readme for the symbols.
+//! Positions are in tiles with the origin at the bottom left corner and y pointing up.
+
+use bevy::math::{IVec2, Vec2};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tile {
+    /// Towers can be built here.
+    Ground,
+    Path,
+    Water,
+}
+
+#[derive(Clone, Debug)]
+pub struct Map {
+    pub height: i32,
+    tiles: Vec<Tile>,
+    /// Centers of the corners of the path, from where enemies come in to where they leak.
+    pub waypoints: Vec<Vec2>,
+    pub width: i32,
+}
+
+impl Map {
+    /// Fails when the waypoints are not `a`, `b`, ... without gaps or two of them are not in a straight line.
+    pub fn parse(layout: &str) -> Result<Self, String> {
+        let lines: Vec<&str> = layout.lines().filter(|line| !line.is_empty()).collect();
+        let height = lines.len() as i32;
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0) as i32;
+        let mut map = Self {
+            height,
+            tiles: vec![Tile::Ground; (width * height) as usize],
+            waypoints: Vec::new(),
+            width,
+        };
+        let mut corners = Vec::new();
+        for (row, line) in lines.iter().enumerate() {
+            let y = height - 1 - row as i32;
+            for (x, symbol) in line.chars().enumerate() {
+                let cell = IVec2::new(x as i32, y);
+                match symbol {
+                    '~' => map.tiles[(y * width + x as i32) as usize] = Tile::Water,
+                    'a'..='z' => corners.push((symbol, cell)),
+                    _ => {}
+                }
+            }
+        }
+        corners.sort_by_key(|(symbol, _)| *symbol);
+        for (index, (symbol, _)) in corners.iter().enumerate() {
+            if *symbol as usize != 'a' as usize + index {
+                return Err(format!(
+                    "waypoint '{}' is missing",
+                    (b'a' + index as u8) as char
+                ));
+            }
+        }
+        if corners.len() < 2 {
+            return Err("the path needs at least the waypoints 'a' and 'b'".to_string());
+        }
+        for pair in corners.windows(2) {
+            let ((from_symbol, from), (to_symbol, to)) = (pair[0], pair[1]);
+            if from.x != to.x && from.y != to.y {
+                return Err(format!(
+                    "waypoints '{}' and '{}' are not in a straight line",
+                    from_symbol, to_symbol
+                ));
+            }
+            let step = (to - from).signum();
+            let mut cell = from;
+            while cell != to + step {
+                map.tiles[(cell.y * width + cell.x) as usize] = Tile::Path;
+                cell += step;
+            }
+        }
+        map.waypoints = corners
+            .iter()
+            .map(|(_, cell)| cell.as_vec2() + Vec2::splat(0.5))
+            .collect();
+        Ok(map)
+    }
+
+    pub fn cell(position: Vec2) -> IVec2 {
+        position.floor().as_ivec2()
+    }
+
+    /// Total length of the path in tiles.
+    pub fn length(&self) -> f32 {
+        self.waypoints
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum()
+    }
+
+    /// The point `distance` tiles along the path, the end once past it.
+    pub fn point_at(&self, distance: f32) -> Vec2 {
+        let mut left = distance.max(0.0);
+        for pair in self.waypoints.windows(2) {
+            let length = pair[0].distance(pair[1]);
+            if left <= length {
+                return pair[0].lerp(pair[1], left / length);
+            }
+            left -= length;
+        }
+        self.waypoints[self.waypoints.len() - 1]
+    }
+
+    /// Outside the map is water, so nothing can be built there.
+    pub fn tile(&self, cell: IVec2) -> Tile {
+        if cell.x < 0 || cell.y < 0 || cell.x >= self.width || cell.y >= self.height {
+            Tile::Water
+        } else {
+            self.tiles[(cell.y * self.width + cell.x) as usize]
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAYOUT: &str = "
+a...b.
+....~.
+....c.
+";
+
+    #[test]
+    fn parses_the_path_between_waypoints() {
+        let map = Map::parse(LAYOUT).unwrap();
+        assert_eq!((map.width, map.height), (6, 3));
+        assert_eq!(
+            map.waypoints,
+            vec![
+                Vec2::new(0.5, 2.5),
+                Vec2::new(4.5, 2.5),
+                Vec2::new(4.5, 0.5)
+            ]
+        );
+        assert_eq!(map.tile(IVec2::new(2, 2)), Tile::Path);
+        // The path runs over the water between b and c.
+        assert_eq!(map.tile(IVec2::new(4, 1)), Tile::Path);
+        assert_eq!(map.tile(IVec2::new(2, 1)), Tile::Ground);
+        assert_eq!(map.tile(IVec2::new(-1, 1)), Tile::Water);
+        assert_eq!(map.length(), 6.0);
+        assert_eq!(map.point_at(5.0), Vec2::new(4.5, 1.5));
+        assert_eq!(map.point_at(100.0), Vec2::new(4.5, 0.5));
+    }
+
+    #[test]
+    fn rejects_broken_paths() {
+        assert!(Map::parse("a..c\n....").is_err());
+        assert!(Map::parse("a...\n...b").is_err());
+        assert!(Map::parse("a...").is_err());
+    }
+
+    #[test]
+    fn bundled_map_is_valid() {
+        let map = Map::parse(include_str!("../assets/map.txt")).unwrap();
+        assert_eq!((map.width, map.height), (24, 14));
+        assert!(map.length() > 40.0);
+    }
+}
@@ -0,0 +1,135 @@
+//! The kinds of towers and what they can do at each level.
+
+use bevy::math::IVec2;
+
+pub const MAX_LEVEL: u32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TowerKind {
+    /// Cheap, quick single shots.
+    Arrow,
+    /// Slow shells that hurt everything around where they land.
+    Cannon,
+    /// Hits instantly, never misses.
+    Laser,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub damage: f32,
+    /// Shots per second.
+    pub fire_rate: f32,
+    /// In tiles, from the center of the tower.
+    pub range: f32,
+    /// Radius around the impact that takes damage, 0 for a single target.
+    pub splash: f32,
+}
+
+impl TowerKind {
+    pub const ALL: [Self; 3] = [Self::Arrow, Self::Cannon, Self::Laser];
+
+    pub fn cost(self) -> u32 {
+        match self {
+            Self::Arrow => 20,
+            Self::Cannon => 40,
+            Self::Laser => 60,
+        }
+    }
+
+    /// Lasers hit at once; the others fire projectiles that take time to arrive.
+    pub fn hitscan(self) -> bool {
+        self == Self::Laser
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Arrow => "Arrow",
+            Self::Cannon => "Cannon",
+            Self::Laser => "Laser",
+        }
+    }
+
+    /// Every level after the first adds half the base damage, a bit of range and fire rate.
+    pub fn stats(self, level: u32) -> Stats {
+        let base = match self {
+            Self::Arrow => Stats {
+                damage: 4.0,
+                fire_rate: 1.5,
+                range: 3.0,
+                splash: 0.0,
+            },
+            Self::Cannon => Stats {
+                damage: 10.0,
+                fire_rate: 0.6,
+                range: 2.5,
+                splash: 1.0,
+            },
+            Self::Laser => Stats {
+                damage: 1.5,
+                fire_rate: 6.0,
+                range: 3.5,
+                splash: 0.0,
+            },
+        };
+        let upgrades = level.saturating_sub(1) as f32;
+        Stats {
+            damage: base.damage * (1.0 + 0.5 * upgrades),
+            fire_rate: base.fire_rate * (1.0 + 0.2 * upgrades),
+            range: base.range + 0.5 * upgrades,
+            splash: base.splash,
+        }
+    }
+
+    /// Gold to go from `level` to the next one, `None` at the top level.
+    pub fn upgrade_cost(self, level: u32) -> Option<u32> {
+        if level < MAX_LEVEL {
+            Some(self.cost() * level)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tower {
+    pub cell: IVec2,
+    /// Seconds until it can fire again.
+    pub cooldown: f32,
+    pub kind: TowerKind,
+    pub level: u32,
+}
+
+impl Tower {
+    pub fn new(kind: TowerKind, cell: IVec2) -> Self {
+        Self {
+            cell,
+            cooldown: 0.0,
+            kind,
+            level: 1,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.kind.stats(self.level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrades_improve_every_stat_but_splash() {
+        for kind in TowerKind::ALL {
+            for level in 1..MAX_LEVEL {
+                let (now, next) = (kind.stats(level), kind.stats(level + 1));
+                assert!(next.damage > now.damage);
+                assert!(next.fire_rate > now.fire_rate);
+                assert!(next.range > now.range);
+                assert_eq!(next.splash, now.splash);
+            }
+            assert_eq!(kind.upgrade_cost(1), Some(kind.cost()));
+            assert_eq!(kind.upgrade_cost(MAX_LEVEL), None);
+        }
+    }
+}
@@ -0,0 +1,192 @@
+//! Enemy waves, read from a text file, see `assets/waves.txt` for the format.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EnemyKind {
+    Grunt,
+    Runner,
+    Tank,
+}
+
+impl EnemyKind {
+    /// Lives lost when it reaches the end of the path.
+    pub fn damage(self) -> u32 {
+        match self {
+            Self::Grunt | Self::Runner => 1,
+            Self::Tank => 3,
+        }
+    }
+
+    /// Health in the first wave; later waves send tougher enemies.
+    pub fn health(self) -> f32 {
+        match self {
+            Self::Grunt => 12.0,
+            Self::Runner => 7.0,
+            Self::Tank => 60.0,
+        }
+    }
+
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "grunt" => Some(Self::Grunt),
+            "runner" => Some(Self::Runner),
+            "tank" => Some(Self::Tank),
+            _ => None,
+        }
+    }
+
+    /// Gold for killing it.
+    pub fn reward(self) -> u32 {
+        match self {
+            Self::Grunt => 3,
+            Self::Runner => 4,
+            Self::Tank => 12,
+        }
+    }
+
+    /// Tiles per second.
+    pub fn speed(self) -> f32 {
+        match self {
+            Self::Grunt => 1.6,
+            Self::Runner => 3.0,
+            Self::Tank => 0.9,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Group {
+    pub count: u32,
+    /// Seconds between two enemies of the group.
+    pub interval: f32,
+    pub kind: EnemyKind,
+}
+
+pub type Wave = Vec<Group>;
+
+/// Fails with the line number and what is wrong with it.
+pub fn parse(text: &str) -> Result<Vec<Wave>, String> {
+    let mut waves = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let error = |message: &str| format!("line {}: {}", number + 1, message);
+        let mut wave = Vec::new();
+        for group in line.split(',') {
+            let parts: Vec<&str> = group.split_whitespace().collect();
+            let (count, kind, interval) = match parts[..] {
+                [count, kind, interval] => (count, kind, interval),
+                _ => return Err(error("expected <count> <enemy> <seconds>")),
+            };
+            wave.push(Group {
+                count: count.parse().map_err(|_| error("bad count"))?,
+                interval: interval.parse().map_err(|_| error("bad seconds"))?,
+                kind: EnemyKind::parse(kind).ok_or_else(|| error("unknown enemy"))?,
+            });
+        }
+        waves.push(wave);
+    }
+    Ok(waves)
+}
+
+/// Sends the enemies of a wave in order.
+#[derive(Clone, Debug)]
+pub struct Spawner {
+    group: usize,
+    /// Enemies of the current group sent so far.
+    sent: u32,
+    until_next: f32,
+    wave: Wave,
+}
+
+impl Spawner {
+    pub fn new(wave: Wave) -> Self {
+        Self {
+            group: 0,
+            sent: 0,
+            until_next: 0.0,
+            wave,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.group >= self.wave.len()
+    }
+
+    /// Advances by `delta` seconds and returns the enemies to send now.
+    pub fn update(&mut self, delta: f32) -> Vec<EnemyKind> {
+        let mut spawned = Vec::new();
+        self.until_next -= delta;
+        while let Some(group) = self.wave.get(self.group) {
+            if self.sent == group.count {
+                self.group += 1;
+                self.sent = 0;
+                continue;
+            }
+            if self.until_next > 0.0 {
+                break;
+            }
+            spawned.push(group.kind);
+            self.sent += 1;
+            self.until_next += group.interval;
+        }
+        spawned
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_groups_and_skips_comments() {
+        let waves = parse("# comment\n\n2 grunt 1.0\n1 tank 2, 3 runner 0.5\n").unwrap();
+        assert_eq!(waves.len(), 2);
+        assert_eq!(
+            waves[1],
+            vec![
+                Group {
+                    count: 1,
+                    interval: 2.0,
+                    kind: EnemyKind::Tank
+                },
+                Group {
+                    count: 3,
+                    interval: 0.5,
+                    kind: EnemyKind::Runner
+                },
+            ]
+        );
+        assert_eq!(
+            parse("2 grunt\n").unwrap_err(),
+            "line 1: expected <count> <enemy> <seconds>"
+        );
+        assert_eq!(
+            parse("\n2 dragon 1\n").unwrap_err(),
+            "line 2: unknown enemy"
+        );
+    }
+
+    #[test]
+    fn spawns_at_the_intervals_of_each_group() {
+        let wave = parse("2 grunt 1.0, 2 runner 0.5").unwrap().remove(0);
+        let mut spawner = Spawner::new(wave);
+        assert_eq!(spawner.update(0.1), vec![EnemyKind::Grunt]);
+        assert_eq!(spawner.update(0.5), vec![]);
+        assert_eq!(spawner.update(0.5), vec![EnemyKind::Grunt]);
+        // The next group waits for the interval of the last enemy before it.
+        assert_eq!(spawner.update(0.5), vec![]);
+        assert_eq!(spawner.update(0.5), vec![EnemyKind::Runner]);
+        assert!(!spawner.is_done());
+        assert_eq!(spawner.update(0.5), vec![EnemyKind::Runner]);
+        assert!(spawner.is_done());
+        assert_eq!(spawner.update(5.0), vec![]);
+    }
+
+    #[test]
+    fn bundled_waves_are_valid() {
+        let waves = parse(include_str!("../assets/waves.txt")).unwrap();
+        assert_eq!(waves.len(), 10);
+    }
+}
@@ -0,0 +1,615 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::{thread_rng, Rng};
+use round::{Falling, Keystroke, Round};
+use stats::{History, Record};
+use words::{Language, Level};
+
+pub mod round;
+pub mod stats;
+pub mod words;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.1, 0.16);
+/// Width of a character of the falling texts, which are set in a monospaced font.
+const CHAR_WIDTH: f32 = 15.6;
+/// Bars in the chart of past rounds.
+const CHART_BARS: usize = 20;
+/// Where the bars of the chart stand, and how high the fastest one reaches.
+const CHART_BOTTOM: f32 = -250.0;
+const CHART_HEIGHT: f32 = 170.0;
+/// Texts that get this low are lost.
+const FLOOR: f32 = -262.0;
+const FLOOR_COLOR: Color = Color::rgb(0.7, 0.2, 0.2);
+const LIVES: u32 = 3;
+/// Seconds after which texts fall and appear twice as fast as at the start.
+const RAMP_SECONDS: f32 = 90.0;
+const TARGET_COLOR: Color = Color::rgb(1.0, 0.85, 0.4);
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.92, 0.95);
+const TEXT_SIZE: f32 = 26.0;
+/// Where new texts appear.
+const TOP: f32 = 262.0;
+const TYPED_COLOR: Color = Color::rgb(0.4, 0.85, 0.45);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Everything drawn for the falling texts, rebuilt every frame.
+#[derive(Component)]
+struct Actor;
+
+/// The bars, labels and numbers of the statistics screen.
+#[derive(Component)]
+struct ChartItem;
+
+struct Game {
+    language: Language,
+    level: Level,
+    lives: u32,
+    /// Seconds until the next text appears.
+    next_text: f32,
+    round: Round,
+    seconds: f32,
+    sentences: Vec<&'static str>,
+    words: Vec<&'static str>,
+}
+
+impl Game {
+    fn new(language: Language, level: Level) -> Self {
+        Self {
+            language,
+            level,
+            lives: LIVES,
+            next_text: 0.0,
+            round: Round::default(),
+            seconds: 0.0,
+            sentences: language.sentences(),
+            words: language.words(),
+        }
+    }
+
+    /// How much faster than at the start texts fall and appear.
+    fn pace(&self) -> f32 {
+        1.0 + self.seconds / RAMP_SECONDS
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+    Statistics,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+struct Menu {
+    language: usize,
+    level: usize,
+}
+
+/// The menu, or the result of a round.
+#[derive(Component)]
+struct MenuText;
+
+struct Sounds {
+    dropped: Handle<AudioSource>,
+    finished: Handle<AudioSource>,
+    hit: Handle<AudioSource>,
+    miss: Handle<AudioSource>,
+    over: Handle<AudioSource>,
+}
+
+pub struct TypingPlugin;
+
+impl Plugin for TypingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Menu {
+                language: 0,
+                level: 0,
+            })
+            .insert_resource(Game::new(Language::English, Level::Easy))
+            .insert_resource(History::load())
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(play_update),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(over_enter))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system_set(
+                SystemSet::on_enter(GameState::Statistics).with_system(statistics_enter),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Statistics).with_system(statistics_input),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Statistics).with_system(statistics_exit))
+            .add_system(falling_render)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Typing".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// The falling texts, the characters typed so far set apart from the rest.
+fn falling_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() != GameState::Playing {
+        return;
+    }
+    for (index, falling) in game.round.falling.iter().enumerate() {
+        let typed: String = falling.text[..falling.typed].iter().collect();
+        let rest: String = falling.text[falling.typed..].iter().collect();
+        let color = if game.round.target == Some(index) {
+            TARGET_COLOR
+        } else {
+            TEXT_COLOR
+        };
+        let mut bundle = game_hud::world_text(
+            &font,
+            typed,
+            TEXT_SIZE,
+            TYPED_COLOR,
+            Vec3::new(falling.x, falling.y, 10.0),
+        );
+        bundle.text.sections.push(TextSection {
+            value: rest,
+            style: font.style(TEXT_SIZE, color),
+        });
+        commands.spawn_bundle(bundle).insert(Actor);
+    }
+}
+
+fn format_time(seconds: f32) -> String {
+    let seconds = seconds as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// Color of a level's bars in the chart.
+fn level_color(level: Level) -> Color {
+    match level {
+        Level::Easy => Color::rgb(0.35, 0.75, 0.4),
+        Level::Normal => Color::rgb(0.9, 0.75, 0.3),
+        Level::Hard => Color::rgb(0.85, 0.35, 0.3),
+    }
+}
+
+/// Left/Right picks the language, Up/Down the level, Enter starts and Tab shows the statistics.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    navigation: Res<MenuInput>,
+    mut menu: ResMut<Menu>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let languages = Language::ALL.len();
+    if navigation.just_pressed(Navigate::Left) {
+        menu.language = (menu.language + languages - 1) % languages;
+    } else if navigation.just_pressed(Navigate::Right) {
+        menu.language = (menu.language + 1) % languages;
+    } else if navigation.just_pressed(Navigate::Up) {
+        menu.level = menu.level.saturating_sub(1);
+    } else if navigation.just_pressed(Navigate::Down) {
+        menu.level = (menu.level + 1).min(Level::ALL.len() - 1);
+    } else if keyboard_input.just_pressed(KeyCode::Tab) {
+        keyboard_input.clear_just_pressed(KeyCode::Tab);
+        state.set(GameState::Statistics).unwrap();
+    } else if navigation.just_pressed(Navigate::Confirm) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(Language::ALL[menu.language], Level::ALL[menu.level]);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn menu_label(menu: &Menu, history: &History) -> String {
+    let language = Language::ALL[menu.language];
+    let levels = Level::ALL
+        .iter()
+        .enumerate()
+        .map(|(index, level)| {
+            let marker = if index == menu.level { ">" } else { " " };
+            let best = match history.best(language, *level) {
+                Some(record) => format!("best {:>3.0} WPM", record.wpm),
+                None => String::new(),
+            };
+            format!("{} {:<8}{:<12}", marker, level.name(), best)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "TYPING TRAINER\n\n< {:^10} >\n\n{}\n\nLeft/Right: language   Up/Down: level\nEnter: play   Tab: statistics",
+        language.name(),
+        levels
+    )
+}
+
+/// Records the round, unless nothing was typed at all.
+fn over_enter(
+    game: Res<Game>,
+    mut history: ResMut<History>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    audio.play(sounds.over.clone());
+    let tally = game.round.tally;
+    if tally.keystrokes == 0 {
+        return;
+    }
+    history.push(Record {
+        accuracy: tally.accuracy(),
+        day: stats::today(),
+        language: game.language,
+        level: game.level,
+        texts: tally.texts,
+        wpm: tally.wpm(game.seconds),
+    });
+    history.save();
+}
+
+/// Enter plays again with the same language and level, Escape goes back to the menu.
+fn over_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(game.language, game.level);
+        state.set(GameState::Playing).unwrap();
+    } else if keyboard_input.just_pressed(KeyCode::Escape) {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+fn over_label(game: &Game, history: &History) -> String {
+    let tally = game.round.tally;
+    let wpm = tally.wpm(game.seconds);
+    let best = history
+        .best(game.language, game.level)
+        .map_or(0.0, |record| record.wpm);
+    let verdict = if tally.keystrokes > 0 && wpm >= best {
+        "New best!".to_string()
+    } else {
+        format!("Best {:.0} WPM", best)
+    };
+    format!(
+        "ROUND OVER\n\n{:.0} WPM   {:.0}% accuracy\n{} texts in {}\n\n{}",
+        wpm,
+        tally.accuracy(),
+        tally.texts,
+        format_time(game.seconds),
+        verdict
+    )
+}
+
+/// Typed characters go to the falling texts; Backspace lets go of the target and Escape ends
+/// the round.
+fn play_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        keyboard_input.clear_just_pressed(KeyCode::Escape);
+        state.set(GameState::Over).unwrap();
+        return;
+    }
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        game.round.release();
+    }
+    for character in characters.iter().map(|event| event.char) {
+        if character.is_control() {
+            continue;
+        }
+        let sound = match game.round.type_character(character) {
+            Keystroke::Ignored => continue,
+            Keystroke::Hit => &sounds.hit,
+            Keystroke::Miss => &sounds.miss,
+            Keystroke::Finished => &sounds.finished,
+        };
+        audio.play(sound.clone());
+    }
+}
+
+/// Moves the texts down, brings in new ones and takes a life for each that reaches the floor.
+fn play_update(
+    time: Res<Time>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let delta = time.delta_seconds();
+    game.seconds += delta;
+    let pace = game.pace();
+    game.next_text -= delta;
+    if game.next_text <= 0.0 || game.round.falling.is_empty() {
+        game.next_text = game.level.spawn_interval() / pace;
+        let mut rng = thread_rng();
+        let text = words::pick(&game.words, &game.sentences, game.level, &mut rng);
+        let reach = WINDOW_WIDTH / 2.0 - 20.0 - text.chars().count() as f32 * CHAR_WIDTH / 2.0;
+        let x = if reach > 0.0 {
+            rng.gen_range(-reach, reach)
+        } else {
+            0.0
+        };
+        game.round.falling.push(Falling::new(text, x, TOP));
+    }
+    let distance = game.level.fall_speed() * pace * delta;
+    let dropped = game.round.fall(distance, FLOOR) as u32;
+    if dropped == 0 {
+        return;
+    }
+    audio.play(sounds.dropped.clone());
+    game.lives = game.lives.saturating_sub(dropped);
+    if game.lives == 0 {
+        state.set(GameState::Over).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands.spawn_bundle(SpriteBundle {
+        sprite: Sprite {
+            color: FLOOR_COLOR,
+            custom_size: Some(Vec2::new(WINDOW_WIDTH, 2.0)),
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(0.0, FLOOR - TEXT_SIZE / 2.0, 0.0),
+        ..Default::default()
+    });
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 0.0, 900.0),
+        ))
+        .insert(MenuText);
+
+    commands.insert_resource(Sounds {
+        dropped: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 220.0, 0.08).volume(0.15),
+            Tone::new(Waveform::Square, 150.0, 0.16).fade().volume(0.15),
+        ])),
+        finished: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 784.0, 0.05),
+            Tone::new(Waveform::Triangle, 1047.0, 0.08).fade(),
+        ])),
+        hit: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.02)
+                .fade()
+                .volume(0.2)
+                .into(),
+        ),
+        miss: audio_sources.add(Tone::new(Waveform::Square, 140.0, 0.08).volume(0.12).into()),
+        over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 392.0, 0.15),
+            Tone::new(Waveform::Triangle, 262.0, 0.4).fade(),
+        ])),
+    });
+}
+
+/// The best speed per language and level, averages of the last rounds against the ones before,
+/// and a bar per recent round.
+fn statistics_enter(mut commands: Commands, font: Res<HudFont>, history: Res<History>) {
+    let levels: String = Level::ALL
+        .iter()
+        .map(|level| format!("{:>8}", level.name()))
+        .collect();
+    let table = Language::ALL
+        .iter()
+        .map(|language| {
+            let bests: String = Level::ALL
+                .iter()
+                .map(|level| match history.best(*language, *level) {
+                    Some(record) => format!("{:>8.0}", record.wpm),
+                    None => format!("{:>8}", "-"),
+                })
+                .collect();
+            format!("{:<10}{}", language.name(), bests)
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let average = |records: &[Record]| match History::average(records) {
+        Some((wpm, accuracy)) => format!("{:.0} WPM, {:.0}%", wpm, accuracy),
+        None => "-".to_string(),
+    };
+    let records = &history.records;
+    let last = records.len().saturating_sub(10);
+    let summary = format!(
+        "STATISTICS\n\n{} rounds on {} days\nLast 10: {}   Before: {}\n\nBest WPM\n{:<10}{}\n{}",
+        records.len(),
+        history.days(),
+        average(&records[last..]),
+        average(&records[last.saturating_sub(10)..last]),
+        "",
+        levels,
+        table
+    );
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            summary,
+            18.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 150.0, 10.0),
+        ))
+        .insert(ChartItem);
+
+    let recent = history.recent(CHART_BARS);
+    let fastest = recent.iter().map(|record| record.wpm).fold(10.0, f32::max);
+    let spacing = (WINDOW_WIDTH - 120.0) / CHART_BARS as f32;
+    for (index, record) in recent.iter().enumerate() {
+        let height = (record.wpm / fastest * CHART_HEIGHT).max(2.0);
+        let x = (index as f32 - (CHART_BARS as f32 - 1.0) / 2.0) * spacing;
+        commands
+            .spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: level_color(record.level),
+                    custom_size: Some(Vec2::new(spacing * 0.7, height)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(x, CHART_BOTTOM + height / 2.0, 5.0),
+                ..Default::default()
+            })
+            .insert(ChartItem);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                format!("{:.0}", record.wpm),
+                13.0,
+                TEXT_COLOR,
+                Vec3::new(x, CHART_BOTTOM + height + 10.0, 10.0),
+            ))
+            .insert(ChartItem);
+    }
+    let legend = Level::ALL.iter().enumerate().map(|(index, level)| {
+        (
+            level,
+            Vec3::new((index as f32 - 1.0) * 140.0, CHART_BOTTOM - 22.0, 10.0),
+        )
+    });
+    for (level, position) in legend {
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                format!("\u{25A0} {}", level.name()),
+                14.0,
+                level_color(*level),
+                position,
+            ))
+            .insert(ChartItem);
+    }
+}
+
+fn statistics_exit(mut commands: Commands, items: Query<Entity, With<ChartItem>>) {
+    for entity in items.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn statistics_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+) {
+    for key in [KeyCode::Tab, KeyCode::Escape, KeyCode::Return] {
+        if keyboard_input.just_pressed(key) {
+            keyboard_input.clear_just_pressed(key);
+            state.set(GameState::Menu).unwrap();
+            return;
+        }
+    }
+}
+
+fn texts_update(
+    game: Res<Game>,
+    menu: Res<Menu>,
+    history: Res<History>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MenuText>>,
+    )>,
+) {
+    let tally = game.round.tally;
+    let (hud, help, overlay) = match state.current() {
+        GameState::Menu => (String::new(), String::new(), menu_label(&menu, &history)),
+        GameState::Playing => (
+            format!(
+                "{} {}   Lives {}   Texts {}   {:.0} WPM   {:.0}%   {}",
+                game.language.name(),
+                game.level.name(),
+                "\u{2665}".repeat(game.lives as usize),
+                tally.texts,
+                tally.wpm(game.seconds),
+                tally.accuracy(),
+                format_time(game.seconds)
+            ),
+            "Type the falling texts before they land   Backspace: pick another text   Esc: end round"
+                .to_string(),
+            String::new(),
+        ),
+        GameState::Over => (
+            String::new(),
+            "Enter: play again   Esc: menu".to_string(),
+            over_label(&game, &history),
+        ),
+        GameState::Statistics => (
+            String::new(),
+            "Colors show the level   Tab/Esc: menu".to_string(),
+            String::new(),
+        ),
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.clone();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = overlay.clone();
+    }
+}
@@ -0,0 +1,164 @@
+//! The texts falling down the screen and the keystrokes aimed at them.
+
+use crate::stats::Tally;
+
+/// A text on its way down.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Falling {
+    pub text: Vec<char>,
+    /// Characters typed so far.
+    pub typed: usize,
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Falling {
+    pub fn new(text: &str, x: f32, y: f32) -> Self {
+        Self {
+            text: text.chars().collect(),
+            typed: 0,
+            x,
+            y,
+        }
+    }
+}
+
+/// What a typed character did.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Keystroke {
+    /// Nothing was aimed at, e.g. a space between words.
+    Ignored,
+    Hit,
+    Miss,
+    /// The last character of a text, which is gone now.
+    Finished,
+}
+
+#[derive(Default)]
+pub struct Round {
+    pub falling: Vec<Falling>,
+    /// The text being typed, once its first character is.
+    pub target: Option<usize>,
+    pub tally: Tally,
+}
+
+impl Round {
+    /// Drops the texts that fell below `floor` after moving all of them down by `distance`,
+    /// returning how many did.
+    pub fn fall(&mut self, distance: f32, floor: f32) -> usize {
+        for falling in &mut self.falling {
+            falling.y -= distance;
+        }
+        let mut dropped = 0;
+        let mut index = 0;
+        while index < self.falling.len() {
+            if self.falling[index].y < floor {
+                self.remove(index);
+                dropped += 1;
+            } else {
+                index += 1;
+            }
+        }
+        dropped
+    }
+
+    /// Types `character` into the target, or picks as target the lowest text starting with it.
+    pub fn type_character(&mut self, character: char) -> Keystroke {
+        let target = match self.target {
+            Some(target) => target,
+            None => {
+                let lowest = (0..self.falling.len())
+                    .filter(|index| self.falling[*index].text.first() == Some(&character))
+                    .min_by(|a, b| self.falling[*a].y.total_cmp(&self.falling[*b].y));
+                match lowest {
+                    Some(index) => index,
+                    None if character == ' ' => return Keystroke::Ignored,
+                    None => {
+                        self.tally.keystrokes += 1;
+                        return Keystroke::Miss;
+                    }
+                }
+            }
+        };
+        self.tally.keystrokes += 1;
+        let falling = &mut self.falling[target];
+        if falling.text[falling.typed] != character {
+            return Keystroke::Miss;
+        }
+        self.tally.correct += 1;
+        falling.typed += 1;
+        if falling.typed < falling.text.len() {
+            self.target = Some(target);
+            return Keystroke::Hit;
+        }
+        // The space that would follow the text counts towards the speed, as is usual for WPM.
+        self.tally.characters += falling.text.len() as u32 + 1;
+        self.tally.texts += 1;
+        self.remove(target);
+        Keystroke::Finished
+    }
+
+    /// Lets go of the target so that another text can be picked, typed characters and all.
+    pub fn release(&mut self) {
+        if let Some(target) = self.target.take() {
+            self.falling[target].typed = 0;
+        }
+    }
+
+    fn remove(&mut self, index: usize) {
+        self.falling.remove(index);
+        self.target = match self.target {
+            Some(target) if target == index => None,
+            Some(target) if target > index => Some(target - 1),
+            target => target,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round() -> Round {
+        Round {
+            falling: vec![
+                Falling::new("tea", -100.0, 50.0),
+                Falling::new("two", 100.0, -20.0),
+                Falling::new("cat", 0.0, 0.0),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn targets_the_lowest_matching_text() {
+        let mut round = round();
+        assert_eq!(round.type_character('t'), Keystroke::Hit);
+        assert_eq!(round.target, Some(1));
+        assert_eq!(round.type_character('e'), Keystroke::Miss);
+        assert_eq!(round.type_character('w'), Keystroke::Hit);
+        assert_eq!(round.type_character('o'), Keystroke::Finished);
+        assert_eq!(round.target, None);
+        assert_eq!(round.falling.len(), 2);
+        assert_eq!(round.tally.keystrokes, 4);
+        assert_eq!(round.tally.correct, 3);
+        assert_eq!(round.tally.characters, 4);
+        assert_eq!(round.type_character(' '), Keystroke::Ignored);
+        assert_eq!(round.type_character('x'), Keystroke::Miss);
+    }
+
+    #[test]
+    fn falling_drops_texts_and_keeps_the_target() {
+        let mut round = round();
+        round.type_character('c');
+        assert_eq!(round.fall(30.0, -40.0), 1);
+        assert_eq!(round.falling.len(), 2);
+        assert_eq!(round.target, Some(1));
+        assert_eq!(round.falling[1].typed, 1);
+        round.release();
+        assert_eq!(round.target, None);
+        assert_eq!(round.falling[1].typed, 0);
+        assert_eq!(round.fall(100.0, -40.0), 2);
+        assert!(round.falling.is_empty());
+    }
+}
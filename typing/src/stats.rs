@@ -0,0 +1,170 @@
+//! Speed and accuracy of a round, and every finished round kept between sessions.
+
+use crate::words::{Language, Level};
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const GAME: &str = "typing";
+const HISTORY_FILE: &str = "history";
+/// Rounds kept; older ones are forgotten.
+const MAX_ROUNDS: usize = 500;
+
+/// Keystrokes of a round.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    /// Characters of the finished texts, with a space after each.
+    pub characters: u32,
+    pub correct: u32,
+    pub keystrokes: u32,
+    /// Texts typed to the end.
+    pub texts: u32,
+}
+
+impl Tally {
+    /// Share of the keystrokes that were right, in percent.
+    pub fn accuracy(&self) -> f32 {
+        if self.keystrokes == 0 {
+            return 100.0;
+        }
+        self.correct as f32 * 100.0 / self.keystrokes as f32
+    }
+
+    /// Words per minute, a word being five characters.
+    pub fn wpm(&self, seconds: f32) -> f32 {
+        if seconds <= 0.0 {
+            return 0.0;
+        }
+        self.characters as f32 / 5.0 / (seconds / 60.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    pub accuracy: f32,
+    /// Days since 1970-01-01.
+    pub day: u64,
+    pub language: Language,
+    pub level: Level,
+    pub texts: u32,
+    pub wpm: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct History {
+    /// Oldest first.
+    pub records: Vec<Record>,
+}
+
+impl History {
+    /// Mean speed and accuracy of the given records.
+    pub fn average<'a>(records: impl IntoIterator<Item = &'a Record>) -> Option<(f32, f32)> {
+        let (mut count, mut wpm, mut accuracy) = (0, 0.0, 0.0);
+        for record in records {
+            count += 1;
+            wpm += record.wpm;
+            accuracy += record.accuracy;
+        }
+        if count == 0 {
+            return None;
+        }
+        Some((wpm / count as f32, accuracy / count as f32))
+    }
+
+    /// The fastest round in the language at the level.
+    pub fn best(&self, language: Language, level: Level) -> Option<&Record> {
+        self.records
+            .iter()
+            .filter(|record| record.language == language && record.level == level)
+            .max_by(|a, b| a.wpm.total_cmp(&b.wpm))
+    }
+
+    /// Distinct days with at least one round.
+    pub fn days(&self) -> usize {
+        let mut days: Vec<u64> = self.records.iter().map(|record| record.day).collect();
+        days.dedup();
+        days.len()
+    }
+
+    pub fn load() -> Self {
+        game_persistence::load_or_default(GAME, HISTORY_FILE)
+    }
+
+    pub fn push(&mut self, record: Record) {
+        self.records.push(record);
+        if self.records.len() > MAX_ROUNDS {
+            self.records.remove(0);
+        }
+    }
+
+    /// The last `count` records, oldest first.
+    pub fn recent(&self, count: usize) -> &[Record] {
+        &self.records[self.records.len().saturating_sub(count)..]
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save(GAME, HISTORY_FILE, self) {
+            eprintln!("could not save statistics: {}", error);
+        }
+    }
+}
+
+/// Days since 1970-01-01 in UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / (24 * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(day: u64, level: Level, wpm: f32) -> Record {
+        Record {
+            accuracy: 90.0,
+            day,
+            language: Language::English,
+            level,
+            texts: 10,
+            wpm,
+        }
+    }
+
+    #[test]
+    fn speed_and_accuracy() {
+        let tally = Tally {
+            characters: 150,
+            correct: 90,
+            keystrokes: 120,
+            texts: 25,
+        };
+        assert_eq!(tally.wpm(60.0), 30.0);
+        assert_eq!(tally.wpm(30.0), 60.0);
+        assert_eq!(tally.accuracy(), 75.0);
+        assert_eq!(Tally::default().wpm(0.0), 0.0);
+        assert_eq!(Tally::default().accuracy(), 100.0);
+    }
+
+    #[test]
+    fn history_over_time() {
+        let mut history = History::default();
+        history.push(record(10, Level::Easy, 30.0));
+        history.push(record(10, Level::Normal, 40.0));
+        history.push(record(12, Level::Easy, 36.0));
+        assert_eq!(history.days(), 2);
+        assert_eq!(
+            history.best(Language::English, Level::Easy).unwrap().wpm,
+            36.0
+        );
+        assert!(history.best(Language::French, Level::Easy).is_none());
+        assert_eq!(history.recent(2).len(), 2);
+        assert_eq!(history.recent(2)[0].wpm, 40.0);
+        assert_eq!(History::average(history.recent(2)), Some((38.0, 90.0)));
+        assert_eq!(History::average(&[]), None);
+        for day in 0..MAX_ROUNDS as u64 {
+            history.push(record(day + 20, Level::Hard, 50.0));
+        }
+        assert_eq!(history.records.len(), MAX_ROUNDS);
+        assert_eq!(history.records[0].day, 20);
+    }
+}
@@ -0,0 +1,166 @@
+//! The bundled word lists, one word per line, with short sentences for the hard level, and
+//! what each level asks for.
+
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+/// One in this many texts on the hard level is a sentence.
+const SENTENCE_ODDS: u32 = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Language {
+    English,
+    German,
+    Spanish,
+    French,
+}
+
+impl Language {
+    pub const ALL: [Language; 4] = [
+        Language::English,
+        Language::German,
+        Language::Spanish,
+        Language::French,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::English => "English",
+            Self::German => "Deutsch",
+            Self::Spanish => "Español",
+            Self::French => "Français",
+        }
+    }
+
+    pub fn sentences(self) -> Vec<&'static str> {
+        lines(match self {
+            Self::English => include_str!("../assets/sentences/english.txt"),
+            Self::German => include_str!("../assets/sentences/german.txt"),
+            Self::Spanish => include_str!("../assets/sentences/spanish.txt"),
+            Self::French => include_str!("../assets/sentences/french.txt"),
+        })
+    }
+
+    pub fn words(self) -> Vec<&'static str> {
+        lines(match self {
+            Self::English => include_str!("../assets/words/english.txt"),
+            Self::German => include_str!("../assets/words/german.txt"),
+            Self::Spanish => include_str!("../assets/words/spanish.txt"),
+            Self::French => include_str!("../assets/words/french.txt"),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Level {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Level {
+    pub const ALL: [Level; 3] = [Level::Easy, Level::Normal, Level::Hard];
+
+    /// Pixels a text falls per second at the start.
+    pub fn fall_speed(self) -> f32 {
+        match self {
+            Self::Easy => 28.0,
+            Self::Normal => 40.0,
+            Self::Hard => 52.0,
+        }
+    }
+
+    /// Shortest and longest word, in characters.
+    pub fn lengths(self) -> (usize, usize) {
+        match self {
+            Self::Easy => (2, 5),
+            Self::Normal => (4, 8),
+            Self::Hard => (6, 14),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Easy => "Easy",
+            Self::Normal => "Normal",
+            Self::Hard => "Hard",
+        }
+    }
+
+    /// Seconds between two texts at the start.
+    pub fn spawn_interval(self) -> f32 {
+        match self {
+            Self::Easy => 2.6,
+            Self::Normal => 2.0,
+            Self::Hard => 1.8,
+        }
+    }
+}
+
+/// A word fit for the level, or now and then a sentence on the hard level.
+pub fn pick<'a>(
+    words: &[&'a str],
+    sentences: &[&'a str],
+    level: Level,
+    rng: &mut impl Rng,
+) -> &'a str {
+    if level == Level::Hard && !sentences.is_empty() && rng.gen_range(0, SENTENCE_ODDS) == 0 {
+        return sentences.choose(rng).unwrap();
+    }
+    let (shortest, longest) = level.lengths();
+    let fitting: Vec<&str> = words
+        .iter()
+        .copied()
+        .filter(|word| (shortest..=longest).contains(&word.chars().count()))
+        .collect();
+    fitting
+        .choose(rng)
+        .or_else(|| words.choose(rng))
+        .copied()
+        .unwrap_or("")
+}
+
+fn lines(text: &'static str) -> Vec<&'static str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    #[test]
+    fn every_language_has_words_for_every_level() {
+        for language in Language::ALL {
+            let words = language.words();
+            assert!(!language.sentences().is_empty());
+            for level in Level::ALL {
+                let (shortest, longest) = level.lengths();
+                let fitting = words
+                    .iter()
+                    .filter(|word| (shortest..=longest).contains(&word.chars().count()))
+                    .count();
+                assert!(fitting >= 40, "{:?} {:?}", language, level);
+            }
+        }
+    }
+
+    #[test]
+    fn picks_fit_the_level() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let words = Language::English.words();
+        let sentences = Language::English.sentences();
+        for _ in 0..100 {
+            let word = pick(&words, &sentences, Level::Easy, &mut rng);
+            assert!((2..=5).contains(&word.len()), "{}", word);
+        }
+        let hard: Vec<&str> = (0..100)
+            .map(|_| pick(&words, &sentences, Level::Hard, &mut rng))
+            .collect();
+        assert!(hard.iter().any(|text| text.contains(' ')));
+        assert!(hard.iter().all(|text| text.len() >= 6));
+    }
+}
@@ -0,0 +1,614 @@
+use bevy::{math::const_vec2, prelude::*};
+use game_audio::{Tone, Waveform};
+use game_cards::Card;
+use game_hud::{HudFont, HudPlugin};
+use poker::{Hand, MAX_COINS};
+use rand::{seq::SliceRandom, thread_rng};
+use serde::{Deserialize, Serialize};
+
+pub mod poker;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.05, 0.08, 0.3);
+const BANKROLL_FILE: &str = "bankroll";
+const BET_COLUMN_COLOR: Color = Color::rgb(0.55, 0.08, 0.1);
+const CARD_SIZE: Vec2 = const_vec2!([120.0, 168.0]);
+const CARD_SPACING: f32 = 145.0;
+const CARD_Y: f32 = -60.0;
+const COLUMN_WIDTH: f32 = 80.0;
+const HELD_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+/// How far a held card is raised.
+const HELD_LIFT: f32 = 12.0;
+const NAME_X: f32 = -330.0;
+const PAYTABLE_TOP: f32 = 290.0;
+const ROW_HEIGHT: f32 = 19.0;
+/// Credits to start with, and to start over with once broke.
+const STARTING_CREDITS: u32 = 200;
+const STATISTICS_FILE: &str = "statistics";
+const TEXT_COLOR: Color = Color::rgb(0.95, 0.95, 0.9);
+const WIN_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+#[derive(Serialize, Deserialize)]
+struct Bankroll {
+    best: u32,
+    credits: u32,
+}
+
+impl Default for Bankroll {
+    fn default() -> Self {
+        Self {
+            best: STARTING_CREDITS,
+            credits: STARTING_CREDITS,
+        }
+    }
+}
+
+impl Bankroll {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("videopoker", BANKROLL_FILE, self) {
+            eprintln!("could not save bankroll: {}", error);
+        }
+    }
+}
+
+/// Marks the column of the paytable for the current bet.
+#[derive(Component)]
+struct BetColumn;
+
+/// Cards and their labels, rebuilt whenever the machine changes.
+#[derive(Component)]
+struct CardSprite;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Betting,
+    /// Five cards are dealt and the player picks which to hold.
+    Holding,
+    Statistics,
+}
+
+#[derive(Component)]
+struct HelpText;
+
+#[derive(Component)]
+struct HudText;
+
+struct Machine {
+    /// The five cards on screen; empty before the first deal.
+    cards: Vec<Card>,
+    coins: u32,
+    /// What is left of the deck to draw from.
+    deck: Vec<Card>,
+    held: [bool; 5],
+    message: String,
+    /// The paying hand on screen, lit up in the paytable.
+    result: Option<Hand>,
+}
+
+impl Machine {
+    fn hand(&self) -> Option<[Card; 5]> {
+        self.cards.as_slice().try_into().ok()
+    }
+}
+
+#[derive(Component)]
+struct MessageText;
+
+/// A hand name or pay in the paytable, by row.
+#[derive(Component)]
+struct PaytableCell {
+    row: usize,
+}
+
+struct Sounds {
+    card: Handle<AudioSource>,
+    coin: Handle<AudioSource>,
+    hold: Handle<AudioSource>,
+    jackpot: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+}
+
+/// Hands achieved over every session, kept with the bankroll.
+#[derive(Default, Serialize, Deserialize)]
+struct Statistics {
+    /// Per paying hand, in paytable order.
+    hands: [u32; 9],
+    played: u32,
+    wagered: u64,
+    won: u64,
+}
+
+impl Statistics {
+    fn save(&self) {
+        if let Err(error) = game_persistence::save("videopoker", STATISTICS_FILE, self) {
+            eprintln!("could not save statistics: {}", error);
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatisticsText;
+
+pub struct VideoPokerPlugin;
+
+impl Plugin for VideoPokerPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(game_persistence::load_or_default::<Bankroll>(
+                "videopoker",
+                BANKROLL_FILE,
+            ))
+            .insert_resource(game_persistence::load_or_default::<Statistics>(
+                "videopoker",
+                STATISTICS_FILE,
+            ))
+            .insert_resource(Machine {
+                cards: Vec::new(),
+                coins: MAX_COINS,
+                deck: Vec::new(),
+                held: [false; 5],
+                message: "Press Enter to deal".to_string(),
+                result: None,
+            })
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Betting)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_enter(GameState::Betting).with_system(betting_enter))
+            .add_system_set(SystemSet::on_update(GameState::Betting).with_system(betting_input))
+            .add_system_set(SystemSet::on_update(GameState::Holding).with_system(holding_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Statistics).with_system(statistics_input),
+            )
+            .add_system(cards_render)
+            .add_system(paytable_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Video Poker".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Starts over with a fresh stack once the credits ran out.
+fn betting_enter(mut machine: ResMut<Machine>, mut bankroll: ResMut<Bankroll>) {
+    if bankroll.credits == 0 {
+        bankroll.credits = STARTING_CREDITS;
+        bankroll.save();
+        machine.message = format!("Out of credits: starting over with {}", STARTING_CREDITS);
+    }
+    machine.coins = machine.coins.min(bankroll.credits).max(1);
+}
+
+/// Up/Down changes the bet, Enter deals and M deals with the maximum bet.
+fn betting_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut machine: ResMut<Machine>,
+    mut bankroll: ResMut<Bankroll>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let most = bankroll.credits.min(MAX_COINS);
+    if keyboard_input.just_pressed(KeyCode::Tab) {
+        keyboard_input.clear_just_pressed(KeyCode::Tab);
+        state.set(GameState::Statistics).unwrap();
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Up, KeyCode::Right]) && machine.coins < most {
+        machine.coins += 1;
+        audio.play(sounds.coin.clone());
+    } else if keyboard_input.any_just_pressed([KeyCode::Down, KeyCode::Left]) && machine.coins > 1 {
+        machine.coins -= 1;
+        audio.play(sounds.coin.clone());
+    }
+    if keyboard_input.just_pressed(KeyCode::M) {
+        machine.coins = most;
+    } else if !keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        return;
+    }
+
+    bankroll.credits -= machine.coins;
+    bankroll.save();
+    let mut deck = game_cards::standard_deck();
+    deck.shuffle(&mut thread_rng());
+    machine.cards = deck.split_off(deck.len() - 5);
+    machine.deck = deck;
+    machine.held = [false; 5];
+    // A hand that already pays is shown right away, as on a real machine.
+    machine.result = machine.hand().and_then(|hand| poker::evaluate(&hand));
+    machine.message = match machine.result {
+        Some(hand) => hand.name().to_string(),
+        None => String::new(),
+    };
+    audio.play(sounds.card.clone());
+    state.set(GameState::Holding).unwrap();
+}
+
+fn card_translation(index: usize, held: bool) -> Vec3 {
+    let lift = if held { HELD_LIFT } else { 0.0 };
+    Vec3::new(
+        (index as f32 - 2.0) * CARD_SPACING,
+        CARD_Y + lift,
+        1.0 + index as f32 * 0.1,
+    )
+}
+
+/// Rebuilds the cards, the HELD marks and the key labels whenever the machine changes.
+fn cards_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    machine: Res<Machine>,
+    state: Res<State<GameState>>,
+    sprites: Query<Entity, With<CardSprite>>,
+) {
+    if !machine.is_changed() && !state.is_changed() {
+        return;
+    }
+    for entity in sprites.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    if *state.current() == GameState::Statistics {
+        return;
+    }
+    let holding = *state.current() == GameState::Holding;
+    for index in 0..5 {
+        let held = holding && machine.held[index];
+        let translation = card_translation(index, held);
+        game_cards::spawn_card(
+            &mut commands,
+            &font,
+            machine.cards.get(index).copied(),
+            CARD_SIZE,
+            translation,
+        )
+        .insert(CardSprite);
+        if held {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    "HELD",
+                    22.0,
+                    HELD_COLOR,
+                    Vec3::new(translation.x, CARD_Y + CARD_SIZE.y / 2.0 + 30.0, 10.0),
+                ))
+                .insert(CardSprite);
+        }
+        if holding {
+            commands
+                .spawn_bundle(game_hud::world_text(
+                    &font,
+                    (index + 1).to_string(),
+                    18.0,
+                    TEXT_COLOR,
+                    Vec3::new(translation.x, CARD_Y - CARD_SIZE.y / 2.0 - 20.0, 10.0),
+                ))
+                .insert(CardSprite);
+        }
+    }
+}
+
+fn cell_position(column: usize, row: usize) -> Vec2 {
+    let y = PAYTABLE_TOP - row as f32 * ROW_HEIGHT;
+    if column == 0 {
+        Vec2::new(NAME_X, y)
+    } else {
+        Vec2::new(column_x(column), y)
+    }
+}
+
+fn column_x(column: usize) -> f32 {
+    column as f32 * COLUMN_WIDTH - 60.0
+}
+
+/// 1-5 or a click holds a card, Enter draws the rest and pays the hand.
+fn holding_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut machine: ResMut<Machine>,
+    mut bankroll: ResMut<Bankroll>,
+    mut statistics: ResMut<Statistics>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let keys = [
+        [KeyCode::Key1, KeyCode::Numpad1],
+        [KeyCode::Key2, KeyCode::Numpad2],
+        [KeyCode::Key3, KeyCode::Numpad3],
+        [KeyCode::Key4, KeyCode::Numpad4],
+        [KeyCode::Key5, KeyCode::Numpad5],
+    ];
+    let mut toggled = keys
+        .iter()
+        .position(|keys| keyboard_input.any_just_pressed(*keys));
+    if mouse_input.just_pressed(MouseButton::Left) {
+        if let Some(cursor) = game_hud::cursor_world_position(&windows) {
+            toggled = toggled.or_else(|| {
+                (0..5).find(|index| {
+                    let center = card_translation(*index, machine.held[*index]).truncate();
+                    let offset = (cursor - center).abs();
+                    offset.x <= CARD_SIZE.x / 2.0 && offset.y <= CARD_SIZE.y / 2.0
+                })
+            });
+        }
+    }
+    if let Some(index) = toggled {
+        machine.held[index] = !machine.held[index];
+        audio.play(sounds.hold.clone());
+    }
+    if !keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        return;
+    }
+    // Otherwise the same key press would deal the next hand right away.
+    keyboard_input.clear_just_pressed(KeyCode::Return);
+    keyboard_input.clear_just_pressed(KeyCode::Space);
+
+    for index in 0..5 {
+        if !machine.held[index] {
+            machine.cards[index] = machine.deck.pop().unwrap();
+        }
+    }
+    machine.result = machine.hand().and_then(|hand| poker::evaluate(&hand));
+    let paid = match machine.result {
+        Some(hand) => hand.payout(machine.coins),
+        None => 0,
+    };
+    bankroll.credits += paid;
+    bankroll.best = bankroll.best.max(bankroll.credits);
+    bankroll.save();
+    statistics.played += 1;
+    statistics.wagered += machine.coins as u64;
+    statistics.won += paid as u64;
+    if let Some(hand) = machine.result {
+        statistics.hands[hand.index()] += 1;
+    }
+    statistics.save();
+
+    machine.message = match machine.result {
+        Some(hand) => format!("{}: you win {}", hand.name(), paid),
+        None => "No win".to_string(),
+    };
+    match machine.result {
+        Some(Hand::RoyalFlush | Hand::StraightFlush | Hand::FourOfAKind) => {
+            audio.play(sounds.jackpot.clone());
+        }
+        Some(_) => audio.play(sounds.win.clone()),
+        None => audio.play(sounds.card.clone()),
+    }
+    state.set(GameState::Betting).unwrap();
+}
+
+/// Lights up the column of the current bet and the row of the hand on screen.
+fn paytable_update(
+    machine: Res<Machine>,
+    state: Res<State<GameState>>,
+    mut cells: Query<(&PaytableCell, &mut Text, &mut Visibility)>,
+    mut columns: Query<(&mut Transform, &mut Visibility), (With<BetColumn>, Without<PaytableCell>)>,
+) {
+    if !machine.is_changed() && !state.is_changed() {
+        return;
+    }
+    let shown = *state.current() != GameState::Statistics;
+    for (mut transform, mut visibility) in columns.iter_mut() {
+        transform.translation.x = column_x(machine.coins as usize);
+        visibility.is_visible = shown;
+    }
+    let lit = machine.result.map(Hand::index);
+    for (cell, mut text, mut visibility) in cells.iter_mut() {
+        visibility.is_visible = shown;
+        text.sections[0].style.color = if lit == Some(cell.row) {
+            WIN_COLOR
+        } else {
+            TEXT_COLOR
+        };
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(34.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            16.0,
+            TEXT_COLOR,
+            Rect {
+                bottom: Val::Px(8.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HelpText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            26.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 80.0, 20.0),
+        ))
+        .insert(MessageText);
+    let mut statistics =
+        game_hud::world_text(&font, "", 18.0, TEXT_COLOR, Vec3::new(-260.0, 40.0, 20.0));
+    statistics.text.alignment.horizontal = HorizontalAlign::Left;
+    commands.spawn_bundle(statistics).insert(StatisticsText);
+
+    let rows = Hand::ALL.len() as f32;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: BET_COLUMN_COLOR,
+                custom_size: Some(Vec2::new(COLUMN_WIDTH - 6.0, rows * ROW_HEIGHT + 8.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(
+                column_x(MAX_COINS as usize),
+                PAYTABLE_TOP - (rows - 1.0) * ROW_HEIGHT / 2.0,
+                0.0,
+            ),
+            ..Default::default()
+        })
+        .insert(BetColumn);
+    for (row, hand) in Hand::ALL.iter().enumerate() {
+        for column in 0..=MAX_COINS as usize {
+            let value = if column == 0 {
+                hand.name().to_string()
+            } else {
+                hand.payout(column as u32).to_string()
+            };
+            let mut text = game_hud::world_text(
+                &font,
+                value,
+                16.0,
+                TEXT_COLOR,
+                cell_position(column, row).extend(5.0),
+            );
+            if column == 0 {
+                // Hand names line up on the left.
+                text.text.alignment.horizontal = HorizontalAlign::Left;
+            }
+            commands.spawn_bundle(text).insert(PaytableCell { row });
+        }
+    }
+
+    commands.insert_resource(Sounds {
+        card: audio_sources.add(
+            Tone::new(Waveform::Noise, 0.0, 0.06)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        coin: audio_sources.add(
+            Tone::new(Waveform::Square, 1200.0, 0.04)
+                .volume(0.12)
+                .into(),
+        ),
+        hold: audio_sources.add(Tone::new(Waveform::Square, 660.0, 0.04).volume(0.15).into()),
+        jackpot: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 659.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 784.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1047.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1319.0, 0.4).fade().volume(0.2),
+        ])),
+        win: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 880.0, 0.25),
+        ])),
+    });
+}
+
+/// Tab or Esc goes back to the machine, R clears the statistics.
+fn statistics_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut statistics: ResMut<Statistics>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::R) {
+        *statistics = Statistics::default();
+        statistics.save();
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Tab, KeyCode::Escape, KeyCode::Return]) {
+        keyboard_input.clear_just_pressed(KeyCode::Tab);
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Betting).unwrap();
+    }
+}
+
+/// The statistics screen: how often each hand came up and what the machine paid back.
+fn statistics_text(statistics: &Statistics) -> String {
+    let mut lines = vec![
+        format!("{:<18}{:>10}", "Hands played", statistics.played),
+        format!("{:<18}{:>10}", "Credits bet", statistics.wagered),
+        format!("{:<18}{:>10}", "Credits won", statistics.won),
+    ];
+    if statistics.wagered > 0 {
+        let rate = statistics.won as f32 / statistics.wagered as f32 * 100.0;
+        lines.push(format!("{:<18}{:>9.1}%", "Return", rate));
+    }
+    lines.push(String::new());
+    for hand in Hand::ALL {
+        let count = statistics.hands[hand.index()];
+        let frequency = match statistics.played.checked_div(count) {
+            Some(every) => format!("1 in {}", every),
+            None => "-".to_string(),
+        };
+        lines.push(format!("{:<18}{:>10}{:>16}", hand.name(), count, frequency));
+    }
+    lines.join("\n")
+}
+
+fn texts_update(
+    machine: Res<Machine>,
+    bankroll: Res<Bankroll>,
+    statistics: Res<Statistics>,
+    state: Res<State<GameState>>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<HelpText>>,
+        QueryState<&mut Text, With<MessageText>>,
+        QueryState<(&mut Text, &mut Visibility), With<StatisticsText>>,
+    )>,
+) {
+    if !machine.is_changed()
+        && !bankroll.is_changed()
+        && !statistics.is_changed()
+        && !state.is_changed()
+    {
+        return;
+    }
+    let showing_statistics = *state.current() == GameState::Statistics;
+    let hud = format!(
+        "CREDITS {}   BET {}   BEST {}",
+        bankroll.credits, machine.coins, bankroll.best
+    );
+    let help = match state.current() {
+        GameState::Betting => "Up/Down: bet 1-5   M: max bet   Enter: deal   Tab: statistics",
+        GameState::Holding => "1-5 / click: hold   Enter: draw",
+        GameState::Statistics => "R: reset   Tab / Esc: back",
+    };
+    let message = if showing_statistics {
+        "STATISTICS".to_string()
+    } else {
+        machine.message.clone()
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = help.to_string();
+    }
+    for mut text in texts.q2().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+    for (mut text, mut visibility) in texts.q3().iter_mut() {
+        visibility.is_visible = showing_statistics;
+        text.sections[0].value = statistics_text(&statistics);
+    }
+}
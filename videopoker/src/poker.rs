@@ -0,0 +1,191 @@
+//! Hand evaluation and the full pay (9/6) Jacks or Better paytable.
+
+use game_cards::Card;
+
+/// Coins that can be bet on a hand; the royal flush pays a bonus on the last one.
+pub const MAX_COINS: u32 = 5;
+
+/// The paying hands, best first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hand {
+    RoyalFlush,
+    StraightFlush,
+    FourOfAKind,
+    FullHouse,
+    Flush,
+    Straight,
+    ThreeOfAKind,
+    TwoPair,
+    JacksOrBetter,
+}
+
+impl Hand {
+    pub const ALL: [Hand; 9] = [
+        Self::RoyalFlush,
+        Self::StraightFlush,
+        Self::FourOfAKind,
+        Self::FullHouse,
+        Self::Flush,
+        Self::Straight,
+        Self::ThreeOfAKind,
+        Self::TwoPair,
+        Self::JacksOrBetter,
+    ];
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|hand| *hand == self).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::RoyalFlush => "Royal Flush",
+            Self::StraightFlush => "Straight Flush",
+            Self::FourOfAKind => "Four of a Kind",
+            Self::FullHouse => "Full House",
+            Self::Flush => "Flush",
+            Self::Straight => "Straight",
+            Self::ThreeOfAKind => "Three of a Kind",
+            Self::TwoPair => "Two Pair",
+            Self::JacksOrBetter => "Jacks or Better",
+        }
+    }
+
+    /// Credits paid with `coins` bet. The bet is taken on the deal, so Jacks or Better only
+    /// gives it back.
+    pub fn payout(self, coins: u32) -> u32 {
+        let per_coin = match self {
+            Self::RoyalFlush if coins == MAX_COINS => return 4000,
+            Self::RoyalFlush => 250,
+            Self::StraightFlush => 50,
+            Self::FourOfAKind => 25,
+            Self::FullHouse => 9,
+            Self::Flush => 6,
+            Self::Straight => 4,
+            Self::ThreeOfAKind => 3,
+            Self::TwoPair => 2,
+            Self::JacksOrBetter => 1,
+        };
+        per_coin * coins
+    }
+}
+
+/// The paying hand the cards make, if any.
+pub fn evaluate(cards: &[Card; 5]) -> Option<Hand> {
+    let mut counts = [0u8; 14];
+    for card in cards {
+        counts[card.rank as usize] += 1;
+    }
+    let mut groups: Vec<u8> = counts.iter().copied().filter(|count| *count > 1).collect();
+    groups.sort_unstable();
+    let mut ranks: Vec<u8> = cards.iter().map(|card| card.rank).collect();
+    ranks.sort_unstable();
+
+    let flush = cards.iter().all(|card| card.suit == cards[0].suit);
+    // The ace is low in A-2-3-4-5 and high in 10-J-Q-K-A.
+    let broadway = ranks == [1, 10, 11, 12, 13];
+    let straight = groups.is_empty() && (ranks[4] - ranks[0] == 4 || broadway);
+    let high_pair = (1..14).any(|rank| counts[rank] == 2 && (rank == 1 || rank >= 11));
+
+    let hand = if straight && flush && broadway {
+        Hand::RoyalFlush
+    } else if straight && flush {
+        Hand::StraightFlush
+    } else if groups == [4] {
+        Hand::FourOfAKind
+    } else if groups == [2, 3] {
+        Hand::FullHouse
+    } else if flush {
+        Hand::Flush
+    } else if straight {
+        Hand::Straight
+    } else if groups == [3] {
+        Hand::ThreeOfAKind
+    } else if groups == [2, 2] {
+        Hand::TwoPair
+    } else if high_pair {
+        Hand::JacksOrBetter
+    } else {
+        return None;
+    };
+    Some(hand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game_cards::Suit;
+
+    /// Cards from (rank, suit) pairs.
+    fn cards(cards: [(u8, Suit); 5]) -> [Card; 5] {
+        cards.map(|(rank, suit)| Card::new(rank, suit))
+    }
+
+    /// Cards by rank in mixed suits.
+    fn offsuit(ranks: [u8; 5]) -> [Card; 5] {
+        let mut index = 0;
+        ranks.map(|rank| {
+            index += 1;
+            Card::new(rank, Suit::ALL[index % 4])
+        })
+    }
+
+    #[test]
+    fn straights_and_flushes() {
+        use Suit::*;
+        let royal = cards([
+            (10, Hearts),
+            (1, Hearts),
+            (12, Hearts),
+            (11, Hearts),
+            (13, Hearts),
+        ]);
+        assert_eq!(evaluate(&royal), Some(Hand::RoyalFlush));
+        let wheel = cards([(1, Clubs), (2, Clubs), (3, Clubs), (4, Clubs), (5, Clubs)]);
+        assert_eq!(evaluate(&wheel), Some(Hand::StraightFlush));
+        assert_eq!(evaluate(&offsuit([1, 2, 3, 4, 5])), Some(Hand::Straight));
+        assert_eq!(
+            evaluate(&offsuit([10, 11, 12, 13, 1])),
+            Some(Hand::Straight)
+        );
+        // No wrapping around the ace.
+        assert_eq!(evaluate(&offsuit([11, 12, 13, 1, 2])), None);
+        let flush = cards([
+            (2, Spades),
+            (7, Spades),
+            (9, Spades),
+            (11, Spades),
+            (4, Spades),
+        ]);
+        assert_eq!(evaluate(&flush), Some(Hand::Flush));
+    }
+
+    #[test]
+    fn pairs_and_sets() {
+        assert_eq!(evaluate(&offsuit([9, 9, 9, 9, 2])), Some(Hand::FourOfAKind));
+        assert_eq!(evaluate(&offsuit([3, 3, 8, 8, 8])), Some(Hand::FullHouse));
+        assert_eq!(
+            evaluate(&offsuit([5, 5, 5, 1, 13])),
+            Some(Hand::ThreeOfAKind)
+        );
+        assert_eq!(evaluate(&offsuit([4, 4, 2, 2, 9])), Some(Hand::TwoPair));
+        assert_eq!(
+            evaluate(&offsuit([11, 11, 2, 5, 9])),
+            Some(Hand::JacksOrBetter)
+        );
+        assert_eq!(
+            evaluate(&offsuit([1, 1, 2, 5, 9])),
+            Some(Hand::JacksOrBetter)
+        );
+        // Tens do not pay.
+        assert_eq!(evaluate(&offsuit([10, 10, 2, 5, 9])), None);
+    }
+
+    #[test]
+    fn paytable() {
+        assert_eq!(Hand::FullHouse.payout(1), 9);
+        assert_eq!(Hand::Flush.payout(3), 18);
+        assert_eq!(Hand::RoyalFlush.payout(4), 1000);
+        assert_eq!(Hand::RoyalFlush.payout(5), 4000);
+        assert_eq!(Hand::JacksOrBetter.index(), 8);
+    }
+}
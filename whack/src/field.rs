@@ -0,0 +1,201 @@
+//! The holes, the moles popping out of them and the score of a round.
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+pub const COLUMNS: usize = 3;
+pub const HOLES: usize = COLUMNS * ROWS;
+pub const ROUND_SECONDS: f32 = 60.0;
+/// Seconds a mole takes to come all the way out of its hole.
+pub const RISE_TIME: f32 = 0.12;
+pub const ROWS: usize = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Mole,
+    /// Whacking it costs points.
+    Decoy,
+}
+
+impl Kind {
+    pub fn points(self) -> i32 {
+        match self {
+            Self::Mole => 10,
+            Self::Decoy => -20,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mole {
+    pub age: f32,
+    pub hole: usize,
+    pub kind: Kind,
+    /// Seconds until it hides again.
+    pub lifetime: f32,
+}
+
+impl Mole {
+    /// How far it is out of its hole, from 0 to 1.
+    pub fn raised(&self) -> f32 {
+        (self.age.min(self.lifetime - self.age) / RISE_TIME).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    elapsed: f32,
+    pub moles: Vec<Mole>,
+    pub score: u32,
+    until_spawn: f32,
+}
+
+impl Default for Field {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            moles: Vec::new(),
+            score: 0,
+            until_spawn: 0.5,
+        }
+    }
+}
+
+impl Field {
+    pub fn is_over(&self) -> bool {
+        self.elapsed >= ROUND_SECONDS
+    }
+
+    /// Share of the round played, from 0 to 1.
+    pub fn progress(&self) -> f32 {
+        (self.elapsed / ROUND_SECONDS).min(1.0)
+    }
+
+    pub fn time_left(&self) -> f32 {
+        (ROUND_SECONDS - self.elapsed).max(0.0)
+    }
+
+    /// Advances the round; returns whether a mole popped up.
+    pub fn update(&mut self, delta: f32, rng: &mut impl Rng) -> bool {
+        if self.is_over() {
+            return false;
+        }
+        self.elapsed += delta;
+        for mole in self.moles.iter_mut() {
+            mole.age += delta;
+        }
+        self.moles.retain(|mole| mole.age < mole.lifetime);
+        if self.is_over() {
+            self.moles.clear();
+            return false;
+        }
+        self.until_spawn -= delta;
+        if self.until_spawn > 0.0 {
+            return false;
+        }
+        let progress = self.progress();
+        self.until_spawn += spawn_interval(progress);
+        let free: Vec<usize> = (0..HOLES)
+            .filter(|hole| !self.moles.iter().any(|mole| mole.hole == *hole))
+            .collect();
+        let hole = match free.choose(rng) {
+            Some(hole) => *hole,
+            None => return false,
+        };
+        let kind = if rng.gen_bool(0.15 + 0.15 * progress as f64) {
+            Kind::Decoy
+        } else {
+            Kind::Mole
+        };
+        self.moles.push(Mole {
+            age: 0.0,
+            hole,
+            kind,
+            lifetime: 1.5 - 0.7 * progress,
+        });
+        true
+    }
+
+    /// Hits whatever is out of `hole`; the score never drops below zero.
+    pub fn whack(&mut self, hole: usize) -> Option<Kind> {
+        if self.is_over() {
+            return None;
+        }
+        let index = self.moles.iter().position(|mole| mole.hole == hole)?;
+        let kind = self.moles.swap_remove(index).kind;
+        self.score = (self.score as i32 + kind.points()).max(0) as u32;
+        Some(kind)
+    }
+}
+
+/// Seconds between two pop-ups, shrinking as the round goes on.
+pub fn spawn_interval(progress: f32) -> f32 {
+    1.0 - 0.65 * progress
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const STEP: f32 = 1.0 / 60.0;
+
+    #[test]
+    fn pop_ups_get_more_frequent() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut field = Field::default();
+        let mut spawns = [0; 6];
+        while !field.is_over() {
+            let segment = (field.progress() * 6.0) as usize;
+            if field.update(STEP, &mut rng) {
+                spawns[segment.min(5)] += 1;
+            }
+            assert!(field.moles.len() <= HOLES);
+        }
+        assert!(spawns[5] > spawns[0], "{:?}", spawns);
+        assert!(field.moles.is_empty());
+        assert!(!field.update(STEP, &mut rng));
+    }
+
+    #[test]
+    fn decoys_cost_points() {
+        let mut field = Field::default();
+        let mole = |hole, kind| Mole {
+            age: 0.5,
+            hole,
+            kind,
+            lifetime: 1.0,
+        };
+        field.moles = vec![
+            mole(0, Kind::Mole),
+            mole(4, Kind::Decoy),
+            mole(8, Kind::Mole),
+        ];
+        assert_eq!(field.whack(0), Some(Kind::Mole));
+        assert_eq!(field.whack(0), None, "already whacked");
+        assert_eq!(field.whack(3), None);
+        assert_eq!(field.score, 10);
+        assert_eq!(field.whack(4), Some(Kind::Decoy));
+        assert_eq!(field.score, 0);
+        assert_eq!(field.whack(8), Some(Kind::Mole));
+        assert_eq!(field.score, 10);
+    }
+
+    #[test]
+    fn moles_rise_and_hide() {
+        let mut mole = Mole {
+            age: 0.0,
+            hole: 0,
+            kind: Kind::Mole,
+            lifetime: 1.0,
+        };
+        assert_eq!(mole.raised(), 0.0);
+        mole.age = RISE_TIME / 2.0;
+        assert!((mole.raised() - 0.5).abs() < 1e-5);
+        mole.age = 0.5;
+        assert_eq!(mole.raised(), 1.0);
+        mole.age = 1.0;
+        assert_eq!(mole.raised(), 0.0);
+    }
+}
@@ -0,0 +1,365 @@
+use bevy::prelude::*;
+use field::{Field, Kind, COLUMNS, HOLES, ROWS};
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use game_platform::Platform;
+use game_scores::{Order, Scores, ScoresPlugin};
+use rand::thread_rng;
+
+pub mod field;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.35, 0.6, 0.25);
+const DECOY_COLOR: Color = Color::rgb(0.2, 0.2, 0.22);
+const FLASH_DURATION: f32 = 0.6;
+const FUSE_COLOR: Color = Color::rgb(0.9, 0.2, 0.1);
+const HOLE_COLOR: Color = Color::rgb(0.2, 0.13, 0.08);
+const HOLE_SIZE: Vec2 = bevy::math::const_vec2!([130.0, 36.0]);
+const LEADERBOARD_SIZE: usize = 5;
+const MOLE_COLOR: Color = Color::rgb(0.5, 0.32, 0.18);
+const MOLE_SIZE: Vec2 = bevy::math::const_vec2!([86.0, 96.0]);
+const NOSE_COLOR: Color = Color::rgb(0.95, 0.6, 0.65);
+/// Seconds after a round before a click starts the next one, so late whacks do not.
+const RESTART_DELAY: f32 = 1.0;
+const SPACING: Vec2 = bevy::math::const_vec2!([190.0, 170.0]);
+const TEXT_COLOR: Color = Color::rgb(0.97, 0.97, 0.92);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 640.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+/// A score popping up where something was whacked.
+#[derive(Component)]
+struct Flash(Timer);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Ready,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Component)]
+struct MessageText;
+
+/// Runs out when clicks may start a new round.
+struct RestartDelay(Timer);
+
+struct Sounds {
+    decoy: Handle<AudioSource>,
+    pop: Handle<AudioSource>,
+    whack: Handle<AudioSource>,
+}
+
+pub struct WhackPlugin;
+
+impl Plugin for WhackPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(Field::default())
+            .insert_resource(RestartDelay(Timer::from_seconds(RESTART_DELAY, false)))
+            .add_plugin(ScoresPlugin::new("whack", Order::Highest))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Ready)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Ready).with_system(start_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(field_update)
+                    .with_system(whack_input),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::Over).with_system(round_over))
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(start_input))
+            .add_system(field_render)
+            .add_system(flash_update)
+            .add_system(hud_update)
+            .add_system(message_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Whack-a-mole".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Redraws the holes and whatever is sticking out of them, every frame.
+fn field_render(mut commands: Commands, field: Res<Field>, actors: Query<Entity, With<Actor>>) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    for hole in 0..HOLES {
+        commands
+            .spawn_bundle(rectangle(HOLE_COLOR, HOLE_SIZE, hole_center(hole), 0.0))
+            .insert(Actor);
+    }
+    for mole in field.moles.iter() {
+        let height = MOLE_SIZE.y * mole.raised();
+        if height <= 0.0 {
+            continue;
+        }
+        // Rises from the middle of its hole, so the front half of the hole stays visible.
+        let bottom = hole_center(mole.hole);
+        let center = bottom + Vec2::new(0.0, height / 2.0);
+        let (color, mark_color, mark_size) = match mole.kind {
+            Kind::Mole => (MOLE_COLOR, NOSE_COLOR, Vec2::new(18.0, 12.0)),
+            Kind::Decoy => (DECOY_COLOR, FUSE_COLOR, Vec2::new(8.0, 24.0)),
+        };
+        commands
+            .spawn_bundle(rectangle(
+                color,
+                Vec2::new(MOLE_SIZE.x, height),
+                center,
+                1.0,
+            ))
+            .insert(Actor);
+        let mark = match mole.kind {
+            Kind::Mole => bottom + Vec2::new(0.0, height - 30.0),
+            Kind::Decoy => bottom + Vec2::new(0.0, height + 10.0),
+        };
+        if mark.y > bottom.y + mark_size.y {
+            commands
+                .spawn_bundle(rectangle(mark_color, mark_size, mark, 2.0))
+                .insert(Actor);
+        }
+    }
+}
+
+fn field_update(
+    time: Res<Time>,
+    mut field: ResMut<Field>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if field.update(time.delta_seconds(), &mut thread_rng()) {
+        audio.play(sounds.pop.clone());
+    }
+    if field.is_over() {
+        state.set(GameState::Over).unwrap();
+    }
+}
+
+/// Scores float up and fade out.
+fn flash_update(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut flashes: Query<(Entity, &mut Flash, &mut Transform, &mut Text)>,
+) {
+    for (entity, mut flash, mut transform, mut text) in flashes.iter_mut() {
+        flash.0.tick(time.delta());
+        if flash.0.finished() {
+            commands.entity(entity).despawn();
+            continue;
+        }
+        transform.translation.y += 60.0 * time.delta_seconds();
+        text.sections[0].style.color.set_a(1.0 - flash.0.percent());
+    }
+}
+
+/// Holes are numbered row by row from the top left.
+fn hole_center(hole: usize) -> Vec2 {
+    let column = (hole % COLUMNS) as f32 - (COLUMNS - 1) as f32 / 2.0;
+    let row = (hole / COLUMNS) as f32 - (ROWS - 1) as f32 / 2.0;
+    Vec2::new(column * SPACING.x, -row * SPACING.y - 60.0)
+}
+
+fn hud_update(field: Res<Field>, scores: Res<Scores>, mut texts: Query<&mut Text, With<HudText>>) {
+    if !field.is_changed() && !scores.is_changed() {
+        return;
+    }
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = format!(
+            "SCORE {}   TIME {}   BEST {}",
+            field.score,
+            field.time_left().ceil(),
+            scores.best("").map_or(0, |entry| entry.score)
+        );
+    }
+}
+
+fn message_update(
+    field: Res<Field>,
+    scores: Res<Scores>,
+    state: Res<State<GameState>>,
+    mut texts: Query<&mut Text, With<MessageText>>,
+) {
+    if !state.is_changed() && !scores.is_changed() {
+        return;
+    }
+    let value = match state.current() {
+        GameState::Ready => {
+            "Whack the moles, not the bombs!\nClick or press Enter to start".to_string()
+        }
+        GameState::Playing => String::new(),
+        GameState::Over => {
+            let mut value = format!("Time's up! Score {}\n", field.score);
+            let table = scores.table("").iter().take(LEADERBOARD_SIZE);
+            for (place, entry) in table.enumerate() {
+                value.push_str(&format!("   {}. {}", place + 1, entry.score));
+            }
+            value.push_str("\nClick or press Enter to play again");
+            value
+        }
+    };
+    for mut text in texts.iter_mut() {
+        text.sections[0].value = value.clone();
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn round_over(
+    field: Res<Field>,
+    mut scores: ResMut<Scores>,
+    platform: Res<Platform>,
+    mut restart_delay: ResMut<RestartDelay>,
+) {
+    restart_delay.0.reset();
+    if scores.add_unnamed("", field.score).is_some() {
+        scores.save(&platform);
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            24.0,
+            TEXT_COLOR,
+            Vec3::new(0.0, 250.0, 10.0),
+        ))
+        .insert(MessageText);
+
+    commands.insert_resource(Sounds {
+        decoy: audio_sources.add(
+            Tone::new(Waveform::Noise, 200.0, 0.4)
+                .fade()
+                .volume(0.4)
+                .into(),
+        ),
+        pop: audio_sources.add(
+            Tone::new(Waveform::Sine, 300.0, 0.08)
+                .slide(600.0)
+                .volume(0.15)
+                .into(),
+        ),
+        whack: audio_sources.add(
+            Tone::new(Waveform::Square, 160.0, 0.1)
+                .slide(80.0)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+    });
+}
+
+/// A click, a tap or Enter starts a new round.
+fn start_input(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    mut field: ResMut<Field>,
+    mut restart_delay: ResMut<RestartDelay>,
+    mut state: ResMut<State<GameState>>,
+) {
+    restart_delay.0.tick(time.delta());
+    let clicked =
+        mouse_input.just_pressed(MouseButton::Left) || touches.iter_just_pressed().next().is_some();
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space])
+        || (clicked && restart_delay.0.finished())
+    {
+        *field = Field::default();
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Every click or tap hits the hole under it.
+fn whack_input(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mouse_input: Res<Input<MouseButton>>,
+    touches: Res<Touches>,
+    windows: Res<Windows>,
+    mut field: ResMut<Field>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let window = match windows.get_primary() {
+        Some(window) => window,
+        None => return,
+    };
+    let half_size = Vec2::new(window.width(), window.height()) / 2.0;
+    let mut positions: Vec<Vec2> = touches
+        .iter_just_pressed()
+        .map(|touch| touch.position() - half_size)
+        .collect();
+    if mouse_input.just_pressed(MouseButton::Left) {
+        positions.extend(game_hud::cursor_world_position(&windows));
+    }
+    for position in positions {
+        let hole = (0..HOLES).find(|hole| {
+            let offset = position - hole_center(*hole);
+            offset.x.abs() <= MOLE_SIZE.x / 2.0
+                && offset.y >= -HOLE_SIZE.y / 2.0
+                && offset.y <= MOLE_SIZE.y
+        });
+        let kind = match hole.and_then(|hole| field.whack(hole)) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let (sound, color) = match kind {
+            Kind::Mole => (&sounds.whack, TEXT_COLOR),
+            Kind::Decoy => (&sounds.decoy, FUSE_COLOR),
+        };
+        audio.play(sound.clone());
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                format!("{:+}", kind.points()),
+                28.0,
+                color,
+                position.extend(20.0),
+            ))
+            .insert(Flash(Timer::from_seconds(FLASH_DURATION, false)));
+    }
+}
@@ -0,0 +1,512 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin};
+use puzzle::{Mark, Puzzle, MAX_GUESSES};
+use rand::{thread_rng, Rng};
+use stats::{Daily, Stats};
+use words::{Words, WORD_LENGTH};
+
+pub mod puzzle;
+pub mod stats;
+pub mod words;
+
+const ABSENT_COLOR: Color = Color::rgb(0.23, 0.23, 0.25);
+const BACKGROUND_COLOR: Color = Color::rgb(0.07, 0.07, 0.08);
+const BOARD_TOP: f32 = WINDOW_HEIGHT / 2.0 - HUD_HEIGHT - 20.0;
+const CORRECT_COLOR: Color = Color::rgb(0.33, 0.6, 0.3);
+const EMPTY_COLOR: Color = Color::rgb(0.14, 0.14, 0.16);
+const GAP: f32 = 6.0;
+const HUD_COLOR: Color = Color::rgb(0.95, 0.95, 0.95);
+const HUD_HEIGHT: f32 = 40.0;
+const KEY_COLOR: Color = Color::rgb(0.45, 0.45, 0.5);
+const KEY_HEIGHT: f32 = 54.0;
+const KEY_ROWS: [&str; 3] = ["qwertyuiop", "asdfghjkl", "zxcvbnm"];
+const KEY_WIDTH: f32 = 40.0;
+const KEYBOARD_TOP: f32 = BOARD_TOP - MAX_GUESSES as f32 * (TILE_SIZE + GAP) - 50.0;
+const MESSAGE_COLOR: Color = Color::rgb(0.95, 0.85, 0.4);
+const OVERLAY_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.9);
+const PRESENT_COLOR: Color = Color::rgb(0.75, 0.65, 0.25);
+const TILE_SIZE: f32 = 58.0;
+const TYPED_COLOR: Color = Color::rgb(0.3, 0.3, 0.34);
+/// Enter and erase are wider than the letter keys.
+const WIDE_KEY_WIDTH: f32 = 64.0;
+const WORDS: &str = include_str!("../assets/words.txt");
+
+pub const WINDOW_HEIGHT: f32 = 700.0;
+pub const WINDOW_WIDTH: f32 = 500.0;
+
+/// Letter tiles of the board, rebuilt whenever the puzzle changes.
+#[derive(Component)]
+struct BoardTile;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Playing,
+    Finished,
+}
+
+/// The puzzle being played and the answer to the last guess that did not count.
+struct Game {
+    /// The day of the daily puzzle, `None` for practice with a random word.
+    day: Option<u64>,
+    message: Option<String>,
+    puzzle: Puzzle,
+}
+
+impl Game {
+    /// Today's puzzle, with the guesses already made on it.
+    fn daily(words: &Words, stats: &Stats) -> Self {
+        let day = words::today();
+        let mut puzzle = Puzzle::new(words.daily(day));
+        if let Some(daily) = stats.daily.as_ref().filter(|daily| daily.day == day) {
+            puzzle.guesses = daily.guesses.clone();
+        }
+        Self {
+            day: Some(day),
+            message: None,
+            puzzle,
+        }
+    }
+
+    fn practice(words: &Words) -> Self {
+        let index = thread_rng().gen_range(0, words.len());
+        Self {
+            day: None,
+            message: None,
+            puzzle: Puzzle::new(words.get(index)),
+        }
+    }
+}
+
+#[derive(Component)]
+struct HudText;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Enter,
+    Erase,
+    Letter(char),
+}
+
+/// A key of the on-screen keyboard, rebuilt whenever the puzzle changes.
+#[derive(Component)]
+struct KeyboardKey;
+
+#[derive(Component)]
+struct MessageText;
+
+#[derive(Component)]
+struct OverlayText;
+
+struct Sounds {
+    invalid: Handle<AudioSource>,
+    lost: Handle<AudioSource>,
+    submit: Handle<AudioSource>,
+    won: Handle<AudioSource>,
+}
+
+pub struct WordlePlugin;
+
+impl Plugin for WordlePlugin {
+    fn build(&self, app: &mut App) {
+        let words = Words::parse(WORDS);
+        let stats = Stats::load();
+        let game = Game::daily(&words, &stats);
+        // Today's puzzle may already be done, from an earlier session.
+        let state = if game.puzzle.is_over() {
+            GameState::Finished
+        } else {
+            GameState::Playing
+        };
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(words)
+            .insert_resource(stats)
+            .insert_resource(game)
+            .add_plugin(HudPlugin)
+            .add_state(state)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Playing).with_system(guess_input))
+            .add_system_set(SystemSet::on_enter(GameState::Finished).with_system(summary))
+            .add_system_set(SystemSet::on_update(GameState::Finished).with_system(finished_input))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Finished).with_system(despawn_all::<OverlayText>),
+            )
+            .add_system(board_render)
+            .add_system(keyboard_render)
+            .add_system(hud_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Wordle".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+fn board_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    tiles: Query<Entity, With<BoardTile>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+    for entity in tiles.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    let puzzle = &game.puzzle;
+    let width = WORD_LENGTH as f32 * (TILE_SIZE + GAP) - GAP;
+    for row in 0..MAX_GUESSES {
+        let (letters, marks): (Vec<char>, Option<[Mark; WORD_LENGTH]>) =
+            match puzzle.guesses.get(row) {
+                Some(guess) => (guess.chars().collect(), Some(puzzle.marks(row))),
+                None if row == puzzle.guesses.len() => (puzzle.input.chars().collect(), None),
+                None => (Vec::new(), None),
+            };
+        for column in 0..WORD_LENGTH {
+            let center = Vec2::new(
+                -width / 2.0 + TILE_SIZE / 2.0 + column as f32 * (TILE_SIZE + GAP),
+                BOARD_TOP - TILE_SIZE / 2.0 - row as f32 * (TILE_SIZE + GAP),
+            );
+            let letter = letters.get(column);
+            let color = match (marks, letter) {
+                (Some(marks), _) => mark_color(marks[column]),
+                (None, Some(_)) => TYPED_COLOR,
+                (None, None) => EMPTY_COLOR,
+            };
+            commands
+                .spawn_bundle(rectangle(color, Vec2::splat(TILE_SIZE), center))
+                .insert(BoardTile);
+            if let Some(letter) = letter {
+                commands
+                    .spawn_bundle(game_hud::world_text(
+                        &font,
+                        letter.to_ascii_uppercase().to_string(),
+                        TILE_SIZE * 0.6,
+                        HUD_COLOR,
+                        center.extend(1.0),
+                    ))
+                    .insert(BoardTile);
+            }
+        }
+    }
+}
+
+fn despawn_all<T: Component>(mut commands: Commands, entities: Query<Entity, With<T>>) {
+    for entity in entities.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Enter starts a practice round with a random word, which does not count for the statistics.
+fn finished_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    words: Res<Words>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::Space]) {
+        *game = Game::practice(&words);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+/// Typing on the keyboard or clicking the on-screen keys.
+fn guess_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    words: Res<Words>,
+    mut game: ResMut<Game>,
+    mut stats: ResMut<Stats>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    let mut pressed: Vec<Key> = characters
+        .iter()
+        .map(|event| event.char)
+        .filter(char::is_ascii_alphabetic)
+        .map(Key::Letter)
+        .collect();
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        pressed.push(Key::Erase);
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Return, KeyCode::NumpadEnter]) {
+        pressed.push(Key::Enter);
+    }
+    if mouse_input.just_pressed(MouseButton::Left) {
+        let cursor = game_hud::cursor_world_position(&windows);
+        let clicked = keys().into_iter().find(|(_, center, size)| {
+            cursor.map_or(false, |cursor| {
+                let offset = (cursor - *center).abs();
+                offset.x <= size.x / 2.0 && offset.y <= size.y / 2.0
+            })
+        });
+        pressed.extend(clicked.map(|(key, _, _)| key));
+    }
+    if pressed.is_empty() {
+        return;
+    }
+
+    let game = &mut *game;
+    for key in pressed {
+        game.message = None;
+        match key {
+            Key::Erase => game.puzzle.erase(),
+            Key::Letter(letter) => game.puzzle.type_letter(letter),
+            Key::Enter => match game.puzzle.submit(&words) {
+                Ok(()) => {
+                    if let Some(day) = game.day {
+                        let puzzle = &game.puzzle;
+                        stats.daily = Some(Daily {
+                            day,
+                            guesses: puzzle.guesses.clone(),
+                        });
+                        if puzzle.is_over() {
+                            stats.record(day, puzzle.is_won(), puzzle.guesses.len());
+                        }
+                        stats.save();
+                    }
+                    if game.puzzle.is_over() {
+                        let sound = if game.puzzle.is_won() {
+                            &sounds.won
+                        } else {
+                            &sounds.lost
+                        };
+                        audio.play(sound.clone());
+                        // The same press must not also start a practice round right away.
+                        keyboard_input.clear_just_pressed(KeyCode::Return);
+                        state.set(GameState::Finished).unwrap();
+                        return;
+                    }
+                    audio.play(sounds.submit.clone());
+                }
+                Err(error) => {
+                    game.message = Some(error.to_string());
+                    audio.play(sounds.invalid.clone());
+                }
+            },
+        }
+    }
+}
+
+fn hud_update(
+    game: Res<Game>,
+    stats: Res<Stats>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+    )>,
+) {
+    if !game.is_changed() && !stats.is_changed() {
+        return;
+    }
+    let title = match game.day {
+        Some(day) => format!("DAILY #{}", words::puzzle_number(day)),
+        None => "PRACTICE".to_string(),
+    };
+    let hud = format!(
+        "{}   STREAK {}   BEST {}",
+        title, stats.current_streak, stats.max_streak
+    );
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    let message = game.message.clone().unwrap_or_default();
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+/// The on-screen keys with their centers and sizes; enter and erase frame the bottom row.
+fn keys() -> Vec<(Key, Vec2, Vec2)> {
+    let mut keys = Vec::new();
+    for (row, letters) in KEY_ROWS.iter().enumerate() {
+        let mut row_keys: Vec<(Key, f32)> = letters
+            .chars()
+            .map(|letter| (Key::Letter(letter), KEY_WIDTH))
+            .collect();
+        if row == KEY_ROWS.len() - 1 {
+            row_keys.insert(0, (Key::Enter, WIDE_KEY_WIDTH));
+            row_keys.push((Key::Erase, WIDE_KEY_WIDTH));
+        }
+        let width: f32 = row_keys.iter().map(|(_, width)| width + GAP).sum::<f32>() - GAP;
+        let y = KEYBOARD_TOP - KEY_HEIGHT / 2.0 - row as f32 * (KEY_HEIGHT + GAP);
+        let mut left = -width / 2.0;
+        for (key, key_width) in row_keys {
+            keys.push((
+                key,
+                Vec2::new(left + key_width / 2.0, y),
+                Vec2::new(key_width, KEY_HEIGHT),
+            ));
+            left += key_width + GAP;
+        }
+    }
+    keys
+}
+
+/// Colors every letter key by the best mark its letter earned so far.
+fn keyboard_render(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    game: Res<Game>,
+    existing: Query<Entity, With<KeyboardKey>>,
+) {
+    if !game.is_changed() {
+        return;
+    }
+    for entity in existing.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    for (key, center, size) in keys() {
+        let (label, color) = match key {
+            Key::Enter => ("ENTER".to_string(), KEY_COLOR),
+            Key::Erase => ("\u{232B}".to_string(), KEY_COLOR),
+            Key::Letter(letter) => (
+                letter.to_ascii_uppercase().to_string(),
+                game.puzzle.letter(letter).map_or(KEY_COLOR, mark_color),
+            ),
+        };
+        commands
+            .spawn_bundle(rectangle(color, size, center))
+            .insert(KeyboardKey);
+        commands
+            .spawn_bundle(game_hud::world_text(
+                &font,
+                label,
+                if size.x > KEY_WIDTH { 14.0 } else { 20.0 },
+                HUD_COLOR,
+                center.extend(1.0),
+            ))
+            .insert(KeyboardKey);
+    }
+}
+
+fn mark_color(mark: Mark) -> Color {
+    match mark {
+        Mark::Absent => ABSENT_COLOR,
+        Mark::Correct => CORRECT_COLOR,
+        Mark::Present => PRESENT_COLOR,
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(0.0)),
+        ..Default::default()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::screen_text(
+            &font,
+            "",
+            20.0,
+            HUD_COLOR,
+            Rect {
+                top: Val::Px(10.0),
+                left: Val::Px(10.0),
+                ..Default::default()
+            },
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            20.0,
+            MESSAGE_COLOR,
+            Vec3::new(0.0, KEYBOARD_TOP + 25.0, 1.0),
+        ))
+        .insert(MessageText);
+
+    commands.insert_resource(Sounds {
+        invalid: audio_sources.add(Tone::new(Waveform::Square, 150.0, 0.15).volume(0.2).into()),
+        lost: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 392.0, 0.2),
+            Tone::new(Waveform::Triangle, 330.0, 0.2),
+            Tone::new(Waveform::Triangle, 262.0, 0.4),
+        ])),
+        submit: audio_sources.add(
+            Tone::new(Waveform::Sine, 660.0, 0.08)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        won: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.1),
+            Tone::new(Waveform::Triangle, 659.0, 0.1),
+            Tone::new(Waveform::Triangle, 784.0, 0.1),
+            Tone::new(Waveform::Triangle, 1047.0, 0.35),
+        ])),
+    });
+}
+
+/// The result with the statistics of the daily puzzles and how the wins are spread over the guesses.
+fn summary(mut commands: Commands, font: Res<HudFont>, game: Res<Game>, stats: Res<Stats>) {
+    let puzzle = &game.puzzle;
+    let mut message = if puzzle.is_won() {
+        format!("Solved in {}/{}!", puzzle.guesses.len(), MAX_GUESSES)
+    } else {
+        format!("The word was {}", puzzle.answer().to_uppercase())
+    };
+    message.push_str(&format!(
+        "\n\nPlayed {}   Won {}%\nStreak {}   Best {}\n",
+        stats.played,
+        stats.win_percentage(),
+        stats.current_streak,
+        stats.max_streak,
+    ));
+    let most = stats.distribution.iter().copied().max().unwrap_or(0).max(1);
+    for (index, count) in stats.distribution.iter().enumerate() {
+        let bar = "\u{2588}".repeat((*count * 12 / most) as usize);
+        message.push_str(&format!("\n{} {:<12} {:>3}", index + 1, bar, count));
+    }
+    message.push_str(if game.day.is_some() {
+        "\n\nA new daily word comes tomorrow.\nEnter: practice with a random word"
+    } else {
+        "\n\nEnter: another practice word"
+    });
+    let center = Vec2::new(
+        0.0,
+        BOARD_TOP - MAX_GUESSES as f32 * (TILE_SIZE + GAP) / 2.0,
+    );
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: OVERLAY_COLOR,
+                custom_size: Some(Vec2::new(WINDOW_WIDTH - 40.0, 400.0)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(center.extend(90.0)),
+            ..Default::default()
+        })
+        .insert(OverlayText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            message,
+            20.0,
+            HUD_COLOR,
+            center.extend(100.0),
+        ))
+        .insert(OverlayText);
+}
@@ -0,0 +1,188 @@
+//! One puzzle: the guesses made so far and the colors they earn.
+
+use crate::words::{Words, WORD_LENGTH};
+use std::fmt;
+
+pub const MAX_GUESSES: usize = 6;
+
+/// What a letter of a guess says about the answer, ordered from least to most helpful.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Mark {
+    Absent,
+    /// In the answer, but somewhere else.
+    Present,
+    Correct,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GuessError {
+    NotAWord,
+    TooShort,
+}
+
+impl fmt::Display for GuessError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            Self::NotAWord => "Not in the word list",
+            Self::TooShort => "Not enough letters",
+        };
+        f.write_str(message)
+    }
+}
+
+/// Marks every letter of `guess`; a letter that appears more often in the guess than in the answer
+/// is only marked as often as the answer has it, correct positions first.
+pub fn score(guess: &str, answer: &str) -> [Mark; WORD_LENGTH] {
+    let guess: Vec<char> = guess.chars().collect();
+    let answer: Vec<char> = answer.chars().collect();
+    let mut marks = [Mark::Absent; WORD_LENGTH];
+    let mut unmatched = Vec::new();
+    for index in 0..WORD_LENGTH {
+        if guess[index] == answer[index] {
+            marks[index] = Mark::Correct;
+        } else {
+            unmatched.push(answer[index]);
+        }
+    }
+    for index in 0..WORD_LENGTH {
+        if marks[index] == Mark::Correct {
+            continue;
+        }
+        if let Some(position) = unmatched.iter().position(|letter| *letter == guess[index]) {
+            unmatched.swap_remove(position);
+            marks[index] = Mark::Present;
+        }
+    }
+    marks
+}
+
+#[derive(Clone, Debug)]
+pub struct Puzzle {
+    answer: String,
+    pub guesses: Vec<String>,
+    /// The letters typed for the next guess.
+    pub input: String,
+}
+
+impl Puzzle {
+    pub fn new(answer: &str) -> Self {
+        Self {
+            answer: answer.to_string(),
+            guesses: Vec::new(),
+            input: String::new(),
+        }
+    }
+
+    pub fn answer(&self) -> &str {
+        &self.answer
+    }
+
+    pub fn erase(&mut self) {
+        self.input.pop();
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.is_won() || self.guesses.len() == MAX_GUESSES
+    }
+
+    pub fn is_won(&self) -> bool {
+        self.guesses.last() == Some(&self.answer)
+    }
+
+    /// The best mark any guess earned for `letter`, `None` while it was not guessed.
+    pub fn letter(&self, letter: char) -> Option<Mark> {
+        self.guesses
+            .iter()
+            .flat_map(|guess| guess.chars().zip(score(guess, &self.answer)))
+            .filter(|(guessed, _)| *guessed == letter)
+            .map(|(_, mark)| mark)
+            .max()
+    }
+
+    pub fn marks(&self, guess: usize) -> [Mark; WORD_LENGTH] {
+        score(&self.guesses[guess], &self.answer)
+    }
+
+    /// Turns the typed letters into a guess.
+    pub fn submit(&mut self, words: &Words) -> Result<(), GuessError> {
+        if self.input.len() < WORD_LENGTH {
+            return Err(GuessError::TooShort);
+        }
+        if !words.contains(&self.input) {
+            return Err(GuessError::NotAWord);
+        }
+        self.guesses.push(std::mem::take(&mut self.input));
+        Ok(())
+    }
+
+    /// Adds a letter to the next guess, ignoring anything that is not a letter or does not fit.
+    pub fn type_letter(&mut self, letter: char) {
+        if letter.is_ascii_alphabetic() && self.input.len() < WORD_LENGTH && !self.is_over() {
+            self.input.push(letter.to_ascii_lowercase());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Mark::*;
+
+    #[test]
+    fn scores_repeated_letters_only_as_often_as_the_answer_has_them() {
+        assert_eq!(score("crane", "crane"), [Correct; 5]);
+        assert_eq!(
+            score("speed", "abide"),
+            [Absent, Absent, Present, Absent, Present]
+        );
+        // The correct `e` uses up the only `e` of the answer.
+        assert_eq!(
+            score("geese", "those"),
+            [Absent, Absent, Absent, Correct, Correct]
+        );
+        assert_eq!(
+            score("eerie", "elder"),
+            [Correct, Present, Present, Absent, Absent]
+        );
+    }
+
+    #[test]
+    fn six_guesses_and_only_words() {
+        let words = Words::parse("crane\nchess\nmoney\nabide");
+        let mut puzzle = Puzzle::new("money");
+        for letter in "cra".chars() {
+            puzzle.type_letter(letter);
+        }
+        assert_eq!(puzzle.submit(&words), Err(GuessError::TooShort));
+        puzzle.erase();
+        for letter in "xyz!".chars() {
+            puzzle.type_letter(letter);
+        }
+        assert_eq!(puzzle.input, "crxyz");
+        assert_eq!(puzzle.submit(&words), Err(GuessError::NotAWord));
+        for _ in 0..MAX_GUESSES {
+            puzzle.input = "crane".to_string();
+            puzzle.submit(&words).unwrap();
+        }
+        assert!(puzzle.is_over());
+        assert!(!puzzle.is_won());
+        puzzle.type_letter('a');
+        assert_eq!(puzzle.input, "");
+    }
+
+    #[test]
+    fn keyboard_shows_the_best_mark_per_letter() {
+        let words = Words::parse("crane\nchess\nmoney");
+        let mut puzzle = Puzzle::new("chess");
+        puzzle.input = "crane".to_string();
+        puzzle.submit(&words).unwrap();
+        assert_eq!(puzzle.letter('c'), Some(Correct));
+        assert_eq!(puzzle.letter('e'), Some(Present));
+        assert_eq!(puzzle.letter('r'), Some(Absent));
+        assert_eq!(puzzle.letter('s'), None);
+        puzzle.input = "chess".to_string();
+        puzzle.submit(&words).unwrap();
+        assert_eq!(puzzle.letter('e'), Some(Correct));
+        assert!(puzzle.is_won() && puzzle.is_over());
+    }
+}
@@ -0,0 +1,90 @@
+//! Statistics over all daily puzzles and the progress on today's, kept between sessions.
+
+use crate::puzzle::MAX_GUESSES;
+use serde::{Deserialize, Serialize};
+
+const STATS_FILE: &str = "stats";
+
+/// The guesses made on the daily puzzle of a day, so it cannot be started over by restarting the game.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Daily {
+    pub day: u64,
+    pub guesses: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    /// Wins in a row, on consecutive days.
+    pub current_streak: u32,
+    pub daily: Option<Daily>,
+    /// How many wins took one guess, two guesses and so on.
+    pub distribution: [u32; MAX_GUESSES],
+    last_won_day: Option<u64>,
+    pub max_streak: u32,
+    pub played: u32,
+    pub won: u32,
+}
+
+impl Stats {
+    /// Counts a finished daily puzzle; a streak only continues if the previous day's puzzle was won too.
+    pub fn record(&mut self, day: u64, won: bool, guesses: usize) {
+        self.played += 1;
+        if !won {
+            self.current_streak = 0;
+            return;
+        }
+        self.won += 1;
+        self.distribution[guesses.clamp(1, MAX_GUESSES) - 1] += 1;
+        self.current_streak = match self.last_won_day {
+            Some(last) if last + 1 == day => self.current_streak + 1,
+            _ => 1,
+        };
+        self.last_won_day = Some(day);
+        self.max_streak = self.max_streak.max(self.current_streak);
+    }
+
+    pub fn load() -> Self {
+        game_persistence::load_or_default("wordle", STATS_FILE)
+    }
+
+    pub fn save(&self) {
+        if let Err(error) = game_persistence::save("wordle", STATS_FILE, self) {
+            eprintln!("could not save statistics: {}", error);
+        }
+    }
+
+    /// Share of the played puzzles that were won, in percent.
+    pub fn win_percentage(&self) -> u32 {
+        (self.won * 100).checked_div(self.played).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn streaks_need_consecutive_wins() {
+        let mut stats = Stats::default();
+        stats.record(10, true, 3);
+        stats.record(11, true, 4);
+        assert_eq!(stats.current_streak, 2);
+        // Skipping a day starts over.
+        stats.record(13, true, 3);
+        assert_eq!(stats.current_streak, 1);
+        stats.record(14, false, 6);
+        assert_eq!(stats.current_streak, 0);
+        stats.record(15, true, 1);
+        assert_eq!(
+            (
+                stats.current_streak,
+                stats.max_streak,
+                stats.played,
+                stats.won
+            ),
+            (1, 2, 5, 4)
+        );
+        assert_eq!(stats.distribution, [1, 0, 2, 1, 0, 0]);
+        assert_eq!(stats.win_percentage(), 80);
+    }
+}
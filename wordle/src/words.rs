@@ -0,0 +1,92 @@
+//! The bundled word list, which doubles as the answers and the accepted guesses.
+
+use std::collections::HashSet;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Day number of 2022-01-01, the first daily puzzle.
+const FIRST_DAY: u64 = 18_993;
+pub const WORD_LENGTH: usize = 5;
+
+pub struct Words {
+    /// In the order the daily puzzles use them.
+    answers: Vec<String>,
+    known: HashSet<String>,
+}
+
+impl Words {
+    /// One lowercase word per line; lines of any other length are skipped.
+    pub fn parse(text: &str) -> Self {
+        let answers: Vec<String> = text
+            .lines()
+            .map(|line| line.trim().to_lowercase())
+            .filter(|word| {
+                word.len() == WORD_LENGTH && word.chars().all(|c| c.is_ascii_alphabetic())
+            })
+            .collect();
+        Self {
+            known: answers.iter().cloned().collect(),
+            answers,
+        }
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.known.contains(word)
+    }
+
+    /// The answer of the daily puzzle on `day`; everyone playing on the same day gets the same word.
+    pub fn daily(&self, day: u64) -> &str {
+        &self.answers[(day % self.answers.len() as u64) as usize]
+    }
+
+    pub fn get(&self, index: usize) -> &str {
+        &self.answers[index % self.answers.len()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+}
+
+/// The number shown for the daily puzzle of `day`, counting from 1.
+pub fn puzzle_number(day: u64) -> u64 {
+    day.saturating_sub(FIRST_DAY) + 1
+}
+
+/// Days since 1970-01-01 in UTC, so the daily puzzle changes at midnight UTC.
+pub fn today() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() / (24 * 60 * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_only_five_letter_words() {
+        let words = Words::parse("crane\nCHESS\n\nword\nlonger\nab-cd\n");
+        assert_eq!(words.len(), 2);
+        assert!(words.contains("chess"));
+        assert!(!words.contains("word"));
+    }
+
+    #[test]
+    fn daily_words_follow_the_list() {
+        let words = Words::parse("crane\nchess\nmoney");
+        assert_eq!(words.daily(FIRST_DAY), words.daily(FIRST_DAY + 3));
+        assert_ne!(words.daily(FIRST_DAY), words.daily(FIRST_DAY + 1));
+        assert_eq!(puzzle_number(FIRST_DAY), 1);
+        assert!(puzzle_number(today()) > 1);
+    }
+
+    #[test]
+    fn bundled_list_has_a_year_of_words() {
+        let words = Words::parse(include_str!("../assets/words.txt"));
+        assert!(words.len() >= 365);
+    }
+}
@@ -0,0 +1,108 @@
+//! Holds the dice that give the highest expected value for the next roll, and scores the box
+//! worth most compared to what it usually earns.
+
+use crate::scorecard::{Category, Dice, Scorecard, UPPER_BONUS, UPPER_BONUS_AT, YAHTZEE_BONUS};
+
+/// Points a box typically earns over a game, so a roll is only spent on it when it beats that.
+const PAR: [f32; 13] = [
+    2.0, 5.0, 8.0, 11.0, 14.0, 17.0, 18.0, 8.0, 18.0, 22.0, 16.0, 12.0, 22.0,
+];
+
+/// The box for the dice, after the last roll or when no reroll would do better.
+pub fn choose_category(card: &Scorecard, dice: &Dice) -> Category {
+    let allowed = card.allowed(dice);
+    let mut best = allowed[0];
+    for category in allowed {
+        if value(card, category, dice) > value(card, best, dice) {
+            best = category;
+        }
+    }
+    best
+}
+
+/// Which dice to keep for the next roll. Every choice of held dice is tried against every way
+/// the others could land, scoring each outcome by its best box.
+pub fn choose_hold(card: &Scorecard, dice: &Dice) -> [bool; 5] {
+    let mut best = ([true; 5], f32::MIN);
+    let mut tried: Vec<Vec<u8>> = Vec::new();
+    for mask in 0..32u8 {
+        let hold = [0, 1, 2, 3, 4].map(|index| mask & (1 << index) != 0);
+        let mut held: Vec<u8> = (0..5)
+            .filter(|index| hold[*index])
+            .map(|index| dice[index])
+            .collect();
+        // Holding the same faces from other dice gives the same outcomes.
+        held.sort_unstable();
+        if tried.contains(&held) {
+            continue;
+        }
+        let expected = expected_value(card, &held);
+        if expected > best.1 {
+            best = (hold, expected);
+        }
+        tried.push(held);
+    }
+    best.0
+}
+
+/// Average value of the best box once the dice not held are rolled.
+fn expected_value(card: &Scorecard, held: &[u8]) -> f32 {
+    let rolled = 5 - held.len() as u32;
+    let outcomes = 6u32.pow(rolled);
+    let mut total = 0.0;
+    for outcome in 0..outcomes {
+        let mut dice = [0; 5];
+        dice[..held.len()].copy_from_slice(held);
+        let mut rest = outcome;
+        for die in dice[held.len()..].iter_mut() {
+            *die = (rest % 6) as u8 + 1;
+            rest /= 6;
+        }
+        let best = card
+            .allowed(&dice)
+            .into_iter()
+            .map(|category| value(card, category, &dice))
+            .fold(f32::MIN, f32::max);
+        total += best;
+    }
+    total / outcomes as f32
+}
+
+/// Points for the box measured against its par, counting the upper bonus once it is reached.
+fn value(card: &Scorecard, category: Category, dice: &Dice) -> f32 {
+    let points = card.points(category, dice);
+    let mut value = points as f32 - PAR[category.index()];
+    if category.face().is_some() {
+        let upper = card.upper();
+        if upper < UPPER_BONUS_AT && upper + points >= UPPER_BONUS_AT {
+            value += UPPER_BONUS as f32;
+        }
+    }
+    if card.is_joker(dice) && card.boxes[Category::Yahtzee.index()] == Some(50) {
+        value += YAHTZEE_BONUS as f32;
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_a_pair_of_sixes_and_scores_a_yahtzee() {
+        let card = Scorecard::default();
+        let hold = choose_hold(&card, &[6, 2, 6, 1, 4]);
+        assert_eq!(hold, [true, false, true, false, false]);
+        assert_eq!(choose_category(&card, &[3; 5]), Category::Yahtzee);
+    }
+
+    #[test]
+    fn holds_a_large_straight() {
+        let card = Scorecard::default();
+        assert_eq!(choose_hold(&card, &[2, 3, 4, 5, 6]), [true; 5]);
+        assert_eq!(
+            choose_category(&card, &[2, 3, 4, 5, 6]),
+            Category::LargeStraight
+        );
+    }
+}
@@ -0,0 +1,802 @@
+use bevy::prelude::*;
+use game_audio::{Tone, Waveform};
+use game_hud::{HudFont, HudPlugin, MenuInput, Navigate};
+use rand::{thread_rng, Rng};
+use scorecard::{Category, Dice, Scorecard};
+
+pub mod ai;
+pub mod scorecard;
+
+const BACKGROUND_COLOR: Color = Color::rgb(0.08, 0.2, 0.14);
+const COLUMN_WIDTH: f32 = 84.0;
+/// Pause before each action of the computer.
+const DELAY: f32 = 0.8;
+const DIE_COLOR: Color = Color::rgb(0.96, 0.95, 0.9);
+const DIE_GAP: f32 = 10.0;
+const DIE_SIZE: f32 = 58.0;
+const DICE_Y: f32 = 90.0;
+const HELD_COLOR: Color = Color::rgb(0.95, 0.8, 0.2);
+const HOVER_COLOR: Color = Color::rgba(0.95, 0.8, 0.2, 0.25);
+const LABEL_WIDTH: f32 = 170.0;
+const PANEL_X: f32 = 280.0;
+const PIP_COLOR: Color = Color::rgb(0.12, 0.1, 0.1);
+const POTENTIAL_COLOR: Color = Color::rgb(0.55, 0.7, 0.6);
+/// Rolls per turn.
+const ROLLS: u8 = 3;
+const ROW_COLORS: [Color; 2] = [Color::rgb(0.1, 0.25, 0.17), Color::rgb(0.12, 0.29, 0.2)];
+const ROW_HEIGHT: f32 = 27.0;
+/// Seconds the dice tumble after a roll.
+const TUMBLE: f32 = 0.5;
+const TABLE_LEFT: f32 = -430.0;
+const TABLE_TOP: f32 = 270.0;
+const TEXT_COLOR: Color = Color::rgb(0.93, 0.95, 0.92);
+const TURN_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.08);
+
+pub const WINDOW_HEIGHT: f32 = 640.0;
+pub const WINDOW_WIDTH: f32 = 900.0;
+
+/// Something drawn for the current frame only.
+#[derive(Component)]
+struct Actor;
+
+/// One cell of the scorecard table; column 0 holds the row names, then one per player.
+#[derive(Component)]
+struct CellText {
+    column: usize,
+    row: usize,
+}
+
+/// Runs out when the computer acts next.
+struct Delay(Timer);
+
+struct Game {
+    dice: Dice,
+    held: [bool; 5],
+    message: String,
+    players: Vec<Player>,
+    /// The computer has picked the dice to hold and rolls next.
+    planned: bool,
+    /// Rolls made this turn.
+    rolls: u8,
+    /// Seconds the dice still tumble.
+    tumble: f32,
+    turn: usize,
+}
+
+impl Game {
+    fn new(seats: &[Seat]) -> Self {
+        let players = seats
+            .iter()
+            .enumerate()
+            .filter(|(_, seat)| **seat != Seat::Empty)
+            .map(|(index, seat)| Player {
+                card: Scorecard::default(),
+                computer: *seat == Seat::Computer,
+                name: match seat {
+                    Seat::Computer => format!("CPU {}", index + 1),
+                    _ => format!("Player {}", index + 1),
+                },
+            })
+            .collect();
+        let mut game = Self {
+            dice: [1; 5],
+            held: [false; 5],
+            message: String::new(),
+            players,
+            planned: false,
+            rolls: 0,
+            tumble: 0.0,
+            turn: 0,
+        };
+        game.message = format!("{} to roll", game.players[0].name);
+        game
+    }
+
+    fn can_roll(&self) -> bool {
+        self.rolls < ROLLS && self.tumble <= 0.0
+    }
+
+    /// The categories the current player may score, once the dice were rolled.
+    fn allowed(&self) -> Vec<Category> {
+        if self.rolls == 0 || self.tumble > 0.0 {
+            return Vec::new();
+        }
+        self.player().card.allowed(&self.dice)
+    }
+
+    fn is_over(&self) -> bool {
+        self.players.iter().all(|player| player.card.is_complete())
+    }
+
+    fn player(&self) -> &Player {
+        &self.players[self.turn]
+    }
+
+    fn roll(&mut self) {
+        let mut rng = thread_rng();
+        for (die, held) in self.dice.iter_mut().zip(self.held) {
+            if !held {
+                *die = rng.gen_range(1, 7);
+            }
+        }
+        self.rolls += 1;
+        self.tumble = TUMBLE;
+        self.message = match ROLLS - self.rolls {
+            0 => "Pick a box to score".to_string(),
+            1 => "1 roll left".to_string(),
+            left => format!("{} rolls left", left),
+        };
+    }
+
+    /// Fills a box for the current player and hands the dice to the next one.
+    fn score(&mut self, category: Category) -> u32 {
+        let dice = self.dice;
+        let card = &mut self.players[self.turn].card;
+        let before = card.total();
+        card.record(category, &dice);
+        let points = card.total() - before;
+        self.message = format!(
+            "{} scored {} in {}",
+            self.players[self.turn].name,
+            points,
+            category.name()
+        );
+        self.turn = (self.turn + 1) % self.players.len();
+        self.held = [false; 5];
+        self.rolls = 0;
+        self.planned = false;
+        points
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum GameState {
+    Menu,
+    Playing,
+    Over,
+}
+
+#[derive(Component)]
+struct HudText;
+
+struct MenuChoice {
+    /// The seat being changed.
+    row: usize,
+    seats: [Seat; 4],
+}
+
+#[derive(Component)]
+struct MessageText;
+
+/// The background stripe of a table row.
+#[derive(Component)]
+struct RowStripe;
+
+struct Player {
+    card: Scorecard,
+    computer: bool,
+    name: String,
+}
+
+/// A row of the table: the header, one per box, then the sums.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Row {
+    Header,
+    Box(Category),
+    Upper,
+    UpperBonus,
+    YahtzeeBonus,
+    Total,
+}
+
+impl Row {
+    fn all() -> Vec<Row> {
+        let mut rows = vec![Self::Header];
+        rows.extend(
+            Category::ALL[..6]
+                .iter()
+                .map(|category| Self::Box(*category)),
+        );
+        rows.extend([Self::Upper, Self::UpperBonus]);
+        rows.extend(
+            Category::ALL[6..]
+                .iter()
+                .map(|category| Self::Box(*category)),
+        );
+        rows.extend([Self::YahtzeeBonus, Self::Total]);
+        rows
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Header => "",
+            Self::Box(category) => category.name(),
+            Self::Upper => "Upper total",
+            Self::UpperBonus => "Bonus (63+)",
+            Self::YahtzeeBonus => "Yahtzee bonus",
+            Self::Total => "TOTAL",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Seat {
+    Human,
+    Computer,
+    Empty,
+}
+
+impl Seat {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Human => "Human",
+            Self::Computer => "Computer",
+            Self::Empty => "-",
+        }
+    }
+}
+
+struct Sounds {
+    hold: Handle<AudioSource>,
+    over: Handle<AudioSource>,
+    roll: Handle<AudioSource>,
+    score: Handle<AudioSource>,
+    yahtzee: Handle<AudioSource>,
+}
+
+pub struct YahtzeePlugin;
+
+impl Plugin for YahtzeePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ClearColor(BACKGROUND_COLOR))
+            .insert_resource(MenuChoice {
+                row: 0,
+                seats: [Seat::Human, Seat::Computer, Seat::Empty, Seat::Empty],
+            })
+            .insert_resource(Game::new(&[Seat::Human]))
+            .insert_resource(Delay(Timer::from_seconds(DELAY, false)))
+            .add_plugin(HudPlugin)
+            .add_state(GameState::Menu)
+            .add_startup_system(setup)
+            .add_system_set(SystemSet::on_update(GameState::Menu).with_system(menu_input))
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(play_input)
+                    .with_system(computer_turn)
+                    .with_system(tumble_update),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Over).with_system(over_input))
+            .add_system(dice_render)
+            .add_system(escape_input)
+            .add_system(table_update)
+            .add_system(texts_update);
+    }
+}
+
+pub fn window_descriptor() -> WindowDescriptor {
+    WindowDescriptor {
+        title: "Yahtzee".to_string(),
+        width: WINDOW_WIDTH,
+        height: WINDOW_HEIGHT,
+        resizable: false,
+        ..Default::default()
+    }
+}
+
+/// Which player column and table row are under `position`.
+fn cell_at(position: Vec2) -> Option<(usize, usize)> {
+    let x = position.x - TABLE_LEFT - LABEL_WIDTH;
+    let y = TABLE_TOP - position.y;
+    if y < 0.0 || y >= Row::all().len() as f32 * ROW_HEIGHT || x < -LABEL_WIDTH {
+        return None;
+    }
+    let column = if x < 0.0 {
+        0
+    } else {
+        (x / COLUMN_WIDTH) as usize + 1
+    };
+    Some((column, (y / ROW_HEIGHT) as usize))
+}
+
+fn cell_center(column: usize, row: usize) -> Vec2 {
+    let x = if column == 0 {
+        TABLE_LEFT + 12.0
+    } else {
+        TABLE_LEFT + LABEL_WIDTH + (column as f32 - 0.5) * COLUMN_WIDTH
+    };
+    Vec2::new(x, TABLE_TOP - (row as f32 + 0.5) * ROW_HEIGHT)
+}
+
+/// Rolls, holds and scores for computer players, one step per delay.
+fn computer_turn(
+    time: Res<Time>,
+    mut delay: ResMut<Delay>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if !game.player().computer || game.tumble > 0.0 {
+        delay.0.reset();
+        return;
+    }
+    if !delay.0.tick(time.delta()).finished() {
+        return;
+    }
+    delay.0.reset();
+    if game.rolls == 0 || game.planned {
+        game.planned = false;
+        game.roll();
+        audio.play(sounds.roll.clone());
+        return;
+    }
+    let card = game.player().card.clone();
+    if game.rolls < ROLLS {
+        let hold = ai::choose_hold(&card, &game.dice);
+        if hold != [true; 5] {
+            if hold != game.held {
+                audio.play(sounds.hold.clone());
+            }
+            game.held = hold;
+            game.planned = true;
+            return;
+        }
+    }
+    let category = ai::choose_category(&card, &game.dice);
+    score(&mut game, &mut state, category, &audio, &sounds);
+}
+
+/// Draws the five dice, held ones lowered onto a marker, and the roll button.
+fn dice_render(
+    mut commands: Commands,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    actors: Query<Entity, With<Actor>>,
+) {
+    for entity in actors.iter() {
+        commands.entity(entity).despawn();
+    }
+    if *state.current() != GameState::Playing || game.rolls == 0 {
+        return;
+    }
+    let mut rng = thread_rng();
+    for (index, die) in game.dice.iter().enumerate() {
+        let held = game.held[index];
+        let face = if game.tumble > 0.0 && !held {
+            rng.gen_range(1, 7)
+        } else {
+            *die
+        };
+        let mut center = die_center(index);
+        if held {
+            commands
+                .spawn_bundle(rectangle(
+                    HELD_COLOR,
+                    Vec2::splat(DIE_SIZE + 8.0),
+                    center - Vec2::new(0.0, 14.0),
+                    1.0,
+                ))
+                .insert(Actor);
+            center.y -= 14.0;
+        } else if game.tumble > 0.0 {
+            center += Vec2::new(rng.gen_range(-3.0, 3.0), rng.gen_range(-3.0, 3.0));
+        }
+        commands
+            .spawn_bundle(rectangle(DIE_COLOR, Vec2::splat(DIE_SIZE), center, 2.0))
+            .insert(Actor);
+        for (column, row) in die_pips(face) {
+            let offset = Vec2::new(*column as f32, *row as f32) * DIE_SIZE * 0.27;
+            commands
+                .spawn_bundle(rectangle(
+                    PIP_COLOR,
+                    Vec2::splat(10.0),
+                    center + offset,
+                    3.0,
+                ))
+                .insert(Actor);
+        }
+    }
+}
+
+fn die_at(position: Vec2) -> Option<usize> {
+    (0..5).find(|index| {
+        let offset = (position - die_center(*index)).abs();
+        offset.x <= DIE_SIZE / 2.0 && offset.y <= DIE_SIZE / 2.0 + 14.0
+    })
+}
+
+fn die_center(index: usize) -> Vec2 {
+    let x = PANEL_X + (index as f32 - 2.0) * (DIE_SIZE + DIE_GAP);
+    Vec2::new(x, DICE_Y)
+}
+
+/// Offsets of the pips on a die face, in steps from its center.
+fn die_pips(die: u8) -> &'static [(i8, i8)] {
+    match die {
+        1 => &[(0, 0)],
+        2 => &[(-1, 1), (1, -1)],
+        3 => &[(-1, 1), (0, 0), (1, -1)],
+        4 => &[(-1, 1), (1, 1), (-1, -1), (1, -1)],
+        5 => &[(-1, 1), (1, 1), (0, 0), (-1, -1), (1, -1)],
+        _ => &[(-1, 1), (1, 1), (-1, 0), (1, 0), (-1, -1), (1, -1)],
+    }
+}
+
+/// Escape leaves a game for the menu at any time.
+fn escape_input(keyboard_input: Res<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) && *state.current() != GameState::Menu {
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Up/Down picks a seat, Left/Right changes who sits there, Enter starts.
+fn menu_input(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    menu: Res<MenuInput>,
+    mut choice: ResMut<MenuChoice>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+) {
+    const ORDER: [Seat; 3] = [Seat::Human, Seat::Computer, Seat::Empty];
+    if menu.just_pressed(Navigate::Up) {
+        choice.row = choice.row.saturating_sub(1);
+    } else if menu.just_pressed(Navigate::Down) {
+        choice.row = (choice.row + 1).min(3);
+    } else if menu.horizontal() != 0 {
+        let row = choice.row;
+        let index = ORDER
+            .iter()
+            .position(|seat| *seat == choice.seats[row])
+            .unwrap();
+        let step = if menu.just_pressed(Navigate::Left) {
+            ORDER.len() - 1
+        } else {
+            1
+        };
+        choice.seats[row] = ORDER[(index + step) % ORDER.len()];
+    } else if menu.just_pressed(Navigate::Confirm)
+        && choice.seats.iter().any(|seat| *seat != Seat::Empty)
+    {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        *game = Game::new(&choice.seats);
+        state.set(GameState::Playing).unwrap();
+    }
+}
+
+fn over_input(mut keyboard_input: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        keyboard_input.clear_just_pressed(KeyCode::Return);
+        state.set(GameState::Menu).unwrap();
+    }
+}
+
+/// Space rolls, 1-5 or a click on a die holds it, a click on an open box scores it.
+fn play_input(
+    keyboard_input: Res<Input<KeyCode>>,
+    mouse_input: Res<Input<MouseButton>>,
+    windows: Res<Windows>,
+    mut game: ResMut<Game>,
+    mut state: ResMut<State<GameState>>,
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+) {
+    if game.player().computer {
+        return;
+    }
+    if keyboard_input.any_just_pressed([KeyCode::Space, KeyCode::R]) && game.can_roll() {
+        game.roll();
+        audio.play(sounds.roll.clone());
+        return;
+    }
+    let keys = [
+        KeyCode::Key1,
+        KeyCode::Key2,
+        KeyCode::Key3,
+        KeyCode::Key4,
+        KeyCode::Key5,
+    ];
+    let cursor = game_hud::cursor_world_position(&windows);
+    let clicked = if mouse_input.just_pressed(MouseButton::Left) {
+        cursor
+    } else {
+        None
+    };
+    let toggled = keys
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+        .or_else(|| clicked.and_then(die_at));
+    if let Some(index) = toggled {
+        if game.rolls > 0 && game.can_roll() {
+            game.held[index] = !game.held[index];
+            audio.play(sounds.hold.clone());
+        }
+        return;
+    }
+    let row = match clicked.and_then(cell_at) {
+        Some((_, row)) => Row::all()[row],
+        None => return,
+    };
+    if let Row::Box(category) = row {
+        if game.allowed().contains(&category) {
+            score(&mut game, &mut state, category, &audio, &sounds);
+        }
+    }
+}
+
+fn rectangle(color: Color, size: Vec2, center: Vec2, z: f32) -> SpriteBundle {
+    SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(size),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(center.extend(z)),
+        ..Default::default()
+    }
+}
+
+fn score(
+    game: &mut Game,
+    state: &mut State<GameState>,
+    category: Category,
+    audio: &Audio,
+    sounds: &Sounds,
+) {
+    let yahtzee = Category::Yahtzee.points(&game.dice) == 50;
+    let points = game.score(category);
+    audio.play(if yahtzee && points > 0 {
+        sounds.yahtzee.clone()
+    } else {
+        sounds.score.clone()
+    });
+    if game.is_over() {
+        audio.play(sounds.over.clone());
+        state.set(GameState::Over).unwrap();
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    font: Res<HudFont>,
+    mut audio_sources: ResMut<Assets<AudioSource>>,
+) {
+    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            22.0,
+            TEXT_COLOR,
+            Vec3::new(PANEL_X, 200.0, 10.0),
+        ))
+        .insert(HudText);
+    commands
+        .spawn_bundle(game_hud::world_text(
+            &font,
+            "",
+            18.0,
+            TEXT_COLOR,
+            Vec3::new(PANEL_X, -60.0, 10.0),
+        ))
+        .insert(MessageText);
+    for (row, _) in Row::all().iter().enumerate() {
+        let center = Vec2::new(
+            TABLE_LEFT + (LABEL_WIDTH + 4.0 * COLUMN_WIDTH) / 2.0,
+            cell_center(0, row).y,
+        );
+        commands
+            .spawn_bundle(rectangle(
+                ROW_COLORS[row % 2],
+                Vec2::new(LABEL_WIDTH + 4.0 * COLUMN_WIDTH, ROW_HEIGHT),
+                center,
+                0.0,
+            ))
+            .insert(RowStripe);
+        for column in 0..5 {
+            let mut text = game_hud::world_text(
+                &font,
+                "",
+                16.0,
+                TEXT_COLOR,
+                cell_center(column, row).extend(5.0),
+            );
+            if column == 0 {
+                // Row names line up on the left.
+                text.text.alignment.horizontal = HorizontalAlign::Left;
+            }
+            commands.spawn_bundle(text).insert(CellText { column, row });
+        }
+    }
+
+    commands.insert_resource(Sounds {
+        hold: audio_sources.add(Tone::new(Waveform::Square, 660.0, 0.04).volume(0.15).into()),
+        over: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Triangle, 523.0, 0.15),
+            Tone::new(Waveform::Triangle, 659.0, 0.15),
+            Tone::new(Waveform::Triangle, 784.0, 0.15),
+            Tone::new(Waveform::Triangle, 1047.0, 0.4),
+        ])),
+        roll: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.3),
+            Tone::new(Waveform::Noise, 0.0, 0.04).volume(0.2),
+            Tone::new(Waveform::Noise, 0.0, 0.05).volume(0.25),
+            Tone::new(Waveform::Noise, 0.0, 0.08).fade().volume(0.3),
+        ])),
+        score: audio_sources.add(
+            Tone::new(Waveform::Sine, 880.0, 0.2)
+                .fade()
+                .volume(0.3)
+                .into(),
+        ),
+        yahtzee: audio_sources.add(game_audio::sequence(&[
+            Tone::new(Waveform::Square, 523.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 659.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 784.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1047.0, 0.1).volume(0.2),
+            Tone::new(Waveform::Square, 1319.0, 0.4).fade().volume(0.2),
+        ])),
+    });
+}
+
+/// Fills the scorecard: scored boxes, the sums and, for the player to move, what each open box
+/// would score. The row under the mouse and the current column are highlighted.
+fn table_update(
+    mut commands: Commands,
+    windows: Res<Windows>,
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    mut cells: Query<(&CellText, &mut Text, &mut Visibility)>,
+    mut stripes: Query<&mut Visibility, (With<RowStripe>, Without<CellText>)>,
+) {
+    let menu = *state.current() == GameState::Menu;
+    let rows = Row::all();
+    let allowed = if game.player().computer {
+        Vec::new()
+    } else {
+        game.allowed()
+    };
+    if !menu && *state.current() != GameState::Over {
+        let column = game.turn + 1;
+        let top = cell_center(column, 0).y + ROW_HEIGHT / 2.0;
+        let height = rows.len() as f32 * ROW_HEIGHT;
+        commands
+            .spawn_bundle(rectangle(
+                TURN_COLOR,
+                Vec2::new(COLUMN_WIDTH, height),
+                Vec2::new(cell_center(column, 0).x, top - height / 2.0),
+                1.0,
+            ))
+            .insert(Actor);
+        let hovered = game_hud::cursor_world_position(&windows)
+            .and_then(cell_at)
+            .map(|(_, row)| rows[row]);
+        if let Some(Row::Box(category)) = hovered {
+            if allowed.contains(&category) {
+                let row = rows.iter().position(|other| *other == Row::Box(category));
+                let y = cell_center(0, row.unwrap_or(0)).y;
+                let width = LABEL_WIDTH + 4.0 * COLUMN_WIDTH;
+                commands
+                    .spawn_bundle(rectangle(
+                        HOVER_COLOR,
+                        Vec2::new(width, ROW_HEIGHT),
+                        Vec2::new(TABLE_LEFT + width / 2.0, y),
+                        1.5,
+                    ))
+                    .insert(Actor);
+            }
+        }
+    }
+
+    if !game.is_changed() && !state.is_changed() {
+        return;
+    }
+    for mut visibility in stripes.iter_mut() {
+        visibility.is_visible = !menu;
+    }
+    for (cell, mut text, mut visibility) in cells.iter_mut() {
+        visibility.is_visible = !menu;
+        let row = rows[cell.row];
+        let mut color = TEXT_COLOR;
+        let value = if cell.column == 0 {
+            row.name().to_string()
+        } else {
+            match game.players.get(cell.column - 1) {
+                None => String::new(),
+                Some(player) => {
+                    let card = &player.card;
+                    match row {
+                        Row::Header => player.name.clone(),
+                        Row::Box(category) => match card.boxes[category.index()] {
+                            Some(points) => points.to_string(),
+                            None if cell.column - 1 == game.turn && allowed.contains(&category) => {
+                                color = POTENTIAL_COLOR;
+                                card.points(category, &game.dice).to_string()
+                            }
+                            None => String::new(),
+                        },
+                        Row::Upper => card.upper().to_string(),
+                        Row::UpperBonus => card.upper_bonus().to_string(),
+                        Row::YahtzeeBonus => card.yahtzee_bonus.to_string(),
+                        Row::Total => card.total().to_string(),
+                    }
+                }
+            }
+        };
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+}
+
+fn texts_update(
+    game: Res<Game>,
+    state: Res<State<GameState>>,
+    choice: Res<MenuChoice>,
+    mut texts: QuerySet<(
+        QueryState<&mut Text, With<HudText>>,
+        QueryState<&mut Text, With<MessageText>>,
+    )>,
+) {
+    if !game.is_changed() && !state.is_changed() && !choice.is_changed() {
+        return;
+    }
+    let (hud, message) = match state.current() {
+        GameState::Menu => {
+            let mut menu = "YAHTZEE\n\n".to_string();
+            for (index, seat) in choice.seats.iter().enumerate() {
+                let marker = if index == choice.row { ">" } else { " " };
+                menu.push_str(&format!(
+                    "{} Player {}: {:<8}\n",
+                    marker,
+                    index + 1,
+                    seat.name()
+                ));
+            }
+            (
+                menu,
+                "Up/Down: seat   Left/Right: change\nEnter: start".to_string(),
+            )
+        }
+        GameState::Over => {
+            let mut ranking: Vec<&Player> = game.players.iter().collect();
+            ranking.sort_by_key(|player| std::cmp::Reverse(player.card.total()));
+            let mut standings = "FINAL SCORES\n\n".to_string();
+            for (place, player) in ranking.iter().enumerate() {
+                standings.push_str(&format!(
+                    "{}. {:<9}{:>4}\n",
+                    place + 1,
+                    player.name,
+                    player.card.total()
+                ));
+            }
+            (standings, "Enter: menu".to_string())
+        }
+        GameState::Playing => {
+            let player = game.player();
+            let help = if player.computer {
+                String::new()
+            } else if game.rolls == 0 {
+                "Space: roll".to_string()
+            } else if game.rolls < ROLLS {
+                "Space: roll again   1-5 / click: hold\nClick a box to score".to_string()
+            } else {
+                "Click a box to score".to_string()
+            };
+            (
+                format!("{}\nRoll {} of {}", player.name, game.rolls, ROLLS),
+                format!("{}\n\n{}", game.message, help),
+            )
+        }
+    };
+    for mut text in texts.q0().iter_mut() {
+        text.sections[0].value = hud.clone();
+    }
+    for mut text in texts.q1().iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+fn tumble_update(time: Res<Time>, mut game: ResMut<Game>) {
+    if game.tumble > 0.0 {
+        game.tumble -= time.delta_seconds();
+    }
+}
@@ -0,0 +1,14 @@
+use bevy::prelude::*;
+use game_console::ConsolePlugin;
+use game_theme::ThemePlugin;
+use yahtzee::YahtzeePlugin;
+
+fn main() {
+    App::new()
+        .insert_resource(yahtzee::window_descriptor())
+        .add_plugins(DefaultPlugins)
+        .add_plugin(YahtzeePlugin)
+        .add_plugin(ConsolePlugin)
+        .add_plugin(ThemePlugin::default())
+        .run();
+}
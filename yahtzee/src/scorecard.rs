@@ -0,0 +1,262 @@
+//! The thirteen boxes of a Yahtzee scorecard and the rules for filling them.
+
+/// Bonus for reaching `UPPER_BONUS_AT` in the upper section.
+pub const UPPER_BONUS: u32 = 35;
+pub const UPPER_BONUS_AT: u32 = 63;
+/// Bonus for each further Yahtzee once the Yahtzee box holds 50.
+pub const YAHTZEE_BONUS: u32 = 100;
+
+pub type Dice = [u8; 5];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    Ones,
+    Twos,
+    Threes,
+    Fours,
+    Fives,
+    Sixes,
+    ThreeOfAKind,
+    FourOfAKind,
+    FullHouse,
+    SmallStraight,
+    LargeStraight,
+    Yahtzee,
+    Chance,
+}
+
+impl Category {
+    pub const ALL: [Category; 13] = [
+        Self::Ones,
+        Self::Twos,
+        Self::Threes,
+        Self::Fours,
+        Self::Fives,
+        Self::Sixes,
+        Self::ThreeOfAKind,
+        Self::FourOfAKind,
+        Self::FullHouse,
+        Self::SmallStraight,
+        Self::LargeStraight,
+        Self::Yahtzee,
+        Self::Chance,
+    ];
+
+    /// The face counted by an upper section box.
+    pub fn face(self) -> Option<u8> {
+        match self {
+            Self::Ones => Some(1),
+            Self::Twos => Some(2),
+            Self::Threes => Some(3),
+            Self::Fours => Some(4),
+            Self::Fives => Some(5),
+            Self::Sixes => Some(6),
+            _ => None,
+        }
+    }
+
+    pub fn index(self) -> usize {
+        Self::ALL.iter().position(|other| *other == self).unwrap()
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ones => "Ones",
+            Self::Twos => "Twos",
+            Self::Threes => "Threes",
+            Self::Fours => "Fours",
+            Self::Fives => "Fives",
+            Self::Sixes => "Sixes",
+            Self::ThreeOfAKind => "3 of a kind",
+            Self::FourOfAKind => "4 of a kind",
+            Self::FullHouse => "Full house",
+            Self::SmallStraight => "Sm. straight",
+            Self::LargeStraight => "Lg. straight",
+            Self::Yahtzee => "Yahtzee",
+            Self::Chance => "Chance",
+        }
+    }
+
+    /// Points for the dice in this box, without the joker rules.
+    pub fn points(self, dice: &Dice) -> u32 {
+        let counts = counts(dice);
+        let sum: u32 = dice.iter().map(|die| *die as u32).sum();
+        let most = counts.iter().copied().max().unwrap_or(0);
+        // Longest run of consecutive faces present.
+        let mut run = 0;
+        let mut longest = 0;
+        for count in &counts[1..] {
+            run = if *count > 0 { run + 1 } else { 0 };
+            longest = longest.max(run);
+        }
+        match self {
+            Self::ThreeOfAKind if most >= 3 => sum,
+            Self::FourOfAKind if most >= 4 => sum,
+            Self::FullHouse if counts.contains(&3) && counts.contains(&2) => 25,
+            Self::SmallStraight if longest >= 4 => 30,
+            Self::LargeStraight if longest == 5 => 40,
+            Self::Yahtzee if most == 5 => 50,
+            Self::Chance => sum,
+            _ => match self.face() {
+                Some(face) => counts[face as usize] as u32 * face as u32,
+                None => 0,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Scorecard {
+    pub boxes: [Option<u32>; 13],
+    /// Points from further Yahtzees.
+    pub yahtzee_bonus: u32,
+}
+
+impl Scorecard {
+    /// The open boxes these dice may go in. A further Yahtzee is a joker: it has to go in
+    /// its upper box if that is open, otherwise in any open lower box, and only when those
+    /// are all filled in an open upper box.
+    pub fn allowed(&self, dice: &Dice) -> Vec<Category> {
+        let open: Vec<Category> = Category::ALL
+            .into_iter()
+            .filter(|category| self.boxes[category.index()].is_none())
+            .collect();
+        if !self.is_joker(dice) {
+            return open;
+        }
+        let upper = Category::ALL[dice[0] as usize - 1];
+        if open.contains(&upper) {
+            return vec![upper];
+        }
+        let lower: Vec<Category> = open
+            .iter()
+            .copied()
+            .filter(|category| category.face().is_none())
+            .collect();
+        if lower.is_empty() {
+            open
+        } else {
+            lower
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.boxes.iter().all(Option::is_some)
+    }
+
+    /// Whether the dice are a Yahtzee rolled after the Yahtzee box was filled.
+    pub fn is_joker(&self, dice: &Dice) -> bool {
+        Category::Yahtzee.points(dice) == 50 && self.boxes[Category::Yahtzee.index()].is_some()
+    }
+
+    /// Points the dice would get in `category`; a joker scores full points in the lower boxes.
+    pub fn points(&self, category: Category, dice: &Dice) -> u32 {
+        if self.is_joker(dice) {
+            match category {
+                Category::FullHouse => return 25,
+                Category::SmallStraight => return 30,
+                Category::LargeStraight => return 40,
+                _ => {}
+            }
+        }
+        category.points(dice)
+    }
+
+    /// Fills a box allowed for the dice, with the bonus for a further Yahtzee.
+    pub fn record(&mut self, category: Category, dice: &Dice) {
+        if self.is_joker(dice) && self.boxes[Category::Yahtzee.index()] == Some(50) {
+            self.yahtzee_bonus += YAHTZEE_BONUS;
+        }
+        self.boxes[category.index()] = Some(self.points(category, dice));
+    }
+
+    pub fn total(&self) -> u32 {
+        let lower: u32 = self.boxes[6..].iter().flatten().sum();
+        self.upper() + self.upper_bonus() + lower + self.yahtzee_bonus
+    }
+
+    pub fn upper(&self) -> u32 {
+        self.boxes[..6].iter().flatten().sum()
+    }
+
+    pub fn upper_bonus(&self) -> u32 {
+        if self.upper() >= UPPER_BONUS_AT {
+            UPPER_BONUS
+        } else {
+            0
+        }
+    }
+}
+
+/// How many dice show each face, indexed by the face.
+pub fn counts(dice: &Dice) -> [u8; 7] {
+    let mut counts = [0; 7];
+    for die in dice {
+        counts[*die as usize] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn boxes_score_their_patterns() {
+        assert_eq!(Category::Threes.points(&[3, 3, 1, 3, 6]), 9);
+        assert_eq!(Category::ThreeOfAKind.points(&[3, 3, 1, 3, 6]), 16);
+        assert_eq!(Category::FourOfAKind.points(&[3, 3, 1, 3, 6]), 0);
+        assert_eq!(Category::FullHouse.points(&[2, 5, 2, 5, 5]), 25);
+        assert_eq!(Category::FullHouse.points(&[5, 5, 5, 5, 5]), 0);
+        assert_eq!(Category::SmallStraight.points(&[4, 3, 2, 1, 1]), 30);
+        assert_eq!(Category::LargeStraight.points(&[4, 3, 2, 1, 1]), 0);
+        assert_eq!(Category::LargeStraight.points(&[2, 3, 4, 5, 6]), 40);
+        assert_eq!(Category::Chance.points(&[2, 3, 4, 5, 6]), 20);
+    }
+
+    #[test]
+    fn upper_bonus_at_sixty_three() {
+        let mut card = Scorecard::default();
+        for (category, dice) in [
+            (Category::Fours, [4, 4, 4, 1, 2]),
+            (Category::Fives, [5, 5, 5, 1, 2]),
+            (Category::Sixes, [6, 6, 6, 1, 2]),
+            (Category::Threes, [3, 3, 3, 1, 2]),
+            (Category::Twos, [2, 2, 2, 1, 5]),
+        ] {
+            card.record(category, &dice);
+        }
+        assert_eq!(card.upper(), 60);
+        assert_eq!(card.upper_bonus(), 0);
+        card.record(Category::Ones, &[1, 1, 1, 2, 3]);
+        assert_eq!(card.upper_bonus(), UPPER_BONUS);
+        assert_eq!(card.total(), 63 + UPPER_BONUS);
+    }
+
+    #[test]
+    fn further_yahtzees_are_jokers() {
+        let mut card = Scorecard::default();
+        let fours = [4; 5];
+        assert!(card.allowed(&fours).contains(&Category::Chance));
+        card.record(Category::Yahtzee, &fours);
+        // The Fours box is open, so the joker has to go there.
+        assert_eq!(card.allowed(&fours), vec![Category::Fours]);
+        card.record(Category::Fours, &fours);
+        assert_eq!(card.yahtzee_bonus, YAHTZEE_BONUS);
+        // Now any open lower box, at full points.
+        let allowed = card.allowed(&fours);
+        assert!(allowed.contains(&Category::LargeStraight));
+        assert!(!allowed.contains(&Category::Ones));
+        assert_eq!(card.points(Category::LargeStraight, &fours), 40);
+    }
+
+    #[test]
+    fn no_bonus_after_a_scratched_yahtzee() {
+        let mut card = Scorecard::default();
+        card.record(Category::Yahtzee, &[1, 2, 3, 4, 6]);
+        assert_eq!(card.boxes[Category::Yahtzee.index()], Some(0));
+        card.record(Category::Sixes, &[6; 5]);
+        assert_eq!(card.yahtzee_bonus, 0);
+        assert_eq!(card.total(), 30);
+    }
+}